@@ -0,0 +1,9 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type CheckpointCreatedEvent = { 
+/**
+ * Id to pass to [`Op::BranchFromCheckpoint`].
+ */
+checkpoint_id: string, };
@@ -0,0 +1,9 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Token counts are estimates of the history size, and are `None` when the
+ * history could not be estimated.
+ */
+export type CompactionFinishedEvent = { tokens_before: bigint | null, tokens_after: bigint | null, };
@@ -0,0 +1,17 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ContextWindowStatusEvent = { 
+/**
+ * Estimated tokens taken up by the conversation history.
+ */
+used_tokens: number, 
+/**
+ * Size of the model's context window.
+ */
+max_tokens: number, 
+/**
+ * Percentage (0-100) of the context window in use.
+ */
+pct_full: number, };
@@ -0,0 +1,22 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ModelCost } from "./ModelCost";
+
+/**
+ * Estimated spend, computed from token usage and per-model prices.
+ */
+export type CostUpdateEvent = { 
+/**
+ * Estimated cost of the current or most recent turn, in USD.
+ */
+turn_cost_usd: number, 
+/**
+ * Estimated cost of the session so far, in USD.
+ */
+session_cost_usd: number, 
+/**
+ * Usage per provider and model. Models without a known price count
+ * toward neither total.
+ */
+models: Array<ModelCost>, };
@@ -2,4 +2,4 @@
 
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type ExecCommandSource = "agent" | "user_shell" | "unified_exec_startup" | "unified_exec_interaction";
+export type ExecCommandSource = "agent" | "user_shell" | "unified_exec_startup" | "unified_exec_interaction" | "verify";
@@ -0,0 +1,9 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Where the effective value of a feature flag came from. Later variants take
+ * precedence over earlier ones.
+ */
+export type FeatureSource = "default" | "remote" | "config" | "profile" | "cli" | "requirement";
@@ -0,0 +1,14 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { McpServerHealth } from "./McpServerHealth";
+
+export type McpServerStatusEvent = { 
+/**
+ * Server name.
+ */
+server: string, 
+/**
+ * Current health of the server.
+ */
+status: McpServerHealth, };
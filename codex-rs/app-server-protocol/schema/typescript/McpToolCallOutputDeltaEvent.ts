@@ -0,0 +1,14 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type McpToolCallOutputDeltaEvent = { 
+/**
+ * Identifier for the McpToolCallBegin that produced this chunk.
+ */
+call_id: string, 
+/**
+ * Partial result reported by the server. The final result's text is the
+ * concatenation of every chunk.
+ */
+delta: string, };
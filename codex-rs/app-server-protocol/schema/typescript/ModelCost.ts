@@ -0,0 +1,9 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ModelCost = { provider: string, model: string, input_tokens: number, cached_input_tokens: number, output_tokens: number, 
+/**
+ * Estimated cost in USD, or `None` when no price is known for the model.
+ */
+cost_usd: number | null, };
@@ -0,0 +1,20 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * One added or deleted file, or one changed region of an updated file.
+ */
+export type PatchReviewHunk = { 
+/**
+ * Identifies the hunk in the [`PatchReviewResponse`].
+ */
+index: number, path: string, 
+/**
+ * Destination of the file when the hunk belongs to a rename.
+ */
+move_path: string | null, 
+/**
+ * Diff lines prefixed with `-`, `+` or a space for context.
+ */
+diff: string, };
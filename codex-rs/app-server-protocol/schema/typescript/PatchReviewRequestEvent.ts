@@ -0,0 +1,23 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PatchReviewHunk } from "./PatchReviewHunk";
+
+/**
+ * Asks the client to accept or reject each hunk of a patch before it is
+ * applied. Sent instead of [`ApplyPatchApprovalRequestEvent`] when the
+ * `patch_hunk_review` feature is enabled; answered with `Op::PatchReview`.
+ */
+export type PatchReviewRequestEvent = { 
+/**
+ * Responses API call id for the associated patch apply call.
+ */
+call_id: string, 
+/**
+ * Turn ID that this patch belongs to.
+ */
+turn_id: string, hunks: Array<PatchReviewHunk>, 
+/**
+ * Optional explanatory reason (e.g. request for extra write access).
+ */
+reason: string | null, };
@@ -0,0 +1,21 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ProviderFellBackEvent = { 
+/**
+ * Id of the provider that became unavailable.
+ */
+from_provider: string, 
+/**
+ * Id of the provider the turn continues with.
+ */
+to_provider: string, 
+/**
+ * Model requested from `to_provider`.
+ */
+model: string, 
+/**
+ * The error that exhausted `from_provider`'s retries.
+ */
+reason: string, };
@@ -0,0 +1,21 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ProviderUnhealthyEvent = { 
+/**
+ * Id of the provider whose circuit breaker opened.
+ */
+provider: string, 
+/**
+ * Failed requests in a row that opened the circuit breaker.
+ */
+consecutive_failures: number, 
+/**
+ * Seconds until a request is sent to the provider again.
+ */
+retry_after_seconds: bigint, 
+/**
+ * The error from the last failed request.
+ */
+reason: string, };
@@ -0,0 +1,10 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FeatureSource } from "./FeatureSource";
+
+export type ResolvedFeature = { 
+/**
+ * The `[features]` key, e.g. `undo`.
+ */
+key: string, enabled: boolean, default_enabled: boolean, source: FeatureSource, };
@@ -0,0 +1,14 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SecretsRedactedEvent = { call_id: string, 
+/**
+ * Kinds of credentials replaced, e.g. `aws_access_key_id`, each listed
+ * once.
+ */
+kinds: Array<string>, 
+/**
+ * Number of values replaced.
+ */
+count: number, };
@@ -0,0 +1,8 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AskForApproval } from "./AskForApproval";
+import type { ReasoningEffort } from "./ReasoningEffort";
+import type { SandboxPolicy } from "./SandboxPolicy";
+
+export type SessionSettingsUpdatedEvent = { model: string, reasoning_effort: ReasoningEffort | null, approval_policy: AskForApproval, sandbox_policy: SandboxPolicy, cwd: string, };
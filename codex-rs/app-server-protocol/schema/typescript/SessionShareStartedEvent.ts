@@ -0,0 +1,15 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SessionShareStartedEvent = { 
+/**
+ * WebSocket URL, e.g. `ws://127.0.0.1:53121/`. Observers may add an
+ * `offset` query parameter to replay events from that sequence number.
+ */
+url: string, 
+/**
+ * Sent by observers as `Authorization: Bearer <token>` or as a `token`
+ * query parameter.
+ */
+token: string, };
@@ -4,4 +4,9 @@
 import type { RateLimitSnapshot } from "./RateLimitSnapshot";
 import type { TokenUsageInfo } from "./TokenUsageInfo";
 
-export type TokenCountEvent = { info: TokenUsageInfo | null, rate_limits: RateLimitSnapshot | null, };
+export type TokenCountEvent = { info: TokenUsageInfo | null, rate_limits: RateLimitSnapshot | null, 
+/**
+ * Share of the session's input tokens served from the provider's prompt
+ * cache, from 0 to 1. `None` until some input has been counted.
+ */
+prompt_cache_hit_rate: number | null, };
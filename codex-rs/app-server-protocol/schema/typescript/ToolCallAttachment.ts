@@ -0,0 +1,8 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Content attached to a [`ResponseItem::CustomToolCallWithAttachments`].
+ */
+export type ToolCallAttachment = { "type": "input_image", image_url: string, } | { "type": "resource", uri: string, mime_type?: string, };
@@ -0,0 +1,14 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ToolCallQueueReason } from "./ToolCallQueueReason";
+
+export type ToolCallQueuedEvent = { call_id: string, 
+/**
+ * MCP server whose limits the call is waiting on.
+ */
+server: string, tool: string, 
+/**
+ * Which of the server's limits the call is waiting on.
+ */
+reason: ToolCallQueueReason, };
@@ -0,0 +1,9 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ToolOutputRedactedEvent = { call_id: string, 
+/**
+ * Bytes of matched content that were replaced.
+ */
+bytes_redacted: number, };
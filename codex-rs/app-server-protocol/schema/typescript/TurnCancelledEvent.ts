@@ -0,0 +1,10 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TurnAbortReason } from "./TurnAbortReason";
+
+export type TurnCancelledEvent = { turn_id: string, reason: TurnAbortReason, 
+/**
+ * Assistant text streamed during the turn before it was cancelled.
+ */
+partial_output: string | null, };
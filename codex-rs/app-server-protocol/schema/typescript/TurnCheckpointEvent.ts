@@ -0,0 +1,13 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type TurnCheckpointEvent = { turn_id: string, 
+/**
+ * Assistant text streamed since the last completed assistant message.
+ */
+partial_agent_message: string | null, 
+/**
+ * Unified diff of the files changed so far in the turn.
+ */
+unified_diff: string | null, };
@@ -1,5 +1,11 @@
 // GENERATED CODE! DO NOT MODIFY BY HAND!
 
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TurnRootDiff } from "./TurnRootDiff";
 
-export type TurnDiffEvent = { unified_diff: string, };
+export type TurnDiffEvent = { unified_diff: string, 
+/**
+ * The same changes split by repository, with paths relative to each
+ * one. Only set when the turn changed files in more than one.
+ */
+root_diffs?: Array<TurnRootDiff>, };
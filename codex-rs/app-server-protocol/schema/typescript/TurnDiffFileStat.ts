@@ -0,0 +1,15 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+import type { TurnDiffFileStatus } from "./TurnDiffFileStatus";
+
+export type TurnDiffFileStat = { path: string, 
+/**
+ * Where the file was before the turn, when it was renamed or moved.
+ */
+old_path?: string, status: TurnDiffFileStatus, added_lines: bigint, removed_lines: bigint, 
+/**
+ * Either side is not UTF-8 text, so no lines are counted.
+ */
+binary: boolean, };
@@ -0,0 +1,10 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TurnDiffFileStat } from "./TurnDiffFileStat";
+
+export type TurnDiffUpdatedEvent = { turn_id: string, 
+/**
+ * Every file the turn has changed so far, sorted by path.
+ */
+files: Array<TurnDiffFileStat>, };
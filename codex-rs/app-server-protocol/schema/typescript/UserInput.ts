@@ -13,4 +13,4 @@ export type UserInput = { "type": "text", text: string,
  * or persist rich input markers (e.g., image placeholders) across history
  * and resume without mutating the literal text.
  */
-text_elements: Array<TextElement>, } | { "type": "image", image_url: string, } | { "type": "local_image", path: string, } | { "type": "skill", name: string, path: string, } | { "type": "mention", name: string, path: string, };
+text_elements: Array<TextElement>, } | { "type": "image", image_url: string, } | { "type": "local_image", path: string, } | { "type": "skill", name: string, path: string, } | { "type": "mention", name: string, path: string, } | { "type": "system_note", text: string, };
@@ -18,8 +18,12 @@ export type { ApplyPatchApprovalResponse } from "./ApplyPatchApprovalResponse";
 export type { AskForApproval } from "./AskForApproval";
 export type { AuthMode } from "./AuthMode";
 export type { BackgroundEventEvent } from "./BackgroundEventEvent";
+export type { BranchedFromCheckpointEvent } from "./BranchedFromCheckpointEvent";
 export type { ByteRange } from "./ByteRange";
 export type { CallToolResult } from "./CallToolResult";
+export type { ChaosFaultInjectedEvent } from "./ChaosFaultInjectedEvent";
+export type { ChaosFaultType } from "./ChaosFaultType";
+export type { CheckpointCreatedEvent } from "./CheckpointCreatedEvent";
 export type { ClientInfo } from "./ClientInfo";
 export type { ClientNotification } from "./ClientNotification";
 export type { ClientRequest } from "./ClientRequest";
@@ -37,11 +41,15 @@ export type { CollabResumeEndEvent } from "./CollabResumeEndEvent";
 export type { CollabWaitingBeginEvent } from "./CollabWaitingBeginEvent";
 export type { CollabWaitingEndEvent } from "./CollabWaitingEndEvent";
 export type { CollaborationMode } from "./CollaborationMode";
+export type { CompactionFinishedEvent } from "./CompactionFinishedEvent";
+export type { CompactionStartedEvent } from "./CompactionStartedEvent";
 export type { ContentItem } from "./ContentItem";
 export type { ContextCompactedEvent } from "./ContextCompactedEvent";
 export type { ContextCompactionItem } from "./ContextCompactionItem";
+export type { ContextWindowStatusEvent } from "./ContextWindowStatusEvent";
 export type { ConversationGitInfo } from "./ConversationGitInfo";
 export type { ConversationSummary } from "./ConversationSummary";
+export type { CostUpdateEvent } from "./CostUpdateEvent";
 export type { CreditsSnapshot } from "./CreditsSnapshot";
 export type { CustomPrompt } from "./CustomPrompt";
 export type { DeprecationNoticeEvent } from "./DeprecationNoticeEvent";
@@ -63,6 +71,8 @@ export type { ExecCommandStatus } from "./ExecCommandStatus";
 export type { ExecOutputStream } from "./ExecOutputStream";
 export type { ExecPolicyAmendment } from "./ExecPolicyAmendment";
 export type { ExitedReviewModeEvent } from "./ExitedReviewModeEvent";
+export type { FeatureSource } from "./FeatureSource";
+export type { FeaturesResolvedEvent } from "./FeaturesResolvedEvent";
 export type { FileChange } from "./FileChange";
 export type { FileSystemPermissions } from "./FileSystemPermissions";
 export type { ForcedLoginMethod } from "./ForcedLoginMethod";
@@ -106,14 +116,18 @@ export type { MacOsSeatbeltProfileExtensions } from "./MacOsSeatbeltProfileExten
 export type { McpAuthStatus } from "./McpAuthStatus";
 export type { McpInvocation } from "./McpInvocation";
 export type { McpListToolsResponseEvent } from "./McpListToolsResponseEvent";
+export type { McpServerHealth } from "./McpServerHealth";
+export type { McpServerStatusEvent } from "./McpServerStatusEvent";
 export type { McpStartupCompleteEvent } from "./McpStartupCompleteEvent";
 export type { McpStartupFailure } from "./McpStartupFailure";
 export type { McpStartupStatus } from "./McpStartupStatus";
 export type { McpStartupUpdateEvent } from "./McpStartupUpdateEvent";
 export type { McpToolCallBeginEvent } from "./McpToolCallBeginEvent";
 export type { McpToolCallEndEvent } from "./McpToolCallEndEvent";
+export type { McpToolCallOutputDeltaEvent } from "./McpToolCallOutputDeltaEvent";
 export type { MessagePhase } from "./MessagePhase";
 export type { ModeKind } from "./ModeKind";
+export type { ModelCost } from "./ModelCost";
 export type { ModelRerouteEvent } from "./ModelRerouteEvent";
 export type { ModelRerouteReason } from "./ModelRerouteReason";
 export type { NetworkAccess } from "./NetworkAccess";
@@ -126,12 +140,16 @@ export type { ParsedCommand } from "./ParsedCommand";
 export type { PatchApplyBeginEvent } from "./PatchApplyBeginEvent";
 export type { PatchApplyEndEvent } from "./PatchApplyEndEvent";
 export type { PatchApplyStatus } from "./PatchApplyStatus";
+export type { PatchReviewHunk } from "./PatchReviewHunk";
+export type { PatchReviewRequestEvent } from "./PatchReviewRequestEvent";
 export type { PermissionProfile } from "./PermissionProfile";
 export type { Personality } from "./Personality";
 export type { PlanDeltaEvent } from "./PlanDeltaEvent";
 export type { PlanItem } from "./PlanItem";
 export type { PlanItemArg } from "./PlanItemArg";
 export type { PlanType } from "./PlanType";
+export type { ProviderFellBackEvent } from "./ProviderFellBackEvent";
+export type { ProviderUnhealthyEvent } from "./ProviderUnhealthyEvent";
 export type { RateLimitSnapshot } from "./RateLimitSnapshot";
 export type { RateLimitWindow } from "./RateLimitWindow";
 export type { RawResponseItemEvent } from "./RawResponseItemEvent";
@@ -157,6 +175,7 @@ export type { RequestId } from "./RequestId";
 export type { RequestUserInputEvent } from "./RequestUserInputEvent";
 export type { RequestUserInputQuestion } from "./RequestUserInputQuestion";
 export type { RequestUserInputQuestionOption } from "./RequestUserInputQuestionOption";
+export type { ResolvedFeature } from "./ResolvedFeature";
 export type { Resource } from "./Resource";
 export type { ResourceTemplate } from "./ResourceTemplate";
 export type { ResponseItem } from "./ResponseItem";
@@ -168,11 +187,14 @@ export type { ReviewOutputEvent } from "./ReviewOutputEvent";
 export type { ReviewRequest } from "./ReviewRequest";
 export type { ReviewTarget } from "./ReviewTarget";
 export type { SandboxPolicy } from "./SandboxPolicy";
+export type { SecretsRedactedEvent } from "./SecretsRedactedEvent";
 export type { ServerNotification } from "./ServerNotification";
 export type { ServerRequest } from "./ServerRequest";
 export type { ServiceTier } from "./ServiceTier";
 export type { SessionConfiguredEvent } from "./SessionConfiguredEvent";
 export type { SessionNetworkProxyRuntime } from "./SessionNetworkProxyRuntime";
+export type { SessionSettingsUpdatedEvent } from "./SessionSettingsUpdatedEvent";
+export type { SessionShareStartedEvent } from "./SessionShareStartedEvent";
 export type { SessionSource } from "./SessionSource";
 export type { Settings } from "./Settings";
 export type { SkillDependencies } from "./SkillDependencies";
@@ -194,12 +216,25 @@ export type { TokenCountEvent } from "./TokenCountEvent";
 export type { TokenUsage } from "./TokenUsage";
 export type { TokenUsageInfo } from "./TokenUsageInfo";
 export type { Tool } from "./Tool";
+export type { ToolCacheHitEvent } from "./ToolCacheHitEvent";
+export type { ToolCallAttachment } from "./ToolCallAttachment";
+export type { ToolCallQueueReason } from "./ToolCallQueueReason";
+export type { ToolCallQueuedEvent } from "./ToolCallQueuedEvent";
+export type { ToolCallStartEvent } from "./ToolCallStartEvent";
+export type { ToolOutputRedactedEvent } from "./ToolOutputRedactedEvent";
 export type { TurnAbortReason } from "./TurnAbortReason";
 export type { TurnAbortedEvent } from "./TurnAbortedEvent";
+export type { TurnCancelledEvent } from "./TurnCancelledEvent";
+export type { TurnCheckpointEvent } from "./TurnCheckpointEvent";
 export type { TurnCompleteEvent } from "./TurnCompleteEvent";
 export type { TurnDiffEvent } from "./TurnDiffEvent";
+export type { TurnDiffFileStat } from "./TurnDiffFileStat";
+export type { TurnDiffFileStatus } from "./TurnDiffFileStatus";
+export type { TurnDiffUpdatedEvent } from "./TurnDiffUpdatedEvent";
 export type { TurnItem } from "./TurnItem";
+export type { TurnRootDiff } from "./TurnRootDiff";
 export type { TurnStartedEvent } from "./TurnStartedEvent";
+export type { TurnTimedOutEvent } from "./TurnTimedOutEvent";
 export type { UndoCompletedEvent } from "./UndoCompletedEvent";
 export type { UndoStartedEvent } from "./UndoStartedEvent";
 export type { UpdatePlanArgs } from "./UpdatePlanArgs";
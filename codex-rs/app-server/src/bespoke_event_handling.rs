@@ -1843,7 +1843,9 @@ async fn handle_token_count_event(
     token_count_event: TokenCountEvent,
     outgoing: &ThreadScopedOutgoingMessageSender,
 ) {
-    let TokenCountEvent { info, rate_limits } = token_count_event;
+    let TokenCountEvent {
+        info, rate_limits, ..
+    } = token_count_event;
     if let Some(token_usage) = info.map(ThreadTokenUsage::from) {
         let notification = ThreadTokenUsageUpdatedNotification {
             thread_id: conversation_id.to_string(),
@@ -2845,6 +2847,7 @@ mod tests {
             TokenCountEvent {
                 info: Some(info),
                 rate_limits: Some(rate_limits),
+                prompt_cache_hit_rate: None,
             },
             &outgoing,
         )
@@ -2899,6 +2902,7 @@ mod tests {
             TokenCountEvent {
                 info: None,
                 rate_limits: None,
+                prompt_cache_hit_rate: None,
             },
             &outgoing,
         )
@@ -3198,6 +3202,7 @@ mod tests {
             "turn-1",
             TurnDiffEvent {
                 unified_diff: unified_diff.clone(),
+                root_diffs: Vec::new(),
             },
             ApiVersion::V2,
             &outgoing,
@@ -3235,6 +3240,7 @@ mod tests {
             "turn-1",
             TurnDiffEvent {
                 unified_diff: "diff".to_string(),
+                root_diffs: Vec::new(),
             },
             ApiVersion::V1,
             &outgoing,
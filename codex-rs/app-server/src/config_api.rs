@@ -220,6 +220,7 @@ mod tests {
                     ("personality".to_string(), true),
                 ]),
             }),
+            feature_defaults: None,
             mcp_servers: None,
             rules: None,
             enforce_residency: Some(CoreResidencyRequirement::Us),
@@ -290,6 +291,7 @@ mod tests {
             allowed_sandbox_modes: None,
             allowed_web_search_modes: Some(Vec::new()),
             feature_requirements: None,
+            feature_defaults: None,
             mcp_servers: None,
             rules: None,
             enforce_residency: None,
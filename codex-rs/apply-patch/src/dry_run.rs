@@ -0,0 +1,163 @@
+//! Checking which hunks of a patch would apply, without changing any files.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::ApplyPatchError;
+use crate::Hunk;
+use crate::locate_chunk;
+use crate::parse_patch;
+use crate::read_file_to_update;
+use crate::split_lines;
+
+/// A hunk that would not apply to the files as they are now.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HunkConflict {
+    pub path: PathBuf,
+    /// 1-based position of the failing chunk within its `*** Update File`
+    /// hunk, or `None` when the file itself cannot be updated or deleted.
+    pub chunk: Option<usize>,
+    pub reason: String,
+}
+
+/// Result of [`dry_run_patch`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DryRunReport {
+    /// Number of hunks checked, counting each chunk of an update separately.
+    pub hunks_checked: usize,
+    pub conflicts: Vec<HunkConflict>,
+}
+
+impl DryRunReport {
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Checks every hunk of `patch` against the files under `cwd` without
+/// writing anything. Unlike applying the patch, checking continues past a
+/// conflict so that all of them are reported.
+pub fn dry_run_patch(patch: &str, cwd: &Path) -> Result<DryRunReport, ApplyPatchError> {
+    let hunks = parse_patch(patch)?.hunks;
+    let mut report = DryRunReport::default();
+    for hunk in &hunks {
+        let path = hunk.resolve_path(cwd);
+        match hunk {
+            Hunk::AddFile { .. } => report.hunks_checked += 1,
+            Hunk::DeleteFile { .. } => {
+                report.hunks_checked += 1;
+                if !path.is_file() {
+                    report.conflicts.push(HunkConflict {
+                        reason: format!("File to delete does not exist: {}", path.display()),
+                        path,
+                        chunk: None,
+                    });
+                }
+            }
+            Hunk::UpdateFile { chunks, .. } => {
+                let contents = match read_file_to_update(&path) {
+                    Ok(contents) => contents,
+                    Err(err) => {
+                        report.hunks_checked += 1;
+                        report.conflicts.push(HunkConflict {
+                            path,
+                            chunk: None,
+                            reason: err.to_string(),
+                        });
+                        continue;
+                    }
+                };
+                let lines = split_lines(&contents);
+                let mut line_index = 0;
+                for (index, chunk) in chunks.iter().enumerate() {
+                    report.hunks_checked += 1;
+                    match locate_chunk(&lines, &path, chunk, line_index) {
+                        Ok((_, next_line_index)) => line_index = next_line_index,
+                        Err(err) => report.conflicts.push(HunkConflict {
+                            path: path.clone(),
+                            chunk: Some(index + 1),
+                            reason: err.to_string(),
+                        }),
+                    }
+                }
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Writes `report` in the style of [`crate::print_summary`].
+pub fn print_dry_run_report(
+    report: &DryRunReport,
+    out: &mut impl std::io::Write,
+) -> std::io::Result<()> {
+    if report.is_clean() {
+        let hunks_checked = report.hunks_checked;
+        return writeln!(out, "Dry run: all {hunks_checked} hunks would apply.");
+    }
+    writeln!(
+        out,
+        "Dry run: {} of {} hunks would conflict:",
+        report.conflicts.len(),
+        report.hunks_checked
+    )?;
+    for conflict in &report.conflicts {
+        let path = conflict.path.display();
+        let reason = &conflict.reason;
+        match conflict.chunk {
+            Some(chunk) => writeln!(out, "C {path} (hunk {chunk}): {reason}")?,
+            None => writeln!(out, "C {path}: {reason}")?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    #[test]
+    fn reports_every_conflicting_hunk_without_writing() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let file = dir.path().join("lib.rs");
+        std::fs::write(&file, "fn a() {}\nfn b() {}\nfn c() {}\n")?;
+        let patch = "*** Begin Patch
+*** Update File: lib.rs
+@@
+-fn a() {}
++fn a() -> u8 { 1 }
+@@
+-fn stale() {}
++fn fresh() {}
+@@
+-fn c() {}
++fn c() -> u8 { 3 }
+*** Delete File: missing.rs
+*** Add File: new.rs
++fn new() {}
+*** End Patch";
+
+        let report = dry_run_patch(patch, dir.path())?;
+
+        assert_eq!(report.hunks_checked, 5);
+        assert_eq!(
+            report
+                .conflicts
+                .iter()
+                .map(|conflict| (conflict.path.clone(), conflict.chunk))
+                .collect::<Vec<_>>(),
+            vec![
+                (file.clone(), Some(2)),
+                (dir.path().join("missing.rs"), None)
+            ]
+        );
+        assert_eq!(
+            std::fs::read_to_string(&file)?,
+            "fn a() {}\nfn b() {}\nfn c() {}\n"
+        );
+        assert!(!dir.path().join("new.rs").exists());
+        Ok(())
+    }
+}
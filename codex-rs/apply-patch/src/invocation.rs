@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::path::Path;
+use std::path::PathBuf;
 use std::sync::LazyLock;
 
 use tree_sitter::Parser;
@@ -15,9 +16,11 @@ use crate::ApplyPatchFileChange;
 use crate::ApplyPatchFileUpdate;
 use crate::IoError;
 use crate::MaybeApplyPatchVerified;
+use crate::merged_update_from_chunks;
 use crate::parser::Hunk;
 use crate::parser::ParseError;
 use crate::parser::parse_patch;
+use crate::parser::render_patch;
 use crate::unified_diff_from_chunks;
 use std::str::Utf8Error;
 use tree_sitter::LanguageError;
@@ -130,6 +133,19 @@ pub fn maybe_parse_apply_patch(argv: &[String]) -> MaybeApplyPatch {
 /// cwd must be an absolute path so that we can resolve relative paths in the
 /// patch.
 pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApplyPatchVerified {
+    maybe_parse_apply_patch_verified_with_merge_bases(argv, cwd, &HashMap::new())
+}
+
+/// Like [`maybe_parse_apply_patch_verified`], but an update that no longer
+/// applies to a file is retried as a three-way merge when `merge_bases` holds
+/// an earlier version of that file, keyed by absolute path, that the patch
+/// may have been written against. The returned action's `patch` is rewritten
+/// so that applying it produces the merged contents.
+pub fn maybe_parse_apply_patch_verified_with_merge_bases(
+    argv: &[String],
+    cwd: &Path,
+    merge_bases: &HashMap<PathBuf, String>,
+) -> MaybeApplyPatchVerified {
     // Detect a raw patch body passed directly as the command or as the body of a shell
     // script. In these cases, report an explicit error rather than applying the patch.
     if let [body] = argv
@@ -161,11 +177,18 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                 })
                 .unwrap_or_else(|| cwd.to_path_buf());
             let mut changes = HashMap::new();
+            let mut resolved_hunks = Vec::with_capacity(hunks.len());
+            let mut merged = false;
             for hunk in hunks {
                 let path = hunk.resolve_path(&effective_cwd);
-                match hunk {
+                match &hunk {
                     Hunk::AddFile { contents, .. } => {
-                        changes.insert(path, ApplyPatchFileChange::Add { content: contents });
+                        changes.insert(
+                            path,
+                            ApplyPatchFileChange::Add {
+                                content: contents.clone(),
+                            },
+                        );
                     }
                     Hunk::DeleteFile { .. } => {
                         let content = match std::fs::read_to_string(&path) {
@@ -182,31 +205,58 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                         changes.insert(path, ApplyPatchFileChange::Delete { content });
                     }
                     Hunk::UpdateFile {
-                        move_path, chunks, ..
+                        path: hunk_path,
+                        move_path,
+                        chunks,
                     } => {
-                        let ApplyPatchFileUpdate {
-                            unified_diff,
-                            content: contents,
-                        } = match unified_diff_from_chunks(&path, &chunks) {
-                            Ok(diff) => diff,
+                        let (update, merge_chunk) = match unified_diff_from_chunks(&path, chunks) {
+                            Ok(update) => (update, None),
+                            Err(e @ ApplyPatchError::ComputeReplacements(_)) => {
+                                let Some((merge_chunk, update)) =
+                                    merge_bases.get(&path).and_then(|base| {
+                                        merged_update_from_chunks(&path, base, chunks)
+                                    })
+                                else {
+                                    return MaybeApplyPatchVerified::CorrectnessError(e);
+                                };
+                                (update, Some(merge_chunk))
+                            }
                             Err(e) => {
                                 return MaybeApplyPatchVerified::CorrectnessError(e);
                             }
                         };
+                        let ApplyPatchFileUpdate {
+                            unified_diff,
+                            content: contents,
+                        } = update;
                         changes.insert(
                             path,
                             ApplyPatchFileChange::Update {
                                 unified_diff,
-                                move_path: move_path.map(|p| effective_cwd.join(p)),
+                                move_path: move_path.as_ref().map(|p| effective_cwd.join(p)),
                                 new_content: contents,
                             },
                         );
+                        if let Some(merge_chunk) = merge_chunk {
+                            merged = true;
+                            resolved_hunks.push(Hunk::UpdateFile {
+                                path: hunk_path.clone(),
+                                move_path: move_path.clone(),
+                                chunks: vec![merge_chunk],
+                            });
+                            continue;
+                        }
                     }
                 }
+                resolved_hunks.push(hunk);
             }
             MaybeApplyPatchVerified::Body(ApplyPatchAction {
                 changes,
-                patch,
+                patch: if merged {
+                    render_patch(&resolved_hunks)
+                } else {
+                    patch
+                },
                 cwd: effective_cwd,
             })
         }
@@ -810,4 +860,50 @@ PATCH"#,
             other => panic!("expected update change, got {other:?}"),
         }
     }
+
+    #[test]
+    fn test_stale_update_merges_onto_merge_base() {
+        let session_dir = tempdir().unwrap();
+        let path = session_dir.path().join("source.txt");
+        fs::write(&path, "a\nB\nc\nd\ne\n").unwrap();
+        let argv = vec![
+            "apply_patch".to_string(),
+            wrap_patch("*** Update File: source.txt\n@@\n b\n c\n-d\n+D"),
+        ];
+
+        assert!(matches!(
+            maybe_parse_apply_patch_verified(&argv, session_dir.path()),
+            MaybeApplyPatchVerified::CorrectnessError(ApplyPatchError::ComputeReplacements(_))
+        ));
+
+        let merge_bases = HashMap::from([(path.clone(), "a\nb\nc\nd\ne\n".to_string())]);
+        let action = match maybe_parse_apply_patch_verified_with_merge_bases(
+            &argv,
+            session_dir.path(),
+            &merge_bases,
+        ) {
+            MaybeApplyPatchVerified::Body(action) => action,
+            other => panic!("expected verified body, got {other:?}"),
+        };
+        match action.changes().get(&path) {
+            Some(ApplyPatchFileChange::Update { new_content, .. }) => {
+                assert_eq!(new_content, "a\nB\nc\nD\ne\n");
+            }
+            other => panic!("expected update change, got {other:?}"),
+        }
+
+        // The rewritten patch applies to the current contents without a base.
+        let rewritten = vec!["apply_patch".to_string(), action.patch.clone()];
+        match maybe_parse_apply_patch_verified(&rewritten, session_dir.path()) {
+            MaybeApplyPatchVerified::Body(action) => assert_eq!(
+                action.changes().get(&path),
+                Some(&ApplyPatchFileChange::Update {
+                    unified_diff: "@@ -3,3 +3,3 @@\n c\n-d\n+D\n e\n".to_string(),
+                    move_path: None,
+                    new_content: "a\nB\nc\nD\ne\n".to_string(),
+                })
+            ),
+            other => panic!("expected verified body, got {other:?}"),
+        }
+    }
 }
@@ -1,7 +1,11 @@
+mod dry_run;
 mod invocation;
+mod merge;
 mod parser;
+mod review;
 mod seek_sequence;
 mod standalone_executable;
+mod str_replace;
 
 use std::collections::HashMap;
 use std::path::Path;
@@ -17,8 +21,20 @@ pub use parser::parse_patch;
 use similar::TextDiff;
 use thiserror::Error;
 
+pub use dry_run::DryRunReport;
+pub use dry_run::HunkConflict;
+pub use dry_run::dry_run_patch;
+pub use dry_run::print_dry_run_report;
 pub use invocation::maybe_parse_apply_patch_verified;
+pub use invocation::maybe_parse_apply_patch_verified_with_merge_bases;
+pub use review::ReviewHunk;
+pub use review::review_hunks;
+pub use review::select_hunks;
 pub use standalone_executable::main;
+pub use str_replace::StrReplaceError;
+pub use str_replace::StrReplaceMatch;
+pub use str_replace::StrReplacePatch;
+pub use str_replace::str_replace_patch;
 
 use crate::invocation::ExtractHeredocError;
 
@@ -349,35 +365,55 @@ fn derive_new_contents_from_chunks(
     path: &Path,
     chunks: &[UpdateFileChunk],
 ) -> std::result::Result<AppliedPatch, ApplyPatchError> {
-    let original_contents = match std::fs::read_to_string(path) {
-        Ok(contents) => contents,
-        Err(err) => {
-            return Err(ApplyPatchError::IoError(IoError {
-                context: format!("Failed to read file to update {}", path.display()),
-                source: err,
-            }));
-        }
-    };
+    let original_contents = read_file_to_update(path)?;
+    let new_contents = new_contents_from_chunks(&original_contents, path, chunks)?;
+    Ok(AppliedPatch {
+        original_contents,
+        new_contents,
+    })
+}
 
-    let mut original_lines: Vec<String> = original_contents.split('\n').map(String::from).collect();
+fn read_file_to_update(path: &Path) -> std::result::Result<String, ApplyPatchError> {
+    std::fs::read_to_string(path).map_err(|err| {
+        ApplyPatchError::IoError(IoError {
+            context: format!("Failed to read file to update {}", path.display()),
+            source: err,
+        })
+    })
+}
 
-    // Drop the trailing empty element that results from the final newline so
-    // that line counts match the behaviour of standard `diff`.
-    if original_lines.last().is_some_and(String::is_empty) {
-        original_lines.pop();
+/// Applies `chunks` to `original_contents`, the contents of the file at
+/// `path`, which is only used in error messages.
+fn new_contents_from_chunks(
+    original_contents: &str,
+    path: &Path,
+    chunks: &[UpdateFileChunk],
+) -> std::result::Result<String, ApplyPatchError> {
+    let original_lines = split_lines(original_contents);
+    let replacements = compute_replacements(&original_lines, path, chunks)?;
+    Ok(join_lines(apply_replacements(
+        original_lines,
+        &replacements,
+    )))
+}
+
+/// Splits file contents into lines, dropping the trailing empty element that
+/// results from the final newline so that line counts match the behaviour of
+/// standard `diff`.
+fn split_lines(contents: &str) -> Vec<String> {
+    let mut lines: Vec<String> = contents.split('\n').map(String::from).collect();
+    if lines.last().is_some_and(String::is_empty) {
+        lines.pop();
     }
+    lines
+}
 
-    let replacements = compute_replacements(&original_lines, path, chunks)?;
-    let new_lines = apply_replacements(original_lines, &replacements);
-    let mut new_lines = new_lines;
-    if !new_lines.last().is_some_and(String::is_empty) {
-        new_lines.push(String::new());
+/// Inverse of [`split_lines`]; the result always ends with a newline.
+fn join_lines(mut lines: Vec<String>) -> String {
+    if !lines.last().is_some_and(String::is_empty) {
+        lines.push(String::new());
     }
-    let new_contents = new_lines.join("\n");
-    Ok(AppliedPatch {
-        original_contents,
-        new_contents,
-    })
+    lines.join("\n")
 }
 
 /// Compute a list of replacements needed to transform `original_lines` into the
@@ -392,85 +428,95 @@ fn compute_replacements(
     let mut line_index: usize = 0;
 
     for chunk in chunks {
-        // If a chunk has a `change_context`, we use seek_sequence to find it, then
-        // adjust our `line_index` to continue from there.
-        if let Some(ctx_line) = &chunk.change_context {
-            if let Some(idx) = seek_sequence::seek_sequence(
-                original_lines,
-                std::slice::from_ref(ctx_line),
-                line_index,
-                false,
-            ) {
-                line_index = idx + 1;
-            } else {
-                return Err(ApplyPatchError::ComputeReplacements(format!(
-                    "Failed to find context '{}' in {}",
-                    ctx_line,
-                    path.display()
-                )));
-            }
-        }
-
-        if chunk.old_lines.is_empty() {
-            // Pure addition (no old lines). We'll add them at the end or just
-            // before the final empty line if one exists.
-            let insertion_idx = if original_lines.last().is_some_and(String::is_empty) {
-                original_lines.len() - 1
-            } else {
-                original_lines.len()
-            };
-            replacements.push((insertion_idx, 0, chunk.new_lines.clone()));
-            continue;
-        }
-
-        // Otherwise, try to match the existing lines in the file with the old lines
-        // from the chunk. If found, schedule that region for replacement.
-        // Attempt to locate the `old_lines` verbatim within the file.  In many
-        // real‑world diffs the last element of `old_lines` is an *empty* string
-        // representing the terminating newline of the region being replaced.
-        // This sentinel is not present in `original_lines` because we strip the
-        // trailing empty slice emitted by `split('\n')`.  If a direct search
-        // fails and the pattern ends with an empty string, retry without that
-        // final element so that modifications touching the end‑of‑file can be
-        // located reliably.
-
-        let mut pattern: &[String] = &chunk.old_lines;
-        let mut found =
-            seek_sequence::seek_sequence(original_lines, pattern, line_index, chunk.is_end_of_file);
-
-        let mut new_slice: &[String] = &chunk.new_lines;
+        let (replacement, next_line_index) = locate_chunk(original_lines, path, chunk, line_index)?;
+        replacements.push(replacement);
+        line_index = next_line_index;
+    }
 
-        if found.is_none() && pattern.last().is_some_and(String::is_empty) {
-            // Retry without the trailing empty line which represents the final
-            // newline in the file.
-            pattern = &pattern[..pattern.len() - 1];
-            if new_slice.last().is_some_and(String::is_empty) {
-                new_slice = &new_slice[..new_slice.len() - 1];
-            }
+    replacements.sort_by(|(lhs_idx, _, _), (rhs_idx, _, _)| lhs_idx.cmp(rhs_idx));
 
-            found = seek_sequence::seek_sequence(
-                original_lines,
-                pattern,
-                line_index,
-                chunk.is_end_of_file,
-            );
-        }
+    Ok(replacements)
+}
 
-        if let Some(start_idx) = found {
-            replacements.push((start_idx, pattern.len(), new_slice.to_vec()));
-            line_index = start_idx + pattern.len();
+/// Finds where `chunk` applies in `original_lines`, searching from
+/// `line_index`. Returns the chunk's `(start_index, old_len, new_lines)`
+/// replacement and the index the next chunk is searched from.
+fn locate_chunk(
+    original_lines: &[String],
+    path: &Path,
+    chunk: &UpdateFileChunk,
+    mut line_index: usize,
+) -> std::result::Result<((usize, usize, Vec<String>), usize), ApplyPatchError> {
+    // If a chunk has a `change_context`, we use seek_sequence to find it, then
+    // adjust our `line_index` to continue from there.
+    if let Some(ctx_line) = &chunk.change_context {
+        if let Some(idx) = seek_sequence::seek_sequence(
+            original_lines,
+            std::slice::from_ref(ctx_line),
+            line_index,
+            false,
+        ) {
+            line_index = idx + 1;
         } else {
             return Err(ApplyPatchError::ComputeReplacements(format!(
-                "Failed to find expected lines in {}:\n{}",
-                path.display(),
-                chunk.old_lines.join("\n"),
+                "Failed to find context '{}' in {}",
+                ctx_line,
+                path.display()
             )));
         }
     }
 
-    replacements.sort_by(|(lhs_idx, _, _), (rhs_idx, _, _)| lhs_idx.cmp(rhs_idx));
+    if chunk.old_lines.is_empty() {
+        // Pure addition (no old lines). We'll add them at the end or just
+        // before the final empty line if one exists.
+        let insertion_idx = if original_lines.last().is_some_and(String::is_empty) {
+            original_lines.len() - 1
+        } else {
+            original_lines.len()
+        };
+        return Ok(((insertion_idx, 0, chunk.new_lines.clone()), line_index));
+    }
 
-    Ok(replacements)
+    // Otherwise, try to match the existing lines in the file with the old lines
+    // from the chunk. If found, schedule that region for replacement.
+    // Attempt to locate the `old_lines` verbatim within the file.  In many
+    // real‑world diffs the last element of `old_lines` is an *empty* string
+    // representing the terminating newline of the region being replaced.
+    // This sentinel is not present in `original_lines` because we strip the
+    // trailing empty slice emitted by `split('\n')`.  If a direct search
+    // fails and the pattern ends with an empty string, retry without that
+    // final element so that modifications touching the end‑of‑file can be
+    // located reliably.
+
+    let mut pattern: &[String] = &chunk.old_lines;
+    let mut found =
+        seek_sequence::seek_sequence(original_lines, pattern, line_index, chunk.is_end_of_file);
+
+    let mut new_slice: &[String] = &chunk.new_lines;
+
+    if found.is_none() && pattern.last().is_some_and(String::is_empty) {
+        // Retry without the trailing empty line which represents the final
+        // newline in the file.
+        pattern = &pattern[..pattern.len() - 1];
+        if new_slice.last().is_some_and(String::is_empty) {
+            new_slice = &new_slice[..new_slice.len() - 1];
+        }
+
+        found =
+            seek_sequence::seek_sequence(original_lines, pattern, line_index, chunk.is_end_of_file);
+    }
+
+    match found {
+        Some(start_idx) => Ok((
+            (start_idx, pattern.len(), new_slice.to_vec()),
+            start_idx + pattern.len(),
+        )),
+        None => Err(ApplyPatchError::ComputeReplacements(format!(
+            "Failed to find expected lines in {}:\n{}",
+            path.display(),
+            chunk.old_lines.join("\n"),
+        ))),
+    }
 }
 
 /// Apply the `(start_index, old_len, new_lines)` replacements to `original_lines`,
@@ -532,6 +578,45 @@ pub fn unified_diff_from_chunks_with_context(
     })
 }
 
+/// Like [`unified_diff_from_chunks`], for `chunks` that were written against
+/// `base`, an earlier version of the file at `path`, and no longer apply to
+/// it. The chunks are applied to `base` instead, and the result is three-way
+/// merged with the file's current contents. Also returns a chunk that turns
+/// the current contents into the merged ones. Returns `None` when the chunks
+/// do not apply to `base` either, or both sides changed the same lines.
+fn merged_update_from_chunks(
+    path: &Path,
+    base: &str,
+    chunks: &[UpdateFileChunk],
+) -> Option<(UpdateFileChunk, ApplyPatchFileUpdate)> {
+    let current_contents = read_file_to_update(path).ok()?;
+    let patched_base = new_contents_from_chunks(base, path, chunks).ok()?;
+    let current_lines = split_lines(&current_contents);
+    let merged_lines = merge::merge_three_way(
+        &split_lines(base),
+        &current_lines,
+        &split_lines(&patched_base),
+    )?;
+    let content = join_lines(merged_lines.clone());
+    let unified_diff = TextDiff::from_lines(&current_contents, &content)
+        .unified_diff()
+        .context_radius(1)
+        .to_string();
+    let chunk = UpdateFileChunk {
+        change_context: None,
+        old_lines: current_lines,
+        new_lines: merged_lines,
+        is_end_of_file: false,
+    };
+    Some((
+        chunk,
+        ApplyPatchFileUpdate {
+            unified_diff,
+            content,
+        },
+    ))
+}
+
 /// Print the summary of changes in git-style format.
 /// Write a summary of changes to the given writer.
 pub fn print_summary(
@@ -0,0 +1,100 @@
+//! Line-based three-way merge, used to apply a patch that was written against
+//! an older version of a file.
+
+use std::ops::Range;
+
+use similar::Algorithm;
+use similar::DiffTag;
+use similar::capture_diff_slices;
+
+/// A change to a range of `base` lines.
+#[derive(Debug, PartialEq)]
+struct Edit<'a> {
+    base: Range<usize>,
+    lines: &'a [String],
+}
+
+/// Merges the changes `ours` and `theirs` each made to `base`. Returns `None`
+/// when the two sides change the same or adjacent lines differently, since
+/// there is then no single correct result.
+pub(crate) fn merge_three_way(
+    base: &[String],
+    ours: &[String],
+    theirs: &[String],
+) -> Option<Vec<String>> {
+    let mut edits = diff_edits(base, ours);
+    edits.extend(diff_edits(base, theirs));
+    edits.sort_by_key(|edit| (edit.base.start, edit.base.end));
+    edits.dedup();
+
+    let mut merged = Vec::with_capacity(ours.len().max(theirs.len()));
+    let mut cursor = 0;
+    let mut previous: Option<&Edit> = None;
+    for edit in &edits {
+        if let Some(previous) = previous
+            && edit.base.start <= previous.base.end
+        {
+            return None;
+        }
+        merged.extend_from_slice(&base[cursor..edit.base.start]);
+        merged.extend_from_slice(edit.lines);
+        cursor = edit.base.end;
+        previous = Some(edit);
+    }
+    merged.extend_from_slice(&base[cursor..]);
+    Some(merged)
+}
+
+fn diff_edits<'a>(base: &[String], changed: &'a [String]) -> Vec<Edit<'a>> {
+    capture_diff_slices(Algorithm::Myers, base, changed)
+        .into_iter()
+        .map(|op| op.as_tag_tuple())
+        .filter(|(tag, _, _)| *tag != DiffTag::Equal)
+        .map(|(_, base, changed_range)| Edit {
+            base,
+            lines: &changed[changed_range],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn merges_changes_to_separate_regions() {
+        let base = lines("a\nb\nc\nd\ne\nf");
+        let ours = lines("a\nB\nc\nd\ne\nf");
+        let theirs = lines("a\nb\nc\nd\nE\nf\ng");
+
+        assert_eq!(
+            merge_three_way(&base, &ours, &theirs),
+            Some(lines("a\nB\nc\nd\nE\nf\ng"))
+        );
+    }
+
+    #[test]
+    fn identical_changes_merge_once() {
+        let base = lines("a\nb\nc");
+        let changed = lines("a\nB\nc");
+
+        assert_eq!(
+            merge_three_way(&base, &changed, &changed),
+            Some(changed.clone())
+        );
+    }
+
+    #[test]
+    fn overlapping_changes_do_not_merge() {
+        let base = lines("a\nb\nc");
+        let ours = lines("a\nB\nc");
+        let theirs = lines("a\nX\nc");
+
+        assert_eq!(merge_three_way(&base, &ours, &theirs), None);
+    }
+}
@@ -112,6 +112,55 @@ pub fn parse_patch(patch: &str) -> Result<ApplyPatchArgs, ParseError> {
     parse_patch_text(patch, mode)
 }
 
+/// Renders `hunks` as patch text that [`parse_patch`] parses back into the
+/// same hunks. Context lines are not reconstructed: each chunk is written as
+/// its old lines removed followed by its new lines added.
+pub(crate) fn render_patch(hunks: &[Hunk]) -> String {
+    let mut patch = format!("{BEGIN_PATCH_MARKER}\n");
+    for hunk in hunks {
+        match hunk {
+            AddFile { path, contents } => {
+                patch.push_str(&format!("{ADD_FILE_MARKER}{}\n", path.display()));
+                for line in contents.lines() {
+                    patch.push_str(&format!("+{line}\n"));
+                }
+            }
+            DeleteFile { path } => {
+                patch.push_str(&format!("{DELETE_FILE_MARKER}{}\n", path.display()));
+            }
+            UpdateFile {
+                path,
+                move_path,
+                chunks,
+            } => {
+                patch.push_str(&format!("{UPDATE_FILE_MARKER}{}\n", path.display()));
+                if let Some(move_path) = move_path {
+                    patch.push_str(&format!("{MOVE_TO_MARKER}{}\n", move_path.display()));
+                }
+                for chunk in chunks {
+                    match &chunk.change_context {
+                        Some(context) => {
+                            patch.push_str(&format!("{CHANGE_CONTEXT_MARKER}{context}\n"));
+                        }
+                        None => patch.push_str(&format!("{EMPTY_CHANGE_CONTEXT_MARKER}\n")),
+                    }
+                    for line in &chunk.old_lines {
+                        patch.push_str(&format!("-{line}\n"));
+                    }
+                    for line in &chunk.new_lines {
+                        patch.push_str(&format!("+{line}\n"));
+                    }
+                    if chunk.is_end_of_file {
+                        patch.push_str(&format!("{EOF_MARKER}\n"));
+                    }
+                }
+            }
+        }
+    }
+    patch.push_str(END_PATCH_MARKER);
+    patch
+}
+
 enum ParseMode {
     /// Parse the patch text argument as is.
     Strict,
@@ -761,3 +810,33 @@ fn test_update_file_chunk() {
         ))
     );
 }
+
+#[test]
+fn test_render_patch_round_trips() {
+    let patch = "*** Begin Patch
+*** Add File: new.txt
++hello
+*** Delete File: old.txt
+*** Update File: src/lib.rs
+*** Move to: src/main.rs
+@@ fn main() {
+ context
+-before
+
++after
+@@
++tail
+*** End of File
+*** End Patch";
+    let hunks = parse_patch_text(patch, ParseMode::Strict)
+        .map(|args| args.hunks)
+        .unwrap_or_default();
+    assert_eq!(hunks.len(), 3);
+
+    let rendered = render_patch(&hunks);
+
+    assert_eq!(
+        parse_patch_text(&rendered, ParseMode::Strict).map(|args| args.hunks),
+        Ok(hunks)
+    );
+}
@@ -0,0 +1,224 @@
+//! Splitting a patch into hunks that can be accepted or rejected one by one.
+//!
+//! Hunks are numbered in patch order. An added or deleted file is one hunk,
+//! each chunk of an updated file is one hunk, and an update without chunks
+//! (a pure rename) is one hunk.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::Hunk;
+use crate::ParseError;
+use crate::parse_patch;
+use crate::parser::render_patch;
+use similar::ChangeTag;
+use similar::TextDiff;
+
+/// One independently reviewable piece of a patch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReviewHunk {
+    /// Path of the file the hunk changes, resolved against the patch's cwd.
+    pub path: PathBuf,
+    /// Destination of the file when the hunk belongs to a rename.
+    pub move_path: Option<PathBuf>,
+    /// The hunk as diff lines prefixed with `-`, `+` or a space for context,
+    /// preceded by an `@@` line for chunks of an updated file.
+    pub diff: String,
+}
+
+/// Lists the hunks of `patch` in review order. Deleted files are read from
+/// `cwd` so the hunk shows what is being removed.
+pub fn review_hunks(patch: &str, cwd: &Path) -> Result<Vec<ReviewHunk>, ParseError> {
+    let mut hunks = Vec::new();
+    for hunk in parse_patch(patch)?.hunks {
+        let path = hunk.resolve_path(cwd);
+        match hunk {
+            Hunk::AddFile { contents, .. } => hunks.push(ReviewHunk {
+                path,
+                move_path: None,
+                diff: prefixed_lines('+', &contents),
+            }),
+            Hunk::DeleteFile { .. } => {
+                let contents = std::fs::read_to_string(&path).unwrap_or_default();
+                hunks.push(ReviewHunk {
+                    path,
+                    move_path: None,
+                    diff: prefixed_lines('-', &contents),
+                });
+            }
+            Hunk::UpdateFile {
+                move_path, chunks, ..
+            } => {
+                let move_path = move_path.map(|move_path| cwd.join(move_path));
+                if chunks.is_empty() {
+                    hunks.push(ReviewHunk {
+                        path,
+                        move_path,
+                        diff: String::new(),
+                    });
+                    continue;
+                }
+                for chunk in chunks {
+                    let mut diff = match &chunk.change_context {
+                        Some(context) => format!("@@ {context}\n"),
+                        None => "@@\n".to_string(),
+                    };
+                    let old_lines = chunk
+                        .old_lines
+                        .iter()
+                        .map(String::as_str)
+                        .collect::<Vec<_>>();
+                    let new_lines = chunk
+                        .new_lines
+                        .iter()
+                        .map(String::as_str)
+                        .collect::<Vec<_>>();
+                    for change in TextDiff::from_slices(&old_lines, &new_lines).iter_all_changes() {
+                        let sign = match change.tag() {
+                            ChangeTag::Delete => '-',
+                            ChangeTag::Insert => '+',
+                            ChangeTag::Equal => ' ',
+                        };
+                        diff.push_str(&format!("{sign}{}\n", change.value()));
+                    }
+                    hunks.push(ReviewHunk {
+                        path: path.clone(),
+                        move_path: move_path.clone(),
+                        diff,
+                    });
+                }
+            }
+        }
+    }
+    Ok(hunks)
+}
+
+/// Rebuilds `patch` keeping only the hunks whose index satisfies `accept`.
+/// A renamed file is only renamed when at least one of its hunks is kept.
+/// Returns `None` when no hunk is kept.
+pub fn select_hunks(
+    patch: &str,
+    accept: impl Fn(usize) -> bool,
+) -> Result<Option<String>, ParseError> {
+    let mut index = 0;
+    let mut next = || {
+        let accepted = accept(index);
+        index += 1;
+        accepted
+    };
+    let mut kept = Vec::new();
+    for hunk in parse_patch(patch)?.hunks {
+        match hunk {
+            Hunk::AddFile { .. } | Hunk::DeleteFile { .. } => {
+                if next() {
+                    kept.push(hunk);
+                }
+            }
+            Hunk::UpdateFile {
+                path,
+                move_path,
+                chunks,
+            } => {
+                if chunks.is_empty() {
+                    if next() {
+                        kept.push(Hunk::UpdateFile {
+                            path,
+                            move_path,
+                            chunks,
+                        });
+                    }
+                    continue;
+                }
+                let chunks = chunks.into_iter().filter(|_| next()).collect::<Vec<_>>();
+                if !chunks.is_empty() {
+                    kept.push(Hunk::UpdateFile {
+                        path,
+                        move_path,
+                        chunks,
+                    });
+                }
+            }
+        }
+    }
+    Ok((!kept.is_empty()).then(|| render_patch(&kept)))
+}
+
+fn prefixed_lines(prefix: char, contents: &str) -> String {
+    contents
+        .lines()
+        .map(|line| format!("{prefix}{line}\n"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    const PATCH: &str = "*** Begin Patch
+*** Add File: notes.txt
++hello
+*** Update File: lib.rs
+@@ fn a() {
+-    1
++    2
+@@ fn c() {
+-    3
++    4
+*** Delete File: old.rs
+*** End Patch";
+
+    #[test]
+    fn lists_one_hunk_per_file_or_chunk() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        std::fs::write(dir.path().join("old.rs"), "gone\n")?;
+
+        let hunks = review_hunks(PATCH, dir.path())?;
+        assert_eq!(
+            hunks,
+            vec![
+                ReviewHunk {
+                    path: dir.path().join("notes.txt"),
+                    move_path: None,
+                    diff: "+hello\n".to_string(),
+                },
+                ReviewHunk {
+                    path: dir.path().join("lib.rs"),
+                    move_path: None,
+                    diff: "@@ fn a() {\n-    1\n+    2\n".to_string(),
+                },
+                ReviewHunk {
+                    path: dir.path().join("lib.rs"),
+                    move_path: None,
+                    diff: "@@ fn c() {\n-    3\n+    4\n".to_string(),
+                },
+                ReviewHunk {
+                    path: dir.path().join("old.rs"),
+                    move_path: None,
+                    diff: "-gone\n".to_string(),
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn keeps_only_accepted_hunks() -> anyhow::Result<()> {
+        let selected = select_hunks(PATCH, |index| index == 2 || index == 3)?;
+        assert_eq!(
+            selected.as_deref(),
+            Some(
+                "*** Begin Patch
+*** Update File: lib.rs
+@@ fn c() {
+-    3
++    4
+*** Delete File: old.rs
+*** End Patch"
+            )
+        );
+        assert_eq!(select_hunks(PATCH, |_| false)?, None);
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 use std::io::Read;
 use std::io::Write;
+use std::path::Path;
 
 pub fn main() -> ! {
     let exit_code = run_main();
@@ -9,9 +10,11 @@ pub fn main() -> ! {
 /// We would prefer to return `std::process::ExitCode`, but its `exit_process()`
 /// method is still a nightly API and we want main() to return !.
 pub fn run_main() -> i32 {
-    // Expect either one argument (the full apply_patch payload) or read it from stdin.
-    let mut args = std::env::args_os();
+    // Expect either one argument (the full apply_patch payload) or read it from stdin,
+    // optionally preceded by `--dry-run`.
+    let mut args = std::env::args_os().peekable();
     let _argv0 = args.next();
+    let dry_run = args.next_if(|arg| arg == "--dry-run").is_some();
 
     let patch_arg = match args.next() {
         Some(arg) => match arg.into_string() {
@@ -27,7 +30,9 @@ pub fn run_main() -> i32 {
             match std::io::stdin().read_to_string(&mut buf) {
                 Ok(_) => {
                     if buf.is_empty() {
-                        eprintln!("Usage: apply_patch 'PATCH'\n       echo 'PATCH' | apply_patch");
+                        eprintln!(
+                            "Usage: apply_patch [--dry-run] 'PATCH'\n       echo 'PATCH' | apply_patch [--dry-run]"
+                        );
                         return 2;
                     }
                     buf
@@ -48,6 +53,19 @@ pub fn run_main() -> i32 {
 
     let mut stdout = std::io::stdout();
     let mut stderr = std::io::stderr();
+    if dry_run {
+        return match crate::dry_run_patch(&patch_arg, Path::new("")) {
+            Ok(report) => {
+                let _ = crate::print_dry_run_report(&report, &mut stdout);
+                let _ = stdout.flush();
+                if report.is_clean() { 0 } else { 1 }
+            }
+            Err(err) => {
+                eprintln!("{err}");
+                1
+            }
+        };
+    }
     match crate::apply_patch(&patch_arg, &mut stdout, &mut stderr) {
         Ok(()) => {
             // Flush to ensure output ordering when used in pipelines.
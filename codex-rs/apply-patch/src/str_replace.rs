@@ -0,0 +1,306 @@
+//! Search/replace edits, turned into patches so they apply, get approved and
+//! show up in diffs exactly like `apply_patch` calls.
+//!
+//! `old_string` is first matched exactly. When it does not occur, it is
+//! matched line by line ignoring leading and trailing whitespace, which
+//! tolerates edits whose indentation drifted from the file.
+
+use std::ops::Range;
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::Hunk;
+use crate::parser::UpdateFileChunk;
+use crate::parser::render_patch;
+
+/// How `old_string` was found in the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrReplaceMatch {
+    Exact,
+    /// Matched whole lines, ignoring leading and trailing whitespace.
+    IgnoringWhitespace,
+}
+
+/// A search/replace edit expressed as an `apply_patch` patch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrReplacePatch {
+    pub patch: String,
+    /// Number of occurrences of `old_string` replaced.
+    pub replacements: usize,
+    pub matched: StrReplaceMatch,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum StrReplaceError {
+    #[error("file does not exist; pass an empty old_string to create it")]
+    FileNotFound,
+    #[error("file already exists; an empty old_string only creates new files")]
+    FileExists,
+    #[error("old_string and new_string are identical")]
+    NoChange,
+    #[error("old_string was not found in the file")]
+    NotFound,
+    #[error(
+        "old_string occurs {0} times in the file; include more surrounding context to make it unique, or set replace_all"
+    )]
+    Ambiguous(usize),
+}
+
+/// Builds the patch that replaces `old` with `new` in the file at `path`,
+/// whose current text is `contents` (`None` when it does not exist). An
+/// empty `old` creates the file with `new` as its contents. Unless
+/// `replace_all` is set, `old` must occur exactly once.
+pub fn str_replace_patch(
+    path: &Path,
+    contents: Option<&str>,
+    old: &str,
+    new: &str,
+    replace_all: bool,
+) -> Result<StrReplacePatch, StrReplaceError> {
+    if old == new {
+        return Err(StrReplaceError::NoChange);
+    }
+    let contents = match (contents, old.is_empty()) {
+        (None, true) => {
+            return Ok(StrReplacePatch {
+                patch: render_patch(&[Hunk::AddFile {
+                    path: path.to_path_buf(),
+                    contents: new.to_string(),
+                }]),
+                replacements: 1,
+                matched: StrReplaceMatch::Exact,
+            });
+        }
+        (None, false) => return Err(StrReplaceError::FileNotFound),
+        (Some(_), true) => return Err(StrReplaceError::FileExists),
+        (Some(contents), false) => contents,
+    };
+
+    let exact = contents
+        .match_indices(old)
+        .map(|(start, _)| start..start + old.len())
+        .collect::<Vec<_>>();
+    let (spans, replacement, matched) = if exact.is_empty() {
+        // Whole lines are replaced, so the line break after the last one
+        // stays in the file rather than coming from `new`.
+        (
+            whitespace_insensitive_matches(contents, old),
+            new.strip_suffix('\n').unwrap_or(new),
+            StrReplaceMatch::IgnoringWhitespace,
+        )
+    } else {
+        (exact, new, StrReplaceMatch::Exact)
+    };
+    match spans.len() {
+        0 => return Err(StrReplaceError::NotFound),
+        1 => {}
+        count if !replace_all => return Err(StrReplaceError::Ambiguous(count)),
+        _ => {}
+    }
+
+    let chunks = replacement_chunks(contents, &spans, replacement);
+    Ok(StrReplacePatch {
+        patch: render_patch(&[Hunk::UpdateFile {
+            path: path.to_path_buf(),
+            move_path: None,
+            chunks,
+        }]),
+        replacements: spans.len(),
+        matched,
+    })
+}
+
+/// Spans of whole lines whose trimmed text equals the trimmed lines of
+/// `old`, from the start of the first line to the end of the last one
+/// (excluding its line break).
+fn whitespace_insensitive_matches(contents: &str, old: &str) -> Vec<Range<usize>> {
+    let needle = old.lines().map(str::trim).collect::<Vec<_>>();
+    if needle.iter().all(|line| line.is_empty()) {
+        return Vec::new();
+    }
+    let mut start = 0;
+    let lines = contents
+        .split_inclusive('\n')
+        .map(|line| {
+            let span = start..start + line.trim_end_matches('\n').len();
+            start += line.len();
+            span
+        })
+        .collect::<Vec<_>>();
+
+    let mut matches = Vec::new();
+    let mut index = 0;
+    while index + needle.len() <= lines.len() {
+        let window = &lines[index..index + needle.len()];
+        if window
+            .iter()
+            .zip(&needle)
+            .all(|(span, line)| contents[span.clone()].trim() == *line)
+        {
+            matches.push(window[0].start..window[needle.len() - 1].end);
+            index += needle.len();
+        } else {
+            index += 1;
+        }
+    }
+    matches
+}
+
+/// One chunk per group of lines touched by `spans`, which are sorted and do
+/// not overlap. Spans that share a line end up in the same chunk.
+fn replacement_chunks(
+    contents: &str,
+    spans: &[Range<usize>],
+    replacement: &str,
+) -> Vec<UpdateFileChunk> {
+    let mut chunks = Vec::new();
+    let mut first = 0;
+    while first < spans.len() {
+        let start = line_start(contents, spans[first].start);
+        let mut end = line_end(contents, spans[first].end - 1);
+        let mut last = first + 1;
+        let new_text = loop {
+            while last < spans.len() && spans[last].start < end {
+                end = end.max(line_end(contents, spans[last].end - 1));
+                last += 1;
+            }
+            let new_text = replace_spans(contents, start..end, &spans[first..last], replacement);
+            // A replacement that drops the final line break would join the
+            // next line onto this chunk, so that line becomes part of it.
+            if end < contents.len() && !new_text.is_empty() && !new_text.ends_with('\n') {
+                end = line_end(contents, end);
+                continue;
+            }
+            break new_text;
+        };
+        chunks.push(UpdateFileChunk {
+            change_context: None,
+            old_lines: contents[start..end].lines().map(str::to_string).collect(),
+            new_lines: new_text.lines().map(str::to_string).collect(),
+            is_end_of_file: false,
+        });
+        first = last;
+    }
+    chunks
+}
+
+fn replace_spans(
+    contents: &str,
+    region: Range<usize>,
+    spans: &[Range<usize>],
+    replacement: &str,
+) -> String {
+    let mut text = String::new();
+    let mut cursor = region.start;
+    for span in spans {
+        text.push_str(&contents[cursor..span.start]);
+        text.push_str(replacement);
+        cursor = span.end;
+    }
+    text.push_str(&contents[cursor..region.end]);
+    text
+}
+
+/// Byte offset where the line containing byte `pos` starts.
+fn line_start(contents: &str, pos: usize) -> usize {
+    contents.as_bytes()[..pos]
+        .iter()
+        .rposition(|byte| *byte == b'\n')
+        .map_or(0, |index| index + 1)
+}
+
+/// Byte offset just past the line break ending the line that contains byte
+/// `pos`, or the end of `contents` for a last line without one.
+fn line_end(contents: &str, pos: usize) -> usize {
+    contents.as_bytes()[pos..]
+        .iter()
+        .position(|byte| *byte == b'\n')
+        .map_or(contents.len(), |index| pos + index + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apply_patch;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    fn edit(
+        contents: &str,
+        old: &str,
+        new: &str,
+        replace_all: bool,
+    ) -> Result<(String, StrReplacePatch), StrReplaceError> {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("file.txt");
+        std::fs::write(&path, contents).expect("write file");
+        let edit = str_replace_patch(&path, Some(contents), old, new, replace_all)?;
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&edit.patch, &mut stdout, &mut stderr).expect("patch applies");
+        Ok((std::fs::read_to_string(&path).expect("read file"), edit))
+    }
+
+    #[test]
+    fn replaces_a_unique_exact_match() {
+        let (updated, edit) = edit(
+            "fn a() {\n    one(); two();\n}\n",
+            "two()",
+            "three()",
+            false,
+        )
+        .expect("edit applies");
+        assert_eq!(updated, "fn a() {\n    one(); three();\n}\n");
+        assert_eq!(edit.replacements, 1);
+        assert_eq!(edit.matched, StrReplaceMatch::Exact);
+    }
+
+    #[test]
+    fn rejects_ambiguous_matches_unless_replacing_all() {
+        let contents = "x = 1\ny = 1\nx = 1\n";
+        assert_eq!(
+            edit(contents, "x = 1", "x = 2", false).map(|(updated, _)| updated),
+            Err(StrReplaceError::Ambiguous(2))
+        );
+        let (updated, edit) = edit(contents, "x = 1", "x = 2", true).expect("edit applies");
+        assert_eq!(updated, "x = 2\ny = 1\nx = 2\n");
+        assert_eq!(edit.replacements, 2);
+    }
+
+    #[test]
+    fn falls_back_to_matching_lines_ignoring_whitespace() {
+        let (updated, edit) = edit(
+            "if ok {\n\t\treturn 1;\n\t}\n",
+            "return 1;\n}",
+            "return 2;\n}\n",
+            false,
+        )
+        .expect("edit applies");
+        assert_eq!(updated, "if ok {\nreturn 2;\n}\n");
+        assert_eq!(edit.matched, StrReplaceMatch::IgnoringWhitespace);
+    }
+
+    #[test]
+    fn removing_a_line_break_joins_the_next_line() {
+        let (updated, _) = edit("a,\nb\nc\n", ",\n", ", ", false).expect("edit applies");
+        assert_eq!(updated, "a, b\nc\n");
+    }
+
+    #[test]
+    fn empty_old_string_creates_missing_files_only() {
+        let path = Path::new("new.txt");
+        assert_eq!(
+            str_replace_patch(path, None, "", "hello\n", false).map(|edit| edit.patch),
+            Ok("*** Begin Patch\n*** Add File: new.txt\n+hello\n*** End Patch".to_string())
+        );
+        assert_eq!(
+            str_replace_patch(path, Some("hello\n"), "", "bye\n", false),
+            Err(StrReplaceError::FileExists)
+        );
+        assert_eq!(
+            str_replace_patch(path, None, "hello", "bye", false),
+            Err(StrReplaceError::FileNotFound)
+        );
+    }
+}
@@ -89,3 +89,35 @@ fn test_apply_patch_cli_stdin_add_and_update() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_apply_patch_cli_dry_run_reports_conflicts() -> anyhow::Result<()> {
+    let tmp = tempdir()?;
+    let file = "dry_run.txt";
+    let absolute_path = tmp.path().join(file);
+    fs::write(&absolute_path, "one\ntwo\n")?;
+
+    let patch = format!(
+        r#"*** Begin Patch
+*** Update File: {file}
+@@
+-one
++uno
+@@
+-three
++tres
+*** End Patch"#
+    );
+    apply_patch_command()?
+        .arg("--dry-run")
+        .arg(patch)
+        .current_dir(tmp.path())
+        .assert()
+        .failure()
+        .stdout(format!(
+            "Dry run: 1 of 2 hunks would conflict:\nC {file} (hunk 2): Failed to find expected lines in {file}:\nthree\n"
+        ));
+    assert_eq!(fs::read_to_string(&absolute_path)?, "one\ntwo\n");
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use codex_core::audit::AuditVerification;
+use codex_core::audit::audit_key_path;
+use codex_core::audit::audit_log_path;
+use codex_core::audit::verify_audit_log;
+use codex_core::config::Config;
+use codex_utils_cli::CliConfigOverrides;
+
+/// Subcommands:
+/// - `verify` — check the hash chain and signatures of the `[audit]` log
+#[derive(Debug, clap::Parser)]
+pub struct AuditCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub subcommand: AuditSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum AuditSubcommand {
+    /// Check that no audit log entry was edited, reordered, removed or
+    /// written without the signing key.
+    Verify(AuditVerifyArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct AuditVerifyArgs {
+    /// Log to check. Defaults to the configured `[audit]` log.
+    #[arg(long, value_name = "FILE")]
+    pub log: Option<PathBuf>,
+
+    /// Key the log was signed with. Defaults to the configured `[audit]` key.
+    #[arg(long, value_name = "FILE")]
+    pub key: Option<PathBuf>,
+}
+
+impl AuditCli {
+    pub async fn run(self) -> Result<()> {
+        let AuditCli {
+            config_overrides,
+            subcommand,
+        } = self;
+
+        match subcommand {
+            AuditSubcommand::Verify(args) => run_verify(&config_overrides, args).await,
+        }
+    }
+}
+
+async fn run_verify(config_overrides: &CliConfigOverrides, args: AuditVerifyArgs) -> Result<()> {
+    let overrides = config_overrides
+        .parse_overrides()
+        .map_err(anyhow::Error::msg)?;
+    let config = Config::load_with_cli_overrides(overrides)
+        .await
+        .context("failed to load configuration")?;
+
+    let log_path = args.log.unwrap_or_else(|| audit_log_path(&config));
+    let key_path = args.key.unwrap_or_else(|| audit_key_path(&config));
+    let verification = verify_audit_log(&log_path, &key_path)
+        .with_context(|| format!("failed to read {}", log_path.display()))?;
+    match verification {
+        AuditVerification::Intact { entries } => {
+            println!("{}: {entries} entries, intact", log_path.display());
+            Ok(())
+        }
+        AuditVerification::Broken { line, reason } => {
+            bail!("{}: line {line}: {reason}", log_path.display())
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use codex_core::config::Config;
+use codex_core::find_archived_thread_path_by_id_str;
+use codex_core::find_thread_path_by_id_str;
+use codex_core::find_thread_path_by_name_str;
+use codex_core::session_export::SessionReport;
+use codex_protocol::ThreadId;
+use codex_utils_cli::CliConfigOverrides;
+
+/// Renders a recorded session as a Markdown or HTML report.
+#[derive(Debug, clap::Parser)]
+pub struct ExportCommand {
+    /// Id or name of the session to export.
+    pub session: String,
+
+    #[arg(long, value_enum, default_value_t = ExportFormat::Markdown)]
+    pub format: ExportFormat,
+
+    /// Write the report to this file instead of stdout.
+    #[arg(long, short = 'o', value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+}
+
+pub async fn run_export_command(export_cli: ExportCommand) -> Result<()> {
+    let config = Config::load_with_cli_overrides(
+        export_cli
+            .config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?,
+    )
+    .await
+    .context("failed to load configuration")?;
+
+    let session = export_cli.session.as_str();
+    let path = if ThreadId::from_string(session).is_ok() {
+        match find_thread_path_by_id_str(&config.codex_home, session).await? {
+            Some(path) => Some(path),
+            None => find_archived_thread_path_by_id_str(&config.codex_home, session).await?,
+        }
+    } else {
+        find_thread_path_by_name_str(&config.codex_home, session).await?
+    };
+    let Some(path) = path else {
+        bail!("no session found for `{session}`");
+    };
+
+    let report = SessionReport::load(&path)
+        .await
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let rendered = match export_cli.format {
+        ExportFormat::Markdown => report.to_markdown(),
+        ExportFormat::Html => report.to_html(),
+    };
+    match export_cli.output {
+        Some(output) => {
+            std::fs::write(&output, rendered)
+                .with_context(|| format!("failed to write {}", output.display()))?;
+            println!("Exported session to {}", output.display());
+        }
+        None => print!("{rendered}"),
+    }
+    Ok(())
+}
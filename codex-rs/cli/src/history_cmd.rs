@@ -0,0 +1,179 @@
+use anyhow::Context;
+use anyhow::Result;
+use chrono::DateTime;
+use chrono::NaiveDate;
+use chrono::Utc;
+use codex_core::config::Config;
+use codex_core::state_db;
+use codex_state::ThreadSearchHit;
+use codex_state::ThreadSearchQuery;
+use codex_utils_cli::CliConfigOverrides;
+
+/// Searches recorded sessions.
+#[derive(Debug, clap::Parser)]
+pub struct HistoryCommand {
+    #[command(subcommand)]
+    pub subcommand: HistorySubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum HistorySubcommand {
+    /// Find sessions whose messages, commands or edited file paths contain
+    /// every word of a query.
+    Search(HistorySearchCommand),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct HistorySearchCommand {
+    /// Words to search for, e.g. `flaky auth test`.
+    #[arg(value_name = "QUERY", required = true, num_args = 1..)]
+    pub query: Vec<String>,
+
+    /// Only sessions active on or after this date (YYYY-MM-DD or RFC 3339).
+    #[arg(long, value_name = "DATE", value_parser = parse_since)]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Only sessions started on or before this date (YYYY-MM-DD or RFC 3339).
+    #[arg(long, value_name = "DATE", value_parser = parse_until)]
+    pub until: Option<DateTime<Utc>>,
+
+    /// Only sessions whose working directory or git remote contains this.
+    #[arg(long, value_name = "REPO")]
+    pub repo: Option<String>,
+
+    /// Only sessions with turns run by this model.
+    #[arg(long, value_name = "MODEL")]
+    pub model: Option<String>,
+
+    /// Maximum number of sessions to list.
+    #[arg(long, short = 'n', default_value_t = 20)]
+    pub limit: usize,
+
+    /// Print one JSON object per session instead of text.
+    #[arg(long)]
+    pub json: bool,
+
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+}
+
+pub async fn run_history_command(history_cli: HistoryCommand) -> Result<()> {
+    match history_cli.subcommand {
+        HistorySubcommand::Search(search_cli) => run_history_search(search_cli).await,
+    }
+}
+
+async fn run_history_search(search_cli: HistorySearchCommand) -> Result<()> {
+    let config = Config::load_with_cli_overrides(
+        search_cli
+            .config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?,
+    )
+    .await
+    .context("failed to load configuration")?;
+
+    let query = ThreadSearchQuery {
+        text: search_cli.query.join(" "),
+        since: search_cli.since,
+        until: search_cli.until,
+        repo: search_cli.repo,
+        model: search_cli.model,
+        limit: search_cli.limit,
+    };
+    let hits = state_db::search_threads(&config, &query)
+        .await
+        .context("failed to search session history")?;
+
+    if search_cli.json {
+        for hit in &hits {
+            println!("{}", hit_to_json(hit));
+        }
+        return Ok(());
+    }
+    if hits.is_empty() {
+        println!("No sessions found.");
+        return Ok(());
+    }
+    for hit in &hits {
+        let thread = &hit.thread;
+        println!(
+            "{}  {}  {}",
+            thread.id,
+            thread.updated_at.format("%Y-%m-%d %H:%M"),
+            thread.cwd.display()
+        );
+        if let Some(title) = thread.title.lines().next().filter(|line| !line.is_empty()) {
+            println!("    {title}");
+        }
+        println!("    {}: {}", hit.kind.as_str(), single_line(&hit.snippet));
+    }
+    Ok(())
+}
+
+fn hit_to_json(hit: &ThreadSearchHit) -> serde_json::Value {
+    let thread = &hit.thread;
+    serde_json::json!({
+        "id": thread.id.to_string(),
+        "rollout_path": thread.rollout_path,
+        "created_at": thread.created_at.to_rfc3339(),
+        "updated_at": thread.updated_at.to_rfc3339(),
+        "cwd": thread.cwd,
+        "title": thread.title,
+        "git_origin_url": thread.git_origin_url,
+        "kind": hit.kind.as_str(),
+        "snippet": hit.snippet,
+    })
+}
+
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn parse_since(value: &str) -> Result<DateTime<Utc>, String> {
+    parse_date(value, false)
+}
+
+fn parse_until(value: &str) -> Result<DateTime<Utc>, String> {
+    parse_date(value, true)
+}
+
+/// Parses an RFC 3339 timestamp, or a date meaning the start of that day
+/// (or its end, with `end_of_day`) in UTC.
+fn parse_date(value: &str, end_of_day: bool) -> Result<DateTime<Utc>, String> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("invalid date `{value}`, expected YYYY-MM-DD or RFC 3339"))?;
+    let time = if end_of_day {
+        date.and_hms_opt(23, 59, 59)
+    } else {
+        date.and_hms_opt(0, 0, 0)
+    };
+    time.map(|time| time.and_utc())
+        .ok_or_else(|| format!("invalid date `{value}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn dates_cover_whole_days() {
+        assert_eq!(
+            parse_since("2026-03-01").map(|date| date.to_rfc3339()),
+            Ok("2026-03-01T00:00:00+00:00".to_string())
+        );
+        assert_eq!(
+            parse_until("2026-03-01").map(|date| date.to_rfc3339()),
+            Ok("2026-03-01T23:59:59+00:00".to_string())
+        );
+        assert_eq!(
+            parse_since("2026-03-01T12:30:00+02:00").map(|date| date.to_rfc3339()),
+            Ok("2026-03-01T10:30:00+00:00".to_string())
+        );
+        assert!(parse_since("last week").is_err());
+    }
+}
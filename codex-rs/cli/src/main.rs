@@ -17,6 +17,7 @@ use codex_cli::login::run_login_with_chatgpt;
 use codex_cli::login::run_login_with_device_code;
 use codex_cli::login::run_logout;
 use codex_cloud_tasks::Cli as CloudTasksCli;
+use codex_exec::BatchCli;
 use codex_exec::Cli as ExecCli;
 use codex_exec::Command as ExecCommand;
 use codex_exec::ReviewArgs;
@@ -36,13 +37,27 @@ use supports_color::Stream;
 
 #[cfg(target_os = "macos")]
 mod app_cmd;
+mod audit_cmd;
 #[cfg(target_os = "macos")]
 mod desktop_app;
+mod export_cmd;
+mod history_cmd;
 mod mcp_cmd;
+mod merge_cmd;
+mod policy_cmd;
 #[cfg(not(windows))]
 mod wsl_paths;
 
+use crate::audit_cmd::AuditCli;
+use crate::export_cmd::ExportCommand;
+use crate::export_cmd::run_export_command;
+use crate::history_cmd::HistoryCommand;
+use crate::history_cmd::HistorySubcommand;
+use crate::history_cmd::run_history_command;
 use crate::mcp_cmd::McpCli;
+use crate::merge_cmd::MergeCommand;
+use crate::merge_cmd::run_merge_command;
+use crate::policy_cmd::PolicyCli;
 
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
@@ -87,6 +102,9 @@ enum Subcommand {
     #[clap(visible_alias = "e")]
     Exec(ExecCli),
 
+    /// Run many prompts across many repositories from a JSON manifest, non-interactively.
+    Batch(BatchCli),
+
     /// Run a code review non-interactively.
     Review(ReviewArgs),
 
@@ -126,6 +144,15 @@ enum Subcommand {
     #[clap(visible_alias = "a")]
     Apply(ApplyCommand),
 
+    /// Apply the changes a session made in its isolated worktree to your checkout.
+    Merge(MergeCommand),
+
+    /// Export a recorded session as a Markdown or HTML report.
+    Export(ExportCommand),
+
+    /// Search recorded sessions by their messages, commands and edited files.
+    History(HistoryCommand),
+
     /// Resume a previous interactive session (picker by default; use --last to continue the most recent).
     Resume(ResumeCommand),
 
@@ -146,6 +173,12 @@ enum Subcommand {
 
     /// Inspect feature flags.
     Features(FeaturesCli),
+
+    /// Check tool calls against configured approval rules.
+    Policy(PolicyCli),
+
+    /// Check the signed log of executed commands and applied patches.
+    Audit(AuditCli),
 }
 
 #[derive(Debug, Parser)]
@@ -585,6 +618,13 @@ async fn cli_main(arg0_paths: Arg0DispatchPaths) -> anyhow::Result<()> {
             );
             codex_exec::run_main(exec_cli, arg0_paths.clone()).await?;
         }
+        Some(Subcommand::Batch(mut batch_cli)) => {
+            prepend_config_flags(
+                &mut batch_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            codex_exec::run_batch(batch_cli, arg0_paths.clone()).await?;
+        }
         Some(Subcommand::Review(review_args)) => {
             let mut exec_cli = ExecCli::try_parse_from(["codex", "exec"])?;
             exec_cli.command = Some(ExecCommand::Review(review_args));
@@ -602,6 +642,20 @@ async fn cli_main(arg0_paths: Arg0DispatchPaths) -> anyhow::Result<()> {
             prepend_config_flags(&mut mcp_cli.config_overrides, root_config_overrides.clone());
             mcp_cli.run().await?;
         }
+        Some(Subcommand::Policy(mut policy_cli)) => {
+            prepend_config_flags(
+                &mut policy_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            policy_cli.run().await?;
+        }
+        Some(Subcommand::Audit(mut audit_cli)) => {
+            prepend_config_flags(
+                &mut audit_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            audit_cli.run().await?;
+        }
         Some(Subcommand::AppServer(app_server_cli)) => match app_server_cli.subcommand {
             None => {
                 let transport = app_server_cli.listen;
@@ -772,6 +826,29 @@ async fn cli_main(arg0_paths: Arg0DispatchPaths) -> anyhow::Result<()> {
             );
             run_apply_command(apply_cli, None).await?;
         }
+        Some(Subcommand::Merge(mut merge_cli)) => {
+            prepend_config_flags(
+                &mut merge_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            run_merge_command(merge_cli).await?;
+        }
+        Some(Subcommand::Export(mut export_cli)) => {
+            prepend_config_flags(
+                &mut export_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            run_export_command(export_cli).await?;
+        }
+        Some(Subcommand::History(mut history_cli)) => {
+            match &mut history_cli.subcommand {
+                HistorySubcommand::Search(search_cli) => prepend_config_flags(
+                    &mut search_cli.config_overrides,
+                    root_config_overrides.clone(),
+                ),
+            }
+            run_history_command(history_cli).await?;
+        }
         Some(Subcommand::ResponsesApiProxy(args)) => {
             tokio::task::spawn_blocking(move || codex_responses_api_proxy::run_main(args))
                 .await??;
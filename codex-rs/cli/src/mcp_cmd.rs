@@ -254,6 +254,8 @@ async fn run_add(config_overrides: &CliConfigOverrides, add_args: AddArgs) -> Re
         disabled_tools: None,
         scopes: None,
         oauth_resource: None,
+        max_concurrent_calls: None,
+        max_calls_per_minute: None,
     };
 
     servers.insert(name.clone(), new_entry);
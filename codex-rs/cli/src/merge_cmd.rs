@@ -0,0 +1,64 @@
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use codex_core::config::Config;
+use codex_core::session_worktree::SessionWorktree;
+use codex_core::session_worktree::merge_session_worktree;
+use codex_core::session_worktree::remove_session_worktree;
+use codex_protocol::ThreadId;
+use codex_utils_cli::CliConfigOverrides;
+
+/// Applies the changes a session made in its worktree to the checkout it was
+/// started from (see `features.worktree_isolation`).
+#[derive(Debug, clap::Parser)]
+pub struct MergeCommand {
+    /// Id of the session whose worktree to merge.
+    pub session_id: String,
+
+    /// Delete the worktree after a successful merge.
+    #[arg(long)]
+    pub remove: bool,
+
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+}
+
+pub async fn run_merge_command(merge_cli: MergeCommand) -> Result<()> {
+    let config = Config::load_with_cli_overrides(
+        merge_cli
+            .config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?,
+    )
+    .await
+    .context("failed to load configuration")?;
+
+    let thread_id = ThreadId::from_string(&merge_cli.session_id)
+        .with_context(|| format!("invalid session id `{}`", merge_cli.session_id))?;
+    let Some(mut worktree) = SessionWorktree::load(&config.codex_home, thread_id)? else {
+        bail!("session {thread_id} has no worktree");
+    };
+
+    match merge_session_worktree(&config.codex_home, &mut worktree)? {
+        None => println!("No changes to merge from {}", worktree.worktree.display()),
+        Some(res) if res.exit_code != 0 => bail!(
+            "Git apply failed (applied={}, skipped={}, conflicts={})\nstdout:\n{}\nstderr:\n{}",
+            res.applied_paths.len(),
+            res.skipped_paths.len(),
+            res.conflicted_paths.len(),
+            res.stdout,
+            res.stderr
+        ),
+        Some(res) => println!(
+            "Merged {} path(s) into {}",
+            res.applied_paths.len(),
+            worktree.repo_root.display()
+        ),
+    }
+
+    if merge_cli.remove {
+        remove_session_worktree(&config.codex_home, &worktree)?;
+        println!("Removed {}", worktree.worktree.display());
+    }
+    Ok(())
+}
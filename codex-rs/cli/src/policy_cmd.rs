@@ -0,0 +1,100 @@
+use anyhow::Context;
+use anyhow::Result;
+use codex_core::approval_rules::ApprovalSubject;
+use codex_core::approval_rules::PatchedFile;
+use codex_core::approval_rules::evaluate_approval_rules;
+use codex_core::config::Config;
+use codex_core::config::types::ApprovalRuleDecision;
+use codex_utils_cli::CliConfigOverrides;
+
+/// Subcommands:
+/// - `test` — evaluate a sample tool call against `[[approval_rules]]`
+#[derive(Debug, clap::Parser)]
+pub struct PolicyCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub subcommand: PolicySubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum PolicySubcommand {
+    /// Show which approval rule, if any, decides a sample tool call.
+    Test(PolicyTestArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct PolicyTestArgs {
+    #[command(subcommand)]
+    pub call: SampleToolCall,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum SampleToolCall {
+    /// A shell command, e.g. `codex policy test command -- cargo test`.
+    Command {
+        #[arg(trailing_var_arg = true, num_args = 1.., required = true)]
+        command: Vec<String>,
+    },
+
+    /// An `apply_patch` call that writes the given files.
+    Patch {
+        #[arg(required = true)]
+        paths: Vec<String>,
+
+        /// Size of each written file, in bytes.
+        #[arg(long, default_value_t = 0)]
+        bytes: u64,
+    },
+
+    /// A call to `tool` on the MCP server `server`.
+    Mcp { server: String, tool: String },
+}
+
+impl PolicyCli {
+    pub async fn run(self) -> Result<()> {
+        let PolicyCli {
+            config_overrides,
+            subcommand,
+        } = self;
+
+        match subcommand {
+            PolicySubcommand::Test(args) => run_test(&config_overrides, args).await,
+        }
+    }
+}
+
+async fn run_test(config_overrides: &CliConfigOverrides, args: PolicyTestArgs) -> Result<()> {
+    let overrides = config_overrides
+        .parse_overrides()
+        .map_err(anyhow::Error::msg)?;
+    let config = Config::load_with_cli_overrides(overrides)
+        .await
+        .context("failed to load configuration")?;
+
+    let subject = match &args.call {
+        SampleToolCall::Command { command } => ApprovalSubject::Command(command),
+        SampleToolCall::Patch { paths, bytes } => ApprovalSubject::Patch(
+            paths
+                .iter()
+                .map(|path| PatchedFile::new(&config.cwd.join(path), &config.cwd, *bytes))
+                .collect(),
+        ),
+        SampleToolCall::Mcp { server, tool } => ApprovalSubject::McpTool { server, tool },
+    };
+
+    match evaluate_approval_rules(&config.approval_rules, &subject) {
+        Some(rule) => {
+            let decision = match rule.decision {
+                ApprovalRuleDecision::Approve => "approve",
+                ApprovalRuleDecision::Deny => "deny",
+                ApprovalRuleDecision::Ask => "ask",
+            };
+            println!("{decision} ({rule})");
+        }
+        None => println!("no approval rule matched; the approval policy decides"),
+    }
+
+    Ok(())
+}
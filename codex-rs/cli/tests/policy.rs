@@ -0,0 +1,89 @@
+use std::path::Path;
+
+use anyhow::Result;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+fn codex_command(codex_home: &Path) -> Result<assert_cmd::Command> {
+    let mut cmd = assert_cmd::Command::new(codex_utils_cargo_bin::cargo_bin("codex")?);
+    cmd.env("CODEX_HOME", codex_home);
+    Ok(cmd)
+}
+
+fn write_rules(codex_home: &Path) -> Result<()> {
+    std::fs::write(
+        codex_home.join("config.toml"),
+        r#"
+[[approval_rules]]
+decision = "deny"
+command_prefix = ["rm"]
+
+[[approval_rules]]
+decision = "approve"
+command_prefix = ["cargo", "test"]
+
+[[approval_rules]]
+decision = "approve"
+paths = ["src/*"]
+max_file_bytes = 1000
+
+[[approval_rules]]
+decision = "ask"
+mcp_server = "github"
+"#,
+    )?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn policy_test_reports_matching_command_rule() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    write_rules(codex_home.path())?;
+
+    codex_command(codex_home.path())?
+        .args([
+            "policy", "test", "command", "--", "cargo", "test", "-p", "x",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("approve (approval rule 2)"));
+    codex_command(codex_home.path())?
+        .args([
+            "policy",
+            "test",
+            "command",
+            "--",
+            "bash",
+            "-lc",
+            "ls && rm -rf target",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("deny (approval rule 1)"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn policy_test_reports_patch_and_mcp_rules() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    write_rules(codex_home.path())?;
+
+    codex_command(codex_home.path())?
+        .args(["policy", "test", "patch", "src/lib.rs", "--bytes", "10"])
+        .assert()
+        .success()
+        .stdout(contains("approve (approval rule 3)"));
+    codex_command(codex_home.path())?
+        .args(["policy", "test", "patch", "src/lib.rs", "--bytes", "5000"])
+        .assert()
+        .success()
+        .stdout(contains("no approval rule matched"));
+    codex_command(codex_home.path())?
+        .args(["policy", "test", "mcp", "github", "create_issue"])
+        .assert()
+        .success()
+        .stdout(contains("ask (approval rule 4)"));
+
+    Ok(())
+}
@@ -762,6 +762,7 @@ mod tests {
                 allowed_sandbox_modes: None,
                 allowed_web_search_modes: None,
                 feature_requirements: None,
+                feature_defaults: None,
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: None,
@@ -805,6 +806,7 @@ mod tests {
                 allowed_sandbox_modes: None,
                 allowed_web_search_modes: None,
                 feature_requirements: None,
+                feature_defaults: None,
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: None,
@@ -859,6 +861,7 @@ mod tests {
                 allowed_sandbox_modes: None,
                 allowed_web_search_modes: None,
                 feature_requirements: None,
+                feature_defaults: None,
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: None,
@@ -916,6 +919,7 @@ mod tests {
                 allowed_sandbox_modes: None,
                 allowed_web_search_modes: None,
                 feature_requirements: None,
+                feature_defaults: None,
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: None,
@@ -944,6 +948,7 @@ mod tests {
                 allowed_sandbox_modes: None,
                 allowed_web_search_modes: None,
                 feature_requirements: None,
+                feature_defaults: None,
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: None,
@@ -992,6 +997,7 @@ mod tests {
                 allowed_sandbox_modes: None,
                 allowed_web_search_modes: None,
                 feature_requirements: None,
+                feature_defaults: None,
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: None,
@@ -1039,6 +1045,7 @@ mod tests {
                 allowed_sandbox_modes: None,
                 allowed_web_search_modes: None,
                 feature_requirements: None,
+                feature_defaults: None,
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: None,
@@ -1090,6 +1097,7 @@ mod tests {
                 allowed_sandbox_modes: None,
                 allowed_web_search_modes: None,
                 feature_requirements: None,
+                feature_defaults: None,
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: None,
@@ -1142,6 +1150,7 @@ mod tests {
                 allowed_sandbox_modes: None,
                 allowed_web_search_modes: None,
                 feature_requirements: None,
+                feature_defaults: None,
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: None,
@@ -1194,6 +1203,7 @@ mod tests {
                 allowed_sandbox_modes: None,
                 allowed_web_search_modes: None,
                 feature_requirements: None,
+                feature_defaults: None,
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: None,
@@ -1283,6 +1293,7 @@ mod tests {
                 allowed_sandbox_modes: None,
                 allowed_web_search_modes: None,
                 feature_requirements: None,
+                feature_defaults: None,
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: None,
@@ -1307,6 +1318,7 @@ mod tests {
                 allowed_sandbox_modes: None,
                 allowed_web_search_modes: None,
                 feature_requirements: None,
+                feature_defaults: None,
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: None,
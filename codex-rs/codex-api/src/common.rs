@@ -23,6 +23,13 @@ pub struct CompactionInput<'a> {
     pub instructions: &'a str,
 }
 
+/// Input payload for the embeddings endpoint.
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbeddingsInput<'a> {
+    pub model: &'a str,
+    pub input: &'a [String],
+}
+
 /// Canonical input payload for the memory summarize endpoint.
 #[derive(Debug, Clone, Serialize)]
 pub struct MemorySummarizeInput {
@@ -156,8 +163,14 @@ pub struct ResponsesApiRequest {
     pub service_tier: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prompt_cache_key: Option<String>,
+    /// How long the provider keeps the prompt's cached prefix, e.g. `24h`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_cache_retention: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub text: Option<TextControls>,
+    /// Upper bound on the tokens generated for the response.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
 }
 
 impl From<&ResponsesApiRequest> for ResponseCreateWsRequest {
@@ -176,7 +189,9 @@ impl From<&ResponsesApiRequest> for ResponseCreateWsRequest {
             include: request.include.clone(),
             service_tier: request.service_tier.clone(),
             prompt_cache_key: request.prompt_cache_key.clone(),
+            prompt_cache_retention: request.prompt_cache_retention.clone(),
             text: request.text.clone(),
+            max_tokens: request.max_tokens,
             generate: None,
             client_metadata: None,
         }
@@ -201,9 +216,14 @@ pub struct ResponseCreateWsRequest {
     pub service_tier: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prompt_cache_key: Option<String>,
+    /// How long the provider keeps the prompt's cached prefix, e.g. `24h`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_cache_retention: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub text: Option<TextControls>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub generate: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub client_metadata: Option<HashMap<String, String>>,
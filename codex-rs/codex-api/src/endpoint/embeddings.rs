@@ -0,0 +1,179 @@
+use crate::auth::AuthProvider;
+use crate::common::EmbeddingsInput;
+use crate::endpoint::session::EndpointSession;
+use crate::error::ApiError;
+use crate::provider::Provider;
+use codex_client::HttpTransport;
+use codex_client::RequestTelemetry;
+use http::HeaderMap;
+use http::Method;
+use serde::Deserialize;
+use serde_json::to_value;
+use std::sync::Arc;
+
+pub struct EmbeddingsClient<T: HttpTransport, A: AuthProvider> {
+    session: EndpointSession<T, A>,
+}
+
+impl<T: HttpTransport, A: AuthProvider> EmbeddingsClient<T, A> {
+    pub fn new(transport: T, provider: Provider, auth: A) -> Self {
+        Self {
+            session: EndpointSession::new(transport, provider, auth),
+        }
+    }
+
+    pub fn with_telemetry(self, request: Option<Arc<dyn RequestTelemetry>>) -> Self {
+        Self {
+            session: self.session.with_request_telemetry(request),
+        }
+    }
+
+    fn path() -> &'static str {
+        "embeddings"
+    }
+
+    /// Embeds each of `input.input`, returning the vectors in input order.
+    pub async fn embed(
+        &self,
+        input: &EmbeddingsInput<'_>,
+        extra_headers: HeaderMap,
+    ) -> Result<Vec<Vec<f32>>, ApiError> {
+        let body = to_value(input)
+            .map_err(|e| ApiError::Stream(format!("failed to encode embeddings input: {e}")))?;
+        let resp = self
+            .session
+            .execute(Method::POST, Self::path(), extra_headers, Some(body))
+            .await?;
+        let mut parsed: EmbeddingsResponse =
+            serde_json::from_slice(&resp.body).map_err(|e| ApiError::Stream(e.to_string()))?;
+        if parsed.data.len() != input.input.len() {
+            return Err(ApiError::Stream(format!(
+                "expected {} embeddings, got {}",
+                input.input.len(),
+                parsed.data.len()
+            )));
+        }
+        parsed.data.sort_by_key(|embedding| embedding.index);
+        Ok(parsed
+            .data
+            .into_iter()
+            .map(|embedding| embedding.embedding)
+            .collect())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingsResponse {
+    data: Vec<Embedding>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Embedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::RetryConfig;
+    use async_trait::async_trait;
+    use codex_client::Request;
+    use codex_client::Response;
+    use codex_client::StreamResponse;
+    use codex_client::TransportError;
+    use http::StatusCode;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    #[derive(Clone, Default)]
+    struct DummyAuth;
+
+    impl AuthProvider for DummyAuth {
+        fn bearer_token(&self) -> Option<String> {
+            None
+        }
+    }
+
+    #[derive(Clone)]
+    struct CapturingTransport {
+        last_request: Arc<Mutex<Option<Request>>>,
+        response_body: Arc<Vec<u8>>,
+    }
+
+    #[async_trait]
+    impl HttpTransport for CapturingTransport {
+        async fn execute(&self, req: Request) -> Result<Response, TransportError> {
+            *self.last_request.lock().expect("lock request store") = Some(req);
+            Ok(Response {
+                status: StatusCode::OK,
+                headers: HeaderMap::new(),
+                body: self.response_body.as_ref().clone().into(),
+            })
+        }
+
+        async fn stream(&self, _req: Request) -> Result<StreamResponse, TransportError> {
+            Err(TransportError::Build("stream should not run".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn embed_posts_inputs_and_orders_vectors_by_index() {
+        let transport = CapturingTransport {
+            last_request: Arc::new(Mutex::new(None)),
+            response_body: Arc::new(
+                serde_json::to_vec(&json!({
+                    "data": [
+                        {"index": 1, "embedding": [0.0, 1.0]},
+                        {"index": 0, "embedding": [1.0, 0.0]}
+                    ]
+                }))
+                .expect("serialize response"),
+            ),
+        };
+        let provider = Provider {
+            name: "test".to_string(),
+            base_url: "https://example.com/v1".to_string(),
+            query_params: None,
+            headers: HeaderMap::new(),
+            retry: RetryConfig {
+                max_attempts: 1,
+                base_delay: Duration::from_millis(1),
+                retry_429: false,
+                retry_5xx: true,
+                retry_transport: true,
+                backoff_multiplier: 2.0,
+                max_delay: None,
+                retry_status_codes: None,
+            },
+            stream_idle_timeout: Duration::from_secs(1),
+        };
+        let client = EmbeddingsClient::new(transport.clone(), provider, DummyAuth);
+        let input = vec!["fn a()".to_string(), "fn b()".to_string()];
+
+        let embeddings = client
+            .embed(
+                &EmbeddingsInput {
+                    model: "text-embedding-test",
+                    input: &input,
+                },
+                HeaderMap::new(),
+            )
+            .await
+            .expect("embed request should succeed");
+        assert_eq!(embeddings, vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+
+        let request = transport
+            .last_request
+            .lock()
+            .expect("lock request store")
+            .clone()
+            .expect("request should be captured");
+        assert_eq!(request.url, "https://example.com/v1/embeddings");
+        let body = request.body.expect("request body should be present");
+        assert_eq!(body["model"], "text-embedding-test");
+        assert_eq!(body["input"], json!(["fn a()", "fn b()"]));
+    }
+}
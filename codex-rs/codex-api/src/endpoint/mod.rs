@@ -1,4 +1,5 @@
 pub mod compact;
+pub mod embeddings;
 pub mod memories;
 pub mod models;
 pub mod realtime_websocket;
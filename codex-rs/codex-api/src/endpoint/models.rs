@@ -143,6 +143,9 @@ mod tests {
                 retry_429: false,
                 retry_5xx: true,
                 retry_transport: true,
+                backoff_multiplier: 2.0,
+                max_delay: None,
+                retry_status_codes: None,
             },
             stream_idle_timeout: Duration::from_secs(1),
         }
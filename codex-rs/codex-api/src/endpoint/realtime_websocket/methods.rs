@@ -879,6 +879,9 @@ mod tests {
                 retry_429: false,
                 retry_5xx: false,
                 retry_transport: false,
+                backoff_multiplier: 2.0,
+                max_delay: None,
+                retry_status_codes: None,
             },
             stream_idle_timeout: Duration::from_secs(5),
         };
@@ -1019,6 +1022,9 @@ mod tests {
                 retry_429: false,
                 retry_5xx: false,
                 retry_transport: false,
+                backoff_multiplier: 2.0,
+                max_delay: None,
+                retry_status_codes: None,
             },
             stream_idle_timeout: Duration::from_secs(5),
         };
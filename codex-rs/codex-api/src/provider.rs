@@ -19,6 +19,11 @@ pub struct RetryConfig {
     pub retry_429: bool,
     pub retry_5xx: bool,
     pub retry_transport: bool,
+    /// Factor each retry's delay grows by over the previous one.
+    pub backoff_multiplier: f64,
+    pub max_delay: Option<Duration>,
+    /// HTTP statuses to retry. When set, replaces `retry_429` and `retry_5xx`.
+    pub retry_status_codes: Option<Vec<u16>>,
 }
 
 impl RetryConfig {
@@ -26,10 +31,13 @@ impl RetryConfig {
         RetryPolicy {
             max_attempts: self.max_attempts,
             base_delay: self.base_delay,
+            backoff_multiplier: self.backoff_multiplier,
+            max_delay: self.max_delay,
             retry_on: RetryOn {
                 retry_429: self.retry_429,
                 retry_5xx: self.retry_5xx,
                 retry_transport: self.retry_transport,
+                status_codes: self.retry_status_codes.clone(),
             },
         }
     }
@@ -22,7 +22,8 @@ pub(crate) fn attach_item_ids(payload_json: &mut Value, original_items: &[Respon
         | ResponseItem::WebSearchCall { id: Some(id), .. }
         | ResponseItem::FunctionCall { id: Some(id), .. }
         | ResponseItem::LocalShellCall { id: Some(id), .. }
-        | ResponseItem::CustomToolCall { id: Some(id), .. } = item
+        | ResponseItem::CustomToolCall { id: Some(id), .. }
+        | ResponseItem::CustomToolCallWithAttachments { id: Some(id), .. } = item
         {
             if id.is_empty() {
                 continue;
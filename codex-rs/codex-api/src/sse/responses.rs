@@ -228,13 +228,39 @@ impl ResponsesEventError {
     }
 }
 
+/// Parses a finished output item. Items of a type this client does not know
+/// become [`ResponseItem::Other`], except those carrying a `call_id`: those
+/// are tool calls, kept as [`ResponseItem::UnsupportedToolCall`] so they are
+/// failed rather than lost.
+fn parse_output_item(item_val: &Value) -> Option<ResponseItem> {
+    let item = ResponseItem::deserialize(item_val).ok()?;
+    if !matches!(item, ResponseItem::Other) {
+        return Some(item);
+    }
+    let Some(call_id) = item_val.get("call_id").and_then(Value::as_str) else {
+        return Some(item);
+    };
+    Some(ResponseItem::UnsupportedToolCall {
+        item_type: item_val
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        call_id: call_id.to_string(),
+        name: item_val
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+    })
+}
+
 pub fn process_responses_event(
     event: ResponsesStreamEvent,
 ) -> std::result::Result<Option<ResponseEvent>, ResponsesEventError> {
     match event.kind.as_str() {
         "response.output_item.done" => {
             if let Some(item_val) = event.item {
-                if let Ok(item) = serde_json::from_value::<ResponseItem>(item_val) {
+                if let Some(item) = parse_output_item(&item_val) {
                     return Ok(Some(ResponseEvent::OutputItemDone(item)));
                 }
                 debug!("failed to parse ResponseItem from output_item.done");
@@ -974,6 +1000,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn unknown_output_items_with_a_call_id_are_kept_as_unsupported_tool_calls() {
+        let done = |item: Value| {
+            let event: ResponsesStreamEvent = serde_json::from_value(json!({
+                "type": "response.output_item.done",
+                "item": item,
+            }))
+            .expect("expected event to deserialize");
+            match process_responses_event(event) {
+                Ok(Some(ResponseEvent::OutputItemDone(item))) => item,
+                other => panic!("expected an output item, got {other:?}"),
+            }
+        };
+
+        assert_eq!(
+            done(json!({
+                "type": "structured_tool_call",
+                "call_id": "call-1",
+                "name": "lookup",
+                "input": {"query": "parser"},
+            })),
+            ResponseItem::UnsupportedToolCall {
+                item_type: "structured_tool_call".to_string(),
+                call_id: "call-1".to_string(),
+                name: Some("lookup".to_string()),
+            }
+        );
+        assert_eq!(
+            done(json!({"type": "annotation", "text": "not a call"})),
+            ResponseItem::Other
+        );
+    }
+
     #[test]
     fn test_try_parse_retry_after() {
         let err = Error {
@@ -132,6 +132,9 @@ fn provider(name: &str) -> Provider {
             retry_429: false,
             retry_5xx: false,
             retry_transport: true,
+            backoff_multiplier: 2.0,
+            max_delay: None,
+            retry_status_codes: None,
         },
         stream_idle_timeout: Duration::from_millis(10),
     }
@@ -267,7 +270,9 @@ async fn streaming_client_retries_on_transport_error() -> Result<()> {
         include: Vec::new(),
         service_tier: None,
         prompt_cache_key: None,
+        prompt_cache_retention: None,
         text: None,
+        max_tokens: None,
     };
     let client = ResponsesClient::new(transport.clone(), provider, NoAuth);
 
@@ -309,7 +314,9 @@ async fn azure_default_store_attaches_ids_and_headers() -> Result<()> {
         include: Vec::new(),
         service_tier: None,
         prompt_cache_key: None,
+        prompt_cache_retention: None,
         text: None,
+        max_tokens: None,
     };
 
     let mut extra_headers = HeaderMap::new();
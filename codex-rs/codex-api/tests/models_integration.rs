@@ -41,6 +41,9 @@ fn provider(base_url: &str) -> Provider {
             retry_429: false,
             retry_5xx: true,
             retry_transport: true,
+            backoff_multiplier: 2.0,
+            max_delay: None,
+            retry_status_codes: None,
         },
         stream_idle_timeout: std::time::Duration::from_secs(1),
     }
@@ -87,6 +90,7 @@ async fn models_client_hits_models_endpoint() {
             web_search_tool_type: Default::default(),
             truncation_policy: TruncationPolicyConfig::bytes(10_000),
             supports_parallel_tool_calls: false,
+            supports_tool_calls: true,
             supports_image_detail_original: false,
             context_window: Some(272_000),
             auto_compact_token_limit: None,
@@ -62,6 +62,9 @@ fn test_provider(base_url: String) -> Provider {
             retry_429: false,
             retry_5xx: false,
             retry_transport: false,
+            backoff_multiplier: 2.0,
+            max_delay: None,
+            retry_status_codes: None,
         },
         stream_idle_timeout: Duration::from_secs(5),
     }
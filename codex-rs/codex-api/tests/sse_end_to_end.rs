@@ -70,6 +70,9 @@ fn provider(name: &str) -> Provider {
             retry_429: false,
             retry_5xx: false,
             retry_transport: true,
+            backoff_multiplier: 2.0,
+            max_delay: None,
+            retry_status_codes: None,
         },
         stream_idle_timeout: Duration::from_millis(50),
     }
@@ -9,6 +9,10 @@ use tokio::time::sleep;
 pub struct RetryPolicy {
     pub max_attempts: u64,
     pub base_delay: Duration,
+    /// Factor each retry's delay grows by over the previous one.
+    pub backoff_multiplier: f64,
+    /// Upper bound on the delay between attempts.
+    pub max_delay: Option<Duration>,
     pub retry_on: RetryOn,
 }
 
@@ -17,6 +21,8 @@ pub struct RetryOn {
     pub retry_429: bool,
     pub retry_5xx: bool,
     pub retry_transport: bool,
+    /// HTTP statuses to retry. When set, replaces `retry_429` and `retry_5xx`.
+    pub status_codes: Option<Vec<u16>>,
 }
 
 impl RetryOn {
@@ -25,10 +31,13 @@ impl RetryOn {
             return false;
         }
         match err {
-            TransportError::Http { status, .. } => {
-                (self.retry_429 && status.as_u16() == 429)
-                    || (self.retry_5xx && status.is_server_error())
-            }
+            TransportError::Http { status, .. } => match &self.status_codes {
+                Some(status_codes) => status_codes.contains(&status.as_u16()),
+                None => {
+                    (self.retry_429 && status.as_u16() == 429)
+                        || (self.retry_5xx && status.is_server_error())
+                }
+            },
             TransportError::Timeout | TransportError::Network(_) => self.retry_transport,
             _ => false,
         }
@@ -36,14 +45,27 @@ impl RetryOn {
 }
 
 pub fn backoff(base: Duration, attempt: u64) -> Duration {
+    exponential_backoff(base, 2.0, None, attempt)
+}
+
+/// Delay before retry `attempt` (1-based): `base * multiplier^(attempt - 1)`
+/// with ±10% jitter, capped at `max_delay`.
+pub fn exponential_backoff(
+    base: Duration,
+    multiplier: f64,
+    max_delay: Option<Duration>,
+    attempt: u64,
+) -> Duration {
     if attempt == 0 {
         return base;
     }
-    let exp = 2u64.saturating_pow(attempt as u32 - 1);
-    let millis = base.as_millis() as u64;
-    let raw = millis.saturating_mul(exp);
+    let exp = multiplier
+        .max(1.0)
+        .powi(attempt.saturating_sub(1).min(i32::MAX as u64) as i32);
     let jitter: f64 = rand::rng().random_range(0.9..1.1);
-    Duration::from_millis((raw as f64 * jitter) as u64)
+    let millis = base.as_millis() as f64 * exp * jitter;
+    let delay = Duration::from_millis(millis.min(u64::MAX as f64) as u64);
+    max_delay.map_or(delay, |max_delay| delay.min(max_delay))
 }
 
 pub async fn run_with_retry<T, F, Fut>(
@@ -64,7 +86,13 @@ where
                     .retry_on
                     .should_retry(&err, attempt, policy.max_attempts) =>
             {
-                sleep(backoff(policy.base_delay, attempt + 1)).await;
+                sleep(exponential_backoff(
+                    policy.base_delay,
+                    policy.backoff_multiplier,
+                    policy.max_delay,
+                    attempt + 1,
+                ))
+                .await;
             }
             Err(err) => return Err(err),
         }
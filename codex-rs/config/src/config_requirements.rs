@@ -80,6 +80,10 @@ pub struct ConfigRequirements {
     pub sandbox_policy: ConstrainedWithSource<SandboxPolicy>,
     pub web_search_mode: ConstrainedWithSource<WebSearchMode>,
     pub feature_requirements: Option<Sourced<FeatureRequirementsToml>>,
+    /// Feature values used in place of the built-in defaults. Unlike
+    /// `feature_requirements`, config.toml and CLI flags can still override
+    /// them.
+    pub feature_defaults: Option<Sourced<FeatureRequirementsToml>>,
     pub mcp_servers: Option<Sourced<BTreeMap<String, McpServerRequirement>>>,
     pub exec_policy: Option<Sourced<RequirementsExecPolicy>>,
     pub enforce_residency: ConstrainedWithSource<Option<ResidencyRequirement>>,
@@ -103,6 +107,7 @@ impl Default for ConfigRequirements {
                 None,
             ),
             feature_requirements: None,
+            feature_defaults: None,
             mcp_servers: None,
             exec_policy: None,
             enforce_residency: ConstrainedWithSource::new(Constrained::allow_any(None), None),
@@ -253,6 +258,10 @@ pub struct ConfigRequirementsToml {
     pub allowed_web_search_modes: Option<Vec<WebSearchModeRequirement>>,
     #[serde(rename = "features", alias = "feature_requirements")]
     pub feature_requirements: Option<FeatureRequirementsToml>,
+    /// `[feature_defaults]`: feature values that replace the built-in
+    /// defaults, e.g. to roll a feature out remotely through cloud
+    /// requirements, without pinning them.
+    pub feature_defaults: Option<FeatureRequirementsToml>,
     pub mcp_servers: Option<BTreeMap<String, McpServerRequirement>>,
     pub rules: Option<RequirementsExecPolicyToml>,
     pub enforce_residency: Option<ResidencyRequirement>,
@@ -288,6 +297,7 @@ pub struct ConfigRequirementsWithSources {
     pub allowed_sandbox_modes: Option<Sourced<Vec<SandboxModeRequirement>>>,
     pub allowed_web_search_modes: Option<Sourced<Vec<WebSearchModeRequirement>>>,
     pub feature_requirements: Option<Sourced<FeatureRequirementsToml>>,
+    pub feature_defaults: Option<Sourced<FeatureRequirementsToml>>,
     pub mcp_servers: Option<Sourced<BTreeMap<String, McpServerRequirement>>>,
     pub rules: Option<Sourced<RequirementsExecPolicyToml>>,
     pub enforce_residency: Option<Sourced<ResidencyRequirement>>,
@@ -324,6 +334,7 @@ impl ConfigRequirementsWithSources {
                 allowed_sandbox_modes,
                 allowed_web_search_modes,
                 feature_requirements,
+                feature_defaults,
                 mcp_servers,
                 rules,
                 enforce_residency,
@@ -338,6 +349,7 @@ impl ConfigRequirementsWithSources {
             allowed_sandbox_modes,
             allowed_web_search_modes,
             feature_requirements,
+            feature_defaults,
             mcp_servers,
             rules,
             enforce_residency,
@@ -348,6 +360,7 @@ impl ConfigRequirementsWithSources {
             allowed_sandbox_modes: allowed_sandbox_modes.map(|sourced| sourced.value),
             allowed_web_search_modes: allowed_web_search_modes.map(|sourced| sourced.value),
             feature_requirements: feature_requirements.map(|sourced| sourced.value),
+            feature_defaults: feature_defaults.map(|sourced| sourced.value),
             mcp_servers: mcp_servers.map(|sourced| sourced.value),
             rules: rules.map(|sourced| sourced.value),
             enforce_residency: enforce_residency.map(|sourced| sourced.value),
@@ -398,6 +411,10 @@ impl ConfigRequirementsToml {
                 .feature_requirements
                 .as_ref()
                 .is_none_or(FeatureRequirementsToml::is_empty)
+            && self
+                .feature_defaults
+                .as_ref()
+                .is_none_or(FeatureRequirementsToml::is_empty)
             && self.mcp_servers.is_none()
             && self.rules.is_none()
             && self.enforce_residency.is_none()
@@ -414,6 +431,7 @@ impl TryFrom<ConfigRequirementsWithSources> for ConfigRequirements {
             allowed_sandbox_modes,
             allowed_web_search_modes,
             feature_requirements,
+            feature_defaults,
             mcp_servers,
             rules,
             enforce_residency,
@@ -552,6 +570,7 @@ impl TryFrom<ConfigRequirementsWithSources> for ConfigRequirements {
         };
         let feature_requirements =
             feature_requirements.filter(|requirements| !requirements.value.is_empty());
+        let feature_defaults = feature_defaults.filter(|defaults| !defaults.value.is_empty());
 
         let enforce_residency = match enforce_residency {
             Some(Sourced {
@@ -585,6 +604,7 @@ impl TryFrom<ConfigRequirementsWithSources> for ConfigRequirements {
             sandbox_policy,
             web_search_mode,
             feature_requirements,
+            feature_defaults,
             mcp_servers,
             exec_policy,
             enforce_residency,
@@ -621,6 +641,7 @@ mod tests {
             allowed_sandbox_modes,
             allowed_web_search_modes,
             feature_requirements,
+            feature_defaults,
             mcp_servers,
             rules,
             enforce_residency,
@@ -635,6 +656,8 @@ mod tests {
                 .map(|value| Sourced::new(value, RequirementSource::Unknown)),
             feature_requirements: feature_requirements
                 .map(|value| Sourced::new(value, RequirementSource::Unknown)),
+            feature_defaults: feature_defaults
+                .map(|value| Sourced::new(value, RequirementSource::Unknown)),
             mcp_servers: mcp_servers.map(|value| Sourced::new(value, RequirementSource::Unknown)),
             rules: rules.map(|value| Sourced::new(value, RequirementSource::Unknown)),
             enforce_residency: enforce_residency
@@ -660,6 +683,9 @@ mod tests {
         let feature_requirements = FeatureRequirementsToml {
             entries: BTreeMap::from([("personality".to_string(), true)]),
         };
+        let feature_defaults = FeatureRequirementsToml {
+            entries: BTreeMap::from([("apps".to_string(), true)]),
+        };
         let enforce_residency = ResidencyRequirement::Us;
         let enforce_source = source.clone();
 
@@ -670,6 +696,7 @@ mod tests {
             allowed_sandbox_modes: Some(allowed_sandbox_modes.clone()),
             allowed_web_search_modes: Some(allowed_web_search_modes.clone()),
             feature_requirements: Some(feature_requirements.clone()),
+            feature_defaults: Some(feature_defaults.clone()),
             mcp_servers: None,
             rules: None,
             enforce_residency: Some(enforce_residency),
@@ -694,6 +721,7 @@ mod tests {
                     feature_requirements,
                     enforce_source.clone(),
                 )),
+                feature_defaults: Some(Sourced::new(feature_defaults, enforce_source.clone())),
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: Some(Sourced::new(enforce_residency, enforce_source)),
@@ -727,6 +755,7 @@ mod tests {
                 allowed_sandbox_modes: None,
                 allowed_web_search_modes: None,
                 feature_requirements: None,
+                feature_defaults: None,
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: None,
@@ -768,6 +797,7 @@ mod tests {
                 allowed_sandbox_modes: None,
                 allowed_web_search_modes: None,
                 feature_requirements: None,
+                feature_defaults: None,
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: None,
@@ -1114,6 +1144,31 @@ mod tests {
                 RequirementSource::Unknown,
             ))
         );
+        assert_eq!(requirements.feature_defaults, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn deserialize_feature_defaults() -> Result<()> {
+        let toml_str = r#"
+            [feature_defaults]
+            apps = true
+        "#;
+        let config: ConfigRequirementsToml = from_str(toml_str)?;
+        assert!(!config.is_empty());
+        let requirements: ConfigRequirements = with_unknown_source(config).try_into()?;
+
+        assert_eq!(requirements.feature_requirements, None);
+        assert_eq!(
+            requirements.feature_defaults,
+            Some(Sourced::new(
+                FeatureRequirementsToml {
+                    entries: BTreeMap::from([("apps".to_string(), true)]),
+                },
+                RequirementSource::Unknown,
+            ))
+        );
 
         Ok(())
     }
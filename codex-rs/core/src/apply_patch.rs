@@ -1,3 +1,6 @@
+use crate::approval_rules::ApprovalSubject;
+use crate::approval_rules::apply_approval_rule;
+use crate::approval_rules::evaluate_approval_rules;
 use crate::codex::TurnContext;
 use crate::function_tool::FunctionCallError;
 use crate::protocol::FileChange;
@@ -36,7 +39,11 @@ pub(crate) async fn apply_patch(
     turn_context: &TurnContext,
     action: ApplyPatchAction,
 ) -> InternalApplyPatchInvocation {
-    match assess_patch_safety(
+    let approval_rule = evaluate_approval_rules(
+        &turn_context.config.approval_rules,
+        &ApprovalSubject::for_patch(&action, &turn_context.cwd),
+    );
+    let invocation = match assess_patch_safety(
         &action,
         turn_context.approval_policy.value(),
         turn_context.sandbox_policy.get(),
@@ -71,6 +78,30 @@ pub(crate) async fn apply_patch(
         SafetyCheck::Reject { reason } => InternalApplyPatchInvocation::Output(Err(
             FunctionCallError::RespondToModel(format!("patch rejected: {reason}")),
         )),
+    };
+
+    let InternalApplyPatchInvocation::DelegateToExec(exec) = invocation else {
+        return invocation;
+    };
+    if approval_rule.is_none() {
+        return InternalApplyPatchInvocation::DelegateToExec(exec);
+    }
+    match apply_approval_rule(
+        exec.exec_approval_requirement,
+        approval_rule,
+        turn_context.approval_policy.value(),
+    ) {
+        ExecApprovalRequirement::Forbidden { reason } => InternalApplyPatchInvocation::Output(Err(
+            FunctionCallError::RespondToModel(format!("patch rejected: {reason}")),
+        )),
+        exec_approval_requirement => InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
+            action: exec.action,
+            auto_approved: matches!(
+                exec_approval_requirement,
+                ExecApprovalRequirement::Skip { .. }
+            ),
+            exec_approval_requirement,
+        }),
     }
 }
 
@@ -0,0 +1,384 @@
+//! Declarative approval rules from `[[approval_rules]]`.
+//!
+//! Rules are checked in order before the approval policy. The first rule that
+//! matches a tool call approves it, denies it, or asks the user; calls that
+//! match no rule are left to the approval policy and exec policy as usual.
+
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_apply_patch::ApplyPatchAction;
+use codex_apply_patch::ApplyPatchFileChange;
+use codex_protocol::protocol::AskForApproval;
+use wildmatch::WildMatchPattern;
+
+use crate::config::types::ApprovalRule;
+use crate::config::types::ApprovalRuleDecision;
+use crate::exec_policy::commands_for_exec_policy;
+use crate::tools::sandboxing::ExecApprovalRequirement;
+
+/// A tool call, as far as approval rules are concerned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApprovalSubject<'a> {
+    /// The argv of a shell command.
+    Command(&'a [String]),
+    /// The files an `apply_patch` call writes.
+    Patch(Vec<PatchedFile>),
+    /// A call to `tool` on the MCP server `server`.
+    McpTool { server: &'a str, tool: &'a str },
+}
+
+/// A file written by a patch and the size of its new contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchedFile {
+    /// Relative to the working directory when the file is under it.
+    pub path: PathBuf,
+    pub bytes: u64,
+}
+
+impl PatchedFile {
+    pub fn new(path: &Path, cwd: &Path, bytes: u64) -> Self {
+        Self {
+            path: path.strip_prefix(cwd).unwrap_or(path).to_path_buf(),
+            bytes,
+        }
+    }
+}
+
+impl ApprovalSubject<'_> {
+    pub fn for_patch(action: &ApplyPatchAction, cwd: &Path) -> Self {
+        let mut files = Vec::new();
+        for (path, change) in action.changes() {
+            match change {
+                ApplyPatchFileChange::Add { content } => {
+                    files.push(PatchedFile::new(path, cwd, content.len() as u64));
+                }
+                ApplyPatchFileChange::Delete { .. } => {
+                    files.push(PatchedFile::new(path, cwd, 0));
+                }
+                ApplyPatchFileChange::Update {
+                    move_path,
+                    new_content,
+                    ..
+                } => {
+                    let bytes = new_content.len() as u64;
+                    files.push(PatchedFile::new(path, cwd, bytes));
+                    if let Some(move_path) = move_path {
+                        files.push(PatchedFile::new(move_path, cwd, bytes));
+                    }
+                }
+            }
+        }
+        ApprovalSubject::Patch(files)
+    }
+}
+
+/// The rule that decided a tool call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApprovalRuleMatch {
+    /// Position of the rule in `approval_rules`, starting at 0.
+    pub rule_index: usize,
+    pub decision: ApprovalRuleDecision,
+}
+
+impl fmt::Display for ApprovalRuleMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "approval rule {}", self.rule_index + 1)
+    }
+}
+
+/// Returns the first of `rules` that matches `subject`.
+pub fn evaluate_approval_rules(
+    rules: &[ApprovalRule],
+    subject: &ApprovalSubject<'_>,
+) -> Option<ApprovalRuleMatch> {
+    rules
+        .iter()
+        .position(|rule| rule_matches(rule, subject))
+        .map(|rule_index| ApprovalRuleMatch {
+            rule_index,
+            decision: rules[rule_index].decision,
+        })
+}
+
+fn rule_matches(rule: &ApprovalRule, subject: &ApprovalSubject<'_>) -> bool {
+    let match_any = rule.decision == ApprovalRuleDecision::Deny;
+    let has_command_matcher = rule.command_prefix.is_some();
+    let has_patch_matcher = rule.paths.is_some() || rule.max_file_bytes.is_some();
+    let has_mcp_matcher = rule.mcp_server.is_some() || rule.mcp_tool.is_some();
+    match subject {
+        ApprovalSubject::Command(command) => {
+            if has_patch_matcher || has_mcp_matcher {
+                return false;
+            }
+            let Some(prefix) = &rule.command_prefix else {
+                return true;
+            };
+            let (commands, _) = commands_for_exec_policy(command);
+            let starts_with_prefix = |command: &Vec<String>| command.starts_with(prefix);
+            if match_any {
+                commands.iter().any(starts_with_prefix)
+            } else {
+                commands.iter().all(starts_with_prefix)
+            }
+        }
+        ApprovalSubject::Patch(files) => {
+            if has_command_matcher || has_mcp_matcher {
+                return false;
+            }
+            if let Some(max_file_bytes) = rule.max_file_bytes
+                && files.iter().any(|file| file.bytes > max_file_bytes)
+            {
+                return false;
+            }
+            let Some(paths) = &rule.paths else {
+                return true;
+            };
+            let patterns = paths
+                .iter()
+                .map(|path| WildMatchPattern::<'*', '?'>::new(path))
+                .collect::<Vec<_>>();
+            let matches_glob = |file: &PatchedFile| {
+                let path = file.path.to_string_lossy();
+                patterns.iter().any(|pattern| pattern.matches(&path))
+            };
+            if match_any {
+                files.iter().any(matches_glob)
+            } else {
+                files.iter().all(matches_glob)
+            }
+        }
+        ApprovalSubject::McpTool { server, tool } => {
+            !has_command_matcher
+                && !has_patch_matcher
+                && rule
+                    .mcp_server
+                    .as_deref()
+                    .is_none_or(|name| name == *server)
+                && rule.mcp_tool.as_deref().is_none_or(|name| name == *tool)
+        }
+    }
+}
+
+/// Applies the decision of a matching rule to the approval requirement the
+/// approval policy produced. Calls the exec policy forbids stay forbidden.
+pub(crate) fn apply_approval_rule(
+    requirement: ExecApprovalRequirement,
+    rule: Option<ApprovalRuleMatch>,
+    approval_policy: AskForApproval,
+) -> ExecApprovalRequirement {
+    let Some(rule) = rule else {
+        return requirement;
+    };
+    match (rule.decision, requirement) {
+        (_, requirement @ ExecApprovalRequirement::Forbidden { .. }) => requirement,
+        (ApprovalRuleDecision::Approve, requirement @ ExecApprovalRequirement::Skip { .. }) => {
+            requirement
+        }
+        (ApprovalRuleDecision::Approve, _) => ExecApprovalRequirement::Skip {
+            bypass_sandbox: false,
+            proposed_execpolicy_amendment: None,
+        },
+        (ApprovalRuleDecision::Deny, _) => ExecApprovalRequirement::Forbidden {
+            reason: format!("denied by {rule}"),
+        },
+        (ApprovalRuleDecision::Ask, _) if approval_policy == AskForApproval::Never => {
+            ExecApprovalRequirement::Forbidden {
+                reason: format!("{rule} requires approval, but the approval policy is never"),
+            }
+        }
+        (ApprovalRuleDecision::Ask, _) => ExecApprovalRequirement::NeedsApproval {
+            reason: Some(format!("{rule} requires approval")),
+            proposed_execpolicy_amendment: None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn rule(decision: ApprovalRuleDecision) -> ApprovalRule {
+        ApprovalRule {
+            decision,
+            command_prefix: None,
+            paths: None,
+            max_file_bytes: None,
+            mcp_server: None,
+            mcp_tool: None,
+        }
+    }
+
+    fn words(command: &[&str]) -> Vec<String> {
+        command.iter().map(ToString::to_string).collect()
+    }
+
+    fn matched(rules: &[ApprovalRule], subject: &ApprovalSubject<'_>) -> Option<usize> {
+        evaluate_approval_rules(rules, subject).map(|rule| rule.rule_index)
+    }
+
+    #[test]
+    fn command_prefix_rules_check_every_command_in_a_script() {
+        let rules = vec![
+            ApprovalRule {
+                command_prefix: Some(words(&["rm"])),
+                ..rule(ApprovalRuleDecision::Deny)
+            },
+            ApprovalRule {
+                command_prefix: Some(words(&["cargo", "test"])),
+                ..rule(ApprovalRuleDecision::Approve)
+            },
+        ];
+
+        let cargo_test = words(&["cargo", "test", "-p", "codex-core"]);
+        assert_eq!(
+            matched(&rules, &ApprovalSubject::Command(&cargo_test)),
+            Some(1)
+        );
+        let chained = words(&["bash", "-lc", "cargo test && cargo test -p codex-cli"]);
+        assert_eq!(
+            matched(&rules, &ApprovalSubject::Command(&chained)),
+            Some(1)
+        );
+        let mixed = words(&["bash", "-lc", "cargo test && rm -rf target"]);
+        assert_eq!(matched(&rules, &ApprovalSubject::Command(&mixed)), Some(0));
+        let other = words(&["cargo", "build"]);
+        assert_eq!(matched(&rules, &ApprovalSubject::Command(&other)), None);
+    }
+
+    #[test]
+    fn patch_rules_match_paths_and_sizes() {
+        let rules = vec![
+            ApprovalRule {
+                paths: Some(vec!["secrets/*".to_string()]),
+                ..rule(ApprovalRuleDecision::Deny)
+            },
+            ApprovalRule {
+                paths: Some(vec!["src/*".to_string(), "docs/*.md".to_string()]),
+                max_file_bytes: Some(1_000),
+                ..rule(ApprovalRuleDecision::Approve)
+            },
+        ];
+        let cwd = Path::new("/repo");
+        let patch = |files: &[(&str, u64)]| {
+            ApprovalSubject::Patch(
+                files
+                    .iter()
+                    .map(|(path, bytes)| PatchedFile::new(Path::new(path), cwd, *bytes))
+                    .collect(),
+            )
+        };
+
+        assert_eq!(
+            matched(
+                &rules,
+                &patch(&[("/repo/src/a/b.rs", 10), ("/repo/docs/x.md", 5)])
+            ),
+            Some(1)
+        );
+        assert_eq!(
+            matched(&rules, &patch(&[("/repo/src/big.rs", 1_001)])),
+            None
+        );
+        assert_eq!(
+            matched(
+                &rules,
+                &patch(&[("/repo/src/a.rs", 1), ("/repo/build.rs", 1)])
+            ),
+            None
+        );
+        assert_eq!(
+            matched(
+                &rules,
+                &patch(&[("/repo/src/a.rs", 1), ("/repo/secrets/key", 1)])
+            ),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn mcp_rules_match_server_and_tool_only() {
+        let rules = vec![
+            ApprovalRule {
+                mcp_server: Some("docs".to_string()),
+                ..rule(ApprovalRuleDecision::Approve)
+            },
+            ApprovalRule {
+                mcp_tool: Some("delete_issue".to_string()),
+                ..rule(ApprovalRuleDecision::Deny)
+            },
+            rule(ApprovalRuleDecision::Ask),
+        ];
+
+        let call = |server, tool| ApprovalSubject::McpTool { server, tool };
+        assert_eq!(matched(&rules, &call("docs", "search")), Some(0));
+        assert_eq!(matched(&rules, &call("github", "delete_issue")), Some(1));
+        assert_eq!(matched(&rules, &call("github", "list_issues")), Some(2));
+        let ls = words(&["ls"]);
+        assert_eq!(matched(&rules, &ApprovalSubject::Command(&ls)), Some(2));
+    }
+
+    #[test]
+    fn rule_decisions_override_approval_requirements_except_forbidden() {
+        let needs_approval = || ExecApprovalRequirement::NeedsApproval {
+            reason: None,
+            proposed_execpolicy_amendment: None,
+        };
+        let rule = |decision| {
+            Some(ApprovalRuleMatch {
+                rule_index: 2,
+                decision,
+            })
+        };
+
+        assert_eq!(
+            apply_approval_rule(
+                needs_approval(),
+                rule(ApprovalRuleDecision::Approve),
+                AskForApproval::OnRequest
+            ),
+            ExecApprovalRequirement::Skip {
+                bypass_sandbox: false,
+                proposed_execpolicy_amendment: None,
+            }
+        );
+        assert_eq!(
+            apply_approval_rule(
+                needs_approval(),
+                rule(ApprovalRuleDecision::Deny),
+                AskForApproval::OnRequest
+            ),
+            ExecApprovalRequirement::Forbidden {
+                reason: "denied by approval rule 3".to_string(),
+            }
+        );
+        assert_eq!(
+            apply_approval_rule(
+                ExecApprovalRequirement::Skip {
+                    bypass_sandbox: false,
+                    proposed_execpolicy_amendment: None,
+                },
+                rule(ApprovalRuleDecision::Ask),
+                AskForApproval::Never
+            ),
+            ExecApprovalRequirement::Forbidden {
+                reason: "approval rule 3 requires approval, but the approval policy is never"
+                    .to_string(),
+            }
+        );
+        assert_eq!(
+            apply_approval_rule(
+                ExecApprovalRequirement::Forbidden {
+                    reason: "blocked".to_string(),
+                },
+                rule(ApprovalRuleDecision::Approve),
+                AskForApproval::OnRequest
+            ),
+            ExecApprovalRequirement::Forbidden {
+                reason: "blocked".to_string(),
+            }
+        );
+    }
+}
@@ -0,0 +1,655 @@
+//! Tamper-evident audit log of what the agent did, from `[audit]`.
+//!
+//! Every approval decision, sandbox attempt, executed command and applied
+//! patch, and every tool call dispatched by a router with an encryption key,
+//! is appended to a JSONL file, `$CODEX_HOME/audit.jsonl` by default,
+//! kept apart from the session rollouts. Each line is
+//! `{"entry": {...}, "hash": ..., "signature": ...}`: `hash` is the SHA-256 of
+//! the serialized entry, which carries the previous line's hash as
+//! `prev_hash`, and `signature` is an HMAC-SHA256 of `hash` under a key kept
+//! outside the log. Editing, reordering or removing a line breaks the chain
+//! from that line on, and forging one needs the key. Cutting lines off the
+//! end leaves a valid chain, so compare the entry count with an earlier
+//! verification to catch that.
+//!
+//! Sessions append under an advisory lock on the log, so any number of
+//! sessions and processes share one chain.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use chrono::DateTime;
+use chrono::Utc;
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::ExecCommandSource;
+use codex_protocol::protocol::ExecCommandStatus;
+use codex_protocol::protocol::FileChange;
+use codex_protocol::protocol::PatchApplyStatus;
+use ring::hmac;
+use ring::rand::SecureRandom;
+use ring::rand::SystemRandom;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use tracing::warn;
+
+use crate::config::Config;
+use crate::tools::audit::EncryptedEntry;
+
+pub const AUDIT_LOG_FILENAME: &str = "audit.jsonl";
+pub const AUDIT_KEY_FILENAME: &str = "audit.key";
+
+/// `prev_hash` of the first entry.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+const KEY_LEN: usize = 32;
+/// Bytes read at a time from the end of the log to find its last line.
+const TAIL_CHUNK_BYTES: u64 = 8 * 1024;
+
+/// The audit log file `config` writes to.
+pub fn audit_log_path(config: &Config) -> PathBuf {
+    config.audit.path.as_ref().map_or_else(
+        || config.codex_home.join(AUDIT_LOG_FILENAME),
+        |path| path.to_path_buf(),
+    )
+}
+
+/// The key `config` signs audit entries with.
+pub fn audit_key_path(config: &Config) -> PathBuf {
+    config.audit.key_file.as_ref().map_or_else(
+        || config.codex_home.join(AUDIT_KEY_FILENAME),
+        |path| path.to_path_buf(),
+    )
+}
+
+/// One line of the audit log, before it is hashed and signed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Position in the log, starting at 0.
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub thread_id: ThreadId,
+    pub turn_id: String,
+    /// `hash` of the line before, or all zeros for the first line.
+    pub prev_hash: String,
+    pub event: AuditEvent,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// How a tool call was cleared to run, or refused. `decision` is
+    /// `auto_approved` when no approval was needed, `forbidden` when policy
+    /// refused it, and otherwise the reviewer's decision.
+    Approval {
+        call_id: String,
+        tool: String,
+        decision: String,
+    },
+    /// One attempt at running a tool call under `sandbox` (`none` when
+    /// unsandboxed), and whether the sandbox blocked it.
+    SandboxAttempt {
+        call_id: String,
+        tool: String,
+        sandbox: String,
+        denied: bool,
+        exit_code: Option<i32>,
+    },
+    /// A command that ran to completion or failed. Declined commands only
+    /// show up as their approval.
+    Exec {
+        call_id: String,
+        command: Vec<String>,
+        cwd: PathBuf,
+        source: ExecCommandSource,
+        status: ExecCommandStatus,
+        exit_code: i32,
+        duration_ms: u64,
+    },
+    /// A patch that was applied, or failed after possibly writing some files.
+    Patch {
+        call_id: String,
+        status: PatchApplyStatus,
+        files: Vec<AuditFileChange>,
+    },
+    /// A tool call with its arguments and output sealed; see
+    /// [`crate::tools::audit::decrypt_entry`].
+    ToolCall(EncryptedEntry),
+}
+
+/// A file touched by a patch. Hashes are the SHA-256 of the file's contents,
+/// or `None` where the file did not exist.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditFileChange {
+    pub path: PathBuf,
+    pub before_sha256: Option<String>,
+    pub after_sha256: Option<String>,
+}
+
+/// A line as written to the log.
+#[derive(Serialize, Deserialize)]
+struct AuditLine {
+    entry: serde_json::Value,
+    hash: String,
+    signature: String,
+}
+
+/// Appends a session's audit events to the log.
+pub(crate) struct AuditLog {
+    path: PathBuf,
+    key: hmac::Key,
+    thread_id: ThreadId,
+    /// Hashes of the files each in-flight patch touches, taken before it
+    /// applies, keyed by call id.
+    pending_patches: Mutex<HashMap<String, Vec<(PathBuf, Option<String>)>>>,
+}
+
+impl AuditLog {
+    /// The session's log when `[audit]` is enabled, creating the signing key
+    /// on first use.
+    pub(crate) fn from_config(
+        config: &Config,
+        thread_id: ThreadId,
+    ) -> std::io::Result<Option<Self>> {
+        if !config.audit.enabled.unwrap_or(false) {
+            return Ok(None);
+        }
+        Self::open(audit_log_path(config), &audit_key_path(config), thread_id).map(Some)
+    }
+
+    /// The log at `path`, signed with the key at `key_path`, which is created
+    /// if missing.
+    pub(crate) fn open(
+        path: PathBuf,
+        key_path: &Path,
+        thread_id: ThreadId,
+    ) -> std::io::Result<Self> {
+        Ok(Self {
+            path,
+            key: load_or_create_key(key_path)?,
+            thread_id,
+            pending_patches: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub(crate) async fn record(&self, turn_id: &str, event: AuditEvent) {
+        let path = self.path.clone();
+        let key = self.key.clone();
+        let thread_id = self.thread_id;
+        let turn_id = turn_id.to_string();
+        let result = tokio::task::spawn_blocking(move || {
+            append_entry(&path, &key, thread_id, turn_id, event)
+        })
+        .await;
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => warn!("failed to append to {}: {err}", self.path.display()),
+            Err(err) => warn!("audit log writer panicked: {err}"),
+        }
+    }
+
+    /// Records the commands and patches in a session event. Patches are
+    /// recorded when they end, with the hashes taken when they began.
+    pub(crate) async fn record_event(&self, event: &Event) {
+        match &event.msg {
+            EventMsg::ExecCommandEnd(end) if end.status != ExecCommandStatus::Declined => {
+                let audit_event = AuditEvent::Exec {
+                    call_id: end.call_id.clone(),
+                    command: end.command.clone(),
+                    cwd: end.cwd.clone(),
+                    source: end.source,
+                    status: end.status.clone(),
+                    exit_code: end.exit_code,
+                    duration_ms: u64::try_from(end.duration.as_millis()).unwrap_or(u64::MAX),
+                };
+                self.record(&event.id, audit_event).await;
+            }
+            EventMsg::PatchApplyBegin(begin) => {
+                let paths = begin.changes.keys().cloned().collect();
+                let before = hash_files_blocking(paths).await;
+                self.pending_patches
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .insert(begin.call_id.clone(), before);
+            }
+            EventMsg::PatchApplyEnd(end) => {
+                let before = self
+                    .pending_patches
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .remove(&end.call_id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect::<HashMap<_, _>>();
+                if end.status == PatchApplyStatus::Declined {
+                    return;
+                }
+                let mut destinations = end
+                    .changes
+                    .iter()
+                    .map(|(path, change)| match change {
+                        FileChange::Update {
+                            move_path: Some(move_path),
+                            ..
+                        } => (path.clone(), move_path.clone()),
+                        FileChange::Add { .. }
+                        | FileChange::Delete { .. }
+                        | FileChange::Update { .. } => (path.clone(), path.clone()),
+                    })
+                    .collect::<Vec<_>>();
+                destinations.sort();
+                let after = hash_files_blocking(
+                    destinations
+                        .iter()
+                        .map(|(_, destination)| destination.clone())
+                        .collect(),
+                )
+                .await;
+                let files = destinations
+                    .into_iter()
+                    .zip(after)
+                    .map(|((path, _), (_, after_sha256))| AuditFileChange {
+                        before_sha256: before.get(&path).cloned().flatten(),
+                        after_sha256,
+                        path,
+                    })
+                    .collect();
+                let audit_event = AuditEvent::Patch {
+                    call_id: end.call_id.clone(),
+                    status: end.status.clone(),
+                    files,
+                };
+                self.record(&event.id, audit_event).await;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Outcome of [`verify_audit_log`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditVerification {
+    /// Every line is signed and chained to the one before.
+    Intact { entries: u64 },
+    /// `line` (1-based) is the first line that does not check out; the lines
+    /// before it do.
+    Broken { line: u64, reason: String },
+}
+
+/// Checks the hash chain and signatures of the log at `log_path` with the key
+/// at `key_path`. A missing log is intact with no entries.
+pub fn verify_audit_log(log_path: &Path, key_path: &Path) -> std::io::Result<AuditVerification> {
+    let key = read_key(key_path)?;
+    let contents = match std::fs::read_to_string(log_path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    Ok(verify_lines(&contents, &key))
+}
+
+/// The entries of the log at `log_path`, oldest first, without checking
+/// their chain; see [`verify_audit_log`]. A missing log has no entries.
+pub fn read_audit_log(log_path: &Path) -> std::io::Result<Vec<AuditEntry>> {
+    let contents = match std::fs::read_to_string(log_path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    contents
+        .lines()
+        .map(|line| {
+            let line = serde_json::from_str::<AuditLine>(line)?;
+            serde_json::from_value::<AuditEntry>(line.entry)
+        })
+        .collect::<Result<_, _>>()
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+}
+
+fn verify_lines(contents: &str, key: &hmac::Key) -> AuditVerification {
+    let mut prev_hash = GENESIS_HASH.to_string();
+    let mut entries = 0;
+    for (index, raw_line) in contents.lines().enumerate() {
+        let line = index as u64 + 1;
+        let broken = |reason: String| AuditVerification::Broken { line, reason };
+        let parsed = match serde_json::from_str::<AuditLine>(raw_line) {
+            Ok(parsed) => parsed,
+            Err(err) => return broken(format!("not an audit entry: {err}")),
+        };
+        if entry_hash(&parsed.entry) != parsed.hash {
+            return broken("entry does not match its hash".to_string());
+        }
+        if sign(key, &parsed.hash) != parsed.signature {
+            return broken("signature does not match the key".to_string());
+        }
+        if parsed
+            .entry
+            .get("prev_hash")
+            .and_then(serde_json::Value::as_str)
+            != Some(prev_hash.as_str())
+        {
+            return broken("does not follow the entry before it".to_string());
+        }
+        if parsed.entry.get("seq").and_then(serde_json::Value::as_u64) != Some(entries) {
+            return broken(format!("expected entry number {entries}"));
+        }
+        prev_hash = parsed.hash;
+        entries += 1;
+    }
+    AuditVerification::Intact { entries }
+}
+
+fn append_entry(
+    path: &Path,
+    key: &hmac::Key,
+    thread_id: ThreadId,
+    turn_id: String,
+    event: AuditEvent,
+) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = OpenOptions::new();
+    options.read(true).append(true).create(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(path)?;
+    file.lock()?;
+
+    let (seq, prev_hash) = match read_last_line(&mut file)? {
+        Some(last) => {
+            let last = serde_json::from_str::<AuditLine>(&last).map_err(|err| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("last line is not an audit entry: {err}"),
+                )
+            })?;
+            let seq = last
+                .entry
+                .get("seq")
+                .and_then(serde_json::Value::as_u64)
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "last audit entry has no sequence number",
+                    )
+                })?;
+            (seq + 1, last.hash)
+        }
+        None => (0, GENESIS_HASH.to_string()),
+    };
+    let entry = serde_json::to_value(AuditEntry {
+        seq,
+        timestamp: Utc::now(),
+        thread_id,
+        turn_id,
+        prev_hash,
+        event,
+    })
+    .map_err(std::io::Error::other)?;
+    let hash = entry_hash(&entry);
+    let signature = sign(key, &hash);
+    let mut line = serde_json::to_string(&AuditLine {
+        entry,
+        hash,
+        signature,
+    })
+    .map_err(std::io::Error::other)?;
+    line.push('\n');
+    file.write_all(line.as_bytes())?;
+    file.sync_data()
+}
+
+/// The last non-empty line of `file`, read backwards from its end.
+fn read_last_line(file: &mut File) -> std::io::Result<Option<String>> {
+    let mut start = file.metadata()?.len();
+    let mut tail = Vec::new();
+    while start > 0 {
+        let chunk_len = TAIL_CHUNK_BYTES.min(start);
+        start -= chunk_len;
+        file.seek(SeekFrom::Start(start))?;
+        let mut chunk = vec![0; chunk_len as usize];
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+        let body = tail.strip_suffix(b"\n").unwrap_or(&tail);
+        if let Some(newline) = body.iter().rposition(|byte| *byte == b'\n') {
+            return Ok(Some(
+                String::from_utf8_lossy(&body[newline + 1..]).into_owned(),
+            ));
+        }
+    }
+    let body = tail.strip_suffix(b"\n").unwrap_or(&tail);
+    Ok((!body.is_empty()).then(|| String::from_utf8_lossy(body).into_owned()))
+}
+
+fn entry_hash(entry: &serde_json::Value) -> String {
+    format!("{:x}", Sha256::digest(entry.to_string().as_bytes()))
+}
+
+fn sign(key: &hmac::Key, hash: &str) -> String {
+    let tag = hmac::sign(key, hash.as_bytes());
+    let mut hex = String::with_capacity(tag.as_ref().len() * 2);
+    for byte in tag.as_ref() {
+        let _ = write!(hex, "{byte:02x}");
+    }
+    hex
+}
+
+async fn hash_files_blocking(paths: Vec<PathBuf>) -> Vec<(PathBuf, Option<String>)> {
+    let fallback = paths
+        .iter()
+        .map(|path| (path.clone(), None))
+        .collect::<Vec<_>>();
+    tokio::task::spawn_blocking(move || {
+        paths
+            .into_iter()
+            .map(|path| {
+                let hash = std::fs::read(&path)
+                    .ok()
+                    .map(|contents| format!("{:x}", Sha256::digest(contents)));
+                (path, hash)
+            })
+            .collect()
+    })
+    .await
+    .unwrap_or(fallback)
+}
+
+fn load_or_create_key(path: &Path) -> std::io::Result<hmac::Key> {
+    match read_key(path) {
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        result => return result,
+    }
+    let mut secret = [0u8; KEY_LEN];
+    SystemRandom::new()
+        .fill(&mut secret)
+        .map_err(|_| std::io::Error::other("failed to generate an audit key"))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    match options.open(path) {
+        Ok(mut file) => {
+            file.write_all(BASE64_STANDARD.encode(secret).as_bytes())?;
+            file.sync_all()?;
+            Ok(hmac::Key::new(hmac::HMAC_SHA256, &secret))
+        }
+        // Another session created it first.
+        Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => read_key(path),
+        Err(err) => Err(err),
+    }
+}
+
+fn read_key(path: &Path) -> std::io::Result<hmac::Key> {
+    let encoded = std::fs::read_to_string(path)?;
+    let secret = BASE64_STANDARD.decode(encoded.trim()).map_err(|err| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("{} is not a base64 audit key: {err}", path.display()),
+        )
+    })?;
+    Ok(hmac::Key::new(hmac::HMAC_SHA256, &secret))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::protocol::PatchApplyBeginEvent;
+    use codex_protocol::protocol::PatchApplyEndEvent;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn test_key() -> hmac::Key {
+        hmac::Key::new(hmac::HMAC_SHA256, &[7; KEY_LEN])
+    }
+
+    fn approval(call_id: &str) -> AuditEvent {
+        AuditEvent::Approval {
+            call_id: call_id.to_string(),
+            tool: "shell".to_string(),
+            decision: "approved".to_string(),
+        }
+    }
+
+    fn write_entries(path: &Path, count: usize) {
+        for index in 0..count {
+            append_entry(
+                path,
+                &test_key(),
+                ThreadId::new(),
+                "turn-1".to_string(),
+                approval(&format!("call-{index}")),
+            )
+            .expect("append entry");
+        }
+    }
+
+    #[test]
+    fn appended_entries_form_a_verifiable_chain() {
+        let dir = TempDir::new().expect("tempdir");
+        let path = dir.path().join(AUDIT_LOG_FILENAME);
+        write_entries(&path, 3);
+
+        let contents = std::fs::read_to_string(&path).expect("read log");
+        assert_eq!(
+            verify_lines(&contents, &test_key()),
+            AuditVerification::Intact { entries: 3 }
+        );
+        let wrong_key = hmac::Key::new(hmac::HMAC_SHA256, &[8; KEY_LEN]);
+        assert_eq!(
+            verify_lines(&contents, &wrong_key),
+            AuditVerification::Broken {
+                line: 1,
+                reason: "signature does not match the key".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn edited_or_removed_entries_break_the_chain() {
+        let dir = TempDir::new().expect("tempdir");
+        let path = dir.path().join(AUDIT_LOG_FILENAME);
+        write_entries(&path, 3);
+        let contents = std::fs::read_to_string(&path).expect("read log");
+
+        let edited = contents.replacen("call-1", "call-9", 1);
+        assert_eq!(
+            verify_lines(&edited, &test_key()),
+            AuditVerification::Broken {
+                line: 2,
+                reason: "entry does not match its hash".to_string(),
+            }
+        );
+
+        let lines = contents.lines().collect::<Vec<_>>();
+        let removed = format!("{}\n{}\n", lines[0], lines[2]);
+        assert_eq!(
+            verify_lines(&removed, &test_key()),
+            AuditVerification::Broken {
+                line: 2,
+                reason: "does not follow the entry before it".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn patches_are_recorded_with_file_hashes_before_and_after() {
+        let dir = TempDir::new().expect("tempdir");
+        let file = dir.path().join("notes.txt");
+        std::fs::write(&file, "before").expect("write file");
+        let log = AuditLog {
+            path: dir.path().join(AUDIT_LOG_FILENAME),
+            key: test_key(),
+            thread_id: ThreadId::new(),
+            pending_patches: Mutex::new(HashMap::new()),
+        };
+        let changes = HashMap::from([(
+            file.clone(),
+            FileChange::Update {
+                unified_diff: String::new(),
+                move_path: None,
+            },
+        )]);
+
+        log.record_event(&Event {
+            id: "turn-1".to_string(),
+            msg: EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
+                call_id: "call-1".to_string(),
+                turn_id: "turn-1".to_string(),
+                auto_approved: true,
+                changes: changes.clone(),
+            }),
+        })
+        .await;
+        std::fs::write(&file, "after").expect("write file");
+        log.record_event(&Event {
+            id: "turn-1".to_string(),
+            msg: EventMsg::PatchApplyEnd(PatchApplyEndEvent {
+                call_id: "call-1".to_string(),
+                turn_id: "turn-1".to_string(),
+                stdout: String::new(),
+                stderr: String::new(),
+                success: true,
+                changes,
+                status: PatchApplyStatus::Completed,
+            }),
+        })
+        .await;
+
+        let contents = std::fs::read_to_string(&log.path).expect("read log");
+        let line = serde_json::from_str::<AuditLine>(contents.trim()).expect("audit line");
+        let entry = serde_json::from_value::<AuditEntry>(line.entry).expect("audit entry");
+        assert_eq!(
+            entry.event,
+            AuditEvent::Patch {
+                call_id: "call-1".to_string(),
+                status: PatchApplyStatus::Completed,
+                files: vec![AuditFileChange {
+                    path: file,
+                    before_sha256: Some(format!("{:x}", Sha256::digest(b"before"))),
+                    after_sha256: Some(format!("{:x}", Sha256::digest(b"after"))),
+                }],
+            }
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! Session checkpoints that outlive the process.
+//!
+//! A checkpoint captures the conversation history of a [`Session`] so a later
+//! process can restore it with [`CodexThread::load_checkpoint`]. Stores only
+//! deal in opaque bytes; the encoding lives here.
+//!
+//! [`Session`]: crate::codex::Session
+//! [`CodexThread::load_checkpoint`]: crate::CodexThread::load_checkpoint
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use codex_protocol::ThreadId;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::TokenUsageInfo;
+use codex_protocol::protocol::TurnContextItem;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::error::CodexErr;
+use crate::error::Result as CodexResult;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CheckpointId(String);
+
+/// Where [`Session::checkpoint`] keeps checkpoints, under `CODEX_HOME`.
+///
+/// [`Session::checkpoint`]: crate::codex::Session::checkpoint
+pub(crate) const CHECKPOINTS_SUBDIR: &str = "checkpoints";
+
+impl CheckpointId {
+    pub(crate) fn new() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+
+    /// Parses an id received from a client. Ids are always UUIDs, so
+    /// anything else (such as a path) is rejected.
+    pub fn parse(id: &str) -> Option<Self> {
+        Uuid::parse_str(id).ok().map(|uuid| Self(uuid.to_string()))
+    }
+}
+
+impl fmt::Display for CheckpointId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Byte-oriented storage for checkpoints.
+#[async_trait]
+pub trait CheckpointStore: Send + Sync {
+    async fn put(&self, id: &CheckpointId, bytes: Vec<u8>) -> io::Result<()>;
+
+    /// Returns `None` when no checkpoint exists under `id`.
+    async fn get(&self, id: &CheckpointId) -> io::Result<Option<Vec<u8>>>;
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryCheckpointStore {
+    checkpoints: Mutex<HashMap<CheckpointId, Vec<u8>>>,
+}
+
+#[async_trait]
+impl CheckpointStore for InMemoryCheckpointStore {
+    async fn put(&self, id: &CheckpointId, bytes: Vec<u8>) -> io::Result<()> {
+        self.checkpoints.lock().await.insert(id.clone(), bytes);
+        Ok(())
+    }
+
+    async fn get(&self, id: &CheckpointId) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.checkpoints.lock().await.get(id).cloned())
+    }
+}
+
+/// Stores each checkpoint as `<dir>/<id>.json`.
+#[derive(Debug)]
+pub struct FileCheckpointStore {
+    dir: PathBuf,
+}
+
+impl FileCheckpointStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path_for(&self, id: &CheckpointId) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+}
+
+#[async_trait]
+impl CheckpointStore for FileCheckpointStore {
+    async fn put(&self, id: &CheckpointId, bytes: Vec<u8>) -> io::Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(self.path_for(id), bytes).await
+    }
+
+    async fn get(&self, id: &CheckpointId) -> io::Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path_for(id)).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// What a checkpoint records about a session.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct SessionCheckpoint {
+    pub(crate) conversation_id: ThreadId,
+    pub(crate) history: Vec<ResponseItem>,
+    pub(crate) reference_context_item: Option<TurnContextItem>,
+    /// Absent in checkpoints saved before token usage was recorded.
+    #[serde(default)]
+    pub(crate) token_info: Option<TokenUsageInfo>,
+}
+
+impl SessionCheckpoint {
+    pub(crate) async fn load(store: &dyn CheckpointStore, id: &CheckpointId) -> CodexResult<Self> {
+        let Some(bytes) = store.get(id).await? else {
+            return Err(CodexErr::InvalidRequest(format!(
+                "checkpoint {id} not found"
+            )));
+        };
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn file_store_round_trips_bytes_and_reports_missing_ids() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let store = FileCheckpointStore::new(dir.path().join("checkpoints"));
+        let id = CheckpointId::new();
+
+        store
+            .put(&id, b"checkpoint".to_vec())
+            .await
+            .expect("put checkpoint");
+
+        assert_eq!(
+            store.get(&id).await.expect("get checkpoint"),
+            Some(b"checkpoint".to_vec())
+        );
+        assert_eq!(
+            store
+                .get(&CheckpointId::new())
+                .await
+                .expect("get missing checkpoint"),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_only_accepts_uuids() {
+        let id = CheckpointId::new();
+        assert_eq!(CheckpointId::parse(&id.to_string()), Some(id));
+        assert_eq!(CheckpointId::parse("../../config"), None);
+    }
+}
@@ -34,8 +34,22 @@ use crate::api_bridge::CoreAuthProvider;
 use crate::api_bridge::auth_provider_from_auth;
 use crate::api_bridge::map_api_error;
 use crate::auth::UnauthorizedRecovery;
+#[cfg(test)]
+use crate::session_recording::Playback;
+#[cfg(test)]
+use crate::session_recording::Recording;
+#[cfg(test)]
+use crate::session_recording::RecordingHandle;
+#[cfg(test)]
+use crate::session_recording::SessionCapture;
+use crate::tools::resilience::Admission;
+use crate::tools::resilience::CircuitBreaker;
+use crate::tools::resilience::CircuitBreakerConfig;
+use crate::tools::resilience::CircuitState;
 use codex_api::CompactClient as ApiCompactClient;
 use codex_api::CompactionInput as ApiCompactionInput;
+use codex_api::EmbeddingsClient as ApiEmbeddingsClient;
+use codex_api::EmbeddingsInput as ApiEmbeddingsInput;
 use codex_api::MemoriesClient as ApiMemoriesClient;
 use codex_api::MemorySummarizeInput as ApiMemorySummarizeInput;
 use codex_api::MemorySummarizeOutput as ApiMemorySummarizeOutput;
@@ -58,6 +72,7 @@ use codex_api::create_text_param_for_request;
 use codex_api::error::ApiError;
 use codex_api::requests::responses::Compression;
 use codex_otel::SessionTelemetry;
+use codex_otel::current_span_w3c_trace_context_within;
 
 use codex_protocol::ThreadId;
 use codex_protocol::config_types::ReasoningSummary as ReasoningSummaryConfig;
@@ -67,6 +82,7 @@ use codex_protocol::models::ResponseItem;
 use codex_protocol::openai_models::ModelInfo;
 use codex_protocol::openai_models::ReasoningEffort as ReasoningEffortConfig;
 use codex_protocol::protocol::SessionSource;
+use codex_protocol::protocol::W3cTraceContext;
 use eventsource_stream::Event;
 use eventsource_stream::EventStreamError;
 use futures::StreamExt;
@@ -78,6 +94,7 @@ use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 use tokio::sync::oneshot::error::TryRecvError;
+use tokio::time::Instant;
 use tokio_tungstenite::tungstenite::Error;
 use tokio_tungstenite::tungstenite::Message;
 use tracing::trace;
@@ -90,6 +107,7 @@ use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::client_common::ResponseStream;
 use crate::config::Config;
+use crate::config::types::PromptCacheRetention;
 use crate::default_client::build_reqwest_client;
 use crate::error::CodexErr;
 use crate::error::Result;
@@ -131,6 +149,20 @@ struct ModelClientState {
     beta_features_header: Option<String>,
     disable_websockets: AtomicBool,
     cached_websocket_session: StdMutex<WebsocketSession>,
+    #[cfg(test)]
+    session_capture: StdMutex<SessionCapture>,
+    /// Upstream trace that requests are propagated into; see
+    /// [`ModelClient::set_trace_context`].
+    trace_context: StdMutex<Option<W3cTraceContext>>,
+    /// Output cap sent with every Responses request; see
+    /// [`ModelClient::set_max_response_tokens`].
+    max_response_tokens: StdMutex<Option<u32>>,
+    /// Cache retention sent with every Responses request; see
+    /// [`ModelClient::set_prompt_cache_retention`].
+    prompt_cache_retention: StdMutex<Option<PromptCacheRetention>>,
+    /// Opens after repeated provider outages, when the provider configures a
+    /// circuit breaker; see [`ModelClientSession::provider_retry_after`].
+    circuit_breaker: Option<(CircuitBreakerConfig, CircuitBreaker)>,
 }
 
 /// Resolved API client setup for a single request attempt.
@@ -224,6 +256,18 @@ impl ModelClient {
         include_timing_metrics: bool,
         beta_features_header: Option<String>,
     ) -> Self {
+        let circuit_breaker =
+            provider
+                .circuit_breaker()
+                .map(|(failure_threshold, open_duration)| {
+                    (
+                        CircuitBreakerConfig {
+                            failure_threshold,
+                            open_duration,
+                        },
+                        CircuitBreaker::default(),
+                    )
+                });
         Self {
             state: Arc::new(ModelClientState {
                 auth_manager,
@@ -237,10 +281,48 @@ impl ModelClient {
                 beta_features_header,
                 disable_websockets: AtomicBool::new(false),
                 cached_websocket_session: StdMutex::new(WebsocketSession::default()),
+                #[cfg(test)]
+                session_capture: StdMutex::new(SessionCapture::default()),
+                trace_context: StdMutex::new(None),
+                max_response_tokens: StdMutex::new(None),
+                prompt_cache_retention: StdMutex::new(None),
+                circuit_breaker,
             }),
         }
     }
 
+    /// Creates a client for the same session that sends its requests to
+    /// `provider` instead, e.g. to fall back to it while this client's
+    /// provider is down.
+    pub(crate) fn with_provider(&self, provider: ModelProviderInfo) -> Self {
+        let state = &self.state;
+        let client = Self::new(
+            state.auth_manager.clone(),
+            state.conversation_id,
+            provider,
+            state.session_source.clone(),
+            state.model_verbosity,
+            state.responses_websockets_enabled_by_feature,
+            state.enable_request_compression,
+            state.include_timing_metrics,
+            state.beta_features_header.clone(),
+        );
+        client.set_trace_context(self.trace_context());
+        client.set_max_response_tokens(self.max_response_tokens());
+        client.set_prompt_cache_retention(self.prompt_cache_retention());
+        client
+    }
+
+    /// This client when it already sends to `provider`, otherwise a client
+    /// for `provider` made by [`ModelClient::with_provider`].
+    pub(crate) fn for_provider(&self, provider: &ModelProviderInfo) -> Self {
+        if self.state.provider == *provider {
+            self.clone()
+        } else {
+            self.with_provider(provider.clone())
+        }
+    }
+
     /// Creates a fresh turn-scoped streaming session.
     ///
     /// This constructor does not perform network I/O itself; the session opens a websocket lazily
@@ -253,6 +335,104 @@ impl ModelClient {
         }
     }
 
+    /// Starts capturing every streamed request and its response events.
+    /// Replaces any recording already in progress.
+    #[cfg(test)]
+    pub(crate) fn start_recording(&self) -> RecordingHandle {
+        let handle = RecordingHandle::default();
+        self.session_capture().recording = Some(handle.clone());
+        handle
+    }
+
+    /// Serves subsequent stream requests from `recording` instead of the provider.
+    #[cfg(test)]
+    pub(crate) fn start_playback(&self, recording: Recording) {
+        self.session_capture().playback = Some(Arc::new(Playback::new(recording)));
+    }
+
+    /// Sends `traceparent` and `tracestate` headers for `trace` with every
+    /// subsequent request. `None` stops propagation.
+    pub(crate) fn set_trace_context(&self, trace: Option<W3cTraceContext>) {
+        *self
+            .state
+            .trace_context
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = trace;
+    }
+
+    pub(crate) fn trace_context(&self) -> Option<W3cTraceContext> {
+        self.state
+            .trace_context
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Caps the tokens the model may generate for each subsequent request.
+    /// `None` removes the cap.
+    pub(crate) fn set_max_response_tokens(&self, max_tokens: Option<u32>) {
+        *self
+            .state
+            .max_response_tokens
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = max_tokens;
+    }
+
+    /// Asks the provider to keep cached prompt prefixes for `retention` on
+    /// every subsequent request. `None` leaves retention to the provider.
+    pub(crate) fn set_prompt_cache_retention(&self, retention: Option<PromptCacheRetention>) {
+        *self
+            .state
+            .prompt_cache_retention
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = retention;
+    }
+
+    fn prompt_cache_retention(&self) -> Option<PromptCacheRetention> {
+        *self
+            .state
+            .prompt_cache_retention
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn max_response_tokens(&self) -> Option<u32> {
+        *self
+            .state
+            .max_response_tokens
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// W3C trace headers for the session's trace context, naming the current
+    /// span as the parent when it belongs to that trace.
+    fn build_trace_headers(&self) -> ApiHeaderMap {
+        let mut headers = ApiHeaderMap::new();
+        let Some(trace) = self.trace_context() else {
+            return headers;
+        };
+        let trace = current_span_w3c_trace_context_within(&trace);
+        if let Some(traceparent) = trace.traceparent
+            && let Ok(value) = HeaderValue::from_str(&traceparent)
+        {
+            headers.insert("traceparent", value);
+        }
+        if let Some(tracestate) = trace.tracestate
+            && let Ok(value) = HeaderValue::from_str(&tracestate)
+        {
+            headers.insert("tracestate", value);
+        }
+        headers
+    }
+
+    #[cfg(test)]
+    fn session_capture(&self) -> std::sync::MutexGuard<'_, SessionCapture> {
+        self.state
+            .session_capture
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
     fn take_cached_websocket_session(&self) -> WebsocketSession {
         let mut cached_websocket_session = self
             .state
@@ -301,6 +481,7 @@ impl ModelClient {
         };
 
         let mut extra_headers = self.build_subagent_headers();
+        extra_headers.extend(self.build_trace_headers());
         extra_headers.extend(build_conversation_headers(Some(
             self.state.conversation_id.to_string(),
         )));
@@ -343,8 +524,38 @@ impl ModelClient {
             }),
         };
 
+        let mut extra_headers = self.build_subagent_headers();
+        extra_headers.extend(self.build_trace_headers());
+        client
+            .summarize_input(&payload, extra_headers)
+            .await
+            .map_err(map_api_error)
+    }
+
+    /// Embeds each of `input` with the embedding model `model`.
+    ///
+    /// This is a unary call to `/v1/embeddings`; vectors come back in input order.
+    pub async fn embed(
+        &self,
+        model: &str,
+        input: &[String],
+        session_telemetry: &SessionTelemetry,
+    ) -> Result<Vec<Vec<f32>>> {
+        if input.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let client_setup = self.current_client_setup().await?;
+        let transport = ReqwestTransport::new(build_reqwest_client());
+        let request_telemetry = Self::build_request_telemetry(session_telemetry);
+        let client =
+            ApiEmbeddingsClient::new(transport, client_setup.api_provider, client_setup.api_auth)
+                .with_telemetry(Some(request_telemetry));
+
+        let mut extra_headers = self.build_subagent_headers();
+        extra_headers.extend(self.build_trace_headers());
         client
-            .summarize_input(&payload, self.build_subagent_headers())
+            .embed(&ApiEmbeddingsInput { model, input }, extra_headers)
             .await
             .map_err(map_api_error)
     }
@@ -455,6 +666,7 @@ impl ModelClient {
         headers.extend(build_conversation_headers(Some(
             self.state.conversation_id.to_string(),
         )));
+        headers.extend(self.build_trace_headers());
         headers.insert(
             OPENAI_BETA_HEADER,
             HeaderValue::from_static(RESPONSES_WEBSOCKETS_V2_BETA_HEADER_VALUE),
@@ -478,6 +690,38 @@ impl Drop for ModelClientSession {
 }
 
 impl ModelClientSession {
+    /// How much longer requests to the provider are refused because its
+    /// circuit breaker is open, or `None` when a request may be sent. Once the
+    /// cooldown passes, one request is let through to probe the provider.
+    pub(crate) fn provider_retry_after(&self) -> Option<Duration> {
+        let (_, breaker) = self.client.state.circuit_breaker.as_ref()?;
+        let now = Instant::now();
+        match breaker.admit(now) {
+            Admission::Allowed | Admission::Probe => None,
+            Admission::Rejected => Some(match breaker.state() {
+                CircuitState::Open { until } => until.saturating_duration_since(now),
+                CircuitState::Closed { .. } | CircuitState::HalfOpen { .. } => Duration::ZERO,
+            }),
+        }
+    }
+
+    /// Records a request the provider answered, which closes its circuit.
+    pub(crate) fn record_provider_success(&self) {
+        if let Some((_, breaker)) = &self.client.state.circuit_breaker {
+            breaker.record_success();
+        }
+    }
+
+    /// Records a request that failed because the provider is unavailable.
+    /// Returns the breaker's settings when this failure opened the circuit.
+    pub(crate) fn record_provider_failure(&self) -> Option<CircuitBreakerConfig> {
+        let (config, breaker) = self.client.state.circuit_breaker.as_ref()?;
+        let was_open = matches!(breaker.state(), CircuitState::Open { .. });
+        breaker.record_failure(config, Instant::now());
+        let is_open = matches!(breaker.state(), CircuitState::Open { .. });
+        (is_open && !was_open).then_some(*config)
+    }
+
     fn activate_http_fallback(&self, websocket_enabled: bool) -> bool {
         websocket_enabled
             && !self
@@ -550,7 +794,12 @@ impl ModelClientSession {
                 None => None,
             },
             prompt_cache_key,
+            prompt_cache_retention: self
+                .client
+                .prompt_cache_retention()
+                .map(|retention| retention.as_str().to_string()),
             text,
+            max_tokens: self.client.max_response_tokens(),
         };
         Ok(request)
     }
@@ -567,14 +816,16 @@ impl ModelClientSession {
     ) -> ApiResponsesOptions {
         let turn_metadata_header = parse_turn_metadata_header(turn_metadata_header);
         let conversation_id = self.client.state.conversation_id.to_string();
+        let mut extra_headers = build_responses_headers(
+            self.client.state.beta_features_header.as_deref(),
+            Some(&self.turn_state),
+            turn_metadata_header.as_ref(),
+        );
+        extra_headers.extend(self.client.build_trace_headers());
         ApiResponsesOptions {
             conversation_id: Some(conversation_id),
             session_source: Some(self.client.state.session_source.clone()),
-            extra_headers: build_responses_headers(
-                self.client.state.beta_features_header.as_deref(),
-                Some(&self.turn_state),
-                turn_metadata_header.as_ref(),
-            ),
+            extra_headers,
             compression,
             turn_state: Some(Arc::clone(&self.turn_state)),
         }
@@ -982,6 +1233,39 @@ impl ModelClientSession {
         summary: ReasoningSummaryConfig,
         service_tier: Option<ServiceTier>,
         turn_metadata_header: Option<&str>,
+    ) -> Result<ResponseStream> {
+        #[cfg(test)]
+        let capture = self.client.session_capture().clone();
+        #[cfg(test)]
+        if let Some(playback) = &capture.playback {
+            return Ok(capture.tap(&prompt.input, playback.next_stream()?));
+        }
+        let stream = self
+            .stream_from_provider(
+                prompt,
+                model_info,
+                session_telemetry,
+                effort,
+                summary,
+                service_tier,
+                turn_metadata_header,
+            )
+            .await?;
+        #[cfg(test)]
+        let stream = capture.tap(&prompt.input, stream);
+        Ok(stream)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn stream_from_provider(
+        &mut self,
+        prompt: &Prompt,
+        model_info: &ModelInfo,
+        session_telemetry: &SessionTelemetry,
+        effort: Option<ReasoningEffortConfig>,
+        summary: ReasoningSummaryConfig,
+        service_tier: Option<ServiceTier>,
+        turn_metadata_header: Option<&str>,
     ) -> Result<ResponseStream> {
         let wire_api = self.client.state.provider.wire_api;
         match wire_api {
@@ -1261,11 +1545,22 @@ impl WebsocketTelemetry for ApiTelemetry {
 #[cfg(test)]
 mod tests {
     use super::ModelClient;
+    use crate::client_common::Prompt;
+    use crate::client_common::ResponseEvent;
     use codex_otel::SessionTelemetry;
     use codex_protocol::ThreadId;
+    use codex_protocol::config_types::ReasoningSummary as ReasoningSummaryConfig;
+    use codex_protocol::models::ContentItem;
+    use codex_protocol::models::ResponseItem;
     use codex_protocol::openai_models::ModelInfo;
     use codex_protocol::protocol::SessionSource;
     use codex_protocol::protocol::SubAgentSource;
+    use codex_protocol::protocol::W3cTraceContext;
+    use core_test_support::responses::ev_completed;
+    use core_test_support::responses::ev_response_created;
+    use core_test_support::responses::mount_sse_sequence;
+    use core_test_support::responses::sse;
+    use futures::StreamExt;
     use pretty_assertions::assert_eq;
     use serde_json::json;
 
@@ -1332,6 +1627,60 @@ mod tests {
         )
     }
 
+    #[test]
+    fn provider_circuit_breaker_opens_after_consecutive_failures() {
+        let mut provider = crate::model_provider_info::create_oss_provider_with_base_url(
+            "https://example.com/v1",
+            crate::model_provider_info::WireApi::Responses,
+        );
+        provider.retry = Some(crate::model_provider_info::ModelRetryConfig {
+            circuit_breaker_threshold: Some(2),
+            circuit_breaker_cooldown_secs: Some(0),
+            ..Default::default()
+        });
+        let session = ModelClient::new(
+            None,
+            ThreadId::new(),
+            provider,
+            SessionSource::Cli,
+            None,
+            false,
+            false,
+            false,
+            None,
+        )
+        .new_session();
+
+        assert_eq!(session.record_provider_failure(), None);
+        session.record_provider_success();
+        assert_eq!(session.record_provider_failure(), None);
+        let opened = session
+            .record_provider_failure()
+            .expect("second failure in a row opens the circuit");
+        assert_eq!(opened.failure_threshold, 2);
+
+        // The cooldown has passed, so one probe goes through while further
+        // requests wait for its outcome.
+        assert_eq!(session.provider_retry_after(), None);
+        assert!(session.provider_retry_after().is_some());
+        session.record_provider_success();
+        assert_eq!(session.provider_retry_after(), None);
+    }
+
+    #[test]
+    fn for_provider_reuses_the_client_only_for_its_own_provider() {
+        let client = test_model_client(SessionSource::Cli);
+        let same = client.for_provider(&client.state.provider);
+        assert!(std::sync::Arc::ptr_eq(&client.state, &same.state));
+
+        let fallback = crate::model_provider_info::create_oss_provider_with_base_url(
+            "https://fallback.example.com/v1",
+            crate::model_provider_info::WireApi::Responses,
+        );
+        let other = client.for_provider(&fallback);
+        assert_eq!(other.state.provider, fallback);
+    }
+
     #[test]
     fn build_subagent_headers_sets_other_subagent_label() {
         let client = test_model_client(SessionSource::SubAgent(SubAgentSource::Other(
@@ -1344,6 +1693,182 @@ mod tests {
         assert_eq!(value, Some("memory_consolidation"));
     }
 
+    #[tokio::test]
+    async fn trace_context_is_propagated_as_w3c_headers() {
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("POST"))
+            .and(wiremock::matchers::path("/v1/responses/compact"))
+            .respond_with(
+                wiremock::ResponseTemplate::new(200).set_body_json(json!({ "output": [] })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let provider = crate::model_provider_info::create_oss_provider_with_base_url(
+            &format!("{}/v1", server.uri()),
+            crate::model_provider_info::WireApi::Responses,
+        );
+        let client = ModelClient::new(
+            None,
+            ThreadId::new(),
+            provider,
+            SessionSource::Cli,
+            None,
+            false,
+            false,
+            false,
+            None,
+        );
+        client.set_trace_context(Some(W3cTraceContext {
+            traceparent: Some("00-00000000000000000000000000000011-0000000000000022-01".into()),
+            tracestate: Some("vendor=value".into()),
+        }));
+        let prompt = Prompt {
+            input: vec![ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: "hello".to_string(),
+                }],
+                end_turn: None,
+                phase: None,
+            }],
+            ..Default::default()
+        };
+
+        client
+            .compact_conversation_history(&prompt, &test_model_info(), &test_session_telemetry())
+            .await
+            .expect("compact request should succeed");
+
+        let requests = server.received_requests().await.expect("recorded requests");
+        let header = |name: &str| {
+            requests[0]
+                .headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        assert_eq!(
+            header("traceparent").as_deref(),
+            Some("00-00000000000000000000000000000011-0000000000000022-01")
+        );
+        assert_eq!(header("tracestate").as_deref(), Some("vendor=value"));
+    }
+
+    #[tokio::test]
+    async fn max_response_tokens_is_sent_until_cleared() {
+        let server = wiremock::MockServer::start().await;
+        let body = sse(vec![ev_response_created("resp-1"), ev_completed("resp-1")]);
+        let responses = mount_sse_sequence(&server, vec![body.clone(), body]).await;
+        let provider = crate::model_provider_info::create_oss_provider_with_base_url(
+            &format!("{}/v1", server.uri()),
+            crate::model_provider_info::WireApi::Responses,
+        );
+        let client = ModelClient::new(
+            None,
+            ThreadId::new(),
+            provider,
+            SessionSource::Cli,
+            None,
+            false,
+            false,
+            false,
+            None,
+        );
+        let prompt = Prompt {
+            input: vec![ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: "hello".to_string(),
+                }],
+                end_turn: None,
+                phase: None,
+            }],
+            ..Default::default()
+        };
+
+        for max_tokens in [Some(256), None] {
+            client.set_max_response_tokens(max_tokens);
+            let mut stream = client
+                .new_session()
+                .stream(
+                    &prompt,
+                    &test_model_info(),
+                    &test_session_telemetry(),
+                    None,
+                    ReasoningSummaryConfig::Auto,
+                    None,
+                    None,
+                )
+                .await
+                .expect("stream should start");
+            while let Some(event) = stream.next().await {
+                if matches!(event, Ok(ResponseEvent::Completed { .. }) | Err(_)) {
+                    break;
+                }
+            }
+        }
+
+        let requests = responses.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].body_json()["max_tokens"], json!(256));
+        assert_eq!(requests[1].body_json().get("max_tokens"), None);
+    }
+
+    #[tokio::test]
+    async fn prompt_cache_retention_is_sent_with_requests() {
+        let server = wiremock::MockServer::start().await;
+        let body = sse(vec![ev_response_created("resp-1"), ev_completed("resp-1")]);
+        let responses = mount_sse_sequence(&server, vec![body]).await;
+        let provider = crate::model_provider_info::create_oss_provider_with_base_url(
+            &format!("{}/v1", server.uri()),
+            crate::model_provider_info::WireApi::Responses,
+        );
+        let client = ModelClient::new(
+            None,
+            ThreadId::new(),
+            provider,
+            SessionSource::Cli,
+            None,
+            false,
+            false,
+            false,
+            None,
+        );
+        client.set_prompt_cache_retention(Some(PromptCacheRetention::Extended));
+
+        let mut stream = client
+            .new_session()
+            .stream(
+                &Prompt::default(),
+                &test_model_info(),
+                &test_session_telemetry(),
+                None,
+                ReasoningSummaryConfig::Auto,
+                None,
+                None,
+            )
+            .await
+            .expect("stream should start");
+        while let Some(event) = stream.next().await {
+            if matches!(event, Ok(ResponseEvent::Completed { .. }) | Err(_)) {
+                break;
+            }
+        }
+
+        let requests = responses.requests();
+        assert_eq!(
+            requests[0].body_json()["prompt_cache_retention"],
+            json!("24h")
+        );
+        assert_eq!(
+            requests[0].body_json()["prompt_cache_key"],
+            json!(client.state.conversation_id.to_string())
+        );
+    }
+
     #[tokio::test]
     async fn summarize_memories_returns_empty_for_empty_input() {
         let client = test_model_client(SessionSource::Cli);
@@ -338,11 +338,13 @@ mod tests {
             stream: true,
             include: vec![],
             prompt_cache_key: None,
+            prompt_cache_retention: None,
             service_tier: None,
             text: Some(TextControls {
                 verbosity: Some(OpenAiVerbosity::Low),
                 format: None,
             }),
+            max_tokens: None,
         };
 
         let v = serde_json::to_value(&req).expect("json");
@@ -380,8 +382,10 @@ mod tests {
             stream: true,
             include: vec![],
             prompt_cache_key: None,
+            prompt_cache_retention: None,
             service_tier: None,
             text: Some(text_controls),
+            max_tokens: None,
         };
 
         let v = serde_json::to_value(&req).expect("json");
@@ -417,8 +421,10 @@ mod tests {
             stream: true,
             include: vec![],
             prompt_cache_key: None,
+            prompt_cache_retention: None,
             service_tier: None,
             text: None,
+            max_tokens: None,
         };
 
         let v = serde_json::to_value(&req).expect("json");
@@ -439,8 +445,10 @@ mod tests {
             stream: true,
             include: vec![],
             prompt_cache_key: None,
+            prompt_cache_retention: None,
             service_tier: Some(ServiceTier::Flex.to_string()),
             text: None,
+            max_tokens: None,
         };
 
         let v = serde_json::to_value(&req).expect("json");
@@ -0,0 +1,412 @@
+//! Incremental embedding index of a workspace, used by the `code_search` tool.
+//!
+//! Files that `.gitignore` does not exclude are split into chunks of
+//! [`CHUNK_LINES`] lines and each chunk is embedded once. The index is kept at
+//! `$CODEX_HOME/code_index/<hash of the workspace root>.json` and refreshed
+//! lazily before each query: files whose size or modification time changed,
+//! or that the current turn patched, are chunked again, and only chunks whose
+//! text changed are sent for embedding.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+
+const CODE_INDEX_DIR: &str = "code_index";
+pub(crate) const CHUNK_LINES: usize = 40;
+/// Larger files are not indexed.
+const MAX_FILE_BYTES: u64 = 512 * 1024;
+/// Chunks sent per embeddings request.
+const EMBED_BATCH_SIZE: usize = 64;
+
+#[async_trait]
+pub(crate) trait Embedder: Send + Sync {
+    /// Embeds each of `input`, returning the vectors in input order.
+    async fn embed(&self, input: &[String]) -> anyhow::Result<Vec<Vec<f32>>>;
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct CodeIndex {
+    model: String,
+    /// Keyed by path relative to the workspace root.
+    files: BTreeMap<PathBuf, IndexedFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexedFile {
+    len: u64,
+    modified_ms: u64,
+    chunks: Vec<IndexedChunk>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexedChunk {
+    /// 1-based, inclusive.
+    start_line: usize,
+    end_line: usize,
+    hash: String,
+    embedding: Vec<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CodeSearchHit {
+    pub(crate) path: PathBuf,
+    pub(crate) start_line: usize,
+    pub(crate) end_line: usize,
+    pub(crate) score: f32,
+}
+
+/// A chunk of a re-read file, waiting for its embedding.
+struct PendingChunk {
+    path: PathBuf,
+    chunk_index: usize,
+    text: String,
+}
+
+impl CodeIndex {
+    pub(crate) fn index_path(codex_home: &Path, root: &Path) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(root.to_string_lossy().as_bytes());
+        let digest = format!("{:x}", hasher.finalize());
+        let key = digest.get(..16).unwrap_or(&digest);
+        codex_home.join(CODE_INDEX_DIR).join(format!("{key}.json"))
+    }
+
+    /// Loads the index at `path`. A missing or unreadable index, or one built
+    /// with another embedding model, starts over empty.
+    pub(crate) async fn load(path: &Path, model: &str) -> Self {
+        let loaded = match tokio::fs::read(path).await {
+            Ok(bytes) => serde_json::from_slice::<CodeIndex>(&bytes).ok(),
+            Err(_) => None,
+        };
+        match loaded {
+            Some(index) if index.model == model => index,
+            _ => CodeIndex {
+                model: model.to_string(),
+                files: BTreeMap::new(),
+            },
+        }
+    }
+
+    pub(crate) async fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let bytes = serde_json::to_vec(self).map_err(std::io::Error::other)?;
+        tokio::fs::write(path, bytes).await
+    }
+
+    /// Brings the index up to date with the files under `root`. `changed`
+    /// lists paths known to have been edited, which are re-read even if their
+    /// size and modification time look unchanged. Returns whether anything
+    /// in the index changed.
+    pub(crate) async fn refresh(
+        &mut self,
+        root: &Path,
+        changed: &[PathBuf],
+        embedder: &dyn Embedder,
+    ) -> anyhow::Result<bool> {
+        let walk_root = root.to_path_buf();
+        let files = tokio::task::spawn_blocking(move || walk_workspace(&walk_root)).await?;
+        let changed = changed
+            .iter()
+            .filter_map(|path| path.strip_prefix(root).ok())
+            .collect::<HashSet<_>>();
+
+        let mut updated = false;
+        let walked = files
+            .iter()
+            .map(|(path, _, _)| path.clone())
+            .collect::<HashSet<_>>();
+        let before = self.files.len();
+        self.files.retain(|path, _| walked.contains(path));
+        updated |= self.files.len() != before;
+
+        let mut pending = Vec::new();
+        for (path, len, modified_ms) in files {
+            if let Some(existing) = self.files.get(&path)
+                && existing.len == len
+                && existing.modified_ms == modified_ms
+                && !changed.contains(path.as_path())
+            {
+                continue;
+            }
+            let Ok(contents) = tokio::fs::read_to_string(root.join(&path)).await else {
+                updated |= self.files.remove(&path).is_some();
+                continue;
+            };
+            let mut previous = self
+                .files
+                .remove(&path)
+                .map(|file| {
+                    file.chunks
+                        .into_iter()
+                        .map(|chunk| (chunk.hash, chunk.embedding))
+                        .collect::<HashMap<_, _>>()
+                })
+                .unwrap_or_default();
+            let mut chunks = Vec::new();
+            for (chunk_index, (start_line, text)) in chunk_lines(&contents).into_iter().enumerate()
+            {
+                let hash = chunk_hash(&text);
+                let embedding = previous.remove(&hash).unwrap_or_default();
+                if embedding.is_empty() {
+                    pending.push(PendingChunk {
+                        path: path.clone(),
+                        chunk_index,
+                        text: format!("{}\n{text}", path.display()),
+                    });
+                }
+                chunks.push(IndexedChunk {
+                    start_line,
+                    end_line: start_line + text.lines().count().saturating_sub(1),
+                    hash,
+                    embedding,
+                });
+            }
+            self.files.insert(
+                path,
+                IndexedFile {
+                    len,
+                    modified_ms,
+                    chunks,
+                },
+            );
+            updated = true;
+        }
+
+        for batch in pending.chunks(EMBED_BATCH_SIZE) {
+            let input = batch
+                .iter()
+                .map(|chunk| chunk.text.clone())
+                .collect::<Vec<_>>();
+            let embeddings = embedder.embed(&input).await?;
+            for (chunk, embedding) in batch.iter().zip(embeddings) {
+                if let Some(indexed) = self
+                    .files
+                    .get_mut(&chunk.path)
+                    .and_then(|file| file.chunks.get_mut(chunk.chunk_index))
+                {
+                    indexed.embedding = embedding;
+                }
+            }
+        }
+        Ok(updated)
+    }
+
+    /// Returns the `limit` chunks most similar to `query`.
+    pub(crate) fn search(&self, query: &[f32], limit: usize) -> Vec<CodeSearchHit> {
+        let mut hits = self
+            .files
+            .iter()
+            .flat_map(|(path, file)| {
+                file.chunks.iter().map(move |chunk| CodeSearchHit {
+                    path: path.clone(),
+                    start_line: chunk.start_line,
+                    end_line: chunk.end_line,
+                    score: cosine_similarity(query, &chunk.embedding),
+                })
+            })
+            .collect::<Vec<_>>();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+        hits
+    }
+}
+
+/// Lists `(relative path, size, modified ms)` for the files under `root`
+/// that `.gitignore` and friends do not exclude.
+fn walk_workspace(root: &Path) -> Vec<(PathBuf, u64, u64)> {
+    ignore::WalkBuilder::new(root)
+        .require_git(false)
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_file())
+        })
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            if metadata.len() > MAX_FILE_BYTES {
+                return None;
+            }
+            let modified_ms = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |duration| duration.as_millis() as u64);
+            let path = entry.path().strip_prefix(root).ok()?.to_path_buf();
+            Some((path, metadata.len(), modified_ms))
+        })
+        .collect()
+}
+
+/// Splits `contents` into chunks of up to [`CHUNK_LINES`] lines, returning
+/// each chunk's 1-based first line and text. Blank chunks are dropped.
+fn chunk_lines(contents: &str) -> Vec<(usize, String)> {
+    let lines = contents.lines().collect::<Vec<_>>();
+    lines
+        .chunks(CHUNK_LINES)
+        .enumerate()
+        .filter(|(_, chunk)| chunk.iter().any(|line| !line.trim().is_empty()))
+        .map(|(index, chunk)| (index * CHUNK_LINES + 1, chunk.join("\n")))
+        .collect()
+}
+
+fn chunk_hash(text: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(text.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot = a.iter().zip(b).map(|(a, b)| a * b).sum::<f32>();
+    let norm_a = a.iter().map(|a| a * a).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|b| b * b).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    const VOCABULARY: &[&str] = &["parse", "config", "render", "html", "network"];
+
+    /// Embeds text as counts of a few words and records what it was asked
+    /// to embed.
+    #[derive(Default)]
+    struct WordCountEmbedder {
+        calls: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Embedder for WordCountEmbedder {
+        async fn embed(&self, input: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
+            self.calls
+                .lock()
+                .expect("lock calls")
+                .extend(input.iter().cloned());
+            Ok(input
+                .iter()
+                .map(|text| {
+                    VOCABULARY
+                        .iter()
+                        .map(|word| text.matches(word).count() as f32)
+                        .collect()
+                })
+                .collect())
+        }
+    }
+
+    impl WordCountEmbedder {
+        fn take_calls(&self) -> Vec<String> {
+            std::mem::take(&mut *self.calls.lock().expect("lock calls"))
+        }
+    }
+
+    #[tokio::test]
+    async fn refresh_skips_ignored_files_and_only_embeds_changed_chunks() -> anyhow::Result<()> {
+        let root = TempDir::new()?;
+        std::fs::write(root.path().join(".gitignore"), "target/\n")?;
+        std::fs::create_dir(root.path().join("target"))?;
+        std::fs::write(root.path().join("target/out.rs"), "parse config\n")?;
+        std::fs::write(root.path().join("config.rs"), "fn parse_config() {}\n")?;
+        std::fs::write(root.path().join("render.rs"), "fn render_html() {}\n")?;
+        let embedder = WordCountEmbedder::default();
+        let mut index = CodeIndex::load(&root.path().join("missing.json"), "test-model").await;
+
+        assert!(index.refresh(root.path(), &[], &embedder).await?);
+        let mut embedded = embedder.take_calls();
+        embedded.sort();
+        assert_eq!(
+            embedded,
+            vec![
+                "config.rs\nfn parse_config() {}".to_string(),
+                "render.rs\nfn render_html() {}".to_string(),
+            ]
+        );
+
+        let hits = index.search(&[0.0, 0.0, 1.0, 1.0, 0.0], 1);
+        assert_eq!(
+            hits.iter().map(|hit| &hit.path).collect::<Vec<_>>(),
+            vec![&PathBuf::from("render.rs")]
+        );
+
+        assert!(!index.refresh(root.path(), &[], &embedder).await?);
+        assert!(embedder.take_calls().is_empty());
+
+        std::fs::write(root.path().join("config.rs"), "fn parse_network() {}\n")?;
+        index
+            .refresh(root.path(), &[root.path().join("config.rs")], &embedder)
+            .await?;
+        assert_eq!(
+            embedder.take_calls(),
+            vec!["config.rs\nfn parse_network() {}".to_string()]
+        );
+
+        std::fs::remove_file(root.path().join("render.rs"))?;
+        assert!(index.refresh(root.path(), &[], &embedder).await?);
+        assert_eq!(
+            index.search(&[0.0, 0.0, 1.0, 1.0, 0.0], 10).len(),
+            1,
+            "removed files leave the index"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn saved_index_reloads_only_for_the_same_model() -> anyhow::Result<()> {
+        let codex_home = TempDir::new()?;
+        let root = TempDir::new()?;
+        std::fs::write(root.path().join("lib.rs"), "fn parse() {}\n")?;
+        let path = CodeIndex::index_path(codex_home.path(), root.path());
+        let embedder = WordCountEmbedder::default();
+
+        let mut index = CodeIndex::load(&path, "model-a").await;
+        index.refresh(root.path(), &[], &embedder).await?;
+        index.save(&path).await?;
+
+        assert_eq!(CodeIndex::load(&path, "model-a").await.files.len(), 1);
+        assert!(CodeIndex::load(&path, "model-b").await.files.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn chunks_cover_every_line_once() {
+        let contents = (1..=CHUNK_LINES + 5)
+            .map(|line| format!("line {line}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let chunks = chunk_lines(&contents);
+
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|(start, text)| (*start, text.lines().count()))
+                .collect::<Vec<_>>(),
+            vec![(1, CHUNK_LINES), (CHUNK_LINES + 1, 5)]
+        );
+    }
+}
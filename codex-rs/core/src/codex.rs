@@ -5,6 +5,7 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::AtomicU64;
+use std::time::Duration;
 
 use crate::AuthManager;
 use crate::CodexAuth;
@@ -17,11 +18,18 @@ use crate::analytics_client::AppInvocation;
 use crate::analytics_client::InvocationType;
 use crate::analytics_client::build_track_events_context;
 use crate::apps::render_apps_section;
+use crate::checkpoint::CHECKPOINTS_SUBDIR;
+use crate::checkpoint::CheckpointId;
+use crate::checkpoint::CheckpointStore;
+use crate::checkpoint::FileCheckpointStore;
+use crate::checkpoint::SessionCheckpoint;
 use crate::commit_attribution::commit_message_trailer_instruction;
 use crate::compact;
+use crate::compact::CompactStrategy;
 use crate::compact::InitialContextInjection;
+use crate::compact::PinError;
+use crate::compact::compact_strategy;
 use crate::compact::run_inline_auto_compact_task;
-use crate::compact::should_use_remote_compact_task;
 use crate::compact_remote::run_inline_remote_auto_compact_task;
 use crate::config::ManagedFeatures;
 use crate::connectors;
@@ -29,9 +37,15 @@ use crate::exec_policy::ExecPolicyManager;
 use crate::features::FEATURES;
 use crate::features::Feature;
 use crate::features::maybe_push_unstable_features_warning;
+use crate::features::resolve_features;
 #[cfg(test)]
+use crate::models_manager::capabilities::ModelCapabilities;
 use crate::models_manager::collaboration_mode_presets::CollaborationModesConfig;
 use crate::models_manager::manager::ModelsManager;
+use crate::models_manager::pricing::CostTracker;
+use crate::models_manager::pricing::pricing_for_model;
+use crate::models_manager::provider_chain::ProviderChain;
+use crate::models_manager::provider_chain::ProviderFallback;
 use crate::parse_command::parse_command;
 use crate::parse_turn_item;
 use crate::realtime_conversation::RealtimeConversationManager;
@@ -57,6 +71,8 @@ use chrono::Local;
 use chrono::Utc;
 use codex_app_server_protocol::McpServerElicitationRequest;
 use codex_app_server_protocol::McpServerElicitationRequestParams;
+use codex_hooks::CommandHookConfig;
+use codex_hooks::CommandHookEvent;
 use codex_hooks::HookEvent;
 use codex_hooks::HookEventAfterAgent;
 use codex_hooks::HookPayload;
@@ -66,8 +82,10 @@ use codex_hooks::HooksConfig;
 use codex_network_proxy::NetworkProxy;
 use codex_network_proxy::NetworkProxyAuditMetadata;
 use codex_network_proxy::normalize_host;
+use codex_otel::context_from_w3c_trace_context;
 use codex_otel::current_span_trace_id;
 use codex_otel::current_span_w3c_trace_context;
+use codex_otel::current_span_w3c_trace_context_within;
 use codex_otel::set_parent_from_w3c_trace_context;
 use codex_protocol::ThreadId;
 use codex_protocol::approvals::ElicitationRequestEvent;
@@ -93,6 +111,8 @@ use codex_protocol::protocol::FileChange;
 use codex_protocol::protocol::HasLegacyEvent;
 use codex_protocol::protocol::ItemCompletedEvent;
 use codex_protocol::protocol::ItemStartedEvent;
+use codex_protocol::protocol::ProviderFellBackEvent;
+use codex_protocol::protocol::ProviderUnhealthyEvent;
 use codex_protocol::protocol::RawResponseItemEvent;
 use codex_protocol::protocol::ReviewRequest;
 use codex_protocol::protocol::RolloutItem;
@@ -102,6 +122,7 @@ use codex_protocol::protocol::TurnAbortReason;
 use codex_protocol::protocol::TurnContextItem;
 use codex_protocol::protocol::TurnContextNetworkItem;
 use codex_protocol::protocol::TurnStartedEvent;
+use codex_protocol::protocol::W3cTraceContext;
 use codex_protocol::request_user_input::RequestUserInputArgs;
 use codex_protocol::request_user_input::RequestUserInputResponse;
 use codex_rmcp_client::ElicitationResponse;
@@ -124,6 +145,7 @@ use serde_json;
 use serde_json::Value;
 use tokio::sync::Mutex;
 use tokio::sync::RwLock;
+use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 use tokio::sync::watch;
 use tokio::task::JoinHandle;
@@ -154,8 +176,12 @@ use crate::config::ConstraintResult;
 use crate::config::GhostSnapshotConfig;
 use crate::config::StartedNetworkProxy;
 use crate::config::resolve_web_search_mode_for_turn;
+use crate::config::types::LifecycleHook;
+use crate::config::types::LifecycleHookEvent;
 use crate::config::types::McpServerConfig;
 use crate::config::types::ShellEnvironmentPolicy;
+use crate::config::types::TokenBudgetConfig;
+use crate::context_item::ContextItem;
 use crate::context_manager::ContextManager;
 use crate::context_manager::TotalTokenUsageBreakdown;
 use crate::environment_context::EnvironmentContext;
@@ -165,9 +191,18 @@ use crate::error::Result as CodexResult;
 use crate::exec::StreamOutput;
 use codex_config::CONFIG_TOML_FILE;
 
+mod output_schema;
 mod rollout_reconstruction;
 #[cfg(test)]
 mod rollout_reconstruction_tests;
+mod turn_recovery;
+mod turn_timeout;
+mod verify;
+
+use output_schema::OutputSchemaCheck;
+use turn_recovery::TurnCheckpointer;
+use turn_timeout::TurnTimeout;
+use verify::VerifyLoop;
 
 #[derive(Debug, PartialEq)]
 pub enum SteerInputError {
@@ -188,22 +223,28 @@ pub(crate) struct PreviousTurnSettings {
     pub(crate) realtime_active: Option<bool>,
 }
 
+use crate::audit::AuditLog;
 use crate::exec_policy::ExecPolicyUpdateError;
 use crate::feedback_tags;
 use crate::file_watcher::FileWatcher;
 use crate::file_watcher::FileWatcherEvent;
 use crate::git_info::get_git_repo_root;
+use crate::image_attachment::ImageAttachment;
+use crate::image_attachment::ImageHandle;
 use crate::instructions::UserInstructions;
 use crate::mcp::CODEX_APPS_MCP_SERVER_NAME;
 use crate::mcp::McpManager;
 use crate::mcp::auth::compute_auth_statuses;
+use crate::mcp::call_limiter::McpCallLimiter;
 use crate::mcp::maybe_prompt_and_install_mcp_dependencies;
 use crate::mcp::with_codex_apps_mcp;
+use crate::mcp_connection_manager::AmbiguousToolName;
 use crate::mcp_connection_manager::McpConnectionManager;
 use crate::mcp_connection_manager::codex_apps_tools_cache_key;
 use crate::mcp_connection_manager::filter_codex_apps_mcp_tools_only;
 use crate::mcp_connection_manager::filter_mcp_tools_by_name;
 use crate::mcp_connection_manager::filter_non_codex_apps_mcp_tools_only;
+use crate::mcp_supervisor::McpSupervisor;
 use crate::memories;
 use crate::mentions::build_connector_slug_counts;
 use crate::mentions::build_skill_name_counts;
@@ -220,6 +261,8 @@ use crate::protocol::ApplyPatchApprovalRequestEvent;
 use crate::protocol::AskForApproval;
 use crate::protocol::BackgroundEventEvent;
 use crate::protocol::CompactedItem;
+use crate::protocol::ContextWindowStatusEvent;
+use crate::protocol::CostUpdateEvent;
 use crate::protocol::DeprecationNoticeEvent;
 use crate::protocol::ErrorEvent;
 use crate::protocol::Event;
@@ -230,6 +273,9 @@ use crate::protocol::ModelRerouteEvent;
 use crate::protocol::ModelRerouteReason;
 use crate::protocol::NetworkApprovalContext;
 use crate::protocol::Op;
+use crate::protocol::PatchReviewHunk;
+use crate::protocol::PatchReviewRequestEvent;
+use crate::protocol::PatchReviewResponse;
 use crate::protocol::PlanDeltaEvent;
 use crate::protocol::RateLimitSnapshot;
 use crate::protocol::ReasoningContentDeltaEvent;
@@ -249,13 +295,17 @@ use crate::protocol::Submission;
 use crate::protocol::TokenCountEvent;
 use crate::protocol::TokenUsage;
 use crate::protocol::TokenUsageInfo;
-use crate::protocol::TurnDiffEvent;
 use crate::protocol::WarningEvent;
 use crate::rollout::RolloutRecorder;
 use crate::rollout::RolloutRecorderParams;
 use crate::rollout::map_session_init_error;
 use crate::rollout::metadata;
 use crate::rollout::policy::EventPersistenceMode;
+#[cfg(test)]
+use crate::session_recording::Recording;
+#[cfg(test)]
+use crate::session_recording::RecordingHandle;
+use crate::session_share::SessionShare;
 use crate::shell;
 use crate::shell_snapshot::ShellSnapshot;
 use crate::skills::SkillError;
@@ -279,25 +329,56 @@ use crate::tasks::RegularTask;
 use crate::tasks::ReviewTask;
 use crate::tasks::SessionTask;
 use crate::tasks::SessionTaskContext;
+use crate::tasks::TurnOutcomes;
+use crate::token_budget::TokenBudgetStatus;
 use crate::tools::ToolRouter;
+use crate::tools::audit::AuditHook;
+use crate::tools::call_dedupe::CallIdDedupe;
+use crate::tools::call_events::ToolCallEvent;
+use crate::tools::call_events::ToolCallSubscribers;
+use crate::tools::chaos::Chaos;
 use crate::tools::context::SharedTurnDiffTracker;
+use crate::tools::emulation::emulated_tools_instructions;
+use crate::tools::emulation::lower_tool_history;
 use crate::tools::handlers::SEARCH_TOOL_BM25_TOOL_NAME;
+use crate::tools::in_flight::ActiveToolCalls;
+use crate::tools::in_flight::InFlightToolCall;
+use crate::tools::in_flight::PendingToolCall;
+use crate::tools::in_flight::pending_tool_calls;
 use crate::tools::js_repl::JsReplHandle;
 use crate::tools::js_repl::resolve_compatible_node;
+use crate::tools::middleware::AfterAllHook;
+use crate::tools::middleware::BeforeAllHook;
+use crate::tools::middleware::DispatchInterceptor;
+use crate::tools::middleware::SandboxPermissionsHook;
+use crate::tools::middleware::SessionToolMiddleware;
 use crate::tools::network_approval::NetworkApprovalService;
 use crate::tools::network_approval::build_blocked_request_observer;
 use crate::tools::network_approval::build_network_policy_decider;
+use crate::tools::output_cache::InMemoryToolOutputCache;
+use crate::tools::output_cache::ToolOutputCache;
+use crate::tools::output_transform::ToolOutputTransformer;
 use crate::tools::parallel::ToolCallRuntime;
+use crate::tools::router::ArchivedToolCall;
+use crate::tools::router::CallArchive;
+use crate::tools::router::TurnToolOverrides;
 use crate::tools::sandboxing::ApprovalStore;
+use crate::tools::secret_redaction::redact_event_secrets;
+use crate::tools::spec::ToolFilter;
 use crate::tools::spec::ToolsConfig;
 use crate::tools::spec::ToolsConfigParams;
+use crate::tools::usage_summary::ToolUsageKind;
+use crate::tools::usage_summary::summarize_tool_usage;
 use crate::turn_diff_tracker::TurnDiffTracker;
 use crate::turn_timing::TurnTimingState;
 use crate::turn_timing::record_turn_ttfm_metric;
 use crate::turn_timing::record_turn_ttft_metric;
 use crate::unified_exec::UnifiedExecProcessManager;
-use crate::util::backoff;
+use crate::watched_files::WatchedFiles;
 use crate::windows_sandbox::WindowsSandboxLevelExt;
+use crate::workspace_roots::WorkspaceRoot;
+use crate::workspace_roots::named_workspace_roots;
+use crate::workspace_roots::resolve_root_relative;
 use codex_async_utils::OrCancelExt;
 use codex_otel::SessionTelemetry;
 use codex_otel::TelemetryAuthMode;
@@ -313,6 +394,7 @@ use codex_protocol::models::ResponseItem;
 use codex_protocol::openai_models::ReasoningEffort as ReasoningEffortConfig;
 use codex_protocol::protocol::CodexErrorInfo;
 use codex_protocol::protocol::InitialHistory;
+use codex_protocol::user_input::SYSTEM_NOTE_PREFIX;
 use codex_protocol::user_input::UserInput;
 use codex_utils_absolute_path::AbsolutePathBuf;
 use codex_utils_readiness::Readiness;
@@ -340,6 +422,8 @@ pub struct CodexSpawnOk {
 
 pub(crate) const INITIAL_SUBMIT_ID: &str = "";
 pub(crate) const SUBMISSION_CHANNEL_CAPACITY: usize = 512;
+/// [`Session::send_context_window_status`] warns above this utilization.
+const CONTEXT_WINDOW_WARNING_PCT: f64 = 80.0;
 const CYBER_VERIFY_URL: &str = "https://chatgpt.com/cyber";
 const CYBER_SAFETY_URL: &str = "https://developers.openai.com/codex/concepts/cyber-safety";
 
@@ -631,6 +715,10 @@ impl Codex {
     pub(crate) fn enabled(&self, feature: Feature) -> bool {
         self.session.enabled(feature)
     }
+
+    pub(crate) fn get_call_by_id(&self, call_id: &str) -> Option<ArchivedToolCall> {
+        self.session.get_call_by_id(call_id)
+    }
 }
 
 /// Context for an initialized model agent
@@ -684,6 +772,9 @@ pub(crate) struct TurnContext {
     /// the model as well as sandbox policies are resolved against this path
     /// instead of `std::env::current_dir()`.
     pub(crate) cwd: PathBuf,
+    /// Workspace roots besides `cwd`, which paths can address as
+    /// `@<name>/<path>`.
+    pub(crate) workspace_roots: Vec<WorkspaceRoot>,
     pub(crate) current_date: Option<String>,
     pub(crate) timezone: Option<String>,
     pub(crate) app_server_client_name: Option<String>,
@@ -711,6 +802,13 @@ pub(crate) struct TurnContext {
     pub(crate) turn_metadata_state: Arc<TurnMetadataState>,
     pub(crate) turn_skills: TurnSkillsContext,
     pub(crate) turn_timing_state: Arc<TurnTimingState>,
+    /// Assistant text streamed so far this turn, reported if it is cancelled.
+    pub(crate) streamed_output: Arc<std::sync::Mutex<String>>,
+    /// Tools disabled, and the dispatch timeout, for this turn only. See
+    /// [`CodexThread::set_next_turn_tool_overrides`].
+    ///
+    /// [`CodexThread::set_next_turn_tool_overrides`]: crate::CodexThread::set_next_turn_tool_overrides
+    pub(crate) tool_overrides: TurnToolOverrides,
 }
 impl TurnContext {
     pub(crate) fn model_context_window(&self) -> Option<i64> {
@@ -720,6 +818,19 @@ impl TurnContext {
         })
     }
 
+    pub(crate) fn model_capabilities(&self) -> ModelCapabilities {
+        ModelCapabilities::from(&self.model_info)
+    }
+
+    /// Budget for items recorded into history, raised to fit the largest
+    /// `[tool_output]` limit.
+    pub(crate) fn history_truncation_policy(&self) -> TruncationPolicy {
+        crate::tools::output_policy::history_truncation_policy(
+            &self.config.tool_output,
+            self.truncation_policy,
+        )
+    }
+
     pub(crate) async fn with_model(&self, model: String, models_manager: &ModelsManager) -> Self {
         let mut config = (*self.config).clone();
         config.model = Some(model.clone());
@@ -759,7 +870,12 @@ impl TurnContext {
         })
         .with_web_search_config(self.tools_config.web_search_config.clone())
         .with_allow_login_shell(self.tools_config.allow_login_shell)
-        .with_agent_roles(config.agent_roles.clone());
+        .with_max_argument_size_bytes(self.tools_config.max_argument_size_bytes)
+        .with_tool_fallbacks(self.tools_config.tool_fallbacks.clone())
+        .with_agent_roles(config.agent_roles.clone())
+        .with_workspace_roots(self.workspace_roots.clone())
+        .with_tool_filter(self.tools_config.tool_filter.clone())
+        .with_tool_overrides(self.tools_config.tool_overrides.clone());
 
         Self {
             sub_id: self.sub_id.clone(),
@@ -777,6 +893,7 @@ impl TurnContext {
             reasoning_summary: self.reasoning_summary,
             session_source: self.session_source.clone(),
             cwd: self.cwd.clone(),
+            workspace_roots: self.workspace_roots.clone(),
             current_date: self.current_date.clone(),
             timezone: self.timezone.clone(),
             app_server_client_name: self.app_server_client_name.clone(),
@@ -804,13 +921,37 @@ impl TurnContext {
             turn_metadata_state: self.turn_metadata_state.clone(),
             turn_skills: self.turn_skills.clone(),
             turn_timing_state: Arc::clone(&self.turn_timing_state),
+            streamed_output: Arc::clone(&self.streamed_output),
+            tool_overrides: self.tool_overrides.clone(),
         }
     }
 
+    /// Builds the context for continuing this turn on `fallback`'s provider,
+    /// keeping the current model unless the fallback names another one.
+    pub(crate) async fn with_provider_fallback(
+        &self,
+        fallback: &ProviderFallback,
+        models_manager: &ModelsManager,
+    ) -> Self {
+        let model = fallback
+            .model
+            .clone()
+            .unwrap_or_else(|| self.model_info.slug.clone());
+        let mut turn_context = self.with_model(model, models_manager).await;
+        let config = Arc::make_mut(&mut turn_context.config);
+        config.model_provider_id = fallback.provider_id.clone();
+        config.model_provider = fallback.provider.clone();
+        turn_context.provider = fallback.provider.clone();
+        // Tool calls already in flight for this turn wait on the same gate.
+        turn_context.tool_call_gate = Arc::clone(&self.tool_call_gate);
+        turn_context
+    }
+
     pub(crate) fn resolve_path(&self, path: Option<String>) -> PathBuf {
-        path.as_ref()
-            .map(PathBuf::from)
-            .map_or_else(|| self.cwd.clone(), |p| self.cwd.join(p))
+        let Some(path) = path else {
+            return self.cwd.clone();
+        };
+        resolve_root_relative(&self.workspace_roots, &path).unwrap_or_else(|| self.cwd.join(path))
     }
 
     pub(crate) fn compact_prompt(&self) -> &str {
@@ -866,6 +1007,22 @@ fn local_time_context() -> (String, String) {
     }
 }
 
+const DEFAULT_LIFECYCLE_HOOK_TIMEOUT_MS: u64 = 10_000;
+
+fn command_hook_config(hook: &LifecycleHook) -> CommandHookConfig {
+    CommandHookConfig {
+        event: match hook.event {
+            LifecycleHookEvent::TurnCompleted => CommandHookEvent::TurnCompleted,
+            LifecycleHookEvent::PatchApplied => CommandHookEvent::PatchApplied,
+            LifecycleHookEvent::ToolDenied => CommandHookEvent::ToolDenied,
+        },
+        argv: hook.command.clone(),
+        timeout: Duration::from_millis(
+            hook.timeout_ms.unwrap_or(DEFAULT_LIFECYCLE_HOOK_TIMEOUT_MS),
+        ),
+    }
+}
+
 #[derive(Clone)]
 pub(crate) struct SessionConfiguration {
     /// Provider identifier ("openai", "openrouter", ...).
@@ -928,6 +1085,10 @@ impl SessionConfiguration {
         &self.codex_home
     }
 
+    pub(crate) fn token_budget(&self) -> TokenBudgetConfig {
+        self.original_config_do_not_use.token_budget
+    }
+
     fn thread_config_snapshot(&self) -> ThreadConfigSnapshot {
         ThreadConfigSnapshot {
             model: self.collaboration_mode.model().to_string(),
@@ -1101,6 +1262,12 @@ impl Session {
                         };
                         sess.send_event_raw(event).await;
                     }
+                    Ok(FileWatcherEvent::ProjectDocsChanged { .. }) => {
+                        let Some(sess) = weak_sess.upgrade() else {
+                            break;
+                        };
+                        sess.refresh_user_instructions().await;
+                    }
                     Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
                     Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
                 }
@@ -1108,6 +1275,29 @@ impl Session {
         });
     }
 
+    /// Rebuilds the user instructions from the project docs on disk. The next
+    /// turn sends the model the new instructions if they changed.
+    async fn refresh_user_instructions(&self) {
+        let config = {
+            let state = self.state.lock().await;
+            let mut config = (*state.session_configuration.original_config_do_not_use).clone();
+            config.cwd = state.session_configuration.cwd.clone();
+            config
+        };
+        let loaded_skills = self.services.skills_manager.skills_for_config(&config);
+        let loaded_plugins = self.services.plugins_manager.plugins_for_config(&config);
+        let allowed_skills_for_implicit_invocation =
+            loaded_skills.allowed_skills_for_implicit_invocation();
+        let user_instructions = get_user_instructions(
+            &config,
+            Some(&allowed_skills_for_implicit_invocation),
+            Some(loaded_plugins.capability_summaries()),
+        )
+        .await;
+        let mut state = self.state.lock().await;
+        state.session_configuration.user_instructions = user_instructions;
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn make_turn_context(
         auth_manager: Option<Arc<AuthManager>>,
@@ -1134,6 +1324,8 @@ impl Session {
         let provider_for_context = provider;
         let session_telemetry_for_context = session_telemetry;
         let per_turn_config = Arc::new(per_turn_config);
+        let cwd = session_configuration.cwd.clone();
+        let workspace_roots = named_workspace_roots(&cwd, &per_turn_config.workspace_roots);
 
         let tools_config = ToolsConfig::new(&ToolsConfigParams {
             model_info: &model_info,
@@ -1143,9 +1335,17 @@ impl Session {
         })
         .with_web_search_config(per_turn_config.web_search_config.clone())
         .with_allow_login_shell(per_turn_config.permissions.allow_login_shell)
-        .with_agent_roles(per_turn_config.agent_roles.clone());
+        .with_max_argument_size_bytes(per_turn_config.tool_max_argument_size_bytes)
+        .with_configured_max_parallel_tool_calls(per_turn_config.tool_max_parallel_calls)
+        .with_tool_fallbacks(per_turn_config.tool_fallbacks.clone())
+        .with_agent_roles(per_turn_config.agent_roles.clone())
+        .with_workspace_roots(workspace_roots.clone())
+        .with_tool_filter(ToolFilter {
+            allow: per_turn_config.allowed_tools.clone(),
+            deny: per_turn_config.disabled_tools.clone(),
+        })
+        .with_tool_overrides(per_turn_config.tool_overrides.clone());
 
-        let cwd = session_configuration.cwd.clone();
         let turn_metadata_state = Arc::new(TurnMetadataState::new(
             sub_id.clone(),
             cwd.clone(),
@@ -1169,6 +1369,7 @@ impl Session {
             reasoning_summary,
             session_source,
             cwd,
+            workspace_roots,
             current_date: Some(current_date),
             timezone: Some(timezone),
             app_server_client_name: session_configuration.app_server_client_name.clone(),
@@ -1196,6 +1397,8 @@ impl Session {
             turn_metadata_state,
             turn_skills: TurnSkillsContext::new(skills_outcome),
             turn_timing_state: Arc::new(TurnTimingState::default()),
+            streamed_output: Arc::default(),
+            tool_overrides: TurnToolOverrides::default(),
         }
     }
 
@@ -1263,6 +1466,29 @@ impl Session {
                 ),
             ),
         };
+        if config.features.enabled(Feature::WorktreeIsolation)
+            && !matches!(
+                session_configuration.session_source,
+                SessionSource::SubAgent(_)
+            )
+        {
+            match crate::session_worktree::isolate_session_cwd(
+                &config.codex_home,
+                conversation_id,
+                &session_configuration.cwd,
+            )
+            .await
+            {
+                Ok(Some(cwd)) => {
+                    info!("session {conversation_id} is working in {}", cwd.display());
+                    session_configuration.cwd = cwd;
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    warn!("failed to create a worktree for session {conversation_id}: {err:#}")
+                }
+            }
+        }
         let state_builder = match &initial_history {
             InitialHistory::Resumed(resumed) => metadata::builder_from_items(
                 resumed.history.as_slice(),
@@ -1364,6 +1590,10 @@ impl Session {
             });
         }
         maybe_push_unstable_features_warning(&config, &mut post_session_configured_events);
+        post_session_configured_events.push(Event {
+            id: INITIAL_SUBMIT_ID.to_owned(),
+            msg: EventMsg::FeaturesResolved(resolve_features(&config)),
+        });
         if config.permissions.approval_policy.value() == AskForApproval::OnFailure {
             post_session_configured_events.push(Event {
                 id: "".to_owned(),
@@ -1481,7 +1711,10 @@ impl Session {
                 }
             };
         session_configuration.thread_name = thread_name.clone();
-        let state = SessionState::new(session_configuration.clone());
+        let mut state = SessionState::new(session_configuration.clone());
+        if config.features.enabled(Feature::ToolOutputCache) {
+            state.set_tool_output_cache(Arc::new(InMemoryToolOutputCache::new()));
+        }
         let managed_network_requirements_enabled = config.managed_network_requirements_enabled();
         let network_approval = Arc::new(NetworkApprovalService::default());
         // The managed proxy can call back into core for allowlist-miss decisions.
@@ -1528,6 +1761,13 @@ impl Session {
                 (None, None)
             };
 
+        let audit_log = AuditLog::from_config(&config, conversation_id).map_err(|err| {
+            anyhow::anyhow!(
+                "failed to open the audit log key {}: {err}",
+                crate::audit::audit_key_path(&config).display()
+            )
+        })?;
+
         let services = SessionServices {
             // Initialize the MCP connection manager with an uninitialized
             // instance. It will be replaced with one created via
@@ -1540,6 +1780,7 @@ impl Session {
                 &config.permissions.approval_policy,
             ))),
             mcp_startup_cancellation_token: Mutex::new(CancellationToken::new()),
+            mcp_supervisor: McpSupervisor::default(),
             unified_exec_manager: UnifiedExecProcessManager::new(
                 config.background_terminal_max_timeout,
             ),
@@ -1551,6 +1792,7 @@ impl Session {
             ),
             hooks: Hooks::new(HooksConfig {
                 legacy_notify_argv: config.notify.clone(),
+                commands: config.hooks.iter().map(command_hook_config).collect(),
             }),
             rollout: Mutex::new(rollout_recorder),
             user_shell: Arc::new(default_shell),
@@ -1581,7 +1823,21 @@ impl Session {
                 config.features.enabled(Feature::RuntimeMetrics),
                 Self::build_model_client_beta_features_header(config.as_ref()),
             ),
+            active_tool_calls: ActiveToolCalls::default(),
+            tool_call_ids: CallIdDedupe::default(),
+            tool_call_archive: CallArchive::default(),
+            tool_middleware: SessionToolMiddleware::default(),
+            tool_chaos: config
+                .tool_chaos
+                .map(|chaos| Arc::new(Chaos::new(chaos.into()))),
+            tool_call_subscribers: ToolCallSubscribers::default(),
+            turn_outcomes: TurnOutcomes::default(),
+            session_share: SessionShare::default(),
+            audit_log,
         };
+        services
+            .model_client
+            .set_prompt_cache_retention(config.prompt_cache_retention);
         let js_repl = Arc::new(JsReplHandle::with_node_path(
             config.js_repl_node_path.clone(),
             config.js_repl_node_module_dirs.clone(),
@@ -1604,6 +1860,7 @@ impl Session {
             let mut guard = network_policy_decider_session.write().await;
             *guard = Arc::downgrade(&sess);
         }
+        sess.services.mcp_supervisor.spawn(Arc::downgrade(&sess));
         // Dispatch the SessionConfiguredEvent first and then report any errors.
         // If resuming, include converted initial messages in the payload so UIs can render them immediately.
         let initial_messages = initial_history.get_event_msgs();
@@ -1719,6 +1976,53 @@ impl Session {
         self.services.state_db.clone()
     }
 
+    pub(crate) async fn set_next_turn_tool_overrides(&self, overrides: TurnToolOverrides) {
+        self.state
+            .lock()
+            .await
+            .set_next_turn_tool_overrides(overrides);
+    }
+
+    /// Looks up a tool call dispatched earlier in this session, in any turn.
+    /// Only the most recent calls are kept; see [`ToolRouter::get_call_by_id`].
+    pub(crate) fn get_call_by_id(&self, call_id: &str) -> Option<ArchivedToolCall> {
+        self.services.tool_call_archive.get(call_id)
+    }
+
+    /// Runs `hook` before every tool call dispatched in this session, from
+    /// the next sampling request on.
+    pub(crate) fn register_before_all(&self, hook: Arc<dyn BeforeAllHook>) {
+        self.services.tool_middleware.register_before_all(hook);
+    }
+
+    /// Runs `hook` after every tool call dispatched in this session, from
+    /// the next sampling request on.
+    pub(crate) fn register_after_all(&self, hook: Arc<dyn AfterAllHook>) {
+        self.services.tool_middleware.register_after_all(hook);
+    }
+
+    /// Reports every tool call dispatched in this session to `hook`, from the
+    /// next sampling request on.
+    pub(crate) fn register_audit_hook(&self, hook: Arc<dyn AuditHook>) {
+        self.services.tool_middleware.register_audit_hook(hook);
+    }
+
+    /// Hands every tool call dispatched in this session to `interceptor`,
+    /// from the next sampling request on.
+    pub(crate) fn set_dispatch_interceptor(&self, interceptor: Arc<dyn DispatchInterceptor>) {
+        self.services
+            .tool_middleware
+            .set_dispatch_interceptor(interceptor);
+    }
+
+    /// Lets `hook` decide the sandbox permissions of every local shell call
+    /// in this session, from the next sampling request on.
+    pub(crate) fn set_sandbox_permissions_hook(&self, hook: Arc<dyn SandboxPermissionsHook>) {
+        self.services
+            .tool_middleware
+            .set_sandbox_permissions_hook(hook);
+    }
+
     /// Ensure rollout file writes are durably flushed.
     pub(crate) async fn flush_rollout(&self) {
         let recorder = {
@@ -1796,6 +2100,15 @@ impl Session {
         }
     }
 
+    pub(crate) async fn get_compact_prompt(&self) -> String {
+        let state = self.state.lock().await;
+        state
+            .session_configuration
+            .compact_prompt
+            .clone()
+            .unwrap_or_else(|| compact::SUMMARIZATION_PROMPT.to_string())
+    }
+
     pub(crate) async fn merge_mcp_tool_selection(&self, tool_names: Vec<String>) -> Vec<String> {
         let mut state = self.state.lock().await;
         state.merge_mcp_tool_selection(tool_names)
@@ -1831,12 +2144,330 @@ impl Session {
         state.get_connector_selection()
     }
 
+    /// Resolves the capabilities of the session's current model through the
+    /// models manager, honoring config overrides such as the context window.
+    pub(crate) async fn get_model_capabilities(&self) -> ModelCapabilities {
+        let session_configuration = {
+            let state = self.state.lock().await;
+            state.session_configuration.clone()
+        };
+        let per_turn_config = Self::build_per_turn_config(&session_configuration);
+        let model_info = self
+            .services
+            .models_manager
+            .get_model_info(
+                session_configuration.collaboration_mode.model(),
+                &per_turn_config,
+            )
+            .await;
+        ModelCapabilities::from(&model_info)
+    }
+
     // Clears connector IDs that were accumulated for explicit selection.
     pub(crate) async fn clear_connector_selection(&self) {
         let mut state = self.state.lock().await;
         state.clear_connector_selection();
     }
 
+    pub(crate) async fn record_tool_usage(&self, turn_id: &str, kind: ToolUsageKind) {
+        let mut state = self.state.lock().await;
+        state.record_tool_usage(turn_id, kind);
+    }
+
+    /// Returns a plain-English description of the tools used during `turn_id`,
+    /// e.g. "In this turn, the model read 3 files and ran 2 shell commands."
+    pub(crate) async fn summarize_tools_used(&self, turn_id: &str) -> String {
+        let state = self.state.lock().await;
+        summarize_tool_usage(state.tool_usage(turn_id))
+    }
+
+    /// Registers a transformer applied to every subsequent tool result before
+    /// it is returned to the model. Transformers run in registration order.
+    pub(crate) async fn apply_tool_output_transformer(
+        &self,
+        transformer: Arc<dyn ToolOutputTransformer>,
+    ) {
+        let mut state = self.state.lock().await;
+        state.push_tool_output_transformer(transformer);
+    }
+
+    /// Asks for compaction summaries in `lang`, a BCP-47 tag such as `ja` or
+    /// `pt-BR`. An empty tag clears the preference.
+    pub(crate) async fn set_preferred_language(&self, lang: &str) {
+        let lang = lang.trim();
+        let mut state = self.state.lock().await;
+        state.set_preferred_language((!lang.is_empty()).then(|| lang.to_string()));
+    }
+
+    pub(crate) async fn preferred_language(&self) -> Option<String> {
+        let state = self.state.lock().await;
+        state.preferred_language()
+    }
+
+    pub(crate) async fn tool_output_transformers(&self) -> Vec<Arc<dyn ToolOutputTransformer>> {
+        let state = self.state.lock().await;
+        state.tool_output_transformers()
+    }
+
+    /// Answers later read-only tool calls from `cache` when it holds the
+    /// output of an identical earlier call, emitting
+    /// [`EventMsg::ToolCacheHit`] instead of dispatching. Any mutating call
+    /// clears the cache, since it may have changed what a read would return.
+    pub(crate) async fn with_tool_output_cache(&self, cache: Arc<dyn ToolOutputCache>) {
+        let mut state = self.state.lock().await;
+        state.set_tool_output_cache(cache);
+    }
+
+    pub(crate) async fn tool_output_cache(&self) -> Option<Arc<dyn ToolOutputCache>> {
+        let state = self.state.lock().await;
+        state.tool_output_cache()
+    }
+
+    pub(crate) async fn watched_files(&self) -> Arc<std::sync::Mutex<WatchedFiles>> {
+        let state = self.state.lock().await;
+        state.watched_files()
+    }
+
+    /// Rescans the files registered with `watch_files` and returns a
+    /// synthetic `watch_files` call reporting those changed since the last
+    /// turn, or nothing when none did.
+    async fn watched_file_change_items(&self, turn_context: &TurnContext) -> Vec<ResponseItem> {
+        let watched_files = self.watched_files().await;
+        let changes = tokio::task::spawn_blocking(move || {
+            watched_files
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .take_changes()
+        })
+        .await
+        .unwrap_or_default();
+        if changes.is_empty() {
+            return Vec::new();
+        }
+        Vec::from(changes.to_response_items(format!("watch-files-{}", turn_context.sub_id)))
+    }
+
+    /// Re-stamps the watched files among `paths`, the files the agent patched
+    /// this turn, so the next turn does not report its own edits.
+    async fn acknowledge_own_file_changes(&self, paths: Vec<PathBuf>) {
+        if paths.is_empty() {
+            return;
+        }
+        let watched_files = self.watched_files().await;
+        let _ = tokio::task::spawn_blocking(move || {
+            watched_files
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .acknowledge(&paths);
+        })
+        .await;
+    }
+
+    /// Stores `image` for the rest of the session and returns a handle that
+    /// later user messages can reference.
+    pub(crate) async fn attach_image(&self, image: ImageAttachment) -> ImageHandle {
+        let mut state = self.state.lock().await;
+        state.attach_image(image)
+    }
+
+    /// Content parts for the image behind `handle`, shaped for the current
+    /// model: a base64 `input_image` part when it supports vision, a text
+    /// placeholder otherwise. Returns `None` for unknown handles.
+    pub(crate) async fn image_content_items(
+        &self,
+        handle: ImageHandle,
+    ) -> Option<Vec<ContentItem>> {
+        let image = {
+            let state = self.state.lock().await;
+            state.image_attachment(handle).cloned()
+        }?;
+        let supports_vision = self.get_model_capabilities().await.supports_vision;
+        Some(image.content_items(supports_vision))
+    }
+
+    /// Persists this session's conversation history to `store` and returns
+    /// the id to restore it with [`Session::load_checkpoint`].
+    pub(crate) async fn save_checkpoint(
+        &self,
+        store: Arc<dyn CheckpointStore>,
+    ) -> CodexResult<CheckpointId> {
+        let checkpoint = {
+            let state = self.state.lock().await;
+            SessionCheckpoint {
+                conversation_id: self.conversation_id,
+                history: state.clone_history().raw_items().to_vec(),
+                reference_context_item: state.reference_context_item(),
+                token_info: state.token_info(),
+            }
+        };
+        let id = CheckpointId::new();
+        store.put(&id, serde_json::to_vec(&checkpoint)?).await?;
+        Ok(id)
+    }
+
+    /// Replaces this session's conversation history with the checkpoint saved
+    /// under `id`. Sessions are built by `Codex::spawn`, so restoring happens
+    /// into a freshly spawned session rather than by constructing a new one.
+    pub(crate) async fn load_checkpoint(
+        &self,
+        store: Arc<dyn CheckpointStore>,
+        id: &CheckpointId,
+    ) -> CodexResult<()> {
+        let checkpoint = SessionCheckpoint::load(store.as_ref(), id).await?;
+        self.replay_from_checkpoint(checkpoint).await
+    }
+
+    /// Saves a checkpoint of this session under `CODEX_HOME` for a later
+    /// [`Session::branch_from`].
+    pub(crate) async fn checkpoint(&self) -> CodexResult<CheckpointId> {
+        self.save_checkpoint(self.checkpoint_store().await).await
+    }
+
+    /// Returns the conversation to the checkpoint saved by
+    /// [`Session::checkpoint`] under `id`. Unlike
+    /// [`Session::replay_from_checkpoint`], the restored history is also
+    /// recorded in the rollout, as a compaction, so resuming the thread
+    /// resumes the branch.
+    pub(crate) async fn branch_from(&self, id: &CheckpointId) -> CodexResult<()> {
+        if self.active_turn.lock().await.is_some() {
+            return Err(CodexErr::InvalidRequest(
+                "cannot branch from a checkpoint while a turn is in progress".to_string(),
+            ));
+        }
+        let SessionCheckpoint {
+            conversation_id: _,
+            history,
+            reference_context_item,
+            token_info,
+        } = SessionCheckpoint::load(self.checkpoint_store().await.as_ref(), id).await?;
+        let compacted_item = CompactedItem {
+            message: String::new(),
+            replacement_history: Some(history.clone()),
+        };
+        self.replace_compacted_history(history, reference_context_item, compacted_item)
+            .await;
+        self.restore_token_usage(token_info).await;
+        Ok(())
+    }
+
+    async fn checkpoint_store(&self) -> Arc<dyn CheckpointStore> {
+        let dir = self
+            .codex_home()
+            .await
+            .join(CHECKPOINTS_SUBDIR)
+            .join(self.conversation_id.to_string());
+        Arc::new(FileCheckpointStore::new(dir))
+    }
+
+    /// Restores `checkpoint` into this session, whether it was saved before
+    /// or after a compaction. Like [`Session::load_checkpoint`], this replays
+    /// into a freshly spawned session rather than constructing one. A checkpoint taken after compaction holds the
+    /// summary in place of the turns it replaced; if that compaction stripped
+    /// the initial context, the checkpoint has no reference context item and
+    /// the next turn injects the full context again, as it would have in the
+    /// original session. Token usage is re-estimated from the restored
+    /// history so auto-compaction sees the context window actually in use.
+    pub(crate) async fn replay_from_checkpoint(
+        &self,
+        checkpoint: SessionCheckpoint,
+    ) -> CodexResult<()> {
+        let SessionCheckpoint {
+            conversation_id,
+            history,
+            reference_context_item,
+            token_info,
+        } = checkpoint;
+        debug!(
+            "replaying checkpoint of {conversation_id} ({} items) into {}",
+            history.len(),
+            self.conversation_id
+        );
+        self.replace_history(history, reference_context_item).await;
+        self.restore_token_usage(token_info).await;
+        Ok(())
+    }
+
+    /// Restores the cumulative usage saved with a checkpoint, if any, and
+    /// re-estimates the context window in use from the restored history.
+    async fn restore_token_usage(&self, token_info: Option<TokenUsageInfo>) {
+        if token_info.is_some() {
+            self.state.lock().await.set_token_info(token_info);
+        }
+        let turn_context = self.new_default_turn().await;
+        self.recompute_token_usage(&turn_context).await;
+    }
+
+    /// Tool calls that have been dispatched and have not finished yet,
+    /// oldest first.
+    pub(crate) fn get_active_tool_calls(&self) -> Vec<InFlightToolCall> {
+        self.services.active_tool_calls.snapshot()
+    }
+
+    /// Tool calls in the conversation history that have no recorded result
+    /// and are not running in this process, oldest first. After restoring a
+    /// session, these are the calls that were in flight when it was saved;
+    /// the caller decides whether to re-dispatch them or record failures.
+    pub(crate) async fn get_pending_tool_calls(&self) -> Vec<PendingToolCall> {
+        let history = self.clone_history().await;
+        pending_tool_calls(history.raw_items())
+            .into_iter()
+            .filter(|call| !self.services.active_tool_calls.contains(&call.call_id))
+            .collect()
+    }
+
+    /// Streams a [`ToolCallEvent`] when each tool call dispatched from now on
+    /// starts and again when it finishes.
+    pub(crate) fn subscribe_to_tool_calls(&self) -> impl Stream<Item = ToolCallEvent> + use<> {
+        self.services.tool_call_subscribers.subscribe()
+    }
+
+    /// Continues the upstream distributed trace identified by the W3C
+    /// `traceparent` and `tracestate` header values. Submissions that carry no
+    /// trace context of their own are parented to it, and it is propagated on
+    /// every model request and MCP tool call. An invalid `traceparent` is
+    /// ignored.
+    pub(crate) fn set_trace_context(&self, traceparent: String, tracestate: Option<String>) {
+        let trace = W3cTraceContext {
+            traceparent: Some(traceparent),
+            tracestate,
+        };
+        if context_from_w3c_trace_context(&trace).is_none() {
+            warn!("ignoring invalid session traceparent");
+            return;
+        }
+        self.services.model_client.set_trace_context(Some(trace));
+    }
+
+    /// Caps how many tokens the model may generate in each response, for
+    /// every request from now on. `None` lifts the cap. Unlike a token
+    /// budget, this bounds a single response rather than the session.
+    pub(crate) fn set_max_response_tokens(&self, max_tokens: Option<u32>) {
+        self.services
+            .model_client
+            .set_max_response_tokens(max_tokens);
+    }
+
+    pub(crate) fn trace_context(&self) -> Option<W3cTraceContext> {
+        self.services.model_client.trace_context()
+    }
+
+    /// Starts capturing every model request this session sends and the
+    /// response events streamed back. Save the returned handle to produce a
+    /// golden-master recording for [`Session::playback`].
+    #[cfg(test)]
+    pub(crate) fn record(&self) -> RecordingHandle {
+        self.services.model_client.start_recording()
+    }
+
+    /// Switches this session to serve model responses from the recording at
+    /// `path` instead of the provider, in the order they were recorded.
+    #[cfg(test)]
+    pub(crate) fn playback(&self, path: &Path) -> std::io::Result<()> {
+        let recording = Recording::load(path)?;
+        self.services.model_client.start_playback(recording);
+        Ok(())
+    }
+
     async fn record_initial_history(&self, conversation_history: InitialHistory) {
         let turn_context = self.new_default_turn().await;
         self.clear_mcp_tool_selection().await;
@@ -1907,6 +2538,9 @@ impl Session {
                     self.record_into_history(&reconstructed_history, &turn_context)
                         .await;
                 }
+                // A turn left running by a crash is closed out before anything else is added.
+                self.recover_interrupted_turn(&turn_context, &rollout_items)
+                    .await;
 
                 // Seed usage info from the recorded rollout so UIs can show token counts
                 // immediately on resume/fork.
@@ -1914,6 +2548,10 @@ impl Session {
                     let mut state = self.state.lock().await;
                     state.set_token_info(Some(info));
                 }
+                if let Some(summary) = Self::last_cost_update_from_rollout(&rollout_items) {
+                    let mut state = self.state.lock().await;
+                    state.cost_tracker = CostTracker::from_summary(summary);
+                }
                 if let Some(selected_tools) = restored_tool_selection {
                     self.set_mcp_tool_selection(selected_tools).await;
                 }
@@ -1955,6 +2593,10 @@ impl Session {
                     let mut state = self.state.lock().await;
                     state.set_token_info(Some(info));
                 }
+                if let Some(summary) = Self::last_cost_update_from_rollout(&rollout_items) {
+                    let mut state = self.state.lock().await;
+                    state.cost_tracker = CostTracker::from_summary(summary);
+                }
                 if let Some(selected_tools) = restored_tool_selection {
                     self.set_mcp_tool_selection(selected_tools).await;
                 }
@@ -1991,6 +2633,13 @@ impl Session {
         })
     }
 
+    fn last_cost_update_from_rollout(rollout_items: &[RolloutItem]) -> Option<&CostUpdateEvent> {
+        rollout_items.iter().rev().find_map(|item| match item {
+            RolloutItem::EventMsg(EventMsg::CostUpdate(ev)) => Some(ev),
+            _ => None,
+        })
+    }
+
     fn extract_mcp_tool_selection_from_rollout(
         rollout_items: &[RolloutItem],
     ) -> Option<Vec<String>> {
@@ -2126,6 +2775,7 @@ impl Session {
             previous_cwd,
             codex_home,
             session_source,
+            tool_overrides,
         ) = {
             let mut state = self.state.lock().await;
             match state.session_configuration.clone().apply(&updates) {
@@ -2142,6 +2792,7 @@ impl Session {
                         previous_cwd,
                         codex_home,
                         session_source,
+                        state.take_next_turn_tool_overrides(),
                     )
                 }
                 Err(err) => {
@@ -2172,6 +2823,7 @@ impl Session {
                 session_configuration,
                 updates.final_output_json_schema,
                 sandbox_policy_changed,
+                tool_overrides,
             )
             .await)
     }
@@ -2182,6 +2834,7 @@ impl Session {
         session_configuration: SessionConfiguration,
         final_output_json_schema: Option<Option<Value>>,
         sandbox_policy_changed: bool,
+        tool_overrides: TurnToolOverrides,
     ) -> Arc<TurnContext> {
         let per_turn_config = Self::build_per_turn_config(&session_configuration);
         self.services
@@ -2245,6 +2898,7 @@ impl Session {
         if let Some(final_schema) = final_output_json_schema {
             turn_context.final_output_json_schema = final_schema;
         }
+        turn_context.tool_overrides = tool_overrides;
         let turn_context = Arc::new(turn_context);
         turn_context.turn_metadata_state.spawn_git_enrichment_task();
         turn_context
@@ -2316,6 +2970,14 @@ impl Session {
             &startup_cancellation_token,
         )
         .await?;
+        let live_mcp_tools = self
+            .services
+            .mcp_connection_manager
+            .read()
+            .await
+            .list_all_tools()
+            .await;
+        startup_router.warn_on_mcp_schema_drift(&live_mcp_tools);
         let startup_prompt = build_prompt(
             Vec::new(),
             startup_router.as_ref(),
@@ -2398,8 +3060,14 @@ impl Session {
             let state = self.state.lock().await;
             state.session_configuration.clone()
         };
-        self.new_turn_from_configuration(sub_id, session_configuration, None, false)
-            .await
+        self.new_turn_from_configuration(
+            sub_id,
+            session_configuration,
+            None,
+            false,
+            TurnToolOverrides::default(),
+        )
+        .await
     }
 
     async fn build_settings_update_items(
@@ -2479,6 +3147,10 @@ impl Session {
         // Persist the event into rollout (recorder filters as needed)
         let rollout_items = vec![RolloutItem::EventMsg(event.msg.clone())];
         self.persist_rollout_items(&rollout_items).await;
+        if let Some(audit_log) = self.services.audit_log.as_ref() {
+            audit_log.record_event(&event).await;
+        }
+        self.services.session_share.publish(&event);
         if let Err(e) = self.tx_event.send(event).await {
             debug!("dropping event because channel is closed: {e}");
         }
@@ -2497,6 +3169,10 @@ impl Session {
         self.persist_rollout_items(&[RolloutItem::EventMsg(event.msg.clone())])
             .await;
         self.flush_rollout().await;
+        if let Some(audit_log) = self.services.audit_log.as_ref() {
+            audit_log.record_event(&event).await;
+        }
+        self.services.session_share.publish(&event);
         if let Err(e) = self.tx_event.send(event).await {
             debug!("dropping event because channel is closed: {e}");
         }
@@ -2816,6 +3492,40 @@ impl Session {
         rx_approve
     }
 
+    /// Asks the client which hunks of a patch to apply. Returns `None` when
+    /// the request is dropped, e.g. because the turn was interrupted.
+    pub async fn request_patch_review(
+        &self,
+        turn_context: &TurnContext,
+        call_id: String,
+        hunks: Vec<PatchReviewHunk>,
+        reason: Option<String>,
+    ) -> Option<PatchReviewResponse> {
+        let (tx_response, rx_response) = oneshot::channel();
+        let prev_entry = {
+            let mut active = self.active_turn.lock().await;
+            match active.as_mut() {
+                Some(at) => {
+                    let mut ts = at.turn_state.lock().await;
+                    ts.insert_pending_patch_review(call_id.clone(), tx_response)
+                }
+                None => None,
+            }
+        };
+        if prev_entry.is_some() {
+            warn!("Overwriting existing pending patch review for call_id: {call_id}");
+        }
+
+        let event = EventMsg::PatchReviewRequest(PatchReviewRequestEvent {
+            call_id,
+            turn_id: turn_context.sub_id.clone(),
+            hunks,
+            reason,
+        });
+        self.send_event(turn_context, event).await;
+        rx_response.await.ok()
+    }
+
     pub async fn request_user_input(
         &self,
         turn_context: &TurnContext,
@@ -2973,6 +3683,27 @@ impl Session {
         }
     }
 
+    pub async fn notify_patch_review(&self, call_id: &str, response: PatchReviewResponse) {
+        let entry = {
+            let mut active = self.active_turn.lock().await;
+            match active.as_mut() {
+                Some(at) => {
+                    let mut ts = at.turn_state.lock().await;
+                    ts.remove_pending_patch_review(call_id)
+                }
+                None => None,
+            }
+        };
+        match entry {
+            Some(tx_response) => {
+                tx_response.send(response).ok();
+            }
+            None => {
+                warn!("No pending patch review found for call_id: {call_id}");
+            }
+        }
+    }
+
     pub async fn notify_approval(&self, approval_id: &str, decision: ReviewDecision) {
         let entry = {
             let mut active = self.active_turn.lock().await;
@@ -3044,7 +3775,38 @@ impl Session {
         turn_context: &TurnContext,
     ) {
         let mut state = self.state.lock().await;
-        state.record_items(items.iter(), turn_context.truncation_policy);
+        state.record_turn_items(
+            &turn_context.sub_id,
+            items.iter(),
+            turn_context.history_truncation_policy(),
+        );
+    }
+
+    /// Keeps turn `turn_id` out of every later compaction; its items stay in
+    /// the history verbatim. Fails if the turn has no items in the current
+    /// history.
+    pub(crate) async fn pin_turn(&self, turn_id: &str) -> Result<(), PinError> {
+        self.state.lock().await.pin_turn(turn_id)
+    }
+
+    pub(crate) async fn unpin_turn(&self, turn_id: &str) {
+        self.state.lock().await.unpin_turn(turn_id);
+    }
+
+    pub(crate) async fn pinned_turn_items(&self) -> Vec<Vec<ResponseItem>> {
+        self.state.lock().await.pinned_turn_items()
+    }
+
+    /// Appends `item` to the history as-is, without starting a turn. The
+    /// model sees it from the next request on.
+    pub(crate) async fn push_context_item(&self, item: ContextItem) {
+        let response_item = item.to_response_item();
+        let turn_context = self.new_default_turn().await;
+        self.record_conversation_items(&turn_context, std::slice::from_ref(&response_item))
+            .await;
+        if item.pinned {
+            self.state.lock().await.pin_context_item(response_item);
+        }
     }
 
     pub(crate) async fn record_model_warning(&self, message: impl Into<String>, ctx: &TurnContext) {
@@ -3209,6 +3971,16 @@ impl Session {
         {
             developer_sections.push(memory_prompt);
         }
+        if turn_context.features.enabled(Feature::ProjectMemory)
+            && turn_context.config.memories.use_memories
+            && let Some(project_memory) = build_project_memory_instructions(
+                &turn_context.config.codex_home,
+                &turn_context.cwd,
+            )
+            .await
+        {
+            developer_sections.push(project_memory);
+        }
         // Add developer instructions from collaboration_mode if they exist and are non-empty
         if let Some(collab_instructions) =
             DeveloperInstructions::from_collaboration_mode(&collaboration_mode)
@@ -3290,9 +4062,26 @@ impl Session {
             let guard = self.services.rollout.lock().await;
             guard.clone()
         };
-        if let Some(rec) = recorder
-            && let Err(e) = rec.record_items(items).await
-        {
+        let Some(rec) = recorder else {
+            return;
+        };
+        // Tool output in history is redacted when it is recorded; the copies
+        // carried by end events are redacted here.
+        let redacted_items;
+        let items = if self.features.enabled(Feature::SecretsRedaction) {
+            redacted_items = items
+                .iter()
+                .map(|item| match item {
+                    RolloutItem::EventMsg(msg) => redact_event_secrets(msg)
+                        .map_or_else(|| item.clone(), RolloutItem::EventMsg),
+                    _ => item.clone(),
+                })
+                .collect::<Vec<_>>();
+            redacted_items.as_slice()
+        } else {
+            items
+        };
+        if let Err(e) = rec.record_items(items).await {
             error!("failed to record rollout items: {e:#}");
         }
     }
@@ -3357,11 +4146,64 @@ impl Session {
         turn_context: &TurnContext,
         token_usage: Option<&TokenUsage>,
     ) {
-        if let Some(token_usage) = token_usage {
+        let Some(token_usage) = token_usage else {
+            self.send_token_count_event(turn_context).await;
+            return;
+        };
+        let model = turn_context.model_info.slug.as_str();
+        let pricing = pricing_for_model(&turn_context.config.model_pricing, model);
+        let (cost_usd, cost_update) = {
             let mut state = self.state.lock().await;
             state.update_token_info_from_usage(token_usage, turn_context.model_context_window());
+            let cost_usd = state.cost_tracker.record(
+                &turn_context.provider.name,
+                model,
+                token_usage,
+                pricing.as_ref(),
+            );
+            (cost_usd, state.cost_tracker.summary())
+        };
+        if let Some(cost_usd) = cost_usd {
+            record_cost_metrics(turn_context, cost_usd);
         }
         self.send_token_count_event(turn_context).await;
+        self.send_event(turn_context, EventMsg::CostUpdate(cost_update))
+            .await;
+    }
+
+    /// Reports how full the context window is, estimated from the history
+    /// and base instructions, with a warning when it is over
+    /// [`CONTEXT_WINDOW_WARNING_PCT`] percent full. Nothing is sent when the
+    /// model's context window is unknown.
+    pub(crate) async fn send_context_window_status(&self, turn_context: &TurnContext) {
+        let Some(max_tokens) = turn_context.model_context_window() else {
+            return;
+        };
+        let base_instructions = self.get_base_instructions().await;
+        let Some(used_tokens) = self
+            .clone_history()
+            .await
+            .estimate_token_count_with_base_instructions(&base_instructions)
+        else {
+            return;
+        };
+        let pct_full = used_tokens as f64 / max_tokens.max(1) as f64 * 100.0;
+        self.send_event(
+            turn_context,
+            EventMsg::ContextWindowStatus(ContextWindowStatusEvent {
+                used_tokens,
+                max_tokens,
+                pct_full,
+            }),
+        )
+        .await;
+        if pct_full > CONTEXT_WINDOW_WARNING_PCT {
+            let message = format!(
+                "The context window is {pct_full:.0}% full. Older context will be compacted soon; consider starting a new thread."
+            );
+            self.send_event(turn_context, EventMsg::Warning(WarningEvent { message }))
+                .await;
+        }
     }
 
     pub(crate) async fn recompute_token_usage(&self, turn_context: &TurnContext) {
@@ -3442,7 +4284,14 @@ impl Session {
             let state = self.state.lock().await;
             state.token_info_and_rate_limits()
         };
-        let event = EventMsg::TokenCount(TokenCountEvent { info, rate_limits });
+        let prompt_cache_hit_rate = info
+            .as_ref()
+            .and_then(|info| info.total_token_usage.cache_hit_rate());
+        let event = EventMsg::TokenCount(TokenCountEvent {
+            info,
+            rate_limits,
+            prompt_cache_hit_rate,
+        });
         self.send_event(turn_context, event).await;
     }
 
@@ -3608,6 +4457,20 @@ impl Session {
         }
     }
 
+    /// Makes the running turn's diff readable through [`Op::GetTurnDiff`].
+    async fn set_turn_diff_tracker(&self, tracker: SharedTurnDiffTracker) {
+        let active = self.active_turn.lock().await;
+        if let Some(at) = active.as_ref() {
+            at.turn_state.lock().await.turn_diff_tracker = Some(tracker);
+        }
+    }
+
+    async fn turn_diff_tracker(&self) -> Option<SharedTurnDiffTracker> {
+        let active = self.active_turn.lock().await;
+        let at = active.as_ref()?;
+        at.turn_state.lock().await.turn_diff_tracker.clone()
+    }
+
     pub async fn has_pending_input(&self) -> bool {
         let active = self.active_turn.lock().await;
         match active.as_ref() {
@@ -3658,21 +4521,44 @@ impl Session {
             .await
     }
 
+    pub(crate) async fn mcp_call_limiter(&self, server: &str) -> Option<Arc<McpCallLimiter>> {
+        self.services
+            .mcp_connection_manager
+            .read()
+            .await
+            .call_limiter(server)
+    }
+
     pub async fn call_tool(
         &self,
         server: &str,
         tool: &str,
         arguments: Option<serde_json::Value>,
+        partial_output: Option<mpsc::UnboundedSender<String>>,
     ) -> anyhow::Result<CallToolResult> {
+        let meta = self.trace_context().map(|trace| {
+            let trace = current_span_w3c_trace_context_within(&trace);
+            let mut meta = serde_json::Map::new();
+            if let Some(traceparent) = trace.traceparent {
+                meta.insert("traceparent".to_string(), traceparent.into());
+            }
+            if let Some(tracestate) = trace.tracestate {
+                meta.insert("tracestate".to_string(), tracestate.into());
+            }
+            meta
+        });
         self.services
             .mcp_connection_manager
             .read()
             .await
-            .call_tool(server, tool, arguments)
+            .call_tool(server, tool, arguments, meta, partial_output)
             .await
     }
 
-    pub(crate) async fn parse_mcp_tool_name(&self, tool_name: &str) -> Option<(String, String)> {
+    pub(crate) async fn parse_mcp_tool_name(
+        &self,
+        tool_name: &str,
+    ) -> Result<Option<(String, String)>, AmbiguousToolName> {
         self.services
             .mcp_connection_manager
             .read()
@@ -3695,6 +4581,45 @@ impl Session {
         &self.services.hooks
     }
 
+    /// The model client for `turn_context`'s provider, which is no longer
+    /// the session's once the turn has fallen back to another provider.
+    pub(crate) fn model_client_for(&self, turn_context: &TurnContext) -> ModelClient {
+        self.services
+            .model_client
+            .for_provider(&turn_context.provider)
+    }
+
+    /// Runs the hooks for an event that cannot be aborted, logging failures.
+    pub(crate) async fn run_lifecycle_hooks(
+        &self,
+        turn_context: &TurnContext,
+        hook_event: HookEvent,
+    ) {
+        let hook_outcomes = self
+            .hooks()
+            .dispatch(HookPayload {
+                session_id: self.conversation_id,
+                cwd: turn_context.cwd.clone(),
+                client: turn_context.app_server_client_name.clone(),
+                triggered_at: chrono::Utc::now(),
+                hook_event,
+            })
+            .await;
+        for hook_outcome in hook_outcomes {
+            match hook_outcome.result {
+                HookResult::Success => {}
+                HookResult::FailedContinue(error) | HookResult::FailedAbort(error) => {
+                    warn!(
+                        turn_id = %turn_context.sub_id,
+                        hook_name = %hook_outcome.hook_name,
+                        error = %error,
+                        "lifecycle hook failed"
+                    );
+                }
+            }
+        }
+    }
+
     pub(crate) fn user_shell(&self) -> Arc<shell::Shell> {
         Arc::clone(&self.services.user_shell)
     }
@@ -3822,7 +4747,7 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
     // To break out of this loop, send Op::Shutdown.
     while let Ok(sub) = rx_sub.recv().await {
         debug!(?sub, "Submission");
-        let dispatch_span = submission_dispatch_span(&sub);
+        let dispatch_span = submission_dispatch_span(&sub, sess.trace_context().as_ref());
         let should_exit = async {
             match sub.op.clone() {
                 Op::Interrupt => {
@@ -3833,6 +4758,22 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
                     handlers::clean_background_terminals(&sess).await;
                     false
                 }
+                Op::TerminalInput { process_id, data } => {
+                    handlers::terminal_input(&sess, sub.id.clone(), process_id, data).await;
+                    false
+                }
+                Op::TerminalResize {
+                    process_id,
+                    rows,
+                    cols,
+                } => {
+                    handlers::terminal_resize(&sess, sub.id.clone(), process_id, rows, cols).await;
+                    false
+                }
+                Op::TerminalDetach { process_id } => {
+                    handlers::terminal_detach(&sess, sub.id.clone(), process_id).await;
+                    false
+                }
                 Op::RealtimeConversationStart(params) => {
                     if let Err(err) =
                         handle_realtime_conversation_start(&sess, sub.id.clone(), params).await
@@ -3916,6 +4857,10 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
                     handlers::patch_approval(&sess, id, decision).await;
                     false
                 }
+                Op::PatchReview { id, response } => {
+                    handlers::patch_review(&sess, id, response).await;
+                    false
+                }
                 Op::UserInputAnswer { id, response } => {
                     handlers::request_user_input_response(&sess, id, response).await;
                     false
@@ -3988,6 +4933,14 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
                     handlers::compact(&sess, sub.id.clone()).await;
                     false
                 }
+                Op::PinTurn { turn_id } => {
+                    handlers::pin_turn(&sess, sub.id.clone(), turn_id).await;
+                    false
+                }
+                Op::UnpinTurn { turn_id } => {
+                    sess.unpin_turn(&turn_id).await;
+                    false
+                }
                 Op::DropMemories => {
                     handlers::drop_memories(&sess, &config, sub.id.clone()).await;
                     false
@@ -4000,6 +4953,42 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
                     handlers::thread_rollback(&sess, sub.id.clone(), num_turns).await;
                     false
                 }
+                Op::Checkpoint => {
+                    handlers::checkpoint(&sess, sub.id.clone()).await;
+                    false
+                }
+                Op::BranchFromCheckpoint { checkpoint_id } => {
+                    handlers::branch_from_checkpoint(&sess, sub.id.clone(), checkpoint_id).await;
+                    false
+                }
+                Op::GetCostSummary => {
+                    handlers::get_cost_summary(&sess, sub.id.clone()).await;
+                    false
+                }
+                Op::GetFeatures => {
+                    handlers::get_features(&sess, sub.id.clone()).await;
+                    false
+                }
+                Op::GetTurnDiff => {
+                    handlers::get_turn_diff(&sess, sub.id.clone()).await;
+                    false
+                }
+                Op::StartSessionShare { bind_address } => {
+                    handlers::start_session_share(&sess, sub.id.clone(), bind_address).await;
+                    false
+                }
+                Op::StopSessionShare => {
+                    sess.services.session_share.stop();
+                    false
+                }
+                Op::SetTokenBudget {
+                    max_tokens_per_turn,
+                    max_tokens_per_session,
+                } => {
+                    handlers::set_token_budget(&sess, max_tokens_per_turn, max_tokens_per_session)
+                        .await;
+                    false
+                }
                 Op::SetThreadName { name } => {
                     handlers::set_thread_name(&sess, sub.id.clone(), name).await;
                     false
@@ -4043,14 +5032,19 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
     debug!("Agent loop exited");
 }
 
-fn submission_dispatch_span(sub: &Submission) -> tracing::Span {
+/// Span for handling `sub`, parented to the submission's trace context or,
+/// failing that, to `session_trace`.
+fn submission_dispatch_span(
+    sub: &Submission,
+    session_trace: Option<&W3cTraceContext>,
+) -> tracing::Span {
     let dispatch_span = match &sub.op {
         Op::RealtimeConversationAudio(_) => {
             debug_span!("submission_dispatch", submission.id = sub.id.as_str())
         }
         _ => info_span!("submission_dispatch", submission.id = sub.id.as_str()),
     };
-    if let Some(trace) = sub.trace.as_ref()
+    if let Some(trace) = sub.trace.as_ref().or(session_trace)
         && !set_parent_from_w3c_trace_context(&dispatch_span, trace)
     {
         warn!(
@@ -4068,8 +5062,12 @@ mod handlers {
     use crate::codex::SteerInputError;
 
     use crate::codex::spawn_review_thread;
+    use crate::compact::compaction_prompt;
     use crate::config::Config;
 
+    use crate::checkpoint::CheckpointId;
+    use crate::features::Feature;
+    use crate::features::resolve_features;
     use crate::mcp::auth::compute_auth_statuses;
     use crate::mcp::collect_mcp_snapshot_from_manager;
     use crate::review_prompts::resolve_review_request;
@@ -4080,7 +5078,10 @@ mod handlers {
     use crate::tasks::UserShellCommandMode;
     use crate::tasks::UserShellCommandTask;
     use crate::tasks::execute_user_shell_command;
+    use crate::unified_exec::UnifiedExecError;
     use codex_protocol::custom_prompts::CustomPrompt;
+    use codex_protocol::protocol::BranchedFromCheckpointEvent;
+    use codex_protocol::protocol::CheckpointCreatedEvent;
     use codex_protocol::protocol::CodexErrorInfo;
     use codex_protocol::protocol::ErrorEvent;
     use codex_protocol::protocol::Event;
@@ -4090,6 +5091,7 @@ mod handlers {
     use codex_protocol::protocol::ListSkillsResponseEvent;
     use codex_protocol::protocol::McpServerRefreshConfig;
     use codex_protocol::protocol::Op;
+    use codex_protocol::protocol::PatchReviewResponse;
     use codex_protocol::protocol::RemoteSkillDownloadedEvent;
     use codex_protocol::protocol::RemoteSkillHazelnutScope;
     use codex_protocol::protocol::RemoteSkillProductSurface;
@@ -4097,10 +5099,12 @@ mod handlers {
     use codex_protocol::protocol::ReviewDecision;
     use codex_protocol::protocol::ReviewRequest;
     use codex_protocol::protocol::RolloutItem;
+    use codex_protocol::protocol::SessionSettingsUpdatedEvent;
     use codex_protocol::protocol::SkillsListEntry;
     use codex_protocol::protocol::ThreadNameUpdatedEvent;
     use codex_protocol::protocol::ThreadRolledBackEvent;
     use codex_protocol::protocol::TurnAbortReason;
+    use codex_protocol::protocol::TurnDiffEvent;
     use codex_protocol::protocol::WarningEvent;
     use codex_protocol::request_user_input::RequestUserInputResponse;
 
@@ -4127,6 +5131,56 @@ mod handlers {
         sess.close_unified_exec_processes().await;
     }
 
+    pub async fn terminal_input(sess: &Session, sub_id: String, process_id: String, data: String) {
+        let result = sess
+            .services
+            .unified_exec_manager
+            .write_terminal_input(&process_id, &data)
+            .await;
+        send_terminal_error(sess, sub_id, result).await;
+    }
+
+    pub async fn terminal_resize(
+        sess: &Session,
+        sub_id: String,
+        process_id: String,
+        rows: u16,
+        cols: u16,
+    ) {
+        let result = sess
+            .services
+            .unified_exec_manager
+            .resize_terminal(&process_id, codex_utils_pty::TerminalSize { rows, cols })
+            .await;
+        send_terminal_error(sess, sub_id, result).await;
+    }
+
+    pub async fn terminal_detach(sess: &Session, sub_id: String, process_id: String) {
+        let result = sess
+            .services
+            .unified_exec_manager
+            .detach_terminal(&process_id)
+            .await;
+        send_terminal_error(sess, sub_id, result).await;
+    }
+
+    async fn send_terminal_error(
+        sess: &Session,
+        sub_id: String,
+        result: Result<(), UnifiedExecError>,
+    ) {
+        if let Err(err) = result {
+            sess.send_event_raw(Event {
+                id: sub_id,
+                msg: EventMsg::Error(ErrorEvent {
+                    message: err.to_string(),
+                    codex_error_info: Some(CodexErrorInfo::BadRequest),
+                }),
+            })
+            .await;
+        }
+    }
+
     pub async fn override_turn_context(
         sess: &Session,
         sub_id: String,
@@ -4141,7 +5195,27 @@ mod handlers {
                 }),
             })
             .await;
+            return;
         }
+
+        // Report the resulting settings rather than echoing the request, so
+        // clients see values filled in from the collaboration mode as well.
+        let event = {
+            let state = sess.state.lock().await;
+            let session_configuration = &state.session_configuration;
+            SessionSettingsUpdatedEvent {
+                model: session_configuration.collaboration_mode.model().to_string(),
+                reasoning_effort: session_configuration.collaboration_mode.reasoning_effort(),
+                approval_policy: session_configuration.approval_policy.value(),
+                sandbox_policy: session_configuration.sandbox_policy.get().clone(),
+                cwd: session_configuration.cwd.clone(),
+            }
+        };
+        sess.send_event_raw(Event {
+            id: sub_id,
+            msg: EventMsg::SessionSettingsUpdated(event),
+        })
+        .await;
     }
 
     pub async fn user_input_or_turn(sess: &Arc<Session>, sub_id: String, op: Op) {
@@ -4336,6 +5410,10 @@ mod handlers {
         }
     }
 
+    pub async fn patch_review(sess: &Arc<Session>, id: String, response: PatchReviewResponse) {
+        sess.notify_patch_review(&id, response).await;
+    }
+
     pub async fn request_user_input_response(
         sess: &Arc<Session>,
         id: String,
@@ -4428,6 +5506,64 @@ mod handlers {
         sess.send_event_raw(event).await;
     }
 
+    pub async fn get_cost_summary(sess: &Session, sub_id: String) {
+        let summary = sess.state.lock().await.cost_tracker.summary();
+        sess.send_event_raw(Event {
+            id: sub_id,
+            msg: EventMsg::CostUpdate(summary),
+        })
+        .await;
+    }
+
+    pub async fn get_features(sess: &Session, sub_id: String) {
+        let config = sess.get_config().await;
+        sess.send_event_raw(Event {
+            id: sub_id,
+            msg: EventMsg::FeaturesResolved(resolve_features(&config)),
+        })
+        .await;
+    }
+
+    pub async fn get_turn_diff(sess: &Session, sub_id: String) {
+        let turn_diff_event = match sess.turn_diff_tracker().await {
+            Some(tracker) => tracker.lock().await.turn_diff_event().ok().flatten(),
+            None => None,
+        };
+        sess.send_event_raw(Event {
+            id: sub_id,
+            msg: EventMsg::TurnDiff(turn_diff_event.unwrap_or_else(|| TurnDiffEvent {
+                unified_diff: String::new(),
+                root_diffs: Vec::new(),
+            })),
+        })
+        .await;
+    }
+
+    pub async fn start_session_share(sess: &Session, sub_id: String, bind_address: Option<String>) {
+        let config = sess.get_config().await;
+        let msg = if !config.features.enabled(Feature::SessionSharing) {
+            EventMsg::Error(ErrorEvent {
+                message: "session sharing is disabled; enable the `session_sharing` feature"
+                    .to_string(),
+                codex_error_info: Some(CodexErrorInfo::BadRequest),
+            })
+        } else {
+            match sess
+                .services
+                .session_share
+                .start(bind_address.as_deref())
+                .await
+            {
+                Ok(started) => EventMsg::SessionShareStarted(started),
+                Err(err) => EventMsg::Error(ErrorEvent {
+                    message: format!("failed to start session sharing: {err}"),
+                    codex_error_info: Some(CodexErrorInfo::Other),
+                }),
+            }
+        };
+        sess.send_event_raw(Event { id: sub_id, msg }).await;
+    }
+
     pub async fn list_custom_prompts(sess: &Session, sub_id: String) {
         let custom_prompts: Vec<CustomPrompt> =
             if let Some(dir) = crate::custom_prompts::default_prompts_dir() {
@@ -4580,7 +5716,7 @@ mod handlers {
         sess.spawn_task(
             Arc::clone(&turn_context),
             vec![UserInput::Text {
-                text: turn_context.compact_prompt().to_string(),
+                text: compaction_prompt(sess, &turn_context).await,
                 // Compaction prompt is synthesized; no UI element ranges to preserve.
                 text_elements: Vec::new(),
             }],
@@ -4760,6 +5896,71 @@ mod handlers {
         .await;
     }
 
+    pub async fn checkpoint(sess: &Arc<Session>, sub_id: String) {
+        let msg = match sess.checkpoint().await {
+            Ok(id) => EventMsg::CheckpointCreated(CheckpointCreatedEvent {
+                checkpoint_id: id.to_string(),
+            }),
+            Err(e) => EventMsg::Error(ErrorEvent {
+                message: format!("Failed to save checkpoint: {e}"),
+                codex_error_info: Some(CodexErrorInfo::Other),
+            }),
+        };
+        sess.send_event_raw(Event { id: sub_id, msg }).await;
+    }
+
+    pub async fn branch_from_checkpoint(
+        sess: &Arc<Session>,
+        sub_id: String,
+        checkpoint_id: String,
+    ) {
+        let Some(id) = CheckpointId::parse(&checkpoint_id) else {
+            let event = Event {
+                id: sub_id,
+                msg: EventMsg::Error(ErrorEvent {
+                    message: format!("Invalid checkpoint id: {checkpoint_id}"),
+                    codex_error_info: Some(CodexErrorInfo::BadRequest),
+                }),
+            };
+            sess.send_event_raw(event).await;
+            return;
+        };
+        let msg = match sess.branch_from(&id).await {
+            Ok(()) => {
+                EventMsg::BranchedFromCheckpoint(BranchedFromCheckpointEvent { checkpoint_id })
+            }
+            Err(e) => EventMsg::Error(ErrorEvent {
+                message: format!("Failed to branch from checkpoint: {e}"),
+                codex_error_info: Some(CodexErrorInfo::Other),
+            }),
+        };
+        sess.send_event_raw(Event { id: sub_id, msg }).await;
+    }
+
+    pub async fn pin_turn(sess: &Arc<Session>, sub_id: String, turn_id: String) {
+        if let Err(e) = sess.pin_turn(&turn_id).await {
+            let event = Event {
+                id: sub_id,
+                msg: EventMsg::Error(ErrorEvent {
+                    message: format!("Failed to pin turn: {e}"),
+                    codex_error_info: Some(CodexErrorInfo::BadRequest),
+                }),
+            };
+            sess.send_event_raw(event).await;
+        }
+    }
+
+    pub async fn set_token_budget(
+        sess: &Arc<Session>,
+        max_tokens_per_turn: Option<i64>,
+        max_tokens_per_session: Option<i64>,
+    ) {
+        let mut state = sess.state.lock().await;
+        state
+            .token_budget
+            .set_limits(max_tokens_per_turn, max_tokens_per_session);
+    }
+
     /// Persists the thread name in the session index, updates in-memory state, and emits
     /// a `ThreadNameUpdated` event on success.
     ///
@@ -4828,6 +6029,7 @@ mod handlers {
 
     pub async fn shutdown(sess: &Arc<Session>, sub_id: String) -> bool {
         sess.abort_all_tasks(TurnAbortReason::Interrupted).await;
+        sess.services.session_share.stop();
         let _ = sess.conversation.shutdown().await;
         sess.services
             .unified_exec_manager
@@ -4939,7 +6141,16 @@ async fn spawn_review_thread(
     })
     .with_web_search_config(None)
     .with_allow_login_shell(config.permissions.allow_login_shell)
-    .with_agent_roles(config.agent_roles.clone());
+    .with_max_argument_size_bytes(config.tool_max_argument_size_bytes)
+    .with_configured_max_parallel_tool_calls(config.tool_max_parallel_calls)
+    .with_tool_fallbacks(config.tool_fallbacks.clone())
+    .with_agent_roles(config.agent_roles.clone())
+    .with_workspace_roots(parent_turn_context.workspace_roots.clone())
+    .with_tool_filter(ToolFilter {
+        allow: None,
+        deny: config.disabled_tools.clone(),
+    })
+    .with_tool_overrides(config.tool_overrides.clone());
 
     let review_prompt = resolved.prompt.clone();
     let provider = parent_turn_context.provider.clone();
@@ -5016,6 +6227,7 @@ async fn spawn_review_thread(
         windows_sandbox_level: parent_turn_context.windows_sandbox_level,
         shell_environment_policy: parent_turn_context.shell_environment_policy.clone(),
         cwd: parent_turn_context.cwd.clone(),
+        workspace_roots: parent_turn_context.workspace_roots.clone(),
         final_output_json_schema: None,
         codex_linux_sandbox_exe: parent_turn_context.codex_linux_sandbox_exe.clone(),
         tool_call_gate: Arc::new(ReadinessFlag::new()),
@@ -5025,6 +6237,8 @@ async fn spawn_review_thread(
         turn_metadata_state,
         turn_skills: TurnSkillsContext::new(parent_turn_context.turn_skills.outcome.clone()),
         turn_timing_state: Arc::new(TurnTimingState::default()),
+        streamed_output: Arc::default(),
+        tool_overrides: TurnToolOverrides::default(),
     };
 
     // Seed the child task with the review prompt as the initial user message.
@@ -5127,9 +6341,10 @@ pub(crate) async fn run_turn(
     if input.is_empty() {
         return None;
     }
+    let (system_notes, input) = split_system_notes(input);
 
     let model_info = turn_context.model_info.clone();
-    let auto_compact_limit = model_info.auto_compact_token_limit().unwrap_or(i64::MAX);
+    let mut auto_compact_limit = model_info.auto_compact_token_limit().unwrap_or(i64::MAX);
 
     let event = EventMsg::TurnStarted(TurnStartedEvent {
         turn_id: turn_context.sub_id.clone(),
@@ -5137,6 +6352,23 @@ pub(crate) async fn run_turn(
         collaboration_mode_kind: turn_context.collaboration_mode.mode,
     });
     sess.send_event(&turn_context, event).await;
+    let budget_status = {
+        let mut state = sess.state.lock().await;
+        state.cost_tracker.start_turn();
+        let session_tokens = session_token_spend(&state);
+        state.token_budget.start_turn(session_tokens)
+    };
+    if let Some(message) = budget_status.message() {
+        sess.send_event(
+            &turn_context,
+            EventMsg::Error(ErrorEvent {
+                message: format!("{message} Raise the limit to start a new turn."),
+                codex_error_info: Some(CodexErrorInfo::Other),
+            }),
+        )
+        .await;
+        return None;
+    }
     // TODO(ccunningham): Pre-turn compaction runs before context updates and the
     // new user message are recorded. Estimate pending incoming items (context
     // diffs/full reinjection + user input) and trigger compaction preemptively
@@ -5289,10 +6521,12 @@ pub(crate) async fn run_turn(
     sess.merge_connector_selection(explicitly_enabled_connectors.clone())
         .await;
 
-    let initial_input_for_turn: ResponseInputItem = ResponseInputItem::from(input.clone());
-    let response_item: ResponseItem = initial_input_for_turn.clone().into();
-    sess.record_user_prompt_and_emit_turn_item(turn_context.as_ref(), &input, response_item)
-        .await;
+    if !input.is_empty() {
+        let initial_input_for_turn: ResponseInputItem = ResponseInputItem::from(input.clone());
+        let response_item: ResponseItem = initial_input_for_turn.into();
+        sess.record_user_prompt_and_emit_turn_item(turn_context.as_ref(), &input, response_item)
+            .await;
+    }
     // Track the previous-turn baseline from the regular user-turn path only so
     // standalone tasks (compact/shell/review/undo) cannot suppress future
     // model/realtime injections.
@@ -5311,18 +6545,33 @@ pub(crate) async fn run_turn(
             .await;
     }
 
+    let watched_file_items = sess.watched_file_change_items(&turn_context).await;
+    if !watched_file_items.is_empty() {
+        sess.record_conversation_items(&turn_context, &watched_file_items)
+            .await;
+    }
+
     sess.maybe_start_ghost_snapshot(Arc::clone(&turn_context), cancellation_token.child_token())
         .await;
     let mut last_agent_message: Option<String> = None;
     // Although from the perspective of codex.rs, TurnDiffTracker has the lifecycle of a Task which contains
     // many turns, from the perspective of the user, it is a single turn.
     let turn_diff_tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
+    sess.set_turn_diff_tracker(Arc::clone(&turn_diff_tracker))
+        .await;
     let mut server_model_warning_emitted_for_turn = false;
 
     // `ModelClientSession` is turn-scoped and caches WebSocket + sticky routing state, so we reuse
     // one instance across retries within this turn.
     let mut client_session =
         prewarmed_client_session.unwrap_or_else(|| sess.services.model_client.new_session());
+    // Sampling moves to the next configured provider once the current one
+    // stays unavailable after its own retries.
+    let mut provider_chain = ProviderChain::from_config(&turn_context.config);
+    let mut sampling_turn_context = Arc::clone(&turn_context);
+    let mut turn_timeout = TurnTimeout::from_config(&turn_context.config);
+    let mut output_schema_check = OutputSchemaCheck::from_turn(&turn_context);
+    let mut verify_loop = VerifyLoop::from_config(&turn_context.config);
 
     loop {
         // Note that pending_input would be something like a message the user
@@ -5355,11 +6604,20 @@ pub(crate) async fn run_turn(
             }
         }
 
+        if let Some(timeout) = turn_timeout.as_mut()
+            && timeout.expired()
+        {
+            timeout.start_wrap_up(&sess, &turn_context).await;
+        }
+
         // Construct the input that we will send to the model.
         let sampling_request_input: Vec<ResponseItem> = {
-            sess.clone_history()
+            let mut input = sess
+                .clone_history()
                 .await
-                .for_prompt(&turn_context.model_info.input_modalities)
+                .for_prompt(&sampling_turn_context.model_info.input_modalities);
+            insert_system_notes(&mut input, &system_notes);
+            input
         };
 
         let sampling_request_input_messages = sampling_request_input
@@ -5371,9 +6629,13 @@ pub(crate) async fn run_turn(
             .map(|user_message| user_message.message())
             .collect::<Vec<String>>();
         let turn_metadata_header = turn_context.turn_metadata_state.current_header_value();
+        let sampling_cancellation_token = cancellation_token.child_token();
+        let _turn_timer = turn_timeout
+            .as_ref()
+            .map(|timeout| timeout.arm(&turn_context, sampling_cancellation_token.clone()));
         match run_sampling_request(
             Arc::clone(&sess),
-            Arc::clone(&turn_context),
+            Arc::clone(&sampling_turn_context),
             Arc::clone(&turn_diff_tracker),
             &mut client_session,
             turn_metadata_header.as_deref(),
@@ -5381,7 +6643,7 @@ pub(crate) async fn run_turn(
             &turn_enabled_connectors,
             skills_outcome,
             &mut server_model_warning_emitted_for_turn,
-            cancellation_token.child_token(),
+            sampling_cancellation_token,
         )
         .await
         {
@@ -5393,8 +6655,9 @@ pub(crate) async fn run_turn(
                 let total_usage_tokens = sess.get_total_token_usage().await;
                 let token_limit_reached = total_usage_tokens >= auto_compact_limit;
 
-                let estimated_token_count =
-                    sess.get_estimated_token_count(turn_context.as_ref()).await;
+                let estimated_token_count = sess
+                    .get_estimated_token_count(sampling_turn_context.as_ref())
+                    .await;
 
                 trace!(
                     turn_id = %turn_context.sub_id,
@@ -5406,11 +6669,26 @@ pub(crate) async fn run_turn(
                     "post sampling token usage"
                 );
 
+                if !check_token_budget(&sess, &sampling_turn_context, needs_follow_up).await {
+                    return None;
+                }
+
+                // The model was asked to stop calling tools once the turn
+                // timed out, so its wrap-up reply ends the turn either way.
+                if needs_follow_up
+                    && turn_timeout
+                        .as_ref()
+                        .is_some_and(TurnTimeout::is_wrapping_up)
+                {
+                    last_agent_message = sampling_request_last_agent_message;
+                    break;
+                }
+
                 // as long as compaction works well in getting us way below the token limit, we shouldn't worry about being in an infinite loop.
                 if token_limit_reached && needs_follow_up {
                     if run_auto_compact(
                         &sess,
-                        &turn_context,
+                        &sampling_turn_context,
                         InitialContextInjection::BeforeLastUserMessage,
                     )
                     .await
@@ -5421,6 +6699,33 @@ pub(crate) async fn run_turn(
                     continue;
                 }
 
+                if !needs_follow_up
+                    && let Some(verify) = verify_loop.as_mut()
+                    && verify
+                        .request_fix(
+                            &sess,
+                            &sampling_turn_context,
+                            &turn_diff_tracker,
+                            &cancellation_token,
+                        )
+                        .await
+                {
+                    continue;
+                }
+
+                if !needs_follow_up
+                    && let Some(check) = output_schema_check.as_mut()
+                    && check
+                        .request_correction(
+                            &sess,
+                            &sampling_turn_context,
+                            sampling_request_last_agent_message.as_deref(),
+                        )
+                        .await
+                {
+                    continue;
+                }
+
                 if !needs_follow_up {
                     last_agent_message = sampling_request_last_agent_message;
                     let hook_outcomes = sess
@@ -5486,6 +6791,13 @@ pub(crate) async fn run_turn(
                 continue;
             }
             Err(CodexErr::TurnAborted) => {
+                // Cancelled by the turn timer rather than the user: the next
+                // iteration asks the model to wrap up.
+                if !cancellation_token.is_cancelled()
+                    && turn_timeout.as_ref().is_some_and(TurnTimeout::expired)
+                {
+                    continue;
+                }
                 // Aborted turn is reported via a different event.
                 break;
             }
@@ -5506,6 +6818,31 @@ pub(crate) async fn run_turn(
                 break;
             }
             Err(e) => {
+                if e.is_provider_outage()
+                    && let Some(fallback) = provider_chain.next_fallback()
+                {
+                    warn!(
+                        "model provider {} unavailable, falling back to {}: {e:#}",
+                        sampling_turn_context.config.model_provider_id, fallback.provider_id
+                    );
+                    let fallback_context = sampling_turn_context
+                        .with_provider_fallback(&fallback, &sess.services.models_manager)
+                        .await;
+                    client_session = sess.model_client_for(&fallback_context).new_session();
+                    auto_compact_limit = fallback_context
+                        .model_info
+                        .auto_compact_token_limit()
+                        .unwrap_or(i64::MAX);
+                    let event = EventMsg::ProviderFellBack(ProviderFellBackEvent {
+                        from_provider: sampling_turn_context.config.model_provider_id.clone(),
+                        to_provider: fallback.provider_id,
+                        model: fallback_context.model_info.slug.clone(),
+                        reason: e.to_string(),
+                    });
+                    sess.send_event(&turn_context, event).await;
+                    sampling_turn_context = Arc::new(fallback_context);
+                    continue;
+                }
                 info!("Turn error: {e:#}");
                 let event = EventMsg::Error(e.to_error_event(None));
                 sess.send_event(&turn_context, event).await;
@@ -5515,9 +6852,74 @@ pub(crate) async fn run_turn(
         }
     }
 
+    let changed_paths = turn_diff_tracker.lock().await.changed_paths();
+    sess.acknowledge_own_file_changes(changed_paths).await;
+
     last_agent_message
 }
 
+/// Counts the estimated cost of a model response, in micro-USD because
+/// counters are integers. The session telemetry tags it with the model; the
+/// per-feature counter attributes it to each opt-in feature that is enabled.
+fn record_cost_metrics(turn_context: &TurnContext, cost_usd: f64) {
+    let cost_micros = (cost_usd * 1_000_000.0).round() as i64;
+    if cost_micros <= 0 {
+        return;
+    }
+    turn_context.session_telemetry.counter(
+        "codex.cost.usd_micros",
+        cost_micros,
+        &[("provider", turn_context.provider.name.as_str())],
+    );
+    for feature in turn_context.features.enabled_features() {
+        if !feature.default_enabled() {
+            turn_context.session_telemetry.counter(
+                "codex.cost.usd_micros_by_feature",
+                cost_micros,
+                &[("feature", feature.key())],
+            );
+        }
+    }
+}
+
+fn session_token_spend(state: &SessionState) -> i64 {
+    state
+        .token_info()
+        .map_or(0, |info| info.total_token_usage.total_tokens)
+}
+
+/// Checks the turn's spend after a model request. Emits a warning as a limit
+/// nears, and an error once one is exceeded while the turn still has work to
+/// do, in which case it returns `false` and the turn stops.
+async fn check_token_budget(
+    sess: &Session,
+    turn_context: &TurnContext,
+    needs_follow_up: bool,
+) -> bool {
+    let status = {
+        let mut state = sess.state.lock().await;
+        let session_tokens = session_token_spend(&state);
+        state.token_budget.check(session_tokens)
+    };
+    let Some(message) = status.message() else {
+        return true;
+    };
+    if needs_follow_up && matches!(status, TokenBudgetStatus::Exhausted { .. }) {
+        sess.send_event(
+            turn_context,
+            EventMsg::Error(ErrorEvent {
+                message,
+                codex_error_info: Some(CodexErrorInfo::Other),
+            }),
+        )
+        .await;
+        return false;
+    }
+    sess.send_event(turn_context, EventMsg::Warning(WarningEvent { message }))
+        .await;
+    true
+}
+
 async fn run_pre_sampling_compact(
     sess: &Arc<Session>,
     turn_context: &Arc<TurnContext>,
@@ -5591,7 +6993,7 @@ async fn run_auto_compact(
     turn_context: &Arc<TurnContext>,
     initial_context_injection: InitialContextInjection,
 ) -> CodexResult<()> {
-    if should_use_remote_compact_task(&turn_context.provider) {
+    if compact_strategy(turn_context) == CompactStrategy::Remote {
         run_inline_remote_auto_compact_task(
             Arc::clone(sess),
             Arc::clone(turn_context),
@@ -5765,16 +7167,63 @@ fn codex_apps_connector_id(tool: &crate::mcp_connection_manager::ToolInfo) -> Op
     tool.connector_id.as_deref()
 }
 
+/// Separates [`UserInput::SystemNote`]s from the rest of the turn's input and
+/// renders each as a system message.
+fn split_system_notes(input: Vec<UserInput>) -> (Vec<ResponseItem>, Vec<UserInput>) {
+    let mut notes = Vec::new();
+    let mut rest = Vec::with_capacity(input.len());
+    for item in input {
+        match item {
+            UserInput::SystemNote { text } => notes.push(ResponseItem::Message {
+                id: None,
+                role: "system".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: format!("{SYSTEM_NOTE_PREFIX}{text}"),
+                }],
+                end_turn: None,
+                phase: None,
+            }),
+            item => rest.push(item),
+        }
+    }
+    (notes, rest)
+}
+
+/// Places the turn's system notes just before its latest user message. The
+/// notes are never recorded, so they drop out of the next turn's prompt.
+fn insert_system_notes(input: &mut Vec<ResponseItem>, notes: &[ResponseItem]) {
+    if notes.is_empty() {
+        return;
+    }
+    let position = input
+        .iter()
+        .rposition(|item| matches!(parse_turn_item(item), Some(TurnItem::UserMessage(_))))
+        .unwrap_or(input.len());
+    input.splice(position..position, notes.iter().cloned());
+}
+
 fn build_prompt(
     input: Vec<ResponseItem>,
     router: &ToolRouter,
     turn_context: &TurnContext,
-    base_instructions: BaseInstructions,
+    mut base_instructions: BaseInstructions,
 ) -> Prompt {
+    let capabilities = turn_context.model_capabilities();
+    let (input, tools) = if capabilities.supports_tool_use {
+        (input, router.specs())
+    } else {
+        // The backend takes no tool definitions, so describe the tools in the
+        // instructions and replay earlier calls as plain messages.
+        if let Some(tools_instructions) = emulated_tools_instructions(&router.specs()) {
+            base_instructions.text.push_str("\n\n");
+            base_instructions.text.push_str(&tools_instructions);
+        }
+        (lower_tool_history(input), Vec::new())
+    };
     Prompt {
         input,
-        tools: router.specs(),
-        parallel_tool_calls: turn_context.model_info.supports_parallel_tool_calls,
+        tools,
+        parallel_tool_calls: capabilities.supports_parallel_tool_calls,
         base_instructions,
         personality: turn_context.personality,
         output_schema: turn_context.final_output_json_schema.clone(),
@@ -5821,7 +7270,13 @@ async fn run_sampling_request(
     );
     let mut retries = 0;
     loop {
-        let err = match try_run_sampling_request(
+        if let Some(retry_after) = client_session.provider_retry_after() {
+            return Err(CodexErr::ProviderUnhealthy {
+                provider: turn_context.config.model_provider_id.clone(),
+                retry_after,
+            });
+        }
+        let result = try_run_sampling_request(
             Arc::clone(&router),
             Arc::clone(&sess),
             Arc::clone(&turn_context),
@@ -5832,8 +7287,11 @@ async fn run_sampling_request(
             &prompt,
             cancellation_token.child_token(),
         )
-        .await
-        {
+        .await;
+        if !result.as_ref().is_err_and(CodexErr::is_provider_outage) {
+            client_session.record_provider_success();
+        }
+        let err = match result {
             Ok(output) => {
                 return Ok(output);
             }
@@ -5851,6 +7309,24 @@ async fn run_sampling_request(
             Err(err) => err,
         };
 
+        // Stop retrying once the provider's circuit breaker opens, so the
+        // turn can move on to a fallback provider or fail fast.
+        if err.is_provider_outage()
+            && let Some(breaker) = client_session.record_provider_failure()
+        {
+            sess.send_event(
+                &turn_context,
+                EventMsg::ProviderUnhealthy(ProviderUnhealthyEvent {
+                    provider: turn_context.config.model_provider_id.clone(),
+                    consecutive_failures: breaker.failure_threshold,
+                    retry_after_seconds: breaker.open_duration.as_secs(),
+                    reason: err.to_string(),
+                }),
+            )
+            .await;
+            return Err(err);
+        }
+
         if !err.is_retryable() {
             return Err(err);
         }
@@ -5877,9 +7353,9 @@ async fn run_sampling_request(
             retries += 1;
             let delay = match &err {
                 CodexErr::Stream(_, requested_delay) => {
-                    requested_delay.unwrap_or_else(|| backoff(retries))
+                    requested_delay.unwrap_or_else(|| turn_context.provider.retry_delay(retries))
                 }
-                _ => backoff(retries),
+                _ => turn_context.provider.retry_delay(retries),
             };
             warn!(
                 "stream disconnected - retrying sampling request ({retries}/{max_retries} in {delay:?})...",
@@ -5980,7 +7456,7 @@ async fn built_tools(
             connectors::filter_codex_apps_tools_by_policy(selected_mcp_tools, &turn_context.config);
     }
 
-    Ok(Arc::new(ToolRouter::from_config(
+    let mut router = ToolRouter::from_config(
         &turn_context.tools_config,
         has_mcp_servers.then(|| {
             mcp_tools
@@ -5990,7 +7466,21 @@ async fn built_tools(
         }),
         app_tools,
         turn_context.dynamic_tools.as_slice(),
-    )))
+    );
+    router.set_mcp_servers(turn_context.config.mcp_servers.get().clone());
+    router.set_read_only(
+        turn_context.collaboration_mode.mode == ModeKind::Plan
+            && turn_context.features.enabled(Feature::ReadOnlyPlanMode),
+    );
+    router.set_call_ids(sess.services.tool_call_ids.clone());
+    router.set_call_archive(sess.services.tool_call_archive.clone());
+    sess.services.tool_middleware.apply_to(&mut router);
+    if let Some(chaos) = &sess.services.tool_chaos {
+        router.set_chaos(Arc::clone(chaos));
+    }
+    Ok(Arc::new(
+        router.clone_for_turn(turn_context.tool_overrides.clone()),
+    ))
 }
 
 #[derive(Debug)]
@@ -6188,6 +7678,7 @@ fn realtime_text_for_event(msg: &EventMsg) -> Option<String> {
         | EventMsg::TurnStarted(_)
         | EventMsg::TurnComplete(_)
         | EventMsg::TokenCount(_)
+        | EventMsg::CostUpdate(_)
         | EventMsg::UserMessage(_)
         | EventMsg::AgentMessageDelta(_)
         | EventMsg::AgentReasoning(_)
@@ -6199,6 +7690,9 @@ fn realtime_text_for_event(msg: &EventMsg) -> Option<String> {
         | EventMsg::ThreadNameUpdated(_)
         | EventMsg::McpStartupUpdate(_)
         | EventMsg::McpStartupComplete(_)
+        | EventMsg::McpServerStatus(_)
+        | EventMsg::ProviderFellBack(_)
+        | EventMsg::ProviderUnhealthy(_)
         | EventMsg::McpToolCallBegin(_)
         | EventMsg::McpToolCallEnd(_)
         | EventMsg::WebSearchBegin(_)
@@ -6216,14 +7710,18 @@ fn realtime_text_for_event(msg: &EventMsg) -> Option<String> {
         | EventMsg::RequestUserInput(_)
         | EventMsg::DynamicToolCallRequest(_)
         | EventMsg::DynamicToolCallResponse(_)
+        | EventMsg::ToolCallStart(_)
+        | EventMsg::ToolCallQueued(_)
         | EventMsg::ElicitationRequest(_)
         | EventMsg::ApplyPatchApprovalRequest(_)
+        | EventMsg::PatchReviewRequest(_)
         | EventMsg::DeprecationNotice(_)
         | EventMsg::BackgroundEvent(_)
         | EventMsg::UndoStarted(_)
         | EventMsg::UndoCompleted(_)
         | EventMsg::StreamError(_)
         | EventMsg::TurnDiff(_)
+        | EventMsg::TurnDiffUpdated(_)
         | EventMsg::GetHistoryEntryResponse(_)
         | EventMsg::McpListToolsResponse(_)
         | EventMsg::ListCustomPromptsResponse(_)
@@ -6233,6 +7731,22 @@ fn realtime_text_for_event(msg: &EventMsg) -> Option<String> {
         | EventMsg::SkillsUpdateAvailable
         | EventMsg::PlanUpdate(_)
         | EventMsg::TurnAborted(_)
+        | EventMsg::TurnCancelled(_)
+        | EventMsg::TurnTimedOut(_)
+        | EventMsg::TurnCheckpoint(_)
+        | EventMsg::ToolCacheHit(_)
+        | EventMsg::ChaosFaultInjected(_)
+        | EventMsg::McpToolCallOutputDelta(_)
+        | EventMsg::ToolOutputRedacted(_)
+        | EventMsg::SecretsRedacted(_)
+        | EventMsg::SessionSettingsUpdated(_)
+        | EventMsg::FeaturesResolved(_)
+        | EventMsg::SessionShareStarted(_)
+        | EventMsg::CompactionStarted(_)
+        | EventMsg::CompactionFinished(_)
+        | EventMsg::ContextWindowStatus(_)
+        | EventMsg::CheckpointCreated(_)
+        | EventMsg::BranchedFromCheckpoint(_)
         | EventMsg::ShutdownComplete
         | EventMsg::EnteredReviewMode(_)
         | EventMsg::ExitedReviewMode(_)
@@ -6345,6 +7859,11 @@ async fn emit_streamed_assistant_text_delta(
     if parsed.visible_text.is_empty() {
         return;
     }
+    turn_context
+        .streamed_output
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .push_str(&parsed.visible_text);
     let event = AgentMessageContentDeltaEvent {
         thread_id: sess.conversation_id.to_string(),
         turn_id: turn_context.sub_id.clone(),
@@ -6515,16 +8034,17 @@ async fn handle_assistant_item_done_in_plan_mode(
 }
 
 async fn drain_in_flight(
-    in_flight: &mut FuturesOrdered<BoxFuture<'static, CodexResult<ResponseInputItem>>>,
+    in_flight: &mut FuturesOrdered<BoxFuture<'static, CodexResult<Option<ResponseInputItem>>>>,
     sess: Arc<Session>,
     turn_context: Arc<TurnContext>,
 ) -> CodexResult<()> {
     while let Some(res) = in_flight.next().await {
         match res {
-            Ok(response_input) => {
+            Ok(Some(response_input)) => {
                 sess.record_conversation_items(&turn_context, &[response_input.into()])
                     .await;
             }
+            Ok(None) => {}
             Err(err) => {
                 error_or_panic(format!("in-flight tool future failed during drain: {err}"));
             }
@@ -6534,11 +8054,16 @@ async fn drain_in_flight(
 }
 
 #[allow(clippy::too_many_arguments)]
-#[instrument(level = "trace",
+#[instrument(level = "info",
+    name = "model_request",
     skip_all,
     fields(
         turn_id = %turn_context.sub_id,
-        model = %turn_context.model_info.slug
+        model = %turn_context.model_info.slug,
+        provider = %turn_context.provider.name,
+        response_id = field::Empty,
+        input_tokens = field::Empty,
+        output_tokens = field::Empty
     )
 )]
 async fn try_run_sampling_request(
@@ -6580,7 +8105,7 @@ async fn try_run_sampling_request(
         Arc::clone(&turn_context),
         Arc::clone(&turn_diff_tracker),
     );
-    let mut in_flight: FuturesOrdered<BoxFuture<'static, CodexResult<ResponseInputItem>>> =
+    let mut in_flight: FuturesOrdered<BoxFuture<'static, CodexResult<Option<ResponseInputItem>>>> =
         FuturesOrdered::new();
     let mut needs_follow_up = false;
     let mut last_agent_message: Option<String> = None;
@@ -6589,6 +8114,7 @@ async fn try_run_sampling_request(
     let plan_mode = turn_context.collaboration_mode.mode == ModeKind::Plan;
     let mut assistant_message_stream_parsers = AssistantMessageStreamParsers::new(plan_mode);
     let mut plan_mode_state = plan_mode.then(|| PlanModeStreamState::new(&turn_context.sub_id));
+    let mut checkpointer = TurnCheckpointer::new(&turn_context);
     let receiving_span = trace_span!("receiving_stream");
     let outcome: CodexResult<SamplingRequestResult> = loop {
         let handle_responses = trace_span!(
@@ -6672,6 +8198,7 @@ async fn try_run_sampling_request(
                     last_agent_message = Some(agent_message);
                 }
                 needs_follow_up |= output_result.needs_follow_up;
+                checkpointer.mark_output_recorded(&turn_context);
             }
             ResponseEvent::OutputItemAdded(item) => {
                 if let Some(turn_item) =
@@ -6748,9 +8275,15 @@ async fn try_run_sampling_request(
                 sess.services.models_manager.refresh_if_new_etag(etag).await;
             }
             ResponseEvent::Completed {
-                response_id: _,
+                response_id,
                 token_usage,
             } => {
+                let request_span = tracing::Span::current();
+                request_span.record("response_id", response_id.as_str());
+                if let Some(usage) = token_usage.as_ref() {
+                    request_span.record("input_tokens", usage.input_tokens);
+                    request_span.record("output_tokens", usage.output_tokens);
+                }
                 flush_assistant_text_segments_all(
                     &sess,
                     &turn_context,
@@ -6784,6 +8317,7 @@ async fn try_run_sampling_request(
                             parsed,
                         )
                         .await;
+                        checkpointer.checkpoint_output(&sess, &turn_context).await;
                     } else {
                         let event = AgentMessageContentDeltaEvent {
                             thread_id: sess.conversation_id.to_string(),
@@ -6860,12 +8394,15 @@ async fn try_run_sampling_request(
     drain_in_flight(&mut in_flight, sess.clone(), turn_context.clone()).await?;
 
     if should_emit_turn_diff {
-        let unified_diff = {
+        let turn_diff_event = {
             let mut tracker = turn_diff_tracker.lock().await;
-            tracker.get_unified_diff()
+            tracker.turn_diff_event()
         };
-        if let Ok(Some(unified_diff)) = unified_diff {
-            let msg = EventMsg::TurnDiff(TurnDiffEvent { unified_diff });
+        if let Ok(Some(turn_diff_event)) = turn_diff_event {
+            checkpointer
+                .checkpoint_diff(&sess, &turn_context, turn_diff_event.unified_diff.clone())
+                .await;
+            let msg = EventMsg::TurnDiff(turn_diff_event);
             sess.clone().send_event(&turn_context, msg).await;
         }
     }
@@ -6882,6 +8419,7 @@ pub(super) fn get_last_assistant_message_from_turn(responses: &[ResponseItem]) -
     None
 }
 
+use crate::memories::facts::build_project_memory_instructions;
 use crate::memories::prompts::build_memory_tool_developer_instructions;
 #[cfg(test)]
 pub(crate) use tests::make_session_and_context;
@@ -0,0 +1,296 @@
+use super::*;
+
+/// Correction requests sent when a final message does not match the turn's
+/// output schema, before the mismatch is reported and the turn ends anyway.
+const MAX_OUTPUT_SCHEMA_RETRIES: u32 = 2;
+
+/// Checks the final message of a turn against `final_output_json_schema`.
+///
+/// The schema is also sent to the model as a structured-output format, but
+/// providers without structured outputs ignore it, so the reply is validated
+/// here and the model is asked to correct it when it does not match.
+pub(super) struct OutputSchemaCheck {
+    schema: Value,
+    retries: u32,
+}
+
+impl OutputSchemaCheck {
+    /// Returns `None` when the turn has no output schema.
+    pub(super) fn from_turn(turn_context: &TurnContext) -> Option<Self> {
+        Some(Self {
+            schema: turn_context.final_output_json_schema.clone()?,
+            retries: 0,
+        })
+    }
+
+    /// Validates the final message. When it does not match and retries
+    /// remain, asks the model for a corrected reply and returns `true` so the
+    /// turn keeps going. Once retries run out the mismatch is reported as a
+    /// warning and the message is kept.
+    pub(super) async fn request_correction(
+        &mut self,
+        sess: &Session,
+        turn_context: &TurnContext,
+        message: Option<&str>,
+    ) -> bool {
+        let Err(error) = validate_final_output(&self.schema, message) else {
+            return false;
+        };
+        if self.retries >= MAX_OUTPUT_SCHEMA_RETRIES {
+            warn!(
+                turn_id = %turn_context.sub_id,
+                error = %error,
+                "final message does not match the output schema"
+            );
+            sess.send_event(
+                turn_context,
+                EventMsg::Warning(WarningEvent {
+                    message: format!("The final message does not match the output schema: {error}"),
+                }),
+            )
+            .await;
+            return false;
+        }
+        self.retries += 1;
+        warn!(
+            turn_id = %turn_context.sub_id,
+            error = %error,
+            retry = self.retries,
+            "final message does not match the output schema; asking for a correction"
+        );
+        let message: ResponseItem = DeveloperInstructions::new(format!(
+            "Your final message must be a single JSON value matching the requested output schema, with no other text. It did not match: {error}. Reply again with only the corrected JSON."
+        ))
+        .into();
+        sess.record_conversation_items(turn_context, std::slice::from_ref(&message))
+            .await;
+        true
+    }
+}
+
+/// Parses a final message as JSON and checks it against `schema`.
+///
+/// Supports the subset of JSON Schema used for structured outputs: `type`,
+/// `enum`, `const`, `properties`, `required`, `additionalProperties`,
+/// `items`, `anyOf`, `oneOf`, `allOf` and local `$ref`s. Other keywords are
+/// ignored.
+pub(super) fn validate_final_output(
+    schema: &Value,
+    message: Option<&str>,
+) -> Result<Value, String> {
+    let message = message
+        .map(str::trim)
+        .filter(|message| !message.is_empty())
+        .ok_or_else(|| "the final message is empty".to_string())?;
+    let value: Value = serde_json::from_str(message)
+        .map_err(|err| format!("the final message is not valid JSON ({err})"))?;
+    validate_value(schema, schema, &value, "$")?;
+    Ok(value)
+}
+
+fn validate_value(schema: &Value, root: &Value, value: &Value, path: &str) -> Result<(), String> {
+    let schema = match schema {
+        Value::Bool(true) => return Ok(()),
+        Value::Bool(false) => return Err(format!("{path} is not allowed")),
+        Value::Object(schema) => schema,
+        _ => return Ok(()),
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let target = reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer))
+            .ok_or_else(|| format!("unsupported schema reference `{reference}`"))?;
+        validate_value(target, root, value, path)?;
+    }
+
+    if let Some(expected) = schema.get("type") {
+        let types = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|name| has_type(value, name)) {
+            return Err(format!(
+                "{path} should be of type {}, got {}",
+                types.join(" or "),
+                type_name(value)
+            ));
+        }
+    }
+
+    if let Some(Value::Array(allowed)) = schema.get("enum")
+        && !allowed.contains(value)
+    {
+        return Err(format!(
+            "{path} should be one of {}",
+            Value::Array(allowed.clone())
+        ));
+    }
+    if let Some(expected) = schema.get("const")
+        && expected != value
+    {
+        return Err(format!("{path} should be {expected}"));
+    }
+
+    if let Some(Value::Array(options)) = schema.get("anyOf")
+        && !options
+            .iter()
+            .any(|option| validate_value(option, root, value, path).is_ok())
+    {
+        return Err(format!("{path} does not match any of the allowed schemas"));
+    }
+    if let Some(Value::Array(options)) = schema.get("oneOf") {
+        let matches = options
+            .iter()
+            .filter(|option| validate_value(option, root, value, path).is_ok())
+            .count();
+        if matches != 1 {
+            return Err(format!(
+                "{path} should match exactly one of the allowed schemas, matched {matches}"
+            ));
+        }
+    }
+    if let Some(Value::Array(options)) = schema.get("allOf") {
+        for option in options {
+            validate_value(option, root, value, path)?;
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for name in required.iter().filter_map(Value::as_str) {
+                    if !object.contains_key(name) {
+                        return Err(format!("{path} is missing required property `{name}`"));
+                    }
+                }
+            }
+            for (name, property) in object {
+                let property_path = format!("{path}.{name}");
+                match properties.and_then(|properties| properties.get(name)) {
+                    Some(property_schema) => {
+                        validate_value(property_schema, root, property, &property_path)?;
+                    }
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            return Err(format!("{path} has unexpected property `{name}`"));
+                        }
+                        Some(additional) => {
+                            validate_value(additional, root, property, &property_path)?;
+                        }
+                        None => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_value(item_schema, root, item, &format!("{path}[{index}]"))?;
+                }
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64()
+                || value.is_u64()
+                || value.as_f64().is_some_and(|number| number.fract() == 0.0)
+        }
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate_final_output;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn validates_structured_output_schemas() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "status": { "enum": ["ok", "failed"] },
+                "files": { "type": "array", "items": { "$ref": "#/$defs/file" } },
+                "summary": { "type": ["string", "null"] }
+            },
+            "required": ["status", "files"],
+            "additionalProperties": false,
+            "$defs": {
+                "file": {
+                    "type": "object",
+                    "properties": { "path": { "type": "string" }, "lines": { "type": "integer" } },
+                    "required": ["path"]
+                }
+            }
+        });
+
+        assert_eq!(
+            validate_final_output(
+                &schema,
+                Some(
+                    r#" {"status": "ok", "files": [{"path": "a.rs", "lines": 3}], "summary": null} "#
+                )
+            ),
+            Ok(json!({
+                "status": "ok",
+                "files": [{"path": "a.rs", "lines": 3}],
+                "summary": null
+            }))
+        );
+        assert_eq!(
+            validate_final_output(&schema, Some(r#"{"status": "ok"}"#)),
+            Err("$ is missing required property `files`".to_string())
+        );
+        assert_eq!(
+            validate_final_output(
+                &schema,
+                Some(r#"{"status": "ok", "files": [{"path": "a.rs", "lines": 1.5}]}"#)
+            ),
+            Err("$.files[0].lines should be of type integer, got number".to_string())
+        );
+        assert_eq!(
+            validate_final_output(&schema, Some(r#"{"status": "done", "files": []}"#)),
+            Err(r#"$.status should be one of ["ok","failed"]"#.to_string())
+        );
+        assert_eq!(
+            validate_final_output(
+                &schema,
+                Some(r#"{"status": "ok", "files": [], "extra": 1}"#)
+            ),
+            Err("$ has unexpected property `extra`".to_string())
+        );
+        assert!(validate_final_output(&schema, Some("Done! Here is the JSON")).is_err());
+        assert_eq!(
+            validate_final_output(&schema, None),
+            Err("the final message is empty".to_string())
+        );
+    }
+}
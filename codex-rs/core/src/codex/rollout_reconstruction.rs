@@ -240,7 +240,7 @@ impl Session {
                 RolloutItem::ResponseItem(response_item) => {
                     history.record_items(
                         std::iter::once(response_item),
-                        turn_context.truncation_policy,
+                        turn_context.history_truncation_policy(),
                     );
                 }
                 RolloutItem::Compacted(compacted) => {
@@ -0,0 +1,392 @@
+use super::*;
+
+use std::time::Instant;
+
+use codex_protocol::models::FunctionCallOutputPayload;
+
+use crate::contextual_user_message::TURN_ABORTED_OPEN_TAG;
+use crate::protocol::TurnAbortedEvent;
+use crate::protocol::TurnCheckpointEvent;
+
+/// Minimum time between checkpoints of streamed assistant text.
+const TURN_CHECKPOINT_INTERVAL: Duration = Duration::from_secs(2);
+
+const INTERRUPTED_TOOL_CALL_OUTPUT: &str = "aborted: Codex exited before this call completed";
+
+const TURN_INTERRUPTED_BY_EXIT_GUIDANCE: &str = "The previous turn was cut short because Codex exited unexpectedly. Tool calls that were still running were aborted and may have partially executed; verify current state before retrying.";
+
+/// Persists [`TurnCheckpointEvent`]s while a sampling request streams, so
+/// that [`Session::recover_interrupted_turn`] has something to work with.
+pub(super) struct TurnCheckpointer {
+    /// Length of `TurnContext::streamed_output` already covered by recorded
+    /// assistant messages.
+    recorded_output_len: usize,
+    last_checkpoint: Instant,
+}
+
+impl TurnCheckpointer {
+    pub(super) fn new(turn_context: &TurnContext) -> Self {
+        Self {
+            recorded_output_len: streamed_output_len(turn_context),
+            last_checkpoint: Instant::now(),
+        }
+    }
+
+    /// Marks the text streamed so far as recorded, once the item that
+    /// carried it has been written to history.
+    pub(super) fn mark_output_recorded(&mut self, turn_context: &TurnContext) {
+        self.recorded_output_len = streamed_output_len(turn_context);
+    }
+
+    /// Persists the unrecorded assistant text, at most once per
+    /// [`TURN_CHECKPOINT_INTERVAL`].
+    pub(super) async fn checkpoint_output(&mut self, sess: &Session, turn_context: &TurnContext) {
+        if self.last_checkpoint.elapsed() < TURN_CHECKPOINT_INTERVAL {
+            return;
+        }
+        let partial_agent_message = {
+            let streamed_output = turn_context
+                .streamed_output
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            streamed_output
+                .get(self.recorded_output_len..)
+                .filter(|partial| !partial.is_empty())
+                .map(str::to_string)
+        };
+        if partial_agent_message.is_none() {
+            return;
+        }
+        self.persist(sess, turn_context, partial_agent_message, None)
+            .await;
+    }
+
+    pub(super) async fn checkpoint_diff(
+        &mut self,
+        sess: &Session,
+        turn_context: &TurnContext,
+        unified_diff: String,
+    ) {
+        self.persist(sess, turn_context, None, Some(unified_diff))
+            .await;
+    }
+
+    async fn persist(
+        &mut self,
+        sess: &Session,
+        turn_context: &TurnContext,
+        partial_agent_message: Option<String>,
+        unified_diff: Option<String>,
+    ) {
+        self.last_checkpoint = Instant::now();
+        let checkpoint = TurnCheckpointEvent {
+            turn_id: turn_context.sub_id.clone(),
+            partial_agent_message,
+            unified_diff,
+        };
+        sess.persist_rollout_items(&[RolloutItem::EventMsg(EventMsg::TurnCheckpoint(checkpoint))])
+            .await;
+    }
+}
+
+fn streamed_output_len(turn_context: &TurnContext) -> usize {
+    turn_context
+        .streamed_output
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .len()
+}
+
+/// The newest turn in a rollout, when it started but never completed or
+/// aborted.
+#[derive(Debug, PartialEq)]
+pub(super) struct InterruptedTurn {
+    pub(super) turn_id: String,
+    /// Failure outputs for the tool calls that were still running.
+    pub(super) aborted_call_outputs: Vec<ResponseItem>,
+    pub(super) partial_agent_message: Option<String>,
+    pub(super) unified_diff: Option<String>,
+}
+
+pub(super) fn find_interrupted_turn(rollout_items: &[RolloutItem]) -> Option<InterruptedTurn> {
+    let (start, turn_id) = rollout_items
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(index, item)| match item {
+            RolloutItem::EventMsg(EventMsg::TurnStarted(event)) => {
+                Some((index, event.turn_id.clone()))
+            }
+            _ => None,
+        })?;
+
+    let mut pending_calls: Vec<ResponseItem> = Vec::new();
+    let mut partial_agent_message = None;
+    let mut unified_diff = None;
+    for item in &rollout_items[start + 1..] {
+        match item {
+            RolloutItem::EventMsg(EventMsg::TurnComplete(_) | EventMsg::TurnAborted(_)) => {
+                return None;
+            }
+            RolloutItem::EventMsg(EventMsg::TurnCheckpoint(checkpoint))
+                if checkpoint.turn_id == turn_id =>
+            {
+                if checkpoint.partial_agent_message.is_some() {
+                    partial_agent_message = checkpoint.partial_agent_message.clone();
+                }
+                if checkpoint.unified_diff.is_some() {
+                    unified_diff = checkpoint.unified_diff.clone();
+                }
+            }
+            RolloutItem::ResponseItem(ResponseItem::Message { role, .. })
+                if role == "assistant" =>
+            {
+                partial_agent_message = None;
+            }
+            RolloutItem::ResponseItem(
+                ResponseItem::FunctionCall { call_id, .. }
+                | ResponseItem::LocalShellCall {
+                    call_id: Some(call_id),
+                    ..
+                },
+            ) => pending_calls.push(ResponseItem::FunctionCallOutput {
+                call_id: call_id.clone(),
+                output: FunctionCallOutputPayload::from_text(
+                    INTERRUPTED_TOOL_CALL_OUTPUT.to_string(),
+                ),
+            }),
+            RolloutItem::ResponseItem(
+                ResponseItem::CustomToolCall { call_id, .. }
+                | ResponseItem::CustomToolCallWithAttachments { call_id, .. },
+            ) => pending_calls.push(ResponseItem::CustomToolCallOutput {
+                call_id: call_id.clone(),
+                output: FunctionCallOutputPayload::from_text(
+                    INTERRUPTED_TOOL_CALL_OUTPUT.to_string(),
+                ),
+            }),
+            RolloutItem::ResponseItem(
+                ResponseItem::FunctionCallOutput { call_id, .. }
+                | ResponseItem::CustomToolCallOutput { call_id, .. },
+            ) => pending_calls.retain(|pending| match pending {
+                ResponseItem::FunctionCallOutput {
+                    call_id: pending_id,
+                    ..
+                }
+                | ResponseItem::CustomToolCallOutput {
+                    call_id: pending_id,
+                    ..
+                } => pending_id != call_id,
+                _ => true,
+            }),
+            _ => {}
+        }
+    }
+
+    Some(InterruptedTurn {
+        turn_id,
+        aborted_call_outputs: pending_calls,
+        partial_agent_message,
+        unified_diff,
+    })
+}
+
+/// Paths named by the `diff --git` headers of `unified_diff`.
+fn changed_paths(unified_diff: &str) -> Vec<&str> {
+    unified_diff
+        .lines()
+        .filter_map(|line| line.strip_prefix("diff --git "))
+        .filter_map(|header| header.rsplit_once(" b/").map(|(_, path)| path))
+        .collect()
+}
+
+impl Session {
+    /// Closes out a turn that was still running when the previous process
+    /// exited. Pending tool calls get failure outputs instead of being
+    /// replayed, since they may already have had side effects; streamed
+    /// assistant text is kept; and the turn is recorded as aborted so it is
+    /// only recovered once.
+    pub(super) async fn recover_interrupted_turn(
+        &self,
+        turn_context: &TurnContext,
+        rollout_items: &[RolloutItem],
+    ) {
+        let Some(turn) = find_interrupted_turn(rollout_items) else {
+            return;
+        };
+        let aborted_calls = turn.aborted_call_outputs.len();
+        let mut items = turn.aborted_call_outputs;
+        if let Some(text) = turn.partial_agent_message {
+            items.push(ResponseItem::Message {
+                id: None,
+                role: "assistant".to_string(),
+                content: vec![ContentItem::OutputText { text }],
+                end_turn: None,
+                phase: None,
+            });
+        }
+        let mut guidance = TURN_INTERRUPTED_BY_EXIT_GUIDANCE.to_string();
+        let changed_paths = turn
+            .unified_diff
+            .as_deref()
+            .map(changed_paths)
+            .unwrap_or_default();
+        if !changed_paths.is_empty() {
+            guidance.push_str("\nFiles changed before the interruption:");
+            for path in &changed_paths {
+                guidance.push_str(&format!("\n- {path}"));
+            }
+        }
+        items.push(ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: format!("{TURN_ABORTED_OPEN_TAG}\n{guidance}\n</turn_aborted>"),
+            }],
+            end_turn: None,
+            phase: None,
+        });
+        self.record_conversation_items(turn_context, &items).await;
+
+        self.send_event(
+            turn_context,
+            EventMsg::TurnAborted(TurnAbortedEvent {
+                turn_id: Some(turn.turn_id),
+                reason: TurnAbortReason::Interrupted,
+            }),
+        )
+        .await;
+        let mut message =
+            "The previous turn was interrupted because Codex exited before it completed."
+                .to_string();
+        if aborted_calls > 0 {
+            message.push_str(&format!(
+                " {aborted_calls} running tool call(s) were aborted and may have partially executed."
+            ));
+        }
+        if !changed_paths.is_empty() {
+            message.push_str(&format!(
+                " {} file(s) had already been changed.",
+                changed_paths.len()
+            ));
+        }
+        self.send_event(turn_context, EventMsg::Warning(WarningEvent { message }))
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::InitialHistory;
+    use crate::protocol::ResumedHistory;
+    use crate::protocol::TurnCompleteEvent;
+    use codex_protocol::ThreadId;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn turn_started(turn_id: &str) -> RolloutItem {
+        RolloutItem::EventMsg(EventMsg::TurnStarted(TurnStartedEvent {
+            turn_id: turn_id.to_string(),
+            model_context_window: None,
+            collaboration_mode_kind: ModeKind::default(),
+        }))
+    }
+
+    fn function_call(call_id: &str) -> RolloutItem {
+        RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+            id: None,
+            name: "shell".to_string(),
+            arguments: "{}".to_string(),
+            call_id: call_id.to_string(),
+        })
+    }
+
+    fn checkpoint(partial: Option<&str>, diff: Option<&str>) -> RolloutItem {
+        RolloutItem::EventMsg(EventMsg::TurnCheckpoint(TurnCheckpointEvent {
+            turn_id: "turn-2".to_string(),
+            partial_agent_message: partial.map(str::to_string),
+            unified_diff: diff.map(str::to_string),
+        }))
+    }
+
+    #[test]
+    fn completed_turns_are_not_interrupted() {
+        let items = vec![
+            turn_started("turn-1"),
+            function_call("call-1"),
+            RolloutItem::EventMsg(EventMsg::TurnComplete(TurnCompleteEvent {
+                turn_id: "turn-1".to_string(),
+                last_agent_message: None,
+            })),
+        ];
+
+        assert_eq!(find_interrupted_turn(&items), None);
+        assert_eq!(find_interrupted_turn(&[]), None);
+    }
+
+    #[test]
+    fn unfinished_turn_aborts_pending_calls_and_keeps_checkpoints() {
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n";
+        let items = vec![
+            turn_started("turn-2"),
+            function_call("call-1"),
+            RolloutItem::ResponseItem(ResponseItem::FunctionCallOutput {
+                call_id: "call-1".to_string(),
+                output: FunctionCallOutputPayload::from_text("ok".to_string()),
+            }),
+            checkpoint(None, Some(diff)),
+            function_call("call-2"),
+            checkpoint(Some("Now I will"), None),
+        ];
+
+        assert_eq!(
+            find_interrupted_turn(&items),
+            Some(InterruptedTurn {
+                turn_id: "turn-2".to_string(),
+                aborted_call_outputs: vec![ResponseItem::FunctionCallOutput {
+                    call_id: "call-2".to_string(),
+                    output: FunctionCallOutputPayload::from_text(
+                        INTERRUPTED_TOOL_CALL_OUTPUT.to_string()
+                    ),
+                }],
+                partial_agent_message: Some("Now I will".to_string()),
+                unified_diff: Some(diff.to_string()),
+            })
+        );
+        assert_eq!(changed_paths(diff), vec!["src/lib.rs"]);
+    }
+
+    #[tokio::test]
+    async fn resume_closes_out_interrupted_turn_once() {
+        let (session, _turn_context) = make_session_and_context().await;
+        let mut rollout_items = vec![turn_started("turn-2"), function_call("call-1")];
+
+        session
+            .record_initial_history(InitialHistory::Resumed(ResumedHistory {
+                conversation_id: ThreadId::default(),
+                history: rollout_items.clone(),
+                rollout_path: PathBuf::from("/tmp/resume.jsonl"),
+            }))
+            .await;
+
+        let history = session.clone_history().await;
+        assert!(
+            history
+                .raw_items()
+                .contains(&ResponseItem::FunctionCallOutput {
+                    call_id: "call-1".to_string(),
+                    output: FunctionCallOutputPayload::from_text(
+                        INTERRUPTED_TOOL_CALL_OUTPUT.to_string()
+                    ),
+                })
+        );
+
+        rollout_items.push(RolloutItem::EventMsg(EventMsg::TurnAborted(
+            TurnAbortedEvent {
+                turn_id: Some("turn-2".to_string()),
+                reason: TurnAbortReason::Interrupted,
+            },
+        )));
+        assert_eq!(find_interrupted_turn(&rollout_items), None);
+    }
+}
@@ -0,0 +1,84 @@
+use super::*;
+
+use tokio::time::Instant;
+use tokio_util::task::AbortOnDropHandle;
+
+use crate::protocol::TurnTimedOutEvent;
+
+/// Time the model gets to answer the wrap-up request of a timed-out turn
+/// before that request is cancelled as well.
+const TURN_TIMEOUT_WRAP_UP_GRACE: Duration = Duration::from_secs(30);
+
+const TURN_TIMED_OUT_GUIDANCE: &str = "This turn reached its time limit. Tool calls that were still running were cancelled and may have partially executed. Do not call any more tools: reply with a short summary of what was done and what remains.";
+
+/// Enforces `turn_timeout_seconds` on a regular turn. Once the deadline
+/// passes, the sampling request in progress is cancelled and the model gets
+/// one last request, bounded by [`TURN_TIMEOUT_WRAP_UP_GRACE`], to wrap up.
+pub(super) struct TurnTimeout {
+    timeout_seconds: u64,
+    deadline: Instant,
+    wrapping_up: bool,
+}
+
+impl TurnTimeout {
+    /// Returns `None` when turns are not time-limited.
+    pub(super) fn from_config(config: &Config) -> Option<Self> {
+        let timeout_seconds = config.turn_timeout_seconds?;
+        Some(Self {
+            timeout_seconds,
+            deadline: Instant::now() + Duration::from_secs(timeout_seconds),
+            wrapping_up: false,
+        })
+    }
+
+    /// Cancels `cancellation_token` when the deadline passes. The timer is
+    /// disarmed when the returned handle is dropped.
+    pub(super) fn arm(
+        &self,
+        turn_context: &Arc<TurnContext>,
+        cancellation_token: CancellationToken,
+    ) -> AbortOnDropHandle<()> {
+        let deadline = self.deadline;
+        let turn_context = Arc::clone(turn_context);
+        AbortOnDropHandle::new(tokio::spawn(async move {
+            tokio::time::sleep_until(deadline).await;
+            turn_context.turn_timing_state.mark_timed_out().await;
+            cancellation_token.cancel();
+        }))
+    }
+
+    /// Whether the turn ran out of time and has not been asked to wrap up.
+    pub(super) fn expired(&self) -> bool {
+        !self.wrapping_up && Instant::now() >= self.deadline
+    }
+
+    pub(super) fn is_wrapping_up(&self) -> bool {
+        self.wrapping_up
+    }
+
+    /// Reports the timeout to clients and asks the model for a final
+    /// summary. Tool calls cancelled by the timer have already been answered
+    /// with timeout outputs by the time this runs.
+    pub(super) async fn start_wrap_up(&mut self, sess: &Session, turn_context: &TurnContext) {
+        turn_context.turn_timing_state.mark_timed_out().await;
+        self.wrapping_up = true;
+        self.deadline = Instant::now() + TURN_TIMEOUT_WRAP_UP_GRACE;
+        warn!(
+            turn_id = %turn_context.sub_id,
+            timeout_seconds = self.timeout_seconds,
+            "turn timed out; asking the model to wrap up"
+        );
+        sess.send_event(
+            turn_context,
+            EventMsg::TurnTimedOut(TurnTimedOutEvent {
+                turn_id: turn_context.sub_id.clone(),
+                timeout_seconds: self.timeout_seconds,
+            }),
+        )
+        .await;
+        let message: ResponseItem =
+            DeveloperInstructions::new(TURN_TIMED_OUT_GUIDANCE.to_string()).into();
+        sess.record_conversation_items(turn_context, std::slice::from_ref(&message))
+            .await;
+    }
+}
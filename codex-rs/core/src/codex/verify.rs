@@ -0,0 +1,436 @@
+use super::*;
+
+use codex_async_utils::CancelErr;
+
+use crate::exec::ExecOutputCap;
+use crate::exec::ExecToolCallOutput;
+use crate::exec::SandboxType;
+use crate::exec::StdoutStream;
+use crate::exec::execute_exec_request;
+use crate::exec_env::create_env;
+use crate::protocol::ExecCommandBeginEvent;
+use crate::protocol::ExecCommandEndEvent;
+use crate::protocol::ExecCommandSource;
+use crate::protocol::ExecCommandStatus;
+use crate::sandboxing::ExecRequest;
+use crate::sandboxing::SandboxPermissions;
+use crate::tools::format_exec_output_str;
+use crate::tools::runtimes::maybe_wrap_shell_lc_with_snapshot;
+
+const DEFAULT_VERIFY_MAX_ITERATIONS: u32 = 3;
+const DEFAULT_VERIFY_TIMEOUT_MS: u64 = 10 * 60 * 1000;
+
+/// Failures listed in the summary sent to the model; the rest are counted.
+const MAX_REPORTED_FAILURES: usize = 20;
+
+/// Lines from the end of the command's output included after the failures.
+const OUTPUT_TAIL_LINES: usize = 40;
+
+/// Runs the `[verify]` command once the model ends a turn that changed files.
+///
+/// When the command fails, its failures are summarized for the model and the
+/// turn keeps going so the model can fix them. The command runs again every
+/// time the model stops with a different set of changes, up to
+/// `max_iterations` corrections per turn.
+pub(super) struct VerifyLoop {
+    command: String,
+    max_iterations: u32,
+    timeout_ms: u64,
+    iterations: u32,
+    verified_diff: Option<String>,
+}
+
+impl VerifyLoop {
+    /// Returns `None` when no verification command is configured.
+    pub(super) fn from_config(config: &Config) -> Option<Self> {
+        let command = config.verify.command.as_deref()?.trim();
+        if command.is_empty() {
+            return None;
+        }
+        Some(Self {
+            command: command.to_string(),
+            max_iterations: config
+                .verify
+                .max_iterations
+                .unwrap_or(DEFAULT_VERIFY_MAX_ITERATIONS),
+            timeout_ms: config
+                .verify
+                .timeout_ms
+                .unwrap_or(DEFAULT_VERIFY_TIMEOUT_MS),
+            iterations: 0,
+            verified_diff: None,
+        })
+    }
+
+    /// Verifies the turn's changes. When the command fails and iterations
+    /// remain, records a failure summary for the model and returns `true` so
+    /// the turn keeps going. Turns without new changes are not verified.
+    pub(super) async fn request_fix(
+        &mut self,
+        sess: &Arc<Session>,
+        turn_context: &TurnContext,
+        turn_diff_tracker: &SharedTurnDiffTracker,
+        cancellation_token: &CancellationToken,
+    ) -> bool {
+        let diff = match turn_diff_tracker.lock().await.get_unified_diff() {
+            Ok(Some(diff)) => diff,
+            Ok(None) => return false,
+            Err(err) => {
+                warn!(
+                    turn_id = %turn_context.sub_id,
+                    error = %err,
+                    "failed to compute the turn diff; skipping verification"
+                );
+                return false;
+            }
+        };
+        if self.verified_diff.as_deref() == Some(diff.as_str()) {
+            return false;
+        }
+        self.verified_diff = Some(diff);
+
+        let Some(output) = self
+            .run_command(sess, turn_context, cancellation_token)
+            .await
+        else {
+            return false;
+        };
+        if output.exit_code == 0 {
+            return false;
+        }
+        if self.iterations >= self.max_iterations {
+            warn!(
+                turn_id = %turn_context.sub_id,
+                exit_code = output.exit_code,
+                iterations = self.iterations,
+                "verification still fails; ending the turn"
+            );
+            sess.send_event(
+                turn_context,
+                EventMsg::Warning(WarningEvent {
+                    message: format!(
+                        "Verification command `{}` still fails after {} attempts to fix it.",
+                        self.command, self.iterations
+                    ),
+                }),
+            )
+            .await;
+            return false;
+        }
+        self.iterations += 1;
+        let message: ResponseItem = DeveloperInstructions::new(failure_report(
+            &self.command,
+            &output,
+            self.iterations,
+            self.max_iterations,
+        ))
+        .into();
+        sess.record_conversation_items(turn_context, std::slice::from_ref(&message))
+            .await;
+        true
+    }
+
+    /// Runs the command unsandboxed in the turn's working directory, like a
+    /// user shell command, and reports it to clients as an exec call.
+    async fn run_command(
+        &self,
+        sess: &Arc<Session>,
+        turn_context: &TurnContext,
+        cancellation_token: &CancellationToken,
+    ) -> Option<ExecToolCallOutput> {
+        let session_shell = sess.user_shell();
+        let display_command = session_shell.derive_exec_args(&self.command, true);
+        let exec_command = maybe_wrap_shell_lc_with_snapshot(
+            &display_command,
+            session_shell.as_ref(),
+            turn_context.cwd.as_path(),
+            &turn_context.shell_environment_policy.r#set,
+        );
+        let call_id = Uuid::new_v4().to_string();
+        let cwd = turn_context.cwd.clone();
+        let parsed_cmd = parse_command(&display_command);
+        sess.send_event(
+            turn_context,
+            EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
+                call_id: call_id.clone(),
+                process_id: None,
+                turn_id: turn_context.sub_id.clone(),
+                command: display_command.clone(),
+                cwd: cwd.clone(),
+                parsed_cmd: parsed_cmd.clone(),
+                source: ExecCommandSource::Verify,
+                interaction_input: None,
+            }),
+        )
+        .await;
+
+        let sandbox_policy = SandboxPolicy::DangerFullAccess;
+        let exec_request = ExecRequest {
+            command: exec_command,
+            cwd: cwd.clone(),
+            env: create_env(
+                &turn_context.shell_environment_policy,
+                Some(sess.conversation_id),
+            ),
+            network: turn_context.network.clone(),
+            expiration: self.timeout_ms.into(),
+            sandbox: SandboxType::None,
+            windows_sandbox_level: turn_context.windows_sandbox_level,
+            sandbox_permissions: SandboxPermissions::UseDefault,
+            sandbox_policy: sandbox_policy.clone(),
+            file_system_sandbox_policy: FileSystemSandboxPolicy::from(&sandbox_policy),
+            network_sandbox_policy: NetworkSandboxPolicy::from(&sandbox_policy),
+            justification: None,
+            arg0: None,
+        };
+        let stdout_stream = Some(StdoutStream {
+            sub_id: turn_context.sub_id.clone(),
+            call_id: call_id.clone(),
+            tx_event: sess.get_tx_event(),
+            output_cap: ExecOutputCap::from_config(&turn_context.config),
+        });
+
+        let output = match execute_exec_request(exec_request, &sandbox_policy, stdout_stream, None)
+            .or_cancel(cancellation_token)
+            .await
+        {
+            Err(CancelErr::Cancelled) => return None,
+            Ok(Ok(output)) => output,
+            Ok(Err(err)) => {
+                warn!(
+                    turn_id = %turn_context.sub_id,
+                    error = %err,
+                    "verification command could not run"
+                );
+                let message = format!("execution error: {err}");
+                ExecToolCallOutput {
+                    exit_code: -1,
+                    stdout: StreamOutput::new(String::new()),
+                    stderr: StreamOutput::new(message.clone()),
+                    aggregated_output: StreamOutput::new(message),
+                    duration: Duration::ZERO,
+                    timed_out: false,
+                }
+            }
+        };
+        sess.send_event(
+            turn_context,
+            EventMsg::ExecCommandEnd(ExecCommandEndEvent {
+                call_id,
+                process_id: None,
+                turn_id: turn_context.sub_id.clone(),
+                command: display_command,
+                cwd,
+                parsed_cmd,
+                source: ExecCommandSource::Verify,
+                interaction_input: None,
+                stdout: output.stdout.text.clone(),
+                stderr: output.stderr.text.clone(),
+                aggregated_output: output.aggregated_output.text.clone(),
+                exit_code: output.exit_code,
+                duration: output.duration,
+                formatted_output: format_exec_output_str(&output, turn_context.truncation_policy),
+                status: if output.exit_code == 0 {
+                    ExecCommandStatus::Completed
+                } else {
+                    ExecCommandStatus::Failed
+                },
+            }),
+        )
+        .await;
+        Some(output)
+    }
+}
+
+/// The message asking the model to fix a failed verification run.
+fn failure_report(
+    command: &str,
+    output: &ExecToolCallOutput,
+    iteration: u32,
+    max_iterations: u32,
+) -> String {
+    let text = &output.aggregated_output.text;
+    let outcome = if output.timed_out {
+        "timed out".to_string()
+    } else {
+        format!("failed with exit code {}", output.exit_code)
+    };
+    let mut report = format!(
+        "The verification command `{command}` {outcome} after your changes (fix attempt {iteration} of {max_iterations})."
+    );
+    let failures = parse_failures(text);
+    if !failures.is_empty() {
+        report.push_str("\n\nFailures:");
+        for failure in failures.iter().take(MAX_REPORTED_FAILURES) {
+            report.push_str("\n- ");
+            report.push_str(failure);
+        }
+        if failures.len() > MAX_REPORTED_FAILURES {
+            report.push_str(&format!(
+                "\n- ... and {} more",
+                failures.len() - MAX_REPORTED_FAILURES
+            ));
+        }
+    }
+    let lines = text.lines().collect::<Vec<_>>();
+    let tail = lines[lines.len().saturating_sub(OUTPUT_TAIL_LINES)..].join("\n");
+    if !tail.trim().is_empty() {
+        report.push_str("\n\nEnd of the output:\n```\n");
+        report.push_str(&tail);
+        report.push_str("\n```");
+    }
+    report.push_str(
+        "\n\nFix the failures, then finish your reply as usual; the command runs again once you stop.",
+    );
+    report
+}
+
+/// Picks failing tests and compiler errors out of a verification run.
+///
+/// Recognizes the output of `cargo test` and rustc, pytest, `go test`, and
+/// compilers that print `path:line:col: error: message`. Each failure is
+/// reported once, in the order it first appears.
+pub(super) fn parse_failures(output: &str) -> Vec<String> {
+    let lines = output.lines().map(str::trim_end).collect::<Vec<_>>();
+    let mut failures: Vec<String> = Vec::new();
+    let mut push = |failure: String| {
+        if !failures.contains(&failure) {
+            failures.push(failure);
+        }
+    };
+
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if let Some(name) = trimmed
+            .strip_prefix("test ")
+            .and_then(|rest| rest.strip_suffix(" ... FAILED"))
+        {
+            push(match libtest_panic(&lines, name) {
+                Some(panic) => format!("test `{name}` failed: {panic}"),
+                None => format!("test `{name}` failed"),
+            });
+        } else if let Some(rest) = trimmed.strip_prefix("FAILED ")
+            && rest.contains("::")
+        {
+            push(format!("test {rest}"));
+        } else if let Some(rest) = trimmed.strip_prefix("--- FAIL: ") {
+            let name = rest.split_whitespace().next().unwrap_or(rest);
+            push(format!("test `{name}` failed"));
+        } else if trimmed.starts_with("error[") || is_rustc_error(trimmed) {
+            let location = lines[index + 1..]
+                .iter()
+                .take(3)
+                .find_map(|line| line.trim_start().strip_prefix("--> "));
+            push(match location {
+                Some(location) => format!("{trimmed} at {location}"),
+                None => trimmed.to_string(),
+            });
+        } else if trimmed.contains(": error") && trimmed.split(':').count() >= 4 {
+            push(trimmed.to_string());
+        }
+    }
+    failures
+}
+
+/// rustc's `error: message` lines, minus cargo's closing summaries.
+fn is_rustc_error(line: &str) -> bool {
+    let Some(message) = line.strip_prefix("error: ") else {
+        return false;
+    };
+    ![
+        "could not compile",
+        "aborting due to",
+        "test failed",
+        "build failed",
+    ]
+    .iter()
+    .any(|summary| message.starts_with(summary))
+}
+
+/// The panic message and location of a failed libtest test, from its
+/// `---- name stdout ----` section.
+fn libtest_panic(lines: &[&str], name: &str) -> Option<String> {
+    let header = format!("---- {name} stdout ----");
+    let start = lines.iter().position(|line| line.trim() == header)?;
+    let section = lines[start + 1..]
+        .iter()
+        .take_while(|line| !line.starts_with("---- ") && line.trim() != "failures:");
+    let mut section = section.peekable();
+    while let Some(line) = section.next() {
+        let Some((_, rest)) = line.split_once("panicked at ") else {
+            continue;
+        };
+        let location = rest.trim_end_matches(':');
+        let message = section
+            .peek()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .unwrap_or_default();
+        return Some(if message.is_empty() {
+            format!("panicked at {location}")
+        } else {
+            format!("{message} (at {location})")
+        });
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_failures;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_cargo_test_failures_and_compiler_errors() {
+        let output = "\
+running 2 tests
+test parser::tests::parses_empty ... ok
+test parser::tests::parses_nested ... FAILED
+
+failures:
+
+---- parser::tests::parses_nested stdout ----
+
+thread 'parser::tests::parses_nested' panicked at src/parser.rs:42:9:
+assertion `left == right` failed
+  left: 1
+ right: 2
+
+failures:
+    parser::tests::parses_nested
+
+test result: FAILED. 1 passed; 1 failed
+error: test failed, to rerun pass `--lib`
+error[E0308]: mismatched types
+  --> src/lib.rs:3:5
+error: could not compile `demo` (lib) due to 1 previous error
+";
+
+        assert_eq!(
+            parse_failures(output),
+            vec![
+                "test `parser::tests::parses_nested` failed: assertion `left == right` failed (at src/parser.rs:42:9)".to_string(),
+                "error[E0308]: mismatched types at src/lib.rs:3:5".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_other_test_runners() {
+        let output = "\
+FAILED tests/test_api.py::test_login - AssertionError: 401 != 200
+--- FAIL: TestParse (0.00s)
+src/main.c:10:5: error: expected ';' before 'return'
+All good otherwise: error free
+";
+
+        assert_eq!(
+            parse_failures(output),
+            vec![
+                "test tests/test_api.py::test_login - AssertionError: 401 != 200".to_string(),
+                "test `TestParse` failed".to_string(),
+                "src/main.c:10:5: error: expected ';' before 'return'".to_string(),
+            ]
+        );
+    }
+}
@@ -212,7 +212,7 @@ async fn forward_events(
                     } => {}
                     Event {
                         id: _,
-                        msg: EventMsg::TokenCount(_),
+                        msg: EventMsg::TokenCount(_) | EventMsg::CostUpdate(_),
                     } => {}
                     Event {
                         id: _,
@@ -7,8 +7,10 @@ use crate::config_loader::ConfigLayerStackOrdering;
 use crate::config_loader::NetworkConstraints;
 use crate::config_loader::RequirementSource;
 use crate::config_loader::Sourced;
+use crate::context_item::MessageRole;
 use crate::exec::ExecToolCallOutput;
 use crate::function_tool::FunctionCallError;
+use crate::instructions::USER_INSTRUCTIONS_PREFIX;
 use crate::mcp_connection_manager::ToolInfo;
 use crate::models_manager::model_info;
 use crate::shell::default_user_shell;
@@ -37,6 +39,8 @@ use crate::rollout::recorder::RolloutRecorderParams;
 use crate::state::TaskKind;
 use crate::tasks::SessionTask;
 use crate::tasks::SessionTaskContext;
+use crate::tasks::TurnId;
+use crate::tasks::TurnOutcome;
 use crate::tools::ToolRouter;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
@@ -57,12 +61,14 @@ use codex_protocol::protocol::ConversationAudioParams;
 use codex_protocol::protocol::RealtimeAudioFrame;
 use codex_protocol::protocol::Submission;
 use codex_protocol::protocol::W3cTraceContext;
+use opentelemetry::trace::SpanId;
 use opentelemetry::trace::TraceContextExt;
 use opentelemetry::trace::TraceId;
 use opentelemetry::trace::TracerProvider as _;
 use opentelemetry_sdk::trace::SdkTracerProvider;
 use std::path::Path;
 use std::time::Duration;
+use tokio::sync::Notify;
 use tokio::time::sleep;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use tracing_subscriber::prelude::*;
@@ -851,24 +857,28 @@ async fn record_initial_history_seeds_token_info_from_rollout() {
         TokenCountEvent {
             info: Some(info1),
             rate_limits: None,
+            prompt_cache_hit_rate: None,
         },
     )));
     rollout_items.push(RolloutItem::EventMsg(EventMsg::TokenCount(
         TokenCountEvent {
             info: None,
             rate_limits: None,
+            prompt_cache_hit_rate: None,
         },
     )));
     rollout_items.push(RolloutItem::EventMsg(EventMsg::TokenCount(
         TokenCountEvent {
             info: Some(info2.clone()),
             rate_limits: None,
+            prompt_cache_hit_rate: None,
         },
     )));
     rollout_items.push(RolloutItem::EventMsg(EventMsg::TokenCount(
         TokenCountEvent {
             info: None,
             rate_limits: None,
+            prompt_cache_hit_rate: None,
         },
     )));
 
@@ -1144,6 +1154,38 @@ async fn thread_rollback_fails_without_persisted_rollout_path() {
     assert_eq!(sess.clone_history().await.raw_items(), initial_context);
 }
 
+#[tokio::test]
+async fn override_turn_context_reports_the_updated_settings() {
+    use crate::protocol::AskForApproval;
+
+    let (sess, tc, rx) = make_session_and_context_with_rx().await;
+
+    handlers::override_turn_context(
+        &sess,
+        "sub-1".to_string(),
+        SessionSettingsUpdate {
+            approval_policy: Some(AskForApproval::Never),
+            ..Default::default()
+        },
+    )
+    .await;
+
+    let deadline = StdDuration::from_secs(2);
+    let start = std::time::Instant::now();
+    let event = loop {
+        let remaining = deadline.saturating_sub(start.elapsed());
+        let evt = tokio::time::timeout(remaining, rx.recv())
+            .await
+            .expect("timeout waiting for event")
+            .expect("event");
+        if let EventMsg::SessionSettingsUpdated(event) = evt.msg {
+            break event;
+        }
+    };
+    assert_eq!(event.approval_policy, AskForApproval::Never);
+    assert_eq!(event.cwd, tc.cwd);
+}
+
 #[tokio::test]
 async fn thread_rollback_recomputes_previous_turn_settings_and_reference_context_from_replay() {
     let (sess, tc, rx) = make_session_and_context_with_rx().await;
@@ -1603,6 +1645,7 @@ fn prefers_structured_content_when_present() {
             .unwrap(),
         ),
         success: Some(true),
+        error_code: None,
     };
 
     assert_eq!(expected, got);
@@ -1681,6 +1724,7 @@ fn falls_back_to_content_when_structured_is_null() {
             serde_json::to_string(&vec![text_block("hello"), text_block("world")]).unwrap(),
         ),
         success: Some(true),
+        error_code: None,
     };
 
     assert_eq!(expected, got);
@@ -1701,6 +1745,7 @@ fn success_flag_reflects_is_error_true() {
             serde_json::to_string(&json!({ "message": "bad" })).unwrap(),
         ),
         success: Some(false),
+        error_code: None,
     };
 
     assert_eq!(expected, got);
@@ -1721,6 +1766,7 @@ fn success_flag_true_with_no_error_and_content_used() {
             serde_json::to_string(&vec![text_block("alpha")]).unwrap(),
         ),
         success: Some(true),
+        error_code: None,
     };
 
     assert_eq!(expected, got);
@@ -2063,6 +2109,7 @@ pub(crate) async fn make_session_and_context() -> (Session, TurnContext) {
             ),
         )),
         mcp_startup_cancellation_token: Mutex::new(CancellationToken::new()),
+        mcp_supervisor: McpSupervisor::default(),
         unified_exec_manager: UnifiedExecProcessManager::new(
             config.background_terminal_max_timeout,
         ),
@@ -2074,6 +2121,7 @@ pub(crate) async fn make_session_and_context() -> (Session, TurnContext) {
         ),
         hooks: Hooks::new(HooksConfig {
             legacy_notify_argv: config.notify.clone(),
+            commands: Vec::new(),
         }),
         rollout: Mutex::new(None),
         user_shell: Arc::new(default_user_shell()),
@@ -2104,6 +2152,15 @@ pub(crate) async fn make_session_and_context() -> (Session, TurnContext) {
             config.features.enabled(Feature::RuntimeMetrics),
             Session::build_model_client_beta_features_header(config.as_ref()),
         ),
+        active_tool_calls: ActiveToolCalls::default(),
+        tool_call_ids: CallIdDedupe::default(),
+        tool_call_archive: CallArchive::default(),
+        tool_middleware: SessionToolMiddleware::default(),
+        tool_chaos: None,
+        tool_call_subscribers: ToolCallSubscribers::default(),
+        turn_outcomes: TurnOutcomes::default(),
+        session_share: SessionShare::default(),
+        audit_log: None,
     };
     let js_repl = Arc::new(JsReplHandle::with_node_path(
         config.js_repl_node_path.clone(),
@@ -2242,11 +2299,14 @@ fn submission_dispatch_span_prefers_submission_trace_context() {
         tracestate: Some("vendor=value".into()),
     };
     let dispatch_span = ambient_span.in_scope(|| {
-        submission_dispatch_span(&Submission {
-            id: "sub-1".into(),
-            op: Op::Interrupt,
-            trace: Some(submission_trace),
-        })
+        submission_dispatch_span(
+            &Submission {
+                id: "sub-1".into(),
+                op: Op::Interrupt,
+                trace: Some(submission_trace),
+            },
+            None,
+        )
     });
 
     let trace_id = dispatch_span.context().span().span_context().trace_id();
@@ -2256,22 +2316,73 @@ fn submission_dispatch_span_prefers_submission_trace_context() {
     );
 }
 
+#[tokio::test]
+async fn submission_dispatch_span_falls_back_to_session_trace_context() {
+    let (session, _turn_context) = make_session_and_context().await;
+    init_test_tracing();
+
+    session.set_trace_context("not-a-traceparent".to_string(), None);
+    assert_eq!(session.trace_context(), None);
+
+    session.set_trace_context(
+        "00-00000000000000000000000000000077-0000000000000088-01".to_string(),
+        Some("vendor=value".to_string()),
+    );
+    let session_trace = session.trace_context();
+    let dispatch_span = submission_dispatch_span(
+        &Submission {
+            id: "sub-1".into(),
+            op: Op::Interrupt,
+            trace: None,
+        },
+        session_trace.as_ref(),
+    );
+
+    let dispatch_context = dispatch_span.context();
+    let span_context = dispatch_context.span().span_context().clone();
+    assert_eq!(
+        span_context.trace_id(),
+        TraceId::from_hex("00000000000000000000000000000077").expect("trace id")
+    );
+    assert_ne!(
+        span_context.span_id(),
+        SpanId::from_hex("0000000000000088").expect("span id")
+    );
+
+    // Requests made while the dispatch span is current are its children.
+    let propagated = dispatch_span.in_scope(|| {
+        codex_otel::current_span_w3c_trace_context_within(
+            session_trace.as_ref().expect("session trace"),
+        )
+    });
+    assert_eq!(
+        propagated.traceparent,
+        Some(format!(
+            "00-00000000000000000000000000000077-{}-01",
+            span_context.span_id()
+        ))
+    );
+}
+
 #[test]
 fn submission_dispatch_span_uses_debug_for_realtime_audio() {
     init_test_tracing();
 
-    let dispatch_span = submission_dispatch_span(&Submission {
-        id: "sub-1".into(),
-        op: Op::RealtimeConversationAudio(ConversationAudioParams {
-            frame: RealtimeAudioFrame {
-                data: "ZmFrZQ==".into(),
-                sample_rate: 16_000,
-                num_channels: 1,
-                samples_per_channel: Some(160),
-            },
-        }),
-        trace: None,
-    });
+    let dispatch_span = submission_dispatch_span(
+        &Submission {
+            id: "sub-1".into(),
+            op: Op::RealtimeConversationAudio(ConversationAudioParams {
+                frame: RealtimeAudioFrame {
+                    data: "ZmFrZQ==".into(),
+                    sample_rate: 16_000,
+                    num_channels: 1,
+                    samples_per_channel: Some(160),
+                },
+            }),
+            trace: None,
+        },
+        None,
+    );
 
     assert_eq!(
         dispatch_span.metadata().expect("span metadata").level(),
@@ -2328,11 +2439,14 @@ async fn spawn_task_turn_span_inherits_dispatch_trace_context() {
             .instrument(request_span)
             .await;
 
-    let dispatch_span = submission_dispatch_span(&Submission {
-        id: "sub-1".into(),
-        op: Op::Interrupt,
-        trace: Some(submission_trace.clone()),
-    });
+    let dispatch_span = submission_dispatch_span(
+        &Submission {
+            id: "sub-1".into(),
+            op: Op::Interrupt,
+            trace: Some(submission_trace.clone()),
+        },
+        None,
+    );
     let dispatch_span_id = dispatch_span.context().span().span_context().span_id();
 
     let (sess, tc, rx) = make_session_and_context_with_rx().await;
@@ -2470,6 +2584,7 @@ pub(crate) async fn make_session_and_context_with_dynamic_tools_and_rx(
             ),
         )),
         mcp_startup_cancellation_token: Mutex::new(CancellationToken::new()),
+        mcp_supervisor: McpSupervisor::default(),
         unified_exec_manager: UnifiedExecProcessManager::new(
             config.background_terminal_max_timeout,
         ),
@@ -2481,6 +2596,7 @@ pub(crate) async fn make_session_and_context_with_dynamic_tools_and_rx(
         ),
         hooks: Hooks::new(HooksConfig {
             legacy_notify_argv: config.notify.clone(),
+            commands: Vec::new(),
         }),
         rollout: Mutex::new(None),
         user_shell: Arc::new(default_user_shell()),
@@ -2511,6 +2627,15 @@ pub(crate) async fn make_session_and_context_with_dynamic_tools_and_rx(
             config.features.enabled(Feature::RuntimeMetrics),
             Session::build_model_client_beta_features_header(config.as_ref()),
         ),
+        active_tool_calls: ActiveToolCalls::default(),
+        tool_call_ids: CallIdDedupe::default(),
+        tool_call_archive: CallArchive::default(),
+        tool_middleware: SessionToolMiddleware::default(),
+        tool_chaos: None,
+        tool_call_subscribers: ToolCallSubscribers::default(),
+        turn_outcomes: TurnOutcomes::default(),
+        session_share: SessionShare::default(),
+        audit_log: None,
     };
     let js_repl = Arc::new(JsReplHandle::with_node_path(
         config.js_repl_node_path.clone(),
@@ -2742,6 +2867,44 @@ async fn build_settings_update_items_emits_environment_item_for_time_changes() {
     assert!(environment_update.contains("<timezone>Europe/Berlin</timezone>"));
 }
 
+#[tokio::test]
+async fn build_settings_update_items_resends_changed_user_instructions() {
+    let (session, previous_context) = make_session_and_context().await;
+    let previous_context = Arc::new(previous_context);
+    let mut current_context = previous_context
+        .with_model(
+            previous_context.model_info.slug.clone(),
+            &session.services.models_manager,
+        )
+        .await;
+    let reference_context_item = previous_context.to_turn_context_item();
+    let agents_instructions = |items: &[ResponseItem]| {
+        items.iter().find_map(|item| match item {
+            ResponseItem::Message { role, content, .. } if role == "user" => {
+                let [ContentItem::InputText { text }] = content.as_slice() else {
+                    return None;
+                };
+                text.starts_with(USER_INSTRUCTIONS_PREFIX)
+                    .then(|| text.clone())
+            }
+            _ => None,
+        })
+    };
+
+    let update_items = session
+        .build_settings_update_items(Some(&reference_context_item), &current_context)
+        .await;
+    assert_eq!(agents_instructions(&update_items), None);
+
+    current_context.user_instructions = Some("Run `just fmt` before committing.".to_string());
+    let update_items = session
+        .build_settings_update_items(Some(&reference_context_item), &current_context)
+        .await;
+    let instructions = agents_instructions(&update_items)
+        .expect("user instructions update item should be emitted");
+    assert!(instructions.contains("Run `just fmt` before committing."));
+}
+
 #[tokio::test]
 async fn build_settings_update_items_emits_realtime_start_when_session_becomes_live() {
     let (session, previous_context) = make_session_and_context().await;
@@ -3204,6 +3367,100 @@ impl SessionTask for NeverEndingTask {
     }
 }
 
+struct DependentTask {
+    dependencies: Vec<TurnId>,
+    started: Arc<Notify>,
+}
+
+#[async_trait::async_trait]
+impl SessionTask for DependentTask {
+    fn kind(&self) -> TaskKind {
+        TaskKind::Regular
+    }
+
+    fn span_name(&self) -> &'static str {
+        "session_task.dependent"
+    }
+
+    fn input_dependencies(&self) -> Vec<TurnId> {
+        self.dependencies.clone()
+    }
+
+    async fn run(
+        self: Arc<Self>,
+        _session: Arc<SessionTaskContext>,
+        _ctx: Arc<TurnContext>,
+        _input: Vec<UserInput>,
+        _cancellation_token: CancellationToken,
+    ) -> Option<String> {
+        self.started.notify_one();
+        None
+    }
+}
+
+#[tokio::test]
+async fn task_waits_for_input_dependencies_before_starting() {
+    let (sess, tc, _rx) = make_session_and_context_with_rx().await;
+    let started = Arc::new(Notify::new());
+    sess.spawn_task(
+        Arc::clone(&tc),
+        Vec::new(),
+        DependentTask {
+            dependencies: vec!["turn-prior".to_string()],
+            started: Arc::clone(&started),
+        },
+    )
+    .await;
+
+    assert!(
+        tokio::time::timeout(StdDuration::from_millis(100), started.notified())
+            .await
+            .is_err(),
+        "task started before its dependency completed"
+    );
+
+    sess.services
+        .turn_outcomes
+        .record("turn-prior", TurnOutcome::Completed);
+
+    tokio::time::timeout(StdDuration::from_secs(2), started.notified())
+        .await
+        .expect("task starts once its dependency completes");
+}
+
+#[tokio::test]
+async fn task_with_aborted_dependency_fails_without_running() {
+    let (sess, tc, rx) = make_session_and_context_with_rx().await;
+    sess.services
+        .turn_outcomes
+        .record("turn-prior", TurnOutcome::Aborted);
+    let started = Arc::new(Notify::new());
+    sess.spawn_task(
+        Arc::clone(&tc),
+        Vec::new(),
+        DependentTask {
+            dependencies: vec!["turn-prior".to_string()],
+            started: Arc::clone(&started),
+        },
+    )
+    .await;
+
+    let evt = tokio::time::timeout(StdDuration::from_secs(2), rx.recv())
+        .await
+        .expect("timeout waiting for event")
+        .expect("event");
+    match evt.msg {
+        EventMsg::Error(err) => assert!(err.message.contains("turn-prior")),
+        other => panic!("unexpected event: {other:?}"),
+    }
+    assert!(
+        tokio::time::timeout(StdDuration::from_millis(100), started.notified())
+            .await
+            .is_err(),
+        "task ran despite its dependency being aborted"
+    );
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 #[test_log::test]
 async fn abort_regular_task_emits_turn_aborted_only() {
@@ -3596,6 +3853,63 @@ async fn fatal_tool_error_stops_turn_and_reports_error() {
     }
 }
 
+#[tokio::test]
+async fn tool_calls_stay_archived_across_sampling_requests() {
+    let (session, turn_context) = make_session_and_context().await;
+    let session = Arc::new(session);
+    let turn_context = Arc::new(turn_context);
+    let cancellation_token = CancellationToken::new();
+    let first_request = built_tools(
+        session.as_ref(),
+        turn_context.as_ref(),
+        &[],
+        &HashSet::new(),
+        None,
+        &cancellation_token,
+    )
+    .await
+    .expect("build tools for the first request");
+
+    first_request
+        .dispatch_tool_call(
+            Arc::clone(&session),
+            Arc::clone(&turn_context),
+            Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+            crate::tools::router::ToolCall {
+                tool_name: "no_such_tool".to_string(),
+                call_id: "call-archived".to_string(),
+                payload: ToolPayload::Function {
+                    arguments: "{}".to_string(),
+                },
+                tags: HashMap::new(),
+                dry_run: false,
+            },
+            ToolCallSource::Direct,
+        )
+        .await
+        .expect("unknown tools fail without ending the turn");
+    drop(first_request);
+    let second_request = built_tools(
+        session.as_ref(),
+        turn_context.as_ref(),
+        &[],
+        &HashSet::new(),
+        None,
+        &cancellation_token,
+    )
+    .await
+    .expect("build tools for the second request");
+
+    let archived = session
+        .get_call_by_id("call-archived")
+        .expect("call archived in the session");
+    assert_eq!(archived.tool_name, "no_such_tool");
+    assert_eq!(
+        second_request.get_call_by_id("call-archived"),
+        Some(archived)
+    );
+}
+
 async fn sample_rollout(
     session: &Session,
     _turn_context: &TurnContext,
@@ -3840,6 +4154,7 @@ async fn rejects_escalated_permissions_when_policy_not_on_request() {
                 })
                 .to_string(),
             },
+            dry_run: false,
         })
         .await;
 
@@ -3883,6 +4198,7 @@ async fn rejects_escalated_permissions_when_policy_not_on_request() {
                 })
                 .to_string(),
             },
+            dry_run: false,
         })
         .await;
 
@@ -3942,6 +4258,7 @@ async fn unified_exec_rejects_escalated_permissions_when_policy_not_on_request()
                 })
                 .to_string(),
             },
+            dry_run: false,
         })
         .await;
 
@@ -3956,3 +4273,636 @@ async fn unified_exec_rejects_escalated_permissions_when_policy_not_on_request()
 
     pretty_assertions::assert_eq!(output, expected);
 }
+
+#[tokio::test]
+async fn summarize_tools_used_is_scoped_to_turn() {
+    let (session, _turn_context) = make_session_and_context().await;
+
+    session
+        .record_tool_usage("turn-1", ToolUsageKind::ReadFile)
+        .await;
+    session
+        .record_tool_usage("turn-1", ToolUsageKind::ShellCommand)
+        .await;
+    session
+        .record_tool_usage("turn-2", ToolUsageKind::WebSearch)
+        .await;
+    session
+        .record_tool_usage("turn-1", ToolUsageKind::ReadFile)
+        .await;
+
+    assert_eq!(
+        session.summarize_tools_used("turn-1").await,
+        "In this turn, the model read 2 files and ran 1 shell command."
+    );
+    assert_eq!(
+        session.summarize_tools_used("turn-2").await,
+        "In this turn, the model performed 1 web search."
+    );
+    assert_eq!(
+        session.summarize_tools_used("turn-3").await,
+        "In this turn, the model did not use any tools."
+    );
+}
+
+#[tokio::test]
+async fn get_model_capabilities_matches_turn_model_info() {
+    let (session, turn_context) = make_session_and_context().await;
+
+    let capabilities = session.get_model_capabilities().await;
+
+    assert_eq!(capabilities, turn_context.model_capabilities());
+    assert_eq!(
+        capabilities.supports_vision,
+        turn_context
+            .model_info
+            .input_modalities
+            .contains(&codex_protocol::openai_models::InputModality::Image)
+    );
+}
+
+#[tokio::test]
+async fn build_prompt_follows_parallel_tool_call_capability() {
+    let (_session, mut turn_context) = make_session_and_context().await;
+    let router = ToolRouter::from_config(
+        &turn_context.tools_config,
+        None,
+        None,
+        turn_context.dynamic_tools.as_slice(),
+    );
+
+    for supports_parallel_tool_calls in [true, false] {
+        turn_context.model_info.supports_parallel_tool_calls = supports_parallel_tool_calls;
+        let prompt = build_prompt(
+            Vec::new(),
+            &router,
+            &turn_context,
+            BaseInstructions {
+                text: String::new(),
+            },
+        );
+
+        assert_eq!(prompt.parallel_tool_calls, supports_parallel_tool_calls);
+        assert_eq!(prompt.tools.len(), router.specs().len());
+    }
+}
+
+#[tokio::test]
+async fn build_prompt_emulates_tools_without_native_tool_calls() {
+    let (_session, mut turn_context) = make_session_and_context().await;
+    turn_context.model_info.supports_tool_calls = false;
+    let router = ToolRouter::from_config(
+        &turn_context.tools_config,
+        None,
+        None,
+        turn_context.dynamic_tools.as_slice(),
+    );
+    let call = ResponseItem::FunctionCall {
+        id: None,
+        name: "shell_command".to_string(),
+        arguments: r#"{"command":"ls"}"#.to_string(),
+        call_id: "emulated_1".to_string(),
+    };
+
+    let prompt = build_prompt(
+        vec![call],
+        &router,
+        &turn_context,
+        BaseInstructions {
+            text: "base".to_string(),
+        },
+    );
+
+    assert!(prompt.tools.is_empty());
+    assert!(!prompt.parallel_tool_calls);
+    assert!(
+        prompt
+            .base_instructions
+            .text
+            .starts_with("base\n\n## Tools")
+    );
+    assert!(matches!(
+        prompt.input.as_slice(),
+        [ResponseItem::Message { role, .. }] if role == "assistant"
+    ));
+}
+
+#[tokio::test]
+async fn attached_images_resolve_to_vision_content_parts() {
+    let (session, turn_context) = make_session_and_context().await;
+    assert!(turn_context.model_capabilities().supports_vision);
+
+    let first = session
+        .attach_image(ImageAttachment {
+            data: b"first".to_vec(),
+            mime_type: "image/png".to_string(),
+            label: None,
+        })
+        .await;
+    let second = session
+        .attach_image(ImageAttachment {
+            data: b"second".to_vec(),
+            mime_type: "image/jpeg".to_string(),
+            label: Some("diagram".to_string()),
+        })
+        .await;
+    assert_ne!(first, second);
+
+    let items = session
+        .image_content_items(second)
+        .await
+        .expect("attached image should resolve");
+    assert_eq!(
+        items,
+        vec![
+            ContentItem::InputText {
+                text: "diagram".to_string(),
+            },
+            ContentItem::InputText {
+                text: "<image>".to_string(),
+            },
+            ContentItem::InputImage {
+                image_url: "data:image/jpeg;base64,c2Vjb25k".to_string(),
+            },
+            ContentItem::InputText {
+                text: "</image>".to_string(),
+            },
+        ]
+    );
+    assert_eq!(session.image_content_items(ImageHandle(42)).await, None);
+}
+
+#[tokio::test]
+async fn pinned_turn_survives_compaction() {
+    let (session, mut turn_context) = make_session_and_context().await;
+    for turn in 1..=3 {
+        turn_context.sub_id = format!("turn-{turn}");
+        session
+            .record_into_history(
+                &[
+                    user_message(&format!("question {turn}")),
+                    assistant_message(&format!("answer {turn}")),
+                ],
+                &turn_context,
+            )
+            .await;
+    }
+    assert_eq!(
+        session.pin_turn("turn-missing").await,
+        Err(PinError::UnknownTurn("turn-missing".to_string()))
+    );
+    session.pin_turn("turn-1").await.expect("pin turn 1");
+
+    let history = session.clone_history().await.raw_items().to_vec();
+    let compacted = compact::build_compacted_history_keeping_pinned_turns(
+        &history,
+        &session.pinned_turn_items().await,
+        &format!("{}\nsummary", compact::SUMMARY_PREFIX),
+    );
+    session.replace_history(compacted, None).await;
+
+    let history = session.clone_history().await.raw_items().to_vec();
+    assert_eq!(
+        history[..2].to_vec(),
+        vec![user_message("question 1"), assistant_message("answer 1")]
+    );
+    assert!(!history.contains(&assistant_message("answer 2")));
+    assert_eq!(
+        history
+            .iter()
+            .filter(|item| **item == user_message("question 1"))
+            .count(),
+        1
+    );
+
+    session.unpin_turn("turn-1").await;
+    assert!(session.pinned_turn_items().await.is_empty());
+}
+
+#[tokio::test]
+async fn pin_turn_op_reports_unknown_turns() {
+    let (sess, tc, rx) = make_session_and_context_with_rx().await;
+    sess.record_into_history(
+        &[user_message("question"), assistant_message("answer")],
+        tc.as_ref(),
+    )
+    .await;
+    while rx.try_recv().is_ok() {}
+
+    handlers::pin_turn(&sess, "sub-1".to_string(), tc.sub_id.clone()).await;
+    assert!(rx.try_recv().is_err());
+    assert_eq!(sess.pinned_turn_items().await.len(), 1);
+
+    handlers::pin_turn(&sess, "sub-2".to_string(), "turn-missing".to_string()).await;
+    let event = rx.try_recv().expect("error event");
+    assert_eq!(event.id, "sub-2");
+    let EventMsg::Error(error) = event.msg else {
+        panic!("expected an error event, got {:?}", event.msg);
+    };
+    assert_eq!(
+        error.message,
+        "Failed to pin turn: turn turn-missing has no items in the current history"
+    );
+    assert_eq!(error.codex_error_info, Some(CodexErrorInfo::BadRequest));
+}
+
+#[tokio::test]
+async fn context_window_status_is_sent_after_each_turn() {
+    let (sess, tc, rx) = make_session_and_context_with_rx().await;
+    let mut used_tokens = Vec::new();
+    for turn in 0..3 {
+        sess.record_into_history(
+            &[
+                user_message(&format!("question {turn} ").repeat(200)),
+                assistant_message(&format!("answer {turn} ").repeat(200)),
+            ],
+            tc.as_ref(),
+        )
+        .await;
+        while rx.try_recv().is_ok() {}
+
+        sess.on_task_finished(Arc::clone(&tc), None).await;
+
+        let mut statuses = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            if let EventMsg::ContextWindowStatus(status) = event.msg {
+                statuses.push(status);
+            }
+        }
+        assert_eq!(statuses.len(), 1, "turn {turn}");
+        let status = &statuses[0];
+        assert_eq!(Some(status.max_tokens), tc.model_context_window());
+        assert_eq!(
+            status.pct_full,
+            status.used_tokens as f64 / status.max_tokens as f64 * 100.0
+        );
+        used_tokens.push(status.used_tokens);
+    }
+
+    assert!(
+        used_tokens.windows(2).all(|pair| pair[0] < pair[1]),
+        "used tokens did not grow: {used_tokens:?}"
+    );
+}
+
+#[tokio::test]
+async fn pinned_context_item_survives_compaction() {
+    let (session, mut turn_context) = make_session_and_context().await;
+    session
+        .push_context_item(ContextItem {
+            role: MessageRole::Developer,
+            content: "API reference: use v2 endpoints".to_string(),
+            pinned: true,
+        })
+        .await;
+    session
+        .push_context_item(ContextItem {
+            role: MessageRole::User,
+            content: "scratch snippet".to_string(),
+            pinned: false,
+        })
+        .await;
+    turn_context.sub_id = "turn-1".to_string();
+    session
+        .record_into_history(
+            &[user_message("question"), assistant_message("answer")],
+            &turn_context,
+        )
+        .await;
+    let pinned = ContextItem {
+        role: MessageRole::Developer,
+        content: "API reference: use v2 endpoints".to_string(),
+        pinned: true,
+    }
+    .to_response_item();
+    let scratch = ContextItem {
+        role: MessageRole::User,
+        content: "scratch snippet".to_string(),
+        pinned: false,
+    }
+    .to_response_item();
+    assert!(session.clone_history().await.raw_items().contains(&scratch));
+
+    let history = session.clone_history().await.raw_items().to_vec();
+    let compacted = compact::build_compacted_history_keeping_pinned_turns(
+        &history,
+        &session.pinned_turn_items().await,
+        &format!("{}\nsummary", compact::SUMMARY_PREFIX),
+    );
+    session.replace_history(compacted, None).await;
+
+    let history = session.clone_history().await.raw_items().to_vec();
+    assert_eq!(history.first(), Some(&pinned));
+    assert_eq!(history.iter().filter(|item| **item == pinned).count(), 1);
+    assert!(!history.contains(&assistant_message("answer")));
+}
+
+#[tokio::test]
+async fn checkpoint_round_trip_restores_identical_history() {
+    let (session, turn_context) = make_session_and_context().await;
+    for turn in 1..=5 {
+        session
+            .record_into_history(
+                &[
+                    user_message(&format!("question {turn}")),
+                    assistant_message(&format!("answer {turn}")),
+                ],
+                &turn_context,
+            )
+            .await;
+    }
+    let expected = session.clone_history().await.raw_items().to_vec();
+    let dir = tempfile::tempdir().expect("tempdir");
+
+    for store in [
+        Arc::new(crate::checkpoint::InMemoryCheckpointStore::default()) as Arc<dyn CheckpointStore>,
+        Arc::new(crate::checkpoint::FileCheckpointStore::new(dir.path())),
+    ] {
+        let id = session
+            .save_checkpoint(Arc::clone(&store))
+            .await
+            .expect("save checkpoint");
+        let (restored, _) = make_session_and_context().await;
+
+        restored
+            .load_checkpoint(store, &id)
+            .await
+            .expect("load checkpoint");
+
+        assert_eq!(restored.clone_history().await.raw_items(), expected);
+    }
+
+    let missing = session
+        .load_checkpoint(
+            Arc::new(crate::checkpoint::InMemoryCheckpointStore::default()),
+            &CheckpointId::new(),
+        )
+        .await;
+    assert!(missing.is_err());
+}
+
+#[tokio::test]
+async fn branch_from_checkpoint_discards_later_turns() {
+    let (session, turn_context) = make_session_and_context().await;
+    session
+        .record_into_history(
+            &[user_message("question 1"), assistant_message("answer 1")],
+            &turn_context,
+        )
+        .await;
+    let expected = session.clone_history().await.raw_items().to_vec();
+    let id = session.checkpoint().await.expect("save checkpoint");
+
+    session
+        .record_into_history(
+            &[user_message("question 2"), assistant_message("answer 2")],
+            &turn_context,
+        )
+        .await;
+    session
+        .branch_from(&id)
+        .await
+        .expect("branch from checkpoint");
+
+    assert_eq!(session.clone_history().await.raw_items(), expected);
+    assert!(session.branch_from(&CheckpointId::new()).await.is_err());
+}
+
+#[tokio::test]
+async fn pending_tool_calls_are_recovered_from_checkpoint() {
+    let (session, turn_context) = make_session_and_context().await;
+    let function_call = |name: &str, call_id: &str| ResponseItem::FunctionCall {
+        id: None,
+        name: name.to_string(),
+        arguments: "{}".to_string(),
+        call_id: call_id.to_string(),
+    };
+    session
+        .record_into_history(
+            &[
+                user_message("run the tools"),
+                function_call("read_file", "call-done"),
+                ResponseItem::FunctionCallOutput {
+                    call_id: "call-done".to_string(),
+                    output: FunctionCallOutputPayload::from_text("ok".to_string()),
+                },
+                function_call("shell", "call-1"),
+                function_call("read_file", "call-2"),
+            ],
+            &turn_context,
+        )
+        .await;
+    let store: Arc<dyn CheckpointStore> =
+        Arc::new(crate::checkpoint::InMemoryCheckpointStore::default());
+    let id = session
+        .save_checkpoint(Arc::clone(&store))
+        .await
+        .expect("save checkpoint");
+    let (restored, _) = make_session_and_context().await;
+
+    restored
+        .load_checkpoint(store, &id)
+        .await
+        .expect("load checkpoint");
+
+    assert_eq!(
+        restored.get_pending_tool_calls().await,
+        vec![
+            PendingToolCall {
+                call_id: "call-1".to_string(),
+                tool_name: "shell".to_string(),
+                item: function_call("shell", "call-1"),
+            },
+            PendingToolCall {
+                call_id: "call-2".to_string(),
+                tool_name: "read_file".to_string(),
+                item: function_call("read_file", "call-2"),
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn replay_from_checkpoint_restores_full_and_compacted_history() {
+    let (session, turn_context) = make_session_and_context().await;
+    session
+        .record_context_updates_and_set_reference_context_item(&turn_context)
+        .await;
+    for turn in 1..=3 {
+        session
+            .record_into_history(
+                &[
+                    user_message(&format!("question {turn}")),
+                    assistant_message(&format!("answer {turn}")),
+                ],
+                &turn_context,
+            )
+            .await;
+    }
+    let store: Arc<dyn CheckpointStore> =
+        Arc::new(crate::checkpoint::InMemoryCheckpointStore::default());
+    let read_checkpoint = |id: CheckpointId| {
+        let store = Arc::clone(&store);
+        async move {
+            let bytes = store
+                .get(&id)
+                .await
+                .expect("read checkpoint")
+                .expect("checkpoint exists");
+            serde_json::from_slice::<crate::checkpoint::SessionCheckpoint>(&bytes)
+                .expect("decode checkpoint")
+        }
+    };
+
+    let full_id = session
+        .save_checkpoint(Arc::clone(&store))
+        .await
+        .expect("save full checkpoint");
+    let full_history = session.clone_history().await.raw_items().to_vec();
+
+    // Manual compaction drops the initial context and the reference context
+    // item, leaving only the user messages and the summary.
+    let summary = format!(
+        "{}\nanswered three questions",
+        crate::compact::SUMMARY_PREFIX
+    );
+    let compacted_history = crate::compact::build_compacted_history(
+        Vec::new(),
+        &[
+            "question 1".to_string(),
+            "question 2".to_string(),
+            "question 3".to_string(),
+        ],
+        &summary,
+    );
+    session
+        .replace_compacted_history(
+            compacted_history.clone(),
+            None,
+            CompactedItem {
+                message: summary,
+                replacement_history: Some(compacted_history.clone()),
+            },
+        )
+        .await;
+    let compacted_id = session
+        .save_checkpoint(Arc::clone(&store))
+        .await
+        .expect("save compacted checkpoint");
+
+    let (from_full, _) = make_session_and_context().await;
+    from_full
+        .replay_from_checkpoint(read_checkpoint(full_id).await)
+        .await
+        .expect("replay full checkpoint");
+    let (from_compacted, _) = make_session_and_context().await;
+    from_compacted
+        .replay_from_checkpoint(read_checkpoint(compacted_id).await)
+        .await
+        .expect("replay compacted checkpoint");
+
+    async fn tokens_in_use(session: &Session) -> i64 {
+        session
+            .state
+            .lock()
+            .await
+            .token_info()
+            .expect("token info")
+            .last_token_usage
+            .total_tokens
+    }
+    assert!(tokens_in_use(&from_compacted).await < tokens_in_use(&from_full).await);
+
+    // The full checkpoint already carries this context, so the next turn adds
+    // nothing; the compacted one gets the full initial context back.
+    from_full
+        .record_context_updates_and_set_reference_context_item(&turn_context)
+        .await;
+    assert_eq!(from_full.clone_history().await.raw_items(), full_history);
+
+    from_compacted
+        .record_context_updates_and_set_reference_context_item(&turn_context)
+        .await;
+    let mut expected = compacted_history;
+    expected.extend(from_compacted.build_initial_context(&turn_context).await);
+    assert_eq!(from_compacted.clone_history().await.raw_items(), expected);
+    assert!(from_compacted.reference_context_item().await.is_some());
+}
+
+#[tokio::test]
+async fn playback_reproduces_recorded_session_events() {
+    use crate::compact::InitialContextInjection;
+    use crate::compact::run_inline_auto_compact_task;
+    use crate::session_recording::RecordedEvent;
+    use crate::session_recording::RecordedExchange;
+    use crate::session_recording::Recording;
+
+    async fn compact_and_collect_events(
+        sess: &Arc<Session>,
+        tc: &Arc<TurnContext>,
+        rx: &async_channel::Receiver<Event>,
+    ) -> Vec<String> {
+        run_inline_auto_compact_task(
+            Arc::clone(sess),
+            Arc::clone(tc),
+            InitialContextInjection::DoNotInject,
+        )
+        .await
+        .expect("compaction should succeed");
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event.msg.to_string());
+        }
+        events
+    }
+
+    // Stands in for the provider while the original session is recorded.
+    let summary = "Cloned the repo and fixed the flaky parser test.";
+    let provider = Recording {
+        exchanges: vec![RecordedExchange {
+            input: Vec::new(),
+            events: vec![
+                RecordedEvent::OutputItemDone {
+                    item: ResponseItem::Message {
+                        id: None,
+                        role: "assistant".to_string(),
+                        content: vec![ContentItem::OutputText {
+                            text: summary.to_string(),
+                        }],
+                        end_turn: None,
+                        phase: None,
+                    },
+                },
+                RecordedEvent::Completed {
+                    response_id: "resp-1".to_string(),
+                    token_usage: None,
+                },
+            ],
+        }],
+    };
+    let (original, original_tc, original_rx) = make_session_and_context_with_rx().await;
+    original
+        .services
+        .model_client
+        .start_playback(provider.clone());
+    let handle = original.record();
+    let recorded_events = compact_and_collect_events(&original, &original_tc, &original_rx).await;
+
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("golden.json");
+    handle.save(&path).expect("save recording");
+    let recording = handle.snapshot();
+    assert_eq!(recording.exchanges.len(), 1);
+    assert_eq!(recording.exchanges[0].events, provider.exchanges[0].events);
+
+    let (replayed, replayed_tc, replayed_rx) = make_session_and_context_with_rx().await;
+    replayed.playback(&path).expect("load recording");
+    let replayed_events = compact_and_collect_events(&replayed, &replayed_tc, &replayed_rx).await;
+
+    assert!(!recorded_events.is_empty());
+    assert_eq!(replayed_events, recorded_events);
+    assert_eq!(
+        replayed.clone_history().await.raw_items(),
+        original.clone_history().await.raw_items()
+    );
+}
@@ -97,6 +97,7 @@ async fn guardian_allows_shell_additional_permissions_requests_past_policy_valid
                 })
                 .to_string(),
             },
+            dry_run: false,
         })
         .await;
 
@@ -161,6 +162,7 @@ async fn guardian_allows_unified_exec_additional_permissions_requests_past_polic
                 })
                 .to_string(),
             },
+            dry_run: false,
         })
         .await;
 
@@ -1,13 +1,28 @@
 use crate::agent::AgentStatus;
+use crate::checkpoint::CheckpointId;
+use crate::checkpoint::CheckpointStore;
 use crate::codex::Codex;
 use crate::codex::SteerInputError;
 use crate::config::ConstraintResult;
+use crate::context_item::ContextItem;
 use crate::error::Result as CodexResult;
 use crate::features::Feature;
 use crate::file_watcher::WatchRegistration;
 use crate::protocol::Event;
 use crate::protocol::Op;
 use crate::protocol::Submission;
+use crate::tools::audit::AuditHook;
+use crate::tools::call_events::ToolCallEvent;
+use crate::tools::in_flight::InFlightToolCall;
+use crate::tools::in_flight::PendingToolCall;
+use crate::tools::middleware::AfterAllHook;
+use crate::tools::middleware::BeforeAllHook;
+use crate::tools::middleware::DispatchInterceptor;
+use crate::tools::middleware::SandboxPermissionsHook;
+use crate::tools::output_cache::ToolOutputCache;
+use crate::tools::output_transform::ToolOutputTransformer;
+use crate::tools::router::ArchivedToolCall;
+use crate::tools::router::TurnToolOverrides;
 use codex_protocol::config_types::Personality;
 use codex_protocol::config_types::ServiceTier;
 use codex_protocol::models::ContentItem;
@@ -19,7 +34,9 @@ use codex_protocol::protocol::SandboxPolicy;
 use codex_protocol::protocol::SessionSource;
 use codex_protocol::protocol::TokenUsage;
 use codex_protocol::user_input::UserInput;
+use futures::Stream;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tokio::sync::watch;
 
 use crate::state_db::StateDbHandle;
@@ -143,4 +160,126 @@ impl CodexThread {
     pub fn enabled(&self, feature: Feature) -> bool {
         self.codex.enabled(feature)
     }
+
+    /// Disables tools, or bounds every tool dispatch, for the next turn
+    /// started by user input only, e.g. to keep web search out of a
+    /// refactoring turn. Replaces overrides not yet applied.
+    pub async fn set_next_turn_tool_overrides(&self, overrides: TurnToolOverrides) {
+        self.codex
+            .session
+            .set_next_turn_tool_overrides(overrides)
+            .await;
+    }
+
+    /// Describes in plain English the tools used during `turn_id`, the id of
+    /// the submission that started the turn, e.g. "In this turn, the model
+    /// read 3 files and ran 2 shell commands."
+    pub async fn summarize_tools_used(&self, turn_id: &str) -> String {
+        self.codex.session.summarize_tools_used(turn_id).await
+    }
+
+    /// Passes the result of every later tool call in this thread through
+    /// `transformer` before it reaches the model. Transformers run in
+    /// registration order.
+    pub async fn apply_tool_output_transformer(&self, transformer: Arc<dyn ToolOutputTransformer>) {
+        self.codex
+            .session
+            .apply_tool_output_transformer(transformer)
+            .await;
+    }
+
+    /// Tool calls in this thread that have been dispatched and have not
+    /// finished yet, oldest first.
+    pub fn get_active_tool_calls(&self) -> Vec<InFlightToolCall> {
+        self.codex.session.get_active_tool_calls()
+    }
+
+    /// Saves this thread's conversation history to `store` and returns the
+    /// id to restore it with [`CodexThread::load_checkpoint`], possibly in
+    /// another process.
+    pub async fn save_checkpoint(
+        &self,
+        store: Arc<dyn CheckpointStore>,
+    ) -> CodexResult<CheckpointId> {
+        self.codex.session.save_checkpoint(store).await
+    }
+
+    /// Replaces this thread's conversation history with the checkpoint saved
+    /// under `id` in `store`.
+    pub async fn load_checkpoint(
+        &self,
+        store: Arc<dyn CheckpointStore>,
+        id: &CheckpointId,
+    ) -> CodexResult<()> {
+        self.codex.session.load_checkpoint(store, id).await
+    }
+
+    /// Answers later read-only tool calls in this thread from `cache` when it
+    /// holds the output of an identical earlier call, emitting
+    /// `EventMsg::ToolCacheHit` instead of dispatching.
+    pub async fn with_tool_output_cache(&self, cache: Arc<dyn ToolOutputCache>) {
+        self.codex.session.with_tool_output_cache(cache).await;
+    }
+
+    /// Appends `item` to this thread's history without starting a turn, e.g.
+    /// reference docs the model should see from the next request on.
+    pub async fn push_context_item(&self, item: ContextItem) {
+        self.codex.session.push_context_item(item).await;
+    }
+
+    /// Streams a [`ToolCallEvent`] when each tool call dispatched in this
+    /// thread from now on starts and again when it finishes. Dropping the
+    /// stream unsubscribes.
+    pub fn subscribe_to_tool_calls(&self) -> impl Stream<Item = ToolCallEvent> + use<> {
+        self.codex.session.subscribe_to_tool_calls()
+    }
+
+    /// Tool calls in this thread's history that have no recorded result and
+    /// are not running, oldest first. After [`CodexThread::load_checkpoint`]
+    /// these are the calls that were in flight when the checkpoint was
+    /// saved, for the caller to re-dispatch or answer with a failure.
+    pub async fn get_pending_tool_calls(&self) -> Vec<PendingToolCall> {
+        self.codex.session.get_pending_tool_calls().await
+    }
+
+    /// Looks up a tool call dispatched earlier in this thread by its call id.
+    /// Only the most recent calls are kept.
+    pub fn get_call_by_id(&self, call_id: &str) -> Option<ArchivedToolCall> {
+        self.codex.get_call_by_id(call_id)
+    }
+
+    /// Runs `hook` before every tool call dispatched in this thread, whatever
+    /// the tool, from the next sampling request on. See
+    /// [`BeforeAllHook::before`].
+    pub fn register_before_all(&self, hook: Arc<dyn BeforeAllHook>) {
+        self.codex.session.register_before_all(hook);
+    }
+
+    /// Runs `hook` after every tool call dispatched in this thread, whatever
+    /// the tool or outcome, from the next sampling request on.
+    pub fn register_after_all(&self, hook: Arc<dyn AfterAllHook>) {
+        self.codex.session.register_after_all(hook);
+    }
+
+    /// Reports every tool call dispatched in this thread to `hook`, with its
+    /// redacted arguments and outcome, from the next sampling request on.
+    /// See [`AuditHook::record`].
+    pub fn register_audit_hook(&self, hook: Arc<dyn AuditHook>) {
+        self.codex.session.register_audit_hook(hook);
+    }
+
+    /// Hands every tool call dispatched in this thread to `interceptor`,
+    /// replacing any previous interceptor, from the next sampling request on.
+    /// See [`DispatchInterceptor::intercept`].
+    pub fn set_dispatch_interceptor(&self, interceptor: Arc<dyn DispatchInterceptor>) {
+        self.codex.session.set_dispatch_interceptor(interceptor);
+    }
+
+    /// Lets `hook` decide the sandbox permissions of every local shell call
+    /// in this thread, or veto the command, replacing any previous hook,
+    /// from the next sampling request on. See
+    /// [`SandboxPermissionsHook::resolve`].
+    pub fn set_sandbox_permissions_hook(&self, hook: Arc<dyn SandboxPermissionsHook>) {
+        self.codex.session.set_sandbox_permissions_hook(hook);
+    }
 }
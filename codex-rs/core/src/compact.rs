@@ -1,6 +1,7 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::sync::Arc;
 
-use crate::ModelProviderInfo;
 use crate::Prompt;
 use crate::client::ModelClientSession;
 use crate::client_common::ResponseEvent;
@@ -9,6 +10,10 @@ use crate::codex::PreviousTurnSettings;
 use crate::codex::Session;
 use crate::codex::TurnContext;
 use crate::codex::get_last_assistant_message_from_turn;
+use crate::config::types::CompactionMode;
+use crate::config::types::CompactionStrategy;
+use crate::context_manager::ContextManager;
+use crate::context_manager::estimate_item_token_count;
 use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
 use crate::protocol::CompactedItem;
@@ -18,7 +23,6 @@ use crate::protocol::WarningEvent;
 use crate::truncate::TruncationPolicy;
 use crate::truncate::approx_token_count;
 use crate::truncate::truncate_text;
-use crate::util::backoff;
 use codex_protocol::items::ContextCompactionItem;
 use codex_protocol::items::TurnItem;
 use codex_protocol::models::ContentItem;
@@ -26,11 +30,35 @@ use codex_protocol::models::ResponseInputItem;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::user_input::UserInput;
 use futures::prelude::*;
+use thiserror::Error;
 use tracing::error;
+use tracing::info;
 
 pub const SUMMARIZATION_PROMPT: &str = include_str!("../templates/compact/prompt.md");
 pub const SUMMARY_PREFIX: &str = include_str!("../templates/compact/summary_prefix.md");
+/// Instructions for [`merge_compacts`], followed by the summaries to merge.
+const MERGE_PROMPT: &str = include_str!("../templates/compact/merge.md");
+/// Appended to summarization prompts that do not place `{{language}}`
+/// themselves when the session has a preferred language.
+const LANGUAGE_INSTRUCTION: &str = include_str!("../templates/compact/language.md");
+const LANGUAGE_PLACEHOLDER: &str = "{{language}}";
+/// Substituted for `{{language}}` when no language is preferred.
+const DEFAULT_SUMMARY_LANGUAGE: &str = "the language of the conversation";
 const COMPACT_USER_MESSAGE_MAX_TOKENS: usize = 20_000;
+/// Budget for each chunk summarized by
+/// [`CompactionStrategy::HierarchicalSummaries`]. A single turn larger than
+/// this is still summarized as one chunk.
+const HIERARCHICAL_CHUNK_MAX_TOKENS: i64 = 32_000;
+/// Stands in for the summary under [`CompactionStrategy::TruncateOldest`].
+const TRUNCATED_HISTORY_NOTE: &str =
+    "Older messages in this thread were dropped without being summarized.";
+
+// Adaptive summary budgets, as fractions of the context window. Between the
+// two utilization thresholds the budget shrinks linearly.
+const RELAXED_SUMMARY_FRACTION: f64 = 0.1;
+const PRESSURED_SUMMARY_FRACTION: f64 = 0.05;
+const RELAXED_CONTEXT_UTILIZATION: f64 = 0.5;
+const PRESSURED_CONTEXT_UTILIZATION: f64 = 0.8;
 
 /// Controls whether compaction replacement history must include initial context.
 ///
@@ -47,8 +75,69 @@ pub(crate) enum InitialContextInjection {
     DoNotInject,
 }
 
-pub(crate) fn should_use_remote_compact_task(provider: &ModelProviderInfo) -> bool {
-    provider.is_openai()
+/// Where `turn_context` compacts: as forced by `compaction_mode`, or
+/// remotely for OpenAI providers and locally otherwise.
+pub(crate) fn compact_strategy(turn_context: &TurnContext) -> CompactStrategy {
+    match turn_context.config.compaction_mode {
+        CompactionMode::Auto if turn_context.provider.is_openai() => CompactStrategy::Remote,
+        CompactionMode::Auto | CompactionMode::Local => CompactStrategy::Local,
+        CompactionMode::Remote => CompactStrategy::Remote,
+    }
+}
+
+/// Where the summary is written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompactStrategy {
+    /// The model summarizes the history in an ordinary turn.
+    Local,
+    /// The provider's compaction endpoint returns the replacement history.
+    Remote,
+}
+
+/// Options for one compaction, shared by the local and remote tasks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompactContext {
+    /// Model that writes the summary.
+    pub model_name: String,
+    /// Length budget appended to the summarization prompt. Local only.
+    pub max_summary_tokens: Option<i64>,
+    pub strategy: CompactStrategy,
+    /// BCP-47 tag of the language to write the summary in. Local only.
+    pub preferred_language: Option<String>,
+    /// How the history is squashed. Local only.
+    pub local_strategy: CompactionStrategy,
+}
+
+impl CompactContext {
+    /// The options `sess` compacts with by default in `turn_context`.
+    pub(crate) async fn for_turn(sess: &Session, turn_context: &TurnContext) -> Self {
+        let max_summary_tokens = match turn_context.model_context_window() {
+            Some(context_window) if turn_context.config.compact_adaptive_summary_length => {
+                let tokens_in_use = sess.get_total_token_usage().await;
+                Some(adaptive_summary_target_tokens(
+                    context_window,
+                    tokens_in_use,
+                ))
+            }
+            _ => None,
+        };
+        Self {
+            model_name: turn_context.model_info.slug.clone(),
+            max_summary_tokens,
+            strategy: compact_strategy(turn_context),
+            preferred_language: sess.preferred_language().await,
+            local_strategy: turn_context.config.compaction_strategy,
+        }
+    }
+
+    /// `prompt` with the preferred language and length budget applied.
+    pub(crate) fn summarization_prompt(&self, prompt: &str) -> String {
+        let prompt = with_language_instruction(prompt, self.preferred_language.as_deref());
+        match self.max_summary_tokens {
+            Some(target_tokens) => with_summary_length_instruction(&prompt, target_tokens),
+            None => prompt,
+        }
+    }
 }
 
 pub(crate) async fn run_inline_auto_compact_task(
@@ -56,14 +145,21 @@ pub(crate) async fn run_inline_auto_compact_task(
     turn_context: Arc<TurnContext>,
     initial_context_injection: InitialContextInjection,
 ) -> CodexResult<()> {
-    let prompt = turn_context.compact_prompt().to_string();
+    let compact = CompactContext::for_turn(&sess, &turn_context).await;
     let input = vec![UserInput::Text {
-        text: prompt,
+        text: compact.summarization_prompt(turn_context.compact_prompt()),
         // Compaction prompt is synthesized; no UI element ranges to preserve.
         text_elements: Vec::new(),
     }];
 
-    run_compact_task_inner(sess, turn_context, input, initial_context_injection).await?;
+    run_compact_task_inner(
+        sess,
+        turn_context,
+        input,
+        &compact,
+        initial_context_injection,
+    )
+    .await?;
     Ok(())
 }
 
@@ -71,6 +167,7 @@ pub(crate) async fn run_compact_task(
     sess: Arc<Session>,
     turn_context: Arc<TurnContext>,
     input: Vec<UserInput>,
+    compact: CompactContext,
 ) -> CodexResult<()> {
     let start_event = EventMsg::TurnStarted(TurnStartedEvent {
         turn_id: turn_context.sub_id.clone(),
@@ -78,10 +175,22 @@ pub(crate) async fn run_compact_task(
         collaboration_mode_kind: turn_context.collaboration_mode.mode,
     });
     sess.send_event(&turn_context, start_event).await;
+    run_started_compact_task(sess, turn_context, input, compact).await
+}
+
+/// [`run_compact_task`] for a turn whose start has already been reported,
+/// e.g. by a remote compaction that failed.
+pub(crate) async fn run_started_compact_task(
+    sess: Arc<Session>,
+    turn_context: Arc<TurnContext>,
+    input: Vec<UserInput>,
+    compact: CompactContext,
+) -> CodexResult<()> {
     run_compact_task_inner(
         sess.clone(),
         turn_context,
         input,
+        &compact,
         InitialContextInjection::DoNotInject,
     )
     .await
@@ -91,110 +200,139 @@ async fn run_compact_task_inner(
     sess: Arc<Session>,
     turn_context: Arc<TurnContext>,
     input: Vec<UserInput>,
+    compact: &CompactContext,
     initial_context_injection: InitialContextInjection,
 ) -> CodexResult<()> {
+    info!(
+        model = %compact.model_name,
+        "compacting conversation history"
+    );
     let compaction_item = TurnItem::ContextCompaction(ContextCompactionItem::new());
     sess.emit_turn_item_started(&turn_context, &compaction_item)
         .await;
     let initial_input_for_turn: ResponseInputItem = ResponseInputItem::from(input);
 
+    let pinned_turns = sess.pinned_turn_items().await;
     let mut history = sess.clone_history().await;
-    history.record_items(
-        &[initial_input_for_turn.into()],
-        turn_context.truncation_policy,
-    );
-
-    let mut truncated_count = 0usize;
-
-    let max_retries = turn_context.provider.stream_max_retries();
-    let mut retries = 0;
-    let mut client_session = sess.services.model_client.new_session();
-    // Reuse one client session so turn-scoped state (sticky routing, websocket incremental
-    // request tracking)
-    // survives retries within this compact turn.
-
-    loop {
-        // Clone is required because of the loop
-        let turn_input = history
-            .clone()
-            .for_prompt(&turn_context.model_info.input_modalities);
-        let turn_input_len = turn_input.len();
-        let prompt = Prompt {
-            input: turn_input,
-            base_instructions: sess.get_base_instructions().await,
-            personality: turn_context.personality,
-            ..Default::default()
-        };
-        let turn_metadata_header = turn_context.turn_metadata_state.current_header_value();
-        let attempt_result = drain_to_completed(
-            &sess,
-            turn_context.as_ref(),
-            &mut client_session,
-            turn_metadata_header.as_deref(),
-            &prompt,
-        )
-        .await;
-
-        match attempt_result {
-            Ok(()) => {
-                if truncated_count > 0 {
-                    sess.notify_background_event(
-                        turn_context.as_ref(),
-                        format!(
-                            "Trimmed {truncated_count} older thread item(s) before compacting so the prompt fits the model context window."
-                        ),
-                    )
-                    .await;
+    history.replace(reorder_history_for_coherence(select_turns_for_compaction(
+        history.raw_items(),
+        &pinned_turns,
+    )));
+    let summarization_request: ResponseItem = initial_input_for_turn.into();
+    // Summaries written outside an ordinary summarization turn, which are not
+    // recorded in history.
+    let precomputed_summary = match compact.local_strategy {
+        CompactionStrategy::Summarize => None,
+        CompactionStrategy::TruncateOldest => Some(TRUNCATED_HISTORY_NOTE.to_string()),
+        CompactionStrategy::HierarchicalSummaries => {
+            match summarize_in_chunks(&sess, &turn_context, &history, &summarization_request).await
+            {
+                Ok(summary) => Some(summary.text),
+                Err(CodexErr::Interrupted) => return Err(CodexErr::Interrupted),
+                Err(e) => {
+                    let event = EventMsg::Error(e.to_error_event(None));
+                    sess.send_event(&turn_context, event).await;
+                    return Err(e);
                 }
-                break;
-            }
-            Err(CodexErr::Interrupted) => {
-                return Err(CodexErr::Interrupted);
             }
-            Err(e @ CodexErr::ContextWindowExceeded) => {
-                if turn_input_len > 1 {
-                    // Trim from the beginning to preserve cache (prefix-based) and keep recent messages intact.
-                    error!(
-                        "Context window exceeded while compacting; removing oldest history item. Error: {e}"
-                    );
-                    history.remove_first_item();
-                    truncated_count += 1;
-                    retries = 0;
-                    continue;
+        }
+    };
+    history.record_items(&[summarization_request], turn_context.truncation_policy);
+
+    if precomputed_summary.is_none() {
+        let mut truncated_count = 0usize;
+
+        let max_retries = turn_context.provider.stream_max_retries();
+        let mut retries = 0;
+        let mut client_session = sess.model_client_for(turn_context.as_ref()).new_session();
+        // Reuse one client session so turn-scoped state (sticky routing, websocket incremental
+        // request tracking)
+        // survives retries within this compact turn.
+
+        loop {
+            // Clone is required because of the loop
+            let turn_input = history
+                .clone()
+                .for_prompt(&turn_context.model_info.input_modalities);
+            let turn_input_len = turn_input.len();
+            let prompt = Prompt {
+                input: turn_input,
+                base_instructions: sess.get_base_instructions().await,
+                personality: turn_context.personality,
+                ..Default::default()
+            };
+            let turn_metadata_header = turn_context.turn_metadata_state.current_header_value();
+            let attempt_result = drain_to_completed(
+                &sess,
+                turn_context.as_ref(),
+                &mut client_session,
+                turn_metadata_header.as_deref(),
+                &prompt,
+            )
+            .await;
+
+            match attempt_result {
+                Ok(()) => {
+                    if truncated_count > 0 {
+                        sess.notify_background_event(
+                            turn_context.as_ref(),
+                            format!(
+                                "Trimmed {truncated_count} older thread item(s) before compacting so the prompt fits the model context window."
+                            ),
+                        )
+                        .await;
+                    }
+                    break;
                 }
-                sess.set_total_tokens_full(turn_context.as_ref()).await;
-                let event = EventMsg::Error(e.to_error_event(None));
-                sess.send_event(&turn_context, event).await;
-                return Err(e);
-            }
-            Err(e) => {
-                if retries < max_retries {
-                    retries += 1;
-                    let delay = backoff(retries);
-                    sess.notify_stream_error(
-                        turn_context.as_ref(),
-                        format!("Reconnecting... {retries}/{max_retries}"),
-                        e,
-                    )
-                    .await;
-                    tokio::time::sleep(delay).await;
-                    continue;
-                } else {
+                Err(CodexErr::Interrupted) => {
+                    return Err(CodexErr::Interrupted);
+                }
+                Err(e @ CodexErr::ContextWindowExceeded) => {
+                    if turn_input_len > 1 {
+                        // Trim from the beginning to preserve cache (prefix-based) and keep recent messages intact.
+                        error!(
+                            "Context window exceeded while compacting; removing oldest history item. Error: {e}"
+                        );
+                        history.remove_first_item();
+                        truncated_count += 1;
+                        retries = 0;
+                        continue;
+                    }
+                    sess.set_total_tokens_full(turn_context.as_ref()).await;
                     let event = EventMsg::Error(e.to_error_event(None));
                     sess.send_event(&turn_context, event).await;
                     return Err(e);
                 }
+                Err(e) => {
+                    if retries < max_retries {
+                        retries += 1;
+                        let delay = turn_context.provider.retry_delay(retries);
+                        sess.notify_stream_error(
+                            turn_context.as_ref(),
+                            format!("Reconnecting... {retries}/{max_retries}"),
+                            e,
+                        )
+                        .await;
+                        tokio::time::sleep(delay).await;
+                        continue;
+                    } else {
+                        let event = EventMsg::Error(e.to_error_event(None));
+                        sess.send_event(&turn_context, event).await;
+                        return Err(e);
+                    }
+                }
             }
         }
     }
 
     let history_snapshot = sess.clone_history().await;
     let history_items = history_snapshot.raw_items();
-    let summary_suffix = get_last_assistant_message_from_turn(history_items).unwrap_or_default();
+    let summary_suffix = precomputed_summary
+        .or_else(|| get_last_assistant_message_from_turn(history_items))
+        .unwrap_or_default();
     let summary_text = format!("{SUMMARY_PREFIX}\n{summary_suffix}");
-    let user_messages = collect_user_messages(history_items);
-
-    let mut new_history = build_compacted_history(Vec::new(), &user_messages, &summary_text);
+    let mut new_history =
+        build_compacted_history_keeping_pinned_turns(history_items, &pinned_turns, &summary_text);
 
     if matches!(
         initial_context_injection,
@@ -231,6 +369,298 @@ async fn run_compact_task_inner(
     Ok(())
 }
 
+/// The summary written by one compaction, without [`SUMMARY_PREFIX`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompactSummary {
+    pub text: String,
+}
+
+impl CompactSummary {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self { text: text.into() }
+    }
+
+    /// The summary carried by a compaction message as recorded in history.
+    pub fn from_message(message: &str) -> Self {
+        let text = message
+            .strip_prefix(SUMMARY_PREFIX)
+            .map_or(message, |text| text.strip_prefix('\n').unwrap_or(text));
+        Self::new(text)
+    }
+}
+
+/// Combines the summaries of successive compactions, oldest first, into one
+/// by asking the model to summarize them together. The request is not
+/// recorded in the session history. Fewer than two summaries are returned
+/// without a model call.
+pub(crate) async fn merge_compacts(
+    sess: &Session,
+    turn_context: &TurnContext,
+    summaries: &[CompactSummary],
+) -> CodexResult<CompactSummary> {
+    match summaries {
+        [] => return Ok(CompactSummary::default()),
+        [summary] => return Ok(summary.clone()),
+        _ => {}
+    }
+    let preferred_language = sess.preferred_language().await;
+    let instructions = with_language_instruction(MERGE_PROMPT, preferred_language.as_deref());
+    let prompt = Prompt {
+        input: vec![merge_compacts_input(&instructions, summaries)],
+        base_instructions: sess.get_base_instructions().await,
+        personality: turn_context.personality,
+        ..Default::default()
+    };
+    collect_assistant_text_with_retries(sess, turn_context, &prompt)
+        .await
+        .map(CompactSummary::new)
+}
+
+/// Summarizes `history` for [`CompactionStrategy::HierarchicalSummaries`]:
+/// each chunk of at most [`HIERARCHICAL_CHUNK_MAX_TOKENS`], followed by
+/// `summarization_request`, is summarized on its own, and the chunk summaries
+/// are merged with [`merge_compacts`]. Nothing is recorded in the session
+/// history.
+async fn summarize_in_chunks(
+    sess: &Session,
+    turn_context: &TurnContext,
+    history: &ContextManager,
+    summarization_request: &ResponseItem,
+) -> CodexResult<CompactSummary> {
+    let base_instructions = sess.get_base_instructions().await;
+    let mut summaries = Vec::new();
+    for chunk in split_into_chunks(history.raw_items(), HIERARCHICAL_CHUNK_MAX_TOKENS) {
+        let mut chunk_history = history.clone();
+        chunk_history.replace(chunk.to_vec());
+        chunk_history.record_items(
+            std::slice::from_ref(summarization_request),
+            turn_context.truncation_policy,
+        );
+        let prompt = Prompt {
+            input: chunk_history.for_prompt(&turn_context.model_info.input_modalities),
+            base_instructions: base_instructions.clone(),
+            personality: turn_context.personality,
+            ..Default::default()
+        };
+        let text = collect_assistant_text_with_retries(sess, turn_context, &prompt).await?;
+        summaries.push(CompactSummary::new(text));
+    }
+    merge_compacts(sess, turn_context, &summaries).await
+}
+
+/// Splits `items` into runs of whole turns of at most `max_tokens` each,
+/// oldest first. A turn starts at a user message; a turn larger than
+/// `max_tokens` gets a run of its own.
+fn split_into_chunks(items: &[ResponseItem], max_tokens: i64) -> Vec<&[ResponseItem]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut last_turn_start = 0;
+    let mut tokens = 0i64;
+    for (index, item) in items.iter().enumerate() {
+        if index > start && matches!(item, ResponseItem::Message { role, .. } if role == "user") {
+            last_turn_start = index;
+        }
+        tokens = tokens.saturating_add(estimate_item_token_count(item));
+        if tokens > max_tokens && last_turn_start > start {
+            chunks.push(&items[start..last_turn_start]);
+            start = last_turn_start;
+            tokens = items[start..=index]
+                .iter()
+                .map(estimate_item_token_count)
+                .fold(0i64, i64::saturating_add);
+        }
+    }
+    if start < items.len() {
+        chunks.push(&items[start..]);
+    }
+    chunks
+}
+
+/// The single user message asking the model to merge `summaries`.
+fn merge_compacts_input(instructions: &str, summaries: &[CompactSummary]) -> ResponseItem {
+    let summaries = summaries
+        .iter()
+        .enumerate()
+        .map(|(index, summary)| {
+            format!(
+                "<summary index=\"{}\">\n{}\n</summary>",
+                index + 1,
+                summary.text.trim()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: format!("{}\n\n{summaries}", instructions.trim_end()),
+        }],
+        end_turn: None,
+        phase: None,
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub(crate) enum PinError {
+    #[error("turn {0} has no items in the current history")]
+    UnknownTurn(String),
+}
+
+/// The history items compaction may summarize away: `items` minus every
+/// pinned turn. Each pinned turn is removed where its items appear as a
+/// contiguous run; a pinned turn no longer present is ignored.
+pub(crate) fn select_turns_for_compaction(
+    items: &[ResponseItem],
+    pinned_turns: &[Vec<ResponseItem>],
+) -> Vec<ResponseItem> {
+    let mut candidates = items.to_vec();
+    for pinned in pinned_turns.iter().filter(|pinned| !pinned.is_empty()) {
+        if let Some(start) = candidates
+            .windows(pinned.len())
+            .position(|window| window == pinned.as_slice())
+        {
+            candidates.drain(start..start + pinned.len());
+        }
+    }
+    candidates
+}
+
+/// Moves every tool output recorded before its call to just after the call,
+/// which parallel dispatch and streamed MCP results occasionally produce.
+/// All other items, including outputs whose call is missing, keep their
+/// relative order.
+pub(crate) fn reorder_history_for_coherence(items: Vec<ResponseItem>) -> Vec<ResponseItem> {
+    let call_ids: HashSet<String> = items
+        .iter()
+        .filter_map(tool_call_id)
+        .map(str::to_string)
+        .collect();
+    let mut seen_calls = HashSet::new();
+    let mut early_outputs: HashMap<String, Vec<ResponseItem>> = HashMap::new();
+    let mut reordered = Vec::with_capacity(items.len());
+    for item in items {
+        if let Some(call_id) = tool_output_call_id(&item)
+            && call_ids.contains(call_id)
+            && !seen_calls.contains(call_id)
+        {
+            early_outputs
+                .entry(call_id.to_string())
+                .or_default()
+                .push(item);
+            continue;
+        }
+        let call_id = tool_call_id(&item).map(str::to_string);
+        reordered.push(item);
+        if let Some(call_id) = call_id {
+            reordered.extend(early_outputs.remove(&call_id).unwrap_or_default());
+            seen_calls.insert(call_id);
+        }
+    }
+    reordered
+}
+
+fn tool_call_id(item: &ResponseItem) -> Option<&str> {
+    match item {
+        ResponseItem::FunctionCall { call_id, .. }
+        | ResponseItem::CustomToolCall { call_id, .. }
+        | ResponseItem::CustomToolCallWithAttachments { call_id, .. }
+        | ResponseItem::LocalShellCall {
+            call_id: Some(call_id),
+            ..
+        } => Some(call_id),
+        _ => None,
+    }
+}
+
+fn tool_output_call_id(item: &ResponseItem) -> Option<&str> {
+    match item {
+        ResponseItem::FunctionCallOutput { call_id, .. }
+        | ResponseItem::CustomToolCallOutput { call_id, .. } => Some(call_id),
+        _ => None,
+    }
+}
+
+/// The summarization prompt for `turn_context`. With
+/// `compact_adaptive_summary_length` enabled it ends with a length budget
+/// derived from how full the context window currently is.
+pub(crate) async fn compaction_prompt(sess: &Session, turn_context: &TurnContext) -> String {
+    CompactContext::for_turn(sess, turn_context)
+        .await
+        .summarization_prompt(turn_context.compact_prompt())
+}
+
+/// Fills `{{language}}` in `prompt` with `language` (a BCP-47 tag). Prompts
+/// without the placeholder get [`LANGUAGE_INSTRUCTION`] appended, and are
+/// left as-is when no language is preferred.
+fn with_language_instruction(prompt: &str, language: Option<&str>) -> String {
+    let language = language.map(language_display_name);
+    if prompt.contains(LANGUAGE_PLACEHOLDER) {
+        return prompt.replace(
+            LANGUAGE_PLACEHOLDER,
+            language.as_deref().unwrap_or(DEFAULT_SUMMARY_LANGUAGE),
+        );
+    }
+    match language {
+        Some(language) => format!(
+            "{}\n\n{}",
+            prompt.trim_end(),
+            LANGUAGE_INSTRUCTION.replace(LANGUAGE_PLACEHOLDER, &language)
+        ),
+        None => prompt.to_string(),
+    }
+}
+
+/// English name of the primary subtag of `tag`, followed by the tag itself,
+/// e.g. `Japanese (ja)`. Unknown languages are named by their tag alone.
+fn language_display_name(tag: &str) -> String {
+    let primary = tag.split(['-', '_']).next().unwrap_or(tag);
+    let name = match primary.to_ascii_lowercase().as_str() {
+        "ar" => "Arabic",
+        "de" => "German",
+        "en" => "English",
+        "es" => "Spanish",
+        "fr" => "French",
+        "hi" => "Hindi",
+        "it" => "Italian",
+        "ja" => "Japanese",
+        "ko" => "Korean",
+        "nl" => "Dutch",
+        "pl" => "Polish",
+        "pt" => "Portuguese",
+        "ru" => "Russian",
+        "tr" => "Turkish",
+        "uk" => "Ukrainian",
+        "zh" => "Chinese",
+        _ => return tag.to_string(),
+    };
+    format!("{name} ({tag})")
+}
+
+/// Summary budget for a context window that currently holds `tokens_in_use`
+/// tokens: 10% of the window below 50% utilization, 5% above 80%.
+pub(crate) fn adaptive_summary_target_tokens(context_window: i64, tokens_in_use: i64) -> i64 {
+    let utilization = tokens_in_use as f64 / context_window.max(1) as f64;
+    let fraction = if utilization <= RELAXED_CONTEXT_UTILIZATION {
+        RELAXED_SUMMARY_FRACTION
+    } else if utilization >= PRESSURED_CONTEXT_UTILIZATION {
+        PRESSURED_SUMMARY_FRACTION
+    } else {
+        let progress = (utilization - RELAXED_CONTEXT_UTILIZATION)
+            / (PRESSURED_CONTEXT_UTILIZATION - RELAXED_CONTEXT_UTILIZATION);
+        RELAXED_SUMMARY_FRACTION
+            - (RELAXED_SUMMARY_FRACTION - PRESSURED_SUMMARY_FRACTION) * progress
+    };
+    (context_window as f64 * fraction).round() as i64
+}
+
+fn with_summary_length_instruction(prompt: &str, target_tokens: i64) -> String {
+    format!(
+        "{}\n\nKeep the summary under {target_tokens} tokens.",
+        prompt.trim_end()
+    )
+}
+
 pub fn content_items_to_text(content: &[ContentItem]) -> Option<String> {
     let mut pieces = Vec::new();
     for item in content {
@@ -334,6 +764,18 @@ pub(crate) fn build_compacted_history(
     )
 }
 
+/// Like [`build_compacted_history`] over the compaction candidates of
+/// `history_items`, with every pinned turn kept verbatim at the start.
+pub(crate) fn build_compacted_history_keeping_pinned_turns(
+    history_items: &[ResponseItem],
+    pinned_turns: &[Vec<ResponseItem>],
+    summary_text: &str,
+) -> Vec<ResponseItem> {
+    let user_messages =
+        collect_user_messages(&select_turns_for_compaction(history_items, pinned_turns));
+    build_compacted_history(pinned_turns.concat(), &user_messages, summary_text)
+}
+
 fn build_compacted_history_with_limit(
     mut history: Vec<ResponseItem>,
     user_messages: &[String],
@@ -437,10 +879,81 @@ async fn drain_to_completed(
     }
 }
 
+/// [`collect_assistant_text`] in a fresh client session, retrying stream
+/// errors up to the provider's retry limit.
+async fn collect_assistant_text_with_retries(
+    sess: &Session,
+    turn_context: &TurnContext,
+    prompt: &Prompt,
+) -> CodexResult<String> {
+    let max_retries = turn_context.provider.stream_max_retries();
+    let mut retries = 0;
+    let mut client_session = sess.model_client_for(turn_context).new_session();
+    loop {
+        let turn_metadata_header = turn_context.turn_metadata_state.current_header_value();
+        match collect_assistant_text(
+            turn_context,
+            &mut client_session,
+            turn_metadata_header.as_deref(),
+            prompt,
+        )
+        .await
+        {
+            Ok(text) => return Ok(text),
+            Err(e @ (CodexErr::Interrupted | CodexErr::ContextWindowExceeded)) => return Err(e),
+            Err(_) if retries < max_retries => {
+                retries += 1;
+                tokio::time::sleep(turn_context.provider.retry_delay(retries)).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Streams `prompt` and returns the text of the last assistant message,
+/// without recording anything into the session.
+async fn collect_assistant_text(
+    turn_context: &TurnContext,
+    client_session: &mut ModelClientSession,
+    turn_metadata_header: Option<&str>,
+    prompt: &Prompt,
+) -> CodexResult<String> {
+    let mut stream = client_session
+        .stream(
+            prompt,
+            &turn_context.model_info,
+            &turn_context.session_telemetry,
+            turn_context.reasoning_effort,
+            turn_context.reasoning_summary,
+            turn_context.config.service_tier,
+            turn_metadata_header,
+        )
+        .await?;
+    let mut text = String::new();
+    loop {
+        let Some(event) = stream.next().await else {
+            return Err(CodexErr::Stream(
+                "stream closed before response.completed".into(),
+                None,
+            ));
+        };
+        match event? {
+            ResponseEvent::OutputItemDone(ResponseItem::Message { role, content, .. })
+                if role == "assistant" =>
+            {
+                text = content_items_to_text(&content).unwrap_or_default();
+            }
+            ResponseEvent::Completed { .. } => return Ok(text),
+            _ => {}
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
     use pretty_assertions::assert_eq;
 
     async fn process_compacted_history_with_test_session(
@@ -462,6 +975,197 @@ mod tests {
         (refreshed, initial_context)
     }
 
+    #[tokio::test]
+    async fn merge_compacts_summarizes_all_summaries_in_one_request() {
+        use crate::session_recording::RecordedEvent;
+        use crate::session_recording::RecordedExchange;
+        use crate::session_recording::Recording;
+
+        let (session, turn_context) = crate::codex::make_session_and_context().await;
+        let summaries = [
+            CompactSummary::new("Cloned the repo and ran the test suite."),
+            CompactSummary::from_message(&format!(
+                "{SUMMARY_PREFIX}\nFixed the flaky parser test."
+            )),
+            CompactSummary::new("User asked to keep the public API unchanged."),
+        ];
+        assert_eq!(summaries[1].text, "Fixed the flaky parser test.");
+
+        // All three summaries go to the model in a single message.
+        let ResponseItem::Message { role, content, .. } =
+            merge_compacts_input(MERGE_PROMPT, &summaries)
+        else {
+            panic!("expected a message");
+        };
+        assert_eq!(role, "user");
+        assert_eq!(
+            content_items_to_text(&content),
+            Some(format!(
+                "{}\n\n\
+                 <summary index=\"1\">\nCloned the repo and ran the test suite.\n</summary>\n\n\
+                 <summary index=\"2\">\nFixed the flaky parser test.\n</summary>\n\n\
+                 <summary index=\"3\">\nUser asked to keep the public API unchanged.\n</summary>",
+                MERGE_PROMPT.trim_end()
+            ))
+        );
+
+        // The recording holds exactly one response; a second request would fail.
+        let merged = "Repo cloned and parser test fixed; keep the public API unchanged.";
+        session.services.model_client.start_playback(Recording {
+            exchanges: vec![RecordedExchange {
+                input: Vec::new(),
+                events: vec![
+                    RecordedEvent::OutputItemDone {
+                        item: ResponseItem::Message {
+                            id: None,
+                            role: "assistant".to_string(),
+                            content: vec![ContentItem::OutputText {
+                                text: merged.to_string(),
+                            }],
+                            end_turn: None,
+                            phase: None,
+                        },
+                    },
+                    RecordedEvent::Completed {
+                        response_id: "resp-1".to_string(),
+                        token_usage: None,
+                    },
+                ],
+            }],
+        });
+        let history_len = session.clone_history().await.raw_items().len();
+
+        let result = merge_compacts(&session, &turn_context, &summaries)
+            .await
+            .expect("merge should succeed");
+
+        assert_eq!(result, CompactSummary::new(merged));
+        assert_eq!(session.clone_history().await.raw_items().len(), history_len);
+    }
+
+    #[tokio::test]
+    async fn preferred_language_is_named_in_compaction_prompt() {
+        let (session, turn_context) = crate::codex::make_session_and_context().await;
+
+        session.set_preferred_language("ja").await;
+        let prompt = compaction_prompt(&session, &turn_context).await;
+
+        assert!(prompt.starts_with(turn_context.compact_prompt().trim_end()));
+        assert!(
+            prompt.contains("Write the summary in Japanese (ja)."),
+            "prompt does not name the language: {prompt}"
+        );
+        assert_eq!(
+            with_language_instruction("Summarize in {{language}}.", Some("pt-BR")),
+            "Summarize in Portuguese (pt-BR)."
+        );
+        assert_eq!(
+            with_language_instruction("Summarize in {{language}}.", None),
+            "Summarize in the language of the conversation."
+        );
+    }
+
+    #[test]
+    fn adaptive_summary_length_tracks_context_pressure() {
+        let relaxed = with_summary_length_instruction(
+            SUMMARIZATION_PROMPT,
+            adaptive_summary_target_tokens(200_000, 60_000),
+        );
+        let pressured = with_summary_length_instruction(
+            SUMMARIZATION_PROMPT,
+            adaptive_summary_target_tokens(200_000, 180_000),
+        );
+
+        assert!(relaxed.ends_with("Keep the summary under 20000 tokens."));
+        assert!(pressured.ends_with("Keep the summary under 10000 tokens."));
+        assert_eq!(adaptive_summary_target_tokens(200_000, 130_000), 15_000);
+    }
+
+    #[tokio::test]
+    async fn compaction_prompt_adds_length_budget_only_when_enabled() {
+        let (session, mut turn_context) = crate::codex::make_session_and_context().await;
+        assert_eq!(
+            compaction_prompt(&session, &turn_context).await,
+            turn_context.compact_prompt()
+        );
+
+        let mut config = (*turn_context.config).clone();
+        config.compact_adaptive_summary_length = true;
+        turn_context.config = Arc::new(config);
+        let context_window = turn_context
+            .model_context_window()
+            .expect("test model has a context window");
+
+        let prompt = compaction_prompt(&session, &turn_context).await;
+
+        assert!(prompt.starts_with(turn_context.compact_prompt().trim_end()));
+        assert!(prompt.ends_with(&format!(
+            "Keep the summary under {} tokens.",
+            context_window / 10
+        )));
+    }
+
+    #[test]
+    fn reorder_history_moves_outputs_after_their_calls() {
+        let call = |call_id: &str| ResponseItem::FunctionCall {
+            id: None,
+            name: "shell".to_string(),
+            arguments: "{}".to_string(),
+            call_id: call_id.to_string(),
+        };
+        let output = |call_id: &str| ResponseItem::FunctionCallOutput {
+            call_id: call_id.to_string(),
+            output: FunctionCallOutputPayload::from_text(format!("{call_id} done")),
+        };
+        let custom_call = ResponseItem::CustomToolCall {
+            id: None,
+            status: None,
+            call_id: "patch".to_string(),
+            name: "apply_patch".to_string(),
+            input: "*** Begin Patch".to_string(),
+        };
+        let custom_output = ResponseItem::CustomToolCallOutput {
+            call_id: "patch".to_string(),
+            output: FunctionCallOutputPayload::from_text("patched".to_string()),
+        };
+        let message = |text: &str| ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+            end_turn: None,
+            phase: None,
+        };
+
+        let reordered = reorder_history_for_coherence(vec![
+            message("first"),
+            output("b"),
+            custom_output.clone(),
+            call("a"),
+            output("orphan"),
+            call("b"),
+            message("second"),
+            output("a"),
+            custom_call.clone(),
+        ]);
+
+        assert_eq!(
+            reordered,
+            vec![
+                message("first"),
+                call("a"),
+                output("orphan"),
+                call("b"),
+                output("b"),
+                message("second"),
+                output("a"),
+                custom_call,
+                custom_output,
+            ]
+        );
+    }
+
     #[test]
     fn content_items_to_text_joins_non_empty_segments() {
         let items = vec![
@@ -1005,4 +1709,72 @@ keep me updated
         ];
         assert_eq!(refreshed, expected);
     }
+
+    #[tokio::test]
+    async fn compaction_mode_overrides_provider_detection() {
+        let (_session, mut turn_context) = crate::codex::make_session_and_context().await;
+        let self_hosted = crate::model_provider_info::create_oss_provider_with_base_url(
+            "http://localhost:8080/v1",
+            crate::model_provider_info::WireApi::Responses,
+        );
+        let openai = crate::ModelProviderInfo::create_openai_provider();
+
+        let mut strategies = Vec::new();
+        for mode in [
+            CompactionMode::Auto,
+            CompactionMode::Local,
+            CompactionMode::Remote,
+        ] {
+            let mut config = (*turn_context.config).clone();
+            config.compaction_mode = mode;
+            turn_context.config = Arc::new(config);
+            for provider in [&openai, &self_hosted] {
+                turn_context.provider = provider.clone();
+                strategies.push(compact_strategy(&turn_context));
+            }
+        }
+
+        assert_eq!(
+            strategies,
+            vec![
+                CompactStrategy::Remote,
+                CompactStrategy::Local,
+                CompactStrategy::Local,
+                CompactStrategy::Local,
+                CompactStrategy::Remote,
+                CompactStrategy::Remote,
+            ]
+        );
+    }
+
+    #[test]
+    fn split_into_chunks_keeps_turns_whole() {
+        let message = |role: &str, text: &str| ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+            end_turn: None,
+            phase: None,
+        };
+        let items = vec![
+            message("user", "first"),
+            message("assistant", &"a".repeat(400)),
+            message("user", "second"),
+            message("assistant", &"b".repeat(400)),
+            message("user", "third"),
+        ];
+        let turn_tokens =
+            estimate_item_token_count(&items[0]) + estimate_item_token_count(&items[1]);
+
+        let chunks = split_into_chunks(&items, turn_tokens);
+        assert_eq!(chunks, vec![&items[0..2], &items[2..4], &items[4..5]]);
+
+        // A budget smaller than one turn still keeps each turn in one chunk.
+        let chunks = split_into_chunks(&items, 1);
+        assert_eq!(chunks, vec![&items[0..2], &items[2..4], &items[4..5]]);
+
+        assert_eq!(split_into_chunks(&items, i64::MAX), vec![&items[..]]);
+    }
 }
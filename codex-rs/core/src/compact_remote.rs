@@ -3,8 +3,10 @@ use std::sync::Arc;
 use crate::Prompt;
 use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::compact::CompactContext;
 use crate::compact::InitialContextInjection;
 use crate::compact::insert_initial_context_before_last_real_user_or_summary;
+use crate::compact::select_turns_for_compaction;
 use crate::context_manager::ContextManager;
 use crate::context_manager::TotalTokenUsageBreakdown;
 use crate::context_manager::estimate_response_item_model_visible_bytes;
@@ -27,13 +29,18 @@ pub(crate) async fn run_inline_remote_auto_compact_task(
     turn_context: Arc<TurnContext>,
     initial_context_injection: InitialContextInjection,
 ) -> CodexResult<()> {
-    run_remote_compact_task_inner(&sess, &turn_context, initial_context_injection).await?;
+    let compact = CompactContext::for_turn(&sess, &turn_context).await;
+    run_remote_compact_task_inner(&sess, &turn_context, &compact, initial_context_injection)
+        .await?;
     Ok(())
 }
 
+/// Unlike auto compaction, a failure is returned without an error event so
+/// that the caller can fall back to local compaction.
 pub(crate) async fn run_remote_compact_task(
     sess: Arc<Session>,
     turn_context: Arc<TurnContext>,
+    compact: CompactContext,
 ) -> CodexResult<()> {
     let start_event = EventMsg::TurnStarted(TurnStartedEvent {
         turn_id: turn_context.sub_id.clone(),
@@ -42,16 +49,24 @@ pub(crate) async fn run_remote_compact_task(
     });
     sess.send_event(&turn_context, start_event).await;
 
-    run_remote_compact_task_inner(&sess, &turn_context, InitialContextInjection::DoNotInject).await
+    run_remote_compact_task_inner_impl(
+        &sess,
+        &turn_context,
+        &compact,
+        InitialContextInjection::DoNotInject,
+    )
+    .await
 }
 
 async fn run_remote_compact_task_inner(
     sess: &Arc<Session>,
     turn_context: &Arc<TurnContext>,
+    compact: &CompactContext,
     initial_context_injection: InitialContextInjection,
 ) -> CodexResult<()> {
     if let Err(err) =
-        run_remote_compact_task_inner_impl(sess, turn_context, initial_context_injection).await
+        run_remote_compact_task_inner_impl(sess, turn_context, compact, initial_context_injection)
+            .await
     {
         let event = EventMsg::Error(
             err.to_error_event(Some("Error running remote compact task".to_string())),
@@ -65,12 +80,22 @@ async fn run_remote_compact_task_inner(
 async fn run_remote_compact_task_inner_impl(
     sess: &Arc<Session>,
     turn_context: &Arc<TurnContext>,
+    compact: &CompactContext,
     initial_context_injection: InitialContextInjection,
 ) -> CodexResult<()> {
+    info!(
+        model = %compact.model_name,
+        "compacting conversation history remotely"
+    );
     let compaction_item = TurnItem::ContextCompaction(ContextCompactionItem::new());
     sess.emit_turn_item_started(turn_context, &compaction_item)
         .await;
+    let pinned_turns = sess.pinned_turn_items().await;
     let mut history = sess.clone_history().await;
+    history.replace(select_turns_for_compaction(
+        history.raw_items(),
+        &pinned_turns,
+    ));
     let base_instructions = sess.get_base_instructions().await;
     let deleted_items = trim_function_call_history_to_fit_context_window(
         &mut history,
@@ -102,8 +127,7 @@ async fn run_remote_compact_task_inner_impl(
     };
 
     let mut new_history = sess
-        .services
-        .model_client
+        .model_client_for(turn_context)
         .compact_conversation_history(
             &prompt,
             &turn_context.model_info,
@@ -130,6 +154,9 @@ async fn run_remote_compact_task_inner_impl(
     )
     .await;
 
+    if !pinned_turns.is_empty() {
+        new_history.splice(0..0, pinned_turns.concat());
+    }
     if !ghost_snapshots.is_empty() {
         new_history.extend(ghost_snapshots);
     }
@@ -204,10 +231,12 @@ fn should_keep_compacted_history_item(item: &ResponseItem) -> bool {
         | ResponseItem::FunctionCall { .. }
         | ResponseItem::FunctionCallOutput { .. }
         | ResponseItem::CustomToolCall { .. }
+        | ResponseItem::CustomToolCallWithAttachments { .. }
         | ResponseItem::CustomToolCallOutput { .. }
         | ResponseItem::WebSearchCall { .. }
         | ResponseItem::ImageGenerationCall { .. }
         | ResponseItem::GhostSnapshot { .. }
+        | ResponseItem::UnsupportedToolCall { .. }
         | ResponseItem::Other => false,
     }
 }
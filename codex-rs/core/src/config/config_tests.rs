@@ -1,6 +1,9 @@
 use crate::config::edit::ConfigEdit;
 use crate::config::edit::ConfigEditsBuilder;
 use crate::config::edit::apply_blocking;
+use crate::config::types::AuditConfig;
+use crate::config::types::CompactionMode;
+use crate::config::types::CompactionStrategy;
 use crate::config::types::FeedbackConfigToml;
 use crate::config::types::HistoryPersistence;
 use crate::config::types::McpServerTransportConfig;
@@ -9,6 +12,11 @@ use crate::config::types::MemoriesToml;
 use crate::config::types::ModelAvailabilityNuxConfig;
 use crate::config::types::NotificationMethod;
 use crate::config::types::Notifications;
+use crate::config::types::ShellOutputRetention;
+use crate::config::types::TokenBudgetConfig;
+use crate::config::types::ToolOutputConfig;
+use crate::config::types::VerifyConfig;
+use crate::config::types::WebFetchConfig;
 use crate::config_loader::RequirementSource;
 use crate::features::Feature;
 use assert_matches::assert_matches;
@@ -17,6 +25,7 @@ use codex_protocol::permissions::FileSystemAccessMode;
 use codex_protocol::permissions::FileSystemPath;
 use codex_protocol::permissions::FileSystemSandboxEntry;
 use codex_protocol::permissions::FileSystemSpecialPath;
+use codex_protocol::protocol::FeatureSource;
 use serde::Deserialize;
 use tempfile::tempdir;
 
@@ -47,6 +56,8 @@ fn stdio_mcp(command: &str) -> McpServerConfig {
         disabled_tools: None,
         scopes: None,
         oauth_resource: None,
+        max_concurrent_calls: None,
+        max_calls_per_minute: None,
     }
 }
 
@@ -67,6 +78,8 @@ fn http_mcp(url: &str) -> McpServerConfig {
         disabled_tools: None,
         scopes: None,
         oauth_resource: None,
+        max_concurrent_calls: None,
+        max_calls_per_minute: None,
     }
 }
 
@@ -155,6 +168,53 @@ consolidation_model = "gpt-5"
     );
 }
 
+#[test]
+fn model_retry_applies_to_every_provider_under_provider_overrides() {
+    let cfg = toml::from_str::<ConfigToml>(
+        r#"
+model_provider = "flaky"
+
+[model_retry]
+max_retries = 2
+backoff_multiplier = 3.0
+circuit_breaker_threshold = 5
+
+[model_providers.flaky]
+name = "Flaky"
+base_url = "https://flaky.example.com/v1"
+
+[model_providers.flaky.retry]
+max_retries = 8
+retry_status_codes = [502, 503]
+"#,
+    )
+    .expect("TOML deserialization should succeed");
+
+    let config = Config::load_from_base_config_with_overrides(
+        cfg,
+        ConfigOverrides::default(),
+        tempdir().expect("tempdir").path().to_path_buf(),
+    )
+    .expect("load config");
+
+    assert_eq!(
+        config.model_provider.retry,
+        Some(ModelRetryConfig {
+            max_retries: Some(8),
+            backoff_multiplier: Some(3.0),
+            retry_status_codes: Some(vec![502, 503]),
+            circuit_breaker_threshold: Some(5),
+            ..ModelRetryConfig::default()
+        })
+    );
+    assert_eq!(config.model_provider.request_max_retries(), 8);
+    assert_eq!(
+        config.model_provider.circuit_breaker(),
+        Some((5, Duration::from_secs(60)))
+    );
+    assert_eq!(config.model_providers["openai"].request_max_retries(), 2);
+}
+
 #[test]
 fn config_toml_deserializes_model_availability_nux() {
     let toml = r#"
@@ -714,6 +774,27 @@ fn tui_config_missing_notifications_field_defaults_to_enabled() {
     );
 }
 
+#[test]
+fn sandbox_backend_parses_container_runtimes() {
+    let cfg = toml::from_str::<ConfigToml>(
+        r#"
+[sandbox_backend.podman]
+image = "ubuntu:24.04"
+"#,
+    )
+    .expect("TOML deserialization should succeed");
+    assert_eq!(
+        cfg.sandbox_backend,
+        Some(SandboxBackendKind::Podman {
+            image: "ubuntu:24.04".to_string(),
+        })
+    );
+
+    let cfg = toml::from_str::<ConfigToml>(r#"sandbox_backend = "platform""#)
+        .expect("TOML deserialization should succeed");
+    assert_eq!(cfg.sandbox_backend, Some(SandboxBackendKind::Platform));
+}
+
 #[test]
 fn test_sandbox_config_parsing() {
     let sandbox_full_access = r#"
@@ -1188,6 +1269,62 @@ fn web_search_mode_prefers_profile_over_legacy_flags() {
     );
 }
 
+#[test]
+fn tool_overrides_layer_profile_over_base_config() {
+    let cfg: ConfigToml = toml::from_str(
+        r#"
+[tools]
+disabled = ["view_image", "docs"]
+
+[tools.overrides.shell]
+max_timeout_ms = 60000
+
+[tools.overrides.update_plan]
+name = "plan"
+"#,
+    )
+    .expect("TOML deserialization should succeed");
+    let profile: ConfigProfile = toml::from_str(
+        r#"
+[tools]
+disabled = ["mcp__docs__search", "docs"]
+
+[tools.overrides.shell]
+allowed_commands = ["git"]
+"#,
+    )
+    .expect("TOML deserialization should succeed");
+
+    let (disabled, overrides) = resolve_tool_overrides(&cfg, &profile);
+    assert_eq!(
+        disabled,
+        vec![
+            "view_image".to_string(),
+            "docs".to_string(),
+            "mcp__docs__search".to_string(),
+        ]
+    );
+    assert_eq!(
+        overrides,
+        BTreeMap::from([
+            (
+                "shell".to_string(),
+                ToolOverride {
+                    allowed_commands: Some(vec!["git".to_string()]),
+                    ..Default::default()
+                },
+            ),
+            (
+                "update_plan".to_string(),
+                ToolOverride {
+                    name: Some("plan".to_string()),
+                    ..Default::default()
+                },
+            ),
+        ])
+    );
+}
+
 #[test]
 fn web_search_mode_disabled_overrides_legacy_request() {
     let cfg = ConfigToml {
@@ -1549,6 +1686,8 @@ async fn replace_mcp_servers_round_trips_entries() -> anyhow::Result<()> {
             disabled_tools: None,
             scopes: None,
             oauth_resource: None,
+            max_concurrent_calls: None,
+            max_calls_per_minute: None,
         },
     );
 
@@ -1705,6 +1844,8 @@ async fn replace_mcp_servers_serializes_env_sorted() -> anyhow::Result<()> {
             disabled_tools: None,
             scopes: None,
             oauth_resource: None,
+            max_concurrent_calls: None,
+            max_calls_per_minute: None,
         },
     )]);
 
@@ -1777,6 +1918,8 @@ async fn replace_mcp_servers_serializes_env_vars() -> anyhow::Result<()> {
             disabled_tools: None,
             scopes: None,
             oauth_resource: None,
+            max_concurrent_calls: None,
+            max_calls_per_minute: None,
         },
     )]);
 
@@ -1829,6 +1972,8 @@ async fn replace_mcp_servers_serializes_cwd() -> anyhow::Result<()> {
             disabled_tools: None,
             scopes: None,
             oauth_resource: None,
+            max_concurrent_calls: None,
+            max_calls_per_minute: None,
         },
     )]);
 
@@ -1879,6 +2024,8 @@ async fn replace_mcp_servers_streamable_http_serializes_bearer_token() -> anyhow
             disabled_tools: None,
             scopes: None,
             oauth_resource: None,
+            max_concurrent_calls: None,
+            max_calls_per_minute: None,
         },
     )]);
 
@@ -1945,6 +2092,8 @@ async fn replace_mcp_servers_streamable_http_serializes_custom_headers() -> anyh
             disabled_tools: None,
             scopes: None,
             oauth_resource: None,
+            max_concurrent_calls: None,
+            max_calls_per_minute: None,
         },
     )]);
     apply_blocking(
@@ -2023,6 +2172,8 @@ async fn replace_mcp_servers_streamable_http_removes_optional_sections() -> anyh
             disabled_tools: None,
             scopes: None,
             oauth_resource: None,
+            max_concurrent_calls: None,
+            max_calls_per_minute: None,
         },
     )]);
 
@@ -2054,6 +2205,8 @@ async fn replace_mcp_servers_streamable_http_removes_optional_sections() -> anyh
             disabled_tools: None,
             scopes: None,
             oauth_resource: None,
+            max_concurrent_calls: None,
+            max_calls_per_minute: None,
         },
     );
     apply_blocking(
@@ -2120,6 +2273,8 @@ async fn replace_mcp_servers_streamable_http_isolates_headers_between_servers()
                 disabled_tools: None,
                 scopes: None,
                 oauth_resource: None,
+                max_concurrent_calls: None,
+                max_calls_per_minute: None,
             },
         ),
         (
@@ -2141,6 +2296,8 @@ async fn replace_mcp_servers_streamable_http_isolates_headers_between_servers()
                 disabled_tools: None,
                 scopes: None,
                 oauth_resource: None,
+                max_concurrent_calls: None,
+                max_calls_per_minute: None,
             },
         ),
     ]);
@@ -2225,6 +2382,8 @@ async fn replace_mcp_servers_serializes_disabled_flag() -> anyhow::Result<()> {
             disabled_tools: None,
             scopes: None,
             oauth_resource: None,
+            max_concurrent_calls: None,
+            max_calls_per_minute: None,
         },
     )]);
 
@@ -2271,6 +2430,8 @@ async fn replace_mcp_servers_serializes_required_flag() -> anyhow::Result<()> {
             disabled_tools: None,
             scopes: None,
             oauth_resource: None,
+            max_concurrent_calls: None,
+            max_calls_per_minute: None,
         },
     )]);
 
@@ -2317,6 +2478,8 @@ async fn replace_mcp_servers_serializes_tool_filters() -> anyhow::Result<()> {
             disabled_tools: Some(vec!["blocked".to_string()]),
             scopes: None,
             oauth_resource: None,
+            max_concurrent_calls: None,
+            max_calls_per_minute: None,
         },
     )]);
 
@@ -2367,6 +2530,8 @@ async fn replace_mcp_servers_streamable_http_serializes_oauth_resource() -> anyh
             disabled_tools: None,
             scopes: None,
             oauth_resource: Some("https://resource.example.com".to_string()),
+            max_concurrent_calls: None,
+            max_calls_per_minute: None,
         },
     )]);
 
@@ -2946,6 +3111,7 @@ model_verbosity = "high"
         request_max_retries: Some(4),
         stream_max_retries: Some(10),
         stream_idle_timeout_ms: Some(300_000),
+        retry: None,
         requires_openai_auth: false,
         supports_websockets: false,
     };
@@ -3031,6 +3197,26 @@ fn test_precedence_fixture_with_o3_profile() -> std::io::Result<()> {
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             project_doc_fallback_filenames: Vec::new(),
             tool_output_token_limit: None,
+            tool_max_argument_size_bytes: None,
+            tool_max_parallel_calls: None,
+            tool_fallbacks: HashMap::new(),
+            tool_chaos: None,
+            shell_output_max_bytes: None,
+            shell_output_retention: ShellOutputRetention::Head,
+            token_budget: TokenBudgetConfig::default(),
+            prompt_cache_retention: None,
+            turn_timeout_seconds: None,
+            verify: VerifyConfig::default(),
+            tool_output: ToolOutputConfig::default(),
+            audit: AuditConfig::default(),
+            web_fetch: WebFetchConfig::default(),
+            provider_fallbacks: Vec::new(),
+            model_pricing: HashMap::new(),
+            sandbox_rules: Vec::new(),
+            approval_rules: Vec::new(),
+            hooks: Vec::new(),
+            workspace_roots: Vec::new(),
+            allowed_tools: None,
             agent_max_threads: DEFAULT_AGENT_MAX_THREADS,
             agent_max_depth: DEFAULT_AGENT_MAX_DEPTH,
             agent_roles: BTreeMap::new(),
@@ -3044,6 +3230,7 @@ fn test_precedence_fixture_with_o3_profile() -> std::io::Result<()> {
             history: History::default(),
             ephemeral: false,
             file_opener: UriBasedFileOpener::VsCode,
+            sandbox_backend: SandboxBackendKind::Platform,
             codex_linux_sandbox_exe: None,
             main_execve_wrapper_exe: None,
             js_repl_node_path: None,
@@ -3067,12 +3254,17 @@ fn test_precedence_fixture_with_o3_profile() -> std::io::Result<()> {
             base_instructions: None,
             developer_instructions: None,
             compact_prompt: None,
+            compact_adaptive_summary_length: false,
+            compaction_mode: CompactionMode::Auto,
+            compaction_strategy: CompactionStrategy::Summarize,
             commit_attribution: None,
             forced_chatgpt_workspace_id: None,
             forced_login_method: None,
             include_apply_patch_tool: false,
             web_search_mode: Constrained::allow_any(WebSearchMode::Cached),
             web_search_config: None,
+            disabled_tools: Vec::new(),
+            tool_overrides: BTreeMap::new(),
             use_experimental_unified_exec_tool: !cfg!(windows),
             background_terminal_max_timeout: DEFAULT_MAX_BACKGROUND_TERMINAL_TIMEOUT_MS,
             ghost_snapshot: GhostSnapshotConfig::default(),
@@ -3166,6 +3358,26 @@ fn test_precedence_fixture_with_gpt3_profile() -> std::io::Result<()> {
         project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
         project_doc_fallback_filenames: Vec::new(),
         tool_output_token_limit: None,
+        tool_max_argument_size_bytes: None,
+        tool_max_parallel_calls: None,
+        tool_fallbacks: HashMap::new(),
+        tool_chaos: None,
+        shell_output_max_bytes: None,
+        shell_output_retention: ShellOutputRetention::Head,
+        token_budget: TokenBudgetConfig::default(),
+        prompt_cache_retention: None,
+        turn_timeout_seconds: None,
+        verify: VerifyConfig::default(),
+        tool_output: ToolOutputConfig::default(),
+        audit: AuditConfig::default(),
+        web_fetch: WebFetchConfig::default(),
+        provider_fallbacks: Vec::new(),
+        model_pricing: HashMap::new(),
+        sandbox_rules: Vec::new(),
+        approval_rules: Vec::new(),
+        hooks: Vec::new(),
+        workspace_roots: Vec::new(),
+        allowed_tools: None,
         agent_max_threads: DEFAULT_AGENT_MAX_THREADS,
         agent_max_depth: DEFAULT_AGENT_MAX_DEPTH,
         agent_roles: BTreeMap::new(),
@@ -3179,6 +3391,7 @@ fn test_precedence_fixture_with_gpt3_profile() -> std::io::Result<()> {
         history: History::default(),
         ephemeral: false,
         file_opener: UriBasedFileOpener::VsCode,
+        sandbox_backend: SandboxBackendKind::Platform,
         codex_linux_sandbox_exe: None,
         main_execve_wrapper_exe: None,
         js_repl_node_path: None,
@@ -3202,12 +3415,17 @@ fn test_precedence_fixture_with_gpt3_profile() -> std::io::Result<()> {
         base_instructions: None,
         developer_instructions: None,
         compact_prompt: None,
+        compact_adaptive_summary_length: false,
+        compaction_mode: CompactionMode::Auto,
+        compaction_strategy: CompactionStrategy::Summarize,
         commit_attribution: None,
         forced_chatgpt_workspace_id: None,
         forced_login_method: None,
         include_apply_patch_tool: false,
         web_search_mode: Constrained::allow_any(WebSearchMode::Cached),
         web_search_config: None,
+        disabled_tools: Vec::new(),
+        tool_overrides: BTreeMap::new(),
         use_experimental_unified_exec_tool: !cfg!(windows),
         background_terminal_max_timeout: DEFAULT_MAX_BACKGROUND_TERMINAL_TIMEOUT_MS,
         ghost_snapshot: GhostSnapshotConfig::default(),
@@ -3299,6 +3517,26 @@ fn test_precedence_fixture_with_zdr_profile() -> std::io::Result<()> {
         project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
         project_doc_fallback_filenames: Vec::new(),
         tool_output_token_limit: None,
+        tool_max_argument_size_bytes: None,
+        tool_max_parallel_calls: None,
+        tool_fallbacks: HashMap::new(),
+        tool_chaos: None,
+        shell_output_max_bytes: None,
+        shell_output_retention: ShellOutputRetention::Head,
+        token_budget: TokenBudgetConfig::default(),
+        prompt_cache_retention: None,
+        turn_timeout_seconds: None,
+        verify: VerifyConfig::default(),
+        tool_output: ToolOutputConfig::default(),
+        audit: AuditConfig::default(),
+        web_fetch: WebFetchConfig::default(),
+        provider_fallbacks: Vec::new(),
+        model_pricing: HashMap::new(),
+        sandbox_rules: Vec::new(),
+        approval_rules: Vec::new(),
+        hooks: Vec::new(),
+        workspace_roots: Vec::new(),
+        allowed_tools: None,
         agent_max_threads: DEFAULT_AGENT_MAX_THREADS,
         agent_max_depth: DEFAULT_AGENT_MAX_DEPTH,
         agent_roles: BTreeMap::new(),
@@ -3312,6 +3550,7 @@ fn test_precedence_fixture_with_zdr_profile() -> std::io::Result<()> {
         history: History::default(),
         ephemeral: false,
         file_opener: UriBasedFileOpener::VsCode,
+        sandbox_backend: SandboxBackendKind::Platform,
         codex_linux_sandbox_exe: None,
         main_execve_wrapper_exe: None,
         js_repl_node_path: None,
@@ -3335,12 +3574,17 @@ fn test_precedence_fixture_with_zdr_profile() -> std::io::Result<()> {
         base_instructions: None,
         developer_instructions: None,
         compact_prompt: None,
+        compact_adaptive_summary_length: false,
+        compaction_mode: CompactionMode::Auto,
+        compaction_strategy: CompactionStrategy::Summarize,
         commit_attribution: None,
         forced_chatgpt_workspace_id: None,
         forced_login_method: None,
         include_apply_patch_tool: false,
         web_search_mode: Constrained::allow_any(WebSearchMode::Cached),
         web_search_config: None,
+        disabled_tools: Vec::new(),
+        tool_overrides: BTreeMap::new(),
         use_experimental_unified_exec_tool: !cfg!(windows),
         background_terminal_max_timeout: DEFAULT_MAX_BACKGROUND_TERMINAL_TIMEOUT_MS,
         ghost_snapshot: GhostSnapshotConfig::default(),
@@ -3418,6 +3662,26 @@ fn test_precedence_fixture_with_gpt5_profile() -> std::io::Result<()> {
         project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
         project_doc_fallback_filenames: Vec::new(),
         tool_output_token_limit: None,
+        tool_max_argument_size_bytes: None,
+        tool_max_parallel_calls: None,
+        tool_fallbacks: HashMap::new(),
+        tool_chaos: None,
+        shell_output_max_bytes: None,
+        shell_output_retention: ShellOutputRetention::Head,
+        token_budget: TokenBudgetConfig::default(),
+        prompt_cache_retention: None,
+        turn_timeout_seconds: None,
+        verify: VerifyConfig::default(),
+        tool_output: ToolOutputConfig::default(),
+        audit: AuditConfig::default(),
+        web_fetch: WebFetchConfig::default(),
+        provider_fallbacks: Vec::new(),
+        model_pricing: HashMap::new(),
+        sandbox_rules: Vec::new(),
+        approval_rules: Vec::new(),
+        hooks: Vec::new(),
+        workspace_roots: Vec::new(),
+        allowed_tools: None,
         agent_max_threads: DEFAULT_AGENT_MAX_THREADS,
         agent_max_depth: DEFAULT_AGENT_MAX_DEPTH,
         agent_roles: BTreeMap::new(),
@@ -3431,6 +3695,7 @@ fn test_precedence_fixture_with_gpt5_profile() -> std::io::Result<()> {
         history: History::default(),
         ephemeral: false,
         file_opener: UriBasedFileOpener::VsCode,
+        sandbox_backend: SandboxBackendKind::Platform,
         codex_linux_sandbox_exe: None,
         main_execve_wrapper_exe: None,
         js_repl_node_path: None,
@@ -3454,12 +3719,17 @@ fn test_precedence_fixture_with_gpt5_profile() -> std::io::Result<()> {
         base_instructions: None,
         developer_instructions: None,
         compact_prompt: None,
+        compact_adaptive_summary_length: false,
+        compaction_mode: CompactionMode::Auto,
+        compaction_strategy: CompactionStrategy::Summarize,
         commit_attribution: None,
         forced_chatgpt_workspace_id: None,
         forced_login_method: None,
         include_apply_patch_tool: false,
         web_search_mode: Constrained::allow_any(WebSearchMode::Cached),
         web_search_config: None,
+        disabled_tools: Vec::new(),
+        tool_overrides: BTreeMap::new(),
         use_experimental_unified_exec_tool: !cfg!(windows),
         background_terminal_max_timeout: DEFAULT_MAX_BACKGROUND_TERMINAL_TIMEOUT_MS,
         ghost_snapshot: GhostSnapshotConfig::default(),
@@ -3500,6 +3770,7 @@ fn test_requirements_web_search_mode_allowlist_does_not_warn_when_unset() -> any
             crate::config_loader::WebSearchModeRequirement::Cached,
         ]),
         feature_requirements: None,
+        feature_defaults: None,
         mcp_servers: None,
         rules: None,
         enforce_residency: None,
@@ -4098,6 +4369,7 @@ async fn explicit_sandbox_mode_falls_back_when_disallowed_by_requirements() -> s
         allowed_sandbox_modes: Some(vec![crate::config_loader::SandboxModeRequirement::ReadOnly]),
         allowed_web_search_modes: None,
         feature_requirements: None,
+        feature_defaults: None,
         mcp_servers: None,
         rules: None,
         enforce_residency: None,
@@ -4251,6 +4523,52 @@ async fn feature_requirements_normalize_effective_feature_values() -> std::io::R
     Ok(())
 }
 
+#[tokio::test]
+async fn feature_defaults_rank_below_config_and_cli() -> std::io::Result<()> {
+    let codex_home = TempDir::new()?;
+    std::fs::write(
+        codex_home.path().join(CONFIG_TOML_FILE),
+        r#"
+[features]
+undo = false
+"#,
+    )?;
+
+    let config = ConfigBuilder::default()
+        .codex_home(codex_home.path().to_path_buf())
+        .fallback_cwd(Some(codex_home.path().to_path_buf()))
+        .cli_overrides(vec![(
+            "features.personality".to_string(),
+            TomlValue::Boolean(false),
+        )])
+        .cloud_requirements(CloudRequirementsLoader::new(async {
+            Ok(Some(crate::config_loader::ConfigRequirementsToml {
+                feature_defaults: Some(crate::config_loader::FeatureRequirementsToml {
+                    entries: BTreeMap::from([
+                        ("undo".to_string(), true),
+                        ("shell_tool".to_string(), false),
+                        ("personality".to_string(), true),
+                    ]),
+                }),
+                ..Default::default()
+            }))
+        }))
+        .build()
+        .await?;
+
+    let resolved = crate::features::resolve_features(&config)
+        .features
+        .into_iter()
+        .map(|feature| (feature.key.clone(), (feature.enabled, feature.source)))
+        .collect::<HashMap<_, _>>();
+    assert_eq!(resolved["undo"], (false, FeatureSource::Config));
+    assert_eq!(resolved["shell_tool"], (false, FeatureSource::Remote));
+    assert_eq!(resolved["personality"], (false, FeatureSource::Cli));
+    assert_eq!(resolved["unified_exec"].1, FeatureSource::Default);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn explicit_feature_config_is_normalized_by_requirements() -> std::io::Result<()> {
     let codex_home = TempDir::new()?;
@@ -225,6 +225,13 @@ mod document_helpers {
         {
             entry["oauth_resource"] = value(resource.clone());
         }
+        if let Some(max_concurrent_calls) = config.max_concurrent_calls {
+            entry["max_concurrent_calls"] =
+                value(i64::try_from(max_concurrent_calls).unwrap_or(i64::MAX));
+        }
+        if let Some(max_calls_per_minute) = config.max_calls_per_minute {
+            entry["max_calls_per_minute"] = value(i64::from(max_calls_per_minute));
+        }
 
         entry
     }
@@ -1530,6 +1537,8 @@ gpt-5 = "gpt-5.1"
                 disabled_tools: None,
                 scopes: None,
                 oauth_resource: None,
+                max_concurrent_calls: None,
+                max_calls_per_minute: None,
             },
         );
 
@@ -1555,6 +1564,8 @@ gpt-5 = "gpt-5.1"
                 disabled_tools: Some(vec!["forbidden".to_string()]),
                 scopes: None,
                 oauth_resource: Some("https://resource.example.com".to_string()),
+                max_concurrent_calls: None,
+                max_calls_per_minute: None,
             },
         );
 
@@ -1624,6 +1635,8 @@ foo = { command = "cmd" }
                 disabled_tools: None,
                 scopes: None,
                 oauth_resource: None,
+                max_concurrent_calls: None,
+                max_calls_per_minute: None,
             },
         );
 
@@ -1671,6 +1684,8 @@ foo = { command = "cmd" } # keep me
                 disabled_tools: None,
                 scopes: None,
                 oauth_resource: None,
+                max_concurrent_calls: None,
+                max_calls_per_minute: None,
             },
         );
 
@@ -1717,6 +1732,8 @@ foo = { command = "cmd", args = ["--flag"] } # keep me
                 disabled_tools: None,
                 scopes: None,
                 oauth_resource: None,
+                max_concurrent_calls: None,
+                max_calls_per_minute: None,
             },
         );
 
@@ -1764,6 +1781,8 @@ foo = { command = "cmd" }
                 disabled_tools: None,
                 scopes: None,
                 oauth_resource: None,
+                max_concurrent_calls: None,
+                max_calls_per_minute: None,
             },
         );
 
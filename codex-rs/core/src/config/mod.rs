@@ -1,15 +1,21 @@
 use crate::auth::AuthCredentialsStoreMode;
 use crate::config::edit::ConfigEdit;
 use crate::config::edit::ConfigEditsBuilder;
+use crate::config::types::ApprovalRule;
 use crate::config::types::AppsConfigToml;
+use crate::config::types::AuditConfig;
+use crate::config::types::CompactionMode;
+use crate::config::types::CompactionStrategy;
 use crate::config::types::DEFAULT_OTEL_ENVIRONMENT;
 use crate::config::types::History;
+use crate::config::types::LifecycleHook;
 use crate::config::types::McpServerConfig;
 use crate::config::types::McpServerDisabledReason;
 use crate::config::types::McpServerTransportConfig;
 use crate::config::types::MemoriesConfig;
 use crate::config::types::MemoriesToml;
 use crate::config::types::ModelAvailabilityNuxConfig;
+use crate::config::types::ModelPricing;
 use crate::config::types::Notice;
 use crate::config::types::NotificationMethod;
 use crate::config::types::Notifications;
@@ -17,12 +23,23 @@ use crate::config::types::OtelConfig;
 use crate::config::types::OtelConfigToml;
 use crate::config::types::OtelExporterKind;
 use crate::config::types::PluginConfig;
+use crate::config::types::PromptCacheRetention;
+use crate::config::types::ProviderFallbackConfig;
+use crate::config::types::SandboxBackendKind;
+use crate::config::types::SandboxPathRule;
 use crate::config::types::SandboxWorkspaceWrite;
 use crate::config::types::ShellEnvironmentPolicy;
 use crate::config::types::ShellEnvironmentPolicyToml;
+use crate::config::types::ShellOutputRetention;
 use crate::config::types::SkillsConfig;
+use crate::config::types::TokenBudgetConfig;
+use crate::config::types::ToolChaosConfig;
+use crate::config::types::ToolOutputConfig;
+use crate::config::types::ToolOverride;
 use crate::config::types::Tui;
 use crate::config::types::UriBasedFileOpener;
+use crate::config::types::VerifyConfig;
+use crate::config::types::WebFetchConfig;
 use crate::config::types::WindowsSandboxModeToml;
 use crate::config::types::WindowsToml;
 use crate::config_loader::CloudRequirementsLoader;
@@ -45,6 +62,7 @@ use crate::memories::memory_root;
 use crate::model_provider_info::LEGACY_OLLAMA_CHAT_PROVIDER_ID;
 use crate::model_provider_info::LMSTUDIO_OSS_PROVIDER_ID;
 use crate::model_provider_info::ModelProviderInfo;
+use crate::model_provider_info::ModelRetryConfig;
 use crate::model_provider_info::OLLAMA_CHAT_PROVIDER_REMOVED_ERROR;
 use crate::model_provider_info::OLLAMA_OSS_PROVIDER_ID;
 use crate::model_provider_info::built_in_model_providers;
@@ -256,6 +274,16 @@ pub struct Config {
     /// Compact prompt override.
     pub compact_prompt: Option<String>,
 
+    /// When true, compaction asks for a summary sized to the current context
+    /// pressure instead of an open-ended one.
+    pub compact_adaptive_summary_length: bool,
+
+    /// Forces local or remote compaction instead of choosing by provider.
+    pub compaction_mode: CompactionMode,
+
+    /// How local compaction squashes history.
+    pub compaction_strategy: CompactionStrategy,
+
     /// Optional commit attribution text for commit message co-author trailers.
     ///
     /// - `None`: use default attribution (`Codex <noreply@openai.com>`)
@@ -367,6 +395,82 @@ pub struct Config {
     /// Token budget applied when storing tool/function outputs in the context manager.
     pub tool_output_token_limit: Option<usize>,
 
+    /// Largest tool call arguments, in bytes, that are dispatched to a tool.
+    pub tool_max_argument_size_bytes: Option<usize>,
+
+    /// How many parallel-safe tool calls from one model response may run at
+    /// once. Defaults to the number of CPUs.
+    pub tool_max_parallel_calls: Option<usize>,
+
+    /// Tool to call, with the same arguments, when the keyed tool fails,
+    /// e.g. an offline search tool for `web_search`.
+    pub tool_fallbacks: HashMap<String, String>,
+
+    /// Faults injected into every tool call, for resilience testing.
+    pub tool_chaos: Option<ToolChaosConfig>,
+
+    /// Bytes of output kept from each stream of a shell command. Output past
+    /// the cap is still streamed live but not returned to the model.
+    pub shell_output_max_bytes: Option<usize>,
+
+    /// Which end of a shell command's output is kept once it exceeds
+    /// `shell_output_max_bytes`.
+    pub shell_output_retention: ShellOutputRetention,
+
+    /// Per-turn and per-session token limits.
+    pub token_budget: TokenBudgetConfig,
+
+    /// How long the provider should keep cached prompt prefixes. `None`
+    /// leaves it to the provider.
+    pub prompt_cache_retention: Option<PromptCacheRetention>,
+
+    /// Wall-clock limit on a single turn, in seconds. When it passes,
+    /// running tool calls are cancelled and the model is asked to wrap up.
+    pub turn_timeout_seconds: Option<u64>,
+
+    /// Command run after a turn changes files, whose failures are fed back
+    /// to the model.
+    pub verify: VerifyConfig,
+
+    /// Per-tool limits, archiving and summarization for oversized tool
+    /// results.
+    pub tool_output: ToolOutputConfig,
+
+    /// Signed, hash-chained log of executed commands, sandbox decisions and
+    /// applied patches.
+    pub audit: AuditConfig,
+
+    /// Settings for the `web_fetch` tool.
+    pub web_fetch: WebFetchConfig,
+
+    /// Providers a turn's model requests move on to, in order, when the
+    /// current provider is unavailable.
+    pub provider_fallbacks: Vec<ProviderFallbackConfig>,
+
+    /// Prices, keyed by model slug, that override the built-in pricing table
+    /// when estimating spend.
+    pub model_pricing: HashMap<String, ModelPricing>,
+
+    /// Path-scoped permissions layered onto the sandbox policy of each
+    /// command that runs with the default sandbox permissions.
+    pub sandbox_rules: Vec<SandboxPathRule>,
+
+    /// Declarative rules that approve, deny or ask for tool calls before the
+    /// approval policy is consulted.
+    pub approval_rules: Vec<ApprovalRule>,
+
+    /// User commands run on lifecycle events such as `turn.completed`.
+    pub hooks: Vec<LifecycleHook>,
+
+    /// Absolute paths of the workspace roots besides `cwd`. They are writable
+    /// under a `workspace-write` sandbox.
+    pub workspace_roots: Vec<PathBuf>,
+
+    /// Names of the tools, or MCP servers, the session may use. `None` allows
+    /// every tool. This is never read from `config.toml`; `spawn_agent` sets
+    /// it to restrict a sub-agent's tool set.
+    pub allowed_tools: Option<Vec<String>>,
+
     /// Maximum number of agent threads that can be open concurrently.
     pub agent_max_threads: Option<usize>,
     /// Maximum runtime in seconds for agent job workers before they are failed.
@@ -401,6 +505,10 @@ pub struct Config {
     /// output will be hyperlinked using the specified URI scheme.
     pub file_opener: UriBasedFileOpener,
 
+    /// Where sandboxed commands run: the platform sandbox, or a Docker or
+    /// Podman container.
+    pub sandbox_backend: SandboxBackendKind,
+
     /// Path to the `codex-linux-sandbox` executable. This must be set if
     /// [`crate::exec::SandboxType::LinuxSeccomp`] is used. Note that this
     /// cannot be set in the config file: it must be set in code via
@@ -487,6 +595,14 @@ pub struct Config {
     /// Additional parameters for the web search tool when it is enabled.
     pub web_search_config: Option<WebSearchConfig>,
 
+    /// Built-in tools, MCP servers and qualified MCP tools from
+    /// `tools.disabled`, never offered to the model.
+    pub disabled_tools: Vec<String>,
+
+    /// Adjustments to built-in tools from `tools.overrides`, keyed by the
+    /// tool's built-in name.
+    pub tool_overrides: BTreeMap<String, ToolOverride>,
+
     /// If set to `true`, used only the experimental unified exec tool.
     pub use_experimental_unified_exec_tool: bool,
 
@@ -1067,6 +1183,9 @@ pub struct ConfigToml {
     /// Sandbox configuration to apply if `sandbox` is `WorkspaceWrite`.
     pub sandbox_workspace_write: Option<SandboxWorkspaceWrite>,
 
+    /// Where sandboxed commands run. Defaults to the platform sandbox.
+    pub sandbox_backend: Option<SandboxBackendKind>,
+
     /// Default named permissions profile to apply from the `[permissions]`
     /// table.
     pub default_permissions: Option<String>,
@@ -1095,6 +1214,21 @@ pub struct ConfigToml {
     /// Compact prompt used for history compaction.
     pub compact_prompt: Option<String>,
 
+    /// Size compaction summaries to context pressure: about 10% of the
+    /// context window when it is under half full, 5% when it is over 80% full.
+    pub compact_adaptive_summary_length: Option<bool>,
+
+    /// Where compaction runs. `auto` (default) uses the provider's compaction
+    /// endpoint for OpenAI providers and summarizes with the model otherwise;
+    /// `local` and `remote` force one or the other.
+    pub compaction_mode: Option<CompactionMode>,
+
+    /// How local compaction squashes history: `summarize` (default) asks the
+    /// model for one summary, `truncate-oldest` drops older history without a
+    /// model call, and `hierarchical-summaries` summarizes the history in
+    /// chunks and merges the chunk summaries.
+    pub compaction_strategy: Option<CompactionStrategy>,
+
     /// Optional commit attribution text for commit message co-author trailers.
     ///
     /// Set to an empty string to disable automatic commit attribution.
@@ -1152,6 +1286,92 @@ pub struct ConfigToml {
     /// Token budget applied when storing tool/function outputs in the context manager.
     pub tool_output_token_limit: Option<usize>,
 
+    /// Largest tool call arguments, in bytes, that are dispatched to a tool.
+    pub tool_max_argument_size_bytes: Option<usize>,
+
+    /// How many parallel-safe tool calls from one model response may run at
+    /// once. Defaults to the number of CPUs.
+    pub tool_max_parallel_calls: Option<usize>,
+
+    /// Tool to call, with the same arguments, when the keyed tool fails,
+    /// e.g. an offline search tool for `web_search`.
+    pub tool_fallbacks: Option<HashMap<String, String>>,
+
+    /// Fail, delay or garble tool calls at random, to test how the agent
+    /// copes with unreliable tools. Off when unset.
+    pub tool_chaos: Option<ToolChaosConfig>,
+
+    /// Bytes of output kept from each stream of a shell command. Output past
+    /// the cap is still streamed live but not returned to the model.
+    /// Default: `1048576` (1 MiB).
+    pub shell_output_max_bytes: Option<usize>,
+
+    /// Which end of a shell command's output is kept once it exceeds
+    /// `shell_output_max_bytes`. Default: `head`.
+    pub shell_output_retention: Option<ShellOutputRetention>,
+
+    /// Per-turn and per-session token limits. Unlimited by default.
+    pub token_budget: Option<TokenBudgetConfig>,
+
+    /// How long the provider should keep cached prompt prefixes: `in_memory`
+    /// or `24h`. Longer retention makes resumed and slow-paced sessions
+    /// cheaper. Left to the provider by default.
+    pub prompt_cache_retention: Option<PromptCacheRetention>,
+
+    /// Wall-clock limit on a single turn, in seconds. When it passes,
+    /// running tool calls are cancelled and the model is asked to wrap up.
+    /// Unlimited by default.
+    pub turn_timeout_seconds: Option<u64>,
+
+    /// Command run after a turn changes files, such as a test suite. When it
+    /// fails, the failures are summarized for the model, which gets up to
+    /// `max_iterations` more tries to fix them. Off by default.
+    pub verify: Option<VerifyConfig>,
+
+    /// How oversized tool results are cut down: per-tool token or byte
+    /// limits, how much of the start versus the end is kept, archiving of the
+    /// full output, and summaries of very large results by a smaller model.
+    pub tool_output: Option<ToolOutputConfig>,
+
+    /// Append-only log of every approval decision, sandbox attempt, executed
+    /// command and applied patch, with hashes of the patched files before and
+    /// after. Entries are hash-chained and signed; check them with
+    /// `codex audit verify`. Off by default.
+    pub audit: Option<AuditConfig>,
+
+    /// Domain allowlist, robots.txt handling and output budget for the
+    /// `web_fetch` tool.
+    pub web_fetch: Option<WebFetchConfig>,
+
+    /// Providers to fall back to, in order, when the model provider keeps
+    /// failing with 429 or 5xx responses after its retries run out.
+    pub provider_fallbacks: Option<Vec<ProviderFallbackConfig>>,
+
+    /// Retry and circuit breaker settings for every model provider. A
+    /// provider's own `retry` table overrides them field by field.
+    pub model_retry: Option<ModelRetryConfig>,
+
+    /// USD prices per million tokens for models missing from, or priced
+    /// differently than, the built-in table, keyed by model slug.
+    pub model_pricing: Option<HashMap<String, ModelPricing>>,
+
+    /// Path-scoped sandbox permissions, e.g. write access to `target` under
+    /// each command's working directory or no access to `~/.ssh`.
+    pub sandbox_rules: Option<Vec<SandboxPathRule>>,
+
+    /// Rules, checked in order, that approve, deny or ask for shell commands,
+    /// patches and MCP tool calls before the approval policy is consulted.
+    pub approval_rules: Option<Vec<ApprovalRule>>,
+
+    /// Commands to run when the agent completes a turn, applies a patch or
+    /// has a tool call denied. Each receives the event as JSON on stdin.
+    pub hooks: Option<Vec<LifecycleHook>>,
+
+    /// Further repositories or directories the agent works in next to the
+    /// working directory, e.g. a backend checkout beside a frontend one.
+    /// Relative paths are resolved against the working directory.
+    pub workspace_roots: Option<Vec<PathBuf>>,
+
     /// Maximum poll window for background terminal output (`write_stdin`), in milliseconds.
     /// Default: `300000` (5 minutes).
     pub background_terminal_max_timeout: Option<u64>,
@@ -1390,6 +1610,17 @@ pub struct ToolsToml {
     /// Enable the `view_image` tool that lets the agent attach local images.
     #[serde(default)]
     pub view_image: Option<bool>,
+
+    /// Built-in tools, MCP servers, or qualified MCP tools
+    /// (`mcp__<server>__<tool>`) never offered to the model. A profile's list
+    /// adds to the base list.
+    #[serde(default)]
+    pub disabled: Option<Vec<String>>,
+
+    /// Adjustments to built-in tools, keyed by the tool's built-in name. A
+    /// profile's entry for a tool replaces the base entry.
+    #[serde(default)]
+    pub overrides: Option<BTreeMap<String, ToolOverride>>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, JsonSchema)]
@@ -1739,6 +1970,29 @@ fn resolve_web_search_mode(
     None
 }
 
+/// `tools.disabled` and `tools.overrides`, with the profile layered over the
+/// base config.
+fn resolve_tool_overrides(
+    config_toml: &ConfigToml,
+    config_profile: &ConfigProfile,
+) -> (Vec<String>, BTreeMap<String, ToolOverride>) {
+    let mut disabled = Vec::new();
+    let mut overrides = BTreeMap::new();
+    for tools in [config_toml.tools.as_ref(), config_profile.tools.as_ref()]
+        .into_iter()
+        .flatten()
+    {
+        for name in tools.disabled.iter().flatten() {
+            let name = name.trim();
+            if !name.is_empty() && !disabled.iter().any(|existing| existing == name) {
+                disabled.push(name.to_string());
+            }
+        }
+        overrides.extend(tools.overrides.clone().unwrap_or_default());
+    }
+    (disabled, overrides)
+}
+
 fn resolve_web_search_config(
     config_toml: &ConfigToml,
     config_profile: &ConfigProfile,
@@ -1821,6 +2075,7 @@ impl Config {
             sandbox_policy: mut constrained_sandbox_policy,
             web_search_mode: mut constrained_web_search_mode,
             feature_requirements,
+            feature_defaults,
             mcp_servers,
             exec_policy: _,
             enforce_residency,
@@ -1878,7 +2133,14 @@ impl Config {
             web_search_request: override_tools_web_search_request,
         };
 
-        let configured_features = Features::from_config(&cfg, &config_profile, feature_overrides);
+        let configured_features = Features::from_config(
+            &cfg,
+            &config_profile,
+            feature_overrides,
+            feature_defaults
+                .as_ref()
+                .map(|defaults| &defaults.value.entries),
+        );
         let features = ManagedFeatures::from_configured(configured_features, feature_requirements)?;
         let windows_sandbox_mode = resolve_windows_sandbox_mode(&cfg, &config_profile);
         let resolved_cwd = normalize_for_native_workdir({
@@ -1943,6 +2205,18 @@ impl Config {
         {
             additional_writable_roots.push(memories_root);
         }
+        let workspace_roots = cfg
+            .workspace_roots
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|path| AbsolutePathBuf::resolve_path_against_base(path, &resolved_cwd))
+            .collect::<Result<Vec<_>, _>>()?;
+        for root in &workspace_roots {
+            if !additional_writable_roots.contains(root) {
+                additional_writable_roots.push(root.clone());
+            }
+        }
 
         let profiles_are_active = matches!(
             permission_config_syntax,
@@ -2040,12 +2314,18 @@ impl Config {
         let web_search_mode = resolve_web_search_mode(&cfg, &config_profile, &features)
             .unwrap_or(WebSearchMode::Cached);
         let web_search_config = resolve_web_search_config(&cfg, &config_profile);
+        let (disabled_tools, tool_overrides) = resolve_tool_overrides(&cfg, &config_profile);
 
         let mut model_providers = built_in_model_providers();
         // Merge user-defined providers into the built-in list.
         for (key, provider) in cfg.model_providers.into_iter() {
             model_providers.entry(key).or_insert(provider);
         }
+        if let Some(model_retry) = &cfg.model_retry {
+            for provider in model_providers.values_mut() {
+                provider.retry = Some(provider.retry.take().unwrap_or_default().or(model_retry));
+            }
+        }
 
         let model_provider_id = model_provider
             .or(config_profile.model_provider)
@@ -2193,14 +2473,17 @@ impl Config {
             _ => None,
         };
 
-        let compact_prompt = compact_prompt.or(cfg.compact_prompt).and_then(|value| {
-            let trimmed = value.trim();
-            if trimmed.is_empty() {
-                None
-            } else {
-                Some(trimmed.to_string())
-            }
-        });
+        let compact_prompt = compact_prompt
+            .or(config_profile.compact_prompt)
+            .or(cfg.compact_prompt)
+            .and_then(|value| {
+                let trimmed = value.trim();
+                if trimmed.is_empty() {
+                    None
+                } else {
+                    Some(trimmed.to_string())
+                }
+            });
 
         let commit_attribution = cfg.commit_attribution;
 
@@ -2367,6 +2650,12 @@ impl Config {
             personality,
             developer_instructions,
             compact_prompt,
+            compact_adaptive_summary_length: cfg.compact_adaptive_summary_length.unwrap_or(false),
+            compaction_mode: cfg.compaction_mode.unwrap_or_default(),
+            compaction_strategy: config_profile
+                .compaction_strategy
+                .or(cfg.compaction_strategy)
+                .unwrap_or_default(),
             commit_attribution,
             // The config.toml omits "_mode" because it's a config file. However, "_mode"
             // is important in code to differentiate the mode from the store implementation.
@@ -2393,6 +2682,29 @@ impl Config {
                 })
                 .collect(),
             tool_output_token_limit: cfg.tool_output_token_limit,
+            tool_max_argument_size_bytes: cfg.tool_max_argument_size_bytes,
+            tool_max_parallel_calls: cfg.tool_max_parallel_calls,
+            tool_fallbacks: cfg.tool_fallbacks.unwrap_or_default(),
+            tool_chaos: cfg.tool_chaos,
+            shell_output_max_bytes: cfg.shell_output_max_bytes,
+            shell_output_retention: cfg.shell_output_retention.unwrap_or_default(),
+            token_budget: cfg.token_budget.unwrap_or_default(),
+            turn_timeout_seconds: cfg.turn_timeout_seconds.filter(|secs| *secs > 0),
+            verify: cfg.verify.unwrap_or_default(),
+            tool_output: cfg.tool_output.unwrap_or_default(),
+            audit: cfg.audit.unwrap_or_default(),
+            prompt_cache_retention: cfg.prompt_cache_retention,
+            web_fetch: cfg.web_fetch.unwrap_or_default(),
+            provider_fallbacks: cfg.provider_fallbacks.unwrap_or_default(),
+            model_pricing: cfg.model_pricing.unwrap_or_default(),
+            sandbox_rules: cfg.sandbox_rules.unwrap_or_default(),
+            approval_rules: cfg.approval_rules.unwrap_or_default(),
+            hooks: cfg.hooks.unwrap_or_default(),
+            workspace_roots: workspace_roots
+                .into_iter()
+                .map(AbsolutePathBuf::into_path_buf)
+                .collect(),
+            allowed_tools: None,
             agent_max_threads,
             agent_max_depth,
             agent_roles,
@@ -2405,6 +2717,7 @@ impl Config {
             history,
             ephemeral: ephemeral.unwrap_or_default(),
             file_opener: cfg.file_opener.unwrap_or(UriBasedFileOpener::VsCode),
+            sandbox_backend: cfg.sandbox_backend.unwrap_or_default(),
             codex_linux_sandbox_exe,
             main_execve_wrapper_exe,
             js_repl_node_path,
@@ -2447,6 +2760,8 @@ impl Config {
             include_apply_patch_tool: include_apply_patch_tool_flag,
             web_search_mode: constrained_web_search_mode.value,
             web_search_config,
+            disabled_tools,
+            tool_overrides,
             use_experimental_unified_exec_tool,
             background_terminal_max_timeout,
             ghost_snapshot,
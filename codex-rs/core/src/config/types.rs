@@ -9,6 +9,7 @@ pub use codex_protocol::config_types::ModeKind;
 pub use codex_protocol::config_types::Personality;
 pub use codex_protocol::config_types::ServiceTier;
 pub use codex_protocol::config_types::WebSearchMode;
+use codex_protocol::permissions::FileSystemAccessMode;
 use codex_utils_absolute_path::AbsolutePathBuf;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
@@ -104,6 +105,16 @@ pub struct McpServerConfig {
     /// Optional OAuth resource parameter to include during MCP login (RFC 8707).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub oauth_resource: Option<String>,
+
+    /// Maximum number of tool calls to this server that may run at once.
+    /// Further calls wait for one to finish.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_calls: Option<usize>,
+
+    /// Maximum number of tool calls to this server started per minute.
+    /// Further calls wait until the oldest falls out of the window.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_calls_per_minute: Option<u32>,
 }
 
 // Raw MCP config shape used for deserialization and JSON Schema generation.
@@ -150,6 +161,10 @@ pub(crate) struct RawMcpServerConfig {
     pub scopes: Option<Vec<String>>,
     #[serde(default)]
     pub oauth_resource: Option<String>,
+    #[serde(default)]
+    pub max_concurrent_calls: Option<usize>,
+    #[serde(default)]
+    pub max_calls_per_minute: Option<u32>,
 }
 
 impl<'de> Deserialize<'de> for McpServerConfig {
@@ -232,6 +247,8 @@ impl<'de> Deserialize<'de> for McpServerConfig {
             disabled_tools,
             scopes,
             oauth_resource,
+            max_concurrent_calls: raw.max_concurrent_calls,
+            max_calls_per_minute: raw.max_calls_per_minute,
         })
     }
 }
@@ -351,6 +368,294 @@ pub enum HistoryPersistence {
     None,
 }
 
+/// Where compaction runs.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum CompactionMode {
+    /// Use the provider's compaction endpoint for OpenAI providers, and
+    /// summarize with the model otherwise.
+    #[default]
+    Auto,
+    /// Always summarize with the model.
+    Local,
+    /// Always use the provider's compaction endpoint.
+    Remote,
+}
+
+/// How local compaction squashes history.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum CompactionStrategy {
+    /// Ask the model for one summary of the whole history.
+    #[default]
+    Summarize,
+    /// Drop older history without a model call, keeping only the most recent
+    /// user messages.
+    TruncateOldest,
+    /// Summarize the history in chunks, then merge the chunk summaries, so a
+    /// history larger than the context window is summarized in full.
+    HierarchicalSummaries,
+}
+
+/// Which end of a shell command's output is kept once it exceeds
+/// `shell_output_max_bytes`.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ShellOutputRetention {
+    /// Keep the first bytes and drop the rest.
+    #[default]
+    Head,
+    /// Keep the last bytes, where build and test failures usually are.
+    Tail,
+}
+
+/// How long the provider keeps the cached prefixes of prompts, sent as the
+/// Responses API `prompt_cache_retention` parameter.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, JsonSchema)]
+pub enum PromptCacheRetention {
+    /// Keep prefixes in memory, for minutes after their last use.
+    #[serde(rename = "in_memory")]
+    InMemory,
+    /// Keep prefixes for up to a day, so a session resumed after a pause
+    /// still reuses them.
+    #[serde(rename = "24h")]
+    Extended,
+}
+
+impl PromptCacheRetention {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PromptCacheRetention::InMemory => "in_memory",
+            PromptCacheRetention::Extended => "24h",
+        }
+    }
+}
+
+/// Caps on the tokens a session spends, counting input and output tokens as
+/// reported by the model provider.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct TokenBudgetConfig {
+    /// Most tokens a single turn may use. A turn that goes over stops once its
+    /// current model request finishes.
+    pub max_tokens_per_turn: Option<i64>,
+    /// Most tokens the whole session may use. Once spent, new turns are
+    /// refused until the limit is raised.
+    pub max_tokens_per_session: Option<i64>,
+    /// Percentage of a limit at which a warning is emitted. Default: `80`.
+    pub warning_threshold_percent: Option<u8>,
+}
+
+/// Settings for the `web_fetch` tool, which is enabled by the `web_fetch`
+/// feature. The tool only runs when the sandbox allows network access, and
+/// never fetches loopback, private or link-local addresses.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct WebFetchConfig {
+    /// Domains that may be fetched, including their subdomains. When unset,
+    /// any public domain may be fetched.
+    pub allowed_domains: Option<Vec<String>>,
+    /// Most tokens of converted page content returned to the model.
+    /// Default: `10000`.
+    pub max_tokens: Option<usize>,
+    /// Skip pages the site's robots.txt disallows. Default: `true`.
+    pub respect_robots_txt: Option<bool>,
+}
+
+/// Limits on one tool's results, from `[tool_output.tools.<tool>]`. Unset
+/// fields fall back to the `[tool_output]` values.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ToolOutputLimits {
+    /// Most tokens of the result kept for the model.
+    pub max_tokens: Option<usize>,
+    /// Most bytes of the result kept for the model. Takes precedence over
+    /// `max_tokens`.
+    pub max_bytes: Option<usize>,
+    /// Share of the kept output taken from its start, between `0.0` and
+    /// `1.0`; the rest comes from its end.
+    pub head_ratio: Option<f64>,
+}
+
+/// How oversized tool results are cut down before they reach the model.
+/// Without this table, every result is cut to the model's tool output limit,
+/// keeping equal parts of its start and end.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ToolOutputConfig {
+    /// Most tokens of a result kept for the model. Defaults to the model's
+    /// tool output limit.
+    pub max_tokens: Option<usize>,
+    /// Most bytes of a result kept for the model. Takes precedence over
+    /// `max_tokens`.
+    pub max_bytes: Option<usize>,
+    /// Share of the kept output taken from its start, between `0.0` and
+    /// `1.0`; the rest comes from its end, where test failures and errors
+    /// usually are. Default: `0.5`.
+    pub head_ratio: Option<f64>,
+    /// Save the full text of every cut result under
+    /// `$CODEX_HOME/tool_outputs/` and give the model its path.
+    /// Default: `false`.
+    pub archive: Option<bool>,
+    /// Results over this many tokens are summarized by `summary_model`
+    /// instead of cut. Off by default.
+    pub summarize_over_tokens: Option<usize>,
+    /// Model that writes those summaries. Default: `gpt-5.1-codex-mini`.
+    pub summary_model: Option<String>,
+    /// Limits for single tools, keyed by tool name.
+    #[serde(default)]
+    pub tools: BTreeMap<String, ToolOutputLimits>,
+}
+
+/// A check run after a turn changes files. When it fails, a summary of the
+/// failures is sent back to the model to fix before the turn ends.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct VerifyConfig {
+    /// Shell command run in the session's working directory, e.g.
+    /// `cargo test -p my-crate`. Verification is off when unset.
+    pub command: Option<String>,
+    /// Times the model is sent back to fix failures within one turn.
+    /// Default: `3`.
+    pub max_iterations: Option<u32>,
+    /// Kills the command if it has not exited after this many milliseconds.
+    /// Default: `600000` (10 minutes).
+    pub timeout_ms: Option<u64>,
+}
+
+/// Tamper-evident log of what the agent did, kept apart from the session
+/// rollouts. Read with `codex audit verify`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct AuditConfig {
+    /// Append every approval decision, sandbox attempt, executed command and
+    /// applied patch to the audit log. Default: `false`.
+    pub enabled: Option<bool>,
+    /// Log file. Default: `$CODEX_HOME/audit.jsonl`.
+    pub path: Option<AbsolutePathBuf>,
+    /// Key the log's entries are signed with, created on first use.
+    /// Default: `$CODEX_HOME/audit.key`.
+    pub key_file: Option<AbsolutePathBuf>,
+}
+
+/// Faults injected into tool calls, to test how the agent copes with
+/// failing, slow or garbled tools.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ToolChaosConfig {
+    /// Fraction of tool calls, from 0.0 to 1.0, that fail instead of
+    /// running. Default: `0.0`.
+    pub error_rate: Option<f32>,
+    /// Upper bound of the random delay added before each call, in
+    /// milliseconds. Default: `0`.
+    pub latency_jitter_ms: Option<u64>,
+    /// Truncate the arguments of some calls that are not failed outright.
+    /// Default: `false`.
+    pub random_payload_corruption: Option<bool>,
+    /// Seeds the fault RNG, so a session's faults can be reproduced.
+    /// Default: `0`.
+    pub seed: Option<u64>,
+}
+
+/// A provider to retry a turn's model requests with when the providers
+/// before it in `provider_fallbacks` are unavailable.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ProviderFallbackConfig {
+    /// Key of the provider in `model_providers`, or a built-in provider id.
+    pub model_provider: String,
+    /// Model to request from the provider. Defaults to the current model.
+    pub model: Option<String>,
+}
+
+/// Price of a model in USD per million tokens, used to estimate spend.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ModelPricing {
+    pub input: f64,
+    /// Price of input tokens served from the prompt cache. Defaults to
+    /// `input`.
+    pub cached_input: Option<f64>,
+    pub output: f64,
+}
+
+/// Access to a path for sandboxed commands that run with the default sandbox
+/// permissions, layered on top of the session's sandbox policy.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct SandboxPathRule {
+    /// Absolute path, `~/` path, or path relative to the command's working
+    /// directory.
+    pub path: String,
+    /// `write` makes the path writable, `read` readable, and `none` keeps
+    /// rules from granting anything under it.
+    pub access: FileSystemAccessMode,
+}
+
+/// What an approval rule decides for the tool calls it matches.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalRuleDecision {
+    /// Run the call without asking. Sandboxed calls stay sandboxed.
+    Approve,
+    /// Refuse the call without asking.
+    Deny,
+    /// Ask the user, even when the approval policy would not.
+    Ask,
+}
+
+/// A declarative rule from `[[approval_rules]]`. A rule matches a tool call
+/// when every matcher it sets matches; rules without matchers match every
+/// call. The first matching rule decides, and calls that match no rule go
+/// through the approval policy as usual.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ApprovalRule {
+    pub decision: ApprovalRuleDecision,
+    /// Matches shell commands that start with these words. A `deny` rule
+    /// matches when any command in a script does, other rules when all do.
+    pub command_prefix: Option<Vec<String>>,
+    /// Matches `apply_patch` calls by the files they write, as globs
+    /// relative to the working directory where `*` also matches `/`. A `deny`
+    /// rule matches when any file does, other rules when all do.
+    pub paths: Option<Vec<String>>,
+    /// Matches `apply_patch` calls that leave every file they write at or
+    /// below this many bytes.
+    pub max_file_bytes: Option<u64>,
+    /// Matches calls to tools of this MCP server.
+    pub mcp_server: Option<String>,
+    /// Matches calls to MCP tools with this name.
+    pub mcp_tool: Option<String>,
+}
+
+/// Lifecycle events a `[[hooks]]` command can run on.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+pub enum LifecycleHookEvent {
+    /// The agent finished a turn.
+    #[serde(rename = "turn.completed")]
+    TurnCompleted,
+    /// An `apply_patch` call changed files.
+    #[serde(rename = "patch.applied")]
+    PatchApplied,
+    /// A tool call was rejected by the user, an approval rule or the
+    /// approval policy.
+    #[serde(rename = "tool.denied")]
+    ToolDenied,
+}
+
+/// A user command from `[[hooks]]`, run with the event payload as JSON on
+/// stdin and the session's working directory as its own.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct LifecycleHook {
+    pub event: LifecycleHookEvent,
+    /// Program and arguments, e.g. `["cargo", "fmt"]`.
+    pub command: Vec<String>,
+    /// Kills the command if it has not exited after this many milliseconds.
+    /// Default: `10000`.
+    pub timeout_ms: Option<u64>,
+}
+
 // ===== Analytics configuration =====
 
 /// Analytics settings loaded from config.toml. Fields are optional so we can apply defaults.
@@ -368,6 +673,24 @@ pub struct FeedbackConfigToml {
     pub enabled: Option<bool>,
 }
 
+/// Adjustments to one built-in tool, from `[tools.overrides.<tool>]`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ToolOverride {
+    /// Name the tool is offered under instead of its built-in name.
+    pub name: Option<String>,
+    /// Replaces the tool's description.
+    pub description: Option<String>,
+    /// Shell tools only: programs a command may run. Scripts are split into
+    /// their commands, and calls whose script cannot be split safely are
+    /// rejected.
+    pub allowed_commands: Option<Vec<String>>,
+    /// Upper bound for the tool's `timeout_ms` argument. Larger values are
+    /// lowered to it, and calls without a timeout get it.
+    #[schemars(range(min = 1))]
+    pub max_timeout_ms: Option<u64>,
+}
+
 /// Memories settings loaded from config.toml.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, JsonSchema)]
 #[schemars(deny_unknown_fields)]
@@ -813,6 +1136,21 @@ impl From<SandboxWorkspaceWrite> for codex_app_server_protocol::SandboxSettings
     }
 }
 
+/// Where sandboxed commands run.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+#[serde(rename_all = "kebab-case")]
+pub enum SandboxBackendKind {
+    /// The platform sandbox: Seatbelt on macOS, Landlock or bubblewrap on
+    /// Linux, and a restricted token on Windows.
+    #[default]
+    Platform,
+    /// A throwaway Docker container started from `image`.
+    Docker { image: String },
+    /// A throwaway Podman container started from `image`.
+    Podman { image: String },
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum ShellEnvironmentPolicyInherit {
@@ -1155,6 +1493,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn deserialize_server_config_with_call_limits() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            command = "echo"
+            max_concurrent_calls = 2
+            max_calls_per_minute = 30
+        "#,
+        )
+        .expect("should deserialize config with call limits");
+
+        assert_eq!(cfg.max_concurrent_calls, Some(2));
+        assert_eq!(cfg.max_calls_per_minute, Some(30));
+    }
+
     #[test]
     fn deserialize_server_config_with_tool_filters() {
         let cfg: McpServerConfig = toml::from_str(
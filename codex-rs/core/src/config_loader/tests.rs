@@ -604,6 +604,7 @@ allowed_approval_policies = ["on-request"]
                 allowed_sandbox_modes: None,
                 allowed_web_search_modes: None,
                 feature_requirements: None,
+                feature_defaults: None,
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: None,
@@ -653,6 +654,7 @@ allowed_approval_policies = ["on-request"]
             allowed_sandbox_modes: None,
             allowed_web_search_modes: None,
             feature_requirements: None,
+            feature_defaults: None,
             mcp_servers: None,
             rules: None,
             enforce_residency: None,
@@ -691,6 +693,7 @@ async fn load_config_layers_includes_cloud_requirements() -> anyhow::Result<()>
         allowed_sandbox_modes: None,
         allowed_web_search_modes: None,
         feature_requirements: None,
+        feature_defaults: None,
         mcp_servers: None,
         rules: None,
         enforce_residency: None,
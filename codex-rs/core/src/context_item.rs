@@ -0,0 +1,54 @@
+//! Content placed in the context window directly, without a user turn.
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageRole {
+    User,
+    Assistant,
+    Developer,
+    System,
+}
+
+impl MessageRole {
+    fn as_str(self) -> &'static str {
+        match self {
+            MessageRole::User => "user",
+            MessageRole::Assistant => "assistant",
+            MessageRole::Developer => "developer",
+            MessageRole::System => "system",
+        }
+    }
+}
+
+/// An item for [`CodexThread::push_context_item`]. Pinned items survive
+/// compaction verbatim; the rest are summarized away with the turns around
+/// them.
+///
+/// [`CodexThread::push_context_item`]: crate::CodexThread::push_context_item
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContextItem {
+    pub role: MessageRole,
+    pub content: String,
+    pub pinned: bool,
+}
+
+impl ContextItem {
+    pub(crate) fn to_response_item(&self) -> ResponseItem {
+        let text = self.content.clone();
+        let content = match self.role {
+            MessageRole::Assistant => ContentItem::OutputText { text },
+            MessageRole::User | MessageRole::Developer | MessageRole::System => {
+                ContentItem::InputText { text }
+            }
+        };
+        ResponseItem::Message {
+            id: None,
+            role: self.role.as_str().to_string(),
+            content: vec![content],
+            end_turn: None,
+            phase: None,
+        }
+    }
+}
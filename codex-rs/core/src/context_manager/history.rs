@@ -379,8 +379,10 @@ impl ContextManager {
             | ResponseItem::WebSearchCall { .. }
             | ResponseItem::ImageGenerationCall { .. }
             | ResponseItem::CustomToolCall { .. }
+            | ResponseItem::CustomToolCallWithAttachments { .. }
             | ResponseItem::Compaction { .. }
             | ResponseItem::GhostSnapshot { .. }
+            | ResponseItem::UnsupportedToolCall { .. }
             | ResponseItem::Other => item.clone(),
         }
     }
@@ -397,11 +399,13 @@ fn truncate_function_output_payload(
         FunctionCallOutputBody::ContentItems(items) => FunctionCallOutputBody::ContentItems(
             truncate_function_output_items_with_policy(items, policy),
         ),
+        FunctionCallOutputBody::Stream(uri) => FunctionCallOutputBody::Stream(uri.clone()),
     };
 
     FunctionCallOutputPayload {
         body,
         success: output.success,
+        error_code: output.error_code.clone(),
     }
 }
 
@@ -414,6 +418,7 @@ fn is_api_message(message: &ResponseItem) -> bool {
         ResponseItem::FunctionCallOutput { .. }
         | ResponseItem::FunctionCall { .. }
         | ResponseItem::CustomToolCall { .. }
+        | ResponseItem::CustomToolCallWithAttachments { .. }
         | ResponseItem::CustomToolCallOutput { .. }
         | ResponseItem::LocalShellCall { .. }
         | ResponseItem::Reasoning { .. }
@@ -421,7 +426,9 @@ fn is_api_message(message: &ResponseItem) -> bool {
         | ResponseItem::ImageGenerationCall { .. }
         | ResponseItem::Compaction { .. } => true,
         ResponseItem::GhostSnapshot { .. } => false,
-        ResponseItem::Other => false,
+        // Never sent back: the provider would not accept a call without its
+        // original shape.
+        ResponseItem::UnsupportedToolCall { .. } | ResponseItem::Other => false,
     }
 }
 
@@ -433,7 +440,7 @@ fn estimate_reasoning_length(encoded_len: usize) -> usize {
         .saturating_sub(650)
 }
 
-fn estimate_item_token_count(item: &ResponseItem) -> i64 {
+pub(crate) fn estimate_item_token_count(item: &ResponseItem) -> i64 {
     let model_visible_bytes = estimate_response_item_model_visible_bytes(item);
     approx_tokens_from_byte_count_i64(model_visible_bytes)
 }
@@ -608,6 +615,8 @@ fn is_model_generated_item(item: &ResponseItem) -> bool {
         | ResponseItem::WebSearchCall { .. }
         | ResponseItem::ImageGenerationCall { .. }
         | ResponseItem::CustomToolCall { .. }
+        | ResponseItem::CustomToolCallWithAttachments { .. }
+        | ResponseItem::UnsupportedToolCall { .. }
         | ResponseItem::LocalShellCall { .. }
         | ResponseItem::Compaction { .. } => true,
         ResponseItem::FunctionCallOutput { .. }
@@ -592,6 +592,7 @@ fn replace_last_turn_images_replaces_tool_output_images() {
                     },
                 ]),
                 success: Some(true),
+                error_code: None,
             },
         },
     ];
@@ -612,6 +613,7 @@ fn replace_last_turn_images_replaces_tool_output_images() {
                         },
                     ]),
                     success: Some(true),
+                    error_code: None,
                 },
             },
         ]
@@ -648,6 +650,7 @@ fn remove_first_item_handles_local_shell_pair() {
                 working_directory: None,
                 env: None,
                 user: None,
+                justification: None,
             }),
         },
         ResponseItem::FunctionCallOutput {
@@ -829,6 +832,7 @@ fn normalization_retains_local_shell_outputs() {
                 working_directory: None,
                 env: None,
                 user: None,
+                justification: None,
             }),
         },
         ResponseItem::FunctionCallOutput {
@@ -856,6 +860,7 @@ fn record_items_truncates_function_call_output_content() {
         output: FunctionCallOutputPayload {
             body: FunctionCallOutputBody::Text(long_output.clone()),
             success: Some(true),
+            error_code: None,
         },
     };
 
@@ -920,6 +925,7 @@ fn record_items_respects_custom_token_limit() {
         output: FunctionCallOutputPayload {
             body: FunctionCallOutputBody::Text(long_output),
             success: Some(true),
+            error_code: None,
         },
     };
 
@@ -1106,6 +1112,7 @@ fn normalize_adds_missing_output_for_local_shell_call_with_id() {
             working_directory: None,
             env: None,
             user: None,
+            justification: None,
         }),
     }];
     let mut h = create_history_with_items(items);
@@ -1125,6 +1132,7 @@ fn normalize_adds_missing_output_for_local_shell_call_with_id() {
                     working_directory: None,
                     env: None,
                     user: None,
+                    justification: None,
                 }),
             },
             ResponseItem::FunctionCallOutput {
@@ -1198,6 +1206,7 @@ fn normalize_mixed_inserts_and_removals() {
                 working_directory: None,
                 env: None,
                 user: None,
+                justification: None,
             }),
         },
     ];
@@ -1239,6 +1248,7 @@ fn normalize_mixed_inserts_and_removals() {
                     working_directory: None,
                     env: None,
                     user: None,
+                    justification: None,
                 }),
             },
             ResponseItem::FunctionCallOutput {
@@ -1305,6 +1315,7 @@ fn normalize_adds_missing_output_for_local_shell_call_with_id_panics_in_debug()
             working_directory: None,
             env: None,
             user: None,
+            justification: None,
         }),
     }];
     let mut h = create_history_with_items(items);
@@ -1367,6 +1378,7 @@ fn normalize_mixed_inserts_and_removals_panics_in_debug() {
                 working_directory: None,
                 env: None,
                 user: None,
+                justification: None,
             }),
         },
     ];
@@ -38,7 +38,8 @@ pub(crate) fn ensure_call_outputs_present(items: &mut Vec<ResponseItem>) {
                     ));
                 }
             }
-            ResponseItem::CustomToolCall { call_id, .. } => {
+            ResponseItem::CustomToolCall { call_id, .. }
+            | ResponseItem::CustomToolCallWithAttachments { call_id, .. } => {
                 let has_output = items.iter().any(|i| match i {
                     ResponseItem::CustomToolCallOutput {
                         call_id: existing, ..
@@ -116,7 +117,8 @@ pub(crate) fn remove_orphan_outputs(items: &mut Vec<ResponseItem>) {
     let custom_tool_call_ids: HashSet<String> = items
         .iter()
         .filter_map(|i| match i {
-            ResponseItem::CustomToolCall { call_id, .. } => Some(call_id.clone()),
+            ResponseItem::CustomToolCall { call_id, .. }
+            | ResponseItem::CustomToolCallWithAttachments { call_id, .. } => Some(call_id.clone()),
             _ => None,
         })
         .collect();
@@ -168,7 +170,8 @@ pub(crate) fn remove_corresponding_for(items: &mut Vec<ResponseItem>, item: &Res
                 items.remove(pos);
             }
         }
-        ResponseItem::CustomToolCall { call_id, .. } => {
+        ResponseItem::CustomToolCall { call_id, .. }
+        | ResponseItem::CustomToolCallWithAttachments { call_id, .. } => {
             remove_first_matching(items, |i| {
                 matches!(
                     i,
@@ -179,10 +182,14 @@ pub(crate) fn remove_corresponding_for(items: &mut Vec<ResponseItem>, item: &Res
             });
         }
         ResponseItem::CustomToolCallOutput { call_id, .. } => {
-            remove_first_matching(
-                items,
-                |i| matches!(i, ResponseItem::CustomToolCall { call_id: existing, .. } if existing == call_id),
-            );
+            remove_first_matching(items, |i| {
+                matches!(
+                    i,
+                    ResponseItem::CustomToolCall { call_id: existing, .. }
+                    | ResponseItem::CustomToolCallWithAttachments { call_id: existing, .. }
+                        if existing == call_id
+                )
+            });
         }
         ResponseItem::LocalShellCall {
             call_id: Some(call_id),
@@ -2,6 +2,7 @@ use crate::codex::PreviousTurnSettings;
 use crate::codex::TurnContext;
 use crate::environment_context::EnvironmentContext;
 use crate::features::Feature;
+use crate::instructions::UserInstructions;
 use crate::shell::Shell;
 use codex_execpolicy::Policy;
 use codex_protocol::config_types::Personality;
@@ -28,6 +29,24 @@ fn build_environment_update_item(
     ))
 }
 
+/// Resends the AGENTS.md instructions when they changed since the previous
+/// turn, e.g. because a project doc was edited mid-session. Removed
+/// instructions are sent as an empty block so the stale ones stop applying.
+fn build_user_instructions_update_item(
+    previous: Option<&TurnContextItem>,
+    next: &TurnContext,
+) -> Option<ResponseItem> {
+    let prev = previous?;
+    if prev.user_instructions == next.user_instructions {
+        return None;
+    }
+
+    Some(ResponseItem::from(UserInstructions {
+        text: next.user_instructions.clone().unwrap_or_default(),
+        directory: next.cwd.to_string_lossy().into_owned(),
+    }))
+}
+
 fn build_permissions_update_item(
     previous: Option<&TurnContextItem>,
     next: &TurnContext,
@@ -197,10 +216,13 @@ pub(crate) fn build_settings_update_items(
     .map(DeveloperInstructions::into_text)
     .collect();
 
-    let mut items = Vec::with_capacity(2);
+    let mut items = Vec::with_capacity(3);
     if let Some(developer_message) = build_developer_update_item(developer_update_sections) {
         items.push(developer_message);
     }
+    if let Some(user_instructions) = build_user_instructions_update_item(previous, next) {
+        items.push(user_instructions);
+    }
     if let Some(contextual_user_message) = contextual_user_message {
         items.push(contextual_user_message);
     }
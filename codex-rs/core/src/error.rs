@@ -137,6 +137,17 @@ pub enum CodexErr {
     #[error("We're currently experiencing high demand, which may cause temporary errors.")]
     InternalServerError,
 
+    /// The provider's circuit breaker is open after repeated failures, so the
+    /// request was not sent.
+    #[error(
+        "Model provider `{provider}` is unavailable after repeated failures; it will be tried again in {}s.",
+        .retry_after.as_secs()
+    )]
+    ProviderUnhealthy {
+        provider: String,
+        retry_after: Duration,
+    },
+
     /// Retry limit exceeded.
     #[error("{0}")]
     RetryLimit(RetryLimitReachedError),
@@ -213,7 +224,8 @@ impl CodexErr {
             | CodexErr::Spawn
             | CodexErr::SessionConfiguredNotFirstEvent
             | CodexErr::UsageLimitReached(_)
-            | CodexErr::ServerOverloaded => false,
+            | CodexErr::ServerOverloaded
+            | CodexErr::ProviderUnhealthy { .. } => false,
             CodexErr::Stream(..)
             | CodexErr::Timeout
             | CodexErr::UnexpectedStatus(_)
@@ -228,6 +240,26 @@ impl CodexErr {
             CodexErr::LandlockRuleset(_) | CodexErr::LandlockPathFd(_) => false,
         }
     }
+
+    /// Whether the error means the model provider is unavailable, i.e. it kept
+    /// answering 429 or 5xx, or could not be reached, once retries ran out. A
+    /// turn that fails this way can be retried with another provider.
+    pub fn is_provider_outage(&self) -> bool {
+        let is_outage_status = |status: StatusCode| {
+            status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+        };
+        match self {
+            CodexErr::RetryLimit(err) => is_outage_status(err.status),
+            CodexErr::UnexpectedStatus(err) => is_outage_status(err.status),
+            CodexErr::Stream(..)
+            | CodexErr::ResponseStreamFailed(_)
+            | CodexErr::ConnectionFailed(_)
+            | CodexErr::InternalServerError
+            | CodexErr::ServerOverloaded
+            | CodexErr::ProviderUnhealthy { .. } => true,
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -1143,4 +1175,20 @@ mod tests {
             assert_eq!(err.to_string(), expected);
         });
     }
+
+    #[test]
+    fn provider_outage_covers_rate_limits_and_server_errors() {
+        let retry_limit = |status| {
+            CodexErr::RetryLimit(RetryLimitReachedError {
+                status,
+                request_id: None,
+            })
+        };
+        assert!(retry_limit(StatusCode::TOO_MANY_REQUESTS).is_provider_outage());
+        assert!(retry_limit(StatusCode::BAD_GATEWAY).is_provider_outage());
+        assert!(!retry_limit(StatusCode::BAD_REQUEST).is_provider_outage());
+        assert!(CodexErr::Stream("disconnected".to_string(), None).is_provider_outage());
+        assert!(!CodexErr::ContextWindowExceeded.is_provider_outage());
+        assert!(!CodexErr::QuotaExceeded.is_provider_outage());
+    }
 }
@@ -16,6 +16,8 @@ use tokio::io::BufReader;
 use tokio::process::Child;
 use tokio_util::sync::CancellationToken;
 
+use crate::config::Config;
+use crate::config::types::ShellOutputRetention;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::SandboxErr;
@@ -175,6 +177,36 @@ pub struct StdoutStream {
     pub sub_id: String,
     pub call_id: String,
     pub tx_event: Sender<Event>,
+    /// How much of the streamed output is kept for the call's result.
+    pub output_cap: ExecOutputCap,
+}
+
+/// How many bytes of each output stream an exec call keeps, and from which
+/// end. Every byte is still streamed as it is read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExecOutputCap {
+    pub max_bytes: usize,
+    pub retention: ShellOutputRetention,
+}
+
+impl ExecOutputCap {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_bytes: config
+                .shell_output_max_bytes
+                .unwrap_or(EXEC_OUTPUT_MAX_BYTES),
+            retention: config.shell_output_retention,
+        }
+    }
+}
+
+impl Default for ExecOutputCap {
+    fn default() -> Self {
+        Self {
+            max_bytes: EXEC_OUTPUT_MAX_BYTES,
+            retention: ShellOutputRetention::Head,
+        }
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -271,6 +303,8 @@ pub fn build_exec_request(
             codex_linux_sandbox_exe: codex_linux_sandbox_exe.as_ref(),
             use_linux_sandbox_bwrap,
             windows_sandbox_level,
+            backend: None,
+            sandbox_path_rules: &[],
         })
         .map_err(CodexErr::from)?;
     Ok(exec_req)
@@ -494,7 +528,7 @@ async fn exec_windows_sandbox(
         text: stderr_text,
         truncated_after_lines: None,
     };
-    let aggregated_output = aggregate_output(&stdout, &stderr);
+    let aggregated_output = aggregate_output(&stdout, &stderr, ExecOutputCap::default());
 
     Ok(RawExecToolCallOutput {
         exit_status,
@@ -689,12 +723,30 @@ fn append_capped(dst: &mut Vec<u8>, src: &[u8], max_bytes: usize) {
     dst.extend_from_slice(&src[..take]);
 }
 
+/// Appends `src` while keeping roughly the last `max_bytes` of output. `dst`
+/// may grow to twice `max_bytes` so the front is not shifted on every chunk;
+/// call [`keep_tail`] once reading is done.
+#[inline]
+fn append_tail_capped(dst: &mut Vec<u8>, src: &[u8], max_bytes: usize) {
+    dst.extend_from_slice(src);
+    if dst.len() > max_bytes.saturating_mul(2) {
+        keep_tail(dst, max_bytes);
+    }
+}
+
+#[inline]
+fn keep_tail(dst: &mut Vec<u8>, max_bytes: usize) {
+    let excess = dst.len().saturating_sub(max_bytes);
+    dst.drain(..excess);
+}
+
 fn aggregate_output(
     stdout: &StreamOutput<Vec<u8>>,
     stderr: &StreamOutput<Vec<u8>>,
+    cap: ExecOutputCap,
 ) -> StreamOutput<Vec<u8>> {
     let total_len = stdout.text.len().saturating_add(stderr.text.len());
-    let max_bytes = EXEC_OUTPUT_MAX_BYTES;
+    let max_bytes = cap.max_bytes;
     let mut aggregated = Vec::with_capacity(total_len.min(max_bytes));
 
     if total_len <= max_bytes {
@@ -713,8 +765,16 @@ fn aggregate_output(
     let remaining = max_bytes.saturating_sub(want_stdout + stderr_take);
     let stdout_take = want_stdout + remaining.min(stdout.text.len().saturating_sub(want_stdout));
 
-    aggregated.extend_from_slice(&stdout.text[..stdout_take]);
-    aggregated.extend_from_slice(&stderr.text[..stderr_take]);
+    match cap.retention {
+        ShellOutputRetention::Head => {
+            aggregated.extend_from_slice(&stdout.text[..stdout_take]);
+            aggregated.extend_from_slice(&stderr.text[..stderr_take]);
+        }
+        ShellOutputRetention::Tail => {
+            aggregated.extend_from_slice(&stdout.text[stdout.text.len() - stdout_take..]);
+            aggregated.extend_from_slice(&stderr.text[stderr.text.len() - stderr_take..]);
+        }
+    }
 
     StreamOutput {
         text: aggregated,
@@ -866,15 +926,21 @@ async fn consume_truncated_output(
         ))
     })?;
 
+    let output_cap = stdout_stream
+        .as_ref()
+        .map(|stream| stream.output_cap)
+        .unwrap_or_default();
     let stdout_handle = tokio::spawn(read_capped(
         BufReader::new(stdout_reader),
         stdout_stream.clone(),
         false,
+        output_cap,
     ));
     let stderr_handle = tokio::spawn(read_capped(
         BufReader::new(stderr_reader),
         stdout_stream.clone(),
         true,
+        output_cap,
     ));
 
     let (exit_status, timed_out) = tokio::select! {
@@ -930,7 +996,7 @@ async fn consume_truncated_output(
         Duration::from_millis(IO_DRAIN_TIMEOUT_MS),
     )
     .await?;
-    let aggregated_output = aggregate_output(&stdout, &stderr);
+    let aggregated_output = aggregate_output(&stdout, &stderr, output_cap);
 
     Ok(RawExecToolCallOutput {
         exit_status,
@@ -945,8 +1011,9 @@ async fn read_capped<R: AsyncRead + Unpin + Send + 'static>(
     mut reader: R,
     stream: Option<StdoutStream>,
     is_stderr: bool,
+    cap: ExecOutputCap,
 ) -> io::Result<StreamOutput<Vec<u8>>> {
-    let mut buf = Vec::with_capacity(AGGREGATE_BUFFER_INITIAL_CAPACITY.min(EXEC_OUTPUT_MAX_BYTES));
+    let mut buf = Vec::with_capacity(AGGREGATE_BUFFER_INITIAL_CAPACITY.min(cap.max_bytes));
     let mut tmp = [0u8; READ_CHUNK_SIZE];
     let mut emitted_deltas: usize = 0;
 
@@ -978,9 +1045,15 @@ async fn read_capped<R: AsyncRead + Unpin + Send + 'static>(
             emitted_deltas += 1;
         }
 
-        append_capped(&mut buf, &tmp[..n], EXEC_OUTPUT_MAX_BYTES);
+        match cap.retention {
+            ShellOutputRetention::Head => append_capped(&mut buf, &tmp[..n], cap.max_bytes),
+            ShellOutputRetention::Tail => append_tail_capped(&mut buf, &tmp[..n], cap.max_bytes),
+        }
         // Continue reading to EOF to avoid back-pressure
     }
+    if cap.retention == ShellOutputRetention::Tail {
+        keep_tail(&mut buf, cap.max_bytes);
+    }
 
     Ok(StreamOutput {
         text: buf,
@@ -1103,10 +1176,50 @@ mod tests {
             writer.write_all(&bytes).await.expect("write");
         });
 
-        let out = read_capped(reader, None, false).await.expect("read");
+        let out = read_capped(reader, None, false, ExecOutputCap::default())
+            .await
+            .expect("read");
         assert_eq!(out.text.len(), EXEC_OUTPUT_MAX_BYTES);
     }
 
+    #[tokio::test]
+    async fn read_capped_with_tail_retention_keeps_last_bytes() {
+        let (mut writer, reader) = tokio::io::duplex(1024);
+        let mut bytes = vec![b'a'; 64 * 1024];
+        bytes.extend_from_slice(b"error: test failed");
+        tokio::spawn(async move {
+            writer.write_all(&bytes).await.expect("write");
+        });
+
+        let cap = ExecOutputCap {
+            max_bytes: 1024,
+            retention: ShellOutputRetention::Tail,
+        };
+        let out = read_capped(reader, None, false, cap).await.expect("read");
+        assert_eq!(out.text.len(), 1024);
+        assert!(out.text.ends_with(b"error: test failed"));
+    }
+
+    #[test]
+    fn aggregate_output_with_tail_retention_keeps_stream_tails() {
+        let stdout = StreamOutput {
+            text: b"stdout head, stdout tail".to_vec(),
+            truncated_after_lines: None,
+        };
+        let stderr = StreamOutput {
+            text: b"stderr head, stderr tail".to_vec(),
+            truncated_after_lines: None,
+        };
+
+        let cap = ExecOutputCap {
+            max_bytes: 24,
+            retention: ShellOutputRetention::Tail,
+        };
+        let aggregated = aggregate_output(&stdout, &stderr, cap);
+        // A third of the cap goes to stdout, the rest to stderr.
+        assert_eq!(aggregated.text, b"out tailead, stderr tail".to_vec());
+    }
+
     #[test]
     fn aggregate_output_prefers_stderr_on_contention() {
         let stdout = StreamOutput {
@@ -1118,7 +1231,7 @@ mod tests {
             truncated_after_lines: None,
         };
 
-        let aggregated = aggregate_output(&stdout, &stderr);
+        let aggregated = aggregate_output(&stdout, &stderr, ExecOutputCap::default());
         let stdout_cap = EXEC_OUTPUT_MAX_BYTES / 3;
         let stderr_cap = EXEC_OUTPUT_MAX_BYTES.saturating_sub(stdout_cap);
 
@@ -1139,7 +1252,7 @@ mod tests {
             truncated_after_lines: None,
         };
 
-        let aggregated = aggregate_output(&stdout, &stderr);
+        let aggregated = aggregate_output(&stdout, &stderr, ExecOutputCap::default());
         let stderr_cap = EXEC_OUTPUT_MAX_BYTES.saturating_sub(stdout_len);
 
         assert_eq!(aggregated.text.len(), EXEC_OUTPUT_MAX_BYTES);
@@ -1158,7 +1271,7 @@ mod tests {
             truncated_after_lines: None,
         };
 
-        let aggregated = aggregate_output(&stdout, &stderr);
+        let aggregated = aggregate_output(&stdout, &stderr, ExecOutputCap::default());
         let stdout_len = EXEC_OUTPUT_MAX_BYTES.saturating_sub(1);
 
         assert_eq!(aggregated.text.len(), EXEC_OUTPUT_MAX_BYTES);
@@ -1177,7 +1290,7 @@ mod tests {
             truncated_after_lines: None,
         };
 
-        let aggregated = aggregate_output(&stdout, &stderr);
+        let aggregated = aggregate_output(&stdout, &stderr, ExecOutputCap::default());
         let mut expected = Vec::new();
         expected.extend_from_slice(&stdout.text);
         expected.extend_from_slice(&stderr.text);
@@ -196,6 +196,40 @@ impl ExecPolicyManager {
         self.policy.load_full()
     }
 
+    /// Returns the configured allow prefix that covers every command parsed
+    /// from `command`, or `None` when any part is unmatched or not allowed.
+    pub(crate) fn matching_allow_prefix_rule(&self, command: &[String]) -> Option<Vec<String>> {
+        let exec_policy = self.current();
+        let (commands, _) = commands_for_exec_policy(command);
+        let match_options = MatchOptions {
+            resolve_host_executables: true,
+        };
+        let mut allowed_prefix: Option<Vec<String>> = None;
+        for cmd in &commands {
+            let matched_rules =
+                exec_policy.matches_for_command_with_options(cmd, None, &match_options);
+            if matched_rules
+                .iter()
+                .any(|rule_match| rule_match.decision() != Decision::Allow)
+            {
+                return None;
+            }
+            let prefix = matched_rules
+                .into_iter()
+                .filter_map(|rule_match| match rule_match {
+                    RuleMatch::PrefixRuleMatch { matched_prefix, .. } => Some(matched_prefix),
+                    _ => None,
+                })
+                .max_by_key(Vec::len)?;
+            match &allowed_prefix {
+                Some(existing) if *existing != prefix => return None,
+                Some(_) => {}
+                None => allowed_prefix = Some(prefix),
+            }
+        }
+        allowed_prefix
+    }
+
     pub(crate) async fn create_exec_approval_requirement_for_command(
         &self,
         req: ExecApprovalRequest<'_>,
@@ -569,7 +603,7 @@ fn default_policy_path(codex_home: &Path) -> PathBuf {
     codex_home.join(RULES_DIR_NAME).join(DEFAULT_POLICY_FILE)
 }
 
-fn commands_for_exec_policy(command: &[String]) -> (Vec<Vec<String>>, bool) {
+pub(crate) fn commands_for_exec_policy(command: &[String]) -> (Vec<Vec<String>>, bool) {
     if let Some(commands) = parse_shell_lc_plain_commands(command)
         && !commands.is_empty()
     {
@@ -1265,6 +1299,42 @@ prefix_rule(pattern=["rm"], decision="forbidden")
         assert_eq!(commands_for_exec_policy(&command), (vec![command], false));
     }
 
+    #[test]
+    fn matching_allow_prefix_rule_reports_the_configured_prefix() {
+        let policy_src = r#"
+prefix_rule(pattern=["git"], decision="allow")
+prefix_rule(pattern=["rm"], decision="forbidden")
+"#;
+        let mut parser = PolicyParser::new();
+        parser
+            .parse("test.rules", policy_src)
+            .expect("parse policy");
+        let manager = ExecPolicyManager::new(Arc::new(parser.build()));
+        let command = |args: &[&str]| args.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        assert_eq!(
+            manager.matching_allow_prefix_rule(&command(&["git", "status"])),
+            Some(vec!["git".to_string()])
+        );
+        assert_eq!(
+            manager.matching_allow_prefix_rule(&command(&[
+                "bash",
+                "-lc",
+                "git add . && git commit"
+            ])),
+            Some(vec!["git".to_string()])
+        );
+        assert_eq!(
+            manager.matching_allow_prefix_rule(&command(&["bash", "-lc", "git status && ls"])),
+            None
+        );
+        assert_eq!(
+            manager.matching_allow_prefix_rule(&command(&["rm", "-rf", "build"])),
+            None
+        );
+        assert_eq!(manager.matching_allow_prefix_rule(&command(&["ls"])), None);
+    }
+
     #[tokio::test]
     async fn evaluates_heredoc_script_against_prefix_rules() {
         let policy_src = r#"prefix_rule(pattern=["python3"], decision="allow")"#;
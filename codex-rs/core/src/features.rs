@@ -10,7 +10,11 @@ use crate::config::ConfigToml;
 use crate::config::profile::ConfigProfile;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
+use crate::protocol::FeatureSource;
+use crate::protocol::FeaturesResolvedEvent;
+use crate::protocol::ResolvedFeature;
 use crate::protocol::WarningEvent;
+use codex_app_server_protocol::ConfigLayerSource;
 use codex_config::CONFIG_TOML_FILE;
 use codex_otel::SessionTelemetry;
 use schemars::JsonSchema;
@@ -87,6 +91,9 @@ pub enum Feature {
     JsReplToolsOnly,
     /// Use the single unified PTY-backed exec tool.
     UnifiedExec,
+    /// Let the model list and kill unified exec processes left running
+    /// across turns.
+    BackgroundJobs,
     /// Route shell tool execution through the zsh exec bridge.
     ShellZshFork,
     /// Include the freeform apply_patch tool.
@@ -98,8 +105,45 @@ pub enum Feature {
     /// Allow the model to request web searches that fetch cached content.
     /// Takes precedence over `WebSearchRequest`.
     WebSearchCached,
+    /// Let the model fetch web pages as markdown with the `web_fetch` tool.
+    WebFetch,
+    /// Answer repeated read-only calls to cacheable tools from a per-session
+    /// cache instead of running them again.
+    ToolOutputCache,
+    /// Run sessions in a dedicated git worktree; `codex merge` applies their
+    /// changes to the live checkout.
+    WorktreeIsolation,
+    /// Let the model search the workspace semantically with the
+    /// embeddings-backed `code_search` tool.
+    CodeSearch,
+    /// Let the model register files with the `watch_files` tool and hear
+    /// about changes made outside its own patches at the start of later
+    /// turns.
+    WatchFiles,
+    /// Allow streaming a session's events over WebSocket to read-only
+    /// observers with `Op::StartSessionShare`.
+    SessionSharing,
+    /// Let the model keep per-project facts across sessions with the
+    /// `memory_write` and `memory_read` tools, and include the most relevant
+    /// ones in new sessions.
+    ProjectMemory,
+    /// Probe local inference servers for what the model supports, and
+    /// emulate tool calls through JSON replies when it has no native tools.
+    LocalModelProbing,
+    /// Ask the client to accept or reject each hunk of a patch that needs
+    /// approval, instead of approving or denying the whole patch.
+    PatchHunkReview,
+    /// Reject mutating tool calls during Plan mode turns and hide the tools
+    /// that can only mutate, instead of relying on the prompt alone.
+    ReadOnlyPlanMode,
     /// Legacy search-tool feature flag kept for backward compatibility.
     SearchTool,
+    /// Replace credentials found in tool output with placeholders before the
+    /// output is recorded in history or the rollout.
+    SecretsRedaction,
+    /// Attach the images a shell command writes to `CODEX_IMAGE_OUTPUT_DIR`
+    /// to its output.
+    ShellImageOutput,
     /// Use the bubblewrap-based Linux sandbox pipeline.
     UseLinuxSandboxBwrap,
     /// Allow the model to request approval and propose exec rules.
@@ -344,12 +388,34 @@ impl Features {
         }
     }
 
+    /// Apply `[feature_defaults]` from requirements on top of the built-in
+    /// defaults. Only canonical keys are accepted.
+    pub fn apply_defaults(&mut self, defaults: &BTreeMap<String, bool>) {
+        for (key, enabled) in defaults {
+            match canonical_feature_for_key(key) {
+                Some(feature) => {
+                    self.set_enabled(feature, *enabled);
+                }
+                None => {
+                    tracing::warn!("unknown feature key in feature_defaults: {key}");
+                }
+            }
+        }
+    }
+
+    /// Resolves features in increasing precedence: built-in defaults,
+    /// `remote_defaults`, config.toml, the active profile, then `overrides`.
+    /// CLI flags are part of the config layers and so win over config files.
     pub fn from_config(
         cfg: &ConfigToml,
         config_profile: &ConfigProfile,
         overrides: FeatureOverrides,
+        remote_defaults: Option<&BTreeMap<String, bool>>,
     ) -> Self {
         let mut features = Features::with_defaults();
+        if let Some(remote_defaults) = remote_defaults {
+            features.apply_defaults(remote_defaults);
+        }
 
         let base_legacy = LegacyFeatureToggles {
             experimental_use_freeform_apply_patch: cfg.experimental_use_freeform_apply_patch,
@@ -486,6 +552,12 @@ pub const FEATURES: &[FeatureSpec] = &[
         stage: Stage::Stable,
         default_enabled: !cfg!(windows),
     },
+    FeatureSpec {
+        id: Feature::BackgroundJobs,
+        key: "background_jobs",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
     FeatureSpec {
         id: Feature::ShellZshFork,
         key: "shell_zsh_fork",
@@ -526,12 +598,84 @@ pub const FEATURES: &[FeatureSpec] = &[
         stage: Stage::Deprecated,
         default_enabled: false,
     },
+    FeatureSpec {
+        id: Feature::WebFetch,
+        key: "web_fetch",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::ToolOutputCache,
+        key: "tool_output_cache",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::WorktreeIsolation,
+        key: "worktree_isolation",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::CodeSearch,
+        key: "code_search",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::WatchFiles,
+        key: "watch_files",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::SessionSharing,
+        key: "session_sharing",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::ProjectMemory,
+        key: "project_memory",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::LocalModelProbing,
+        key: "local_model_probing",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::PatchHunkReview,
+        key: "patch_hunk_review",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::ReadOnlyPlanMode,
+        key: "read_only_plan_mode",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
     FeatureSpec {
         id: Feature::SearchTool,
         key: "search_tool",
         stage: Stage::Removed,
         default_enabled: false,
     },
+    FeatureSpec {
+        id: Feature::SecretsRedaction,
+        key: "secrets_redaction",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::ShellImageOutput,
+        key: "shell_image_output",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
     // Experimental program. Rendered in the `/experimental` menu for users.
     FeatureSpec {
         id: Feature::CodexGitCommit,
@@ -782,6 +926,64 @@ pub const FEATURES: &[FeatureSpec] = &[
     },
 ];
 
+/// Every known feature with its effective value and where that value came
+/// from, for debugging why sessions behave differently across machines.
+pub fn resolve_features(config: &Config) -> FeaturesResolvedEvent {
+    let requirements = config.config_layer_stack.requirements();
+    let pinned = requirements
+        .feature_requirements
+        .as_ref()
+        .map(|requirements| &requirements.value.entries);
+    let remote_defaults = requirements
+        .feature_defaults
+        .as_ref()
+        .map(|defaults| &defaults.value.entries);
+    let origins = config.config_layer_stack.origins();
+    let layer_source = |path: String| {
+        origins.get(&path).map(|origin| match origin.name {
+            ConfigLayerSource::SessionFlags => FeatureSource::Cli,
+            _ => FeatureSource::Config,
+        })
+    };
+
+    let features = FEATURES
+        .iter()
+        .filter(|spec| !matches!(spec.stage, Stage::Removed))
+        .map(|spec| {
+            let enabled = config.features.enabled(spec.id);
+            let profile_source = config.active_profile.as_ref().and_then(|profile| {
+                layer_source(format!("profiles.{profile}.features.{}", spec.key)).map(|source| {
+                    match source {
+                        FeatureSource::Config => FeatureSource::Profile,
+                        source => source,
+                    }
+                })
+            });
+            let source = if pinned.is_some_and(|pinned| pinned.contains_key(spec.key)) {
+                FeatureSource::Requirement
+            } else if let Some(source) =
+                profile_source.or_else(|| layer_source(format!("features.{}", spec.key)))
+            {
+                source
+            } else if remote_defaults.is_some_and(|defaults| defaults.contains_key(spec.key)) {
+                FeatureSource::Remote
+            } else if enabled != spec.default_enabled {
+                // Legacy settings and overrides that are not `[features]` keys.
+                FeatureSource::Config
+            } else {
+                FeatureSource::Default
+            };
+            ResolvedFeature {
+                key: spec.key.to_string(),
+                enabled,
+                default_enabled: spec.default_enabled,
+                source,
+            }
+        })
+        .collect();
+    FeaturesResolvedEvent { features }
+}
+
 /// Push a warning event if any under-development features are enabled.
 pub fn maybe_push_unstable_features_warning(
     config: &Config,
@@ -1,5 +1,6 @@
-//! Watches skill roots for changes and broadcasts coarse-grained
-//! `FileWatcherEvent`s that higher-level components react to on the next turn.
+//! Watches skill roots and project docs (`AGENTS.md` and friends) for changes
+//! and broadcasts coarse-grained `FileWatcherEvent`s that higher-level
+//! components react to on the next turn.
 
 use std::collections::HashMap;
 use std::collections::HashSet;
@@ -23,15 +24,20 @@ use tokio::time::sleep_until;
 use tracing::warn;
 
 use crate::config::Config;
+use crate::project_doc::project_doc_watch_paths;
 use crate::skills::SkillsManager;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FileWatcherEvent {
     SkillsChanged { paths: Vec<PathBuf> },
+    ProjectDocsChanged { paths: Vec<PathBuf> },
 }
 
 struct WatchState {
     skills_root_ref_counts: HashMap<PathBuf, usize>,
+    /// Candidate project doc paths, which need not exist. Their parent
+    /// directories are watched non-recursively.
+    project_doc_ref_counts: HashMap<PathBuf, usize>,
 }
 
 struct FileWatcherInner {
@@ -94,12 +100,14 @@ pub(crate) struct FileWatcher {
 pub(crate) struct WatchRegistration {
     file_watcher: std::sync::Weak<FileWatcher>,
     roots: Vec<PathBuf>,
+    project_docs: Vec<PathBuf>,
 }
 
 impl Drop for WatchRegistration {
     fn drop(&mut self) {
         if let Some(file_watcher) = self.file_watcher.upgrade() {
             file_watcher.unregister_roots(&self.roots);
+            file_watcher.unregister_project_docs(&self.project_docs);
         }
     }
 }
@@ -118,6 +126,7 @@ impl FileWatcher {
         let (tx, _) = broadcast::channel(128);
         let state = Arc::new(RwLock::new(WatchState {
             skills_root_ref_counts: HashMap::new(),
+            project_doc_ref_counts: HashMap::new(),
         }));
         let file_watcher = Self {
             inner: Some(Mutex::new(inner)),
@@ -134,6 +143,7 @@ impl FileWatcher {
             inner: None,
             state: Arc::new(RwLock::new(WatchState {
                 skills_root_ref_counts: HashMap::new(),
+                project_doc_ref_counts: HashMap::new(),
             })),
             tx,
         }
@@ -159,9 +169,15 @@ impl FileWatcher {
             self.register_skills_root(root.clone());
         }
 
+        let project_docs = project_doc_watch_paths(config);
+        for path in &project_docs {
+            self.register_project_doc(path.clone());
+        }
+
         WatchRegistration {
             file_watcher: Arc::downgrade(self),
             roots: registered_roots,
+            project_docs,
         }
     }
 
@@ -177,10 +193,15 @@ impl FileWatcher {
             handle.spawn(async move {
                 let now = Instant::now();
                 let mut skills = ThrottledPaths::new(now);
+                let mut project_docs = ThrottledPaths::new(now);
 
                 loop {
                     let now = Instant::now();
-                    let next_deadline = skills.next_deadline(now);
+                    let next_deadline =
+                        match (skills.next_deadline(now), project_docs.next_deadline(now)) {
+                            (Some(a), Some(b)) => Some(a.min(b)),
+                            (a, b) => a.or(b),
+                        };
                     let timer_deadline = next_deadline
                         .unwrap_or_else(|| now + Duration::from_secs(60 * 60 * 24 * 365));
                     let timer = sleep_until(timer_deadline);
@@ -191,12 +212,19 @@ impl FileWatcher {
                             match res {
                                 Some(Ok(event)) => {
                                     let skills_paths = classify_event(&event, &state);
+                                    let project_doc_paths =
+                                        classify_project_doc_event(&event, &state);
                                     let now = Instant::now();
                                     skills.add(skills_paths);
+                                    project_docs.add(project_doc_paths);
 
                                     if let Some(paths) = skills.take_ready(now) {
                                         let _ = tx.send(FileWatcherEvent::SkillsChanged { paths });
                                     }
+                                    if let Some(paths) = project_docs.take_ready(now) {
+                                        let _ = tx
+                                            .send(FileWatcherEvent::ProjectDocsChanged { paths });
+                                    }
                                 }
                                 Some(Err(err)) => {
                                     warn!("file watcher error: {err}");
@@ -208,6 +236,10 @@ impl FileWatcher {
                                     if let Some(paths) = skills.take_pending(now) {
                                         let _ = tx.send(FileWatcherEvent::SkillsChanged { paths });
                                     }
+                                    if let Some(paths) = project_docs.take_pending(now) {
+                                        let _ = tx
+                                            .send(FileWatcherEvent::ProjectDocsChanged { paths });
+                                    }
                                     break;
                                 }
                             }
@@ -217,6 +249,9 @@ impl FileWatcher {
                             if let Some(paths) = skills.take_ready(now) {
                                 let _ = tx.send(FileWatcherEvent::SkillsChanged { paths });
                             }
+                            if let Some(paths) = project_docs.take_ready(now) {
+                                let _ = tx.send(FileWatcherEvent::ProjectDocsChanged { paths });
+                            }
                         }
                     }
                 }
@@ -284,6 +319,66 @@ impl FileWatcher {
         }
     }
 
+    fn register_project_doc(&self, path: PathBuf) {
+        let mut state = self
+            .state
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let Some(dir) = path.parent().map(Path::to_path_buf) else {
+            return;
+        };
+        let dir_watched = state
+            .project_doc_ref_counts
+            .keys()
+            .any(|doc| doc.parent() == Some(dir.as_path()));
+        *state.project_doc_ref_counts.entry(path).or_insert(0) += 1;
+        if !dir_watched {
+            self.watch_path(dir, RecursiveMode::NonRecursive);
+        }
+    }
+
+    fn unregister_project_docs(&self, paths: &[PathBuf]) {
+        let mut state = self
+            .state
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let mut released_dirs = Vec::new();
+        for path in paths {
+            let Some(count) = state.project_doc_ref_counts.get_mut(path) else {
+                continue;
+            };
+            if *count > 1 {
+                *count -= 1;
+                continue;
+            }
+            state.project_doc_ref_counts.remove(path);
+            if let Some(dir) = path.parent() {
+                released_dirs.push(dir.to_path_buf());
+            }
+        }
+        released_dirs.retain(|dir| {
+            !state.skills_root_ref_counts.contains_key(dir)
+                && !state
+                    .project_doc_ref_counts
+                    .keys()
+                    .any(|doc| doc.parent() == Some(dir.as_path()))
+        });
+        let Some(inner) = &self.inner else {
+            return;
+        };
+        let mut guard = inner
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        for dir in released_dirs {
+            if guard.watched_paths.remove(&dir).is_none() {
+                continue;
+            }
+            if let Err(err) = guard.watcher.unwatch(&dir) {
+                warn!("failed to unwatch {}: {err}", dir.display());
+            }
+        }
+    }
+
     fn watch_path(&self, path: PathBuf, mode: RecursiveMode) {
         let Some(inner) = &self.inner else {
             return;
@@ -349,6 +444,25 @@ fn is_skills_path(path: &Path, roots: &HashSet<PathBuf>) -> bool {
     roots.iter().any(|root| path.starts_with(root))
 }
 
+fn classify_project_doc_event(event: &Event, state: &RwLock<WatchState>) -> Vec<PathBuf> {
+    if !matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    ) {
+        return Vec::new();
+    }
+
+    let state = state
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    event
+        .paths
+        .iter()
+        .filter(|path| state.project_doc_ref_counts.contains_key(*path))
+        .cloned()
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -413,6 +527,7 @@ mod tests {
         let root = path("/tmp/skills");
         let state = RwLock::new(WatchState {
             skills_root_ref_counts: HashMap::from([(root.clone(), 1)]),
+            project_doc_ref_counts: HashMap::new(),
         });
         let event = notify_event(
             EventKind::Create(CreateKind::Any),
@@ -432,6 +547,7 @@ mod tests {
         let root_b = path("/tmp/workspace/.codex/skills");
         let state = RwLock::new(WatchState {
             skills_root_ref_counts: HashMap::from([(root_a.clone(), 1), (root_b.clone(), 1)]),
+            project_doc_ref_counts: HashMap::new(),
         });
         let event = notify_event(
             EventKind::Modify(ModifyKind::Any),
@@ -454,6 +570,7 @@ mod tests {
         let root = path("/tmp/skills");
         let state = RwLock::new(WatchState {
             skills_root_ref_counts: HashMap::from([(root.clone(), 1)]),
+            project_doc_ref_counts: HashMap::new(),
         });
         let path = root.join("demo/SKILL.md");
 
@@ -470,6 +587,33 @@ mod tests {
         assert_eq!(classify_event(&other_event, &state), Vec::<PathBuf>::new());
     }
 
+    #[test]
+    fn classify_project_doc_event_only_reports_registered_docs() {
+        let watcher = Arc::new(FileWatcher::noop());
+        let doc = path("/repo/AGENTS.md");
+        watcher.register_project_doc(doc.clone());
+        let event = notify_event(
+            EventKind::Modify(ModifyKind::Any),
+            vec![doc.clone(), path("/repo/README.md")],
+        );
+
+        assert_eq!(
+            classify_project_doc_event(&event, &watcher.state),
+            vec![doc.clone()]
+        );
+
+        let registration = WatchRegistration {
+            file_watcher: Arc::downgrade(&watcher),
+            roots: Vec::new(),
+            project_docs: vec![doc],
+        };
+        drop(registration);
+        assert_eq!(
+            classify_project_doc_event(&event, &watcher.state),
+            Vec::<PathBuf>::new()
+        );
+    }
+
     #[test]
     fn register_skills_root_dedupes_state_entries() {
         let watcher = FileWatcher::noop();
@@ -490,6 +634,7 @@ mod tests {
         let registration = WatchRegistration {
             file_watcher: Arc::downgrade(&watcher),
             roots: vec![root],
+            project_docs: Vec::new(),
         };
 
         drop(registration);
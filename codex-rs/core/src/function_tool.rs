@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Clone, Debug, Error, PartialEq)]
 pub enum FunctionCallError {
     #[error("{0}")]
     RespondToModel(String),
@@ -8,4 +8,44 @@ pub enum FunctionCallError {
     MissingLocalShellCallId,
     #[error("Fatal error: {0}")]
     Fatal(String),
+    #[error(
+        "tool call arguments are {size_bytes} bytes, which exceeds the {limit_bytes} byte limit"
+    )]
+    PayloadTooLarge {
+        size_bytes: usize,
+        limit_bytes: usize,
+    },
+    #[error(
+        "tool {tool_name} timed out after {elapsed_ms}ms and was stopped before returning a result"
+    )]
+    TimedOut { tool_name: String, elapsed_ms: u128 },
+    /// The connection to an MCP server failed while making the call, as
+    /// opposed to the tool itself reporting an error.
+    #[error("{0}")]
+    McpTransport(String),
+    #[error("tool {tool_name} was not run: the ${budget_usd} budget for paid tools is spent")]
+    BudgetExhausted { tool_name: String, budget_usd: f64 },
+    #[error("call {call_id} is already running; its output is reported once it finishes")]
+    DuplicateCallId { call_id: String },
+    /// The model emitted a tool call of a type this client cannot run.
+    #[error("tool call {call_id} has unsupported type `{item_type}` and was not run")]
+    UnsupportedToolCall { item_type: String, call_id: String },
+}
+
+impl FunctionCallError {
+    /// Stable, machine-readable name of the variant, reported alongside the
+    /// message so callers can classify failures without parsing it.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::RespondToModel(_) => "respond_to_model",
+            Self::MissingLocalShellCallId => "missing_call_id",
+            Self::Fatal(_) => "fatal",
+            Self::PayloadTooLarge { .. } => "payload_too_large",
+            Self::TimedOut { .. } => "timed_out",
+            Self::McpTransport(_) => "mcp_transport",
+            Self::BudgetExhausted { .. } => "budget_exhausted",
+            Self::DuplicateCallId { .. } => "duplicate_call_id",
+            Self::UnsupportedToolCall { .. } => "unsupported_tool_call",
+        }
+    }
 }
@@ -443,6 +443,12 @@ fn collect_guardian_transcript_entries(items: &[ResponseItem]) -> Vec<GuardianTr
                 name,
                 input,
                 ..
+            }
+            | ResponseItem::CustomToolCallWithAttachments {
+                call_id,
+                name,
+                input,
+                ..
             } => {
                 tool_names_by_call_id.insert(call_id.clone(), name.clone());
                 (!input.trim().is_empty()).then(|| GuardianTranscriptEntry {
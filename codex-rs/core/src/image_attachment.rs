@@ -0,0 +1,110 @@
+//! In-memory images attached to a session so later messages can reference
+//! them by handle instead of re-sending the bytes.
+
+use std::fmt;
+
+use base64::Engine;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::image_close_tag_text;
+use codex_protocol::models::image_open_tag_text;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImageAttachment {
+    pub data: Vec<u8>,
+    /// MIME type of `data`, e.g. `image/png`.
+    pub mime_type: String,
+    pub label: Option<String>,
+}
+
+/// Reference to an image registered with [`Session::attach_image`].
+///
+/// [`Session::attach_image`]: crate::codex::Session::attach_image
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ImageHandle(pub(crate) u64);
+
+impl fmt::Display for ImageHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "image-{}", self.0)
+    }
+}
+
+impl ImageAttachment {
+    /// Base64 `data:` URL accepted by the `image_url` field of vision inputs.
+    pub(crate) fn data_url(&self) -> String {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&self.data);
+        format!("data:{};base64,{encoded}", self.mime_type)
+    }
+
+    /// Content parts for a user message referencing this image. Models without
+    /// vision support get a text placeholder instead of the image bytes.
+    pub(crate) fn content_items(&self, supports_vision: bool) -> Vec<ContentItem> {
+        let mut items = Vec::with_capacity(4);
+        if let Some(label) = &self.label {
+            items.push(ContentItem::InputText {
+                text: label.clone(),
+            });
+        }
+        if supports_vision {
+            items.push(ContentItem::InputText {
+                text: image_open_tag_text(),
+            });
+            items.push(ContentItem::InputImage {
+                image_url: self.data_url(),
+            });
+            items.push(ContentItem::InputText {
+                text: image_close_tag_text(),
+            });
+        } else {
+            items.push(ContentItem::InputText {
+                text: format!(
+                    "[{} image omitted: the current model does not accept image input]",
+                    self.mime_type
+                ),
+            });
+        }
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn attachment(label: Option<&str>) -> ImageAttachment {
+        ImageAttachment {
+            data: vec![0x89, b'P', b'N', b'G'],
+            mime_type: "image/png".to_string(),
+            label: label.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn vision_content_serializes_as_input_image_data_url() {
+        let items = attachment(Some("screenshot")).content_items(true);
+
+        assert_eq!(
+            serde_json::to_value(&items).expect("serialize content items"),
+            json!([
+                { "type": "input_text", "text": "screenshot" },
+                { "type": "input_text", "text": "<image>" },
+                { "type": "input_image", "image_url": "data:image/png;base64,iVBORw==" },
+                { "type": "input_text", "text": "</image>" },
+            ])
+        );
+    }
+
+    #[test]
+    fn text_only_models_get_placeholder() {
+        let items = attachment(None).content_items(false);
+
+        assert_eq!(
+            items,
+            vec![ContentItem::InputText {
+                text: "[image/png image omitted: the current model does not accept image input]"
+                    .to_string(),
+            }]
+        );
+    }
+}
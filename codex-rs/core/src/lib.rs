@@ -8,10 +8,18 @@
 mod analytics_client;
 pub mod api_bridge;
 mod apply_patch;
+pub mod approval_rules;
 mod apps;
+pub mod audit;
 pub mod auth;
+mod checkpoint;
+pub use checkpoint::CheckpointId;
+pub use checkpoint::CheckpointStore;
+pub use checkpoint::FileCheckpointStore;
+pub use checkpoint::InMemoryCheckpointStore;
 mod client;
 mod client_common;
+mod code_index;
 pub mod codex;
 mod realtime_context;
 mod realtime_conversation;
@@ -27,6 +35,9 @@ mod commit_attribution;
 pub mod config;
 pub mod config_loader;
 pub mod connectors;
+mod context_item;
+pub use context_item::ContextItem;
+pub use context_item::MessageRole;
 mod context_manager;
 mod contextual_user_message;
 pub mod custom_prompts;
@@ -42,6 +53,7 @@ mod file_watcher;
 mod flags;
 pub mod git_info;
 mod guardian;
+mod image_attachment;
 pub mod instructions;
 pub mod landlock;
 pub mod mcp;
@@ -53,6 +65,7 @@ pub use mcp_connection_manager::MCP_SANDBOX_STATE_CAPABILITY;
 pub use mcp_connection_manager::MCP_SANDBOX_STATE_METHOD;
 pub use mcp_connection_manager::SandboxState;
 pub use text_encoding::bytes_to_string_smart;
+mod mcp_supervisor;
 mod mcp_tool_call;
 mod memories;
 mod mentions;
@@ -63,11 +76,17 @@ pub mod personality_migration;
 pub mod plugins;
 mod sandbox_tags;
 pub mod sandboxing;
+pub mod session_export;
 mod session_prefix;
+#[cfg(test)]
+mod session_recording;
+mod session_share;
+pub mod session_worktree;
 mod shell_detect;
 mod stream_events_utils;
 pub mod test_support;
 mod text_encoding;
+mod token_budget;
 pub mod token_data;
 mod truncate;
 mod unified_exec;
@@ -77,6 +96,7 @@ pub use model_provider_info::DEFAULT_LMSTUDIO_PORT;
 pub use model_provider_info::DEFAULT_OLLAMA_PORT;
 pub use model_provider_info::LMSTUDIO_OSS_PROVIDER_ID;
 pub use model_provider_info::ModelProviderInfo;
+pub use model_provider_info::ModelRetryConfig;
 pub use model_provider_info::OLLAMA_OSS_PROVIDER_ID;
 pub use model_provider_info::WireApi;
 pub use model_provider_info::built_in_model_providers;
@@ -114,6 +134,8 @@ mod tools;
 pub mod turn_diff_tracker;
 mod turn_metadata;
 mod turn_timing;
+mod watched_files;
+mod workspace_roots;
 pub use rollout::ARCHIVED_SESSIONS_SUBDIR;
 pub use rollout::INTERACTIVE_SESSION_SOURCES;
 pub use rollout::RolloutRecorder;
@@ -138,6 +160,7 @@ pub use rollout::policy::EventPersistenceMode;
 pub use rollout::rollout_date_parts;
 pub use rollout::session_index::find_thread_names_by_ids;
 mod function_tool;
+pub use function_tool::FunctionCallError;
 mod state;
 mod tasks;
 mod user_shell_command;
@@ -165,7 +188,52 @@ pub use exec_policy::format_exec_policy_error_with_source;
 pub use exec_policy::load_exec_policy;
 pub use file_watcher::FileWatcherEvent;
 pub use safety::get_platform_sandbox;
+pub use tools::audit::AuditHook;
+pub use tools::audit::AuditOutcome;
+pub use tools::audit::AuditRecord;
+pub use tools::audit::EncryptedEntry;
+pub use tools::audit::EncryptionKey;
+pub use tools::audit::decrypt_entry;
+pub use tools::call_events::ToolCallEvent;
+#[cfg(feature = "dynamic-plugins")]
+pub use tools::dynamic_plugins::FreeOutputFnV1;
+#[cfg(feature = "dynamic-plugins")]
+pub use tools::dynamic_plugins::PluginLoadError;
+#[cfg(feature = "dynamic-plugins")]
+pub use tools::dynamic_plugins::TOOL_PLUGIN_ABI_VERSION;
+#[cfg(feature = "dynamic-plugins")]
+pub use tools::dynamic_plugins::TOOL_PLUGIN_SYMBOL;
+#[cfg(feature = "dynamic-plugins")]
+pub use tools::dynamic_plugins::ToolCallFnV1;
+#[cfg(feature = "dynamic-plugins")]
+pub use tools::dynamic_plugins::ToolDefV1;
+#[cfg(feature = "dynamic-plugins")]
+pub use tools::dynamic_plugins::ToolPluginV1;
+pub use tools::in_flight::InFlightToolCall;
+pub use tools::in_flight::PendingToolCall;
+pub use tools::middleware::AfterAllHook;
+pub use tools::middleware::BeforeAllHook;
+pub use tools::middleware::BoxedDispatchFn;
+pub use tools::middleware::DispatchInterceptor;
+pub use tools::middleware::SandboxPermissionsHook;
+pub use tools::output_cache::CachedToolOutput;
+pub use tools::output_cache::InMemoryToolOutputCache;
+pub use tools::output_cache::ToolOutputCache;
+pub use tools::output_cache::ToolOutputCacheKey;
+pub use tools::output_transform::ToolOutputTransformer;
+pub use tools::router::ArchivedToolCall;
+pub use tools::router::ToolCall;
+pub use tools::router::ToolCallSource;
+pub use tools::router::TurnToolOverrides;
 pub use tools::spec::parse_tool_input_schema;
+#[cfg(feature = "wasm-plugins")]
+pub use tools::wasm_plugins::WASM_ALLOC_EXPORT;
+#[cfg(feature = "wasm-plugins")]
+pub use tools::wasm_plugins::WASM_TOOL_MANIFEST_EXPORT;
+#[cfg(feature = "wasm-plugins")]
+pub use tools::wasm_plugins::WasmPluginError;
+#[cfg(feature = "wasm-plugins")]
+pub use tools::wasm_plugins::WasmPluginLimits;
 pub use turn_metadata::build_turn_metadata_header;
 pub mod compact;
 pub mod memory_trace;
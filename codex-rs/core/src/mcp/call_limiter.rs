@@ -0,0 +1,162 @@
+//! Per-server limits on MCP tool calls, set with `max_concurrent_calls` and
+//! `max_calls_per_minute`. A call over either limit waits for room instead
+//! of failing, so a slow server is not flooded by parallel calls.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_protocol::protocol::ToolCallQueueReason;
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::Semaphore;
+
+use crate::config::types::McpServerConfig;
+
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+pub(crate) struct McpCallLimiter {
+    concurrency: Option<Arc<Semaphore>>,
+    max_calls_per_minute: Option<usize>,
+    /// Start times of the calls made within the last [`RATE_LIMIT_WINDOW`].
+    recent_calls: Mutex<VecDeque<Instant>>,
+}
+
+/// Held while a call runs; frees its concurrency slot when dropped.
+pub(crate) struct McpCallPermit {
+    _concurrency: Option<OwnedSemaphorePermit>,
+}
+
+impl McpCallLimiter {
+    /// Returns `None` when `config` sets no limits.
+    pub(crate) fn from_config(config: &McpServerConfig) -> Option<Self> {
+        if config.max_concurrent_calls.is_none() && config.max_calls_per_minute.is_none() {
+            return None;
+        }
+        Some(Self {
+            concurrency: config
+                .max_concurrent_calls
+                .map(|max| Arc::new(Semaphore::new(max.max(1)))),
+            max_calls_per_minute: config
+                .max_calls_per_minute
+                .map(|max| usize::try_from(max.max(1)).unwrap_or(usize::MAX)),
+            recent_calls: Mutex::default(),
+        })
+    }
+
+    /// Takes a slot if one is free right now, or reports which limit the
+    /// call would have to wait on.
+    pub(crate) fn try_acquire(&self) -> Result<McpCallPermit, ToolCallQueueReason> {
+        let concurrency = match &self.concurrency {
+            Some(semaphore) => Some(
+                Arc::clone(semaphore)
+                    .try_acquire_owned()
+                    .map_err(|_| ToolCallQueueReason::MaxConcurrency)?,
+            ),
+            None => None,
+        };
+        self.reserve_rate_slot(Instant::now())
+            .map_err(|_| ToolCallQueueReason::RateLimit)?;
+        Ok(McpCallPermit {
+            _concurrency: concurrency,
+        })
+    }
+
+    /// Waits for a concurrency slot, then for room under the rate limit.
+    pub(crate) async fn acquire(&self) -> McpCallPermit {
+        let concurrency = match &self.concurrency {
+            // The semaphore is never closed, so this only fails if it were.
+            Some(semaphore) => Arc::clone(semaphore).acquire_owned().await.ok(),
+            None => None,
+        };
+        while let Err(wait) = self.reserve_rate_slot(Instant::now()) {
+            tokio::time::sleep(wait).await;
+        }
+        McpCallPermit {
+            _concurrency: concurrency,
+        }
+    }
+
+    /// Records a call starting at `now` if the rate limit allows it;
+    /// otherwise returns how long until it will.
+    fn reserve_rate_slot(&self, now: Instant) -> Result<(), Duration> {
+        let Some(max_calls) = self.max_calls_per_minute else {
+            return Ok(());
+        };
+        let mut recent_calls = self
+            .recent_calls
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        while recent_calls
+            .front()
+            .is_some_and(|start| now.saturating_duration_since(*start) >= RATE_LIMIT_WINDOW)
+        {
+            recent_calls.pop_front();
+        }
+        match recent_calls.front() {
+            Some(oldest) if recent_calls.len() >= max_calls => {
+                Err(RATE_LIMIT_WINDOW.saturating_sub(now.saturating_duration_since(*oldest)))
+            }
+            _ => {
+                recent_calls.push_back(now);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn limiter(
+        max_concurrent_calls: Option<usize>,
+        max_calls_per_minute: Option<u32>,
+    ) -> McpCallLimiter {
+        let mut config: McpServerConfig =
+            toml::from_str(r#"command = "echo""#).expect("should deserialize command config");
+        config.max_concurrent_calls = max_concurrent_calls;
+        config.max_calls_per_minute = max_calls_per_minute;
+        McpCallLimiter::from_config(&config).expect("limits are set")
+    }
+
+    #[tokio::test]
+    async fn concurrent_calls_wait_for_a_free_slot() {
+        let limiter = limiter(Some(1), None);
+
+        let first = limiter.try_acquire().expect("first call runs");
+        assert!(matches!(
+            limiter.try_acquire(),
+            Err(ToolCallQueueReason::MaxConcurrency)
+        ));
+        drop(first);
+        let _second = limiter.acquire().await;
+    }
+
+    #[test]
+    fn calls_over_the_rate_limit_wait_for_the_window() {
+        let limiter = limiter(None, Some(2));
+        let start = Instant::now();
+
+        assert_eq!(limiter.reserve_rate_slot(start), Ok(()));
+        assert_eq!(
+            limiter.reserve_rate_slot(start + Duration::from_secs(10)),
+            Ok(())
+        );
+        assert_eq!(
+            limiter.reserve_rate_slot(start + Duration::from_secs(20)),
+            Err(Duration::from_secs(40))
+        );
+        assert_eq!(
+            limiter.reserve_rate_slot(start + Duration::from_secs(60)),
+            Ok(())
+        );
+        assert!(matches!(
+            limiter.try_acquire(),
+            Err(ToolCallQueueReason::RateLimit)
+        ));
+    }
+}
@@ -1,4 +1,5 @@
 pub mod auth;
+pub(crate) mod call_limiter;
 mod skill_dependencies;
 pub(crate) use skill_dependencies::maybe_prompt_and_install_mcp_dependencies;
 
@@ -201,6 +202,8 @@ fn codex_apps_mcp_server_config(config: &Config, auth: Option<&CodexAuth>) -> Mc
         disabled_tools: None,
         scopes: None,
         oauth_resource: None,
+        max_concurrent_calls: None,
+        max_calls_per_minute: None,
     }
 }
 
@@ -783,6 +786,8 @@ mod tests {
                 disabled_tools: None,
                 scopes: None,
                 oauth_resource: None,
+                max_concurrent_calls: None,
+                max_calls_per_minute: None,
             },
         );
         config
@@ -394,6 +394,8 @@ fn mcp_dependency_to_server_config(
             disabled_tools: None,
             scopes: None,
             oauth_resource: None,
+            max_concurrent_calls: None,
+            max_calls_per_minute: None,
         });
     }
 
@@ -419,6 +421,8 @@ fn mcp_dependency_to_server_config(
             disabled_tools: None,
             scopes: None,
             oauth_resource: None,
+            max_concurrent_calls: None,
+            max_calls_per_minute: None,
         });
     }
 
@@ -476,6 +480,8 @@ mod tests {
                 disabled_tools: None,
                 scopes: None,
                 oauth_resource: None,
+                max_concurrent_calls: None,
+                max_calls_per_minute: None,
             },
         )]);
 
@@ -525,6 +531,8 @@ mod tests {
                 disabled_tools: None,
                 scopes: None,
                 oauth_resource: None,
+                max_concurrent_calls: None,
+                max_calls_per_minute: None,
             },
         )]);
 
@@ -22,6 +22,7 @@ use std::time::Instant;
 use crate::mcp::CODEX_APPS_MCP_SERVER_NAME;
 use crate::mcp::ToolPluginProvenance;
 use crate::mcp::auth::McpAuthStatusEntry;
+use crate::mcp::call_limiter::McpCallLimiter;
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
@@ -71,6 +72,7 @@ use serde::Serialize;
 use sha1::Digest;
 use sha1::Sha1;
 use tokio::sync::Mutex;
+use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::oneshot;
 use tokio::task::JoinSet;
 use tokio_util::sync::CancellationToken;
@@ -418,6 +420,9 @@ struct AsyncManagedClient {
     startup_snapshot: Option<Vec<ToolInfo>>,
     startup_complete: Arc<AtomicBool>,
     tool_plugin_provenance: Arc<ToolPluginProvenance>,
+    /// Throttles tool calls when the server sets `max_concurrent_calls` or
+    /// `max_calls_per_minute`.
+    call_limiter: Option<Arc<McpCallLimiter>>,
 }
 
 impl AsyncManagedClient {
@@ -435,6 +440,7 @@ impl AsyncManagedClient {
         tool_plugin_provenance: Arc<ToolPluginProvenance>,
     ) -> Self {
         let tool_filter = ToolFilter::from_config(&config);
+        let call_limiter = McpCallLimiter::from_config(&config).map(Arc::new);
         let startup_snapshot = load_startup_cached_codex_apps_tools_snapshot(
             &server_name,
             codex_apps_tools_cache_context.as_ref(),
@@ -490,6 +496,7 @@ impl AsyncManagedClient {
             startup_snapshot,
             startup_complete,
             tool_plugin_provenance,
+            call_limiter,
         }
     }
 
@@ -586,19 +593,27 @@ pub struct SandboxState {
     pub use_linux_sandbox_bwrap: bool,
 }
 
+/// Builds a new, not yet connected, client for one server.
+type ClientFactory = Arc<dyn Fn() -> AsyncManagedClient + Send + Sync>;
+
 /// A thin wrapper around a set of running [`RmcpClient`] instances.
 pub(crate) struct McpConnectionManager {
     clients: HashMap<String, AsyncManagedClient>,
+    client_factories: HashMap<String, ClientFactory>,
     server_origins: HashMap<String, String>,
     elicitation_requests: ElicitationRequestManager,
+    /// Last sandbox state sent to the servers, replayed to restarted ones.
+    sandbox_state: StdMutex<Option<SandboxState>>,
 }
 
 impl McpConnectionManager {
     pub(crate) fn new_uninitialized(approval_policy: &Constrained<AskForApproval>) -> Self {
         Self {
             clients: HashMap::new(),
+            client_factories: HashMap::new(),
             server_origins: HashMap::new(),
             elicitation_requests: ElicitationRequestManager::new(approval_policy.value()),
+            sandbox_state: StdMutex::new(None),
         }
     }
 
@@ -637,6 +652,7 @@ impl McpConnectionManager {
     ) -> (Self, CancellationToken) {
         let cancel_token = CancellationToken::new();
         let mut clients = HashMap::new();
+        let mut client_factories = HashMap::new();
         let mut server_origins = HashMap::new();
         let mut join_set = JoinSet::new();
         let elicitation_requests = ElicitationRequestManager::new(approval_policy.value());
@@ -663,17 +679,28 @@ impl McpConnectionManager {
             } else {
                 None
             };
-            let async_managed_client = AsyncManagedClient::new(
-                server_name.clone(),
-                cfg,
-                store_mode,
-                cancel_token.clone(),
-                tx_event.clone(),
-                elicitation_requests.clone(),
-                codex_apps_tools_cache_context,
-                Arc::clone(&tool_plugin_provenance),
-            );
+            let client_factory: ClientFactory = {
+                let server_name = server_name.clone();
+                let cancel_token = cancel_token.clone();
+                let tx_event = tx_event.clone();
+                let elicitation_requests = elicitation_requests.clone();
+                let tool_plugin_provenance = Arc::clone(&tool_plugin_provenance);
+                Arc::new(move || {
+                    AsyncManagedClient::new(
+                        server_name.clone(),
+                        cfg.clone(),
+                        store_mode,
+                        cancel_token.clone(),
+                        tx_event.clone(),
+                        elicitation_requests.clone(),
+                        codex_apps_tools_cache_context.clone(),
+                        Arc::clone(&tool_plugin_provenance),
+                    )
+                })
+            };
+            let async_managed_client = client_factory();
             clients.insert(server_name.clone(), async_managed_client.clone());
+            client_factories.insert(server_name.clone(), client_factory);
             let tx_event = tx_event.clone();
             let auth_entry = auth_entries.get(&server_name).cloned();
             let sandbox_state = initial_sandbox_state.clone();
@@ -719,8 +746,10 @@ impl McpConnectionManager {
         }
         let manager = Self {
             clients,
+            client_factories,
             server_origins,
             elicitation_requests: elicitation_requests.clone(),
+            sandbox_state: StdMutex::new(Some(initial_sandbox_state)),
         };
         tokio::spawn(async move {
             let outcomes = join_set.join_all().await;
@@ -747,6 +776,96 @@ impl McpConnectionManager {
         (manager, cancel_token)
     }
 
+    /// Drops the connection to `server_name` so that the next call to it
+    /// connects again. The old connection closes once calls already using it
+    /// finish. Returns whether a live connection was dropped; a server that is
+    /// still starting is left alone.
+    pub(crate) fn evict(&mut self, server_name: &str) -> bool {
+        let (Some(client), Some(client_factory)) = (
+            self.clients.get(server_name),
+            self.client_factories.get(server_name),
+        ) else {
+            return false;
+        };
+        let was_connected = match client.client.peek() {
+            None => return false,
+            Some(outcome) => outcome.is_ok(),
+        };
+        self.clients
+            .insert(server_name.to_string(), client_factory());
+        was_connected
+    }
+
+    /// Replaces the connection to `server_name` with a new one, whatever the
+    /// state of the old one, and returns a future that resolves once the new
+    /// connection has started up and listed its tools. Returns `None` for an
+    /// unknown server.
+    pub(crate) fn restart(
+        &mut self,
+        server_name: &str,
+    ) -> Option<BoxFuture<'static, Result<(), String>>> {
+        let client = self.client_factories.get(server_name)?();
+        self.clients.insert(server_name.to_string(), client.clone());
+        let sandbox_state = self
+            .sandbox_state
+            .lock()
+            .ok()
+            .and_then(|state| state.clone());
+        let server_name = server_name.to_string();
+        Some(
+            async move {
+                client
+                    .client()
+                    .await
+                    .map_err(startup_outcome_error_message)?;
+                if let Some(sandbox_state) = sandbox_state
+                    && let Err(e) = client.notify_sandbox_state_change(&sandbox_state).await
+                {
+                    warn!("Failed to notify sandbox state to MCP server {server_name}: {e:#}");
+                }
+                Ok(())
+            }
+            .boxed(),
+        )
+    }
+
+    /// Returns, for each server that started successfully, a future that
+    /// checks whether it still answers requests. The futures hold their own
+    /// handles to the connections, so they can be awaited without holding
+    /// the manager.
+    pub(crate) fn health_checks(
+        &self,
+        timeout: Duration,
+    ) -> Vec<(String, BoxFuture<'static, Result<(), String>>)> {
+        self.clients
+            .iter()
+            .filter_map(|(server_name, client)| {
+                let Some(Ok(managed_client)) = client.client.peek() else {
+                    return None;
+                };
+                let client = Arc::clone(&managed_client.client);
+                let check = async move {
+                    match tokio::time::timeout(timeout, client.list_tools(None, None)).await {
+                        Err(_) => Err(format!("no response within {timeout:?}")),
+                        Ok(Err(error)) if RmcpClient::is_transport_error(&error) => {
+                            Err(format!("{error:#}"))
+                        }
+                        // A server that answers, even with an error, is alive.
+                        Ok(_) => Ok(()),
+                    }
+                };
+                Some((server_name.clone(), check.boxed()))
+            })
+            .collect()
+    }
+
+    /// Limiter for calls to `server_name`, if it sets call limits.
+    pub(crate) fn call_limiter(&self, server_name: &str) -> Option<Arc<McpCallLimiter>> {
+        self.clients
+            .get(server_name)
+            .and_then(|client| client.call_limiter.clone())
+    }
+
     async fn client_by_name(&self, name: &str) -> Result<ManagedClient> {
         self.clients
             .get(name)
@@ -996,12 +1115,16 @@ impl McpConnectionManager {
         aggregated
     }
 
-    /// Invoke the tool indicated by the (server, tool) pair.
+    /// Invoke the tool indicated by the (server, tool) pair. `meta` is sent as
+    /// the request's `_meta`. With `partial_output`, the server is asked for
+    /// progress and the message of each progress notification is sent there.
     pub async fn call_tool(
         &self,
         server: &str,
         tool: &str,
         arguments: Option<serde_json::Value>,
+        meta: Option<serde_json::Map<String, serde_json::Value>>,
+        partial_output: Option<UnboundedSender<String>>,
     ) -> Result<CallToolResult> {
         let client = self.client_by_name(server).await?;
         if !client.tool_filter.allows(tool) {
@@ -1010,11 +1133,27 @@ impl McpConnectionManager {
             ));
         }
 
-        let result: rmcp::model::CallToolResult = client
-            .client
-            .call_tool(tool.to_string(), arguments, client.tool_timeout)
-            .await
-            .with_context(|| format!("tool call failed for `{server}/{tool}`"))?;
+        let result: rmcp::model::CallToolResult = match partial_output {
+            Some(partial_output) => {
+                client
+                    .client
+                    .call_tool_with_progress(
+                        tool.to_string(),
+                        arguments,
+                        meta,
+                        client.tool_timeout,
+                        partial_output,
+                    )
+                    .await
+            }
+            None => {
+                client
+                    .client
+                    .call_tool_with_meta(tool.to_string(), arguments, meta, client.tool_timeout)
+                    .await
+            }
+        }
+        .with_context(|| format!("tool call failed for `{server}/{tool}`"))?;
 
         let content = result
             .content
@@ -1082,14 +1221,40 @@ impl McpConnectionManager {
             .with_context(|| format!("resources/read failed for `{server}` ({uri})"))
     }
 
-    pub async fn parse_tool_name(&self, tool_name: &str) -> Option<(String, String)> {
-        self.list_all_tools()
-            .await
-            .get(tool_name)
-            .map(|tool| (tool.server_name.clone(), tool.tool_name.clone()))
+    /// Maps a qualified tool name back to its `(server, tool)` pair. Fails when
+    /// tools on more than one server qualify to the same name, since
+    /// [`Self::list_all_tools`] would otherwise keep an arbitrary one of them.
+    pub async fn parse_tool_name(
+        &self,
+        tool_name: &str,
+    ) -> Result<Option<(String, String)>, AmbiguousToolName> {
+        let mut candidates = Vec::new();
+        for managed_client in self.clients.values() {
+            let Some(server_tools) = managed_client.listed_tools().await else {
+                continue;
+            };
+            if let Some(tool) = qualify_tools(server_tools).remove(tool_name) {
+                candidates.push((tool.server_name, tool.tool_name));
+            }
+        }
+        if candidates.len() <= 1 {
+            return Ok(candidates.pop());
+        }
+        let mut servers = candidates
+            .into_iter()
+            .map(|(server, _)| server)
+            .collect::<Vec<_>>();
+        servers.sort();
+        Err(AmbiguousToolName {
+            tool_name: tool_name.to_string(),
+            servers,
+        })
     }
 
     pub async fn notify_sandbox_state_change(&self, sandbox_state: &SandboxState) -> Result<()> {
+        if let Ok(mut state) = self.sandbox_state.lock() {
+            *state = Some(sandbox_state.clone());
+        }
         let mut join_set = JoinSet::new();
 
         for async_managed_client in self.clients.values() {
@@ -1272,6 +1437,18 @@ fn resolve_bearer_token(
     }
 }
 
+/// A qualified tool name that more than one MCP server resolves to.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "tool `{tool_name}` is ambiguous: it is provided by MCP servers {}; re-issue the call with a name qualified for a single server",
+    .servers.join(", ")
+)]
+pub(crate) struct AmbiguousToolName {
+    pub(crate) tool_name: String,
+    /// Candidate servers, sorted by name.
+    pub(crate) servers: Vec<String>,
+}
+
 #[derive(Debug, Clone, thiserror::Error)]
 enum StartupOutcomeError {
     #[error("MCP startup cancelled")]
@@ -1305,28 +1482,15 @@ fn elicitation_capability_for_server(server_name: &str) -> Option<ElicitationCap
     }
 }
 
-async fn start_server_task(
-    server_name: String,
-    client: Arc<RmcpClient>,
-    params: StartServerTaskParams,
-) -> Result<ManagedClient, StartupOutcomeError> {
-    let StartServerTaskParams {
-        startup_timeout,
-        tool_timeout,
-        tool_filter,
-        tx_event,
-        elicitation_requests,
-        codex_apps_tools_cache_context,
-    } = params;
-    let elicitation = elicitation_capability_for_server(&server_name);
-    let params = InitializeRequestParams {
+fn initialize_params(server_name: &str) -> InitializeRequestParams {
+    InitializeRequestParams {
         meta: None,
         capabilities: ClientCapabilities {
             experimental: None,
             extensions: None,
             roots: None,
             sampling: None,
-            elicitation,
+            elicitation: elicitation_capability_for_server(server_name),
             tasks: None,
         },
         client_info: Implementation {
@@ -1338,7 +1502,78 @@ async fn start_server_task(
             website_url: None,
         },
         protocol_version: ProtocolVersion::V_2025_06_18,
-    };
+    }
+}
+
+/// Connects to `config` outside of the managed startup path and lists the
+/// tools its filter allows. Elicitation requests are declined, since there
+/// is no session to route them to.
+pub(crate) async fn list_server_tools(
+    server_name: &str,
+    config: &McpServerConfig,
+    store_mode: OAuthCredentialsStoreMode,
+) -> Result<Vec<Tool>> {
+    let filter = ToolFilter::from_config(config);
+    let tools = list_all_server_tools(server_name, config, store_mode)
+        .await?
+        .into_iter()
+        .filter(|tool| filter.allows(tool.name.as_ref()))
+        .collect();
+    Ok(tools)
+}
+
+/// Like [`list_server_tools`], but ignores the server's `enabled_tools` and
+/// `disabled_tools` filters.
+pub(crate) async fn list_all_server_tools(
+    server_name: &str,
+    config: &McpServerConfig,
+    store_mode: OAuthCredentialsStoreMode,
+) -> Result<Vec<Tool>> {
+    let startup_timeout = config.startup_timeout_sec.or(Some(DEFAULT_STARTUP_TIMEOUT));
+    let client = make_rmcp_client(server_name, config.transport.clone(), store_mode)
+        .await
+        .map_err(|err| anyhow!("failed to start MCP server {server_name}: {err}"))?;
+    let decline_elicitation: SendElicitation = Box::new(|_, _| {
+        async {
+            Ok(ElicitationResponse {
+                action: ElicitationAction::Decline,
+                content: None,
+                meta: None,
+            })
+        }
+        .boxed()
+    });
+    client
+        .initialize(
+            initialize_params(server_name),
+            startup_timeout,
+            decline_elicitation,
+        )
+        .await?;
+    let tools = client
+        .list_tools_with_connector_ids(None, startup_timeout)
+        .await?
+        .tools
+        .into_iter()
+        .map(|tool| tool.tool)
+        .collect();
+    Ok(tools)
+}
+
+async fn start_server_task(
+    server_name: String,
+    client: Arc<RmcpClient>,
+    params: StartServerTaskParams,
+) -> Result<ManagedClient, StartupOutcomeError> {
+    let StartServerTaskParams {
+        startup_timeout,
+        tool_timeout,
+        tool_filter,
+        tx_event,
+        elicitation_requests,
+        codex_apps_tools_cache_context,
+    } = params;
+    let params = initialize_params(&server_name);
 
     let send_elicitation = elicitation_requests.make_sender(server_name.clone(), tx_event);
 
@@ -2078,6 +2313,7 @@ mod tests {
                 startup_snapshot: Some(startup_tools),
                 startup_complete: Arc::new(std::sync::atomic::AtomicBool::new(false)),
                 tool_plugin_provenance: Arc::new(ToolPluginProvenance::default()),
+                call_limiter: None,
             },
         );
 
@@ -2089,6 +2325,53 @@ mod tests {
         assert_eq!(tool.tool_name, "calendar_create_event");
     }
 
+    #[tokio::test]
+    async fn parse_tool_name_reports_names_shared_across_servers() {
+        let approval_policy = Constrained::allow_any(AskForApproval::OnFailure);
+        let mut manager = McpConnectionManager::new_uninitialized(&approval_policy);
+        for (server_name, tools) in [
+            (
+                "alpha",
+                vec![
+                    create_test_tool("alpha", "beta__gamma"),
+                    create_test_tool("alpha", "delta"),
+                ],
+            ),
+            (
+                "alpha__beta",
+                vec![create_test_tool("alpha__beta", "gamma")],
+            ),
+        ] {
+            let pending_client =
+                futures::future::pending::<Result<ManagedClient, StartupOutcomeError>>()
+                    .boxed()
+                    .shared();
+            manager.clients.insert(
+                server_name.to_string(),
+                AsyncManagedClient {
+                    client: pending_client,
+                    startup_snapshot: Some(tools),
+                    startup_complete: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+                    tool_plugin_provenance: Arc::new(ToolPluginProvenance::default()),
+                    call_limiter: None,
+                },
+            );
+        }
+
+        assert_eq!(
+            manager.parse_tool_name("mcp__alpha__beta__gamma").await,
+            Err(AmbiguousToolName {
+                tool_name: "mcp__alpha__beta__gamma".to_string(),
+                servers: vec!["alpha".to_string(), "alpha__beta".to_string()],
+            })
+        );
+        assert_eq!(
+            manager.parse_tool_name("mcp__alpha__delta").await,
+            Ok(Some(("alpha".to_string(), "delta".to_string())))
+        );
+        assert_eq!(manager.parse_tool_name("shell").await, Ok(None));
+    }
+
     #[tokio::test]
     async fn list_all_tools_blocks_while_client_is_pending_without_startup_snapshot() {
         let pending_client =
@@ -2104,6 +2387,7 @@ mod tests {
                 startup_snapshot: None,
                 startup_complete: Arc::new(std::sync::atomic::AtomicBool::new(false)),
                 tool_plugin_provenance: Arc::new(ToolPluginProvenance::default()),
+                call_limiter: None,
             },
         );
 
@@ -2127,6 +2411,7 @@ mod tests {
                 startup_snapshot: Some(Vec::new()),
                 startup_complete: Arc::new(std::sync::atomic::AtomicBool::new(false)),
                 tool_plugin_provenance: Arc::new(ToolPluginProvenance::default()),
+                call_limiter: None,
             },
         );
 
@@ -2159,6 +2444,7 @@ mod tests {
                 startup_snapshot: Some(startup_tools),
                 startup_complete,
                 tool_plugin_provenance: Arc::new(ToolPluginProvenance::default()),
+                call_limiter: None,
             },
         );
 
@@ -2206,6 +2492,8 @@ mod tests {
                 disabled_tools: None,
                 scopes: None,
                 oauth_resource: None,
+                max_concurrent_calls: None,
+                max_calls_per_minute: None,
             },
             auth_status: McpAuthStatus::Unsupported,
         };
@@ -2254,6 +2542,8 @@ mod tests {
                 disabled_tools: None,
                 scopes: None,
                 oauth_resource: None,
+                max_concurrent_calls: None,
+                max_calls_per_minute: None,
             },
             auth_status: McpAuthStatus::Unsupported,
         };
@@ -2306,4 +2596,107 @@ mod tests {
 
         assert_eq!(transport_origin(&transport), Some("stdio".to_string()));
     }
+
+    #[tokio::test]
+    async fn evicted_server_reconnects_on_next_call() -> anyhow::Result<()> {
+        let stdio_server = codex_utils_cargo_bin::cargo_bin("test_stdio_server")?;
+        let codex_home = tempdir()?;
+        let (tx_event, _rx_event) = async_channel::unbounded();
+        let approval_policy = Constrained::allow_any(AskForApproval::OnFailure);
+        let (mut manager, _cancel_token) = McpConnectionManager::new(
+            &HashMap::from([(
+                "rmcp".to_string(),
+                serde_json::from_value(serde_json::json!({
+                    "command": stdio_server,
+                    "startup_timeout_sec": 10.0,
+                }))?,
+            )]),
+            OAuthCredentialsStoreMode::default(),
+            HashMap::new(),
+            &approval_policy,
+            tx_event,
+            SandboxState {
+                sandbox_policy: SandboxPolicy::new_read_only_policy(),
+                codex_linux_sandbox_exe: None,
+                sandbox_cwd: codex_home.path().to_path_buf(),
+                use_linux_sandbox_bwrap: false,
+            },
+            codex_home.path().to_path_buf(),
+            codex_apps_tools_cache_key(None),
+            ToolPluginProvenance::default(),
+        )
+        .await;
+        let echo_args = serde_json::json!({ "message": "ping" });
+
+        manager
+            .call_tool("rmcp", "echo", Some(echo_args.clone()), None, None)
+            .await?;
+        let first = manager.client_by_name("rmcp").await?.client;
+
+        assert!(manager.evict("rmcp"));
+        assert!(!manager.evict("unknown"));
+
+        manager
+            .call_tool("rmcp", "echo", Some(echo_args), None, None)
+            .await?;
+        let second = manager.client_by_name("rmcp").await?.client;
+        assert!(!Arc::ptr_eq(&first, &second));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn restarted_server_passes_health_check() -> anyhow::Result<()> {
+        let stdio_server = codex_utils_cargo_bin::cargo_bin("test_stdio_server")?;
+        let codex_home = tempdir()?;
+        let (tx_event, _rx_event) = async_channel::unbounded();
+        let approval_policy = Constrained::allow_any(AskForApproval::OnFailure);
+        let (mut manager, _cancel_token) = McpConnectionManager::new(
+            &HashMap::from([(
+                "rmcp".to_string(),
+                serde_json::from_value(serde_json::json!({
+                    "command": stdio_server,
+                    "startup_timeout_sec": 10.0,
+                }))?,
+            )]),
+            OAuthCredentialsStoreMode::default(),
+            HashMap::new(),
+            &approval_policy,
+            tx_event,
+            SandboxState {
+                sandbox_policy: SandboxPolicy::new_read_only_policy(),
+                codex_linux_sandbox_exe: None,
+                sandbox_cwd: codex_home.path().to_path_buf(),
+                use_linux_sandbox_bwrap: false,
+            },
+            codex_home.path().to_path_buf(),
+            codex_apps_tools_cache_key(None),
+            ToolPluginProvenance::default(),
+        )
+        .await;
+        assert!(
+            manager
+                .wait_for_server_ready("rmcp", Duration::from_secs(10))
+                .await
+        );
+        let first = manager.client_by_name("rmcp").await?.client;
+
+        let restart = manager.restart("rmcp").expect("known server");
+        assert!(manager.restart("unknown").is_none());
+        // A server that is still starting is not checked.
+        assert!(manager.health_checks(Duration::from_secs(10)).is_empty());
+        restart.await.map_err(anyhow::Error::msg)?;
+
+        let second = manager.client_by_name("rmcp").await?.client;
+        assert!(!Arc::ptr_eq(&first, &second));
+        let checks = manager.health_checks(Duration::from_secs(10));
+        assert_eq!(checks.len(), 1);
+        for (server_name, check) in checks {
+            assert_eq!(server_name, "rmcp");
+            check.await.map_err(anyhow::Error::msg)?;
+        }
+        assert!(!manager.list_all_tools().await.is_empty());
+
+        Ok(())
+    }
 }
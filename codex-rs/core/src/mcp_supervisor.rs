@@ -0,0 +1,172 @@
+//! Keeps a session's MCP servers connected after startup.
+//!
+//! The supervisor periodically checks every server that started, and
+//! restarts one that stopped answering, e.g. because its process crashed,
+//! with exponential backoff until it is back. A restart reruns startup,
+//! including tool discovery. Each change in a server's health is reported as
+//! an [`EventMsg::McpServerStatus`] so clients can show degraded servers.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::Mutex as StdMutex;
+use std::sync::Weak;
+use std::time::Duration;
+
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::McpServerHealth;
+use codex_protocol::protocol::McpServerStatusEvent;
+use futures::future::join_all;
+use tokio::sync::Notify;
+use tracing::warn;
+
+use crate::codex::INITIAL_SUBMIT_ID;
+use crate::codex::Session;
+use crate::tools::resilience::RetryPolicy;
+
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Reconnects are retried until the server is back or the session ends, so
+/// only the backoff applies.
+const RECONNECT_BACKOFF: RetryPolicy = RetryPolicy {
+    max_retries: u32::MAX,
+    initial_backoff: Duration::from_secs(1),
+    max_backoff: Duration::from_secs(60),
+};
+
+/// Handle to a session's supervisor.
+#[derive(Clone, Default)]
+pub(crate) struct McpSupervisor {
+    check_now: Arc<Notify>,
+    /// Servers with a reconnect in progress, which are not checked again
+    /// until it finishes.
+    reconnecting: Arc<StdMutex<HashSet<String>>>,
+}
+
+impl McpSupervisor {
+    /// Starts supervising `session`'s MCP servers. The supervisor stops once
+    /// the session is dropped.
+    pub(crate) fn spawn(&self, session: Weak<Session>) {
+        let supervisor = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(HEALTH_CHECK_INTERVAL) => {}
+                    _ = supervisor.check_now.notified() => {}
+                }
+                if !supervisor.check_servers(&session).await {
+                    return;
+                }
+            }
+        });
+    }
+
+    /// Checks the servers right away instead of at the next interval, e.g.
+    /// after a tool call could not reach its server.
+    pub(crate) fn request_check(&self) {
+        self.check_now.notify_one();
+    }
+
+    /// Returns `false` once the session is gone.
+    async fn check_servers(&self, session: &Weak<Session>) -> bool {
+        let Some(sess) = session.upgrade() else {
+            return false;
+        };
+        let checks = sess
+            .services
+            .mcp_connection_manager
+            .read()
+            .await
+            .health_checks(HEALTH_CHECK_TIMEOUT);
+        drop(sess);
+
+        let checks = checks
+            .into_iter()
+            .filter(|(server_name, _)| !self.is_reconnecting(server_name));
+        let results =
+            join_all(checks.map(|(server_name, check)| async move { (server_name, check.await) }))
+                .await;
+        for (server_name, result) in results {
+            let Err(error) = result else {
+                continue;
+            };
+            if let Ok(mut reconnecting) = self.reconnecting.lock()
+                && !reconnecting.insert(server_name.clone())
+            {
+                continue;
+            }
+            let supervisor = self.clone();
+            let session = session.clone();
+            tokio::spawn(async move {
+                supervisor.reconnect(&session, &server_name, error).await;
+                if let Ok(mut reconnecting) = supervisor.reconnecting.lock() {
+                    reconnecting.remove(&server_name);
+                }
+            });
+        }
+        true
+    }
+
+    fn is_reconnecting(&self, server_name: &str) -> bool {
+        self.reconnecting
+            .lock()
+            .is_ok_and(|reconnecting| reconnecting.contains(server_name))
+    }
+
+    async fn reconnect(&self, session: &Weak<Session>, server_name: &str, error: String) {
+        warn!("MCP server {server_name} stopped responding: {error}");
+        if let Some(sess) = session.upgrade() {
+            send_status(&sess, server_name, McpServerHealth::Degraded { error }).await;
+        }
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            tokio::time::sleep(RECONNECT_BACKOFF.backoff(attempt)).await;
+            let Some(sess) = session.upgrade() else {
+                return;
+            };
+            send_status(
+                &sess,
+                server_name,
+                McpServerHealth::Reconnecting { attempt },
+            )
+            .await;
+            let restart = sess
+                .services
+                .mcp_connection_manager
+                .write()
+                .await
+                .restart(server_name);
+            // The server was removed, e.g. by an MCP refresh.
+            let Some(restart) = restart else {
+                return;
+            };
+            drop(sess);
+
+            match restart.await {
+                Ok(()) => {
+                    if let Some(sess) = session.upgrade() {
+                        send_status(&sess, server_name, McpServerHealth::Healthy).await;
+                    }
+                    return;
+                }
+                Err(error) => {
+                    warn!(
+                        "failed to reconnect MCP server {server_name} (attempt {attempt}): {error}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+async fn send_status(sess: &Session, server_name: &str, status: McpServerHealth) {
+    sess.send_event_raw(Event {
+        id: INITIAL_SUBMIT_ID.to_owned(),
+        msg: EventMsg::McpServerStatus(McpServerStatusEvent {
+            server: server_name.to_string(),
+            status,
+        }),
+    })
+    .await;
+}
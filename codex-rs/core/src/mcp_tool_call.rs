@@ -11,11 +11,15 @@ use tracing::error;
 use crate::analytics_client::AppInvocation;
 use crate::analytics_client::InvocationType;
 use crate::analytics_client::build_track_events_context;
+use crate::approval_rules::ApprovalSubject;
+use crate::approval_rules::evaluate_approval_rules;
 use crate::codex::Session;
 use crate::codex::TurnContext;
 use crate::config::types::AppToolApproval;
+use crate::config::types::ApprovalRuleDecision;
 use crate::connectors;
 use crate::features::Feature;
+use crate::function_tool::FunctionCallError;
 use crate::guardian::GuardianReviewRequest;
 use crate::guardian::review_approval_request;
 use crate::guardian::routes_approval_to_guardian;
@@ -24,7 +28,10 @@ use crate::protocol::EventMsg;
 use crate::protocol::McpInvocation;
 use crate::protocol::McpToolCallBeginEvent;
 use crate::protocol::McpToolCallEndEvent;
+use crate::protocol::McpToolCallOutputDeltaEvent;
 use crate::state_db;
+use codex_hooks::HookEvent;
+use codex_hooks::HookEventToolDenied;
 use codex_protocol::mcp::CallToolResult;
 use codex_protocol::models::FunctionCallOutputBody;
 use codex_protocol::models::FunctionCallOutputPayload;
@@ -33,6 +40,7 @@ use codex_protocol::openai_models::InputModality;
 use codex_protocol::protocol::AskForApproval;
 use codex_protocol::protocol::ReviewDecision;
 use codex_protocol::protocol::SandboxPolicy;
+use codex_protocol::protocol::ToolCallQueuedEvent;
 use codex_protocol::request_user_input::RequestUserInputAnswer;
 use codex_protocol::request_user_input::RequestUserInputArgs;
 use codex_protocol::request_user_input::RequestUserInputQuestion;
@@ -40,12 +48,19 @@ use codex_protocol::request_user_input::RequestUserInputQuestionOption;
 use codex_protocol::request_user_input::RequestUserInputResponse;
 use codex_rmcp_client::ElicitationAction;
 use codex_rmcp_client::ElicitationResponse;
+use codex_rmcp_client::RmcpClient;
 use rmcp::model::ToolAnnotations;
 use serde::Serialize;
 use std::sync::Arc;
+use tokio::sync::mpsc;
 
 /// Handles the specified tool call dispatches the appropriate
 /// `McpToolCallBegin` and `McpToolCallEnd` events to the `Session`.
+///
+/// Returns [`FunctionCallError::McpTransport`] when the connection to the
+/// server fails during a call that did not need approval, so the router can
+/// retry it without asking the user again. Other failures are reported in the
+/// returned output.
 pub(crate) async fn handle_mcp_tool_call(
     sess: Arc<Session>,
     turn_context: &Arc<TurnContext>,
@@ -53,7 +68,7 @@ pub(crate) async fn handle_mcp_tool_call(
     server: String,
     tool_name: String,
     arguments: String,
-) -> ResponseInputItem {
+) -> Result<ResponseInputItem, FunctionCallError> {
     // Parse the `arguments` as JSON. An empty string is OK, but invalid JSON
     // is not.
     let arguments_value = if arguments.trim().is_empty() {
@@ -63,13 +78,14 @@ pub(crate) async fn handle_mcp_tool_call(
             Ok(value) => Some(value),
             Err(e) => {
                 error!("failed to parse tool call arguments: {e}");
-                return ResponseInputItem::FunctionCallOutput {
+                return Ok(ResponseInputItem::FunctionCallOutput {
                     call_id: call_id.clone(),
                     output: FunctionCallOutputPayload {
                         body: FunctionCallOutputBody::Text(format!("err: {e}")),
                         success: Some(false),
+                        error_code: None,
                     },
-                };
+                });
             }
         }
     };
@@ -100,20 +116,44 @@ pub(crate) async fn handle_mcp_tool_call(
         connectors::AppToolPolicy::default()
     };
 
-    if server == CODEX_APPS_MCP_SERVER_NAME && !app_tool_policy.enabled {
+    let approval_rule = evaluate_approval_rules(
+        &turn_context.config.approval_rules,
+        &ApprovalSubject::McpTool {
+            server: &server,
+            tool: &tool_name,
+        },
+    );
+    let blocked_reason = if server == CODEX_APPS_MCP_SERVER_NAME && !app_tool_policy.enabled {
+        Some("MCP tool call blocked by app configuration".to_string())
+    } else if let Some(rule) = approval_rule
+        && rule.decision == ApprovalRuleDecision::Deny
+    {
+        Some(format!("MCP tool call denied by {rule}"))
+    } else {
+        None
+    };
+    if let Some(blocked_reason) = blocked_reason {
+        run_tool_denied_hooks(
+            sess.as_ref(),
+            turn_context.as_ref(),
+            &call_id,
+            &tool_name,
+            &blocked_reason,
+        )
+        .await;
         let result = notify_mcp_tool_call_skip(
             sess.as_ref(),
             turn_context.as_ref(),
             &call_id,
             invocation,
-            "MCP tool call blocked by app configuration".to_string(),
+            blocked_reason,
         )
         .await;
         let status = if result.is_ok() { "ok" } else { "error" };
         turn_context
             .session_telemetry
             .counter("codex.mcp.call", 1, &[("status", status)]);
-        return ResponseInputItem::McpToolCallOutput { call_id, result };
+        return Ok(ResponseInputItem::McpToolCallOutput { call_id, result });
     }
 
     if let Some(decision) = maybe_request_mcp_tool_approval(
@@ -122,7 +162,11 @@ pub(crate) async fn handle_mcp_tool_call(
         &call_id,
         &invocation,
         metadata.as_ref(),
-        app_tool_policy.approval,
+        match approval_rule.map(|rule| rule.decision) {
+            Some(ApprovalRuleDecision::Approve) => AppToolApproval::Approve,
+            Some(ApprovalRuleDecision::Ask) => AppToolApproval::Prompt,
+            Some(ApprovalRuleDecision::Deny) | None => app_tool_policy.approval,
+        },
     )
     .await
     {
@@ -141,10 +185,16 @@ pub(crate) async fn handle_mcp_tool_call(
                 maybe_mark_thread_memory_mode_polluted(sess.as_ref(), turn_context.as_ref()).await;
 
                 let start = Instant::now();
-                let result = sess
-                    .call_tool(&server, &tool_name, arguments_value.clone())
-                    .await
-                    .map_err(|e| format!("tool call error: {e:?}"));
+                let result = call_mcp_tool_streaming(
+                    sess.as_ref(),
+                    turn_context.as_ref(),
+                    &call_id,
+                    &server,
+                    &tool_name,
+                    arguments_value.clone(),
+                )
+                .await
+                .map_err(|e| format!("tool call error: {e:?}"));
                 let result = sanitize_mcp_tool_result_for_model(
                     turn_context
                         .model_info
@@ -178,6 +228,14 @@ pub(crate) async fn handle_mcp_tool_call(
             }
             McpToolApprovalDecision::Decline => {
                 let message = "user rejected MCP tool call".to_string();
+                run_tool_denied_hooks(
+                    sess.as_ref(),
+                    turn_context.as_ref(),
+                    &call_id,
+                    &tool_name,
+                    &message,
+                )
+                .await;
                 notify_mcp_tool_call_skip(
                     sess.as_ref(),
                     turn_context.as_ref(),
@@ -205,7 +263,7 @@ pub(crate) async fn handle_mcp_tool_call(
             .session_telemetry
             .counter("codex.mcp.call", 1, &[("status", status)]);
 
-        return ResponseInputItem::McpToolCallOutput { call_id, result };
+        return Ok(ResponseInputItem::McpToolCallOutput { call_id, result });
     }
 
     let tool_call_begin_event = EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
@@ -217,10 +275,20 @@ pub(crate) async fn handle_mcp_tool_call(
 
     let start = Instant::now();
     // Perform the tool call.
-    let result = sess
-        .call_tool(&server, &tool_name, arguments_value.clone())
-        .await
-        .map_err(|e| format!("tool call error: {e:?}"));
+    let result = call_mcp_tool_streaming(
+        sess.as_ref(),
+        turn_context.as_ref(),
+        &call_id,
+        &server,
+        &tool_name,
+        arguments_value.clone(),
+    )
+    .await;
+    let transport_failed = result.as_ref().is_err_and(RmcpClient::is_transport_error);
+    if transport_failed {
+        sess.services.mcp_supervisor.request_check();
+    }
+    let result = result.map_err(|e| format!("tool call error: {e:?}"));
     let result = sanitize_mcp_tool_result_for_model(
         turn_context
             .model_info
@@ -251,7 +319,10 @@ pub(crate) async fn handle_mcp_tool_call(
         .session_telemetry
         .counter("codex.mcp.call", 1, &[("status", status)]);
 
-    ResponseInputItem::McpToolCallOutput { call_id, result }
+    match result {
+        Err(message) if transport_failed => Err(FunctionCallError::McpTransport(message)),
+        result => Ok(ResponseInputItem::McpToolCallOutput { call_id, result }),
+    }
 }
 
 async fn maybe_mark_thread_memory_mode_polluted(sess: &Session, turn_context: &TurnContext) {
@@ -301,6 +372,81 @@ fn sanitize_mcp_tool_result_for_model(
     })
 }
 
+/// Calls the tool while relaying any progress messages the server sends as
+/// `McpToolCallOutputDelta` events. When the server streamed output this way,
+/// the streamed text replaces the final content so the model sees the same
+/// output the user did; servers that do not stream keep their buffered result.
+async fn call_mcp_tool_streaming(
+    sess: &Session,
+    turn_context: &TurnContext,
+    call_id: &str,
+    server: &str,
+    tool_name: &str,
+    arguments: Option<serde_json::Value>,
+) -> anyhow::Result<CallToolResult> {
+    let _permit = match sess.mcp_call_limiter(server).await {
+        Some(limiter) => Some(match limiter.try_acquire() {
+            Ok(permit) => permit,
+            Err(reason) => {
+                let event = EventMsg::ToolCallQueued(ToolCallQueuedEvent {
+                    call_id: call_id.to_string(),
+                    server: server.to_string(),
+                    tool: tool_name.to_string(),
+                    reason,
+                });
+                notify_mcp_tool_call_event(sess, turn_context, event).await;
+                limiter.acquire().await
+            }
+        }),
+        None => None,
+    };
+
+    let (partial_tx, mut partial_rx) = mpsc::unbounded_channel();
+    let call = sess.call_tool(server, tool_name, arguments, Some(partial_tx));
+    tokio::pin!(call);
+
+    let mut streamed = String::new();
+    let result = loop {
+        tokio::select! {
+            result = &mut call => break result,
+            Some(delta) = partial_rx.recv() => {
+                relay_mcp_tool_output_delta(sess, turn_context, call_id, delta, &mut streamed)
+                    .await;
+            }
+        }
+    };
+    while let Ok(delta) = partial_rx.try_recv() {
+        relay_mcp_tool_output_delta(sess, turn_context, call_id, delta, &mut streamed).await;
+    }
+
+    let result = result?;
+    if streamed.is_empty() {
+        return Ok(result);
+    }
+    Ok(CallToolResult {
+        content: vec![serde_json::json!({
+            "type": "text",
+            "text": streamed,
+        })],
+        ..result
+    })
+}
+
+async fn relay_mcp_tool_output_delta(
+    sess: &Session,
+    turn_context: &TurnContext,
+    call_id: &str,
+    delta: String,
+    streamed: &mut String,
+) {
+    streamed.push_str(&delta);
+    let event = EventMsg::McpToolCallOutputDelta(McpToolCallOutputDeltaEvent {
+        call_id: call_id.to_string(),
+        delta,
+    });
+    notify_mcp_tool_call_event(sess, turn_context, event).await;
+}
+
 async fn notify_mcp_tool_call_event(sess: &Session, turn_context: &TurnContext, event: EventMsg) {
     sess.send_event(turn_context, event).await;
 }
@@ -1003,6 +1149,27 @@ async fn notify_mcp_tool_call_skip(
     Err(message)
 }
 
+async fn run_tool_denied_hooks(
+    sess: &Session,
+    turn_context: &TurnContext,
+    call_id: &str,
+    tool_name: &str,
+    reason: &str,
+) {
+    sess.run_lifecycle_hooks(
+        turn_context,
+        HookEvent::ToolDenied {
+            event: HookEventToolDenied {
+                turn_id: turn_context.sub_id.clone(),
+                call_id: call_id.to_string(),
+                tool_name: tool_name.to_string(),
+                reason: reason.to_string(),
+            },
+        },
+    )
+    .await;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,557 @@
+//! Facts the model saves about a project with the `memory_write` tool.
+//!
+//! Unlike the startup pipeline, which extracts memories from old rollouts in
+//! the background, these are written deliberately by the model and scoped to
+//! one project: the git root of the working directory, or the working
+//! directory itself outside a repository. Each project has a JSON file under
+//! `<codex_home>/project_memory/`. Facts expire after a TTL, the file holds a
+//! bounded number of facts of bounded size, and the most relevant ones are
+//! added to the developer instructions of new sessions in that project.
+
+use std::fs::File;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use chrono::Duration;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::git_info::resolve_root_git_project_for_trust;
+use crate::truncate::TruncationPolicy;
+use crate::truncate::truncate_text;
+
+const PROJECT_MEMORY_SUBDIR: &str = "project_memory";
+/// Longest fact accepted, in characters.
+pub(crate) const MAX_FACT_CHARS: usize = 2_000;
+/// Facts kept per project. Saving past this evicts the least relevant one.
+pub(crate) const MAX_FACTS_PER_PROJECT: usize = 200;
+pub(crate) const DEFAULT_TTL_DAYS: i64 = 90;
+pub(crate) const MAX_TTL_DAYS: i64 = 365;
+/// Facts included in a new session's developer instructions.
+const STARTUP_FACTS_LIMIT: usize = 10;
+const STARTUP_FACTS_TOKEN_LIMIT: usize = 2_000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum FactImportance {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+impl FactImportance {
+    fn weight(self) -> f64 {
+        match self {
+            Self::Low => 0.5,
+            Self::Normal => 1.0,
+            Self::High => 2.0,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::High => "high",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Fact {
+    pub(crate) id: String,
+    pub(crate) content: String,
+    #[serde(default)]
+    pub(crate) tags: Vec<String>,
+    #[serde(default)]
+    pub(crate) importance: FactImportance,
+    pub(crate) created_at: DateTime<Utc>,
+    pub(crate) updated_at: DateTime<Utc>,
+    pub(crate) expires_at: DateTime<Utc>,
+    #[serde(default)]
+    pub(crate) read_count: u64,
+    #[serde(default)]
+    pub(crate) last_read_at: Option<DateTime<Utc>>,
+}
+
+impl Fact {
+    /// Renders the fact as one list item for the model.
+    pub(crate) fn to_line(&self) -> String {
+        let mut details = vec![self.importance.as_str().to_string()];
+        if !self.tags.is_empty() {
+            details.push(format!("tags: {}", self.tags.join(", ")));
+        }
+        details.push(format!("expires {}", self.expires_at.format("%Y-%m-%d")));
+        format!("- [{}] ({}) {}", self.id, details.join("; "), self.content)
+    }
+
+    /// Higher is more relevant. Importance dominates; facts read or updated
+    /// recently and read often rank above stale ones.
+    fn relevance(&self, now: DateTime<Utc>) -> f64 {
+        let last_used = self
+            .last_read_at
+            .map_or(self.updated_at, |read| read.max(self.updated_at));
+        let idle_days = (now - last_used).num_days().max(0) as f64;
+        let recency = 1.0 / (1.0 + idle_days / 30.0);
+        let usage = (1.0 + self.read_count as f64).ln();
+        self.importance.weight() + recency + 0.25 * usage
+    }
+
+    /// Fraction of `terms` found in the content or tags.
+    fn match_ratio(&self, terms: &[String]) -> f64 {
+        if terms.is_empty() {
+            return 1.0;
+        }
+        let haystack = format!("{} {}", self.content, self.tags.join(" ")).to_lowercase();
+        let matched = terms
+            .iter()
+            .filter(|term| haystack.contains(term.as_str()))
+            .count();
+        matched as f64 / terms.len() as f64
+    }
+}
+
+/// A `memory_write` request.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FactWrite {
+    /// Replaces the fact with this id instead of adding one.
+    pub(crate) id: Option<String>,
+    pub(crate) content: String,
+    pub(crate) tags: Vec<String>,
+    pub(crate) importance: FactImportance,
+    pub(crate) ttl_days: Option<i64>,
+}
+
+/// Result of saving a fact.
+#[derive(Debug, PartialEq)]
+pub(crate) struct FactWritten {
+    pub(crate) id: String,
+    pub(crate) expires_at: DateTime<Utc>,
+    /// Fact dropped to stay within [`MAX_FACTS_PER_PROJECT`].
+    pub(crate) evicted: Option<Fact>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FactsFile {
+    project_root: PathBuf,
+    #[serde(default)]
+    facts: Vec<Fact>,
+}
+
+/// The unexpired facts of one project.
+#[derive(Debug)]
+pub(crate) struct ProjectFacts {
+    path: PathBuf,
+    file: FactsFile,
+}
+
+impl ProjectFacts {
+    /// Loads, modifies and saves the facts of the project containing `cwd`
+    /// while holding an advisory lock, so concurrent sessions do not lose
+    /// each other's writes.
+    pub(crate) fn update<T>(
+        codex_home: &Path,
+        cwd: &Path,
+        now: DateTime<Utc>,
+        apply: impl FnOnce(&mut Self) -> T,
+    ) -> std::io::Result<T> {
+        let (path, project_root) = store_path(codex_home, cwd);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let lock = File::create(path.with_extension("lock"))?;
+        lock.lock()?;
+        let mut facts = Self::load_from(path, project_root, now)?;
+        let result = apply(&mut facts);
+        facts.save()?;
+        Ok(result)
+    }
+
+    /// Loads the facts of the project containing `cwd` without locking.
+    pub(crate) fn load(codex_home: &Path, cwd: &Path, now: DateTime<Utc>) -> std::io::Result<Self> {
+        let (path, project_root) = store_path(codex_home, cwd);
+        Self::load_from(path, project_root, now)
+    }
+
+    fn load_from(
+        path: PathBuf,
+        project_root: PathBuf,
+        now: DateTime<Utc>,
+    ) -> std::io::Result<Self> {
+        let mut file = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str::<FactsFile>(&contents)
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => FactsFile::default(),
+            Err(err) => return Err(err),
+        };
+        file.project_root = project_root;
+        file.facts.retain(|fact| fact.expires_at > now);
+        Ok(Self { path, file })
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        if self.file.facts.is_empty() {
+            return match std::fs::remove_file(&self.path) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+                _ => Ok(()),
+            };
+        }
+        let contents = serde_json::to_vec_pretty(&self.file)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        // Write beside the store and rename so unlocked readers never see a
+        // partial file.
+        let tmp_path = self.path.with_extension("json.tmp");
+        let mut tmp = File::create(&tmp_path)?;
+        tmp.write_all(&contents)?;
+        tmp.sync_all()?;
+        std::fs::rename(tmp_path, &self.path)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.file.facts.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.file.facts.is_empty()
+    }
+
+    /// Adds a fact, or replaces the one with `write.id`.
+    pub(crate) fn write(
+        &mut self,
+        write: FactWrite,
+        now: DateTime<Utc>,
+    ) -> Result<FactWritten, String> {
+        let content = write.content.trim().to_string();
+        if content.is_empty() {
+            return Err("content must not be empty".to_string());
+        }
+        let chars = content.chars().count();
+        if chars > MAX_FACT_CHARS {
+            return Err(format!(
+                "content is {chars} characters; memories are limited to {MAX_FACT_CHARS}. Save a shorter summary instead."
+            ));
+        }
+        let ttl_days = write.ttl_days.unwrap_or(DEFAULT_TTL_DAYS);
+        if !(1..=MAX_TTL_DAYS).contains(&ttl_days) {
+            return Err(format!(
+                "ttl_days must be between 1 and {MAX_TTL_DAYS}, got {ttl_days}"
+            ));
+        }
+        let tags = write
+            .tags
+            .iter()
+            .map(|tag| tag.trim().to_lowercase())
+            .filter(|tag| !tag.is_empty())
+            .collect::<Vec<_>>();
+        let expires_at = now + Duration::days(ttl_days);
+
+        if let Some(id) = write.id {
+            let fact = self
+                .file
+                .facts
+                .iter_mut()
+                .find(|fact| fact.id == id)
+                .ok_or_else(|| format!("no memory with id `{id}`"))?;
+            fact.content = content;
+            fact.tags = tags;
+            fact.importance = write.importance;
+            fact.updated_at = now;
+            fact.expires_at = expires_at;
+            return Ok(FactWritten {
+                id,
+                expires_at,
+                evicted: None,
+            });
+        }
+
+        let evicted = if self.file.facts.len() >= MAX_FACTS_PER_PROJECT {
+            self.file
+                .facts
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.relevance(now).total_cmp(&b.relevance(now)))
+                .map(|(index, _)| index)
+                .map(|index| self.file.facts.remove(index))
+        } else {
+            None
+        };
+        let id = uuid::Uuid::new_v4().simple().to_string()[..8].to_string();
+        self.file.facts.push(Fact {
+            id: id.clone(),
+            content,
+            tags,
+            importance: write.importance,
+            created_at: now,
+            updated_at: now,
+            expires_at,
+            read_count: 0,
+            last_read_at: None,
+        });
+        Ok(FactWritten {
+            id,
+            expires_at,
+            evicted,
+        })
+    }
+
+    /// Removes the fact with `id`, returning whether it existed.
+    pub(crate) fn forget(&mut self, id: &str) -> bool {
+        let before = self.file.facts.len();
+        self.file.facts.retain(|fact| fact.id != id);
+        self.file.facts.len() != before
+    }
+
+    /// Returns up to `limit` facts matching any word of `query` (every fact
+    /// when there is none), best match first, and counts them as read.
+    pub(crate) fn read(
+        &mut self,
+        query: Option<&str>,
+        limit: usize,
+        now: DateTime<Utc>,
+    ) -> Vec<Fact> {
+        let terms = query
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+        let ids = self
+            .ranked(&terms, now)
+            .into_iter()
+            .take(limit)
+            .map(|fact| fact.id.clone())
+            .collect::<Vec<_>>();
+        ids.iter()
+            .filter_map(|id| {
+                let fact = self.file.facts.iter_mut().find(|fact| &fact.id == id)?;
+                fact.read_count += 1;
+                fact.last_read_at = Some(now);
+                Some(fact.clone())
+            })
+            .collect()
+    }
+
+    /// The facts worth putting in front of a new session: everything but
+    /// low-importance facts, most relevant first.
+    fn startup_facts(&self, now: DateTime<Utc>) -> Vec<&Fact> {
+        self.ranked(&[], now)
+            .into_iter()
+            .filter(|fact| fact.importance != FactImportance::Low)
+            .take(STARTUP_FACTS_LIMIT)
+            .collect()
+    }
+
+    fn ranked(&self, terms: &[String], now: DateTime<Utc>) -> Vec<&Fact> {
+        let mut scored = self
+            .file
+            .facts
+            .iter()
+            .map(|fact| (fact.match_ratio(terms) * fact.relevance(now), fact))
+            .filter(|(score, _)| *score > 0.0)
+            .collect::<Vec<_>>();
+        scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        scored.into_iter().map(|(_, fact)| fact).collect()
+    }
+}
+
+/// Builds the project memory section of a new session's developer
+/// instructions, or `None` when the project has no facts worth including.
+pub(crate) async fn build_project_memory_instructions(
+    codex_home: &Path,
+    cwd: &Path,
+) -> Option<String> {
+    let codex_home = codex_home.to_path_buf();
+    let cwd = cwd.to_path_buf();
+    let lines = tokio::task::spawn_blocking(move || {
+        let now = Utc::now();
+        let facts = ProjectFacts::load(&codex_home, &cwd, now).ok()?;
+        let lines = facts
+            .startup_facts(now)
+            .into_iter()
+            .map(Fact::to_line)
+            .collect::<Vec<_>>();
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    })
+    .await
+    .ok()??;
+    let lines = truncate_text(&lines, TruncationPolicy::Tokens(STARTUP_FACTS_TOKEN_LIMIT));
+    Some(format!(
+        "## Project memory\nFacts saved with `memory_write` in earlier sessions on this project, most relevant first. They may be out of date: check them before relying on them, and update or forget the ones that are wrong. Use `memory_read` to search for others.\n{lines}"
+    ))
+}
+
+/// The store file for the project containing `cwd`, and that project's root.
+fn store_path(codex_home: &Path, cwd: &Path) -> (PathBuf, PathBuf) {
+    let project_root = resolve_root_git_project_for_trust(cwd).unwrap_or_else(|| cwd.to_path_buf());
+    let mut hasher = Sha256::new();
+    hasher.update(project_root.to_string_lossy().as_bytes());
+    let hex = format!("{:x}", hasher.finalize());
+    let name = project_root
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "root".to_string());
+    let path = codex_home
+        .join(PROJECT_MEMORY_SUBDIR)
+        .join(format!("{name}-{}.json", &hex[..16]));
+    (path, project_root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn fact(content: &str, importance: FactImportance) -> FactWrite {
+        FactWrite {
+            content: content.to_string(),
+            importance,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn facts_persist_per_project_and_expire() {
+        let codex_home = tempfile::tempdir().expect("codex home");
+        let project = tempfile::tempdir().expect("project");
+        let other_project = tempfile::tempdir().expect("other project");
+        let now = Utc::now();
+
+        let written = ProjectFacts::update(codex_home.path(), project.path(), now, |facts| {
+            facts.write(
+                FactWrite {
+                    ttl_days: Some(1),
+                    ..fact(
+                        "integration tests need `just db-up` first",
+                        FactImportance::High,
+                    )
+                },
+                now,
+            )
+        })
+        .expect("update")
+        .expect("write");
+        assert_eq!(written.expires_at, now + Duration::days(1));
+
+        let loaded = ProjectFacts::load(codex_home.path(), project.path(), now).expect("load");
+        assert_eq!(loaded.len(), 1);
+        let other = ProjectFacts::load(codex_home.path(), other_project.path(), now).expect("load");
+        assert!(other.is_empty());
+
+        let later = now + Duration::days(2);
+        let expired = ProjectFacts::load(codex_home.path(), project.path(), later).expect("load");
+        assert!(expired.is_empty());
+    }
+
+    #[test]
+    fn write_validates_updates_and_evicts_least_relevant() {
+        let codex_home = tempfile::tempdir().expect("codex home");
+        let project = tempfile::tempdir().expect("project");
+        let now = Utc::now();
+        let mut facts = ProjectFacts::load(codex_home.path(), project.path(), now).expect("load");
+
+        assert!(
+            facts
+                .write(fact("  ", FactImportance::Normal), now)
+                .is_err()
+        );
+        assert!(
+            facts
+                .write(
+                    fact(&"x".repeat(MAX_FACT_CHARS + 1), FactImportance::Normal),
+                    now
+                )
+                .is_err()
+        );
+        assert!(
+            facts
+                .write(
+                    FactWrite {
+                        ttl_days: Some(MAX_TTL_DAYS + 1),
+                        ..fact("too long lived", FactImportance::Normal)
+                    },
+                    now
+                )
+                .is_err()
+        );
+
+        let low = facts
+            .write(fact("low priority", FactImportance::Low), now)
+            .expect("write");
+        for index in 1..MAX_FACTS_PER_PROJECT {
+            facts
+                .write(fact(&format!("fact {index}"), FactImportance::Normal), now)
+                .expect("write");
+        }
+        let written = facts
+            .write(fact("one too many", FactImportance::High), now)
+            .expect("write");
+        assert_eq!(facts.len(), MAX_FACTS_PER_PROJECT);
+        assert_eq!(
+            written.evicted.map(|evicted| evicted.id),
+            Some(low.id.clone())
+        );
+
+        facts
+            .write(
+                FactWrite {
+                    id: Some(written.id.clone()),
+                    ..fact("replaced", FactImportance::High)
+                },
+                now,
+            )
+            .expect("update");
+        assert_eq!(
+            facts
+                .read(Some("replaced"), 5, now)
+                .into_iter()
+                .map(|fact| fact.id)
+                .collect::<Vec<_>>(),
+            vec![written.id.clone()]
+        );
+        assert!(
+            facts
+                .write(
+                    FactWrite {
+                        id: Some(low.id.clone()),
+                        ..fact("gone", FactImportance::Normal)
+                    },
+                    now
+                )
+                .is_err()
+        );
+        assert!(facts.forget(&written.id));
+        assert!(!facts.forget(&written.id));
+    }
+
+    #[tokio::test]
+    async fn startup_instructions_include_relevant_facts_only() {
+        let codex_home = tempfile::tempdir().expect("codex home");
+        let project = tempfile::tempdir().expect("project");
+        assert_eq!(
+            build_project_memory_instructions(codex_home.path(), project.path()).await,
+            None
+        );
+
+        let now = Utc::now();
+        ProjectFacts::update(codex_home.path(), project.path(), now, |facts| {
+            facts
+                .write(fact("use pnpm, not npm", FactImportance::High), now)
+                .expect("write");
+            facts
+                .write(fact("the logo is blue", FactImportance::Low), now)
+                .expect("write");
+        })
+        .expect("update");
+
+        let instructions = build_project_memory_instructions(codex_home.path(), project.path())
+            .await
+            .expect("instructions");
+        assert!(instructions.contains("use pnpm, not npm"));
+        assert!(!instructions.contains("the logo is blue"));
+    }
+}
@@ -3,9 +3,12 @@
 //! The startup memory pipeline is split into two phases:
 //! - Phase 1: select rollouts, extract stage-1 raw memories, persist stage-1 outputs, and enqueue consolidation.
 //! - Phase 2: claim a global consolidation lock, materialize consolidation inputs, and dispatch one consolidation agent.
+//!
+//! Separately, [`facts`] stores per-project facts the model saves explicitly with `memory_write`.
 
 pub(crate) mod citations;
 mod control;
+pub(crate) mod facts;
 mod phase1;
 mod phase2;
 pub(crate) mod prompts;
@@ -21,6 +21,9 @@ use std::time::Duration;
 const DEFAULT_STREAM_IDLE_TIMEOUT_MS: u64 = 300_000;
 const DEFAULT_STREAM_MAX_RETRIES: u64 = 5;
 const DEFAULT_REQUEST_MAX_RETRIES: u64 = 4;
+const DEFAULT_RETRY_INITIAL_DELAY_MS: u64 = 200;
+const DEFAULT_RETRY_BACKOFF_MULTIPLIER: f64 = 2.0;
+const DEFAULT_CIRCUIT_BREAKER_COOLDOWN_SECS: u64 = 60;
 /// Hard cap for user-configured `stream_max_retries`.
 const MAX_STREAM_MAX_RETRIES: u64 = 100;
 /// Hard cap for user-configured `request_max_retries`.
@@ -101,6 +104,10 @@ pub struct ModelProviderInfo {
     /// the connection as lost.
     pub stream_idle_timeout_ms: Option<u64>,
 
+    /// How failed requests to this provider are retried. Unset fields fall
+    /// back to the top-level `model_retry` table.
+    pub retry: Option<ModelRetryConfig>,
+
     /// Does this provider require an OpenAI API Key or ChatGPT login token? If true,
     /// user is presented with login screen on first run, and login preference and token/key
     /// are stored in auth.json. If false (which is the default), login screen is skipped,
@@ -113,6 +120,53 @@ pub struct ModelProviderInfo {
     pub supports_websockets: bool,
 }
 
+/// How failed model requests are retried, and when a provider is considered
+/// unhealthy.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ModelRetryConfig {
+    /// Maximum number of times to retry a failed HTTP request. The provider's
+    /// `request_max_retries` takes precedence.
+    pub max_retries: Option<u64>,
+    /// Delay before the first retry, in milliseconds. Default: `200`.
+    pub initial_delay_ms: Option<u64>,
+    /// Factor each further retry's delay is multiplied by. Default: `2.0`.
+    pub backoff_multiplier: Option<f64>,
+    /// Longest delay between two attempts, in milliseconds. Default: no limit.
+    pub max_delay_ms: Option<u64>,
+    /// HTTP status codes a failed request is retried on. Default: any 5xx.
+    pub retry_status_codes: Option<Vec<u16>>,
+    /// Failed model requests in a row, retries included, after which the
+    /// provider is considered unhealthy: further requests fail at once, or go
+    /// to the next `provider_fallbacks` entry, until the cooldown passes.
+    /// Default: unset, which never gives up on the provider.
+    pub circuit_breaker_threshold: Option<u32>,
+    /// Seconds an unhealthy provider is skipped before a request is tried
+    /// again. Default: `60`.
+    pub circuit_breaker_cooldown_secs: Option<u64>,
+}
+
+impl ModelRetryConfig {
+    /// Fills fields left unset here from `defaults`.
+    pub fn or(self, defaults: &ModelRetryConfig) -> Self {
+        Self {
+            max_retries: self.max_retries.or(defaults.max_retries),
+            initial_delay_ms: self.initial_delay_ms.or(defaults.initial_delay_ms),
+            backoff_multiplier: self.backoff_multiplier.or(defaults.backoff_multiplier),
+            max_delay_ms: self.max_delay_ms.or(defaults.max_delay_ms),
+            retry_status_codes: self
+                .retry_status_codes
+                .or_else(|| defaults.retry_status_codes.clone()),
+            circuit_breaker_threshold: self
+                .circuit_breaker_threshold
+                .or(defaults.circuit_breaker_threshold),
+            circuit_breaker_cooldown_secs: self
+                .circuit_breaker_cooldown_secs
+                .or(defaults.circuit_breaker_cooldown_secs),
+        }
+    }
+}
+
 impl ModelProviderInfo {
     fn build_header_map(&self) -> crate::error::Result<HeaderMap> {
         let capacity = self.http_headers.as_ref().map_or(0, HashMap::len)
@@ -156,12 +210,16 @@ impl ModelProviderInfo {
             .unwrap_or_else(|| default_base_url.to_string());
 
         let headers = self.build_header_map()?;
+        let retry_config = self.retry.as_ref();
         let retry = ApiRetryConfig {
             max_attempts: self.request_max_retries(),
-            base_delay: Duration::from_millis(200),
+            base_delay: self.retry_initial_delay(),
             retry_429: false,
             retry_5xx: true,
             retry_transport: true,
+            backoff_multiplier: self.retry_backoff_multiplier(),
+            max_delay: self.retry_max_delay(),
+            retry_status_codes: retry_config.and_then(|retry| retry.retry_status_codes.clone()),
         };
 
         Ok(ApiProvider {
@@ -198,6 +256,7 @@ impl ModelProviderInfo {
     /// Effective maximum number of request retries for this provider.
     pub fn request_max_retries(&self) -> u64 {
         self.request_max_retries
+            .or_else(|| self.retry.as_ref().and_then(|retry| retry.max_retries))
             .unwrap_or(DEFAULT_REQUEST_MAX_RETRIES)
             .min(MAX_REQUEST_MAX_RETRIES)
     }
@@ -215,6 +274,52 @@ impl ModelProviderInfo {
             .map(Duration::from_millis)
             .unwrap_or(Duration::from_millis(DEFAULT_STREAM_IDLE_TIMEOUT_MS))
     }
+
+    /// Delay before the `attempt`-th retry (1-based) of a failed request.
+    pub fn retry_delay(&self, attempt: u64) -> Duration {
+        codex_client::exponential_backoff(
+            self.retry_initial_delay(),
+            self.retry_backoff_multiplier(),
+            self.retry_max_delay(),
+            attempt,
+        )
+    }
+
+    /// Consecutive failures that mark this provider unhealthy, and how long it
+    /// then stays unhealthy. `None` when no circuit breaker is configured.
+    pub fn circuit_breaker(&self) -> Option<(u32, Duration)> {
+        let retry = self.retry.as_ref()?;
+        let threshold = retry
+            .circuit_breaker_threshold
+            .filter(|threshold| *threshold > 0)?;
+        let cooldown = retry
+            .circuit_breaker_cooldown_secs
+            .unwrap_or(DEFAULT_CIRCUIT_BREAKER_COOLDOWN_SECS);
+        Some((threshold, Duration::from_secs(cooldown)))
+    }
+
+    fn retry_initial_delay(&self) -> Duration {
+        Duration::from_millis(
+            self.retry
+                .as_ref()
+                .and_then(|retry| retry.initial_delay_ms)
+                .unwrap_or(DEFAULT_RETRY_INITIAL_DELAY_MS),
+        )
+    }
+
+    fn retry_backoff_multiplier(&self) -> f64 {
+        self.retry
+            .as_ref()
+            .and_then(|retry| retry.backoff_multiplier)
+            .unwrap_or(DEFAULT_RETRY_BACKOFF_MULTIPLIER)
+    }
+
+    fn retry_max_delay(&self) -> Option<Duration> {
+        self.retry
+            .as_ref()
+            .and_then(|retry| retry.max_delay_ms)
+            .map(Duration::from_millis)
+    }
     pub fn create_openai_provider() -> ModelProviderInfo {
         ModelProviderInfo {
             name: OPENAI_PROVIDER_NAME.into(),
@@ -251,6 +356,7 @@ impl ModelProviderInfo {
             request_max_retries: None,
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
+            retry: None,
             requires_openai_auth: true,
             supports_websockets: true,
         }
@@ -324,6 +430,7 @@ pub fn create_oss_provider_with_base_url(base_url: &str, wire_api: WireApi) -> M
         request_max_retries: None,
         stream_max_retries: None,
         stream_idle_timeout_ms: None,
+        retry: None,
         requires_openai_auth: false,
         supports_websockets: false,
     }
@@ -334,6 +441,31 @@ mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn retry_delay_follows_the_configured_curve() {
+        let mut provider =
+            create_oss_provider_with_base_url("http://localhost:11434/v1", WireApi::Responses);
+        provider.retry = Some(ModelRetryConfig {
+            initial_delay_ms: Some(100),
+            backoff_multiplier: Some(3.0),
+            max_delay_ms: Some(500),
+            ..ModelRetryConfig::default()
+        });
+
+        let first = provider.retry_delay(1);
+        assert!(
+            (Duration::from_millis(90)..=Duration::from_millis(110)).contains(&first),
+            "{first:?}"
+        );
+        let second = provider.retry_delay(2);
+        assert!(
+            (Duration::from_millis(270)..=Duration::from_millis(330)).contains(&second),
+            "{second:?}"
+        );
+        assert_eq!(provider.retry_delay(4), Duration::from_millis(500));
+        assert_eq!(provider.circuit_breaker(), None);
+    }
+
     #[test]
     fn test_deserialize_ollama_model_provider_toml() {
         let azure_provider_toml = r#"
@@ -353,6 +485,7 @@ base_url = "http://localhost:11434/v1"
             request_max_retries: None,
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
+            retry: None,
             requires_openai_auth: false,
             supports_websockets: false,
         };
@@ -384,6 +517,7 @@ query_params = { api-version = "2025-04-01-preview" }
             request_max_retries: None,
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
+            retry: None,
             requires_openai_auth: false,
             supports_websockets: false,
         };
@@ -418,6 +552,7 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
             request_max_retries: None,
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
+            retry: None,
             requires_openai_auth: false,
             supports_websockets: false,
         };
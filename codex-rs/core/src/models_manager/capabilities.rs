@@ -0,0 +1,67 @@
+use codex_protocol::openai_models::InputModality;
+use codex_protocol::openai_models::ModelInfo;
+
+/// Features the active model supports, derived from its [`ModelInfo`].
+///
+/// Callers should consult this instead of reaching into individual
+/// `ModelInfo` fields so request shaping stays consistent across call sites.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModelCapabilities {
+    /// Every model served through the Responses API streams its output.
+    pub supports_streaming: bool,
+    pub supports_tool_use: bool,
+    pub supports_parallel_tool_calls: bool,
+    pub context_window: Option<i64>,
+    /// Whether the model accepts image inputs.
+    pub supports_vision: bool,
+}
+
+impl From<&ModelInfo> for ModelCapabilities {
+    fn from(model_info: &ModelInfo) -> Self {
+        Self {
+            supports_streaming: true,
+            supports_tool_use: model_info.supports_tool_calls,
+            supports_parallel_tool_calls: model_info.supports_tool_calls
+                && model_info.supports_parallel_tool_calls,
+            context_window: model_info.context_window,
+            supports_vision: model_info.input_modalities.contains(&InputModality::Image),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models_manager::model_info::model_info_from_slug;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn capabilities_mirror_model_info() {
+        let mut model_info = model_info_from_slug("unknown-model");
+        model_info.supports_parallel_tool_calls = true;
+        model_info.context_window = Some(128_000);
+        model_info.input_modalities = vec![InputModality::Text, InputModality::Image];
+
+        assert_eq!(
+            ModelCapabilities::from(&model_info),
+            ModelCapabilities {
+                supports_streaming: true,
+                supports_tool_use: true,
+                supports_parallel_tool_calls: true,
+                context_window: Some(128_000),
+                supports_vision: true,
+            }
+        );
+    }
+
+    #[test]
+    fn text_only_model_does_not_support_vision() {
+        let mut model_info = model_info_from_slug("unknown-model");
+        model_info.input_modalities = vec![InputModality::Text];
+
+        let capabilities = ModelCapabilities::from(&model_info);
+
+        assert!(!capabilities.supports_vision);
+        assert!(!capabilities.supports_parallel_tool_calls);
+    }
+}
@@ -0,0 +1,341 @@
+//! Capability probing for models served by a local inference server.
+//!
+//! The bundled catalog only describes OpenAI models, so a model served by
+//! Ollama, LM Studio, llama.cpp or vLLM otherwise runs with fallback metadata
+//! that assumes native tool calling and image input. Each of those servers
+//! reports what the loaded model can do through its own endpoint; the probe
+//! tries them in turn and keeps whatever the first one that answers reports.
+
+use std::time::Duration;
+
+use codex_protocol::openai_models::InputModality;
+use codex_protocol::openai_models::ModelInfo;
+use reqwest::Client;
+use serde_json::Value as JsonValue;
+use serde_json::json;
+use tracing::debug;
+
+use crate::model_provider_info::ModelProviderInfo;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// What a local server reported about one model. `None` means the server did
+/// not say, and the catalog or fallback metadata is kept for that field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct LocalModelCapabilities {
+    pub(crate) tool_calls: Option<bool>,
+    pub(crate) parallel_tool_calls: Option<bool>,
+    pub(crate) vision: Option<bool>,
+    pub(crate) context_window: Option<i64>,
+}
+
+impl LocalModelCapabilities {
+    pub(crate) fn apply_to(&self, model_info: &mut ModelInfo) {
+        if let Some(tool_calls) = self.tool_calls {
+            model_info.supports_tool_calls = tool_calls;
+        }
+        if let Some(parallel_tool_calls) = self.parallel_tool_calls {
+            model_info.supports_parallel_tool_calls = parallel_tool_calls;
+        }
+        if !model_info.supports_tool_calls {
+            model_info.supports_parallel_tool_calls = false;
+        }
+        match self.vision {
+            Some(true) if !model_info.input_modalities.contains(&InputModality::Image) => {
+                model_info.input_modalities.push(InputModality::Image);
+            }
+            Some(false) => model_info
+                .input_modalities
+                .retain(|modality| *modality != InputModality::Image),
+            _ => {}
+        }
+        if let Some(context_window) = self.context_window {
+            model_info.context_window = Some(context_window);
+        }
+    }
+}
+
+/// Returns the server root (the base URL without its `/v1` suffix) when the
+/// provider points at this machine. Remote providers are never probed.
+pub(crate) fn local_server_root(provider: &ModelProviderInfo) -> Option<String> {
+    let base_url = provider.base_url.as_deref()?;
+    let url = url::Url::parse(base_url).ok()?;
+    let is_local = match url.host()? {
+        url::Host::Domain(domain) => domain.eq_ignore_ascii_case("localhost"),
+        url::Host::Ipv4(addr) => addr.is_loopback() || addr.is_unspecified(),
+        url::Host::Ipv6(addr) => addr.is_loopback() || addr.is_unspecified(),
+    };
+    if !is_local {
+        return None;
+    }
+    let trimmed = base_url.trim_end_matches('/');
+    Some(
+        trimmed
+            .strip_suffix("/v1")
+            .unwrap_or(trimmed)
+            .trim_end_matches('/')
+            .to_string(),
+    )
+}
+
+/// Asks the local server behind `provider` what `model` supports. Returns
+/// `None` when the provider is not local or no known endpoint answered.
+pub(crate) async fn probe_local_model(
+    client: &Client,
+    provider: &ModelProviderInfo,
+    model: &str,
+) -> Option<LocalModelCapabilities> {
+    let root = local_server_root(provider)?;
+    let api_key = provider.api_key().ok().flatten();
+
+    if let Some(body) = fetch_json(
+        client
+            .post(format!("{root}/api/show"))
+            .json(&json!({ "model": model })),
+        api_key.as_deref(),
+    )
+    .await
+        && let Some(capabilities) = parse_ollama_show(&body)
+    {
+        return Some(capabilities);
+    }
+    if let Some(body) = fetch_json(
+        client.get(format!("{root}/api/v0/models/{model}")),
+        api_key.as_deref(),
+    )
+    .await
+        && let Some(capabilities) = parse_lmstudio_model(&body)
+    {
+        return Some(capabilities);
+    }
+    if let Some(body) = fetch_json(client.get(format!("{root}/props")), api_key.as_deref()).await
+        && let Some(capabilities) = parse_llama_cpp_props(&body)
+    {
+        return Some(capabilities);
+    }
+    if let Some(body) =
+        fetch_json(client.get(format!("{root}/v1/models")), api_key.as_deref()).await
+    {
+        return parse_openai_models(&body, model);
+    }
+    debug!("no local capability endpoint answered at {root}");
+    None
+}
+
+async fn fetch_json(request: reqwest::RequestBuilder, api_key: Option<&str>) -> Option<JsonValue> {
+    let request = match api_key {
+        Some(api_key) => request.bearer_auth(api_key),
+        None => request,
+    };
+    let response = request.timeout(PROBE_TIMEOUT).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    response.json::<JsonValue>().await.ok()
+}
+
+/// Ollama's `POST /api/show` lists `capabilities` such as `tools` and `vision`,
+/// and the context length under an architecture-prefixed `model_info` key.
+fn parse_ollama_show(body: &JsonValue) -> Option<LocalModelCapabilities> {
+    let capabilities = body.get("capabilities")?.as_array()?;
+    let has = |name: &str| {
+        capabilities
+            .iter()
+            .any(|value| value.as_str() == Some(name))
+    };
+    let context_window = body
+        .get("model_info")
+        .and_then(JsonValue::as_object)
+        .and_then(|info| {
+            info.iter()
+                .find(|(key, _)| key.ends_with(".context_length"))
+                .and_then(|(_, value)| value.as_i64())
+        });
+    Some(LocalModelCapabilities {
+        tool_calls: Some(has("tools")),
+        parallel_tool_calls: None,
+        vision: Some(has("vision")),
+        context_window,
+    })
+}
+
+/// LM Studio's `GET /api/v0/models/{model}` reports `tool_use` in
+/// `capabilities` and marks vision models with `type: "vlm"`.
+fn parse_lmstudio_model(body: &JsonValue) -> Option<LocalModelCapabilities> {
+    let model_type = body.get("type")?.as_str()?;
+    let tool_calls = body
+        .get("capabilities")
+        .and_then(JsonValue::as_array)
+        .map(|capabilities| {
+            capabilities
+                .iter()
+                .any(|value| value.as_str() == Some("tool_use"))
+        });
+    Some(LocalModelCapabilities {
+        tool_calls: Some(tool_calls.unwrap_or(false)),
+        parallel_tool_calls: None,
+        vision: Some(model_type == "vlm"),
+        context_window: body.get("max_context_length").and_then(JsonValue::as_i64),
+    })
+}
+
+/// llama.cpp's `GET /props` describes the loaded chat template's tool support
+/// and whether a multimodal projector is loaded.
+fn parse_llama_cpp_props(body: &JsonValue) -> Option<LocalModelCapabilities> {
+    let template_caps = body.get("chat_template_caps")?;
+    Some(LocalModelCapabilities {
+        tool_calls: template_caps
+            .get("supports_tools")
+            .and_then(JsonValue::as_bool),
+        parallel_tool_calls: template_caps
+            .get("supports_parallel_tool_calls")
+            .and_then(JsonValue::as_bool),
+        vision: body
+            .get("modalities")
+            .and_then(|modalities| modalities.get("vision"))
+            .and_then(JsonValue::as_bool),
+        context_window: body
+            .get("default_generation_settings")
+            .and_then(|settings| settings.get("n_ctx"))
+            .and_then(JsonValue::as_i64),
+    })
+}
+
+/// OpenAI-compatible servers such as vLLM only list models; vLLM adds the
+/// context length as `max_model_len`. Tool support is left to the catalog.
+fn parse_openai_models(body: &JsonValue, model: &str) -> Option<LocalModelCapabilities> {
+    let entry = body
+        .get("data")?
+        .as_array()?
+        .iter()
+        .find(|entry| entry.get("id").and_then(JsonValue::as_str) == Some(model))?;
+    Some(LocalModelCapabilities {
+        context_window: entry.get("max_model_len").and_then(JsonValue::as_i64),
+        ..LocalModelCapabilities::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_provider_info::WireApi;
+    use crate::model_provider_info::create_oss_provider_with_base_url;
+    use crate::models_manager::model_info::model_info_from_slug;
+    use pretty_assertions::assert_eq;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+
+    #[test]
+    fn only_loopback_providers_are_probed() {
+        let local =
+            create_oss_provider_with_base_url("http://localhost:11434/v1", WireApi::Responses);
+        let remote =
+            create_oss_provider_with_base_url("https://models.example.com/v1", WireApi::Responses);
+
+        assert_eq!(
+            local_server_root(&local),
+            Some("http://localhost:11434".to_string())
+        );
+        assert_eq!(local_server_root(&remote), None);
+    }
+
+    #[test]
+    fn parses_each_server_flavor() {
+        assert_eq!(
+            parse_ollama_show(&json!({
+                "capabilities": ["completion", "tools"],
+                "model_info": { "qwen3.context_length": 40960 },
+            })),
+            Some(LocalModelCapabilities {
+                tool_calls: Some(true),
+                parallel_tool_calls: None,
+                vision: Some(false),
+                context_window: Some(40_960),
+            })
+        );
+        assert_eq!(
+            parse_lmstudio_model(&json!({
+                "type": "vlm",
+                "max_context_length": 8192,
+            })),
+            Some(LocalModelCapabilities {
+                tool_calls: Some(false),
+                parallel_tool_calls: None,
+                vision: Some(true),
+                context_window: Some(8192),
+            })
+        );
+        assert_eq!(
+            parse_llama_cpp_props(&json!({
+                "chat_template_caps": {
+                    "supports_tools": true,
+                    "supports_parallel_tool_calls": false,
+                },
+                "modalities": { "vision": false },
+                "default_generation_settings": { "n_ctx": 4096 },
+            })),
+            Some(LocalModelCapabilities {
+                tool_calls: Some(true),
+                parallel_tool_calls: Some(false),
+                vision: Some(false),
+                context_window: Some(4096),
+            })
+        );
+        assert_eq!(
+            parse_openai_models(
+                &json!({ "data": [{ "id": "qwen", "max_model_len": 32768 }] }),
+                "qwen",
+            ),
+            Some(LocalModelCapabilities {
+                context_window: Some(32_768),
+                ..LocalModelCapabilities::default()
+            })
+        );
+    }
+
+    #[test]
+    fn models_without_tools_lose_parallel_calls_and_images() {
+        let mut model_info = model_info_from_slug("llama3");
+        model_info.supports_parallel_tool_calls = true;
+
+        LocalModelCapabilities {
+            tool_calls: Some(false),
+            parallel_tool_calls: None,
+            vision: Some(false),
+            context_window: Some(8192),
+        }
+        .apply_to(&mut model_info);
+
+        assert!(!model_info.supports_tool_calls);
+        assert!(!model_info.supports_parallel_tool_calls);
+        assert_eq!(model_info.input_modalities, vec![InputModality::Text]);
+        assert_eq!(model_info.context_window, Some(8192));
+    }
+
+    #[tokio::test]
+    async fn probe_falls_through_to_llama_cpp_props() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/props"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "chat_template_caps": { "supports_tools": false },
+            })))
+            .mount(&server)
+            .await;
+        let provider =
+            create_oss_provider_with_base_url(&format!("{}/v1", server.uri()), WireApi::Responses);
+
+        let capabilities = probe_local_model(&Client::new(), &provider, "local-model").await;
+
+        assert_eq!(
+            capabilities,
+            Some(LocalModelCapabilities {
+                tool_calls: Some(false),
+                ..LocalModelCapabilities::default()
+            })
+        );
+    }
+}
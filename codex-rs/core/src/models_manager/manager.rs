@@ -7,9 +7,12 @@ use crate::config::Config;
 use crate::default_client::build_reqwest_client;
 use crate::error::CodexErr;
 use crate::error::Result as CoreResult;
+use crate::features::Feature;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::models_manager::collaboration_mode_presets::CollaborationModesConfig;
 use crate::models_manager::collaboration_mode_presets::builtin_collaboration_mode_presets;
+use crate::models_manager::local_probe;
+use crate::models_manager::local_probe::LocalModelCapabilities;
 use crate::models_manager::model_info;
 use codex_api::ModelsClient;
 use codex_api::ReqwestTransport;
@@ -18,6 +21,7 @@ use codex_protocol::openai_models::ModelInfo;
 use codex_protocol::openai_models::ModelPreset;
 use codex_protocol::openai_models::ModelsResponse;
 use http::HeaderMap;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
@@ -61,6 +65,8 @@ pub struct ModelsManager {
     etag: RwLock<Option<String>>,
     cache_manager: ModelsCacheManager,
     provider: ModelProviderInfo,
+    /// Probe results for models served locally, keyed by server root and model.
+    local_capabilities: RwLock<HashMap<String, Option<LocalModelCapabilities>>>,
 }
 
 impl ModelsManager {
@@ -96,6 +102,7 @@ impl ModelsManager {
             etag: RwLock::new(None),
             cache_manager,
             provider: ModelProviderInfo::create_openai_provider(),
+            local_capabilities: RwLock::new(HashMap::new()),
         }
     }
 
@@ -162,7 +169,39 @@ impl ModelsManager {
     /// Look up model metadata, applying remote overrides and config adjustments.
     pub async fn get_model_info(&self, model: &str, config: &Config) -> ModelInfo {
         let remote_models = self.get_remote_models().await;
-        Self::construct_model_info_from_candidates(model, &remote_models, config)
+        let local_capabilities = self.local_model_capabilities(model, config).await;
+        Self::construct_model_info_from_candidates(
+            model,
+            &remote_models,
+            local_capabilities.as_ref(),
+            config,
+        )
+    }
+
+    /// Ask the local server behind the configured provider what `model`
+    /// supports. Each server and model is probed once per manager.
+    async fn local_model_capabilities(
+        &self,
+        model: &str,
+        config: &Config,
+    ) -> Option<LocalModelCapabilities> {
+        if !config.features.enabled(Feature::LocalModelProbing) {
+            return None;
+        }
+        let root = local_probe::local_server_root(&config.model_provider)?;
+        let key = format!("{root}#{model}");
+        if let Some(cached) = self.local_capabilities.read().await.get(&key) {
+            return cached.clone();
+        }
+        let probed =
+            local_probe::probe_local_model(&build_reqwest_client(), &config.model_provider, model)
+                .await;
+        info!("local model capabilities for {model}: {probed:?}");
+        self.local_capabilities
+            .write()
+            .await
+            .insert(key, probed.clone());
+        probed
     }
 
     fn find_model_by_longest_prefix(model: &str, candidates: &[ModelInfo]) -> Option<ModelInfo> {
@@ -204,13 +243,14 @@ impl ModelsManager {
     fn construct_model_info_from_candidates(
         model: &str,
         candidates: &[ModelInfo],
+        local_capabilities: Option<&LocalModelCapabilities>,
         config: &Config,
     ) -> ModelInfo {
         // First use the normal longest-prefix match. If that misses, allow a narrowly scoped
         // retry for namespaced slugs like `custom/gpt-5.3-codex`.
         let remote = Self::find_model_by_longest_prefix(model, candidates)
             .or_else(|| Self::find_model_by_namespaced_suffix(model, candidates));
-        let model_info = if let Some(remote) = remote {
+        let mut model_info = if let Some(remote) = remote {
             ModelInfo {
                 slug: model.to_string(),
                 used_fallback_model_metadata: false,
@@ -219,6 +259,9 @@ impl ModelsManager {
         } else {
             model_info::model_info_from_slug(model)
         };
+        if let Some(local_capabilities) = local_capabilities {
+            local_capabilities.apply_to(&mut model_info);
+        }
         model_info::with_config_overrides(model_info, config)
     }
 
@@ -394,6 +437,7 @@ impl ModelsManager {
             etag: RwLock::new(None),
             cache_manager,
             provider,
+            local_capabilities: RwLock::new(HashMap::new()),
         }
     }
 
@@ -423,7 +467,7 @@ impl ModelsManager {
         } else {
             &[]
         };
-        Self::construct_model_info_from_candidates(model, candidates, config)
+        Self::construct_model_info_from_candidates(model, candidates, None, config)
     }
 }
 
@@ -502,6 +546,7 @@ mod tests {
             request_max_retries: Some(0),
             stream_max_retries: Some(0),
             stream_idle_timeout_ms: Some(5_000),
+            retry: None,
             requires_openai_auth: false,
             supports_websockets: false,
         }
@@ -1,8 +1,12 @@
 pub mod cache;
+pub mod capabilities;
 pub mod collaboration_mode_presets;
+pub(crate) mod local_probe;
 pub mod manager;
 pub mod model_info;
 pub mod model_presets;
+pub(crate) mod pricing;
+pub(crate) mod provider_chain;
 
 /// Convert the client version string to a whole version string (e.g. "1.2.3-alpha.4" -> "1.2.3").
 pub fn client_version_to_whole() -> String {
@@ -82,6 +82,7 @@ pub(crate) fn model_info_from_slug(slug: &str) -> ModelInfo {
         web_search_tool_type: WebSearchToolType::Text,
         truncation_policy: TruncationPolicyConfig::bytes(10_000),
         supports_parallel_tool_calls: false,
+        supports_tool_calls: true,
         supports_image_detail_original: false,
         context_window: Some(272_000),
         auto_compact_token_limit: None,
@@ -0,0 +1,221 @@
+//! Estimating what a session spends on model requests.
+//!
+//! Costs are estimates: token counts reported by the provider multiplied by
+//! per-model prices from `model_pricing` in config or the built-in table
+//! below. Models with no known price are still tracked, without a cost.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+use codex_protocol::protocol::CostUpdateEvent;
+use codex_protocol::protocol::ModelCost;
+use codex_protocol::protocol::TokenUsage;
+
+use crate::config::types::ModelPricing;
+
+const TOKENS_PER_PRICE_UNIT: f64 = 1_000_000.0;
+
+/// USD per million input, cached input and output tokens.
+const BUILT_IN_PRICING: &[(&str, f64, f64, f64)] = &[
+    ("gpt-5", 1.25, 0.125, 10.0),
+    ("gpt-5-codex", 1.25, 0.125, 10.0),
+    ("gpt-5-codex-mini", 0.25, 0.025, 2.0),
+    ("gpt-5-mini", 0.25, 0.025, 2.0),
+    ("gpt-5-nano", 0.05, 0.005, 0.4),
+    ("gpt-5.1", 1.25, 0.125, 10.0),
+    ("gpt-5.1-codex", 1.25, 0.125, 10.0),
+    ("gpt-5.1-codex-max", 1.25, 0.125, 10.0),
+    ("gpt-5.1-codex-mini", 0.25, 0.025, 2.0),
+    ("gpt-5.2", 1.75, 0.175, 14.0),
+    ("gpt-5.2-codex", 1.75, 0.175, 14.0),
+    ("gpt-4.1", 2.0, 0.5, 8.0),
+    ("gpt-4.1-mini", 0.4, 0.1, 1.6),
+    ("o3", 2.0, 0.5, 8.0),
+    ("o4-mini", 1.1, 0.275, 4.4),
+    ("codex-mini-latest", 1.5, 0.375, 6.0),
+];
+
+/// Price for `model`, preferring `overrides` over the built-in table. Dated
+/// snapshots such as `gpt-4.1-2025-04-14` use the price of their base model.
+pub(crate) fn pricing_for_model(
+    overrides: &HashMap<String, ModelPricing>,
+    model: &str,
+) -> Option<ModelPricing> {
+    if let Some(pricing) = overrides.get(model) {
+        return Some(*pricing);
+    }
+    BUILT_IN_PRICING
+        .iter()
+        .find(|(slug, ..)| {
+            model == *slug
+                || model
+                    .strip_prefix(slug)
+                    .is_some_and(|rest| rest.starts_with("-20"))
+        })
+        .map(|&(_, input, cached_input, output)| ModelPricing {
+            input,
+            cached_input: Some(cached_input),
+            output,
+        })
+}
+
+/// Estimated USD cost of `usage`. Input tokens include cached ones and
+/// output tokens include reasoning ones, as reported by the Responses API.
+fn estimate_cost_usd(pricing: &ModelPricing, usage: &TokenUsage) -> f64 {
+    let cached_input_tokens = usage.cached_input_tokens.max(0);
+    let uncached_input_tokens = usage
+        .input_tokens
+        .saturating_sub(cached_input_tokens)
+        .max(0);
+    let cached_input_price = pricing.cached_input.unwrap_or(pricing.input);
+    (uncached_input_tokens as f64 * pricing.input
+        + cached_input_tokens as f64 * cached_input_price
+        + usage.output_tokens.max(0) as f64 * pricing.output)
+        / TOKENS_PER_PRICE_UNIT
+}
+
+/// Accumulates usage and estimated cost per provider and model for a
+/// session and its current turn.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CostTracker {
+    turn_cost_usd: f64,
+    models: BTreeMap<(String, String), ModelCost>,
+}
+
+impl CostTracker {
+    /// Restores the totals reported by an earlier [`CostUpdateEvent`], e.g.
+    /// when resuming a thread.
+    pub(crate) fn from_summary(summary: &CostUpdateEvent) -> Self {
+        Self {
+            turn_cost_usd: 0.0,
+            models: summary
+                .models
+                .iter()
+                .map(|cost| ((cost.provider.clone(), cost.model.clone()), cost.clone()))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn start_turn(&mut self) {
+        self.turn_cost_usd = 0.0;
+    }
+
+    /// Adds the usage of one model response and returns its estimated cost,
+    /// or `None` when `pricing` is unknown.
+    pub(crate) fn record(
+        &mut self,
+        provider: &str,
+        model: &str,
+        usage: &TokenUsage,
+        pricing: Option<&ModelPricing>,
+    ) -> Option<f64> {
+        let cost_usd = pricing.map(|pricing| estimate_cost_usd(pricing, usage));
+        let entry = self
+            .models
+            .entry((provider.to_string(), model.to_string()))
+            .or_insert_with(|| ModelCost {
+                provider: provider.to_string(),
+                model: model.to_string(),
+                input_tokens: 0,
+                cached_input_tokens: 0,
+                output_tokens: 0,
+                cost_usd: None,
+            });
+        entry.input_tokens += usage.input_tokens;
+        entry.cached_input_tokens += usage.cached_input_tokens;
+        entry.output_tokens += usage.output_tokens;
+        if let Some(cost_usd) = cost_usd {
+            *entry.cost_usd.get_or_insert(0.0) += cost_usd;
+            self.turn_cost_usd += cost_usd;
+        }
+        cost_usd
+    }
+
+    pub(crate) fn summary(&self) -> CostUpdateEvent {
+        CostUpdateEvent {
+            turn_cost_usd: self.turn_cost_usd,
+            session_cost_usd: self.models.values().filter_map(|cost| cost.cost_usd).sum(),
+            models: self.models.values().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn usage(input_tokens: i64, cached_input_tokens: i64, output_tokens: i64) -> TokenUsage {
+        TokenUsage {
+            input_tokens,
+            cached_input_tokens,
+            output_tokens,
+            reasoning_output_tokens: 0,
+            total_tokens: input_tokens + output_tokens,
+        }
+    }
+
+    #[test]
+    fn overrides_take_precedence_and_snapshots_match_their_base_model() {
+        let custom = ModelPricing {
+            input: 3.0,
+            cached_input: None,
+            output: 15.0,
+        };
+        let overrides = HashMap::from([("gpt-5".to_string(), custom)]);
+
+        assert_eq!(pricing_for_model(&overrides, "gpt-5"), Some(custom));
+        assert_eq!(
+            pricing_for_model(&HashMap::new(), "gpt-4.1-2025-04-14"),
+            pricing_for_model(&HashMap::new(), "gpt-4.1")
+        );
+        assert_eq!(pricing_for_model(&HashMap::new(), "gpt-4.1-nano"), None);
+        assert_eq!(pricing_for_model(&HashMap::new(), "my-local-model"), None);
+    }
+
+    #[test]
+    fn tracks_turn_and_session_cost_per_model() {
+        let pricing = ModelPricing {
+            input: 2.0,
+            cached_input: Some(0.5),
+            output: 8.0,
+        };
+        let mut tracker = CostTracker::default();
+        tracker.record("openai", "o3", &usage(1_000_000, 0, 0), Some(&pricing));
+        tracker.start_turn();
+        let cost = tracker.record(
+            "openai",
+            "o3",
+            &usage(1_000_000, 500_000, 250_000),
+            Some(&pricing),
+        );
+        tracker.record("ollama", "llama", &usage(10, 0, 5), None);
+
+        assert_eq!(cost, Some(1.0 + 0.25 + 2.0));
+        assert_eq!(
+            tracker.summary(),
+            CostUpdateEvent {
+                turn_cost_usd: 3.25,
+                session_cost_usd: 5.25,
+                models: vec![
+                    ModelCost {
+                        provider: "ollama".to_string(),
+                        model: "llama".to_string(),
+                        input_tokens: 10,
+                        cached_input_tokens: 0,
+                        output_tokens: 5,
+                        cost_usd: None,
+                    },
+                    ModelCost {
+                        provider: "openai".to_string(),
+                        model: "o3".to_string(),
+                        input_tokens: 2_000_000,
+                        cached_input_tokens: 500_000,
+                        output_tokens: 250_000,
+                        cost_usd: Some(5.25),
+                    },
+                ],
+            }
+        );
+    }
+}
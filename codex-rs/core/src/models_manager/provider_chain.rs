@@ -0,0 +1,100 @@
+//! Fallback providers for a turn whose model provider becomes unavailable.
+//!
+//! The chain is configured with `provider_fallbacks`. Once the current
+//! provider's own retries run out on a 429, a 5xx or a dropped connection,
+//! the turn moves on to the next provider in the chain and stays there.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use tracing::warn;
+
+use crate::config::Config;
+use crate::config::types::ProviderFallbackConfig;
+use crate::model_provider_info::ModelProviderInfo;
+
+/// A provider the turn can fall back to.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ProviderFallback {
+    pub(crate) provider_id: String,
+    pub(crate) provider: ModelProviderInfo,
+    /// Model to request instead of the turn's current one.
+    pub(crate) model: Option<String>,
+}
+
+/// The fallbacks a turn has not tried yet, in order.
+#[derive(Debug, Default)]
+pub(crate) struct ProviderChain {
+    fallbacks: VecDeque<ProviderFallback>,
+}
+
+impl ProviderChain {
+    pub(crate) fn from_config(config: &Config) -> Self {
+        Self::new(&config.provider_fallbacks, &config.model_providers)
+    }
+
+    /// Fallbacks naming a provider missing from `providers` are skipped.
+    fn new(
+        fallbacks: &[ProviderFallbackConfig],
+        providers: &HashMap<String, ModelProviderInfo>,
+    ) -> Self {
+        let fallbacks = fallbacks
+            .iter()
+            .filter_map(|fallback| {
+                let Some(provider) = providers.get(&fallback.model_provider) else {
+                    warn!(
+                        "ignoring provider fallback to unknown model provider {}",
+                        fallback.model_provider
+                    );
+                    return None;
+                };
+                Some(ProviderFallback {
+                    provider_id: fallback.model_provider.clone(),
+                    provider: provider.clone(),
+                    model: fallback.model.clone(),
+                })
+            })
+            .collect();
+        Self { fallbacks }
+    }
+
+    /// Takes the next provider to fall back to, if any are left.
+    pub(crate) fn next_fallback(&mut self) -> Option<ProviderFallback> {
+        self.fallbacks.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_provider_info::built_in_model_providers;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn chain_yields_known_providers_in_order() {
+        let providers = built_in_model_providers();
+        let fallbacks = [
+            ProviderFallbackConfig {
+                model_provider: "ollama".to_string(),
+                model: Some("gpt-oss:20b".to_string()),
+            },
+            ProviderFallbackConfig {
+                model_provider: "missing".to_string(),
+                model: None,
+            },
+            ProviderFallbackConfig {
+                model_provider: "openai".to_string(),
+                model: None,
+            },
+        ];
+        let mut chain = ProviderChain::new(&fallbacks, &providers);
+
+        let first = chain.next_fallback().expect("first fallback");
+        assert_eq!(first.provider_id, "ollama");
+        assert_eq!(first.model.as_deref(), Some("gpt-oss:20b"));
+        let second = chain.next_fallback().expect("second fallback");
+        assert_eq!(second.provider_id, "openai");
+        assert_eq!(second.provider, providers["openai"]);
+        assert_eq!(chain.next_fallback(), None);
+    }
+}
@@ -441,8 +441,17 @@ fn plugins_feature_enabled_from_stack(config_layer_stack: &ConfigLayerStack) ->
     let config_profile = config_toml
         .get_config_profile(config_toml.profile.clone())
         .unwrap_or_else(|_| ConfigProfile::default());
-    let features =
-        Features::from_config(&config_toml, &config_profile, FeatureOverrides::default());
+    let feature_defaults = config_layer_stack
+        .requirements()
+        .feature_defaults
+        .as_ref()
+        .map(|defaults| &defaults.value.entries);
+    let features = Features::from_config(
+        &config_toml,
+        &config_profile,
+        FeatureOverrides::default(),
+        feature_defaults,
+    );
     features.enabled(Feature::Plugins)
 }
 
@@ -976,6 +985,8 @@ mod tests {
                         disabled_tools: None,
                         scopes: None,
                         oauth_resource: None,
+                        max_concurrent_calls: None,
+                        max_calls_per_minute: None,
                     },
                 )]),
                 apps: vec![AppConnectorId("connector_example".to_string())],
@@ -1103,6 +1114,8 @@ mod tests {
                     disabled_tools: None,
                     scopes: None,
                     oauth_resource: None,
+                    max_concurrent_calls: None,
+                    max_calls_per_minute: None,
                 },
             )])
         );
@@ -1206,6 +1219,8 @@ mod tests {
                     disabled_tools: None,
                     scopes: None,
                     oauth_resource: None,
+                    max_concurrent_calls: None,
+                    max_calls_per_minute: None,
                 },
             )])
         );
@@ -1353,6 +1368,8 @@ mod tests {
             disabled_tools: None,
             scopes: None,
             oauth_resource: None,
+            max_concurrent_calls: None,
+            max_calls_per_minute: None,
         };
         let plugin = |config_name: &str, dir_name: &str, manifest_name: &str| LoadedPlugin {
             config_name: config_name.to_string(),
@@ -205,6 +205,53 @@ pub async fn read_project_docs(config: &Config) -> std::io::Result<Option<String
 /// directory (inclusive). Symlinks are allowed. When `project_doc_max_bytes`
 /// is zero, returns an empty list.
 pub fn discover_project_doc_paths(config: &Config) -> std::io::Result<Vec<PathBuf>> {
+    let mut found: Vec<PathBuf> = Vec::new();
+    let candidate_filenames = candidate_filenames(config);
+    for d in project_doc_search_dirs(config)? {
+        for name in &candidate_filenames {
+            let candidate = d.join(name);
+            match std::fs::symlink_metadata(&candidate) {
+                Ok(md) => {
+                    let ft = md.file_type();
+                    // Allow regular files and symlinks; opening will later fail for dangling links.
+                    if ft.is_file() || ft.is_symlink() {
+                        found.push(candidate);
+                        break;
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    Ok(found)
+}
+
+/// Every path a project doc could be read from for `config`, whether or not
+/// it exists yet, so that creating, editing or removing any of them can be
+/// picked up mid-session.
+pub(crate) fn project_doc_watch_paths(config: &Config) -> Vec<PathBuf> {
+    if config.project_doc_max_bytes == 0 {
+        return Vec::new();
+    }
+    let search_dirs = match project_doc_search_dirs(config) {
+        Ok(search_dirs) => search_dirs,
+        Err(err) => {
+            tracing::warn!("failed to resolve project doc directories: {err}");
+            return Vec::new();
+        }
+    };
+    let candidate_filenames = candidate_filenames(config);
+    search_dirs
+        .iter()
+        .flat_map(|dir| candidate_filenames.iter().map(|name| dir.join(name)))
+        .collect()
+}
+
+/// Directories searched for project docs, from the project root to the
+/// current working directory (inclusive).
+fn project_doc_search_dirs(config: &Config) -> std::io::Result<Vec<PathBuf>> {
     let mut dir = config.cwd.clone();
     if let Ok(canon) = normalize_path(&dir) {
         dir = canon;
@@ -249,7 +296,7 @@ pub fn discover_project_doc_paths(config: &Config) -> std::io::Result<Vec<PathBu
         }
     }
 
-    let search_dirs: Vec<PathBuf> = if let Some(root) = project_root {
+    Ok(if let Some(root) = project_root {
         let mut dirs = Vec::new();
         let mut cursor = dir.as_path();
         loop {
@@ -266,29 +313,7 @@ pub fn discover_project_doc_paths(config: &Config) -> std::io::Result<Vec<PathBu
         dirs
     } else {
         vec![dir]
-    };
-
-    let mut found: Vec<PathBuf> = Vec::new();
-    let candidate_filenames = candidate_filenames(config);
-    for d in search_dirs {
-        for name in &candidate_filenames {
-            let candidate = d.join(name);
-            match std::fs::symlink_metadata(&candidate) {
-                Ok(md) => {
-                    let ft = md.file_type();
-                    // Allow regular files and symlinks; opening will later fail for dangling links.
-                    if ft.is_file() || ft.is_symlink() {
-                        found.push(candidate);
-                        break;
-                    }
-                }
-                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
-                Err(e) => return Err(e),
-            }
-        }
-    }
-
-    Ok(found)
+    })
 }
 
 fn candidate_filenames<'a>(config: &'a Config) -> Vec<&'a str> {
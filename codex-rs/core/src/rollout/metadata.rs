@@ -127,6 +127,7 @@ pub(crate) async fn extract_metadata_from_rollout(
             | RolloutItem::EventMsg(_) => None,
         }),
         parse_errors,
+        search_index: codex_state::thread_search_index(&items),
     })
 }
 
@@ -267,6 +268,15 @@ pub(crate) async fn backfill_sessions(runtime: &codex_state::StateRuntime, confi
                             continue;
                         }
                         stats.upserted = stats.upserted.saturating_add(1);
+                        if let Err(err) = runtime
+                            .replace_thread_search(metadata.id, &outcome.search_index)
+                            .await
+                        {
+                            warn!(
+                                "failed to index rollout {} for search: {err}",
+                                rollout.path.display()
+                            );
+                        }
                         if let Ok(meta_line) =
                             rollout::list::read_session_meta_line(&rollout.path).await
                         {
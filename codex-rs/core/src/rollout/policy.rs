@@ -33,12 +33,13 @@ pub(crate) fn should_persist_response_item(item: &ResponseItem) -> bool {
         | ResponseItem::FunctionCall { .. }
         | ResponseItem::FunctionCallOutput { .. }
         | ResponseItem::CustomToolCall { .. }
+        | ResponseItem::CustomToolCallWithAttachments { .. }
         | ResponseItem::CustomToolCallOutput { .. }
         | ResponseItem::WebSearchCall { .. }
         | ResponseItem::ImageGenerationCall { .. }
         | ResponseItem::GhostSnapshot { .. }
         | ResponseItem::Compaction { .. } => true,
-        ResponseItem::Other => false,
+        ResponseItem::UnsupportedToolCall { .. } | ResponseItem::Other => false,
     }
 }
 
@@ -51,12 +52,14 @@ pub(crate) fn should_persist_response_item_for_memories(item: &ResponseItem) ->
         | ResponseItem::FunctionCall { .. }
         | ResponseItem::FunctionCallOutput { .. }
         | ResponseItem::CustomToolCall { .. }
+        | ResponseItem::CustomToolCallWithAttachments { .. }
         | ResponseItem::CustomToolCallOutput { .. }
         | ResponseItem::WebSearchCall { .. } => true,
         ResponseItem::Reasoning { .. }
         | ResponseItem::ImageGenerationCall { .. }
         | ResponseItem::GhostSnapshot { .. }
         | ResponseItem::Compaction { .. }
+        | ResponseItem::UnsupportedToolCall { .. }
         | ResponseItem::Other => false,
     }
 }
@@ -94,13 +97,16 @@ fn event_msg_persistence_mode(ev: &EventMsg) -> Option<EventPersistenceMode> {
         | EventMsg::AgentReasoning(_)
         | EventMsg::AgentReasoningRawContent(_)
         | EventMsg::TokenCount(_)
+        | EventMsg::CostUpdate(_)
         | EventMsg::ContextCompacted(_)
         | EventMsg::EnteredReviewMode(_)
         | EventMsg::ExitedReviewMode(_)
         | EventMsg::ThreadRolledBack(_)
         | EventMsg::UndoCompleted(_)
         | EventMsg::TurnAborted(_)
+        | EventMsg::TurnTimedOut(_)
         | EventMsg::TurnStarted(_)
+        | EventMsg::TurnCheckpoint(_)
         | EventMsg::TurnComplete(_) => Some(EventPersistenceMode::Limited),
         EventMsg::ItemCompleted(event) => {
             // Plan items are derived from streaming tags and are not part of the
@@ -125,8 +131,13 @@ fn event_msg_persistence_mode(ev: &EventMsg) -> Option<EventPersistenceMode> {
         | EventMsg::CollabCloseEnd(_)
         | EventMsg::CollabResumeEnd(_)
         | EventMsg::DynamicToolCallRequest(_)
-        | EventMsg::DynamicToolCallResponse(_) => Some(EventPersistenceMode::Extended),
+        | EventMsg::DynamicToolCallResponse(_)
+        | EventMsg::ToolOutputRedacted(_)
+        | EventMsg::SecretsRedacted(_)
+        | EventMsg::SessionSettingsUpdated(_)
+        | EventMsg::FeaturesResolved(_) => Some(EventPersistenceMode::Extended),
         EventMsg::Warning(_)
+        | EventMsg::SessionShareStarted(_)
         | EventMsg::RealtimeConversationStarted(_)
         | EventMsg::RealtimeConversationRealtime(_)
         | EventMsg::RealtimeConversationClosed(_)
@@ -139,23 +150,39 @@ fn event_msg_persistence_mode(ev: &EventMsg) -> Option<EventPersistenceMode> {
         | EventMsg::SessionConfigured(_)
         | EventMsg::ThreadNameUpdated(_)
         | EventMsg::McpToolCallBegin(_)
+        | EventMsg::ToolCallStart(_)
+        | EventMsg::ToolCallQueued(_)
+        | EventMsg::TurnCancelled(_)
+        | EventMsg::ToolCacheHit(_)
+        | EventMsg::ChaosFaultInjected(_)
+        | EventMsg::CompactionStarted(_)
+        | EventMsg::CompactionFinished(_)
+        | EventMsg::ContextWindowStatus(_)
+        | EventMsg::CheckpointCreated(_)
+        | EventMsg::BranchedFromCheckpoint(_)
         | EventMsg::WebSearchBegin(_)
         | EventMsg::ExecCommandBegin(_)
         | EventMsg::TerminalInteraction(_)
         | EventMsg::ExecCommandOutputDelta(_)
+        | EventMsg::McpToolCallOutputDelta(_)
         | EventMsg::ExecApprovalRequest(_)
         | EventMsg::RequestUserInput(_)
         | EventMsg::ElicitationRequest(_)
         | EventMsg::ApplyPatchApprovalRequest(_)
+        | EventMsg::PatchReviewRequest(_)
         | EventMsg::BackgroundEvent(_)
         | EventMsg::StreamError(_)
         | EventMsg::PatchApplyBegin(_)
         | EventMsg::TurnDiff(_)
+        | EventMsg::TurnDiffUpdated(_)
         | EventMsg::GetHistoryEntryResponse(_)
         | EventMsg::UndoStarted(_)
         | EventMsg::McpListToolsResponse(_)
         | EventMsg::McpStartupUpdate(_)
         | EventMsg::McpStartupComplete(_)
+        | EventMsg::McpServerStatus(_)
+        | EventMsg::ProviderFellBack(_)
+        | EventMsg::ProviderUnhealthy(_)
         | EventMsg::ListCustomPromptsResponse(_)
         | EventMsg::ListSkillsResponse(_)
         | EventMsg::ListRemoteSkillsResponse(_)
@@ -902,6 +902,29 @@ async fn sync_thread_state_after_write(
     items: &[RolloutItem],
     default_provider: &str,
     new_thread_memory_mode: Option<&str>,
+) {
+    update_thread_metadata_after_write(
+        state_db_ctx,
+        rollout_path,
+        state_builder,
+        items,
+        default_provider,
+        new_thread_memory_mode,
+    )
+    .await;
+    let thread_id = state_builder
+        .map(|builder| builder.id)
+        .or_else(|| metadata::builder_from_items(items, rollout_path).map(|builder| builder.id));
+    state_db::index_thread_search(state_db_ctx, thread_id, items, "rollout_writer").await;
+}
+
+async fn update_thread_metadata_after_write(
+    state_db_ctx: Option<&StateRuntime>,
+    rollout_path: &Path,
+    state_builder: Option<&ThreadMetadataBuilder>,
+    items: &[RolloutItem],
+    default_provider: &str,
+    new_thread_memory_mode: Option<&str>,
 ) {
     let updated_at = Utc::now();
     if new_thread_memory_mode.is_some()
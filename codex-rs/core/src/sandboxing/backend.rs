@@ -0,0 +1,293 @@
+//! Sandbox backends that run a command somewhere other than a platform
+//! sandbox, e.g. inside a throwaway Docker or Podman container.
+//!
+//! A backend only rewrites the command line; [`SandboxManager::transform`]
+//! uses it in place of the platform wrapper when one is configured.
+//!
+//! [`SandboxManager::transform`]: super::SandboxManager::transform
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::config::types::SandboxBackendKind;
+use crate::protocol::SandboxPolicy;
+
+/// What a backend needs to know to confine a command.
+pub struct SandboxBackendContext<'a> {
+    pub cwd: &'a Path,
+    pub env: &'a HashMap<String, String>,
+    pub sandbox_policy: &'a SandboxPolicy,
+    pub sandbox_policy_cwd: &'a Path,
+    pub network_enabled: bool,
+}
+
+/// Runs sandboxed commands in place of the platform sandbox.
+pub trait SandboxBackend: Send + Sync {
+    /// Short name exported to the command as `CODEX_SANDBOX`.
+    fn name(&self) -> &'static str;
+
+    /// Returns the command line that runs `command` inside the backend.
+    fn wrap_command(
+        &self,
+        command: Vec<String>,
+        context: &SandboxBackendContext<'_>,
+    ) -> Vec<String>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerRuntime {
+    Docker,
+    Podman,
+}
+
+impl ContainerRuntime {
+    fn program(self) -> &'static str {
+        match self {
+            ContainerRuntime::Docker => "docker",
+            ContainerRuntime::Podman => "podman",
+        }
+    }
+}
+
+/// Runs each command in a fresh container that only sees the working
+/// directory and the policy's writable roots, all mounted at their host paths.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContainerSandbox {
+    pub runtime: ContainerRuntime,
+    pub image: String,
+    /// `--user` for the container, so files written to mounted roots are
+    /// owned by the host user rather than the container's root.
+    pub user: Option<String>,
+}
+
+impl ContainerSandbox {
+    /// Returns the configured container backend, or `None` when commands
+    /// should use the platform sandbox.
+    pub fn from_config(kind: &SandboxBackendKind) -> Option<Self> {
+        let (runtime, image) = match kind {
+            SandboxBackendKind::Platform => return None,
+            SandboxBackendKind::Docker { image } => (ContainerRuntime::Docker, image),
+            SandboxBackendKind::Podman { image } => (ContainerRuntime::Podman, image),
+        };
+        Some(Self {
+            runtime,
+            image: image.clone(),
+            user: host_user(runtime),
+        })
+    }
+}
+
+/// Rootless Podman already maps the container's root to the host user, so
+/// only Docker needs an explicit `--user`.
+#[cfg(unix)]
+fn host_user(runtime: ContainerRuntime) -> Option<String> {
+    match runtime {
+        ContainerRuntime::Docker => {
+            // SAFETY: getuid and getgid cannot fail and have no side effects.
+            let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+            Some(format!("{uid}:{gid}"))
+        }
+        ContainerRuntime::Podman => None,
+    }
+}
+
+#[cfg(not(unix))]
+fn host_user(_runtime: ContainerRuntime) -> Option<String> {
+    None
+}
+
+impl SandboxBackend for ContainerSandbox {
+    fn name(&self) -> &'static str {
+        self.runtime.program()
+    }
+
+    fn wrap_command(
+        &self,
+        command: Vec<String>,
+        context: &SandboxBackendContext<'_>,
+    ) -> Vec<String> {
+        let mut args = vec![
+            self.runtime.program().to_string(),
+            "run".to_string(),
+            "--rm".to_string(),
+            "-i".to_string(),
+        ];
+        if let Some(user) = &self.user {
+            args.push("--user".to_string());
+            args.push(user.clone());
+        }
+        if !context.network_enabled {
+            args.push("--network".to_string());
+            args.push("none".to_string());
+        }
+
+        let writable_roots = context
+            .sandbox_policy
+            .get_writable_roots_with_cwd(context.sandbox_policy_cwd);
+        let mut read_only_dirs = vec![context.cwd];
+        if context.sandbox_policy_cwd != context.cwd {
+            read_only_dirs.push(context.sandbox_policy_cwd);
+        }
+        for dir in read_only_dirs {
+            if !writable_roots.iter().any(|root| root.root.as_path() == dir) {
+                args.push("--volume".to_string());
+                args.push(volume(dir, "ro"));
+            }
+        }
+        for root in &writable_roots {
+            args.push("--volume".to_string());
+            args.push(volume(root.root.as_path(), "rw"));
+            for subpath in &root.read_only_subpaths {
+                if subpath.as_path().exists() {
+                    args.push("--volume".to_string());
+                    args.push(volume(subpath.as_path(), "ro"));
+                }
+            }
+        }
+
+        args.push("--workdir".to_string());
+        args.push(context.cwd.to_string_lossy().into_owned());
+        // Pass variables by name so their values stay out of the runtime's
+        // argv. `PATH` is left to the image, whose layout differs from the host.
+        let mut env_keys: Vec<&String> = context.env.keys().filter(|key| *key != "PATH").collect();
+        env_keys.sort();
+        for key in env_keys {
+            args.push("--env".to_string());
+            args.push(key.clone());
+        }
+
+        args.push(self.image.clone());
+        args.extend(command);
+        args
+    }
+}
+
+fn volume(path: &Path, mode: &str) -> String {
+    let path = path.to_string_lossy();
+    format!("{path}:{path}:{mode}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::protocol::ReadOnlyAccess;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn docker(image: &str) -> ContainerSandbox {
+        ContainerSandbox {
+            runtime: ContainerRuntime::Docker,
+            image: image.to_string(),
+            user: None,
+        }
+    }
+
+    #[test]
+    fn from_config_selects_runtime() {
+        assert_eq!(
+            ContainerSandbox::from_config(&SandboxBackendKind::Platform),
+            None
+        );
+        let podman = ContainerSandbox::from_config(&SandboxBackendKind::Podman {
+            image: "ubuntu:24.04".to_string(),
+        })
+        .expect("podman backend");
+        assert_eq!(
+            podman,
+            ContainerSandbox {
+                runtime: ContainerRuntime::Podman,
+                image: "ubuntu:24.04".to_string(),
+                user: None,
+            }
+        );
+        assert_eq!(podman.name(), "podman");
+    }
+
+    #[test]
+    fn read_only_policy_mounts_cwd_read_only_without_network() {
+        let temp_dir = TempDir::new().expect("create temp dir");
+        let cwd = temp_dir.path();
+        let env = HashMap::from([
+            ("PATH".to_string(), "/usr/bin".to_string()),
+            ("HOME".to_string(), "/home/me".to_string()),
+        ]);
+        let policy = SandboxPolicy::ReadOnly {
+            access: ReadOnlyAccess::FullAccess,
+            network_access: false,
+        };
+
+        let command = docker("ubuntu:24.04").wrap_command(
+            vec!["ls".to_string(), "-la".to_string()],
+            &SandboxBackendContext {
+                cwd,
+                env: &env,
+                sandbox_policy: &policy,
+                sandbox_policy_cwd: cwd,
+                network_enabled: false,
+            },
+        );
+
+        let cwd = cwd.to_string_lossy();
+        assert_eq!(
+            command,
+            vec![
+                "docker".to_string(),
+                "run".to_string(),
+                "--rm".to_string(),
+                "-i".to_string(),
+                "--network".to_string(),
+                "none".to_string(),
+                "--volume".to_string(),
+                format!("{cwd}:{cwd}:ro"),
+                "--workdir".to_string(),
+                cwd.to_string(),
+                "--env".to_string(),
+                "HOME".to_string(),
+                "ubuntu:24.04".to_string(),
+                "ls".to_string(),
+                "-la".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn workspace_write_policy_mounts_writable_roots() {
+        let temp_dir = TempDir::new().expect("create temp dir");
+        let cwd = dunce::canonicalize(temp_dir.path()).expect("canonicalize temp dir");
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: Vec::new(),
+            read_only_access: ReadOnlyAccess::FullAccess,
+            network_access: true,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+        };
+
+        let command = docker("ubuntu:24.04").wrap_command(
+            vec!["true".to_string()],
+            &SandboxBackendContext {
+                cwd: &cwd,
+                env: &HashMap::new(),
+                sandbox_policy: &policy,
+                sandbox_policy_cwd: &cwd,
+                network_enabled: true,
+            },
+        );
+
+        let cwd = cwd.to_string_lossy();
+        assert_eq!(
+            command,
+            vec![
+                "docker".to_string(),
+                "run".to_string(),
+                "--rm".to_string(),
+                "-i".to_string(),
+                "--volume".to_string(),
+                format!("{cwd}:{cwd}:rw"),
+                "--workdir".to_string(),
+                cwd.to_string(),
+                "ubuntu:24.04".to_string(),
+                "true".to_string(),
+            ]
+        );
+    }
+}
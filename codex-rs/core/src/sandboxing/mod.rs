@@ -6,8 +6,11 @@ sandbox placement and transformation of portable CommandSpec into a
 ready‑to‑spawn environment.
 */
 
+pub mod backend;
 pub(crate) mod macos_permissions;
+pub(crate) mod path_rules;
 
+use crate::config::types::SandboxPathRule;
 use crate::exec::ExecExpiration;
 use crate::exec::ExecToolCallOutput;
 use crate::exec::SandboxType;
@@ -20,10 +23,11 @@ use crate::protocol::SandboxPolicy;
 use crate::seatbelt::MACOS_PATH_TO_SEATBELT_EXECUTABLE;
 #[cfg(target_os = "macos")]
 use crate::seatbelt::create_seatbelt_command_args_with_extensions;
-#[cfg(target_os = "macos")]
 use crate::spawn::CODEX_SANDBOX_ENV_VAR;
 use crate::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
 use crate::tools::sandboxing::SandboxablePreference;
+use backend::SandboxBackend;
+use backend::SandboxBackendContext;
 use codex_network_proxy::NetworkProxy;
 use codex_protocol::config_types::WindowsSandboxLevel;
 use codex_protocol::models::FileSystemPermissions;
@@ -42,6 +46,8 @@ use codex_protocol::protocol::ReadOnlyAccess;
 use codex_utils_absolute_path::AbsolutePathBuf;
 use dunce::canonicalize;
 use macos_permissions::merge_macos_seatbelt_profile_extensions;
+use path_rules::compile_sandbox_path_rules;
+use path_rules::deny_file_system_paths;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::path::Path;
@@ -95,6 +101,12 @@ pub(crate) struct SandboxTransformRequest<'a> {
     pub codex_linux_sandbox_exe: Option<&'a PathBuf>,
     pub use_linux_sandbox_bwrap: bool,
     pub windows_sandbox_level: WindowsSandboxLevel,
+    /// Runs the command through this backend instead of the platform sandbox
+    /// whenever `sandbox` is not [`SandboxType::None`].
+    pub backend: Option<&'a dyn SandboxBackend>,
+    /// Path rules layered onto `policy` when the command runs with
+    /// [`SandboxPermissions::UseDefault`].
+    pub sandbox_path_rules: &'a [SandboxPathRule],
 }
 
 pub enum SandboxPreference {
@@ -442,10 +454,18 @@ impl SandboxManager {
             codex_linux_sandbox_exe,
             use_linux_sandbox_bwrap,
             windows_sandbox_level,
+            backend,
+            sandbox_path_rules,
         } = request;
         #[cfg(not(target_os = "macos"))]
         let macos_seatbelt_profile_extensions = None;
-        let additional_permissions = spec.additional_permissions.take();
+        let path_rules = (spec.sandbox_permissions == SandboxPermissions::UseDefault)
+            .then(|| compile_sandbox_path_rules(sandbox_path_rules, &spec.cwd))
+            .unwrap_or_default();
+        let additional_permissions = spec
+            .additional_permissions
+            .take()
+            .or(path_rules.permissions);
         let EffectiveSandboxPermissions {
             sandbox_policy: effective_policy,
             macos_seatbelt_profile_extensions: _effective_macos_seatbelt_profile_extensions,
@@ -478,6 +498,8 @@ impl SandboxManager {
             } else {
                 (file_system_policy.clone(), network_policy)
             };
+        let effective_file_system_policy =
+            deny_file_system_paths(effective_file_system_policy, path_rules.denied);
         let mut env = spec.env;
         if !effective_network_policy.is_enabled() {
             env.insert(
@@ -490,55 +512,77 @@ impl SandboxManager {
         command.push(spec.program);
         command.append(&mut spec.args);
 
-        let (command, sandbox_env, arg0_override) = match sandbox {
-            SandboxType::None => (command, HashMap::new(), None),
-            #[cfg(target_os = "macos")]
-            SandboxType::MacosSeatbelt => {
-                let mut seatbelt_env = HashMap::new();
-                seatbelt_env.insert(CODEX_SANDBOX_ENV_VAR.to_string(), "seatbelt".to_string());
-                let mut args = create_seatbelt_command_args_with_extensions(
-                    command.clone(),
-                    &effective_policy,
-                    sandbox_policy_cwd,
-                    enforce_managed_network,
-                    network,
-                    _effective_macos_seatbelt_profile_extensions.as_ref(),
-                );
-                let mut full_command = Vec::with_capacity(1 + args.len());
-                full_command.push(MACOS_PATH_TO_SEATBELT_EXECUTABLE.to_string());
-                full_command.append(&mut args);
-                (full_command, seatbelt_env, None)
-            }
-            #[cfg(not(target_os = "macos"))]
-            SandboxType::MacosSeatbelt => return Err(SandboxTransformError::SeatbeltUnavailable),
-            SandboxType::LinuxSeccomp => {
-                let exe = codex_linux_sandbox_exe
-                    .ok_or(SandboxTransformError::MissingLinuxSandboxExecutable)?;
-                let allow_proxy_network = allow_network_for_proxy(enforce_managed_network);
-                let mut args = create_linux_sandbox_command_args(
-                    command.clone(),
-                    &effective_policy,
+        let backend = backend.filter(|_| sandbox != SandboxType::None);
+        let (command, sandbox_env, arg0_override) = if let Some(backend) = backend {
+            let command = backend.wrap_command(
+                command,
+                &SandboxBackendContext {
+                    cwd: &spec.cwd,
+                    env: &env,
+                    sandbox_policy: &effective_policy,
                     sandbox_policy_cwd,
-                    use_linux_sandbox_bwrap,
-                    allow_proxy_network,
-                );
-                let mut full_command = Vec::with_capacity(1 + args.len());
-                full_command.push(exe.to_string_lossy().to_string());
-                full_command.append(&mut args);
-                (
-                    full_command,
-                    HashMap::new(),
-                    Some("codex-linux-sandbox".to_string()),
-                )
+                    network_enabled: effective_network_policy.is_enabled(),
+                },
+            );
+            let mut backend_env = HashMap::new();
+            backend_env.insert(
+                CODEX_SANDBOX_ENV_VAR.to_string(),
+                backend.name().to_string(),
+            );
+            (command, backend_env, None)
+        } else {
+            match sandbox {
+                SandboxType::None => (command, HashMap::new(), None),
+                #[cfg(target_os = "macos")]
+                SandboxType::MacosSeatbelt => {
+                    let mut seatbelt_env = HashMap::new();
+                    seatbelt_env.insert(CODEX_SANDBOX_ENV_VAR.to_string(), "seatbelt".to_string());
+                    let mut args = create_seatbelt_command_args_with_extensions(
+                        command.clone(),
+                        &effective_policy,
+                        sandbox_policy_cwd,
+                        enforce_managed_network,
+                        network,
+                        _effective_macos_seatbelt_profile_extensions.as_ref(),
+                    );
+                    let mut full_command = Vec::with_capacity(1 + args.len());
+                    full_command.push(MACOS_PATH_TO_SEATBELT_EXECUTABLE.to_string());
+                    full_command.append(&mut args);
+                    (full_command, seatbelt_env, None)
+                }
+                #[cfg(not(target_os = "macos"))]
+                SandboxType::MacosSeatbelt => {
+                    return Err(SandboxTransformError::SeatbeltUnavailable);
+                }
+                SandboxType::LinuxSeccomp => {
+                    let exe = codex_linux_sandbox_exe
+                        .ok_or(SandboxTransformError::MissingLinuxSandboxExecutable)?;
+                    let allow_proxy_network = allow_network_for_proxy(enforce_managed_network);
+                    let mut args = create_linux_sandbox_command_args(
+                        command.clone(),
+                        &effective_policy,
+                        sandbox_policy_cwd,
+                        use_linux_sandbox_bwrap,
+                        allow_proxy_network,
+                    );
+                    let mut full_command = Vec::with_capacity(1 + args.len());
+                    full_command.push(exe.to_string_lossy().to_string());
+                    full_command.append(&mut args);
+                    (
+                        full_command,
+                        HashMap::new(),
+                        Some("codex-linux-sandbox".to_string()),
+                    )
+                }
+                // On Windows, the restricted token sandbox executes in-process via the
+                // codex-windows-sandbox crate. We leave the command unchanged here and
+                // branch during execution based on the sandbox type.
+                #[cfg(target_os = "windows")]
+                SandboxType::WindowsRestrictedToken => (command, HashMap::new(), None),
+                // When building for non-Windows targets, this variant is never constructed.
+                #[cfg(not(target_os = "windows"))]
+                SandboxType::WindowsRestrictedToken => (command, HashMap::new(), None),
             }
-            // On Windows, the restricted token sandbox executes in-process via the
-            // codex-windows-sandbox crate. We leave the command unchanged here and
-            // branch during execution based on the sandbox type.
-            #[cfg(target_os = "windows")]
-            SandboxType::WindowsRestrictedToken => (command, HashMap::new(), None),
-            // When building for non-Windows targets, this variant is never constructed.
-            #[cfg(not(target_os = "windows"))]
-            SandboxType::WindowsRestrictedToken => (command, HashMap::new(), None),
         };
 
         env.extend(sandbox_env);
@@ -549,7 +593,13 @@ impl SandboxManager {
             env,
             network: network.cloned(),
             expiration: spec.expiration,
-            sandbox,
+            // The backend's command is the sandbox, so it is spawned as a
+            // plain process.
+            sandbox: if backend.is_some() {
+                SandboxType::None
+            } else {
+                sandbox
+            },
             windows_sandbox_level,
             sandbox_permissions: spec.sandbox_permissions,
             sandbox_policy: effective_policy,
@@ -591,6 +641,7 @@ mod tests {
     use super::normalize_additional_permissions;
     use super::sandbox_policy_with_additional_permissions;
     use super::should_require_platform_sandbox;
+    use crate::config::types::SandboxPathRule;
     use crate::exec::SandboxType;
     use crate::protocol::NetworkAccess;
     use crate::protocol::ReadOnlyAccess;
@@ -724,6 +775,8 @@ mod tests {
                 codex_linux_sandbox_exe: None,
                 use_linux_sandbox_bwrap: false,
                 windows_sandbox_level: WindowsSandboxLevel::Disabled,
+                backend: None,
+                sandbox_path_rules: &[],
             })
             .expect("transform");
 
@@ -737,6 +790,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn transform_runs_sandboxed_commands_through_configured_backend() {
+        let manager = SandboxManager::new();
+        let cwd = std::env::current_dir().expect("current dir");
+        let backend = super::backend::ContainerSandbox {
+            runtime: super::backend::ContainerRuntime::Podman,
+            image: "ubuntu:24.04".to_string(),
+            user: None,
+        };
+        let exec_request = manager
+            .transform(super::SandboxTransformRequest {
+                spec: super::CommandSpec {
+                    program: "true".to_string(),
+                    args: Vec::new(),
+                    cwd: cwd.clone(),
+                    env: HashMap::new(),
+                    expiration: crate::exec::ExecExpiration::DefaultTimeout,
+                    sandbox_permissions: super::SandboxPermissions::UseDefault,
+                    additional_permissions: None,
+                    justification: None,
+                },
+                policy: &SandboxPolicy::new_read_only_policy(),
+                file_system_policy: &FileSystemSandboxPolicy::unrestricted(),
+                network_policy: NetworkSandboxPolicy::Restricted,
+                sandbox: SandboxType::LinuxSeccomp,
+                enforce_managed_network: false,
+                network: None,
+                sandbox_policy_cwd: cwd.as_path(),
+                #[cfg(target_os = "macos")]
+                macos_seatbelt_profile_extensions: None,
+                codex_linux_sandbox_exe: None,
+                use_linux_sandbox_bwrap: false,
+                windows_sandbox_level: WindowsSandboxLevel::Disabled,
+                backend: Some(&backend),
+                sandbox_path_rules: &[],
+            })
+            .expect("transform");
+
+        assert_eq!(exec_request.sandbox, SandboxType::None);
+        assert_eq!(
+            exec_request.command.first().map(String::as_str),
+            Some("podman")
+        );
+        assert_eq!(
+            exec_request.command.last().map(String::as_str),
+            Some("true")
+        );
+        assert_eq!(
+            exec_request.env.get(crate::spawn::CODEX_SANDBOX_ENV_VAR),
+            Some(&"podman".to_string())
+        );
+    }
+
     #[test]
     fn normalize_additional_permissions_preserves_network() {
         let temp_dir = TempDir::new().expect("create temp dir");
@@ -968,6 +1074,8 @@ mod tests {
                 codex_linux_sandbox_exe: None,
                 use_linux_sandbox_bwrap: false,
                 windows_sandbox_level: WindowsSandboxLevel::Disabled,
+                backend: None,
+                sandbox_path_rules: &[],
             })
             .expect("transform");
 
@@ -983,6 +1091,75 @@ mod tests {
         );
     }
 
+    #[test]
+    fn transform_applies_sandbox_path_rules_relative_to_command_cwd() {
+        let manager = SandboxManager::new();
+        let temp_dir = TempDir::new().expect("create temp dir");
+        let cwd = AbsolutePathBuf::from_absolute_path(
+            canonicalize(temp_dir.path()).expect("canonicalize temp dir"),
+        )
+        .expect("absolute temp dir");
+        let rules = vec![
+            SandboxPathRule {
+                path: "target".to_string(),
+                access: FileSystemAccessMode::Write,
+            },
+            SandboxPathRule {
+                path: "target/secrets".to_string(),
+                access: FileSystemAccessMode::None,
+            },
+        ];
+        let policy = SandboxPolicy::new_read_only_policy();
+        let file_system_policy = FileSystemSandboxPolicy::from(&policy);
+        let exec_request = manager
+            .transform(super::SandboxTransformRequest {
+                spec: super::CommandSpec {
+                    program: "true".to_string(),
+                    args: Vec::new(),
+                    cwd: cwd.to_path_buf(),
+                    env: HashMap::new(),
+                    expiration: crate::exec::ExecExpiration::DefaultTimeout,
+                    sandbox_permissions: super::SandboxPermissions::UseDefault,
+                    additional_permissions: None,
+                    justification: None,
+                },
+                policy: &policy,
+                file_system_policy: &file_system_policy,
+                network_policy: NetworkSandboxPolicy::Restricted,
+                sandbox: SandboxType::None,
+                enforce_managed_network: false,
+                network: None,
+                sandbox_policy_cwd: cwd.as_path(),
+                #[cfg(target_os = "macos")]
+                macos_seatbelt_profile_extensions: None,
+                codex_linux_sandbox_exe: None,
+                use_linux_sandbox_bwrap: false,
+                windows_sandbox_level: WindowsSandboxLevel::Disabled,
+                backend: None,
+                sandbox_path_rules: &rules,
+            })
+            .expect("transform");
+
+        let target = cwd.join("target").expect("target path");
+        let secrets = cwd.join("target/secrets").expect("secrets path");
+        assert!(matches!(
+            &exec_request.sandbox_policy,
+            SandboxPolicy::WorkspaceWrite { writable_roots, .. } if writable_roots == &vec![target.clone()]
+        ));
+        assert!(exec_request.file_system_sandbox_policy.entries.contains(
+            &FileSystemSandboxEntry {
+                path: FileSystemPath::Path { path: target },
+                access: FileSystemAccessMode::Write,
+            }
+        ));
+        assert_eq!(
+            exec_request
+                .file_system_sandbox_policy
+                .get_unreadable_roots_with_cwd(cwd.as_path()),
+            vec![secrets]
+        );
+    }
+
     #[test]
     fn transform_additional_permissions_preserves_denied_entries() {
         let manager = SandboxManager::new();
@@ -1040,6 +1217,8 @@ mod tests {
                 codex_linux_sandbox_exe: None,
                 use_linux_sandbox_bwrap: false,
                 windows_sandbox_level: WindowsSandboxLevel::Disabled,
+                backend: None,
+                sandbox_path_rules: &[],
             })
             .expect("transform");
 
@@ -0,0 +1,158 @@
+//! Path-scoped sandbox rules from `[[sandbox_rules]]`.
+//!
+//! Rules are resolved against each command's working directory, so a rule
+//! such as `{ path = "target", access = "write" }` follows the command into
+//! whichever project it runs in. Read and write rules widen the command's
+//! sandbox through the same path as requested additional permissions; `none`
+//! rules take precedence over both and are recorded as unreadable roots in
+//! the command's [`FileSystemSandboxPolicy`].
+
+use std::path::Path;
+
+use codex_protocol::models::FileSystemPermissions;
+use codex_protocol::models::PermissionProfile;
+use codex_protocol::permissions::FileSystemAccessMode;
+use codex_protocol::permissions::FileSystemPath;
+use codex_protocol::permissions::FileSystemSandboxEntry;
+use codex_protocol::permissions::FileSystemSandboxKind;
+use codex_protocol::permissions::FileSystemSandboxPolicy;
+use codex_utils_absolute_path::AbsolutePathBuf;
+
+use crate::config::types::SandboxPathRule;
+
+/// Sandbox rules resolved for a single command.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct CompiledSandboxPathRules {
+    /// Paths the rules make readable or writable, outside any denied path.
+    pub(crate) permissions: Option<PermissionProfile>,
+    pub(crate) denied: Vec<AbsolutePathBuf>,
+}
+
+pub(crate) fn compile_sandbox_path_rules(
+    rules: &[SandboxPathRule],
+    cwd: &Path,
+) -> CompiledSandboxPathRules {
+    let mut reads = Vec::new();
+    let mut writes = Vec::new();
+    let mut denied = Vec::new();
+    for rule in rules {
+        let Ok(path) = AbsolutePathBuf::resolve_path_against_base(&rule.path, cwd) else {
+            continue;
+        };
+        let paths = match rule.access {
+            FileSystemAccessMode::None => &mut denied,
+            FileSystemAccessMode::Read => &mut reads,
+            FileSystemAccessMode::Write => &mut writes,
+        };
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+
+    let is_allowed = |path: &AbsolutePathBuf| {
+        !denied
+            .iter()
+            .any(|denied| path.as_path().starts_with(denied.as_path()))
+    };
+    reads.retain(is_allowed);
+    writes.retain(is_allowed);
+
+    let permissions = (!reads.is_empty() || !writes.is_empty()).then(|| PermissionProfile {
+        file_system: Some(FileSystemPermissions {
+            read: Some(reads),
+            write: Some(writes),
+        }),
+        ..Default::default()
+    });
+    CompiledSandboxPathRules {
+        permissions,
+        denied,
+    }
+}
+
+/// Adds `denied` to a restricted `policy` as paths with no access.
+/// Unrestricted and external sandbox policies are returned unchanged.
+pub(crate) fn deny_file_system_paths(
+    mut policy: FileSystemSandboxPolicy,
+    denied: Vec<AbsolutePathBuf>,
+) -> FileSystemSandboxPolicy {
+    if policy.kind != FileSystemSandboxKind::Restricted {
+        return policy;
+    }
+    for path in denied {
+        let entry = FileSystemSandboxEntry {
+            path: FileSystemPath::Path { path },
+            access: FileSystemAccessMode::None,
+        };
+        if !policy.entries.contains(&entry) {
+            policy.entries.push(entry);
+        }
+    }
+    policy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn rule(path: &str, access: FileSystemAccessMode) -> SandboxPathRule {
+        SandboxPathRule {
+            path: path.to_string(),
+            access,
+        }
+    }
+
+    #[test]
+    fn rules_resolve_against_command_cwd_and_denials_win() {
+        let cwd = Path::new("/work/project");
+        let abs = |path: &str| AbsolutePathBuf::from_absolute_path(path).expect("absolute path");
+
+        let compiled = compile_sandbox_path_rules(
+            &[
+                rule("target", FileSystemAccessMode::Write),
+                rule("node_modules", FileSystemAccessMode::Write),
+                rule("/work/secrets", FileSystemAccessMode::None),
+                rule("../secrets/cache", FileSystemAccessMode::Write),
+                rule("/opt/sdk", FileSystemAccessMode::Read),
+            ],
+            cwd,
+        );
+
+        assert_eq!(
+            compiled,
+            CompiledSandboxPathRules {
+                permissions: Some(PermissionProfile {
+                    file_system: Some(FileSystemPermissions {
+                        read: Some(vec![abs("/opt/sdk")]),
+                        write: Some(vec![
+                            abs("/work/project/target"),
+                            abs("/work/project/node_modules"),
+                        ]),
+                    }),
+                    ..Default::default()
+                }),
+                denied: vec![abs("/work/secrets")],
+            }
+        );
+    }
+
+    #[test]
+    fn denied_paths_only_extend_restricted_policies() {
+        let denied = vec![AbsolutePathBuf::from_absolute_path("/home/user/.ssh").expect("path")];
+
+        assert_eq!(
+            deny_file_system_paths(FileSystemSandboxPolicy::unrestricted(), denied.clone()),
+            FileSystemSandboxPolicy::unrestricted()
+        );
+        assert_eq!(
+            deny_file_system_paths(FileSystemSandboxPolicy::restricted(Vec::new()), denied).entries,
+            vec![FileSystemSandboxEntry {
+                path: FileSystemPath::Path {
+                    path: AbsolutePathBuf::from_absolute_path("/home/user/.ssh").expect("path"),
+                },
+                access: FileSystemAccessMode::None,
+            }]
+        );
+    }
+}
@@ -0,0 +1,438 @@
+//! Renders a recorded session as a shareable Markdown or single-file HTML
+//! report: the conversation, tool calls with truncated output, the patches
+//! the agent applied, and the session's token usage.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use codex_protocol::models::LocalShellAction;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::SessionMeta;
+use codex_protocol::protocol::TokenUsage;
+
+use crate::rollout::RolloutRecorder;
+use crate::truncate::TruncationPolicy;
+use crate::truncate::truncate_text;
+
+/// Tool output kept per call; the rest is elided from the middle.
+const TOOL_OUTPUT_BYTES: usize = 2_000;
+
+#[derive(Debug, Default, Clone)]
+pub struct SessionReport {
+    pub meta: Option<SessionMeta>,
+    pub model: Option<String>,
+    pub entries: Vec<ReportEntry>,
+    /// Token usage as of the last token count in the session.
+    pub token_usage: Option<TokenUsage>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReportEntry {
+    User(String),
+    Assistant(String),
+    ToolCall {
+        name: String,
+        input: String,
+        output: Option<String>,
+    },
+    /// An `apply_patch` call, rendered as a diff.
+    Patch {
+        patch: String,
+        output: Option<String>,
+    },
+}
+
+impl SessionReport {
+    /// Loads the rollout file at `path` and builds its report.
+    pub async fn load(path: &Path) -> std::io::Result<Self> {
+        let history = RolloutRecorder::get_rollout_history(path).await?;
+        Ok(Self::from_rollout_items(&history.get_rollout_items()))
+    }
+
+    pub fn from_rollout_items(items: &[RolloutItem]) -> Self {
+        let mut report = SessionReport::default();
+        let mut calls_by_id = HashMap::new();
+        for item in items {
+            match item {
+                RolloutItem::SessionMeta(meta_line) => {
+                    report.meta.get_or_insert_with(|| meta_line.meta.clone());
+                }
+                RolloutItem::TurnContext(turn_context) => {
+                    report.model = Some(turn_context.model.clone());
+                }
+                RolloutItem::EventMsg(EventMsg::UserMessage(event)) => {
+                    report
+                        .entries
+                        .push(ReportEntry::User(event.message.clone()));
+                }
+                RolloutItem::EventMsg(EventMsg::AgentMessage(event)) => {
+                    report
+                        .entries
+                        .push(ReportEntry::Assistant(event.message.clone()));
+                }
+                RolloutItem::EventMsg(EventMsg::TokenCount(event)) => {
+                    if let Some(info) = &event.info {
+                        report.token_usage = Some(info.total_token_usage.clone());
+                    }
+                }
+                RolloutItem::ResponseItem(item) => {
+                    report.push_response_item(item, &mut calls_by_id);
+                }
+                RolloutItem::EventMsg(_) | RolloutItem::Compacted(_) => {}
+            }
+        }
+        report
+    }
+
+    fn push_response_item(
+        &mut self,
+        item: &ResponseItem,
+        calls_by_id: &mut HashMap<String, usize>,
+    ) {
+        let (call_id, entry) = match item {
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => (call_id.clone(), tool_call_entry(name, arguments)),
+            ResponseItem::CustomToolCall {
+                name,
+                input,
+                call_id,
+                ..
+            }
+            | ResponseItem::CustomToolCallWithAttachments {
+                name,
+                input,
+                call_id,
+                ..
+            } => (call_id.clone(), tool_call_entry(name, input)),
+            ResponseItem::LocalShellCall {
+                call_id,
+                action: LocalShellAction::Exec(exec),
+                ..
+            } => (
+                call_id.clone().unwrap_or_default(),
+                ReportEntry::ToolCall {
+                    name: "shell".to_string(),
+                    input: shlex::try_join(exec.command.iter().map(String::as_str))
+                        .unwrap_or_else(|_| exec.command.join(" ")),
+                    output: None,
+                },
+            ),
+            ResponseItem::FunctionCallOutput { call_id, output }
+            | ResponseItem::CustomToolCallOutput { call_id, output } => {
+                let text = output.body.to_text().unwrap_or_default();
+                if let Some(index) = calls_by_id.get(call_id)
+                    && let Some(
+                        ReportEntry::ToolCall { output, .. } | ReportEntry::Patch { output, .. },
+                    ) = self.entries.get_mut(*index)
+                {
+                    *output = Some(truncate_text(
+                        &text,
+                        TruncationPolicy::Bytes(TOOL_OUTPUT_BYTES),
+                    ));
+                }
+                return;
+            }
+            _ => return,
+        };
+        calls_by_id.insert(call_id, self.entries.len());
+        self.entries.push(entry);
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}\n", self.title());
+        for (label, value) in self.summary() {
+            let _ = writeln!(out, "- **{label}:** {value}");
+        }
+        for entry in &self.entries {
+            out.push('\n');
+            match entry {
+                ReportEntry::User(message) => {
+                    let _ = writeln!(out, "## User\n\n{message}");
+                }
+                ReportEntry::Assistant(message) => {
+                    let _ = writeln!(out, "## Assistant\n\n{message}");
+                }
+                ReportEntry::ToolCall {
+                    name,
+                    input,
+                    output,
+                } => {
+                    let _ = writeln!(out, "### Tool call: `{name}`\n");
+                    out.push_str(&markdown_fence("", input));
+                    if let Some(output) = output {
+                        out.push_str("\nOutput:\n\n");
+                        out.push_str(&markdown_fence("", output));
+                    }
+                }
+                ReportEntry::Patch { patch, output } => {
+                    out.push_str("### Patch\n\n");
+                    out.push_str(&markdown_fence("diff", patch));
+                    if let Some(output) = output {
+                        out.push_str("\nOutput:\n\n");
+                        out.push_str(&markdown_fence("", output));
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        let title = html_escape(&self.title());
+        let _ = write!(
+            out,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{title}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n\
+             <h1>{title}</h1>\n<ul class=\"summary\">\n"
+        );
+        for (label, value) in self.summary() {
+            let _ = writeln!(
+                out,
+                "<li><strong>{label}:</strong> {}</li>",
+                html_escape(&value)
+            );
+        }
+        out.push_str("</ul>\n");
+        for entry in &self.entries {
+            match entry {
+                ReportEntry::User(message) => {
+                    let _ = writeln!(
+                        out,
+                        "<section class=\"user\"><h2>User</h2><pre>{}</pre></section>",
+                        html_escape(message)
+                    );
+                }
+                ReportEntry::Assistant(message) => {
+                    let _ = writeln!(
+                        out,
+                        "<section class=\"assistant\"><h2>Assistant</h2><pre>{}</pre></section>",
+                        html_escape(message)
+                    );
+                }
+                ReportEntry::ToolCall {
+                    name,
+                    input,
+                    output,
+                } => {
+                    let _ = write!(
+                        out,
+                        "<details class=\"tool\"><summary>Tool call: <code>{}</code></summary><pre>{}</pre>",
+                        html_escape(name),
+                        html_escape(input)
+                    );
+                    push_html_output(&mut out, output.as_deref());
+                }
+                ReportEntry::Patch { patch, output } => {
+                    out.push_str("<details class=\"tool\" open><summary>Patch</summary><pre>");
+                    for line in patch.lines() {
+                        let class = match line.chars().next() {
+                            Some('+') => "add",
+                            Some('-') => "del",
+                            Some('*') | Some('@') => "hunk",
+                            _ => "ctx",
+                        };
+                        let _ =
+                            writeln!(out, "<span class=\"{class}\">{}</span>", html_escape(line));
+                    }
+                    out.push_str("</pre>");
+                    push_html_output(&mut out, output.as_deref());
+                }
+            }
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    fn title(&self) -> String {
+        match &self.meta {
+            Some(meta) => format!("Codex session {}", meta.id),
+            None => "Codex session".to_string(),
+        }
+    }
+
+    fn summary(&self) -> Vec<(&'static str, String)> {
+        let mut summary = Vec::new();
+        if let Some(meta) = &self.meta {
+            summary.push(("Started", meta.timestamp.clone()));
+            summary.push(("Directory", meta.cwd.display().to_string()));
+        }
+        if let Some(model) = &self.model {
+            summary.push(("Model", model.clone()));
+        }
+        if let Some(usage) = &self.token_usage {
+            summary.push((
+                "Tokens",
+                format!(
+                    "{} total ({} input, {} cached, {} output, {} reasoning)",
+                    usage.total_tokens,
+                    usage.input_tokens,
+                    usage.cached_input_tokens,
+                    usage.output_tokens,
+                    usage.reasoning_output_tokens
+                ),
+            ));
+        }
+        summary
+    }
+}
+
+fn tool_call_entry(name: &str, input: &str) -> ReportEntry {
+    if name == "apply_patch" {
+        let patch = serde_json::from_str::<serde_json::Value>(input)
+            .ok()
+            .and_then(|args| args.get("input")?.as_str().map(str::to_string))
+            .unwrap_or_else(|| input.to_string());
+        return ReportEntry::Patch {
+            patch,
+            output: None,
+        };
+    }
+    ReportEntry::ToolCall {
+        name: name.to_string(),
+        input: input.to_string(),
+        output: None,
+    }
+}
+
+/// Fences `text` with enough backticks that none inside it close the block.
+fn markdown_fence(info: &str, text: &str) -> String {
+    let longest_run = text.split(|ch| ch != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("{fence}{info}\n{}\n{fence}\n", text.trim_end_matches('\n'))
+}
+
+fn push_html_output(out: &mut String, output: Option<&str>) {
+    if let Some(output) = output {
+        let _ = write!(out, "<pre class=\"output\">{}</pre>", html_escape(output));
+    }
+    out.push_str("</details>\n");
+}
+
+fn html_escape(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for ch in input.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+const HTML_STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:60rem;margin:2rem auto;\
+padding:0 1rem;color:#1f2328}pre{white-space:pre-wrap;word-break:break-word;background:#f6f8fa;\
+padding:.75rem;border-radius:6px}section.user pre{background:#ddf4ff}details{margin:1rem 0}\
+summary{cursor:pointer;font-weight:600}pre.output{background:#fff8c5}.add{color:#1a7f37}\
+.del{color:#cf222e}.hunk{color:#8250df}";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use codex_protocol::protocol::AgentMessageEvent;
+    use codex_protocol::protocol::TokenCountEvent;
+    use codex_protocol::protocol::TokenUsageInfo;
+    use codex_protocol::protocol::UserMessageEvent;
+    use pretty_assertions::assert_eq;
+
+    fn items() -> Vec<RolloutItem> {
+        vec![
+            RolloutItem::EventMsg(EventMsg::UserMessage(UserMessageEvent {
+                message: "fix <the> bug".to_string(),
+                images: None,
+                local_images: Vec::new(),
+                text_elements: Vec::new(),
+            })),
+            RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: r#"{"command":["ls"]}"#.to_string(),
+                call_id: "call-1".to_string(),
+            }),
+            RolloutItem::ResponseItem(ResponseItem::FunctionCallOutput {
+                call_id: "call-1".to_string(),
+                output: FunctionCallOutputPayload::from_text("x".repeat(10_000)),
+            }),
+            RolloutItem::ResponseItem(ResponseItem::CustomToolCall {
+                id: None,
+                status: None,
+                call_id: "call-2".to_string(),
+                name: "apply_patch".to_string(),
+                input: "*** Begin Patch\n*** Update File: a.rs\n-old\n+new\n*** End Patch"
+                    .to_string(),
+            }),
+            RolloutItem::EventMsg(EventMsg::AgentMessage(AgentMessageEvent {
+                message: "Done.".to_string(),
+                phase: None,
+            })),
+            RolloutItem::EventMsg(EventMsg::TokenCount(TokenCountEvent {
+                info: Some(TokenUsageInfo {
+                    total_token_usage: TokenUsage {
+                        input_tokens: 100,
+                        cached_input_tokens: 40,
+                        output_tokens: 20,
+                        reasoning_output_tokens: 5,
+                        total_tokens: 120,
+                    },
+                    last_token_usage: TokenUsage::default(),
+                    model_context_window: None,
+                }),
+                rate_limits: None,
+                prompt_cache_hit_rate: None,
+            })),
+        ]
+    }
+
+    #[test]
+    fn report_pairs_tool_calls_with_truncated_output() {
+        let report = SessionReport::from_rollout_items(&items());
+
+        assert_eq!(report.entries.len(), 4);
+        let ReportEntry::ToolCall {
+            name,
+            output: Some(output),
+            ..
+        } = &report.entries[1]
+        else {
+            panic!("expected a tool call with output: {:?}", report.entries[1]);
+        };
+        assert_eq!(name, "shell");
+        assert!(output.len() < 10_000, "{output}");
+        assert!(matches!(report.entries[2], ReportEntry::Patch { .. }));
+        assert_eq!(
+            report.token_usage.map(|usage| usage.total_tokens),
+            Some(120)
+        );
+    }
+
+    #[test]
+    fn renders_markdown_and_escaped_html() {
+        let report = SessionReport::from_rollout_items(&items());
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("## User\n\nfix <the> bug"), "{markdown}");
+        assert!(markdown.contains("```diff\n*** Begin Patch"), "{markdown}");
+        assert!(
+            markdown.contains("120 total (100 input, 40 cached, 20 output, 5 reasoning)"),
+            "{markdown}"
+        );
+
+        let html = report.to_html();
+        assert!(html.contains("fix &lt;the&gt; bug"), "{html}");
+        assert!(html.contains("<span class=\"add\">+new</span>"), "{html}");
+        assert!(!html.contains("<the>"), "{html}");
+    }
+}
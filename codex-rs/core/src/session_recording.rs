@@ -0,0 +1,354 @@
+//! Record and replay model traffic for golden-master tests.
+//!
+//! A [`RecordingHandle`] captures every request sent through the session's
+//! [`ModelClient`](crate::client::ModelClient) together with the response
+//! events streamed back. The saved JSON can later be loaded into a
+//! [`Playback`], which serves the recorded responses in order instead of
+//! contacting the provider, so a session driven by the same inputs produces
+//! the same `EventMsg` sequence.
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+
+use codex_api::common::ResponseEvent;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::RateLimitSnapshot;
+use codex_protocol::protocol::TokenUsage;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+use crate::client_common::ResponseStream;
+use crate::error::CodexErr;
+use crate::error::Result;
+
+/// Serializable mirror of [`ResponseEvent`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum RecordedEvent {
+    Created,
+    OutputItemDone {
+        item: ResponseItem,
+    },
+    OutputItemAdded {
+        item: ResponseItem,
+    },
+    ServerModel {
+        model: String,
+    },
+    ServerReasoningIncluded {
+        included: bool,
+    },
+    Completed {
+        response_id: String,
+        token_usage: Option<TokenUsage>,
+    },
+    OutputTextDelta {
+        delta: String,
+    },
+    ReasoningSummaryDelta {
+        delta: String,
+        summary_index: i64,
+    },
+    ReasoningContentDelta {
+        delta: String,
+        content_index: i64,
+    },
+    ReasoningSummaryPartAdded {
+        summary_index: i64,
+    },
+    RateLimits {
+        snapshot: RateLimitSnapshot,
+    },
+    ModelsEtag {
+        etag: String,
+    },
+}
+
+impl From<&ResponseEvent> for RecordedEvent {
+    fn from(event: &ResponseEvent) -> Self {
+        match event {
+            ResponseEvent::Created => Self::Created,
+            ResponseEvent::OutputItemDone(item) => Self::OutputItemDone { item: item.clone() },
+            ResponseEvent::OutputItemAdded(item) => Self::OutputItemAdded { item: item.clone() },
+            ResponseEvent::ServerModel(model) => Self::ServerModel {
+                model: model.clone(),
+            },
+            ResponseEvent::ServerReasoningIncluded(included) => Self::ServerReasoningIncluded {
+                included: *included,
+            },
+            ResponseEvent::Completed {
+                response_id,
+                token_usage,
+            } => Self::Completed {
+                response_id: response_id.clone(),
+                token_usage: token_usage.clone(),
+            },
+            ResponseEvent::OutputTextDelta(delta) => Self::OutputTextDelta {
+                delta: delta.clone(),
+            },
+            ResponseEvent::ReasoningSummaryDelta {
+                delta,
+                summary_index,
+            } => Self::ReasoningSummaryDelta {
+                delta: delta.clone(),
+                summary_index: *summary_index,
+            },
+            ResponseEvent::ReasoningContentDelta {
+                delta,
+                content_index,
+            } => Self::ReasoningContentDelta {
+                delta: delta.clone(),
+                content_index: *content_index,
+            },
+            ResponseEvent::ReasoningSummaryPartAdded { summary_index } => {
+                Self::ReasoningSummaryPartAdded {
+                    summary_index: *summary_index,
+                }
+            }
+            ResponseEvent::RateLimits(snapshot) => Self::RateLimits {
+                snapshot: snapshot.clone(),
+            },
+            ResponseEvent::ModelsEtag(etag) => Self::ModelsEtag { etag: etag.clone() },
+        }
+    }
+}
+
+impl From<RecordedEvent> for ResponseEvent {
+    fn from(event: RecordedEvent) -> Self {
+        match event {
+            RecordedEvent::Created => Self::Created,
+            RecordedEvent::OutputItemDone { item } => Self::OutputItemDone(item),
+            RecordedEvent::OutputItemAdded { item } => Self::OutputItemAdded(item),
+            RecordedEvent::ServerModel { model } => Self::ServerModel(model),
+            RecordedEvent::ServerReasoningIncluded { included } => {
+                Self::ServerReasoningIncluded(included)
+            }
+            RecordedEvent::Completed {
+                response_id,
+                token_usage,
+            } => Self::Completed {
+                response_id,
+                token_usage,
+            },
+            RecordedEvent::OutputTextDelta { delta } => Self::OutputTextDelta(delta),
+            RecordedEvent::ReasoningSummaryDelta {
+                delta,
+                summary_index,
+            } => Self::ReasoningSummaryDelta {
+                delta,
+                summary_index,
+            },
+            RecordedEvent::ReasoningContentDelta {
+                delta,
+                content_index,
+            } => Self::ReasoningContentDelta {
+                delta,
+                content_index,
+            },
+            RecordedEvent::ReasoningSummaryPartAdded { summary_index } => {
+                Self::ReasoningSummaryPartAdded { summary_index }
+            }
+            RecordedEvent::RateLimits { snapshot } => Self::RateLimits(snapshot),
+            RecordedEvent::ModelsEtag { etag } => Self::ModelsEtag(etag),
+        }
+    }
+}
+
+/// One model request and the events the provider streamed back for it.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RecordedExchange {
+    pub(crate) input: Vec<ResponseItem>,
+    pub(crate) events: Vec<RecordedEvent>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Recording {
+    pub(crate) exchanges: Vec<RecordedExchange>,
+}
+
+impl Recording {
+    pub(crate) fn load(path: &Path) -> std::io::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        serde_json::from_str(&contents).map_err(std::io::Error::other)
+    }
+}
+
+/// Live view of a recording in progress. Clones share the same recording.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RecordingHandle {
+    recording: Arc<Mutex<Recording>>,
+}
+
+impl RecordingHandle {
+    /// Copy of everything captured so far.
+    pub(crate) fn snapshot(&self) -> Recording {
+        self.lock().clone()
+    }
+
+    /// Writes the recording captured so far to `path` as pretty-printed JSON.
+    pub(crate) fn save(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(&self.snapshot()).map_err(std::io::Error::other)?;
+        std::fs::write(path, json)
+    }
+
+    /// Records `input` as a new exchange and returns a stream that forwards
+    /// `stream` while appending each successful event to that exchange.
+    pub(crate) fn tap(
+        &self,
+        input: Vec<ResponseItem>,
+        mut stream: ResponseStream,
+    ) -> ResponseStream {
+        let index = {
+            let mut recording = self.lock();
+            recording.exchanges.push(RecordedExchange {
+                input,
+                events: Vec::new(),
+            });
+            recording.exchanges.len() - 1
+        };
+        let (tx_event, rx_event) = mpsc::channel(1600);
+        let handle = self.clone();
+        tokio::spawn(async move {
+            while let Some(event) = stream.rx_event.recv().await {
+                if let Ok(event) = &event
+                    && let Some(exchange) = handle.lock().exchanges.get_mut(index)
+                {
+                    exchange.events.push(RecordedEvent::from(event));
+                }
+                if tx_event.send(event).await.is_err() {
+                    break;
+                }
+            }
+        });
+        ResponseStream { rx_event }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Recording> {
+        match self.recording.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// Serves recorded exchanges in order in place of provider requests.
+#[derive(Debug)]
+pub(crate) struct Playback {
+    exchanges: Mutex<VecDeque<RecordedExchange>>,
+}
+
+impl Playback {
+    pub(crate) fn new(recording: Recording) -> Self {
+        Self {
+            exchanges: Mutex::new(recording.exchanges.into()),
+        }
+    }
+
+    /// Stream for the next recorded exchange. Fails once the recording is
+    /// exhausted, since the session has diverged from what was recorded.
+    pub(crate) fn next_stream(&self) -> Result<ResponseStream> {
+        let exchange = match self.exchanges.lock() {
+            Ok(mut guard) => guard.pop_front(),
+            Err(poisoned) => poisoned.into_inner().pop_front(),
+        }
+        .ok_or_else(|| {
+            CodexErr::InvalidRequest(
+                "playback recording has no more recorded responses".to_string(),
+            )
+        })?;
+        let (tx_event, rx_event) = mpsc::channel(exchange.events.len().max(1));
+        for event in exchange.events {
+            // The channel is sized to hold every event, so this cannot fail.
+            let _ = tx_event.try_send(Ok(ResponseEvent::from(event)));
+        }
+        Ok(ResponseStream { rx_event })
+    }
+}
+
+/// How the session's model client treats outgoing requests.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SessionCapture {
+    /// Captures every stream, whether it came from the provider or from
+    /// `playback`.
+    pub(crate) recording: Option<RecordingHandle>,
+    /// Serves streams in place of the provider.
+    pub(crate) playback: Option<Arc<Playback>>,
+}
+
+impl SessionCapture {
+    /// `stream` as sent for `input`, recorded if a recording is in progress.
+    pub(crate) fn tap(&self, input: &[ResponseItem], stream: ResponseStream) -> ResponseStream {
+        match &self.recording {
+            Some(handle) => handle.tap(input.to_vec(), stream),
+            None => stream,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::ContentItem;
+    use futures::StreamExt;
+    use pretty_assertions::assert_eq;
+
+    fn assistant_message(text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "assistant".to_string(),
+            content: vec![ContentItem::OutputText {
+                text: text.to_string(),
+            }],
+            end_turn: None,
+            phase: None,
+        }
+    }
+
+    fn live_stream(events: Vec<ResponseEvent>) -> ResponseStream {
+        let (tx_event, rx_event) = mpsc::channel(events.len().max(1));
+        for event in events {
+            tx_event.try_send(Ok(event)).expect("channel has capacity");
+        }
+        ResponseStream { rx_event }
+    }
+
+    async fn collect(stream: ResponseStream) -> Vec<RecordedEvent> {
+        stream
+            .map(|event| RecordedEvent::from(&event.expect("stream event")))
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn playback_reproduces_recorded_events() {
+        let handle = RecordingHandle::default();
+        let live = live_stream(vec![
+            ResponseEvent::Created,
+            ResponseEvent::OutputTextDelta("hi".to_string()),
+            ResponseEvent::OutputItemDone(assistant_message("hi")),
+            ResponseEvent::Completed {
+                response_id: "resp-1".to_string(),
+                token_usage: None,
+            },
+        ]);
+        let recorded = collect(handle.tap(vec![assistant_message("input")], live)).await;
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("recording.json");
+        handle.save(&path).expect("save recording");
+        let playback = Playback::new(Recording::load(&path).expect("load recording"));
+
+        let replayed = collect(playback.next_stream().expect("recorded exchange")).await;
+
+        assert_eq!(replayed, recorded);
+        assert_eq!(
+            handle.snapshot().exchanges[0].input,
+            vec![assistant_message("input")]
+        );
+        assert!(playback.next_stream().is_err());
+    }
+}
@@ -0,0 +1,349 @@
+//! Read-only sharing of a session's event feed over WebSocket.
+//!
+//! `Op::StartSessionShare` binds a local listener and returns its URL and a
+//! random token. Observers connect with the token, either as a
+//! `Authorization: Bearer` header or a `token` query parameter, and receive
+//! every event the session emits as a text frame `{"seq": n, "event": ...}`.
+//! An `offset` query parameter replays retained events from that sequence
+//! number first, so an observer that reconnects can resume where it left off.
+//! Anything observers send is ignored.
+
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::SessionShareStartedEvent;
+use futures::SinkExt;
+use futures::StreamExt;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::sync::watch;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::handshake::server::ErrorResponse;
+use tokio_tungstenite::tungstenite::handshake::server::Request;
+use tokio_tungstenite::tungstenite::handshake::server::Response;
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+use tracing::warn;
+
+/// Events kept for replay. Observers asking for an older offset start at the
+/// oldest retained event, which they can tell from its `seq`.
+const MAX_RETAINED_EVENTS: usize = 10_000;
+const DEFAULT_BIND_ADDRESS: &str = "127.0.0.1:0";
+
+/// The session's share server, if one is running.
+#[derive(Default)]
+pub(crate) struct SessionShare {
+    server: Mutex<Option<ShareServer>>,
+}
+
+struct ShareServer {
+    url: String,
+    token: String,
+    log: Arc<ShareLog>,
+    shutdown: CancellationToken,
+}
+
+impl Drop for ShareServer {
+    fn drop(&mut self) {
+        self.shutdown.cancel();
+    }
+}
+
+impl SessionShare {
+    /// Starts sharing on `bind_address` (a local ephemeral port by default).
+    /// When sharing already, returns the running server's URL and token.
+    pub(crate) async fn start(
+        &self,
+        bind_address: Option<&str>,
+    ) -> std::io::Result<SessionShareStartedEvent> {
+        if let Some(started) = self.started() {
+            return Ok(started);
+        }
+        let listener = TcpListener::bind(bind_address.unwrap_or(DEFAULT_BIND_ADDRESS)).await?;
+        let addr = listener.local_addr()?;
+        let server = ShareServer {
+            url: format!("ws://{addr}/"),
+            token: uuid::Uuid::new_v4().simple().to_string(),
+            log: Arc::new(ShareLog::new()),
+            shutdown: CancellationToken::new(),
+        };
+        tokio::spawn(accept_observers(
+            listener,
+            Arc::clone(&server.log),
+            server.token.clone(),
+            server.shutdown.clone(),
+        ));
+        let mut slot = self.server.lock().unwrap_or_else(PoisonError::into_inner);
+        // Another start may have won the race while this one was binding.
+        let server = slot.get_or_insert(server);
+        Ok(SessionShareStartedEvent {
+            url: server.url.clone(),
+            token: server.token.clone(),
+        })
+    }
+
+    /// Stops sharing and disconnects every observer.
+    pub(crate) fn stop(&self) {
+        self.server
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+    }
+
+    fn started(&self) -> Option<SessionShareStartedEvent> {
+        let slot = self.server.lock().unwrap_or_else(PoisonError::into_inner);
+        slot.as_ref().map(|server| SessionShareStartedEvent {
+            url: server.url.clone(),
+            token: server.token.clone(),
+        })
+    }
+
+    /// Forwards `event` to observers. Does nothing unless sharing.
+    pub(crate) fn publish(&self, event: &Event) {
+        let log = {
+            let slot = self.server.lock().unwrap_or_else(PoisonError::into_inner);
+            match slot.as_ref() {
+                Some(server) => Arc::clone(&server.log),
+                None => return,
+            }
+        };
+        log.push(event);
+    }
+}
+
+/// Serialized events with their sequence numbers, oldest first.
+struct ShareLog {
+    entries: Mutex<VecDeque<(u64, Arc<str>)>>,
+    /// Sequence number the next event will get.
+    next_seq: watch::Sender<u64>,
+}
+
+impl ShareLog {
+    fn new() -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::new()),
+            next_seq: watch::Sender::new(0),
+        }
+    }
+
+    fn push(&self, event: &Event) {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let seq = *self.next_seq.borrow();
+        let frame = match serde_json::to_string(&serde_json::json!({
+            "seq": seq,
+            "event": event,
+        })) {
+            Ok(frame) => frame,
+            Err(err) => {
+                warn!("failed to serialize shared event: {err}");
+                return;
+            }
+        };
+        entries.push_back((seq, Arc::from(frame)));
+        if entries.len() > MAX_RETAINED_EVENTS {
+            entries.pop_front();
+        }
+        self.next_seq.send_replace(seq + 1);
+    }
+
+    fn since(&self, seq: u64) -> Vec<(u64, Arc<str>)> {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries
+            .iter()
+            .filter(|(entry_seq, _)| *entry_seq >= seq)
+            .cloned()
+            .collect()
+    }
+}
+
+async fn accept_observers(
+    listener: TcpListener,
+    log: Arc<ShareLog>,
+    token: String,
+    shutdown: CancellationToken,
+) {
+    loop {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = shutdown.cancelled() => return,
+        };
+        match accepted {
+            Ok((stream, peer_addr)) => {
+                tokio::spawn(serve_observer(
+                    stream,
+                    peer_addr,
+                    Arc::clone(&log),
+                    token.clone(),
+                    shutdown.clone(),
+                ));
+            }
+            Err(err) => warn!("failed to accept session share connection: {err}"),
+        }
+    }
+}
+
+async fn serve_observer(
+    stream: TcpStream,
+    peer_addr: SocketAddr,
+    log: Arc<ShareLog>,
+    token: String,
+    shutdown: CancellationToken,
+) {
+    let mut next_seq = 0;
+    let callback = |request: &Request, response: Response| {
+        let query = parse_query(request.uri().query());
+        let bearer = request
+            .headers()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        let presented = bearer.or(query.token.as_deref());
+        if presented != Some(token.as_str()) {
+            let mut error = ErrorResponse::new(Some("invalid session share token".to_string()));
+            *error.status_mut() = StatusCode::UNAUTHORIZED;
+            return Err(error);
+        }
+        next_seq = query.offset.unwrap_or(0);
+        Ok(response)
+    };
+    let websocket = match tokio_tungstenite::accept_hdr_async(stream, callback).await {
+        Ok(websocket) => websocket,
+        Err(err) => {
+            debug!(%peer_addr, "rejected session share connection: {err}");
+            return;
+        }
+    };
+    debug!(%peer_addr, "session share observer connected");
+
+    let (mut writer, mut reader) = websocket.split();
+    let mut latest = log.next_seq.subscribe();
+    loop {
+        latest.mark_unchanged();
+        for (seq, frame) in log.since(next_seq) {
+            if writer
+                .send(Message::Text(frame.to_string().into()))
+                .await
+                .is_err()
+            {
+                return;
+            }
+            next_seq = seq + 1;
+        }
+        tokio::select! {
+            changed = latest.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+            }
+            incoming = reader.next() => match incoming {
+                // Observers are read-only; only a close ends the feed.
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+            _ = shutdown.cancelled() => break,
+        }
+    }
+    let _ = writer.send(Message::Close(None)).await;
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ShareQuery {
+    token: Option<String>,
+    offset: Option<u64>,
+}
+
+fn parse_query(query: Option<&str>) -> ShareQuery {
+    let mut parsed = ShareQuery::default();
+    for (key, value) in url::form_urlencoded::parse(query.unwrap_or_default().as_bytes()) {
+        match key.as_ref() {
+            "token" => parsed.token = Some(value.into_owned()),
+            "offset" => parsed.offset = value.parse().ok(),
+            _ => {}
+        }
+    }
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::protocol::EventMsg;
+    use codex_protocol::protocol::WarningEvent;
+    use pretty_assertions::assert_eq;
+    use serde_json::Value;
+
+    fn warning(message: &str) -> Event {
+        Event {
+            id: "sub".to_string(),
+            msg: EventMsg::Warning(WarningEvent {
+                message: message.to_string(),
+            }),
+        }
+    }
+
+    #[test]
+    fn parses_token_and_offset() {
+        assert_eq!(
+            parse_query(Some("token=abc&offset=12&other=1")),
+            ShareQuery {
+                token: Some("abc".to_string()),
+                offset: Some(12),
+            }
+        );
+        assert_eq!(parse_query(None), ShareQuery::default());
+    }
+
+    #[tokio::test]
+    async fn observers_replay_from_offset_then_follow_live_events() -> anyhow::Result<()> {
+        let share = SessionShare::default();
+        share.publish(&warning("before sharing"));
+        let started = share.start(None).await?;
+        assert_eq!(share.start(None).await?, started);
+        share.publish(&warning("zero"));
+        share.publish(&warning("one"));
+
+        let rejected =
+            tokio_tungstenite::connect_async(format!("{}?token=wrong", started.url)).await;
+        assert!(rejected.is_err());
+
+        let (mut observer, _) = tokio_tungstenite::connect_async(format!(
+            "{}?token={}&offset=1",
+            started.url, started.token
+        ))
+        .await?;
+        share.publish(&warning("two"));
+
+        let mut received = Vec::new();
+        while received.len() < 2 {
+            let Some(message) = observer.next().await.transpose()? else {
+                break;
+            };
+            let Message::Text(frame) = message else {
+                continue;
+            };
+            let frame: Value = serde_json::from_str(&frame)?;
+            received.push((
+                frame["seq"].as_u64(),
+                frame["event"]["msg"]["message"]
+                    .as_str()
+                    .map(str::to_string),
+            ));
+        }
+        assert_eq!(
+            received,
+            vec![
+                (Some(1), Some("one".to_string())),
+                (Some(2), Some("two".to_string())),
+            ]
+        );
+
+        share.stop();
+        share.publish(&warning("after sharing"));
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+//! Per-session git worktrees for `features.worktree_isolation`.
+//!
+//! With the feature enabled, a session started inside a git repository works
+//! in a linked worktree under `$CODEX_HOME/worktrees/<thread id>`, checked out
+//! at a snapshot of the live checkout that includes uncommitted changes. The
+//! agent's edits stay in that worktree until `codex merge` applies them back.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_git::ApplyGitRequest;
+use codex_git::ApplyGitResult;
+use codex_git::CreateGhostCommitOptions;
+use codex_git::add_detached_worktree;
+use codex_git::apply_git_patch;
+use codex_git::create_ghost_commit;
+use codex_git::diff_commits;
+use codex_git::remove_worktree;
+use codex_protocol::ThreadId;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::git_info::get_git_repo_root;
+
+const WORKTREES_DIR: &str = "worktrees";
+
+/// A session's worktree, recorded next to it as `<thread id>.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionWorktree {
+    pub thread_id: ThreadId,
+    /// Root of the checkout the session was started from.
+    pub repo_root: PathBuf,
+    pub worktree: PathBuf,
+    /// Commit the worktree's changes are diffed against: the snapshot it was
+    /// created from, then the worktree state at the last merge.
+    pub base_commit: String,
+}
+
+impl SessionWorktree {
+    /// Loads the worktree recorded for `thread_id`, if there is one.
+    pub fn load(codex_home: &Path, thread_id: ThreadId) -> io::Result<Option<Self>> {
+        match std::fs::read_to_string(metadata_path(codex_home, thread_id)) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map(Some)
+                .map_err(io::Error::other),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn save(&self, codex_home: &Path) -> io::Result<()> {
+        let path = metadata_path(codex_home, self.thread_id);
+        let contents = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        std::fs::write(path, contents)
+    }
+}
+
+fn metadata_path(codex_home: &Path, thread_id: ThreadId) -> PathBuf {
+    codex_home
+        .join(WORKTREES_DIR)
+        .join(format!("{thread_id}.json"))
+}
+
+/// Returns the directory a session started in `cwd` should work in: the
+/// matching directory of its worktree, created on first use. Returns `None`
+/// when `cwd` is not inside a git repository.
+pub(crate) async fn isolate_session_cwd(
+    codex_home: &Path,
+    thread_id: ThreadId,
+    cwd: &Path,
+) -> anyhow::Result<Option<PathBuf>> {
+    let codex_home = codex_home.to_path_buf();
+    let cwd = cwd.to_path_buf();
+    tokio::task::spawn_blocking(move || isolate_session_cwd_blocking(&codex_home, thread_id, &cwd))
+        .await?
+}
+
+fn isolate_session_cwd_blocking(
+    codex_home: &Path,
+    thread_id: ThreadId,
+    cwd: &Path,
+) -> anyhow::Result<Option<PathBuf>> {
+    let Some(repo_root) = get_git_repo_root(cwd) else {
+        return Ok(None);
+    };
+    let worktree = match SessionWorktree::load(codex_home, thread_id)? {
+        Some(existing) if existing.worktree.is_dir() => existing,
+        _ => {
+            let worktree = codex_home.join(WORKTREES_DIR).join(thread_id.to_string());
+            std::fs::create_dir_all(codex_home.join(WORKTREES_DIR))?;
+            let snapshot = create_ghost_commit(&CreateGhostCommitOptions::new(&repo_root))?;
+            add_detached_worktree(&repo_root, &worktree, snapshot.id())?;
+            let session_worktree = SessionWorktree {
+                thread_id,
+                repo_root: repo_root.clone(),
+                worktree,
+                base_commit: snapshot.id().to_string(),
+            };
+            session_worktree.save(codex_home)?;
+            session_worktree
+        }
+    };
+    let relative_cwd = cwd.strip_prefix(&repo_root).unwrap_or(Path::new(""));
+    Ok(Some(worktree.worktree.join(relative_cwd)))
+}
+
+/// Applies the changes made in `worktree` since its base commit to the live
+/// checkout with `git apply --3way`. Returns `None` when there is nothing to
+/// merge. On success the base commit moves forward, so the next merge only
+/// carries later changes.
+pub fn merge_session_worktree(
+    codex_home: &Path,
+    worktree: &mut SessionWorktree,
+) -> anyhow::Result<Option<ApplyGitResult>> {
+    let snapshot = create_ghost_commit(&CreateGhostCommitOptions::new(&worktree.worktree))?;
+    let diff = diff_commits(&worktree.repo_root, &worktree.base_commit, snapshot.id())?;
+    if diff.is_empty() {
+        return Ok(None);
+    }
+    let result = apply_git_patch(&ApplyGitRequest {
+        cwd: worktree.repo_root.clone(),
+        diff,
+        revert: false,
+        preflight: false,
+    })?;
+    if result.exit_code == 0 {
+        worktree.base_commit = snapshot.id().to_string();
+        worktree.save(codex_home)?;
+    }
+    Ok(Some(result))
+}
+
+/// Deletes the worktree and its record, discarding unmerged changes.
+pub fn remove_session_worktree(
+    codex_home: &Path,
+    worktree: &SessionWorktree,
+) -> anyhow::Result<()> {
+    if worktree.worktree.is_dir() {
+        remove_worktree(&worktree.repo_root, &worktree.worktree)?;
+    }
+    std::fs::remove_file(metadata_path(codex_home, worktree.thread_id))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn git(repo: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(repo)
+            .args(args)
+            .status()
+            .expect("git command");
+        assert!(status.success(), "git command failed: {args:?}");
+    }
+
+    #[tokio::test]
+    async fn edits_stay_in_the_worktree_until_merged() -> anyhow::Result<()> {
+        let codex_home = TempDir::new()?;
+        let repo = TempDir::new()?;
+        git(repo.path(), &["init", "--initial-branch=main"]);
+        std::fs::create_dir(repo.path().join("src"))?;
+        std::fs::write(repo.path().join("src/lib.rs"), "fn a() {}\n")?;
+        git(repo.path(), &["add", "."]);
+        git(
+            repo.path(),
+            &[
+                "-c",
+                "user.name=Tester",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-m",
+                "init",
+            ],
+        );
+        std::fs::write(repo.path().join("notes.txt"), "uncommitted\n")?;
+        let thread_id = ThreadId::new();
+
+        let cwd = isolate_session_cwd(codex_home.path(), thread_id, &repo.path().join("src"))
+            .await?
+            .expect("repo has a worktree");
+        let mut worktree = SessionWorktree::load(codex_home.path(), thread_id)?.expect("recorded");
+        assert_eq!(cwd, worktree.worktree.join("src"));
+        assert_eq!(
+            std::fs::read_to_string(worktree.worktree.join("notes.txt"))?,
+            "uncommitted\n"
+        );
+
+        std::fs::write(cwd.join("lib.rs"), "fn b() {}\n")?;
+        assert_eq!(
+            std::fs::read_to_string(repo.path().join("src/lib.rs"))?,
+            "fn a() {}\n"
+        );
+
+        let merged = merge_session_worktree(codex_home.path(), &mut worktree)?.expect("changes");
+        assert_eq!(merged.exit_code, 0, "{}", merged.stderr);
+        assert_eq!(
+            std::fs::read_to_string(repo.path().join("src/lib.rs"))?,
+            "fn b() {}\n"
+        );
+        assert!(merge_session_worktree(codex_home.path(), &mut worktree)?.is_none());
+
+        remove_session_worktree(codex_home.path(), &worktree)?;
+        assert_eq!(SessionWorktree::load(codex_home.path(), thread_id)?, None);
+
+        Ok(())
+    }
+}
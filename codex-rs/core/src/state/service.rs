@@ -5,17 +5,27 @@ use crate::AuthManager;
 use crate::RolloutRecorder;
 use crate::agent::AgentControl;
 use crate::analytics_client::AnalyticsEventsClient;
+use crate::audit::AuditLog;
 use crate::client::ModelClient;
 use crate::config::StartedNetworkProxy;
 use crate::exec_policy::ExecPolicyManager;
 use crate::file_watcher::FileWatcher;
 use crate::mcp::McpManager;
 use crate::mcp_connection_manager::McpConnectionManager;
+use crate::mcp_supervisor::McpSupervisor;
 use crate::models_manager::manager::ModelsManager;
 use crate::plugins::PluginsManager;
+use crate::session_share::SessionShare;
 use crate::skills::SkillsManager;
 use crate::state_db::StateDbHandle;
+use crate::tasks::TurnOutcomes;
+use crate::tools::call_dedupe::CallIdDedupe;
+use crate::tools::call_events::ToolCallSubscribers;
+use crate::tools::chaos::Chaos;
+use crate::tools::in_flight::ActiveToolCalls;
+use crate::tools::middleware::SessionToolMiddleware;
 use crate::tools::network_approval::NetworkApprovalService;
+use crate::tools::router::CallArchive;
 use crate::tools::runtimes::ExecveSessionApproval;
 use crate::tools::sandboxing::ApprovalStore;
 use crate::unified_exec::UnifiedExecProcessManager;
@@ -31,6 +41,7 @@ use tokio_util::sync::CancellationToken;
 pub(crate) struct SessionServices {
     pub(crate) mcp_connection_manager: Arc<RwLock<McpConnectionManager>>,
     pub(crate) mcp_startup_cancellation_token: Mutex<CancellationToken>,
+    pub(crate) mcp_supervisor: McpSupervisor,
     pub(crate) unified_exec_manager: UnifiedExecProcessManager,
     #[cfg_attr(not(unix), allow(dead_code))]
     pub(crate) shell_zsh_path: Option<PathBuf>,
@@ -59,4 +70,32 @@ pub(crate) struct SessionServices {
     pub(crate) state_db: Option<StateDbHandle>,
     /// Session-scoped model client shared across turns.
     pub(crate) model_client: ModelClient,
+    /// Tool calls currently being dispatched, across all turns.
+    pub(crate) active_tool_calls: ActiveToolCalls,
+    /// Call ids running or recently completed in this session. Shared by the
+    /// router of every sampling request, so a repeated call id does not run
+    /// its tool again in a later request.
+    pub(crate) tool_call_ids: CallIdDedupe,
+    /// The session's most recently dispatched tool calls, recorded by the
+    /// router of every sampling request. See [`Session::get_call_by_id`].
+    ///
+    /// [`Session::get_call_by_id`]: crate::codex::Session::get_call_by_id
+    pub(crate) tool_call_archive: CallArchive,
+    /// Middleware embedders registered for every tool call in this session,
+    /// applied to the router of each sampling request.
+    pub(crate) tool_middleware: SessionToolMiddleware,
+    /// Faults from `tool_chaos`, drawn from one RNG across the session so a
+    /// seed reproduces the whole session's faults.
+    pub(crate) tool_chaos: Option<Arc<Chaos>>,
+    /// Receivers of [`Session::subscribe_to_tool_calls`] streams.
+    ///
+    /// [`Session::subscribe_to_tool_calls`]: crate::codex::Session::subscribe_to_tool_calls
+    pub(crate) tool_call_subscribers: ToolCallSubscribers,
+    /// How finished turns ended, for tasks that depend on earlier turns.
+    pub(crate) turn_outcomes: TurnOutcomes,
+    /// Read-only WebSocket feed of this session's events, when shared.
+    pub(crate) session_share: SessionShare,
+    /// Signed log of commands, sandbox decisions and patches, when `[audit]`
+    /// is enabled.
+    pub(crate) audit_log: Option<AuditLog>,
 }
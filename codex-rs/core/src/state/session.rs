@@ -1,19 +1,31 @@
 //! Session-wide mutable state.
 
 use codex_protocol::models::ResponseItem;
+use indexmap::IndexMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::sync::Arc;
 use tokio::task::JoinHandle;
 
 use crate::codex::PreviousTurnSettings;
 use crate::codex::SessionConfiguration;
+use crate::compact::PinError;
 use crate::context_manager::ContextManager;
 use crate::error::Result as CodexResult;
+use crate::image_attachment::ImageAttachment;
+use crate::image_attachment::ImageHandle;
+use crate::models_manager::pricing::CostTracker;
 use crate::protocol::RateLimitSnapshot;
 use crate::protocol::TokenUsage;
 use crate::protocol::TokenUsageInfo;
 use crate::tasks::RegularTask;
+use crate::token_budget::TokenBudgetTracker;
+use crate::tools::output_cache::ToolOutputCache;
+use crate::tools::output_transform::ToolOutputTransformer;
+use crate::tools::router::TurnToolOverrides;
+use crate::tools::usage_summary::ToolUsageKind;
 use crate::truncate::TruncationPolicy;
+use crate::watched_files::WatchedFiles;
 use codex_protocol::protocol::TurnContextItem;
 
 /// Persistent, session-scoped state previously stored directly on `Session`.
@@ -32,12 +44,40 @@ pub(crate) struct SessionState {
     pub(crate) startup_regular_task: Option<JoinHandle<CodexResult<RegularTask>>>,
     pub(crate) active_mcp_tool_selection: Option<Vec<String>>,
     pub(crate) active_connector_selection: HashSet<String>,
+    /// Tool overrides for the next regular turn only.
+    next_turn_tool_overrides: Option<TurnToolOverrides>,
+    /// Tool usage per turn id, in call order.
+    tool_usage_by_turn: HashMap<String, Vec<ToolUsageKind>>,
+    /// Applied to every tool result, in registration order.
+    tool_output_transformers: Vec<Arc<dyn ToolOutputTransformer>>,
+    /// Consulted before dispatching read-only tool calls.
+    tool_output_cache: Option<Arc<dyn ToolOutputCache>>,
+    image_attachments: HashMap<ImageHandle, ImageAttachment>,
+    next_image_handle: u64,
+    /// Turn id and history index of each turn's first recorded item, oldest
+    /// first. Cleared whenever the history is replaced.
+    turn_starts: Vec<(String, usize)>,
+    /// Items of each pinned turn as they were when it was pinned.
+    pinned_turns: IndexMap<String, Vec<ResponseItem>>,
+    /// Items pushed with `pinned` set, in the order they were pushed.
+    pinned_context_items: Vec<ResponseItem>,
+    /// BCP-47 tag of the language compaction summaries are written in.
+    preferred_language: Option<String>,
+    /// Spend against the `[token_budget]` limits, which
+    /// `Op::SetTokenBudget` can replace.
+    pub(crate) token_budget: TokenBudgetTracker,
+    /// Estimated spend per provider and model.
+    pub(crate) cost_tracker: CostTracker,
+    /// Files registered with the `watch_files` tool. Scans block, so this is
+    /// locked from `spawn_blocking` rather than under the state lock.
+    watched_files: Arc<std::sync::Mutex<WatchedFiles>>,
 }
 
 impl SessionState {
     /// Create a new session state mirroring previous `State::default()` semantics.
     pub(crate) fn new(session_configuration: SessionConfiguration) -> Self {
         let history = ContextManager::new();
+        let token_budget = TokenBudgetTracker::new(session_configuration.token_budget());
         Self {
             session_configuration,
             history,
@@ -49,6 +89,19 @@ impl SessionState {
             startup_regular_task: None,
             active_mcp_tool_selection: None,
             active_connector_selection: HashSet::new(),
+            next_turn_tool_overrides: None,
+            tool_usage_by_turn: HashMap::new(),
+            tool_output_transformers: Vec::new(),
+            tool_output_cache: None,
+            image_attachments: HashMap::new(),
+            next_image_handle: 1,
+            turn_starts: Vec::new(),
+            pinned_turns: IndexMap::new(),
+            pinned_context_items: Vec::new(),
+            preferred_language: None,
+            token_budget,
+            cost_tracker: CostTracker::default(),
+            watched_files: Arc::default(),
         }
     }
 
@@ -61,6 +114,77 @@ impl SessionState {
         self.history.record_items(items, policy);
     }
 
+    /// Records `items` as part of turn `turn_id`.
+    pub(crate) fn record_turn_items<I>(&mut self, turn_id: &str, items: I, policy: TruncationPolicy)
+    where
+        I: IntoIterator,
+        I::Item: std::ops::Deref<Target = ResponseItem>,
+    {
+        if self
+            .turn_starts
+            .last()
+            .is_none_or(|(last_turn_id, _)| last_turn_id != turn_id)
+        {
+            let start = self.history.raw_items().len();
+            self.turn_starts.push((turn_id.to_string(), start));
+        }
+        self.record_items(items, policy);
+    }
+
+    /// Keeps the current items of `turn_id` through every later compaction.
+    pub(crate) fn pin_turn(&mut self, turn_id: &str) -> Result<(), PinError> {
+        if self.pinned_turns.contains_key(turn_id) {
+            return Ok(());
+        }
+        let items = self.turn_items(turn_id);
+        if items.is_empty() {
+            return Err(PinError::UnknownTurn(turn_id.to_string()));
+        }
+        self.pinned_turns.insert(turn_id.to_string(), items);
+        Ok(())
+    }
+
+    pub(crate) fn unpin_turn(&mut self, turn_id: &str) {
+        self.pinned_turns.shift_remove(turn_id);
+    }
+
+    pub(crate) fn pin_context_item(&mut self, item: ResponseItem) {
+        self.pinned_context_items.push(item);
+    }
+
+    /// Items of every pinned turn, in the order the turns were pinned,
+    /// followed by each pinned context item as a turn of its own.
+    pub(crate) fn pinned_turn_items(&self) -> Vec<Vec<ResponseItem>> {
+        self.pinned_turns
+            .values()
+            .cloned()
+            .chain(
+                self.pinned_context_items
+                    .iter()
+                    .map(|item| vec![item.clone()]),
+            )
+            .collect()
+    }
+
+    fn turn_items(&self, turn_id: &str) -> Vec<ResponseItem> {
+        let items = self.history.raw_items();
+        let Some(index) = self
+            .turn_starts
+            .iter()
+            .position(|(start_turn_id, _)| start_turn_id == turn_id)
+        else {
+            return Vec::new();
+        };
+        let start = self.turn_starts[index].1.min(items.len());
+        let end = self
+            .turn_starts
+            .get(index + 1)
+            .map_or(items.len(), |(_, next_start)| {
+                (*next_start).min(items.len())
+            });
+        items[start..end.max(start)].to_vec()
+    }
+
     pub(crate) fn previous_turn_settings(&self) -> Option<PreviousTurnSettings> {
         self.previous_turn_settings.clone()
     }
@@ -83,6 +207,7 @@ impl SessionState {
         self.history.replace(items);
         self.history
             .set_reference_context_item(reference_context_item);
+        self.turn_starts.clear();
     }
 
     pub(crate) fn set_token_info(&mut self, info: Option<TokenUsageInfo>) {
@@ -236,6 +361,74 @@ impl SessionState {
     pub(crate) fn clear_connector_selection(&mut self) {
         self.active_connector_selection.clear();
     }
+
+    /// Applies `overrides` to the next regular turn, replacing any not yet
+    /// applied.
+    pub(crate) fn set_next_turn_tool_overrides(&mut self, overrides: TurnToolOverrides) {
+        self.next_turn_tool_overrides = Some(overrides);
+    }
+
+    pub(crate) fn take_next_turn_tool_overrides(&mut self) -> TurnToolOverrides {
+        self.next_turn_tool_overrides.take().unwrap_or_default()
+    }
+
+    // Records a tool call made during `turn_id`.
+    pub(crate) fn record_tool_usage(&mut self, turn_id: &str, kind: ToolUsageKind) {
+        self.tool_usage_by_turn
+            .entry(turn_id.to_string())
+            .or_default()
+            .push(kind);
+    }
+
+    // Returns the tool calls recorded for `turn_id`, in call order.
+    pub(crate) fn tool_usage(&self, turn_id: &str) -> &[ToolUsageKind] {
+        self.tool_usage_by_turn
+            .get(turn_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn push_tool_output_transformer(
+        &mut self,
+        transformer: Arc<dyn ToolOutputTransformer>,
+    ) {
+        self.tool_output_transformers.push(transformer);
+    }
+
+    pub(crate) fn tool_output_transformers(&self) -> Vec<Arc<dyn ToolOutputTransformer>> {
+        self.tool_output_transformers.clone()
+    }
+
+    pub(crate) fn set_tool_output_cache(&mut self, cache: Arc<dyn ToolOutputCache>) {
+        self.tool_output_cache = Some(cache);
+    }
+
+    pub(crate) fn tool_output_cache(&self) -> Option<Arc<dyn ToolOutputCache>> {
+        self.tool_output_cache.clone()
+    }
+
+    pub(crate) fn watched_files(&self) -> Arc<std::sync::Mutex<WatchedFiles>> {
+        Arc::clone(&self.watched_files)
+    }
+
+    pub(crate) fn set_preferred_language(&mut self, language: Option<String>) {
+        self.preferred_language = language;
+    }
+
+    pub(crate) fn preferred_language(&self) -> Option<String> {
+        self.preferred_language.clone()
+    }
+
+    pub(crate) fn attach_image(&mut self, image: ImageAttachment) -> ImageHandle {
+        let handle = ImageHandle(self.next_image_handle);
+        self.next_image_handle = self.next_image_handle.saturating_add(1);
+        self.image_attachments.insert(handle, image);
+        handle
+    }
+
+    pub(crate) fn image_attachment(&self, handle: ImageHandle) -> Option<&ImageAttachment> {
+        self.image_attachments.get(&handle)
+    }
 }
 
 // Sometimes new snapshots don't include credits or plan information.
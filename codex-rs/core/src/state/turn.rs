@@ -16,9 +16,11 @@ use rmcp::model::RequestId;
 use tokio::sync::oneshot;
 
 use crate::codex::TurnContext;
+use crate::protocol::PatchReviewResponse;
 use crate::protocol::ReviewDecision;
 use crate::protocol::TokenUsage;
 use crate::tasks::SessionTask;
+use crate::tools::context::SharedTurnDiffTracker;
 
 /// Metadata about the currently running turn.
 pub(crate) struct ActiveTurn {
@@ -76,9 +78,12 @@ pub(crate) struct TurnState {
     pending_user_input: HashMap<String, oneshot::Sender<RequestUserInputResponse>>,
     pending_elicitations: HashMap<(String, RequestId), oneshot::Sender<ElicitationResponse>>,
     pending_dynamic_tools: HashMap<String, oneshot::Sender<DynamicToolResponse>>,
+    pending_patch_reviews: HashMap<String, oneshot::Sender<PatchReviewResponse>>,
     pending_input: Vec<ResponseInputItem>,
     pub(crate) tool_calls: u64,
     pub(crate) token_usage_at_turn_start: TokenUsage,
+    /// The running turn's diff tracker, so its diff can be read mid-turn.
+    pub(crate) turn_diff_tracker: Option<SharedTurnDiffTracker>,
 }
 
 impl TurnState {
@@ -102,6 +107,7 @@ impl TurnState {
         self.pending_user_input.clear();
         self.pending_elicitations.clear();
         self.pending_dynamic_tools.clear();
+        self.pending_patch_reviews.clear();
         self.pending_input.clear();
     }
 
@@ -154,6 +160,21 @@ impl TurnState {
         self.pending_dynamic_tools.remove(key)
     }
 
+    pub(crate) fn insert_pending_patch_review(
+        &mut self,
+        key: String,
+        tx: oneshot::Sender<PatchReviewResponse>,
+    ) -> Option<oneshot::Sender<PatchReviewResponse>> {
+        self.pending_patch_reviews.insert(key, tx)
+    }
+
+    pub(crate) fn remove_pending_patch_review(
+        &mut self,
+        key: &str,
+    ) -> Option<oneshot::Sender<PatchReviewResponse>> {
+        self.pending_patch_reviews.remove(key)
+    }
+
     pub(crate) fn push_pending_input(&mut self, input: ResponseInputItem) {
         self.pending_input.push(input);
     }
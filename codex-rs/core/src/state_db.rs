@@ -398,6 +398,15 @@ pub async fn reconcile_rollout(
         );
         return;
     }
+    if let Err(err) = ctx
+        .replace_thread_search(metadata.id, &outcome.search_index)
+        .await
+    {
+        warn!(
+            "state db reconcile_rollout search index update failed {}: {err}",
+            rollout_path.display()
+        );
+    }
     if let Ok(meta_line) = crate::rollout::list::read_session_meta_line(rollout_path).await {
         persist_dynamic_tools(
             Some(ctx),
@@ -522,6 +531,44 @@ pub async fn apply_rollout_items(
     }
 }
 
+/// Add the searchable text in newly written rollout items to SQLite.
+pub async fn index_thread_search(
+    context: Option<&codex_state::StateRuntime>,
+    thread_id: Option<ThreadId>,
+    items: &[RolloutItem],
+    stage: &str,
+) {
+    let Some(ctx) = context else {
+        return;
+    };
+    let Some(thread_id) = thread_id else {
+        return;
+    };
+    let index = codex_state::thread_search_index(items);
+    if let Err(err) = ctx.index_thread_search(thread_id, &index).await {
+        warn!("state db index_thread_search failed during {stage} for {thread_id}: {err}");
+    }
+}
+
+/// Search recorded sessions by their messages, commands and touched files.
+///
+/// Sessions recorded before the index existed are indexed by the rollout
+/// backfill; when it has not finished yet, it is run here first.
+pub async fn search_threads(
+    config: &Config,
+    query: &codex_state::ThreadSearchQuery,
+) -> anyhow::Result<Vec<codex_state::ThreadSearchHit>> {
+    let runtime = codex_state::StateRuntime::init(
+        config.sqlite_home.clone(),
+        config.model_provider_id.clone(),
+    )
+    .await?;
+    if runtime.get_backfill_state().await?.status != codex_state::BackfillStatus::Complete {
+        metadata::backfill_sessions(runtime.as_ref(), config).await;
+    }
+    runtime.search_threads(query).await
+}
+
 pub async fn touch_thread_updated_at(
     context: Option<&codex_state::StateRuntime>,
     thread_id: Option<ThreadId>,
@@ -18,12 +18,16 @@ use crate::function_tool::FunctionCallError;
 use crate::memories::citations::get_thread_id_from_citations;
 use crate::parse_turn_item;
 use crate::state_db;
+use crate::tools::emulation::parse_emulated_tool_call;
 use crate::tools::parallel::ToolCallRuntime;
 use crate::tools::router::ToolRouter;
+use crate::tools::usage_summary::ToolUsageKind;
 use codex_protocol::models::FunctionCallOutputBody;
 use codex_protocol::models::FunctionCallOutputPayload;
 use codex_protocol::models::ResponseInputItem;
 use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::WarningEvent;
 use codex_utils_stream_parser::strip_proposed_plan_blocks;
 use futures::Future;
 use tracing::debug;
@@ -138,7 +142,7 @@ async fn record_stage1_output_usage_for_completed_item(
 /// queuing any tool execution futures. This records items immediately so
 /// history and rollout stay in sync even if the turn is later cancelled.
 pub(crate) type InFlightFuture<'f> =
-    Pin<Box<dyn Future<Output = Result<ResponseInputItem>> + Send + 'f>>;
+    Pin<Box<dyn Future<Output = Result<Option<ResponseInputItem>>> + Send + 'f>>;
 
 #[derive(Default)]
 pub(crate) struct OutputItemResult {
@@ -163,6 +167,24 @@ pub(crate) async fn handle_output_item_done(
     let mut output = OutputItemResult::default();
     let plan_mode = ctx.turn_context.collaboration_mode.mode == ModeKind::Plan;
 
+    // Without native tools the model asks for a tool by replying with JSON.
+    // Finish the streamed message as it was shown, then dispatch the call.
+    let item = if ctx.turn_context.model_capabilities().supports_tool_use {
+        item
+    } else if let Some(call) = parse_emulated_tool_call(&item) {
+        if previously_active_item.is_some()
+            && let Some(turn_item) =
+                handle_non_tool_response_item(&item, plan_mode, Some(&ctx.turn_context.cwd)).await
+        {
+            ctx.sess
+                .emit_turn_item_completed(&ctx.turn_context, turn_item)
+                .await;
+        }
+        call
+    } else {
+        item
+    };
+
     match ToolRouter::build_tool_call(ctx.sess.as_ref(), item.clone()).await {
         // The model emitted a tool call; log it, persist the item immediately, and queue the tool execution.
         Ok(Some(call)) => {
@@ -176,6 +198,9 @@ pub(crate) async fn handle_output_item_done(
 
             record_completed_response_item(ctx.sess.as_ref(), ctx.turn_context.as_ref(), &item)
                 .await;
+            ctx.sess
+                .record_tool_usage(&ctx.turn_context.sub_id, ToolUsageKind::from_call(&call))
+                .await;
 
             let cancellation_token = ctx.cancellation_token.child_token();
             let tool_future: InFlightFuture<'static> = Box::pin(
@@ -212,6 +237,11 @@ pub(crate) async fn handle_output_item_done(
 
             record_completed_response_item(ctx.sess.as_ref(), ctx.turn_context.as_ref(), &item)
                 .await;
+            if matches!(item, ResponseItem::WebSearchCall { .. }) {
+                ctx.sess
+                    .record_tool_usage(&ctx.turn_context.sub_id, ToolUsageKind::WebSearch)
+                    .await;
+            }
             let last_agent_message = last_assistant_message_from_item(&item, plan_mode);
 
             output.last_agent_message = last_agent_message;
@@ -245,7 +275,15 @@ pub(crate) async fn handle_output_item_done(
             output.needs_follow_up = true;
         }
         // The tool request should be answered directly (or was denied); push that response into the transcript.
-        Err(FunctionCallError::RespondToModel(message)) => {
+        Err(
+            err @ (FunctionCallError::RespondToModel(_)
+            | FunctionCallError::PayloadTooLarge { .. }
+            | FunctionCallError::TimedOut { .. }
+            | FunctionCallError::McpTransport(_)
+            | FunctionCallError::BudgetExhausted { .. }
+            | FunctionCallError::DuplicateCallId { .. }),
+        ) => {
+            let message = err.to_string();
             let response = ResponseInputItem::FunctionCallOutput {
                 call_id: String::new(),
                 output: FunctionCallOutputPayload {
@@ -266,6 +304,18 @@ pub(crate) async fn handle_output_item_done(
 
             output.needs_follow_up = true;
         }
+        // The call cannot be run, nor answered: the provider would not accept
+        // it back in its parsed form. Tell the user instead of dropping it.
+        Err(err @ FunctionCallError::UnsupportedToolCall { .. }) => {
+            let message = err.to_string();
+            tracing::error!("{message}");
+            ctx.sess
+                .send_event(
+                    &ctx.turn_context,
+                    EventMsg::Warning(WarningEvent { message }),
+                )
+                .await;
+        }
         // A fatal error occurred; surface it back into history.
         Err(FunctionCallError::Fatal(message)) => {
             return Err(CodexErr::Fatal(message));
@@ -365,6 +415,7 @@ pub(crate) fn response_input_to_response_item(input: &ResponseInputItem) -> Opti
                 Err(err) => FunctionCallOutputPayload {
                     body: FunctionCallOutputBody::Text(err.clone()),
                     success: Some(false),
+                    error_code: None,
                 },
             };
             Some(ResponseItem::FunctionCallOutput {
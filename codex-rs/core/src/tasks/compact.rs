@@ -2,15 +2,55 @@ use std::sync::Arc;
 
 use super::SessionTask;
 use super::SessionTaskContext;
+use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::compact::CompactContext;
+use crate::compact::CompactStrategy;
+use crate::error::CodexErr;
+use crate::protocol::CompactionFinishedEvent;
+use crate::protocol::CompactionStartedEvent;
+use crate::protocol::EventMsg;
+use crate::protocol::WarningEvent;
 use crate::state::TaskKind;
+use crate::truncate::approx_token_count;
 use async_trait::async_trait;
 use codex_protocol::user_input::UserInput;
 use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+/// Expected summary length as a fraction of the compacted input.
+pub(crate) const DEFAULT_SUMMARY_OUTPUT_RATIO: f64 = 0.1;
 
 #[derive(Clone, Copy, Default)]
 pub(crate) struct CompactTask;
 
+impl CompactTask {
+    /// Pre-flight estimate of the tokens a compaction would consume: the
+    /// history and summarization prompt sent as input, plus a summary sized at
+    /// [`DEFAULT_SUMMARY_OUTPUT_RATIO`] of that input.
+    pub(crate) async fn estimated_cost_tokens(session: &Session) -> u64 {
+        Self::estimated_cost_tokens_with_ratio(session, DEFAULT_SUMMARY_OUTPUT_RATIO).await
+    }
+
+    pub(crate) async fn estimated_cost_tokens_with_ratio(
+        session: &Session,
+        summary_output_ratio: f64,
+    ) -> u64 {
+        let base_instructions = session.get_base_instructions().await;
+        let history_tokens = session
+            .clone_history()
+            .await
+            .estimate_token_count_with_base_instructions(&base_instructions)
+            .unwrap_or(0);
+        let prompt_tokens = approx_token_count(&session.get_compact_prompt().await);
+        let input_tokens = u64::try_from(history_tokens)
+            .unwrap_or(0)
+            .saturating_add(u64::try_from(prompt_tokens).unwrap_or(u64::MAX));
+        let output_tokens = (input_tokens as f64 * summary_output_ratio.max(0.0)).ceil() as u64;
+        input_tokens.saturating_add(output_tokens)
+    }
+}
+
 #[async_trait]
 impl SessionTask for CompactTask {
     fn kind(&self) -> TaskKind {
@@ -26,24 +66,203 @@ impl SessionTask for CompactTask {
         session: Arc<SessionTaskContext>,
         ctx: Arc<TurnContext>,
         input: Vec<UserInput>,
-        _cancellation_token: CancellationToken,
+        cancellation_token: CancellationToken,
     ) -> Option<String> {
         let session = session.clone_session();
-        let _ = if crate::compact::should_use_remote_compact_task(&ctx.provider) {
-            let _ = session.services.session_telemetry.counter(
-                "codex.task.compact",
-                1,
-                &[("type", "remote")],
-            );
-            crate::compact_remote::run_remote_compact_task(session.clone(), ctx).await
-        } else {
-            let _ = session.services.session_telemetry.counter(
-                "codex.task.compact",
-                1,
-                &[("type", "local")],
-            );
-            crate::compact::run_compact_task(session.clone(), ctx, input).await
+        let compact = CompactContext::for_turn(&session, &ctx).await;
+        let requested_type = match compact.strategy {
+            CompactStrategy::Remote => "remote",
+            CompactStrategy::Local => "local",
+        };
+        session
+            .send_event(
+                &ctx,
+                EventMsg::CompactionStarted(CompactionStartedEvent {
+                    remote: compact.strategy == CompactStrategy::Remote,
+                }),
+            )
+            .await;
+        let tokens_before = session.clone_history().await.estimate_token_count(&ctx);
+        session.services.session_telemetry.histogram(
+            "codex.task.compact.estimated_cost_tokens",
+            i64::try_from(Self::estimated_cost_tokens(&session).await).unwrap_or(i64::MAX),
+            &[("type", requested_type)],
+        );
+        // Resolves to the compaction that ran, for the counter's `type`, and
+        // whether it succeeded.
+        let compaction = async {
+            match compact.strategy {
+                CompactStrategy::Remote => {
+                    let remote = crate::compact_remote::run_remote_compact_task(
+                        session.clone(),
+                        Arc::clone(&ctx),
+                        compact.clone(),
+                    )
+                    .await;
+                    match remote {
+                        Ok(()) => ("remote", true),
+                        Err(CodexErr::Interrupted) => ("remote", false),
+                        Err(err) => {
+                            warn!("remote compaction failed, compacting locally: {err}");
+                            session
+                                .send_event(
+                                    &ctx,
+                                    EventMsg::Warning(WarningEvent {
+                                        message: format!(
+                                            "Remote compaction failed, compacting locally instead: {err}"
+                                        ),
+                                    }),
+                                )
+                                .await;
+                            let local = crate::compact::run_started_compact_task(
+                                session.clone(),
+                                Arc::clone(&ctx),
+                                input,
+                                CompactContext {
+                                    strategy: CompactStrategy::Local,
+                                    ..compact
+                                },
+                            )
+                            .await;
+                            ("remote_fallback_local", local.is_ok())
+                        }
+                    }
+                }
+                CompactStrategy::Local => {
+                    let local = crate::compact::run_compact_task(
+                        session.clone(),
+                        Arc::clone(&ctx),
+                        input,
+                        compact,
+                    )
+                    .await;
+                    ("local", local.is_ok())
+                }
+            }
         };
+        // Dropping the compaction future aborts it before it can replace the
+        // history or report an error. Biased so that a cancellation racing
+        // with completion counts as a cancellation, and only once.
+        let finished = tokio::select! {
+            biased;
+            _ = cancellation_token.cancelled() => None,
+            finished = compaction => Some(finished),
+        };
+        let cancelled = finished.is_none();
+        let (compact_type, succeeded) = finished.unwrap_or((requested_type, false));
+        if succeeded {
+            let tokens_after = session.clone_history().await.estimate_token_count(&ctx);
+            session
+                .send_event(
+                    &ctx,
+                    EventMsg::CompactionFinished(CompactionFinishedEvent {
+                        tokens_before,
+                        tokens_after,
+                    }),
+                )
+                .await;
+        }
+        let outcome = if cancelled { "cancelled" } else { "completed" };
+        let _ = session.services.session_telemetry.counter(
+            "codex.task.compact",
+            1,
+            &[("type", compact_type), ("outcome", outcome)],
+        );
+        if cancelled {
+            session
+                .notify_background_event(&ctx, "Compaction cancelled.")
+                .await;
+        }
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codex::make_session_and_context;
+    use crate::codex::make_session_and_context_with_rx;
+    use codex_protocol::models::ContentItem;
+    use codex_protocol::models::ResponseItem;
+    use codex_protocol::protocol::EventMsg;
+    use pretty_assertions::assert_eq;
+
+    fn user_message(text: String) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText { text }],
+            end_turn: None,
+            phase: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn estimated_cost_tracks_history_size() {
+        let (session, turn_context) = make_session_and_context().await;
+        let messages: Vec<String> = (0..4)
+            .map(|i| format!("message {i} ").repeat(500))
+            .collect();
+        let items: Vec<ResponseItem> = messages.iter().cloned().map(user_message).collect();
+        session.record_into_history(&items, &turn_context).await;
+
+        let input_tokens = messages
+            .iter()
+            .chain([
+                &session.get_base_instructions().await.text,
+                &session.get_compact_prompt().await,
+            ])
+            .map(|text| approx_token_count(text))
+            .sum::<usize>() as f64;
+        let expected = input_tokens * (1.0 + DEFAULT_SUMMARY_OUTPUT_RATIO);
+
+        let estimate = CompactTask::estimated_cost_tokens(&session).await as f64;
+
+        assert!(
+            (estimate - expected).abs() <= expected * 0.2,
+            "estimate {estimate} is not within 20% of {expected}"
+        );
+    }
+
+    #[tokio::test]
+    async fn cancelled_compaction_reports_cancellation_without_error() {
+        let (session, turn_context, rx) = make_session_and_context_with_rx().await;
+        let history_before = session.clone_history().await.raw_items().to_vec();
+        let cancellation_token = CancellationToken::new();
+        cancellation_token.cancel();
+
+        let result = Arc::new(CompactTask)
+            .run(
+                Arc::new(SessionTaskContext::new(Arc::clone(&session))),
+                turn_context,
+                Vec::new(),
+                cancellation_token,
+            )
+            .await;
+
+        assert_eq!(result, None);
+        assert_eq!(
+            session.clone_history().await.raw_items().to_vec(),
+            history_before
+        );
+        let mut messages = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            match event.msg {
+                EventMsg::Error(error) => panic!("unexpected error event: {error:?}"),
+                EventMsg::BackgroundEvent(event) => messages.push(event.message),
+                _ => {}
+            }
+        }
+        assert_eq!(messages, vec!["Compaction cancelled.".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn summary_ratio_scales_output_estimate() {
+        let (session, _turn_context) = make_session_and_context().await;
+
+        let input_only = CompactTask::estimated_cost_tokens_with_ratio(&session, 0.0).await;
+        let doubled = CompactTask::estimated_cost_tokens_with_ratio(&session, 1.0).await;
+
+        assert_eq!(doubled, input_only * 2);
+    }
+}
@@ -0,0 +1,80 @@
+//! Tracks how finished turns ended so tasks can wait on earlier turns.
+
+use std::collections::HashMap;
+
+use tokio::sync::watch;
+
+/// Identifies a turn; this is the turn context's `sub_id`.
+pub(crate) type TurnId = String;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TurnOutcome {
+    Completed,
+    /// Interrupted or replaced before it finished.
+    Aborted,
+}
+
+#[derive(Debug)]
+pub(crate) struct TurnOutcomes {
+    outcomes: watch::Sender<HashMap<TurnId, TurnOutcome>>,
+}
+
+impl Default for TurnOutcomes {
+    fn default() -> Self {
+        Self {
+            outcomes: watch::Sender::new(HashMap::new()),
+        }
+    }
+}
+
+impl TurnOutcomes {
+    pub(crate) fn record(&self, turn_id: &str, outcome: TurnOutcome) {
+        self.outcomes.send_modify(|outcomes| {
+            outcomes.insert(turn_id.to_string(), outcome);
+        });
+    }
+
+    /// Resolves once every turn in `turn_ids` has completed, or as soon as
+    /// one of them is known to have been aborted, returning that turn's id.
+    pub(crate) async fn wait_for(&self, turn_ids: &[TurnId]) -> Result<(), TurnId> {
+        let mut receiver = self.outcomes.subscribe();
+        let mut failed = None;
+        // The sender lives as long as `self`, so the channel cannot close.
+        let _ = receiver
+            .wait_for(|outcomes| {
+                failed = turn_ids
+                    .iter()
+                    .find(|turn_id| outcomes.get(*turn_id) == Some(&TurnOutcome::Aborted))
+                    .cloned();
+                failed.is_some()
+                    || turn_ids
+                        .iter()
+                        .all(|turn_id| outcomes.contains_key(turn_id))
+            })
+            .await;
+        match failed {
+            Some(turn_id) => Err(turn_id),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn wait_for_fails_fast_on_aborted_dependency() {
+        let outcomes = TurnOutcomes::default();
+        outcomes.record("turn-1", TurnOutcome::Aborted);
+
+        assert_eq!(
+            outcomes
+                .wait_for(&["turn-1".to_string(), "turn-2".to_string()])
+                .await,
+            Err("turn-1".to_string())
+        );
+        assert_eq!(outcomes.wait_for(&[]).await, Ok(()));
+    }
+}
@@ -1,4 +1,5 @@
 mod compact;
+mod dependencies;
 mod ghost_snapshot;
 mod regular;
 mod review;
@@ -25,10 +26,13 @@ use crate::codex::TurnContext;
 use crate::contextual_user_message::TURN_ABORTED_OPEN_TAG;
 use crate::event_mapping::parse_turn_item;
 use crate::models_manager::manager::ModelsManager;
+use crate::protocol::CodexErrorInfo;
+use crate::protocol::ErrorEvent;
 use crate::protocol::EventMsg;
 use crate::protocol::TokenUsage;
 use crate::protocol::TurnAbortReason;
 use crate::protocol::TurnAbortedEvent;
+use crate::protocol::TurnCancelledEvent;
 use crate::protocol::TurnCompleteEvent;
 use crate::state::ActiveTurn;
 use crate::state::RunningTask;
@@ -42,6 +46,9 @@ use codex_protocol::user_input::UserInput;
 
 use crate::features::Feature;
 pub(crate) use compact::CompactTask;
+pub(crate) use dependencies::TurnId;
+pub(crate) use dependencies::TurnOutcome;
+pub(crate) use dependencies::TurnOutcomes;
 pub(crate) use ghost_snapshot::GhostSnapshotTask;
 pub(crate) use regular::RegularTask;
 pub(crate) use review::ReviewTask;
@@ -94,6 +101,13 @@ pub(crate) trait SessionTask: Send + Sync + 'static {
     /// Returns the tracing name for a spawned task span.
     fn span_name(&self) -> &'static str;
 
+    /// Turns whose output this task needs. The task does not start until
+    /// all of them have completed, and fails without running if any of them
+    /// was aborted. Defaults to none.
+    fn input_dependencies(&self) -> Vec<TurnId> {
+        Vec::new()
+    }
+
     /// Executes the task until completion or cancellation.
     ///
     /// Implementations typically stream protocol events using `session` and
@@ -166,15 +180,38 @@ impl Session {
             tokio::spawn(
                 async move {
                     let ctx_for_finish = Arc::clone(&ctx);
-                    let last_agent_message = task_for_run
-                        .run(
-                            Arc::clone(&session_ctx),
-                            ctx,
-                            input,
-                            task_cancellation_token.child_token(),
-                        )
-                        .await;
                     let sess = session_ctx.clone_session();
+                    let dependencies = task_for_run.input_dependencies();
+                    let dependencies_ready = select! {
+                        ready = sess.services.turn_outcomes.wait_for(&dependencies) => Some(ready),
+                        _ = task_cancellation_token.cancelled() => None,
+                    };
+                    let last_agent_message = match dependencies_ready {
+                        Some(Ok(())) => {
+                            task_for_run
+                                .run(
+                                    Arc::clone(&session_ctx),
+                                    ctx,
+                                    input,
+                                    task_cancellation_token.child_token(),
+                                )
+                                .await
+                        }
+                        Some(Err(failed_turn_id)) => {
+                            sess.send_event(
+                                ctx.as_ref(),
+                                EventMsg::Error(ErrorEvent {
+                                    message: format!(
+                                        "turn {failed_turn_id} was aborted; this task depends on its output"
+                                    ),
+                                    codex_error_info: Some(CodexErrorInfo::Other),
+                                }),
+                            )
+                            .await;
+                            None
+                        }
+                        None => None,
+                    };
                     sess.flush_rollout().await;
                     if !task_cancellation_token.is_cancelled() {
                         // Emit completion uniformly from spawn site so all tasks share the same lifecycle.
@@ -217,6 +254,9 @@ impl Session {
         turn_context
             .turn_metadata_state
             .cancel_git_enrichment_task();
+        self.services
+            .turn_outcomes
+            .record(&turn_context.sub_id, TurnOutcome::Completed);
 
         let mut active = self.active_turn.lock().await;
         let mut pending_input = Vec::<ResponseInputItem>::new();
@@ -321,6 +361,7 @@ impl Session {
                 &[("token_type", "reasoning_output"), tmp_mem],
             );
         }
+        self.send_context_window_status(turn_context.as_ref()).await;
         let event = EventMsg::TurnComplete(TurnCompleteEvent {
             turn_id: turn_context.sub_id.clone(),
             last_agent_message,
@@ -369,6 +410,9 @@ impl Session {
 
         trace!(task_kind = ?task.kind, sub_id, "aborting running task");
         task.cancellation_token.cancel();
+        self.services
+            .turn_outcomes
+            .record(&sub_id, TurnOutcome::Aborted);
         task.turn_context
             .turn_metadata_state
             .cancel_git_enrichment_task();
@@ -410,6 +454,20 @@ impl Session {
             self.flush_rollout().await;
         }
 
+        let partial_output = std::mem::take(
+            &mut *task
+                .turn_context
+                .streamed_output
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+        );
+        let event = EventMsg::TurnCancelled(TurnCancelledEvent {
+            turn_id: task.turn_context.sub_id.clone(),
+            reason: reason.clone(),
+            partial_output: (!partial_output.is_empty()).then_some(partial_output),
+        });
+        self.send_event(task.turn_context.as_ref(), event).await;
+
         let event = EventMsg::TurnAborted(TurnAbortedEvent {
             turn_id: Some(task.turn_context.sub_id.clone()),
             reason,
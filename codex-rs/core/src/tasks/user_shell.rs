@@ -10,6 +10,7 @@ use tracing::error;
 use uuid::Uuid;
 
 use crate::codex::TurnContext;
+use crate::exec::ExecOutputCap;
 use crate::exec::ExecToolCallOutput;
 use crate::exec::SandboxType;
 use crate::exec::StdoutStream;
@@ -179,6 +180,7 @@ pub(crate) async fn execute_user_shell_command(
         sub_id: turn_context.sub_id.clone(),
         call_id: call_id.clone(),
         tx_event: session.get_tx_event(),
+        output_cap: ExecOutputCap::from_config(&turn_context.config),
     });
 
     let exec_result = execute_exec_request(exec_env, &sandbox_policy, stdout_stream, None)
@@ -0,0 +1,226 @@
+//! Enforcement of the `[token_budget]` limits on how many tokens a session
+//! and each of its turns may spend.
+//!
+//! Spend is the cumulative `total_tokens` reported by the model provider, so
+//! it covers both prompt and completion tokens.
+
+use crate::config::types::TokenBudgetConfig;
+
+const DEFAULT_WARNING_THRESHOLD_PERCENT: u8 = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenBudgetScope {
+    Turn,
+    Session,
+}
+
+impl TokenBudgetScope {
+    fn label(self) -> &'static str {
+        match self {
+            TokenBudgetScope::Turn => "turn",
+            TokenBudgetScope::Session => "session",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenBudgetStatus {
+    WithinBudget,
+    /// Spend crossed the warning threshold of `scope`'s limit. Reported once
+    /// per limit.
+    Warning {
+        scope: TokenBudgetScope,
+        used: i64,
+        limit: i64,
+    },
+    Exhausted {
+        scope: TokenBudgetScope,
+        used: i64,
+        limit: i64,
+    },
+}
+
+impl TokenBudgetStatus {
+    pub(crate) fn message(&self) -> Option<String> {
+        match *self {
+            TokenBudgetStatus::WithinBudget => None,
+            TokenBudgetStatus::Warning { scope, used, limit } => {
+                let scope = scope.label();
+                Some(format!(
+                    "This {scope} has used {used} of its {limit} token budget."
+                ))
+            }
+            TokenBudgetStatus::Exhausted { scope, used, limit } => {
+                let scope = scope.label();
+                Some(format!(
+                    "This {scope} has used {used} tokens, exceeding its {limit} token budget."
+                ))
+            }
+        }
+    }
+}
+
+/// Tracks a session's spend against its token budget, along with which
+/// warnings have already been emitted.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TokenBudgetTracker {
+    limits: TokenBudgetConfig,
+    /// Session spend when the current turn started.
+    turn_start_tokens: i64,
+    turn_warned: bool,
+    session_warned: bool,
+}
+
+impl TokenBudgetTracker {
+    pub(crate) fn new(limits: TokenBudgetConfig) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    /// Replaces both limits; `None` removes a limit. Warnings are re-armed so
+    /// the new limits warn once too.
+    pub(crate) fn set_limits(
+        &mut self,
+        max_tokens_per_turn: Option<i64>,
+        max_tokens_per_session: Option<i64>,
+    ) {
+        self.limits.max_tokens_per_turn = max_tokens_per_turn;
+        self.limits.max_tokens_per_session = max_tokens_per_session;
+        self.turn_warned = false;
+        self.session_warned = false;
+    }
+
+    /// Starts tracking a new turn. Returns [`TokenBudgetStatus::Exhausted`]
+    /// when the session budget is already spent and the turn must not run.
+    pub(crate) fn start_turn(&mut self, session_tokens: i64) -> TokenBudgetStatus {
+        self.turn_start_tokens = session_tokens;
+        self.turn_warned = false;
+        match self.limits.max_tokens_per_session {
+            Some(limit) if session_tokens >= limit => TokenBudgetStatus::Exhausted {
+                scope: TokenBudgetScope::Session,
+                used: session_tokens,
+                limit,
+            },
+            _ => TokenBudgetStatus::WithinBudget,
+        }
+    }
+
+    /// Checks spend after a model request of the current turn.
+    pub(crate) fn check(&mut self, session_tokens: i64) -> TokenBudgetStatus {
+        let turn_tokens = session_tokens.saturating_sub(self.turn_start_tokens);
+        let session = self
+            .limits
+            .max_tokens_per_session
+            .map(|limit| (TokenBudgetScope::Session, session_tokens, limit));
+        let turn = self
+            .limits
+            .max_tokens_per_turn
+            .map(|limit| (TokenBudgetScope::Turn, turn_tokens, limit));
+
+        for (scope, used, limit) in session.into_iter().chain(turn) {
+            if used >= limit {
+                return TokenBudgetStatus::Exhausted { scope, used, limit };
+            }
+        }
+        for (scope, used, limit) in session.into_iter().chain(turn) {
+            let warned = match scope {
+                TokenBudgetScope::Turn => &mut self.turn_warned,
+                TokenBudgetScope::Session => &mut self.session_warned,
+            };
+            if !*warned && used >= self.warning_threshold(limit) {
+                *warned = true;
+                return TokenBudgetStatus::Warning { scope, used, limit };
+            }
+        }
+        TokenBudgetStatus::WithinBudget
+    }
+
+    fn warning_threshold(&self, limit: i64) -> i64 {
+        let percent = self
+            .limits
+            .warning_threshold_percent
+            .unwrap_or(DEFAULT_WARNING_THRESHOLD_PERCENT)
+            .min(100);
+        limit.saturating_mul(i64::from(percent)) / 100
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn tracker(per_turn: Option<i64>, per_session: Option<i64>) -> TokenBudgetTracker {
+        TokenBudgetTracker::new(TokenBudgetConfig {
+            max_tokens_per_turn: per_turn,
+            max_tokens_per_session: per_session,
+            warning_threshold_percent: None,
+        })
+    }
+
+    #[test]
+    fn warns_once_then_reports_exhausted_turn() {
+        let mut budget = tracker(Some(1_000), None);
+        assert_eq!(budget.start_turn(5_000), TokenBudgetStatus::WithinBudget);
+
+        assert_eq!(budget.check(5_500), TokenBudgetStatus::WithinBudget);
+        assert_eq!(
+            budget.check(5_800),
+            TokenBudgetStatus::Warning {
+                scope: TokenBudgetScope::Turn,
+                used: 800,
+                limit: 1_000,
+            }
+        );
+        assert_eq!(budget.check(5_900), TokenBudgetStatus::WithinBudget);
+        assert_eq!(
+            budget.check(6_000),
+            TokenBudgetStatus::Exhausted {
+                scope: TokenBudgetScope::Turn,
+                used: 1_000,
+                limit: 1_000,
+            }
+        );
+
+        // Each turn gets its own budget and its own warning.
+        assert_eq!(budget.start_turn(6_000), TokenBudgetStatus::WithinBudget);
+        assert_eq!(
+            budget.check(6_900),
+            TokenBudgetStatus::Warning {
+                scope: TokenBudgetScope::Turn,
+                used: 900,
+                limit: 1_000,
+            }
+        );
+    }
+
+    #[test]
+    fn exhausted_session_refuses_turns_until_limit_is_raised() {
+        let mut budget = tracker(None, Some(10_000));
+        assert_eq!(
+            budget.start_turn(10_000),
+            TokenBudgetStatus::Exhausted {
+                scope: TokenBudgetScope::Session,
+                used: 10_000,
+                limit: 10_000,
+            }
+        );
+
+        budget.set_limits(None, Some(20_000));
+        assert_eq!(budget.start_turn(10_000), TokenBudgetStatus::WithinBudget);
+        assert_eq!(
+            budget.check(16_000),
+            TokenBudgetStatus::Warning {
+                scope: TokenBudgetScope::Session,
+                used: 16_000,
+                limit: 20_000,
+            }
+        );
+
+        budget.set_limits(None, None);
+        assert_eq!(budget.start_turn(50_000), TokenBudgetStatus::WithinBudget);
+        assert_eq!(budget.check(90_000), TokenBudgetStatus::WithinBudget);
+    }
+}
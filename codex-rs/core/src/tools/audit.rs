@@ -0,0 +1,342 @@
+//! Audit trail of dispatched tool calls.
+//!
+//! Every call is reported to the [`AuditHook`]s registered with
+//! [`register_audit_hook`], with its arguments scrubbed of well-known
+//! secrets.
+//!
+//! Tool arguments can carry credentials and results can carry PII, so when a
+//! [`ToolRouter`] is built [`with_encryption`], both are sealed with
+//! AES-256-GCM and the sealed call is appended to the session's audit log
+//! (see [`crate::audit`]), when `[audit]` is enabled. The call id and the
+//! field name are bound in as associated data, so a sealed field cannot be
+//! replayed under another call's id or swapped with the call's other field.
+//!
+//! [`ToolRouter`]: crate::tools::router::ToolRouter
+//! [`with_encryption`]: crate::tools::router::ToolRouter::with_encryption
+//! [`register_audit_hook`]: crate::tools::router::ToolRouter::register_audit_hook
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use codex_protocol::models::ResponseInputItem;
+use ring::aead::AES_256_GCM;
+use ring::aead::Aad;
+use ring::aead::LessSafeKey;
+use ring::aead::NONCE_LEN;
+use ring::aead::Nonce;
+use ring::aead::UnboundKey;
+use ring::rand::SecureRandom;
+use ring::rand::SystemRandom;
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::function_tool::FunctionCallError;
+
+pub const ENCRYPTION_KEY_LEN: usize = 32;
+
+/// Associated data names of the sealed fields of an [`EncryptedEntry`].
+const ARGUMENTS_FIELD: &str = "arguments";
+const OUTPUT_FIELD: &str = "output";
+
+/// AES-256-GCM key. `Debug` never prints the key material.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionKey([u8; ENCRYPTION_KEY_LEN]);
+
+impl EncryptionKey {
+    pub fn from_bytes(bytes: [u8; ENCRYPTION_KEY_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    /// A fresh key from the system's secure random source.
+    pub fn generate() -> Result<Self, AuditCryptoError> {
+        let mut bytes = [0u8; ENCRYPTION_KEY_LEN];
+        SystemRandom::new()
+            .fill(&mut bytes)
+            .map_err(|_| AuditCryptoError::Random)?;
+        Ok(Self(bytes))
+    }
+
+    fn aead_key(&self) -> Result<LessSafeKey, AuditCryptoError> {
+        UnboundKey::new(&AES_256_GCM, &self.0)
+            .map(LessSafeKey::new)
+            .map_err(|_| AuditCryptoError::InvalidKey)
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncryptionKey(..)")
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AuditCryptoError {
+    #[error("failed to generate random bytes")]
+    Random,
+    #[error("invalid encryption key")]
+    InvalidKey,
+    #[error("failed to encrypt audit entry")]
+    Seal,
+    #[error("failed to decrypt audit entry; wrong key or tampered data")]
+    Open,
+    #[error("decrypted audit entry is not valid UTF-8")]
+    InvalidUtf8,
+}
+
+/// Ciphertext with its tag appended, plus the nonce it was sealed with. Both
+/// are base64 in the audit log.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealedField {
+    #[serde(with = "base64_bytes")]
+    pub nonce: [u8; NONCE_LEN],
+    #[serde(with = "base64_bytes")]
+    pub ciphertext: Vec<u8>,
+}
+
+/// A tool call as written to the audit log.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedEntry {
+    pub call_id: String,
+    pub tool_name: String,
+    /// Caller-defined tags; kept in plaintext so entries can be correlated
+    /// without the key.
+    pub tags: HashMap<String, String>,
+    pub dispatched_at: SystemTime,
+    pub duration: Duration,
+    pub arguments: SealedField,
+    pub output: SealedField,
+}
+
+/// Plaintext view of an [`EncryptedEntry`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub call_id: String,
+    pub tool_name: String,
+    pub tags: HashMap<String, String>,
+    pub dispatched_at: SystemTime,
+    pub duration: Duration,
+    pub arguments: String,
+    pub output: String,
+}
+
+impl EncryptedEntry {
+    pub(crate) fn seal(entry: &AuditEntry, key: &EncryptionKey) -> Result<Self, AuditCryptoError> {
+        Ok(Self {
+            call_id: entry.call_id.clone(),
+            tool_name: entry.tool_name.clone(),
+            tags: entry.tags.clone(),
+            dispatched_at: entry.dispatched_at,
+            duration: entry.duration,
+            arguments: seal(key, &entry.call_id, ARGUMENTS_FIELD, &entry.arguments)?,
+            output: seal(key, &entry.call_id, OUTPUT_FIELD, &entry.output)?,
+        })
+    }
+}
+
+/// Decrypts a tool call read back from the audit log, e.g. with
+/// [`crate::audit::read_audit_log`].
+pub fn decrypt_entry(
+    entry: &EncryptedEntry,
+    key: &EncryptionKey,
+) -> Result<AuditEntry, AuditCryptoError> {
+    Ok(AuditEntry {
+        call_id: entry.call_id.clone(),
+        tool_name: entry.tool_name.clone(),
+        tags: entry.tags.clone(),
+        dispatched_at: entry.dispatched_at,
+        duration: entry.duration,
+        arguments: open(key, &entry.call_id, ARGUMENTS_FIELD, &entry.arguments)?,
+        output: open(key, &entry.call_id, OUTPUT_FIELD, &entry.output)?,
+    })
+}
+
+/// How a dispatched call ended, as reported to an [`AuditHook`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuditOutcome {
+    Success,
+    /// The call produced a failure output for the model, e.g. a rejected
+    /// command or an MCP tool error.
+    Failure,
+    /// The call failed with an error that ends the turn.
+    Fatal,
+}
+
+impl AuditOutcome {
+    pub(crate) fn of(result: &Result<ResponseInputItem, FunctionCallError>) -> Self {
+        match result {
+            Ok(
+                ResponseInputItem::FunctionCallOutput { output, .. }
+                | ResponseInputItem::CustomToolCallOutput { output, .. },
+            ) if output.success == Some(false) => Self::Failure,
+            Ok(ResponseInputItem::McpToolCallOutput { result: Err(_), .. }) => Self::Failure,
+            Ok(ResponseInputItem::McpToolCallOutput {
+                result: Ok(result), ..
+            }) if result.is_error == Some(true) => Self::Failure,
+            Ok(_) => Self::Success,
+            Err(FunctionCallError::Fatal(_)) => Self::Fatal,
+            Err(_) => Self::Failure,
+        }
+    }
+}
+
+/// A dispatched tool call, as reported to an [`AuditHook`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditRecord {
+    pub call_id: String,
+    pub tool_name: String,
+    /// MCP server, for MCP calls.
+    pub mcp_server: Option<String>,
+    /// The call's arguments, with well-known secrets redacted.
+    pub arguments: String,
+    pub outcome: AuditOutcome,
+    pub duration: Duration,
+}
+
+pub trait AuditHook: Send + Sync {
+    /// Runs once per dispatched call, after its outcome is known. An error
+    /// is logged and has no effect on the call.
+    fn record(&self, record: &AuditRecord) -> anyhow::Result<()>;
+}
+
+/// `field` of call `call_id`, as associated data. Field names never contain
+/// a NUL, so the two parts cannot run into each other.
+fn associated_data(call_id: &str, field: &str) -> Vec<u8> {
+    format!("{field}\0{call_id}").into_bytes()
+}
+
+fn seal(
+    key: &EncryptionKey,
+    call_id: &str,
+    field: &str,
+    plaintext: &str,
+) -> Result<SealedField, AuditCryptoError> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| AuditCryptoError::Random)?;
+    let mut ciphertext = plaintext.as_bytes().to_vec();
+    key.aead_key()?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(associated_data(call_id, field)),
+            &mut ciphertext,
+        )
+        .map_err(|_| AuditCryptoError::Seal)?;
+    Ok(SealedField { nonce, ciphertext })
+}
+
+fn open(
+    key: &EncryptionKey,
+    call_id: &str,
+    field: &str,
+    sealed: &SealedField,
+) -> Result<String, AuditCryptoError> {
+    let mut buffer = sealed.ciphertext.clone();
+    let plaintext = key
+        .aead_key()?
+        .open_in_place(
+            Nonce::assume_unique_for_key(sealed.nonce),
+            Aad::from(associated_data(call_id, field)),
+            &mut buffer,
+        )
+        .map_err(|_| AuditCryptoError::Open)?;
+    String::from_utf8(plaintext.to_vec()).map_err(|_| AuditCryptoError::InvalidUtf8)
+}
+
+/// Bytes as a base64 string.
+mod base64_bytes {
+    use super::BASE64_STANDARD;
+    use base64::Engine;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serializer;
+    use serde::de::Error;
+
+    pub(super) fn serialize<S: Serializer>(
+        bytes: impl AsRef<[u8]>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&BASE64_STANDARD.encode(bytes))
+    }
+
+    pub(super) fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: TryFrom<Vec<u8>>,
+    {
+        let encoded = String::deserialize(deserializer)?;
+        let bytes = BASE64_STANDARD.decode(encoded).map_err(D::Error::custom)?;
+        T::try_from(bytes).map_err(|_| D::Error::custom("unexpected number of bytes"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn entry() -> AuditEntry {
+        AuditEntry {
+            call_id: "call-1".to_string(),
+            tool_name: "shell".to_string(),
+            tags: HashMap::from([("team".to_string(), "infra".to_string())]),
+            dispatched_at: SystemTime::UNIX_EPOCH,
+            duration: Duration::from_millis(5),
+            arguments: r#"{"command":["curl","-H","Authorization: Bearer sk-secret"]}"#.to_string(),
+            output: "user email: jane@example.com".to_string(),
+        }
+    }
+
+    fn contains(haystack: &[u8], needle: &str) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle.as_bytes())
+    }
+
+    #[test]
+    fn encrypted_entries_round_trip_and_hide_plaintext() {
+        let key = EncryptionKey::generate().expect("generate key");
+
+        let encrypted = EncryptedEntry::seal(&entry(), &key).expect("seal entry");
+        let line = serde_json::to_string(&encrypted).expect("serialize entry");
+
+        assert!(!contains(&encrypted.arguments.ciphertext, "sk-secret"));
+        assert!(!contains(&encrypted.output.ciphertext, "jane@example.com"));
+        assert!(!line.contains("sk-secret"));
+        assert_eq!(
+            serde_json::from_str::<EncryptedEntry>(&line).expect("parse entry"),
+            encrypted
+        );
+        assert_eq!(decrypt_entry(&encrypted, &key), Ok(entry()));
+    }
+
+    #[test]
+    fn decrypting_with_wrong_key_call_id_or_field_fails() {
+        let key = EncryptionKey::from_bytes([7; ENCRYPTION_KEY_LEN]);
+        let encrypted = EncryptedEntry::seal(&entry(), &key).expect("seal entry");
+
+        assert_eq!(
+            decrypt_entry(
+                &encrypted,
+                &EncryptionKey::from_bytes([8; ENCRYPTION_KEY_LEN])
+            ),
+            Err(AuditCryptoError::Open)
+        );
+        let replayed = EncryptedEntry {
+            call_id: "call-2".to_string(),
+            ..encrypted.clone()
+        };
+        assert_eq!(decrypt_entry(&replayed, &key), Err(AuditCryptoError::Open));
+        let swapped = EncryptedEntry {
+            arguments: encrypted.output.clone(),
+            output: encrypted.arguments.clone(),
+            ..encrypted
+        };
+        assert_eq!(decrypt_entry(&swapped, &key), Err(AuditCryptoError::Open));
+    }
+}
@@ -0,0 +1,161 @@
+//! Keeps a call id from running its tool twice, e.g. when a model repeats a
+//! call or a harness retries a request. See [`CallIdDedupe::reserve`].
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+
+use codex_protocol::models::ResponseInputItem;
+
+/// How many completed calls are remembered; older ones are forgotten, so a
+/// call id reused after that many other calls runs again.
+pub(crate) const RECENT_CALL_IDS_CAPACITY: usize = 1024;
+
+/// Call ids that are running, and the responses of the most recently
+/// completed ones.
+#[derive(Clone, Debug)]
+pub(crate) struct CallIdDedupe {
+    state: Arc<Mutex<DedupeState>>,
+    capacity: usize,
+}
+
+#[derive(Debug, Default)]
+struct DedupeState {
+    in_flight: HashSet<String>,
+    completed: HashMap<String, ResponseInputItem>,
+    /// Keys of `completed`, oldest first.
+    completion_order: VecDeque<String>,
+}
+
+/// Why [`CallIdDedupe::reserve`] refused a call id.
+#[derive(Debug, PartialEq)]
+pub(crate) enum DuplicateCall {
+    /// A call with this id is still running.
+    InFlight,
+    /// A call with this id already finished with this response.
+    Completed(ResponseInputItem),
+}
+
+impl Default for CallIdDedupe {
+    fn default() -> Self {
+        Self::with_capacity(RECENT_CALL_IDS_CAPACITY)
+    }
+}
+
+impl CallIdDedupe {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            state: Arc::default(),
+            capacity,
+        }
+    }
+
+    /// Marks `call_id` as running, unless it is already running or finished
+    /// recently. The id stays reserved until the returned reservation is
+    /// completed or dropped; a dropped reservation lets the id run again.
+    pub(crate) fn reserve(&self, call_id: &str) -> Result<CallIdReservation, DuplicateCall> {
+        let mut state = self.lock_state();
+        if let Some(response) = state.completed.get(call_id) {
+            return Err(DuplicateCall::Completed(response.clone()));
+        }
+        if !state.in_flight.insert(call_id.to_string()) {
+            return Err(DuplicateCall::InFlight);
+        }
+        Ok(CallIdReservation {
+            dedupe: self.clone(),
+            call_id: call_id.to_string(),
+        })
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, DedupeState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A call id held by [`CallIdDedupe::reserve`].
+#[derive(Debug)]
+pub(crate) struct CallIdReservation {
+    dedupe: CallIdDedupe,
+    call_id: String,
+}
+
+impl CallIdReservation {
+    /// Remembers `response` as the result of this call id, forgetting the
+    /// oldest completed call once more than the capacity are remembered.
+    pub(crate) fn complete(self, response: &ResponseInputItem) {
+        let mut state = self.dedupe.lock_state();
+        if state
+            .completed
+            .insert(self.call_id.clone(), response.clone())
+            .is_none()
+        {
+            state.completion_order.push_back(self.call_id.clone());
+        }
+        while state.completion_order.len() > self.dedupe.capacity {
+            if let Some(evicted) = state.completion_order.pop_front() {
+                state.completed.remove(&evicted);
+            }
+        }
+    }
+}
+
+impl Drop for CallIdReservation {
+    fn drop(&mut self) {
+        self.dedupe.lock_state().in_flight.remove(&self.call_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    fn output(call_id: &str) -> ResponseInputItem {
+        ResponseInputItem::FunctionCallOutput {
+            call_id: call_id.to_string(),
+            output: FunctionCallOutputPayload::from_text(format!("output of {call_id}")),
+        }
+    }
+
+    #[test]
+    fn duplicates_are_refused_until_the_window_forgets_them() {
+        let dedupe = CallIdDedupe::with_capacity(2);
+
+        let first = dedupe.reserve("call-1").expect("first reservation");
+        assert_eq!(
+            dedupe.reserve("call-1").err(),
+            Some(DuplicateCall::InFlight)
+        );
+        first.complete(&output("call-1"));
+        assert_eq!(
+            dedupe.reserve("call-1").err(),
+            Some(DuplicateCall::Completed(output("call-1")))
+        );
+
+        for call_id in ["call-2", "call-3"] {
+            dedupe
+                .reserve(call_id)
+                .expect("new call id")
+                .complete(&output(call_id));
+        }
+        assert!(dedupe.reserve("call-1").is_ok());
+        assert_eq!(
+            dedupe.reserve("call-3").err(),
+            Some(DuplicateCall::Completed(output("call-3")))
+        );
+    }
+
+    #[test]
+    fn dropped_reservations_release_the_call_id() {
+        let dedupe = CallIdDedupe::default();
+
+        drop(dedupe.reserve("call-1").expect("first reservation"));
+
+        assert!(dedupe.reserve("call-1").is_ok());
+    }
+}
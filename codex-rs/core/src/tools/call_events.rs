@@ -0,0 +1,67 @@
+//! Typed lifecycle events for the tool calls of a session.
+//!
+//! See [`CodexThread::subscribe_to_tool_calls`].
+//!
+//! [`CodexThread::subscribe_to_tool_calls`]: crate::CodexThread::subscribe_to_tool_calls
+
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+
+use codex_protocol::models::ResponseInputItem;
+use futures::Stream;
+use tokio::sync::mpsc;
+
+use crate::function_tool::FunctionCallError;
+use crate::tools::router::ToolCall;
+
+#[derive(Clone, Debug)]
+pub enum ToolCallEvent {
+    Started(ToolCall),
+    /// The call produced a response for the model. Errors the model is told
+    /// about, e.g. a rejected command, arrive here as a failure output.
+    Completed {
+        call_id: String,
+        result: ResponseInputItem,
+    },
+    /// The call failed with an error that ends the turn.
+    Failed {
+        call_id: String,
+        error: FunctionCallError,
+    },
+}
+
+/// Session-wide set of [`ToolCallEvent`] subscribers.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ToolCallSubscribers {
+    senders: Arc<Mutex<Vec<mpsc::UnboundedSender<ToolCallEvent>>>>,
+}
+
+impl ToolCallSubscribers {
+    /// Streams every event published from now on. Dropping the stream
+    /// unsubscribes.
+    pub(crate) fn subscribe(&self) -> impl Stream<Item = ToolCallEvent> + use<> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.lock().push(tx);
+        futures::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|event| (event, rx))
+        })
+    }
+
+    /// Sends the event built by `event` to every subscriber. `event` is not
+    /// called when nobody is subscribed.
+    pub(crate) fn publish(&self, event: impl FnOnce() -> ToolCallEvent) {
+        let mut senders = self.lock();
+        if senders.is_empty() {
+            return;
+        }
+        let event = event();
+        senders.retain(|tx| tx.send(event.clone()).is_ok());
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<mpsc::UnboundedSender<ToolCallEvent>>> {
+        self.senders
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,183 @@
+//! Fault injection for exercising how callers cope with failing, slow, or
+//! garbled tool calls.
+//!
+//! See [`ToolRouter::enable_chaos`].
+//!
+//! [`ToolRouter::enable_chaos`]: crate::tools::router::ToolRouter::enable_chaos
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use codex_protocol::protocol::ChaosFaultType;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use crate::config::types::ToolChaosConfig;
+use crate::tools::context::ToolPayload;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChaosConfig {
+    /// Fraction of calls, from 0.0 to 1.0, that fail instead of running.
+    pub error_rate: f32,
+    /// Upper bound of the random delay added before each call.
+    pub latency_jitter_ms: u64,
+    /// Truncate the arguments of calls that are not failed outright.
+    pub random_payload_corruption: bool,
+    /// Seeds the RNG so that a run's faults can be reproduced.
+    pub seed: u64,
+}
+
+impl From<ToolChaosConfig> for ChaosConfig {
+    fn from(config: ToolChaosConfig) -> Self {
+        Self {
+            error_rate: config.error_rate.unwrap_or_default(),
+            latency_jitter_ms: config.latency_jitter_ms.unwrap_or_default(),
+            random_payload_corruption: config.random_payload_corruption.unwrap_or_default(),
+            seed: config.seed.unwrap_or_default(),
+        }
+    }
+}
+
+/// Faults chosen for one call.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ChaosPlan {
+    pub(crate) latency: Option<Duration>,
+    pub(crate) corrupt_payload: bool,
+    pub(crate) fail: bool,
+}
+
+impl ChaosPlan {
+    pub(crate) fn fault_types(&self) -> Vec<ChaosFaultType> {
+        let mut faults = Vec::new();
+        if self.latency.is_some() {
+            faults.push(ChaosFaultType::Latency);
+        }
+        if self.corrupt_payload {
+            faults.push(ChaosFaultType::PayloadCorruption);
+        }
+        if self.fail {
+            faults.push(ChaosFaultType::Error);
+        }
+        faults
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Chaos {
+    config: ChaosConfig,
+    rng: Mutex<StdRng>,
+}
+
+impl Chaos {
+    pub(crate) fn new(config: ChaosConfig) -> Self {
+        Self {
+            config,
+            rng: Mutex::new(StdRng::seed_from_u64(config.seed)),
+        }
+    }
+
+    /// Draws the faults for the next call. Draws happen in a fixed order, so
+    /// the same seed and call sequence always yields the same plans.
+    pub(crate) fn plan(&self) -> ChaosPlan {
+        let mut rng = self
+            .rng
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let latency = (self.config.latency_jitter_ms > 0)
+            .then(|| Duration::from_millis(rng.random_range(0..=self.config.latency_jitter_ms)));
+        let fail = rng.random::<f32>() < self.config.error_rate;
+        let corrupt_payload =
+            !fail && self.config.random_payload_corruption && rng.random::<bool>();
+        ChaosPlan {
+            latency,
+            corrupt_payload,
+            fail,
+        }
+    }
+}
+
+/// Cuts the payload's arguments in half, which leaves JSON arguments
+/// unparseable. Local shell commands lose their last argument instead.
+pub(crate) fn corrupt_payload(payload: ToolPayload) -> ToolPayload {
+    fn truncate(text: String) -> String {
+        let mut end = text.len() / 2;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text[..end].to_string()
+    }
+    match payload {
+        ToolPayload::Function { arguments } => ToolPayload::Function {
+            arguments: truncate(arguments),
+        },
+        ToolPayload::Custom { input, attachments } => ToolPayload::Custom {
+            input: truncate(input),
+            attachments,
+        },
+        ToolPayload::Mcp {
+            server,
+            tool,
+            raw_arguments,
+        } => ToolPayload::Mcp {
+            server,
+            tool,
+            raw_arguments: truncate(raw_arguments),
+        },
+        ToolPayload::LocalShell { mut params } => {
+            params.command.pop();
+            ToolPayload::LocalShell { params }
+        }
+        ToolPayload::GraphQL {
+            endpoint,
+            query,
+            variables,
+            headers,
+        } => ToolPayload::GraphQL {
+            endpoint,
+            query: truncate(query),
+            variables,
+            headers,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn same_seed_draws_same_faults() {
+        let config = ChaosConfig {
+            error_rate: 0.5,
+            latency_jitter_ms: 100,
+            random_payload_corruption: true,
+            seed: 42,
+        };
+        let first = Chaos::new(config);
+        let second = Chaos::new(config);
+
+        let first_plans = (0..20).map(|_| first.plan()).collect::<Vec<_>>();
+        let second_plans = (0..20).map(|_| second.plan()).collect::<Vec<_>>();
+
+        assert_eq!(first_plans, second_plans);
+        assert!(
+            first_plans
+                .iter()
+                .all(|plan| !(plan.fail && plan.corrupt_payload))
+        );
+    }
+
+    #[test]
+    fn corruption_truncates_arguments() {
+        let payload = corrupt_payload(ToolPayload::Function {
+            arguments: r#"{"path":"a"}"#.to_string(),
+        });
+
+        let ToolPayload::Function { arguments } = payload else {
+            panic!("unexpected payload: {payload:?}");
+        };
+        assert_eq!(arguments, r#"{"path"#);
+    }
+}
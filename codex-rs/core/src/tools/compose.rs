@@ -0,0 +1,191 @@
+//! Pipeline tools built by chaining existing function tools.
+//!
+//! See [`ToolRegistry::compose_tools`].
+//!
+//! [`ToolRegistry::compose_tools`]: crate::tools::registry::ToolRegistry::compose_tools
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use thiserror::Error;
+
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::tools::registry::ToolRegistry;
+use codex_protocol::models::FunctionCallOutputBody;
+use codex_protocol::models::ResponseInputItem;
+
+/// Placeholder in [`ComposeStep::argument_template`] replaced with the
+/// previous step's output.
+pub const PREV_OUTPUT_PLACEHOLDER: &str = "{prev_output}";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComposeStep {
+    pub tool_name: String,
+    /// JSON arguments for this step. Every [`PREV_OUTPUT_PLACEHOLDER`] is
+    /// replaced with the previous output, escaped for use inside a JSON
+    /// string. The first step sees the composed call's raw arguments.
+    pub argument_template: String,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ComposeError {
+    #[error("composed tool {0} has no steps")]
+    EmptyPipeline(String),
+    #[error("a handler is already registered for tool {0}")]
+    DuplicateTool(String),
+    #[error("step {index} of composed tool {name} uses unknown tool {tool_name}")]
+    UnknownTool {
+        name: String,
+        index: usize,
+        tool_name: String,
+    },
+    #[error("step {index} of composed tool {name} uses {tool_name}, which is not a function tool")]
+    UnsupportedTool {
+        name: String,
+        index: usize,
+        tool_name: String,
+    },
+}
+
+/// Runs each step in order through [`ToolRegistry::dispatch`], feeding the
+/// previous output into the next step's arguments, so every step is subject
+/// to the same policies, middleware, budgets and output limits as a direct
+/// call. Step `n` (from 1) of call `id` runs as call `id/step-n`.
+pub(crate) struct ComposedToolHandler {
+    name: String,
+    steps: Vec<(ComposeStep, Arc<dyn ToolHandler>)>,
+}
+
+impl ComposedToolHandler {
+    pub(crate) fn new(name: String, steps: Vec<(ComposeStep, Arc<dyn ToolHandler>)>) -> Self {
+        Self { name, steps }
+    }
+
+    /// Runs the pipeline for `invocation`, dispatching each step through
+    /// `registry`.
+    pub(crate) async fn run(
+        &self,
+        registry: &ToolRegistry,
+        invocation: ToolInvocation,
+    ) -> Result<ToolOutput, FunctionCallError> {
+        let ToolPayload::Function { arguments } = &invocation.payload else {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "{} handler received unsupported payload",
+                self.name
+            )));
+        };
+        let mut prev_output = arguments.clone();
+        for (index, (step, _)) in self.steps.iter().enumerate() {
+            let step_invocation = ToolInvocation {
+                session: Arc::clone(&invocation.session),
+                turn: Arc::clone(&invocation.turn),
+                tracker: Arc::clone(&invocation.tracker),
+                call_id: step_call_id(&invocation.call_id, index),
+                tool_name: step.tool_name.clone(),
+                payload: ToolPayload::Function {
+                    arguments: render_template(&step.argument_template, &prev_output),
+                },
+                dry_run: false,
+            };
+            // Boxed as a trait object: the step's dispatch may run another
+            // composed tool, which would otherwise make this future contain
+            // itself.
+            let dispatch: BoxFuture<'_, Result<ResponseInputItem, FunctionCallError>> =
+                Box::pin(registry.dispatch(step_invocation));
+            let response = dispatch.await.map_err(|err| match err {
+                FunctionCallError::RespondToModel(message) => FunctionCallError::RespondToModel(
+                    format!("{} failed at step {}: {message}", self.name, step.tool_name),
+                ),
+                err => err,
+            })?;
+            prev_output = step_output_text(&self.name, &step.tool_name, response)?;
+        }
+        Ok(ToolOutput::Function {
+            body: FunctionCallOutputBody::Text(prev_output),
+            success: Some(true),
+        })
+    }
+}
+
+#[async_trait]
+impl ToolHandler for ComposedToolHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn is_mutating(&self, invocation: &ToolInvocation) -> bool {
+        for (_, handler) in &self.steps {
+            if handler.is_mutating(invocation).await {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn is_pure(&self) -> bool {
+        self.steps.iter().all(|(_, handler)| handler.is_pure())
+    }
+
+    async fn handle(&self, _invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        Err(FunctionCallError::Fatal(format!(
+            "composed tool {} must be run through ToolRegistry::dispatch",
+            self.name
+        )))
+    }
+}
+
+fn step_call_id(call_id: &str, index: usize) -> String {
+    format!("{call_id}/step-{}", index + 1)
+}
+
+fn render_template(template: &str, prev_output: &str) -> String {
+    let escaped = serde_json::to_string(prev_output).unwrap_or_default();
+    let escaped = escaped
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or_default();
+    template.replace(PREV_OUTPUT_PLACEHOLDER, escaped)
+}
+
+fn step_output_text(
+    name: &str,
+    tool_name: &str,
+    response: ResponseInputItem,
+) -> Result<String, FunctionCallError> {
+    match response {
+        ResponseInputItem::FunctionCallOutput { output, .. }
+        | ResponseInputItem::CustomToolCallOutput { output, .. } => match output.success {
+            Some(false) => Err(FunctionCallError::RespondToModel(format!(
+                "{name} failed at step {tool_name}: {}",
+                output.body.to_text().unwrap_or_default()
+            ))),
+            _ => Ok(output.body.to_text().unwrap_or_default()),
+        },
+        ResponseInputItem::McpToolCallOutput { .. } | ResponseInputItem::Message { .. } => {
+            Err(FunctionCallError::RespondToModel(format!(
+                "{name} failed at step {tool_name}: unexpected output"
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn render_template_escapes_prev_output_for_json_strings() {
+        let rendered = render_template(r#"{"text": "{prev_output}"}"#, "L1: say \"hi\"\nL2: bye");
+
+        let value: serde_json::Value =
+            serde_json::from_str(&rendered).expect("rendered template is valid JSON");
+        assert_eq!(value["text"], "L1: say \"hi\"\nL2: bye");
+    }
+}
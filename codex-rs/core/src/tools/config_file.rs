@@ -0,0 +1,252 @@
+//! TOML files describing a [`ToolsConfig`], which may inherit from one
+//! another through `inherit_from`.
+//!
+//! See [`ToolRouter::from_config_file`].
+//!
+//! [`ToolRouter::from_config_file`]: crate::tools::router::ToolRouter::from_config_file
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::config_types::WebSearchMode;
+use codex_protocol::openai_models::ConfigShellToolType;
+use codex_protocol::protocol::SessionSource;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::features::Features;
+use crate::models_manager::model_info::model_info_from_slug;
+use crate::tools::spec::ToolsConfig;
+use crate::tools::spec::ToolsConfigParams;
+
+#[derive(Debug, Error)]
+pub enum ConfigLoadError {
+    #[error("failed to read tools config {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("failed to parse tools config {}: {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("tools config inheritance cycle: {}", display_chain(chain))]
+    InheritanceCycle { chain: Vec<PathBuf> },
+    #[error("tools config {} does not set `model`, directly or by inheritance", path.display())]
+    MissingModel { path: PathBuf },
+}
+
+/// One tools config file. Every setting is optional; unset settings fall
+/// back to the file named by `inherit_from`, then to the defaults for
+/// `model`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ToolsConfigFile {
+    /// Parent config, relative to the directory of this file.
+    pub inherit_from: Option<PathBuf>,
+    /// Model slug whose metadata seeds the defaults.
+    pub model: Option<String>,
+    pub shell_type: Option<ConfigShellToolType>,
+    pub allow_login_shell: Option<bool>,
+    pub web_search_mode: Option<WebSearchMode>,
+    pub max_argument_size_bytes: Option<usize>,
+    /// Merged key by key with the parent's table.
+    #[serde(default)]
+    pub features: BTreeMap<String, bool>,
+}
+
+impl ToolsConfigFile {
+    /// Reads the file at `path` and merges in everything it inherits from.
+    /// The returned file has no `inherit_from`.
+    pub fn load(path: &Path) -> Result<Self, ConfigLoadError> {
+        let mut chain = Vec::new();
+        Self::load_inner(path, &mut chain)
+    }
+
+    fn load_inner(path: &Path, chain: &mut Vec<PathBuf>) -> Result<Self, ConfigLoadError> {
+        let path = std::fs::canonicalize(path).map_err(|source| ConfigLoadError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        if chain.contains(&path) {
+            let mut chain = chain.clone();
+            chain.push(path);
+            return Err(ConfigLoadError::InheritanceCycle { chain });
+        }
+        let contents = std::fs::read_to_string(&path).map_err(|source| ConfigLoadError::Io {
+            path: path.clone(),
+            source,
+        })?;
+        let file: Self = toml::from_str(&contents).map_err(|source| ConfigLoadError::Parse {
+            path: path.clone(),
+            source,
+        })?;
+
+        let Some(parent) = file.inherit_from.clone() else {
+            return Ok(file);
+        };
+        let parent = match path.parent() {
+            Some(dir) => dir.join(parent),
+            None => parent,
+        };
+        chain.push(path);
+        let parent = Self::load_inner(&parent, chain)?;
+        chain.pop();
+        Ok(parent.merge(file))
+    }
+
+    /// Layers `child` over `self`.
+    fn merge(mut self, child: Self) -> Self {
+        self.features.extend(child.features);
+        Self {
+            inherit_from: None,
+            model: child.model.or(self.model),
+            shell_type: child.shell_type.or(self.shell_type),
+            allow_login_shell: child.allow_login_shell.or(self.allow_login_shell),
+            web_search_mode: child.web_search_mode.or(self.web_search_mode),
+            max_argument_size_bytes: child
+                .max_argument_size_bytes
+                .or(self.max_argument_size_bytes),
+            features: self.features,
+        }
+    }
+
+    /// Builds the [`ToolsConfig`] this file describes. `path` is only used
+    /// in the error when no `model` is set.
+    pub(crate) fn into_tools_config(self, path: &Path) -> Result<ToolsConfig, ConfigLoadError> {
+        let Some(model) = self.model.clone() else {
+            return Err(ConfigLoadError::MissingModel {
+                path: path.to_path_buf(),
+            });
+        };
+        Ok(self.into_tools_config_for_model(&model))
+    }
+
+    /// Builds the [`ToolsConfig`] this file describes for `model`, which
+    /// takes the place of `self.model`.
+    pub(crate) fn into_tools_config_for_model(self, model: &str) -> ToolsConfig {
+        let model_info = model_info_from_slug(model);
+        let mut features = Features::with_defaults();
+        features.apply_map(&self.features);
+        let mut config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: self.web_search_mode,
+            session_source: SessionSource::Exec,
+        })
+        .with_max_argument_size_bytes(self.max_argument_size_bytes);
+        if let Some(allow_login_shell) = self.allow_login_shell {
+            config = config.with_allow_login_shell(allow_login_shell);
+        }
+        if let Some(shell_type) = self.shell_type {
+            config.shell_type = shell_type;
+        }
+        config
+    }
+}
+
+fn display_chain(chain: &[PathBuf]) -> String {
+    chain
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn write(dir: &Path, name: &str, contents: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, contents).expect("write config");
+        path
+    }
+
+    #[test]
+    fn three_level_chain_merges_child_over_parent() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir(dir.path().join("base")).expect("create base dir");
+        write(
+            &dir.path().join("base"),
+            "root.toml",
+            r#"
+model = "gpt-5-codex"
+shell_type = "shell_command"
+allow_login_shell = false
+max_argument_size_bytes = 1024
+
+[features]
+unified_exec = true
+js_repl = true
+"#,
+        );
+        write(
+            dir.path(),
+            "team.toml",
+            r#"
+inherit_from = "base/root.toml"
+web_search_mode = "live"
+max_argument_size_bytes = 2048
+
+[features]
+js_repl = false
+"#,
+        );
+        let leaf = write(
+            dir.path(),
+            "leaf.toml",
+            r#"
+inherit_from = "team.toml"
+allow_login_shell = true
+"#,
+        );
+
+        let file = ToolsConfigFile::load(&leaf).expect("load chain");
+
+        assert_eq!(
+            file,
+            ToolsConfigFile {
+                inherit_from: None,
+                model: Some("gpt-5-codex".to_string()),
+                shell_type: Some(ConfigShellToolType::ShellCommand),
+                allow_login_shell: Some(true),
+                web_search_mode: Some(WebSearchMode::Live),
+                max_argument_size_bytes: Some(2048),
+                features: BTreeMap::from([
+                    ("js_repl".to_string(), false),
+                    ("unified_exec".to_string(), true),
+                ]),
+            }
+        );
+
+        let config = file.into_tools_config(&leaf).expect("build tools config");
+        assert_eq!(config.shell_type, ConfigShellToolType::ShellCommand);
+        assert!(config.allow_login_shell);
+        assert_eq!(config.web_search_mode, Some(WebSearchMode::Live));
+        assert_eq!(config.max_argument_size_bytes, Some(2048));
+        assert!(!config.js_repl_enabled);
+    }
+
+    #[test]
+    fn inheritance_cycle_is_rejected() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let a = write(dir.path(), "a.toml", "inherit_from = \"b.toml\"\n");
+        write(dir.path(), "b.toml", "inherit_from = \"a.toml\"\n");
+
+        let err = ToolsConfigFile::load(&a).expect_err("cycle should fail");
+
+        let ConfigLoadError::InheritanceCycle { chain } = err else {
+            panic!("unexpected error: {err}");
+        };
+        let names = chain
+            .iter()
+            .filter_map(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["a.toml", "b.toml", "a.toml"]);
+    }
+}
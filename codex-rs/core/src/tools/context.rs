@@ -9,8 +9,11 @@ use codex_protocol::models::FunctionCallOutputBody;
 use codex_protocol::models::FunctionCallOutputPayload;
 use codex_protocol::models::ResponseInputItem;
 use codex_protocol::models::ShellToolCallParams;
+use codex_protocol::models::ToolCallAttachment;
 use codex_utils_string::take_bytes_at_char_boundary;
+use serde_json::Value as JsonValue;
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
@@ -30,6 +33,10 @@ pub struct ToolInvocation {
     pub call_id: String,
     pub tool_name: String,
     pub payload: ToolPayload,
+    /// Preview the call instead of executing it; see [`ToolCall::dry_run`].
+    ///
+    /// [`ToolCall::dry_run`]: crate::tools::router::ToolCall::dry_run
+    pub dry_run: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -39,6 +46,9 @@ pub enum ToolPayload {
     },
     Custom {
         input: String,
+        /// Attachments sent along with `input`; empty for plain custom tool
+        /// calls.
+        attachments: Vec<ToolCallAttachment>,
     },
     LocalShell {
         params: ShellToolCallParams,
@@ -48,15 +58,23 @@ pub enum ToolPayload {
         tool: String,
         raw_arguments: String,
     },
+    /// A GraphQL operation POSTed to `endpoint` with `headers`.
+    GraphQL {
+        endpoint: String,
+        query: String,
+        variables: JsonValue,
+        headers: HashMap<String, String>,
+    },
 }
 
 impl ToolPayload {
     pub fn log_payload(&self) -> Cow<'_, str> {
         match self {
             ToolPayload::Function { arguments } => Cow::Borrowed(arguments),
-            ToolPayload::Custom { input } => Cow::Borrowed(input),
+            ToolPayload::Custom { input, .. } => Cow::Borrowed(input),
             ToolPayload::LocalShell { params } => Cow::Owned(params.command.join(" ")),
             ToolPayload::Mcp { raw_arguments, .. } => Cow::Borrowed(raw_arguments),
+            ToolPayload::GraphQL { query, .. } => Cow::Borrowed(query),
         }
     }
 }
@@ -100,7 +118,11 @@ impl ToolOutput {
                 if matches!(payload, ToolPayload::Custom { .. }) {
                     return ResponseInputItem::CustomToolCallOutput {
                         call_id: call_id.to_string(),
-                        output: FunctionCallOutputPayload { body, success },
+                        output: FunctionCallOutputPayload {
+                            body,
+                            success,
+                            error_code: None,
+                        },
                     };
                 }
 
@@ -108,7 +130,11 @@ impl ToolOutput {
                 // tools and MCP adaptation) preserve the exact body shape.
                 ResponseInputItem::FunctionCallOutput {
                     call_id: call_id.to_string(),
-                    output: FunctionCallOutputPayload { body, success },
+                    output: FunctionCallOutputPayload {
+                        body,
+                        success,
+                        error_code: None,
+                    },
                 }
             }
             // Direct MCP response path for MCP tool result envelopes.
@@ -120,7 +146,7 @@ impl ToolOutput {
     }
 }
 
-fn telemetry_preview(content: &str) -> String {
+pub(crate) fn telemetry_preview(content: &str) -> String {
     let truncated_slice = take_bytes_at_char_boundary(content, TELEMETRY_PREVIEW_MAX_BYTES);
     let truncated_by_bytes = truncated_slice.len() < content.len();
 
@@ -170,6 +196,7 @@ mod tests {
     fn custom_tool_calls_should_roundtrip_as_custom_outputs() {
         let payload = ToolPayload::Custom {
             input: "patch".to_string(),
+            attachments: Vec::new(),
         };
         let response = ToolOutput::Function {
             body: FunctionCallOutputBody::Text("patched".to_string()),
@@ -214,6 +241,7 @@ mod tests {
     fn custom_tool_calls_can_derive_text_from_content_items() {
         let payload = ToolPayload::Custom {
             input: "patch".to_string(),
+            attachments: Vec::new(),
         };
         let response = ToolOutput::Function {
             body: FunctionCallOutputBody::ContentItems(vec![
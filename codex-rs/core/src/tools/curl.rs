@@ -0,0 +1,126 @@
+//! `curl` reproductions of MCP tool calls, for debugging failing servers.
+//!
+//! See [`ToolRouter::generate_curl_equivalent`].
+//!
+//! [`ToolRouter::generate_curl_equivalent`]: crate::tools::router::ToolRouter::generate_curl_equivalent
+
+use std::collections::BTreeMap;
+
+use serde_json::Value as JsonValue;
+use serde_json::json;
+
+use crate::config::types::McpServerConfig;
+use crate::config::types::McpServerTransportConfig;
+
+/// Stands in for credentials, which are never written into the command.
+pub const TOKEN_PLACEHOLDER: &str = "$TOKEN";
+
+/// Builds a single-line `curl` command that POSTs the `tools/call` request
+/// for `tool` to `config`'s endpoint. Returns `None` for stdio servers and
+/// for arguments that are not valid JSON, which the client would not send.
+///
+/// The bearer token and any header whose name suggests a credential are
+/// replaced with [`TOKEN_PLACEHOLDER`]; headers read from the environment
+/// reference their variable. Servers that require an `Mcp-Session-Id` need
+/// one from a prior `initialize` request.
+pub(crate) fn mcp_call_as_curl(
+    config: &McpServerConfig,
+    tool: &str,
+    raw_arguments: &str,
+) -> Option<String> {
+    let McpServerTransportConfig::StreamableHttp {
+        url,
+        bearer_token_env_var,
+        http_headers,
+        env_http_headers,
+    } = &config.transport
+    else {
+        return None;
+    };
+
+    let arguments = if raw_arguments.trim().is_empty() {
+        None
+    } else {
+        Some(serde_json::from_str::<JsonValue>(raw_arguments).ok()?)
+    };
+    let mut params = json!({ "name": tool });
+    if let Some(arguments) = arguments {
+        params["arguments"] = arguments;
+    }
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "tools/call",
+        "params": params,
+    });
+
+    // Sorted so the command is stable across runs.
+    let mut headers: BTreeMap<String, String> = BTreeMap::new();
+    for (name, value) in http_headers.iter().flatten() {
+        let header = if is_sensitive_header(name) {
+            double_quote(&format!("{name}: {TOKEN_PLACEHOLDER}"))
+        } else {
+            single_quote(&format!("{name}: {value}"))
+        };
+        headers.insert(name.to_ascii_lowercase(), header);
+    }
+    for (name, env_var) in env_http_headers.iter().flatten() {
+        let value = if is_sensitive_header(name) {
+            TOKEN_PLACEHOLDER.to_string()
+        } else {
+            format!("${{{env_var}}}")
+        };
+        headers.insert(
+            name.to_ascii_lowercase(),
+            double_quote(&format!("{name}: {value}")),
+        );
+    }
+    if bearer_token_env_var.is_some() {
+        headers.insert(
+            "authorization".to_string(),
+            double_quote(&format!("Authorization: Bearer {TOKEN_PLACEHOLDER}")),
+        );
+    }
+
+    let mut command = format!(
+        "curl -X POST {} -H {} -H {}",
+        single_quote(url),
+        single_quote("Content-Type: application/json"),
+        single_quote("Accept: application/json, text/event-stream"),
+    );
+    for header in headers.values() {
+        command.push_str(" -H ");
+        command.push_str(header);
+    }
+    command.push_str(" --data ");
+    command.push_str(&single_quote(&body.to_string()));
+    Some(command)
+}
+
+fn is_sensitive_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    matches!(
+        name.as_str(),
+        "authorization" | "proxy-authorization" | "cookie"
+    ) || ["token", "secret", "api-key", "apikey"]
+        .iter()
+        .any(|marker| name.contains(marker))
+}
+
+fn single_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Quotes `value` so the shell still expands `$VAR` references in it.
+fn double_quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for ch in value.chars() {
+        if matches!(ch, '"' | '\\' | '`') {
+            quoted.push('\\');
+        }
+        quoted.push(ch);
+    }
+    quoted.push('"');
+    quoted
+}
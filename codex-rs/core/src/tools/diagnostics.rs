@@ -0,0 +1,262 @@
+//! Health and configuration report for a [`ToolRouter`]. See
+//! [`ToolRouter::diagnostics_report`].
+//!
+//! The router has no result cache, so the report carries no cache hit rates.
+//!
+//! [`ToolRouter`]: crate::tools::router::ToolRouter
+//! [`ToolRouter::diagnostics_report`]: crate::tools::router::ToolRouter::diagnostics_report
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use tokio::time::Instant;
+
+use crate::tools::resilience::CircuitState;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct DiagnosticsReport {
+    /// Every advertised or registered tool, sorted by name.
+    pub tools: Vec<ToolDiagnostics>,
+    /// MCP servers that contribute at least one tool, sorted by name.
+    pub mcp_servers: Vec<McpServerDiagnostics>,
+    /// Circuit breakers created by resilient dispatch, sorted by key.
+    pub circuit_breakers: Vec<CircuitBreakerDiagnostics>,
+    /// Human-readable descriptions of suspicious configuration.
+    pub anomalies: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ToolDiagnostics {
+    pub name: String,
+    pub status: ToolStatus,
+    pub supports_parallel_tool_calls: bool,
+    pub fallback: Option<String>,
+}
+
+/// What a tool palette needs to show one tool. See
+/// [`ToolRouter::tool_descriptors`].
+///
+/// [`ToolRouter::tool_descriptors`]: crate::tools::router::ToolRouter::tool_descriptors
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ToolDescriptor {
+    pub name: String,
+    pub supports_parallel_tool_calls: bool,
+    /// Server that serves the tool, for MCP tools.
+    pub mcp_server: Option<String>,
+    /// Whether the tool was supplied by the client as a `DynamicToolSpec`.
+    pub dynamic: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolStatus {
+    Enabled,
+    DisabledForTurn,
+    /// Advertised to the model, but no handler is registered.
+    MissingHandler,
+    /// Registered, but not advertised to the model.
+    Unadvertised,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct McpServerDiagnostics {
+    pub name: String,
+    pub tool_count: usize,
+    pub status: McpServerStatus,
+}
+
+/// Connectivity as observed through the server's circuit breaker.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum McpServerStatus {
+    /// No call has gone through resilient dispatch yet.
+    Unknown,
+    Healthy,
+    /// Recent calls failed, but the circuit is still closed.
+    Degraded,
+    Unreachable,
+    Recovering,
+}
+
+/// Result of [`ToolRouter::tool_health_check`].
+///
+/// [`ToolRouter::tool_health_check`]: crate::tools::router::ToolRouter::tool_health_check
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ToolHealthStatus {
+    /// How long the probe took; 0 for tools that are not probed.
+    Healthy { latency_ms: u64 },
+    /// Calls may work, but the tool is not fully available or could not be
+    /// probed.
+    Degraded { message: String },
+    /// Calls will fail.
+    Unhealthy { message: String },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CircuitBreakerDiagnostics {
+    pub key: String,
+    pub state: CircuitBreakerState,
+    /// Failures since the last success; only tracked while closed.
+    pub consecutive_failures: u32,
+    /// Time until an open circuit admits a probe.
+    pub reopens_in_ms: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitBreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl CircuitBreakerDiagnostics {
+    pub(crate) fn new(key: String, state: CircuitState, now: Instant) -> Self {
+        let (state, consecutive_failures, reopens_in_ms) = match state {
+            CircuitState::Closed {
+                consecutive_failures,
+            } => (CircuitBreakerState::Closed, consecutive_failures, None),
+            CircuitState::Open { until } => {
+                let remaining = until.saturating_duration_since(now).as_millis();
+                (
+                    CircuitBreakerState::Open,
+                    0,
+                    Some(u64::try_from(remaining).unwrap_or(u64::MAX)),
+                )
+            }
+            CircuitState::HalfOpen { .. } => (CircuitBreakerState::HalfOpen, 0, None),
+        };
+        Self {
+            key,
+            state,
+            consecutive_failures,
+            reopens_in_ms,
+        }
+    }
+
+    fn server_status(&self) -> McpServerStatus {
+        match self.state {
+            CircuitBreakerState::Closed if self.consecutive_failures == 0 => {
+                McpServerStatus::Healthy
+            }
+            CircuitBreakerState::Closed => McpServerStatus::Degraded,
+            CircuitBreakerState::Open => McpServerStatus::Unreachable,
+            CircuitBreakerState::HalfOpen => McpServerStatus::Recovering,
+        }
+    }
+}
+
+/// Groups MCP tools by server and derives each server's status from the
+/// circuit breaker keyed by its name.
+pub(crate) fn mcp_server_diagnostics(
+    tools: &[ToolDiagnostics],
+    circuit_breakers: &[CircuitBreakerDiagnostics],
+) -> Vec<McpServerDiagnostics> {
+    let mut tool_counts: BTreeMap<String, usize> = BTreeMap::new();
+    for tool in tools {
+        if let Some((server, _)) = crate::mcp::split_qualified_tool_name(&tool.name) {
+            *tool_counts.entry(server).or_default() += 1;
+        }
+    }
+    tool_counts
+        .into_iter()
+        .map(|(name, tool_count)| {
+            let status = circuit_breakers
+                .iter()
+                .find(|breaker| breaker.key == name)
+                .map_or(McpServerStatus::Unknown, |breaker| breaker.server_status());
+            McpServerDiagnostics {
+                name,
+                tool_count,
+                status,
+            }
+        })
+        .collect()
+}
+
+impl DiagnosticsReport {
+    pub fn to_json(&self) -> JsonValue {
+        serde_json::to_value(self).unwrap_or(JsonValue::Null)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Tool diagnostics\n\n## Tools\n\n");
+        if self.tools.is_empty() {
+            out.push_str("No tools.\n");
+        } else {
+            out.push_str("| Tool | Status | Parallel | Fallback |\n|---|---|---|---|\n");
+            for tool in &self.tools {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {} |",
+                    tool.name,
+                    label(&tool.status),
+                    if tool.supports_parallel_tool_calls {
+                        "yes"
+                    } else {
+                        "no"
+                    },
+                    tool.fallback.as_deref().unwrap_or("-"),
+                );
+            }
+        }
+
+        out.push_str("\n## MCP servers\n\n");
+        if self.mcp_servers.is_empty() {
+            out.push_str("No MCP servers.\n");
+        } else {
+            out.push_str("| Server | Tools | Status |\n|---|---|---|\n");
+            for server in &self.mcp_servers {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} |",
+                    server.name,
+                    server.tool_count,
+                    label(&server.status),
+                );
+            }
+        }
+
+        out.push_str("\n## Circuit breakers\n\n");
+        if self.circuit_breakers.is_empty() {
+            out.push_str("No circuit breakers.\n");
+        } else {
+            out.push_str(
+                "| Key | State | Consecutive failures | Reopens in |\n|---|---|---|---|\n",
+            );
+            for breaker in &self.circuit_breakers {
+                let reopens_in = breaker
+                    .reopens_in_ms
+                    .map_or_else(|| "-".to_string(), |ms| format!("{ms}ms"));
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {reopens_in} |",
+                    breaker.key,
+                    label(&breaker.state),
+                    breaker.consecutive_failures,
+                );
+            }
+        }
+
+        out.push_str("\n## Anomalies\n\n");
+        if self.anomalies.is_empty() {
+            out.push_str("None.\n");
+        } else {
+            for anomaly in &self.anomalies {
+                let _ = writeln!(out, "- {anomaly}");
+            }
+        }
+        out
+    }
+}
+
+/// The serialized (snake_case) name of a unit enum variant.
+fn label(value: &impl Serialize) -> String {
+    match serde_json::to_value(value) {
+        Ok(JsonValue::String(label)) => label,
+        _ => String::new(),
+    }
+}
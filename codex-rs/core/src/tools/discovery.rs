@@ -0,0 +1,277 @@
+//! Builds dynamic tool specs by asking an MCP server which tools it exposes.
+//!
+//! See [`ToolRegistry::discover_tools`] and
+//! [`ToolRegistry::introspect_mcp_server`].
+//!
+//! [`ToolRegistry::discover_tools`]: crate::tools::registry::ToolRegistry::discover_tools
+//! [`ToolRegistry::introspect_mcp_server`]: crate::tools::registry::ToolRegistry::introspect_mcp_server
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+
+use codex_protocol::dynamic_tools::DynamicToolSpec;
+use rmcp::model::Tool;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use sha2::Digest;
+use sha2::Sha256;
+use thiserror::Error;
+use tracing::warn;
+
+/// Spec hash last discovered for each server in this process, so a server
+/// that changes its tools between connections is noticed.
+static PINNED_SPEC_HASHES: LazyLock<Mutex<HashMap<String, String>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Error)]
+pub enum DiscoveryError {
+    #[error("MCP server {server_name} is disabled")]
+    Disabled { server_name: String },
+    #[error("failed to list tools from MCP server {server_name}: {message}")]
+    ListTools {
+        server_name: String,
+        message: String,
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum IntrospectError {
+    #[error("failed to list tools from MCP server {server_name}: {message}")]
+    ListTools {
+        server_name: String,
+        message: String,
+    },
+}
+
+/// A tool exactly as the live MCP server describes it.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct McpToolInfo {
+    pub name: String,
+    pub description: Option<String>,
+    pub input_schema: JsonValue,
+    pub output_schema: Option<JsonValue>,
+}
+
+impl From<Tool> for McpToolInfo {
+    fn from(tool: Tool) -> Self {
+        Self {
+            name: tool.name.into_owned(),
+            description: tool.description.map(std::borrow::Cow::into_owned),
+            input_schema: JsonValue::Object((*tool.input_schema).clone()),
+            output_schema: tool
+                .output_schema
+                .map(|schema| JsonValue::Object((*schema).clone())),
+        }
+    }
+}
+
+pub(crate) fn dynamic_spec_for_tool(tool: Tool) -> DynamicToolSpec {
+    DynamicToolSpec {
+        name: tool.name.into_owned(),
+        description: tool
+            .description
+            .map(std::borrow::Cow::into_owned)
+            .unwrap_or_default(),
+        input_schema: JsonValue::Object((*tool.input_schema).clone()),
+    }
+}
+
+/// Stable hash of `specs`, independent of the order the server listed them.
+pub fn spec_hash(specs: &[DynamicToolSpec]) -> String {
+    let mut sorted: Vec<&DynamicToolSpec> = specs.iter().collect();
+    sorted.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut hasher = Sha256::new();
+    for spec in sorted {
+        hasher.update(serde_json::to_vec(spec).unwrap_or_default());
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Records `hash` as the pinned spec hash for `server_name` and returns the
+/// previous hash if it differed.
+pub(crate) fn pin_spec_hash(server_name: &str, hash: String) -> Option<String> {
+    let mut pinned = PINNED_SPEC_HASHES
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    match pinned.insert(server_name.to_string(), hash.clone()) {
+        Some(previous) if previous != hash => {
+            warn!(
+                "MCP server {server_name} changed its tools since it was last discovered \
+                 (spec hash {previous} -> {hash})"
+            );
+            Some(previous)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::types::McpServerConfig;
+    use crate::config::types::McpServerTransportConfig;
+    use crate::tools::registry::ToolRegistry;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn spec(name: &str) -> DynamicToolSpec {
+        DynamicToolSpec {
+            name: name.to_string(),
+            description: format!("{name} tool"),
+            input_schema: json!({"type": "object", "properties": {}}),
+        }
+    }
+
+    #[tokio::test]
+    async fn discover_tools_lists_every_tool_on_the_server() -> anyhow::Result<()> {
+        let server = McpServerConfig {
+            transport: McpServerTransportConfig::Stdio {
+                command: core_test_support::stdio_server_bin()?,
+                args: Vec::new(),
+                env: None,
+                env_vars: Vec::new(),
+                cwd: None,
+            },
+            enabled: true,
+            required: false,
+            disabled_reason: None,
+            startup_timeout_sec: Some(Duration::from_secs(10)),
+            tool_timeout_sec: None,
+            enabled_tools: None,
+            disabled_tools: None,
+            scopes: None,
+            oauth_resource: None,
+            max_concurrent_calls: None,
+            max_calls_per_minute: None,
+        };
+
+        let specs = ToolRegistry::discover_tools("discovery_test", &server).await?;
+
+        let mut names: Vec<&str> = specs.iter().map(|spec| spec.name.as_str()).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["echo", "image", "image_scenario"]);
+        assert!(specs.iter().all(|spec| spec.input_schema.is_object()));
+        let rediscovered = ToolRegistry::discover_tools("discovery_test", &server).await?;
+        assert_eq!(spec_hash(&rediscovered), spec_hash(&specs));
+
+        Ok(())
+    }
+
+    /// Answers streamable HTTP JSON-RPC requests as an MCP server exposing
+    /// [`mock_tools`].
+    struct MockMcpServer;
+
+    fn mock_tools() -> Vec<JsonValue> {
+        [
+            "create_issue",
+            "close_issue",
+            "list_issues",
+            "add_label",
+            "search",
+        ]
+        .iter()
+        .map(|name| {
+            json!({
+                "name": name,
+                "description": format!("{name} tool"),
+                "inputSchema": {
+                    "type": "object",
+                    "properties": { "id": { "type": "integer" } },
+                    "required": ["id"],
+                },
+            })
+        })
+        .collect()
+    }
+
+    impl wiremock::Respond for MockMcpServer {
+        fn respond(&self, request: &wiremock::Request) -> wiremock::ResponseTemplate {
+            let body: JsonValue = serde_json::from_slice(&request.body).unwrap_or_default();
+            let id = body.get("id").cloned().unwrap_or(JsonValue::Null);
+            let result = match body.get("method").and_then(JsonValue::as_str) {
+                Some("initialize") => json!({
+                    "protocolVersion": body
+                        .pointer("/params/protocolVersion")
+                        .cloned()
+                        .unwrap_or_else(|| json!("2025-06-18")),
+                    "capabilities": { "tools": {} },
+                    "serverInfo": { "name": "mock", "version": "1.0.0" },
+                }),
+                Some("tools/list") => json!({ "tools": mock_tools() }),
+                _ => return wiremock::ResponseTemplate::new(202),
+            };
+            wiremock::ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": result,
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn introspect_mcp_server_returns_every_tool_with_its_schema() -> anyhow::Result<()> {
+        let mock = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("POST"))
+            .and(wiremock::matchers::path("/mcp"))
+            .respond_with(MockMcpServer)
+            .mount(&mock)
+            .await;
+        let server = McpServerConfig {
+            transport: McpServerTransportConfig::StreamableHttp {
+                url: format!("{}/mcp", mock.uri()),
+                bearer_token_env_var: None,
+                http_headers: None,
+                env_http_headers: None,
+            },
+            // Introspection looks past both of these.
+            enabled: false,
+            required: false,
+            disabled_reason: None,
+            startup_timeout_sec: Some(Duration::from_secs(10)),
+            tool_timeout_sec: None,
+            enabled_tools: Some(vec!["search".to_string()]),
+            disabled_tools: None,
+            scopes: None,
+            oauth_resource: None,
+            max_concurrent_calls: None,
+            max_calls_per_minute: None,
+        };
+
+        let tools = ToolRegistry::introspect_mcp_server("introspect_test", &server).await?;
+
+        let expected: Vec<McpToolInfo> = mock_tools()
+            .into_iter()
+            .map(|tool| McpToolInfo {
+                name: tool["name"].as_str().unwrap_or_default().to_string(),
+                description: tool["description"].as_str().map(str::to_string),
+                input_schema: tool["inputSchema"].clone(),
+                output_schema: None,
+            })
+            .collect();
+        assert_eq!(tools, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn spec_hash_ignores_listing_order() {
+        assert_eq!(
+            spec_hash(&[spec("a"), spec("b")]),
+            spec_hash(&[spec("b"), spec("a")])
+        );
+        assert!(spec_hash(&[spec("a")]) != spec_hash(&[spec("a"), spec("b")]));
+    }
+
+    #[test]
+    fn pin_spec_hash_reports_changes_on_reconnect() {
+        let server = "pin_spec_hash_reports_changes_on_reconnect";
+        let first = spec_hash(&[spec("a")]);
+        let second = spec_hash(&[spec("a"), spec("b")]);
+
+        assert_eq!(pin_spec_hash(server, first.clone()), None);
+        assert_eq!(pin_spec_hash(server, first.clone()), None);
+        assert_eq!(pin_spec_hash(server, second), Some(first));
+    }
+}
@@ -0,0 +1,99 @@
+//! Structured JSON records of tool dispatches.
+//!
+//! A router built with [`ToolRouter::log_to_structured_json`] emits one
+//! `tracing` event per dispatch under [`TOOL_DISPATCH_LOG_TARGET`]. Install
+//! [`structured_json_layer`] to write those events as one JSON object per
+//! line, e.g.
+//!
+//! ```json
+//! {"timestamp":"...","level":"INFO","message":"tool call dispatched","call_id":"call-1","tool_name":"shell","session_id":"...","duration_ms":12,"success":true,"error_kind":"none"}
+//! ```
+//!
+//! [`ToolRouter::log_to_structured_json`]: crate::tools::router::ToolRouter::log_to_structured_json
+
+use std::time::Duration;
+
+use codex_protocol::ThreadId;
+use codex_protocol::models::ResponseInputItem;
+use tracing::Level;
+use tracing::Subscriber;
+use tracing::info;
+use tracing::warn;
+use tracing_subscriber::Layer;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::function_tool::FunctionCallError;
+
+/// `tracing` target of the per-dispatch events.
+pub const TOOL_DISPATCH_LOG_TARGET: &str = "codex_core::tool_dispatch";
+
+/// A layer that writes only the per-dispatch events, as flattened JSON, to
+/// `make_writer`.
+pub fn structured_json_layer<S, W>(make_writer: W) -> impl Layer<S>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt::layer()
+        .json()
+        .flatten_event(true)
+        .with_current_span(false)
+        .with_span_list(false)
+        .with_target(false)
+        .with_writer(make_writer)
+        .with_filter(Targets::new().with_target(TOOL_DISPATCH_LOG_TARGET, Level::INFO))
+}
+
+/// Emits the dispatch event for one call. Failed calls are logged at `WARN`.
+pub(crate) fn log_dispatch(
+    call_id: &str,
+    tool_name: &str,
+    session_id: ThreadId,
+    duration: Duration,
+    result: &Result<ResponseInputItem, FunctionCallError>,
+) {
+    let duration_ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+    let error_kind = error_kind(result);
+    let success = error_kind == "none";
+    if success {
+        info!(
+            target: TOOL_DISPATCH_LOG_TARGET,
+            call_id,
+            tool_name,
+            session_id = %session_id,
+            duration_ms,
+            success,
+            error_kind,
+            "tool call dispatched"
+        );
+    } else {
+        warn!(
+            target: TOOL_DISPATCH_LOG_TARGET,
+            call_id,
+            tool_name,
+            session_id = %session_id,
+            duration_ms,
+            success,
+            error_kind,
+            "tool call dispatched"
+        );
+    }
+}
+
+fn error_kind(result: &Result<ResponseInputItem, FunctionCallError>) -> &'static str {
+    match result {
+        Ok(
+            ResponseInputItem::FunctionCallOutput { output, .. }
+            | ResponseInputItem::CustomToolCallOutput { output, .. },
+        ) if output.success == Some(false) => "tool_failed",
+        Ok(ResponseInputItem::McpToolCallOutput { result, .. }) => match result {
+            Ok(result) if result.is_error == Some(true) => "tool_failed",
+            Ok(_) => "none",
+            Err(_) => "mcp_error",
+        },
+        Ok(_) => "none",
+        Err(err) => err.code(),
+    }
+}
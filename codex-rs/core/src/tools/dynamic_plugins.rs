@@ -0,0 +1,436 @@
+//! Tool bundles loaded from shared libraries (`.so` / `.dylib` / `.dll`).
+//!
+//! A plugin exports an unmangled C function named `codex_tool_plugin_v1`
+//! that returns a pointer to a [`ToolPluginV1`]:
+//!
+//! ```ignore
+//! #[unsafe(no_mangle)]
+//! pub extern "C" fn codex_tool_plugin_v1() -> *const ToolPluginV1 { ... }
+//! ```
+//!
+//! Only `#[repr(C)]` types cross the library boundary, so a plugin does not
+//! need the host's compiler or `codex-core` version; it mirrors the types
+//! below. A breaking change to them gets a new symbol and
+//! [`TOOL_PLUGIN_ABI_VERSION`].
+
+use std::collections::HashSet;
+use std::ffi::CStr;
+use std::ffi::CString;
+use std::ffi::c_char;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use libloading::Library;
+use thiserror::Error;
+
+use crate::client_common::tools::ResponsesApiTool;
+use crate::client_common::tools::ToolSpec;
+use crate::tools::registry::ConfiguredToolSpec;
+use crate::tools::registry::ToolRegistry;
+use crate::tools::spec::parse_tool_input_schema;
+
+/// Name of the symbol every plugin library must export.
+pub const TOOL_PLUGIN_SYMBOL: &str = "codex_tool_plugin_v1";
+
+/// Version a plugin reports in [`ToolPluginV1::abi_version`].
+pub const TOOL_PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Runs a plugin tool with the call's NUL-terminated JSON `arguments`. The
+/// tool stores a NUL-terminated UTF-8 string in `*output`, which the host
+/// releases with [`ToolPluginV1::free_output`]: the output for the model
+/// when it returns 0, or an error message otherwise.
+pub type ToolCallFnV1 =
+    unsafe extern "C" fn(arguments: *const c_char, output: *mut *mut c_char) -> i32;
+
+/// Releases a string a tool stored in its `output`.
+pub type FreeOutputFnV1 = unsafe extern "C" fn(output: *mut c_char);
+
+type ToolPluginEntryPoint = unsafe extern "C" fn() -> *const ToolPluginV1;
+
+/// A plugin tool. The strings are NUL-terminated UTF-8 and, like the struct,
+/// must stay valid while the library is loaded.
+#[repr(C)]
+pub struct ToolDefV1 {
+    pub name: *const c_char,
+    pub description: *const c_char,
+    /// JSON schema for the tool's arguments.
+    pub input_schema: *const c_char,
+    pub call: ToolCallFnV1,
+}
+
+/// What `codex_tool_plugin_v1` returns. Must stay valid while the library is
+/// loaded.
+#[repr(C)]
+pub struct ToolPluginV1 {
+    /// Must be [`TOOL_PLUGIN_ABI_VERSION`].
+    pub abi_version: u32,
+    /// Array of `tool_count` tools.
+    pub tools: *const ToolDefV1,
+    pub tool_count: usize,
+    pub free_output: FreeOutputFnV1,
+}
+
+/// Handler for a plugin tool: receives the raw JSON arguments and returns
+/// the output for the model, or an error message.
+pub(crate) type ToolBundleHandler = Box<dyn Fn(&str) -> Result<String, String> + Send + Sync>;
+
+pub(crate) struct ToolBundle {
+    pub(crate) name: String,
+    pub(crate) description: String,
+    /// JSON schema for the tool's arguments.
+    pub(crate) schema: serde_json::Value,
+    pub(crate) handler: ToolBundleHandler,
+}
+
+#[derive(Debug, Error)]
+pub enum PluginLoadError {
+    #[error("failed to load plugin {}: {source}", path.display())]
+    Open {
+        path: PathBuf,
+        source: libloading::Error,
+    },
+    #[error("plugin {} does not export {TOOL_PLUGIN_SYMBOL}: {source}", path.display())]
+    MissingSymbol {
+        path: PathBuf,
+        source: libloading::Error,
+    },
+    #[error(
+        "plugin {} uses ABI version {abi_version}, but only version {TOOL_PLUGIN_ABI_VERSION} is supported",
+        path.display()
+    )]
+    UnsupportedAbi { path: PathBuf, abi_version: u32 },
+    #[error("plugin {} is invalid: {message}", path.display())]
+    InvalidPlugin { path: PathBuf, message: String },
+    #[error("plugin tool {name} is invalid: {message}")]
+    InvalidTool { name: String, message: String },
+    #[error("plugins must be loaded before the router is shared")]
+    RouterShared,
+}
+
+/// Opens `path` and collects the tool bundles it exports. The bundles' handlers
+/// keep the library loaded.
+pub(crate) fn open_tool_bundles(path: &Path) -> Result<Vec<ToolBundle>, PluginLoadError> {
+    // SAFETY: loading a library runs its initializers. Plugins are trusted
+    // code installed by the user, same as any other binary on the machine.
+    let library = unsafe { Library::new(path) }.map_err(|source| PluginLoadError::Open {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    // SAFETY: the plugin ABI fixes the symbol's signature.
+    let plugin = unsafe {
+        let entry_point = library
+            .get::<ToolPluginEntryPoint>(TOOL_PLUGIN_SYMBOL.as_bytes())
+            .map_err(|source| PluginLoadError::MissingSymbol {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        entry_point()
+    };
+    // SAFETY: the plugin ABI requires `plugin` to stay valid while the
+    // library is loaded, and every handler holds the library.
+    unsafe { read_plugin(path, plugin, Some(Arc::new(library))) }
+}
+
+/// Copies the tools `plugin` describes into bundles whose handlers call into
+/// the plugin and hold `library`.
+///
+/// # Safety
+///
+/// `plugin`, if not null, must point to a [`ToolPluginV1`] that follows the
+/// plugin ABI and stays valid while `library` is loaded.
+unsafe fn read_plugin(
+    path: &Path,
+    plugin: *const ToolPluginV1,
+    library: Option<Arc<Library>>,
+) -> Result<Vec<ToolBundle>, PluginLoadError> {
+    let invalid_plugin = |message: &str| PluginLoadError::InvalidPlugin {
+        path: path.to_path_buf(),
+        message: message.to_string(),
+    };
+    // SAFETY: guaranteed by the caller.
+    let Some(plugin) = (unsafe { plugin.as_ref() }) else {
+        return Err(invalid_plugin("entry point returned a null pointer"));
+    };
+    if plugin.abi_version != TOOL_PLUGIN_ABI_VERSION {
+        return Err(PluginLoadError::UnsupportedAbi {
+            path: path.to_path_buf(),
+            abi_version: plugin.abi_version,
+        });
+    }
+    let tools = match plugin.tool_count {
+        0 => &[][..],
+        _ if plugin.tools.is_null() => return Err(invalid_plugin("tools is a null pointer")),
+        // SAFETY: the ABI makes `tools` an array of `tool_count` tools.
+        count => unsafe { std::slice::from_raw_parts(plugin.tools, count) },
+    };
+
+    let mut bundles = Vec::with_capacity(tools.len());
+    for (index, tool) in tools.iter().enumerate() {
+        let read = |field: *const c_char, field_name: &str| {
+            (!field.is_null())
+                // SAFETY: the ABI makes each string NUL-terminated.
+                .then(|| unsafe { CStr::from_ptr(field) })
+                .and_then(|text| text.to_str().ok())
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    invalid_plugin(&format!(
+                        "tool {index} has a null or non-UTF-8 {field_name}"
+                    ))
+                })
+        };
+        let name = read(tool.name, "name")?;
+        let description = read(tool.description, "description")?;
+        let schema =
+            serde_json::from_str(&read(tool.input_schema, "input_schema")?).map_err(|err| {
+                PluginLoadError::InvalidTool {
+                    name: name.clone(),
+                    message: format!("input schema is not JSON: {err}"),
+                }
+            })?;
+        let call = tool.call;
+        let free_output = plugin.free_output;
+        let library = library.clone();
+        bundles.push(ToolBundle {
+            name,
+            description,
+            schema,
+            handler: Box::new(move |arguments| {
+                let _library = &library;
+                call_plugin_tool(call, free_output, arguments)
+            }),
+        });
+    }
+    Ok(bundles)
+}
+
+fn call_plugin_tool(
+    call: ToolCallFnV1,
+    free_output: FreeOutputFnV1,
+    arguments: &str,
+) -> Result<String, String> {
+    let arguments = CString::new(arguments)
+        .map_err(|_| "tool arguments must not contain a NUL byte".to_string())?;
+    let mut output: *mut c_char = std::ptr::null_mut();
+    // SAFETY: `arguments` is NUL-terminated and outlives the call, and
+    // `output` is a valid place for the tool to store its string.
+    let status = unsafe { call(arguments.as_ptr(), &mut output) };
+    let text = if output.is_null() {
+        String::new()
+    } else {
+        // SAFETY: the tool stored a NUL-terminated string, which is copied
+        // before the plugin releases it.
+        unsafe {
+            let text = CStr::from_ptr(output).to_string_lossy().into_owned();
+            free_output(output);
+            text
+        }
+    };
+    if status == 0 { Ok(text) } else { Err(text) }
+}
+
+/// Registers `bundles` as function tools and advertises them in `specs`.
+/// Every bundle is validated before any is registered, so a bad bundle
+/// leaves the registry untouched.
+pub(crate) fn register_bundles(
+    registry: &mut ToolRegistry,
+    specs: &mut Vec<ConfiguredToolSpec>,
+    bundles: Vec<ToolBundle>,
+) -> Result<Vec<String>, PluginLoadError> {
+    let mut seen = HashSet::new();
+    let mut validated = Vec::with_capacity(bundles.len());
+    for bundle in bundles {
+        let invalid = |message: String| PluginLoadError::InvalidTool {
+            name: bundle.name.clone(),
+            message,
+        };
+        let parameters =
+            parse_tool_input_schema(&bundle.schema).map_err(|err| invalid(err.to_string()))?;
+        if registry.handler(&bundle.name).is_some() || !seen.insert(bundle.name.clone()) {
+            return Err(invalid(
+                "a tool with this name is already registered".to_string(),
+            ));
+        }
+        validated.push((bundle, parameters));
+    }
+
+    let mut names = Vec::with_capacity(validated.len());
+    for (bundle, parameters) in validated {
+        registry
+            .register_function_tool(&bundle.name, bundle.schema, bundle.handler)
+            .map_err(|err| PluginLoadError::InvalidTool {
+                name: bundle.name.clone(),
+                message: err.to_string(),
+            })?;
+        specs.push(ConfiguredToolSpec::new(
+            ToolSpec::Function(ResponsesApiTool {
+                name: bundle.name.clone(),
+                description: bundle.description,
+                strict: false,
+                parameters,
+            }),
+            false,
+        ));
+        names.push(bundle.name);
+    }
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    fn shout(arguments: &str) -> Result<String, String> {
+        Ok(arguments.to_uppercase())
+    }
+
+    fn bundle(name: &str) -> ToolBundle {
+        ToolBundle {
+            name: name.to_string(),
+            description: "Shouts the arguments back.".to_string(),
+            schema: serde_json::json!({"type": "object", "properties": {}}),
+            handler: Box::new(shout),
+        }
+    }
+
+    unsafe extern "C" fn echo(arguments: *const c_char, output: *mut *mut c_char) -> i32 {
+        // SAFETY: the host passes NUL-terminated arguments and a valid
+        // output place.
+        unsafe {
+            let arguments = CStr::from_ptr(arguments).to_string_lossy();
+            *output = CString::new(format!("echo {arguments}"))
+                .unwrap_or_default()
+                .into_raw();
+        }
+        0
+    }
+
+    unsafe extern "C" fn refuse(_arguments: *const c_char, output: *mut *mut c_char) -> i32 {
+        // SAFETY: the host passes a valid output place.
+        unsafe { *output = c"refused".to_owned().into_raw() };
+        1
+    }
+
+    unsafe extern "C" fn free_output(output: *mut c_char) {
+        // SAFETY: every output was allocated by `CString::into_raw`.
+        drop(unsafe { CString::from_raw(output) });
+    }
+
+    fn tool(name: &'static CStr, call: ToolCallFnV1) -> ToolDefV1 {
+        ToolDefV1 {
+            name: name.as_ptr(),
+            description: c"Test tool.".as_ptr(),
+            input_schema: cr#"{"type": "object", "properties": {}}"#.as_ptr(),
+            call,
+        }
+    }
+
+    fn plugin(abi_version: u32, tools: &[ToolDefV1]) -> ToolPluginV1 {
+        ToolPluginV1 {
+            abi_version,
+            tools: tools.as_ptr(),
+            tool_count: tools.len(),
+            free_output,
+        }
+    }
+
+    #[test]
+    fn read_plugin_calls_through_the_c_abi() {
+        let tools = [tool(c"echo", echo), tool(c"refuse", refuse)];
+        let plugin = plugin(TOOL_PLUGIN_ABI_VERSION, &tools);
+
+        // SAFETY: `plugin` and its tools outlive the call.
+        let bundles = unsafe { read_plugin(Path::new("test-plugin"), &plugin, None) }
+            .expect("plugin is valid");
+
+        assert_eq!(
+            bundles
+                .iter()
+                .map(|bundle| bundle.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["echo", "refuse"]
+        );
+        assert_eq!(
+            bundles[0].schema,
+            serde_json::json!({"type": "object", "properties": {}})
+        );
+        assert_eq!((bundles[0].handler)("{}"), Ok("echo {}".to_string()));
+        assert_eq!((bundles[1].handler)("{}"), Err("refused".to_string()));
+    }
+
+    #[test]
+    fn read_plugin_rejects_other_abi_versions() {
+        let tools = [tool(c"echo", echo)];
+        let plugin = plugin(TOOL_PLUGIN_ABI_VERSION + 1, &tools);
+
+        // SAFETY: `plugin` and its tools outlive the call.
+        let result = unsafe { read_plugin(Path::new("test-plugin"), &plugin, None) };
+
+        assert!(matches!(
+            result,
+            Err(PluginLoadError::UnsupportedAbi { abi_version, .. })
+                if abi_version == TOOL_PLUGIN_ABI_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn read_plugin_rejects_null_strings() {
+        let mut tools = [tool(c"echo", echo)];
+        tools[0].name = std::ptr::null();
+        let plugin = plugin(TOOL_PLUGIN_ABI_VERSION, &tools);
+
+        // SAFETY: `plugin` and its tools outlive the call.
+        let result = unsafe { read_plugin(Path::new("test-plugin"), &plugin, None) };
+
+        assert!(matches!(result, Err(PluginLoadError::InvalidPlugin { .. })));
+    }
+
+    #[test]
+    fn register_bundles_adds_handlers_and_specs() {
+        let mut registry = ToolRegistry::new(HashMap::new());
+        let mut specs = Vec::new();
+
+        let names = register_bundles(
+            &mut registry,
+            &mut specs,
+            vec![bundle("shout"), bundle("shout_again")],
+        )
+        .expect("bundles register");
+
+        assert_eq!(names, vec!["shout".to_string(), "shout_again".to_string()]);
+        assert!(registry.handler("shout").is_some());
+        assert_eq!(
+            specs
+                .iter()
+                .map(|spec| spec.spec.name().to_string())
+                .collect::<Vec<_>>(),
+            names
+        );
+    }
+
+    #[test]
+    fn duplicate_bundle_names_register_nothing() {
+        let mut registry = ToolRegistry::new(HashMap::new());
+        let mut specs = Vec::new();
+
+        let err = register_bundles(
+            &mut registry,
+            &mut specs,
+            vec![bundle("shout"), bundle("shout")],
+        )
+        .expect_err("duplicate names are rejected");
+
+        assert!(matches!(err, PluginLoadError::InvalidTool { .. }));
+        assert!(registry.handler("shout").is_none());
+        assert!(specs.is_empty());
+    }
+
+    #[test]
+    fn missing_library_reports_open_error() {
+        let result = open_tool_bundles(Path::new("/nonexistent/libcodex_plugin.so"));
+
+        assert!(matches!(result, Err(PluginLoadError::Open { .. })));
+    }
+}
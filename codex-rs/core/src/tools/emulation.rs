@@ -0,0 +1,221 @@
+//! Tool calls for models whose backend has no native tool calling.
+//!
+//! Some models served by local inference servers were never trained with a
+//! tool-call template, so the server rejects or ignores `tools`. For those
+//! models the function tools are described in the instructions instead, the
+//! model answers with a single JSON object naming the tool, and that reply is
+//! turned back into a regular function call before it reaches the router.
+//! Earlier calls and their outputs are sent back as plain messages, since the
+//! server cannot accept `function_call` items either.
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::client_common::tools::ToolSpec;
+
+const EMULATED_CALL_ID_PREFIX: &str = "emulated_";
+
+#[derive(Deserialize)]
+struct EmulatedCall {
+    tool: String,
+    #[serde(default)]
+    arguments: Option<JsonValue>,
+}
+
+/// Describes the function tools in `specs` and how to call them. Returns
+/// `None` when there are no function tools to offer.
+pub(crate) fn emulated_tools_instructions(specs: &[ToolSpec]) -> Option<String> {
+    let tools = specs
+        .iter()
+        .filter_map(|spec| match spec {
+            ToolSpec::Function(tool) => Some(format!(
+                "### {}\n{}\nParameters (JSON Schema): {}",
+                tool.name,
+                tool.description,
+                serde_json::to_string(&tool.parameters).unwrap_or_default()
+            )),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if tools.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "## Tools\n\
+         You can call the tools below. To call one, reply with nothing but a JSON object of the form \
+         {{\"tool\": \"<name>\", \"arguments\": {{...}}}} and wait for its result before continuing. \
+         Call one tool per reply. When you are done, answer normally without JSON.\n\n{}",
+        tools.join("\n\n")
+    ))
+}
+
+/// Turns an assistant reply that consists of a single tool-call object into
+/// a function call. Any other item is left alone.
+pub(crate) fn parse_emulated_tool_call(item: &ResponseItem) -> Option<ResponseItem> {
+    let ResponseItem::Message { role, content, .. } = item else {
+        return None;
+    };
+    if role != "assistant" {
+        return None;
+    }
+    let text = content
+        .iter()
+        .filter_map(|content| match content {
+            ContentItem::OutputText { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<String>();
+    let text = text.trim();
+    let text = text
+        .strip_prefix("```json")
+        .or_else(|| text.strip_prefix("```"))
+        .and_then(|text| text.strip_suffix("```"))
+        .map_or(text, str::trim);
+    let call: EmulatedCall = serde_json::from_str(text).ok()?;
+    if call.tool.is_empty() {
+        return None;
+    }
+    Some(ResponseItem::FunctionCall {
+        id: None,
+        name: call.tool,
+        arguments: call.arguments.unwrap_or_else(|| json!({})).to_string(),
+        call_id: format!("{EMULATED_CALL_ID_PREFIX}{}", Uuid::new_v4()),
+    })
+}
+
+/// Rewrites function calls and their outputs as the plain messages the model
+/// exchanged, so the history can be replayed to a backend without tools.
+pub(crate) fn lower_tool_history(input: Vec<ResponseItem>) -> Vec<ResponseItem> {
+    input
+        .into_iter()
+        .map(|item| match item {
+            ResponseItem::FunctionCall {
+                name, arguments, ..
+            } => {
+                let arguments = serde_json::from_str::<JsonValue>(&arguments)
+                    .unwrap_or(JsonValue::String(arguments));
+                text_message(
+                    "assistant",
+                    ContentItem::OutputText {
+                        text: json!({ "tool": name, "arguments": arguments }).to_string(),
+                    },
+                )
+            }
+            ResponseItem::FunctionCallOutput { output, .. } => text_message(
+                "user",
+                ContentItem::InputText {
+                    text: format!(
+                        "Tool result:\n{}",
+                        output.body.to_text().unwrap_or_default()
+                    ),
+                },
+            ),
+            item => item,
+        })
+        .collect()
+}
+
+fn text_message(role: &str, content: ContentItem) -> ResponseItem {
+    ResponseItem::Message {
+        id: None,
+        role: role.to_string(),
+        content: vec![content],
+        end_turn: None,
+        phase: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    fn assistant(text: &str) -> ResponseItem {
+        text_message(
+            "assistant",
+            ContentItem::OutputText {
+                text: text.to_string(),
+            },
+        )
+    }
+
+    #[test]
+    fn json_reply_becomes_function_call() {
+        let item = assistant(
+            "```json\n{\"tool\": \"shell_command\", \"arguments\": {\"command\": \"ls\"}}\n```",
+        );
+
+        let Some(ResponseItem::FunctionCall {
+            name,
+            arguments,
+            call_id,
+            ..
+        }) = parse_emulated_tool_call(&item)
+        else {
+            panic!("expected an emulated function call");
+        };
+
+        assert_eq!(name, "shell_command");
+        assert_eq!(arguments, r#"{"command":"ls"}"#);
+        assert!(call_id.starts_with(EMULATED_CALL_ID_PREFIX));
+    }
+
+    #[test]
+    fn prose_reply_is_left_alone() {
+        assert_eq!(parse_emulated_tool_call(&assistant("All done.")), None);
+        assert_eq!(
+            parse_emulated_tool_call(&assistant(r#"{"answer": 42}"#)),
+            None
+        );
+    }
+
+    #[test]
+    fn history_is_replayed_as_messages() {
+        let lowered = lower_tool_history(vec![
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell_command".to_string(),
+                arguments: r#"{"command":"ls"}"#.to_string(),
+                call_id: "emulated_1".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "emulated_1".to_string(),
+                output: FunctionCallOutputPayload::from_text("Cargo.toml".to_string()),
+            },
+        ]);
+
+        let [
+            ResponseItem::Message {
+                role: call_role,
+                content: call_content,
+                ..
+            },
+            output,
+        ] = lowered.as_slice()
+        else {
+            panic!("expected two messages, got {lowered:?}");
+        };
+        let [ContentItem::OutputText { text: call_text }] = call_content.as_slice() else {
+            panic!("expected the call as output text, got {call_content:?}");
+        };
+        assert_eq!(call_role, "assistant");
+        assert_eq!(
+            serde_json::from_str::<JsonValue>(call_text).ok(),
+            Some(json!({ "tool": "shell_command", "arguments": { "command": "ls" } }))
+        );
+        assert_eq!(
+            output,
+            &text_message(
+                "user",
+                ContentItem::InputText {
+                    text: "Tool result:\nCargo.toml".to_string(),
+                },
+            )
+        );
+    }
+}
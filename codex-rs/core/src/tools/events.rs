@@ -14,9 +14,13 @@ use crate::protocol::FileChange;
 use crate::protocol::PatchApplyBeginEvent;
 use crate::protocol::PatchApplyEndEvent;
 use crate::protocol::PatchApplyStatus;
-use crate::protocol::TurnDiffEvent;
 use crate::tools::context::SharedTurnDiffTracker;
+use crate::tools::output_policy;
 use crate::tools::sandboxing::ToolError;
+use crate::truncate::TruncationPolicy;
+use codex_hooks::HookEvent;
+use codex_hooks::HookEventPatchApplied;
+use codex_hooks::HookEventToolDenied;
 use codex_protocol::parse_command::ParsedCommand;
 use std::collections::HashMap;
 use std::path::Path;
@@ -279,6 +283,14 @@ impl ToolEmitter {
         }
     }
 
+    fn tool_name(&self) -> &'static str {
+        match self {
+            Self::Shell { .. } => "shell",
+            Self::ApplyPatch { .. } => "apply_patch",
+            Self::UnifiedExec { .. } => "exec_command",
+        }
+    }
+
     pub async fn begin(&self, ctx: ToolEventCtx<'_>) {
         self.emit(ctx, ToolEventStage::Begin).await;
     }
@@ -288,11 +300,18 @@ impl ToolEmitter {
         output: &ExecToolCallOutput,
         ctx: ToolEventCtx<'_>,
     ) -> String {
+        // With `[tool_output]` configured, the registry cuts the result to the
+        // tool's own limit, so the full output is passed on.
+        let truncation_policy = if output_policy::is_configured(&ctx.turn.config.tool_output) {
+            TruncationPolicy::Bytes(usize::MAX)
+        } else {
+            ctx.turn.truncation_policy
+        };
         match self {
             Self::Shell { freeform: true, .. } => {
-                super::format_exec_output_for_model_freeform(output, ctx.turn.truncation_policy)
+                super::format_exec_output_for_model_freeform(output, truncation_policy)
             }
-            _ => super::format_exec_output_for_model_structured(output, ctx.turn.truncation_policy),
+            _ => super::format_exec_output_for_model_structured(output, truncation_policy),
         }
     }
 
@@ -346,6 +365,19 @@ impl ToolEmitter {
                 } else {
                     msg
                 };
+                ctx.session
+                    .run_lifecycle_hooks(
+                        ctx.turn,
+                        HookEvent::ToolDenied {
+                            event: HookEventToolDenied {
+                                turn_id: ctx.turn.sub_id.clone(),
+                                call_id: ctx.call_id.to_string(),
+                                tool_name: self.tool_name().to_string(),
+                                reason: normalized.clone(),
+                            },
+                        },
+                    )
+                    .await;
                 let event = ToolEventStage::Failure(ToolEventFailure::Rejected(normalized.clone()));
                 let result = Err(FunctionCallError::RespondToModel(normalized));
                 (event, result)
@@ -496,6 +528,12 @@ async fn emit_patch_end(
     success: bool,
     status: PatchApplyStatus,
 ) {
+    let mut applied_paths = if status == PatchApplyStatus::Completed {
+        changes.keys().cloned().collect::<Vec<_>>()
+    } else {
+        Vec::new()
+    };
+    applied_paths.sort();
     ctx.session
         .send_event(
             ctx.turn,
@@ -512,14 +550,29 @@ async fn emit_patch_end(
         .await;
 
     if let Some(tracker) = ctx.turn_diff_tracker {
-        let unified_diff = {
+        let turn_diff_event = {
             let mut guard = tracker.lock().await;
-            guard.get_unified_diff()
+            guard.turn_diff_event()
         };
-        if let Ok(Some(unified_diff)) = unified_diff {
+        if let Ok(Some(turn_diff_event)) = turn_diff_event {
             ctx.session
-                .send_event(ctx.turn, EventMsg::TurnDiff(TurnDiffEvent { unified_diff }))
+                .send_event(ctx.turn, EventMsg::TurnDiff(turn_diff_event))
                 .await;
         }
     }
+
+    if !applied_paths.is_empty() {
+        ctx.session
+            .run_lifecycle_hooks(
+                ctx.turn,
+                HookEvent::PatchApplied {
+                    event: HookEventPatchApplied {
+                        turn_id: ctx.turn.sub_id.clone(),
+                        call_id: ctx.call_id.to_string(),
+                        paths: applied_paths,
+                    },
+                },
+            )
+            .await;
+    }
 }
@@ -0,0 +1,198 @@
+//! Organizational policies checked before every tool call, e.g. "no shell
+//! commands that delete files".
+//!
+//! See [`ToolRegistry::apply_governance_policy`].
+//!
+//! [`ToolRegistry::apply_governance_policy`]: crate::tools::registry::ToolRegistry::apply_governance_policy
+
+use codex_protocol::models::FunctionCallOutputBody;
+use codex_protocol::models::FunctionCallOutputContentItem;
+use regex_lite::Regex;
+use serde_json::Value as JsonValue;
+
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::router::ToolCall;
+
+pub const REDACTED: &str = "[REDACTED]";
+
+#[derive(Clone, Debug)]
+pub enum PolicyResult {
+    Allow,
+    /// The call is not run; `reason` is reported to the model.
+    Deny {
+        reason: String,
+    },
+    /// The call runs with every match of `patterns` in its arguments, and
+    /// later in its output, replaced by [`REDACTED`].
+    Redact {
+        patterns: Vec<Regex>,
+    },
+}
+
+pub trait GovernancePolicy: Send + Sync {
+    fn evaluate(&self, call: &ToolCall) -> PolicyResult;
+}
+
+/// Replaces every match of `patterns` in the arguments of `payload`. For
+/// GraphQL calls, the query and string variables are redacted but the
+/// endpoint and headers are not.
+pub(crate) fn redact_payload(payload: ToolPayload, patterns: &[Regex]) -> ToolPayload {
+    let redact = |text: String| {
+        patterns.iter().fold(text, |text, pattern| {
+            pattern.replace_all(&text, REDACTED).into_owned()
+        })
+    };
+    match payload {
+        ToolPayload::Function { arguments } => ToolPayload::Function {
+            arguments: redact(arguments),
+        },
+        ToolPayload::Custom { input, attachments } => ToolPayload::Custom {
+            input: redact(input),
+            attachments,
+        },
+        ToolPayload::Mcp {
+            server,
+            tool,
+            raw_arguments,
+        } => ToolPayload::Mcp {
+            server,
+            tool,
+            raw_arguments: redact(raw_arguments),
+        },
+        ToolPayload::LocalShell { mut params } => {
+            params.command = params.command.into_iter().map(redact).collect();
+            ToolPayload::LocalShell { params }
+        }
+        ToolPayload::GraphQL {
+            endpoint,
+            query,
+            mut variables,
+            headers,
+        } => {
+            redact_json_strings(&mut variables, &redact);
+            ToolPayload::GraphQL {
+                endpoint,
+                query: redact(query),
+                variables,
+                headers,
+            }
+        }
+    }
+}
+
+/// Replaces every match of `patterns` in the text of `output`, returning the
+/// redacted output and how many bytes of matched content were replaced.
+/// Images are kept; MCP results have their text content and error redacted.
+pub(crate) fn redact_output(output: ToolOutput, patterns: &[Regex]) -> (ToolOutput, usize) {
+    let mut bytes_redacted = 0;
+    let output = map_output_text(output, |text| {
+        patterns.iter().fold(text, |text, pattern| {
+            bytes_redacted += pattern
+                .find_iter(&text)
+                .map(|found| found.len())
+                .sum::<usize>();
+            pattern.replace_all(&text, REDACTED).into_owned()
+        })
+    });
+    (output, bytes_redacted)
+}
+
+/// Rewrites every piece of text in `output` with `redact`: text bodies and
+/// content items, and the text content and error of MCP results.
+pub(crate) fn map_output_text(
+    output: ToolOutput,
+    mut redact: impl FnMut(String) -> String,
+) -> ToolOutput {
+    match output {
+        ToolOutput::Function { body, success } => {
+            let body = match body {
+                FunctionCallOutputBody::Text(text) => FunctionCallOutputBody::Text(redact(text)),
+                FunctionCallOutputBody::ContentItems(items) => {
+                    FunctionCallOutputBody::ContentItems(
+                        items
+                            .into_iter()
+                            .map(|item| match item {
+                                FunctionCallOutputContentItem::InputText { text } => {
+                                    FunctionCallOutputContentItem::InputText { text: redact(text) }
+                                }
+                                other => other,
+                            })
+                            .collect(),
+                    )
+                }
+                other => other,
+            };
+            ToolOutput::Function { body, success }
+        }
+        ToolOutput::Mcp {
+            result: Ok(mut result),
+        } => {
+            for item in &mut result.content {
+                if let Some(JsonValue::String(text)) = item.get_mut("text") {
+                    *text = redact(std::mem::take(text));
+                }
+            }
+            ToolOutput::Mcp { result: Ok(result) }
+        }
+        ToolOutput::Mcp {
+            result: Err(message),
+        } => ToolOutput::Mcp {
+            result: Err(redact(message)),
+        },
+    }
+}
+
+fn redact_json_strings(value: &mut JsonValue, redact: &impl Fn(String) -> String) {
+    match value {
+        JsonValue::String(text) => *text = redact(std::mem::take(text)),
+        JsonValue::Array(items) => {
+            for item in items {
+                redact_json_strings(item, redact);
+            }
+        }
+        JsonValue::Object(fields) => {
+            for field in fields.values_mut() {
+                redact_json_strings(field, redact);
+            }
+        }
+        JsonValue::Null | JsonValue::Bool(_) | JsonValue::Number(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    #[test]
+    fn graphql_variables_are_redacted_but_endpoint_is_kept() {
+        let token = Regex::new("secret-[0-9]+").expect("valid regex");
+        let payload = redact_payload(
+            ToolPayload::GraphQL {
+                endpoint: "https://example.com/secret-1/graphql".to_string(),
+                query: "query { user(token: \"secret-2\") { id } }".to_string(),
+                variables: serde_json::json!({"filter": {"tokens": ["secret-3", 4]}}),
+                headers: HashMap::new(),
+            },
+            &[token],
+        );
+
+        let ToolPayload::GraphQL {
+            endpoint,
+            query,
+            variables,
+            ..
+        } = payload
+        else {
+            panic!("unexpected payload: {payload:?}");
+        };
+        assert_eq!(endpoint, "https://example.com/secret-1/graphql");
+        assert_eq!(query, "query { user(token: \"[REDACTED]\") { id } }");
+        assert_eq!(
+            variables,
+            serde_json::json!({"filter": {"tokens": ["[REDACTED]", 4]}})
+        );
+    }
+}
@@ -0,0 +1,626 @@
+//! Function tools generated from a GraphQL endpoint's introspected schema:
+//! one per query field and one per mutation field.
+//!
+//! See [`ToolRegistry::load_from_graphql_introspection`].
+//!
+//! [`ToolRegistry::load_from_graphql_introspection`]: crate::tools::registry::ToolRegistry::load_from_graphql_introspection
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use codex_protocol::models::FunctionCallOutputBody;
+use serde::Deserialize;
+use serde_json::Map as JsonMap;
+use serde_json::Value as JsonValue;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::client_common::tools::ResponsesApiTool;
+use crate::client_common::tools::ToolSpec;
+use crate::default_client::build_reqwest_client;
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::handlers::graphql::execute_graphql;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::tools::spec::parse_tool_input_schema;
+
+const INTROSPECTION_QUERY: &str = "query IntrospectionQuery { __schema { \
+    queryType { name } mutationType { name } \
+    types { kind name description \
+      fields { name description args { name description type { ...TypeRef } } type { ...TypeRef } } \
+      inputFields { name description type { ...TypeRef } } \
+      enumValues { name } } } } \
+    fragment TypeRef on __Type { kind name ofType { kind name ofType { kind name \
+      ofType { kind name ofType { kind name } } } } }";
+
+#[derive(Debug, Error)]
+pub enum GraphQlLoadError {
+    #[error("GraphQL introspection request to {endpoint} failed: {message}")]
+    Request { endpoint: String, message: String },
+    #[error("failed to parse GraphQL introspection response from {endpoint}: {message}")]
+    Parse { endpoint: String, message: String },
+    #[error("GraphQL introspection errors from {endpoint}: {messages}")]
+    Errors { endpoint: String, messages: String },
+    #[error("invalid input schema generated for GraphQL operation {name}: {message}")]
+    InvalidSchema { name: String, message: String },
+    #[error("a handler is already registered for tool {0}")]
+    DuplicateTool(String),
+}
+
+#[derive(Debug, Deserialize)]
+struct IntrospectionResponse {
+    #[serde(default)]
+    data: Option<IntrospectionData>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct IntrospectionData {
+    #[serde(rename = "__schema")]
+    schema: IntrospectionSchema,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IntrospectionSchema {
+    query_type: Option<NamedType>,
+    mutation_type: Option<NamedType>,
+    types: Vec<FullType>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NamedType {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FullType {
+    kind: String,
+    name: Option<String>,
+    description: Option<String>,
+    #[serde(default)]
+    fields: Option<Vec<Field>>,
+    #[serde(default)]
+    input_fields: Option<Vec<InputValue>>,
+    #[serde(default)]
+    enum_values: Option<Vec<EnumValue>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Field {
+    name: String,
+    description: Option<String>,
+    #[serde(default)]
+    args: Vec<InputValue>,
+    #[serde(rename = "type")]
+    type_ref: TypeRef,
+}
+
+#[derive(Debug, Deserialize)]
+struct InputValue {
+    name: String,
+    description: Option<String>,
+    #[serde(rename = "type")]
+    type_ref: TypeRef,
+}
+
+#[derive(Debug, Deserialize)]
+struct EnumValue {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TypeRef {
+    kind: String,
+    name: Option<String>,
+    of_type: Option<Box<TypeRef>>,
+}
+
+impl TypeRef {
+    /// The type as written in a GraphQL document, e.g. `[ID!]!`.
+    fn to_graphql(&self) -> String {
+        match (self.kind.as_str(), &self.of_type) {
+            ("NON_NULL", Some(inner)) => format!("{}!", inner.to_graphql()),
+            ("LIST", Some(inner)) => format!("[{}]", inner.to_graphql()),
+            _ => self.name.clone().unwrap_or_default(),
+        }
+    }
+
+    /// The named type underneath any list and non-null wrappers.
+    fn named(&self) -> &str {
+        match &self.of_type {
+            Some(inner) => inner.named(),
+            None => self.name.as_deref().unwrap_or_default(),
+        }
+    }
+
+    fn is_non_null(&self) -> bool {
+        self.kind == "NON_NULL"
+    }
+}
+
+/// A query or mutation field, ready to be registered as a function tool.
+pub(crate) struct GraphQlOperation {
+    pub(crate) name: String,
+    pub(crate) spec: ToolSpec,
+    document: String,
+    mutation: bool,
+}
+
+impl GraphQlOperation {
+    pub(crate) fn into_handler(
+        self,
+        endpoint: &str,
+        headers: HashMap<String, String>,
+    ) -> GraphQlOperationHandler {
+        GraphQlOperationHandler {
+            endpoint: endpoint.to_string(),
+            headers,
+            document: self.document,
+            mutation: self.mutation,
+        }
+    }
+}
+
+/// Runs one generated operation, passing the call's arguments as its
+/// variables.
+pub(crate) struct GraphQlOperationHandler {
+    endpoint: String,
+    headers: HashMap<String, String>,
+    document: String,
+    mutation: bool,
+}
+
+#[async_trait]
+impl ToolHandler for GraphQlOperationHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn is_mutating(&self, _invocation: &ToolInvocation) -> bool {
+        self.mutation
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolPayload::Function { arguments } = invocation.payload else {
+            return Err(FunctionCallError::RespondToModel(
+                "graphql operation handler received unsupported payload".to_string(),
+            ));
+        };
+        let variables = if arguments.trim().is_empty() {
+            json!({})
+        } else {
+            serde_json::from_str::<JsonValue>(&arguments).map_err(|err| {
+                FunctionCallError::RespondToModel(format!(
+                    "failed to parse function arguments: {err}"
+                ))
+            })?
+        };
+        let data =
+            execute_graphql(&self.endpoint, &self.document, variables, &self.headers).await?;
+        Ok(ToolOutput::Function {
+            body: FunctionCallOutputBody::Text(data.to_string()),
+            success: Some(true),
+        })
+    }
+}
+
+/// Fetches the schema of `endpoint` and builds an operation for every field
+/// of its query and mutation root types.
+pub(crate) async fn introspect(
+    endpoint: &str,
+    headers: &HashMap<String, String>,
+) -> Result<Vec<GraphQlOperation>, GraphQlLoadError> {
+    let request_error = |message: String| GraphQlLoadError::Request {
+        endpoint: endpoint.to_string(),
+        message,
+    };
+    let mut request = build_reqwest_client()
+        .post(endpoint)
+        .json(&json!({ "query": INTROSPECTION_QUERY }));
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|err| request_error(err.to_string()))?;
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|err| request_error(err.to_string()))?;
+    if !status.is_success() {
+        return Err(request_error(format!("status {status}: {body}")));
+    }
+    let response: IntrospectionResponse =
+        serde_json::from_str(&body).map_err(|err| GraphQlLoadError::Parse {
+            endpoint: endpoint.to_string(),
+            message: err.to_string(),
+        })?;
+    if !response.errors.is_empty() {
+        let messages = response
+            .errors
+            .iter()
+            .map(|error| error.message.as_str())
+            .collect::<Vec<_>>()
+            .join("; ");
+        return Err(GraphQlLoadError::Errors {
+            endpoint: endpoint.to_string(),
+            messages,
+        });
+    }
+    let Some(data) = response.data else {
+        return Err(GraphQlLoadError::Parse {
+            endpoint: endpoint.to_string(),
+            message: "response has no data".to_string(),
+        });
+    };
+    operations(&data.schema)
+}
+
+fn operations(schema: &IntrospectionSchema) -> Result<Vec<GraphQlOperation>, GraphQlLoadError> {
+    let types: HashMap<&str, &FullType> = schema
+        .types
+        .iter()
+        .filter_map(|full_type| Some((full_type.name.as_deref()?, full_type)))
+        .collect();
+    let roots = [
+        (schema.query_type.as_ref(), false),
+        (schema.mutation_type.as_ref(), true),
+    ];
+    let mut operations = Vec::new();
+    for (root, mutation) in roots {
+        let Some(root) = root.and_then(|root| types.get(root.name.as_str())) else {
+            continue;
+        };
+        for field in root.fields.iter().flatten() {
+            operations.push(operation(field, mutation, &types)?);
+        }
+    }
+    Ok(operations)
+}
+
+fn operation(
+    field: &Field,
+    mutation: bool,
+    types: &HashMap<&str, &FullType>,
+) -> Result<GraphQlOperation, GraphQlLoadError> {
+    let parameters = object_schema(&field.args, types, &mut Vec::new());
+    let parameters =
+        parse_tool_input_schema(&parameters).map_err(|err| GraphQlLoadError::InvalidSchema {
+            name: field.name.clone(),
+            message: err.to_string(),
+        })?;
+    let keyword = if mutation { "mutation" } else { "query" };
+    let description = field
+        .description
+        .clone()
+        .unwrap_or_else(|| format!("GraphQL {keyword} `{}`.", field.name));
+    Ok(GraphQlOperation {
+        name: field.name.clone(),
+        spec: ToolSpec::Function(ResponsesApiTool {
+            name: field.name.clone(),
+            description,
+            strict: false,
+            parameters,
+        }),
+        document: document(keyword, field, types),
+        mutation,
+    })
+}
+
+/// `query user($id: ID!) { user(id: $id) { id name } }`, selecting the
+/// return type's scalar fields.
+fn document(keyword: &str, field: &Field, types: &HashMap<&str, &FullType>) -> String {
+    let name = &field.name;
+    let (variables, arguments) = if field.args.is_empty() {
+        (String::new(), String::new())
+    } else {
+        let variables = field
+            .args
+            .iter()
+            .map(|arg| format!("${}: {}", arg.name, arg.type_ref.to_graphql()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let arguments = field
+            .args
+            .iter()
+            .map(|arg| format!("{0}: ${0}", arg.name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        (format!("({variables})"), format!("({arguments})"))
+    };
+    let selection = selection_set(&field.type_ref, types);
+    format!("{keyword} {name}{variables} {{ {name}{arguments}{selection} }}")
+}
+
+fn selection_set(type_ref: &TypeRef, types: &HashMap<&str, &FullType>) -> String {
+    let Some(named) = types.get(type_ref.named()) else {
+        return String::new();
+    };
+    match named.kind.as_str() {
+        "OBJECT" | "INTERFACE" => {
+            let leaves = named
+                .fields
+                .iter()
+                .flatten()
+                .filter(|field| field.args.iter().all(|arg| !arg.type_ref.is_non_null()))
+                .filter(|field| {
+                    types.get(field.type_ref.named()).is_some_and(|field_type| {
+                        matches!(field_type.kind.as_str(), "SCALAR" | "ENUM")
+                    })
+                })
+                .map(|field| field.name.as_str())
+                .collect::<Vec<_>>();
+            if leaves.is_empty() {
+                " { __typename }".to_string()
+            } else {
+                format!(" {{ {} }}", leaves.join(" "))
+            }
+        }
+        "UNION" => " { __typename }".to_string(),
+        _ => String::new(),
+    }
+}
+
+fn object_schema(
+    fields: &[InputValue],
+    types: &HashMap<&str, &FullType>,
+    visiting: &mut Vec<String>,
+) -> JsonValue {
+    let mut properties = JsonMap::new();
+    let mut required = Vec::new();
+    for field in fields {
+        let mut schema = type_schema(&field.type_ref, types, visiting);
+        if let (Some(description), JsonValue::Object(schema)) = (&field.description, &mut schema) {
+            schema.insert("description".to_string(), json!(description));
+        }
+        properties.insert(field.name.clone(), schema);
+        if field.type_ref.is_non_null() {
+            required.push(field.name.clone());
+        }
+    }
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+fn type_schema(
+    type_ref: &TypeRef,
+    types: &HashMap<&str, &FullType>,
+    visiting: &mut Vec<String>,
+) -> JsonValue {
+    match (type_ref.kind.as_str(), &type_ref.of_type) {
+        ("NON_NULL", Some(inner)) => return type_schema(inner, types, visiting),
+        ("LIST", Some(inner)) => {
+            return json!({
+                "type": "array",
+                "items": type_schema(inner, types, visiting),
+            });
+        }
+        _ => {}
+    }
+    let name = type_ref.name.as_deref().unwrap_or_default();
+    let Some(named) = types.get(name) else {
+        return json!({ "type": "string" });
+    };
+    match named.kind.as_str() {
+        "ENUM" => {
+            let values = named
+                .enum_values
+                .iter()
+                .flatten()
+                .map(|value| value.name.clone())
+                .collect::<Vec<_>>();
+            json!({ "type": "string", "enum": values })
+        }
+        // Recursive input types are cut off at the first repeat.
+        "INPUT_OBJECT" if visiting.iter().any(|seen| seen == name) => {
+            json!({ "type": "object", "properties": {} })
+        }
+        "INPUT_OBJECT" => {
+            visiting.push(name.to_string());
+            let mut schema = object_schema(
+                named.input_fields.as_deref().unwrap_or_default(),
+                types,
+                visiting,
+            );
+            visiting.pop();
+            if let (Some(description), JsonValue::Object(schema)) =
+                (&named.description, &mut schema)
+            {
+                schema.insert("description".to_string(), json!(description));
+            }
+            schema
+        }
+        _ => match name {
+            "Int" | "Float" => json!({ "type": "number" }),
+            "Boolean" => json!({ "type": "boolean" }),
+            _ => json!({ "type": "string" }),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::registry::ToolRegistry;
+    use pretty_assertions::assert_eq;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+
+    fn named(kind: &str, name: &str) -> JsonValue {
+        json!({ "kind": kind, "name": name, "ofType": null })
+    }
+
+    fn non_null(inner: JsonValue) -> JsonValue {
+        json!({ "kind": "NON_NULL", "name": null, "ofType": inner })
+    }
+
+    fn list(inner: JsonValue) -> JsonValue {
+        json!({ "kind": "LIST", "name": null, "ofType": inner })
+    }
+
+    fn introspection_response() -> JsonValue {
+        let id = non_null(named("SCALAR", "ID"));
+        let user = named("OBJECT", "User");
+        json!({
+            "data": {
+                "__schema": {
+                    "queryType": { "name": "Query" },
+                    "mutationType": { "name": "Mutation" },
+                    "types": [
+                        {
+                            "kind": "OBJECT",
+                            "name": "Query",
+                            "fields": [
+                                {
+                                    "name": "user",
+                                    "description": "Looks up a user by id.",
+                                    "args": [{ "name": "id", "description": null, "type": id }],
+                                    "type": user,
+                                },
+                                { "name": "users", "args": [], "type": list(user.clone()) },
+                            ],
+                        },
+                        {
+                            "kind": "OBJECT",
+                            "name": "Mutation",
+                            "fields": [{
+                                "name": "createUser",
+                                "args": [{
+                                    "name": "input",
+                                    "type": non_null(named("INPUT_OBJECT", "CreateUserInput")),
+                                }],
+                                "type": user,
+                            }],
+                        },
+                        {
+                            "kind": "OBJECT",
+                            "name": "User",
+                            "fields": [
+                                { "name": "id", "args": [], "type": id },
+                                { "name": "name", "args": [], "type": named("SCALAR", "String") },
+                                { "name": "role", "args": [], "type": named("ENUM", "Role") },
+                                { "name": "friends", "args": [], "type": list(user.clone()) },
+                            ],
+                        },
+                        {
+                            "kind": "INPUT_OBJECT",
+                            "name": "CreateUserInput",
+                            "inputFields": [
+                                { "name": "name", "type": non_null(named("SCALAR", "String")) },
+                                { "name": "age", "type": named("SCALAR", "Int") },
+                                { "name": "role", "type": named("ENUM", "Role") },
+                            ],
+                        },
+                        {
+                            "kind": "ENUM",
+                            "name": "Role",
+                            "enumValues": [{ "name": "ADMIN" }, { "name": "MEMBER" }],
+                        },
+                        { "kind": "SCALAR", "name": "ID" },
+                        { "kind": "SCALAR", "name": "String" },
+                        { "kind": "SCALAR", "name": "Int" },
+                    ],
+                },
+            },
+        })
+    }
+
+    #[tokio::test]
+    async fn introspection_generates_one_tool_per_operation() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/graphql"))
+            .and(header("authorization", "Bearer token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(introspection_response()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let mut registry = ToolRegistry::new(HashMap::new());
+
+        let loaded = registry
+            .load_from_graphql_introspection(
+                &format!("{}/graphql", server.uri()),
+                HashMap::from([("authorization".to_string(), "Bearer token".to_string())]),
+            )
+            .await
+            .expect("load graphql tools");
+
+        assert_eq!(loaded, 3);
+        let mut names = registry
+            .graphql_tool_specs()
+            .iter()
+            .map(|spec| spec.spec.name().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["createUser", "user", "users"]);
+        assert!(names.iter().all(|name| registry.handler(name).is_some()));
+    }
+
+    #[test]
+    fn operations_map_arguments_to_json_schema_and_documents() {
+        let response: IntrospectionResponse =
+            serde_json::from_value(introspection_response()).expect("parse introspection");
+        let schema = response.data.expect("data").schema;
+        let operations = operations(&schema).expect("build operations");
+
+        let create_user = operations
+            .iter()
+            .find(|operation| operation.name == "createUser")
+            .expect("createUser operation");
+        assert!(create_user.mutation);
+        assert_eq!(
+            create_user.document,
+            "mutation createUser($input: CreateUserInput!) { createUser(input: $input) { id name role } }"
+        );
+        let ToolSpec::Function(tool) = &create_user.spec else {
+            panic!("unexpected spec");
+        };
+        assert_eq!(
+            tool.parameters,
+            parse_tool_input_schema(&json!({
+                "type": "object",
+                "properties": {
+                    "input": {
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string" },
+                            "age": { "type": "number" },
+                            "role": { "type": "string", "enum": ["ADMIN", "MEMBER"] },
+                        },
+                        "required": ["name"],
+                    },
+                },
+                "required": ["input"],
+            }))
+            .expect("valid schema")
+        );
+
+        let users = operations
+            .iter()
+            .find(|operation| operation.name == "users")
+            .expect("users operation");
+        assert!(!users.mutation);
+        assert_eq!(users.document, "query users { users { id name role } }");
+    }
+}
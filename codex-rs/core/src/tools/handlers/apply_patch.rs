@@ -1,8 +1,12 @@
 use codex_protocol::models::FunctionCallOutputBody;
+use serde_json::Value as JsonValue;
 use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::path::Path;
 
 use crate::apply_patch;
+use crate::apply_patch::ApplyPatchExec;
 use crate::apply_patch::InternalApplyPatchInvocation;
 use crate::apply_patch::convert_apply_patch_to_protocol;
 use crate::client_common::tools::FreeformTool;
@@ -11,7 +15,10 @@ use crate::client_common::tools::ResponsesApiTool;
 use crate::client_common::tools::ToolSpec;
 use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::features::Feature;
 use crate::function_tool::FunctionCallError;
+use crate::guardian::routes_approval_to_guardian;
+use crate::protocol::PatchReviewHunk;
 use crate::tools::context::SharedTurnDiffTracker;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
@@ -24,7 +31,9 @@ use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
 use crate::tools::runtimes::apply_patch::ApplyPatchRequest;
 use crate::tools::runtimes::apply_patch::ApplyPatchRuntime;
+use crate::tools::sandboxing::ExecApprovalRequirement;
 use crate::tools::sandboxing::ToolCtx;
+use crate::tools::sandboxing::ToolError;
 use crate::tools::spec::ApplyPatchToolArgs;
 use crate::tools::spec::JsonSchema;
 use async_trait::async_trait;
@@ -61,6 +70,146 @@ fn to_abs_path(cwd: &Path, path: &Path) -> Option<AbsolutePathBuf> {
     AbsolutePathBuf::resolve_path_against_base(path, cwd).ok()
 }
 
+fn patch_input(payload: &ToolPayload) -> Result<String, FunctionCallError> {
+    match payload {
+        ToolPayload::Function { arguments } => {
+            let args: ApplyPatchToolArgs = parse_arguments(arguments)?;
+            Ok(args.input)
+        }
+        ToolPayload::Custom { input, .. } => Ok(input.clone()),
+        _ => Err(FunctionCallError::RespondToModel(
+            "apply_patch handler received unsupported payload".to_string(),
+        )),
+    }
+}
+
+/// Verifies `command` as an apply_patch invocation, letting updates that no
+/// longer apply fall back to a three-way merge against the contents the files
+/// had before this turn first changed them.
+async fn verify_apply_patch(
+    command: &[String],
+    cwd: &Path,
+    tracker: Option<&SharedTurnDiffTracker>,
+) -> codex_apply_patch::MaybeApplyPatchVerified {
+    let merge_bases = match tracker {
+        Some(tracker) => tracker.lock().await.original_contents(),
+        None => HashMap::new(),
+    };
+    codex_apply_patch::maybe_parse_apply_patch_verified_with_merge_bases(command, cwd, &merge_bases)
+}
+
+/// Whether the client should review `apply` hunk by hunk rather than approve
+/// or deny it as a whole.
+fn wants_hunk_review(turn: &TurnContext, apply: &ApplyPatchExec) -> bool {
+    turn.features.enabled(Feature::PatchHunkReview)
+        && matches!(
+            apply.exec_approval_requirement,
+            ExecApprovalRequirement::NeedsApproval { .. }
+        )
+        && !routes_approval_to_guardian(turn)
+}
+
+enum HunkReview {
+    /// Apply `action`, which only contains the accepted hunks.
+    Apply {
+        action: ApplyPatchAction,
+        rejections: Vec<String>,
+    },
+    /// Every hunk was rejected.
+    Rejected { rejections: Vec<String> },
+}
+
+/// Sends the hunks of `apply` to the client for review. Rejected hunks are
+/// described in `rejections` so the model can see what was turned down and
+/// why.
+async fn review_patch_hunks(
+    session: &Session,
+    turn: &TurnContext,
+    tracker: &SharedTurnDiffTracker,
+    call_id: &str,
+    apply: &ApplyPatchExec,
+) -> Result<HunkReview, FunctionCallError> {
+    let action = &apply.action;
+    let hunks = codex_apply_patch::review_hunks(&action.patch, &action.cwd).map_err(|err| {
+        FunctionCallError::RespondToModel(format!("apply_patch verification failed: {err}"))
+    })?;
+    let reason = match &apply.exec_approval_requirement {
+        ExecApprovalRequirement::NeedsApproval { reason, .. } => reason.clone(),
+        _ => None,
+    };
+    let request = hunks
+        .iter()
+        .enumerate()
+        .map(|(index, hunk)| PatchReviewHunk {
+            index: index as u32,
+            path: hunk.path.clone(),
+            move_path: hunk.move_path.clone(),
+            diff: hunk.diff.clone(),
+        })
+        .collect();
+    let Some(response) = session
+        .request_patch_review(turn, call_id.to_string(), request, reason)
+        .await
+    else {
+        return Err(FunctionCallError::RespondToModel(
+            "patch review was cancelled".to_string(),
+        ));
+    };
+
+    let accepted = response
+        .accepted_hunks
+        .iter()
+        .map(|index| *index as usize)
+        .collect::<HashSet<_>>();
+    let reasons = response
+        .rejected_hunks
+        .into_iter()
+        .filter_map(|rejection| Some((rejection.index as usize, rejection.reason?)))
+        .collect::<HashMap<_, _>>();
+    let rejections = hunks
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !accepted.contains(index))
+        .map(|(index, hunk)| {
+            let reason = reasons
+                .get(&index)
+                .map(String::as_str)
+                .unwrap_or("no reason given");
+            format!(
+                "Rejected hunk in {} ({reason}):\n{}",
+                hunk.path.display(),
+                hunk.diff
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let selected =
+        codex_apply_patch::select_hunks(&action.patch, |index| accepted.contains(&index));
+    let Some(patch) = selected.map_err(|err| {
+        FunctionCallError::RespondToModel(format!("apply_patch verification failed: {err}"))
+    })?
+    else {
+        return Ok(HunkReview::Rejected { rejections });
+    };
+    let command = vec!["apply_patch".to_string(), patch];
+    match verify_apply_patch(&command, &action.cwd, Some(tracker)).await {
+        codex_apply_patch::MaybeApplyPatchVerified::Body(action) => {
+            Ok(HunkReview::Apply { action, rejections })
+        }
+        codex_apply_patch::MaybeApplyPatchVerified::CorrectnessError(err) => {
+            Err(FunctionCallError::RespondToModel(format!(
+                "the accepted hunks no longer apply on their own: {err}"
+            )))
+        }
+        codex_apply_patch::MaybeApplyPatchVerified::ShellParseError(_)
+        | codex_apply_patch::MaybeApplyPatchVerified::NotApplyPatch => {
+            Err(FunctionCallError::RespondToModel(
+                "apply_patch handler could not rebuild the accepted hunks".to_string(),
+            ))
+        }
+    }
+}
+
 #[async_trait]
 impl ToolHandler for ApplyPatchHandler {
     fn kind(&self) -> ToolKind {
@@ -78,6 +227,30 @@ impl ToolHandler for ApplyPatchHandler {
         true
     }
 
+    async fn dry_run_preview(
+        &self,
+        invocation: &ToolInvocation,
+    ) -> Result<Option<JsonValue>, FunctionCallError> {
+        let patch_input = patch_input(&invocation.payload)?;
+        let report = codex_apply_patch::dry_run_patch(&patch_input, &invocation.turn.cwd)
+            .map_err(|err| FunctionCallError::RespondToModel(err.to_string()))?;
+        let conflicts = report
+            .conflicts
+            .iter()
+            .map(|conflict| {
+                serde_json::json!({
+                    "path": conflict.path,
+                    "hunk": conflict.chunk,
+                    "reason": conflict.reason,
+                })
+            })
+            .collect::<Vec<_>>();
+        Ok(Some(serde_json::json!({
+            "hunks_checked": report.hunks_checked,
+            "conflicts": conflicts,
+        })))
+    }
+
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
         let ToolInvocation {
             session,
@@ -89,24 +262,13 @@ impl ToolHandler for ApplyPatchHandler {
             ..
         } = invocation;
 
-        let patch_input = match payload {
-            ToolPayload::Function { arguments } => {
-                let args: ApplyPatchToolArgs = parse_arguments(&arguments)?;
-                args.input
-            }
-            ToolPayload::Custom { input } => input,
-            _ => {
-                return Err(FunctionCallError::RespondToModel(
-                    "apply_patch handler received unsupported payload".to_string(),
-                ));
-            }
-        };
+        let patch_input = patch_input(&payload)?;
 
         // Re-parse and verify the patch so we can compute changes and approval.
         // Avoid building temporary ExecParams/command vectors; derive directly from inputs.
         let cwd = turn.cwd.clone();
         let command = vec!["apply_patch".to_string(), patch_input.clone()];
-        match codex_apply_patch::maybe_parse_apply_patch_verified(&command, &cwd) {
+        match verify_apply_patch(&command, &cwd, Some(&tracker)).await {
             codex_apply_patch::MaybeApplyPatchVerified::Body(changes) => {
                 match apply_patch::apply_patch(turn.as_ref(), changes).await {
                     InternalApplyPatchInvocation::Output(item) => {
@@ -116,7 +278,63 @@ impl ToolHandler for ApplyPatchHandler {
                             success: Some(true),
                         })
                     }
-                    InternalApplyPatchInvocation::DelegateToExec(apply) => {
+                    InternalApplyPatchInvocation::DelegateToExec(mut apply) => {
+                        let mut rejections = Vec::new();
+                        if wants_hunk_review(turn.as_ref(), &apply) {
+                            match review_patch_hunks(
+                                session.as_ref(),
+                                turn.as_ref(),
+                                &tracker,
+                                &call_id,
+                                &apply,
+                            )
+                            .await?
+                            {
+                                HunkReview::Apply {
+                                    action,
+                                    rejections: rejected,
+                                } => {
+                                    apply = ApplyPatchExec {
+                                        action,
+                                        auto_approved: false,
+                                        exec_approval_requirement: ExecApprovalRequirement::Skip {
+                                            bypass_sandbox: false,
+                                            proposed_execpolicy_amendment: None,
+                                        },
+                                    };
+                                    rejections = rejected;
+                                }
+                                HunkReview::Rejected { rejections } => {
+                                    let emitter = ToolEmitter::apply_patch(
+                                        convert_apply_patch_to_protocol(&apply.action),
+                                        false,
+                                    );
+                                    let event_ctx = ToolEventCtx::new(
+                                        session.as_ref(),
+                                        turn.as_ref(),
+                                        &call_id,
+                                        Some(&tracker),
+                                    );
+                                    emitter.begin(event_ctx).await;
+                                    let event_ctx = ToolEventCtx::new(
+                                        session.as_ref(),
+                                        turn.as_ref(),
+                                        &call_id,
+                                        Some(&tracker),
+                                    );
+                                    let out = Err(ToolError::Rejected(format!(
+                                        "patch rejected by user\n\n{}",
+                                        rejections.join("\n")
+                                    )));
+                                    return emitter.finish(event_ctx, out).await.map(|content| {
+                                        ToolOutput::Function {
+                                            body: FunctionCallOutputBody::Text(content),
+                                            success: Some(false),
+                                        }
+                                    });
+                                }
+                            }
+                        }
                         let changes = convert_apply_patch_to_protocol(&apply.action);
                         let file_paths = file_paths_for_action(&apply.action);
                         let emitter =
@@ -162,7 +380,14 @@ impl ToolHandler for ApplyPatchHandler {
                             &call_id,
                             Some(&tracker),
                         );
-                        let content = emitter.finish(event_ctx, out).await?;
+                        let mut content = emitter.finish(event_ctx, out).await?;
+                        if !rejections.is_empty() {
+                            content.push_str(&format!(
+                                "\n\nThe user rejected {} hunk(s), which were not applied:\n{}",
+                                rejections.len(),
+                                rejections.join("\n")
+                            ));
+                        }
                         Ok(ToolOutput::Function {
                             body: FunctionCallOutputBody::Text(content),
                             success: Some(true),
@@ -201,7 +426,7 @@ pub(crate) async fn intercept_apply_patch(
     call_id: &str,
     tool_name: &str,
 ) -> Result<Option<ToolOutput>, FunctionCallError> {
-    match codex_apply_patch::maybe_parse_apply_patch_verified(command, cwd) {
+    match verify_apply_patch(command, cwd, tracker).await {
         codex_apply_patch::MaybeApplyPatchVerified::Body(changes) => {
             session
                 .record_model_warning(
@@ -70,7 +70,7 @@ impl ToolHandler for ArtifactsHandler {
         }
 
         let args = match payload {
-            ToolPayload::Custom { input } => parse_freeform_args(&input)?,
+            ToolPayload::Custom { input, .. } => parse_freeform_args(&input)?,
             _ => {
                 return Err(FunctionCallError::RespondToModel(
                     "artifacts expects freeform JavaScript input authored against the preloaded @oai/artifact-tool surface".to_string(),
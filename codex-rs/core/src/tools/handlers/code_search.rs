@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use async_trait::async_trait;
+use codex_otel::SessionTelemetry;
+use codex_protocol::models::FunctionCallOutputBody;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+use crate::client::ModelClient;
+use crate::code_index::CodeIndex;
+use crate::code_index::Embedder;
+use crate::function_tool::FunctionCallError;
+use crate::git_info::get_git_repo_root;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::handlers::parse_arguments;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct CodeSearchHandler;
+
+pub(crate) const CODE_SEARCH_TOOL_NAME: &str = "code_search";
+const EMBEDDING_MODEL: &str = "text-embedding-3-small";
+const DEFAULT_LIMIT: usize = 8;
+const MAX_LIMIT: usize = 30;
+/// Lines of each hit shown to the model.
+const SNIPPET_LINES: usize = 12;
+
+/// Indexes loaded in this process, keyed by index file path. The lock also
+/// serializes refreshes of the same workspace.
+static CODE_INDEXES: LazyLock<Mutex<HashMap<PathBuf, CodeIndex>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn default_limit() -> usize {
+    DEFAULT_LIMIT
+}
+
+#[derive(Deserialize)]
+struct CodeSearchArgs {
+    query: String,
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+struct ModelEmbedder<'a> {
+    client: &'a ModelClient,
+    session_telemetry: &'a SessionTelemetry,
+}
+
+#[async_trait]
+impl Embedder for ModelEmbedder<'_> {
+    async fn embed(&self, input: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
+        Ok(self
+            .client
+            .embed(EMBEDDING_MODEL, input, self.session_telemetry)
+            .await?)
+    }
+}
+
+#[async_trait]
+impl ToolHandler for CodeSearchHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            tracker,
+            payload,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "{CODE_SEARCH_TOOL_NAME} handler received unsupported payload"
+                )));
+            }
+        };
+        let args: CodeSearchArgs = parse_arguments(&arguments)?;
+        let query = args.query.trim();
+        if query.is_empty() {
+            return Err(FunctionCallError::RespondToModel(
+                "query must not be empty".to_string(),
+            ));
+        }
+        let limit = args.limit.clamp(1, MAX_LIMIT);
+
+        let root = get_git_repo_root(&turn.cwd).unwrap_or_else(|| turn.cwd.clone());
+        let changed_paths = tracker.lock().await.changed_paths();
+        let embedder = ModelEmbedder {
+            client: &session.services.model_client,
+            session_telemetry: &turn.session_telemetry,
+        };
+        let index_path = CodeIndex::index_path(&turn.config.codex_home, &root);
+
+        let hits = {
+            let mut indexes = CODE_INDEXES.lock().await;
+            let index = match indexes.entry(index_path.clone()) {
+                std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+                std::collections::hash_map::Entry::Vacant(entry) => {
+                    entry.insert(CodeIndex::load(&index_path, EMBEDDING_MODEL).await)
+                }
+            };
+            let updated = index
+                .refresh(&root, &changed_paths, &embedder)
+                .await
+                .map_err(|err| {
+                    FunctionCallError::RespondToModel(format!(
+                        "failed to update the code index: {err:#}"
+                    ))
+                })?;
+            if updated && let Err(err) = index.save(&index_path).await {
+                tracing::warn!("failed to save code index {}: {err}", index_path.display());
+            }
+            let query_embedding = embedder
+                .embed(&[query.to_string()])
+                .await
+                .ok()
+                .and_then(|mut embeddings| embeddings.pop())
+                .ok_or_else(|| {
+                    FunctionCallError::RespondToModel("failed to embed the query".to_string())
+                })?;
+            index.search(&query_embedding, limit)
+        };
+
+        if hits.is_empty() {
+            return Ok(ToolOutput::Function {
+                body: FunctionCallOutputBody::Text("No indexed files matched.".to_string()),
+                success: Some(true),
+            });
+        }
+        let mut sections = Vec::with_capacity(hits.len());
+        for hit in hits {
+            let snippet = tokio::fs::read_to_string(root.join(&hit.path))
+                .await
+                .map(|contents| {
+                    contents
+                        .lines()
+                        .skip(hit.start_line.saturating_sub(1))
+                        .take(SNIPPET_LINES.min(hit.end_line + 1 - hit.start_line))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+                .unwrap_or_default();
+            sections.push(format!(
+                "{}:{}-{} (score {:.2})\n{snippet}",
+                hit.path.display(),
+                hit.start_line,
+                hit.end_line,
+                hit.score
+            ));
+        }
+        Ok(ToolOutput::Function {
+            body: FunctionCallOutputBody::Text(sections.join("\n\n")),
+            success: Some(true),
+        })
+    }
+}
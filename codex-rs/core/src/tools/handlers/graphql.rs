@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use codex_protocol::models::FunctionCallOutputBody;
+use serde::Deserialize;
+use serde_json::Value;
+use serde_json::json;
+
+use crate::default_client::build_reqwest_client;
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+/// Executes [`ToolPayload::GraphQL`] operations and returns their `data`.
+pub struct GraphQlHandler;
+
+#[derive(Debug, Deserialize)]
+struct GraphQlResponse {
+    #[serde(default)]
+    data: Option<Value>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+#[async_trait]
+impl ToolHandler for GraphQlHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::GraphQL
+    }
+
+    async fn is_mutating(&self, invocation: &ToolInvocation) -> bool {
+        match &invocation.payload {
+            ToolPayload::GraphQL { query, .. } => !is_read_only_query(query),
+            _ => true,
+        }
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let (endpoint, query, variables, headers) = match invocation.payload {
+            ToolPayload::GraphQL {
+                endpoint,
+                query,
+                variables,
+                headers,
+            } => (endpoint, query, variables, headers),
+            _ => {
+                return Err(FunctionCallError::RespondToModel(
+                    "graphql handler received unsupported payload".to_string(),
+                ));
+            }
+        };
+
+        let data = execute_graphql(&endpoint, &query, variables, &headers).await?;
+        Ok(ToolOutput::Function {
+            body: FunctionCallOutputBody::Text(data.to_string()),
+            success: Some(true),
+        })
+    }
+}
+
+/// Shorthand (`{ ... }`) and `query` operations only read; anything else,
+/// including documents we cannot classify, is treated as a mutation.
+fn is_read_only_query(query: &str) -> bool {
+    let query = query.trim_start();
+    query.starts_with('{') || query.starts_with("query")
+}
+
+pub(crate) async fn execute_graphql(
+    endpoint: &str,
+    query: &str,
+    variables: Value,
+    headers: &HashMap<String, String>,
+) -> Result<Value, FunctionCallError> {
+    let client = build_reqwest_client();
+    let mut request = client
+        .post(endpoint)
+        .json(&json!({ "query": query, "variables": variables }));
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+
+    let response = request.send().await.map_err(|err| {
+        FunctionCallError::RespondToModel(format!("GraphQL request to {endpoint} failed: {err}"))
+    })?;
+    let status = response.status();
+    let body = response.text().await.map_err(|err| {
+        FunctionCallError::RespondToModel(format!(
+            "failed to read GraphQL response from {endpoint}: {err}"
+        ))
+    })?;
+    // GraphQL servers may report errors with a non-2xx status, so prefer the
+    // `errors` array when the body has one.
+    let parsed = serde_json::from_str::<GraphQlResponse>(&body);
+    if let Ok(GraphQlResponse { errors, .. }) = &parsed
+        && !errors.is_empty()
+    {
+        let messages = errors
+            .iter()
+            .map(|error| error.message.as_str())
+            .collect::<Vec<_>>()
+            .join("; ");
+        return Err(FunctionCallError::RespondToModel(format!(
+            "GraphQL errors: {messages}"
+        )));
+    }
+    if !status.is_success() {
+        return Err(FunctionCallError::RespondToModel(format!(
+            "GraphQL request to {endpoint} failed with status {status}: {body}"
+        )));
+    }
+    match parsed {
+        Ok(GraphQlResponse { data, .. }) => Ok(data.unwrap_or(Value::Null)),
+        Err(err) => Err(FunctionCallError::RespondToModel(format!(
+            "failed to parse GraphQL response from {endpoint}: {err}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codex::make_session_and_context;
+    use crate::tools::registry::ToolRegistry;
+    use crate::turn_diff_tracker::TurnDiffTracker;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use codex_protocol::models::ResponseInputItem;
+    use pretty_assertions::assert_eq;
+    use std::sync::Arc;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::body_json;
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+
+    const QUERY: &str = "query Repo($name: String!) { repository(name: $name) { stars } }";
+
+    async fn dispatch(endpoint: String) -> Result<ResponseInputItem, FunctionCallError> {
+        let (session, turn) = make_session_and_context().await;
+        let mut registry = ToolRegistry::new(HashMap::new());
+        registry
+            .register_graphql_tool("graphql")
+            .expect("register graphql tool");
+        registry
+            .dispatch(ToolInvocation {
+                session: Arc::new(session),
+                turn: Arc::new(turn),
+                tracker: Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                call_id: "call-1".to_string(),
+                tool_name: "graphql".to_string(),
+                payload: ToolPayload::GraphQL {
+                    endpoint,
+                    query: QUERY.to_string(),
+                    variables: json!({ "name": "codex" }),
+                    headers: HashMap::from([("x-api-key".to_string(), "secret".to_string())]),
+                },
+                dry_run: false,
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn graphql_tool_returns_data() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/graphql"))
+            .and(header("x-api-key", "secret"))
+            .and(body_json(json!({
+                "query": QUERY,
+                "variables": { "name": "codex" },
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "data": { "repository": { "stars": 42 } },
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let result = dispatch(format!("{}/graphql", server.uri())).await;
+
+        let Ok(ResponseInputItem::FunctionCallOutput { output, .. }) = result else {
+            panic!("unexpected result: {result:?}");
+        };
+        assert_eq!(
+            output,
+            FunctionCallOutputPayload {
+                body: FunctionCallOutputBody::Text(
+                    json!({ "repository": { "stars": 42 } }).to_string()
+                ),
+                success: Some(true),
+                error_code: None,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn graphql_errors_are_reported_to_the_model() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/graphql"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "data": null,
+                "errors": [
+                    { "message": "repository not found" },
+                    { "message": "rate limited" },
+                ],
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let result = dispatch(format!("{}/graphql", server.uri())).await;
+
+        let Err(FunctionCallError::RespondToModel(message)) = result else {
+            panic!("unexpected result: {result:?}");
+        };
+        assert_eq!(
+            message,
+            "GraphQL errors: repository not found; rate limited"
+        );
+    }
+
+    #[test]
+    fn only_query_operations_are_read_only() {
+        assert!(is_read_only_query("{ viewer { login } }"));
+        assert!(is_read_only_query("  query Viewer { viewer { login } }"));
+        assert!(!is_read_only_query("mutation { star(id: 1) { ok } }"));
+    }
+}
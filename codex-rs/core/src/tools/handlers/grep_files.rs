@@ -42,6 +42,10 @@ impl ToolHandler for GrepFilesHandler {
         ToolKind::Function
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
         let ToolInvocation { payload, turn, .. } = invocation;
 
@@ -124,7 +124,7 @@ impl ToolHandler for JsReplHandler {
 
         let args = match payload {
             ToolPayload::Function { arguments } => parse_arguments(&arguments)?,
-            ToolPayload::Custom { input } => parse_freeform_args(&input)?,
+            ToolPayload::Custom { input, .. } => parse_freeform_args(&input)?,
             _ => {
                 return Err(FunctionCallError::RespondToModel(
                     "js_repl expects custom or function payload".to_string(),
@@ -52,6 +52,10 @@ impl ToolHandler for ListDirHandler {
         ToolKind::Function
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
         let ToolInvocation { payload, .. } = invocation;
 
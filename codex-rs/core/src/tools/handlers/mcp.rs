@@ -51,7 +51,7 @@ impl ToolHandler for McpHandler {
             tool,
             arguments_str,
         )
-        .await;
+        .await?;
 
         match response {
             ResponseInputItem::McpToolCallOutput { result, .. } => Ok(ToolOutput::Mcp { result }),
@@ -0,0 +1,178 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use codex_protocol::models::FunctionCallOutputBody;
+use serde::Deserialize;
+
+use crate::function_tool::FunctionCallError;
+use crate::memories::facts::Fact;
+use crate::memories::facts::FactImportance;
+use crate::memories::facts::FactWrite;
+use crate::memories::facts::MAX_FACTS_PER_PROJECT;
+use crate::memories::facts::ProjectFacts;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::handlers::parse_arguments;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct MemoryWriteHandler;
+pub struct MemoryReadHandler;
+
+pub(crate) const MEMORY_WRITE_TOOL_NAME: &str = "memory_write";
+pub(crate) const MEMORY_READ_TOOL_NAME: &str = "memory_read";
+
+const DEFAULT_READ_LIMIT: usize = 10;
+const MAX_READ_LIMIT: usize = 50;
+
+#[derive(Deserialize)]
+struct MemoryWriteArgs {
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    importance: FactImportance,
+    #[serde(default)]
+    ttl_days: Option<i64>,
+    #[serde(default)]
+    forget: bool,
+}
+
+#[derive(Deserialize)]
+struct MemoryReadArgs {
+    #[serde(default)]
+    query: Option<String>,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+fn function_arguments(payload: ToolPayload, tool_name: &str) -> Result<String, FunctionCallError> {
+    match payload {
+        ToolPayload::Function { arguments } => Ok(arguments),
+        _ => Err(FunctionCallError::RespondToModel(format!(
+            "{tool_name} handler received unsupported payload"
+        ))),
+    }
+}
+
+#[async_trait]
+impl ToolHandler for MemoryWriteHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation { turn, payload, .. } = invocation;
+        let arguments = function_arguments(payload, MEMORY_WRITE_TOOL_NAME)?;
+        let args: MemoryWriteArgs = parse_arguments(&arguments)?;
+        if args.forget && args.id.is_none() {
+            return Err(FunctionCallError::RespondToModel(
+                "forget requires the id of the memory to remove".to_string(),
+            ));
+        }
+
+        let codex_home = turn.config.codex_home.clone();
+        let cwd = turn.cwd.clone();
+        let message = tokio::task::spawn_blocking(move || {
+            let now = Utc::now();
+            ProjectFacts::update(&codex_home, &cwd, now, |facts| {
+                if args.forget {
+                    let id = args.id.unwrap_or_default();
+                    return if facts.forget(&id) {
+                        Ok(format!("Forgot memory `{id}`."))
+                    } else {
+                        Err(format!("no memory with id `{id}`"))
+                    };
+                }
+                let updating = args.id.is_some();
+                let written = facts.write(
+                    FactWrite {
+                        id: args.id,
+                        content: args.content,
+                        tags: args.tags,
+                        importance: args.importance,
+                        ttl_days: args.ttl_days,
+                    },
+                    now,
+                )?;
+                let mut message = format!(
+                    "{} memory `{}`; it expires on {}. This project has {} of at most {MAX_FACTS_PER_PROJECT} memories.",
+                    if updating { "Updated" } else { "Saved" },
+                    written.id,
+                    written.expires_at.format("%Y-%m-%d"),
+                    facts.len(),
+                );
+                if let Some(evicted) = written.evicted {
+                    message.push_str(&format!(
+                        " To make room, the least relevant memory was removed: {}",
+                        evicted.content
+                    ));
+                }
+                Ok(message)
+            })
+            .map_err(|err| format!("failed to update project memory: {err}"))?
+        })
+        .await
+        .map_err(|err| {
+            FunctionCallError::RespondToModel(format!("failed to update project memory: {err}"))
+        })?
+        .map_err(FunctionCallError::RespondToModel)?;
+
+        Ok(ToolOutput::Function {
+            body: FunctionCallOutputBody::Text(message),
+            success: Some(true),
+        })
+    }
+}
+
+#[async_trait]
+impl ToolHandler for MemoryReadHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation { turn, payload, .. } = invocation;
+        let arguments = function_arguments(payload, MEMORY_READ_TOOL_NAME)?;
+        let args: MemoryReadArgs = parse_arguments(&arguments)?;
+        let limit = args
+            .limit
+            .unwrap_or(DEFAULT_READ_LIMIT)
+            .clamp(1, MAX_READ_LIMIT);
+
+        let codex_home = turn.config.codex_home.clone();
+        let cwd = turn.cwd.clone();
+        let message = tokio::task::spawn_blocking(move || {
+            let now = Utc::now();
+            ProjectFacts::update(&codex_home, &cwd, now, |facts| {
+                if facts.is_empty() {
+                    return "No memories are saved for this project.".to_string();
+                }
+                let found = facts.read(args.query.as_deref(), limit, now);
+                if found.is_empty() {
+                    return "No memories match the query.".to_string();
+                }
+                found
+                    .iter()
+                    .map(Fact::to_line)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+        })
+        .await
+        .map_err(|err| {
+            FunctionCallError::RespondToModel(format!("failed to read project memory: {err}"))
+        })?
+        .map_err(|err| {
+            FunctionCallError::RespondToModel(format!("failed to read project memory: {err}"))
+        })?;
+
+        Ok(ToolOutput::Function {
+            body: FunctionCallOutputBody::Text(message),
+            success: Some(true),
+        })
+    }
+}
@@ -1,21 +1,27 @@
 pub(crate) mod agent_jobs;
 pub mod apply_patch;
 mod artifacts;
+mod code_search;
 mod dynamic;
+pub(crate) mod graphql;
 mod grep_files;
 mod js_repl;
 mod list_dir;
 mod mcp;
 mod mcp_resource;
+mod memory;
 pub(crate) mod multi_agents;
 mod plan;
 mod read_file;
 mod request_user_input;
 mod search_tool_bm25;
 mod shell;
+mod str_replace_edit;
 mod test_sync;
 pub(crate) mod unified_exec;
 mod view_image;
+mod watch_files;
+mod web_fetch;
 
 use codex_utils_absolute_path::AbsolutePathBufGuard;
 pub use plan::PLAN_TOOL;
@@ -29,15 +35,22 @@ use crate::sandboxing::SandboxPermissions;
 use crate::sandboxing::normalize_additional_permissions;
 pub use apply_patch::ApplyPatchHandler;
 pub use artifacts::ArtifactsHandler;
+pub(crate) use code_search::CODE_SEARCH_TOOL_NAME;
+pub use code_search::CodeSearchHandler;
 use codex_protocol::models::PermissionProfile;
 use codex_protocol::protocol::AskForApproval;
 pub use dynamic::DynamicToolHandler;
+pub use graphql::GraphQlHandler;
 pub use grep_files::GrepFilesHandler;
 pub use js_repl::JsReplHandler;
 pub use js_repl::JsReplResetHandler;
 pub use list_dir::ListDirHandler;
 pub use mcp::McpHandler;
 pub use mcp_resource::McpResourceHandler;
+pub(crate) use memory::MEMORY_READ_TOOL_NAME;
+pub(crate) use memory::MEMORY_WRITE_TOOL_NAME;
+pub use memory::MemoryReadHandler;
+pub use memory::MemoryWriteHandler;
 pub use multi_agents::MultiAgentHandler;
 pub use plan::PlanHandler;
 pub use read_file::ReadFileHandler;
@@ -48,9 +61,15 @@ pub(crate) use search_tool_bm25::SEARCH_TOOL_BM25_TOOL_NAME;
 pub use search_tool_bm25::SearchToolBm25Handler;
 pub use shell::ShellCommandHandler;
 pub use shell::ShellHandler;
+pub(crate) use str_replace_edit::STR_REPLACE_EDIT_TOOL_NAME;
+pub use str_replace_edit::StrReplaceEditHandler;
+pub(crate) use str_replace_edit::create_str_replace_edit_tool;
 pub use test_sync::TestSyncHandler;
 pub use unified_exec::UnifiedExecHandler;
 pub use view_image::ViewImageHandler;
+pub(crate) use watch_files::WATCH_FILES_TOOL_NAME;
+pub use watch_files::WatchFilesHandler;
+pub use web_fetch::WebFetchHandler;
 
 fn parse_arguments<T>(arguments: &str) -> Result<T, FunctionCallError>
 where
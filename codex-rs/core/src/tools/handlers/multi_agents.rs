@@ -35,6 +35,7 @@ use codex_protocol::protocol::CollabResumeBeginEvent;
 use codex_protocol::protocol::CollabResumeEndEvent;
 use codex_protocol::protocol::CollabWaitingBeginEvent;
 use codex_protocol::protocol::CollabWaitingEndEvent;
+use codex_protocol::protocol::SandboxPolicy;
 use codex_protocol::protocol::SessionSource;
 use codex_protocol::protocol::SubAgentSource;
 use codex_protocol::user_input::UserInput;
@@ -114,6 +115,9 @@ mod spawn {
         agent_type: Option<String>,
         #[serde(default)]
         fork_context: bool,
+        cwd: Option<String>,
+        max_tokens: Option<i64>,
+        tools: Option<Vec<String>>,
     }
 
     #[derive(Debug, Serialize)]
@@ -161,6 +165,13 @@ mod spawn {
             .await
             .map_err(FunctionCallError::RespondToModel)?;
         apply_spawn_agent_runtime_overrides(&mut config, turn.as_ref())?;
+        apply_spawn_agent_scope(
+            &mut config,
+            turn.as_ref(),
+            args.cwd.as_deref(),
+            args.max_tokens,
+            args.tools,
+        )?;
         apply_spawn_agent_overrides(&mut config, child_depth);
 
         let result = session
@@ -971,6 +982,73 @@ fn apply_spawn_agent_runtime_overrides(
     Ok(())
 }
 
+/// Narrows a child config to the scope requested in a `spawn_agent` call.
+///
+/// A scope can only shrink what the parent turn has: the cwd must lie inside the parent's
+/// workspace, the token budget never exceeds the parent's, and the tool list is intersected with
+/// any list the parent is itself restricted to.
+fn apply_spawn_agent_scope(
+    config: &mut Config,
+    turn: &TurnContext,
+    cwd: Option<&str>,
+    max_tokens: Option<i64>,
+    tools: Option<Vec<String>>,
+) -> Result<(), FunctionCallError> {
+    if let Some(requested) = cwd.map(str::trim).filter(|cwd| !cwd.is_empty()) {
+        let cwd = dunce::canonicalize(turn.resolve_path(Some(requested.to_string())))
+            .ok()
+            .filter(|cwd| cwd.is_dir())
+            .ok_or_else(|| {
+                FunctionCallError::RespondToModel(format!("cwd `{requested}` is not a directory"))
+            })?;
+        let in_workspace = std::iter::once(turn.cwd.as_path())
+            .chain(turn.workspace_roots.iter().map(|root| root.path.as_path()))
+            .filter_map(|root| dunce::canonicalize(root).ok())
+            .any(|root| cwd.starts_with(root));
+        if !in_workspace {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "cwd `{requested}` is outside your workspace"
+            )));
+        }
+        let mut sandbox_policy = config.permissions.sandbox_policy.get().clone();
+        if let SandboxPolicy::WorkspaceWrite { writable_roots, .. } = &mut sandbox_policy {
+            writable_roots.retain(|root| root.as_path().starts_with(&cwd));
+            config
+                .permissions
+                .sandbox_policy
+                .set(sandbox_policy)
+                .map_err(|err| {
+                    FunctionCallError::RespondToModel(format!("sandbox_policy is invalid: {err}"))
+                })?;
+        }
+        config.workspace_roots.clear();
+        config.cwd = cwd;
+    }
+
+    if let Some(max_tokens) = max_tokens {
+        if max_tokens <= 0 {
+            return Err(FunctionCallError::RespondToModel(
+                "max_tokens must be greater than zero".to_string(),
+            ));
+        }
+        let session_limit = &mut config.token_budget.max_tokens_per_session;
+        *session_limit = Some(session_limit.map_or(max_tokens, |limit| limit.min(max_tokens)));
+    }
+
+    if let Some(tools) = tools {
+        let tools = tools
+            .iter()
+            .map(|tool| tool.trim())
+            .filter(|tool| !tool.is_empty())
+            .map(str::to_string);
+        config.allowed_tools = Some(match config.allowed_tools.take() {
+            Some(allowed) => tools.filter(|tool| allowed.contains(tool)).collect(),
+            None => tools.collect(),
+        });
+    }
+    Ok(())
+}
+
 fn apply_spawn_agent_overrides(config: &mut Config, child_depth: i32) {
     if child_depth >= config.agent_max_depth {
         let _ = config.features.disable(Feature::Collab);
@@ -1022,6 +1100,7 @@ mod tests {
             call_id: "call-1".to_string(),
             tool_name: tool_name.to_string(),
             payload,
+            dry_run: false,
         }
     }
 
@@ -1047,6 +1126,7 @@ mod tests {
             "spawn_agent",
             ToolPayload::Custom {
                 input: "hello".to_string(),
+                attachments: Vec::new(),
             },
         );
         let Err(err) = MultiAgentHandler.handle(invocation).await else {
@@ -2095,6 +2175,62 @@ mod tests {
         assert_eq!(config, expected);
     }
 
+    #[tokio::test]
+    async fn spawn_agent_scope_narrows_cwd_token_budget_and_tools() {
+        let (_session, mut turn) = make_session_and_context().await;
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let package_dir = temp_dir.path().join("package");
+        std::fs::create_dir(&package_dir).expect("create package dir");
+        turn.cwd = temp_dir.path().to_path_buf();
+        let mut config = build_agent_spawn_config(
+            &BaseInstructions {
+                text: "base".to_string(),
+            },
+            &turn,
+        )
+        .expect("spawn config");
+        config.workspace_roots = vec![PathBuf::from("/elsewhere")];
+        config.token_budget.max_tokens_per_session = Some(50_000);
+        config.allowed_tools = Some(vec!["shell".to_string(), "read_file".to_string()]);
+
+        apply_spawn_agent_scope(
+            &mut config,
+            &turn,
+            Some("package"),
+            Some(10_000),
+            Some(vec!["shell".to_string(), "web_fetch".to_string()]),
+        )
+        .expect("scope should apply");
+
+        assert_eq!(
+            config.cwd,
+            dunce::canonicalize(&package_dir).expect("canonical package dir")
+        );
+        assert_eq!(config.workspace_roots, Vec::<PathBuf>::new());
+        assert_eq!(config.token_budget.max_tokens_per_session, Some(10_000));
+        assert_eq!(config.allowed_tools, Some(vec!["shell".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn spawn_agent_scope_rejects_cwd_outside_workspace() {
+        let (_session, mut turn) = make_session_and_context().await;
+        let workspace = tempfile::tempdir().expect("temp dir");
+        let outside = tempfile::tempdir().expect("temp dir");
+        turn.cwd = workspace.path().to_path_buf();
+        let mut config = (*turn.config).clone();
+        let outside_path = outside.path().display().to_string();
+
+        let err = apply_spawn_agent_scope(&mut config, &turn, Some(&outside_path), None, None)
+            .expect_err("cwd outside the workspace should be rejected");
+
+        assert_eq!(
+            err,
+            FunctionCallError::RespondToModel(format!(
+                "cwd `{outside_path}` is outside your workspace"
+            ))
+        );
+    }
+
     #[tokio::test]
     async fn build_agent_spawn_config_preserves_base_user_instructions() {
         let (_session, mut turn) = make_session_and_context().await;
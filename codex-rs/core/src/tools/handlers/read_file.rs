@@ -98,6 +98,10 @@ impl ToolHandler for ReadFileHandler {
         ToolKind::Function
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
         let ToolInvocation { payload, .. } = invocation;
 
@@ -1,10 +1,15 @@
 use async_trait::async_trait;
 use codex_protocol::ThreadId;
 use codex_protocol::models::FunctionCallOutputBody;
+use codex_protocol::models::FunctionCallOutputContentItem;
 use codex_protocol::models::ShellCommandToolCallParams;
 use codex_protocol::models::ShellToolCallParams;
+use serde_json::Value as JsonValue;
 use std::sync::Arc;
 
+use crate::approval_rules::ApprovalSubject;
+use crate::approval_rules::apply_approval_rule;
+use crate::approval_rules::evaluate_approval_rules;
 use crate::codex::TurnContext;
 use crate::exec::ExecParams;
 use crate::exec_env::create_env;
@@ -24,6 +29,10 @@ use crate::tools::handlers::apply_patch::intercept_apply_patch;
 use crate::tools::handlers::normalize_and_validate_additional_permissions;
 use crate::tools::handlers::parse_arguments_with_base_path;
 use crate::tools::handlers::resolve_workdir_base_path;
+use crate::tools::handlers::unified_exec::default_exec_yield_time_ms;
+use crate::tools::handlers::unified_exec::format_response;
+use crate::tools::image_output::IMAGE_OUTPUT_DIR_ENV_VAR;
+use crate::tools::image_output::ImageOutputDir;
 use crate::tools::orchestrator::ToolOrchestrator;
 use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
@@ -32,6 +41,8 @@ use crate::tools::runtimes::shell::ShellRuntime;
 use crate::tools::runtimes::shell::ShellRuntimeBackend;
 use crate::tools::sandboxing::ToolCtx;
 use crate::tools::spec::ShellCommandBackendConfig;
+use crate::unified_exec::ExecCommandRequest;
+use crate::unified_exec::UnifiedExecContext;
 use codex_protocol::models::PermissionProfile;
 
 pub struct ShellHandler;
@@ -65,11 +76,15 @@ impl ShellHandler {
         turn_context: &TurnContext,
         thread_id: ThreadId,
     ) -> ExecParams {
+        let mut env = create_env(&turn_context.shell_environment_policy, Some(thread_id));
+        if let Some(overrides) = &params.env {
+            env.extend(overrides.clone());
+        }
         ExecParams {
             command: params.command.clone(),
             cwd: turn_context.resolve_path(params.workdir.clone()),
             expiration: params.timeout_ms.into(),
-            env: create_env(&turn_context.shell_environment_policy, Some(thread_id)),
+            env,
             network: turn_context.network.clone(),
             sandbox_permissions: params.sandbox_permissions.unwrap_or_default(),
             windows_sandbox_level: turn_context.windows_sandbox_level,
@@ -79,6 +94,52 @@ impl ShellHandler {
     }
 }
 
+impl ShellHandler {
+    /// Runs `params.command` attached to a PTY through the unified exec process
+    /// manager, which applies the same approval and sandbox policy as other
+    /// exec paths. Commands still running after the yield window keep running
+    /// in the background and are reported with their process id.
+    async fn run_in_pty(
+        params: &ShellToolCallParams,
+        session: Arc<crate::codex::Session>,
+        turn: Arc<TurnContext>,
+        call_id: String,
+    ) -> Result<ToolOutput, FunctionCallError> {
+        let manager = &session.services.unified_exec_manager;
+        let context = UnifiedExecContext::new(Arc::clone(&session), Arc::clone(&turn), call_id);
+        let process_id = manager.allocate_process_id().await;
+        let response = manager
+            .exec_command(
+                ExecCommandRequest {
+                    command: params.command.clone(),
+                    process_id,
+                    yield_time_ms: params.timeout_ms.unwrap_or_else(default_exec_yield_time_ms),
+                    max_output_tokens: None,
+                    workdir: params
+                        .workdir
+                        .clone()
+                        .map(|dir| turn.resolve_path(Some(dir))),
+                    network: turn.network.clone(),
+                    tty: true,
+                    sandbox_permissions: params.sandbox_permissions.unwrap_or_default(),
+                    additional_permissions: None,
+                    justification: params.justification.clone(),
+                    prefix_rule: None,
+                },
+                &context,
+            )
+            .await
+            .map_err(|err| {
+                FunctionCallError::RespondToModel(format!("pty exec failed: {err:?}"))
+            })?;
+
+        Ok(ToolOutput::Function {
+            body: FunctionCallOutputBody::Text(format_response(&response)),
+            success: Some(response.exit_code.is_none_or(|code| code == 0)),
+        })
+    }
+}
+
 impl ShellCommandHandler {
     fn shell_runtime_backend(&self) -> ShellRuntimeBackend {
         match self.backend {
@@ -129,6 +190,16 @@ impl ShellCommandHandler {
     }
 }
 
+/// What a dry run reports for a shell call: the command it would run, where,
+/// and with which sandbox permissions.
+fn exec_preview(exec_params: &ExecParams) -> JsonValue {
+    serde_json::json!({
+        "command": exec_params.command,
+        "workdir": exec_params.cwd,
+        "sandbox_permissions": exec_params.sandbox_permissions,
+    })
+}
+
 impl From<ShellCommandBackendConfig> for ShellCommandHandler {
     fn from(config: ShellCommandBackendConfig) -> Self {
         let backend = match config {
@@ -164,6 +235,26 @@ impl ToolHandler for ShellHandler {
         }
     }
 
+    async fn dry_run_preview(
+        &self,
+        invocation: &ToolInvocation,
+    ) -> Result<Option<JsonValue>, FunctionCallError> {
+        let thread_id = invocation.session.conversation_id;
+        let exec_params = match &invocation.payload {
+            ToolPayload::Function { arguments } => {
+                let cwd = resolve_workdir_base_path(arguments, invocation.turn.cwd.as_path())?;
+                let params: ShellToolCallParams =
+                    parse_arguments_with_base_path(arguments, cwd.as_path())?;
+                Self::to_exec_params(&params, invocation.turn.as_ref(), thread_id)
+            }
+            ToolPayload::LocalShell { params } => {
+                Self::to_exec_params(params, invocation.turn.as_ref(), thread_id)
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(exec_preview(&exec_params)))
+    }
+
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
         let ToolInvocation {
             session,
@@ -180,6 +271,9 @@ impl ToolHandler for ShellHandler {
                 let cwd = resolve_workdir_base_path(&arguments, turn.cwd.as_path())?;
                 let params: ShellToolCallParams =
                     parse_arguments_with_base_path(&arguments, cwd.as_path())?;
+                if params.use_pty {
+                    return Self::run_in_pty(&params, session, turn, call_id).await;
+                }
                 let prefix_rule = params.prefix_rule.clone();
                 let exec_params =
                     Self::to_exec_params(&params, turn.as_ref(), session.conversation_id);
@@ -197,6 +291,9 @@ impl ToolHandler for ShellHandler {
                 })
                 .await
             }
+            ToolPayload::LocalShell { params } if params.use_pty => {
+                Self::run_in_pty(&params, session, turn, call_id).await
+            }
             ToolPayload::LocalShell { params } => {
                 let exec_params =
                     Self::to_exec_params(&params, turn.as_ref(), session.conversation_id);
@@ -252,6 +349,26 @@ impl ToolHandler for ShellCommandHandler {
             .unwrap_or(true)
     }
 
+    async fn dry_run_preview(
+        &self,
+        invocation: &ToolInvocation,
+    ) -> Result<Option<JsonValue>, FunctionCallError> {
+        let ToolPayload::Function { arguments } = &invocation.payload else {
+            return Ok(None);
+        };
+        let cwd = resolve_workdir_base_path(arguments, invocation.turn.cwd.as_path())?;
+        let params: ShellCommandToolCallParams =
+            parse_arguments_with_base_path(arguments, cwd.as_path())?;
+        let exec_params = Self::to_exec_params(
+            &params,
+            invocation.session.as_ref(),
+            invocation.turn.as_ref(),
+            invocation.session.conversation_id,
+            invocation.turn.tools_config.allow_login_shell,
+        )?;
+        Ok(Some(exec_preview(&exec_params)))
+    }
+
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
         let ToolInvocation {
             session,
@@ -331,6 +448,15 @@ impl ShellHandler {
             }
         }
 
+        let image_output_dir = ImageOutputDir::for_turn(turn.as_ref());
+        if let Some(dir) = &image_output_dir {
+            let path = dir.path().display().to_string();
+            exec_params
+                .env
+                .insert(IMAGE_OUTPUT_DIR_ENV_VAR.to_string(), path.clone());
+            explicit_env_overrides.insert(IMAGE_OUTPUT_DIR_ENV_VAR.to_string(), path);
+        }
+
         let request_permission_enabled = session.features().enabled(Feature::RequestPermissions);
         let normalized_additional_permissions = normalize_and_validate_additional_permissions(
             request_permission_enabled,
@@ -391,6 +517,14 @@ impl ShellHandler {
                 prefix_rule,
             })
             .await;
+        let exec_approval_requirement = apply_approval_rule(
+            exec_approval_requirement,
+            evaluate_approval_rules(
+                &turn.config.approval_rules,
+                &ApprovalSubject::Command(&exec_params.command),
+            ),
+            turn.approval_policy.value(),
+        );
 
         let req = ShellRequest {
             command: exec_params.command.clone(),
@@ -432,8 +566,20 @@ impl ShellHandler {
             .map(|result| result.output);
         let event_ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None);
         let content = emitter.finish(event_ctx, out).await?;
+        let images = image_output_dir
+            .map(|dir| dir.collect(turn.as_ref()))
+            .unwrap_or_default();
+        let body = if images.is_empty() {
+            FunctionCallOutputBody::Text(content)
+        } else {
+            FunctionCallOutputBody::ContentItems(
+                std::iter::once(FunctionCallOutputContentItem::InputText { text: content })
+                    .chain(images)
+                    .collect(),
+            )
+        };
         Ok(ToolOutput::Function {
-            body: FunctionCallOutputBody::Text(content),
+            body,
             success: Some(true),
         })
     }
@@ -441,6 +587,7 @@ impl ShellHandler {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use std::path::PathBuf;
     use std::sync::Arc;
 
@@ -452,11 +599,20 @@ mod tests {
     use crate::is_safe_command::is_known_safe_command;
     use crate::powershell::try_find_powershell_executable_blocking;
     use crate::powershell::try_find_pwsh_executable_blocking;
+    use crate::protocol::AskForApproval;
+    use crate::protocol::SandboxPolicy;
     use crate::sandboxing::SandboxPermissions;
     use crate::shell::Shell;
     use crate::shell::ShellType;
     use crate::shell_snapshot::ShellSnapshot;
+    use crate::tools::context::ToolInvocation;
+    use crate::tools::context::ToolOutput;
+    use crate::tools::context::ToolPayload;
     use crate::tools::handlers::ShellCommandHandler;
+    use crate::tools::handlers::ShellHandler;
+    use crate::tools::registry::ToolHandler;
+    use crate::turn_diff_tracker::TurnDiffTracker;
+    use codex_protocol::models::ShellToolCallParams;
     use tokio::sync::watch;
 
     /// The logic for is_known_safe_command() has heuristics for known shells,
@@ -555,6 +711,40 @@ mod tests {
         assert_eq!(exec_params.arg0, None);
     }
 
+    #[tokio::test]
+    async fn shell_handler_merges_env_overrides_into_inherited_env() {
+        let (session, turn_context) = make_session_and_context().await;
+        let inherited_env = create_env(
+            &turn_context.shell_environment_policy,
+            Some(session.conversation_id),
+        );
+        let mut params = ShellToolCallParams {
+            command: vec!["env".to_string()],
+            workdir: None,
+            timeout_ms: None,
+            sandbox_permissions: None,
+            additional_permissions: None,
+            prefix_rule: None,
+            justification: None,
+            use_pty: false,
+            env: None,
+        };
+
+        let exec_params =
+            ShellHandler::to_exec_params(&params, &turn_context, session.conversation_id);
+        assert_eq!(exec_params.env, inherited_env);
+
+        params.env = Some(HashMap::from([(
+            "CODEX_TEST_CI_TOKEN".to_string(),
+            "token".to_string(),
+        )]));
+        let exec_params =
+            ShellHandler::to_exec_params(&params, &turn_context, session.conversation_id);
+        let mut expected_env = inherited_env;
+        expected_env.insert("CODEX_TEST_CI_TOKEN".to_string(), "token".to_string());
+        assert_eq!(exec_params.env, expected_env);
+    }
+
     #[test]
     fn shell_command_handler_respects_explicit_login_flag() {
         let (_tx, shell_snapshot) = watch::channel(Some(Arc::new(ShellSnapshot {
@@ -621,4 +811,60 @@ mod tests {
             "unexpected error: {err}"
         );
     }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn local_shell_with_use_pty_runs_attached_to_a_terminal() -> anyhow::Result<()> {
+        core_test_support::skip_if_sandbox!(Ok(()));
+
+        let (session, mut turn) = make_session_and_context().await;
+        turn.approval_policy
+            .set(AskForApproval::Never)
+            .expect("test setup should allow updating approval policy");
+        turn.sandbox_policy
+            .set(SandboxPolicy::DangerFullAccess)
+            .expect("test setup should allow updating sandbox policy");
+        turn.file_system_sandbox_policy =
+            codex_protocol::permissions::FileSystemSandboxPolicy::from(turn.sandbox_policy.get());
+        turn.network_sandbox_policy =
+            codex_protocol::permissions::NetworkSandboxPolicy::from(turn.sandbox_policy.get());
+        let params = ShellToolCallParams {
+            command: vec![
+                "bash".to_string(),
+                "-c".to_string(),
+                "test -t 1 && echo hello from a tty".to_string(),
+            ],
+            workdir: None,
+            timeout_ms: Some(5_000),
+            sandbox_permissions: None,
+            additional_permissions: None,
+            prefix_rule: None,
+            justification: None,
+            use_pty: true,
+            env: None,
+        };
+
+        let output = ShellHandler
+            .handle(ToolInvocation {
+                session: Arc::new(session),
+                turn: Arc::new(turn),
+                tracker: Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                call_id: "call-pty".to_string(),
+                tool_name: "local_shell".to_string(),
+                payload: ToolPayload::LocalShell { params },
+                dry_run: false,
+            })
+            .await?;
+
+        let ToolOutput::Function { body, success } = output else {
+            panic!("expected function output");
+        };
+        let text = body.to_text().unwrap_or_default();
+        assert!(
+            text.contains("hello from a tty"),
+            "unexpected output: {text}"
+        );
+        assert_eq!(success, Some(true));
+        Ok(())
+    }
 }
@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use codex_apply_patch::StrReplaceMatch;
+use codex_protocol::models::FunctionCallOutputBody;
+use serde::Deserialize;
+
+use crate::client_common::tools::ResponsesApiTool;
+use crate::client_common::tools::ToolSpec;
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::handlers::ApplyPatchHandler;
+use crate::tools::handlers::parse_arguments;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::tools::spec::JsonSchema;
+
+pub(crate) const STR_REPLACE_EDIT_TOOL_NAME: &str = "str_replace_edit";
+
+/// Edits files by search and replace, for models that are more reliable with
+/// it than with the patch format. Each edit is turned into a patch and run
+/// through [`ApplyPatchHandler`], so approval, sandboxing, events and the
+/// turn diff behave exactly as for `apply_patch`.
+pub struct StrReplaceEditHandler;
+
+#[derive(Deserialize)]
+struct StrReplaceEditArgs {
+    path: String,
+    old_string: String,
+    new_string: String,
+    #[serde(default)]
+    replace_all: bool,
+}
+
+#[async_trait]
+impl ToolHandler for StrReplaceEditHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn is_mutating(&self, _invocation: &ToolInvocation) -> bool {
+        true
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolPayload::Function { arguments } = &invocation.payload else {
+            return Err(FunctionCallError::RespondToModel(
+                "str_replace_edit handler received unsupported payload".to_string(),
+            ));
+        };
+        let args: StrReplaceEditArgs = parse_arguments(arguments)?;
+        let path = invocation.turn.resolve_path(Some(args.path));
+        let contents = match tokio::fs::read_to_string(&path).await {
+            Ok(contents) => Some(contents),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "failed to read {}: {err}",
+                    path.display()
+                )));
+            }
+        };
+        let edit = codex_apply_patch::str_replace_patch(
+            &path,
+            contents.as_deref(),
+            &args.old_string,
+            &args.new_string,
+            args.replace_all,
+        )
+        .map_err(|err| FunctionCallError::RespondToModel(format!("edit failed: {err}")))?;
+
+        let arguments = serde_json::json!({ "input": edit.patch }).to_string();
+        let output = ApplyPatchHandler
+            .handle(ToolInvocation {
+                payload: ToolPayload::Function { arguments },
+                ..invocation
+            })
+            .await?;
+
+        let note = match (edit.matched, edit.replacements) {
+            (StrReplaceMatch::IgnoringWhitespace, count) => Some(format!(
+                "Replaced {count} occurrence(s); old_string only matched after ignoring leading and trailing whitespace, so check the indentation of the result."
+            )),
+            (StrReplaceMatch::Exact, count) if count > 1 => {
+                Some(format!("Replaced {count} occurrences."))
+            }
+            (StrReplaceMatch::Exact, _) => None,
+        };
+        Ok(match (output, note) {
+            (
+                ToolOutput::Function {
+                    body: FunctionCallOutputBody::Text(text),
+                    success,
+                },
+                Some(note),
+            ) => ToolOutput::Function {
+                body: FunctionCallOutputBody::Text(format!("{text}\n{note}")),
+                success,
+            },
+            (output, _) => output,
+        })
+    }
+}
+
+pub(crate) fn create_str_replace_edit_tool() -> ToolSpec {
+    let properties = BTreeMap::from([
+        (
+            "path".to_string(),
+            JsonSchema::String {
+                description: Some("Path of the file to edit, relative to the working directory.".to_string()),
+            },
+        ),
+        (
+            "old_string".to_string(),
+            JsonSchema::String {
+                description: Some(
+                    "Exact text to replace, including enough surrounding lines to occur only once. Pass an empty string to create a new file."
+                        .to_string(),
+                ),
+            },
+        ),
+        (
+            "new_string".to_string(),
+            JsonSchema::String {
+                description: Some(
+                    "Text to put in place of old_string, or the contents of the new file."
+                        .to_string(),
+                ),
+            },
+        ),
+        (
+            "replace_all".to_string(),
+            JsonSchema::Boolean {
+                description: Some(
+                    "Replace every occurrence of old_string instead of requiring exactly one. Defaults to false."
+                        .to_string(),
+                ),
+            },
+        ),
+    ]);
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: STR_REPLACE_EDIT_TOOL_NAME.to_string(),
+        description: "Edits a file by replacing old_string with new_string. old_string must match the file exactly, including whitespace, and occur exactly once unless replace_all is set; read the file first so it is copied accurately. To create a file, pass an empty old_string and the contents as new_string."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec![
+                "path".to_string(),
+                "old_string".to_string(),
+                "new_string".to_string(),
+            ]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
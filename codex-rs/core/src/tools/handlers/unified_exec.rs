@@ -1,6 +1,7 @@
 use crate::features::Feature;
 use crate::function_tool::FunctionCallError;
 use crate::is_safe_command::is_known_safe_command;
+use crate::parse_command::shlex_join;
 use crate::protocol::EventMsg;
 use crate::protocol::TerminalInteractionEvent;
 use crate::sandboxing::SandboxPermissions;
@@ -17,6 +18,7 @@ use crate::tools::handlers::parse_arguments_with_base_path;
 use crate::tools::handlers::resolve_workdir_base_path;
 use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
+use crate::unified_exec::BackgroundProcessSummary;
 use crate::unified_exec::ExecCommandRequest;
 use crate::unified_exec::UnifiedExecContext;
 use crate::unified_exec::UnifiedExecProcessManager;
@@ -68,7 +70,7 @@ struct WriteStdinArgs {
     max_output_tokens: Option<usize>,
 }
 
-fn default_exec_yield_time_ms() -> u64 {
+pub(crate) fn default_exec_yield_time_ms() -> u64 {
     10000
 }
 
@@ -76,6 +78,11 @@ fn default_write_stdin_yield_time_ms() -> u64 {
     250
 }
 
+#[derive(Debug, Deserialize)]
+struct KillBackgroundJobArgs {
+    session_id: i32,
+}
+
 fn default_tty() -> bool {
     false
 }
@@ -266,6 +273,32 @@ impl ToolHandler for UnifiedExecHandler {
 
                 response
             }
+            "list_background_jobs" => {
+                let content = format_background_jobs(&manager.list_processes().await);
+                return Ok(ToolOutput::Function {
+                    body: FunctionCallOutputBody::Text(content),
+                    success: Some(true),
+                });
+            }
+            "kill_background_job" => {
+                let args: KillBackgroundJobArgs = parse_arguments(&arguments)?;
+                let command = manager
+                    .terminate_process(&args.session_id.to_string())
+                    .await
+                    .map_err(|err| {
+                        FunctionCallError::RespondToModel(format!(
+                            "kill_background_job failed: {err}"
+                        ))
+                    })?;
+                let command = shlex_join(&command);
+                return Ok(ToolOutput::Function {
+                    body: FunctionCallOutputBody::Text(format!(
+                        "Terminated session ID {}: {command}",
+                        args.session_id
+                    )),
+                    success: Some(true),
+                });
+            }
             other => {
                 return Err(FunctionCallError::RespondToModel(format!(
                     "unsupported unified exec function {other}"
@@ -307,7 +340,29 @@ pub(crate) fn get_command(
     Ok(shell.derive_exec_args(&args.cmd, use_login_shell))
 }
 
-fn format_response(response: &UnifiedExecResponse) -> String {
+fn format_background_jobs(processes: &[BackgroundProcessSummary]) -> String {
+    if processes.is_empty() {
+        return "No background jobs are running.".to_string();
+    }
+    processes
+        .iter()
+        .map(|process| {
+            let status = match process.exit_code {
+                Some(exit_code) => format!("exited with code {exit_code}"),
+                None => "running".to_string(),
+            };
+            let idle_seconds = process.idle.as_secs();
+            let command = shlex_join(&process.command);
+            format!(
+                "Session ID {} ({status}, idle {idle_seconds}s): {command}",
+                process.process_id
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub(crate) fn format_response(response: &UnifiedExecResponse) -> String {
     let mut sections = Vec::new();
 
     if !response.chunk_id.is_empty() {
@@ -350,6 +405,38 @@ mod tests {
     use std::sync::Arc;
     use tempfile::tempdir;
 
+    #[test]
+    fn format_background_jobs_lists_each_session() {
+        assert_eq!(
+            format_background_jobs(&[]),
+            "No background jobs are running."
+        );
+
+        let processes = [
+            BackgroundProcessSummary {
+                process_id: "3".to_string(),
+                command: vec![
+                    "bash".to_string(),
+                    "-lc".to_string(),
+                    "npm run dev".to_string(),
+                ],
+                exit_code: None,
+                idle: std::time::Duration::from_secs(12),
+            },
+            BackgroundProcessSummary {
+                process_id: "7".to_string(),
+                command: vec!["cargo".to_string(), "watch".to_string()],
+                exit_code: Some(1),
+                idle: std::time::Duration::from_millis(500),
+            },
+        ];
+        assert_eq!(
+            format_background_jobs(&processes),
+            "Session ID 3 (running, idle 12s): bash -lc 'npm run dev'\n\
+             Session ID 7 (exited with code 1, idle 0s): cargo watch"
+        );
+    }
+
     #[test]
     fn test_get_command_uses_default_shell_when_unspecified() -> anyhow::Result<()> {
         let json = r#"{"cmd": "echo hello"}"#;
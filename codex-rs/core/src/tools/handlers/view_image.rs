@@ -1,15 +1,9 @@
 use async_trait::async_trait;
-use codex_protocol::models::ContentItem;
 use codex_protocol::models::FunctionCallOutputBody;
-use codex_protocol::models::FunctionCallOutputContentItem;
-use codex_protocol::models::ImageDetail;
-use codex_protocol::models::local_image_content_items_with_label_number;
 use codex_protocol::openai_models::InputModality;
-use codex_utils_image::PromptImageMode;
 use serde::Deserialize;
 use tokio::fs;
 
-use crate::features::Feature;
 use crate::function_tool::FunctionCallError;
 use crate::protocol::EventMsg;
 use crate::protocol::ViewImageToolCallEvent;
@@ -17,6 +11,7 @@ use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
 use crate::tools::context::ToolPayload;
 use crate::tools::handlers::parse_arguments;
+use crate::tools::image_output::local_image_output_items;
 use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
 
@@ -84,32 +79,7 @@ impl ToolHandler for ViewImageHandler {
         }
         let event_path = abs_path.clone();
 
-        let use_original_detail = turn.config.features.enabled(Feature::ImageDetailOriginal)
-            && turn.model_info.supports_image_detail_original;
-        let image_mode = if use_original_detail {
-            PromptImageMode::Original
-        } else {
-            PromptImageMode::ResizeToFit
-        };
-        let image_detail = use_original_detail.then_some(ImageDetail::Original);
-
-        let content = local_image_content_items_with_label_number(&abs_path, None, image_mode)
-            .into_iter()
-            .map(|item| match item {
-                ContentItem::InputText { text } => {
-                    FunctionCallOutputContentItem::InputText { text }
-                }
-                ContentItem::InputImage { image_url } => {
-                    FunctionCallOutputContentItem::InputImage {
-                        image_url,
-                        detail: image_detail,
-                    }
-                }
-                ContentItem::OutputText { text } => {
-                    FunctionCallOutputContentItem::InputText { text }
-                }
-            })
-            .collect();
+        let content = local_image_output_items(&abs_path, turn.as_ref());
 
         session
             .send_event(
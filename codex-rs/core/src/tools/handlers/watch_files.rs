@@ -0,0 +1,96 @@
+use async_trait::async_trait;
+use codex_protocol::models::FunctionCallOutputBody;
+use serde::Deserialize;
+
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::handlers::parse_arguments;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::watched_files::MAX_WATCHED_FILES;
+
+pub struct WatchFilesHandler;
+
+pub(crate) const WATCH_FILES_TOOL_NAME: &str = "watch_files";
+
+#[derive(Deserialize)]
+struct WatchFilesArgs {
+    #[serde(default)]
+    patterns: Vec<String>,
+    #[serde(default)]
+    stop: bool,
+}
+
+#[async_trait]
+impl ToolHandler for WatchFilesHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            payload,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "{WATCH_FILES_TOOL_NAME} handler received unsupported payload"
+                )));
+            }
+        };
+        let args: WatchFilesArgs = parse_arguments(&arguments)?;
+        if !args.stop
+            && args
+                .patterns
+                .iter()
+                .all(|pattern| pattern.trim().is_empty())
+        {
+            return Err(FunctionCallError::RespondToModel(
+                "patterns must not be empty".to_string(),
+            ));
+        }
+
+        let watched_files = session.watched_files().await;
+        let cwd = turn.cwd.clone();
+        let message = tokio::task::spawn_blocking(move || {
+            let mut watched = watched_files
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            if args.stop {
+                watched.unwatch(&args.patterns)?;
+            } else {
+                watched.watch(&cwd, &args.patterns)?;
+            }
+            if watched.is_empty() {
+                return Ok("No files are being watched.".to_string());
+            }
+            let limit_note = if watched.file_count() >= MAX_WATCHED_FILES {
+                " (the limit; narrow the patterns to watch the rest)".to_string()
+            } else {
+                String::new()
+            };
+            Ok::<_, String>(format!(
+                "Watching {} files{limit_note} matching: {}. Changes made outside your own patches will be reported at the start of the next turn.",
+                watched.file_count(),
+                watched.patterns().join(", ")
+            ))
+        })
+        .await
+        .map_err(|err| {
+            FunctionCallError::RespondToModel(format!("failed to update watched files: {err}"))
+        })?
+        .map_err(FunctionCallError::RespondToModel)?;
+
+        Ok(ToolOutput::Function {
+            body: FunctionCallOutputBody::Text(message),
+            success: Some(true),
+        })
+    }
+}
@@ -0,0 +1,769 @@
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use codex_protocol::models::FunctionCallOutputBody;
+use reqwest::dns::Addrs;
+use reqwest::dns::Name;
+use reqwest::dns::Resolve;
+use reqwest::dns::Resolving;
+use reqwest::header::CONTENT_TYPE;
+use serde::Deserialize;
+use url::Host;
+use url::Url;
+
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::handlers::parse_arguments;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::truncate::TruncationPolicy;
+use crate::truncate::truncate_text;
+
+pub struct WebFetchHandler;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_REDIRECTS: usize = 10;
+/// Larger bodies are cut off before conversion.
+const MAX_BODY_BYTES: usize = 5 * 1024 * 1024;
+const DEFAULT_MAX_TOKENS: usize = 10_000;
+/// Product token matched against robots.txt `User-agent` lines.
+const ROBOTS_USER_AGENT: &str = "codex";
+
+/// Elements whose content is page chrome rather than the page itself.
+const BOILERPLATE_TAGS: &[&str] = &[
+    "aside", "button", "footer", "form", "head", "header", "iframe", "nav", "noscript", "script",
+    "select", "style", "svg", "template",
+];
+
+#[derive(Deserialize)]
+struct WebFetchArgs {
+    url: String,
+}
+
+#[async_trait]
+impl ToolHandler for WebFetchHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation { payload, turn, .. } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(
+                    "web_fetch handler received unsupported payload".to_string(),
+                ));
+            }
+        };
+
+        if !turn.network_sandbox_policy.is_enabled() {
+            return Err(FunctionCallError::RespondToModel(
+                "web_fetch is unavailable because network access is disabled in this sandbox"
+                    .to_string(),
+            ));
+        }
+
+        let args: WebFetchArgs = parse_arguments(&arguments)?;
+        let url = Url::parse(&args.url).map_err(|err| {
+            FunctionCallError::RespondToModel(format!("invalid url {}: {err}", args.url))
+        })?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "web_fetch only supports http and https urls, got {url}"
+            )));
+        }
+        if !is_public_host(&url) {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "web_fetch does not fetch non-public addresses, got {url}"
+            )));
+        }
+
+        let config = &turn.config.web_fetch;
+        let allowed_domains = config.allowed_domains.clone();
+        if !url
+            .host_str()
+            .is_some_and(|host| is_domain_allowed(host, allowed_domains.as_deref()))
+        {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "{url} is not in the domains allowed for web_fetch"
+            )));
+        }
+
+        // Proxies resolve hosts themselves, which would bypass the resolver.
+        let client = reqwest::Client::builder()
+            .user_agent(format!("{ROBOTS_USER_AGENT}/{}", env!("CARGO_PKG_VERSION")))
+            .no_proxy()
+            .dns_resolver(Arc::new(PublicAddressResolver))
+            .timeout(FETCH_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    return attempt.error("too many redirects");
+                }
+                if !is_public_host(attempt.url()) {
+                    return attempt.error("redirect to a non-public address");
+                }
+                let allowed = attempt
+                    .url()
+                    .host_str()
+                    .is_some_and(|host| is_domain_allowed(host, allowed_domains.as_deref()));
+                if allowed {
+                    attempt.follow()
+                } else {
+                    attempt.stop()
+                }
+            }))
+            .build()
+            .map_err(|err| {
+                FunctionCallError::RespondToModel(format!("failed to build http client: {err}"))
+            })?;
+
+        if config.respect_robots_txt.unwrap_or(true) && !robots_txt_allows(&client, &url).await {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "robots.txt disallows fetching {url}"
+            )));
+        }
+
+        let page = fetch_page(&client, &url).await?;
+        let max_tokens = config.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS);
+        let content = truncate_text(&page.markdown, TruncationPolicy::Tokens(max_tokens));
+
+        Ok(ToolOutput::Function {
+            body: FunctionCallOutputBody::Text(format!("Fetched {}\n\n{content}", page.url)),
+            success: Some(true),
+        })
+    }
+}
+
+struct FetchedPage {
+    /// Final URL, after redirects.
+    url: Url,
+    markdown: String,
+}
+
+async fn fetch_page(client: &reqwest::Client, url: &Url) -> Result<FetchedPage, FunctionCallError> {
+    let mut response = client.get(url.clone()).send().await.map_err(|err| {
+        FunctionCallError::RespondToModel(format!("failed to fetch {url}: {err}"))
+    })?;
+    let status = response.status();
+    if status.is_redirection() {
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .unwrap_or_default();
+        return Err(FunctionCallError::RespondToModel(format!(
+            "{url} redirects to {location}, which is not in the domains allowed for web_fetch"
+        )));
+    }
+    if !status.is_success() {
+        return Err(FunctionCallError::RespondToModel(format!(
+            "failed to fetch {url}: HTTP {status}"
+        )));
+    }
+
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("text/html")
+        .to_ascii_lowercase();
+    let is_html = content_type.contains("html");
+    if !is_html && !content_type.starts_with("text/") && !content_type.contains("json") {
+        return Err(FunctionCallError::RespondToModel(format!(
+            "{url} returned unsupported content type {content_type}"
+        )));
+    }
+
+    let final_url = response.url().clone();
+    let mut body = Vec::new();
+    while body.len() < MAX_BODY_BYTES {
+        match response.chunk().await {
+            Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+            Ok(None) => break,
+            Err(err) => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "failed to read {url}: {err}"
+                )));
+            }
+        }
+    }
+    body.truncate(MAX_BODY_BYTES);
+    let text = String::from_utf8_lossy(&body);
+
+    Ok(FetchedPage {
+        url: final_url,
+        markdown: if is_html {
+            html_to_markdown(&text)
+        } else {
+            text.into_owned()
+        },
+    })
+}
+
+/// Resolves hosts for web_fetch, keeping only public addresses so that
+/// neither the requested URL nor a redirect can reach the host's loopback
+/// interface, private networks or cloud metadata endpoints.
+struct PublicAddressResolver;
+
+impl Resolve for PublicAddressResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{host} does not resolve to a public address").into());
+            }
+            let addrs: Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// Hosts given as IP literals are never resolved, so they are checked here;
+/// domain names are checked by [`PublicAddressResolver`].
+fn is_public_host(url: &Url) -> bool {
+    match url.host() {
+        Some(Host::Domain(_)) => true,
+        Some(Host::Ipv4(ip)) => is_public_ip(IpAddr::V4(ip)),
+        Some(Host::Ipv6(ip)) => is_public_ip(IpAddr::V6(ip)),
+        None => false,
+    }
+}
+
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || first == 0
+                // Shared address space (RFC 6598), used for carrier-grade NAT.
+                || (first == 100 && (64..128).contains(&second)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ip(IpAddr::V4(ip)),
+            None => {
+                !(ip.is_unspecified()
+                    || ip.is_loopback()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+/// A host is allowed when it is, or is a subdomain of, one of
+/// `allowed_domains`. `None` allows every public host.
+fn is_domain_allowed(host: &str, allowed_domains: Option<&[String]>) -> bool {
+    let Some(allowed_domains) = allowed_domains else {
+        return true;
+    };
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    allowed_domains.iter().any(|domain| {
+        let domain = domain.trim_start_matches('.').to_ascii_lowercase();
+        host == domain
+            || host
+                .strip_suffix(&domain)
+                .is_some_and(|prefix| prefix.ends_with('.'))
+    })
+}
+
+/// Sites whose robots.txt cannot be fetched are treated as allowing
+/// everything.
+async fn robots_txt_allows(client: &reqwest::Client, url: &Url) -> bool {
+    let Ok(robots_url) = url.join("/robots.txt") else {
+        return true;
+    };
+    let Ok(response) = client.get(robots_url).send().await else {
+        return true;
+    };
+    if !response.status().is_success() {
+        return true;
+    }
+    let Ok(robots_txt) = response.text().await else {
+        return true;
+    };
+    let path = match url.query() {
+        Some(query) => format!("{}?{query}", url.path()),
+        None => url.path().to_string(),
+    };
+    robots_allows(&robots_txt, ROBOTS_USER_AGENT, &path)
+}
+
+/// Applies the rules of the group matching `user_agent`, or of the `*` group
+/// when none does. The longest matching rule wins, and `Allow` wins ties.
+fn robots_allows(robots_txt: &str, user_agent: &str, path: &str) -> bool {
+    let user_agent = user_agent.to_ascii_lowercase();
+    // (agents, rules) per group; a rule is (allow, pattern).
+    let mut groups: Vec<(Vec<String>, Vec<(bool, String)>)> = Vec::new();
+    let mut in_agent_lines = false;
+    for line in robots_txt.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let Some((field, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match field.trim().to_ascii_lowercase().as_str() {
+            "user-agent" => {
+                if !in_agent_lines {
+                    groups.push((Vec::new(), Vec::new()));
+                }
+                in_agent_lines = true;
+                if let Some((agents, _)) = groups.last_mut() {
+                    agents.push(value.to_ascii_lowercase());
+                }
+            }
+            field @ ("allow" | "disallow") => {
+                in_agent_lines = false;
+                if let Some((_, rules)) = groups.last_mut()
+                    && !value.is_empty()
+                {
+                    rules.push((field == "allow", value.to_string()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let rules_for = |wildcard: bool| {
+        groups
+            .iter()
+            .filter(|(agents, _)| {
+                agents.iter().any(|agent| {
+                    if wildcard {
+                        agent == "*"
+                    } else {
+                        !agent.is_empty() && agent != "*" && user_agent.contains(agent.as_str())
+                    }
+                })
+            })
+            .flat_map(|(_, rules)| rules.iter())
+            .collect::<Vec<_>>()
+    };
+    let mut rules = rules_for(false);
+    if rules.is_empty() {
+        rules = rules_for(true);
+    }
+
+    rules
+        .into_iter()
+        .filter(|(_, pattern)| robots_pattern_matches(pattern, path))
+        .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+        .is_none_or(|(allow, _)| *allow)
+}
+
+/// Matches a robots.txt path pattern, where `*` matches any run of
+/// characters and a trailing `$` anchors the end.
+fn robots_pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = parts.next().and_then(|first| path.strip_prefix(first)) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return !anchored || rest.is_empty();
+    };
+    for part in middle {
+        let Some(index) = rest.find(part) else {
+            return false;
+        };
+        rest = &rest[index + part.len()..];
+    }
+    if anchored {
+        rest.ends_with(last)
+    } else {
+        rest.contains(last)
+    }
+}
+
+/// Converts the readable parts of an HTML page to markdown. Boilerplate such
+/// as navigation, scripts and forms is dropped.
+fn html_to_markdown(html: &str) -> String {
+    let mut out = String::new();
+    let mut rest = html;
+    let mut pre_depth = 0usize;
+    let mut links: Vec<Option<String>> = Vec::new();
+
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            push_text(&mut out, rest, pre_depth > 0);
+            break;
+        };
+        push_text(&mut out, &rest[..start], pre_depth > 0);
+        rest = &rest[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment
+                .find("-->")
+                .map_or("", |end| &comment[end + "-->".len()..]);
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        let raw_tag = &rest[1..end];
+        rest = &rest[end + 1..];
+        let Some(tag) = Tag::parse(raw_tag) else {
+            continue;
+        };
+
+        if !tag.closing && BOILERPLATE_TAGS.contains(&tag.name.as_str()) {
+            if !tag.self_closing {
+                rest = skip_element(rest, &tag.name);
+            }
+            continue;
+        }
+
+        match (tag.name.as_str(), tag.closing) {
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", false) => {
+                start_block(&mut out);
+                let level = tag.name[1..].parse().unwrap_or(1);
+                out.push_str(&"#".repeat(level));
+                out.push(' ');
+            }
+            (
+                "p" | "div" | "section" | "article" | "main" | "table" | "ul" | "ol" | "dl"
+                | "figure" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6",
+                _,
+            ) => start_block(&mut out),
+            ("blockquote", false) => {
+                start_block(&mut out);
+                out.push_str("> ");
+            }
+            ("blockquote", true) => start_block(&mut out),
+            ("li", false) => {
+                start_line(&mut out);
+                out.push_str("- ");
+            }
+            ("tr" | "dt" | "dd", _) | ("br", false) => start_line(&mut out),
+            ("td" | "th", false) => out.push(' '),
+            ("hr", false) => {
+                start_block(&mut out);
+                out.push_str("---");
+                start_block(&mut out);
+            }
+            ("pre", false) => {
+                start_block(&mut out);
+                out.push_str("```\n");
+                pre_depth += 1;
+            }
+            ("pre", true) => {
+                pre_depth = pre_depth.saturating_sub(1);
+                start_line(&mut out);
+                out.push_str("```");
+                start_block(&mut out);
+            }
+            ("code", _) if pre_depth == 0 => out.push('`'),
+            ("strong" | "b", _) => out.push_str("**"),
+            ("em" | "i", _) => out.push('*'),
+            ("a", false) => {
+                let href = tag.attribute("href").filter(|href| !href.starts_with('#'));
+                if href.is_some() {
+                    out.push('[');
+                }
+                links.push(href);
+            }
+            ("a", true) => {
+                if let Some(Some(href)) = links.pop() {
+                    out.push_str(&format!("]({href})"));
+                }
+            }
+            ("img", false) => {
+                if let Some(alt) = tag.attribute("alt").filter(|alt| !alt.is_empty()) {
+                    let src = tag.attribute("src").unwrap_or_default();
+                    out.push_str(&format!("![{alt}]({src})"));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    tidy_markdown(&out)
+}
+
+/// Returns what follows the element `name` that `rest` is inside of.
+fn skip_element<'a>(rest: &'a str, name: &str) -> &'a str {
+    let closing_tag = format!("</{name}");
+    let Some(start) = rest.to_ascii_lowercase().find(&closing_tag) else {
+        return "";
+    };
+    let after = &rest[start..];
+    after.find('>').map_or("", |end| &after[end + 1..])
+}
+
+fn push_text(out: &mut String, text: &str, preformatted: bool) {
+    let text = decode_entities(text);
+    if preformatted {
+        out.push_str(&text);
+        return;
+    }
+    for ch in text.chars() {
+        if ch.is_whitespace() {
+            if !out.is_empty() && !out.ends_with([' ', '\n']) {
+                out.push(' ');
+            }
+        } else {
+            out.push(ch);
+        }
+    }
+}
+
+fn start_line(out: &mut String) {
+    out.truncate(out.trim_end_matches(' ').len());
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+fn start_block(out: &mut String) {
+    start_line(out);
+    if !out.is_empty() && !out.ends_with("\n\n") {
+        out.push('\n');
+    }
+}
+
+/// Trims trailing whitespace from every line and collapses runs of blank
+/// lines.
+fn tidy_markdown(markdown: &str) -> String {
+    let mut tidy = String::new();
+    let mut previous_blank = true;
+    for line in markdown.lines() {
+        let line = line.trim_end();
+        let blank = line.is_empty();
+        if blank && previous_blank {
+            continue;
+        }
+        tidy.push_str(line);
+        tidy.push('\n');
+        previous_blank = blank;
+    }
+    tidy.trim_end().to_string()
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest
+            .get(1..)
+            .and_then(|after| after.find(';').filter(|end| *end <= 10))
+            .and_then(|end| decode_entity(&rest[1..=end]).map(|ch| (ch, end + 2)));
+        match entity {
+            Some((ch, len)) => {
+                decoded.push(ch);
+                rest = &rest[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    let ch = match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        _ => {
+            let number = entity.strip_prefix('#')?;
+            let code = match number.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => number.parse().ok()?,
+            };
+            return char::from_u32(code);
+        }
+    };
+    Some(ch)
+}
+
+struct Tag<'a> {
+    /// Lowercase tag name.
+    name: String,
+    closing: bool,
+    self_closing: bool,
+    raw: &'a str,
+}
+
+impl<'a> Tag<'a> {
+    /// Returns `None` for doctypes and processing instructions.
+    fn parse(raw: &'a str) -> Option<Self> {
+        let raw = raw.trim();
+        if raw.starts_with(['!', '?']) {
+            return None;
+        }
+        let (closing, raw) = match raw.strip_prefix('/') {
+            Some(raw) => (true, raw),
+            None => (false, raw),
+        };
+        let name_len = raw
+            .find(|ch: char| ch.is_whitespace() || ch == '/')
+            .unwrap_or(raw.len());
+        let name = raw[..name_len].to_ascii_lowercase();
+        if name.is_empty() {
+            return None;
+        }
+        Some(Self {
+            name,
+            closing,
+            self_closing: raw.ends_with('/'),
+            raw: &raw[name_len..],
+        })
+    }
+
+    fn attribute(&self, name: &str) -> Option<String> {
+        let mut rest = self.raw;
+        loop {
+            rest = rest.trim_start_matches(|ch: char| ch.is_whitespace() || ch == '/');
+            if rest.is_empty() {
+                return None;
+            }
+            let key_len = rest
+                .find(|ch: char| ch.is_whitespace() || ch == '=' || ch == '/')
+                .unwrap_or(rest.len());
+            let key = &rest[..key_len];
+            rest = rest[key_len..].trim_start();
+            let value = match rest.strip_prefix('=') {
+                Some(after) => {
+                    let after = after.trim_start();
+                    let (value, remaining) = match after.chars().next() {
+                        Some(quote @ ('"' | '\'')) => {
+                            let inner = &after[1..];
+                            let end = inner.find(quote).unwrap_or(inner.len());
+                            (&inner[..end], inner.get(end + 1..).unwrap_or_default())
+                        }
+                        _ => {
+                            let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                            (&after[..end], &after[end..])
+                        }
+                    };
+                    rest = remaining;
+                    value
+                }
+                None => "",
+            };
+            if key.eq_ignore_ascii_case(name) {
+                return Some(decode_entities(value));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn converts_html_to_markdown_without_boilerplate() {
+        let html = r#"<!DOCTYPE html>
+<html>
+<head><title>Ignored</title><script>if (a < b) { run(); }</script></head>
+<body>
+  <nav><a href="/">Home</a></nav>
+  <h1>Release &amp; notes</h1>
+  <p>Read the <a href="https://example.com/docs">docs</a> for <strong>details</strong>.</p>
+  <ul><li>One</li><li>Two</li></ul>
+  <pre>let x = 1;
+let y = 2;</pre>
+  <footer>Copyright</footer>
+</body>
+</html>"#;
+
+        assert_eq!(
+            html_to_markdown(html),
+            "# Release & notes\n\nRead the [docs](https://example.com/docs) for **details**.\n\n- One\n- Two\n\n```\nlet x = 1;\nlet y = 2;\n```"
+        );
+    }
+
+    #[test]
+    fn allowed_domains_include_subdomains() {
+        let allowed = vec!["example.com".to_string()];
+        assert!(is_domain_allowed("example.com", Some(&allowed)));
+        assert!(is_domain_allowed("docs.example.com", Some(&allowed)));
+        assert!(!is_domain_allowed("badexample.com", Some(&allowed)));
+        assert!(!is_domain_allowed("example.org", Some(&allowed)));
+        assert!(is_domain_allowed("example.org", None));
+    }
+
+    #[test]
+    fn non_public_addresses_are_refused() {
+        for url in [
+            "http://127.0.0.1:8080/",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://10.1.2.3/",
+            "http://192.168.0.1/",
+            "http://100.64.0.1/",
+            "http://[::1]/",
+            "http://[fd00::1]/",
+            "http://[::ffff:127.0.0.1]/",
+        ] {
+            let url = Url::parse(url).expect("valid url");
+            assert!(!is_public_host(&url), "{url}");
+        }
+        for url in ["https://example.com/", "http://93.184.216.34/"] {
+            let url = Url::parse(url).expect("valid url");
+            assert!(is_public_host(&url), "{url}");
+        }
+    }
+
+    #[tokio::test]
+    async fn resolver_refuses_hosts_without_a_public_address() {
+        let name: Name = "localhost".parse().expect("valid name");
+        let Err(err) = PublicAddressResolver.resolve(name).await else {
+            panic!("localhost resolved to a public address");
+        };
+        assert!(err.to_string().contains("public address"), "{err}");
+    }
+
+    #[test]
+    fn robots_txt_prefers_specific_agent_and_longest_rule() {
+        let robots_txt = "\
+User-agent: *
+Disallow: /
+
+User-agent: Codex
+Disallow: /private
+Allow: /private/public
+Disallow: /*.pdf$
+";
+        assert!(robots_allows(robots_txt, "codex", "/docs"));
+        assert!(!robots_allows(robots_txt, "codex", "/private/keys"));
+        assert!(robots_allows(robots_txt, "codex", "/private/public/page"));
+        assert!(!robots_allows(robots_txt, "codex", "/files/report.pdf"));
+        assert!(robots_allows(robots_txt, "codex", "/files/report.pdf.html"));
+        assert!(!robots_allows(robots_txt, "other-bot", "/docs"));
+    }
+}
@@ -0,0 +1,163 @@
+//! Images handed back to the model as tool output content.
+//!
+//! With the `shell_image_output` feature, each shell command gets an empty
+//! directory in `CODEX_IMAGE_OUTPUT_DIR`. Image files the command leaves
+//! there, such as a screenshot taken by a headless browser, are attached to
+//! the command's output so the next request shows them to the model.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::FunctionCallOutputContentItem;
+use codex_protocol::models::ImageDetail;
+use codex_protocol::models::local_image_content_items_with_label_number;
+use codex_protocol::openai_models::InputModality;
+use codex_utils_image::PromptImageMode;
+use tempfile::TempDir;
+
+use crate::codex::TurnContext;
+use crate::features::Feature;
+
+pub(crate) const IMAGE_OUTPUT_DIR_ENV_VAR: &str = "CODEX_IMAGE_OUTPUT_DIR";
+
+/// Images beyond this many are reported by name instead of attached.
+const MAX_IMAGE_OUTPUTS: usize = 4;
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp"];
+
+/// Content items showing the image at `path` to the model, resized and
+/// detailed the way the turn's model expects.
+pub(crate) fn local_image_output_items(
+    path: &Path,
+    turn: &TurnContext,
+) -> Vec<FunctionCallOutputContentItem> {
+    let use_original_detail = turn.config.features.enabled(Feature::ImageDetailOriginal)
+        && turn.model_info.supports_image_detail_original;
+    let image_mode = if use_original_detail {
+        PromptImageMode::Original
+    } else {
+        PromptImageMode::ResizeToFit
+    };
+    let image_detail = use_original_detail.then_some(ImageDetail::Original);
+
+    local_image_content_items_with_label_number(path, None, image_mode)
+        .into_iter()
+        .map(|item| match item {
+            ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                FunctionCallOutputContentItem::InputText { text }
+            }
+            ContentItem::InputImage { image_url } => FunctionCallOutputContentItem::InputImage {
+                image_url,
+                detail: image_detail,
+            },
+        })
+        .collect()
+}
+
+/// The directory a shell command can write images to. It is deleted when
+/// dropped.
+pub(crate) struct ImageOutputDir {
+    dir: TempDir,
+}
+
+impl ImageOutputDir {
+    /// Returns `None` when the feature is off, the model does not accept
+    /// image input, or the directory cannot be created.
+    pub(crate) fn for_turn(turn: &TurnContext) -> Option<Self> {
+        if !turn.config.features.enabled(Feature::ShellImageOutput)
+            || !turn
+                .model_info
+                .input_modalities
+                .contains(&InputModality::Image)
+        {
+            return None;
+        }
+        match tempfile::Builder::new().prefix("codex-images-").tempdir() {
+            Ok(dir) => Some(Self { dir }),
+            Err(err) => {
+                tracing::warn!("failed to create image output directory: {err}");
+                None
+            }
+        }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Content items for the images the command left in the directory, in
+    /// file name order, each preceded by its name.
+    pub(crate) fn collect(self, turn: &TurnContext) -> Vec<FunctionCallOutputContentItem> {
+        let images = image_files(self.path());
+        let mut items = Vec::new();
+        for path in images.iter().take(MAX_IMAGE_OUTPUTS) {
+            items.push(FunctionCallOutputContentItem::InputText {
+                text: format!("Image output {}:", file_name(path)),
+            });
+            items.extend(local_image_output_items(path, turn));
+        }
+        if images.len() > MAX_IMAGE_OUTPUTS {
+            let skipped = images[MAX_IMAGE_OUTPUTS..]
+                .iter()
+                .map(|path| file_name(path))
+                .collect::<Vec<_>>()
+                .join(", ");
+            items.push(FunctionCallOutputContentItem::InputText {
+                text: format!(
+                    "Only the first {MAX_IMAGE_OUTPUTS} image outputs were attached; skipped: {skipped}"
+                ),
+            });
+        }
+        items
+    }
+}
+
+fn image_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut images = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|extension| extension.to_str())
+                    .is_some_and(|extension| {
+                        IMAGE_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+                    })
+        })
+        .collect::<Vec<_>>();
+    images.sort();
+    images
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn image_files_are_filtered_by_extension_and_sorted() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        for name in ["b.PNG", "a.jpg", "notes.txt", "c.webp"] {
+            std::fs::write(dir.path().join(name), b"").expect("write file");
+        }
+        std::fs::create_dir(dir.path().join("d.png")).expect("create dir");
+
+        let names = image_files(dir.path())
+            .iter()
+            .map(|path| file_name(path))
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["a.jpg", "b.PNG", "c.webp"]);
+    }
+}
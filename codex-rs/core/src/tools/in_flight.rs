@@ -0,0 +1,146 @@
+//! Bookkeeping for tool calls that are currently executing, and for calls
+//! the conversation history left without a result.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use codex_protocol::models::ResponseItem;
+
+/// Snapshot of a tool call that has been dispatched but has not finished.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InFlightToolCall {
+    pub call_id: String,
+    pub tool_name: String,
+    /// Wall-clock start time, in milliseconds since the Unix epoch.
+    pub started_at_ms: u64,
+    pub elapsed_ms: u64,
+}
+
+/// A tool call recorded in the conversation history with no output recorded
+/// for it, e.g. because the process exited while the call was running.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingToolCall {
+    pub call_id: String,
+    pub tool_name: String,
+    /// The call as the model emitted it, for re-dispatching through
+    /// [`ToolRouter::build_tool_call`].
+    ///
+    /// [`ToolRouter::build_tool_call`]: crate::tools::router::ToolRouter::build_tool_call
+    pub item: ResponseItem,
+}
+
+/// The tool calls in `items` that have no matching output, oldest first.
+pub(crate) fn pending_tool_calls(items: &[ResponseItem]) -> Vec<PendingToolCall> {
+    let answered: HashSet<&str> = items
+        .iter()
+        .filter_map(|item| match item {
+            ResponseItem::FunctionCallOutput { call_id, .. }
+            | ResponseItem::CustomToolCallOutput { call_id, .. } => Some(call_id.as_str()),
+            _ => None,
+        })
+        .collect();
+    items
+        .iter()
+        .filter_map(|item| {
+            let (call_id, tool_name) = match item {
+                ResponseItem::FunctionCall { name, call_id, .. }
+                | ResponseItem::CustomToolCall { name, call_id, .. }
+                | ResponseItem::CustomToolCallWithAttachments { name, call_id, .. } => {
+                    (call_id.as_str(), name.as_str())
+                }
+                ResponseItem::LocalShellCall { id, call_id, .. } => {
+                    (call_id.as_deref().or(id.as_deref())?, "local_shell")
+                }
+                _ => return None,
+            };
+            (!answered.contains(call_id)).then(|| PendingToolCall {
+                call_id: call_id.to_string(),
+                tool_name: tool_name.to_string(),
+                item: item.clone(),
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+struct ActiveCall {
+    tool_name: String,
+    started_at_ms: u64,
+    started: Instant,
+}
+
+/// Session-wide set of tool calls currently being dispatched.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ActiveToolCalls {
+    calls: Arc<Mutex<HashMap<String, ActiveCall>>>,
+}
+
+impl ActiveToolCalls {
+    /// Marks `call_id` as running until the returned guard is dropped, so
+    /// calls that are cancelled mid-flight are removed too.
+    pub(crate) fn start(&self, call_id: &str, tool_name: &str) -> ActiveToolCallGuard {
+        let started_at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX))
+            .unwrap_or_default();
+        self.lock().insert(
+            call_id.to_string(),
+            ActiveCall {
+                tool_name: tool_name.to_string(),
+                started_at_ms,
+                started: Instant::now(),
+            },
+        );
+        ActiveToolCallGuard {
+            calls: self.clone(),
+            call_id: call_id.to_string(),
+        }
+    }
+
+    /// Running calls, oldest first.
+    pub(crate) fn snapshot(&self) -> Vec<InFlightToolCall> {
+        let mut calls: Vec<InFlightToolCall> = self
+            .lock()
+            .iter()
+            .map(|(call_id, call)| InFlightToolCall {
+                call_id: call_id.clone(),
+                tool_name: call.tool_name.clone(),
+                started_at_ms: call.started_at_ms,
+                elapsed_ms: u64::try_from(call.started.elapsed().as_millis()).unwrap_or(u64::MAX),
+            })
+            .collect();
+        calls.sort_by(|a, b| {
+            a.started_at_ms
+                .cmp(&b.started_at_ms)
+                .then_with(|| a.call_id.cmp(&b.call_id))
+        });
+        calls
+    }
+
+    pub(crate) fn contains(&self, call_id: &str) -> bool {
+        self.lock().contains_key(call_id)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, ActiveCall>> {
+        self.calls
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+pub(crate) struct ActiveToolCallGuard {
+    calls: ActiveToolCalls,
+    call_id: String,
+}
+
+impl Drop for ActiveToolCallGuard {
+    fn drop(&mut self) {
+        self.calls.lock().remove(&self.call_id);
+    }
+}
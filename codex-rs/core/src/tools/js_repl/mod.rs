@@ -875,6 +875,8 @@ impl JsReplManager {
                     .features
                     .enabled(crate::features::Feature::UseLinuxSandboxBwrap),
                 windows_sandbox_level: turn.windows_sandbox_level,
+                backend: None,
+                sandbox_path_rules: &[],
             })
             .map_err(|err| format!("failed to configure sandbox for js_repl: {err}"))?;
 
@@ -1357,28 +1359,44 @@ impl JsReplManager {
             exec.turn.dynamic_tools.as_slice(),
         );
 
-        let payload =
-            if let Some((server, tool)) = exec.session.parse_mcp_tool_name(&req.tool_name).await {
-                crate::tools::context::ToolPayload::Mcp {
-                    server,
-                    tool,
-                    raw_arguments: req.arguments.clone(),
-                }
-            } else if is_freeform_tool(&router.specs(), &req.tool_name) {
-                crate::tools::context::ToolPayload::Custom {
-                    input: req.arguments.clone(),
-                }
-            } else {
-                crate::tools::context::ToolPayload::Function {
-                    arguments: req.arguments.clone(),
-                }
-            };
+        let mcp_tool = match exec.session.parse_mcp_tool_name(&req.tool_name).await {
+            Ok(mcp_tool) => mcp_tool,
+            Err(err) => {
+                let error = err.to_string();
+                let summary = Self::summarize_tool_call_error(&error);
+                Self::log_tool_call_response(&req, false, &summary, None, Some(&error));
+                return RunToolResult {
+                    id: req.id,
+                    ok: false,
+                    response: None,
+                    error: Some(error),
+                };
+            }
+        };
+        let payload = if let Some((server, tool)) = mcp_tool {
+            crate::tools::context::ToolPayload::Mcp {
+                server,
+                tool,
+                raw_arguments: req.arguments.clone(),
+            }
+        } else if is_freeform_tool(&router.specs(), &req.tool_name) {
+            crate::tools::context::ToolPayload::Custom {
+                input: req.arguments.clone(),
+                attachments: Vec::new(),
+            }
+        } else {
+            crate::tools::context::ToolPayload::Function {
+                arguments: req.arguments.clone(),
+            }
+        };
 
         let tool_name = req.tool_name.clone();
         let call = crate::tools::router::ToolCall {
             tool_name: tool_name.clone(),
             call_id: req.id.clone(),
             payload,
+            tags: HashMap::new(),
+            dry_run: false,
         };
 
         let session = Arc::clone(&exec.session);
@@ -0,0 +1,150 @@
+//! Session-level middleware that runs around every tool dispatch, whatever
+//! the tool. Register hooks with [`ToolRouter::register_before_all`] and
+//! [`ToolRouter::register_after_all`], and an interceptor with
+//! [`ToolRouter::set_dispatch_interceptor`]. A
+//! [`SandboxPermissionsHook`], set with
+//! [`ToolRouter::set_sandbox_permissions_hook`], runs for local shell calls
+//! only.
+//!
+//! Hooks registered on a session through [`SessionToolMiddleware`] are
+//! applied to the router of every sampling request in that session.
+//!
+//! [`ToolRouter::register_before_all`]: crate::tools::router::ToolRouter::register_before_all
+//! [`ToolRouter::register_after_all`]: crate::tools::router::ToolRouter::register_after_all
+//! [`ToolRouter::set_dispatch_interceptor`]: crate::tools::router::ToolRouter::set_dispatch_interceptor
+//! [`ToolRouter::set_sandbox_permissions_hook`]: crate::tools::router::ToolRouter::set_sandbox_permissions_hook
+
+use std::sync::Arc;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+use std::sync::RwLockReadGuard;
+
+use async_trait::async_trait;
+use codex_protocol::models::ResponseInputItem;
+use codex_protocol::models::SandboxPermissions;
+use codex_protocol::models::ShellToolCallParams;
+use futures::future::BoxFuture;
+
+use crate::function_tool::FunctionCallError;
+use crate::tools::audit::AuditHook;
+use crate::tools::router::ToolCall;
+use crate::tools::router::ToolCallSource;
+use crate::tools::router::ToolRouter;
+
+#[async_trait]
+pub trait BeforeAllHook: Send + Sync {
+    /// Runs before `call` is dispatched. Returning an error rejects the call:
+    /// the error is reported to the model as the call's output, or ends the
+    /// turn if it is [`FunctionCallError::Fatal`]. Later hooks do not run.
+    async fn before(
+        &self,
+        call: &ToolCall,
+        source: ToolCallSource,
+    ) -> Result<(), FunctionCallError>;
+}
+
+#[async_trait]
+pub trait AfterAllHook: Send + Sync {
+    /// Runs once `call` has finished, including calls rejected by a
+    /// [`BeforeAllHook`].
+    async fn after(&self, call: &ToolCall, result: &Result<ResponseInputItem, FunctionCallError>);
+}
+
+/// The router's own dispatch of a call, handed to a [`DispatchInterceptor`].
+pub type BoxedDispatchFn<'a> = Box<
+    dyn FnOnce(ToolCall) -> BoxFuture<'a, Result<ResponseInputItem, FunctionCallError>> + Send + 'a,
+>;
+
+#[async_trait]
+pub trait DispatchInterceptor: Send + Sync {
+    /// Handles `call` in place of the router's dispatch, once the
+    /// [`BeforeAllHook`]s have accepted it. Call `real_dispatch`, with `call`
+    /// or a modified copy, to pass it through; or return a result without
+    /// calling it to simulate the tool. The result is archived and seen by
+    /// [`AfterAllHook`]s as if the tool had produced it.
+    async fn intercept(
+        &self,
+        call: ToolCall,
+        real_dispatch: BoxedDispatchFn<'_>,
+    ) -> Result<ResponseInputItem, FunctionCallError>;
+}
+
+#[async_trait]
+pub trait SandboxPermissionsHook: Send + Sync {
+    /// Returns the sandbox permissions a local shell call runs with, given
+    /// its resolved `params`; `params.sandbox_permissions` holds what the call
+    /// asked for. Returning an error vetoes the command: the error is
+    /// reported to the model as the call's output, or ends the turn if it is
+    /// [`FunctionCallError::Fatal`].
+    async fn resolve(
+        &self,
+        params: &ShellToolCallParams,
+    ) -> Result<SandboxPermissions, FunctionCallError>;
+}
+
+/// Middleware registered for a whole session. Cloning shares the
+/// registrations; see [`SessionToolMiddleware::apply_to`].
+#[derive(Clone, Default)]
+pub(crate) struct SessionToolMiddleware {
+    state: Arc<RwLock<SessionToolMiddlewareState>>,
+}
+
+#[derive(Default)]
+struct SessionToolMiddlewareState {
+    before_all: Vec<Arc<dyn BeforeAllHook>>,
+    after_all: Vec<Arc<dyn AfterAllHook>>,
+    dispatch_interceptor: Option<Arc<dyn DispatchInterceptor>>,
+    audit_hooks: Vec<Arc<dyn AuditHook>>,
+    sandbox_permissions_hook: Option<Arc<dyn SandboxPermissionsHook>>,
+}
+
+impl SessionToolMiddleware {
+    pub(crate) fn register_before_all(&self, hook: Arc<dyn BeforeAllHook>) {
+        self.write_state(|state| state.before_all.push(hook));
+    }
+
+    pub(crate) fn register_after_all(&self, hook: Arc<dyn AfterAllHook>) {
+        self.write_state(|state| state.after_all.push(hook));
+    }
+
+    pub(crate) fn register_audit_hook(&self, hook: Arc<dyn AuditHook>) {
+        self.write_state(|state| state.audit_hooks.push(hook));
+    }
+
+    pub(crate) fn set_dispatch_interceptor(&self, interceptor: Arc<dyn DispatchInterceptor>) {
+        self.write_state(|state| state.dispatch_interceptor = Some(interceptor));
+    }
+
+    pub(crate) fn set_sandbox_permissions_hook(&self, hook: Arc<dyn SandboxPermissionsHook>) {
+        self.write_state(|state| state.sandbox_permissions_hook = Some(hook));
+    }
+
+    /// Registers everything on `router`. Routers already built keep the
+    /// middleware they were built with.
+    pub(crate) fn apply_to(&self, router: &mut ToolRouter) {
+        let state = self.read_state();
+        for hook in &state.before_all {
+            router.register_before_all(Arc::clone(hook));
+        }
+        for hook in &state.after_all {
+            router.register_after_all(Arc::clone(hook));
+        }
+        for hook in &state.audit_hooks {
+            router.register_audit_hook(Arc::clone(hook));
+        }
+        if let Some(interceptor) = &state.dispatch_interceptor {
+            router.set_dispatch_interceptor(Arc::clone(interceptor));
+        }
+        if let Some(hook) = &state.sandbox_permissions_hook {
+            router.set_sandbox_permissions_hook(Arc::clone(hook));
+        }
+    }
+
+    fn read_state(&self) -> RwLockReadGuard<'_, SessionToolMiddlewareState> {
+        self.state.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_state(&self, update: impl FnOnce(&mut SessionToolMiddlewareState)) {
+        update(&mut self.state.write().unwrap_or_else(PoisonError::into_inner));
+    }
+}
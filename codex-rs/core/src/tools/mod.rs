@@ -1,15 +1,51 @@
+pub mod audit;
+pub(crate) mod call_dedupe;
+pub mod call_events;
+pub mod chaos;
+pub mod compose;
+pub mod config_file;
 pub mod context;
+pub(crate) mod curl;
+pub mod diagnostics;
+pub mod discovery;
+pub mod dispatch_log;
+#[cfg(feature = "dynamic-plugins")]
+pub mod dynamic_plugins;
+pub(crate) mod emulation;
 pub mod events;
+pub mod governance;
+#[cfg(feature = "graphql-tools")]
+pub mod graphql_introspection;
 pub(crate) mod handlers;
+pub(crate) mod image_output;
+pub(crate) mod in_flight;
 pub mod js_repl;
+pub mod middleware;
 pub(crate) mod network_approval;
+pub mod observability;
 pub mod orchestrator;
+pub mod output_cache;
+pub(crate) mod output_policy;
+pub mod output_transform;
+pub(crate) mod overrides;
 pub mod parallel;
+#[cfg(feature = "proto-config")]
+pub mod proto_config;
+pub mod quota;
 pub mod registry;
+pub mod resilience;
 pub mod router;
 pub mod runtimes;
 pub mod sandboxing;
+pub(crate) mod schema_drift;
+pub(crate) mod secret_redaction;
 pub mod spec;
+pub(crate) mod stream_output;
+pub mod tool_middleware;
+pub(crate) mod usage_summary;
+pub mod validation;
+#[cfg(feature = "wasm-plugins")]
+pub mod wasm_plugins;
 
 use crate::exec::ExecToolCallOutput;
 use crate::truncate::TruncationPolicy;
@@ -0,0 +1,138 @@
+//! The signals emitted around a tool dispatch: a `tool_dispatch` span, the
+//! `codex.tool.duration_ms` histogram, and a structured dispatch log event.
+//!
+//! The span is a child of the `model_request` span that produced the call,
+//! which is itself a child of the `turn` span, so an exported trace shows
+//! where a turn spent its time. Sandboxed runtimes add `sandbox_attempt`
+//! children below it.
+//!
+//! See [`ToolRegistry::dispatch_with_observability`].
+//!
+//! [`ToolRegistry::dispatch_with_observability`]: crate::tools::registry::ToolRegistry::dispatch_with_observability
+
+use std::future::Future;
+use std::time::Instant;
+
+use codex_otel::SessionTelemetry;
+use codex_protocol::ThreadId;
+use codex_protocol::models::ResponseInputItem;
+use tracing::Dispatch;
+use tracing::Instrument;
+use tracing::field;
+
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolPayload;
+use crate::tools::dispatch_log;
+
+const TOOL_DURATION_METRIC: &str = "codex.tool.duration_ms";
+
+/// Where the signals of a dispatch are sent.
+#[derive(Clone)]
+pub struct Observability {
+    /// Records the `codex.tool.duration_ms` histogram.
+    pub telemetry: SessionTelemetry,
+    /// Subscriber that receives the `tool_dispatch` span.
+    pub tracing: Dispatch,
+    /// Subscriber that receives the structured dispatch event, e.g. one with
+    /// a [`dispatch_log::structured_json_layer`]. No event is emitted when
+    /// unset.
+    pub log_sink: Option<Dispatch>,
+}
+
+impl Observability {
+    /// Sends spans to the current default subscriber and emits no
+    /// structured log events.
+    pub fn new(telemetry: SessionTelemetry) -> Self {
+        Self {
+            telemetry,
+            tracing: tracing::dispatcher::get_default(Dispatch::clone),
+            log_sink: None,
+        }
+    }
+
+    pub fn with_tracing(mut self, tracing: Dispatch) -> Self {
+        self.tracing = tracing;
+        self
+    }
+
+    pub fn with_log_sink(mut self, log_sink: Dispatch) -> Self {
+        self.log_sink = Some(log_sink);
+        self
+    }
+
+    /// Runs `dispatch` for `call` and emits all three signals for its result,
+    /// failures included.
+    pub(crate) async fn observe<F>(
+        &self,
+        call: &ObservedCall,
+        dispatch: F,
+    ) -> Result<ResponseInputItem, FunctionCallError>
+    where
+        F: Future<Output = Result<ResponseInputItem, FunctionCallError>>,
+    {
+        let span = tracing::dispatcher::with_default(&self.tracing, || {
+            tracing::info_span!(
+                "tool_dispatch",
+                tool_name = %call.tool_name,
+                call_id = %call.call_id,
+                server = call.server.as_deref().unwrap_or_default(),
+                outcome = field::Empty,
+                duration_ms = field::Empty,
+            )
+        });
+        let started = Instant::now();
+        let result = dispatch.instrument(span.clone()).await;
+        let duration = started.elapsed();
+
+        let outcome = match &result {
+            Ok(_) => "success",
+            Err(FunctionCallError::Fatal(_)) => "fatal",
+            Err(_) => "failure",
+        };
+        span.record("outcome", outcome);
+        span.record("duration_ms", duration.as_secs_f64() * 1000.0);
+        let mut tags = vec![("tool_name", call.tool_name.as_str()), ("outcome", outcome)];
+        if let Some(server) = call.server.as_deref() {
+            tags.push(("server", server));
+        }
+        self.telemetry
+            .record_duration(TOOL_DURATION_METRIC, duration, &tags);
+
+        if let Some(log_sink) = &self.log_sink {
+            tracing::dispatcher::with_default(log_sink, || {
+                dispatch_log::log_dispatch(
+                    &call.call_id,
+                    &call.tool_name,
+                    call.session_id,
+                    duration,
+                    &result,
+                );
+            });
+        }
+        result
+    }
+}
+
+/// What the signals of one dispatch are tagged with.
+pub(crate) struct ObservedCall {
+    pub(crate) call_id: String,
+    pub(crate) tool_name: String,
+    pub(crate) session_id: ThreadId,
+    /// MCP server, for MCP calls.
+    pub(crate) server: Option<String>,
+}
+
+impl ObservedCall {
+    pub(crate) fn for_invocation(invocation: &ToolInvocation) -> Self {
+        Self {
+            call_id: invocation.call_id.clone(),
+            tool_name: invocation.tool_name.clone(),
+            session_id: invocation.session.conversation_id,
+            server: match &invocation.payload {
+                ToolPayload::Mcp { server, .. } => Some(server.clone()),
+                _ => None,
+            },
+        }
+    }
+}
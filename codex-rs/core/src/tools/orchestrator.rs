@@ -4,8 +4,11 @@ Module: orchestrator
 Central place for approvals + sandbox selection + retry semantics. Drives a
 simple sequence for any ToolRuntime: approval → select sandbox → attempt →
 retry with an escalated sandbox strategy on denial (no re‑approval thanks to
-caching).
+caching). Each attempt runs in a `sandbox_attempt` span recording the sandbox
+used, whether it denied the command, and the exit code. Approval decisions and
+attempts also go to the audit log when `[audit]` is enabled.
 */
+use crate::audit::AuditEvent;
 use crate::error::CodexErr;
 use crate::error::SandboxErr;
 use crate::exec::ExecToolCallOutput;
@@ -14,6 +17,8 @@ use crate::guardian::GUARDIAN_REJECTION_MESSAGE;
 use crate::guardian::routes_approval_to_guardian;
 use crate::network_policy_decision::network_approval_context_from_payload;
 use crate::sandboxing::SandboxManager;
+use crate::sandboxing::backend::ContainerSandbox;
+use crate::sandboxing::backend::SandboxBackend;
 use crate::tools::network_approval::DeferredNetworkApproval;
 use crate::tools::network_approval::NetworkApprovalMode;
 use crate::tools::network_approval::begin_network_approval;
@@ -31,6 +36,10 @@ use codex_otel::ToolDecisionSource;
 use codex_protocol::protocol::AskForApproval;
 use codex_protocol::protocol::NetworkPolicyRuleAction;
 use codex_protocol::protocol::ReviewDecision;
+use tracing::Instrument;
+use tracing::Span;
+use tracing::field;
+use tracing::info_span;
 
 pub(crate) struct ToolOrchestrator {
     sandbox: SandboxManager,
@@ -48,6 +57,44 @@ impl ToolOrchestrator {
         }
     }
 
+    async fn record_attempt<Rq, Out, T>(
+        span: &Span,
+        tool: &T,
+        tool_ctx: &ToolCtx,
+        attempt: &SandboxAttempt<'_>,
+        result: &Result<Out, ToolError>,
+    ) where
+        T: ToolRuntime<Rq, Out>,
+    {
+        let mut denied = false;
+        let exit_code = match result {
+            Ok(out) => tool.exit_code(out),
+            Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Denied { output, .. }))) => {
+                span.record("denied", true);
+                denied = true;
+                Some(output.exit_code)
+            }
+            Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Timeout { output }))) => {
+                Some(output.exit_code)
+            }
+            Err(_) => None,
+        };
+        if let Some(exit_code) = exit_code {
+            span.record("exit_code", exit_code);
+        }
+        audit(
+            tool_ctx,
+            AuditEvent::SandboxAttempt {
+                call_id: tool_ctx.call_id.clone(),
+                tool: tool_ctx.tool_name.clone(),
+                sandbox: attempt.sandbox.as_metric_tag().to_string(),
+                denied,
+                exit_code,
+            },
+        )
+        .await;
+    }
+
     async fn run_attempt<Rq, Out, T>(
         tool: &mut T,
         req: &Rq,
@@ -73,7 +120,17 @@ impl ToolOrchestrator {
             call_id: tool_ctx.call_id.clone(),
             tool_name: tool_ctx.tool_name.clone(),
         };
-        let run_result = tool.run(req, attempt, &attempt_tool_ctx).await;
+        let attempt_span = info_span!(
+            "sandbox_attempt",
+            sandbox = attempt.sandbox.as_metric_tag(),
+            denied = false,
+            exit_code = field::Empty,
+        );
+        let run_result = tool
+            .run(req, attempt, &attempt_tool_ctx)
+            .instrument(attempt_span.clone())
+            .await;
+        Self::record_attempt(&attempt_span, tool, tool_ctx, attempt, &run_result).await;
 
         let Some(network_approval) = network_approval else {
             return (run_result, None);
@@ -125,8 +182,10 @@ impl ToolOrchestrator {
         match requirement {
             ExecApprovalRequirement::Skip { .. } => {
                 otel.tool_decision(otel_tn, otel_ci, &ReviewDecision::Approved, otel_cfg);
+                audit_approval(tool_ctx, "auto_approved").await;
             }
             ExecApprovalRequirement::Forbidden { reason } => {
+                audit_approval(tool_ctx, "forbidden").await;
                 return Err(ToolError::Rejected(reason));
             }
             ExecApprovalRequirement::NeedsApproval { reason, .. } => {
@@ -140,6 +199,7 @@ impl ToolOrchestrator {
                 let decision = tool.start_approval_async(req, approval_ctx).await;
 
                 otel.tool_decision(otel_tn, otel_ci, &decision, otel_user.clone());
+                audit_approval(tool_ctx, decision.to_opaque_string()).await;
 
                 match decision {
                     ReviewDecision::Denied | ReviewDecision::Abort => {
@@ -187,6 +247,7 @@ impl ToolOrchestrator {
         // Platform-specific flag gating is handled by SandboxManager::select_initial
         // via crate::safety::get_platform_sandbox(..).
         let use_linux_sandbox_bwrap = turn_ctx.features.enabled(Feature::UseLinuxSandboxBwrap);
+        let container_sandbox = ContainerSandbox::from_config(&turn_ctx.config.sandbox_backend);
         let initial_attempt = SandboxAttempt {
             sandbox: initial_sandbox,
             policy: &turn_ctx.sandbox_policy,
@@ -198,6 +259,10 @@ impl ToolOrchestrator {
             codex_linux_sandbox_exe: turn_ctx.codex_linux_sandbox_exe.as_ref(),
             use_linux_sandbox_bwrap,
             windows_sandbox_level: turn_ctx.windows_sandbox_level,
+            backend: container_sandbox
+                .as_ref()
+                .map(|backend| backend as &dyn SandboxBackend),
+            sandbox_path_rules: &turn_ctx.config.sandbox_rules,
         };
 
         let (first_result, first_deferred_network_approval) = Self::run_attempt(
@@ -285,6 +350,7 @@ impl ToolOrchestrator {
 
                     let decision = tool.start_approval_async(req, approval_ctx).await;
                     otel.tool_decision(otel_tn, otel_ci, &decision, otel_user);
+                    audit_approval(tool_ctx, decision.to_opaque_string()).await;
 
                     match decision {
                         ReviewDecision::Denied | ReviewDecision::Abort => {
@@ -320,6 +386,8 @@ impl ToolOrchestrator {
                     codex_linux_sandbox_exe: None,
                     use_linux_sandbox_bwrap,
                     windows_sandbox_level: turn_ctx.windows_sandbox_level,
+                    backend: None,
+                    sandbox_path_rules: &[],
                 };
 
                 // Second attempt.
@@ -341,6 +409,24 @@ impl ToolOrchestrator {
     }
 }
 
+async fn audit_approval(tool_ctx: &ToolCtx, decision: &str) {
+    audit(
+        tool_ctx,
+        AuditEvent::Approval {
+            call_id: tool_ctx.call_id.clone(),
+            tool: tool_ctx.tool_name.clone(),
+            decision: decision.to_string(),
+        },
+    )
+    .await;
+}
+
+async fn audit(tool_ctx: &ToolCtx, event: AuditEvent) {
+    if let Some(audit_log) = tool_ctx.session.services.audit_log.as_ref() {
+        audit_log.record(&tool_ctx.turn.sub_id, event).await;
+    }
+}
+
 fn build_denial_reason_from_output(_output: &ExecToolCallOutput) -> String {
     // Keep approval reason terse and stable for UX/tests, but accept the
     // output so we can evolve heuristics later without touching call sites.
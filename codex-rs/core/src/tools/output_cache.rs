@@ -0,0 +1,221 @@
+//! Reuse of tool output across identical read-only calls.
+//!
+//! Only tools declared [cacheable](crate::tools::registry::ConfiguredToolSpec::cacheable)
+//! are cached. Entries are keyed on the tool name, its arguments with object
+//! keys sorted, and a fingerprint of the workspace, so a call made after the
+//! checkout changed misses. See [`CodexThread::with_tool_output_cache`].
+//!
+//! [`CodexThread::with_tool_output_cache`]: crate::CodexThread::with_tool_output_cache
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+use std::time::UNIX_EPOCH;
+
+use serde_json::Value as JsonValue;
+use serde_json::json;
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ToolOutputCacheKey {
+    pub tool_name: String,
+    /// The call's arguments, normalized so that calls differing only in key
+    /// order or whitespace share an entry.
+    pub arguments: String,
+    /// See [`workspace_fingerprint`].
+    pub workspace_fingerprint: String,
+}
+
+impl ToolOutputCacheKey {
+    /// Returns `None` for local shell calls, whose arguments do not capture
+    /// everything that affects their output.
+    pub(crate) fn for_call(
+        tool_name: &str,
+        payload: &ToolPayload,
+        workspace_fingerprint: String,
+    ) -> Option<Self> {
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => normalize_arguments(arguments),
+            ToolPayload::Custom { input, .. } => input.clone(),
+            ToolPayload::Mcp { raw_arguments, .. } => normalize_arguments(raw_arguments),
+            ToolPayload::GraphQL {
+                endpoint,
+                query,
+                variables,
+                ..
+            } => json!({
+                "endpoint": endpoint,
+                "query": query,
+                "variables": variables,
+            })
+            .to_string(),
+            ToolPayload::LocalShell { .. } => return None,
+        };
+        Some(Self {
+            tool_name: tool_name.to_string(),
+            arguments,
+            workspace_fingerprint,
+        })
+    }
+}
+
+/// Re-serializes JSON `arguments` with object keys sorted. Arguments that are
+/// not JSON are used as is.
+fn normalize_arguments(arguments: &str) -> String {
+    match serde_json::from_str::<JsonValue>(arguments) {
+        Ok(value) => sort_object_keys(value).to_string(),
+        Err(_) => arguments.to_string(),
+    }
+}
+
+fn sort_object_keys(value: JsonValue) -> JsonValue {
+    match value {
+        JsonValue::Object(map) => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            JsonValue::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sort_object_keys(value)))
+                    .collect(),
+            )
+        }
+        JsonValue::Array(items) => {
+            JsonValue::Array(items.into_iter().map(sort_object_keys).collect())
+        }
+        other => other,
+    }
+}
+
+/// Cheap digest of the workspace at `cwd`: the directory itself plus the
+/// modification times of its git `HEAD` and index, which change on checkout,
+/// commit and staging. Edits made outside of tool calls to unstaged files are
+/// not noticed; mutating tool calls clear the cache instead, and shell tools,
+/// which may read any file, are not cacheable.
+pub(crate) async fn workspace_fingerprint(cwd: &Path) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(cwd.as_os_str().as_encoded_bytes());
+    let mut tracked = vec![cwd.to_path_buf()];
+    for dir in cwd.ancestors() {
+        let git_dir = dir.join(".git");
+        if tokio::fs::metadata(&git_dir)
+            .await
+            .is_ok_and(|metadata| metadata.is_dir())
+        {
+            tracked.push(git_dir.join("HEAD"));
+            tracked.push(git_dir.join("index"));
+            break;
+        }
+    }
+    for path in tracked {
+        hasher.update([0]);
+        let Ok(metadata) = tokio::fs::metadata(&path).await else {
+            continue;
+        };
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        hasher.update(modified.as_nanos().to_le_bytes());
+        hasher.update(metadata.len().to_le_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+#[derive(Clone)]
+pub struct CachedToolOutput {
+    pub output: ToolOutput,
+    pub stored_at: Instant,
+}
+
+/// Storage for [`CachedToolOutput`]s. Only successful outputs of read-only
+/// calls are stored.
+pub trait ToolOutputCache: Send + Sync {
+    fn get(&self, key: &ToolOutputCacheKey) -> Option<CachedToolOutput>;
+
+    fn insert(&self, key: ToolOutputCacheKey, output: CachedToolOutput);
+
+    /// Called after a mutating call, which may have changed what any cached
+    /// call would now return.
+    fn clear(&self);
+}
+
+/// Unbounded cache held in memory for the life of the session.
+#[derive(Default)]
+pub struct InMemoryToolOutputCache {
+    entries: Mutex<HashMap<ToolOutputCacheKey, CachedToolOutput>>,
+}
+
+impl InMemoryToolOutputCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<ToolOutputCacheKey, CachedToolOutput>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl ToolOutputCache for InMemoryToolOutputCache {
+    fn get(&self, key: &ToolOutputCacheKey) -> Option<CachedToolOutput> {
+        self.entries().get(key).cloned()
+    }
+
+    fn insert(&self, key: ToolOutputCacheKey, output: CachedToolOutput) {
+        self.entries().insert(key, output);
+    }
+
+    fn clear(&self) {
+        self.entries().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn key_ignores_argument_order_but_not_workspace() {
+        let key = |arguments: &str, fingerprint: &str| {
+            ToolOutputCacheKey::for_call(
+                "read_file",
+                &ToolPayload::Function {
+                    arguments: arguments.to_string(),
+                },
+                fingerprint.to_string(),
+            )
+        };
+
+        assert_eq!(
+            key(r#"{"file_path": "a.rs", "limit": {"b": 1, "a": 2}}"#, "one"),
+            key(r#"{"limit":{"a":2,"b":1},"file_path":"a.rs"}"#, "one")
+        );
+        assert_ne!(
+            key(r#"{"file_path": "a.rs"}"#, "one"),
+            key(r#"{"file_path": "a.rs"}"#, "two")
+        );
+    }
+
+    #[tokio::test]
+    async fn workspace_fingerprint_changes_with_git_head() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let git_dir = dir.path().join(".git");
+        std::fs::create_dir(&git_dir)?;
+        std::fs::write(git_dir.join("HEAD"), "ref: refs/heads/main\n")?;
+        let before = workspace_fingerprint(dir.path()).await;
+        assert_eq!(before, workspace_fingerprint(dir.path()).await);
+
+        std::fs::write(git_dir.join("HEAD"), "ref: refs/heads/feature-branch\n")?;
+        assert_ne!(before, workspace_fingerprint(dir.path()).await);
+        Ok(())
+    }
+}
@@ -0,0 +1,387 @@
+//! Limits for oversized tool results, from `[tool_output]`.
+//!
+//! Without that table, exec tools cut their output to the model's tool
+//! output limit, keeping equal parts of its start and end, and every result
+//! is cut to that limit again when it is recorded. With it, exec tools hand
+//! over their full output and the registry cuts each result to its own
+//! tool's limit, keeping the configured share of its start. The full text can
+//! be saved to disk first so the model can read the rest, and very large
+//! results can be summarized by a smaller model instead of cut.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::ThreadId;
+use codex_protocol::config_types::ReasoningSummary as ReasoningSummaryConfig;
+use codex_protocol::models::BaseInstructions;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use futures::prelude::*;
+use tracing::warn;
+
+use crate::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::compact::content_items_to_text;
+use crate::config::types::ToolOutputConfig;
+use crate::error::CodexErr;
+use crate::error::Result as CodexResult;
+use crate::truncate::TruncationPolicy;
+use crate::truncate::approx_token_count;
+use crate::truncate::truncate_text_with_head_ratio;
+
+pub(crate) const DEFAULT_SUMMARY_MODEL: &str = "gpt-5.1-codex-mini";
+const DEFAULT_HEAD_RATIO: f64 = 0.5;
+const TOOL_OUTPUTS_SUBDIR: &str = "tool_outputs";
+
+/// Most tokens of a result sent to the summary model.
+const MAX_SUMMARY_INPUT_TOKENS: usize = 100_000;
+
+const SUMMARY_INSTRUCTIONS: &str = "You summarize the output of a tool call for a coding agent that cannot read all of it. Keep every error, failing test, warning, file path and line number exactly as written, along with the final status or exit code. Drop progress lines, passing tests and repeated output. Reply with the summary only.";
+
+/// Whether `[tool_output]` sets anything, in which case the registry applies
+/// [`ToolOutputPolicy`] instead of the fixed truncation.
+pub(crate) fn is_configured(config: &ToolOutputConfig) -> bool {
+    *config != ToolOutputConfig::default()
+}
+
+/// Budget for tool results recorded into history. Starts from `default`, the
+/// model's limit, and grows to fit the largest `[tool_output]` limit, so that
+/// results already cut to a larger per-tool limit are not cut again.
+pub(crate) fn history_truncation_policy(
+    config: &ToolOutputConfig,
+    default: TruncationPolicy,
+) -> TruncationPolicy {
+    if !is_configured(config) {
+        return default;
+    }
+    let global = configured_limit(config.max_tokens, config.max_bytes).unwrap_or(default);
+    let largest = config
+        .tools
+        .values()
+        .filter_map(|limits| configured_limit(limits.max_tokens, limits.max_bytes))
+        .map(|limit| limit.token_budget())
+        .max()
+        .unwrap_or(0);
+    if largest > global.token_budget() {
+        TruncationPolicy::Tokens(largest)
+    } else {
+        global
+    }
+}
+
+fn configured_limit(
+    max_tokens: Option<usize>,
+    max_bytes: Option<usize>,
+) -> Option<TruncationPolicy> {
+    max_bytes
+        .map(TruncationPolicy::Bytes)
+        .or(max_tokens.map(TruncationPolicy::Tokens))
+}
+
+/// How one tool's oversized results are cut down.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ToolOutputPolicy {
+    limit: TruncationPolicy,
+    head_ratio: f64,
+    archive_dir: Option<PathBuf>,
+    summarize_over_tokens: Option<usize>,
+    summary_model: String,
+}
+
+impl ToolOutputPolicy {
+    /// The policy for `tool_name` in `turn`, or `None` when `[tool_output]`
+    /// is not configured.
+    pub(crate) fn for_tool(
+        turn: &TurnContext,
+        conversation_id: ThreadId,
+        tool_name: &str,
+    ) -> Option<Self> {
+        let archive_dir = turn
+            .config
+            .codex_home
+            .join(TOOL_OUTPUTS_SUBDIR)
+            .join(conversation_id.to_string());
+        Self::from_config(
+            &turn.config.tool_output,
+            tool_name,
+            turn.truncation_policy,
+            archive_dir,
+        )
+    }
+
+    fn from_config(
+        config: &ToolOutputConfig,
+        tool_name: &str,
+        default: TruncationPolicy,
+        archive_dir: PathBuf,
+    ) -> Option<Self> {
+        if !is_configured(config) {
+            return None;
+        }
+        let tool = config.tools.get(tool_name);
+        let limit = tool
+            .and_then(|limits| configured_limit(limits.max_tokens, limits.max_bytes))
+            .or_else(|| configured_limit(config.max_tokens, config.max_bytes))
+            .unwrap_or(default);
+        let head_ratio = tool
+            .and_then(|limits| limits.head_ratio)
+            .or(config.head_ratio)
+            .unwrap_or(DEFAULT_HEAD_RATIO)
+            .clamp(0.0, 1.0);
+        Some(Self {
+            limit,
+            head_ratio,
+            archive_dir: config.archive.unwrap_or(false).then_some(archive_dir),
+            summarize_over_tokens: config.summarize_over_tokens,
+            summary_model: config
+                .summary_model
+                .clone()
+                .unwrap_or_else(|| DEFAULT_SUMMARY_MODEL.to_string()),
+        })
+    }
+
+    /// Returns `text` unchanged when it fits the limit. Otherwise saves it
+    /// when archiving is on, then summarizes it when it is over the summary
+    /// threshold, or cuts it down to the limit. A failed summary falls back
+    /// to cutting.
+    pub(crate) async fn apply(
+        &self,
+        session: &Session,
+        turn: &TurnContext,
+        call_id: &str,
+        text: String,
+    ) -> String {
+        if text.len() <= self.limit.byte_budget() {
+            return text;
+        }
+        let archived = match &self.archive_dir {
+            Some(dir) => archive_output(dir, call_id, &text).await,
+            None => None,
+        };
+        if let Some(threshold) = self.summarize_over_tokens
+            && approx_token_count(&text) > threshold
+        {
+            match self.summarize(session, turn, &text).await {
+                Ok(summary) if !summary.trim().is_empty() => {
+                    return format!(
+                        "{}\nSummary by {}:\n\n{}",
+                        header(&text, archived.as_deref()),
+                        self.summary_model,
+                        summary.trim()
+                    );
+                }
+                Ok(_) => warn!(call_id, "tool output summary was empty; cutting instead"),
+                Err(err) => {
+                    warn!(call_id, error = %err, "failed to summarize tool output; cutting instead");
+                }
+            }
+        }
+        self.cut(&text, archived.as_deref())
+    }
+
+    /// Cuts `text` down to the limit, keeping `head_ratio` of it from the
+    /// start, under a header with its line count and archive path.
+    fn cut(&self, text: &str, archived: Option<&Path>) -> String {
+        format!(
+            "{}\n\n{}",
+            header(text, archived),
+            truncate_text_with_head_ratio(text, self.limit, self.head_ratio)
+        )
+    }
+
+    async fn summarize(
+        &self,
+        session: &Session,
+        turn: &TurnContext,
+        text: &str,
+    ) -> CodexResult<String> {
+        let model_info = session
+            .services
+            .models_manager
+            .get_model_info(&self.summary_model, &turn.config)
+            .await;
+        let input = truncate_text_with_head_ratio(
+            text,
+            TruncationPolicy::Tokens(MAX_SUMMARY_INPUT_TOKENS),
+            self.head_ratio,
+        );
+        let prompt = Prompt {
+            input: vec![ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText { text: input }],
+                end_turn: None,
+                phase: None,
+            }],
+            base_instructions: BaseInstructions {
+                text: SUMMARY_INSTRUCTIONS.to_string(),
+            },
+            ..Default::default()
+        };
+        let mut client_session = session.services.model_client.new_session();
+        let mut stream = client_session
+            .stream(
+                &prompt,
+                &model_info,
+                &turn.session_telemetry,
+                None,
+                ReasoningSummaryConfig::None,
+                turn.config.service_tier,
+                None,
+            )
+            .await?;
+        let mut summary = String::new();
+        loop {
+            let Some(event) = stream.next().await else {
+                return Err(CodexErr::Stream(
+                    "stream closed before response.completed".into(),
+                    None,
+                ));
+            };
+            match event? {
+                ResponseEvent::OutputItemDone(ResponseItem::Message { role, content, .. })
+                    if role == "assistant" =>
+                {
+                    summary = content_items_to_text(&content).unwrap_or_default();
+                }
+                ResponseEvent::Completed { .. } => return Ok(summary),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn header(text: &str, archived: Option<&Path>) -> String {
+    let total_lines = text.lines().count();
+    match archived {
+        Some(path) => format!(
+            "Total output lines: {total_lines}\nFull output saved to {}",
+            path.display()
+        ),
+        None => format!("Total output lines: {total_lines}"),
+    }
+}
+
+/// Writes `text` to `<dir>/<call_id>.txt`. Failures are logged and leave the
+/// result without an archive path.
+async fn archive_output(dir: &Path, call_id: &str, text: &str) -> Option<PathBuf> {
+    let file_name: String = call_id
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let path = dir.join(format!("{file_name}.txt"));
+    let result = async {
+        tokio::fs::create_dir_all(dir).await?;
+        tokio::fs::write(&path, text).await
+    }
+    .await;
+    match result {
+        Ok(()) => Some(path),
+        Err(err) => {
+            warn!(path = %path.display(), error = %err, "failed to archive tool output");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::types::ToolOutputLimits;
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+
+    fn config() -> ToolOutputConfig {
+        ToolOutputConfig {
+            max_tokens: Some(1_000),
+            head_ratio: Some(0.2),
+            tools: BTreeMap::from([(
+                "shell_command".to_string(),
+                ToolOutputLimits {
+                    max_bytes: Some(20_000),
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn tool_limits_override_the_table_defaults() {
+        let archive_dir = PathBuf::from("/tmp/archive");
+        let default = TruncationPolicy::Tokens(10_000);
+
+        let shell =
+            ToolOutputPolicy::from_config(&config(), "shell_command", default, archive_dir.clone())
+                .expect("policy");
+        let other = ToolOutputPolicy::from_config(&config(), "read_file", default, archive_dir)
+            .expect("policy");
+
+        assert_eq!(shell.limit, TruncationPolicy::Bytes(20_000));
+        assert_eq!(shell.head_ratio, 0.2);
+        assert_eq!(other.limit, TruncationPolicy::Tokens(1_000));
+        assert_eq!(other.archive_dir, None);
+        assert_eq!(other.summary_model, DEFAULT_SUMMARY_MODEL);
+        assert_eq!(
+            ToolOutputPolicy::from_config(
+                &ToolOutputConfig::default(),
+                "shell_command",
+                default,
+                PathBuf::new(),
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn history_budget_fits_the_largest_tool_limit() {
+        let default = TruncationPolicy::Bytes(10_000);
+
+        assert_eq!(
+            history_truncation_policy(&ToolOutputConfig::default(), default),
+            default
+        );
+        assert_eq!(
+            history_truncation_policy(&config(), default),
+            TruncationPolicy::Tokens(5_000)
+        );
+    }
+
+    #[tokio::test]
+    async fn cut_output_points_at_the_archived_copy() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let policy = ToolOutputPolicy {
+            limit: TruncationPolicy::Bytes(10),
+            head_ratio: 0.2,
+            archive_dir: Some(dir.path().to_path_buf()),
+            summarize_over_tokens: None,
+            summary_model: DEFAULT_SUMMARY_MODEL.to_string(),
+        };
+        let text = "0123456789\nabcdefghij";
+
+        let archived = archive_output(dir.path(), "call/1", text)
+            .await
+            .expect("archived");
+
+        assert_eq!(archived, dir.path().join("call_1.txt"));
+        assert_eq!(
+            tokio::fs::read_to_string(&archived).await.expect("read"),
+            text
+        );
+        assert_eq!(
+            policy.cut(text, Some(&archived)),
+            format!(
+                "Total output lines: 2\nFull output saved to {}\n\n01…11 chars truncated…cdefghij",
+                archived.display()
+            )
+        );
+    }
+}
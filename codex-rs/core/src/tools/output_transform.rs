@@ -0,0 +1,250 @@
+//! Post-processing applied to tool output before it reaches the model.
+
+use std::sync::Arc;
+
+use codex_protocol::models::FunctionCallOutputBody;
+use codex_protocol::models::FunctionCallOutputContentItem;
+use codex_protocol::models::FunctionCallOutputPayload;
+use codex_protocol::models::ResponseInputItem;
+use serde_json::Value as JsonValue;
+
+use crate::tools::context::ToolOutput;
+use crate::truncate::TruncationPolicy;
+use crate::truncate::truncate_function_output_items_with_policy;
+use crate::truncate::truncate_head_and_tail;
+use crate::truncate::truncate_to_byte_limit;
+
+/// Rewrites the text of a tool result, e.g. to strip terminal color codes or
+/// redact secrets. Register one with
+/// [`CodexThread::apply_tool_output_transformer`].
+///
+/// [`CodexThread::apply_tool_output_transformer`]: crate::CodexThread::apply_tool_output_transformer
+pub trait ToolOutputTransformer: Send + Sync {
+    fn transform(&self, tool_name: &str, output: String) -> String;
+}
+
+/// Runs every transformer over the text parts of `output`, in registration
+/// order. Images, streamed bodies, and MCP results are passed through as-is.
+pub(crate) fn transform_tool_output(
+    transformers: &[Arc<dyn ToolOutputTransformer>],
+    tool_name: &str,
+    output: ToolOutput,
+) -> ToolOutput {
+    if transformers.is_empty() {
+        return output;
+    }
+    let apply = |text: String| {
+        transformers.iter().fold(text, |text, transformer| {
+            transformer.transform(tool_name, text)
+        })
+    };
+    match output {
+        ToolOutput::Function { body, success } => {
+            let body = match body {
+                FunctionCallOutputBody::Text(text) => FunctionCallOutputBody::Text(apply(text)),
+                FunctionCallOutputBody::ContentItems(items) => {
+                    FunctionCallOutputBody::ContentItems(
+                        items
+                            .into_iter()
+                            .map(|item| match item {
+                                FunctionCallOutputContentItem::InputText { text } => {
+                                    FunctionCallOutputContentItem::InputText { text: apply(text) }
+                                }
+                                other => other,
+                            })
+                            .collect(),
+                    )
+                }
+                other => other,
+            };
+            ToolOutput::Function { body, success }
+        }
+        other => other,
+    }
+}
+
+/// Enforces a tool's `max_output_bytes`. Text bodies are cut with
+/// [`truncate_to_byte_limit`], from the middle when `keep_tail` is set; text
+/// content items share the limit. MCP results are passed through as-is.
+pub(crate) fn limit_tool_output_bytes(
+    output: ToolOutput,
+    max_bytes: usize,
+    keep_tail: bool,
+) -> ToolOutput {
+    match output {
+        ToolOutput::Function { body, success } => {
+            let body = match body {
+                FunctionCallOutputBody::Text(text) => FunctionCallOutputBody::Text(
+                    truncate_to_byte_limit(&text, max_bytes, keep_tail),
+                ),
+                FunctionCallOutputBody::ContentItems(items) => {
+                    FunctionCallOutputBody::ContentItems(
+                        truncate_function_output_items_with_policy(
+                            &items,
+                            TruncationPolicy::Bytes(max_bytes),
+                        ),
+                    )
+                }
+                other => other,
+            };
+            ToolOutput::Function { body, success }
+        }
+        other => other,
+    }
+}
+
+/// Cap on the size of every tool result, whatever the tool. See
+/// [`ToolRouter::set_output_limit`].
+///
+/// [`ToolRouter::set_output_limit`]: crate::tools::router::ToolRouter::set_output_limit
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OutputLimit {
+    /// Bytes of output text kept per result. Zero disables the limit.
+    pub max_bytes: usize,
+    /// Share of `max_bytes` kept from the start of the text; the rest is
+    /// kept from its end, where errors usually are.
+    pub head_ratio: f64,
+}
+
+impl Default for OutputLimit {
+    fn default() -> Self {
+        Self {
+            max_bytes: 0,
+            head_ratio: 0.5,
+        }
+    }
+}
+
+impl OutputLimit {
+    /// Cuts the text of `response` down to the limit, replacing what is
+    /// dropped with a `[truncated: N bytes omitted]` marker. Text content
+    /// items, including those of MCP results, share the limit.
+    pub(crate) fn apply(&self, response: ResponseInputItem) -> ResponseInputItem {
+        if self.max_bytes == 0 {
+            return response;
+        }
+        match response {
+            ResponseInputItem::FunctionCallOutput { call_id, output } => {
+                ResponseInputItem::FunctionCallOutput {
+                    call_id,
+                    output: self.apply_to_payload(output),
+                }
+            }
+            ResponseInputItem::CustomToolCallOutput { call_id, output } => {
+                ResponseInputItem::CustomToolCallOutput {
+                    call_id,
+                    output: self.apply_to_payload(output),
+                }
+            }
+            ResponseInputItem::McpToolCallOutput { call_id, result } => {
+                let result = match result {
+                    Ok(mut result) => {
+                        let mut remaining = self.max_bytes;
+                        for item in &mut result.content {
+                            if let Some(JsonValue::String(text)) = item.get_mut("text") {
+                                let kept = text.len().min(remaining);
+                                *text = truncate_head_and_tail(text, remaining, self.head_ratio);
+                                remaining -= kept;
+                            }
+                        }
+                        Ok(result)
+                    }
+                    Err(message) => Err(truncate_head_and_tail(
+                        &message,
+                        self.max_bytes,
+                        self.head_ratio,
+                    )),
+                };
+                ResponseInputItem::McpToolCallOutput { call_id, result }
+            }
+            other => other,
+        }
+    }
+
+    fn apply_to_payload(
+        &self,
+        mut payload: FunctionCallOutputPayload,
+    ) -> FunctionCallOutputPayload {
+        payload.body = match payload.body {
+            FunctionCallOutputBody::Text(text) => FunctionCallOutputBody::Text(
+                truncate_head_and_tail(&text, self.max_bytes, self.head_ratio),
+            ),
+            FunctionCallOutputBody::ContentItems(items) => {
+                FunctionCallOutputBody::ContentItems(truncate_function_output_items_with_policy(
+                    &items,
+                    TruncationPolicy::Bytes(self.max_bytes),
+                ))
+            }
+            other => other,
+        };
+        payload
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    struct Append(&'static str);
+
+    impl ToolOutputTransformer for Append {
+        fn transform(&self, tool_name: &str, output: String) -> String {
+            format!("{output}{}:{tool_name};", self.0)
+        }
+    }
+
+    fn text_body(output: ToolOutput) -> FunctionCallOutputBody {
+        match output {
+            ToolOutput::Function { body, .. } => body,
+            ToolOutput::Mcp { .. } => panic!("expected function output"),
+        }
+    }
+
+    #[test]
+    fn transformers_compose_in_registration_order() {
+        let transformers: Vec<Arc<dyn ToolOutputTransformer>> =
+            vec![Arc::new(Append("first")), Arc::new(Append("second"))];
+        let output = ToolOutput::Function {
+            body: FunctionCallOutputBody::Text("out;".to_string()),
+            success: Some(true),
+        };
+
+        let transformed = transform_tool_output(&transformers, "shell", output);
+
+        assert_eq!(
+            text_body(transformed),
+            FunctionCallOutputBody::Text("out;first:shell;second:shell;".to_string())
+        );
+    }
+
+    #[test]
+    fn only_text_content_items_are_transformed() {
+        let transformers: Vec<Arc<dyn ToolOutputTransformer>> = vec![Arc::new(Append("x"))];
+        let image = FunctionCallOutputContentItem::InputImage {
+            image_url: "data:image/png;base64,AAA".to_string(),
+            detail: None,
+        };
+        let output = ToolOutput::Function {
+            body: FunctionCallOutputBody::ContentItems(vec![
+                FunctionCallOutputContentItem::InputText {
+                    text: "a".to_string(),
+                },
+                image.clone(),
+            ]),
+            success: None,
+        };
+
+        let transformed = transform_tool_output(&transformers, "view_image", output);
+
+        assert_eq!(
+            text_body(transformed),
+            FunctionCallOutputBody::ContentItems(vec![
+                FunctionCallOutputContentItem::InputText {
+                    text: "ax:view_image;".to_string(),
+                },
+                image,
+            ])
+        );
+    }
+}
@@ -0,0 +1,381 @@
+//! Adjustments to built-in tools from `[tools.overrides]` in `config.toml`.
+//!
+//! [`build_specs`] applies them after registering the built-in tools and
+//! before MCP and dynamic tools, so they never touch those. A renamed tool is
+//! advertised and dispatched under its new name only. Constraints are both
+//! described in the advertised schema and enforced on every call by
+//! [`ConstrainedToolHandler`], which then hands the call to the built-in
+//! handler under the built-in name.
+//!
+//! [`build_specs`]: crate::tools::spec::build_specs
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::Value as JsonValue;
+use tracing::warn;
+
+use crate::bash::parse_shell_lc_plain_commands;
+use crate::client_common::tools::ToolSpec;
+use crate::config::types::ToolOverride;
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::tools::registry::ToolRegistryBuilder;
+use crate::tools::spec::JsonSchema;
+
+const TIMEOUT_ARGUMENT: &str = "timeout_ms";
+
+/// Tools whose calls carry a command that `allowed_commands` can check.
+const COMMAND_TOOLS: &[&str] = &["shell_command", "exec_command"];
+
+/// Applies `overrides`, keyed by built-in tool name, to the tools registered
+/// on `builder` so far. `shell_aliases` are the names the shell handler
+/// answers to; constraining one of them constrains all of them.
+pub(crate) fn apply_tool_overrides(
+    builder: &mut ToolRegistryBuilder,
+    overrides: &BTreeMap<String, ToolOverride>,
+    shell_aliases: &[String],
+) {
+    for (builtin_name, tool_override) in overrides {
+        let Some(configured) = builder.spec_mut(builtin_name) else {
+            warn!("ignoring tools.overrides.{builtin_name}: no such built-in tool is enabled");
+            continue;
+        };
+        let is_shell_alias = shell_aliases.iter().any(|alias| alias == builtin_name);
+        let checks_commands = tool_override.allowed_commands.is_some()
+            && (is_shell_alias || COMMAND_TOOLS.contains(&builtin_name.as_str()));
+        if tool_override.allowed_commands.is_some() && !checks_commands {
+            warn!("ignoring tools.overrides.{builtin_name}.allowed_commands: not a shell tool");
+        }
+        let caps_timeout = tool_override.max_timeout_ms.is_some()
+            && (matches!(configured.spec, ToolSpec::LocalShell {})
+                || has_timeout_argument(&configured.spec));
+        if tool_override.max_timeout_ms.is_some() && !caps_timeout {
+            warn!("ignoring tools.overrides.{builtin_name}.max_timeout_ms: no timeout_ms argument");
+        }
+        let constraints = ToolConstraints {
+            allowed_commands: tool_override
+                .allowed_commands
+                .clone()
+                .filter(|_| checks_commands),
+            max_timeout_ms: tool_override.max_timeout_ms.filter(|_| caps_timeout),
+        };
+
+        describe_constraints(&mut configured.spec, &constraints);
+        if let Some(description) = &tool_override.description {
+            match &mut configured.spec {
+                ToolSpec::Function(tool) => tool.description = description.clone(),
+                ToolSpec::Freeform(tool) => tool.description = description.clone(),
+                _ => warn!(
+                    "ignoring tools.overrides.{builtin_name}.description: the tool is provided by the model"
+                ),
+            }
+        }
+        let new_name = tool_override
+            .name
+            .as_ref()
+            .filter(|name| *name != builtin_name);
+        let renamed = match (new_name, &mut configured.spec) {
+            (Some(name), ToolSpec::Function(tool)) => {
+                tool.name = name.clone();
+                Some(name.clone())
+            }
+            (Some(name), ToolSpec::Freeform(tool)) => {
+                tool.name = name.clone();
+                Some(name.clone())
+            }
+            (Some(_), _) => {
+                warn!(
+                    "ignoring tools.overrides.{builtin_name}.name: the tool is provided by the model"
+                );
+                None
+            }
+            (None, _) => None,
+        };
+
+        if renamed.is_none() && constraints.is_empty() {
+            continue;
+        }
+        let mut wrapped = vec![builtin_name.clone()];
+        if is_shell_alias {
+            wrapped.extend(
+                shell_aliases
+                    .iter()
+                    .filter(|alias| *alias != builtin_name)
+                    .cloned(),
+            );
+        }
+        for name in wrapped {
+            let Some(inner) = builder.take_handler(&name) else {
+                continue;
+            };
+            let handler = Arc::new(ConstrainedToolHandler {
+                builtin_name: name.clone(),
+                constraints: constraints.clone(),
+                inner,
+            });
+            let registered_name = match &renamed {
+                Some(new_name) if name == *builtin_name => new_name.clone(),
+                _ => name,
+            };
+            builder.register_handler(registered_name, handler);
+        }
+    }
+}
+
+fn has_timeout_argument(spec: &ToolSpec) -> bool {
+    matches!(
+        spec,
+        ToolSpec::Function(tool)
+            if matches!(&tool.parameters, JsonSchema::Object { properties, .. } if properties.contains_key(TIMEOUT_ARGUMENT))
+    )
+}
+
+/// Tells the model about `constraints` in the tool's description and schema.
+fn describe_constraints(spec: &mut ToolSpec, constraints: &ToolConstraints) {
+    let ToolSpec::Function(tool) = spec else {
+        return;
+    };
+    if let Some(allowed) = &constraints.allowed_commands {
+        tool.description.push_str(&format!(
+            "\n\nOnly these programs may be run: {}. Other commands are rejected.",
+            allowed.join(", ")
+        ));
+    }
+    if let Some(max_timeout_ms) = constraints.max_timeout_ms
+        && let JsonSchema::Object { properties, .. } = &mut tool.parameters
+        && let Some(JsonSchema::Number { description }) = properties.get_mut(TIMEOUT_ARGUMENT)
+    {
+        let note = format!("At most {max_timeout_ms}.");
+        *description = Some(match description.take() {
+            Some(existing) => format!("{existing} {note}"),
+            None => note,
+        });
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ToolConstraints {
+    allowed_commands: Option<Vec<String>>,
+    max_timeout_ms: Option<u64>,
+}
+
+impl ToolConstraints {
+    fn is_empty(&self) -> bool {
+        self.allowed_commands.is_none() && self.max_timeout_ms.is_none()
+    }
+
+    /// Checks the call against the constraints, lowering its timeout when
+    /// needed.
+    fn apply(&self, payload: &mut ToolPayload) -> Result<(), String> {
+        match payload {
+            ToolPayload::Function { arguments } => {
+                let mut args: JsonValue = serde_json::from_str(arguments)
+                    .map_err(|err| format!("failed to parse function arguments: {err}"))?;
+                if let Some(allowed) = &self.allowed_commands {
+                    let command = match args.get("command").or_else(|| args.get("cmd")) {
+                        Some(JsonValue::String(script)) => {
+                            vec!["bash".to_string(), "-lc".to_string(), script.clone()]
+                        }
+                        Some(JsonValue::Array(argv)) => argv
+                            .iter()
+                            .map(|word| word.as_str().map(str::to_string))
+                            .collect::<Option<Vec<_>>>()
+                            .unwrap_or_default(),
+                        _ => Vec::new(),
+                    };
+                    check_allowed_commands(&command, allowed)?;
+                }
+                if let Some(max_timeout_ms) = self.max_timeout_ms
+                    && let Some(args) = args.as_object_mut()
+                {
+                    let timeout_ms = args
+                        .get(TIMEOUT_ARGUMENT)
+                        .and_then(JsonValue::as_u64)
+                        .map_or(max_timeout_ms, |timeout_ms| timeout_ms.min(max_timeout_ms));
+                    args.insert(TIMEOUT_ARGUMENT.to_string(), timeout_ms.into());
+                    *arguments = args_to_string(args)?;
+                }
+                Ok(())
+            }
+            ToolPayload::LocalShell { params } => {
+                if let Some(allowed) = &self.allowed_commands {
+                    check_allowed_commands(&params.command, allowed)?;
+                }
+                if let Some(max_timeout_ms) = self.max_timeout_ms {
+                    params.timeout_ms = Some(
+                        params
+                            .timeout_ms
+                            .map_or(max_timeout_ms, |timeout_ms| timeout_ms.min(max_timeout_ms)),
+                    );
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn args_to_string(args: &serde_json::Map<String, JsonValue>) -> Result<String, String> {
+    serde_json::to_string(args).map_err(|err| format!("failed to encode arguments: {err}"))
+}
+
+/// Fails unless every command in `command`, an argv that may be a
+/// `bash -lc` script, runs one of the `allowed` programs. Allowed programs
+/// match by path or by file name.
+fn check_allowed_commands(command: &[String], allowed: &[String]) -> Result<(), String> {
+    if command.is_empty() {
+        return Err("the call has no command to check against the allowed commands".to_string());
+    }
+    let commands = match parse_shell_lc_plain_commands(command) {
+        Some(commands) => commands,
+        None if command.len() >= 2 && matches!(command[1].as_str(), "-lc" | "-c") => {
+            return Err(
+                "only simple commands joined by &&, || or ; can be checked against the allowed commands; split the script into separate calls"
+                    .to_string(),
+            );
+        }
+        None => vec![command.to_vec()],
+    };
+    for program in commands.iter().filter_map(|words| words.first()) {
+        let file_name = Path::new(program)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned());
+        let permitted = allowed
+            .iter()
+            .any(|entry| entry == program || Some(entry) == file_name.as_ref());
+        if !permitted {
+            return Err(format!(
+                "`{program}` is not an allowed command; allowed commands are: {}",
+                allowed.join(", ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Enforces a tool's [`ToolConstraints`], then dispatches to the built-in
+/// handler as `builtin_name`.
+struct ConstrainedToolHandler {
+    builtin_name: String,
+    constraints: ToolConstraints,
+    inner: Arc<dyn ToolHandler>,
+}
+
+impl ConstrainedToolHandler {
+    fn builtin_invocation(&self, invocation: &ToolInvocation) -> ToolInvocation {
+        ToolInvocation {
+            tool_name: self.builtin_name.clone(),
+            ..invocation.clone()
+        }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for ConstrainedToolHandler {
+    fn kind(&self) -> ToolKind {
+        self.inner.kind()
+    }
+
+    fn matches_kind(&self, payload: &ToolPayload) -> bool {
+        self.inner.matches_kind(payload)
+    }
+
+    async fn is_mutating(&self, invocation: &ToolInvocation) -> bool {
+        self.inner
+            .is_mutating(&self.builtin_invocation(invocation))
+            .await
+    }
+
+    fn is_pure(&self) -> bool {
+        self.inner.is_pure()
+    }
+
+    async fn dry_run_preview(
+        &self,
+        invocation: &ToolInvocation,
+    ) -> Result<Option<JsonValue>, FunctionCallError> {
+        let mut invocation = self.builtin_invocation(invocation);
+        self.constraints
+            .apply(&mut invocation.payload)
+            .map_err(FunctionCallError::RespondToModel)?;
+        self.inner.dry_run_preview(&invocation).await
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let mut invocation = ToolInvocation {
+            tool_name: self.builtin_name.clone(),
+            ..invocation
+        };
+        self.constraints
+            .apply(&mut invocation.payload)
+            .map_err(FunctionCallError::RespondToModel)?;
+        self.inner.handle(invocation).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn strings(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| (*word).to_string()).collect()
+    }
+
+    #[test]
+    fn allowed_commands_checks_every_command_of_a_script() {
+        let allowed = strings(&["git", "/usr/bin/cargo"]);
+        assert_eq!(
+            check_allowed_commands(&strings(&["/usr/bin/git", "status"]), &allowed),
+            Ok(())
+        );
+        assert_eq!(
+            check_allowed_commands(
+                &strings(&["bash", "-lc", "git status && cargo test"]),
+                &allowed
+            ),
+            Err(
+                "`cargo` is not an allowed command; allowed commands are: git, /usr/bin/cargo"
+                    .to_string()
+            )
+        );
+        assert!(
+            check_allowed_commands(
+                &strings(&["bash", "-lc", "git log | $(rm -rf /)"]),
+                &allowed
+            )
+            .is_err()
+        );
+        assert!(check_allowed_commands(&[], &allowed).is_err());
+    }
+
+    #[test]
+    fn constraints_cap_timeouts_and_reject_other_commands() {
+        let constraints = ToolConstraints {
+            allowed_commands: Some(strings(&["git"])),
+            max_timeout_ms: Some(1_000),
+        };
+
+        let mut payload = ToolPayload::Function {
+            arguments: r#"{"command":"git status","timeout_ms":60000}"#.to_string(),
+        };
+        assert_eq!(constraints.apply(&mut payload), Ok(()));
+        let ToolPayload::Function { arguments } = payload else {
+            panic!("expected function payload");
+        };
+        let arguments: JsonValue = serde_json::from_str(&arguments).expect("valid json");
+        assert_eq!(arguments["timeout_ms"], 1_000);
+
+        let mut payload = ToolPayload::Function {
+            arguments: r#"{"cmd":"rm -rf target"}"#.to_string(),
+        };
+        assert!(constraints.apply(&mut payload).is_err());
+    }
+}
@@ -8,6 +8,7 @@ use tokio_util::task::AbortOnDropHandle;
 use tracing::Instrument;
 use tracing::instrument;
 use tracing::trace_span;
+use tracing::warn;
 
 use crate::codex::Session;
 use crate::codex::TurnContext;
@@ -46,17 +47,20 @@ impl ToolCallRuntime {
         }
     }
 
+    /// Runs `call` and resolves to its response, or to `None` when its call id
+    /// is still running from an earlier call, which answers for that id.
     #[instrument(level = "trace", skip_all)]
     pub(crate) fn handle_tool_call(
         self,
         call: ToolCall,
         cancellation_token: CancellationToken,
-    ) -> impl std::future::Future<Output = Result<ResponseInputItem, CodexErr>> {
+    ) -> impl std::future::Future<Output = Result<Option<ResponseInputItem>, CodexErr>> {
         let supports_parallel = self.router.tool_supports_parallel(&call.tool_name);
 
         let router = Arc::clone(&self.router);
         let session = Arc::clone(&self.session);
         let turn = Arc::clone(&self.turn_context);
+        let turn_for_abort = Arc::clone(&self.turn_context);
         let tracker = Arc::clone(&self.tracker);
         let lock = Arc::clone(&self.parallel_execution);
         let started = Instant::now();
@@ -75,7 +79,8 @@ impl ToolCallRuntime {
                     _ = cancellation_token.cancelled() => {
                         let secs = started.elapsed().as_secs_f32().max(0.1);
                         dispatch_span.record("aborted", true);
-                        Ok(Self::aborted_response(&call, secs))
+                        let timed_out = turn_for_abort.turn_timing_state.timed_out().await;
+                        Ok(Self::aborted_response(&call, secs, timed_out))
                     },
                     res = async {
                         let _guard = if supports_parallel {
@@ -100,7 +105,11 @@ impl ToolCallRuntime {
 
         async move {
             match handle.await {
-                Ok(Ok(response)) => Ok(response),
+                Ok(Ok(response)) => Ok(Some(response)),
+                Ok(Err(FunctionCallError::DuplicateCallId { call_id })) => {
+                    warn!("ignoring a repeat of call {call_id}, which is still running");
+                    Ok(None)
+                }
                 Ok(Err(FunctionCallError::Fatal(message))) => Err(CodexErr::Fatal(message)),
                 Ok(Err(other)) => Err(CodexErr::Fatal(other.to_string())),
                 Err(err) => Err(CodexErr::Fatal(format!(
@@ -113,35 +122,40 @@ impl ToolCallRuntime {
 }
 
 impl ToolCallRuntime {
-    fn aborted_response(call: &ToolCall, secs: f32) -> ResponseInputItem {
+    fn aborted_response(call: &ToolCall, secs: f32, timed_out: bool) -> ResponseInputItem {
         match &call.payload {
             ToolPayload::Custom { .. } => ResponseInputItem::CustomToolCallOutput {
                 call_id: call.call_id.clone(),
                 output: FunctionCallOutputPayload {
-                    body: FunctionCallOutputBody::Text(Self::abort_message(call, secs)),
+                    body: FunctionCallOutputBody::Text(Self::abort_message(call, secs, timed_out)),
                     ..Default::default()
                 },
             },
             ToolPayload::Mcp { .. } => ResponseInputItem::McpToolCallOutput {
                 call_id: call.call_id.clone(),
-                result: Err(Self::abort_message(call, secs)),
+                result: Err(Self::abort_message(call, secs, timed_out)),
             },
             _ => ResponseInputItem::FunctionCallOutput {
                 call_id: call.call_id.clone(),
                 output: FunctionCallOutputPayload {
-                    body: FunctionCallOutputBody::Text(Self::abort_message(call, secs)),
+                    body: FunctionCallOutputBody::Text(Self::abort_message(call, secs, timed_out)),
                     ..Default::default()
                 },
             },
         }
     }
 
-    fn abort_message(call: &ToolCall, secs: f32) -> String {
+    fn abort_message(call: &ToolCall, secs: f32, timed_out: bool) -> String {
+        let reason = if timed_out {
+            "timed out: the turn reached its time limit"
+        } else {
+            "aborted by user"
+        };
         match call.tool_name.as_str() {
             "shell" | "container.exec" | "local_shell" | "shell_command" | "unified_exec" => {
-                format!("Wall time: {secs:.1} seconds\naborted by user")
+                format!("Wall time: {secs:.1} seconds\n{reason}")
             }
-            _ => format!("aborted by user after {secs:.1}s"),
+            _ => format!("{reason} after {secs:.1}s"),
         }
     }
 }
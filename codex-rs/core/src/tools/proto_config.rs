@@ -0,0 +1,207 @@
+//! Protobuf encoding of a tools config and its dynamic tools, for
+//! deployments that distribute configuration over gRPC. The schema lives in
+//! `core/proto/tools_config.proto`; the messages below are kept in sync with
+//! it by hand.
+//!
+//! See [`ToolRouter::from_proto_config`].
+//!
+//! [`ToolRouter::from_proto_config`]: crate::tools::router::ToolRouter::from_proto_config
+
+use std::collections::BTreeMap;
+
+use codex_protocol::dynamic_tools::DynamicToolSpec;
+use prost::Message;
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+
+use crate::tools::config_file::ToolsConfigFile;
+use crate::tools::spec::ToolsConfig;
+
+#[derive(Debug, Error)]
+pub enum ProtoDecodeError {
+    #[error("failed to decode tools config: {0}")]
+    Decode(#[from] prost::DecodeError),
+    #[error("tools config does not set `model`")]
+    MissingModel,
+    #[error("invalid `{field}` in tools config: {value:?}")]
+    InvalidEnum { field: &'static str, value: String },
+    #[error("invalid input schema for dynamic tool {tool}: {source}")]
+    InvalidInputSchema {
+        tool: String,
+        source: serde_json::Error,
+    },
+}
+
+/// `codex.tools.v1.ToolsConfig`.
+#[derive(Clone, PartialEq, Message)]
+pub struct ToolsConfigProto {
+    #[prost(string, tag = "1")]
+    pub model: String,
+    #[prost(string, optional, tag = "2")]
+    pub shell_type: Option<String>,
+    #[prost(bool, optional, tag = "3")]
+    pub allow_login_shell: Option<bool>,
+    #[prost(string, optional, tag = "4")]
+    pub web_search_mode: Option<String>,
+    #[prost(uint64, optional, tag = "5")]
+    pub max_argument_size_bytes: Option<u64>,
+    #[prost(btree_map = "string, bool", tag = "6")]
+    pub features: BTreeMap<String, bool>,
+    #[prost(message, repeated, tag = "7")]
+    pub dynamic_tools: Vec<DynamicToolSpecProto>,
+}
+
+/// `codex.tools.v1.DynamicToolSpec`.
+#[derive(Clone, PartialEq, Message)]
+pub struct DynamicToolSpecProto {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub description: String,
+    #[prost(string, tag = "3")]
+    pub input_schema_json: String,
+}
+
+impl From<&DynamicToolSpec> for DynamicToolSpecProto {
+    fn from(spec: &DynamicToolSpec) -> Self {
+        Self {
+            name: spec.name.clone(),
+            description: spec.description.clone(),
+            input_schema_json: spec.input_schema.to_string(),
+        }
+    }
+}
+
+impl TryFrom<DynamicToolSpecProto> for DynamicToolSpec {
+    type Error = ProtoDecodeError;
+
+    fn try_from(proto: DynamicToolSpecProto) -> Result<Self, Self::Error> {
+        let input_schema = serde_json::from_str(&proto.input_schema_json).map_err(|source| {
+            ProtoDecodeError::InvalidInputSchema {
+                tool: proto.name.clone(),
+                source,
+            }
+        })?;
+        Ok(Self {
+            name: proto.name,
+            description: proto.description,
+            input_schema,
+        })
+    }
+}
+
+/// Decodes `bytes` into the tools config and dynamic tools it describes.
+pub(crate) fn decode(
+    bytes: &[u8],
+) -> Result<(ToolsConfig, Vec<DynamicToolSpec>), ProtoDecodeError> {
+    let proto = ToolsConfigProto::decode(bytes)?;
+    if proto.model.is_empty() {
+        return Err(ProtoDecodeError::MissingModel);
+    }
+    let file = ToolsConfigFile {
+        inherit_from: None,
+        model: None,
+        shell_type: proto
+            .shell_type
+            .map(|value| parse_enum("shell_type", value))
+            .transpose()?,
+        allow_login_shell: proto.allow_login_shell,
+        web_search_mode: proto
+            .web_search_mode
+            .map(|value| parse_enum("web_search_mode", value))
+            .transpose()?,
+        max_argument_size_bytes: proto
+            .max_argument_size_bytes
+            .map(|bytes| usize::try_from(bytes).unwrap_or(usize::MAX)),
+        features: proto.features,
+    };
+    let dynamic_tools = proto
+        .dynamic_tools
+        .into_iter()
+        .map(DynamicToolSpec::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((
+        file.into_tools_config_for_model(&proto.model),
+        dynamic_tools,
+    ))
+}
+
+/// Parses `value` as the serde name of a config enum variant.
+fn parse_enum<T: DeserializeOwned>(
+    field: &'static str,
+    value: String,
+) -> Result<T, ProtoDecodeError> {
+    serde_json::from_value(serde_json::Value::String(value.clone()))
+        .map_err(|_| ProtoDecodeError::InvalidEnum { field, value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::Features;
+    use crate::models_manager::model_info::model_info_from_slug;
+    use crate::tools::router::ToolRouter;
+    use crate::tools::spec::ToolsConfigParams;
+    use codex_protocol::config_types::WebSearchMode;
+    use codex_protocol::openai_models::ConfigShellToolType;
+    use codex_protocol::protocol::SessionSource;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn proto_config_builds_same_router_as_rust_api() -> anyhow::Result<()> {
+        let lookup = DynamicToolSpec {
+            name: "lookup_ticket".to_string(),
+            description: "Looks up a ticket by id.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {"id": {"type": "string"}},
+                "required": ["id"],
+            }),
+        };
+        let bytes = ToolsConfigProto {
+            model: "gpt-5-codex".to_string(),
+            shell_type: Some("shell_command".to_string()),
+            allow_login_shell: Some(false),
+            web_search_mode: Some("live".to_string()),
+            max_argument_size_bytes: Some(2048),
+            features: BTreeMap::from([("js_repl".to_string(), true)]),
+            dynamic_tools: vec![DynamicToolSpecProto::from(&lookup)],
+        }
+        .encode_to_vec();
+
+        let decoded = ToolRouter::from_proto_config(&bytes)?;
+
+        let mut features = Features::with_defaults();
+        features.apply_map(&BTreeMap::from([("js_repl".to_string(), true)]));
+        let mut config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info_from_slug("gpt-5-codex"),
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Live),
+            session_source: SessionSource::Exec,
+        })
+        .with_max_argument_size_bytes(Some(2048))
+        .with_allow_login_shell(false);
+        config.shell_type = ConfigShellToolType::ShellCommand;
+        let expected = ToolRouter::from_config(&config, None, None, &[lookup]);
+
+        assert_eq!(decoded.specs(), expected.specs());
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_enum_value_is_rejected() {
+        let bytes = ToolsConfigProto {
+            model: "gpt-5-codex".to_string(),
+            shell_type: Some("telnet".to_string()),
+            ..Default::default()
+        }
+        .encode_to_vec();
+
+        let err = decode(&bytes).expect_err("unknown shell type should fail");
+
+        assert_eq!(
+            err.to_string(),
+            r#"invalid `shell_type` in tools config: "telnet""#
+        );
+    }
+}
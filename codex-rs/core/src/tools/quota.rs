@@ -0,0 +1,170 @@
+//! Budgets that tool use is charged against: tokens of tool output, and money
+//! spent on tools that cost something per call.
+//!
+//! See [`ToolRegistry::dispatch_with_quota_check`] and
+//! [`ToolRegistry::throttle_by_cost`].
+//!
+//! [`ToolRegistry::dispatch_with_quota_check`]: crate::tools::registry::ToolRegistry::dispatch_with_quota_check
+//! [`ToolRegistry::throttle_by_cost`]: crate::tools::registry::ToolRegistry::throttle_by_cost
+
+use std::sync::Mutex;
+
+use codex_protocol::models::FunctionCallOutputBody;
+use codex_protocol::models::FunctionCallOutputContentItem;
+use codex_protocol::models::FunctionCallOutputPayload;
+use codex_protocol::models::ResponseInputItem;
+
+use crate::truncate::TruncationPolicy;
+use crate::truncate::approx_token_count;
+use crate::truncate::truncate_function_output_items_with_policy;
+use crate::truncate::truncate_text;
+
+/// Tokens that the output of a sequence of tool calls may still add to the
+/// model's context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TokenBudget {
+    max_output_tokens: u64,
+    remaining: u64,
+}
+
+impl TokenBudget {
+    pub fn new(max_output_tokens: u64) -> Self {
+        Self {
+            max_output_tokens,
+            remaining: max_output_tokens,
+        }
+    }
+
+    pub fn max_output_tokens(&self) -> u64 {
+        self.max_output_tokens
+    }
+
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// Truncates the text of `response` to the remaining budget and deducts
+    /// its estimated token count. Once the budget is spent, text output is
+    /// reduced to a truncation marker. MCP results are charged but never
+    /// truncated.
+    pub(crate) fn charge(&mut self, response: ResponseInputItem) -> ResponseInputItem {
+        match response {
+            ResponseInputItem::FunctionCallOutput { call_id, output } => {
+                ResponseInputItem::FunctionCallOutput {
+                    call_id,
+                    output: self.charge_payload(output),
+                }
+            }
+            ResponseInputItem::CustomToolCallOutput { call_id, output } => {
+                ResponseInputItem::CustomToolCallOutput {
+                    call_id,
+                    output: self.charge_payload(output),
+                }
+            }
+            ResponseInputItem::McpToolCallOutput { call_id, result } => {
+                let text = match &result {
+                    Ok(result) => serde_json::to_string(result).unwrap_or_default(),
+                    Err(err) => err.clone(),
+                };
+                self.deduct(approx_token_count(&text));
+                ResponseInputItem::McpToolCallOutput { call_id, result }
+            }
+            other => other,
+        }
+    }
+
+    fn charge_payload(&mut self, payload: FunctionCallOutputPayload) -> FunctionCallOutputPayload {
+        let policy =
+            TruncationPolicy::Tokens(usize::try_from(self.remaining).unwrap_or(usize::MAX));
+        let FunctionCallOutputPayload {
+            body,
+            success,
+            error_code,
+        } = payload;
+        let body = match body {
+            FunctionCallOutputBody::Text(text) => {
+                let text = truncate_text(&text, policy);
+                self.deduct(approx_token_count(&text));
+                FunctionCallOutputBody::Text(text)
+            }
+            FunctionCallOutputBody::ContentItems(items) => {
+                let items = truncate_function_output_items_with_policy(&items, policy);
+                let tokens = items
+                    .iter()
+                    .map(|item| match item {
+                        FunctionCallOutputContentItem::InputText { text } => {
+                            approx_token_count(text)
+                        }
+                        _ => 0,
+                    })
+                    .sum();
+                self.deduct(tokens);
+                FunctionCallOutputBody::ContentItems(items)
+            }
+            other => other,
+        };
+        FunctionCallOutputPayload {
+            body,
+            success,
+            error_code,
+        }
+    }
+
+    fn deduct(&mut self, tokens: usize) {
+        let tokens = u64::try_from(tokens).unwrap_or(u64::MAX);
+        self.remaining = self.remaining.saturating_sub(tokens);
+    }
+}
+
+/// Estimated price of one call to a tool, e.g. a search API billed per
+/// request.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CostModel {
+    pub cost_per_call_usd: f64,
+}
+
+/// Money that calls to priced tools may spend, shared by every dispatch
+/// through one registry.
+#[derive(Debug)]
+pub(crate) struct CostBudget {
+    budget_usd: f64,
+    spent_usd: Mutex<f64>,
+}
+
+impl CostBudget {
+    pub(crate) fn new(budget_usd: f64) -> Self {
+        Self {
+            budget_usd,
+            spent_usd: Mutex::new(0.0),
+        }
+    }
+
+    pub(crate) fn budget_usd(&self) -> f64 {
+        self.budget_usd
+    }
+
+    pub(crate) fn spent_usd(&self) -> f64 {
+        *self
+            .spent_usd
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Charges one call costing `cost_usd`. Returns `false`, charging
+    /// nothing, when the call would take spending past the budget.
+    pub(crate) fn charge(&self, cost_usd: f64) -> bool {
+        let mut spent_usd = self
+            .spent_usd
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if *spent_usd + cost_usd > self.budget_usd {
+            return false;
+        }
+        *spent_usd += cost_usd;
+        true
+    }
+}
@@ -1,17 +1,69 @@
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::Duration;
 use std::time::Instant;
 
 use crate::client_common::tools::ToolSpec;
+use crate::config::types::McpServerConfig;
 use crate::features::Feature;
 use crate::function_tool::FunctionCallError;
+use crate::mcp_connection_manager::ToolInfo;
+use crate::mcp_connection_manager::list_all_server_tools;
+use crate::mcp_connection_manager::list_server_tools;
 use crate::memories::usage::emit_metric_for_tool_read;
 use crate::protocol::SandboxPolicy;
 use crate::sandbox_tags::sandbox_tag;
+use crate::tools::compose::ComposeError;
+use crate::tools::compose::ComposeStep;
+use crate::tools::compose::ComposedToolHandler;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
 use crate::tools::context::ToolPayload;
+use crate::tools::discovery::DiscoveryError;
+use crate::tools::discovery::IntrospectError;
+use crate::tools::discovery::McpToolInfo;
+use crate::tools::discovery::dynamic_spec_for_tool;
+use crate::tools::discovery::pin_spec_hash;
+use crate::tools::discovery::spec_hash;
+use crate::tools::governance::GovernancePolicy;
+use crate::tools::governance::PolicyResult;
+use crate::tools::governance::redact_output;
+use crate::tools::governance::redact_payload;
+#[cfg(feature = "graphql-tools")]
+use crate::tools::graphql_introspection;
+#[cfg(feature = "graphql-tools")]
+use crate::tools::graphql_introspection::GraphQlLoadError;
+use crate::tools::handlers::DynamicToolHandler;
+use crate::tools::handlers::GraphQlHandler;
+use crate::tools::observability::Observability;
+use crate::tools::observability::ObservedCall;
+use crate::tools::output_cache::CachedToolOutput;
+use crate::tools::output_cache::ToolOutputCacheKey;
+use crate::tools::output_cache::workspace_fingerprint;
+use crate::tools::output_policy::ToolOutputPolicy;
+use crate::tools::output_transform::limit_tool_output_bytes;
+use crate::tools::output_transform::transform_tool_output;
+use crate::tools::quota::CostBudget;
+use crate::tools::quota::CostModel;
+use crate::tools::quota::TokenBudget;
+use crate::tools::resilience::Admission;
+use crate::tools::resilience::CircuitBreaker;
+use crate::tools::resilience::CircuitState;
+use crate::tools::resilience::ResiliencyPolicy;
+use crate::tools::resilience::circuit_open_error;
+use crate::tools::resilience::is_transient_failure;
+use crate::tools::resilience::timeout_error;
+use crate::tools::router::ToolCall;
+use crate::tools::schema_drift::SchemaDrift;
+use crate::tools::schema_drift::diff_input_schemas;
+use crate::tools::secret_redaction::redact_output_secrets;
+use crate::tools::secret_redaction::secrets_redacted_event;
+use crate::tools::spec::SHELL_TOOL_ALIASES;
+use crate::tools::spec::dynamic_tool_to_openai_tool;
+use crate::tools::spec::parse_tool_input_schema;
+use crate::tools::stream_output::resolve_streamed_output;
+use crate::tools::tool_middleware::ToolMiddleware;
 use async_trait::async_trait;
 use codex_hooks::HookEvent;
 use codex_hooks::HookEventAfterToolUse;
@@ -20,14 +72,24 @@ use codex_hooks::HookResult;
 use codex_hooks::HookToolInput;
 use codex_hooks::HookToolInputLocalShell;
 use codex_hooks::HookToolKind;
+use codex_protocol::dynamic_tools::DynamicToolSpec;
+use codex_protocol::models::FunctionCallOutputBody;
 use codex_protocol::models::ResponseInputItem;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::ToolCacheHitEvent;
+use codex_protocol::protocol::ToolOutputRedactedEvent;
+use codex_protocol::protocol::TurnDiffUpdatedEvent;
+use codex_rmcp_client::OAuthCredentialsStoreMode;
 use codex_utils_readiness::Readiness;
+use regex_lite::Regex;
+use serde_json::Value as JsonValue;
 use tracing::warn;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ToolKind {
     Function,
     Mcp,
+    GraphQL,
 }
 
 #[async_trait]
@@ -39,6 +101,7 @@ pub trait ToolHandler: Send + Sync {
             (self.kind(), payload),
             (ToolKind::Function, ToolPayload::Function { .. })
                 | (ToolKind::Mcp, ToolPayload::Mcp { .. })
+                | (ToolKind::GraphQL, ToolPayload::GraphQL { .. })
         )
     }
 
@@ -50,6 +113,22 @@ pub trait ToolHandler: Send + Sync {
         false
     }
 
+    /// Returns `true` if the tool has no side effects at all, so that dry runs
+    /// execute it like any other call.
+    fn is_pure(&self) -> bool {
+        false
+    }
+
+    /// Describes what a dry run of `invocation` would have executed, e.g. the
+    /// resolved command of a shell call. Only asked for tools that are not
+    /// [pure](ToolHandler::is_pure); MCP calls are described by the registry.
+    async fn dry_run_preview(
+        &self,
+        _invocation: &ToolInvocation,
+    ) -> Result<Option<JsonValue>, FunctionCallError> {
+        Ok(None)
+    }
+
     /// Perform the actual [ToolInvocation] and returns a [ToolOutput] containing
     /// the final output to return to the model.
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError>;
@@ -57,15 +136,587 @@ pub trait ToolHandler: Send + Sync {
 
 pub struct ToolRegistry {
     handlers: HashMap<String, Arc<dyn ToolHandler>>,
+    /// Breakers used by [`ToolRegistry::dispatch_resilient`], keyed by MCP
+    /// server name (or tool name for non-MCP tools).
+    circuit_breakers: std::sync::Mutex<HashMap<String, Arc<CircuitBreaker>>>,
+    /// Input schemas of the MCP tools this registry was built with, keyed by
+    /// fully-qualified tool name.
+    mcp_input_schemas: HashMap<String, JsonValue>,
+    /// Dynamic tools this registry was built with.
+    dynamic_tool_names: HashSet<String>,
+    /// Output limits from [`ConfiguredToolSpec::max_output_bytes`], keyed by
+    /// tool name.
+    max_output_bytes: HashMap<String, usize>,
+    /// Specs of the tools added by
+    /// [`ToolRegistry::load_from_graphql_introspection`].
+    #[cfg(feature = "graphql-tools")]
+    graphql_tool_specs: Vec<ConfiguredToolSpec>,
+    /// Dynamic tools added by [`ToolRegistry::get_or_register_dynamic_tool`]
+    /// after the registry was built, keyed by name.
+    runtime_dynamic_tools: std::sync::Mutex<HashMap<String, ConfiguredToolSpec>>,
+    /// Dynamic tools the registry was built with that were later removed by
+    /// [`ToolRegistry::remove_dynamic_tool`]. Locked after
+    /// `runtime_dynamic_tools` when both are held.
+    removed_dynamic_tools: std::sync::Mutex<HashSet<String>>,
+    /// Policies added by [`ToolRegistry::apply_governance_policy`], in the
+    /// order they are evaluated.
+    governance_policies: Vec<Arc<dyn GovernancePolicy>>,
+    /// Per-call prices from [`ConfiguredToolSpec::cost_model`], keyed by tool
+    /// name.
+    cost_models: HashMap<String, CostModel>,
+    /// Set by [`ToolRegistry::throttle_by_cost`].
+    cost_budget: Option<CostBudget>,
+    /// Tools declared [`ConfiguredToolSpec::cacheable`].
+    cacheable_tools: HashSet<String>,
+    /// Added by [`ToolRegistry::add_middleware`], outermost first.
+    middlewares: Vec<Arc<dyn ToolMiddleware>>,
+    /// Pipelines added by [`ToolRegistry::compose_tools`], keyed by name.
+    /// Their handlers are also in `handlers`.
+    composed_tools: HashMap<String, Arc<ComposedToolHandler>>,
 }
 
 impl ToolRegistry {
     pub fn new(handlers: HashMap<String, Arc<dyn ToolHandler>>) -> Self {
-        Self { handlers }
+        Self {
+            handlers,
+            circuit_breakers: std::sync::Mutex::new(HashMap::new()),
+            mcp_input_schemas: HashMap::new(),
+            dynamic_tool_names: HashSet::new(),
+            max_output_bytes: HashMap::new(),
+            #[cfg(feature = "graphql-tools")]
+            graphql_tool_specs: Vec::new(),
+            runtime_dynamic_tools: std::sync::Mutex::new(HashMap::new()),
+            removed_dynamic_tools: std::sync::Mutex::new(HashSet::new()),
+            governance_policies: Vec::new(),
+            cost_models: HashMap::new(),
+            cost_budget: None,
+            cacheable_tools: HashSet::new(),
+            middlewares: Vec::new(),
+            composed_tools: HashMap::new(),
+        }
+    }
+
+    /// Evaluates `policy` before every dispatch, after any policies applied
+    /// earlier. A denied call fails with the policy's reason and is not run;
+    /// later policies see the arguments as redacted by earlier ones. Output
+    /// is redacted with the patterns of every policy that redacted the call,
+    /// and [`EventMsg::ToolOutputRedacted`] reports how much was removed.
+    pub fn apply_governance_policy(&mut self, policy: Arc<dyn GovernancePolicy>) {
+        self.governance_policies.push(policy);
+    }
+
+    /// Wraps every dispatch in `middleware`, inside any middleware added
+    /// earlier: `before` hooks run in the order middleware was added, and
+    /// `after` hooks in reverse. Middleware runs ahead of governance
+    /// policies, so a policy sees the arguments as middleware rewrote them.
+    pub fn add_middleware(&mut self, middleware: Arc<dyn ToolMiddleware>) {
+        self.middlewares.push(middleware);
+    }
+
+    /// Caps the output of `tool_name` at `max_bytes`; see
+    /// [`ConfiguredToolSpec::max_output_bytes`].
+    pub fn set_max_output_size(&mut self, tool_name: impl Into<String>, max_bytes: usize) {
+        self.max_output_bytes.insert(tool_name.into(), max_bytes);
+    }
+
+    /// Output limit of every tool that has one, keyed by tool name.
+    pub fn max_output_size_per_tool(&self) -> &HashMap<String, usize> {
+        &self.max_output_bytes
+    }
+
+    /// Prices calls to `tool_name`; see [`ConfiguredToolSpec::cost_model`].
+    pub fn set_cost_model(&mut self, tool_name: impl Into<String>, cost_model: CostModel) {
+        self.cost_models.insert(tool_name.into(), cost_model);
+    }
+
+    /// Lets read-only calls to `tool_name` be answered from the session's
+    /// tool output cache; see [`ConfiguredToolSpec::cacheable`].
+    pub fn set_cacheable(&mut self, tool_name: impl Into<String>) {
+        self.cacheable_tools.insert(tool_name.into());
+    }
+
+    /// Charges every call to a priced tool against `session_budget_usd`.
+    /// Once a call would take spending past the budget, it fails with
+    /// [`FunctionCallError::BudgetExhausted`] instead of running. Tools
+    /// without a cost model are never throttled, and cached results cost
+    /// nothing.
+    pub fn throttle_by_cost(&mut self, session_budget_usd: f64) {
+        self.cost_budget = Some(CostBudget::new(session_budget_usd));
+    }
+
+    /// Money charged so far under [`ToolRegistry::throttle_by_cost`].
+    pub fn cost_spent_usd(&self) -> f64 {
+        self.cost_budget.as_ref().map_or(0.0, CostBudget::spent_usd)
+    }
+
+    /// Diffs the registered schemas of `server_name`'s tools against the
+    /// server's live tool list, keyed by fully-qualified tool name as returned
+    /// by `McpConnectionManager::list_all_tools`.
+    pub(crate) fn validate_mcp_schema(
+        &self,
+        server_name: &str,
+        live_tools: &HashMap<String, ToolInfo>,
+    ) -> Vec<SchemaDrift> {
+        let mut live_tools: Vec<(&String, &ToolInfo)> = live_tools
+            .iter()
+            .filter(|(_, info)| info.server_name == server_name)
+            .collect();
+        live_tools.sort_by(|a, b| a.0.cmp(b.0));
+
+        live_tools
+            .into_iter()
+            .filter_map(|(name, info)| {
+                let registered = self.mcp_input_schemas.get(name)?;
+                let live = JsonValue::Object(info.tool.input_schema.as_ref().clone());
+                Some(diff_input_schemas(name, registered, &live))
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// Registers a plain closure as a function tool. The closure receives the
+    /// raw JSON arguments; `Ok` output is returned to the model as a successful
+    /// result and `Err` output as a failed one.
+    ///
+    /// Fails if `schema` is not a valid tool input schema or if a handler is
+    /// already registered under `name`.
+    pub fn register_function_tool(
+        &mut self,
+        name: &str,
+        schema: serde_json::Value,
+        f: impl Fn(&str) -> Result<String, String> + Send + Sync + 'static,
+    ) -> anyhow::Result<()> {
+        parse_tool_input_schema(&schema)
+            .map_err(|err| anyhow::anyhow!("invalid input schema for tool {name}: {err}"))?;
+        if self.handlers.contains_key(name) {
+            anyhow::bail!("a handler is already registered for tool {name}");
+        }
+        let handler = FunctionToolHandler {
+            name: name.to_string(),
+            f: Box::new(f),
+        };
+        self.handlers.insert(name.to_string(), Arc::new(handler));
+        Ok(())
+    }
+
+    /// Registers `name` as a tool that executes [`ToolPayload::GraphQL`]
+    /// calls. Fails if a handler is already registered under `name`.
+    pub fn register_graphql_tool(&mut self, name: &str) -> anyhow::Result<()> {
+        if self.handlers.contains_key(name) {
+            anyhow::bail!("a handler is already registered for tool {name}");
+        }
+        self.handlers
+            .insert(name.to_string(), Arc::new(GraphQlHandler));
+        Ok(())
+    }
+
+    /// Introspects the GraphQL schema at `endpoint` and registers one
+    /// function tool per query and mutation, named after the operation. Each
+    /// tool's input schema is derived from the operation's arguments, and
+    /// calls are sent to `endpoint` with `headers` and the arguments as
+    /// variables. Mutations are treated as mutating calls.
+    ///
+    /// Returns the number of tools registered. Nothing is registered if any
+    /// operation's name is already taken.
+    #[cfg(feature = "graphql-tools")]
+    pub async fn load_from_graphql_introspection(
+        &mut self,
+        endpoint: &str,
+        headers: HashMap<String, String>,
+    ) -> Result<usize, GraphQlLoadError> {
+        let operations = graphql_introspection::introspect(endpoint, &headers).await?;
+        let mut seen = std::collections::HashSet::new();
+        for operation in &operations {
+            if self.handlers.contains_key(&operation.name) || !seen.insert(&operation.name) {
+                return Err(GraphQlLoadError::DuplicateTool(operation.name.clone()));
+            }
+        }
+        let count = operations.len();
+        for operation in operations {
+            let name = operation.name.clone();
+            self.graphql_tool_specs
+                .push(ConfiguredToolSpec::new(operation.spec.clone(), false));
+            self.handlers.insert(
+                name,
+                Arc::new(operation.into_handler(endpoint, headers.clone())),
+            );
+        }
+        Ok(count)
+    }
+
+    /// Specs of the tools registered by
+    /// [`ToolRegistry::load_from_graphql_introspection`], to be offered to
+    /// the model alongside the router's own.
+    #[cfg(feature = "graphql-tools")]
+    pub fn graphql_tool_specs(&self) -> &[ConfiguredToolSpec] {
+        &self.graphql_tool_specs
+    }
+
+    /// Registers `name` as a pipeline that runs `steps` in order, passing each
+    /// step's output to the next through its argument template. Every step
+    /// must name an already-registered function tool.
+    pub fn compose_tools(
+        &mut self,
+        name: &str,
+        steps: Vec<ComposeStep>,
+    ) -> Result<(), ComposeError> {
+        if steps.is_empty() {
+            return Err(ComposeError::EmptyPipeline(name.to_string()));
+        }
+        if self.handlers.contains_key(name) {
+            return Err(ComposeError::DuplicateTool(name.to_string()));
+        }
+        let mut resolved = Vec::with_capacity(steps.len());
+        for (index, step) in steps.into_iter().enumerate() {
+            let Some(handler) = self.handler(&step.tool_name) else {
+                return Err(ComposeError::UnknownTool {
+                    name: name.to_string(),
+                    index,
+                    tool_name: step.tool_name,
+                });
+            };
+            if handler.kind() != ToolKind::Function {
+                return Err(ComposeError::UnsupportedTool {
+                    name: name.to_string(),
+                    index,
+                    tool_name: step.tool_name,
+                });
+            }
+            resolved.push((step, handler));
+        }
+        let handler = Arc::new(ComposedToolHandler::new(name.to_string(), resolved));
+        self.handlers.insert(
+            name.to_string(),
+            Arc::clone(&handler) as Arc<dyn ToolHandler>,
+        );
+        self.composed_tools.insert(name.to_string(), handler);
+        Ok(())
+    }
+
+    /// Starts `server`, lists the tools it exposes, and returns them as
+    /// dynamic tool specs to register alongside the static ones (see
+    /// [`ToolRouter::from_config`]). The server's `enabled_tools` and
+    /// `disabled_tools` filters apply.
+    ///
+    /// The hash of the discovered specs is pinned per `server_name`; when a
+    /// later discovery, e.g. after a reconnect, yields different specs, a
+    /// warning is logged.
+    ///
+    /// [`ToolRouter::from_config`]: crate::tools::router::ToolRouter::from_config
+    pub async fn discover_tools(
+        server_name: &str,
+        server: &McpServerConfig,
+    ) -> Result<Vec<DynamicToolSpec>, DiscoveryError> {
+        if !server.enabled {
+            return Err(DiscoveryError::Disabled {
+                server_name: server_name.to_string(),
+            });
+        }
+        let tools = list_server_tools(server_name, server, OAuthCredentialsStoreMode::default())
+            .await
+            .map_err(|err| DiscoveryError::ListTools {
+                server_name: server_name.to_string(),
+                message: err.to_string(),
+            })?;
+        let specs: Vec<DynamicToolSpec> = tools.into_iter().map(dynamic_spec_for_tool).collect();
+        pin_spec_hash(server_name, spec_hash(&specs));
+        Ok(specs)
+    }
+
+    /// Lists every tool `server` currently provides, with its schemas. Unlike
+    /// [`ToolRegistry::discover_tools`], the result ignores the server's
+    /// `enabled` flag and tool filters, so it can be compared with what is
+    /// registered to spot newly available tools and schema drift.
+    pub async fn introspect_mcp_server(
+        server_id: &str,
+        server: &McpServerConfig,
+    ) -> Result<Vec<McpToolInfo>, IntrospectError> {
+        let tools = list_all_server_tools(server_id, server, OAuthCredentialsStoreMode::default())
+            .await
+            .map_err(|err| IntrospectError::ListTools {
+                server_name: server_id.to_string(),
+                message: err.to_string(),
+            })?;
+        Ok(tools.into_iter().map(McpToolInfo::from).collect())
     }
 
     pub fn handler(&self, name: &str) -> Option<Arc<dyn ToolHandler>> {
-        self.handlers.get(name).map(Arc::clone)
+        if let Some(handler) = self.handlers.get(name)
+            && !self.is_removed_dynamic_tool(name)
+        {
+            return Some(Arc::clone(handler));
+        }
+        self.lock_runtime_dynamic_tools()
+            .contains_key(name)
+            .then(|| Arc::new(DynamicToolHandler) as Arc<dyn ToolHandler>)
+    }
+
+    /// Registers `spec` as a dynamic tool unless a tool with its name already
+    /// exists, in which case the existing tool is returned and `spec` is
+    /// ignored. The check and the registration happen under one lock, so
+    /// concurrent callers register each name at most once.
+    pub(crate) fn get_or_register_dynamic_tool(
+        &self,
+        spec: DynamicToolSpec,
+    ) -> Result<ToolRef, RegistrationError> {
+        if self.has_built_handler(&spec.name) {
+            return Ok(ToolRef {
+                name: spec.name,
+                newly_registered: false,
+            });
+        }
+        let mut tools = self.lock_runtime_dynamic_tools();
+        if tools.contains_key(&spec.name) {
+            return Ok(ToolRef {
+                name: spec.name,
+                newly_registered: false,
+            });
+        }
+        let tool =
+            dynamic_tool_to_openai_tool(&spec).map_err(|err| RegistrationError::InvalidSchema {
+                name: spec.name.clone(),
+                message: err.to_string(),
+            })?;
+        tools.insert(
+            spec.name.clone(),
+            ConfiguredToolSpec::new(ToolSpec::Function(tool), false),
+        );
+        Ok(ToolRef {
+            name: spec.name,
+            newly_registered: true,
+        })
+    }
+
+    /// Registers `spec` as a dynamic tool, failing if a tool with its name
+    /// already exists.
+    pub(crate) fn add_dynamic_tool(&self, spec: DynamicToolSpec) -> Result<(), RegistrationError> {
+        let mut tools = self.lock_runtime_dynamic_tools();
+        if tools.contains_key(&spec.name) || self.has_built_handler(&spec.name) {
+            return Err(RegistrationError::AlreadyRegistered { name: spec.name });
+        }
+        let tool =
+            dynamic_tool_to_openai_tool(&spec).map_err(|err| RegistrationError::InvalidSchema {
+                name: spec.name.clone(),
+                message: err.to_string(),
+            })?;
+        tools.insert(
+            spec.name,
+            ConfiguredToolSpec::new(ToolSpec::Function(tool), false),
+        );
+        Ok(())
+    }
+
+    /// Unregisters the dynamic tool `name`, whether it was registered when
+    /// the registry was built or later. Returns whether it was registered.
+    /// Calls that already resolved the tool's handler run to completion.
+    pub(crate) fn remove_dynamic_tool(&self, name: &str) -> bool {
+        let mut tools = self.lock_runtime_dynamic_tools();
+        if tools.remove(name).is_some() {
+            return true;
+        }
+        self.dynamic_tool_names.contains(name)
+            && self
+                .removed_dynamic_tools
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .insert(name.to_string())
+    }
+
+    /// Whether `name` is a dynamic tool the registry was built with that has
+    /// since been removed. A tool added again under the same name is a
+    /// runtime dynamic tool, so this stays true for it.
+    pub(crate) fn is_removed_dynamic_tool(&self, name: &str) -> bool {
+        self.removed_dynamic_tools
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .contains(name)
+    }
+
+    fn has_built_handler(&self, name: &str) -> bool {
+        self.handlers.contains_key(name) && !self.is_removed_dynamic_tool(name)
+    }
+
+    /// Specs of the tools added by
+    /// [`ToolRegistry::get_or_register_dynamic_tool`], sorted by name.
+    pub(crate) fn runtime_dynamic_tool_specs(&self) -> Vec<ConfiguredToolSpec> {
+        let tools = self.lock_runtime_dynamic_tools();
+        let mut specs: Vec<ConfiguredToolSpec> = tools.values().cloned().collect();
+        specs.sort_by(|a, b| a.spec.name().cmp(b.spec.name()));
+        specs
+    }
+
+    /// Whether `name` is a dynamic tool, whether it was registered when the
+    /// registry was built or later.
+    pub(crate) fn is_dynamic_tool(&self, name: &str) -> bool {
+        (self.dynamic_tool_names.contains(name) && !self.is_removed_dynamic_tool(name))
+            || self.lock_runtime_dynamic_tools().contains_key(name)
+    }
+
+    fn lock_runtime_dynamic_tools(
+        &self,
+    ) -> std::sync::MutexGuard<'_, HashMap<String, ConfiguredToolSpec>> {
+        self.runtime_dynamic_tools
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Dispatches `invocation`, retrying transient failures and guarding the
+    /// backend with a circuit breaker.
+    ///
+    /// Closed circuits retry transient failures with backoff until the retry
+    /// budget is spent or the circuit trips. Open circuits fail fast without
+    /// dispatching. A half-open circuit lets exactly one probe through, which is
+    /// never retried; its outcome closes or re-opens the circuit.
+    pub async fn dispatch_resilient(
+        &self,
+        invocation: ToolInvocation,
+        policy: ResiliencyPolicy,
+    ) -> Result<ResponseInputItem, FunctionCallError> {
+        let key = match &invocation.payload {
+            ToolPayload::Mcp { server, .. } => server.clone(),
+            _ => invocation.tool_name.clone(),
+        };
+        let breaker = self.circuit_breaker(&key);
+
+        let mut retry = 0;
+        loop {
+            let admission = breaker.admit(tokio::time::Instant::now());
+            if admission == Admission::Rejected {
+                return Err(circuit_open_error(&key));
+            }
+
+            let attempt = self.dispatch(invocation.clone());
+            let (result, transient) = match policy.timeout.per_attempt {
+                Some(timeout) => match tokio::time::timeout(timeout, attempt).await {
+                    Ok(result) => {
+                        let transient = is_transient_failure(&result);
+                        (result, transient)
+                    }
+                    Err(_) => (Err(timeout_error(&key, timeout)), true),
+                },
+                None => {
+                    let result = attempt.await;
+                    let transient = is_transient_failure(&result);
+                    (result, transient)
+                }
+            };
+
+            if !transient {
+                if matches!(result, Err(FunctionCallError::Fatal(_))) {
+                    breaker.record_failure(&policy.circuit_breaker, tokio::time::Instant::now());
+                } else {
+                    breaker.record_success();
+                }
+                return result;
+            }
+
+            breaker.record_failure(&policy.circuit_breaker, tokio::time::Instant::now());
+            let circuit_closed = matches!(breaker.state(), CircuitState::Closed { .. });
+            if admission == Admission::Probe || !circuit_closed || retry >= policy.retry.max_retries
+            {
+                return result;
+            }
+
+            retry += 1;
+            let delay = policy.retry.backoff(retry);
+            warn!(
+                "retrying {key} after transient failure ({retry}/{}) in {delay:?}",
+                policy.retry.max_retries
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Dispatches `invocation`, emitting its span, duration histogram, and
+    /// structured log event through `obs`, on failure as well as success.
+    pub async fn dispatch_with_observability(
+        &self,
+        invocation: ToolInvocation,
+        obs: &Observability,
+    ) -> Result<ResponseInputItem, FunctionCallError> {
+        let call = ObservedCall::for_invocation(&invocation);
+        obs.observe(&call, self.dispatch(invocation)).await
+    }
+
+    /// Dispatches `invocation` and charges its output against `budget`.
+    ///
+    /// Output that does not fit in the remaining budget is truncated rather
+    /// than failed; once the budget is spent only a truncation marker is
+    /// returned.
+    pub async fn dispatch_with_quota_check(
+        &self,
+        invocation: ToolInvocation,
+        budget: &mut TokenBudget,
+    ) -> Result<ResponseInputItem, FunctionCallError> {
+        let response = self.dispatch(invocation).await?;
+        Ok(budget.charge(response))
+    }
+
+    /// Names of every tool with a registered handler.
+    pub(crate) fn handler_names(&self) -> Vec<String> {
+        self.handlers.keys().cloned().collect()
+    }
+
+    /// State of every circuit breaker created so far, keyed as in
+    /// [`ToolRegistry::dispatch_resilient`].
+    pub(crate) fn circuit_states(&self) -> Vec<(String, CircuitState)> {
+        self.lock_circuit_breakers()
+            .iter()
+            .map(|(key, breaker)| (key.clone(), breaker.state()))
+            .collect()
+    }
+
+    pub(crate) fn circuit_state(&self, key: &str) -> Option<CircuitState> {
+        self.lock_circuit_breakers()
+            .get(key)
+            .map(|breaker| breaker.state())
+    }
+
+    /// Returns the patterns to redact from the call's output.
+    fn apply_governance(
+        &self,
+        invocation: &mut ToolInvocation,
+    ) -> Result<Vec<Regex>, FunctionCallError> {
+        let mut output_patterns = Vec::new();
+        for policy in &self.governance_policies {
+            let call = ToolCall {
+                tool_name: invocation.tool_name.clone(),
+                call_id: invocation.call_id.clone(),
+                payload: invocation.payload.clone(),
+                tags: HashMap::new(),
+                dry_run: invocation.dry_run,
+            };
+            match policy.evaluate(&call) {
+                PolicyResult::Allow => {}
+                PolicyResult::Deny { reason } => {
+                    return Err(FunctionCallError::RespondToModel(format!(
+                        "tool call denied by policy: {reason}"
+                    )));
+                }
+                PolicyResult::Redact { patterns } => {
+                    invocation.payload = redact_payload(call.payload, &patterns);
+                    output_patterns.extend(patterns);
+                }
+            }
+        }
+        Ok(output_patterns)
+    }
+
+    fn circuit_breaker(&self, key: &str) -> Arc<CircuitBreaker> {
+        Arc::clone(
+            self.lock_circuit_breakers()
+                .entry(key.to_string())
+                .or_default(),
+        )
+    }
+
+    fn lock_circuit_breakers(
+        &self,
+    ) -> std::sync::MutexGuard<'_, HashMap<String, Arc<CircuitBreaker>>> {
+        match self.circuit_breakers.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
     }
 
     // TODO(jif) for dynamic tools.
@@ -78,8 +729,36 @@ impl ToolRegistry {
 
     pub async fn dispatch(
         &self,
-        invocation: ToolInvocation,
+        mut invocation: ToolInvocation,
+    ) -> Result<ResponseInputItem, FunctionCallError> {
+        if self.middlewares.is_empty() {
+            return self.dispatch_to_handler(invocation).await;
+        }
+
+        let mut entered = 0;
+        let mut rejection = None;
+        for middleware in &self.middlewares {
+            entered += 1;
+            if let Err(err) = middleware.before(&mut invocation).await {
+                rejection = Some(err);
+                break;
+            }
+        }
+        let mut result = match rejection {
+            Some(err) => Err(err),
+            None => self.dispatch_to_handler(invocation.clone()).await,
+        };
+        for middleware in self.middlewares[..entered].iter().rev() {
+            result = middleware.after(&invocation, result).await;
+        }
+        result
+    }
+
+    async fn dispatch_to_handler(
+        &self,
+        mut invocation: ToolInvocation,
     ) -> Result<ResponseInputItem, FunctionCallError> {
+        let redact_patterns = self.apply_governance(&mut invocation)?;
         let tool_name = invocation.tool_name.clone();
         let call_id_owned = invocation.call_id.clone();
         let otel = invocation.turn.session_telemetry.clone();
@@ -118,7 +797,7 @@ impl ToolRegistry {
         let mcp_server_ref = mcp_server.as_deref();
         let mcp_server_origin_ref = mcp_server_origin.as_deref();
 
-        {
+        if !invocation.dry_run {
             let mut active = invocation.session.active_turn.lock().await;
             if let Some(active_turn) = active.as_mut() {
                 let mut turn_state = active_turn.turn_state.lock().await;
@@ -162,9 +841,90 @@ impl ToolRegistry {
             return Err(FunctionCallError::Fatal(message));
         }
 
+        if invocation.dry_run {
+            check_arguments_parse(&invocation.payload)?;
+            if !handler.is_pure() {
+                let preview = match &invocation.payload {
+                    ToolPayload::Mcp {
+                        server,
+                        tool,
+                        raw_arguments,
+                    } => Some(serde_json::json!({
+                        "server": server,
+                        "tool": tool,
+                        "arguments": serde_json::from_str::<JsonValue>(raw_arguments)
+                            .unwrap_or_default(),
+                    })),
+                    _ => handler.dry_run_preview(&invocation).await?,
+                };
+                let body = match preview {
+                    Some(preview) => format!(
+                        "dry run: {tool_name} call is valid and was not executed; it would run {preview}"
+                    ),
+                    None => format!("dry run: {tool_name} call is valid and was not executed"),
+                };
+                let output = ToolOutput::Function {
+                    body: FunctionCallOutputBody::Text(body),
+                    success: Some(true),
+                };
+                return Ok(output.into_response(&call_id_owned, &payload_for_response));
+            }
+        }
+
         let is_mutating = handler.is_mutating(&invocation).await;
+        let output_cache = invocation.session.tool_output_cache().await;
+        let cache_key = if output_cache.is_some()
+            && !is_mutating
+            && self.cacheable_tools.contains(&tool_name)
+        {
+            ToolOutputCacheKey::for_call(
+                &tool_name,
+                &invocation.payload,
+                workspace_fingerprint(&invocation.turn.cwd).await,
+            )
+        } else {
+            None
+        };
+        let cached = match (&output_cache, &cache_key) {
+            (Some(cache), Some(key)) => {
+                let cached = cache.get(key);
+                otel.tool_cache_lookup(&tool_name, cached.is_some());
+                cached
+            }
+            _ => None,
+        };
+        if let Some(cached) = cached {
+            let age_ms = u64::try_from(cached.stored_at.elapsed().as_millis()).unwrap_or(u64::MAX);
+            invocation
+                .session
+                .send_event(
+                    &invocation.turn,
+                    EventMsg::ToolCacheHit(ToolCacheHitEvent {
+                        call_id: call_id_owned.clone(),
+                        tool_name: tool_name.clone(),
+                        age_ms,
+                    }),
+                )
+                .await;
+            return Ok(cached
+                .output
+                .into_response(&call_id_owned, &payload_for_response));
+        }
+        if let (Some(budget), Some(cost_model)) =
+            (&self.cost_budget, self.cost_models.get(&tool_name))
+            && !budget.charge(cost_model.cost_per_call_usd)
+        {
+            return Err(FunctionCallError::BudgetExhausted {
+                tool_name: tool_name.clone(),
+                budget_usd: budget.budget_usd(),
+            });
+        }
+        if is_mutating && let Some(cache) = &output_cache {
+            cache.clear();
+        }
         let output_cell = tokio::sync::Mutex::new(None);
         let invocation_for_tool = invocation.clone();
+        let composed = self.composed_tools.get(&tool_name).cloned();
 
         let started = Instant::now();
         let result = otel
@@ -184,7 +944,11 @@ impl ToolRegistry {
                             invocation_for_tool.turn.tool_call_gate.wait_ready().await;
                             tracing::trace!("tool gate released");
                         }
-                        match handler.handle(invocation_for_tool).await {
+                        let result = match composed {
+                            Some(composed) => composed.run(self, invocation_for_tool).await,
+                            None => handler.handle(invocation_for_tool).await,
+                        };
+                        match result {
                             Ok(output) => {
                                 let preview = output.log_preview();
                                 let success = output.success_for_logging();
@@ -204,6 +968,9 @@ impl ToolRegistry {
             Err(err) => (err.to_string(), false),
         };
         emit_metric_for_tool_read(&invocation, success).await;
+        if is_mutating {
+            emit_turn_diff_update(&invocation).await;
+        }
         let hook_abort_error = dispatch_after_tool_use_hook(AfterToolUseHookDispatch {
             invocation: &invocation,
             output_preview,
@@ -224,17 +991,176 @@ impl ToolRegistry {
                 let output = guard.take().ok_or_else(|| {
                     FunctionCallError::Fatal("tool produced no output".to_string())
                 })?;
+                let output = resolve_streamed_output(output).await;
+                let transformers = invocation.session.tool_output_transformers().await;
+                let output = transform_tool_output(&transformers, &tool_name, output);
+                let (output, bytes_redacted) = redact_output(output, &redact_patterns);
+                if bytes_redacted > 0 {
+                    invocation
+                        .session
+                        .send_event(
+                            &invocation.turn,
+                            EventMsg::ToolOutputRedacted(ToolOutputRedactedEvent {
+                                call_id: call_id_owned.clone(),
+                                bytes_redacted,
+                            }),
+                        )
+                        .await;
+                }
+                let output = if invocation.turn.features.enabled(Feature::SecretsRedaction) {
+                    let (output, found) = redact_output_secrets(output);
+                    if let Some(event) = secrets_redacted_event(&call_id_owned, &found) {
+                        invocation.session.send_event(&invocation.turn, event).await;
+                    }
+                    output
+                } else {
+                    output
+                };
+                let output = match self.max_output_bytes.get(&tool_name) {
+                    Some(&max_bytes) => {
+                        let keep_tail = is_shell_output(&tool_name, &payload_for_response);
+                        limit_tool_output_bytes(output, max_bytes, keep_tail)
+                    }
+                    None => output,
+                };
+                let output = match (output_policy(&invocation, &tool_name), output) {
+                    (
+                        Some(policy),
+                        ToolOutput::Function {
+                            body: FunctionCallOutputBody::Text(text),
+                            success,
+                        },
+                    ) => ToolOutput::Function {
+                        body: FunctionCallOutputBody::Text(
+                            policy
+                                .apply(&invocation.session, &invocation.turn, &call_id_owned, text)
+                                .await,
+                        ),
+                        success,
+                    },
+                    (_, output) => output,
+                };
+                if let (Some(cache), Some(key)) = (&output_cache, cache_key)
+                    && output.success_for_logging()
+                {
+                    cache.insert(
+                        key,
+                        CachedToolOutput {
+                            output: output.clone(),
+                            stored_at: Instant::now(),
+                        },
+                    );
+                }
                 Ok(output.into_response(&call_id_owned, &payload_for_response))
             }
+            Err(FunctionCallError::RespondToModel(message)) => {
+                let message = match output_policy(&invocation, &tool_name) {
+                    Some(policy) => {
+                        policy
+                            .apply(
+                                &invocation.session,
+                                &invocation.turn,
+                                &call_id_owned,
+                                message,
+                            )
+                            .await
+                    }
+                    None => message,
+                };
+                Err(FunctionCallError::RespondToModel(message))
+            }
             Err(err) => Err(err),
         }
     }
 }
 
+/// Reports the turn's per-file changes after a call that may have changed
+/// files, unless they are the same as in the last report.
+async fn emit_turn_diff_update(invocation: &ToolInvocation) {
+    let Some(files) = invocation.tracker.lock().await.file_stats_if_changed() else {
+        return;
+    };
+    invocation
+        .session
+        .send_event(
+            &invocation.turn,
+            EventMsg::TurnDiffUpdated(TurnDiffUpdatedEvent {
+                turn_id: invocation.turn.sub_id.clone(),
+                files,
+            }),
+        )
+        .await;
+}
+
+/// Rejects JSON-argument payloads whose arguments do not parse, which every
+/// such handler would do before running.
+fn check_arguments_parse(payload: &ToolPayload) -> Result<(), FunctionCallError> {
+    let arguments = match payload {
+        ToolPayload::Function { arguments } => arguments,
+        ToolPayload::Mcp { raw_arguments, .. } if !raw_arguments.trim().is_empty() => raw_arguments,
+        _ => return Ok(()),
+    };
+    serde_json::from_str::<JsonValue>(arguments)
+        .map(|_| ())
+        .map_err(|err| {
+            FunctionCallError::RespondToModel(format!("failed to parse function arguments: {err}"))
+        })
+}
+
+/// The `[tool_output]` policy for `tool_name`, when one is configured.
+fn output_policy(invocation: &ToolInvocation, tool_name: &str) -> Option<ToolOutputPolicy> {
+    ToolOutputPolicy::for_tool(
+        &invocation.turn,
+        invocation.session.conversation_id,
+        tool_name,
+    )
+}
+
+/// Whether `tool_name` returns terminal output, whose last lines (exit
+/// status, errors) matter as much as its first.
+fn is_shell_output(tool_name: &str, payload: &ToolPayload) -> bool {
+    matches!(payload, ToolPayload::LocalShell { .. })
+        || SHELL_TOOL_ALIASES.contains(&tool_name)
+        || matches!(tool_name, "shell_command" | "exec_command" | "write_stdin")
+}
+
+/// A tool returned by [`ToolRouter::get_or_register_dynamic_tool`].
+///
+/// [`ToolRouter::get_or_register_dynamic_tool`]: crate::tools::router::ToolRouter::get_or_register_dynamic_tool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolRef {
+    pub name: String,
+    /// Whether this call registered the tool, rather than finding it.
+    pub newly_registered: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RegistrationError {
+    #[error("invalid input schema for dynamic tool {name}: {message}")]
+    InvalidSchema { name: String, message: String },
+    #[error("a tool named {name} is already registered")]
+    AlreadyRegistered { name: String },
+}
+
 #[derive(Debug, Clone)]
 pub struct ConfiguredToolSpec {
     pub spec: ToolSpec,
     pub supports_parallel_tool_calls: bool,
+    /// Results longer than this are truncated and end with a
+    /// `[truncated: N bytes omitted]` marker. Shell output loses its middle
+    /// instead, so both the first and last lines survive.
+    pub max_output_bytes: Option<usize>,
+    /// Tools this tool calls internally, e.g. `read_file` and `write_file`
+    /// for a refactoring tool.
+    pub tool_depends_on: Vec<String>,
+    /// What each call costs, for tools billed per call. Only charged once
+    /// [`ToolRegistry::throttle_by_cost`] is enabled.
+    pub cost_model: Option<CostModel>,
+    /// Whether the output of a read-only call depends only on the call's
+    /// arguments and the workspace, so an identical later call in the same
+    /// session can reuse it. Only consulted when the session has a tool
+    /// output cache.
+    pub cacheable: bool,
 }
 
 impl ConfiguredToolSpec {
@@ -242,13 +1168,39 @@ impl ConfiguredToolSpec {
         Self {
             spec,
             supports_parallel_tool_calls,
+            max_output_bytes: None,
+            tool_depends_on: Vec::new(),
+            cost_model: None,
+            cacheable: false,
         }
     }
+
+    pub fn with_tool_depends_on(mut self, tool_depends_on: Vec<String>) -> Self {
+        self.tool_depends_on = tool_depends_on;
+        self
+    }
+
+    pub fn with_max_output_bytes(mut self, max_output_bytes: Option<usize>) -> Self {
+        self.max_output_bytes = max_output_bytes;
+        self
+    }
+
+    pub fn with_cost_model(mut self, cost_model: Option<CostModel>) -> Self {
+        self.cost_model = cost_model;
+        self
+    }
+
+    pub fn with_cacheable(mut self, cacheable: bool) -> Self {
+        self.cacheable = cacheable;
+        self
+    }
 }
 
 pub struct ToolRegistryBuilder {
     handlers: HashMap<String, Arc<dyn ToolHandler>>,
     specs: Vec<ConfiguredToolSpec>,
+    mcp_input_schemas: HashMap<String, JsonValue>,
+    dynamic_tool_names: HashSet<String>,
 }
 
 impl ToolRegistryBuilder {
@@ -256,6 +1208,8 @@ impl ToolRegistryBuilder {
         Self {
             handlers: HashMap::new(),
             specs: Vec::new(),
+            mcp_input_schemas: HashMap::new(),
+            dynamic_tool_names: HashSet::new(),
         }
     }
 
@@ -272,6 +1226,17 @@ impl ToolRegistryBuilder {
             .push(ConfiguredToolSpec::new(spec, supports_parallel_tool_calls));
     }
 
+    /// Like [`ToolRegistryBuilder::push_spec_with_parallel_support`], for a
+    /// tool whose read-only calls may be answered from the tool output cache.
+    pub fn push_cacheable_spec_with_parallel_support(
+        &mut self,
+        spec: ToolSpec,
+        supports_parallel_tool_calls: bool,
+    ) {
+        self.specs
+            .push(ConfiguredToolSpec::new(spec, supports_parallel_tool_calls).with_cacheable(true));
+    }
+
     pub fn register_handler(&mut self, name: impl Into<String>, handler: Arc<dyn ToolHandler>) {
         let name = name.into();
         if self
@@ -301,12 +1266,79 @@ impl ToolRegistryBuilder {
     //     }
     // }
 
+    /// The spec pushed under `name`, if any.
+    pub(crate) fn spec_mut(&mut self, name: &str) -> Option<&mut ConfiguredToolSpec> {
+        self.specs
+            .iter_mut()
+            .find(|configured| configured.spec.name() == name)
+    }
+
+    /// Unregisters and returns the handler for `name`.
+    pub(crate) fn take_handler(&mut self, name: &str) -> Option<Arc<dyn ToolHandler>> {
+        self.handlers.remove(name)
+    }
+
+    /// Remembers the schema an MCP tool was registered with so drift can be
+    /// detected later via [`ToolRegistry::validate_mcp_schema`].
+    pub(crate) fn record_mcp_input_schema(&mut self, name: impl Into<String>, schema: JsonValue) {
+        self.mcp_input_schemas.insert(name.into(), schema);
+    }
+
+    /// Marks `name` as a dynamic tool; see [`ToolRegistry::is_dynamic_tool`].
+    pub(crate) fn record_dynamic_tool(&mut self, name: impl Into<String>) {
+        self.dynamic_tool_names.insert(name.into());
+    }
+
     pub fn build(self) -> (Vec<ConfiguredToolSpec>, ToolRegistry) {
-        let registry = ToolRegistry::new(self.handlers);
+        let mut registry = ToolRegistry::new(self.handlers);
+        registry.mcp_input_schemas = self.mcp_input_schemas;
+        registry.dynamic_tool_names = self.dynamic_tool_names;
+        for config in &self.specs {
+            if let Some(max_bytes) = config.max_output_bytes {
+                registry.set_max_output_size(config.spec.name(), max_bytes);
+            }
+            if let Some(cost_model) = config.cost_model {
+                registry.set_cost_model(config.spec.name(), cost_model);
+            }
+            if config.cacheable {
+                registry.set_cacheable(config.spec.name());
+            }
+        }
         (self.specs, registry)
     }
 }
 
+type FunctionToolFn = dyn Fn(&str) -> Result<String, String> + Send + Sync;
+
+/// Adapts a closure registered through [`ToolRegistry::register_function_tool`].
+struct FunctionToolHandler {
+    name: String,
+    f: Box<FunctionToolFn>,
+}
+
+#[async_trait]
+impl ToolHandler for FunctionToolHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolPayload::Function { arguments } = invocation.payload else {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "{} handler received unsupported payload",
+                self.name
+            )));
+        };
+        match (self.f)(&arguments) {
+            Ok(content) => Ok(ToolOutput::Function {
+                body: FunctionCallOutputBody::Text(content),
+                success: Some(true),
+            }),
+            Err(message) => Err(FunctionCallError::RespondToModel(message)),
+        }
+    }
+}
+
 fn unsupported_tool_call_message(payload: &ToolPayload, tool_name: &str) -> String {
     match payload {
         ToolPayload::Custom { .. } => format!("unsupported custom tool call: {tool_name}"),
@@ -331,7 +1363,7 @@ impl From<&ToolPayload> for HookToolInput {
             ToolPayload::Function { arguments } => HookToolInput::Function {
                 arguments: arguments.clone(),
             },
-            ToolPayload::Custom { input } => HookToolInput::Custom {
+            ToolPayload::Custom { input, .. } => HookToolInput::Custom {
                 input: input.clone(),
             },
             ToolPayload::LocalShell { params } => HookToolInput::LocalShell {
@@ -353,6 +1385,20 @@ impl From<&ToolPayload> for HookToolInput {
                 tool: tool.clone(),
                 arguments: raw_arguments.clone(),
             },
+            // Headers are left out since they usually carry credentials.
+            ToolPayload::GraphQL {
+                endpoint,
+                query,
+                variables,
+                ..
+            } => HookToolInput::Function {
+                arguments: serde_json::json!({
+                    "endpoint": endpoint,
+                    "query": query,
+                    "variables": variables,
+                })
+                .to_string(),
+            },
         }
     }
 }
@@ -443,3 +1489,1081 @@ async fn dispatch_after_tool_use_hook(
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client_common::tools::ResponsesApiTool;
+    use crate::codex::make_session_and_context;
+    use crate::tools::output_cache::InMemoryToolOutputCache;
+    use crate::tools::output_transform::ToolOutputTransformer;
+    use crate::tools::resilience::CircuitBreakerConfig;
+    use crate::tools::resilience::RetryPolicy;
+    use crate::turn_diff_tracker::TurnDiffTracker;
+    use codex_otel::metrics::MetricsClient;
+    use codex_otel::metrics::MetricsConfig;
+    use codex_protocol::mcp::CallToolResult;
+    use opentelemetry_sdk::metrics::InMemoryMetricExporter;
+    use opentelemetry_sdk::metrics::data::AggregatedMetrics;
+    use opentelemetry_sdk::metrics::data::MetricData;
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use tracing_subscriber::fmt::format::FmtSpan;
+    use tracing_subscriber::prelude::*;
+
+    struct FlakyMcpHandler {
+        failures_remaining: AtomicUsize,
+        calls: AtomicUsize,
+    }
+
+    impl FlakyMcpHandler {
+        fn new(failures: usize) -> Self {
+            Self {
+                failures_remaining: AtomicUsize::new(failures),
+                calls: AtomicUsize::new(0),
+            }
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl ToolHandler for FlakyMcpHandler {
+        fn kind(&self) -> ToolKind {
+            ToolKind::Mcp
+        }
+
+        async fn handle(
+            &self,
+            _invocation: ToolInvocation,
+        ) -> Result<ToolOutput, FunctionCallError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let failed = self
+                .failures_remaining
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |remaining| {
+                    remaining.checked_sub(1)
+                })
+                .is_ok();
+            let result = if failed {
+                Err("connection closed".to_string())
+            } else {
+                Ok(CallToolResult {
+                    content: Vec::new(),
+                    structured_content: None,
+                    is_error: None,
+                    meta: None,
+                })
+            };
+            Ok(ToolOutput::Mcp { result })
+        }
+    }
+
+    async fn invocation() -> ToolInvocation {
+        let (session, turn) = make_session_and_context().await;
+        ToolInvocation {
+            session: Arc::new(session),
+            turn: Arc::new(turn),
+            tracker: Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+            call_id: "call-1".to_string(),
+            tool_name: "mcp__flaky__echo".to_string(),
+            payload: ToolPayload::Mcp {
+                server: "flaky".to_string(),
+                tool: "echo".to_string(),
+                raw_arguments: "{}".to_string(),
+            },
+            dry_run: false,
+        }
+    }
+
+    fn registry(handler: Arc<FlakyMcpHandler>) -> ToolRegistry {
+        let handler: Arc<dyn ToolHandler> = handler;
+        ToolRegistry::new(HashMap::from([("mcp__flaky__echo".to_string(), handler)]))
+    }
+
+    fn policy(
+        max_retries: u32,
+        failure_threshold: u32,
+        open_duration: Duration,
+    ) -> ResiliencyPolicy {
+        ResiliencyPolicy {
+            retry: RetryPolicy {
+                max_retries,
+                initial_backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(1),
+            },
+            circuit_breaker: CircuitBreakerConfig {
+                failure_threshold,
+                open_duration,
+            },
+            ..Default::default()
+        }
+    }
+
+    fn is_mcp_success(result: &Result<ResponseInputItem, FunctionCallError>) -> bool {
+        matches!(
+            result,
+            Ok(ResponseInputItem::McpToolCallOutput { result: Ok(_), .. })
+        )
+    }
+
+    #[tokio::test]
+    async fn closed_circuit_retries_transient_failures() {
+        let handler = Arc::new(FlakyMcpHandler::new(2));
+        let registry = registry(Arc::clone(&handler));
+
+        let result = registry
+            .dispatch_resilient(invocation().await, policy(2, 5, Duration::from_secs(60)))
+            .await;
+
+        assert!(is_mcp_success(&result), "unexpected result: {result:?}");
+        assert_eq!(handler.calls(), 3);
+        assert_eq!(
+            registry.circuit_state("flaky"),
+            Some(CircuitState::Closed {
+                consecutive_failures: 0
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn open_circuit_stops_retries_and_fails_fast() {
+        let handler = Arc::new(FlakyMcpHandler::new(usize::MAX));
+        let registry = registry(Arc::clone(&handler));
+        let policy = policy(5, 2, Duration::from_secs(60));
+
+        let result = registry
+            .dispatch_resilient(invocation().await, policy)
+            .await;
+        assert!(matches!(
+            result,
+            Ok(ResponseInputItem::McpToolCallOutput { result: Err(_), .. })
+        ));
+        assert_eq!(handler.calls(), 2);
+        assert!(matches!(
+            registry.circuit_state("flaky"),
+            Some(CircuitState::Open { .. })
+        ));
+
+        let result = registry
+            .dispatch_resilient(invocation().await, policy)
+            .await;
+        assert_eq!(result, Err(circuit_open_error("flaky")));
+        assert_eq!(handler.calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn half_open_circuit_sends_single_probe() {
+        let handler = Arc::new(FlakyMcpHandler::new(2));
+        let registry = registry(Arc::clone(&handler));
+        let policy = policy(3, 1, Duration::ZERO);
+
+        // First failure trips the circuit immediately; no retries follow.
+        let result = registry
+            .dispatch_resilient(invocation().await, policy)
+            .await;
+        assert!(!is_mcp_success(&result));
+        assert_eq!(handler.calls(), 1);
+
+        // The failed probe re-opens the circuit and is not retried.
+        let result = registry
+            .dispatch_resilient(invocation().await, policy)
+            .await;
+        assert!(!is_mcp_success(&result));
+        assert_eq!(handler.calls(), 2);
+        assert!(matches!(
+            registry.circuit_state("flaky"),
+            Some(CircuitState::Open { .. })
+        ));
+
+        // A successful probe closes the circuit again.
+        let result = registry
+            .dispatch_resilient(invocation().await, policy)
+            .await;
+        assert!(is_mcp_success(&result), "unexpected result: {result:?}");
+        assert_eq!(handler.calls(), 3);
+        assert_eq!(
+            registry.circuit_state("flaky"),
+            Some(CircuitState::Closed {
+                consecutive_failures: 0
+            })
+        );
+    }
+
+    struct StripAnsi;
+
+    impl ToolOutputTransformer for StripAnsi {
+        fn transform(&self, _tool_name: &str, output: String) -> String {
+            let ansi = regex_lite::Regex::new("\x1b\\[[0-9;]*m").expect("valid regex");
+            ansi.replace_all(&output, "").into_owned()
+        }
+    }
+
+    struct ColoredShellHandler;
+
+    #[async_trait]
+    impl ToolHandler for ColoredShellHandler {
+        fn kind(&self) -> ToolKind {
+            ToolKind::Function
+        }
+
+        async fn handle(
+            &self,
+            _invocation: ToolInvocation,
+        ) -> Result<ToolOutput, FunctionCallError> {
+            Ok(ToolOutput::Function {
+                body: FunctionCallOutputBody::Text(
+                    "\x1b[32mok\x1b[0m: 3 passed, \x1b[1;31m0 failed\x1b[0m".to_string(),
+                ),
+                success: Some(true),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_output_transformers_strip_ansi_from_shell_output() {
+        let handler: Arc<dyn ToolHandler> = Arc::new(ColoredShellHandler);
+        let registry = ToolRegistry::new(HashMap::from([("shell".to_string(), handler)]));
+        let mut invocation = invocation().await;
+        invocation.tool_name = "shell".to_string();
+        invocation.payload = ToolPayload::Function {
+            arguments: "{}".to_string(),
+        };
+        invocation
+            .session
+            .apply_tool_output_transformer(Arc::new(StripAnsi))
+            .await;
+
+        let result = registry.dispatch(invocation).await;
+
+        let Ok(ResponseInputItem::FunctionCallOutput { output, .. }) = result else {
+            panic!("expected function call output, got {result:?}");
+        };
+        assert_eq!(output.text_content(), Some("ok: 3 passed, 0 failed"));
+    }
+
+    #[derive(Clone, Default)]
+    struct CapturedWriter(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl CapturedWriter {
+        fn lines(&self) -> Vec<serde_json::Value> {
+            let bytes = self
+                .0
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .clone();
+            String::from_utf8_lossy(&bytes)
+                .lines()
+                .map(|line| serde_json::from_str(line).expect("json line"))
+                .collect()
+        }
+    }
+
+    impl std::io::Write for CapturedWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn dispatch_with_observability_emits_span_metric_and_log() -> anyhow::Result<()> {
+        let handler = Arc::new(FlakyMcpHandler::new(0));
+        let registry = registry(Arc::clone(&handler));
+        let invocation = invocation().await;
+        let telemetry = invocation
+            .turn
+            .session_telemetry
+            .clone()
+            .with_metrics_without_metadata_tags(MetricsClient::new(
+                MetricsConfig::in_memory(
+                    "test",
+                    "codex-cli",
+                    env!("CARGO_PKG_VERSION"),
+                    InMemoryMetricExporter::default(),
+                )
+                .with_runtime_reader(),
+            )?);
+        let spans = CapturedWriter::default();
+        let span_writer = spans.clone();
+        let logs = CapturedWriter::default();
+        let log_writer = logs.clone();
+        let obs = Observability::new(telemetry.clone())
+            .with_tracing(
+                tracing_subscriber::registry()
+                    .with(
+                        tracing_subscriber::fmt::layer()
+                            .json()
+                            .with_span_events(FmtSpan::CLOSE)
+                            .with_writer(move || span_writer.clone()),
+                    )
+                    .into(),
+            )
+            .with_log_sink(
+                tracing_subscriber::registry()
+                    .with(crate::tools::dispatch_log::structured_json_layer(
+                        move || log_writer.clone(),
+                    ))
+                    .into(),
+            );
+
+        registry
+            .dispatch_with_observability(invocation, &obs)
+            .await?;
+
+        assert_eq!(handler.calls(), 1);
+        let spans = spans.lines();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0]["span"]["name"], "tool_dispatch");
+        assert_eq!(spans[0]["span"]["tool_name"], "mcp__flaky__echo");
+        assert_eq!(spans[0]["span"]["server"], "flaky");
+        assert_eq!(spans[0]["span"]["outcome"], "success");
+        assert!(spans[0]["span"]["duration_ms"].is_number());
+
+        let logs = logs.lines();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0]["call_id"], "call-1");
+        assert_eq!(logs[0]["error_kind"], "none");
+
+        let snapshot = telemetry.snapshot_metrics()?;
+        let metric = snapshot
+            .scope_metrics()
+            .flat_map(|scope| scope.metrics())
+            .find(|metric| metric.name() == "codex.tool.duration_ms")
+            .expect("duration histogram missing");
+        let AggregatedMetrics::F64(MetricData::Histogram(histogram)) = metric.data() else {
+            panic!("unexpected data for {}", metric.name());
+        };
+        let points = histogram.data_points().collect::<Vec<_>>();
+        assert_eq!(points.len(), 1);
+        let tags = points[0]
+            .attributes()
+            .map(|kv| (kv.key.to_string(), kv.value.to_string()))
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(
+            tags,
+            BTreeMap::from([
+                ("outcome".to_string(), "success".to_string()),
+                ("server".to_string(), "flaky".to_string()),
+                ("tool_name".to_string(), "mcp__flaky__echo".to_string()),
+            ])
+        );
+
+        Ok(())
+    }
+
+    async fn dispatch_echo(
+        registry: &ToolRegistry,
+        budget: &mut TokenBudget,
+        arguments: String,
+    ) -> Option<String> {
+        let mut invocation = invocation().await;
+        invocation.tool_name = "echo".to_string();
+        invocation.payload = ToolPayload::Function { arguments };
+        match registry.dispatch_with_quota_check(invocation, budget).await {
+            Ok(ResponseInputItem::FunctionCallOutput { output, .. }) => {
+                output.text_content().map(str::to_string)
+            }
+            other => panic!("expected function call output, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn quota_check_truncates_output_at_budget_boundary() -> anyhow::Result<()> {
+        let mut registry = ToolRegistry::new(HashMap::new());
+        registry.register_function_tool(
+            "echo",
+            serde_json::json!({"type": "object", "properties": {}}),
+            |arguments| Ok(arguments.to_string()),
+        )?;
+        let mut budget = TokenBudget::new(10);
+
+        // 32 bytes is 8 tokens and fits in full.
+        let fits = "a".repeat(32);
+        assert_eq!(
+            dispatch_echo(&registry, &mut budget, fits.clone()).await,
+            Some(fits)
+        );
+        assert_eq!(budget.remaining(), 2);
+
+        // Only 2 tokens (8 bytes) of the next 40 bytes fit.
+        let overflows = format!("{}{}", "b".repeat(20), "c".repeat(20));
+        assert_eq!(
+            dispatch_echo(&registry, &mut budget, overflows).await,
+            Some("bbbb…8 tokens truncated…cccc".to_string())
+        );
+        assert!(budget.is_exhausted());
+
+        assert_eq!(
+            dispatch_echo(&registry, &mut budget, "d".repeat(8)).await,
+            Some("…2 tokens truncated…".to_string())
+        );
+        assert_eq!(budget.max_output_tokens(), 10);
+
+        Ok(())
+    }
+
+    struct BlockDeletes;
+
+    impl GovernancePolicy for BlockDeletes {
+        fn evaluate(&self, call: &ToolCall) -> PolicyResult {
+            if call.payload.log_payload().contains("rm -rf") {
+                PolicyResult::Deny {
+                    reason: "commands that delete files are not allowed".to_string(),
+                }
+            } else {
+                PolicyResult::Allow
+            }
+        }
+    }
+
+    struct RedactEmails;
+
+    impl GovernancePolicy for RedactEmails {
+        fn evaluate(&self, _call: &ToolCall) -> PolicyResult {
+            PolicyResult::Redact {
+                patterns: vec![regex_lite::Regex::new(r"[\w.]+@[\w.]+").expect("valid regex")],
+            }
+        }
+    }
+
+    async fn dispatch_shell(registry: &ToolRegistry, command: &str) -> Result<String, String> {
+        let mut invocation = invocation().await;
+        invocation.tool_name = "shell".to_string();
+        invocation.payload = ToolPayload::Function {
+            arguments: serde_json::json!({"command": ["bash", "-lc", command]}).to_string(),
+        };
+        match registry.dispatch(invocation).await {
+            Ok(ResponseInputItem::FunctionCallOutput { output, .. }) => {
+                Ok(output.text_content().unwrap_or_default().to_string())
+            }
+            Ok(other) => panic!("expected function call output, got {other:?}"),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn governance_policy_denies_deleting_commands() -> anyhow::Result<()> {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let mut registry = ToolRegistry::new(HashMap::new());
+        registry.register_function_tool(
+            "shell",
+            serde_json::json!({"type": "object", "properties": {}}),
+            {
+                let runs = Arc::clone(&runs);
+                move |arguments| {
+                    runs.fetch_add(1, Ordering::SeqCst);
+                    Ok(arguments.to_string())
+                }
+            },
+        )?;
+        registry.apply_governance_policy(Arc::new(BlockDeletes));
+
+        assert_eq!(
+            dispatch_shell(&registry, "rm -rf target").await,
+            Err(
+                "tool call denied by policy: commands that delete files are not allowed"
+                    .to_string()
+            )
+        );
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+
+        assert!(dispatch_shell(&registry, "ls target").await.is_ok());
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        Ok(())
+    }
+
+    struct RecordCallIds {
+        call_ids: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl ToolMiddleware for RecordCallIds {
+        async fn before(&self, invocation: &mut ToolInvocation) -> Result<(), FunctionCallError> {
+            self.call_ids
+                .lock()
+                .expect("call ids lock")
+                .push(invocation.call_id.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn composed_tool_steps_are_dispatched_through_the_registry() -> anyhow::Result<()> {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let call_ids = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut registry = ToolRegistry::new(HashMap::new());
+        registry.register_function_tool(
+            "shell",
+            serde_json::json!({"type": "object", "properties": {}}),
+            {
+                let runs = Arc::clone(&runs);
+                move |_| {
+                    runs.fetch_add(1, Ordering::SeqCst);
+                    Ok("done".to_string())
+                }
+            },
+        )?;
+        for (name, command) in [("clean", "rm -rf"), ("list", "ls")] {
+            registry.compose_tools(
+                name,
+                vec![ComposeStep {
+                    tool_name: "shell".to_string(),
+                    argument_template: format!(
+                        r#"{{"command": ["bash", "-lc", "{command} {{prev_output}}"]}}"#
+                    ),
+                }],
+            )?;
+        }
+        registry.apply_governance_policy(Arc::new(BlockDeletes));
+        registry.add_middleware(Arc::new(RecordCallIds {
+            call_ids: Arc::clone(&call_ids),
+        }));
+
+        let mut invocation = invocation().await;
+        invocation.tool_name = "clean".to_string();
+        invocation.payload = ToolPayload::Function {
+            arguments: "target".to_string(),
+        };
+        let err = registry
+            .dispatch(invocation.clone())
+            .await
+            .expect_err("the rm -rf step is denied");
+        assert!(
+            err.to_string()
+                .contains("commands that delete files are not allowed"),
+            "{err}"
+        );
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+
+        invocation.tool_name = "list".to_string();
+        registry.dispatch(invocation).await?;
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            *call_ids.lock().expect("call ids lock"),
+            vec![
+                "call-1".to_string(),
+                "call-1/step-1".to_string(),
+                "call-1".to_string(),
+                "call-1/step-1".to_string(),
+            ]
+        );
+
+        Ok(())
+    }
+
+    struct BlockRmRf;
+
+    #[async_trait]
+    impl ToolMiddleware for BlockRmRf {
+        async fn before(&self, invocation: &mut ToolInvocation) -> Result<(), FunctionCallError> {
+            if invocation.payload.log_payload().contains("rm -rf") {
+                return Err(FunctionCallError::RespondToModel(
+                    "rm -rf is blocked".to_string(),
+                ));
+            }
+            Ok(())
+        }
+    }
+
+    struct RecordAfter {
+        name: &'static str,
+        finished: Arc<std::sync::Mutex<Vec<&'static str>>>,
+    }
+
+    #[async_trait]
+    impl ToolMiddleware for RecordAfter {
+        async fn after(
+            &self,
+            _invocation: &ToolInvocation,
+            result: Result<ResponseInputItem, FunctionCallError>,
+        ) -> Result<ResponseInputItem, FunctionCallError> {
+            self.finished.lock().expect("finished lock").push(self.name);
+            result
+        }
+    }
+
+    #[tokio::test]
+    async fn middleware_wraps_dispatch_and_can_reject_calls() -> anyhow::Result<()> {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let finished = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut registry = ToolRegistry::new(HashMap::new());
+        registry.register_function_tool(
+            "shell",
+            serde_json::json!({"type": "object", "properties": {}}),
+            {
+                let runs = Arc::clone(&runs);
+                move |arguments| {
+                    runs.fetch_add(1, Ordering::SeqCst);
+                    Ok(arguments.to_string())
+                }
+            },
+        )?;
+        registry.add_middleware(Arc::new(RecordAfter {
+            name: "outer",
+            finished: Arc::clone(&finished),
+        }));
+        registry.add_middleware(Arc::new(
+            crate::tools::tool_middleware::SecretRedactionMiddleware::default(),
+        ));
+        registry.add_middleware(Arc::new(BlockRmRf));
+        registry.add_middleware(Arc::new(
+            crate::tools::tool_middleware::ArgumentSizeLimitMiddleware::new(200),
+        ));
+        registry.add_middleware(Arc::new(RecordAfter {
+            name: "inner",
+            finished: Arc::clone(&finished),
+        }));
+
+        assert_eq!(
+            dispatch_shell(&registry, "curl -H 'Authorization: Bearer abcdefgh12345'").await,
+            Ok(r#"{"command":["bash","-lc","curl -H 'Authorization: [REDACTED]'"]}"#.to_string())
+        );
+        assert_eq!(
+            *finished.lock().expect("finished lock"),
+            vec!["inner", "outer"]
+        );
+
+        assert_eq!(
+            dispatch_shell(&registry, "rm -rf target").await,
+            Err("rm -rf is blocked".to_string())
+        );
+        assert_eq!(
+            dispatch_shell(&registry, &"x".repeat(300)).await,
+            Err("tool call arguments are 329 bytes, which exceeds the 200 byte limit".to_string())
+        );
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            *finished.lock().expect("finished lock"),
+            vec!["inner", "outer", "outer", "outer"]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn governance_policy_redacts_arguments_before_dispatch() -> anyhow::Result<()> {
+        let mut registry = ToolRegistry::new(HashMap::new());
+        registry.register_function_tool(
+            "shell",
+            serde_json::json!({"type": "object", "properties": {}}),
+            |arguments| Ok(arguments.to_string()),
+        )?;
+        registry.apply_governance_policy(Arc::new(RedactEmails));
+        registry.apply_governance_policy(Arc::new(BlockDeletes));
+
+        assert_eq!(
+            dispatch_shell(&registry, "mail alice@example.com").await,
+            Ok(r#"{"command":["bash","-lc","mail [REDACTED]"]}"#.to_string())
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn governance_policy_redacts_output_and_reports_it() -> anyhow::Result<()> {
+        let (session, turn, rx) = crate::codex::make_session_and_context_with_rx().await;
+        let mut registry = ToolRegistry::new(HashMap::new());
+        registry.register_function_tool(
+            "lookup_owner",
+            serde_json::json!({"type": "object", "properties": {}}),
+            |_| Ok("owner: bob@example.com".to_string()),
+        )?;
+        registry.apply_governance_policy(Arc::new(RedactEmails));
+
+        let response = registry
+            .dispatch(ToolInvocation {
+                session,
+                turn,
+                tracker: Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                call_id: "call-1".to_string(),
+                tool_name: "lookup_owner".to_string(),
+                payload: ToolPayload::Function {
+                    arguments: "{}".to_string(),
+                },
+                dry_run: false,
+            })
+            .await?;
+
+        let ResponseInputItem::FunctionCallOutput { output, .. } = response else {
+            panic!("expected function call output, got {response:?}");
+        };
+        assert_eq!(output.text_content(), Some("owner: [REDACTED]"));
+        let redacted = loop {
+            let event = tokio::time::timeout(Duration::from_secs(1), rx.recv()).await??;
+            if let EventMsg::ToolOutputRedacted(redacted) = event.msg {
+                break redacted;
+            }
+        };
+        assert_eq!(
+            redacted,
+            ToolOutputRedactedEvent {
+                call_id: "call-1".to_string(),
+                bytes_redacted: "bob@example.com".len(),
+            }
+        );
+
+        Ok(())
+    }
+
+    struct LongOutputHandler;
+
+    #[async_trait]
+    impl ToolHandler for LongOutputHandler {
+        fn kind(&self) -> ToolKind {
+            ToolKind::Function
+        }
+
+        async fn handle(
+            &self,
+            _invocation: ToolInvocation,
+        ) -> Result<ToolOutput, FunctionCallError> {
+            Ok(ToolOutput::Function {
+                body: FunctionCallOutputBody::Text(format!(
+                    "$ cat large_log\n{}exit code: 1\n",
+                    "noise\n".repeat(100)
+                )),
+                success: Some(true),
+            })
+        }
+    }
+
+    async fn dispatch_long_output(registry: &ToolRegistry, tool_name: &str) -> Option<String> {
+        let mut invocation = invocation().await;
+        invocation.tool_name = tool_name.to_string();
+        invocation.payload = ToolPayload::Function {
+            arguments: "{}".to_string(),
+        };
+        match registry.dispatch(invocation).await {
+            Ok(ResponseInputItem::FunctionCallOutput { output, .. }) => {
+                output.text_content().map(str::to_string)
+            }
+            other => panic!("expected function call output, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn max_output_bytes_truncates_oversized_results() {
+        let handler: Arc<dyn ToolHandler> = Arc::new(LongOutputHandler);
+        let mut builder = ToolRegistryBuilder::new();
+        for name in ["shell", "read_file", "list_dir"] {
+            builder.register_handler(name, Arc::clone(&handler));
+        }
+        let (_, mut registry) = builder.build();
+        registry.set_max_output_size("shell", 32);
+        registry.set_max_output_size("read_file", 16);
+        registry.set_max_output_size("list_dir", 1024);
+
+        // Shell output keeps its first and last lines.
+        assert_eq!(
+            dispatch_long_output(&registry, "shell").await,
+            Some(
+                "$ cat large_log\n\n[truncated: 597 bytes omitted]\nse\nexit code: 1\n".to_string()
+            )
+        );
+        assert_eq!(
+            dispatch_long_output(&registry, "read_file").await,
+            Some("$ cat large_log\n\n[truncated: 613 bytes omitted]".to_string())
+        );
+        // Results within the limit are unmodified.
+        assert_eq!(
+            dispatch_long_output(&registry, "list_dir").await,
+            Some(format!(
+                "$ cat large_log\n{}exit code: 1\n",
+                "noise\n".repeat(100)
+            ))
+        );
+    }
+
+    #[test]
+    fn max_output_bytes_is_carried_from_spec_to_registry() {
+        let mut builder = ToolRegistryBuilder::new();
+        builder.push_spec(ToolSpec::ImageGeneration {
+            output_format: "png".to_string(),
+        });
+        builder.specs.push(
+            ConfiguredToolSpec::new(ToolSpec::LocalShell {}, false)
+                .with_max_output_bytes(Some(4096)),
+        );
+
+        let (specs, registry) = builder.build();
+
+        assert_eq!(specs[0].max_output_bytes, None);
+        assert_eq!(
+            registry.max_output_size_per_tool(),
+            &HashMap::from([("local_shell".to_string(), 4096)])
+        );
+    }
+
+    #[tokio::test]
+    async fn throttle_by_cost_stops_paid_tools_at_the_budget() -> anyhow::Result<()> {
+        let mut builder = ToolRegistryBuilder::new();
+        builder.specs.push(
+            ConfiguredToolSpec::new(
+                ToolSpec::Function(ResponsesApiTool {
+                    name: "web_lookup".to_string(),
+                    description: String::new(),
+                    strict: false,
+                    parameters: parse_tool_input_schema(&serde_json::json!({"type": "object"}))?,
+                }),
+                false,
+            )
+            .with_cost_model(Some(CostModel {
+                cost_per_call_usd: 0.25,
+            })),
+        );
+        let (_, mut registry) = builder.build();
+        for name in ["web_lookup", "read_notes"] {
+            registry.register_function_tool(
+                name,
+                serde_json::json!({"type": "object", "properties": {}}),
+                |_| Ok("ok".to_string()),
+            )?;
+        }
+        registry.throttle_by_cost(0.5);
+        let dispatch = async |tool_name: &str| {
+            let mut invocation = invocation().await;
+            invocation.tool_name = tool_name.to_string();
+            invocation.payload = ToolPayload::Function {
+                arguments: "{}".to_string(),
+            };
+            registry.dispatch(invocation).await
+        };
+
+        dispatch("web_lookup").await?;
+        assert_eq!(registry.cost_spent_usd(), 0.25);
+        dispatch("web_lookup").await?;
+        assert_eq!(registry.cost_spent_usd(), 0.5);
+
+        assert_eq!(
+            dispatch("web_lookup").await,
+            Err(FunctionCallError::BudgetExhausted {
+                tool_name: "web_lookup".to_string(),
+                budget_usd: 0.5,
+            })
+        );
+        assert_eq!(registry.cost_spent_usd(), 0.5);
+        // Tools without a cost model keep running.
+        dispatch("read_notes").await?;
+        Ok(())
+    }
+
+    fn mcp_tool(schema: serde_json::Value) -> rmcp::model::Tool {
+        let serde_json::Value::Object(schema) = schema else {
+            panic!("schema must be an object");
+        };
+        rmcp::model::Tool {
+            name: "search".to_string().into(),
+            title: None,
+            description: Some("Search documents".to_string().into()),
+            input_schema: Arc::new(schema),
+            output_schema: None,
+            annotations: None,
+            execution: None,
+            icons: None,
+            meta: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn validate_mcp_schema_reports_drift_from_live_server() {
+        let (_session, turn) = make_session_and_context().await;
+        let registered = mcp_tool(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string" },
+                "limit": { "type": "number" },
+            },
+        }));
+        let (_, registry) = crate::tools::spec::build_specs(
+            &turn.tools_config,
+            Some(HashMap::from([(
+                "mcp__docs__search".to_string(),
+                registered,
+            )])),
+            None,
+            &[],
+        )
+        .build();
+
+        // The server was upgraded after the registry was built.
+        let live = mcp_tool(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "array" },
+                "cursor": { "type": "string" },
+            },
+        }));
+        let live_tools = HashMap::from([(
+            "mcp__docs__search".to_string(),
+            ToolInfo {
+                server_name: "docs".to_string(),
+                tool_name: "search".to_string(),
+                tool: live,
+                connector_id: None,
+                connector_name: None,
+                plugin_display_names: Vec::new(),
+            },
+        )]);
+
+        assert_eq!(
+            registry.validate_mcp_schema("docs", &live_tools),
+            vec![
+                SchemaDrift::NewParameter {
+                    tool: "mcp__docs__search".to_string(),
+                    parameter: "cursor".to_string(),
+                },
+                SchemaDrift::RemovedParameter {
+                    tool: "mcp__docs__search".to_string(),
+                    parameter: "limit".to_string(),
+                },
+                SchemaDrift::ChangedType {
+                    tool: "mcp__docs__search".to_string(),
+                    parameter: "query".to_string(),
+                    registered: "\"string\"".to_string(),
+                    live: "\"array\"".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            registry.validate_mcp_schema("other", &live_tools),
+            Vec::new()
+        );
+    }
+
+    struct CountingReadFile {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ToolHandler for CountingReadFile {
+        fn kind(&self) -> ToolKind {
+            ToolKind::Function
+        }
+
+        async fn handle(
+            &self,
+            invocation: ToolInvocation,
+        ) -> Result<ToolOutput, FunctionCallError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            crate::tools::handlers::ReadFileHandler
+                .handle(invocation)
+                .await
+        }
+    }
+
+    #[tokio::test]
+    async fn cached_read_file_output_skips_second_dispatch() -> anyhow::Result<()> {
+        let (session, turn, rx) = crate::codex::make_session_and_context_with_rx().await;
+        session
+            .with_tool_output_cache(Arc::new(InMemoryToolOutputCache::new()))
+            .await;
+        let dir = tempfile::tempdir()?;
+        let notes = dir.path().join("notes.txt");
+        std::fs::write(&notes, "cache me\n")?;
+        let handler = Arc::new(CountingReadFile {
+            calls: AtomicUsize::new(0),
+        });
+        let mut registry = ToolRegistry::new(HashMap::from([(
+            "read_file".to_string(),
+            Arc::clone(&handler) as Arc<dyn ToolHandler>,
+        )]));
+        registry.set_cacheable("read_file");
+        let arguments = serde_json::json!({ "file_path": notes }).to_string();
+        let read = |call_id: &str| ToolInvocation {
+            session: Arc::clone(&session),
+            turn: Arc::clone(&turn),
+            tracker: Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+            call_id: call_id.to_string(),
+            tool_name: "read_file".to_string(),
+            payload: ToolPayload::Function {
+                arguments: arguments.clone(),
+            },
+            dry_run: false,
+        };
+
+        let first = registry.dispatch(read("call-1")).await?;
+        let second = registry.dispatch(read("call-2")).await?;
+
+        assert_eq!(handler.calls.load(Ordering::SeqCst), 1);
+        let (
+            ResponseInputItem::FunctionCallOutput {
+                call_id: first_id,
+                output: first_output,
+            },
+            ResponseInputItem::FunctionCallOutput {
+                call_id: second_id,
+                output: second_output,
+            },
+        ) = (first, second)
+        else {
+            panic!("expected function call outputs");
+        };
+        assert_eq!(
+            (first_id.as_str(), second_id.as_str()),
+            ("call-1", "call-2")
+        );
+        assert_eq!(first_output, second_output);
+        let hit = loop {
+            let event = tokio::time::timeout(Duration::from_secs(1), rx.recv()).await??;
+            if let EventMsg::ToolCacheHit(hit) = event.msg {
+                break hit;
+            }
+        };
+        assert_eq!(hit.call_id, "call-2");
+        assert_eq!(hit.tool_name, "read_file");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn tools_not_declared_cacheable_skip_the_cache() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let session = Arc::new(session);
+        let turn = Arc::new(turn);
+        session
+            .with_tool_output_cache(Arc::new(InMemoryToolOutputCache::new()))
+            .await;
+        let dir = tempfile::tempdir()?;
+        let notes = dir.path().join("notes.txt");
+        std::fs::write(&notes, "do not cache me\n")?;
+        let handler = Arc::new(CountingReadFile {
+            calls: AtomicUsize::new(0),
+        });
+        let registry = ToolRegistry::new(HashMap::from([(
+            "read_file".to_string(),
+            Arc::clone(&handler) as Arc<dyn ToolHandler>,
+        )]));
+        let arguments = serde_json::json!({ "file_path": notes }).to_string();
+
+        for call_id in ["call-1", "call-2"] {
+            registry
+                .dispatch(ToolInvocation {
+                    session: Arc::clone(&session),
+                    turn: Arc::clone(&turn),
+                    tracker: Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                    call_id: call_id.to_string(),
+                    tool_name: "read_file".to_string(),
+                    payload: ToolPayload::Function {
+                        arguments: arguments.clone(),
+                    },
+                    dry_run: false,
+                })
+                .await?;
+        }
+
+        assert_eq!(handler.calls.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,344 @@
+//! Retry and circuit-breaker policy for tool dispatch.
+//!
+//! Retries and the circuit breaker have to cooperate: a retry must never be
+//! attempted while the circuit is open, and a half-open circuit only lets a
+//! single probe through. [`ToolRegistry::dispatch_resilient`] drives the state
+//! machine implemented here.
+//!
+//! [`ToolRegistry::dispatch_resilient`]: crate::tools::registry::ToolRegistry::dispatch_resilient
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use codex_protocol::models::ResponseInputItem;
+use tokio::time::Instant;
+
+use crate::function_tool::FunctionCallError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Additional attempts after the first one fails with a transient error.
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Delay before the `retry`-th retry (1-based).
+    pub(crate) fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(2),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that trip the circuit from closed to open.
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before a probe is allowed through.
+    pub open_duration: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TimeoutConfig {
+    /// Upper bound for a single attempt. Timeouts count as transient failures.
+    pub per_attempt: Option<Duration>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResiliencyPolicy {
+    pub retry: RetryPolicy,
+    pub circuit_breaker: CircuitBreakerConfig,
+    pub timeout: TimeoutConfig,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed { consecutive_failures: u32 },
+    Open { until: Instant },
+    HalfOpen { probe_in_flight: bool },
+}
+
+/// Outcome of asking the breaker whether an attempt may proceed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Admission {
+    /// Normal attempt; transient failures may be retried.
+    Allowed,
+    /// The single half-open probe. It must not be retried.
+    Probe,
+    Rejected,
+}
+
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    state: Mutex<CircuitState>,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(CircuitState::Closed {
+                consecutive_failures: 0,
+            }),
+        }
+    }
+}
+
+impl CircuitBreaker {
+    pub(crate) fn state(&self) -> CircuitState {
+        *self.lock()
+    }
+
+    pub(crate) fn admit(&self, now: Instant) -> Admission {
+        let mut state = self.lock();
+        match *state {
+            CircuitState::Closed { .. } => Admission::Allowed,
+            CircuitState::Open { until } if now >= until => {
+                *state = CircuitState::HalfOpen {
+                    probe_in_flight: true,
+                };
+                Admission::Probe
+            }
+            CircuitState::Open { .. } => Admission::Rejected,
+            CircuitState::HalfOpen {
+                probe_in_flight: true,
+            } => Admission::Rejected,
+            CircuitState::HalfOpen {
+                probe_in_flight: false,
+            } => {
+                *state = CircuitState::HalfOpen {
+                    probe_in_flight: true,
+                };
+                Admission::Probe
+            }
+        }
+    }
+
+    pub(crate) fn record_success(&self) {
+        *self.lock() = CircuitState::Closed {
+            consecutive_failures: 0,
+        };
+    }
+
+    pub(crate) fn record_failure(&self, config: &CircuitBreakerConfig, now: Instant) {
+        let mut state = self.lock();
+        let open = CircuitState::Open {
+            until: now + config.open_duration,
+        };
+        *state = match *state {
+            CircuitState::Closed {
+                consecutive_failures,
+            } => {
+                let consecutive_failures = consecutive_failures.saturating_add(1);
+                if consecutive_failures >= config.failure_threshold {
+                    open
+                } else {
+                    CircuitState::Closed {
+                        consecutive_failures,
+                    }
+                }
+            }
+            CircuitState::HalfOpen { .. } | CircuitState::Open { .. } => open,
+        };
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CircuitState> {
+        match self.state.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// Returns `true` when `result` is a failure that is worth retrying. Fatal
+/// errors and errors meant for the model are final; MCP transport failures and
+/// timeouts are transient.
+pub(crate) fn is_transient_failure(result: &Result<ResponseInputItem, FunctionCallError>) -> bool {
+    matches!(
+        result,
+        Ok(ResponseInputItem::McpToolCallOutput { result: Err(_), .. })
+            | Err(FunctionCallError::McpTransport(_))
+    )
+}
+
+pub(crate) fn circuit_open_error(key: &str) -> FunctionCallError {
+    FunctionCallError::RespondToModel(format!(
+        "{key} is temporarily unavailable after repeated failures; try again later"
+    ))
+}
+
+pub(crate) fn timeout_error(key: &str, timeout: Duration) -> FunctionCallError {
+    FunctionCallError::RespondToModel(format!(
+        "{key} did not respond within {}ms",
+        timeout.as_millis()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn config() -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            failure_threshold: 2,
+            open_duration: Duration::from_secs(10),
+        }
+    }
+
+    #[test]
+    fn closed_stays_closed_below_threshold() {
+        let breaker = CircuitBreaker::default();
+        let now = Instant::now();
+
+        breaker.record_failure(&config(), now);
+
+        assert_eq!(
+            breaker.state(),
+            CircuitState::Closed {
+                consecutive_failures: 1
+            }
+        );
+        assert_eq!(breaker.admit(now), Admission::Allowed);
+    }
+
+    #[test]
+    fn closed_opens_at_threshold_and_rejects() {
+        let breaker = CircuitBreaker::default();
+        let now = Instant::now();
+
+        breaker.record_failure(&config(), now);
+        breaker.record_failure(&config(), now);
+
+        assert_eq!(
+            breaker.state(),
+            CircuitState::Open {
+                until: now + Duration::from_secs(10)
+            }
+        );
+        assert_eq!(breaker.admit(now), Admission::Rejected);
+    }
+
+    #[test]
+    fn success_resets_consecutive_failures() {
+        let breaker = CircuitBreaker::default();
+        let now = Instant::now();
+
+        breaker.record_failure(&config(), now);
+        breaker.record_success();
+        breaker.record_failure(&config(), now);
+
+        assert_eq!(
+            breaker.state(),
+            CircuitState::Closed {
+                consecutive_failures: 1
+            }
+        );
+    }
+
+    #[test]
+    fn open_transitions_to_half_open_with_single_probe() {
+        let breaker = CircuitBreaker::default();
+        let now = Instant::now();
+        breaker.record_failure(&config(), now);
+        breaker.record_failure(&config(), now);
+
+        let later = now + Duration::from_secs(10);
+        assert_eq!(breaker.admit(later), Admission::Probe);
+        assert_eq!(
+            breaker.state(),
+            CircuitState::HalfOpen {
+                probe_in_flight: true
+            }
+        );
+        assert_eq!(breaker.admit(later), Admission::Rejected);
+    }
+
+    #[test]
+    fn half_open_probe_success_closes() {
+        let breaker = CircuitBreaker::default();
+        let now = Instant::now();
+        breaker.record_failure(&config(), now);
+        breaker.record_failure(&config(), now);
+        let later = now + Duration::from_secs(10);
+        assert_eq!(breaker.admit(later), Admission::Probe);
+
+        breaker.record_success();
+
+        assert_eq!(
+            breaker.state(),
+            CircuitState::Closed {
+                consecutive_failures: 0
+            }
+        );
+        assert_eq!(breaker.admit(later), Admission::Allowed);
+    }
+
+    #[test]
+    fn half_open_probe_failure_reopens() {
+        let breaker = CircuitBreaker::default();
+        let now = Instant::now();
+        breaker.record_failure(&config(), now);
+        breaker.record_failure(&config(), now);
+        let later = now + Duration::from_secs(10);
+        assert_eq!(breaker.admit(later), Admission::Probe);
+
+        breaker.record_failure(&config(), later);
+
+        assert_eq!(
+            breaker.state(),
+            CircuitState::Open {
+                until: later + Duration::from_secs(10)
+            }
+        );
+        assert_eq!(breaker.admit(later), Admission::Rejected);
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_up_to_cap() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(300),
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn only_mcp_transport_failures_are_transient() {
+        let transport_failure = Ok(ResponseInputItem::McpToolCallOutput {
+            call_id: "call".to_string(),
+            result: Err("connection reset".to_string()),
+        });
+        let model_error = Err(FunctionCallError::RespondToModel("bad args".to_string()));
+        let fatal = Err(FunctionCallError::Fatal("boom".to_string()));
+
+        assert!(is_transient_failure(&transport_failure));
+        assert!(!is_transient_failure(&model_error));
+        assert!(!is_transient_failure(&fatal));
+    }
+}
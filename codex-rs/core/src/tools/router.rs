@@ -1,26 +1,107 @@
+use crate::audit::AuditEvent;
 use crate::client_common::tools::ToolSpec;
 use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::config::types::McpServerConfig;
 use crate::function_tool::FunctionCallError;
+use crate::mcp::split_qualified_tool_name;
 use crate::mcp_connection_manager::ToolInfo;
+use crate::mcp_connection_manager::list_server_tools;
 use crate::sandboxing::SandboxPermissions;
+use crate::tools::audit::AuditEntry;
+use crate::tools::audit::AuditHook;
+use crate::tools::audit::AuditOutcome;
+use crate::tools::audit::AuditRecord;
+use crate::tools::audit::EncryptedEntry;
+use crate::tools::audit::EncryptionKey;
+use crate::tools::call_dedupe::CallIdDedupe;
+use crate::tools::call_dedupe::DuplicateCall;
+use crate::tools::call_events::ToolCallEvent;
+use crate::tools::chaos::Chaos;
+use crate::tools::chaos::ChaosConfig;
+use crate::tools::chaos::corrupt_payload;
+use crate::tools::config_file::ConfigLoadError;
+use crate::tools::config_file::ToolsConfigFile;
 use crate::tools::context::SharedTurnDiffTracker;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolPayload;
+use crate::tools::context::telemetry_preview;
+use crate::tools::curl::mcp_call_as_curl;
+use crate::tools::diagnostics::CircuitBreakerDiagnostics;
+use crate::tools::diagnostics::DiagnosticsReport;
+use crate::tools::diagnostics::ToolDescriptor;
+use crate::tools::diagnostics::ToolDiagnostics;
+use crate::tools::diagnostics::ToolHealthStatus;
+use crate::tools::diagnostics::ToolStatus;
+use crate::tools::diagnostics::mcp_server_diagnostics;
+use crate::tools::dispatch_log;
+#[cfg(feature = "dynamic-plugins")]
+use crate::tools::dynamic_plugins;
+#[cfg(feature = "dynamic-plugins")]
+use crate::tools::dynamic_plugins::PluginLoadError;
+use crate::tools::middleware::AfterAllHook;
+use crate::tools::middleware::BeforeAllHook;
+use crate::tools::middleware::BoxedDispatchFn;
+use crate::tools::middleware::DispatchInterceptor;
+use crate::tools::middleware::SandboxPermissionsHook;
+use crate::tools::observability::Observability;
+use crate::tools::observability::ObservedCall;
+use crate::tools::output_transform::OutputLimit;
+#[cfg(feature = "proto-config")]
+use crate::tools::proto_config;
+#[cfg(feature = "proto-config")]
+use crate::tools::proto_config::ProtoDecodeError;
 use crate::tools::registry::ConfiguredToolSpec;
+use crate::tools::registry::RegistrationError;
+use crate::tools::registry::ToolKind;
+use crate::tools::registry::ToolRef;
 use crate::tools::registry::ToolRegistry;
+use crate::tools::resilience::CircuitState;
+use crate::tools::resilience::RetryPolicy;
+use crate::tools::spec::JsonSchema;
+use crate::tools::spec::ToolFilter;
+use crate::tools::spec::ToolNameCollisions;
 use crate::tools::spec::ToolsConfig;
 use crate::tools::spec::build_specs;
+use crate::tools::validation::ValidationError;
+use crate::tools::validation::validate_arguments;
+#[cfg(feature = "wasm-plugins")]
+use crate::tools::wasm_plugins;
+#[cfg(feature = "wasm-plugins")]
+use crate::tools::wasm_plugins::WasmPluginError;
+#[cfg(feature = "wasm-plugins")]
+use crate::tools::wasm_plugins::WasmPluginLimits;
 use codex_protocol::dynamic_tools::DynamicToolSpec;
 use codex_protocol::models::FunctionCallOutputBody;
+use codex_protocol::models::FunctionCallOutputContentItem;
+use codex_protocol::models::FunctionCallOutputPayload;
 use codex_protocol::models::LocalShellAction;
 use codex_protocol::models::ResponseInputItem;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::models::ShellToolCallParams;
+use codex_protocol::protocol::ChaosFaultInjectedEvent;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::ToolCallStartEvent;
+use codex_rmcp_client::OAuthCredentialsStoreMode;
+use codex_secrets::redact_secrets;
+use futures::FutureExt;
 use rmcp::model::Tool;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::path::Path;
 use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use tokio::sync::Semaphore;
+use tracing::error;
 use tracing::instrument;
+use tracing::warn;
 
 pub use crate::tools::context::ToolCallSource;
 
@@ -29,11 +110,186 @@ pub struct ToolCall {
     pub tool_name: String,
     pub call_id: String,
     pub payload: ToolPayload,
+    /// Caller-defined key-value tags, reported in the `ToolCallStart` event,
+    /// the audit log, and as attributes on the dispatch span.
+    pub tags: HashMap<String, String>,
+    /// Validate the call without executing it; see [`ToolCall::dry_run`].
+    pub dry_run: bool,
 }
 
+impl ToolCall {
+    /// Marks the call as a dry run: it goes through every check that would
+    /// precede execution and, if they pass, gets a synthetic success result
+    /// instead of running the handler. The result describes what would have
+    /// run: the resolved command, workdir, and sandbox permissions of shell
+    /// calls, and the server, tool, and arguments of MCP calls. Tools without
+    /// side effects (see [`ToolHandler::is_pure`]) run as usual.
+    ///
+    /// [`ToolHandler::is_pure`]: crate::tools::registry::ToolHandler::is_pure
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    pub fn with_tag(mut self, key: &str, value: &str) -> Self {
+        self.tags.insert(key.to_string(), value.to_string());
+        self
+    }
+}
+
+/// Settings that only apply for the lifetime of a single turn. See
+/// [`ToolRouter::clone_for_turn`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TurnToolOverrides {
+    /// Tools that are neither advertised to the model nor dispatched this turn.
+    pub disabled_tools: HashSet<String>,
+    /// Upper bound applied to every tool dispatch this turn.
+    pub tool_timeout: Option<Duration>,
+}
+
+/// Snapshot of a dispatched tool call, retained for post-processing. See
+/// [`ToolRouter::get_call_by_id`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchivedToolCall {
+    pub tool_name: String,
+    /// Raw arguments as received from the model.
+    pub arguments: String,
+    pub dispatched_at: SystemTime,
+    /// Truncated preview of the output returned to the model.
+    pub result_summary: String,
+    pub duration: Duration,
+}
+
+/// How many dispatched calls [`ToolRouter::get_call_by_id`] remembers; the
+/// oldest are forgotten first.
+const CALL_ARCHIVE_CAPACITY: usize = 1024;
+
+/// The most recently dispatched calls, keyed by call id. Clones share the
+/// same calls, so a session keeps one archive across all of its routers.
+#[derive(Clone, Debug)]
+pub(crate) struct CallArchive {
+    state: Arc<Mutex<CallArchiveState>>,
+}
+
+#[derive(Debug)]
+struct CallArchiveState {
+    calls: HashMap<String, ArchivedToolCall>,
+    /// Keys of `calls`, oldest first.
+    order: VecDeque<String>,
+    capacity: usize,
+}
+
+impl Default for CallArchive {
+    fn default() -> Self {
+        Self::with_capacity(CALL_ARCHIVE_CAPACITY)
+    }
+}
+
+impl CallArchive {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CallArchiveState {
+                calls: HashMap::new(),
+                order: VecDeque::new(),
+                capacity,
+            })),
+        }
+    }
+
+    fn insert(&self, call_id: String, call: ArchivedToolCall) {
+        let mut state = self.lock_state();
+        if state.calls.insert(call_id.clone(), call).is_none() {
+            state.order.push_back(call_id);
+        }
+        while state.order.len() > state.capacity {
+            if let Some(evicted) = state.order.pop_front() {
+                state.calls.remove(&evicted);
+            }
+        }
+    }
+
+    pub(crate) fn get(&self, call_id: &str) -> Option<ArchivedToolCall> {
+        self.lock_state().calls.get(call_id).cloned()
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, CallArchiveState> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// Stands in for encrypted fields in [`ArchivedToolCall`].
+pub const ENCRYPTED_PLACEHOLDER: &str = "[encrypted]";
+
+/// Counts calls that failed with [`FunctionCallError::Fatal`] and so ended
+/// the turn, tagged with the tool that caused it.
+const TOOL_FATAL_METRIC: &str = "codex.tool.fatal";
+
+/// Tools that can only change the workspace (or, like `js_repl`, run
+/// arbitrary code), so they are not advertised in read-only mode. See
+/// [`ToolRouter::set_read_only`].
+const READ_ONLY_HIDDEN_TOOLS: &[&str] = &[
+    "apply_patch",
+    "artifacts",
+    "js_repl",
+    "js_repl_reset",
+    "str_replace_edit",
+];
+
 pub struct ToolRouter {
-    registry: ToolRegistry,
-    specs: Vec<ConfiguredToolSpec>,
+    registry: Arc<ToolRegistry>,
+    specs: Arc<Vec<ConfiguredToolSpec>>,
+    turn_overrides: TurnToolOverrides,
+    /// Tool to dispatch, with the same arguments, when the keyed tool fails.
+    fallbacks: HashMap<String, String>,
+    /// The last [`CALL_ARCHIVE_CAPACITY`] calls dispatched through this router
+    /// (and its per-turn clones).
+    call_archive: CallArchive,
+    /// Call ids running or recently completed through this router (and its
+    /// per-turn clones), so a repeated call id does not run its tool again.
+    call_ids: CallIdDedupe,
+    /// When set, call arguments and results are only retained encrypted, in
+    /// the session's audit log.
+    encryption_key: Option<EncryptionKey>,
+    /// Emit a structured event per dispatch; see [`dispatch_log`].
+    structured_logging: bool,
+    max_argument_size_bytes: Option<usize>,
+    /// See [`ToolsConfig::schema_validation`].
+    schema_validation: bool,
+    /// Middleware run around every dispatch, in registration order.
+    before_all: Vec<Arc<dyn BeforeAllHook>>,
+    after_all: Vec<Arc<dyn AfterAllHook>>,
+    /// See [`ToolRouter::register_audit_hook`].
+    audit_hooks: Vec<Arc<dyn AuditHook>>,
+    dispatch_interceptor: Option<Arc<dyn DispatchInterceptor>>,
+    /// See [`ToolRouter::set_sandbox_permissions_hook`].
+    sandbox_permissions_hook: Option<Arc<dyn SandboxPermissionsHook>>,
+    chaos: Option<Arc<Chaos>>,
+    /// Names the shell handler answers to; see
+    /// [`ToolsConfig::shell_tool_aliases`].
+    shell_tool_aliases: Arc<Vec<String>>,
+    /// Per-tool dispatch timeouts; see [`ToolsConfig::tool_timeouts`].
+    tool_timeouts: Arc<HashMap<String, Duration>>,
+    /// Bounds how many calls run at once; see
+    /// [`ToolsConfig::max_parallel_tool_calls`].
+    tool_call_permits: Arc<Semaphore>,
+    /// Configuration of the MCP servers behind this router's MCP tools, keyed
+    /// by server name.
+    mcp_servers: Arc<HashMap<String, McpServerConfig>>,
+    /// Retries for MCP calls whose connection fails; see
+    /// [`ToolRouter::set_mcp_transport_retry`].
+    mcp_transport_retry: RetryPolicy,
+    /// See [`ToolRouter::set_output_limit`].
+    output_limit: OutputLimit,
+    /// [`ToolsConfig::tool_filter`], also applied to dynamic tools added
+    /// after the router was built.
+    tool_filter: Arc<ToolFilter>,
+    /// See [`ToolRouter::set_read_only`].
+    read_only: bool,
+    /// Qualified names of the MCP tools whose server annotates them with
+    /// `readOnlyHint: true`.
+    read_only_mcp_tools: Arc<HashSet<String>>,
 }
 
 impl ToolRouter {
@@ -43,24 +299,592 @@ impl ToolRouter {
         app_tools: Option<HashMap<String, ToolInfo>>,
         dynamic_tools: &[DynamicToolSpec],
     ) -> Self {
+        let (router, collisions) = Self::build(config, mcp_tools, app_tools, dynamic_tools);
+        if !collisions.is_empty() {
+            warn!("{collisions}");
+        }
+        router
+    }
+
+    /// Like [`ToolRouter::from_config`], but fails when two tools share a
+    /// name, or when MCP servers expose tools with the same name.
+    pub fn try_from_config(
+        config: &ToolsConfig,
+        mcp_tools: Option<HashMap<String, Tool>>,
+        app_tools: Option<HashMap<String, ToolInfo>>,
+        dynamic_tools: &[DynamicToolSpec],
+    ) -> Result<Self, ToolNameCollisions> {
+        let (router, collisions) = Self::build(config, mcp_tools, app_tools, dynamic_tools);
+        if collisions.is_empty() {
+            Ok(router)
+        } else {
+            Err(collisions)
+        }
+    }
+
+    fn build(
+        config: &ToolsConfig,
+        mcp_tools: Option<HashMap<String, Tool>>,
+        app_tools: Option<HashMap<String, ToolInfo>>,
+        dynamic_tools: &[DynamicToolSpec],
+    ) -> (Self, ToolNameCollisions) {
+        let mcp_tool_names = mcp_tools
+            .iter()
+            .flat_map(HashMap::keys)
+            .cloned()
+            .collect::<Vec<_>>();
+        let read_only_mcp_tools = mcp_tools
+            .iter()
+            .flatten()
+            .filter(|(_, tool)| {
+                tool.annotations
+                    .as_ref()
+                    .and_then(|annotations| annotations.read_only_hint)
+                    == Some(true)
+            })
+            .map(|(name, _)| name.clone())
+            .collect::<HashSet<_>>();
         let builder = build_specs(config, mcp_tools, app_tools, dynamic_tools);
-        let (specs, registry) = builder.build();
+        let (mut specs, registry) = builder.build();
+        let collisions = ToolNameCollisions::find(&specs, &mcp_tool_names);
+        specs.retain(|configured| {
+            let name = configured.spec.name();
+            let mcp_server = mcp_tool_names
+                .iter()
+                .any(|mcp_tool_name| mcp_tool_name == name)
+                .then(|| split_qualified_tool_name(name))
+                .flatten()
+                .map(|(server, _)| server);
+            config.tool_filter.permits(name, mcp_server.as_deref())
+        });
+
+        let router = Self {
+            registry: Arc::new(registry),
+            specs: Arc::new(specs),
+            turn_overrides: TurnToolOverrides::default(),
+            fallbacks: config.tool_fallbacks.clone(),
+            call_archive: CallArchive::default(),
+            call_ids: CallIdDedupe::default(),
+            encryption_key: None,
+            structured_logging: false,
+            max_argument_size_bytes: config.max_argument_size_bytes,
+            schema_validation: config.schema_validation,
+            before_all: Vec::new(),
+            after_all: Vec::new(),
+            audit_hooks: Vec::new(),
+            dispatch_interceptor: None,
+            sandbox_permissions_hook: None,
+            chaos: None,
+            shell_tool_aliases: Arc::new(config.shell_tool_aliases()),
+            tool_timeouts: Arc::new(config.tool_timeouts.clone()),
+            tool_call_permits: Arc::new(Semaphore::new(config.max_parallel_tool_calls.max(1))),
+            mcp_servers: Arc::default(),
+            mcp_transport_retry: RetryPolicy::default(),
+            output_limit: OutputLimit::default(),
+            tool_filter: Arc::new(config.tool_filter.clone()),
+            read_only: false,
+            read_only_mcp_tools: Arc::new(read_only_mcp_tools),
+        };
+        (router, collisions)
+    }
+
+    /// Builds a router from the tools config file at `path`, following its
+    /// `inherit_from` chain. Only built-in tools are registered.
+    pub fn from_config_file(path: &Path) -> Result<Self, ConfigLoadError> {
+        let config = ToolsConfigFile::load(path)?.into_tools_config(path)?;
+        Ok(Self::from_config(&config, None, None, &[]))
+    }
+
+    /// Builds a router from a protobuf-encoded `codex.tools.v1.ToolsConfig`,
+    /// registering its dynamic tools alongside the built-in ones. See
+    /// `core/proto/tools_config.proto`.
+    #[cfg(feature = "proto-config")]
+    pub fn from_proto_config(bytes: &[u8]) -> Result<Self, ProtoDecodeError> {
+        let (config, dynamic_tools) = proto_config::decode(bytes)?;
+        Ok(Self::from_config(&config, None, None, &dynamic_tools))
+    }
+
+    /// Returns a lightweight view of this router with `overrides` applied. The
+    /// registry and specs are shared with `self`, so the original router is
+    /// unaffected.
+    pub fn clone_for_turn(&self, overrides: TurnToolOverrides) -> ToolRouter {
+        Self {
+            registry: Arc::clone(&self.registry),
+            specs: Arc::clone(&self.specs),
+            turn_overrides: overrides,
+            fallbacks: self.fallbacks.clone(),
+            call_archive: self.call_archive.clone(),
+            call_ids: self.call_ids.clone(),
+            encryption_key: self.encryption_key.clone(),
+            structured_logging: self.structured_logging,
+            max_argument_size_bytes: self.max_argument_size_bytes,
+            schema_validation: self.schema_validation,
+            before_all: self.before_all.clone(),
+            after_all: self.after_all.clone(),
+            audit_hooks: self.audit_hooks.clone(),
+            dispatch_interceptor: self.dispatch_interceptor.clone(),
+            sandbox_permissions_hook: self.sandbox_permissions_hook.clone(),
+            chaos: self.chaos.clone(),
+            shell_tool_aliases: Arc::clone(&self.shell_tool_aliases),
+            tool_timeouts: Arc::clone(&self.tool_timeouts),
+            tool_call_permits: Arc::clone(&self.tool_call_permits),
+            mcp_servers: Arc::clone(&self.mcp_servers),
+            mcp_transport_retry: self.mcp_transport_retry,
+            output_limit: self.output_limit,
+            tool_filter: Arc::clone(&self.tool_filter),
+            read_only: self.read_only,
+            read_only_mcp_tools: Arc::clone(&self.read_only_mcp_tools),
+        }
+    }
+
+    /// Routes calls to `fallback` when `primary` fails with a non-fatal error.
+    /// The fallback receives the same arguments and its output is annotated
+    /// with `fallback_used: true`.
+    pub fn set_fallback(&mut self, primary: &str, fallback: &str) {
+        self.fallbacks
+            .insert(primary.to_string(), fallback.to_string());
+    }
+
+    /// Runs `hook` before every call dispatched through this router, whatever
+    /// the tool. See [`BeforeAllHook::before`].
+    pub fn register_before_all(&mut self, hook: Arc<dyn BeforeAllHook>) {
+        self.before_all.push(hook);
+    }
+
+    /// Runs `hook` after every call dispatched through this router, whatever
+    /// the tool or outcome.
+    pub fn register_after_all(&mut self, hook: Arc<dyn AfterAllHook>) {
+        self.after_all.push(hook);
+    }
+
+    /// Reports every call dispatched through this router to `hook`, whatever
+    /// the tool or outcome. Unlike the OTEL metrics, each record carries the
+    /// call's arguments, with well-known secrets redacted. See
+    /// [`AuditHook::record`].
+    pub fn register_audit_hook(&mut self, hook: Arc<dyn AuditHook>) {
+        self.audit_hooks.push(hook);
+    }
+
+    /// Hands every call dispatched through this router to `interceptor`,
+    /// replacing any previous interceptor. See
+    /// [`DispatchInterceptor::intercept`].
+    pub fn set_dispatch_interceptor(&mut self, interceptor: Arc<dyn DispatchInterceptor>) {
+        self.dispatch_interceptor = Some(interceptor);
+    }
+
+    /// Lets `hook` decide the sandbox permissions of every local shell call
+    /// dispatched through this router, or veto the command, replacing any
+    /// previous hook. Without a hook, calls keep the permissions they were
+    /// built with. See [`SandboxPermissionsHook::resolve`].
+    pub fn set_sandbox_permissions_hook(&mut self, hook: Arc<dyn SandboxPermissionsHook>) {
+        self.sandbox_permissions_hook = Some(hook);
+    }
+
+    /// Injects faults into every call dispatched from now on, as drawn from
+    /// `config`. Each fault is reported with an
+    /// [`EventMsg::ChaosFaultInjected`]. Calls that fail the router's own
+    /// checks are rejected before any fault is drawn.
+    pub fn enable_chaos(&mut self, config: ChaosConfig) {
+        self.chaos = Some(Arc::new(Chaos::new(config)));
+    }
+
+    /// Like [`Self::enable_chaos`], drawing from `chaos`, whose RNG may be
+    /// shared with other routers.
+    pub(crate) fn set_chaos(&mut self, chaos: Arc<Chaos>) {
+        self.chaos = Some(chaos);
+    }
+
+    pub fn set_mcp_servers(&mut self, servers: HashMap<String, McpServerConfig>) {
+        self.mcp_servers = Arc::new(servers);
+    }
+
+    /// Retries MCP calls that fail with [`FunctionCallError::McpTransport`]
+    /// under `policy` before reporting the failure to the model. Errors the
+    /// tool itself returns are never retried. Defaults to
+    /// [`RetryPolicy::default`]; `max_retries: 0` turns retries off.
+    pub fn set_mcp_transport_retry(&mut self, policy: RetryPolicy) {
+        self.mcp_transport_retry = policy;
+    }
+
+    /// Truncates the result of every call dispatched through this router to
+    /// `limit`, on top of any per-tool
+    /// [`ConfiguredToolSpec::max_output_bytes`]. Off by default.
+    pub fn set_output_limit(&mut self, limit: OutputLimit) {
+        self.output_limit = limit;
+    }
+
+    /// In read-only mode, tools that can only modify the workspace are not
+    /// advertised: `apply_patch`, dynamic tools, and MCP tools their server
+    /// does not annotate as read-only. Calls that would modify it, such as
+    /// shell commands that are not known to be safe, are rejected with a
+    /// message to the model. Off by default.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// Shares `call_ids` with this router, so that call ids seen by other
+    /// routers of the session are not run again by this one.
+    pub(crate) fn set_call_ids(&mut self, call_ids: CallIdDedupe) {
+        self.call_ids = call_ids;
+    }
+
+    /// Closes the session's connection to MCP server `server_name` so that
+    /// the next call to one of its tools reconnects, e.g. after the server
+    /// was redeployed. Returns `false` if there was no live connection.
+    pub async fn evict_mcp_connection(&self, session: &Session, server_name: &str) -> bool {
+        session
+            .services
+            .mcp_connection_manager
+            .write()
+            .await
+            .evict(server_name)
+    }
+
+    /// A `curl` command reproducing the HTTP request the MCP client makes for
+    /// `call`, with credentials replaced by a `$TOKEN` placeholder. `None`
+    /// unless `call` targets a streamable HTTP MCP server.
+    pub fn generate_curl_equivalent(&self, call: &ToolCall) -> Option<String> {
+        let ToolPayload::Mcp {
+            server,
+            tool,
+            raw_arguments,
+        } = &call.payload
+        else {
+            return None;
+        };
+        mcp_call_as_curl(self.mcp_servers.get(server)?, tool, raw_arguments)
+    }
+
+    /// Snapshot of the tools this router serves, the health of their MCP
+    /// servers and circuit breakers, and configuration that looks wrong.
+    pub fn diagnostics_report(&self) -> DiagnosticsReport {
+        let handler_names: BTreeSet<String> = self.registry.handler_names().into_iter().collect();
+        let mut tools: BTreeMap<String, ToolDiagnostics> = BTreeMap::new();
+        for config in self.specs.iter() {
+            let name = config.spec.name().to_string();
+            let status = if !handler_names.contains(&name) {
+                ToolStatus::MissingHandler
+            } else if self.is_disabled_for_turn(&name) {
+                ToolStatus::DisabledForTurn
+            } else {
+                ToolStatus::Enabled
+            };
+            tools.insert(
+                name.clone(),
+                ToolDiagnostics {
+                    fallback: self.fallbacks.get(&name).cloned(),
+                    name,
+                    status,
+                    supports_parallel_tool_calls: config.supports_parallel_tool_calls,
+                },
+            );
+        }
+        for name in &handler_names {
+            if self.is_shell_tool_alias(name) || tools.contains_key(name) {
+                continue;
+            }
+            let status = if self.is_disabled_for_turn(name) {
+                ToolStatus::DisabledForTurn
+            } else {
+                ToolStatus::Unadvertised
+            };
+            tools.insert(
+                name.clone(),
+                ToolDiagnostics {
+                    name: name.clone(),
+                    status,
+                    supports_parallel_tool_calls: false,
+                    fallback: self.fallbacks.get(name).cloned(),
+                },
+            );
+        }
+        let tools: Vec<ToolDiagnostics> = tools.into_values().collect();
+
+        let now = tokio::time::Instant::now();
+        let mut circuit_breakers: Vec<CircuitBreakerDiagnostics> = self
+            .registry
+            .circuit_states()
+            .into_iter()
+            .map(|(key, state)| CircuitBreakerDiagnostics::new(key, state, now))
+            .collect();
+        circuit_breakers.sort_by(|a, b| a.key.cmp(&b.key));
+
+        let mut anomalies = Vec::new();
+        for tool in &tools {
+            if tool.status == ToolStatus::MissingHandler {
+                anomalies.push(format!(
+                    "tool {} is advertised to the model but has no handler",
+                    tool.name
+                ));
+            }
+        }
+        let mut fallbacks: Vec<(&String, &String)> = self.fallbacks.iter().collect();
+        fallbacks.sort();
+        for (primary, fallback) in fallbacks {
+            if primary == fallback {
+                anomalies.push(format!("tool {primary} falls back to itself"));
+            } else if !handler_names.contains(fallback) {
+                anomalies.push(format!(
+                    "fallback {fallback} for tool {primary} has no handler"
+                ));
+            }
+        }
+        let mut disabled: Vec<&String> = self.turn_overrides.disabled_tools.iter().collect();
+        disabled.sort();
+        for name in disabled {
+            if !handler_names.contains(name) && !tools.iter().any(|tool| &tool.name == name) {
+                anomalies.push(format!("disabled tool {name} is not a known tool"));
+            }
+        }
+        if self.max_argument_size_bytes == Some(0) {
+            anomalies.push("max argument size is 0 bytes, so every call is rejected".to_string());
+        }
+
+        DiagnosticsReport {
+            mcp_servers: mcp_server_diagnostics(&tools, &circuit_breakers),
+            tools,
+            circuit_breakers,
+            anomalies,
+        }
+    }
 
-        Self { registry, specs }
+    fn fallback_for(&self, tool_name: &str) -> Option<&str> {
+        self.fallbacks
+            .get(tool_name)
+            .map(String::as_str)
+            .filter(|fallback| !self.is_disabled_for_turn(fallback))
     }
 
+    /// Includes the tools added by
+    /// [`ToolRouter::get_or_register_dynamic_tool`], after the built ones.
     pub fn specs(&self) -> Vec<ToolSpec> {
         self.specs
             .iter()
-            .map(|config| config.spec.clone())
+            .filter(|config| !self.registry.is_removed_dynamic_tool(config.spec.name()))
+            .cloned()
+            .chain(self.registry.runtime_dynamic_tool_specs())
+            .filter(|config| self.tool_filter.permits(config.spec.name(), None))
+            .filter(|config| !self.is_disabled_for_turn(config.spec.name()))
+            .map(|config| config.spec)
+            .collect()
+    }
+
+    /// Describes the tools in [`ToolRouter::specs`], in the same order.
+    pub fn tool_descriptors(&self) -> Vec<ToolDescriptor> {
+        let runtime_dynamic_tools = self.registry.runtime_dynamic_tool_specs();
+        self.specs
+            .iter()
+            .filter(|config| !self.registry.is_removed_dynamic_tool(config.spec.name()))
+            .chain(&runtime_dynamic_tools)
+            .filter(|config| self.tool_filter.permits(config.spec.name(), None))
+            .filter(|config| !self.is_disabled_for_turn(config.spec.name()))
+            .map(|config| {
+                let name = config.spec.name();
+                let mcp_server = self
+                    .registry
+                    .handler(name)
+                    .filter(|handler| handler.kind() == ToolKind::Mcp)
+                    .and_then(|_| split_qualified_tool_name(name))
+                    .map(|(server, _)| server);
+                ToolDescriptor {
+                    name: name.to_string(),
+                    supports_parallel_tool_calls: config.supports_parallel_tool_calls,
+                    mcp_server,
+                    dynamic: self.registry.is_dynamic_tool(name),
+                }
+            })
             .collect()
     }
 
+    /// Returns the tool named `spec.name`, registering `spec` as a dynamic
+    /// tool first if no such tool exists. Safe to call concurrently: each
+    /// name is registered at most once, and every caller gets the same tool.
+    /// The registration is shared with every router cloned from this one.
+    pub fn get_or_register_dynamic_tool(
+        &self,
+        spec: DynamicToolSpec,
+    ) -> Result<ToolRef, RegistrationError> {
+        self.registry.get_or_register_dynamic_tool(spec)
+    }
+
+    /// Registers `spec` as a dynamic tool without rebuilding the router.
+    /// Fails with [`RegistrationError::AlreadyRegistered`] if a tool with its
+    /// name exists. Like [`ToolRouter::get_or_register_dynamic_tool`], the
+    /// tool is shared with every router cloned from this one.
+    pub fn add_dynamic_tool(&self, spec: DynamicToolSpec) -> Result<(), RegistrationError> {
+        let name_is_built = self.specs.iter().any(|config| {
+            config.spec.name() == spec.name && !self.registry.is_removed_dynamic_tool(&spec.name)
+        });
+        if name_is_built {
+            return Err(RegistrationError::AlreadyRegistered { name: spec.name });
+        }
+        self.registry.add_dynamic_tool(spec)
+    }
+
+    /// Removes the dynamic tool `name` from the specs and the registry,
+    /// returning whether it existed. Calls already being dispatched finish;
+    /// later calls fail as calls to an unknown tool. Built-in tools cannot be
+    /// removed.
+    pub fn remove_dynamic_tool(&self, name: &str) -> bool {
+        self.registry.remove_dynamic_tool(name)
+    }
+
+    /// The spec advertised for `name`, if it is enabled this turn. Shell
+    /// aliases resolve to whichever shell spec is advertised.
+    pub fn get_spec(&self, name: &str) -> Option<&ConfiguredToolSpec> {
+        if self.is_disabled_for_turn(name) {
+            return None;
+        }
+        let find = |wanted: &str| {
+            self.specs.iter().find(|config| {
+                config.spec.name() == wanted
+                    && !self.is_disabled_for_turn(wanted)
+                    && !self.registry.is_removed_dynamic_tool(wanted)
+            })
+        };
+        find(name).or_else(|| {
+            if !self.is_shell_tool_alias(name) {
+                return None;
+            }
+            self.shell_tool_aliases.iter().find_map(|alias| find(alias))
+        })
+    }
+
+    fn is_shell_tool_alias(&self, name: &str) -> bool {
+        self.shell_tool_aliases.iter().any(|alias| alias == name)
+    }
+
+    /// Whether `name` is advertised and enabled this turn. Feature flags are
+    /// applied when the specs are built, so a tool whose feature is off has
+    /// no spec on this router.
+    pub fn is_tool_enabled(&self, name: &str) -> bool {
+        self.get_spec(name).is_some()
+    }
+
+    /// Shell aliases share the parallel support of whichever alias is
+    /// advertised.
     pub fn tool_supports_parallel(&self, tool_name: &str) -> bool {
+        let supports_parallel = |name: &str| {
+            self.specs
+                .iter()
+                .filter(|config| config.supports_parallel_tool_calls)
+                .any(|config| config.spec.name() == name)
+        };
+        supports_parallel(tool_name)
+            || (self.is_shell_tool_alias(tool_name)
+                && self
+                    .shell_tool_aliases
+                    .iter()
+                    .any(|alias| supports_parallel(alias)))
+    }
+
+    /// Adjacency list of [`ConfiguredToolSpec::tool_depends_on`] for every
+    /// tool enabled this turn, keyed by tool name.
+    pub fn tool_dependency_graph(&self) -> HashMap<String, Vec<String>> {
         self.specs
             .iter()
-            .filter(|config| config.supports_parallel_tool_calls)
-            .any(|config| config.spec.name() == tool_name)
+            .filter(|config| !self.is_disabled_for_turn(config.spec.name()))
+            .map(|config| {
+                (
+                    config.spec.name().to_string(),
+                    config.tool_depends_on.clone(),
+                )
+            })
+            .collect()
+    }
+
+    /// Checks whether calls to `name` can currently succeed. MCP tools are
+    /// probed by listing their server's tools, using the configs passed to
+    /// [`ToolRouter::set_mcp_servers`]. Other tools run in-process or on the
+    /// model provider's side, so they are only checked for a handler.
+    pub async fn tool_health_check(&self, name: &str) -> ToolHealthStatus {
+        if self.is_disabled_for_turn(name) {
+            return ToolHealthStatus::Degraded {
+                message: format!("tool {name} is disabled for this turn"),
+            };
+        }
+        let Some(handler) = self.registry.handler(name) else {
+            return match self.get_spec(name).map(|config| &config.spec) {
+                Some(ToolSpec::WebSearch { .. } | ToolSpec::ImageGeneration { .. }) => {
+                    ToolHealthStatus::Healthy { latency_ms: 0 }
+                }
+                Some(_) => ToolHealthStatus::Unhealthy {
+                    message: format!("tool {name} is advertised to the model but has no handler"),
+                },
+                None => ToolHealthStatus::Unhealthy {
+                    message: format!("unknown tool {name}"),
+                },
+            };
+        };
+        match handler.kind() {
+            ToolKind::Mcp => self.mcp_tool_health_check(name).await,
+            // GraphQL endpoints are chosen per call, so there is none to probe.
+            ToolKind::Function | ToolKind::GraphQL => ToolHealthStatus::Healthy { latency_ms: 0 },
+        }
+    }
+
+    async fn mcp_tool_health_check(&self, name: &str) -> ToolHealthStatus {
+        let Some((server, tool)) = split_qualified_tool_name(name) else {
+            return ToolHealthStatus::Degraded {
+                message: format!("tool {name} does not name its MCP server, so it was not probed"),
+            };
+        };
+        if let Some(CircuitState::Open { .. }) = self.registry.circuit_state(&server) {
+            return ToolHealthStatus::Unhealthy {
+                message: format!("circuit breaker for MCP server {server} is open"),
+            };
+        }
+        let Some(config) = self.mcp_servers.get(&server) else {
+            return ToolHealthStatus::Degraded {
+                message: format!("MCP server {server} has no config, so it was not probed"),
+            };
+        };
+        let started = Instant::now();
+        match list_server_tools(&server, config, OAuthCredentialsStoreMode::default()).await {
+            Ok(tools) if tools.iter().any(|listed| listed.name == tool) => {
+                ToolHealthStatus::Healthy {
+                    latency_ms: u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX),
+                }
+            }
+            Ok(_) => ToolHealthStatus::Unhealthy {
+                message: format!("MCP server {server} no longer serves tool {tool}"),
+            },
+            Err(err) => ToolHealthStatus::Unhealthy {
+                message: format!("MCP server {server} is unreachable: {err}"),
+            },
+        }
+    }
+
+    /// Logs a warning for every MCP tool whose live schema no longer matches
+    /// the schema this router was built with.
+    pub(crate) fn warn_on_mcp_schema_drift(&self, live_tools: &HashMap<String, ToolInfo>) {
+        let servers: BTreeSet<&str> = live_tools
+            .values()
+            .map(|info| info.server_name.as_str())
+            .collect();
+        for server in servers {
+            for drift in self.registry.validate_mcp_schema(server, live_tools) {
+                warn!("MCP server {server} schema drift: {drift}");
+            }
+        }
+    }
+
+    fn is_disabled_for_turn(&self, tool_name: &str) -> bool {
+        self.turn_overrides.disabled_tools.contains(tool_name)
+            || self.is_hidden_in_read_only(tool_name)
+    }
+
+    fn is_hidden_in_read_only(&self, tool_name: &str) -> bool {
+        if !self.read_only {
+            return false;
+        }
+        let is_mcp_tool = self
+            .registry
+            .handler(tool_name)
+            .is_some_and(|handler| handler.kind() == ToolKind::Mcp);
+        READ_ONLY_HIDDEN_TOOLS.contains(&tool_name)
+            || self.registry.is_dynamic_tool(tool_name)
+            || (is_mcp_tool && !self.read_only_mcp_tools.contains(tool_name))
     }
 
     #[instrument(level = "trace", skip_all, err)]
@@ -75,7 +899,11 @@ impl ToolRouter {
                 call_id,
                 ..
             } => {
-                if let Some((server, tool)) = session.parse_mcp_tool_name(&name).await {
+                let mcp_tool = session
+                    .parse_mcp_tool_name(&name)
+                    .await
+                    .map_err(|err| FunctionCallError::RespondToModel(err.to_string()))?;
+                if let Some((server, tool)) = mcp_tool {
                     Ok(Some(ToolCall {
                         tool_name: name,
                         call_id,
@@ -84,12 +912,16 @@ impl ToolRouter {
                             tool,
                             raw_arguments: arguments,
                         },
+                        tags: HashMap::new(),
+                        dry_run: false,
                     }))
                 } else {
                     Ok(Some(ToolCall {
                         tool_name: name,
                         call_id,
                         payload: ToolPayload::Function { arguments },
+                        tags: HashMap::new(),
+                        dry_run: false,
                     }))
                 }
             }
@@ -101,7 +933,25 @@ impl ToolRouter {
             } => Ok(Some(ToolCall {
                 tool_name: name,
                 call_id,
-                payload: ToolPayload::Custom { input },
+                payload: ToolPayload::Custom {
+                    input,
+                    attachments: Vec::new(),
+                },
+                tags: HashMap::new(),
+                dry_run: false,
+            })),
+            ResponseItem::CustomToolCallWithAttachments {
+                name,
+                input,
+                call_id,
+                attachments,
+                ..
+            } => Ok(Some(ToolCall {
+                tool_name: name,
+                call_id,
+                payload: ToolPayload::Custom { input, attachments },
+                tags: HashMap::new(),
+                dry_run: false,
             })),
             ResponseItem::LocalShellCall {
                 id,
@@ -115,24 +965,46 @@ impl ToolRouter {
 
                 match action {
                     LocalShellAction::Exec(exec) => {
+                        let prefix_rule = session
+                            .services
+                            .exec_policy
+                            .matching_allow_prefix_rule(&exec.command);
                         let params = ShellToolCallParams {
                             command: exec.command,
                             workdir: exec.working_directory,
                             timeout_ms: exec.timeout_ms,
                             sandbox_permissions: Some(SandboxPermissions::UseDefault),
                             additional_permissions: None,
-                            prefix_rule: None,
-                            justification: None,
+                            prefix_rule,
+                            justification: exec.justification,
+                            use_pty: false,
+                            env: exec.env,
                         };
                         Ok(Some(ToolCall {
                             tool_name: "local_shell".to_string(),
                             call_id,
                             payload: ToolPayload::LocalShell { params },
+                            tags: HashMap::new(),
+                            dry_run: false,
                         }))
                     }
                 }
             }
-            _ => Ok(None),
+            ResponseItem::UnsupportedToolCall {
+                item_type, call_id, ..
+            } => Err(FunctionCallError::UnsupportedToolCall { item_type, call_id }),
+            // Not tool calls, or calls the provider runs itself. Listed one by
+            // one so that a new tool call variant has to be handled here
+            // rather than being dropped.
+            ResponseItem::Message { .. }
+            | ResponseItem::Reasoning { .. }
+            | ResponseItem::FunctionCallOutput { .. }
+            | ResponseItem::CustomToolCallOutput { .. }
+            | ResponseItem::WebSearchCall { .. }
+            | ResponseItem::ImageGenerationCall { .. }
+            | ResponseItem::GhostSnapshot { .. }
+            | ResponseItem::Compaction { .. }
+            | ResponseItem::Other => Ok(None),
         }
     }
 
@@ -144,11 +1016,260 @@ impl ToolRouter {
         tracker: SharedTurnDiffTracker,
         call: ToolCall,
         source: ToolCallSource,
+    ) -> Result<ResponseInputItem, FunctionCallError> {
+        let call_id = call.call_id.clone();
+        let tool_name = call.tool_name.clone();
+        let tags = call.tags.clone();
+        let arguments = call.payload.log_payload().into_owned();
+        let mcp_server = match &call.payload {
+            ToolPayload::Mcp { server, .. } => Some(server.clone()),
+            _ => None,
+        };
+        let dispatched_at = SystemTime::now();
+        let started = Instant::now();
+        let session_id = session.conversation_id;
+        let audit_session = Arc::clone(&session);
+        let telemetry_turn = Arc::clone(&turn);
+        let _active_call = session
+            .services
+            .active_tool_calls
+            .start(&call_id, &tool_name);
+        let subscribers = session.services.tool_call_subscribers.clone();
+        subscribers.publish(|| ToolCallEvent::Started(call.clone()));
+        if !tags.is_empty() {
+            codex_otel::set_span_attributes(
+                &tracing::Span::current(),
+                tags.iter()
+                    .map(|(key, value)| (key.as_str(), value.as_str())),
+            );
+            session
+                .send_event(
+                    &turn,
+                    EventMsg::ToolCallStart(ToolCallStartEvent {
+                        call_id: call_id.clone(),
+                        tool_name: tool_name.clone(),
+                        metadata: tags.clone(),
+                    }),
+                )
+                .await;
+        }
+
+        let after_all_call = (!self.after_all.is_empty()).then(|| call.clone());
+        let result = match self.run_before_all(&call, source).await {
+            Ok(()) => match &self.dispatch_interceptor {
+                Some(interceptor) => {
+                    let real_dispatch: BoxedDispatchFn<'_> = Box::new(move |call| {
+                        self.dispatch_tool_call_unarchived(session, turn, tracker, call, source)
+                            .boxed()
+                    });
+                    interceptor.intercept(call, real_dispatch).await
+                }
+                None => {
+                    self.dispatch_tool_call_unarchived(session, turn, tracker, call, source)
+                        .await
+                }
+            },
+            Err(FunctionCallError::Fatal(message)) => Err(FunctionCallError::Fatal(message)),
+            Err(err) => Ok(Self::failure_response(
+                call_id.clone(),
+                matches!(call.payload, ToolPayload::Custom { .. }),
+                err,
+            )),
+        };
+        if let Some(call) = after_all_call {
+            for hook in &self.after_all {
+                hook.after(&call, &result).await;
+            }
+        }
+        subscribers.publish(|| match &result {
+            Ok(response) => ToolCallEvent::Completed {
+                call_id: call_id.clone(),
+                result: response.clone(),
+            },
+            Err(err) => ToolCallEvent::Failed {
+                call_id: call_id.clone(),
+                error: err.clone(),
+            },
+        });
+
+        let duration = started.elapsed();
+        if let Err(FunctionCallError::Fatal(reason)) = &result {
+            error!(
+                call_id = %call_id,
+                tool_name = %tool_name,
+                mcp_server = mcp_server.as_deref().unwrap_or_default(),
+                "tool call ended the turn: {reason}"
+            );
+            let mut tags = vec![("tool_name", tool_name.as_str())];
+            if let Some(server) = mcp_server.as_deref() {
+                tags.push(("server", server));
+            }
+            telemetry_turn
+                .session_telemetry
+                .counter(TOOL_FATAL_METRIC, 1, &tags);
+        }
+        if !self.audit_hooks.is_empty() {
+            let record = AuditRecord {
+                call_id: call_id.clone(),
+                tool_name: tool_name.clone(),
+                mcp_server,
+                arguments: redact_secrets(arguments.clone()),
+                outcome: AuditOutcome::of(&result),
+                duration,
+            };
+            for hook in &self.audit_hooks {
+                if let Err(err) = hook.record(&record) {
+                    warn!("audit hook failed for {call_id}: {err:#}");
+                }
+            }
+        }
+        if self.structured_logging {
+            dispatch_log::log_dispatch(&call_id, &tool_name, session_id, duration, &result);
+        }
+        let result_summary = match &result {
+            Ok(response) => summarize_response(response),
+            Err(err) => err.to_string(),
+        };
+        let (arguments, result_summary) = match &self.encryption_key {
+            Some(key) => {
+                if let Some(audit_log) = audit_session.services.audit_log.as_ref() {
+                    let entry = AuditEntry {
+                        call_id: call_id.clone(),
+                        tool_name: tool_name.clone(),
+                        tags,
+                        dispatched_at,
+                        duration,
+                        arguments,
+                        output: match &result {
+                            Ok(response) => response_text(response),
+                            Err(err) => err.to_string(),
+                        },
+                    };
+                    match EncryptedEntry::seal(&entry, key) {
+                        Ok(encrypted) => {
+                            audit_log
+                                .record(&telemetry_turn.sub_id, AuditEvent::ToolCall(encrypted))
+                                .await;
+                        }
+                        Err(err) => {
+                            warn!("failed to encrypt audit entry for {call_id}: {err}");
+                        }
+                    }
+                }
+                (
+                    ENCRYPTED_PLACEHOLDER.to_string(),
+                    ENCRYPTED_PLACEHOLDER.to_string(),
+                )
+            }
+            None => (arguments, result_summary),
+        };
+        self.call_archive.insert(
+            call_id,
+            ArchivedToolCall {
+                tool_name,
+                arguments,
+                dispatched_at,
+                result_summary,
+                duration,
+            },
+        );
+        result
+    }
+
+    /// Seals the arguments and output of every call dispatched from now on
+    /// with `key` and appends them to the session's audit log, if `[audit]`
+    /// is enabled. The plaintext call archive then only keeps a placeholder
+    /// for both fields.
+    pub fn with_encryption(mut self, key: EncryptionKey) -> Self {
+        self.encryption_key = Some(key);
+        self
+    }
+
+    /// Emits one structured event per dispatch, with the call id, tool name,
+    /// session id, duration, success flag, and error kind as fields. Install
+    /// [`dispatch_log::structured_json_layer`] to write them as JSON lines.
+    pub fn log_to_structured_json(mut self) -> Self {
+        self.structured_logging = true;
+        self
+    }
+
+    /// Adds `tags` to `call`, replacing any existing tags with the same keys.
+    pub fn tag_call<'a>(
+        mut call: ToolCall,
+        tags: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> ToolCall {
+        for (key, value) in tags {
+            call = call.with_tag(key, value);
+        }
+        call
+    }
+
+    /// Loads the tool bundles exported by the shared library at `path` and
+    /// registers them on this router. Returns the names of the new tools.
+    ///
+    /// Must be called before the router is shared (for example, before any
+    /// [`ToolRouter::clone_for_turn`]).
+    #[cfg(feature = "dynamic-plugins")]
+    pub fn load_plugin(&mut self, path: &Path) -> Result<Vec<String>, PluginLoadError> {
+        let registry = Arc::get_mut(&mut self.registry).ok_or(PluginLoadError::RouterShared)?;
+        let bundles = dynamic_plugins::open_tool_bundles(path)?;
+        dynamic_plugins::register_bundles(registry, Arc::make_mut(&mut self.specs), bundles)
+    }
+
+    /// Compiles the WebAssembly module at `path` and registers the tools it
+    /// declares on this router. Each call runs in a fresh, import-free
+    /// instance bounded by `limits`. Returns the names of the new tools.
+    ///
+    /// Must be called before the router is shared (for example, before any
+    /// [`ToolRouter::clone_for_turn`]).
+    #[cfg(feature = "wasm-plugins")]
+    pub fn load_wasm_plugin(
+        &mut self,
+        path: &Path,
+        limits: WasmPluginLimits,
+    ) -> Result<Vec<String>, WasmPluginError> {
+        let registry = Arc::get_mut(&mut self.registry).ok_or(WasmPluginError::RouterShared)?;
+        let plugin = wasm_plugins::WasmPlugin::open(path, limits)?;
+        wasm_plugins::register_wasm_plugin(registry, Arc::make_mut(&mut self.specs), path, plugin)
+    }
+
+    /// Looks up a call previously dispatched through this router, or through
+    /// any router sharing its archive; see [`Self::set_call_archive`].
+    pub fn get_call_by_id(&self, call_id: &str) -> Option<ArchivedToolCall> {
+        self.call_archive.get(call_id)
+    }
+
+    /// Records calls dispatched through this router in `archive`, which
+    /// outlives the router when it belongs to the session.
+    pub(crate) fn set_call_archive(&mut self, archive: CallArchive) {
+        self.call_archive = archive;
+    }
+
+    async fn run_before_all(
+        &self,
+        call: &ToolCall,
+        source: ToolCallSource,
+    ) -> Result<(), FunctionCallError> {
+        for hook in &self.before_all {
+            hook.before(call, source).await?;
+        }
+        Ok(())
+    }
+
+    async fn dispatch_tool_call_unarchived(
+        &self,
+        session: Arc<Session>,
+        turn: Arc<TurnContext>,
+        tracker: SharedTurnDiffTracker,
+        call: ToolCall,
+        source: ToolCallSource,
     ) -> Result<ResponseInputItem, FunctionCallError> {
         let ToolCall {
             tool_name,
             call_id,
             payload,
+            tags: _,
+            dry_run,
         } = call;
         let payload_outputs_custom = matches!(payload, ToolPayload::Custom { .. });
         let failure_call_id = call_id.clone();
@@ -168,63 +1289,652 @@ impl ToolRouter {
             ));
         }
 
-        let invocation = ToolInvocation {
-            session,
-            turn,
-            tracker,
-            call_id,
-            tool_name,
-            payload,
-        };
-
-        match self.registry.dispatch(invocation).await {
-            Ok(response) => Ok(response),
-            Err(FunctionCallError::Fatal(message)) => Err(FunctionCallError::Fatal(message)),
-            Err(err) => Ok(Self::failure_response(
+        if let Err(err) = self.check_argument_size(&payload) {
+            return Ok(Self::failure_response(
+                failure_call_id,
+                payload_outputs_custom,
+                err,
+            ));
+        }
+
+        if self.schema_validation
+            && let ToolPayload::Function { arguments } = &payload
+            && let Err(err) = self.check_argument_schema(&tool_name, arguments)
+        {
+            return Ok(Self::failure_response(
+                failure_call_id,
+                payload_outputs_custom,
+                err,
+            ));
+        }
+
+        let mcp_server = match &payload {
+            ToolPayload::Mcp { server, .. } => Some(server.as_str()),
+            _ => None,
+        };
+        if !self.tool_filter.permits(&tool_name, mcp_server) {
+            let err = FunctionCallError::RespondToModel(format!(
+                "tool {tool_name} is not permitted in this deployment"
+            ));
+            return Ok(Self::failure_response(
+                failure_call_id,
+                payload_outputs_custom,
+                err,
+            ));
+        }
+
+        if self.is_hidden_in_read_only(&tool_name) {
+            let err = FunctionCallError::RespondToModel(format!(
+                "tool {tool_name} is unavailable in read-only mode"
+            ));
+            return Ok(Self::failure_response(
+                failure_call_id,
+                payload_outputs_custom,
+                err,
+            ));
+        }
+
+        if self.is_disabled_for_turn(&tool_name) {
+            let err = FunctionCallError::RespondToModel(format!(
+                "tool {tool_name} is disabled for this turn"
+            ));
+            return Ok(Self::failure_response(
+                failure_call_id,
+                payload_outputs_custom,
+                err,
+            ));
+        }
+
+        // Dry runs never execute, so they may describe a mutating call.
+        if self.read_only
+            && !dry_run
+            && let Some(handler) = self.registry.handler(&tool_name)
+        {
+            let invocation = ToolInvocation {
+                session: Arc::clone(&session),
+                turn: Arc::clone(&turn),
+                tracker: Arc::clone(&tracker),
+                call_id: call_id.clone(),
+                tool_name: tool_name.clone(),
+                payload: payload.clone(),
+                dry_run,
+            };
+            if handler.is_mutating(&invocation).await {
+                let err = FunctionCallError::RespondToModel(format!(
+                    "{tool_name} call rejected: it could modify the workspace, which is not allowed in read-only mode. Use read-only commands to investigate and propose a plan instead."
+                ));
+                return Ok(Self::failure_response(
+                    failure_call_id,
+                    payload_outputs_custom,
+                    err,
+                ));
+            }
+        }
+
+        // A repeated call id gets the first call's response instead of running
+        // the tool again. Dry runs have no side effects to protect.
+        let reservation = if dry_run {
+            None
+        } else {
+            match self.call_ids.reserve(&call_id) {
+                Ok(reservation) => Some(reservation),
+                Err(DuplicateCall::Completed(response)) => return Ok(response),
+                // The running call reports its own output under this id;
+                // answering the duplicate as well would give the id two.
+                Err(DuplicateCall::InFlight) => {
+                    return Err(FunctionCallError::DuplicateCallId { call_id });
+                }
+            }
+        };
+
+        let mut payload = payload;
+        if let Some(hook) = &self.sandbox_permissions_hook
+            && let ToolPayload::LocalShell { params } = &mut payload
+        {
+            match hook.resolve(params).await {
+                Ok(permissions) => params.sandbox_permissions = Some(permissions),
+                Err(FunctionCallError::Fatal(message)) => {
+                    return Err(FunctionCallError::Fatal(message));
+                }
+                Err(err) => {
+                    return Ok(Self::failure_response(
+                        failure_call_id,
+                        payload_outputs_custom,
+                        err,
+                    ));
+                }
+            }
+        }
+
+        if let Some(chaos) = self.chaos.as_ref().filter(|_| !dry_run) {
+            let plan = chaos.plan();
+            for fault_type in plan.fault_types() {
+                session
+                    .send_event(
+                        &turn,
+                        EventMsg::ChaosFaultInjected(ChaosFaultInjectedEvent {
+                            call_id: call_id.clone(),
+                            fault_type,
+                        }),
+                    )
+                    .await;
+            }
+            if let Some(latency) = plan.latency {
+                tokio::time::sleep(latency).await;
+            }
+            if plan.fail {
+                let err = FunctionCallError::RespondToModel(format!(
+                    "chaos: injected failure for {tool_name}"
+                ));
+                return Ok(Self::failure_response(
+                    failure_call_id,
+                    payload_outputs_custom,
+                    err,
+                ));
+            }
+            if plan.corrupt_payload {
+                payload = corrupt_payload(payload);
+            }
+        }
+
+        // Calls from js_repl run inside a js_repl call that already holds a
+        // permit, so they must not wait for another one. Queued calls do not
+        // start their timeout until they hold a permit.
+        let _permit = match source {
+            ToolCallSource::Direct => self.tool_call_permits.acquire().await.ok(),
+            ToolCallSource::JsRepl => None,
+        };
+
+        let invocation = ToolInvocation {
+            session,
+            turn,
+            tracker,
+            call_id,
+            tool_name: tool_name.clone(),
+            payload,
+            dry_run,
+        };
+        let fallback_invocation = self
+            .fallback_for(&tool_name)
+            .map(|fallback| ToolInvocation {
+                tool_name: fallback.to_string(),
+                ..invocation.clone()
+            });
+
+        let result = match self.dispatch_with_transport_retry(invocation).await {
+            Err(err) if !matches!(err, FunctionCallError::Fatal(_)) => match fallback_invocation {
+                Some(fallback_invocation) => {
+                    let fallback = fallback_invocation.tool_name.clone();
+                    warn!("tool {tool_name} failed ({err}); falling back to {fallback}");
+                    self.dispatch_with_timeout(fallback_invocation)
+                        .await
+                        .map(|response| annotate_fallback(response, &tool_name, &fallback))
+                }
+                None => Err(err),
+            },
+            result => result,
+        };
+
+        let result = match result {
+            Ok(response) => Ok(self.output_limit.apply(response)),
+            Err(FunctionCallError::Fatal(message)) => Err(FunctionCallError::Fatal(message)),
+            Err(err) => Ok(Self::failure_response(
                 failure_call_id,
                 payload_outputs_custom,
                 err,
             )),
+        };
+        if let (Some(reservation), Ok(response)) = (reservation, &result) {
+            reservation.complete(response);
+        }
+        result
+    }
+
+    /// Dispatches `invocation`, retrying MCP calls whose connection failed
+    /// under [`Self::set_mcp_transport_retry`]'s policy. Each attempt gets its
+    /// own timeout.
+    async fn dispatch_with_transport_retry(
+        &self,
+        invocation: ToolInvocation,
+    ) -> Result<ResponseInputItem, FunctionCallError> {
+        if !matches!(invocation.payload, ToolPayload::Mcp { .. }) {
+            return self.dispatch_with_timeout(invocation).await;
+        }
+        let policy = self.mcp_transport_retry;
+        let mut retry = 0;
+        loop {
+            match self.dispatch_with_timeout(invocation.clone()).await {
+                Err(FunctionCallError::McpTransport(message)) if retry < policy.max_retries => {
+                    retry += 1;
+                    let delay = policy.backoff(retry);
+                    warn!(
+                        "retrying {} after MCP transport failure ({retry}/{}) in {delay:?}: {message}",
+                        invocation.tool_name, policy.max_retries
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Dispatches `invocation` under the shorter of the tool's own timeout
+    /// and the turn's. Errors the tool returns in time, fatal ones included,
+    /// pass through unchanged.
+    ///
+    /// Timed-out calls are observed like any other failure; see
+    /// [`Observability`]. The structured dispatch log is left to
+    /// [`Self::dispatch_tool_call`], which logs each call once.
+    async fn dispatch_with_timeout(
+        &self,
+        invocation: ToolInvocation,
+    ) -> Result<ResponseInputItem, FunctionCallError> {
+        let tool_timeout = self.tool_timeouts.get(&invocation.tool_name).copied();
+        let timeout = match (tool_timeout, self.turn_overrides.tool_timeout) {
+            (Some(tool), Some(turn)) => Some(tool.min(turn)),
+            (tool, turn) => tool.or(turn),
+        };
+        let observability = Observability::new(invocation.turn.session_telemetry.clone());
+        let Some(timeout) = timeout else {
+            return self
+                .registry
+                .dispatch_with_observability(invocation, &observability)
+                .await;
+        };
+        let call = ObservedCall::for_invocation(&invocation);
+        let started = Instant::now();
+        observability
+            .observe(&call, async {
+                match tokio::time::timeout(timeout, self.registry.dispatch(invocation)).await {
+                    Ok(result) => result,
+                    Err(_) => Err(FunctionCallError::TimedOut {
+                        tool_name: call.tool_name.clone(),
+                        elapsed_ms: started.elapsed().as_millis(),
+                    }),
+                }
+            })
+            .await
+    }
+
+    /// Dispatches `calls` one at a time, in order, regardless of whether the
+    /// tools support parallel execution. Each call finishes before the next
+    /// one starts, so later calls observe the side effects of earlier ones.
+    ///
+    /// A fatal error stops the chain; calls after it are not dispatched.
+    pub async fn dispatch_ordered(
+        &self,
+        session: Arc<Session>,
+        turn: Arc<TurnContext>,
+        tracker: SharedTurnDiffTracker,
+        calls: Vec<ToolCall>,
+        source: ToolCallSource,
+    ) -> Result<Vec<ResponseInputItem>, FunctionCallError> {
+        let mut responses = Vec::with_capacity(calls.len());
+        for call in calls {
+            let response = self
+                .dispatch_tool_call(
+                    Arc::clone(&session),
+                    Arc::clone(&turn),
+                    Arc::clone(&tracker),
+                    call,
+                    source,
+                )
+                .await?;
+            responses.push(response);
+        }
+        Ok(responses)
+    }
+
+    /// Dispatches `calls` and returns one response per call, in the order of
+    /// `calls`, except that a call whose id is still running gets none: the
+    /// running call answers for that id. Consecutive calls to tools that support parallel execution run
+    /// concurrently, within the [`ToolsConfig::max_parallel_tool_calls`] cap;
+    /// a call to any other tool waits for the calls before it and runs alone.
+    ///
+    /// Unlike [`ToolRouter::dispatch_ordered`], ordinary errors do not stop
+    /// the batch: every such failed call gets a failure response. A
+    /// [`FunctionCallError::Fatal`] error ends the turn, so it is returned
+    /// once the calls running alongside it finish, and no later call starts.
+    pub async fn dispatch_tool_calls(
+        &self,
+        session: Arc<Session>,
+        turn: Arc<TurnContext>,
+        tracker: SharedTurnDiffTracker,
+        calls: Vec<ToolCall>,
+        source: ToolCallSource,
+    ) -> Result<Vec<ResponseInputItem>, FunctionCallError> {
+        let mut responses = Vec::with_capacity(calls.len());
+        let mut parallel_run = Vec::new();
+        for call in calls {
+            if self.tool_supports_parallel(&call.tool_name) {
+                parallel_run.push(call);
+                continue;
+            }
+            let run = parallel_run
+                .drain(..)
+                .map(|call| self.dispatch_or_failure(&session, &turn, &tracker, call, source));
+            for response in futures::future::join_all(run).await {
+                responses.extend(response?);
+            }
+            responses.extend(
+                self.dispatch_or_failure(&session, &turn, &tracker, call, source)
+                    .await?,
+            );
+        }
+        let run = parallel_run
+            .into_iter()
+            .map(|call| self.dispatch_or_failure(&session, &turn, &tracker, call, source));
+        for response in futures::future::join_all(run).await {
+            responses.extend(response?);
+        }
+        Ok(responses)
+    }
+
+    /// Turns every error but [`FunctionCallError::Fatal`] into a failure
+    /// response for the model. A duplicate of a running call gets no
+    /// response.
+    async fn dispatch_or_failure(
+        &self,
+        session: &Arc<Session>,
+        turn: &Arc<TurnContext>,
+        tracker: &SharedTurnDiffTracker,
+        call: ToolCall,
+        source: ToolCallSource,
+    ) -> Result<Option<ResponseInputItem>, FunctionCallError> {
+        let call_id = call.call_id.clone();
+        let payload_outputs_custom = matches!(call.payload, ToolPayload::Custom { .. });
+        match self
+            .dispatch_tool_call(
+                Arc::clone(session),
+                Arc::clone(turn),
+                Arc::clone(tracker),
+                call,
+                source,
+            )
+            .await
+        {
+            Ok(response) => Ok(Some(response)),
+            Err(err @ FunctionCallError::Fatal(_)) => Err(err),
+            Err(FunctionCallError::DuplicateCallId { .. }) => Ok(None),
+            Err(err) => Ok(Some(Self::failure_response(
+                call_id,
+                payload_outputs_custom,
+                err,
+            ))),
+        }
+    }
+
+    /// Rejects payloads larger than the configured `max_argument_size_bytes`.
+    pub fn check_argument_size(&self, payload: &ToolPayload) -> Result<(), FunctionCallError> {
+        let Some(limit_bytes) = self.max_argument_size_bytes else {
+            return Ok(());
+        };
+        let size_bytes = payload.log_payload().len();
+        if size_bytes > limit_bytes {
+            return Err(FunctionCallError::PayloadTooLarge {
+                size_bytes,
+                limit_bytes,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks every call in `calls` without dispatching any of them, so the
+    /// argument errors of a whole parallel batch reach the model in one
+    /// round trip. Every call id is a key of the result; an empty list means
+    /// the call is valid. Arguments are checked against the top-level
+    /// properties of the tool's advertised schema.
+    pub fn batch_validate(&self, calls: &[ToolCall]) -> HashMap<String, Vec<ValidationError>> {
+        let runtime_dynamic_tools = self.registry.runtime_dynamic_tool_specs();
+        let mut seen = HashSet::new();
+        let mut results: HashMap<String, Vec<ValidationError>> = HashMap::new();
+        for call in calls {
+            let errors = results.entry(call.call_id.clone()).or_default();
+            if !seen.insert(call.call_id.as_str()) {
+                errors.push(ValidationError::DuplicateCallId);
+            }
+            errors.extend(self.validate_call(call, &runtime_dynamic_tools));
+        }
+        results
+    }
+
+    fn validate_call(
+        &self,
+        call: &ToolCall,
+        runtime_dynamic_tools: &[ConfiguredToolSpec],
+    ) -> Vec<ValidationError> {
+        let tool_name = call.tool_name.as_str();
+        if self.is_disabled_for_turn(tool_name) {
+            return vec![ValidationError::DisabledForTurn {
+                tool_name: tool_name.to_string(),
+            }];
+        }
+        if self.registry.handler(tool_name).is_none() {
+            return vec![ValidationError::UnknownTool {
+                tool_name: tool_name.to_string(),
+            }];
+        }
+
+        let mut errors = Vec::new();
+        if let Err(FunctionCallError::PayloadTooLarge {
+            size_bytes,
+            limit_bytes,
+        }) = self.check_argument_size(&call.payload)
+        {
+            errors.push(ValidationError::PayloadTooLarge {
+                size_bytes,
+                limit_bytes,
+            });
+        }
+        let arguments = match &call.payload {
+            ToolPayload::Function { arguments } => arguments,
+            ToolPayload::Mcp { raw_arguments, .. } => raw_arguments,
+            _ => return errors,
+        };
+        let schema = self.argument_schema(tool_name, runtime_dynamic_tools);
+        errors.extend(validate_arguments(arguments, schema));
+        errors
+    }
+
+    /// Rejects function arguments that do not match the top-level properties
+    /// of the tool's input schema, naming every offending field.
+    fn check_argument_schema(
+        &self,
+        tool_name: &str,
+        arguments: &str,
+    ) -> Result<(), FunctionCallError> {
+        let runtime_dynamic_tools = self.registry.runtime_dynamic_tool_specs();
+        let schema = self.argument_schema(tool_name, &runtime_dynamic_tools);
+        let errors = validate_arguments(arguments, schema);
+        if errors.is_empty() {
+            return Ok(());
         }
+        let errors = errors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ");
+        Err(FunctionCallError::RespondToModel(format!(
+            "invalid arguments for {tool_name}: {errors}"
+        )))
     }
 
+    fn argument_schema<'a>(
+        &'a self,
+        tool_name: &str,
+        runtime_dynamic_tools: &'a [ConfiguredToolSpec],
+    ) -> Option<&'a JsonSchema> {
+        self.get_spec(tool_name)
+            .or_else(|| {
+                runtime_dynamic_tools
+                    .iter()
+                    .find(|config| config.spec.name() == tool_name)
+            })
+            .and_then(|config| match &config.spec {
+                ToolSpec::Function(tool) => Some(&tool.parameters),
+                _ => None,
+            })
+    }
+
+    /// Reports `err` to the model as the call's output, tagged with
+    /// [`FunctionCallError::code`] as its `error_code`.
     fn failure_response(
         call_id: String,
         payload_outputs_custom: bool,
         err: FunctionCallError,
     ) -> ResponseInputItem {
-        let message = err.to_string();
+        let output = codex_protocol::models::FunctionCallOutputPayload {
+            body: FunctionCallOutputBody::Text(err.to_string()),
+            success: Some(false),
+            error_code: Some(err.code().to_string()),
+        };
         if payload_outputs_custom {
-            ResponseInputItem::CustomToolCallOutput {
-                call_id,
-                output: codex_protocol::models::FunctionCallOutputPayload {
-                    body: FunctionCallOutputBody::Text(message),
-                    success: Some(false),
-                },
-            }
+            ResponseInputItem::CustomToolCallOutput { call_id, output }
         } else {
+            ResponseInputItem::FunctionCallOutput { call_id, output }
+        }
+    }
+}
+
+fn summarize_response(response: &ResponseInputItem) -> String {
+    telemetry_preview(&response_text(response))
+}
+
+/// The full output of `response`, as text.
+fn response_text(response: &ResponseInputItem) -> String {
+    match response {
+        ResponseInputItem::FunctionCallOutput { output, .. }
+        | ResponseInputItem::CustomToolCallOutput { output, .. } => output.to_string(),
+        ResponseInputItem::McpToolCallOutput { result, .. } => match result {
+            Ok(result) => format!("{:?}", result.content),
+            Err(err) => err.clone(),
+        },
+        ResponseInputItem::Message { .. } => String::new(),
+    }
+}
+
+/// Prefixes a fallback result with a note so the model knows which tool
+/// actually produced it. MCP results are returned unchanged.
+fn annotate_fallback(
+    response: ResponseInputItem,
+    primary: &str,
+    fallback: &str,
+) -> ResponseInputItem {
+    let note = format!("fallback_used: true ({primary} failed; result produced by {fallback})");
+    let annotate = |mut output: FunctionCallOutputPayload| {
+        output.body = match output.body {
+            FunctionCallOutputBody::Text(text) => {
+                FunctionCallOutputBody::Text(format!("{note}\n{text}"))
+            }
+            FunctionCallOutputBody::ContentItems(mut items) => {
+                items.insert(
+                    0,
+                    FunctionCallOutputContentItem::InputText { text: note.clone() },
+                );
+                FunctionCallOutputBody::ContentItems(items)
+            }
+            other => other,
+        };
+        output
+    };
+    match response {
+        ResponseInputItem::FunctionCallOutput { call_id, output } => {
             ResponseInputItem::FunctionCallOutput {
                 call_id,
-                output: codex_protocol::models::FunctionCallOutputPayload {
-                    body: FunctionCallOutputBody::Text(message),
-                    success: Some(false),
-                },
+                output: annotate(output),
+            }
+        }
+        ResponseInputItem::CustomToolCallOutput { call_id, output } => {
+            ResponseInputItem::CustomToolCallOutput {
+                call_id,
+                output: annotate(output),
             }
         }
+        other => other,
     }
 }
+
 #[cfg(test)]
 mod tests {
+    use std::path::Path;
     use std::sync::Arc;
 
     use crate::codex::make_session_and_context;
+    use crate::codex::make_session_and_context_with_rx;
     use crate::tools::context::ToolPayload;
     use crate::turn_diff_tracker::TurnDiffTracker;
     use codex_protocol::models::ResponseInputItem;
 
+    use super::ENCRYPTED_PLACEHOLDER;
     use super::ToolCall;
     use super::ToolCallSource;
     use super::ToolRouter;
+    use super::TurnToolOverrides;
+    use crate::audit::AuditEvent;
+    use crate::audit::AuditLog;
+    use crate::audit::read_audit_log;
+    use crate::client_common::tools::ResponsesApiTool;
+    use crate::client_common::tools::ToolSpec;
+    use crate::function_tool::FunctionCallError;
+    use crate::tools::audit::AuditHook;
+    use crate::tools::audit::AuditOutcome;
+    use crate::tools::audit::AuditRecord;
+    use crate::tools::audit::EncryptedEntry;
+    use crate::tools::audit::EncryptionKey;
+    use crate::tools::audit::decrypt_entry;
+    use crate::tools::call_events::ToolCallEvent;
+    use crate::tools::chaos::ChaosConfig;
+    use crate::tools::compose::ComposeError;
+    use crate::tools::compose::ComposeStep;
+    use crate::tools::context::ToolInvocation;
+    use crate::tools::context::ToolOutput;
+    use crate::tools::diagnostics::ToolDescriptor;
+    use crate::tools::diagnostics::ToolHealthStatus;
+    use crate::tools::dispatch_log::structured_json_layer;
+    use crate::tools::handlers::ReadFileHandler;
+    use crate::tools::middleware::AfterAllHook;
+    use crate::tools::middleware::BeforeAllHook;
+    use crate::tools::middleware::BoxedDispatchFn;
+    use crate::tools::middleware::DispatchInterceptor;
+    use crate::tools::middleware::SandboxPermissionsHook;
+    use crate::tools::output_transform::OutputLimit;
+    use crate::tools::registry::ConfiguredToolSpec;
+    use crate::tools::registry::RegistrationError;
+    use crate::tools::registry::ToolHandler;
+    use crate::tools::registry::ToolKind;
+    use crate::tools::registry::ToolRegistry;
+    use crate::tools::resilience::CircuitBreakerConfig;
+    use crate::tools::resilience::ResiliencyPolicy;
+    use crate::tools::resilience::RetryPolicy;
+    use crate::tools::spec::SHELL_TOOL_ALIASES;
+    use crate::tools::spec::ToolFilter;
+    use crate::tools::spec::parse_tool_input_schema;
+    use crate::tools::validation::ValidationError;
+    use async_trait::async_trait;
+    use codex_otel::metrics::MetricsClient;
+    use codex_otel::metrics::MetricsConfig;
+    use codex_protocol::dynamic_tools::DynamicToolSpec;
+    use codex_protocol::models::FunctionCallOutputBody;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use codex_protocol::models::ResponseItem;
+    use codex_protocol::models::SandboxPermissions;
+    use codex_protocol::models::ShellToolCallParams;
+    use codex_protocol::models::ToolCallAttachment;
+    use codex_protocol::openai_models::ConfigShellToolType;
+    use codex_protocol::protocol::ChaosFaultType;
+    use codex_protocol::protocol::EventMsg;
+    use futures::StreamExt;
+    use opentelemetry_sdk::metrics::InMemoryMetricExporter;
+    use opentelemetry_sdk::metrics::data::AggregatedMetrics;
+    use opentelemetry_sdk::metrics::data::MetricData;
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+    use std::collections::HashMap;
+    use std::collections::HashSet;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+    use tracing_subscriber::prelude::*;
 
     #[tokio::test]
     async fn js_repl_tools_only_blocks_direct_tool_calls() -> anyhow::Result<()> {
@@ -259,6 +1969,8 @@ mod tests {
             payload: ToolPayload::Function {
                 arguments: "{}".to_string(),
             },
+            tags: HashMap::new(),
+            dry_run: false,
         };
         let tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
         let response = router
@@ -312,6 +2024,8 @@ mod tests {
             payload: ToolPayload::Function {
                 arguments: "{}".to_string(),
             },
+            tags: HashMap::new(),
+            dry_run: false,
         };
         let tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
         let response = router
@@ -331,4 +2045,3581 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn clone_for_turn_isolates_overrides() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let session = Arc::new(session);
+        let turn = Arc::new(turn);
+        let router = ToolRouter::from_config(
+            &turn.tools_config,
+            None,
+            None,
+            turn.dynamic_tools.as_slice(),
+        );
+
+        let turn_router = router.clone_for_turn(TurnToolOverrides {
+            disabled_tools: ["update_plan".to_string()].into_iter().collect(),
+            ..Default::default()
+        });
+
+        let has_plan_tool = |router: &ToolRouter| {
+            router
+                .specs()
+                .iter()
+                .any(|spec| spec.name() == "update_plan")
+        };
+        assert!(has_plan_tool(&router));
+        assert!(!has_plan_tool(&turn_router));
+
+        let call = ToolCall {
+            tool_name: "update_plan".to_string(),
+            call_id: "call-3".to_string(),
+            payload: ToolPayload::Function {
+                arguments: "{}".to_string(),
+            },
+            tags: HashMap::new(),
+            dry_run: false,
+        };
+        let tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
+        let response = turn_router
+            .dispatch_tool_call(
+                Arc::clone(&session),
+                Arc::clone(&turn),
+                Arc::clone(&tracker),
+                call.clone(),
+                ToolCallSource::Direct,
+            )
+            .await?;
+        match response {
+            ResponseInputItem::FunctionCallOutput { output, .. } => {
+                let content = output.text_content().unwrap_or_default();
+                assert!(
+                    content.contains("disabled for this turn"),
+                    "unexpected tool call message: {content}",
+                );
+            }
+            other => panic!("expected function call output, got {other:?}"),
+        }
+
+        let response = router
+            .dispatch_tool_call(session, turn, tracker, call, ToolCallSource::Direct)
+            .await?;
+        match response {
+            ResponseInputItem::FunctionCallOutput { output, .. } => {
+                let content = output.text_content().unwrap_or_default();
+                assert!(
+                    !content.contains("disabled for this turn"),
+                    "original router should not inherit turn overrides"
+                );
+            }
+            other => panic!("expected function call output, got {other:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_spec_finds_registered_tools_and_shell_aliases() {
+        let (_session, mut turn) = make_session_and_context().await;
+        turn.tools_config.shell_type = ConfigShellToolType::Local;
+        let router = ToolRouter::from_config(
+            &turn.tools_config,
+            None,
+            None,
+            turn.dynamic_tools.as_slice(),
+        );
+
+        let plan = router.get_spec("update_plan").expect("plan tool spec");
+        assert_eq!(plan.spec.name(), "update_plan");
+        assert!(router.get_spec("not_a_tool").is_none());
+        for alias in ["shell", "container.exec", "local_shell"] {
+            let spec = router.get_spec(alias).expect("shell alias resolves");
+            assert_eq!(spec.spec.name(), "local_shell");
+            assert!(spec.supports_parallel_tool_calls);
+        }
+
+        let turn_router = router.clone_for_turn(TurnToolOverrides {
+            disabled_tools: ["update_plan".to_string()].into_iter().collect(),
+            ..Default::default()
+        });
+        assert!(turn_router.get_spec("update_plan").is_none());
+    }
+
+    #[tokio::test]
+    async fn tool_filter_hides_denied_tools_and_rejects_their_calls() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let mcp_tool = |name: &str| rmcp::model::Tool {
+            name: name.to_string().into(),
+            title: None,
+            description: None,
+            input_schema: Arc::new(rmcp::model::object(
+                serde_json::json!({"type": "object", "properties": {}}),
+            )),
+            output_schema: None,
+            annotations: None,
+            execution: None,
+            icons: None,
+            meta: None,
+        };
+        let config = turn.tools_config.clone().with_tool_filter(ToolFilter {
+            allow: Some(vec![
+                "update_plan".to_string(),
+                "docs".to_string(),
+                "mcp__wiki__lookup".to_string(),
+            ]),
+            deny: vec!["update_plan".to_string(), "mcp__docs__delete".to_string()],
+        });
+        let router = ToolRouter::from_config(
+            &config,
+            Some(HashMap::from([
+                ("mcp__docs__search".to_string(), mcp_tool("search")),
+                ("mcp__docs__delete".to_string(), mcp_tool("delete")),
+                ("mcp__wiki__lookup".to_string(), mcp_tool("lookup")),
+            ])),
+            None,
+            &[],
+        );
+
+        let mut names = router
+            .specs()
+            .iter()
+            .map(|spec| spec.name().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["mcp__docs__search", "mcp__wiki__lookup"]);
+
+        let response = router
+            .dispatch_tool_call(
+                Arc::new(session),
+                Arc::new(turn),
+                Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                ToolCall {
+                    tool_name: "update_plan".to_string(),
+                    call_id: "call-1".to_string(),
+                    payload: ToolPayload::Function {
+                        arguments: r#"{"plan":[]}"#.to_string(),
+                    },
+                    tags: HashMap::new(),
+                    dry_run: false,
+                },
+                ToolCallSource::Direct,
+            )
+            .await?;
+        let ResponseInputItem::FunctionCallOutput { output, .. } = response else {
+            panic!("expected function call output, got {response:?}");
+        };
+        assert_eq!(
+            output.text_content(),
+            Some("tool update_plan is not permitted in this deployment")
+        );
+        assert_eq!(output.success, Some(false));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_only_mode_hides_and_rejects_mutating_tools() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let mcp_tool = |name: &str, read_only_hint: Option<bool>| rmcp::model::Tool {
+            name: name.to_string().into(),
+            title: None,
+            description: None,
+            input_schema: Arc::new(rmcp::model::object(
+                serde_json::json!({"type": "object", "properties": {}}),
+            )),
+            output_schema: None,
+            annotations: Some(rmcp::model::ToolAnnotations {
+                destructive_hint: None,
+                idempotent_hint: None,
+                open_world_hint: None,
+                read_only_hint,
+                title: None,
+            }),
+            execution: None,
+            icons: None,
+            meta: None,
+        };
+        let mut router = ToolRouter::from_config(
+            &turn.tools_config,
+            Some(HashMap::from([
+                (
+                    "mcp__docs__search".to_string(),
+                    mcp_tool("search", Some(true)),
+                ),
+                ("mcp__docs__delete".to_string(), mcp_tool("delete", None)),
+            ])),
+            None,
+            &[],
+        );
+        router.set_read_only(true);
+        let mcp_names = router
+            .specs()
+            .iter()
+            .map(|spec| spec.name().to_string())
+            .filter(|name| name.starts_with("mcp__"))
+            .collect::<Vec<_>>();
+        assert_eq!(mcp_names, vec!["mcp__docs__search"]);
+
+        let shell: Arc<dyn ToolHandler> = Arc::new(crate::tools::handlers::ShellHandler);
+        let mut router = router_with(ToolRegistry::new(HashMap::from([(
+            "shell".to_string(),
+            shell,
+        )])));
+        router.set_read_only(true);
+        let dir = tempfile::tempdir()?;
+        let marker = dir.path().join("created.txt");
+        let response = router
+            .dispatch_tool_call(
+                Arc::new(session),
+                Arc::new(turn),
+                Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                ToolCall {
+                    tool_name: "shell".to_string(),
+                    call_id: "call-1".to_string(),
+                    payload: ToolPayload::Function {
+                        arguments: serde_json::json!({ "command": ["touch", marker] }).to_string(),
+                    },
+                    tags: HashMap::new(),
+                    dry_run: false,
+                },
+                ToolCallSource::Direct,
+            )
+            .await?;
+        let ResponseInputItem::FunctionCallOutput { output, .. } = response else {
+            panic!("expected function call output, got {response:?}");
+        };
+        assert_eq!(output.success, Some(false));
+        assert!(
+            output
+                .text_content()
+                .is_some_and(|text| text.contains("not allowed in read-only mode"))
+        );
+        assert!(!marker.exists());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn tool_descriptors_describe_every_spec() {
+        let (_session, turn) = make_session_and_context().await;
+        let dynamic_tool = |name: &str| DynamicToolSpec {
+            name: name.to_string(),
+            description: "Looks up a ticket by id.".to_string(),
+            input_schema: serde_json::json!({"type": "object", "properties": {}}),
+        };
+        let mcp_tool = rmcp::model::Tool {
+            name: "search".into(),
+            title: None,
+            description: Some("Searches the docs.".into()),
+            input_schema: Arc::new(rmcp::model::object(
+                serde_json::json!({"type": "object", "properties": {}}),
+            )),
+            output_schema: None,
+            annotations: None,
+            execution: None,
+            icons: None,
+            meta: None,
+        };
+        let router = ToolRouter::from_config(
+            &turn.tools_config,
+            Some(HashMap::from([("mcp__docs__search".to_string(), mcp_tool)])),
+            None,
+            &[dynamic_tool("lookup_ticket")],
+        );
+        router
+            .get_or_register_dynamic_tool(dynamic_tool("lookup_user"))
+            .expect("register dynamic tool");
+
+        let descriptors = router.tool_descriptors();
+
+        assert_eq!(
+            descriptors
+                .iter()
+                .map(|descriptor| descriptor.name.as_str())
+                .collect::<Vec<_>>(),
+            router
+                .specs()
+                .iter()
+                .map(ToolSpec::name)
+                .collect::<Vec<_>>()
+        );
+        let descriptor = |name: &str| {
+            descriptors
+                .iter()
+                .find(|descriptor| descriptor.name == name)
+                .cloned()
+        };
+        assert_eq!(
+            descriptor("mcp__docs__search"),
+            Some(ToolDescriptor {
+                name: "mcp__docs__search".to_string(),
+                supports_parallel_tool_calls: false,
+                mcp_server: Some("docs".to_string()),
+                dynamic: false,
+            })
+        );
+        assert_eq!(
+            descriptor("view_image"),
+            Some(ToolDescriptor {
+                name: "view_image".to_string(),
+                supports_parallel_tool_calls: true,
+                mcp_server: None,
+                dynamic: false,
+            })
+        );
+        for name in ["lookup_ticket", "lookup_user"] {
+            assert_eq!(
+                descriptor(name),
+                Some(ToolDescriptor {
+                    name: name.to_string(),
+                    supports_parallel_tool_calls: false,
+                    mcp_server: None,
+                    dynamic: true,
+                })
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn is_tool_enabled_respects_registration_features_and_overrides() {
+        let (_session, mut turn) = make_session_and_context().await;
+        turn.tools_config.js_repl_enabled = false;
+        let without_js_repl = ToolRouter::from_config(
+            &turn.tools_config,
+            None,
+            None,
+            turn.dynamic_tools.as_slice(),
+        );
+        turn.tools_config.js_repl_enabled = true;
+        let router = ToolRouter::from_config(
+            &turn.tools_config,
+            None,
+            None,
+            turn.dynamic_tools.as_slice(),
+        );
+        let turn_router = router.clone_for_turn(TurnToolOverrides {
+            disabled_tools: ["update_plan".to_string()].into_iter().collect(),
+            ..Default::default()
+        });
+
+        assert!(!router.is_tool_enabled("not_a_tool"));
+        assert!(!without_js_repl.is_tool_enabled("js_repl"));
+        assert!(router.is_tool_enabled("js_repl"));
+        assert!(router.is_tool_enabled("update_plan"));
+        assert!(!turn_router.is_tool_enabled("update_plan"));
+    }
+
+    /// The tool calls sealed into the audit log at `path`.
+    fn sealed_tool_calls(path: &Path) -> anyhow::Result<Vec<EncryptedEntry>> {
+        Ok(read_audit_log(path)?
+            .into_iter()
+            .filter_map(|entry| match entry.event {
+                AuditEvent::ToolCall(call) => Some(call),
+                _ => None,
+            })
+            .collect())
+    }
+
+    #[tokio::test]
+    async fn encrypted_router_keeps_only_sealed_arguments_and_results() -> anyhow::Result<()> {
+        let (mut session, turn) = make_session_and_context().await;
+        let audit_dir = tempfile::tempdir()?;
+        let audit_path = audit_dir.path().join("audit.jsonl");
+        session.services.audit_log = Some(AuditLog::open(
+            audit_path.clone(),
+            &audit_dir.path().join("audit.key"),
+            session.conversation_id,
+        )?);
+        let mut registry = ToolRegistry::new(HashMap::new());
+        let output = (0..500)
+            .map(|i| format!("user {i}: jane@example.com"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        registry.register_function_tool(
+            "lookup_user",
+            serde_json::json!({"type": "object", "properties": {}}),
+            {
+                let output = output.clone();
+                move |_| Ok(output.clone())
+            },
+        )?;
+        let key = EncryptionKey::generate()?;
+        let router = router_with(registry).with_encryption(key.clone());
+        let arguments = r#"{"api_token":"sk-secret"}"#;
+
+        router
+            .dispatch_tool_call(
+                Arc::new(session),
+                Arc::new(turn),
+                Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                ToolCall {
+                    tool_name: "lookup_user".to_string(),
+                    call_id: "call-audit".to_string(),
+                    payload: ToolPayload::Function {
+                        arguments: arguments.to_string(),
+                    },
+                    tags: HashMap::new(),
+                    dry_run: false,
+                },
+                ToolCallSource::Direct,
+            )
+            .await?;
+
+        let archived = router
+            .get_call_by_id("call-audit")
+            .expect("call is archived");
+        assert_eq!(archived.arguments, ENCRYPTED_PLACEHOLDER);
+        assert_eq!(archived.result_summary, ENCRYPTED_PLACEHOLDER);
+        let log = std::fs::read_to_string(&audit_path)?;
+        assert!(!log.contains("sk-secret"));
+        assert!(!log.contains("jane@example.com"));
+        let entries = sealed_tool_calls(&audit_path)?;
+        assert_eq!(entries.len(), 1);
+        let decrypted = decrypt_entry(&entries[0], &key)?;
+        assert_eq!(decrypted.arguments, arguments);
+        assert_eq!(decrypted.output, output);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn call_tags_are_reported_in_tool_call_start_event() -> anyhow::Result<()> {
+        let (mut session, turn, rx) = make_session_and_context_with_rx().await;
+        let audit_dir = tempfile::tempdir()?;
+        let audit_path = audit_dir.path().join("audit.jsonl");
+        let conversation_id = session.conversation_id;
+        Arc::get_mut(&mut session)
+            .expect("session is not shared yet")
+            .services
+            .audit_log = Some(AuditLog::open(
+            audit_path.clone(),
+            &audit_dir.path().join("audit.key"),
+            conversation_id,
+        )?);
+        let mut registry = ToolRegistry::new(HashMap::new());
+        registry.register_function_tool(
+            "noop",
+            serde_json::json!({"type": "object", "properties": {}}),
+            |_| Ok(String::new()),
+        )?;
+        let router = router_with(registry).with_encryption(EncryptionKey::generate()?);
+        let call = ToolCall {
+            tool_name: "noop".to_string(),
+            call_id: "call-tagged".to_string(),
+            payload: ToolPayload::Function {
+                arguments: "{}".to_string(),
+            },
+            tags: HashMap::new(),
+            dry_run: false,
+        }
+        .with_tag("team", "infra")
+        .with_tag("dashboard", "latency");
+        let call = ToolRouter::tag_call(call, [("request_id", "req-42")]);
+        let expected = HashMap::from([
+            ("team".to_string(), "infra".to_string()),
+            ("dashboard".to_string(), "latency".to_string()),
+            ("request_id".to_string(), "req-42".to_string()),
+        ]);
+
+        router
+            .dispatch_tool_call(
+                session,
+                turn,
+                Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                call,
+                ToolCallSource::Direct,
+            )
+            .await?;
+
+        let start = loop {
+            let event = tokio::time::timeout(Duration::from_secs(1), rx.recv()).await??;
+            if let EventMsg::ToolCallStart(start) = event.msg {
+                break start;
+            }
+        };
+        assert_eq!(start.call_id, "call-tagged");
+        assert_eq!(start.tool_name, "noop");
+        assert_eq!(start.metadata, expected);
+        let entries = sealed_tool_calls(&audit_path)?;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].tags, expected);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn oversized_arguments_are_rejected_before_dispatch() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let session = Arc::new(session);
+        let turn = Arc::new(turn);
+        let tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let handler_calls = Arc::clone(&calls);
+        let mut registry = ToolRegistry::new(HashMap::new());
+        registry.register_function_tool(
+            "count",
+            serde_json::json!({"type": "object"}),
+            move |_| {
+                handler_calls.fetch_add(1, Ordering::SeqCst);
+                Ok("counted".to_string())
+            },
+        )?;
+        let mut router = router_with(registry);
+        router.max_argument_size_bytes = Some(16);
+        let call = |arguments: String| ToolCall {
+            tool_name: "count".to_string(),
+            call_id: "call-count".to_string(),
+            payload: ToolPayload::Function { arguments },
+            tags: HashMap::new(),
+            dry_run: false,
+        };
+        let oversized = call(format!(r#"{{"text":"{}"}}"#, "x".repeat(32)));
+        let expected = FunctionCallError::PayloadTooLarge {
+            size_bytes: 43,
+            limit_bytes: 16,
+        };
+
+        let expected_message = expected.to_string();
+        assert_eq!(
+            router.check_argument_size(&oversized.payload),
+            Err(expected)
+        );
+        let response = router
+            .dispatch_tool_call(
+                Arc::clone(&session),
+                Arc::clone(&turn),
+                Arc::clone(&tracker),
+                oversized,
+                ToolCallSource::Direct,
+            )
+            .await?;
+        match response {
+            ResponseInputItem::FunctionCallOutput { output, .. } => {
+                assert_eq!(output.success, Some(false));
+                assert_eq!(output.body, FunctionCallOutputBody::Text(expected_message));
+            }
+            other => panic!("expected function call output, got {other:?}"),
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        router
+            .dispatch_tool_call(
+                session,
+                turn,
+                tracker,
+                call("{}".to_string()),
+                ToolCallSource::Direct,
+            )
+            .await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn schema_validation_rejects_mismatched_arguments_before_dispatch() -> anyhow::Result<()>
+    {
+        let (session, turn) = make_session_and_context().await;
+        let session = Arc::new(session);
+        let turn = Arc::new(turn);
+        let tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
+        let registry = ToolRegistry::new(HashMap::from([(
+            "read_notes".to_string(),
+            Arc::new(StaticHandler(Ok("notes"))) as Arc<dyn ToolHandler>,
+        )]));
+        let mut router = router_with(registry);
+        router.specs = Arc::new(vec![ConfiguredToolSpec::new(
+            ToolSpec::Function(ResponsesApiTool {
+                name: "read_notes".to_string(),
+                description: String::new(),
+                strict: false,
+                parameters: parse_tool_input_schema(&serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string"},
+                        "limit": {"type": "number"},
+                    },
+                    "required": ["path"],
+                }))
+                .expect("valid schema"),
+            }),
+            true,
+        )]);
+        let call = ToolCall {
+            tool_name: "read_notes".to_string(),
+            call_id: "call-notes".to_string(),
+            payload: ToolPayload::Function {
+                arguments: serde_json::json!({"limit": "ten"}).to_string(),
+            },
+            tags: HashMap::new(),
+            dry_run: false,
+        };
+
+        let unchecked = router
+            .dispatch_tool_call(
+                Arc::clone(&session),
+                Arc::clone(&turn),
+                Arc::clone(&tracker),
+                call.clone(),
+                ToolCallSource::Direct,
+            )
+            .await?;
+        router.schema_validation = true;
+        let checked = router
+            .dispatch_tool_call(session, turn, tracker, call, ToolCallSource::Direct)
+            .await?;
+
+        let output = match unchecked {
+            ResponseInputItem::FunctionCallOutput { output, .. } => output,
+            other => panic!("expected function call output, got {other:?}"),
+        };
+        assert_eq!(
+            output.body,
+            FunctionCallOutputBody::Text("notes".to_string())
+        );
+        let output = match checked {
+            ResponseInputItem::FunctionCallOutput { output, .. } => output,
+            other => panic!("expected function call output, got {other:?}"),
+        };
+        assert_eq!(output.success, Some(false));
+        assert_eq!(
+            output.body,
+            FunctionCallOutputBody::Text(
+                "invalid arguments for read_notes: missing required argument `path`; \
+                 argument `limit` must be of type number"
+                    .to_string()
+            )
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn repeated_call_ids_run_their_tool_once() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let session = Arc::new(session);
+        let turn = Arc::new(turn);
+        let tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let handler_calls = Arc::clone(&calls);
+        let mut registry = ToolRegistry::new(HashMap::new());
+        registry.register_function_tool(
+            "append_line",
+            serde_json::json!({"type": "object"}),
+            move |_| {
+                let count = handler_calls.fetch_add(1, Ordering::SeqCst) + 1;
+                Ok(format!("appended line {count}"))
+            },
+        )?;
+        let router = router_with(registry);
+        let call = ToolCall {
+            tool_name: "append_line".to_string(),
+            call_id: "call-append".to_string(),
+            payload: ToolPayload::Function {
+                arguments: "{}".to_string(),
+            },
+            tags: HashMap::new(),
+            dry_run: false,
+        };
+
+        let first = router
+            .dispatch_tool_call(
+                Arc::clone(&session),
+                Arc::clone(&turn),
+                Arc::clone(&tracker),
+                call.clone(),
+                ToolCallSource::Direct,
+            )
+            .await?;
+        let repeated = router
+            .clone_for_turn(TurnToolOverrides::default())
+            .dispatch_tool_call(session, turn, tracker, call, ToolCallSource::Direct)
+            .await?;
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(repeated, first);
+        match first {
+            ResponseInputItem::FunctionCallOutput { output, .. } => {
+                assert_eq!(output.text_content(), Some("appended line 1"));
+            }
+            other => panic!("expected function call output, got {other:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn session_routers_share_completed_call_ids() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let session = Arc::new(session);
+        let turn = Arc::new(turn);
+        let tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
+        let calls = Arc::new(AtomicUsize::new(0));
+        // Each sampling request builds its own router; see `built_tools`.
+        let request_router = || -> anyhow::Result<ToolRouter> {
+            let handler_calls = Arc::clone(&calls);
+            let mut registry = ToolRegistry::new(HashMap::new());
+            registry.register_function_tool(
+                "append_line",
+                serde_json::json!({"type": "object"}),
+                move |_| {
+                    handler_calls.fetch_add(1, Ordering::SeqCst);
+                    Ok("appended".to_string())
+                },
+            )?;
+            let mut router = router_with(registry);
+            router.set_call_ids(session.services.tool_call_ids.clone());
+            Ok(router)
+        };
+        let call = ToolCall {
+            tool_name: "append_line".to_string(),
+            call_id: "call-append".to_string(),
+            payload: ToolPayload::Function {
+                arguments: "{}".to_string(),
+            },
+            tags: HashMap::new(),
+            dry_run: false,
+        };
+
+        let first = request_router()?
+            .dispatch_tool_call(
+                Arc::clone(&session),
+                Arc::clone(&turn),
+                Arc::clone(&tracker),
+                call.clone(),
+                ToolCallSource::Direct,
+            )
+            .await?;
+        let repeated = request_router()?
+            .dispatch_tool_call(
+                Arc::clone(&session),
+                turn,
+                tracker,
+                call,
+                ToolCallSource::Direct,
+            )
+            .await?;
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(repeated, first);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn repeat_of_a_running_call_is_an_error() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let calls = Arc::new(AtomicUsize::new(0));
+        let handler_calls = Arc::clone(&calls);
+        let mut registry = ToolRegistry::new(HashMap::new());
+        registry.register_function_tool(
+            "append_line",
+            serde_json::json!({"type": "object"}),
+            move |_| {
+                handler_calls.fetch_add(1, Ordering::SeqCst);
+                Ok("appended".to_string())
+            },
+        )?;
+        let router = router_with(registry);
+        let _running = router
+            .call_ids
+            .reserve("call-append")
+            .map_err(|duplicate| anyhow::anyhow!("{duplicate:?}"))?;
+        let call = ToolCall {
+            tool_name: "append_line".to_string(),
+            call_id: "call-append".to_string(),
+            payload: ToolPayload::Function {
+                arguments: "{}".to_string(),
+            },
+            tags: HashMap::new(),
+            dry_run: false,
+        };
+
+        let result = router
+            .dispatch_tool_call(
+                Arc::new(session),
+                Arc::new(turn),
+                Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                call,
+                ToolCallSource::Direct,
+            )
+            .await;
+
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        match result {
+            Err(FunctionCallError::DuplicateCallId { call_id }) => {
+                assert_eq!(call_id, "call-append");
+            }
+            other => panic!("expected a duplicate call id error, got {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn structured_logging_emits_one_json_object_per_dispatch() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let session_id = session.conversation_id.to_string();
+        let session = Arc::new(session);
+        let turn = Arc::new(turn);
+        let tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
+        let mut registry = ToolRegistry::new(HashMap::new());
+        registry.register_function_tool("echo", serde_json::json!({"type": "object"}), |args| {
+            Ok(args.to_string())
+        })?;
+        registry.register_function_tool("broken", serde_json::json!({"type": "object"}), |_| {
+            Err("boom".to_string())
+        })?;
+        let router = router_with(registry).log_to_structured_json();
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let _subscriber = tracing::subscriber::set_default(
+            tracing_subscriber::registry().with(structured_json_layer(move || writer.clone())),
+        );
+
+        for (call_id, tool_name) in [("call-ok", "echo"), ("call-err", "broken")] {
+            router
+                .dispatch_tool_call(
+                    Arc::clone(&session),
+                    Arc::clone(&turn),
+                    Arc::clone(&tracker),
+                    ToolCall {
+                        tool_name: tool_name.to_string(),
+                        call_id: call_id.to_string(),
+                        payload: ToolPayload::Function {
+                            arguments: "{}".to_string(),
+                        },
+                        tags: HashMap::new(),
+                        dry_run: false,
+                    },
+                    ToolCallSource::Direct,
+                )
+                .await?;
+        }
+
+        let output = String::from_utf8(logs.0.lock().expect("logs lock").clone())?;
+        let records = output
+            .lines()
+            .map(serde_json::from_str::<serde_json::Value>)
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(records.len(), 2);
+        for record in &records {
+            for field in [
+                "timestamp",
+                "level",
+                "call_id",
+                "tool_name",
+                "session_id",
+                "duration_ms",
+                "success",
+                "error_kind",
+            ] {
+                assert!(record.get(field).is_some(), "{field} missing from {record}");
+            }
+            assert_eq!(record["session_id"], session_id);
+        }
+        assert_eq!(
+            (
+                &records[0]["call_id"],
+                &records[0]["level"],
+                &records[0]["success"],
+                &records[0]["error_kind"]
+            ),
+            (
+                &serde_json::json!("call-ok"),
+                &serde_json::json!("INFO"),
+                &serde_json::json!(true),
+                &serde_json::json!("none")
+            )
+        );
+        assert_eq!(
+            (
+                &records[1]["call_id"],
+                &records[1]["level"],
+                &records[1]["success"],
+                &records[1]["error_kind"]
+            ),
+            (
+                &serde_json::json!("call-err"),
+                &serde_json::json!("WARN"),
+                &serde_json::json!(false),
+                &serde_json::json!("tool_failed")
+            )
+        );
+
+        Ok(())
+    }
+
+    #[derive(Default)]
+    struct CallCounter {
+        before: AtomicUsize,
+        after: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl BeforeAllHook for CallCounter {
+        async fn before(
+            &self,
+            _call: &ToolCall,
+            _source: ToolCallSource,
+        ) -> Result<(), FunctionCallError> {
+            self.before.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl AfterAllHook for CallCounter {
+        async fn after(
+            &self,
+            _call: &ToolCall,
+            _result: &Result<ResponseInputItem, FunctionCallError>,
+        ) {
+            self.after.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn global_middleware_runs_for_every_tool_type() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let (session, turn) = (Arc::new(session), Arc::new(turn));
+        let mut registry = ToolRegistry::new(HashMap::new());
+        registry.register_function_tool(
+            "noop",
+            serde_json::json!({"type": "object", "properties": {}}),
+            |_| Ok(String::new()),
+        )?;
+        let counter = Arc::new(CallCounter::default());
+        let mut router = router_with(registry);
+        router.register_before_all(counter.clone());
+        router.register_after_all(counter.clone());
+        let router = router.clone_for_turn(TurnToolOverrides::default());
+        let calls = [
+            (
+                "noop",
+                ToolPayload::Function {
+                    arguments: "{}".to_string(),
+                },
+            ),
+            (
+                "apply_patch",
+                ToolPayload::Custom {
+                    input: "*** Begin Patch\n*** End Patch".to_string(),
+                    attachments: Vec::new(),
+                },
+            ),
+            (
+                "mcp__docs__search",
+                ToolPayload::Mcp {
+                    server: "docs".to_string(),
+                    tool: "search".to_string(),
+                    raw_arguments: "{}".to_string(),
+                },
+            ),
+        ];
+
+        for (index, (tool_name, payload)) in calls.into_iter().enumerate() {
+            router
+                .dispatch_tool_call(
+                    Arc::clone(&session),
+                    Arc::clone(&turn),
+                    Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                    ToolCall {
+                        tool_name: tool_name.to_string(),
+                        call_id: format!("call-{index}"),
+                        payload,
+                        tags: HashMap::new(),
+                        dry_run: false,
+                    },
+                    ToolCallSource::Direct,
+                )
+                .await?;
+        }
+
+        assert_eq!(counter.before.load(Ordering::SeqCst), 3);
+        assert_eq!(counter.after.load(Ordering::SeqCst), 3);
+
+        Ok(())
+    }
+
+    #[derive(Default)]
+    struct PassThroughInterceptor {
+        calls: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl DispatchInterceptor for PassThroughInterceptor {
+        async fn intercept(
+            &self,
+            call: ToolCall,
+            real_dispatch: BoxedDispatchFn<'_>,
+        ) -> Result<ResponseInputItem, FunctionCallError> {
+            self.calls
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .push(call.call_id.clone());
+            real_dispatch(call).await
+        }
+    }
+
+    #[tokio::test]
+    async fn dispatch_interceptor_passes_through_to_real_dispatch() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let (session, turn) = (Arc::new(session), Arc::new(turn));
+        let echo_registry = || -> anyhow::Result<ToolRegistry> {
+            let mut registry = ToolRegistry::new(HashMap::new());
+            registry.register_function_tool(
+                "echo",
+                serde_json::json!({"type": "object", "properties": {}}),
+                |arguments| Ok(arguments.to_string()),
+            )?;
+            Ok(registry)
+        };
+        let interceptor = Arc::new(PassThroughInterceptor::default());
+        let mut router = router_with(echo_registry()?);
+        router.set_dispatch_interceptor(interceptor.clone());
+        let router = router.clone_for_turn(TurnToolOverrides::default());
+        let call = |call_id: &str| ToolCall {
+            tool_name: "echo".to_string(),
+            call_id: call_id.to_string(),
+            payload: ToolPayload::Function {
+                arguments: r#"{"text":"hi"}"#.to_string(),
+            },
+            tags: HashMap::new(),
+            dry_run: false,
+        };
+
+        let intercepted = router
+            .dispatch_tool_call(
+                Arc::clone(&session),
+                Arc::clone(&turn),
+                Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                call("call-1"),
+                ToolCallSource::Direct,
+            )
+            .await?;
+        let direct = router_with(echo_registry()?)
+            .dispatch_tool_call(
+                session,
+                turn,
+                Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                call("call-1"),
+                ToolCallSource::Direct,
+            )
+            .await?;
+
+        assert_eq!(intercepted, direct);
+        assert_eq!(
+            *interceptor
+                .calls
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+            vec!["call-1".to_string()]
+        );
+        assert!(router.get_call_by_id("call-1").is_some());
+
+        Ok(())
+    }
+
+    /// Runs `npm install` outside the sandbox and vetoes `rm`.
+    struct NpmInstallPolicy;
+
+    #[async_trait]
+    impl SandboxPermissionsHook for NpmInstallPolicy {
+        async fn resolve(
+            &self,
+            params: &ShellToolCallParams,
+        ) -> Result<SandboxPermissions, FunctionCallError> {
+            let command = params
+                .command
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>();
+            match command.as_slice() {
+                ["npm", "install", ..] => Ok(SandboxPermissions::RequireEscalated),
+                ["rm", ..] => Err(FunctionCallError::RespondToModel(
+                    "rm is not allowed by policy".to_string(),
+                )),
+                _ => Ok(params.sandbox_permissions.unwrap_or_default()),
+            }
+        }
+    }
+
+    /// Reports the sandbox permissions each local shell call arrived with.
+    struct SandboxPermissionsProbe;
+
+    #[async_trait]
+    impl ToolHandler for SandboxPermissionsProbe {
+        fn kind(&self) -> ToolKind {
+            ToolKind::Function
+        }
+
+        fn matches_kind(&self, payload: &ToolPayload) -> bool {
+            matches!(payload, ToolPayload::LocalShell { .. })
+        }
+
+        async fn handle(
+            &self,
+            invocation: ToolInvocation,
+        ) -> Result<ToolOutput, FunctionCallError> {
+            let ToolPayload::LocalShell { params } = invocation.payload else {
+                return Err(FunctionCallError::Fatal("expected local shell".to_string()));
+            };
+            Ok(ToolOutput::Function {
+                body: FunctionCallOutputBody::Text(format!("{:?}", params.sandbox_permissions)),
+                success: Some(true),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn sandbox_permissions_hook_adjusts_or_vetoes_local_shell_calls() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let (session, turn) = (Arc::new(session), Arc::new(turn));
+        let tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
+        let mut router = router_with(ToolRegistry::new(HashMap::from([(
+            "local_shell".to_string(),
+            Arc::new(SandboxPermissionsProbe) as Arc<dyn ToolHandler>,
+        )])));
+        let call = |command: &[&str]| ToolCall {
+            tool_name: "local_shell".to_string(),
+            call_id: format!("call-{}", command[0]),
+            payload: ToolPayload::LocalShell {
+                params: ShellToolCallParams {
+                    command: command.iter().map(ToString::to_string).collect(),
+                    workdir: None,
+                    timeout_ms: None,
+                    sandbox_permissions: Some(SandboxPermissions::UseDefault),
+                    additional_permissions: None,
+                    prefix_rule: None,
+                    justification: None,
+                    use_pty: false,
+                    env: None,
+                },
+            },
+            tags: HashMap::new(),
+            dry_run: false,
+        };
+        let output_texts = |responses: Vec<ResponseInputItem>| {
+            responses
+                .into_iter()
+                .map(|response| match response {
+                    ResponseInputItem::FunctionCallOutput { output, .. } => output.body.to_text(),
+                    other => panic!("expected function call output, got {other:?}"),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let unhooked = router
+            .dispatch_ordered(
+                Arc::clone(&session),
+                Arc::clone(&turn),
+                Arc::clone(&tracker),
+                vec![ToolCall {
+                    call_id: "call-unhooked".to_string(),
+                    ..call(&["npm", "install"])
+                }],
+                ToolCallSource::Direct,
+            )
+            .await?;
+        router.set_sandbox_permissions_hook(Arc::new(NpmInstallPolicy));
+        let hooked = router
+            .clone_for_turn(TurnToolOverrides::default())
+            .dispatch_ordered(
+                session,
+                turn,
+                tracker,
+                vec![
+                    call(&["npm", "install"]),
+                    call(&["ls"]),
+                    call(&["rm", "-rf", "/"]),
+                ],
+                ToolCallSource::Direct,
+            )
+            .await?;
+
+        assert_eq!(
+            output_texts(unhooked),
+            vec![Some("Some(UseDefault)".to_string())]
+        );
+        assert_eq!(
+            output_texts(hooked),
+            vec![
+                Some("Some(RequireEscalated)".to_string()),
+                Some("Some(UseDefault)".to_string()),
+                Some("rm is not allowed by policy".to_string()),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn custom_tool_calls_with_attachments_become_custom_payloads() -> anyhow::Result<()> {
+        let (session, _turn) = make_session_and_context().await;
+        let attachments = vec![
+            ToolCallAttachment::InputImage {
+                image_url: "data:image/png;base64,AAA".to_string(),
+            },
+            ToolCallAttachment::Resource {
+                uri: "file:///tmp/report.csv".to_string(),
+                mime_type: Some("text/csv".to_string()),
+            },
+        ];
+        let item = ResponseItem::CustomToolCallWithAttachments {
+            id: None,
+            status: None,
+            call_id: "call-attached".to_string(),
+            name: "summarize".to_string(),
+            input: "summarize these".to_string(),
+            attachments: attachments.clone(),
+        };
+
+        let call = ToolRouter::build_tool_call(&session, item)
+            .await?
+            .expect("tool call present");
+
+        assert_eq!(call.tool_name, "summarize");
+        assert_eq!(call.call_id, "call-attached");
+        match call.payload {
+            ToolPayload::Custom {
+                input,
+                attachments: payload_attachments,
+            } => {
+                assert_eq!(input, "summarize these");
+                assert_eq!(payload_attachments, attachments);
+            }
+            other => panic!("expected custom payload, got {other:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn build_tool_call_fails_unsupported_tool_calls() {
+        let (session, _turn) = make_session_and_context().await;
+        let item = ResponseItem::UnsupportedToolCall {
+            item_type: "structured_tool_call".to_string(),
+            call_id: "call-unknown".to_string(),
+            name: Some("lookup".to_string()),
+        };
+
+        let err = ToolRouter::build_tool_call(&session, item)
+            .await
+            .expect_err("unsupported call should fail");
+
+        assert_eq!(
+            err,
+            FunctionCallError::UnsupportedToolCall {
+                item_type: "structured_tool_call".to_string(),
+                call_id: "call-unknown".to_string(),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "tool call call-unknown has unsupported type `structured_tool_call` and was not run"
+        );
+        assert!(
+            ToolRouter::build_tool_call(&session, ResponseItem::Other)
+                .await
+                .expect("other items are ignored")
+                .is_none()
+        );
+    }
+
+    struct UnreachableMcpHandler;
+
+    #[async_trait]
+    impl ToolHandler for UnreachableMcpHandler {
+        fn kind(&self) -> ToolKind {
+            ToolKind::Mcp
+        }
+
+        async fn handle(
+            &self,
+            _invocation: ToolInvocation,
+        ) -> Result<ToolOutput, FunctionCallError> {
+            Ok(ToolOutput::Mcp {
+                result: Err("connection refused".to_string()),
+            })
+        }
+    }
+
+    /// Loses its connection on the first `transport_failures` calls, then
+    /// answers with `outcome`.
+    struct FlakyMcpHandler {
+        transport_failures: usize,
+        outcome: Result<&'static str, &'static str>,
+        attempts: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl ToolHandler for FlakyMcpHandler {
+        fn kind(&self) -> ToolKind {
+            ToolKind::Mcp
+        }
+
+        async fn handle(
+            &self,
+            _invocation: ToolInvocation,
+        ) -> Result<ToolOutput, FunctionCallError> {
+            if self.attempts.fetch_add(1, Ordering::SeqCst) < self.transport_failures {
+                return Err(FunctionCallError::McpTransport(
+                    "tool call error: Transport closed".to_string(),
+                ));
+            }
+            match self.outcome {
+                Ok(text) => Ok(ToolOutput::Function {
+                    body: FunctionCallOutputBody::Text(text.to_string()),
+                    success: Some(true),
+                }),
+                Err(message) => Err(FunctionCallError::RespondToModel(message.to_string())),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn diagnostics_report_renders_as_json_and_markdown() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let mcp_handler: Arc<dyn ToolHandler> = Arc::new(UnreachableMcpHandler);
+        let mut registry = ToolRegistry::new(HashMap::from([(
+            "mcp__docs__search".to_string(),
+            mcp_handler,
+        )]));
+        registry.register_function_tool(
+            "lookup",
+            serde_json::json!({"type": "object", "properties": {}}),
+            |_| Ok(String::new()),
+        )?;
+        registry
+            .dispatch_resilient(
+                ToolInvocation {
+                    session: Arc::new(session),
+                    turn: Arc::new(turn),
+                    tracker: Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                    call_id: "call-docs".to_string(),
+                    tool_name: "mcp__docs__search".to_string(),
+                    payload: ToolPayload::Mcp {
+                        server: "docs".to_string(),
+                        tool: "search".to_string(),
+                        raw_arguments: "{}".to_string(),
+                    },
+                    dry_run: false,
+                },
+                ResiliencyPolicy {
+                    retry: RetryPolicy {
+                        max_retries: 0,
+                        ..Default::default()
+                    },
+                    circuit_breaker: CircuitBreakerConfig {
+                        failure_threshold: 1,
+                        open_duration: Duration::ZERO,
+                    },
+                    ..Default::default()
+                },
+            )
+            .await?;
+        let mut router = router_with(registry);
+        router.set_fallback("lookup", "archive_lookup");
+        let router = router.clone_for_turn(TurnToolOverrides {
+            disabled_tools: HashSet::from(["lookup".to_string()]),
+            tool_timeout: None,
+        });
+
+        let report = router.diagnostics_report();
+
+        assert_eq!(
+            report.to_json(),
+            serde_json::json!({
+                "tools": [
+                    {
+                        "name": "lookup",
+                        "status": "disabled_for_turn",
+                        "supports_parallel_tool_calls": false,
+                        "fallback": "archive_lookup",
+                    },
+                    {
+                        "name": "mcp__docs__search",
+                        "status": "unadvertised",
+                        "supports_parallel_tool_calls": false,
+                        "fallback": null,
+                    },
+                ],
+                "mcp_servers": [
+                    {"name": "docs", "tool_count": 1, "status": "unreachable"},
+                ],
+                "circuit_breakers": [
+                    {
+                        "key": "docs",
+                        "state": "open",
+                        "consecutive_failures": 0,
+                        "reopens_in_ms": 0,
+                    },
+                ],
+                "anomalies": ["fallback archive_lookup for tool lookup has no handler"],
+            })
+        );
+        assert_eq!(
+            report.to_markdown(),
+            "# Tool diagnostics\n\
+             \n\
+             ## Tools\n\
+             \n\
+             | Tool | Status | Parallel | Fallback |\n\
+             |---|---|---|---|\n\
+             | lookup | disabled_for_turn | no | archive_lookup |\n\
+             | mcp__docs__search | unadvertised | no | - |\n\
+             \n\
+             ## MCP servers\n\
+             \n\
+             | Server | Tools | Status |\n\
+             |---|---|---|\n\
+             | docs | 1 | unreachable |\n\
+             \n\
+             ## Circuit breakers\n\
+             \n\
+             | Key | State | Consecutive failures | Reopens in |\n\
+             |---|---|---|---|\n\
+             | docs | open | 0 | 0ms |\n\
+             \n\
+             ## Anomalies\n\
+             \n\
+             - fallback archive_lookup for tool lookup has no handler\n"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn tool_health_check_probes_mcp_servers() -> anyhow::Result<()> {
+        let stdio_server = codex_utils_cargo_bin::cargo_bin("test_stdio_server")?;
+        let mcp_handler: Arc<dyn ToolHandler> = Arc::new(UnreachableMcpHandler);
+        let function_handler: Arc<dyn ToolHandler> = Arc::new(StaticHandler(Ok("ok")));
+        let mut router = router_with(ToolRegistry::new(HashMap::from([
+            ("mcp__rmcp__echo".to_string(), Arc::clone(&mcp_handler)),
+            ("mcp__rmcp__retired".to_string(), Arc::clone(&mcp_handler)),
+            ("mcp__offline__search".to_string(), Arc::clone(&mcp_handler)),
+            ("mcp__unconfigured__search".to_string(), mcp_handler),
+            ("lookup".to_string(), function_handler),
+        ])));
+        router.set_mcp_servers(HashMap::from([
+            (
+                "rmcp".to_string(),
+                serde_json::from_value(serde_json::json!({
+                    "command": stdio_server,
+                    "startup_timeout_sec": 10.0,
+                }))?,
+            ),
+            (
+                "offline".to_string(),
+                serde_json::from_value(
+                    serde_json::json!({ "command": "codex-health-check-missing-server" }),
+                )?,
+            ),
+        ]));
+
+        assert!(matches!(
+            router.tool_health_check("mcp__rmcp__echo").await,
+            ToolHealthStatus::Healthy { .. }
+        ));
+        assert_eq!(
+            router.tool_health_check("mcp__rmcp__retired").await,
+            ToolHealthStatus::Unhealthy {
+                message: "MCP server rmcp no longer serves tool retired".to_string(),
+            }
+        );
+        let offline = router.tool_health_check("mcp__offline__search").await;
+        assert!(
+            matches!(
+                &offline,
+                ToolHealthStatus::Unhealthy { message }
+                    if message.starts_with("MCP server offline is unreachable")
+            ),
+            "unexpected status: {offline:?}"
+        );
+        assert_eq!(
+            router.tool_health_check("mcp__unconfigured__search").await,
+            ToolHealthStatus::Degraded {
+                message: "MCP server unconfigured has no config, so it was not probed".to_string(),
+            }
+        );
+        assert_eq!(
+            router.tool_health_check("lookup").await,
+            ToolHealthStatus::Healthy { latency_ms: 0 }
+        );
+        assert_eq!(
+            router.tool_health_check("missing").await,
+            ToolHealthStatus::Unhealthy {
+                message: "unknown tool missing".to_string(),
+            }
+        );
+
+        Ok(())
+    }
+
+    /// Router over `registry` with no advertised specs.
+    #[test]
+    fn curl_equivalent_reproduces_http_mcp_call() -> anyhow::Result<()> {
+        let mut router = router_with(ToolRegistry::new(HashMap::new()));
+        router.set_mcp_servers(HashMap::from([
+            (
+                "docs".to_string(),
+                serde_json::from_value(serde_json::json!({
+                    "url": "https://docs.example/mcp",
+                    "bearer_token_env_var": "DOCS_TOKEN",
+                    "http_headers": { "X-Team": "it's us", "X-Api-Key": "sk-secret" },
+                    "env_http_headers": { "X-Region": "DOCS_REGION" },
+                }))?,
+            ),
+            (
+                "local".to_string(),
+                serde_json::from_value(serde_json::json!({ "command": "docs-server" }))?,
+            ),
+        ]));
+        let mcp_call = |server: &str| ToolCall {
+            tool_name: format!("mcp__{server}__search"),
+            call_id: "call-1".to_string(),
+            payload: ToolPayload::Mcp {
+                server: server.to_string(),
+                tool: "search".to_string(),
+                raw_arguments: r#"{"query":"it's"}"#.to_string(),
+            },
+            tags: HashMap::new(),
+            dry_run: false,
+        };
+
+        let command = router
+            .generate_curl_equivalent(&mcp_call("docs"))
+            .expect("curl command for an HTTP server");
+        assert!(!command.contains("sk-secret"), "leaked secret: {command}");
+        let args = shlex::split(&command).expect("command is valid shell");
+        assert_eq!(
+            args[..args.len() - 1],
+            [
+                "curl",
+                "-X",
+                "POST",
+                "https://docs.example/mcp",
+                "-H",
+                "Content-Type: application/json",
+                "-H",
+                "Accept: application/json, text/event-stream",
+                "-H",
+                "Authorization: Bearer $TOKEN",
+                "-H",
+                "X-Api-Key: $TOKEN",
+                "-H",
+                "X-Region: ${DOCS_REGION}",
+                "-H",
+                "X-Team: it's us",
+                "--data",
+            ]
+        );
+        let body: serde_json::Value = serde_json::from_str(&args[args.len() - 1])?;
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "tools/call",
+                "params": { "name": "search", "arguments": { "query": "it's" } },
+            })
+        );
+
+        // Stdio servers, unknown servers, and non-MCP tools have no HTTP request.
+        assert_eq!(router.generate_curl_equivalent(&mcp_call("local")), None);
+        assert_eq!(router.generate_curl_equivalent(&mcp_call("missing")), None);
+        let function_call = ToolCall {
+            tool_name: "read_file".to_string(),
+            call_id: "call-2".to_string(),
+            payload: ToolPayload::Function {
+                arguments: "{}".to_string(),
+            },
+            tags: HashMap::new(),
+            dry_run: false,
+        };
+        assert_eq!(router.generate_curl_equivalent(&function_call), None);
+
+        Ok(())
+    }
+
+    fn router_with(registry: ToolRegistry) -> ToolRouter {
+        ToolRouter {
+            registry: Arc::new(registry),
+            specs: Arc::new(Vec::new()),
+            turn_overrides: TurnToolOverrides::default(),
+            fallbacks: HashMap::new(),
+            call_archive: CallArchive::default(),
+            call_ids: CallIdDedupe::default(),
+            encryption_key: None,
+            structured_logging: false,
+            max_argument_size_bytes: None,
+            schema_validation: false,
+            before_all: Vec::new(),
+            after_all: Vec::new(),
+            audit_hooks: Vec::new(),
+            dispatch_interceptor: None,
+            sandbox_permissions_hook: None,
+            chaos: None,
+            shell_tool_aliases: Arc::new(SHELL_TOOL_ALIASES.map(str::to_string).to_vec()),
+            tool_timeouts: Arc::default(),
+            tool_call_permits: Arc::new(Semaphore::new(Semaphore::MAX_PERMITS)),
+            mcp_servers: Arc::default(),
+            mcp_transport_retry: RetryPolicy::default(),
+            output_limit: OutputLimit::default(),
+            tool_filter: Arc::default(),
+            read_only: false,
+            read_only_mcp_tools: Arc::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn custom_shell_alias_inherits_parallel_support() {
+        let (_session, mut turn) = make_session_and_context().await;
+        turn.tools_config.shell_type = ConfigShellToolType::Default;
+        let config = turn
+            .tools_config
+            .clone()
+            .with_extra_shell_tool_aliases(vec!["bash".to_string()]);
+
+        let router = ToolRouter::from_config(&config, None, None, &[]);
+
+        assert!(router.tool_supports_parallel("shell"));
+        assert!(router.tool_supports_parallel("bash"));
+        assert!(router.tool_supports_parallel("local_shell"));
+        assert_eq!(
+            router.get_spec("bash").map(|config| config.spec.name()),
+            Some("shell")
+        );
+        assert!(router.registry.handler("bash").is_some());
+        assert!(!router.tool_supports_parallel("zsh"));
+    }
+
+    #[test]
+    fn concurrent_get_or_register_registers_dynamic_tool_once() {
+        let router = router_with(ToolRegistry::new(HashMap::new()));
+        let spec = DynamicToolSpec {
+            name: "lookup_ticket".to_string(),
+            description: "Looks up a ticket.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {"id": {"type": "string"}},
+            }),
+        };
+
+        let tools = std::thread::scope(|scope| {
+            let handles = (0..8)
+                .map(|_| scope.spawn(|| router.get_or_register_dynamic_tool(spec.clone())))
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("thread panicked"))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .expect("registration succeeds");
+
+        assert!(tools.iter().all(|tool| tool.name == "lookup_ticket"));
+        assert_eq!(tools.iter().filter(|tool| tool.newly_registered).count(), 1);
+        assert_eq!(
+            router
+                .specs()
+                .iter()
+                .filter(|spec| spec.name() == "lookup_ticket")
+                .count(),
+            1
+        );
+        assert!(router.registry.handler("lookup_ticket").is_some());
+    }
+
+    #[tokio::test]
+    async fn dynamic_tools_are_added_and_removed_in_place() {
+        let (_session, turn) = make_session_and_context().await;
+        let dynamic_tool = |name: &str| DynamicToolSpec {
+            name: name.to_string(),
+            description: "Looks up a record by id.".to_string(),
+            input_schema: serde_json::json!({"type": "object", "properties": {}}),
+        };
+        let router = ToolRouter::from_config(
+            &turn.tools_config,
+            None,
+            None,
+            &[dynamic_tool("lookup_ticket")],
+        );
+        let turn_router = router.clone_for_turn(TurnToolOverrides::default());
+        let count = |router: &ToolRouter, name: &str| {
+            router
+                .specs()
+                .iter()
+                .filter(|spec| spec.name() == name)
+                .count()
+        };
+
+        router
+            .add_dynamic_tool(dynamic_tool("lookup_user"))
+            .expect("add dynamic tool");
+        assert_eq!(count(&turn_router, "lookup_user"), 1);
+        for name in ["lookup_user", "lookup_ticket", "update_plan"] {
+            assert!(matches!(
+                router.add_dynamic_tool(dynamic_tool(name)),
+                Err(RegistrationError::AlreadyRegistered { name: existing }) if existing == name
+            ));
+        }
+
+        let in_flight = router
+            .registry
+            .handler("lookup_ticket")
+            .expect("built dynamic tool has a handler");
+        assert!(router.remove_dynamic_tool("lookup_ticket"));
+        assert!(router.remove_dynamic_tool("lookup_user"));
+        assert!(!router.remove_dynamic_tool("lookup_ticket"));
+        assert!(!router.remove_dynamic_tool("update_plan"));
+        assert_eq!(count(&turn_router, "lookup_ticket"), 0);
+        assert_eq!(count(&turn_router, "lookup_user"), 0);
+        assert!(turn_router.get_spec("lookup_ticket").is_none());
+        assert!(turn_router.registry.handler("lookup_ticket").is_none());
+        assert_eq!(in_flight.kind(), ToolKind::Function);
+
+        router
+            .add_dynamic_tool(dynamic_tool("lookup_ticket"))
+            .expect("re-add removed dynamic tool");
+        assert_eq!(count(&turn_router, "lookup_ticket"), 1);
+        assert!(turn_router.registry.handler("lookup_ticket").is_some());
+    }
+
+    #[test]
+    fn batch_validate_reports_every_error_of_every_call() {
+        let registry = ToolRegistry::new(HashMap::from([(
+            "read_notes".to_string(),
+            Arc::new(StaticHandler(Ok("notes"))) as Arc<dyn ToolHandler>,
+        )]));
+        let mut router = router_with(registry);
+        router.specs = Arc::new(vec![ConfiguredToolSpec::new(
+            ToolSpec::Function(ResponsesApiTool {
+                name: "read_notes".to_string(),
+                description: String::new(),
+                strict: false,
+                parameters: parse_tool_input_schema(&serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string"},
+                        "limit": {"type": "number"},
+                    },
+                    "required": ["path"],
+                    "additionalProperties": false,
+                }))
+                .expect("valid schema"),
+            }),
+            true,
+        )]);
+        let call = |call_id: &str, tool_name: &str, arguments: serde_json::Value| ToolCall {
+            tool_name: tool_name.to_string(),
+            call_id: call_id.to_string(),
+            payload: ToolPayload::Function {
+                arguments: arguments.to_string(),
+            },
+            tags: HashMap::new(),
+            dry_run: false,
+        };
+        let calls = [
+            call("call-1", "read_notes", serde_json::json!({"path": "a.md"})),
+            call(
+                "call-2",
+                "read_notes",
+                serde_json::json!({"path": "b.md", "limit": 5}),
+            ),
+            call(
+                "call-3",
+                "read_notes",
+                serde_json::json!({"extra": true, "limit": "ten"}),
+            ),
+            call("call-4", "delete_everything", serde_json::json!({})),
+        ];
+
+        let results = router.batch_validate(&calls);
+
+        assert_eq!(
+            results,
+            HashMap::from([
+                ("call-1".to_string(), Vec::new()),
+                ("call-2".to_string(), Vec::new()),
+                (
+                    "call-3".to_string(),
+                    vec![
+                        ValidationError::MissingField {
+                            field: "path".to_string(),
+                        },
+                        ValidationError::UnexpectedField {
+                            field: "extra".to_string(),
+                        },
+                        ValidationError::WrongType {
+                            field: "limit".to_string(),
+                            expected: "number",
+                        },
+                    ],
+                ),
+                (
+                    "call-4".to_string(),
+                    vec![ValidationError::UnknownTool {
+                        tool_name: "delete_everything".to_string(),
+                    }],
+                ),
+            ])
+        );
+    }
+
+    fn function_spec(name: &str) -> ToolSpec {
+        ToolSpec::Function(ResponsesApiTool {
+            name: name.to_string(),
+            description: String::new(),
+            strict: false,
+            parameters: parse_tool_input_schema(
+                &serde_json::json!({"type": "object", "properties": {}}),
+            )
+            .expect("valid schema"),
+        })
+    }
+
+    #[test]
+    fn dependency_graph_lists_each_tools_dependencies() {
+        let mut router = router_with(ToolRegistry::new(HashMap::new()));
+        router.specs = Arc::new(vec![
+            ConfiguredToolSpec::new(function_spec("refactor_function"), false)
+                .with_tool_depends_on(vec!["parse_ast".to_string()]),
+            ConfiguredToolSpec::new(function_spec("parse_ast"), true)
+                .with_tool_depends_on(vec!["read_file".to_string()]),
+            ConfiguredToolSpec::new(function_spec("read_file"), true),
+        ]);
+
+        assert_eq!(
+            router.tool_dependency_graph(),
+            HashMap::from([
+                (
+                    "refactor_function".to_string(),
+                    vec!["parse_ast".to_string()]
+                ),
+                ("parse_ast".to_string(), vec!["read_file".to_string()]),
+                ("read_file".to_string(), Vec::new()),
+            ])
+        );
+    }
+
+    /// Records when each call starts and finishes. Earlier calls sleep longer,
+    /// so any overlap would reorder the log.
+    #[derive(Default)]
+    struct RecordingHandler {
+        events: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl ToolHandler for RecordingHandler {
+        fn kind(&self) -> ToolKind {
+            ToolKind::Function
+        }
+
+        async fn handle(
+            &self,
+            invocation: ToolInvocation,
+        ) -> Result<ToolOutput, FunctionCallError> {
+            let call_id = invocation.call_id;
+            self.events
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .push(format!("start {call_id}"));
+            let delay_ms = match call_id.as_str() {
+                "create" => 30,
+                "write" => 20,
+                _ => 0,
+            };
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+            self.events
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .push(format!("end {call_id}"));
+            Ok(ToolOutput::Function {
+                body: FunctionCallOutputBody::Text(call_id),
+                success: Some(true),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn dispatch_ordered_runs_calls_sequentially() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let handler = Arc::new(RecordingHandler::default());
+        let registry_handler: Arc<dyn ToolHandler> = handler.clone();
+        let router = router_with(ToolRegistry::new(HashMap::from([(
+            "record".to_string(),
+            registry_handler,
+        )])));
+        let calls = ["create", "write", "compile"]
+            .into_iter()
+            .map(|call_id| ToolCall {
+                tool_name: "record".to_string(),
+                call_id: call_id.to_string(),
+                payload: ToolPayload::Function {
+                    arguments: "{}".to_string(),
+                },
+                tags: HashMap::new(),
+                dry_run: false,
+            })
+            .collect();
+
+        let responses = router
+            .dispatch_ordered(
+                Arc::new(session),
+                Arc::new(turn),
+                Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                calls,
+                ToolCallSource::Direct,
+            )
+            .await?;
+
+        let call_ids: Vec<String> = responses
+            .into_iter()
+            .map(|response| match response {
+                ResponseInputItem::FunctionCallOutput { call_id, .. } => call_id,
+                other => panic!("expected function call output, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(call_ids, vec!["create", "write", "compile"]);
+        assert_eq!(
+            *handler
+                .events
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+            vec![
+                "start create",
+                "end create",
+                "start write",
+                "end write",
+                "start compile",
+                "end compile",
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn function_tool_closure_is_dispatched() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let mut registry = ToolRegistry::new(HashMap::new());
+        registry.register_function_tool(
+            "reverse",
+            serde_json::json!({
+                "type": "object",
+                "properties": { "text": { "type": "string" } },
+                "required": ["text"],
+            }),
+            |arguments| {
+                let args: serde_json::Value =
+                    serde_json::from_str(arguments).map_err(|err| err.to_string())?;
+                let text = args["text"]
+                    .as_str()
+                    .ok_or_else(|| "missing text".to_string())?;
+                Ok(text.chars().rev().collect())
+            },
+        )?;
+        assert!(
+            registry
+                .register_function_tool("reverse", serde_json::json!({"type": "object"}), |_| {
+                    Ok(String::new())
+                })
+                .is_err()
+        );
+        let router = router_with(registry);
+
+        let response = router
+            .dispatch_tool_call(
+                Arc::new(session),
+                Arc::new(turn),
+                Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                ToolCall {
+                    tool_name: "reverse".to_string(),
+                    call_id: "call-reverse".to_string(),
+                    payload: ToolPayload::Function {
+                        arguments: r#"{"text":"codex"}"#.to_string(),
+                    },
+                    tags: HashMap::new(),
+                    dry_run: false,
+                },
+                ToolCallSource::Direct,
+            )
+            .await?;
+
+        match response {
+            ResponseInputItem::FunctionCallOutput { output, .. } => {
+                assert_eq!(output.text_content(), Some("xedoc"));
+                assert_eq!(output.success, Some(true));
+            }
+            other => panic!("expected function call output, got {other:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn composed_tool_pipes_read_file_into_summarize_text() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let dir = tempfile::tempdir()?;
+        let notes = dir.path().join("notes.txt");
+        std::fs::write(&notes, "ship the \"fix\"\nwrite tests\nreview\n")?;
+
+        let read_file: Arc<dyn ToolHandler> = Arc::new(ReadFileHandler);
+        let mut registry = ToolRegistry::new(HashMap::from([("read_file".to_string(), read_file)]));
+        registry.register_function_tool(
+            "summarize_text",
+            serde_json::json!({
+                "type": "object",
+                "properties": { "text": { "type": "string" } },
+                "required": ["text"],
+            }),
+            |arguments| {
+                let args: serde_json::Value =
+                    serde_json::from_str(arguments).map_err(|err| err.to_string())?;
+                let text = args["text"]
+                    .as_str()
+                    .ok_or_else(|| "missing text".to_string())?;
+                let first = text.lines().next().unwrap_or_default();
+                Ok(format!(
+                    "{} lines, starting with {first}",
+                    text.lines().count()
+                ))
+            },
+        )?;
+        let read_notes = serde_json::json!({ "file_path": notes }).to_string();
+        registry.compose_tools(
+            "summarize_file",
+            vec![
+                ComposeStep {
+                    tool_name: "read_file".to_string(),
+                    argument_template: read_notes,
+                },
+                ComposeStep {
+                    tool_name: "summarize_text".to_string(),
+                    argument_template: r#"{"text": "{prev_output}"}"#.to_string(),
+                },
+            ],
+        )?;
+        assert_eq!(
+            registry.compose_tools(
+                "broken",
+                vec![ComposeStep {
+                    tool_name: "missing_tool".to_string(),
+                    argument_template: "{}".to_string(),
+                }],
+            ),
+            Err(ComposeError::UnknownTool {
+                name: "broken".to_string(),
+                index: 0,
+                tool_name: "missing_tool".to_string(),
+            })
+        );
+        let router = router_with(registry);
+
+        let response = router
+            .dispatch_tool_call(
+                Arc::new(session),
+                Arc::new(turn),
+                Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                ToolCall {
+                    tool_name: "summarize_file".to_string(),
+                    call_id: "call-compose".to_string(),
+                    payload: ToolPayload::Function {
+                        arguments: "{}".to_string(),
+                    },
+                    tags: HashMap::new(),
+                    dry_run: false,
+                },
+                ToolCallSource::Direct,
+            )
+            .await?;
+
+        match response {
+            ResponseInputItem::FunctionCallOutput { output, .. } => {
+                assert_eq!(
+                    output.text_content(),
+                    Some("3 lines, starting with L1: ship the \"fix\"")
+                );
+                assert_eq!(output.success, Some(true));
+            }
+            other => panic!("expected function call output, got {other:?}"),
+        }
+
+        Ok(())
+    }
+
+    /// Parks every call between two barriers so the test can observe them
+    /// while they are running.
+    struct BlockingHandler {
+        started: Arc<tokio::sync::Barrier>,
+        release: Arc<tokio::sync::Barrier>,
+    }
+
+    #[async_trait]
+    impl ToolHandler for BlockingHandler {
+        fn kind(&self) -> ToolKind {
+            ToolKind::Function
+        }
+
+        async fn handle(
+            &self,
+            _invocation: ToolInvocation,
+        ) -> Result<ToolOutput, FunctionCallError> {
+            self.started.wait().await;
+            self.release.wait().await;
+            Ok(ToolOutput::Function {
+                body: FunctionCallOutputBody::Text("done".to_string()),
+                success: Some(true),
+            })
+        }
+    }
+
+    /// Records the highest number of calls it was running at once.
+    #[derive(Default)]
+    struct ConcurrencyProbe {
+        running: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ToolHandler for ConcurrencyProbe {
+        fn kind(&self) -> ToolKind {
+            ToolKind::Function
+        }
+
+        async fn handle(
+            &self,
+            _invocation: ToolInvocation,
+        ) -> Result<ToolOutput, FunctionCallError> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(running, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(ToolOutput::Function {
+                body: FunctionCallOutputBody::Text("done".to_string()),
+                success: Some(true),
+            })
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn parallel_calls_beyond_the_cap_are_queued() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let (session, turn) = (Arc::new(session), Arc::new(turn));
+        let probe = Arc::new(ConcurrencyProbe::default());
+        let handler: Arc<dyn ToolHandler> = probe.clone();
+        let mut router = router_with(ToolRegistry::new(HashMap::from([(
+            "probe".to_string(),
+            handler,
+        )])));
+        router.tool_call_permits = Arc::new(tokio::sync::Semaphore::new(2));
+        // The per-tool timeout is shorter than the time the last call spends
+        // queued, so it only passes if queueing does not count against it.
+        router.tool_timeouts = Arc::new(HashMap::from([(
+            "probe".to_string(),
+            Duration::from_millis(50),
+        )]));
+
+        let calls = (0..6).map(|index| {
+            router.dispatch_tool_call(
+                Arc::clone(&session),
+                Arc::clone(&turn),
+                Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                ToolCall {
+                    tool_name: "probe".to_string(),
+                    call_id: format!("call-{index}"),
+                    payload: ToolPayload::Function {
+                        arguments: "{}".to_string(),
+                    },
+                    tags: HashMap::new(),
+                    dry_run: false,
+                },
+                ToolCallSource::Direct,
+            )
+        });
+        let responses = futures::future::join_all(calls).await;
+
+        assert_eq!(probe.peak.load(Ordering::SeqCst), 2);
+        for response in responses {
+            let ResponseInputItem::FunctionCallOutput { output, .. } = response? else {
+                panic!("expected function call output");
+            };
+            assert_eq!(output.success, Some(true));
+        }
+        Ok(())
+    }
+
+    /// Logs when each call starts and finishes, by call id.
+    #[derive(Default)]
+    struct CallTimeline {
+        events: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl ToolHandler for CallTimeline {
+        fn kind(&self) -> ToolKind {
+            ToolKind::Function
+        }
+
+        async fn handle(
+            &self,
+            invocation: ToolInvocation,
+        ) -> Result<ToolOutput, FunctionCallError> {
+            let call_id = invocation.call_id;
+            self.events
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .push(format!("start {call_id}"));
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.events
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .push(format!("end {call_id}"));
+            Ok(ToolOutput::Function {
+                body: FunctionCallOutputBody::Text(call_id),
+                success: Some(true),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn dispatch_tool_calls_runs_serial_tools_alone_and_keeps_call_order() {
+        let (session, turn) = make_session_and_context().await;
+        let timeline = Arc::new(CallTimeline::default());
+        let handler: Arc<dyn ToolHandler> = timeline.clone();
+        let mut router = router_with(ToolRegistry::new(HashMap::from([
+            ("read".to_string(), Arc::clone(&handler)),
+            ("write".to_string(), handler),
+        ])));
+        router.specs = Arc::new(vec![
+            ConfiguredToolSpec::new(function_spec("read"), true),
+            ConfiguredToolSpec::new(function_spec("write"), false),
+        ]);
+        let call = |tool_name: &str, call_id: &str| ToolCall {
+            tool_name: tool_name.to_string(),
+            call_id: call_id.to_string(),
+            payload: ToolPayload::Function {
+                arguments: "{}".to_string(),
+            },
+            tags: HashMap::new(),
+            dry_run: false,
+        };
+
+        let responses = router
+            .dispatch_tool_calls(
+                Arc::new(session),
+                Arc::new(turn),
+                Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                vec![
+                    call("read", "read-1"),
+                    call("read", "read-2"),
+                    call("write", "write-1"),
+                    call("read", "read-3"),
+                ],
+                ToolCallSource::Direct,
+            )
+            .await
+            .expect("no call is fatal");
+
+        let outputs: Vec<_> = responses
+            .iter()
+            .map(|response| match response {
+                ResponseInputItem::FunctionCallOutput { call_id, output } => {
+                    (call_id.as_str(), output.text_content())
+                }
+                other => panic!("expected function call output, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            outputs,
+            vec![
+                ("read-1", Some("read-1")),
+                ("read-2", Some("read-2")),
+                ("write-1", Some("write-1")),
+                ("read-3", Some("read-3")),
+            ]
+        );
+        let events = timeline
+            .events
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone();
+        let (first_run, rest) = events.split_at(4);
+        let mut first_run = first_run.to_vec();
+        first_run.sort();
+        assert_eq!(
+            first_run,
+            vec!["end read-1", "end read-2", "start read-1", "start read-2"]
+        );
+        assert!(events[..2].iter().all(|event| event.starts_with("start")));
+        assert_eq!(
+            rest,
+            ["start write-1", "end write-1", "start read-3", "end read-3"]
+        );
+    }
+
+    #[tokio::test]
+    async fn parallel_calls_are_listed_as_active_until_they_finish() {
+        let (session, turn) = make_session_and_context().await;
+        let session = Arc::new(session);
+        let turn = Arc::new(turn);
+        let call_ids = ["call-a", "call-b", "call-c"];
+        let started = Arc::new(tokio::sync::Barrier::new(call_ids.len() + 1));
+        let release = Arc::new(tokio::sync::Barrier::new(call_ids.len() + 1));
+        let handler: Arc<dyn ToolHandler> = Arc::new(BlockingHandler {
+            started: Arc::clone(&started),
+            release: Arc::clone(&release),
+        });
+        let router = Arc::new(router_with(ToolRegistry::new(HashMap::from([(
+            "slow_tool".to_string(),
+            handler,
+        )]))));
+
+        let tasks: Vec<_> = call_ids
+            .iter()
+            .map(|call_id| {
+                let router = Arc::clone(&router);
+                let session = Arc::clone(&session);
+                let turn = Arc::clone(&turn);
+                let call = ToolCall {
+                    tool_name: "slow_tool".to_string(),
+                    call_id: call_id.to_string(),
+                    payload: ToolPayload::Function {
+                        arguments: "{}".to_string(),
+                    },
+                    tags: HashMap::new(),
+                    dry_run: false,
+                };
+                tokio::spawn(async move {
+                    router
+                        .dispatch_tool_call(
+                            session,
+                            turn,
+                            Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                            call,
+                            ToolCallSource::Direct,
+                        )
+                        .await
+                })
+            })
+            .collect();
+        started.wait().await;
+
+        let mut active = session.get_active_tool_calls();
+        active.sort_by(|a, b| a.call_id.cmp(&b.call_id));
+        assert_eq!(
+            active
+                .iter()
+                .map(|call| (call.call_id.as_str(), call.tool_name.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("call-a", "slow_tool"),
+                ("call-b", "slow_tool"),
+                ("call-c", "slow_tool"),
+            ]
+        );
+        assert!(active.iter().all(|call| call.started_at_ms > 0));
+
+        release.wait().await;
+        for task in tasks {
+            task.await
+                .expect("dispatch task")
+                .expect("dispatch succeeds");
+        }
+        assert_eq!(session.get_active_tool_calls(), Vec::new());
+    }
+
+    /// Never finishes, so every call runs into its timeout.
+    struct HangingHandler(ToolKind);
+
+    #[async_trait]
+    impl ToolHandler for HangingHandler {
+        fn kind(&self) -> ToolKind {
+            self.0
+        }
+
+        async fn handle(
+            &self,
+            _invocation: ToolInvocation,
+        ) -> Result<ToolOutput, FunctionCallError> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn per_tool_timeout_reports_timeout_to_model() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let (session, turn) = (Arc::new(session), Arc::new(turn));
+        let function: Arc<dyn ToolHandler> = Arc::new(HangingHandler(ToolKind::Function));
+        let mcp: Arc<dyn ToolHandler> = Arc::new(HangingHandler(ToolKind::Mcp));
+        let mut router = router_with(ToolRegistry::new(HashMap::from([
+            ("slow_lookup".to_string(), function),
+            ("mcp__slow__search".to_string(), mcp),
+        ])));
+        router.tool_timeouts = Arc::new(HashMap::from([
+            ("slow_lookup".to_string(), Duration::from_millis(20)),
+            ("mcp__slow__search".to_string(), Duration::from_millis(20)),
+        ]));
+        let calls = [
+            ToolCall {
+                tool_name: "slow_lookup".to_string(),
+                call_id: "call-function".to_string(),
+                payload: ToolPayload::Function {
+                    arguments: "{}".to_string(),
+                },
+                tags: HashMap::new(),
+                dry_run: false,
+            },
+            ToolCall {
+                tool_name: "mcp__slow__search".to_string(),
+                call_id: "call-mcp".to_string(),
+                payload: ToolPayload::Mcp {
+                    server: "slow".to_string(),
+                    tool: "search".to_string(),
+                    raw_arguments: "{}".to_string(),
+                },
+                tags: HashMap::new(),
+                dry_run: false,
+            },
+        ];
+
+        for call in calls {
+            let tool_name = call.tool_name.clone();
+            let response = router
+                .dispatch_tool_call(
+                    Arc::clone(&session),
+                    Arc::clone(&turn),
+                    Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                    call,
+                    ToolCallSource::Direct,
+                )
+                .await?;
+
+            let ResponseInputItem::FunctionCallOutput { output, .. } = response else {
+                panic!("expected function call output, got {response:?}");
+            };
+            assert_eq!(output.success, Some(false));
+            let text = output.text_content().unwrap_or_default();
+            assert!(
+                text.starts_with(&format!("tool {tool_name} timed out after "))
+                    && text.ends_with("ms and was stopped before returning a result"),
+                "unexpected output: {text}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn mcp_transport_failures_are_retried_but_tool_errors_are_not() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let (session, turn) = (Arc::new(session), Arc::new(turn));
+        let dispatch = async |transport_failures: usize,
+                              outcome: Result<&'static str, &'static str>| {
+            let attempts = Arc::new(AtomicUsize::new(0));
+            let handler: Arc<dyn ToolHandler> = Arc::new(FlakyMcpHandler {
+                transport_failures,
+                outcome,
+                attempts: Arc::clone(&attempts),
+            });
+            let mut router = router_with(ToolRegistry::new(HashMap::from([(
+                "mcp__docs__search".to_string(),
+                handler,
+            )])));
+            router.set_mcp_transport_retry(RetryPolicy {
+                max_retries: 2,
+                initial_backoff: Duration::ZERO,
+                max_backoff: Duration::ZERO,
+            });
+            let response = router
+                .dispatch_tool_call(
+                    Arc::clone(&session),
+                    Arc::clone(&turn),
+                    Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                    ToolCall {
+                        tool_name: "mcp__docs__search".to_string(),
+                        call_id: "call-1".to_string(),
+                        payload: ToolPayload::Mcp {
+                            server: "docs".to_string(),
+                            tool: "search".to_string(),
+                            raw_arguments: "{}".to_string(),
+                        },
+                        tags: HashMap::new(),
+                        dry_run: false,
+                    },
+                    ToolCallSource::Direct,
+                )
+                .await?;
+            let ResponseInputItem::FunctionCallOutput { output, .. } = response else {
+                panic!("expected function call output, got {response:?}");
+            };
+            anyhow::Ok((attempts.load(Ordering::SeqCst), output))
+        };
+
+        let (attempts, output) = dispatch(2, Ok("found")).await?;
+        assert_eq!(attempts, 3);
+        assert_eq!(output.text_content(), Some("found"));
+
+        let (attempts, output) = dispatch(5, Ok("found")).await?;
+        assert_eq!(attempts, 3);
+        assert_eq!(output.error_code.as_deref(), Some("mcp_transport"));
+
+        let (attempts, output) = dispatch(0, Err("no such index")).await?;
+        assert_eq!(attempts, 1);
+        assert_eq!(output.text_content(), Some("no such index"));
+        assert_eq!(output.error_code.as_deref(), Some("respond_to_model"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn output_limit_keeps_head_and_tail_of_every_output() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let (session, turn) = (Arc::new(session), Arc::new(turn));
+        let mcp_handler: Arc<dyn ToolHandler> = Arc::new(UnreachableMcpHandler);
+        let mut registry = ToolRegistry::new(HashMap::from([(
+            "mcp__docs__search".to_string(),
+            mcp_handler,
+        )]));
+        registry.register_function_tool(
+            "dump_log",
+            serde_json::json!({"type": "object", "properties": {}}),
+            |_| Ok(format!("head-{}-tail", "x".repeat(100))),
+        )?;
+        let mut router = router_with(registry);
+        let dispatch = async |router: &ToolRouter, tool_name: &str, payload: ToolPayload| {
+            router
+                .dispatch_tool_call(
+                    Arc::clone(&session),
+                    Arc::clone(&turn),
+                    Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                    ToolCall {
+                        tool_name: tool_name.to_string(),
+                        call_id: "call-1".to_string(),
+                        payload,
+                        tags: HashMap::new(),
+                        dry_run: false,
+                    },
+                    ToolCallSource::Direct,
+                )
+                .await
+        };
+        let function_payload = || ToolPayload::Function {
+            arguments: "{}".to_string(),
+        };
+
+        router.set_output_limit(OutputLimit {
+            max_bytes: 10,
+            head_ratio: 0.5,
+        });
+        let ResponseInputItem::FunctionCallOutput { output, .. } =
+            dispatch(&router, "dump_log", function_payload()).await?
+        else {
+            panic!("expected function call output");
+        };
+        assert_eq!(
+            output.text_content(),
+            Some("head-\n[truncated: 100 bytes omitted]\n-tail")
+        );
+        let mcp_response = dispatch(
+            &router,
+            "mcp__docs__search",
+            ToolPayload::Mcp {
+                server: "docs".to_string(),
+                tool: "search".to_string(),
+                raw_arguments: "{}".to_string(),
+            },
+        )
+        .await?;
+        let ResponseInputItem::McpToolCallOutput { result, .. } = mcp_response else {
+            panic!("expected MCP output, got {mcp_response:?}");
+        };
+        assert_eq!(
+            result.err().as_deref(),
+            Some("conne\n[truncated: 8 bytes omitted]\nfused")
+        );
+
+        router.set_output_limit(OutputLimit {
+            max_bytes: 0,
+            head_ratio: 0.5,
+        });
+        let ResponseInputItem::FunctionCallOutput { output, .. } =
+            dispatch(&router, "dump_log", function_payload()).await?
+        else {
+            panic!("expected function call output");
+        };
+        assert_eq!(
+            output.text_content(),
+            Some(format!("head-{}-tail", "x".repeat(100)).as_str())
+        );
+
+        Ok(())
+    }
+
+    #[derive(Default)]
+    struct RecordingAuditHook {
+        records: std::sync::Mutex<Vec<AuditRecord>>,
+    }
+
+    impl AuditHook for RecordingAuditHook {
+        fn record(&self, record: &AuditRecord) -> anyhow::Result<()> {
+            self.records
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .push(record.clone());
+            Ok(())
+        }
+    }
+
+    struct FailingAuditHook;
+
+    impl AuditHook for FailingAuditHook {
+        fn record(&self, _record: &AuditRecord) -> anyhow::Result<()> {
+            anyhow::bail!("audit sink unavailable")
+        }
+    }
+
+    #[tokio::test]
+    async fn audit_hooks_record_every_call_without_affecting_it() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let (session, turn) = (Arc::new(session), Arc::new(turn));
+        let lookup: Arc<dyn ToolHandler> = Arc::new(StaticHandler(Ok("found")));
+        let mcp_handler: Arc<dyn ToolHandler> = Arc::new(UnreachableMcpHandler);
+        let mut router = router_with(ToolRegistry::new(HashMap::from([
+            ("lookup".to_string(), lookup),
+            ("mcp__docs__search".to_string(), mcp_handler),
+        ])));
+        let recorder = Arc::new(RecordingAuditHook::default());
+        router.register_audit_hook(Arc::new(FailingAuditHook));
+        router.register_audit_hook(recorder.clone());
+        let calls = [
+            (
+                "lookup",
+                ToolPayload::Function {
+                    arguments: r#"{"api_key": "sk-abcdefghijklmnopqrstuvwx"}"#.to_string(),
+                },
+            ),
+            (
+                "mcp__docs__search",
+                ToolPayload::Mcp {
+                    server: "docs".to_string(),
+                    tool: "search".to_string(),
+                    raw_arguments: r#"{"query": "retries"}"#.to_string(),
+                },
+            ),
+        ];
+
+        let mut responses = Vec::new();
+        for (index, (tool_name, payload)) in calls.into_iter().enumerate() {
+            responses.push(
+                router
+                    .dispatch_tool_call(
+                        Arc::clone(&session),
+                        Arc::clone(&turn),
+                        Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                        ToolCall {
+                            tool_name: tool_name.to_string(),
+                            call_id: format!("call-{index}"),
+                            payload,
+                            tags: HashMap::new(),
+                            dry_run: false,
+                        },
+                        ToolCallSource::Direct,
+                    )
+                    .await?,
+            );
+        }
+
+        let ResponseInputItem::FunctionCallOutput { output, .. } = &responses[0] else {
+            panic!("expected function call output, got {:?}", responses[0]);
+        };
+        assert_eq!(output.text_content(), Some("found"));
+        let records = recorder
+            .records
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .iter()
+            .map(|record| {
+                (
+                    record.call_id.clone(),
+                    record.tool_name.clone(),
+                    record.mcp_server.clone(),
+                    record.arguments.clone(),
+                    record.outcome,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            records,
+            vec![
+                (
+                    "call-0".to_string(),
+                    "lookup".to_string(),
+                    None,
+                    r#"{"api_key": "[REDACTED_SECRET]"}"#.to_string(),
+                    AuditOutcome::Success,
+                ),
+                (
+                    "call-1".to_string(),
+                    "mcp__docs__search".to_string(),
+                    Some("docs".to_string()),
+                    r#"{"query": "retries"}"#.to_string(),
+                    AuditOutcome::Failure,
+                ),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn tool_duration_is_recorded_for_every_outcome() -> anyhow::Result<()> {
+        let (session, mut turn) = make_session_and_context().await;
+        let config = MetricsConfig::in_memory(
+            "test",
+            "codex-cli",
+            env!("CARGO_PKG_VERSION"),
+            InMemoryMetricExporter::default(),
+        )
+        .with_runtime_reader();
+        turn.session_telemetry = turn
+            .session_telemetry
+            .clone()
+            .with_metrics_without_metadata_tags(MetricsClient::new(config)?);
+        let (session, turn) = (Arc::new(session), Arc::new(turn));
+        let ok: Arc<dyn ToolHandler> = Arc::new(StaticHandler(Ok("found")));
+        let failing: Arc<dyn ToolHandler> = Arc::new(StaticHandler(Err("not found")));
+        let mcp: Arc<dyn ToolHandler> = Arc::new(UnreachableMcpHandler);
+        let router = router_with(ToolRegistry::new(HashMap::from([
+            ("lookup".to_string(), ok),
+            ("broken_lookup".to_string(), failing),
+            ("mcp__docs__search".to_string(), mcp),
+        ])));
+        let function_call = |tool_name: &str| ToolCall {
+            tool_name: tool_name.to_string(),
+            call_id: format!("call-{tool_name}"),
+            payload: ToolPayload::Function {
+                arguments: "{}".to_string(),
+            },
+            tags: HashMap::new(),
+            dry_run: false,
+        };
+        let calls = [
+            function_call("lookup"),
+            function_call("broken_lookup"),
+            ToolCall {
+                tool_name: "mcp__docs__search".to_string(),
+                call_id: "call-mcp".to_string(),
+                payload: ToolPayload::Mcp {
+                    server: "docs".to_string(),
+                    tool: "search".to_string(),
+                    raw_arguments: "{}".to_string(),
+                },
+                tags: HashMap::new(),
+                dry_run: false,
+            },
+        ];
+
+        for call in calls {
+            router
+                .dispatch_tool_call(
+                    Arc::clone(&session),
+                    Arc::clone(&turn),
+                    Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                    call,
+                    ToolCallSource::Direct,
+                )
+                .await?;
+        }
+
+        let snapshot = turn.session_telemetry.snapshot_metrics()?;
+        let mut recorded = Vec::new();
+        for metric in snapshot
+            .scope_metrics()
+            .flat_map(|scope| scope.metrics())
+            .filter(|metric| metric.name() == "codex.tool.duration_ms")
+        {
+            let AggregatedMetrics::F64(MetricData::Histogram(histogram)) = metric.data() else {
+                panic!("unexpected data for {}", metric.name());
+            };
+            for point in histogram.data_points() {
+                assert_eq!(point.count(), 1);
+                recorded.push(
+                    point
+                        .attributes()
+                        .map(|kv| (kv.key.to_string(), kv.value.to_string()))
+                        .collect::<BTreeMap<_, _>>(),
+                );
+            }
+        }
+        recorded.sort();
+
+        let tags = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<BTreeMap<_, _>>()
+        };
+        let mut expected = vec![
+            tags(&[("outcome", "success"), ("tool_name", "lookup")]),
+            tags(&[("outcome", "failure"), ("tool_name", "broken_lookup")]),
+            tags(&[
+                ("outcome", "success"),
+                ("server", "docs"),
+                ("tool_name", "mcp__docs__search"),
+            ]),
+        ];
+        expected.sort();
+        assert_eq!(recorded, expected);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn fatal_errors_are_counted_per_tool() -> anyhow::Result<()> {
+        let (session, mut turn) = make_session_and_context().await;
+        let config = MetricsConfig::in_memory(
+            "test",
+            "codex-cli",
+            env!("CARGO_PKG_VERSION"),
+            InMemoryMetricExporter::default(),
+        )
+        .with_runtime_reader();
+        turn.session_telemetry = turn
+            .session_telemetry
+            .clone()
+            .with_metrics_without_metadata_tags(MetricsClient::new(config)?);
+        let (session, turn) = (Arc::new(session), Arc::new(turn));
+        let failing: Arc<dyn ToolHandler> = Arc::new(StaticHandler(Err("not found")));
+        let fatal: Arc<dyn ToolHandler> = Arc::new(FatalHandler);
+        let router = router_with(ToolRegistry::new(HashMap::from([
+            ("broken_lookup".to_string(), failing),
+            ("fatal_tool".to_string(), fatal),
+        ])));
+        let dispatch = |tool_name: &str| {
+            router.dispatch_tool_call(
+                Arc::clone(&session),
+                Arc::clone(&turn),
+                Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                ToolCall {
+                    tool_name: tool_name.to_string(),
+                    call_id: format!("call-{tool_name}"),
+                    payload: ToolPayload::Function {
+                        arguments: "{}".to_string(),
+                    },
+                    tags: HashMap::new(),
+                    dry_run: false,
+                },
+                ToolCallSource::Direct,
+            )
+        };
+
+        let recoverable = dispatch("broken_lookup").await?;
+        let fatal = dispatch("fatal_tool").await;
+
+        let ResponseInputItem::FunctionCallOutput { output, .. } = recoverable else {
+            panic!("expected function call output");
+        };
+        assert_eq!(output.text_content(), Some("not found"));
+        assert_eq!(
+            fatal,
+            Err(FunctionCallError::Fatal("sandbox crashed".to_string()))
+        );
+        let snapshot = turn.session_telemetry.snapshot_metrics()?;
+        let mut recorded = Vec::new();
+        for metric in snapshot
+            .scope_metrics()
+            .flat_map(|scope| scope.metrics())
+            .filter(|metric| metric.name() == TOOL_FATAL_METRIC)
+        {
+            let AggregatedMetrics::U64(MetricData::Sum(sum)) = metric.data() else {
+                panic!("unexpected data for {}", metric.name());
+            };
+            for point in sum.data_points() {
+                let tags = point
+                    .attributes()
+                    .map(|kv| (kv.key.to_string(), kv.value.to_string()))
+                    .collect::<Vec<_>>();
+                recorded.push((tags, point.value()));
+            }
+        }
+        assert_eq!(
+            recorded,
+            vec![(vec![("tool_name".to_string(), "fatal_tool".to_string())], 1)]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn failure_response_carries_error_code() {
+        let timed_out = FunctionCallError::TimedOut {
+            tool_name: "slow_lookup".to_string(),
+            elapsed_ms: 20,
+        };
+        let too_large = FunctionCallError::PayloadTooLarge {
+            size_bytes: 2048,
+            limit_bytes: 1024,
+        };
+
+        let expected = |err: &FunctionCallError, code: &str| FunctionCallOutputPayload {
+            body: FunctionCallOutputBody::Text(err.to_string()),
+            success: Some(false),
+            error_code: Some(code.to_string()),
+        };
+        let expected_function = ResponseInputItem::FunctionCallOutput {
+            call_id: "call-1".to_string(),
+            output: expected(&timed_out, "timed_out"),
+        };
+        let expected_custom = ResponseInputItem::CustomToolCallOutput {
+            call_id: "call-2".to_string(),
+            output: expected(&too_large, "payload_too_large"),
+        };
+
+        assert_eq!(
+            ToolRouter::failure_response("call-1".to_string(), false, timed_out),
+            expected_function
+        );
+        assert_eq!(
+            ToolRouter::failure_response("call-2".to_string(), true, too_large),
+            expected_custom
+        );
+    }
+
+    struct StaticHandler(Result<&'static str, &'static str>);
+
+    #[async_trait]
+    impl ToolHandler for StaticHandler {
+        fn kind(&self) -> ToolKind {
+            ToolKind::Function
+        }
+
+        async fn handle(
+            &self,
+            _invocation: ToolInvocation,
+        ) -> Result<ToolOutput, FunctionCallError> {
+            match self.0 {
+                Ok(content) => Ok(ToolOutput::Function {
+                    body: FunctionCallOutputBody::Text(content.to_string()),
+                    success: Some(true),
+                }),
+                Err(message) => Err(FunctionCallError::RespondToModel(message.to_string())),
+            }
+        }
+    }
+
+    fn web_search_router(primary: StaticHandler) -> ToolRouter {
+        let primary: Arc<dyn ToolHandler> = Arc::new(primary);
+        let offline: Arc<dyn ToolHandler> = Arc::new(StaticHandler(Ok("cached results")));
+        let mut router = router_with(ToolRegistry::new(HashMap::from([
+            ("web_search".to_string(), primary),
+            ("web_search_offline".to_string(), offline),
+        ])));
+        router.set_fallback("web_search", "web_search_offline");
+        router
+    }
+
+    async fn dispatch_web_search(router: &ToolRouter) -> anyhow::Result<Option<String>> {
+        let (session, turn) = make_session_and_context().await;
+        let response = router
+            .dispatch_tool_call(
+                Arc::new(session),
+                Arc::new(turn),
+                Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                ToolCall {
+                    tool_name: "web_search".to_string(),
+                    call_id: "call-search".to_string(),
+                    payload: ToolPayload::Function {
+                        arguments: r#"{"query":"rust"}"#.to_string(),
+                    },
+                    tags: HashMap::new(),
+                    dry_run: false,
+                },
+                ToolCallSource::Direct,
+            )
+            .await?;
+        match response {
+            ResponseInputItem::FunctionCallOutput { output, .. } => {
+                Ok(output.text_content().map(str::to_string))
+            }
+            other => panic!("expected function call output, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn fallback_is_used_when_primary_fails() -> anyhow::Result<()> {
+        let router = web_search_router(StaticHandler(Err("network unreachable")));
+
+        let content = dispatch_web_search(&router).await?;
+
+        assert_eq!(
+            content.as_deref(),
+            Some(
+                "fallback_used: true (web_search failed; result produced by web_search_offline)\ncached results"
+            )
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn fallback_is_skipped_when_primary_succeeds() -> anyhow::Result<()> {
+        let router = web_search_router(StaticHandler(Ok("live results")));
+
+        let content = dispatch_web_search(&router).await?;
+
+        assert_eq!(content.as_deref(), Some("live results"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dispatched_calls_are_retrievable_by_id() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let session = Arc::new(session);
+        let turn = Arc::new(turn);
+        let tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
+        let mut registry = ToolRegistry::new(HashMap::new());
+        registry.register_function_tool("echo", serde_json::json!({"type": "object"}), |args| {
+            Ok(format!("echo {args}"))
+        })?;
+        let router = router_with(registry);
+        let turn_router = router.clone_for_turn(TurnToolOverrides::default());
+
+        for i in 0..5 {
+            turn_router
+                .dispatch_tool_call(
+                    Arc::clone(&session),
+                    Arc::clone(&turn),
+                    Arc::clone(&tracker),
+                    ToolCall {
+                        tool_name: "echo".to_string(),
+                        call_id: format!("call-{i}"),
+                        payload: ToolPayload::Function {
+                            arguments: format!(r#"{{"n":{i}}}"#),
+                        },
+                        tags: HashMap::new(),
+                        dry_run: false,
+                    },
+                    ToolCallSource::Direct,
+                )
+                .await?;
+        }
+
+        for i in 0..5 {
+            let archived = router
+                .get_call_by_id(&format!("call-{i}"))
+                .expect("call should be archived");
+            assert_eq!(archived.tool_name, "echo");
+            assert_eq!(archived.arguments, format!(r#"{{"n":{i}}}"#));
+            assert_eq!(archived.result_summary, format!(r#"echo {{"n":{i}}}"#));
+        }
+        assert_eq!(router.get_call_by_id("call-5"), None);
+
+        Ok(())
+    }
+
+    /// Compiles `tests/fixtures/tool_plugin.rs`, which has no dependencies,
+    /// into a shared library in `dir` and returns its path.
+    #[cfg(feature = "dynamic-plugins")]
+    fn compile_tool_plugin_fixture(dir: &Path) -> anyhow::Result<std::path::PathBuf> {
+        let source = codex_utils_cargo_bin::find_resource!("tests/fixtures/tool_plugin.rs")?;
+        let library = dir.join(format!(
+            "{}tool_plugin{}",
+            std::env::consts::DLL_PREFIX,
+            std::env::consts::DLL_SUFFIX
+        ));
+        let rustc = std::env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+        let output = std::process::Command::new(rustc)
+            .args(["--edition=2024", "--crate-type=cdylib", "-o"])
+            .arg(&library)
+            .arg(&source)
+            .output()?;
+        anyhow::ensure!(
+            output.status.success(),
+            "failed to compile {}: {}",
+            source.display(),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        Ok(library)
+    }
+
+    #[cfg(feature = "dynamic-plugins")]
+    #[tokio::test]
+    async fn load_plugin_registers_tools_from_a_shared_library() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let library = compile_tool_plugin_fixture(dir.path())?;
+        let (session, turn) = make_session_and_context().await;
+        let mut router = router_with(ToolRegistry::new(HashMap::new()));
+
+        assert_eq!(
+            router.load_plugin(&library)?,
+            vec!["plugin_echo".to_string()]
+        );
+        assert!(router.is_tool_enabled("plugin_echo"));
+
+        let response = router
+            .dispatch_tool_call(
+                Arc::new(session),
+                Arc::new(turn),
+                Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                ToolCall {
+                    tool_name: "plugin_echo".to_string(),
+                    call_id: "call-plugin".to_string(),
+                    payload: ToolPayload::Function {
+                        arguments: r#"{"n":1}"#.to_string(),
+                    },
+                    tags: HashMap::new(),
+                    dry_run: false,
+                },
+                ToolCallSource::Direct,
+            )
+            .await?;
+        let ResponseInputItem::FunctionCallOutput { output, .. } = response else {
+            panic!("expected function call output, got {response:?}");
+        };
+        assert_eq!(output.text_content(), Some(r#"plugin received {"n":1}"#));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn call_archive_forgets_the_oldest_calls_beyond_its_capacity() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let session = Arc::new(session);
+        let turn = Arc::new(turn);
+        let tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
+        let mut registry = ToolRegistry::new(HashMap::new());
+        registry.register_function_tool("echo", serde_json::json!({"type": "object"}), |args| {
+            Ok(format!("echo {args}"))
+        })?;
+        let mut router = router_with(registry);
+        router.set_call_archive(CallArchive::with_capacity(2));
+
+        for i in 0..3 {
+            router
+                .dispatch_tool_call(
+                    Arc::clone(&session),
+                    Arc::clone(&turn),
+                    Arc::clone(&tracker),
+                    ToolCall {
+                        tool_name: "echo".to_string(),
+                        call_id: format!("call-{i}"),
+                        payload: ToolPayload::Function {
+                            arguments: "{}".to_string(),
+                        },
+                        tags: HashMap::new(),
+                        dry_run: false,
+                    },
+                    ToolCallSource::Direct,
+                )
+                .await?;
+        }
+
+        assert_eq!(router.get_call_by_id("call-0"), None);
+        assert!(router.get_call_by_id("call-1").is_some());
+        assert!(router.get_call_by_id("call-2").is_some());
+        assert_eq!(router.call_archive.lock_state().order.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn from_config_file_applies_inherited_settings() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(
+            dir.path().join("base.toml"),
+            "model = \"gpt-5-codex\"\nshell_type = \"disabled\"\nmax_argument_size_bytes = 64\n",
+        )
+        .expect("write base config");
+        let path = dir.path().join("child.toml");
+        std::fs::write(
+            &path,
+            "inherit_from = \"base.toml\"\nmax_argument_size_bytes = 128\n",
+        )
+        .expect("write child config");
+
+        let router = ToolRouter::from_config_file(&path).expect("load router");
+
+        assert_eq!(router.max_argument_size_bytes, Some(128));
+        assert!(
+            router
+                .specs()
+                .iter()
+                .all(|spec| !SHELL_TOOL_ALIASES.contains(&spec.name()))
+        );
+    }
+
+    #[tokio::test]
+    async fn dry_run_validates_shell_call_without_running_it() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let (session, turn) = (Arc::new(session), Arc::new(turn));
+        let shell: Arc<dyn ToolHandler> = Arc::new(crate::tools::handlers::ShellHandler);
+        let router = router_with(ToolRegistry::new(HashMap::from([(
+            "shell".to_string(),
+            shell,
+        )])));
+        let dir = tempfile::tempdir()?;
+        let marker = dir.path().join("created.txt");
+        let cwd = turn.cwd.clone();
+        let shell_call = |call_id: &str, arguments: String| {
+            ToolCall {
+                tool_name: "shell".to_string(),
+                call_id: call_id.to_string(),
+                payload: ToolPayload::Function { arguments },
+                tags: HashMap::new(),
+                dry_run: false,
+            }
+            .dry_run()
+        };
+
+        let valid = router
+            .dispatch_tool_call(
+                Arc::clone(&session),
+                Arc::clone(&turn),
+                Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                shell_call(
+                    "call-valid",
+                    serde_json::json!({ "command": ["touch", marker] }).to_string(),
+                ),
+                ToolCallSource::Direct,
+            )
+            .await?;
+        let malformed = router
+            .dispatch_tool_call(
+                session,
+                turn,
+                Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                shell_call("call-malformed", "{\"command\": ".to_string()),
+                ToolCallSource::Direct,
+            )
+            .await?;
+
+        let ResponseInputItem::FunctionCallOutput { output, .. } = valid else {
+            panic!("unexpected response: {valid:?}");
+        };
+        assert_eq!(output.success, Some(true));
+        let text = output.body.to_text().unwrap_or_default();
+        let preview = text
+            .strip_prefix("dry run: shell call is valid and was not executed; it would run ")
+            .unwrap_or_else(|| panic!("unexpected dry run output: {text}"));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(preview)?,
+            serde_json::json!({
+                "command": ["touch", marker],
+                "workdir": cwd,
+                "sandbox_permissions": "use_default",
+            })
+        );
+        assert!(!marker.exists());
+        let ResponseInputItem::FunctionCallOutput { output, .. } = malformed else {
+            panic!("unexpected response: {malformed:?}");
+        };
+        assert_eq!(output.success, Some(false));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dry_run_previews_mcp_calls_and_runs_pure_tools() -> anyhow::Result<()> {
+        let (session, turn) = make_session_and_context().await;
+        let (session, turn) = (Arc::new(session), Arc::new(turn));
+        let mcp: Arc<dyn ToolHandler> = Arc::new(UnreachableMcpHandler);
+        let read_file: Arc<dyn ToolHandler> = Arc::new(ReadFileHandler);
+        let router = router_with(ToolRegistry::new(HashMap::from([
+            ("mcp__docs__search".to_string(), mcp),
+            ("read_file".to_string(), read_file),
+        ])));
+        let dir = tempfile::tempdir()?;
+        let notes = dir.path().join("notes.txt");
+        std::fs::write(&notes, "pure tools still run\n")?;
+        let dispatch = |tool_name: &str, payload: ToolPayload| {
+            router.dispatch_tool_call(
+                Arc::clone(&session),
+                Arc::clone(&turn),
+                Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                ToolCall {
+                    tool_name: tool_name.to_string(),
+                    call_id: format!("call-{tool_name}"),
+                    payload,
+                    tags: HashMap::new(),
+                    dry_run: false,
+                }
+                .dry_run(),
+                ToolCallSource::Direct,
+            )
+        };
+
+        let mcp_response = dispatch(
+            "mcp__docs__search",
+            ToolPayload::Mcp {
+                server: "docs".to_string(),
+                tool: "search".to_string(),
+                raw_arguments: r#"{"query":"sandbox"}"#.to_string(),
+            },
+        )
+        .await?;
+        let read_response = dispatch(
+            "read_file",
+            ToolPayload::Function {
+                arguments: serde_json::json!({ "file_path": notes }).to_string(),
+            },
+        )
+        .await?;
+
+        let ResponseInputItem::FunctionCallOutput { output, .. } = mcp_response else {
+            panic!("unexpected response: {mcp_response:?}");
+        };
+        let text = output.body.to_text().unwrap_or_default();
+        let preview = text
+            .strip_prefix(
+                "dry run: mcp__docs__search call is valid and was not executed; it would run ",
+            )
+            .unwrap_or_else(|| panic!("unexpected dry run output: {text}"));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(preview)?,
+            serde_json::json!({
+                "server": "docs",
+                "tool": "search",
+                "arguments": {"query": "sandbox"},
+            })
+        );
+        let ResponseInputItem::FunctionCallOutput { output, .. } = read_response else {
+            panic!("unexpected response: {read_response:?}");
+        };
+        let text = output.body.to_text().unwrap_or_default();
+        assert!(text.contains("pure tools still run"), "{text}");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn chaos_with_full_error_rate_fails_every_call() -> anyhow::Result<()> {
+        let (session, turn, rx) = make_session_and_context_with_rx().await;
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut registry = ToolRegistry::new(HashMap::new());
+        let counter = Arc::clone(&calls);
+        registry.register_function_tool(
+            "noop",
+            serde_json::json!({"type": "object", "properties": {}}),
+            move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(String::new())
+            },
+        )?;
+        let mut router = router_with(registry);
+        router.enable_chaos(ChaosConfig {
+            error_rate: 1.0,
+            latency_jitter_ms: 0,
+            random_payload_corruption: false,
+            seed: 7,
+        });
+
+        for index in 0..3 {
+            let response = router
+                .dispatch_tool_call(
+                    Arc::clone(&session),
+                    Arc::clone(&turn),
+                    Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                    ToolCall {
+                        tool_name: "noop".to_string(),
+                        call_id: format!("call-{index}"),
+                        payload: ToolPayload::Function {
+                            arguments: "{}".to_string(),
+                        },
+                        tags: HashMap::new(),
+                        dry_run: false,
+                    },
+                    ToolCallSource::Direct,
+                )
+                .await?;
+            let ResponseInputItem::FunctionCallOutput { output, .. } = response else {
+                panic!("unexpected response: {response:?}");
+            };
+            assert_eq!(output.success, Some(false));
+            assert_eq!(
+                output.body.to_text().as_deref(),
+                Some("chaos: injected failure for noop")
+            );
+        }
+
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        let mut faults = Vec::new();
+        while faults.len() < 3 {
+            let event = tokio::time::timeout(Duration::from_secs(1), rx.recv()).await??;
+            if let EventMsg::ChaosFaultInjected(fault) = event.msg {
+                faults.push((fault.call_id, fault.fault_type));
+            }
+        }
+        assert_eq!(
+            faults,
+            vec![
+                ("call-0".to_string(), ChaosFaultType::Error),
+                ("call-1".to_string(), ChaosFaultType::Error),
+                ("call-2".to_string(), ChaosFaultType::Error),
+            ]
+        );
+
+        Ok(())
+    }
+
+    /// Fails every call with an error that ends the turn.
+    struct FatalHandler;
+
+    #[async_trait]
+    impl ToolHandler for FatalHandler {
+        fn kind(&self) -> ToolKind {
+            ToolKind::Function
+        }
+
+        async fn handle(
+            &self,
+            _invocation: ToolInvocation,
+        ) -> Result<ToolOutput, FunctionCallError> {
+            Err(FunctionCallError::Fatal("sandbox crashed".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn dispatch_tool_calls_stops_at_a_fatal_error() {
+        let (session, turn) = make_session_and_context().await;
+        let timeline = Arc::new(CallTimeline::default());
+        let recorded: Arc<dyn ToolHandler> = timeline.clone();
+        let fatal: Arc<dyn ToolHandler> = Arc::new(FatalHandler);
+        let mut router = router_with(ToolRegistry::new(HashMap::from([
+            ("write".to_string(), recorded),
+            ("fatal_tool".to_string(), fatal),
+        ])));
+        router.specs = Arc::new(vec![
+            ConfiguredToolSpec::new(function_spec("write"), false),
+            ConfiguredToolSpec::new(function_spec("fatal_tool"), false),
+        ]);
+        let call = |tool_name: &str, call_id: &str| ToolCall {
+            tool_name: tool_name.to_string(),
+            call_id: call_id.to_string(),
+            payload: ToolPayload::Function {
+                arguments: "{}".to_string(),
+            },
+            tags: HashMap::new(),
+            dry_run: false,
+        };
+
+        let result = router
+            .dispatch_tool_calls(
+                Arc::new(session),
+                Arc::new(turn),
+                Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                vec![
+                    call("write", "write-1"),
+                    call("fatal_tool", "fatal-1"),
+                    call("write", "write-2"),
+                ],
+                ToolCallSource::Direct,
+            )
+            .await;
+
+        assert_eq!(
+            result,
+            Err(FunctionCallError::Fatal("sandbox crashed".to_string()))
+        );
+        assert_eq!(
+            *timeline
+                .events
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+            vec!["start write-1".to_string(), "end write-1".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn tool_call_subscribers_see_start_and_outcome_in_order() {
+        let (session, turn) = make_session_and_context().await;
+        let session = Arc::new(session);
+        let turn = Arc::new(turn);
+        let ok: Arc<dyn ToolHandler> = Arc::new(StaticHandler(Ok("done")));
+        let fatal: Arc<dyn ToolHandler> = Arc::new(FatalHandler);
+        let router = router_with(ToolRegistry::new(HashMap::from([
+            ("ok_tool".to_string(), ok),
+            ("fatal_tool".to_string(), fatal),
+        ])));
+        let mut events = Box::pin(session.subscribe_to_tool_calls());
+
+        for (tool_name, call_id) in [("ok_tool", "call-ok"), ("fatal_tool", "call-fatal")] {
+            let _ = router
+                .dispatch_tool_call(
+                    Arc::clone(&session),
+                    Arc::clone(&turn),
+                    Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new())),
+                    ToolCall {
+                        tool_name: tool_name.to_string(),
+                        call_id: call_id.to_string(),
+                        payload: ToolPayload::Function {
+                            arguments: "{}".to_string(),
+                        },
+                        tags: HashMap::new(),
+                        dry_run: false,
+                    },
+                    ToolCallSource::Direct,
+                )
+                .await;
+        }
+
+        let mut received = Vec::new();
+        for _ in 0..4 {
+            received.push(events.next().await.expect("tool call event"));
+        }
+        let summary = received
+            .iter()
+            .map(|event| match event {
+                ToolCallEvent::Started(call) => format!("started {}", call.call_id),
+                ToolCallEvent::Completed { call_id, .. } => format!("completed {call_id}"),
+                ToolCallEvent::Failed { call_id, .. } => format!("failed {call_id}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                "started call-ok",
+                "completed call-ok",
+                "started call-fatal",
+                "failed call-fatal",
+            ]
+        );
+        let ToolCallEvent::Completed { result, .. } = &received[1] else {
+            unreachable!("checked above");
+        };
+        assert_eq!(
+            result,
+            &ResponseInputItem::FunctionCallOutput {
+                call_id: "call-ok".to_string(),
+                output: FunctionCallOutputPayload {
+                    body: FunctionCallOutputBody::Text("done".to_string()),
+                    success: Some(true),
+                    error_code: None,
+                },
+            }
+        );
+        let ToolCallEvent::Failed { error, .. } = &received[3] else {
+            unreachable!("checked above");
+        };
+        assert_eq!(
+            error,
+            &FunctionCallError::Fatal("sandbox crashed".to_string())
+        );
+    }
 }
@@ -102,6 +102,7 @@ impl ApplyPatchRuntime {
             sub_id: ctx.turn.sub_id.clone(),
             call_id: ctx.call_id.clone(),
             tx_event: ctx.session.get_tx_event(),
+            output_cap: crate::exec::ExecOutputCap::from_config(&ctx.turn.config),
         })
     }
 }
@@ -183,6 +184,10 @@ impl Approvable<ApplyPatchRequest> for ApplyPatchRuntime {
 }
 
 impl ToolRuntime<ApplyPatchRequest, ExecToolCallOutput> for ApplyPatchRuntime {
+    fn exit_code(&self, out: &ExecToolCallOutput) -> Option<i32> {
+        Some(out.exit_code)
+    }
+
     async fn run(
         &mut self,
         req: &ApplyPatchRequest,
@@ -112,6 +112,7 @@ impl ShellRuntime {
             sub_id: ctx.turn.sub_id.clone(),
             call_id: ctx.call_id.clone(),
             tx_event: ctx.session.get_tx_event(),
+            output_cap: crate::exec::ExecOutputCap::from_config(&ctx.turn.config),
         })
     }
 }
@@ -218,6 +219,10 @@ impl ToolRuntime<ShellRequest, ExecToolCallOutput> for ShellRuntime {
         })
     }
 
+    fn exit_code(&self, out: &ExecToolCallOutput) -> Option<i32> {
+        Some(out.exit_code)
+    }
+
     async fn run(
         &mut self,
         req: &ShellRequest,
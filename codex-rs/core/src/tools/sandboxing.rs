@@ -6,6 +6,7 @@
 
 use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::config::types::SandboxPathRule;
 use crate::error::CodexErr;
 use crate::protocol::SandboxPolicy;
 use crate::sandboxing::CommandSpec;
@@ -309,6 +310,12 @@ pub(crate) trait ToolRuntime<Req, Out>: Approvable<Req> + Sandboxable {
         None
     }
 
+    /// Exit code of a finished run, recorded on its `sandbox_attempt` span.
+    /// `None` for runtimes whose output outlives the attempt.
+    fn exit_code(&self, _out: &Out) -> Option<i32> {
+        None
+    }
+
     async fn run(
         &mut self,
         req: &Req,
@@ -328,6 +335,8 @@ pub(crate) struct SandboxAttempt<'a> {
     pub codex_linux_sandbox_exe: Option<&'a std::path::PathBuf>,
     pub use_linux_sandbox_bwrap: bool,
     pub windows_sandbox_level: codex_protocol::config_types::WindowsSandboxLevel,
+    pub backend: Option<&'a dyn crate::sandboxing::backend::SandboxBackend>,
+    pub sandbox_path_rules: &'a [SandboxPathRule],
 }
 
 impl<'a> SandboxAttempt<'a> {
@@ -351,6 +360,8 @@ impl<'a> SandboxAttempt<'a> {
                 codex_linux_sandbox_exe: self.codex_linux_sandbox_exe,
                 use_linux_sandbox_bwrap: self.use_linux_sandbox_bwrap,
                 windows_sandbox_level: self.windows_sandbox_level,
+                backend: self.backend,
+                sandbox_path_rules: self.sandbox_path_rules,
             })
     }
 }
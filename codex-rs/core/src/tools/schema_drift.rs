@@ -0,0 +1,157 @@
+//! Detects differences between the MCP tool schemas a [`ToolRegistry`] was
+//! built with and the schemas a server currently advertises.
+//!
+//! [`ToolRegistry`]: crate::tools::registry::ToolRegistry
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use serde_json::Value as JsonValue;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SchemaDrift {
+    /// The server accepts a parameter the registered spec does not advertise.
+    NewParameter { tool: String, parameter: String },
+    /// The registered spec advertises a parameter the server no longer accepts.
+    RemovedParameter { tool: String, parameter: String },
+    ChangedType {
+        tool: String,
+        parameter: String,
+        registered: String,
+        live: String,
+    },
+}
+
+impl fmt::Display for SchemaDrift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaDrift::NewParameter { tool, parameter } => {
+                write!(f, "{tool}: server added parameter `{parameter}`")
+            }
+            SchemaDrift::RemovedParameter { tool, parameter } => {
+                write!(f, "{tool}: server removed parameter `{parameter}`")
+            }
+            SchemaDrift::ChangedType {
+                tool,
+                parameter,
+                registered,
+                live,
+            } => write!(
+                f,
+                "{tool}: parameter `{parameter}` changed type from {registered} to {live}"
+            ),
+        }
+    }
+}
+
+/// Diffs the top-level `properties` of two tool input schemas.
+pub(crate) fn diff_input_schemas(
+    tool: &str,
+    registered: &JsonValue,
+    live: &JsonValue,
+) -> Vec<SchemaDrift> {
+    let registered_properties = properties(registered);
+    let live_properties = properties(live);
+    let names: BTreeSet<&String> = registered_properties
+        .iter()
+        .chain(live_properties.iter())
+        .flat_map(|properties| properties.keys())
+        .collect();
+
+    let mut drift = Vec::new();
+    for name in names {
+        let registered_property = registered_properties.and_then(|p| p.get(name));
+        let live_property = live_properties.and_then(|p| p.get(name));
+        match (registered_property, live_property) {
+            (None, Some(_)) => drift.push(SchemaDrift::NewParameter {
+                tool: tool.to_string(),
+                parameter: name.clone(),
+            }),
+            (Some(_), None) => drift.push(SchemaDrift::RemovedParameter {
+                tool: tool.to_string(),
+                parameter: name.clone(),
+            }),
+            (Some(registered_property), Some(live_property)) => {
+                let registered_type = property_type(registered_property);
+                let live_type = property_type(live_property);
+                if registered_type != live_type {
+                    drift.push(SchemaDrift::ChangedType {
+                        tool: tool.to_string(),
+                        parameter: name.clone(),
+                        registered: registered_type,
+                        live: live_type,
+                    });
+                }
+            }
+            (None, None) => {}
+        }
+    }
+    drift
+}
+
+fn properties(schema: &JsonValue) -> Option<&serde_json::Map<String, JsonValue>> {
+    schema.get("properties").and_then(JsonValue::as_object)
+}
+
+fn property_type(property: &JsonValue) -> String {
+    property
+        .get("type")
+        .map_or_else(|| "unspecified".to_string(), JsonValue::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn identical_schemas_have_no_drift() {
+        let schema = json!({
+            "type": "object",
+            "properties": { "path": { "type": "string" } },
+        });
+
+        assert_eq!(diff_input_schemas("read", &schema, &schema), Vec::new());
+    }
+
+    #[test]
+    fn reports_added_removed_and_retyped_parameters() {
+        let registered = json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string" },
+                "limit": { "type": "integer" },
+                "recursive": { "type": "boolean" },
+            },
+        });
+        let live = json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string" },
+                "limit": { "type": "string" },
+                "encoding": { "type": "string" },
+            },
+        });
+
+        assert_eq!(
+            diff_input_schemas("read", &registered, &live),
+            vec![
+                SchemaDrift::NewParameter {
+                    tool: "read".to_string(),
+                    parameter: "encoding".to_string(),
+                },
+                SchemaDrift::ChangedType {
+                    tool: "read".to_string(),
+                    parameter: "limit".to_string(),
+                    registered: "\"integer\"".to_string(),
+                    live: "\"string\"".to_string(),
+                },
+                SchemaDrift::RemovedParameter {
+                    tool: "read".to_string(),
+                    parameter: "recursive".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -3,21 +3,34 @@ use crate::client_common::tools::FreeformToolFormat;
 use crate::client_common::tools::ResponsesApiTool;
 use crate::client_common::tools::ToolSpec;
 use crate::config::AgentRoleConfig;
+use crate::config::types::ToolOverride;
 use crate::features::Feature;
 use crate::features::Features;
+use crate::mcp::split_qualified_tool_name;
 use crate::mcp_connection_manager::ToolInfo;
 use crate::models_manager::collaboration_mode_presets::CollaborationModesConfig;
+use crate::tools::handlers::CODE_SEARCH_TOOL_NAME;
+use crate::tools::handlers::MEMORY_READ_TOOL_NAME;
+use crate::tools::handlers::MEMORY_WRITE_TOOL_NAME;
 use crate::tools::handlers::PLAN_TOOL;
 use crate::tools::handlers::SEARCH_TOOL_BM25_DEFAULT_LIMIT;
 use crate::tools::handlers::SEARCH_TOOL_BM25_TOOL_NAME;
+use crate::tools::handlers::STR_REPLACE_EDIT_TOOL_NAME;
+use crate::tools::handlers::WATCH_FILES_TOOL_NAME;
 use crate::tools::handlers::agent_jobs::BatchJobHandler;
 use crate::tools::handlers::apply_patch::create_apply_patch_freeform_tool;
 use crate::tools::handlers::apply_patch::create_apply_patch_json_tool;
+use crate::tools::handlers::create_str_replace_edit_tool;
 use crate::tools::handlers::multi_agents::DEFAULT_WAIT_TIMEOUT_MS;
 use crate::tools::handlers::multi_agents::MAX_WAIT_TIMEOUT_MS;
 use crate::tools::handlers::multi_agents::MIN_WAIT_TIMEOUT_MS;
 use crate::tools::handlers::request_user_input_tool_description;
+use crate::tools::image_output::IMAGE_OUTPUT_DIR_ENV_VAR;
+use crate::tools::overrides::apply_tool_overrides;
+use crate::tools::registry::ConfiguredToolSpec;
 use crate::tools::registry::ToolRegistryBuilder;
+use crate::workspace_roots::WorkspaceRoot;
+use crate::workspace_roots::root_relative_path_note;
 use codex_protocol::config_types::WebSearchConfig;
 use codex_protocol::config_types::WebSearchMode;
 use codex_protocol::dynamic_tools::DynamicToolSpec;
@@ -35,10 +48,17 @@ use serde_json::Value as JsonValue;
 use serde_json::json;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
 
+/// Cap on concurrent tool calls when the CPU count is unavailable.
+const DEFAULT_MAX_PARALLEL_TOOL_CALLS: usize = 4;
 const SEARCH_TOOL_BM25_DESCRIPTION_TEMPLATE: &str =
     include_str!("../../templates/search_tool/tool_description.md");
 const WEB_SEARCH_CONTENT_TYPES: [&str; 2] = ["text", "image"];
+/// Names the shell handler answers to. Only one of them is advertised, which
+/// one depends on the shell tool type.
+pub(crate) const SHELL_TOOL_ALIASES: [&str; 3] = ["shell", "container.exec", "local_shell"];
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum ShellCommandBackendConfig {
     Classic,
@@ -51,6 +71,32 @@ pub enum UnifiedExecBackendConfig {
     ZshFork,
 }
 
+/// Which tools a locked-down deployment exposes to the model. An entry names
+/// a tool, either by its advertised name or, for MCP tools, by its
+/// fully-qualified name; an entry may also name an MCP server, matching every
+/// tool of that server.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolFilter {
+    /// When set, only matching tools are exposed.
+    pub allow: Option<Vec<String>>,
+    /// Matching tools are never exposed, even when allowed.
+    pub deny: Vec<String>,
+}
+
+impl ToolFilter {
+    /// Whether `tool_name`, served by the MCP server `mcp_server` if any,
+    /// passes the filter.
+    pub fn permits(&self, tool_name: &str, mcp_server: Option<&str>) -> bool {
+        let matches = |entry: &String| entry == tool_name || mcp_server == Some(entry.as_str());
+        if self.deny.iter().any(matches) {
+            return false;
+        }
+        self.allow
+            .as_ref()
+            .is_none_or(|allow| allow.iter().any(matches))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct ToolsConfig {
     pub shell_type: ConfigShellToolType,
@@ -74,6 +120,44 @@ pub(crate) struct ToolsConfig {
     pub experimental_supported_tools: Vec<String>,
     pub agent_jobs_tools: bool,
     pub agent_jobs_worker_tools: bool,
+    /// Register `list_background_jobs` and `kill_background_job` alongside
+    /// unified exec.
+    pub background_jobs_tools: bool,
+    pub web_fetch_tool: bool,
+    pub code_search_tool: bool,
+    pub watch_files_tool: bool,
+    pub project_memory_tools: bool,
+    /// Tell the model about `CODEX_IMAGE_OUTPUT_DIR`, whose images the
+    /// `shell` and `shell_command` tools attach to their output.
+    pub shell_image_output: bool,
+    /// Calls whose arguments exceed this many bytes are rejected before
+    /// dispatch.
+    pub max_argument_size_bytes: Option<usize>,
+    /// Check function call arguments against the tool's input schema before
+    /// dispatch, so the model learns which field is missing or mistyped.
+    /// MCP and freeform calls are never checked. Off by default.
+    pub schema_validation: bool,
+    /// Names the shell handler answers to in addition to
+    /// [`SHELL_TOOL_ALIASES`], for models that call the shell tool by
+    /// another name.
+    pub extra_shell_tool_aliases: Vec<String>,
+    /// Upper bound on how long each named tool may run, keyed by tool name.
+    /// Tools without an entry run unbounded.
+    pub tool_timeouts: HashMap<String, Duration>,
+    /// Tool each named tool falls back to, with the same arguments, when it
+    /// fails.
+    pub tool_fallbacks: HashMap<String, String>,
+    /// How many tool calls may run at once; further calls queue until one
+    /// finishes. Defaults to the number of CPUs.
+    pub max_parallel_tool_calls: usize,
+    /// Tools left out of the router's specs and rejected on dispatch.
+    pub tool_filter: ToolFilter,
+    /// Renames, descriptions and constraints for built-in tools, keyed by
+    /// built-in name. See [`crate::tools::overrides`].
+    pub tool_overrides: BTreeMap<String, ToolOverride>,
+    /// Workspace roots besides the cwd. The path parameters of the shell and
+    /// search tools tell the model how to address them.
+    pub workspace_roots: Vec<WorkspaceRoot>,
 }
 
 pub(crate) struct ToolsConfigParams<'a> {
@@ -105,6 +189,13 @@ impl ToolsConfig {
         let include_image_gen_tool =
             features.enabled(Feature::ImageGeneration) && supports_image_generation(model_info);
         let include_agent_jobs = include_collab_tools;
+        let include_background_jobs = features.enabled(Feature::BackgroundJobs);
+        let include_web_fetch = features.enabled(Feature::WebFetch);
+        let include_code_search = features.enabled(Feature::CodeSearch);
+        let include_watch_files = features.enabled(Feature::WatchFiles);
+        let include_project_memory = features.enabled(Feature::ProjectMemory);
+        let include_shell_image_output = features.enabled(Feature::ShellImageOutput)
+            && model_info.input_modalities.contains(&InputModality::Image);
         let request_permission_enabled = features.enabled(Feature::RequestPermissions);
         let shell_command_backend =
             if features.enabled(Feature::ShellTool) && features.enabled(Feature::ShellZshFork) {
@@ -137,6 +228,7 @@ impl ToolsConfig {
         let apply_patch_tool_type = match model_info.apply_patch_tool_type {
             Some(ApplyPatchToolType::Freeform) => Some(ApplyPatchToolType::Freeform),
             Some(ApplyPatchToolType::Function) => Some(ApplyPatchToolType::Function),
+            Some(ApplyPatchToolType::StrReplace) => Some(ApplyPatchToolType::StrReplace),
             None => {
                 if include_apply_patch_tool {
                     Some(ApplyPatchToolType::Freeform)
@@ -153,7 +245,7 @@ impl ToolsConfig {
                     if label.starts_with("agent_job:")
             );
 
-        Self {
+        let mut config = Self {
             shell_type,
             shell_command_backend,
             unified_exec_backend,
@@ -175,7 +267,28 @@ impl ToolsConfig {
             experimental_supported_tools: model_info.experimental_supported_tools.clone(),
             agent_jobs_tools: include_agent_jobs,
             agent_jobs_worker_tools,
+            background_jobs_tools: include_background_jobs,
+            web_fetch_tool: include_web_fetch,
+            code_search_tool: include_code_search,
+            watch_files_tool: include_watch_files,
+            project_memory_tools: include_project_memory,
+            shell_image_output: include_shell_image_output,
+            max_argument_size_bytes: None,
+            schema_validation: false,
+            extra_shell_tool_aliases: Vec::new(),
+            tool_timeouts: HashMap::new(),
+            tool_fallbacks: HashMap::new(),
+            max_parallel_tool_calls: std::thread::available_parallelism()
+                .map(std::num::NonZeroUsize::get)
+                .unwrap_or(DEFAULT_MAX_PARALLEL_TOOL_CALLS),
+            tool_filter: ToolFilter::default(),
+            tool_overrides: BTreeMap::new(),
+            workspace_roots: Vec::new(),
+        };
+        if !model_info.supports_tool_calls {
+            config.restrict_to_function_tools();
         }
+        config
     }
 
     pub fn with_agent_roles(mut self, agent_roles: BTreeMap<String, AgentRoleConfig>) -> Self {
@@ -192,6 +305,121 @@ impl ToolsConfig {
         self.web_search_config = web_search_config;
         self
     }
+
+    pub fn with_max_argument_size_bytes(mut self, max_argument_size_bytes: Option<usize>) -> Self {
+        self.max_argument_size_bytes = max_argument_size_bytes;
+        self
+    }
+
+    pub fn with_schema_validation(mut self, schema_validation: bool) -> Self {
+        self.schema_validation = schema_validation;
+        self
+    }
+
+    pub fn with_extra_shell_tool_aliases(mut self, aliases: Vec<String>) -> Self {
+        self.extra_shell_tool_aliases = aliases;
+        self
+    }
+
+    pub fn with_tool_timeouts(mut self, tool_timeouts: HashMap<String, Duration>) -> Self {
+        self.tool_timeouts = tool_timeouts;
+        self
+    }
+
+    pub fn with_tool_fallbacks(mut self, tool_fallbacks: HashMap<String, String>) -> Self {
+        self.tool_fallbacks = tool_fallbacks;
+        self
+    }
+
+    pub fn with_tool_filter(mut self, tool_filter: ToolFilter) -> Self {
+        self.tool_filter = tool_filter;
+        self
+    }
+
+    pub fn with_tool_overrides(mut self, tool_overrides: BTreeMap<String, ToolOverride>) -> Self {
+        self.tool_overrides = tool_overrides;
+        self
+    }
+
+    pub fn with_workspace_roots(mut self, workspace_roots: Vec<WorkspaceRoot>) -> Self {
+        self.workspace_roots = workspace_roots;
+        self
+    }
+
+    /// Values below 1 are raised to 1.
+    pub fn with_max_parallel_tool_calls(mut self, max_parallel_tool_calls: usize) -> Self {
+        self.max_parallel_tool_calls = max_parallel_tool_calls.max(1);
+        self
+    }
+
+    /// Like [`ToolsConfig::with_max_parallel_tool_calls`], keeping the
+    /// default when `max_parallel_tool_calls` is `None`.
+    pub fn with_configured_max_parallel_tool_calls(
+        self,
+        max_parallel_tool_calls: Option<usize>,
+    ) -> Self {
+        match max_parallel_tool_calls {
+            Some(max_parallel_tool_calls) => {
+                self.with_max_parallel_tool_calls(max_parallel_tool_calls)
+            }
+            None => self,
+        }
+    }
+
+    /// Copy of this config for `model_info`, with every tool the model cannot
+    /// use turned off. Tools are only ever turned off, never on:
+    ///
+    /// - `image_generation` needs a model that accepts image input.
+    /// - Shell image output needs a model that accepts image input.
+    /// - Image results from web search need a model whose search tool
+    ///   returns them.
+    /// - Experimental tools need a model that lists them as supported.
+    /// - Without native tool calls, tools switch to their function form or
+    ///   are turned off.
+    pub fn derive_for_model(&self, model_info: &ModelInfo) -> ToolsConfig {
+        let mut config = self.clone();
+        config.image_gen_tool &= supports_image_generation(model_info);
+        config.shell_image_output &= model_info.input_modalities.contains(&InputModality::Image);
+        if model_info.web_search_tool_type == WebSearchToolType::Text {
+            config.web_search_tool_type = WebSearchToolType::Text;
+        }
+        config
+            .experimental_supported_tools
+            .retain(|tool| model_info.experimental_supported_tools.contains(tool));
+        if !model_info.supports_tool_calls {
+            config.restrict_to_function_tools();
+        }
+        config
+    }
+
+    /// A model without native tool calls can only reach function tools, which
+    /// are described in its instructions instead (see
+    /// [`crate::tools::emulation`]). Shell and `apply_patch` switch to their
+    /// function forms; hosted and freeform tools are turned off.
+    fn restrict_to_function_tools(&mut self) {
+        if self.shell_type == ConfigShellToolType::Local {
+            self.shell_type = ConfigShellToolType::ShellCommand;
+        }
+        if self.apply_patch_tool_type == Some(ApplyPatchToolType::Freeform) {
+            self.apply_patch_tool_type = Some(ApplyPatchToolType::Function);
+        }
+        self.web_search_mode = Some(WebSearchMode::Disabled);
+        self.image_gen_tool = false;
+        self.js_repl_enabled = false;
+        self.js_repl_tools_only = false;
+    }
+
+    /// [`SHELL_TOOL_ALIASES`] followed by the extra aliases, without
+    /// duplicates.
+    pub(crate) fn shell_tool_aliases(&self) -> Vec<String> {
+        let mut aliases: Vec<String> = SHELL_TOOL_ALIASES.map(str::to_string).to_vec();
+        for alias in &self.extra_shell_tool_aliases {
+            if !aliases.contains(alias) {
+                aliases.push(alias.clone());
+            }
+        }
+        aliases
+    }
 }
 
 fn supports_image_generation(model_info: &ModelInfo) -> bool {
@@ -428,7 +656,7 @@ fn create_exec_command_tool(allow_login_shell: bool, request_permission_enabled:
             "tty".to_string(),
             JsonSchema::Boolean {
                 description: Some(
-                    "Whether to allocate a TTY for the command. Defaults to false (plain pipes); set to true to open a PTY and access TTY process."
+                    "Whether to allocate a TTY for the command. Defaults to false (plain pipes); set to true to open a PTY for programs that need a terminal, such as REPLs, `git rebase -i` or password prompts. The user can type into a TTY process while it runs."
                         .to_string(),
                 ),
             }
@@ -524,6 +752,228 @@ fn create_write_stdin_tool() -> ToolSpec {
     })
 }
 
+fn create_list_background_jobs_tool() -> ToolSpec {
+    ToolSpec::Function(ResponsesApiTool {
+        name: "list_background_jobs".to_string(),
+        description: "Lists unified exec sessions that are still running, such as dev servers or watch builds started in earlier turns. Poll a session's output with write_stdin and empty chars."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties: BTreeMap::new(),
+            required: None,
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_kill_background_job_tool() -> ToolSpec {
+    let properties = BTreeMap::from([(
+        "session_id".to_string(),
+        JsonSchema::Number {
+            description: Some("Identifier of the unified exec session to terminate.".to_string()),
+        },
+    )]);
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "kill_background_job".to_string(),
+        description: "Terminates a running unified exec session and its child processes."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["session_id".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_web_fetch_tool() -> ToolSpec {
+    let properties = BTreeMap::from([(
+        "url".to_string(),
+        JsonSchema::String {
+            description: Some("Absolute http or https URL of the page to fetch.".to_string()),
+        },
+    )]);
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "web_fetch".to_string(),
+        description: "Fetches a web page and returns its main content converted to markdown. Long pages are truncated."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["url".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_code_search_tool() -> ToolSpec {
+    let properties = BTreeMap::from([
+        (
+            "query".to_string(),
+            JsonSchema::String {
+                description: Some(
+                    "Natural-language description of the code to find, e.g. \"where retries are scheduled\"."
+                        .to_string(),
+                ),
+            },
+        ),
+        (
+            "limit".to_string(),
+            JsonSchema::Number {
+                description: Some("Maximum number of results to return (default 8).".to_string()),
+            },
+        ),
+    ]);
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: CODE_SEARCH_TOOL_NAME.to_string(),
+        description: "Searches the workspace by meaning rather than exact text, using an embedding index of files not excluded by .gitignore. Returns the best-matching line ranges with a snippet of each. Use grep for exact identifiers."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["query".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_watch_files_tool() -> ToolSpec {
+    let properties = BTreeMap::from([
+        (
+            "patterns".to_string(),
+            JsonSchema::Array {
+                items: Box::new(JsonSchema::String { description: None }),
+                description: Some(
+                    "Gitignore-style globs relative to the working directory, e.g. [\"src/**/*.rs\", \"package.json\"]. Prefix a glob with ! to exclude matches."
+                        .to_string(),
+                ),
+            },
+        ),
+        (
+            "stop".to_string(),
+            JsonSchema::Boolean {
+                description: Some(
+                    "Stop watching the given patterns instead, or every pattern when none are given."
+                        .to_string(),
+                ),
+            },
+        ),
+    ]);
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: WATCH_FILES_TOOL_NAME.to_string(),
+        description: "Watches files for changes made by the user or other processes, such as a build regenerating outputs or the user editing alongside you. At the start of each later turn, watched files created, modified or deleted since the previous turn are reported as a watch_files result. Edits you make with patches are not reported; edits made by your own shell commands are. Patterns add to those already watched."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: None,
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_memory_write_tool() -> ToolSpec {
+    let properties = BTreeMap::from([
+        (
+            "content".to_string(),
+            JsonSchema::String {
+                description: Some(
+                    "The fact to remember, self-contained and at most 2000 characters, e.g. \"Integration tests need `just db-up` first.\""
+                        .to_string(),
+                ),
+            },
+        ),
+        (
+            "tags".to_string(),
+            JsonSchema::Array {
+                items: Box::new(JsonSchema::String { description: None }),
+                description: Some("Short keywords that help find the fact later.".to_string()),
+            },
+        ),
+        (
+            "importance".to_string(),
+            JsonSchema::String {
+                description: Some(
+                    "One of \"low\", \"normal\" (default) or \"high\". High-importance facts are shown first at the start of new sessions; low-importance ones only through memory_read."
+                        .to_string(),
+                ),
+            },
+        ),
+        (
+            "ttl_days".to_string(),
+            JsonSchema::Number {
+                description: Some(
+                    "Days until the fact expires, from 1 to 365 (default 90). Writing the fact again renews it."
+                        .to_string(),
+                ),
+            },
+        ),
+        (
+            "id".to_string(),
+            JsonSchema::String {
+                description: Some(
+                    "Id of an existing fact to replace or forget instead of saving a new one."
+                        .to_string(),
+                ),
+            },
+        ),
+        (
+            "forget".to_string(),
+            JsonSchema::Boolean {
+                description: Some("Remove the fact with the given id.".to_string()),
+            },
+        ),
+    ]);
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: MEMORY_WRITE_TOOL_NAME.to_string(),
+        description: "Saves a fact about this project for future sessions, such as a build quirk, a convention the user asked for, or where something lives. Facts are kept per repository and the most relevant ones are shown at the start of later sessions. Save durable, verified facts rather than progress notes, and update or forget facts that turn out to be wrong."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: None,
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_memory_read_tool() -> ToolSpec {
+    let properties = BTreeMap::from([
+        (
+            "query".to_string(),
+            JsonSchema::String {
+                description: Some(
+                    "Words to look for in saved facts and their tags. Omit to list the most relevant facts."
+                        .to_string(),
+                ),
+            },
+        ),
+        (
+            "limit".to_string(),
+            JsonSchema::Number {
+                description: Some("Maximum number of facts to return (default 10, at most 50).".to_string()),
+            },
+        ),
+    ]);
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: MEMORY_READ_TOOL_NAME.to_string(),
+        description: "Searches the facts saved with memory_write for this project in this or earlier sessions. Each result shows the fact's id, which memory_write accepts to update or forget it."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: None,
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
 fn create_shell_tool(request_permission_enabled: bool) -> ToolSpec {
     let mut properties = BTreeMap::from([
         (
@@ -747,6 +1197,34 @@ fn create_spawn_agent_tool(config: &ToolsConfig) -> ToolSpec {
                 ),
             },
         ),
+        (
+            "cwd".to_string(),
+            JsonSchema::String {
+                description: Some(
+                    "Directory the new agent works in. It must be inside your working directory or a workspace root, and relative paths are resolved like other tool paths. The agent can only write inside it."
+                        .to_string(),
+                ),
+            },
+        ),
+        (
+            "max_tokens".to_string(),
+            JsonSchema::Number {
+                description: Some(
+                    "Most tokens the new agent may spend on the task. It stops taking new turns once they are spent."
+                        .to_string(),
+                ),
+            },
+        ),
+        (
+            "tools".to_string(),
+            JsonSchema::Array {
+                items: Box::new(JsonSchema::String { description: None }),
+                description: Some(
+                    "Names of the tools, or MCP servers, the new agent may use. Omit to give it the same tools as you."
+                        .to_string(),
+                ),
+            },
+        ),
     ]);
 
     ToolSpec::Function(ResponsesApiTool {
@@ -1669,7 +2147,7 @@ pub(crate) fn mcp_tool_to_openai_tool(
     })
 }
 
-fn dynamic_tool_to_openai_tool(
+pub(crate) fn dynamic_tool_to_openai_tool(
     tool: &DynamicToolSpec,
 ) -> Result<ResponsesApiTool, serde_json::Error> {
     let input_schema = parse_tool_input_schema(&tool.input_schema)?;
@@ -1800,15 +2278,49 @@ fn sanitize_json_schema(value: &mut JsonValue) {
     }
 }
 
-/// Builds the tool registry builder while collecting tool specs for later serialization.
-pub(crate) fn build_specs(
-    config: &ToolsConfig,
-    mcp_tools: Option<HashMap<String, rmcp::model::Tool>>,
-    app_tools: Option<HashMap<String, ToolInfo>>,
-    dynamic_tools: &[DynamicToolSpec],
+/// Appends how to address the other workspace roots to the description of
+/// the string parameter `param` of a function tool.
+fn with_root_relative_paths(
+    mut spec: ToolSpec,
+    param: &str,
+    workspace_roots: &[WorkspaceRoot],
+) -> ToolSpec {
+    let Some(note) = root_relative_path_note(workspace_roots) else {
+        return spec;
+    };
+    if let ToolSpec::Function(ResponsesApiTool {
+        parameters: JsonSchema::Object { properties, .. },
+        ..
+    }) = &mut spec
+        && let Some(JsonSchema::String { description }) = properties.get_mut(param)
+    {
+        *description = Some(match description.take() {
+            Some(description) => format!("{description} {note}"),
+            None => note,
+        });
+    }
+    spec
+}
+
+fn with_image_output_note(mut spec: ToolSpec, enabled: bool) -> ToolSpec {
+    if enabled && let ToolSpec::Function(ResponsesApiTool { description, .. }) = &mut spec {
+        description.push_str(&format!(
+            "\n- Image files the command writes to the directory in ${IMAGE_OUTPUT_DIR_ENV_VAR} (e.g. a screenshot from a headless browser) are shown to you with its output."
+        ));
+    }
+    spec
+}
+
+/// Builds the tool registry builder while collecting tool specs for later serialization.
+pub(crate) fn build_specs(
+    config: &ToolsConfig,
+    mcp_tools: Option<HashMap<String, rmcp::model::Tool>>,
+    app_tools: Option<HashMap<String, ToolInfo>>,
+    dynamic_tools: &[DynamicToolSpec],
 ) -> ToolRegistryBuilder {
     use crate::tools::handlers::ApplyPatchHandler;
     use crate::tools::handlers::ArtifactsHandler;
+    use crate::tools::handlers::CodeSearchHandler;
     use crate::tools::handlers::DynamicToolHandler;
     use crate::tools::handlers::GrepFilesHandler;
     use crate::tools::handlers::JsReplHandler;
@@ -1816,6 +2328,8 @@ pub(crate) fn build_specs(
     use crate::tools::handlers::ListDirHandler;
     use crate::tools::handlers::McpHandler;
     use crate::tools::handlers::McpResourceHandler;
+    use crate::tools::handlers::MemoryReadHandler;
+    use crate::tools::handlers::MemoryWriteHandler;
     use crate::tools::handlers::MultiAgentHandler;
     use crate::tools::handlers::PlanHandler;
     use crate::tools::handlers::ReadFileHandler;
@@ -1823,9 +2337,12 @@ pub(crate) fn build_specs(
     use crate::tools::handlers::SearchToolBm25Handler;
     use crate::tools::handlers::ShellCommandHandler;
     use crate::tools::handlers::ShellHandler;
+    use crate::tools::handlers::StrReplaceEditHandler;
     use crate::tools::handlers::TestSyncHandler;
     use crate::tools::handlers::UnifiedExecHandler;
     use crate::tools::handlers::ViewImageHandler;
+    use crate::tools::handlers::WatchFilesHandler;
+    use crate::tools::handlers::WebFetchHandler;
     use std::sync::Arc;
 
     let mut builder = ToolRegistryBuilder::new();
@@ -1851,7 +2368,14 @@ pub(crate) fn build_specs(
     match &config.shell_type {
         ConfigShellToolType::Default => {
             builder.push_spec_with_parallel_support(
-                create_shell_tool(request_permission_enabled),
+                with_image_output_note(
+                    with_root_relative_paths(
+                        create_shell_tool(request_permission_enabled),
+                        "workdir",
+                        &config.workspace_roots,
+                    ),
+                    config.shell_image_output,
+                ),
                 true,
             );
         }
@@ -1860,11 +2384,21 @@ pub(crate) fn build_specs(
         }
         ConfigShellToolType::UnifiedExec => {
             builder.push_spec_with_parallel_support(
-                create_exec_command_tool(config.allow_login_shell, request_permission_enabled),
+                with_root_relative_paths(
+                    create_exec_command_tool(config.allow_login_shell, request_permission_enabled),
+                    "workdir",
+                    &config.workspace_roots,
+                ),
                 true,
             );
             builder.push_spec(create_write_stdin_tool());
             builder.register_handler("exec_command", unified_exec_handler.clone());
+            if config.background_jobs_tools {
+                builder.push_spec(create_list_background_jobs_tool());
+                builder.push_spec(create_kill_background_job_tool());
+                builder.register_handler("list_background_jobs", unified_exec_handler.clone());
+                builder.register_handler("kill_background_job", unified_exec_handler.clone());
+            }
             builder.register_handler("write_stdin", unified_exec_handler);
         }
         ConfigShellToolType::Disabled => {
@@ -1872,7 +2406,17 @@ pub(crate) fn build_specs(
         }
         ConfigShellToolType::ShellCommand => {
             builder.push_spec_with_parallel_support(
-                create_shell_command_tool(config.allow_login_shell, request_permission_enabled),
+                with_image_output_note(
+                    with_root_relative_paths(
+                        create_shell_command_tool(
+                            config.allow_login_shell,
+                            request_permission_enabled,
+                        ),
+                        "workdir",
+                        &config.workspace_roots,
+                    ),
+                    config.shell_image_output,
+                ),
                 true,
             );
         }
@@ -1880,9 +2424,9 @@ pub(crate) fn build_specs(
 
     if config.shell_type != ConfigShellToolType::Disabled {
         // Always register shell aliases so older prompts remain compatible.
-        builder.register_handler("shell", shell_handler.clone());
-        builder.register_handler("container.exec", shell_handler.clone());
-        builder.register_handler("local_shell", shell_handler);
+        for alias in config.shell_tool_aliases() {
+            builder.register_handler(alias, shell_handler.clone());
+        }
         builder.register_handler("shell_command", shell_command_handler);
     }
 
@@ -1923,12 +2467,18 @@ pub(crate) fn build_specs(
         match apply_patch_tool_type {
             ApplyPatchToolType::Freeform => {
                 builder.push_spec(create_apply_patch_freeform_tool());
+                builder.register_handler("apply_patch", apply_patch_handler);
             }
             ApplyPatchToolType::Function => {
                 builder.push_spec(create_apply_patch_json_tool());
+                builder.register_handler("apply_patch", apply_patch_handler);
+            }
+            ApplyPatchToolType::StrReplace => {
+                builder.push_spec(create_str_replace_edit_tool());
+                builder
+                    .register_handler(STR_REPLACE_EDIT_TOOL_NAME, Arc::new(StrReplaceEditHandler));
             }
         }
-        builder.register_handler("apply_patch", apply_patch_handler);
     }
 
     if config
@@ -1936,7 +2486,10 @@ pub(crate) fn build_specs(
         .contains(&"grep_files".to_string())
     {
         let grep_files_handler = Arc::new(GrepFilesHandler);
-        builder.push_spec_with_parallel_support(create_grep_files_tool(), true);
+        builder.push_cacheable_spec_with_parallel_support(
+            with_root_relative_paths(create_grep_files_tool(), "path", &config.workspace_roots),
+            true,
+        );
         builder.register_handler("grep_files", grep_files_handler);
     }
 
@@ -1945,7 +2498,7 @@ pub(crate) fn build_specs(
         .contains(&"read_file".to_string())
     {
         let read_file_handler = Arc::new(ReadFileHandler);
-        builder.push_spec_with_parallel_support(create_read_file_tool(), true);
+        builder.push_cacheable_spec_with_parallel_support(create_read_file_tool(), true);
         builder.register_handler("read_file", read_file_handler);
     }
 
@@ -1955,7 +2508,7 @@ pub(crate) fn build_specs(
         .any(|tool| tool == "list_dir")
     {
         let list_dir_handler = Arc::new(ListDirHandler);
-        builder.push_spec_with_parallel_support(create_list_dir_tool(), true);
+        builder.push_cacheable_spec_with_parallel_support(create_list_dir_tool(), true);
         builder.register_handler("list_dir", list_dir_handler);
     }
 
@@ -2003,6 +2556,28 @@ pub(crate) fn build_specs(
         });
     }
 
+    if config.web_fetch_tool {
+        builder.push_cacheable_spec_with_parallel_support(create_web_fetch_tool(), true);
+        builder.register_handler("web_fetch", Arc::new(WebFetchHandler));
+    }
+
+    if config.code_search_tool {
+        builder.push_spec_with_parallel_support(create_code_search_tool(), true);
+        builder.register_handler(CODE_SEARCH_TOOL_NAME, Arc::new(CodeSearchHandler));
+    }
+
+    if config.watch_files_tool {
+        builder.push_spec(create_watch_files_tool());
+        builder.register_handler(WATCH_FILES_TOOL_NAME, Arc::new(WatchFilesHandler));
+    }
+
+    if config.project_memory_tools {
+        builder.push_spec(create_memory_write_tool());
+        builder.push_spec_with_parallel_support(create_memory_read_tool(), true);
+        builder.register_handler(MEMORY_WRITE_TOOL_NAME, Arc::new(MemoryWriteHandler));
+        builder.register_handler(MEMORY_READ_TOOL_NAME, Arc::new(MemoryReadHandler));
+    }
+
     if config.image_gen_tool {
         builder.push_spec(ToolSpec::ImageGeneration {
             output_format: "png".to_string(),
@@ -2041,14 +2616,22 @@ pub(crate) fn build_specs(
         }
     }
 
+    apply_tool_overrides(
+        &mut builder,
+        &config.tool_overrides,
+        &config.shell_tool_aliases(),
+    );
+
     if let Some(mcp_tools) = mcp_tools {
         let mut entries: Vec<(String, rmcp::model::Tool)> = mcp_tools.into_iter().collect();
         entries.sort_by(|a, b| a.0.cmp(&b.0));
 
         for (name, tool) in entries.into_iter() {
-            match mcp_tool_to_openai_tool(name.clone(), tool.clone()) {
+            let input_schema = JsonValue::Object(tool.input_schema.as_ref().clone());
+            match mcp_tool_to_openai_tool(name.clone(), tool) {
                 Ok(converted_tool) => {
                     builder.push_spec(ToolSpec::Function(converted_tool));
+                    builder.record_mcp_input_schema(name.clone(), input_schema);
                     builder.register_handler(name, mcp_handler.clone());
                 }
                 Err(e) => {
@@ -2063,6 +2646,7 @@ pub(crate) fn build_specs(
             match dynamic_tool_to_openai_tool(tool) {
                 Ok(converted_tool) => {
                     builder.push_spec(ToolSpec::Function(converted_tool));
+                    builder.record_dynamic_tool(tool.name.clone());
                     builder.register_handler(tool.name.clone(), dynamic_tool_handler.clone());
                 }
                 Err(e) => {
@@ -2078,6 +2662,76 @@ pub(crate) fn build_specs(
     builder
 }
 
+/// Tool names that resolve to more than one tool. See
+/// [`ToolRouter::try_from_config`].
+///
+/// [`ToolRouter::try_from_config`]: crate::tools::router::ToolRouter::try_from_config
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ToolNameCollisions {
+    /// Names given to more than one spec, e.g. a dynamic tool named like a
+    /// built-in. Only one of them can receive calls.
+    pub duplicate_names: Vec<String>,
+    /// MCP tool names exposed by more than one server, with those servers.
+    /// Each tool stays callable under its qualified name, but the model sees
+    /// several tools that differ only by server.
+    pub shared_mcp_tool_names: BTreeMap<String, Vec<String>>,
+}
+
+impl ToolNameCollisions {
+    /// `mcp_tool_names` are qualified, i.e. `mcp__{server}__{tool}`.
+    pub(crate) fn find(specs: &[ConfiguredToolSpec], mcp_tool_names: &[String]) -> Self {
+        let mut spec_counts = BTreeMap::<&str, usize>::new();
+        for configured in specs {
+            *spec_counts.entry(configured.spec.name()).or_default() += 1;
+        }
+        let duplicate_names = spec_counts
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|(name, _)| name.to_string())
+            .collect();
+
+        let mut servers_by_tool = BTreeMap::<String, Vec<String>>::new();
+        for qualified_name in mcp_tool_names {
+            if let Some((server, tool)) = split_qualified_tool_name(qualified_name) {
+                servers_by_tool.entry(tool).or_default().push(server);
+            }
+        }
+        servers_by_tool.retain(|_, servers| {
+            servers.sort();
+            servers.dedup();
+            servers.len() > 1
+        });
+
+        Self {
+            duplicate_names,
+            shared_mcp_tool_names: servers_by_tool,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.duplicate_names.is_empty() && self.shared_mcp_tool_names.is_empty()
+    }
+}
+
+impl fmt::Display for ToolNameCollisions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut collisions = self
+            .duplicate_names
+            .iter()
+            .map(|name| format!("`{name}` is defined more than once"))
+            .collect::<Vec<_>>();
+        collisions.extend(self.shared_mcp_tool_names.iter().map(|(tool, servers)| {
+            format!(
+                "MCP tool `{tool}` is exposed by servers {}",
+                servers.join(", ")
+            )
+        }));
+        write!(f, "tool name collisions: {}", collisions.join("; "))
+    }
+}
+
+impl std::error::Error for ToolNameCollisions {}
+
 #[cfg(test)]
 mod tests {
     use crate::client_common::tools::FreeformTool;
@@ -2089,6 +2743,7 @@ mod tests {
     use codex_protocol::openai_models::ModelInfo;
     use codex_protocol::openai_models::ModelsResponse;
     use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
 
     use super::*;
 
@@ -2356,6 +3011,278 @@ mod tests {
         assert_contains_tool_names(&tools, &["artifacts"]);
     }
 
+    #[test]
+    fn test_build_specs_background_jobs_tools_enabled() {
+        let config = test_config();
+        let model_info =
+            ModelsManager::construct_model_info_offline_for_tests("gpt-5-codex", &config);
+        let mut features = Features::with_defaults();
+        features.enable(Feature::UnifiedExec);
+        features.enable(Feature::BackgroundJobs);
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+            session_source: SessionSource::Cli,
+        });
+        let (tools, _) = build_specs(&tools_config, None, None, &[]).build();
+        assert_contains_tool_names(
+            &tools,
+            &[
+                "exec_command",
+                "list_background_jobs",
+                "kill_background_job",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_build_specs_web_fetch_tool_enabled() {
+        let config = test_config();
+        let model_info =
+            ModelsManager::construct_model_info_offline_for_tests("gpt-5-codex", &config);
+        let mut features = Features::with_defaults();
+        features.enable(Feature::WebFetch);
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+            session_source: SessionSource::Cli,
+        });
+        let (tools, _) = build_specs(&tools_config, None, None, &[]).build();
+        assert_contains_tool_names(&tools, &["web_fetch"]);
+        assert!(find_tool(&tools, "web_fetch").cacheable);
+    }
+
+    #[test]
+    fn test_build_specs_shell_tools_are_not_cacheable() {
+        let config = test_config();
+        let model_info =
+            ModelsManager::construct_model_info_offline_for_tests("gpt-5-codex", &config);
+        let features = Features::with_defaults();
+        let mut tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+            session_source: SessionSource::Cli,
+        });
+        for (shell_type, tool_name) in [
+            (ConfigShellToolType::Default, "shell"),
+            (ConfigShellToolType::ShellCommand, "shell_command"),
+        ] {
+            tools_config.shell_type = shell_type;
+            let (tools, _) = build_specs(&tools_config, None, None, &[]).build();
+            assert!(!find_tool(&tools, tool_name).cacheable, "{tool_name}");
+        }
+    }
+
+    #[test]
+    fn test_build_specs_describe_workspace_roots_on_workdir() {
+        let config = test_config();
+        let model_info =
+            ModelsManager::construct_model_info_offline_for_tests("gpt-5-codex", &config);
+        let features = Features::with_defaults();
+        let mut tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+            session_source: SessionSource::Cli,
+        })
+        .with_workspace_roots(vec![WorkspaceRoot {
+            name: "backend".to_string(),
+            path: PathBuf::from("/work/backend"),
+        }]);
+        tools_config.shell_type = ConfigShellToolType::ShellCommand;
+        let (tools, _) = build_specs(&tools_config, None, None, &[]).build();
+
+        let ToolSpec::Function(ResponsesApiTool {
+            parameters: JsonSchema::Object { properties, .. },
+            ..
+        }) = &find_tool(&tools, "shell_command").spec
+        else {
+            panic!("shell_command should be a function tool");
+        };
+        let Some(JsonSchema::String {
+            description: Some(description),
+        }) = properties.get("workdir")
+        else {
+            panic!("workdir should be a described string");
+        };
+        assert!(
+            description.ends_with("Roots: `@backend` (/work/backend)."),
+            "{description}"
+        );
+    }
+
+    #[test]
+    fn test_build_specs_code_search_tool_enabled() {
+        let config = test_config();
+        let model_info =
+            ModelsManager::construct_model_info_offline_for_tests("gpt-5-codex", &config);
+        let mut features = Features::with_defaults();
+        features.enable(Feature::CodeSearch);
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+            session_source: SessionSource::Cli,
+        });
+        let (tools, _) = build_specs(&tools_config, None, None, &[]).build();
+        assert_contains_tool_names(&tools, &[CODE_SEARCH_TOOL_NAME]);
+        assert!(!find_tool(&tools, CODE_SEARCH_TOOL_NAME).cacheable);
+    }
+
+    #[test]
+    fn test_build_specs_watch_files_tool_enabled() {
+        let config = test_config();
+        let model_info =
+            ModelsManager::construct_model_info_offline_for_tests("gpt-5-codex", &config);
+        let mut features = Features::with_defaults();
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+            session_source: SessionSource::Cli,
+        });
+        let (tools, _) = build_specs(&tools_config, None, None, &[]).build();
+        assert!(
+            !tools
+                .iter()
+                .any(|tool| tool_name(&tool.spec) == WATCH_FILES_TOOL_NAME)
+        );
+
+        features.enable(Feature::WatchFiles);
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+            session_source: SessionSource::Cli,
+        });
+        let (tools, _) = build_specs(&tools_config, None, None, &[]).build();
+        assert_contains_tool_names(&tools, &[WATCH_FILES_TOOL_NAME]);
+    }
+
+    #[test]
+    fn test_build_specs_project_memory_tools_enabled() {
+        let config = test_config();
+        let model_info =
+            ModelsManager::construct_model_info_offline_for_tests("gpt-5-codex", &config);
+        let mut features = Features::with_defaults();
+        features.enable(Feature::ProjectMemory);
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+            session_source: SessionSource::Cli,
+        });
+        let (tools, _) = build_specs(&tools_config, None, None, &[]).build();
+        assert_contains_tool_names(&tools, &[MEMORY_WRITE_TOOL_NAME, MEMORY_READ_TOOL_NAME]);
+    }
+
+    #[test]
+    fn models_without_native_tool_calls_only_get_function_tools() {
+        let config = test_config();
+        let mut model_info =
+            ModelsManager::construct_model_info_offline_for_tests("gpt-5-codex", &config);
+        model_info.supports_tool_calls = false;
+        model_info.shell_type = ConfigShellToolType::Local;
+        model_info.apply_patch_tool_type = Some(ApplyPatchToolType::Freeform);
+        let mut features = Features::with_defaults();
+        features.disable(Feature::UnifiedExec);
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Live),
+            session_source: SessionSource::Cli,
+        });
+
+        assert_eq!(tools_config.shell_type, ConfigShellToolType::ShellCommand);
+        assert_eq!(
+            tools_config.apply_patch_tool_type,
+            Some(ApplyPatchToolType::Function)
+        );
+        let (tools, _) = build_specs(&tools_config, None, None, &[]).build();
+        for tool in &tools {
+            assert!(
+                matches!(tool.spec, ToolSpec::Function(_)),
+                "unexpected non-function tool {}",
+                tool.spec.name()
+            );
+        }
+    }
+
+    #[test]
+    fn tool_overrides_rename_and_constrain_built_in_tools() {
+        let config = test_config();
+        let model_info =
+            ModelsManager::construct_model_info_offline_for_tests("gpt-5-codex", &config);
+        let features = Features::with_defaults();
+        let mut tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+            session_source: SessionSource::Cli,
+        })
+        .with_tool_overrides(BTreeMap::from([
+            (
+                "shell_command".to_string(),
+                ToolOverride {
+                    name: Some("run".to_string()),
+                    allowed_commands: Some(vec!["git".to_string()]),
+                    max_timeout_ms: Some(1_000),
+                    ..Default::default()
+                },
+            ),
+            (
+                "update_plan".to_string(),
+                ToolOverride {
+                    description: Some("Track your plan.".to_string()),
+                    ..Default::default()
+                },
+            ),
+        ]));
+        tools_config.shell_type = ConfigShellToolType::ShellCommand;
+        let (tools, registry) = build_specs(&tools_config, None, None, &[]).build();
+
+        assert!(
+            !tools
+                .iter()
+                .any(|tool| tool_name(&tool.spec) == "shell_command")
+        );
+        assert!(registry.handler("run").is_some());
+        assert!(registry.handler("shell_command").is_none());
+        let ToolSpec::Function(ResponsesApiTool {
+            description,
+            parameters: JsonSchema::Object { properties, .. },
+            ..
+        }) = &find_tool(&tools, "run").spec
+        else {
+            panic!("run should be a function tool");
+        };
+        assert!(
+            description
+                .ends_with("Only these programs may be run: git. Other commands are rejected."),
+            "{description}"
+        );
+        let Some(JsonSchema::Number {
+            description: Some(timeout_description),
+        }) = properties.get("timeout_ms")
+        else {
+            panic!("timeout_ms should be a described number");
+        };
+        assert!(
+            timeout_description.ends_with("At most 1000."),
+            "{timeout_description}"
+        );
+
+        let ToolSpec::Function(ResponsesApiTool { description, .. }) =
+            &find_tool(&tools, "update_plan").spec
+        else {
+            panic!("update_plan should be a function tool");
+        };
+        assert_eq!(description, "Track your plan.");
+    }
+
     #[test]
     fn test_build_specs_agent_job_worker_tools_enabled() {
         let config = test_config();
@@ -2545,6 +3472,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn derive_for_model_turns_off_tools_the_model_cannot_use() {
+        let config = test_config();
+        let mut capable_model_info =
+            ModelsManager::construct_model_info_offline_for_tests("gpt-5.2", &config);
+        capable_model_info.experimental_supported_tools =
+            vec!["read_file".to_string(), "list_dir".to_string()];
+        let mut text_only_model_info = capable_model_info.clone();
+        text_only_model_info.input_modalities = vec![InputModality::Text];
+        text_only_model_info.experimental_supported_tools = vec!["list_dir".to_string()];
+        let mut features = Features::with_defaults();
+        features.enable(Feature::ImageGeneration);
+        let base = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &capable_model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+            session_source: SessionSource::Cli,
+        });
+
+        let (capable_tools, _) =
+            build_specs(&base.derive_for_model(&capable_model_info), None, None, &[]).build();
+        let (text_only_tools, _) = build_specs(
+            &base.derive_for_model(&text_only_model_info),
+            None,
+            None,
+            &[],
+        )
+        .build();
+
+        assert_contains_tool_names(
+            &capable_tools,
+            &["image_generation", "read_file", "list_dir"],
+        );
+        assert_lacks_tool_name(&text_only_tools, "image_generation");
+        assert_lacks_tool_name(&text_only_tools, "read_file");
+        assert_contains_tool_names(&text_only_tools, &["list_dir"]);
+    }
+
     #[test]
     fn js_repl_freeform_grammar_blocks_common_non_js_prefixes() {
         let ToolSpec::Freeform(FreeformTool { format, .. }) = create_js_repl_tool() else {
@@ -3012,6 +3977,29 @@ mod tests {
         assert!(find_tool(&tools, "read_file").supports_parallel_tool_calls);
     }
 
+    #[test]
+    fn str_replace_models_get_the_edit_tool_instead_of_apply_patch() {
+        let mut model_info = model_info_from_models_json("gpt-5-codex");
+        model_info.apply_patch_tool_type = Some(ApplyPatchToolType::StrReplace);
+        let features = Features::with_defaults();
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+            session_source: SessionSource::Cli,
+        });
+        let (tools, registry) = build_specs(&tools_config, None, None, &[]).build();
+
+        assert_contains_tool_names(&tools, &[STR_REPLACE_EDIT_TOOL_NAME]);
+        assert!(
+            !tools
+                .iter()
+                .any(|tool| tool_name(&tool.spec) == "apply_patch")
+        );
+        assert!(registry.handler(STR_REPLACE_EDIT_TOOL_NAME).is_some());
+        assert!(registry.handler("apply_patch").is_none());
+    }
+
     #[test]
     fn test_test_model_info_includes_sync_tool() {
         let _config = test_config();
@@ -3557,6 +4545,23 @@ Examples of valid command strings:
         assert_eq!(description, &expected);
     }
 
+    #[test]
+    fn shell_image_output_is_described_only_when_enabled() {
+        let description = |enabled| {
+            let ToolSpec::Function(ResponsesApiTool { description, .. }) =
+                with_image_output_note(create_shell_command_tool(true, false), enabled)
+            else {
+                panic!("expected function tool");
+            };
+            description
+        };
+
+        assert!(!description(false).contains(IMAGE_OUTPUT_DIR_ENV_VAR));
+        assert!(description(true).ends_with(
+            "\n- Image files the command writes to the directory in $CODEX_IMAGE_OUTPUT_DIR (e.g. a screenshot from a headless browser) are shown to you with its output."
+        ));
+    }
+
     #[test]
     fn test_get_openai_tools_mcp_tools_with_additional_properties_schema() {
         let config = test_config();
@@ -3694,4 +4699,88 @@ Examples of valid command strings:
             })]
         );
     }
+
+    fn collision_test_tools_config() -> ToolsConfig {
+        let config = test_config();
+        let model_info =
+            ModelsManager::construct_model_info_offline_for_tests("gpt-5-codex", &config);
+        let features = Features::with_defaults();
+        ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+            session_source: SessionSource::Cli,
+        })
+    }
+
+    #[test]
+    fn try_from_config_rejects_dynamic_tool_named_like_builtin() {
+        let tools_config = collision_test_tools_config();
+        let dynamic_tools = [DynamicToolSpec {
+            name: "update_plan".to_string(),
+            description: "Shadows the built-in plan tool.".to_string(),
+            input_schema: serde_json::json!({"type": "object", "properties": {}}),
+        }];
+
+        let err = crate::tools::router::ToolRouter::try_from_config(
+            &tools_config,
+            None,
+            None,
+            &dynamic_tools,
+        )
+        .err()
+        .expect("duplicate tool name should be rejected");
+
+        assert_eq!(
+            err,
+            ToolNameCollisions {
+                duplicate_names: vec!["update_plan".to_string()],
+                shared_mcp_tool_names: BTreeMap::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn try_from_config_flags_mcp_tool_names_shared_across_servers() {
+        let tools_config = collision_test_tools_config();
+        let schema = serde_json::json!({"type": "object", "properties": {}});
+        let mcp_tools = HashMap::from([
+            (
+                "mcp__alpha__search".to_string(),
+                mcp_tool("search", "Search alpha", schema.clone()),
+            ),
+            (
+                "mcp__beta__search".to_string(),
+                mcp_tool("search", "Search beta", schema.clone()),
+            ),
+            (
+                "mcp__beta__fetch".to_string(),
+                mcp_tool("fetch", "Fetch from beta", schema),
+            ),
+        ]);
+
+        let err = crate::tools::router::ToolRouter::try_from_config(
+            &tools_config,
+            Some(mcp_tools),
+            None,
+            &[],
+        )
+        .err()
+        .expect("shared MCP tool name should be rejected");
+
+        assert_eq!(
+            err,
+            ToolNameCollisions {
+                duplicate_names: Vec::new(),
+                shared_mcp_tool_names: BTreeMap::from([(
+                    "search".to_string(),
+                    vec!["alpha".to_string(), "beta".to_string()],
+                )]),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "tool name collisions: MCP tool `search` is exposed by servers alpha, beta"
+        );
+    }
 }
@@ -0,0 +1,228 @@
+//! Resolution of [`FunctionCallOutputBody::Stream`] tool outputs.
+//!
+//! A tool may return the URI of a chunked output stream instead of an inline
+//! body so that tool execution is decoupled from serializing the output into
+//! the context window. The registry fetches and reassembles the stream before
+//! any output transformers, redaction or output limits run, so the model only
+//! ever sees plain text that went through the same limits as an inline body.
+//!
+//! Streams are fetched from the host, outside the sandbox, so only `http` and
+//! `https` URIs on a loopback host are accepted, redirects are not followed,
+//! and the fetch is bounded in time and size.
+
+use std::time::Duration;
+
+use codex_protocol::models::FunctionCallOutputBody;
+use futures::StreamExt;
+use reqwest::redirect;
+use url::Host;
+use url::Url;
+
+use crate::tools::context::ToolOutput;
+
+const STREAM_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Longest gap allowed between two reads of the stream body.
+const STREAM_READ_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_STREAM_BYTES: usize = 16 * 1024 * 1024;
+const ALLOWED_STREAM_SCHEMES: &[&str] = &["http", "https"];
+
+/// Replaces a streamed output body with the reassembled stream contents. A
+/// stream that cannot be fetched becomes a failed output describing why.
+/// Outputs without a stream body are returned unchanged.
+pub(crate) async fn resolve_streamed_output(output: ToolOutput) -> ToolOutput {
+    let ToolOutput::Function {
+        body: FunctionCallOutputBody::Stream(uri),
+        success,
+    } = output
+    else {
+        return output;
+    };
+
+    match fetch_stream(&uri).await {
+        Ok(content) => ToolOutput::Function {
+            body: FunctionCallOutputBody::Text(content),
+            success,
+        },
+        Err(message) => ToolOutput::Function {
+            body: FunctionCallOutputBody::Text(message),
+            success: Some(false),
+        },
+    }
+}
+
+async fn fetch_stream(uri: &str) -> Result<String, String> {
+    let url = allowed_stream_url(uri)?;
+    let client = reqwest::Client::builder()
+        .connect_timeout(STREAM_CONNECT_TIMEOUT)
+        .read_timeout(STREAM_READ_TIMEOUT)
+        .redirect(redirect::Policy::none())
+        .no_proxy()
+        .build()
+        .map_err(|err| format!("failed to fetch tool output stream {uri}: {err}"))?;
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|err| format!("failed to fetch tool output stream {uri}: {err}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!(
+            "failed to fetch tool output stream {uri}: status {status}"
+        ));
+    }
+    let too_large =
+        || format!("tool output stream {uri} exceeds the {MAX_STREAM_BYTES}-byte limit");
+    if response
+        .content_length()
+        .is_some_and(|length| length > MAX_STREAM_BYTES as u64)
+    {
+        return Err(too_large());
+    }
+
+    let mut bytes = Vec::new();
+    let mut chunks = response.bytes_stream();
+    while let Some(chunk) = chunks.next().await {
+        let chunk =
+            chunk.map_err(|err| format!("failed to read tool output stream {uri}: {err}"))?;
+        if bytes.len() + chunk.len() > MAX_STREAM_BYTES {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn allowed_stream_url(uri: &str) -> Result<Url, String> {
+    let url =
+        Url::parse(uri).map_err(|err| format!("invalid tool output stream URI {uri}: {err}"))?;
+    if !ALLOWED_STREAM_SCHEMES.contains(&url.scheme()) {
+        return Err(format!(
+            "tool output stream {uri} must use http or https, not {}",
+            url.scheme()
+        ));
+    }
+    let loopback = match url.host() {
+        Some(Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    };
+    if !loopback {
+        return Err(format!(
+            "tool output stream {uri} must be served from a loopback host"
+        ));
+    }
+    Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+
+    fn stream_output(uri: String) -> ToolOutput {
+        ToolOutput::Function {
+            body: FunctionCallOutputBody::Stream(uri),
+            success: Some(true),
+        }
+    }
+
+    fn text_output(output: ToolOutput) -> (String, Option<bool>) {
+        match output {
+            ToolOutput::Function {
+                body: FunctionCallOutputBody::Text(text),
+                success,
+            } => (text, success),
+            other => panic!("expected text output, got {}", other.log_preview()),
+        }
+    }
+
+    fn failure_text(output: ToolOutput) -> String {
+        let (text, success) = text_output(output);
+        assert_eq!(success, Some(false), "{text}");
+        text
+    }
+
+    #[tokio::test]
+    async fn stream_body_is_fetched_and_reassembled() {
+        let server = MockServer::start().await;
+        let chunks = ["first chunk\n", "second chunk\n", "third chunk"];
+        Mock::given(method("GET"))
+            .and(path("/streams/call-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(chunks.concat()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let resolved =
+            resolve_streamed_output(stream_output(format!("{}/streams/call-1", server.uri())))
+                .await;
+
+        assert_eq!(text_output(resolved), (chunks.concat(), Some(true)));
+    }
+
+    #[tokio::test]
+    async fn failed_stream_fetch_is_reported_as_failure() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/streams/missing"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let text = failure_text(
+            resolve_streamed_output(stream_output(format!("{}/streams/missing", server.uri())))
+                .await,
+        );
+        assert!(text.contains("status 404 Not Found"), "{text}");
+    }
+
+    #[tokio::test]
+    async fn oversized_stream_is_rejected() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/streams/huge"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_bytes(vec![b'x'; MAX_STREAM_BYTES + 1]),
+            )
+            .mount(&server)
+            .await;
+
+        let text = failure_text(
+            resolve_streamed_output(stream_output(format!("{}/streams/huge", server.uri()))).await,
+        );
+        assert!(text.contains("byte limit"), "{text}");
+    }
+
+    #[tokio::test]
+    async fn streams_off_loopback_or_over_other_schemes_are_not_fetched() {
+        for uri in [
+            "http://example.com/streams/call-1",
+            "https://10.0.0.1/streams/call-1",
+            "file:///etc/passwd",
+            "not a uri",
+        ] {
+            let text = failure_text(resolve_streamed_output(stream_output(uri.to_string())).await);
+            assert!(text.contains(uri), "{text}");
+        }
+    }
+
+    #[tokio::test]
+    async fn non_stream_outputs_are_untouched() {
+        let output = ToolOutput::Function {
+            body: FunctionCallOutputBody::Text("inline".to_string()),
+            success: None,
+        };
+
+        assert_eq!(
+            text_output(resolve_streamed_output(output).await),
+            ("inline".to_string(), None)
+        );
+    }
+}
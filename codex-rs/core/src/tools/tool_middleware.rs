@@ -0,0 +1,142 @@
+//! Middleware that wraps every [`ToolInvocation`] handled by a
+//! [`ToolRegistry`], e.g. to log calls, scrub secrets from arguments, or
+//! enforce an organization's own rules.
+//!
+//! See [`ToolRegistry::add_middleware`].
+//!
+//! [`ToolRegistry`]: crate::tools::registry::ToolRegistry
+//! [`ToolRegistry::add_middleware`]: crate::tools::registry::ToolRegistry::add_middleware
+
+use async_trait::async_trait;
+use codex_protocol::models::ResponseInputItem;
+use regex_lite::Regex;
+use tracing::info;
+
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::governance::redact_payload;
+
+#[async_trait]
+pub trait ToolMiddleware: Send + Sync {
+    /// Runs before `invocation` is dispatched, and may rewrite it. Returning
+    /// an error rejects the call: the handler and later middleware do not
+    /// run, and the error goes through [`ToolMiddleware::after`] as the
+    /// call's result.
+    async fn before(&self, _invocation: &mut ToolInvocation) -> Result<(), FunctionCallError> {
+        Ok(())
+    }
+
+    /// Runs once the call has finished, for every middleware whose `before`
+    /// ran, in reverse order. Returns the result passed on to the next
+    /// middleware, or to the caller.
+    async fn after(
+        &self,
+        _invocation: &ToolInvocation,
+        result: Result<ResponseInputItem, FunctionCallError>,
+    ) -> Result<ResponseInputItem, FunctionCallError> {
+        result
+    }
+}
+
+/// Logs each call and its outcome at `info` level.
+#[derive(Debug, Default)]
+pub struct LoggingMiddleware;
+
+#[async_trait]
+impl ToolMiddleware for LoggingMiddleware {
+    async fn before(&self, invocation: &mut ToolInvocation) -> Result<(), FunctionCallError> {
+        info!(
+            tool_name = invocation.tool_name.as_str(),
+            call_id = invocation.call_id.as_str(),
+            "dispatching tool call"
+        );
+        Ok(())
+    }
+
+    async fn after(
+        &self,
+        invocation: &ToolInvocation,
+        result: Result<ResponseInputItem, FunctionCallError>,
+    ) -> Result<ResponseInputItem, FunctionCallError> {
+        match &result {
+            Ok(_) => info!(
+                tool_name = invocation.tool_name.as_str(),
+                call_id = invocation.call_id.as_str(),
+                "tool call finished"
+            ),
+            Err(err) => info!(
+                tool_name = invocation.tool_name.as_str(),
+                call_id = invocation.call_id.as_str(),
+                error = %err,
+                "tool call failed"
+            ),
+        }
+        result
+    }
+}
+
+/// Patterns for common credentials: OpenAI and Anthropic API keys, GitHub
+/// tokens, AWS access key ids, and bearer tokens.
+const DEFAULT_SECRET_PATTERNS: &[&str] = &[
+    r"sk-[A-Za-z0-9_-]{20,}",
+    r"gh[pousr]_[A-Za-z0-9]{36,}",
+    r"AKIA[0-9A-Z]{16}",
+    r"(?i)bearer\s+[A-Za-z0-9._~+/=-]{8,}",
+];
+
+/// Replaces secrets in a call's arguments with
+/// [`REDACTED`](crate::tools::governance::REDACTED) before the tool sees them.
+pub struct SecretRedactionMiddleware {
+    patterns: Vec<Regex>,
+}
+
+impl SecretRedactionMiddleware {
+    pub fn new(patterns: Vec<Regex>) -> Self {
+        Self { patterns }
+    }
+}
+
+impl Default for SecretRedactionMiddleware {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_SECRET_PATTERNS
+                .iter()
+                .filter_map(|pattern| Regex::new(pattern).ok())
+                .collect(),
+        )
+    }
+}
+
+#[async_trait]
+impl ToolMiddleware for SecretRedactionMiddleware {
+    async fn before(&self, invocation: &mut ToolInvocation) -> Result<(), FunctionCallError> {
+        invocation.payload = redact_payload(invocation.payload.clone(), &self.patterns);
+        Ok(())
+    }
+}
+
+/// Rejects calls whose arguments are larger than `max_bytes`.
+#[derive(Debug)]
+pub struct ArgumentSizeLimitMiddleware {
+    max_bytes: usize,
+}
+
+impl ArgumentSizeLimitMiddleware {
+    pub fn new(max_bytes: usize) -> Self {
+        Self { max_bytes }
+    }
+}
+
+#[async_trait]
+impl ToolMiddleware for ArgumentSizeLimitMiddleware {
+    async fn before(&self, invocation: &mut ToolInvocation) -> Result<(), FunctionCallError> {
+        let size_bytes = invocation.payload.log_payload().len();
+        if size_bytes > self.max_bytes {
+            return Err(FunctionCallError::PayloadTooLarge {
+                size_bytes,
+                limit_bytes: self.max_bytes,
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+//! Plain-English summaries of the tools a turn used.
+
+use crate::tools::context::ToolPayload;
+use crate::tools::router::ToolCall;
+
+/// Human-facing category for a tool call. Summaries are phrased in terms of
+/// these categories rather than internal tool names.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ToolUsageKind {
+    ReadFile,
+    ListDir,
+    SearchFiles,
+    ShellCommand,
+    ApplyPatch,
+    WebSearch,
+    ViewImage,
+    UpdatePlan,
+    Mcp,
+    Other,
+}
+
+impl ToolUsageKind {
+    pub(crate) fn from_call(call: &ToolCall) -> Self {
+        if matches!(call.payload, ToolPayload::Mcp { .. }) {
+            return Self::Mcp;
+        }
+        Self::from_tool_name(&call.tool_name)
+    }
+
+    pub(crate) fn from_tool_name(tool_name: &str) -> Self {
+        match tool_name {
+            "read_file" => Self::ReadFile,
+            "list_dir" => Self::ListDir,
+            "grep_files" => Self::SearchFiles,
+            "shell" | "container.exec" | "local_shell" | "shell_command" | "exec_command" => {
+                Self::ShellCommand
+            }
+            "apply_patch" => Self::ApplyPatch,
+            "web_search" => Self::WebSearch,
+            "view_image" => Self::ViewImage,
+            "update_plan" => Self::UpdatePlan,
+            _ => Self::Other,
+        }
+    }
+
+    fn describe(&self, count: usize) -> String {
+        let (verb, singular, plural) = match self {
+            Self::ReadFile => ("read", "file", "files"),
+            Self::ListDir => ("listed", "directory", "directories"),
+            Self::SearchFiles => ("ran", "file search", "file searches"),
+            Self::ShellCommand => ("ran", "shell command", "shell commands"),
+            Self::ApplyPatch => ("applied", "patch", "patches"),
+            Self::WebSearch => ("performed", "web search", "web searches"),
+            Self::ViewImage => ("viewed", "image", "images"),
+            Self::UpdatePlan => ("made", "plan update", "plan updates"),
+            Self::Mcp => ("called", "MCP tool", "MCP tools"),
+            Self::Other => ("used", "other tool", "other tools"),
+        };
+        let noun = if count == 1 { singular } else { plural };
+        format!("{verb} {count} {noun}")
+    }
+}
+
+/// Builds a sentence such as "In this turn, the model read 3 files, ran 2
+/// shell commands, and performed 1 web search." Categories appear in the order
+/// they were first used.
+pub(crate) fn summarize_tool_usage(usage: &[ToolUsageKind]) -> String {
+    let mut counts: Vec<(&ToolUsageKind, usize)> = Vec::new();
+    for kind in usage {
+        match counts.iter_mut().find(|(seen, _)| *seen == kind) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((kind, 1)),
+        }
+    }
+
+    let phrases: Vec<String> = counts
+        .into_iter()
+        .map(|(kind, count)| kind.describe(count))
+        .collect();
+    match phrases.as_slice() {
+        [] => "In this turn, the model did not use any tools.".to_string(),
+        [only] => format!("In this turn, the model {only}."),
+        [first, second] => format!("In this turn, the model {first} and {second}."),
+        [rest @ .., last] => format!("In this turn, the model {}, and {last}.", rest.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn summary_without_tools() {
+        assert_eq!(
+            summarize_tool_usage(&[]),
+            "In this turn, the model did not use any tools."
+        );
+    }
+
+    #[test]
+    fn summary_with_single_category() {
+        assert_eq!(
+            summarize_tool_usage(&[ToolUsageKind::ShellCommand]),
+            "In this turn, the model ran 1 shell command."
+        );
+    }
+
+    #[test]
+    fn summary_with_two_categories() {
+        assert_eq!(
+            summarize_tool_usage(&[
+                ToolUsageKind::ApplyPatch,
+                ToolUsageKind::Mcp,
+                ToolUsageKind::Mcp,
+            ]),
+            "In this turn, the model applied 1 patch and called 2 MCP tools."
+        );
+    }
+
+    #[test]
+    fn summary_groups_by_category_in_first_use_order() {
+        let usage = [
+            "read_file",
+            "shell",
+            "read_file",
+            "web_search",
+            "exec_command",
+            "read_file",
+        ]
+        .into_iter()
+        .map(ToolUsageKind::from_tool_name)
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            summarize_tool_usage(&usage),
+            "In this turn, the model read 3 files, ran 2 shell commands, and performed 1 web search."
+        );
+    }
+
+    #[test]
+    fn unknown_tools_are_not_named() {
+        let summary = summarize_tool_usage(&[ToolUsageKind::from_tool_name("internal_tool")]);
+
+        assert_eq!(summary, "In this turn, the model used 1 other tool.");
+    }
+}
@@ -0,0 +1,123 @@
+//! Pre-flight checks run on a batch of tool calls before any of them is
+//! dispatched, so every argument error can be reported in one round trip.
+//!
+//! See [`ToolRouter::batch_validate`].
+//!
+//! [`ToolRouter::batch_validate`]: crate::tools::router::ToolRouter::batch_validate
+
+use serde_json::Value as JsonValue;
+use thiserror::Error;
+
+use crate::tools::spec::AdditionalProperties;
+use crate::tools::spec::JsonSchema;
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum ValidationError {
+    #[error("unsupported call: {tool_name}")]
+    UnknownTool { tool_name: String },
+    #[error("tool {tool_name} is disabled for this turn")]
+    DisabledForTurn { tool_name: String },
+    #[error("another call in this batch has the same call id")]
+    DuplicateCallId,
+    #[error("arguments are {size_bytes} bytes, over the {limit_bytes} byte limit")]
+    PayloadTooLarge {
+        size_bytes: usize,
+        limit_bytes: usize,
+    },
+    #[error("failed to parse function arguments: {message}")]
+    InvalidJson { message: String },
+    #[error("arguments must be a JSON object")]
+    NotAnObject,
+    #[error("missing required argument `{field}`")]
+    MissingField { field: String },
+    #[error("unexpected argument `{field}`")]
+    UnexpectedField { field: String },
+    #[error("argument `{field}` must be of type {expected}")]
+    WrongType {
+        field: String,
+        expected: &'static str,
+    },
+}
+
+/// Checks that `arguments` parse as JSON and match the top-level properties
+/// of `schema`, if any: required fields are present, no field is unexpected,
+/// and each field has the declared type. Nested values are not inspected.
+pub(crate) fn validate_arguments(
+    arguments: &str,
+    schema: Option<&JsonSchema>,
+) -> Vec<ValidationError> {
+    let value = if arguments.trim().is_empty() {
+        JsonValue::Object(serde_json::Map::new())
+    } else {
+        match serde_json::from_str::<JsonValue>(arguments) {
+            Ok(value) => value,
+            Err(err) => {
+                return vec![ValidationError::InvalidJson {
+                    message: err.to_string(),
+                }];
+            }
+        }
+    };
+    let Some(JsonSchema::Object {
+        properties,
+        required,
+        additional_properties,
+    }) = schema
+    else {
+        return Vec::new();
+    };
+    let JsonValue::Object(fields) = value else {
+        return vec![ValidationError::NotAnObject];
+    };
+
+    let mut errors: Vec<ValidationError> = required
+        .iter()
+        .flatten()
+        .filter(|field| !fields.contains_key(field.as_str()))
+        .map(|field| ValidationError::MissingField {
+            field: field.clone(),
+        })
+        .collect();
+    for (field, value) in &fields {
+        match properties.get(field) {
+            Some(property) if !matches_type(value, property) => {
+                errors.push(ValidationError::WrongType {
+                    field: field.clone(),
+                    expected: type_name(property),
+                });
+            }
+            Some(_) => {}
+            None => {
+                if matches!(
+                    additional_properties,
+                    Some(AdditionalProperties::Boolean(false))
+                ) {
+                    errors.push(ValidationError::UnexpectedField {
+                        field: field.clone(),
+                    });
+                }
+            }
+        }
+    }
+    errors
+}
+
+fn matches_type(value: &JsonValue, schema: &JsonSchema) -> bool {
+    match schema {
+        JsonSchema::Boolean { .. } => value.is_boolean(),
+        JsonSchema::String { .. } => value.is_string(),
+        JsonSchema::Number { .. } => value.is_number(),
+        JsonSchema::Array { .. } => value.is_array(),
+        JsonSchema::Object { .. } => value.is_object(),
+    }
+}
+
+fn type_name(schema: &JsonSchema) -> &'static str {
+    match schema {
+        JsonSchema::Boolean { .. } => "boolean",
+        JsonSchema::String { .. } => "string",
+        JsonSchema::Number { .. } => "number",
+        JsonSchema::Array { .. } => "array",
+        JsonSchema::Object { .. } => "object",
+    }
+}
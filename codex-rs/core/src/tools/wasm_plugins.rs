@@ -0,0 +1,350 @@
+//! Tools implemented as WebAssembly modules and run in-process by wasmtime.
+//!
+//! A plugin module has no imports, so it cannot touch the filesystem, the
+//! network or the host beyond the arguments it is given. It exports:
+//!
+//! - `memory`, its linear memory;
+//! - `codex_alloc(len: i32) -> i32`, returning a buffer of `len` bytes;
+//! - `codex_tool_manifest() -> i64`, returning a JSON array of
+//!   `{"name", "description", "parameters"}` objects;
+//! - one `<name>(ptr: i32, len: i32) -> i64` function per tool, receiving
+//!   the JSON arguments and returning `{"output": "..."}` or
+//!   `{"error": "..."}`.
+//!
+//! Returned strings are UTF-8, packed as `ptr << 32 | len`. Every call runs
+//! in a fresh instance bounded by [`WasmPluginLimits`].
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use thiserror::Error;
+use wasmtime::Engine;
+use wasmtime::Instance;
+use wasmtime::Memory;
+use wasmtime::Module;
+use wasmtime::Store;
+use wasmtime::StoreLimits;
+use wasmtime::StoreLimitsBuilder;
+use wasmtime::Trap;
+
+use crate::client_common::tools::ResponsesApiTool;
+use crate::client_common::tools::ToolSpec;
+use crate::tools::registry::ConfiguredToolSpec;
+use crate::tools::registry::ToolRegistry;
+use crate::tools::spec::parse_tool_input_schema;
+
+/// Export that lists the tools a plugin module provides.
+pub const WASM_TOOL_MANIFEST_EXPORT: &str = "codex_tool_manifest";
+
+/// Export the host calls to get a buffer for a tool's arguments.
+pub const WASM_ALLOC_EXPORT: &str = "codex_alloc";
+
+const MEMORY_EXPORT: &str = "memory";
+
+/// Resources a single plugin call may use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WasmPluginLimits {
+    /// Upper bound on the instance's linear memory.
+    pub max_memory_bytes: usize,
+    /// Units of fuel the call may burn; roughly one per wasm instruction.
+    pub max_fuel: u64,
+}
+
+impl Default for WasmPluginLimits {
+    fn default() -> Self {
+        Self {
+            max_memory_bytes: 64 * 1024 * 1024,
+            max_fuel: 1_000_000_000,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum WasmPluginError {
+    #[error("failed to load wasm plugin {}: {message}", path.display())]
+    Load { path: PathBuf, message: String },
+    #[error("wasm plugin {} has an invalid tool manifest: {message}", path.display())]
+    InvalidManifest { path: PathBuf, message: String },
+    #[error("wasm plugin tool {name} is invalid: {message}")]
+    InvalidTool { name: String, message: String },
+    #[error("plugins must be loaded before the router is shared")]
+    RouterShared,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestTool {
+    name: String,
+    description: String,
+    parameters: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ToolResult {
+    Output(String),
+    Error(String),
+}
+
+struct HostState {
+    limits: StoreLimits,
+}
+
+/// A compiled plugin module, shared by all of its tools.
+pub(crate) struct WasmPlugin {
+    engine: Engine,
+    module: Module,
+    limits: WasmPluginLimits,
+}
+
+impl WasmPlugin {
+    /// Compiles the module at `path`, which may be a `.wasm` binary or the
+    /// `.wat` text format.
+    pub(crate) fn open(path: &Path, limits: WasmPluginLimits) -> Result<Self, WasmPluginError> {
+        let load_error = |err: wasmtime::Error| WasmPluginError::Load {
+            path: path.to_path_buf(),
+            message: format!("{err:#}"),
+        };
+        let mut config = wasmtime::Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(load_error)?;
+        let module = Module::from_file(&engine, path).map_err(load_error)?;
+        Ok(Self {
+            engine,
+            module,
+            limits,
+        })
+    }
+
+    fn manifest(&self, path: &Path) -> Result<Vec<ManifestTool>, WasmPluginError> {
+        let invalid = |message: String| WasmPluginError::InvalidManifest {
+            path: path.to_path_buf(),
+            message,
+        };
+        let (mut store, instance, memory) = self.instantiate().map_err(&invalid)?;
+        let manifest = instance
+            .get_typed_func::<(), i64>(&mut store, WASM_TOOL_MANIFEST_EXPORT)
+            .map_err(|err| invalid(format!("{err:#}")))?
+            .call(&mut store, ())
+            .map_err(|err| invalid(call_error_message(err)))?;
+        let manifest = read_packed_string(&store, &memory, manifest).map_err(&invalid)?;
+        serde_json::from_str(&manifest).map_err(|err| invalid(err.to_string()))
+    }
+
+    /// Runs tool `name` with `arguments` in a fresh instance.
+    fn call(&self, name: &str, arguments: &str) -> Result<String, String> {
+        let (mut store, instance, memory) = self.instantiate()?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, WASM_ALLOC_EXPORT)
+            .map_err(|err| format!("{err:#}"))?;
+        let tool = instance
+            .get_typed_func::<(i32, i32), i64>(&mut store, name)
+            .map_err(|err| format!("{err:#}"))?;
+
+        let len = i32::try_from(arguments.len())
+            .map_err(|_| "tool arguments are too large for a wasm plugin".to_string())?;
+        let ptr = alloc.call(&mut store, len).map_err(call_error_message)?;
+        memory
+            .write(&mut store, ptr as u32 as usize, arguments.as_bytes())
+            .map_err(|err| format!("plugin returned an invalid buffer: {err}"))?;
+        let result = tool
+            .call(&mut store, (ptr, len))
+            .map_err(call_error_message)?;
+        let result = read_packed_string(&store, &memory, result)?;
+        match serde_json::from_str(&result) {
+            Ok(ToolResult::Output(output)) => Ok(output),
+            Ok(ToolResult::Error(message)) => Err(message),
+            Err(err) => Err(format!("plugin returned an invalid result: {err}")),
+        }
+    }
+
+    fn instantiate(&self) -> Result<(Store<HostState>, Instance, Memory), String> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.limits.max_memory_bytes)
+            .instances(1)
+            .build();
+        let mut store = Store::new(&self.engine, HostState { limits });
+        store.limiter(|state| &mut state.limits);
+        store
+            .set_fuel(self.limits.max_fuel)
+            .map_err(|err| format!("{err:#}"))?;
+        // No imports are provided, so modules that need host functions fail here.
+        let instance =
+            Instance::new(&mut store, &self.module, &[]).map_err(|err| format!("{err:#}"))?;
+        let memory = instance
+            .get_memory(&mut store, MEMORY_EXPORT)
+            .ok_or_else(|| format!("plugin does not export `{MEMORY_EXPORT}`"))?;
+        Ok((store, instance, memory))
+    }
+}
+
+fn call_error_message(err: wasmtime::Error) -> String {
+    match err.downcast_ref::<Trap>() {
+        Some(Trap::OutOfFuel) => "plugin exceeded its fuel limit".to_string(),
+        _ => format!("plugin failed: {err:#}"),
+    }
+}
+
+fn read_packed_string(
+    store: &Store<HostState>,
+    memory: &Memory,
+    packed: i64,
+) -> Result<String, String> {
+    let packed = packed as u64;
+    let ptr = (packed >> 32) as usize;
+    let len = (packed & u64::from(u32::MAX)) as usize;
+    let bytes = memory
+        .data(store)
+        .get(ptr..ptr.saturating_add(len))
+        .ok_or_else(|| "plugin returned a string outside its memory".to_string())?;
+    String::from_utf8(bytes.to_vec()).map_err(|_| "plugin returned invalid UTF-8".to_string())
+}
+
+/// Registers every tool in `plugin`'s manifest as a function tool and
+/// advertises it in `specs`. The manifest is validated before any tool is
+/// registered, so a bad plugin leaves the registry untouched.
+pub(crate) fn register_wasm_plugin(
+    registry: &mut ToolRegistry,
+    specs: &mut Vec<ConfiguredToolSpec>,
+    path: &Path,
+    plugin: WasmPlugin,
+) -> Result<Vec<String>, WasmPluginError> {
+    let tools = plugin.manifest(path)?;
+    let mut seen = HashSet::new();
+    let mut validated = Vec::with_capacity(tools.len());
+    for tool in tools {
+        let invalid = |message: String| WasmPluginError::InvalidTool {
+            name: tool.name.clone(),
+            message,
+        };
+        let parameters =
+            parse_tool_input_schema(&tool.parameters).map_err(|err| invalid(err.to_string()))?;
+        if registry.handler(&tool.name).is_some() || !seen.insert(tool.name.clone()) {
+            return Err(invalid(
+                "a tool with this name is already registered".to_string(),
+            ));
+        }
+        if plugin.module.get_export(&tool.name).is_none() {
+            return Err(invalid("the module does not export it".to_string()));
+        }
+        validated.push((tool, parameters));
+    }
+
+    let plugin = Arc::new(plugin);
+    let mut names = Vec::with_capacity(validated.len());
+    for (tool, parameters) in validated {
+        let plugin = Arc::clone(&plugin);
+        let name = tool.name.clone();
+        registry
+            .register_function_tool(&tool.name, tool.parameters, move |arguments| {
+                plugin.call(&name, arguments)
+            })
+            .map_err(|err| WasmPluginError::InvalidTool {
+                name: tool.name.clone(),
+                message: err.to_string(),
+            })?;
+        specs.push(ConfiguredToolSpec::new(
+            ToolSpec::Function(ResponsesApiTool {
+                name: tool.name.clone(),
+                description: tool.description,
+                strict: false,
+                parameters,
+            }),
+            false,
+        ));
+        names.push(tool.name);
+    }
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+    use tempfile::tempdir;
+
+    const MANIFEST: &str = r#"[{"name":"echo","description":"Echoes its arguments.","parameters":{"type":"object","properties":{}}},{"name":"spin","description":"Never returns.","parameters":{"type":"object","properties":{}}}]"#;
+
+    /// `echo` returns its arguments unchanged, so callers pass a result
+    /// object such as `{"output": "..."}`.
+    fn write_plugin(memory_pages: u32) -> anyhow::Result<(TempDir, PathBuf)> {
+        let dir = tempdir()?;
+        let path = dir.path().join("plugin.wat");
+        let wat = format!(
+            r#"(module
+  (memory (export "memory") {memory_pages})
+  (data (i32.const 0) "{data}")
+  (func (export "codex_alloc") (param i32) (result i32) i32.const 4096)
+  (func (export "codex_tool_manifest") (result i64) i64.const {len})
+  (func (export "echo") (param i32 i32) (result i64)
+    local.get 0
+    i64.extend_i32_u
+    i64.const 32
+    i64.shl
+    local.get 1
+    i64.extend_i32_u
+    i64.or)
+  (func (export "spin") (param i32 i32) (result i64)
+    (loop (br 0))
+    unreachable))"#,
+            data = MANIFEST.replace('"', "\\\""),
+            len = MANIFEST.len(),
+        );
+        std::fs::write(&path, wat)?;
+        Ok((dir, path))
+    }
+
+    #[test]
+    fn registers_manifest_tools_and_runs_them() -> anyhow::Result<()> {
+        let (_dir, path) = write_plugin(1)?;
+        let plugin = WasmPlugin::open(&path, WasmPluginLimits::default())?;
+        assert_eq!(
+            plugin.call("echo", r#"{"output":"pong"}"#),
+            Ok("pong".to_string())
+        );
+        assert_eq!(
+            plugin.call("echo", r#"{"error":"bad ticket id"}"#),
+            Err("bad ticket id".to_string())
+        );
+
+        let mut registry = ToolRegistry::new(HashMap::new());
+        let mut specs = Vec::new();
+        let names = register_wasm_plugin(&mut registry, &mut specs, &path, plugin)?;
+
+        assert_eq!(names, vec!["echo".to_string(), "spin".to_string()]);
+        assert!(registry.handler("echo").is_some());
+        assert_eq!(specs.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn calls_are_bounded_by_limits() -> anyhow::Result<()> {
+        let (_dir, path) = write_plugin(1)?;
+        let plugin = WasmPlugin::open(
+            &path,
+            WasmPluginLimits {
+                max_fuel: 10_000,
+                ..WasmPluginLimits::default()
+            },
+        )?;
+        assert_eq!(
+            plugin.call("spin", "{}"),
+            Err("plugin exceeded its fuel limit".to_string())
+        );
+
+        let (_dir, path) = write_plugin(4)?;
+        let plugin = WasmPlugin::open(
+            &path,
+            WasmPluginLimits {
+                max_memory_bytes: 64 * 1024,
+                ..WasmPluginLimits::default()
+            },
+        )?;
+        assert!(plugin.call("echo", r#"{"output":"pong"}"#).is_err());
+        Ok(())
+    }
+}
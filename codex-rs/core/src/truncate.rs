@@ -86,10 +86,20 @@ pub(crate) fn formatted_truncate_text(content: &str, policy: TruncationPolicy) -
 }
 
 pub(crate) fn truncate_text(content: &str, policy: TruncationPolicy) -> String {
+    truncate_text_with_head_ratio(content, policy, 0.5)
+}
+
+/// Like [`truncate_text`], but keeps `head_ratio` of the budget from the
+/// start of `content` and the rest from its end.
+pub(crate) fn truncate_text_with_head_ratio(
+    content: &str,
+    policy: TruncationPolicy,
+    head_ratio: f64,
+) -> String {
     match policy {
-        TruncationPolicy::Bytes(_) => truncate_with_byte_estimate(content, policy),
+        TruncationPolicy::Bytes(_) => truncate_with_byte_estimate(content, policy, head_ratio),
         TruncationPolicy::Tokens(_) => {
-            let (truncated, _) = truncate_with_token_budget(content, policy);
+            let (truncated, _) = truncate_with_token_budget(content, policy, head_ratio);
             truncated
         }
     }
@@ -160,7 +170,11 @@ pub(crate) fn truncate_function_output_items_with_policy(
 /// preserving the beginning and the end. Returns the possibly truncated string
 /// and `Some(original_token_count)` if truncation occurred; otherwise returns
 /// the original string and `None`.
-fn truncate_with_token_budget(s: &str, policy: TruncationPolicy) -> (String, Option<u64>) {
+fn truncate_with_token_budget(
+    s: &str,
+    policy: TruncationPolicy,
+    head_ratio: f64,
+) -> (String, Option<u64>) {
     if s.is_empty() {
         return (String::new(), None);
     }
@@ -171,7 +185,7 @@ fn truncate_with_token_budget(s: &str, policy: TruncationPolicy) -> (String, Opt
         return (s.to_string(), None);
     }
 
-    let truncated = truncate_with_byte_estimate(s, policy);
+    let truncated = truncate_with_byte_estimate(s, policy, head_ratio);
     let approx_total_usize = approx_token_count(s);
     let approx_total = u64::try_from(approx_total_usize).unwrap_or(u64::MAX);
     if truncated == s {
@@ -184,7 +198,7 @@ fn truncate_with_token_budget(s: &str, policy: TruncationPolicy) -> (String, Opt
 /// Truncate a string using a byte budget derived from the token budget, without
 /// performing any real tokenization. This keeps the logic purely byte-based and
 /// uses a bytes placeholder in the truncated output.
-fn truncate_with_byte_estimate(s: &str, policy: TruncationPolicy) -> String {
+fn truncate_with_byte_estimate(s: &str, policy: TruncationPolicy, head_ratio: f64) -> String {
     if s.is_empty() {
         return String::new();
     }
@@ -207,7 +221,8 @@ fn truncate_with_byte_estimate(s: &str, policy: TruncationPolicy) -> String {
 
     let total_bytes = s.len();
 
-    let (left_budget, right_budget) = split_budget(max_bytes);
+    let left_budget = ((max_bytes as f64 * head_ratio.clamp(0.0, 1.0)) as usize).min(max_bytes);
+    let right_budget = max_bytes - left_budget;
 
     let (removed_chars, left, right) = split_string(s, left_budget, right_budget);
 
@@ -219,6 +234,41 @@ fn truncate_with_byte_estimate(s: &str, policy: TruncationPolicy) -> String {
     assemble_truncated_output(left, right, &marker)
 }
 
+/// Cuts `content` down to at most `max_bytes` bytes of the original text and
+/// reports the rest with a `[truncated: N bytes omitted]` marker. With
+/// `keep_tail`, the bytes are dropped from the middle so that both the start
+/// and the end survive; otherwise the marker follows the kept prefix.
+pub(crate) fn truncate_to_byte_limit(content: &str, max_bytes: usize, keep_tail: bool) -> String {
+    if content.len() <= max_bytes {
+        return content.to_string();
+    }
+    let (head_bytes, tail_bytes) = if keep_tail {
+        split_budget(max_bytes)
+    } else {
+        (max_bytes, 0)
+    };
+    let (_, prefix, suffix) = split_string(content, head_bytes, tail_bytes);
+    let omitted = content.len() - prefix.len() - suffix.len();
+    let marker = format!("[truncated: {omitted} bytes omitted]");
+    if keep_tail {
+        format!("{prefix}\n{marker}\n{suffix}")
+    } else {
+        format!("{prefix}\n{marker}")
+    }
+}
+
+/// Like [`truncate_to_byte_limit`] with `keep_tail`, but keeps `head_ratio`
+/// of `max_bytes` from the start of `content` and the rest from its end.
+pub(crate) fn truncate_head_and_tail(content: &str, max_bytes: usize, head_ratio: f64) -> String {
+    if content.len() <= max_bytes {
+        return content.to_string();
+    }
+    let head_bytes = ((max_bytes as f64 * head_ratio.clamp(0.0, 1.0)) as usize).min(max_bytes);
+    let (_, prefix, suffix) = split_string(content, head_bytes, max_bytes - head_bytes);
+    let omitted = content.len() - prefix.len() - suffix.len();
+    format!("{prefix}\n[truncated: {omitted} bytes omitted]\n{suffix}")
+}
+
 fn split_string(s: &str, beginning_bytes: usize, end_bytes: usize) -> (usize, &str, &str) {
     if s.is_empty() {
         return (0, "", "");
@@ -321,7 +371,10 @@ mod tests {
     use super::formatted_truncate_text;
     use super::split_string;
     use super::truncate_function_output_items_with_policy;
+    use super::truncate_head_and_tail;
     use super::truncate_text;
+    use super::truncate_text_with_head_ratio;
+    use super::truncate_to_byte_limit;
     use super::truncate_with_token_budget;
     use codex_protocol::models::FunctionCallOutputContentItem;
     use pretty_assertions::assert_eq;
@@ -447,7 +500,7 @@ mod tests {
     fn truncate_with_token_budget_returns_original_when_under_limit() {
         let s = "short output";
         let limit = 100;
-        let (out, original) = truncate_with_token_budget(s, TruncationPolicy::Tokens(limit));
+        let (out, original) = truncate_with_token_budget(s, TruncationPolicy::Tokens(limit), 0.5);
         assert_eq!(out, s);
         assert_eq!(original, None);
     }
@@ -455,7 +508,7 @@ mod tests {
     #[test]
     fn truncate_with_token_budget_reports_truncation_at_zero_limit() {
         let s = "abcdef";
-        let (out, original) = truncate_with_token_budget(s, TruncationPolicy::Tokens(0));
+        let (out, original) = truncate_with_token_budget(s, TruncationPolicy::Tokens(0), 0.5);
         assert_eq!(out, "…2 tokens truncated…");
         assert_eq!(original, Some(2));
     }
@@ -463,7 +516,7 @@ mod tests {
     #[test]
     fn truncate_middle_tokens_handles_utf8_content() {
         let s = "😀😀😀😀😀😀😀😀😀😀\nsecond line with text\n";
-        let (out, tokens) = truncate_with_token_budget(s, TruncationPolicy::Tokens(8));
+        let (out, tokens) = truncate_with_token_budget(s, TruncationPolicy::Tokens(8), 0.5);
         assert_eq!(out, "😀😀😀😀…8 tokens truncated… line with text\n");
         assert_eq!(tokens, Some(16));
     }
@@ -475,6 +528,55 @@ mod tests {
         assert_eq!(out, "😀😀…21 chars truncated…with text\n");
     }
 
+    #[test]
+    fn truncate_to_byte_limit_keeps_prefix_or_both_ends() {
+        let s = "line one\nline two\nline three\n";
+
+        assert_eq!(truncate_to_byte_limit(s, s.len(), false), s);
+        assert_eq!(
+            truncate_to_byte_limit(s, 8, false),
+            "line one\n[truncated: 21 bytes omitted]"
+        );
+        assert_eq!(
+            truncate_to_byte_limit(s, 16, true),
+            "line one\n\n[truncated: 13 bytes omitted]\ne three\n"
+        );
+        // Multi-byte characters are never split.
+        assert_eq!(
+            truncate_to_byte_limit("😀😀😀", 6, false),
+            "😀\n[truncated: 8 bytes omitted]"
+        );
+    }
+
+    #[test]
+    fn truncate_head_and_tail_splits_budget_by_ratio() {
+        let s = "0123456789abcdefghij";
+
+        assert_eq!(truncate_head_and_tail(s, s.len(), 0.2), s);
+        assert_eq!(
+            truncate_head_and_tail(s, 10, 0.2),
+            "01\n[truncated: 10 bytes omitted]\ncdefghij"
+        );
+        assert_eq!(
+            truncate_head_and_tail(s, 10, 1.0),
+            "0123456789\n[truncated: 10 bytes omitted]\n"
+        );
+    }
+
+    #[test]
+    fn truncate_text_with_head_ratio_favors_the_tail() {
+        let s = "0123456789abcdefghij";
+
+        assert_eq!(
+            truncate_text_with_head_ratio(s, TruncationPolicy::Bytes(10), 0.2),
+            "01…10 chars truncated…cdefghij"
+        );
+        assert_eq!(
+            truncate_text_with_head_ratio(s, TruncationPolicy::Bytes(10), 0.5),
+            truncate_text(s, TruncationPolicy::Bytes(10))
+        );
+    }
+
     #[test]
     fn truncates_across_multiple_under_limit_texts_and_reports_omitted() {
         let chunk = "alpha beta gamma delta epsilon zeta eta theta iota kappa lambda mu nu xi omicron pi rho sigma tau upsilon phi chi psi omega.\n";
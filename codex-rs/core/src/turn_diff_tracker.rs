@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
@@ -11,6 +12,10 @@ use sha1::digest::Output;
 use uuid::Uuid;
 
 use crate::protocol::FileChange;
+use crate::protocol::TurnDiffEvent;
+use crate::protocol::TurnDiffFileStat;
+use crate::protocol::TurnDiffFileStatus;
+use crate::protocol::TurnRootDiff;
 
 const ZERO_OID: &str = "0000000000000000000000000000000000000000";
 const DEV_NULL: &str = "/dev/null";
@@ -40,6 +45,8 @@ pub struct TurnDiffTracker {
     temp_name_to_current_path: HashMap<String, PathBuf>,
     /// Cache of known git worktree roots to avoid repeated filesystem walks.
     git_root_cache: Vec<PathBuf>,
+    /// The per-file stats last returned by `file_stats_if_changed`.
+    last_file_stats: Vec<TurnDiffFileStat>,
 }
 
 impl TurnDiffTracker {
@@ -249,6 +256,183 @@ impl TurnDiffTracker {
         }
     }
 
+    /// The aggregated diff split by the repository each file lives in, or by
+    /// its directory outside any repository. Roots are sorted by path.
+    pub fn get_unified_diffs_by_root(&mut self) -> Vec<TurnRootDiff> {
+        let mut baseline_file_names: Vec<String> =
+            self.baseline_file_info.keys().cloned().collect();
+        baseline_file_names.sort_by_key(|internal| self.get_path_for_internal(internal));
+
+        let mut diffs_by_root: BTreeMap<PathBuf, String> = BTreeMap::new();
+        for internal in baseline_file_names {
+            let Some(path) = self.get_path_for_internal(&internal) else {
+                continue;
+            };
+            let diff = self.get_file_diff(&internal);
+            if diff.trim().is_empty() {
+                continue;
+            }
+            let root = self
+                .find_git_root_cached(&path)
+                .or_else(|| path.parent().map(Path::to_path_buf))
+                .unwrap_or_default();
+            let aggregated = diffs_by_root.entry(root).or_default();
+            aggregated.push_str(&diff);
+            if !aggregated.ends_with('\n') {
+                aggregated.push('\n');
+            }
+        }
+        diffs_by_root
+            .into_iter()
+            .map(|(root, unified_diff)| TurnRootDiff { root, unified_diff })
+            .collect()
+    }
+
+    /// The event describing this turn's changes so far, with per-root diffs
+    /// when the changes span more than one root.
+    pub fn turn_diff_event(&mut self) -> Result<Option<TurnDiffEvent>> {
+        let Some(unified_diff) = self.get_unified_diff()? else {
+            return Ok(None);
+        };
+        let mut root_diffs = self.get_unified_diffs_by_root();
+        if root_diffs.len() < 2 {
+            root_diffs.clear();
+        }
+        Ok(Some(TurnDiffEvent {
+            unified_diff,
+            root_diffs,
+        }))
+    }
+
+    /// Line counts, status and binary flag for every file this turn has
+    /// changed so far, sorted by path. A deleted file and an added file with
+    /// the same contents are reported as a single rename.
+    pub fn file_stats(&self) -> Vec<TurnDiffFileStat> {
+        let mut stats = Vec::new();
+        let mut added = Vec::new();
+        let mut deleted = Vec::new();
+        for internal in self.baseline_file_info.keys() {
+            let Some((stat, content_id)) = self.get_file_stat(internal) else {
+                continue;
+            };
+            match stat.status {
+                TurnDiffFileStatus::Added => added.push((stat, content_id)),
+                TurnDiffFileStatus::Deleted => deleted.push((stat, content_id)),
+                TurnDiffFileStatus::Modified | TurnDiffFileStatus::Renamed => stats.push(stat),
+            }
+        }
+        for (mut stat, content_id) in added {
+            if content_id.is_some()
+                && let Some(position) = deleted.iter().position(|(_, id)| *id == content_id)
+            {
+                let (source, _) = deleted.remove(position);
+                stat.old_path = Some(source.path);
+                stat.status = TurnDiffFileStatus::Renamed;
+                stat.added_lines = 0;
+                stat.removed_lines = 0;
+            }
+            stats.push(stat);
+        }
+        stats.extend(deleted.into_iter().map(|(stat, _)| stat));
+        stats.sort_by(|a, b| a.path.cmp(&b.path));
+        stats
+    }
+
+    /// [`Self::file_stats`], or `None` when they are unchanged since the last
+    /// call. Starts out as if an empty list had been returned.
+    pub fn file_stats_if_changed(&mut self) -> Option<Vec<TurnDiffFileStat>> {
+        let stats = self.file_stats();
+        if stats == self.last_file_stats {
+            return None;
+        }
+        self.last_file_stats = stats.clone();
+        Some(stats)
+    }
+
+    /// Current paths of every file this turn has patched, including deleted
+    /// files and both sides of renames.
+    pub fn changed_paths(&self) -> Vec<PathBuf> {
+        let mut paths = self
+            .temp_name_to_current_path
+            .values()
+            .chain(self.baseline_file_info.values().map(|info| &info.path))
+            .filter(|path| !path.as_os_str().is_empty())
+            .cloned()
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+
+    /// Baseline contents of every tracked file that existed before this turn
+    /// touched it, keyed by the file's current path. Non-UTF-8 files are
+    /// skipped.
+    pub fn original_contents(&self) -> HashMap<PathBuf, String> {
+        self.baseline_file_info
+            .iter()
+            .filter(|(_, info)| info.oid != ZERO_OID)
+            .filter_map(|(internal, info)| {
+                let path = self.temp_name_to_current_path.get(internal)?;
+                let content = String::from_utf8(info.content.clone()).ok()?;
+                Some((path.clone(), content))
+            })
+            .collect()
+    }
+
+    /// The stat for one tracked file, or `None` when its contents match the
+    /// baseline. Added and deleted files also return a content id so that
+    /// `file_stats` can pair them up as renames.
+    fn get_file_stat(
+        &self,
+        internal_file_name: &str,
+    ) -> Option<(TurnDiffFileStat, Option<String>)> {
+        let baseline = self.baseline_file_info.get(internal_file_name)?;
+        let current_path = self.get_path_for_internal(internal_file_name)?;
+        let current_mode = file_mode_for_path(&current_path).unwrap_or(FileMode::Regular);
+        let right_bytes = blob_bytes(&current_path, current_mode);
+        let left_bytes = (baseline.oid != ZERO_OID).then_some(baseline.content.as_slice());
+        if left_bytes == right_bytes.as_deref() {
+            return None;
+        }
+
+        let status = match (left_bytes, right_bytes.as_deref()) {
+            (None, _) => TurnDiffFileStatus::Added,
+            (Some(_), None) => TurnDiffFileStatus::Deleted,
+            (Some(_), Some(_)) if baseline.path != current_path => TurnDiffFileStatus::Renamed,
+            (Some(_), Some(_)) => TurnDiffFileStatus::Modified,
+        };
+        let left_text = left_bytes.map(std::str::from_utf8).transpose().ok();
+        let right_text = right_bytes
+            .as_deref()
+            .map(std::str::from_utf8)
+            .transpose()
+            .ok();
+        let (added_lines, removed_lines, binary) = match (left_text, right_text) {
+            (Some(left), Some(right)) => {
+                let (added, removed) = count_changed_lines(left.unwrap_or(""), right.unwrap_or(""));
+                (added, removed, false)
+            }
+            _ => (0, 0, true),
+        };
+        let content_id = match status {
+            TurnDiffFileStatus::Added => right_bytes.as_deref(),
+            TurnDiffFileStatus::Deleted => left_bytes,
+            TurnDiffFileStatus::Modified | TurnDiffFileStatus::Renamed => None,
+        }
+        .filter(|bytes| !bytes.is_empty())
+        .map(|bytes| format!("{:x}", git_blob_sha1_hex_bytes(bytes)));
+
+        let stat = TurnDiffFileStat {
+            old_path: (status == TurnDiffFileStatus::Renamed).then(|| baseline.path.clone()),
+            path: current_path,
+            status,
+            added_lines,
+            removed_lines,
+            binary,
+        };
+        Some((stat, content_id))
+    }
+
     fn get_file_diff(&mut self, internal_file_name: &str) -> String {
         let mut aggregated = String::new();
 
@@ -368,6 +552,17 @@ impl TurnDiffTracker {
     }
 }
 
+/// Lines added and removed between `old` and `new`.
+fn count_changed_lines(old: &str, new: &str) -> (u64, u64) {
+    similar::TextDiff::from_lines(old, new)
+        .iter_all_changes()
+        .fold((0, 0), |(added, removed), change| match change.tag() {
+            similar::ChangeTag::Insert => (added + 1, removed),
+            similar::ChangeTag::Delete => (added, removed + 1),
+            similar::ChangeTag::Equal => (added, removed),
+        })
+}
+
 /// Compute the Git SHA-1 blob object ID for the given content (bytes).
 fn git_blob_sha1_hex_bytes(data: &[u8]) -> Output<sha1::Sha1> {
     // Git blob hash is sha1 of: "blob <len>\0<data>"
@@ -893,4 +1088,194 @@ index {ZERO_OID}..{right_oid}
         };
         assert_eq!(combined, expected_combined);
     }
+
+    #[test]
+    fn original_contents_follow_moves_and_skip_additions() {
+        let dir = tempdir().unwrap();
+        let src = dir.path().join("src.txt");
+        let dest = dir.path().join("dst.txt");
+        let added = dir.path().join("added.txt");
+        fs::write(&src, "line\n").unwrap();
+
+        let mut acc = TurnDiffTracker::new();
+        acc.on_patch_begin(&HashMap::from([
+            (
+                src.clone(),
+                FileChange::Update {
+                    unified_diff: "".to_owned(),
+                    move_path: Some(dest.clone()),
+                },
+            ),
+            (
+                added,
+                FileChange::Add {
+                    content: "new\n".to_owned(),
+                },
+            ),
+        ]));
+
+        assert_eq!(
+            acc.original_contents(),
+            HashMap::from([(dest, "line\n".to_string())])
+        );
+    }
+
+    #[test]
+    fn diffs_are_split_by_repository() {
+        let dir = tempdir().unwrap();
+        let frontend = dir.path().join("frontend");
+        let backend = dir.path().join("backend");
+        for repo in [&frontend, &backend] {
+            fs::create_dir_all(repo.join(".git")).unwrap();
+        }
+        let frontend_file = frontend.join("app.ts");
+        let backend_file = backend.join("main.rs");
+
+        let mut acc = TurnDiffTracker::new();
+        acc.on_patch_begin(&HashMap::from([(
+            frontend_file.clone(),
+            FileChange::Add {
+                content: "export {}\n".to_string(),
+            },
+        )]));
+        fs::write(&frontend_file, "export {}\n").unwrap();
+        let single_root = acc.turn_diff_event().unwrap().unwrap();
+        assert!(single_root.root_diffs.is_empty());
+
+        acc.on_patch_begin(&HashMap::from([(
+            backend_file.clone(),
+            FileChange::Add {
+                content: "fn main() {}\n".to_string(),
+            },
+        )]));
+        fs::write(&backend_file, "fn main() {}\n").unwrap();
+        let event = acc.turn_diff_event().unwrap().unwrap();
+
+        assert_eq!(
+            event
+                .root_diffs
+                .iter()
+                .map(|root_diff| root_diff.root.clone())
+                .collect::<Vec<_>>(),
+            vec![backend, frontend]
+        );
+        assert!(event.root_diffs[0].unified_diff.contains("+++ b/main.rs"));
+        assert!(!event.root_diffs[0].unified_diff.contains("app.ts"));
+        assert!(event.root_diffs[1].unified_diff.contains("+++ b/app.ts"));
+        assert!(event.unified_diff.contains("+++ b/main.rs"));
+        assert!(event.unified_diff.contains("+++ b/app.ts"));
+    }
+
+    #[test]
+    fn file_stats_count_lines_and_flag_binary_files() {
+        let dir = tempdir().unwrap();
+        let text = dir.path().join("text.txt");
+        let binary = dir.path().join("image.bin");
+        fs::write(&text, "one\ntwo\nthree\n").unwrap();
+        fs::write(&binary, [0u8, 1, 2]).unwrap();
+
+        let mut acc = TurnDiffTracker::new();
+        assert_eq!(acc.file_stats_if_changed(), None);
+        acc.on_patch_begin(&HashMap::from([
+            (
+                text.clone(),
+                FileChange::Update {
+                    unified_diff: String::new(),
+                    move_path: None,
+                },
+            ),
+            (
+                binary.clone(),
+                FileChange::Update {
+                    unified_diff: String::new(),
+                    move_path: None,
+                },
+            ),
+        ]));
+        fs::write(&text, "one\n2\nthree\nfour\n").unwrap();
+        fs::write(&binary, [0u8, 0xff, 2]).unwrap();
+
+        assert_eq!(
+            acc.file_stats_if_changed(),
+            Some(vec![
+                TurnDiffFileStat {
+                    path: binary,
+                    old_path: None,
+                    status: TurnDiffFileStatus::Modified,
+                    added_lines: 0,
+                    removed_lines: 0,
+                    binary: true,
+                },
+                TurnDiffFileStat {
+                    path: text,
+                    old_path: None,
+                    status: TurnDiffFileStatus::Modified,
+                    added_lines: 2,
+                    removed_lines: 1,
+                    binary: false,
+                },
+            ])
+        );
+        assert_eq!(acc.file_stats_if_changed(), None);
+    }
+
+    #[test]
+    fn file_stats_detect_moves_and_delete_add_renames() {
+        let dir = tempdir().unwrap();
+        let moved_src = dir.path().join("a.txt");
+        let moved_dest = dir.path().join("b.txt");
+        let old = dir.path().join("old.txt");
+        let new = dir.path().join("new.txt");
+        fs::write(&moved_src, "moved\n").unwrap();
+        fs::write(&old, "same\n").unwrap();
+
+        let mut acc = TurnDiffTracker::new();
+        acc.on_patch_begin(&HashMap::from([
+            (
+                moved_src.clone(),
+                FileChange::Update {
+                    unified_diff: String::new(),
+                    move_path: Some(moved_dest.clone()),
+                },
+            ),
+            (
+                old.clone(),
+                FileChange::Delete {
+                    content: "same\n".to_string(),
+                },
+            ),
+            (
+                new.clone(),
+                FileChange::Add {
+                    content: "same\n".to_string(),
+                },
+            ),
+        ]));
+        fs::remove_file(&moved_src).unwrap();
+        fs::write(&moved_dest, "moved\nedited\n").unwrap();
+        fs::remove_file(&old).unwrap();
+        fs::write(&new, "same\n").unwrap();
+
+        assert_eq!(
+            acc.file_stats(),
+            vec![
+                TurnDiffFileStat {
+                    path: moved_dest,
+                    old_path: Some(moved_src),
+                    status: TurnDiffFileStatus::Renamed,
+                    added_lines: 1,
+                    removed_lines: 0,
+                    binary: false,
+                },
+                TurnDiffFileStat {
+                    path: new,
+                    old_path: Some(old),
+                    status: TurnDiffFileStatus::Renamed,
+                    added_lines: 0,
+                    removed_lines: 0,
+                    binary: false,
+                },
+            ]
+        );
+    }
 }
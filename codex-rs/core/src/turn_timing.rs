@@ -48,6 +48,7 @@ struct TurnTimingStateInner {
     started_at: Option<Instant>,
     first_token_at: Option<Instant>,
     first_message_at: Option<Instant>,
+    timed_out: bool,
 }
 
 impl TurnTimingState {
@@ -56,6 +57,17 @@ impl TurnTimingState {
         state.started_at = Some(started_at);
         state.first_token_at = None;
         state.first_message_at = None;
+        state.timed_out = false;
+    }
+
+    /// Marks the turn as past its `turn_timeout_seconds`, so work cancelled
+    /// from now on is reported as timed out rather than aborted by the user.
+    pub(crate) async fn mark_timed_out(&self) {
+        self.state.lock().await.timed_out = true;
+    }
+
+    pub(crate) async fn timed_out(&self) -> bool {
+        self.state.lock().await.timed_out
     }
 
     pub(crate) async fn record_ttft_for_response_event(
@@ -142,6 +154,8 @@ fn response_item_records_turn_ttft(item: &ResponseItem) -> bool {
         ResponseItem::LocalShellCall { .. }
         | ResponseItem::FunctionCall { .. }
         | ResponseItem::CustomToolCall { .. }
+        | ResponseItem::CustomToolCallWithAttachments { .. }
+        | ResponseItem::UnsupportedToolCall { .. }
         | ResponseItem::WebSearchCall { .. }
         | ResponseItem::ImageGenerationCall { .. }
         | ResponseItem::GhostSnapshot { .. }
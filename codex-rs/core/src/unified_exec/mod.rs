@@ -121,10 +121,26 @@ pub(crate) struct UnifiedExecResponse {
     pub session_command: Option<Vec<String>>,
 }
 
+/// A process started by `exec_command` that is still tracked, as reported by
+/// [`UnifiedExecProcessManager::list_processes`].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BackgroundProcessSummary {
+    pub process_id: String,
+    pub command: Vec<String>,
+    /// `None` while the process is running.
+    pub exit_code: Option<i32>,
+    /// Time since the process was last started or written to.
+    pub idle: Duration,
+}
+
 #[derive(Default)]
 pub(crate) struct ProcessStore {
     processes: HashMap<String, ProcessEntry>,
     reserved_process_ids: HashSet<String>,
+    /// PTY-backed processes the client can write to, including ones whose
+    /// `exec_command` call has not returned yet and so are not in
+    /// `processes`.
+    terminals: HashMap<String, Weak<UnifiedExecProcess>>,
 }
 
 impl ProcessStore {
@@ -335,6 +351,99 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn background_processes_can_be_listed_and_terminated() -> anyhow::Result<()> {
+        skip_if_sandbox!(Ok(()));
+
+        let (session, turn) = test_session_and_turn().await;
+        let manager = &session.services.unified_exec_manager;
+
+        let server = exec_command(&session, &turn, "sleep 60", 250).await?;
+        let process_id = server.process_id.expect("expected process id");
+
+        let processes = manager.list_processes().await;
+        assert_eq!(processes.len(), 1);
+        assert_eq!(processes[0].process_id, process_id);
+        assert_eq!(processes[0].exit_code, None);
+        assert_eq!(
+            processes[0].command.last().map(String::as_str),
+            Some("sleep 60")
+        );
+
+        let command = manager.terminate_process(&process_id).await?;
+        assert_eq!(command.last().map(String::as_str), Some("sleep 60"));
+        assert_eq!(manager.list_processes().await, Vec::new());
+        assert!(matches!(
+            manager.terminate_process(&process_id).await,
+            Err(UnifiedExecError::UnknownProcessId { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn client_can_type_into_and_detach_from_a_pending_terminal() -> anyhow::Result<()> {
+        skip_if_sandbox!(Ok(()));
+
+        let (session, turn) = test_session_and_turn().await;
+        let pending = tokio::spawn({
+            let session = Arc::clone(&session);
+            let turn = Arc::clone(&turn);
+            async move {
+                exec_command(
+                    &session,
+                    &turn,
+                    "read -r name; echo \"hello $name\"; sleep 60",
+                    30_000,
+                )
+                .await
+            }
+        });
+
+        // The process is not in `processes` until exec_command returns.
+        let manager = &session.services.unified_exec_manager;
+        let mut process_id = None;
+        for _ in 0..100 {
+            process_id = manager
+                .process_store
+                .lock()
+                .await
+                .terminals
+                .keys()
+                .next()
+                .cloned();
+            if process_id.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let process_id = process_id.expect("expected a pending terminal");
+
+        manager.write_terminal_input(&process_id, "codex\n").await?;
+        manager
+            .resize_terminal(
+                &process_id,
+                codex_utils_pty::TerminalSize {
+                    rows: 40,
+                    cols: 120,
+                },
+            )
+            .await?;
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        manager.detach_terminal(&process_id).await?;
+
+        let response = tokio::time::timeout(Duration::from_secs(10), pending).await???;
+        assert!(
+            response.output.contains("hello codex"),
+            "{}",
+            response.output
+        );
+        assert_eq!(response.process_id.as_deref(), Some(process_id.as_str()));
+
+        manager.terminate_process(&process_id).await?;
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn multi_unified_exec_sessions() -> anyhow::Result<()> {
         skip_if_sandbox!(Ok(()));
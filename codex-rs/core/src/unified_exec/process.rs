@@ -20,6 +20,7 @@ use crate::truncate::TruncationPolicy;
 use crate::truncate::formatted_truncate_text;
 use codex_utils_pty::ExecCommandSession;
 use codex_utils_pty::SpawnedPty;
+use codex_utils_pty::TerminalSize;
 
 use super::UNIFIED_EXEC_OUTPUT_MAX_TOKENS;
 use super::UnifiedExecError;
@@ -43,6 +44,7 @@ pub(crate) struct OutputHandles {
     pub(crate) output_closed: Arc<AtomicBool>,
     pub(crate) output_closed_notify: Arc<Notify>,
     pub(crate) cancellation_token: CancellationToken,
+    pub(crate) detach_notify: Arc<Notify>,
 }
 
 #[derive(Debug)]
@@ -55,6 +57,9 @@ pub(crate) struct UnifiedExecProcess {
     output_closed_notify: Arc<Notify>,
     cancellation_token: CancellationToken,
     output_drained: Arc<Notify>,
+    /// Wakes a pending `exec_command` or `write_stdin` poll so it returns
+    /// the output collected so far, see [`UnifiedExecProcess::detach`].
+    detach_notify: Arc<Notify>,
     output_task: JoinHandle<()>,
     sandbox_type: SandboxType,
     _spawn_lifecycle: SpawnLifecycleHandle,
@@ -107,6 +112,7 @@ impl UnifiedExecProcess {
             output_closed_notify,
             cancellation_token,
             output_drained,
+            detach_notify: Arc::new(Notify::new()),
             output_task,
             sandbox_type,
             _spawn_lifecycle: spawn_lifecycle,
@@ -124,6 +130,7 @@ impl UnifiedExecProcess {
             output_closed: Arc::clone(&self.output_closed),
             output_closed_notify: Arc::clone(&self.output_closed_notify),
             cancellation_token: self.cancellation_token.clone(),
+            detach_notify: Arc::clone(&self.detach_notify),
         }
     }
 
@@ -147,6 +154,17 @@ impl UnifiedExecProcess {
         self.process_handle.exit_code()
     }
 
+    /// Resizes the process's PTY. Fails for processes started without one.
+    pub(super) fn resize(&self, size: TerminalSize) -> anyhow::Result<()> {
+        self.process_handle.resize(size)
+    }
+
+    /// Ends any poll currently waiting on this process's output. The process
+    /// keeps running and can be polled again with `write_stdin`.
+    pub(super) fn detach(&self) {
+        self.detach_notify.notify_waiters();
+    }
+
     pub(super) fn terminate(&self) {
         self.output_closed.store(true, Ordering::Release);
         self.output_closed_notify.notify_waiters();
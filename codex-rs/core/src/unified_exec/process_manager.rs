@@ -4,6 +4,7 @@ use std::collections::HashMap;
 use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::Weak;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use tokio::sync::Notify;
@@ -12,6 +13,9 @@ use tokio::time::Duration;
 use tokio::time::Instant;
 use tokio_util::sync::CancellationToken;
 
+use crate::approval_rules::ApprovalSubject;
+use crate::approval_rules::apply_approval_rule;
+use crate::approval_rules::evaluate_approval_rules;
 use crate::exec_env::create_env;
 use crate::exec_policy::ExecApprovalRequest;
 use crate::protocol::ExecCommandSource;
@@ -28,6 +32,7 @@ use crate::tools::sandboxing::ToolCtx;
 use crate::truncate::TruncationPolicy;
 use crate::truncate::approx_token_count;
 use crate::truncate::formatted_truncate_text;
+use crate::unified_exec::BackgroundProcessSummary;
 use crate::unified_exec::ExecCommandRequest;
 use crate::unified_exec::MAX_UNIFIED_EXEC_PROCESSES;
 use crate::unified_exec::MAX_YIELD_TIME_MS;
@@ -98,6 +103,7 @@ struct PreparedProcessHandles {
     output_closed: Arc<AtomicBool>,
     output_closed_notify: Arc<Notify>,
     cancellation_token: CancellationToken,
+    detach_notify: Arc<Notify>,
     command: Vec<String>,
     process_id: String,
     tty: bool,
@@ -207,7 +213,11 @@ impl UnifiedExecProcessManager {
             output_closed,
             output_closed_notify,
             cancellation_token,
+            detach_notify,
         } = process.output_handles();
+        if request.tty {
+            self.register_terminal(&request.process_id, &process).await;
+        }
         let deadline = start + Duration::from_millis(yield_time_ms);
         let collected = Self::collect_output_until_deadline(
             &output_buffer,
@@ -215,6 +225,7 @@ impl UnifiedExecProcessManager {
             &output_closed,
             &output_closed_notify,
             &cancellation_token,
+            &detach_notify,
             deadline,
         )
         .await;
@@ -343,6 +354,7 @@ impl UnifiedExecProcessManager {
             &output_closed,
             &output_closed_notify,
             &cancellation_token,
+            &detach_notify,
             deadline,
         )
         .await;
@@ -441,6 +453,7 @@ impl UnifiedExecProcessManager {
             output_closed,
             output_closed_notify,
             cancellation_token,
+            detach_notify,
         } = entry.process.output_handles();
 
         Ok(PreparedProcessHandles {
@@ -450,6 +463,7 @@ impl UnifiedExecProcessManager {
             output_closed,
             output_closed_notify,
             cancellation_token,
+            detach_notify,
             command: entry.command.clone(),
             process_id: entry.process_id.clone(),
             tty: entry.tty,
@@ -587,6 +601,14 @@ impl UnifiedExecProcessManager {
                 prefix_rule: request.prefix_rule.clone(),
             })
             .await;
+        let exec_approval_requirement = apply_approval_rule(
+            exec_approval_requirement,
+            evaluate_approval_rules(
+                &context.turn.config.approval_rules,
+                &ApprovalSubject::Command(&request.command),
+            ),
+            context.turn.approval_policy.value(),
+        );
         let req = UnifiedExecToolRequest {
             command: request.command.clone(),
             cwd,
@@ -624,10 +646,15 @@ impl UnifiedExecProcessManager {
         output_closed: &Arc<AtomicBool>,
         output_closed_notify: &Arc<Notify>,
         cancellation_token: &CancellationToken,
+        detach_notify: &Arc<Notify>,
         deadline: Instant,
     ) -> Vec<u8> {
         const POST_EXIT_CLOSE_WAIT_CAP: Duration = Duration::from_millis(50);
 
+        let detached = detach_notify.notified();
+        tokio::pin!(detached);
+        detached.as_mut().enable();
+
         let mut collected: Vec<u8> = Vec::with_capacity(4096);
         let mut exit_signal_received = cancellation_token.is_cancelled();
         let mut post_exit_deadline: Option<Instant> = None;
@@ -668,6 +695,7 @@ impl UnifiedExecProcessManager {
                     tokio::select! {
                         _ = &mut notified => {}
                         _ = &mut closed => {}
+                        _ = &mut detached => break,
                         _ = tokio::time::sleep(close_wait_remaining) => break,
                     }
                     continue;
@@ -680,6 +708,7 @@ impl UnifiedExecProcessManager {
                 tokio::select! {
                     _ = &mut notified => {}
                     _ = &mut exit_notified => exit_signal_received = true,
+                    _ = &mut detached => break,
                     _ = tokio::time::sleep(remaining) => break,
                 }
                 continue;
@@ -745,6 +774,103 @@ impl UnifiedExecProcessManager {
             .map(|(process_id, _, _)| process_id)
     }
 
+    /// Processes that are still tracked, oldest id first. Processes that
+    /// exited since they were last polled are included with their exit code.
+    pub(crate) async fn list_processes(&self) -> Vec<BackgroundProcessSummary> {
+        let store = self.process_store.lock().await;
+        let now = Instant::now();
+        let mut processes: Vec<BackgroundProcessSummary> = store
+            .processes
+            .values()
+            .map(|entry| BackgroundProcessSummary {
+                process_id: entry.process_id.clone(),
+                command: entry.command.clone(),
+                exit_code: entry
+                    .process
+                    .has_exited()
+                    .then(|| entry.process.exit_code())
+                    .flatten(),
+                idle: now.saturating_duration_since(entry.last_used),
+            })
+            .collect();
+        processes.sort_by_key(|process| process.process_id.parse::<u64>().ok());
+        processes
+    }
+
+    /// Remembers a PTY-backed process so the user can type into it, resize it
+    /// or detach from it while `exec_command` is still waiting on it.
+    async fn register_terminal(&self, process_id: &str, process: &Arc<UnifiedExecProcess>) {
+        let mut store = self.process_store.lock().await;
+        store
+            .terminals
+            .retain(|_, terminal| terminal.strong_count() > 0);
+        store
+            .terminals
+            .insert(process_id.to_string(), Arc::downgrade(process));
+    }
+
+    async fn terminal(
+        &self,
+        process_id: &str,
+    ) -> Result<Arc<UnifiedExecProcess>, UnifiedExecError> {
+        let store = self.process_store.lock().await;
+        store
+            .terminals
+            .get(process_id)
+            .and_then(Weak::upgrade)
+            .filter(|process| !process.has_exited())
+            .ok_or_else(|| UnifiedExecError::UnknownProcessId {
+                process_id: process_id.to_string(),
+            })
+    }
+
+    /// Writes keystrokes typed by the user to a running process started with
+    /// `tty: true`.
+    pub(crate) async fn write_terminal_input(
+        &self,
+        process_id: &str,
+        data: &str,
+    ) -> Result<(), UnifiedExecError> {
+        let process = self.terminal(process_id).await?;
+        Self::send_input(&process.writer_sender(), data.as_bytes()).await
+    }
+
+    pub(crate) async fn resize_terminal(
+        &self,
+        process_id: &str,
+        size: codex_utils_pty::TerminalSize,
+    ) -> Result<(), UnifiedExecError> {
+        let process = self.terminal(process_id).await?;
+        process
+            .resize(size)
+            .map_err(|err| UnifiedExecError::Resize {
+                message: err.to_string(),
+            })
+    }
+
+    /// Ends the `exec_command` or `write_stdin` call waiting on a process, if
+    /// any, with the output so far. The process keeps running.
+    pub(crate) async fn detach_terminal(&self, process_id: &str) -> Result<(), UnifiedExecError> {
+        self.terminal(process_id).await?.detach();
+        Ok(())
+    }
+
+    /// Terminates one process and stops tracking it. Returns its command.
+    pub(crate) async fn terminate_process(
+        &self,
+        process_id: &str,
+    ) -> Result<Vec<String>, UnifiedExecError> {
+        let entry = self.process_store.lock().await.remove(process_id);
+        let Some(entry) = entry else {
+            return Err(UnifiedExecError::UnknownProcessId {
+                process_id: process_id.to_string(),
+            });
+        };
+        Self::unregister_network_approval_for_entry(&entry).await;
+        entry.process.terminate();
+        Ok(entry.command)
+    }
+
     pub(crate) async fn terminate_all_processes(&self) {
         let entries: Vec<ProcessEntry> = {
             let mut processes = self.process_store.lock().await;
@@ -0,0 +1,306 @@
+//! Files the model registered with the `watch_files` tool.
+//!
+//! Watched files are compared against a snapshot of their size and
+//! modification time at the start of each regular turn, and the differences
+//! are reported to the model as a synthetic `watch_files` call. Files the
+//! agent patched itself are re-stamped at the end of the turn from the turn's
+//! diff tracker, so only changes made by someone else are reported.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use codex_protocol::models::FunctionCallOutputPayload;
+use codex_protocol::models::ResponseItem;
+use ignore::WalkBuilder;
+use ignore::overrides::Override;
+use ignore::overrides::OverrideBuilder;
+
+use crate::tools::handlers::WATCH_FILES_TOOL_NAME;
+
+/// Files tracked across all patterns, so a broad glob such as `**/*` cannot
+/// make every turn walk a huge tree.
+pub(crate) const MAX_WATCHED_FILES: usize = 2_000;
+/// Paths listed per kind of change in one notification; the rest are counted.
+const MAX_REPORTED_PATHS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileStamp {
+    fn read(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        metadata.is_file().then(|| Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+/// Watched glob patterns and the last seen state of the files they match.
+#[derive(Debug, Default)]
+pub(crate) struct WatchedFiles {
+    root: PathBuf,
+    patterns: Vec<String>,
+    matcher: Option<Override>,
+    snapshot: BTreeMap<PathBuf, FileStamp>,
+}
+
+/// Watched files changed since the previous snapshot, relative to the watch
+/// root and sorted.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct WatchedFileChanges {
+    pub(crate) created: Vec<PathBuf>,
+    pub(crate) modified: Vec<PathBuf>,
+    pub(crate) deleted: Vec<PathBuf>,
+}
+
+impl WatchedFileChanges {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.modified.is_empty() && self.deleted.is_empty()
+    }
+
+    /// The synthetic `watch_files` call and output that tell the model about
+    /// these changes.
+    pub(crate) fn to_response_items(&self, call_id: String) -> [ResponseItem; 2] {
+        let mut sections =
+            vec!["Watched files changed outside your own edits since the last turn.".to_string()];
+        for (label, paths) in [
+            ("Created", &self.created),
+            ("Modified", &self.modified),
+            ("Deleted", &self.deleted),
+        ] {
+            if paths.is_empty() {
+                continue;
+            }
+            let mut lines = paths
+                .iter()
+                .take(MAX_REPORTED_PATHS)
+                .map(|path| format!("- {}", path.display()))
+                .collect::<Vec<_>>();
+            if paths.len() > MAX_REPORTED_PATHS {
+                lines.push(format!("- … and {} more", paths.len() - MAX_REPORTED_PATHS));
+            }
+            sections.push(format!("{label}:\n{}", lines.join("\n")));
+        }
+        [
+            ResponseItem::FunctionCall {
+                id: None,
+                name: WATCH_FILES_TOOL_NAME.to_string(),
+                arguments: "{}".to_string(),
+                call_id: call_id.clone(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id,
+                output: FunctionCallOutputPayload::from_text(sections.join("\n\n")),
+            },
+        ]
+    }
+}
+
+impl WatchedFiles {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    pub(crate) fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// Number of files currently matched by the watched patterns.
+    pub(crate) fn file_count(&self) -> usize {
+        self.snapshot.len()
+    }
+
+    /// Adds gitignore-style `patterns`, relative to `root`, and snapshots the
+    /// files they match. Watching from a different root re-anchors the
+    /// patterns already watched.
+    pub(crate) fn watch(&mut self, root: &Path, patterns: &[String]) -> Result<(), String> {
+        let mut watched = self.patterns.clone();
+        for pattern in patterns.iter().map(|pattern| pattern.trim()) {
+            if !pattern.is_empty() && !watched.iter().any(|existing| existing == pattern) {
+                watched.push(pattern.to_string());
+            }
+        }
+        self.rebuild(root, watched)
+    }
+
+    /// Stops watching `patterns`, or every pattern when `patterns` is empty.
+    pub(crate) fn unwatch(&mut self, patterns: &[String]) -> Result<(), String> {
+        let watched = if patterns.is_empty() {
+            Vec::new()
+        } else {
+            self.patterns
+                .iter()
+                .filter(|existing| !patterns.iter().any(|pattern| pattern.trim() == *existing))
+                .cloned()
+                .collect()
+        };
+        let root = self.root.clone();
+        self.rebuild(&root, watched)
+    }
+
+    /// Re-stamps the watched files among `paths`, typically the paths the
+    /// agent patched this turn, so their changes are not reported.
+    pub(crate) fn acknowledge(&mut self, paths: &[PathBuf]) {
+        let Some(matcher) = &self.matcher else {
+            return;
+        };
+        for path in paths {
+            let Ok(relative) = path.strip_prefix(&self.root) else {
+                continue;
+            };
+            match FileStamp::read(path) {
+                Some(stamp) => {
+                    if matcher.matched(relative, false).is_whitelist()
+                        && (self.snapshot.contains_key(relative)
+                            || self.snapshot.len() < MAX_WATCHED_FILES)
+                    {
+                        self.snapshot.insert(relative.to_path_buf(), stamp);
+                    }
+                }
+                None => {
+                    self.snapshot.remove(relative);
+                }
+            }
+        }
+    }
+
+    /// Rescans the watched files and returns what changed since the last
+    /// snapshot, which is then replaced.
+    pub(crate) fn take_changes(&mut self) -> WatchedFileChanges {
+        if self.matcher.is_none() {
+            return WatchedFileChanges::default();
+        }
+        let current = self.scan();
+        let mut changes = WatchedFileChanges::default();
+        for (path, stamp) in &current {
+            match self.snapshot.get(path) {
+                None => changes.created.push(path.clone()),
+                Some(previous) if previous != stamp => changes.modified.push(path.clone()),
+                Some(_) => {}
+            }
+        }
+        changes.deleted = self
+            .snapshot
+            .keys()
+            .filter(|path| !current.contains_key(*path))
+            .cloned()
+            .collect();
+        self.snapshot = current;
+        changes
+    }
+
+    fn rebuild(&mut self, root: &Path, patterns: Vec<String>) -> Result<(), String> {
+        self.matcher = if patterns.is_empty() {
+            None
+        } else {
+            let mut builder = OverrideBuilder::new(root);
+            for pattern in &patterns {
+                builder
+                    .add(pattern)
+                    .map_err(|err| format!("invalid pattern `{pattern}`: {err}"))?;
+            }
+            Some(builder.build().map_err(|err| err.to_string())?)
+        };
+        self.root = root.to_path_buf();
+        self.patterns = patterns;
+        self.snapshot = self.scan();
+        Ok(())
+    }
+
+    fn scan(&self) -> BTreeMap<PathBuf, FileStamp> {
+        let Some(matcher) = &self.matcher else {
+            return BTreeMap::new();
+        };
+        WalkBuilder::new(&self.root)
+            .require_git(false)
+            .hidden(false)
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .overrides(matcher.clone())
+            .build()
+            .filter_map(Result::ok)
+            .filter(|entry| {
+                entry
+                    .file_type()
+                    .is_some_and(|file_type| file_type.is_file())
+            })
+            .filter_map(|entry| {
+                let stamp = FileStamp::read(entry.path())?;
+                let path = entry.path().strip_prefix(&self.root).ok()?.to_path_buf();
+                Some((path, stamp))
+            })
+            .take(MAX_WATCHED_FILES)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn write(path: &Path, contents: &str) {
+        std::fs::create_dir_all(path.parent().expect("parent")).expect("create dir");
+        std::fs::write(path, contents).expect("write file");
+    }
+
+    #[test]
+    fn reports_external_changes_but_not_acknowledged_edits() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let root = dir.path();
+        write(&root.join("src/lib.rs"), "fn a() {}");
+        write(&root.join("src/main.rs"), "fn main() {}");
+        write(&root.join("README.md"), "readme");
+
+        let mut watched = WatchedFiles::default();
+        watched
+            .watch(root, &["src/**/*.rs".to_string()])
+            .expect("watch");
+        assert_eq!(watched.file_count(), 2);
+        assert_eq!(watched.take_changes(), WatchedFileChanges::default());
+
+        // The agent's own patch is acknowledged; someone else's edits are not.
+        write(&root.join("src/lib.rs"), "fn a() { todo!() }");
+        watched.acknowledge(&[root.join("src/lib.rs")]);
+        write(&root.join("src/main.rs"), "fn main() { run() }");
+        write(&root.join("src/new.rs"), "");
+        write(&root.join("README.md"), "changed readme");
+
+        assert_eq!(
+            watched.take_changes(),
+            WatchedFileChanges {
+                created: vec![PathBuf::from("src/new.rs")],
+                modified: vec![PathBuf::from("src/main.rs")],
+                deleted: Vec::new(),
+            }
+        );
+
+        std::fs::remove_file(root.join("src/new.rs")).expect("remove");
+        assert_eq!(
+            watched.take_changes(),
+            WatchedFileChanges {
+                created: Vec::new(),
+                modified: Vec::new(),
+                deleted: vec![PathBuf::from("src/new.rs")],
+            }
+        );
+
+        watched.unwatch(&[]).expect("unwatch");
+        assert!(watched.is_empty());
+        write(&root.join("src/main.rs"), "fn main() {}");
+        assert!(watched.take_changes().is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mut watched = WatchedFiles::default();
+        assert!(watched.watch(dir.path(), &["src/[".to_string()]).is_err());
+        assert!(watched.is_empty());
+    }
+}
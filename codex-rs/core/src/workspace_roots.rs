@@ -0,0 +1,125 @@
+//! Additional workspace roots declared with `workspace_roots` in config.
+//!
+//! A session still has a single cwd, the primary root. Every extra root gets a
+//! short name derived from its directory name, and tools accept paths such as
+//! `@backend/src/main.rs` relative to it.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Prefix that marks a root-relative path, as in `@backend/src`.
+const ROOT_PREFIX: char = '@';
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct WorkspaceRoot {
+    pub(crate) name: String,
+    pub(crate) path: PathBuf,
+}
+
+/// Names each root after its directory, adding `-2`, `-3`, ... when two roots
+/// share a directory name.
+pub(crate) fn named_workspace_roots(cwd: &Path, paths: &[PathBuf]) -> Vec<WorkspaceRoot> {
+    let mut taken = HashSet::new();
+    let mut roots = Vec::with_capacity(paths.len());
+    for path in paths {
+        if path == cwd || roots.iter().any(|root: &WorkspaceRoot| &root.path == path) {
+            continue;
+        }
+        let base = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "root".to_string());
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while !taken.insert(name.clone()) {
+            name = format!("{base}-{suffix}");
+            suffix += 1;
+        }
+        roots.push(WorkspaceRoot {
+            name,
+            path: path.clone(),
+        });
+    }
+    roots
+}
+
+/// Resolves `@<root>` or `@<root>/<path>` against the named root. Returns
+/// `None` for any other path, including `@` paths naming an unknown root.
+pub(crate) fn resolve_root_relative(roots: &[WorkspaceRoot], path: &str) -> Option<PathBuf> {
+    let rest = path.strip_prefix(ROOT_PREFIX)?;
+    let (name, relative) = rest.split_once(['/', '\\']).unwrap_or((rest, ""));
+    let root = roots.iter().find(|root| root.name == name)?;
+    Some(if relative.is_empty() {
+        root.path.clone()
+    } else {
+        root.path.join(relative)
+    })
+}
+
+/// Sentence appended to the description of path parameters that accept
+/// root-relative paths.
+pub(crate) fn root_relative_path_note(roots: &[WorkspaceRoot]) -> Option<String> {
+    if roots.is_empty() {
+        return None;
+    }
+    let roots = roots
+        .iter()
+        .map(|root| format!("`{ROOT_PREFIX}{}` ({})", root.name, root.path.display()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Some(format!(
+        "Paths in another workspace root can be written relative to it as `{ROOT_PREFIX}<root>/<path>`. Roots: {roots}."
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn roots_are_named_after_their_directories() {
+        let roots = named_workspace_roots(
+            Path::new("/work/frontend"),
+            &[
+                PathBuf::from("/work/frontend"),
+                PathBuf::from("/work/backend"),
+                PathBuf::from("/vendor/backend"),
+                PathBuf::from("/work/backend"),
+            ],
+        );
+        assert_eq!(
+            roots,
+            vec![
+                WorkspaceRoot {
+                    name: "backend".to_string(),
+                    path: PathBuf::from("/work/backend"),
+                },
+                WorkspaceRoot {
+                    name: "backend-2".to_string(),
+                    path: PathBuf::from("/vendor/backend"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_only_known_root_relative_paths() {
+        let roots = named_workspace_roots(
+            Path::new("/work/frontend"),
+            &[PathBuf::from("/work/backend")],
+        );
+        assert_eq!(
+            resolve_root_relative(&roots, "@backend/src/main.rs"),
+            Some(PathBuf::from("/work/backend/src/main.rs"))
+        );
+        assert_eq!(
+            resolve_root_relative(&roots, "@backend"),
+            Some(PathBuf::from("/work/backend"))
+        );
+        assert_eq!(resolve_root_relative(&roots, "@docs/index.md"), None);
+        assert_eq!(resolve_root_relative(&roots, "backend/src"), None);
+    }
+}
@@ -0,0 +1,69 @@
+//! Tool plugin loaded by the `dynamic-plugins` tests of `codex-core`. It is
+//! compiled on its own with `rustc --crate-type=cdylib`, so it mirrors the
+//! plugin ABI types instead of depending on `codex-core`.
+
+use std::ffi::CStr;
+use std::ffi::CString;
+use std::ffi::c_char;
+
+type ToolCallFnV1 = unsafe extern "C" fn(arguments: *const c_char, output: *mut *mut c_char) -> i32;
+
+#[repr(C)]
+pub struct ToolDefV1 {
+    name: *const c_char,
+    description: *const c_char,
+    input_schema: *const c_char,
+    call: ToolCallFnV1,
+}
+
+#[repr(C)]
+pub struct ToolPluginV1 {
+    abi_version: u32,
+    tools: *const ToolDefV1,
+    tool_count: usize,
+    free_output: unsafe extern "C" fn(output: *mut c_char),
+}
+
+struct Tools([ToolDefV1; 1]);
+
+struct Plugin(ToolPluginV1);
+
+// SAFETY: both only point at immutable statics.
+unsafe impl Sync for Tools {}
+unsafe impl Sync for Plugin {}
+
+static TOOLS: Tools = Tools([ToolDefV1 {
+    name: c"plugin_echo".as_ptr(),
+    description: c"Echoes the arguments back.".as_ptr(),
+    input_schema: cr#"{"type": "object", "properties": {}}"#.as_ptr(),
+    call: echo,
+}]);
+
+static PLUGIN: Plugin = Plugin(ToolPluginV1 {
+    abi_version: 1,
+    tools: &raw const TOOLS.0 as *const ToolDefV1,
+    tool_count: 1,
+    free_output,
+});
+
+#[unsafe(no_mangle)]
+pub extern "C" fn codex_tool_plugin_v1() -> *const ToolPluginV1 {
+    &PLUGIN.0
+}
+
+unsafe extern "C" fn echo(arguments: *const c_char, output: *mut *mut c_char) -> i32 {
+    // SAFETY: the host passes NUL-terminated arguments and a valid output
+    // place.
+    unsafe {
+        let arguments = CStr::from_ptr(arguments).to_string_lossy();
+        *output = CString::new(format!("plugin received {arguments}"))
+            .unwrap_or_default()
+            .into_raw();
+    }
+    0
+}
+
+unsafe extern "C" fn free_output(output: *mut c_char) {
+    // SAFETY: every output was allocated by `CString::into_raw`.
+    drop(unsafe { CString::from_raw(output) });
+}
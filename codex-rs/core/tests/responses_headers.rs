@@ -53,6 +53,7 @@ async fn responses_stream_includes_subagent_header_on_review() {
         request_max_retries: Some(0),
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
+        retry: None,
         requires_openai_auth: false,
         supports_websockets: false,
     };
@@ -165,6 +166,7 @@ async fn responses_stream_includes_subagent_header_on_other() {
         request_max_retries: Some(0),
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
+        retry: None,
         requires_openai_auth: false,
         supports_websockets: false,
     };
@@ -272,6 +274,7 @@ async fn responses_respects_model_info_overrides_from_config() {
         request_max_retries: Some(0),
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
+        retry: None,
         requires_openai_auth: false,
         supports_websockets: false,
     };
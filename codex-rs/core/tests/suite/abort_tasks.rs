@@ -4,18 +4,27 @@ use std::time::Duration;
 
 use codex_protocol::protocol::EventMsg;
 use codex_protocol::protocol::Op;
+use codex_protocol::protocol::TurnAbortReason;
+use codex_protocol::protocol::TurnCancelledEvent;
 use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
 use core_test_support::responses::ev_completed;
 use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_message_item_added;
+use core_test_support::responses::ev_output_text_delta;
 use core_test_support::responses::ev_response_created;
 use core_test_support::responses::mount_sse_once;
 use core_test_support::responses::mount_sse_sequence;
 use core_test_support::responses::sse;
 use core_test_support::responses::start_mock_server;
+use core_test_support::streaming_sse::StreamingSseChunk;
+use core_test_support::streaming_sse::start_streaming_sse_server;
 use core_test_support::test_codex::test_codex;
 use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
 use regex_lite::Regex;
 use serde_json::json;
+use tokio::sync::oneshot;
 
 /// Integration test: spawn a long‑running shell_command tool via a mocked Responses SSE
 /// function call, then interrupt the session and expect TurnAborted.
@@ -239,3 +248,156 @@ async fn interrupt_persists_turn_aborted_marker_in_next_request() {
         "expected <turn_aborted> marker in follow-up request"
     );
 }
+
+/// Interrupting a turn while the model is still streaming its reply emits
+/// TurnCancelled with the text streamed so far, ahead of TurnAborted.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn interrupt_mid_stream_emits_turn_cancelled_with_partial_output() {
+    // Never released, so the response stays open until the interrupt.
+    let (_hold_completed_tx, hold_completed_rx) = oneshot::channel();
+    let chunks = vec![
+        StreamingSseChunk {
+            gate: None,
+            body: sse(vec![ev_response_created("resp-partial")]),
+        },
+        StreamingSseChunk {
+            gate: None,
+            body: sse(vec![ev_message_item_added("msg-partial", "")]),
+        },
+        StreamingSseChunk {
+            gate: None,
+            body: sse(vec![ev_output_text_delta("Hello, ")]),
+        },
+        StreamingSseChunk {
+            gate: None,
+            body: sse(vec![ev_output_text_delta("wor")]),
+        },
+        StreamingSseChunk {
+            gate: Some(hold_completed_rx),
+            body: sse(vec![ev_completed("resp-partial")]),
+        },
+    ];
+    let (server, _completions) = start_streaming_sse_server(vec![chunks]).await;
+
+    let codex = test_codex()
+        .with_model("gpt-5.1")
+        .build_with_streaming_server(&server)
+        .await
+        .unwrap()
+        .codex;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "say hello".into(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+        })
+        .await
+        .unwrap();
+
+    wait_for_event(
+        &codex,
+        |ev| matches!(ev, EventMsg::AgentMessageContentDelta(delta) if delta.delta == "wor"),
+    )
+    .await;
+    codex.submit(Op::Interrupt).await.unwrap();
+
+    let EventMsg::TurnCancelled(cancelled) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnCancelled(_))).await
+    else {
+        unreachable!("wait_for_event returned a non-matching event");
+    };
+    assert_eq!(
+        cancelled,
+        TurnCancelledEvent {
+            turn_id: cancelled.turn_id.clone(),
+            reason: TurnAbortReason::Interrupted,
+            partial_output: Some("Hello, wor".to_string()),
+        }
+    );
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnAborted(_))).await;
+
+    server.shutdown().await;
+}
+
+/// A turn that outlives `turn_timeout_seconds` has its running tool call
+/// cancelled with a timeout output, emits TurnTimedOut, and still completes
+/// with the model's wrap-up reply.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn turn_timeout_cancels_tools_and_asks_model_to_wrap_up() {
+    let call_id = "call-turn-timeout";
+    let args = json!({
+        "command": "sleep 60",
+        "timeout_ms": 60_000
+    })
+    .to_string();
+    let first_body = sse(vec![
+        ev_response_created("resp-timeout"),
+        ev_function_call(call_id, "shell_command", &args),
+        ev_completed("resp-timeout"),
+    ]);
+    let wrap_up_body = sse(vec![
+        ev_response_created("resp-wrap-up"),
+        ev_assistant_message("msg-wrap-up", "Ran out of time before the sleep finished."),
+        ev_completed("resp-wrap-up"),
+    ]);
+
+    let server = start_mock_server().await;
+    let response_mock = mount_sse_sequence(&server, vec![first_body, wrap_up_body]).await;
+
+    let codex = test_codex()
+        .with_model("gpt-5.1")
+        .with_config(|config| {
+            config.turn_timeout_seconds = Some(1);
+        })
+        .build(&server)
+        .await
+        .unwrap()
+        .codex;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "start sleep".into(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+        })
+        .await
+        .unwrap();
+
+    let EventMsg::TurnTimedOut(timed_out) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnTimedOut(_))).await
+    else {
+        unreachable!("wait_for_event returned a non-matching event");
+    };
+    assert_eq!(timed_out.timeout_seconds, 1);
+    let EventMsg::TurnComplete(complete) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnComplete(_))).await
+    else {
+        unreachable!("wait_for_event returned a non-matching event");
+    };
+    assert_eq!(
+        complete.last_agent_message.as_deref(),
+        Some("Ran out of time before the sleep finished.")
+    );
+
+    let requests = response_mock.requests();
+    assert_eq!(requests.len(), 2, "expected a wrap-up request");
+    let output = response_mock
+        .function_call_output_text(call_id)
+        .expect("missing function_call_output text");
+    assert!(
+        output.ends_with("timed out: the turn reached its time limit"),
+        "unexpected tool output: {output}"
+    );
+    assert!(
+        requests[1]
+            .message_input_texts("developer")
+            .iter()
+            .any(|text| text.contains("This turn reached its time limit.")),
+        "expected wrap-up guidance in the follow-up request"
+    );
+}
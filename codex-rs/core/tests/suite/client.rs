@@ -1734,6 +1734,7 @@ async fn azure_responses_request_includes_store_and_reasoning_ids() {
         request_max_retries: Some(0),
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
+        retry: None,
         requires_openai_auth: false,
         supports_websockets: false,
     };
@@ -1826,6 +1827,7 @@ async fn azure_responses_request_includes_store_and_reasoning_ids() {
             working_directory: None,
             env: None,
             user: None,
+            justification: None,
         }),
     });
     prompt.input.push(ResponseItem::CustomToolCall {
@@ -2334,6 +2336,7 @@ async fn azure_overrides_assign_properties_used_for_responses_url() {
         request_max_retries: None,
         stream_max_retries: None,
         stream_idle_timeout_ms: None,
+        retry: None,
         requires_openai_auth: false,
         supports_websockets: false,
     };
@@ -2418,6 +2421,7 @@ async fn env_var_overrides_loaded_auth() {
         request_max_retries: None,
         stream_max_retries: None,
         stream_idle_timeout_ms: None,
+        retry: None,
         requires_openai_auth: false,
         supports_websockets: false,
     };
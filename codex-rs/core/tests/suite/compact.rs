@@ -626,6 +626,52 @@ async fn manual_compact_emits_context_compaction_items() {
     assert!(legacy_event);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn manual_compact_emits_progress_events_without_history() {
+    skip_if_no_network!();
+
+    let server = start_mock_server().await;
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_assistant_message("m1", SUMMARY_TEXT),
+            ev_completed("r1"),
+        ]),
+    )
+    .await;
+
+    let model_provider = non_openai_model_provider(&server);
+    let codex = test_codex()
+        .with_config(move |config| {
+            config.model_provider = model_provider;
+            set_test_compact_prompt(config);
+        })
+        .build(&server)
+        .await
+        .expect("build codex")
+        .codex;
+
+    codex.submit(Op::Compact).await.expect("run /compact");
+
+    let mut progress = Vec::new();
+    loop {
+        match codex.next_event().await.expect("next event").msg {
+            EventMsg::CompactionStarted(event) => {
+                progress.push(format!("started remote={}", event.remote));
+            }
+            EventMsg::CompactionFinished(event) => {
+                assert!(event.tokens_before.is_some(), "{event:?}");
+                assert!(event.tokens_after.is_some(), "{event:?}");
+                progress.push("finished".to_string());
+            }
+            EventMsg::TurnComplete(_) => break,
+            _ => {}
+        }
+    }
+
+    assert_eq!(progress, vec!["started remote=false", "finished"]);
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn multiple_auto_compact_per_task_runs_after_token_limit_hit() {
     skip_if_no_network!();
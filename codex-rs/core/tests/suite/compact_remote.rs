@@ -948,6 +948,7 @@ async fn remote_manual_compact_emits_context_compaction_items() -> Result<()> {
     let mut started_item = None;
     let mut completed_item = None;
     let mut legacy_event = false;
+    let mut progress = Vec::new();
     let mut saw_turn_complete = false;
 
     while !saw_turn_complete || started_item.is_none() || completed_item.is_none() || !legacy_event
@@ -969,6 +970,13 @@ async fn remote_manual_compact_emits_context_compaction_items() -> Result<()> {
             EventMsg::ContextCompacted(_) => {
                 legacy_event = true;
             }
+            EventMsg::CompactionStarted(event) => {
+                progress.push(format!("started remote={}", event.remote));
+            }
+            EventMsg::CompactionFinished(event) => {
+                assert!(event.tokens_before.is_some(), "{event:?}");
+                progress.push("finished".to_string());
+            }
             EventMsg::TurnComplete(_) => {
                 saw_turn_complete = true;
             }
@@ -980,13 +988,14 @@ async fn remote_manual_compact_emits_context_compaction_items() -> Result<()> {
     let completed_item = completed_item.expect("context compaction item completed");
     assert_eq!(started_item.id, completed_item.id);
     assert!(legacy_event);
+    assert_eq!(progress, vec!["started remote=true", "finished"]);
     assert_eq!(compact_mock.requests().len(), 1);
 
     Ok(())
 }
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-async fn remote_manual_compact_failure_emits_task_error_event() -> Result<()> {
+async fn remote_manual_compact_failure_falls_back_to_local_compaction() -> Result<()> {
     skip_if_no_network!(Ok(()));
 
     let harness = TestCodexHarness::with_builder(
@@ -1009,6 +1018,14 @@ async fn remote_manual_compact_failure_emits_task_error_event() -> Result<()> {
         serde_json::json!({ "output": "invalid compact payload shape" }),
     )
     .await;
+    let local_compact_mock = mount_sse_once(
+        harness.server(),
+        sse(vec![
+            responses::ev_assistant_message("m2", "LOCAL_SUMMARY"),
+            responses::ev_completed("resp-2"),
+        ]),
+    )
+    .await;
 
     codex
         .submit(Op::UserInput {
@@ -1023,23 +1040,31 @@ async fn remote_manual_compact_failure_emits_task_error_event() -> Result<()> {
 
     codex.submit(Op::Compact).await?;
 
-    let error_message = wait_for_event_match(&codex, |event| match event {
-        EventMsg::Error(err) => Some(err.message.clone()),
+    let warning_message = wait_for_event_match(&codex, |event| match event {
+        EventMsg::Error(err) => panic!("unexpected error event: {}", err.message),
+        EventMsg::Warning(warning) => Some(warning.message.clone()),
         _ => None,
     })
     .await;
     assert!(
-        error_message.contains("Error running remote compact task"),
-        "expected remote compact task error prefix, got {error_message}"
+        warning_message.starts_with("Remote compaction failed, compacting locally instead"),
+        "expected local fallback warning, got {warning_message}"
     );
     assert!(
-        error_message.contains("invalid compact payload shape")
-            || error_message.contains("invalid type: string"),
-        "expected invalid compact payload details, got {error_message}"
+        warning_message.contains("invalid compact payload shape")
+            || warning_message.contains("invalid type: string"),
+        "expected invalid compact payload details, got {warning_message}"
     );
-    wait_for_event(&codex, |event| matches!(event, EventMsg::TurnComplete(_))).await;
+    wait_for_event(&codex, |event| {
+        if let EventMsg::Error(err) = event {
+            panic!("unexpected error event: {}", err.message);
+        }
+        matches!(event, EventMsg::TurnComplete(_))
+    })
+    .await;
 
     assert_eq!(compact_mock.requests().len(), 1);
+    assert_eq!(local_compact_mock.requests().len(), 1);
 
     Ok(())
 }
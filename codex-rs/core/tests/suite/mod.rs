@@ -122,9 +122,11 @@ mod sqlite_state;
 mod stream_error_allows_next_turn;
 mod stream_no_completed;
 mod subagent_notifications;
+mod system_note;
 mod text_encoding_fix;
 mod tool_harness;
 mod tool_parallelism;
+mod tool_router;
 mod tools;
 mod truncation;
 mod turn_state;
@@ -133,6 +135,7 @@ mod unified_exec;
 mod unstable_features_warning;
 mod user_notification;
 mod user_shell_cmd;
+mod verify;
 mod view_image;
 mod web_search;
 mod websocket_fallback;
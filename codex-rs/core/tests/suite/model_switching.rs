@@ -68,6 +68,7 @@ fn test_model_info(
         web_search_tool_type: Default::default(),
         truncation_policy: TruncationPolicyConfig::bytes(10_000),
         supports_parallel_tool_calls: false,
+        supports_tool_calls: true,
         supports_image_detail_original: false,
         context_window: Some(272_000),
         auto_compact_token_limit: None,
@@ -688,6 +689,7 @@ async fn model_switch_to_smaller_model_updates_token_context_window() -> Result<
         web_search_tool_type: Default::default(),
         truncation_policy: TruncationPolicyConfig::bytes(10_000),
         supports_parallel_tool_calls: false,
+        supports_tool_calls: true,
         supports_image_detail_original: false,
         context_window: Some(large_context_window),
         auto_compact_token_limit: None,
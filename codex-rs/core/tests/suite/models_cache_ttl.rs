@@ -345,6 +345,7 @@ fn test_remote_model(slug: &str, priority: i32) -> ModelInfo {
         web_search_tool_type: Default::default(),
         truncation_policy: TruncationPolicyConfig::bytes(10_000),
         supports_parallel_tool_calls: false,
+        supports_tool_calls: true,
         supports_image_detail_original: false,
         context_window: Some(272_000),
         auto_compact_token_limit: None,
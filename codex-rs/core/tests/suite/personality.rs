@@ -650,6 +650,7 @@ async fn remote_model_friendly_personality_instructions_with_feature() -> anyhow
         web_search_tool_type: Default::default(),
         truncation_policy: TruncationPolicyConfig::bytes(10_000),
         supports_parallel_tool_calls: false,
+        supports_tool_calls: true,
         supports_image_detail_original: false,
         context_window: Some(128_000),
         auto_compact_token_limit: None,
@@ -765,6 +766,7 @@ async fn user_turn_personality_remote_model_template_includes_update_message() -
         web_search_tool_type: Default::default(),
         truncation_policy: TruncationPolicyConfig::bytes(10_000),
         supports_parallel_tool_calls: false,
+        supports_tool_calls: true,
         supports_image_detail_original: false,
         context_window: Some(128_000),
         auto_compact_token_limit: None,
@@ -304,6 +304,7 @@ async fn remote_models_remote_model_uses_unified_exec() -> Result<()> {
         web_search_tool_type: Default::default(),
         truncation_policy: TruncationPolicyConfig::bytes(10_000),
         supports_parallel_tool_calls: false,
+        supports_tool_calls: true,
         supports_image_detail_original: false,
         context_window: Some(272_000),
         auto_compact_token_limit: None,
@@ -546,6 +547,7 @@ async fn remote_models_apply_remote_base_instructions() -> Result<()> {
         web_search_tool_type: Default::default(),
         truncation_policy: TruncationPolicyConfig::bytes(10_000),
         supports_parallel_tool_calls: false,
+        supports_tool_calls: true,
         supports_image_detail_original: false,
         context_window: Some(272_000),
         auto_compact_token_limit: None,
@@ -1012,6 +1014,7 @@ fn test_remote_model_with_policy(
         web_search_tool_type: Default::default(),
         truncation_policy,
         supports_parallel_tool_calls: false,
+        supports_tool_calls: true,
         supports_image_detail_original: false,
         context_window: Some(272_000),
         auto_compact_token_limit: None,
@@ -105,6 +105,8 @@ async fn stdio_server_round_trip() -> anyhow::Result<()> {
                     disabled_tools: None,
                     scopes: None,
                     oauth_resource: None,
+                    max_concurrent_calls: None,
+                    max_calls_per_minute: None,
                 },
             );
             config
@@ -249,6 +251,8 @@ async fn stdio_image_responses_round_trip() -> anyhow::Result<()> {
                     disabled_tools: None,
                     scopes: None,
                     oauth_resource: None,
+                    max_concurrent_calls: None,
+                    max_calls_per_minute: None,
                 },
             );
             config
@@ -410,6 +414,7 @@ async fn stdio_image_responses_are_sanitized_for_text_only_model() -> anyhow::Re
                 web_search_tool_type: Default::default(),
                 truncation_policy: TruncationPolicyConfig::bytes(10_000),
                 supports_parallel_tool_calls: false,
+                supports_tool_calls: true,
                 supports_image_detail_original: false,
                 context_window: Some(272_000),
                 auto_compact_token_limit: None,
@@ -471,6 +476,8 @@ async fn stdio_image_responses_are_sanitized_for_text_only_model() -> anyhow::Re
                     disabled_tools: None,
                     scopes: None,
                     oauth_resource: None,
+                    max_concurrent_calls: None,
+                    max_calls_per_minute: None,
                 },
             );
             config
@@ -591,6 +598,8 @@ async fn stdio_server_propagates_whitelisted_env_vars() -> anyhow::Result<()> {
                     disabled_tools: None,
                     scopes: None,
                     oauth_resource: None,
+                    max_concurrent_calls: None,
+                    max_calls_per_minute: None,
                 },
             );
             config
@@ -752,6 +761,8 @@ async fn streamable_http_tool_call_round_trip() -> anyhow::Result<()> {
                     disabled_tools: None,
                     scopes: None,
                     oauth_resource: None,
+                    max_concurrent_calls: None,
+                    max_calls_per_minute: None,
                 },
             );
             config
@@ -973,6 +984,8 @@ async fn streamable_http_with_oauth_round_trip_impl() -> anyhow::Result<()> {
                     disabled_tools: None,
                     scopes: None,
                     oauth_resource: None,
+                    max_concurrent_calls: None,
+                    max_calls_per_minute: None,
                 },
             );
             config
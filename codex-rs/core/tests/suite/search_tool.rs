@@ -135,6 +135,8 @@ fn rmcp_server_config(command: String) -> McpServerConfig {
         disabled_tools: None,
         scopes: None,
         oauth_resource: None,
+        max_concurrent_calls: None,
+        max_calls_per_minute: None,
     }
 }
 
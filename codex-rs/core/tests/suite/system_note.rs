@@ -0,0 +1,126 @@
+use anyhow::Result;
+use codex_core::ContextItem;
+use codex_core::MessageRole;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::Op;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn system_note_is_sent_as_system_message_for_one_turn() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let responses = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_assistant_message("msg-1", "retrying"),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_response_created("resp-2"),
+                ev_assistant_message("msg-2", "done"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+    let test = test_codex().build(&server).await?;
+
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![
+                UserInput::SystemNote {
+                    text: "the previous task failed with error X".to_string(),
+                },
+                UserInput::Text {
+                    text: "try again".to_string(),
+                    text_elements: Vec::new(),
+                },
+            ],
+            final_output_json_schema: None,
+        })
+        .await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TurnComplete(_))
+    })
+    .await;
+    test.submit_turn("next task").await?;
+
+    let requests = responses.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(
+        requests[0].message_input_texts("system"),
+        vec!["[SYSTEM NOTE]: the previous task failed with error X".to_string()]
+    );
+    let first_input = requests[0].input();
+    let note_index = first_input
+        .iter()
+        .position(|item| item["role"] == "system")
+        .expect("system note in first request");
+    assert_eq!(first_input[note_index + 1]["role"], "user");
+    assert_eq!(
+        first_input[note_index + 1]["content"][0]["text"],
+        "try again"
+    );
+    assert!(
+        requests[0]
+            .message_input_texts("user")
+            .iter()
+            .all(|text| !text.contains("[SYSTEM NOTE]"))
+    );
+    assert_eq!(
+        requests[1].message_input_texts("system"),
+        Vec::<String>::new()
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pushed_context_item_is_sent_with_the_next_request() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let responses = mount_sse_sequence(
+        &server,
+        vec![sse(vec![
+            ev_response_created("resp-1"),
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-1"),
+        ])],
+    )
+    .await;
+    let test = test_codex().build(&server).await?;
+    let reference = "The parser lives in src/parse.rs.";
+
+    test.codex
+        .push_context_item(ContextItem {
+            role: MessageRole::Developer,
+            content: reference.to_string(),
+            pinned: true,
+        })
+        .await;
+    test.submit_turn("fix the parser").await?;
+
+    let requests = responses.requests();
+    assert_eq!(requests.len(), 1);
+    assert!(
+        requests[0]
+            .message_input_texts("developer")
+            .contains(&reference.to_string())
+    );
+
+    Ok(())
+}
@@ -0,0 +1,491 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use codex_core::AfterAllHook;
+use codex_core::AuditHook;
+use codex_core::AuditOutcome;
+use codex_core::AuditRecord;
+use codex_core::BeforeAllHook;
+use codex_core::BoxedDispatchFn;
+use codex_core::DispatchInterceptor;
+use codex_core::FunctionCallError;
+use codex_core::SandboxPermissionsHook;
+use codex_core::ToolCall;
+use codex_core::ToolCallEvent;
+use codex_core::ToolCallSource;
+use codex_core::ToolOutputTransformer;
+use codex_core::TurnToolOverrides;
+use codex_core::config::types::ToolChaosConfig;
+use codex_protocol::models::ResponseInputItem;
+use codex_protocol::models::SandboxPermissions;
+use codex_protocol::models::ShellToolCallParams;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_local_shell_call;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use futures::StreamExt;
+use serde_json::Value;
+
+fn tool_names(body: &Value) -> Vec<String> {
+    body.get("tools")
+        .and_then(Value::as_array)
+        .map(|tools| {
+            tools
+                .iter()
+                .filter_map(|tool| {
+                    tool.get("name")
+                        .or_else(|| tool.get("type"))
+                        .and_then(Value::as_str)
+                        .map(str::to_string)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn tool_call(id: &str, call_id: &str, name: &str, arguments: &Value) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_function_call(call_id, name, &arguments.to_string()),
+        ev_completed(id),
+    ])
+}
+
+fn assistant_reply(id: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("{id}-msg"), "done"),
+        ev_completed(id),
+    ])
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn turn_tool_overrides_apply_to_the_next_turn_only() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    let responses = mount_sse_sequence(
+        &server,
+        vec![assistant_reply("resp-1"), assistant_reply("resp-2")],
+    )
+    .await;
+
+    test.codex
+        .set_next_turn_tool_overrides(TurnToolOverrides {
+            disabled_tools: HashSet::from(["update_plan".to_string()]),
+            tool_timeout: None,
+        })
+        .await;
+    test.submit_turn("refactor without planning").await?;
+    test.submit_turn("plan the next step").await?;
+
+    let requests = responses.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(!tool_names(&requests[0].body_json()).contains(&"update_plan".to_string()));
+    assert!(tool_names(&requests[1].body_json()).contains(&"update_plan".to_string()));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn configured_tool_fallback_answers_a_failed_call() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(|config| {
+            config.tool_fallbacks =
+                HashMap::from([("plan_v2".to_string(), "update_plan".to_string())]);
+        })
+        .build(&server)
+        .await?;
+    let plan = serde_json::json!({"plan": [{"step": "inspect", "status": "pending"}]});
+    let responses = mount_sse_sequence(
+        &server,
+        vec![
+            tool_call("resp-1", "call-plan", "plan_v2", &plan),
+            assistant_reply("resp-2"),
+        ],
+    )
+    .await;
+
+    test.submit_turn("plan the refactor").await?;
+
+    let requests = responses.requests();
+    assert_eq!(requests.len(), 2);
+    let output = requests[1]
+        .function_call_output_text("call-plan")
+        .expect("fallback output for call-plan");
+    assert!(
+        output.starts_with("fallback_used: true (plan_v2 failed; result produced by update_plan)"),
+        "unexpected output: {output}"
+    );
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct CallCounter {
+    started: AtomicUsize,
+    finished: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl BeforeAllHook for CallCounter {
+    async fn before(
+        &self,
+        _call: &ToolCall,
+        _source: ToolCallSource,
+    ) -> Result<(), FunctionCallError> {
+        self.started.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AfterAllHook for CallCounter {
+    async fn after(&self, call: &ToolCall, _result: &Result<ResponseInputItem, FunctionCallError>) {
+        self.finished
+            .lock()
+            .expect("finished lock")
+            .push(call.tool_name.clone());
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn session_middleware_runs_for_every_tool_call() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    let counter = Arc::new(CallCounter::default());
+    test.codex.register_before_all(counter.clone());
+    test.codex.register_after_all(counter.clone());
+    let plan = serde_json::json!({"plan": [{"step": "inspect", "status": "pending"}]});
+    let command = serde_json::json!({"command": "echo hooked", "timeout_ms": 2_000});
+    let responses = mount_sse_sequence(
+        &server,
+        vec![
+            tool_call("resp-1", "call-plan", "update_plan", &plan),
+            tool_call("resp-2", "call-shell", "shell_command", &command),
+            assistant_reply("resp-3"),
+        ],
+    )
+    .await;
+
+    test.submit_turn("plan, then look around").await?;
+
+    assert_eq!(responses.requests().len(), 3);
+    assert_eq!(counter.started.load(Ordering::SeqCst), 2);
+    assert_eq!(
+        *counter.finished.lock().expect("finished lock"),
+        vec!["update_plan".to_string(), "shell_command".to_string()]
+    );
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct RecordingInterceptor {
+    calls: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl DispatchInterceptor for RecordingInterceptor {
+    async fn intercept(
+        &self,
+        call: ToolCall,
+        real_dispatch: BoxedDispatchFn<'_>,
+    ) -> Result<ResponseInputItem, FunctionCallError> {
+        self.calls
+            .lock()
+            .expect("calls lock")
+            .push(call.call_id.clone());
+        real_dispatch(call).await
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tool_call_subscribers_see_each_call_start_and_complete() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    let mut events = Box::pin(test.codex.subscribe_to_tool_calls());
+    let plan = serde_json::json!({"plan": [{"step": "inspect", "status": "pending"}]});
+    mount_sse_sequence(
+        &server,
+        vec![
+            tool_call("resp-1", "call-plan", "update_plan", &plan),
+            assistant_reply("resp-2"),
+        ],
+    )
+    .await;
+
+    test.submit_turn("plan the refactor").await?;
+
+    let Some(ToolCallEvent::Started(call)) = events.next().await else {
+        panic!("expected the call to start first");
+    };
+    assert_eq!(
+        (call.call_id.as_str(), call.tool_name.as_str()),
+        ("call-plan", "update_plan")
+    );
+    let Some(ToolCallEvent::Completed { call_id, .. }) = events.next().await else {
+        panic!("expected the call to complete");
+    };
+    assert_eq!(call_id, "call-plan");
+
+    Ok(())
+}
+
+struct TaggingTransformer;
+
+impl ToolOutputTransformer for TaggingTransformer {
+    fn transform(&self, tool_name: &str, output: String) -> String {
+        format!("[{tool_name}] {output}")
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tool_output_transformer_rewrites_results_sent_to_the_model() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    test.codex
+        .apply_tool_output_transformer(Arc::new(TaggingTransformer))
+        .await;
+    let plan = serde_json::json!({"plan": [{"step": "inspect", "status": "pending"}]});
+    let responses = mount_sse_sequence(
+        &server,
+        vec![
+            tool_call("resp-1", "call-plan", "update_plan", &plan),
+            assistant_reply("resp-2"),
+        ],
+    )
+    .await;
+
+    test.submit_turn("plan the refactor").await?;
+
+    let requests = responses.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(
+        requests[1]
+            .function_call_output_text("call-plan")
+            .as_deref(),
+        Some("[update_plan] Plan updated")
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn session_dispatch_interceptor_passes_calls_through() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    let interceptor = Arc::new(RecordingInterceptor::default());
+    test.codex.set_dispatch_interceptor(interceptor.clone());
+    let plan = serde_json::json!({"plan": [{"step": "inspect", "status": "pending"}]});
+    let responses = mount_sse_sequence(
+        &server,
+        vec![
+            tool_call("resp-1", "call-plan", "update_plan", &plan),
+            assistant_reply("resp-2"),
+        ],
+    )
+    .await;
+
+    test.submit_turn("plan the refactor").await?;
+
+    assert_eq!(
+        *interceptor.calls.lock().expect("calls lock"),
+        vec!["call-plan".to_string()]
+    );
+    let requests = responses.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(
+        requests[1]
+            .function_call_output_text("call-plan")
+            .as_deref(),
+        Some("Plan updated")
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn configured_chaos_fails_every_tool_call() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(|config| {
+            config.tool_chaos = Some(ToolChaosConfig {
+                error_rate: Some(1.0),
+                ..Default::default()
+            });
+        })
+        .build(&server)
+        .await?;
+    let plan = serde_json::json!({"plan": [{"step": "inspect", "status": "pending"}]});
+    let responses = mount_sse_sequence(
+        &server,
+        vec![
+            tool_call("resp-1", "call-plan-1", "update_plan", &plan),
+            tool_call("resp-2", "call-plan-2", "update_plan", &plan),
+            assistant_reply("resp-3"),
+        ],
+    )
+    .await;
+
+    test.submit_turn("plan the refactor").await?;
+
+    let requests = responses.requests();
+    assert_eq!(requests.len(), 3);
+    for (request, call_id) in [(&requests[1], "call-plan-1"), (&requests[2], "call-plan-2")] {
+        assert_eq!(
+            request.function_call_output_text(call_id).as_deref(),
+            Some("chaos: injected failure for update_plan")
+        );
+    }
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct RecordingAuditHook {
+    records: Mutex<Vec<AuditRecord>>,
+}
+
+impl AuditHook for RecordingAuditHook {
+    fn record(&self, record: &AuditRecord) -> anyhow::Result<()> {
+        self.records
+            .lock()
+            .expect("records lock")
+            .push(record.clone());
+        Ok(())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn session_audit_hook_records_every_tool_call() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    let hook = Arc::new(RecordingAuditHook::default());
+    test.codex.register_audit_hook(hook.clone());
+    let plan = serde_json::json!({"plan": [{"step": "inspect", "status": "pending"}]});
+    mount_sse_sequence(
+        &server,
+        vec![
+            tool_call("resp-1", "call-plan", "update_plan", &plan),
+            tool_call("resp-2", "call-missing", "plan_v2", &plan),
+            assistant_reply("resp-3"),
+        ],
+    )
+    .await;
+
+    test.submit_turn("plan the refactor").await?;
+
+    let records = hook.records.lock().expect("records lock");
+    let summary = records
+        .iter()
+        .map(|record| {
+            (
+                record.call_id.as_str(),
+                record.tool_name.as_str(),
+                record.mcp_server.as_deref(),
+                record.outcome,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            ("call-plan", "update_plan", None, AuditOutcome::Success),
+            ("call-missing", "plan_v2", None, AuditOutcome::Failure),
+        ]
+    );
+    assert_eq!(records[0].arguments, plan.to_string());
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct VetoingSandboxHook {
+    commands: Mutex<Vec<Vec<String>>>,
+}
+
+#[async_trait]
+impl SandboxPermissionsHook for VetoingSandboxHook {
+    async fn resolve(
+        &self,
+        params: &ShellToolCallParams,
+    ) -> Result<SandboxPermissions, FunctionCallError> {
+        self.commands
+            .lock()
+            .expect("commands lock")
+            .push(params.command.clone());
+        Err(FunctionCallError::RespondToModel(
+            "command vetoed by policy".to_string(),
+        ))
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn session_sandbox_permissions_hook_can_veto_local_shell_calls() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().with_model("gpt-5").build(&server).await?;
+    let hook = Arc::new(VetoingSandboxHook::default());
+    test.codex.set_sandbox_permissions_hook(hook.clone());
+    let responses = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_local_shell_call("call-shell", "completed", vec!["/bin/echo", "vetoed"]),
+                ev_completed("resp-1"),
+            ]),
+            assistant_reply("resp-2"),
+        ],
+    )
+    .await;
+
+    test.submit_turn("run the command").await?;
+
+    assert_eq!(
+        *hook.commands.lock().expect("commands lock"),
+        vec![vec!["/bin/echo".to_string(), "vetoed".to_string()]]
+    );
+    let requests = responses.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(
+        requests[1]
+            .function_call_output_text("call-shell")
+            .as_deref(),
+        Some("command vetoed by policy")
+    );
+
+    Ok(())
+}
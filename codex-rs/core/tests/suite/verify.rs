@@ -0,0 +1,101 @@
+#![cfg(not(target_os = "windows"))]
+
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::ExecCommandSource;
+use codex_protocol::protocol::Op;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_apply_patch_function_call;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+
+fn add_file_patch(path: &str) -> String {
+    format!("*** Begin Patch\n*** Add File: {path}\n+hello\n*** End Patch")
+}
+
+/// A turn whose changes fail the `[verify]` command goes back to the model
+/// with the failure, and ends once the model's fix passes.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn failed_verification_is_sent_back_to_the_model() {
+    skip_if_no_network!();
+
+    let server = start_mock_server().await;
+    let response_mock = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_apply_patch_function_call("call-1", &add_file_patch("draft.txt")),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "Added the draft."),
+                ev_completed("resp-2"),
+            ]),
+            sse(vec![
+                ev_response_created("resp-3"),
+                ev_apply_patch_function_call("call-2", &add_file_patch("ok.txt")),
+                ev_completed("resp-3"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-2", "Added ok.txt as well."),
+                ev_completed("resp-4"),
+            ]),
+        ],
+    )
+    .await;
+
+    let codex = test_codex()
+        .with_model("gpt-5.1")
+        .with_config(|config| {
+            config.include_apply_patch_tool = true;
+            config.verify.command = Some("test -f ok.txt".to_string());
+        })
+        .build(&server)
+        .await
+        .expect("build codex")
+        .codex;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "write the draft".into(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+        })
+        .await
+        .expect("submit turn");
+
+    let mut verify_exit_codes = Vec::new();
+    let last_agent_message = loop {
+        match codex.next_event().await.expect("next event").msg {
+            EventMsg::ExecCommandEnd(event) if event.source == ExecCommandSource::Verify => {
+                verify_exit_codes.push(event.exit_code);
+            }
+            EventMsg::TurnComplete(event) => break event.last_agent_message,
+            _ => {}
+        }
+    };
+
+    assert_eq!(verify_exit_codes.len(), 2, "{verify_exit_codes:?}");
+    assert_ne!(verify_exit_codes[0], 0);
+    assert_eq!(verify_exit_codes[1], 0);
+    assert_eq!(last_agent_message.as_deref(), Some("Added ok.txt as well."));
+
+    let requests = response_mock.requests();
+    assert_eq!(requests.len(), 4);
+    assert!(
+        requests[2]
+            .message_input_texts("developer")
+            .iter()
+            .any(|text| text.starts_with("The verification command `test -f ok.txt` failed")),
+        "expected the failure report in the follow-up request"
+    );
+}
@@ -0,0 +1,538 @@
+//! `codex batch`: runs the jobs of a manifest as headless sessions, several
+//! at a time, and writes what each one produced to an output directory.
+//!
+//! The manifest is a JSON file:
+//!
+//! ```json
+//! {
+//!   "jobs": [
+//!     { "id": "api", "cwd": "repos/api", "prompt": "Migrate to the v2 client" },
+//!     { "cwd": "repos/web", "prompt": "Migrate to the v2 client", "config": ["model=\"o3\""] }
+//!   ]
+//! }
+//! ```
+//!
+//! Relative `cwd`s are resolved against the manifest's directory. `config`
+//! takes the same `key=value` overrides as `-c` and applies to that job only.
+//! Each job gets `<output-dir>/<id>/result.json` and, when the session changed
+//! files, `<output-dir>/<id>/diff.patch`.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::Context;
+use codex_arg0::Arg0DispatchPaths;
+use codex_core::AuthManager;
+use codex_core::NewThread;
+use codex_core::ThreadManager;
+use codex_core::auth::enforce_login_restrictions;
+use codex_core::config::Config;
+use codex_core::config::ConfigBuilder;
+use codex_core::config::ConfigOverrides;
+use codex_core::models_manager::collaboration_mode_presets::CollaborationModesConfig;
+use codex_core::models_manager::manager::RefreshStrategy;
+use codex_protocol::approvals::ElicitationAction;
+use codex_protocol::config_types::SandboxMode;
+use codex_protocol::protocol::AskForApproval;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::Op;
+use codex_protocol::protocol::SessionSource;
+use codex_protocol::protocol::TokenUsage;
+use codex_protocol::user_input::UserInput;
+use codex_utils_cli::CliConfigOverrides;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::warn;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::prelude::*;
+
+const DEFAULT_CONCURRENCY: usize = 4;
+
+#[derive(Debug, clap::Parser)]
+pub struct BatchCli {
+    /// JSON manifest listing the jobs to run.
+    #[arg(value_name = "MANIFEST")]
+    pub manifest: PathBuf,
+
+    /// Directory to write each job's result and diff to.
+    #[arg(long = "output-dir", short = 'o', value_name = "DIR")]
+    pub output_dir: PathBuf,
+
+    /// Number of jobs to run at the same time.
+    #[arg(long, short = 'j', default_value_t = DEFAULT_CONCURRENCY)]
+    pub concurrency: usize,
+
+    /// Model for jobs that do not set one in their `config`.
+    #[arg(long, short = 'm')]
+    pub model: Option<String>,
+
+    /// Sandbox policy for commands run by the jobs.
+    #[arg(long = "sandbox", short = 's', value_enum)]
+    pub sandbox_mode: Option<codex_utils_cli::SandboxModeCliArg>,
+
+    /// Run every job without sandboxing. Intended solely for environments
+    /// that are externally sandboxed.
+    #[arg(
+        long = "dangerously-bypass-approvals-and-sandbox",
+        alias = "yolo",
+        default_value_t = false,
+        conflicts_with = "sandbox_mode"
+    )]
+    pub dangerously_bypass_approvals_and_sandbox: bool,
+
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchManifest {
+    jobs: Vec<ManifestJob>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestJob {
+    #[serde(default)]
+    id: Option<String>,
+    cwd: PathBuf,
+    prompt: String,
+    #[serde(default)]
+    config: Vec<String>,
+}
+
+/// A manifest job with its id and working directory resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BatchJob {
+    /// Names the job's output directory. Defaults to the job's position in
+    /// the manifest, starting at 1.
+    id: String,
+    cwd: PathBuf,
+    prompt: String,
+    config: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum BatchJobStatus {
+    Completed,
+    Failed,
+}
+
+/// Written to `<output-dir>/<id>/result.json`.
+#[derive(Debug, Serialize)]
+struct BatchJobResult {
+    id: String,
+    cwd: PathBuf,
+    status: BatchJobStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thread_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_agent_message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token_usage: Option<TokenUsage>,
+    duration_ms: u64,
+    /// Whether `diff.patch` was written next to this file.
+    has_diff: bool,
+}
+
+/// What a job's session produced, before it is written out.
+#[derive(Default)]
+struct JobOutcome {
+    thread_id: Option<String>,
+    last_agent_message: Option<String>,
+    error: Option<String>,
+    token_usage: Option<TokenUsage>,
+    diff: String,
+}
+
+pub async fn run_batch(cli: BatchCli, arg0_paths: Arg0DispatchPaths) -> anyhow::Result<()> {
+    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn"));
+    let _ = tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_filter(env_filter),
+        )
+        .try_init();
+
+    let jobs = load_manifest(&cli.manifest)?;
+    let base_overrides = cli
+        .config_overrides
+        .parse_overrides()
+        .map_err(anyhow::Error::msg)?;
+    let harness_overrides = ConfigOverrides {
+        model: cli.model.clone(),
+        // Nobody is around to answer approval prompts.
+        approval_policy: Some(AskForApproval::Never),
+        sandbox_mode: if cli.dangerously_bypass_approvals_and_sandbox {
+            Some(SandboxMode::DangerFullAccess)
+        } else {
+            cli.sandbox_mode.map(Into::into)
+        },
+        codex_linux_sandbox_exe: arg0_paths.codex_linux_sandbox_exe.clone(),
+        main_execve_wrapper_exe: arg0_paths.main_execve_wrapper_exe.clone(),
+        ..Default::default()
+    };
+    let config = ConfigBuilder::default()
+        .cli_overrides(base_overrides)
+        .harness_overrides(harness_overrides.clone())
+        .build()
+        .await
+        .context("failed to load configuration")?;
+    enforce_login_restrictions(&config)?;
+
+    tokio::fs::create_dir_all(&cli.output_dir)
+        .await
+        .with_context(|| format!("failed to create {}", cli.output_dir.display()))?;
+
+    let auth_manager = AuthManager::shared(
+        config.codex_home.clone(),
+        true,
+        config.cli_auth_credentials_store_mode,
+    );
+    let thread_manager = Arc::new(ThreadManager::new(
+        config.codex_home.clone(),
+        auth_manager,
+        SessionSource::Exec,
+        config.model_catalog.clone(),
+        CollaborationModesConfig {
+            default_mode_request_user_input: config
+                .features
+                .enabled(codex_core::features::Feature::DefaultModeRequestUserInput),
+        },
+    ));
+
+    let total = jobs.len();
+    let permits = Arc::new(Semaphore::new(cli.concurrency.max(1)));
+    let mut join_set = JoinSet::new();
+    for job in jobs {
+        let permits = Arc::clone(&permits);
+        let thread_manager = Arc::clone(&thread_manager);
+        let raw_overrides = cli.config_overrides.raw_overrides.clone();
+        let harness_overrides = harness_overrides.clone();
+        let output_dir = cli.output_dir.clone();
+        join_set.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let started = Instant::now();
+            let outcome = run_job(&thread_manager, raw_overrides, harness_overrides, &job).await;
+            let result = BatchJobResult {
+                id: job.id,
+                cwd: job.cwd,
+                status: if outcome.error.is_some() {
+                    BatchJobStatus::Failed
+                } else {
+                    BatchJobStatus::Completed
+                },
+                thread_id: outcome.thread_id,
+                last_agent_message: outcome.last_agent_message,
+                error: outcome.error,
+                token_usage: outcome.token_usage,
+                duration_ms: u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX),
+                has_diff: !outcome.diff.is_empty(),
+            };
+            if let Err(err) = write_job_output(&output_dir, &result, &outcome.diff).await {
+                warn!(
+                    "failed to write the output of batch job {}: {err:#}",
+                    result.id
+                );
+            }
+            result
+        });
+    }
+
+    let mut failed = 0;
+    while let Some(result) = join_set.join_next().await {
+        let result = result.context("batch job panicked")?;
+        if result.status == BatchJobStatus::Failed {
+            failed += 1;
+        }
+        report_job(&result);
+    }
+    if let Err(err) = thread_manager.remove_and_close_all_threads().await {
+        warn!("failed to shut down batch sessions: {err}");
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{failed} of {total} batch jobs failed");
+    }
+    Ok(())
+}
+
+/// Progress goes to stderr, like the rest of `codex exec`'s non-result
+/// output; the results themselves are in the output directory.
+#[allow(clippy::print_stderr)]
+fn report_job(result: &BatchJobResult) {
+    match result.status {
+        BatchJobStatus::Completed => eprintln!(
+            "completed {}{}",
+            result.id,
+            if result.has_diff {
+                " (changed files)"
+            } else {
+                ""
+            }
+        ),
+        BatchJobStatus::Failed => eprintln!(
+            "failed    {}: {}",
+            result.id,
+            result.error.as_deref().unwrap_or("unknown error")
+        ),
+    }
+}
+
+/// Runs one job to the end of its turn. Failures are reported in the
+/// outcome rather than returned, so one bad job does not stop the batch.
+/// `raw_overrides` are the batch-wide `-c` overrides, which the job's own
+/// `config` entries take precedence over.
+async fn run_job(
+    thread_manager: &ThreadManager,
+    mut raw_overrides: Vec<String>,
+    harness_overrides: ConfigOverrides,
+    job: &BatchJob,
+) -> JobOutcome {
+    let mut outcome = JobOutcome::default();
+    raw_overrides.extend(job.config.iter().cloned());
+    let cli_overrides = match (CliConfigOverrides { raw_overrides }).parse_overrides() {
+        Ok(cli_overrides) => cli_overrides,
+        Err(err) => {
+            outcome.error = Some(format!("invalid config override: {err}"));
+            return outcome;
+        }
+    };
+    let config = match ConfigBuilder::default()
+        .cli_overrides(cli_overrides)
+        .harness_overrides(ConfigOverrides {
+            cwd: Some(job.cwd.clone()),
+            ..harness_overrides
+        })
+        .build()
+        .await
+    {
+        Ok(config) => config,
+        Err(err) => {
+            outcome.error = Some(format!("failed to load configuration: {err}"));
+            return outcome;
+        }
+    };
+    if let Err(err) = run_session(thread_manager, config, job, &mut outcome).await {
+        outcome.error = Some(err.to_string());
+    }
+    outcome
+}
+
+async fn run_session(
+    thread_manager: &ThreadManager,
+    config: Config,
+    job: &BatchJob,
+    outcome: &mut JobOutcome,
+) -> anyhow::Result<()> {
+    let model = thread_manager
+        .get_models_manager()
+        .get_default_model(&config.model, RefreshStrategy::OnlineIfUncached)
+        .await;
+    let NewThread {
+        thread_id, thread, ..
+    } = thread_manager.start_thread(config.clone()).await?;
+    outcome.thread_id = Some(thread_id.to_string());
+
+    thread
+        .submit(Op::UserTurn {
+            items: vec![UserInput::Text {
+                text: job.prompt.clone(),
+                text_elements: Vec::new(),
+            }],
+            cwd: config.cwd.to_path_buf(),
+            approval_policy: config.permissions.approval_policy.value(),
+            sandbox_policy: config.permissions.sandbox_policy.get().clone(),
+            model,
+            effort: config.model_reasoning_effort,
+            summary: None,
+            service_tier: None,
+            final_output_json_schema: None,
+            collaboration_mode: None,
+            personality: None,
+        })
+        .await?;
+
+    let mut shut_down = false;
+    while !shut_down {
+        let event = thread.next_event().await?;
+        match event.msg {
+            EventMsg::TurnDiff(event) => outcome.diff = event.unified_diff,
+            EventMsg::TokenCount(event) => {
+                if let Some(info) = event.info {
+                    outcome.token_usage = Some(info.total_token_usage);
+                }
+            }
+            EventMsg::Error(event) => outcome.error = Some(event.message),
+            EventMsg::ElicitationRequest(event) => {
+                thread
+                    .submit(Op::ResolveElicitation {
+                        server_name: event.server_name,
+                        request_id: event.id,
+                        decision: ElicitationAction::Cancel,
+                        content: None,
+                        meta: None,
+                    })
+                    .await?;
+            }
+            EventMsg::TurnComplete(event) => {
+                outcome.last_agent_message = event.last_agent_message;
+                break;
+            }
+            EventMsg::TurnAborted(event) => {
+                outcome
+                    .error
+                    .get_or_insert_with(|| format!("turn aborted: {:?}", event.reason));
+                break;
+            }
+            EventMsg::ShutdownComplete => {
+                outcome
+                    .error
+                    .get_or_insert_with(|| "session ended before the turn completed".to_string());
+                shut_down = true;
+            }
+            _ => {}
+        }
+    }
+
+    if !shut_down {
+        thread.submit(Op::Shutdown).await?;
+        while !matches!(thread.next_event().await?.msg, EventMsg::ShutdownComplete) {}
+    }
+    thread_manager.remove_thread(&thread_id).await;
+    Ok(())
+}
+
+async fn write_job_output(
+    output_dir: &Path,
+    result: &BatchJobResult,
+    diff: &str,
+) -> anyhow::Result<()> {
+    let dir = output_dir.join(&result.id);
+    tokio::fs::create_dir_all(&dir).await?;
+    tokio::fs::write(
+        dir.join("result.json"),
+        serde_json::to_string_pretty(result)?,
+    )
+    .await?;
+    if !diff.is_empty() {
+        tokio::fs::write(dir.join("diff.patch"), diff).await?;
+    }
+    Ok(())
+}
+
+fn load_manifest(path: &Path) -> anyhow::Result<Vec<BatchJob>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read batch manifest {}", path.display()))?;
+    let manifest: BatchManifest = serde_json::from_str(&contents)
+        .with_context(|| format!("invalid batch manifest {}", path.display()))?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    resolve_jobs(manifest, base_dir)
+}
+
+fn resolve_jobs(manifest: BatchManifest, base_dir: &Path) -> anyhow::Result<Vec<BatchJob>> {
+    if manifest.jobs.is_empty() {
+        anyhow::bail!("batch manifest has no jobs");
+    }
+    let mut seen = HashSet::new();
+    manifest
+        .jobs
+        .into_iter()
+        .enumerate()
+        .map(|(index, job)| {
+            let id = job.id.unwrap_or_else(|| (index + 1).to_string());
+            if id.is_empty()
+                || id.starts_with('.')
+                || !id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            {
+                anyhow::bail!(
+                    "batch job id `{id}` must be made of letters, digits, `-`, `_` and `.`, and not start with `.`"
+                );
+            }
+            if !seen.insert(id.clone()) {
+                anyhow::bail!("duplicate batch job id `{id}`");
+            }
+            Ok(BatchJob {
+                id,
+                cwd: base_dir.join(job.cwd),
+                prompt: job.prompt,
+                config: job.config,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn manifest(json: serde_json::Value) -> BatchManifest {
+        serde_json::from_value(json).expect("valid manifest")
+    }
+
+    #[test]
+    fn resolves_ids_and_relative_cwds() {
+        let jobs = resolve_jobs(
+            manifest(serde_json::json!({
+                "jobs": [
+                    { "id": "api", "cwd": "repos/api", "prompt": "one" },
+                    { "cwd": "/abs/web", "prompt": "two", "config": ["model=\"o3\""] },
+                ]
+            })),
+            Path::new("/work"),
+        )
+        .expect("jobs resolve");
+
+        assert_eq!(
+            jobs,
+            vec![
+                BatchJob {
+                    id: "api".to_string(),
+                    cwd: PathBuf::from("/work/repos/api"),
+                    prompt: "one".to_string(),
+                    config: Vec::new(),
+                },
+                BatchJob {
+                    id: "2".to_string(),
+                    cwd: PathBuf::from("/abs/web"),
+                    prompt: "two".to_string(),
+                    config: vec!["model=\"o3\"".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_duplicate_and_unsafe_ids() {
+        let duplicate = resolve_jobs(
+            manifest(serde_json::json!({
+                "jobs": [
+                    { "id": "a", "cwd": "x", "prompt": "p" },
+                    { "id": "a", "cwd": "y", "prompt": "p" },
+                ]
+            })),
+            Path::new("/work"),
+        );
+        assert!(duplicate.is_err());
+
+        let traversal = resolve_jobs(
+            manifest(serde_json::json!({
+                "jobs": [{ "id": "../escape", "cwd": "x", "prompt": "p" }]
+            })),
+            Path::new("/work"),
+        );
+        assert!(traversal.is_err());
+    }
+}
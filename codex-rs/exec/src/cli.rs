@@ -90,7 +90,7 @@ pub struct Cli {
     #[arg(long = "progress-cursor", default_value_t = false)]
     pub progress_cursor: bool,
 
-    /// Print events to stdout as JSONL.
+    /// Print events to stdout as JSONL. Shorthand for `--output-format json`.
     #[arg(
         long = "json",
         alias = "experimental-json",
@@ -99,6 +99,16 @@ pub struct Cli {
     )]
     pub json: bool,
 
+    /// Format of the events printed while the agent runs: `human` prints
+    /// formatted text to stderr, `json` prints JSONL events to stdout.
+    #[arg(
+        long = "output-format",
+        value_enum,
+        default_value_t = OutputFormat::Human,
+        global = true
+    )]
+    pub output_format: OutputFormat,
+
     /// Specifies file where the last message from the agent should be written.
     #[arg(
         long = "output-last-message",
@@ -249,6 +259,14 @@ pub struct ReviewArgs {
     pub prompt: Option<String>,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    #[default]
+    Human,
+    Json,
+}
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
 #[value(rename_all = "kebab-case")]
 pub enum Color {
@@ -293,6 +311,16 @@ mod tests {
         assert_eq!(effective_prompt.as_deref(), Some(PROMPT));
     }
 
+    #[test]
+    fn output_format_defaults_to_human_and_accepts_json() {
+        let cli = Cli::parse_from(["codex-exec", "hello"]);
+        assert_eq!(cli.output_format, OutputFormat::Human);
+
+        let cli = Cli::parse_from(["codex-exec", "--output-format", "json", "hello"]);
+        assert_eq!(cli.output_format, OutputFormat::Json);
+        assert!(!cli.json);
+    }
+
     #[test]
     fn resume_accepts_output_last_message_flag_after_subcommand() {
         const PROMPT: &str = "echo resume-with-output-file";
@@ -280,6 +280,54 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 };
                 ts_msg!(self, "{} {}", "mcp startup:".style(self.cyan), joined);
             }
+            EventMsg::McpServerStatus(update) => {
+                let status_text = match update.status {
+                    codex_protocol::protocol::McpServerHealth::Healthy => "healthy".to_string(),
+                    codex_protocol::protocol::McpServerHealth::Degraded { ref error } => {
+                        format!("degraded: {error}")
+                    }
+                    codex_protocol::protocol::McpServerHealth::Reconnecting { attempt } => {
+                        format!("reconnecting (attempt {attempt})")
+                    }
+                };
+                ts_msg!(
+                    self,
+                    "{} {} {}",
+                    "mcp:".style(self.cyan),
+                    update.server,
+                    status_text
+                );
+            }
+            EventMsg::ProviderFellBack(ev) => {
+                ts_msg!(
+                    self,
+                    "{} provider {} is unavailable ({}); continuing with {} ({})",
+                    "warning:".style(self.yellow).style(self.bold),
+                    ev.from_provider,
+                    ev.reason,
+                    ev.to_provider,
+                    ev.model
+                );
+            }
+            EventMsg::ProviderUnhealthy(ev) => {
+                ts_msg!(
+                    self,
+                    "{} provider {} failed {} requests in a row ({}); skipping it for {}s",
+                    "warning:".style(self.yellow).style(self.bold),
+                    ev.provider,
+                    ev.consecutive_failures,
+                    ev.reason,
+                    ev.retry_after_seconds
+                );
+            }
+            EventMsg::TurnTimedOut(ev) => {
+                ts_msg!(
+                    self,
+                    "{} turn timed out after {}s; asking the model to wrap up",
+                    "warning:".style(self.yellow).style(self.bold),
+                    ev.timeout_seconds
+                );
+            }
             EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
                 ts_msg!(self, "{}", message.style(self.dimmed));
             }
@@ -596,7 +644,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     eprintln!("{}", line.style(self.dimmed));
                 }
             }
-            EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => {
+            EventMsg::TurnDiff(TurnDiffEvent { unified_diff, .. }) => {
                 ts_msg!(
                     self,
                     "{}",
@@ -854,6 +902,8 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::ThreadNameUpdated(_)
             | EventMsg::ExecApprovalRequest(_)
             | EventMsg::ApplyPatchApprovalRequest(_)
+            | EventMsg::PatchReviewRequest(_)
+            | EventMsg::CostUpdate(_)
             | EventMsg::TerminalInteraction(_)
             | EventMsg::ExecCommandOutputDelta(_)
             | EventMsg::GetHistoryEntryResponse(_)
@@ -886,7 +936,25 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             | EventMsg::RealtimeConversationRealtime(_)
             | EventMsg::RealtimeConversationClosed(_)
             | EventMsg::DynamicToolCallRequest(_)
-            | EventMsg::DynamicToolCallResponse(_) => {}
+            | EventMsg::DynamicToolCallResponse(_)
+            | EventMsg::ToolCallStart(_)
+            | EventMsg::ToolCallQueued(_)
+            | EventMsg::TurnCancelled(_)
+            | EventMsg::TurnCheckpoint(_)
+            | EventMsg::ToolCacheHit(_)
+            | EventMsg::ChaosFaultInjected(_)
+            | EventMsg::McpToolCallOutputDelta(_)
+            | EventMsg::ToolOutputRedacted(_)
+            | EventMsg::SecretsRedacted(_)
+            | EventMsg::SessionSettingsUpdated(_)
+            | EventMsg::FeaturesResolved(_)
+            | EventMsg::SessionShareStarted(_)
+            | EventMsg::TurnDiffUpdated(_)
+            | EventMsg::CompactionStarted(_)
+            | EventMsg::CompactionFinished(_)
+            | EventMsg::ContextWindowStatus(_)
+            | EventMsg::CheckpointCreated(_)
+            | EventMsg::BranchedFromCheckpoint(_) => {}
         }
         CodexStatus::Running
     }
@@ -932,9 +1000,11 @@ impl EventProcessorWithHumanOutput {
             msg,
             EventMsg::ThreadNameUpdated(_)
                 | EventMsg::TokenCount(_)
+                | EventMsg::CostUpdate(_)
                 | EventMsg::TurnStarted(_)
                 | EventMsg::ExecApprovalRequest(_)
                 | EventMsg::ApplyPatchApprovalRequest(_)
+                | EventMsg::PatchReviewRequest(_)
                 | EventMsg::TerminalInteraction(_)
                 | EventMsg::ExecCommandOutputDelta(_)
                 | EventMsg::GetHistoryEntryResponse(_)
@@ -26,6 +26,7 @@ use crate::exec_events::McpToolCallStatus;
 use crate::exec_events::PatchApplyStatus;
 use crate::exec_events::PatchChangeKind;
 use crate::exec_events::ReasoningItem;
+use crate::exec_events::THREAD_EVENT_SCHEMA_VERSION;
 use crate::exec_events::ThreadErrorEvent;
 use crate::exec_events::ThreadEvent;
 use crate::exec_events::ThreadItem;
@@ -70,6 +71,9 @@ pub struct EventProcessorWithJsonOutput {
     running_collab_tool_calls: HashMap<String, RunningCollabToolCall>,
     running_web_search_calls: HashMap<String, String>,
     last_critical_error: Option<ThreadErrorEvent>,
+    // Whether the turn's final message is expected to be JSON matching an
+    // output schema, and so is parsed into `turn.completed`.
+    structured_output: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -113,9 +117,17 @@ impl EventProcessorWithJsonOutput {
             running_collab_tool_calls: HashMap::new(),
             running_web_search_calls: HashMap::new(),
             last_critical_error: None,
+            structured_output: false,
         }
     }
 
+    /// Adds the final message, parsed as JSON, to `turn.completed` events.
+    /// Used when the turn was given an output schema.
+    pub fn with_structured_output(mut self) -> Self {
+        self.structured_output = true;
+        self
+    }
+
     pub fn collect_thread_events(&mut self, event: &protocol::Event) -> Vec<ThreadEvent> {
         match &event.msg {
             protocol::EventMsg::SessionConfigured(ev) => self.handle_session_configured(ev),
@@ -160,7 +172,7 @@ impl EventProcessorWithJsonOutput {
                 Vec::new()
             }
             protocol::EventMsg::TurnStarted(ev) => self.handle_task_started(ev),
-            protocol::EventMsg::TurnComplete(_) => self.handle_task_complete(),
+            protocol::EventMsg::TurnComplete(ev) => self.handle_task_complete(ev),
             protocol::EventMsg::Error(ev) => {
                 let error = ThreadErrorEvent {
                     message: ev.message.clone(),
@@ -205,6 +217,7 @@ impl EventProcessorWithJsonOutput {
     ) -> Vec<ThreadEvent> {
         vec![ThreadEvent::ThreadStarted(ThreadStartedEvent {
             thread_id: payload.session_id.to_string(),
+            schema_version: THREAD_EVENT_SCHEMA_VERSION,
         })]
     }
 
@@ -749,7 +762,7 @@ impl EventProcessorWithJsonOutput {
         vec![ThreadEvent::TurnStarted(TurnStartedEvent {})]
     }
 
-    fn handle_task_complete(&mut self) -> Vec<ThreadEvent> {
+    fn handle_task_complete(&mut self, ev: &protocol::TurnCompleteEvent) -> Vec<ThreadEvent> {
         let usage = if let Some(u) = &self.last_total_token_usage {
             Usage {
                 input_tokens: u.input_tokens,
@@ -790,7 +803,17 @@ impl EventProcessorWithJsonOutput {
         if let Some(error) = self.last_critical_error.take() {
             items.push(ThreadEvent::TurnFailed(TurnFailedEvent { error }));
         } else {
-            items.push(ThreadEvent::TurnCompleted(TurnCompletedEvent { usage }));
+            let output = if self.structured_output {
+                ev.last_agent_message
+                    .as_deref()
+                    .and_then(|message| serde_json::from_str(message.trim()).ok())
+            } else {
+                None
+            };
+            items.push(ThreadEvent::TurnCompleted(TurnCompletedEvent {
+                usage,
+                output,
+            }));
         }
 
         items
@@ -5,6 +5,11 @@ use serde_json::Value as JsonValue;
 use std::collections::HashMap;
 use ts_rs::TS;
 
+/// Version of the JSONL event schema, reported by `thread.started`. It is
+/// bumped when events or fields are removed or change meaning; new events and
+/// fields are added without a bump, so consumers should ignore unknown ones.
+pub const THREAD_EVENT_SCHEMA_VERSION: u32 = 1;
+
 /// Top-level JSONL events emitted by codex exec
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 #[serde(tag = "type")]
@@ -40,6 +45,8 @@ pub enum ThreadEvent {
 pub struct ThreadStartedEvent {
     /// The identified of the new thread. Can be used to resume the thread later.
     pub thread_id: String,
+    /// Version of the event schema; see [`THREAD_EVENT_SCHEMA_VERSION`].
+    pub schema_version: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, Default)]
@@ -49,6 +56,11 @@ pub struct TurnStartedEvent {}
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 pub struct TurnCompletedEvent {
     pub usage: Usage,
+    /// The final agent message parsed as JSON, present when the turn ran with
+    /// an output schema and the message is valid JSON.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub output: Option<JsonValue>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
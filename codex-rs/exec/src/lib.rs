@@ -4,12 +4,15 @@
 // For both modes, any other output must be written to stderr.
 #![deny(clippy::print_stdout)]
 
+mod batch;
 mod cli;
 mod event_processor;
 mod event_processor_with_human_output;
 pub mod event_processor_with_jsonl_output;
 pub mod exec_events;
 
+pub use batch::BatchCli;
+pub use batch::run_batch;
 pub use cli::Cli;
 pub use cli::Command;
 pub use cli::ReviewArgs;
@@ -145,13 +148,15 @@ pub async fn run_main(cli: Cli, arg0_paths: Arg0DispatchPaths) -> anyhow::Result
         ephemeral,
         color,
         last_message_file,
-        json: json_mode,
+        json,
+        output_format,
         sandbox_mode: sandbox_mode_cli_arg,
         prompt,
         output_schema: output_schema_path,
         config_overrides,
         progress_cursor,
     } = cli;
+    let json_mode = json || output_format == cli::OutputFormat::Json;
 
     let (_stdout_with_ansi, stderr_with_ansi) = match color {
         cli::Color::Always => (true, true),
@@ -421,7 +426,14 @@ async fn run_exec_session(args: ExecRunArgs) -> anyhow::Result<()> {
     } = args;
 
     let mut event_processor: Box<dyn EventProcessor> = match json_mode {
-        true => Box::new(EventProcessorWithJsonOutput::new(last_message_file.clone())),
+        true => {
+            let event_processor = EventProcessorWithJsonOutput::new(last_message_file.clone());
+            Box::new(if output_schema_path.is_some() {
+                event_processor.with_structured_output()
+            } else {
+                event_processor
+            })
+        }
         _ => Box::new(EventProcessorWithHumanOutput::create_with_ansi(
             stderr_with_ansi,
             cursor_ansi,
@@ -787,6 +799,7 @@ fn should_suppress_agent_job_event(msg: &EventMsg) -> bool {
         msg,
         EventMsg::ExecApprovalRequest(_)
             | EventMsg::ApplyPatchApprovalRequest(_)
+            | EventMsg::PatchReviewRequest(_)
             | EventMsg::RequestUserInput(_)
             | EventMsg::DynamicToolCallRequest(_)
             | EventMsg::DynamicToolCallResponse(_)
@@ -18,6 +18,7 @@ use codex_exec::exec_events::McpToolCallStatus;
 use codex_exec::exec_events::PatchApplyStatus;
 use codex_exec::exec_events::PatchChangeKind;
 use codex_exec::exec_events::ReasoningItem;
+use codex_exec::exec_events::THREAD_EVENT_SCHEMA_VERSION;
 use codex_exec::exec_events::ThreadErrorEvent;
 use codex_exec::exec_events::ThreadEvent;
 use codex_exec::exec_events::ThreadItem;
@@ -110,6 +111,7 @@ fn session_configured_produces_thread_started_event() {
         out,
         vec![ThreadEvent::ThreadStarted(ThreadStartedEvent {
             thread_id: "67e55044-10b1-426f-9247-bb680e5fe0c8".to_string(),
+            schema_version: THREAD_EVENT_SCHEMA_VERSION,
         })]
     );
 }
@@ -341,6 +343,7 @@ fn plan_update_emits_todo_list_started_updated_and_completed() {
             }),
             ThreadEvent::TurnCompleted(TurnCompletedEvent {
                 usage: Usage::default(),
+                output: None,
             }),
         ]
     );
@@ -1285,6 +1288,7 @@ fn task_complete_produces_turn_completed_with_usage() {
         EventMsg::TokenCount(codex_protocol::protocol::TokenCountEvent {
             info: Some(info),
             rate_limits: None,
+            prompt_cache_hit_rate: None,
         }),
     );
     assert!(ep.collect_thread_events(&token_count_event).is_empty());
@@ -1306,6 +1310,36 @@ fn task_complete_produces_turn_completed_with_usage() {
                 cached_input_tokens: 200,
                 output_tokens: 345,
             },
+            output: None,
+        })]
+    );
+}
+
+#[test]
+fn task_complete_with_structured_output_includes_parsed_message() {
+    let mut ep = EventProcessorWithJsonOutput::new(None).with_structured_output();
+    let complete = |id: &str, message: &str| {
+        event(
+            id,
+            EventMsg::TurnComplete(codex_protocol::protocol::TurnCompleteEvent {
+                turn_id: "turn-1".to_string(),
+                last_agent_message: Some(message.to_string()),
+            }),
+        )
+    };
+
+    assert_eq!(
+        ep.collect_thread_events(&complete("e1", "{\"status\": \"ok\"}\n")),
+        vec![ThreadEvent::TurnCompleted(TurnCompletedEvent {
+            usage: Usage::default(),
+            output: Some(json!({"status": "ok"})),
+        })]
+    );
+    assert_eq!(
+        ep.collect_thread_events(&complete("e2", "not json")),
+        vec![ThreadEvent::TurnCompleted(TurnCompletedEvent {
+            usage: Usage::default(),
+            output: None,
         })]
     );
 }
@@ -0,0 +1,160 @@
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::Hook;
+use crate::HookPayload;
+use crate::HookResult;
+use crate::command_from_argv;
+
+/// Lifecycle events a user command can be attached to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandHookEvent {
+    TurnCompleted,
+    PatchApplied,
+    ToolDenied,
+}
+
+/// An external command run when `event` fires. The hook payload is written to
+/// its stdin as JSON; the command is killed if it outlives `timeout`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandHookConfig {
+    pub event: CommandHookEvent,
+    pub argv: Vec<String>,
+    pub timeout: Duration,
+}
+
+pub fn command_hook(config: CommandHookConfig) -> Hook {
+    let name = config.argv.first().cloned().unwrap_or_default();
+    let config = Arc::new(config);
+    Hook {
+        name,
+        func: Arc::new(move |payload: &HookPayload| {
+            let config = Arc::clone(&config);
+            Box::pin(async move {
+                match run_command_hook(&config, payload).await {
+                    Ok(()) => HookResult::Success,
+                    // A user command must never take the operation down with it.
+                    Err(err) => HookResult::FailedContinue(err.into()),
+                }
+            })
+        }),
+    }
+}
+
+async fn run_command_hook(
+    config: &CommandHookConfig,
+    payload: &HookPayload,
+) -> std::io::Result<()> {
+    let Some(mut command) = command_from_argv(&config.argv) else {
+        return Ok(());
+    };
+    let input = serde_json::to_vec(payload).map_err(std::io::Error::other)?;
+    command
+        .current_dir(&payload.cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+    let mut child = command.spawn()?;
+
+    let run = async {
+        if let Some(mut stdin) = child.stdin.take() {
+            // The command may exit without reading its input; that is not a failure.
+            if let Err(err) = stdin.write_all(&input).await
+                && err.kind() != std::io::ErrorKind::BrokenPipe
+            {
+                return Err(err);
+            }
+        }
+        child.wait().await
+    };
+    let status = match tokio::time::timeout(config.timeout, run).await {
+        Ok(status) => status?,
+        Err(_) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("timed out after {}ms", config.timeout.as_millis()),
+            ));
+        }
+    };
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!("exited with {status}")))
+    }
+}
+
+#[cfg(all(test, not(windows)))]
+mod tests {
+    use std::path::PathBuf;
+
+    use anyhow::Result;
+    use codex_protocol::ThreadId;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::HookEvent;
+    use crate::HookEventToolDenied;
+
+    fn payload(cwd: PathBuf) -> HookPayload {
+        HookPayload {
+            session_id: ThreadId::new(),
+            cwd,
+            client: None,
+            triggered_at: chrono::Utc::now(),
+            hook_event: HookEvent::ToolDenied {
+                event: HookEventToolDenied {
+                    turn_id: "turn-1".to_string(),
+                    call_id: "call-1".to_string(),
+                    tool_name: "shell".to_string(),
+                    reason: "rejected by user".to_string(),
+                },
+            },
+        }
+    }
+
+    fn sh(script: &str, timeout: Duration) -> Hook {
+        command_hook(CommandHookConfig {
+            event: CommandHookEvent::ToolDenied,
+            argv: vec!["/bin/sh".to_string(), "-c".to_string(), script.to_string()],
+            timeout,
+        })
+    }
+
+    #[tokio::test]
+    async fn command_hook_receives_payload_on_stdin() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let payload = payload(temp_dir.path().to_path_buf());
+        let hook = sh("cat > payload.json", Duration::from_secs(5));
+
+        let outcome = hook.execute(&payload).await;
+        assert!(matches!(outcome.result, HookResult::Success));
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("payload.json"))?,
+            serde_json::to_string(&payload)?
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn command_hook_failures_do_not_abort() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let payload = payload(temp_dir.path().to_path_buf());
+
+        let failed = sh("exit 3", Duration::from_secs(5)).execute(&payload).await;
+        assert!(matches!(failed.result, HookResult::FailedContinue(_)));
+
+        let timed_out = sh("sleep 5", Duration::from_millis(100))
+            .execute(&payload)
+            .await;
+        let HookResult::FailedContinue(err) = timed_out.result else {
+            panic!("expected the hook to time out");
+        };
+        assert_eq!(err.to_string(), "timed out after 100ms");
+        Ok(())
+    }
+}
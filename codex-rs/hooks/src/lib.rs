@@ -1,7 +1,11 @@
+mod command_hook;
 mod registry;
 mod types;
 mod user_notification;
 
+pub use command_hook::CommandHookConfig;
+pub use command_hook::CommandHookEvent;
+pub use command_hook::command_hook;
 pub use registry::Hooks;
 pub use registry::HooksConfig;
 pub use registry::command_from_argv;
@@ -9,6 +13,8 @@ pub use types::Hook;
 pub use types::HookEvent;
 pub use types::HookEventAfterAgent;
 pub use types::HookEventAfterToolUse;
+pub use types::HookEventPatchApplied;
+pub use types::HookEventToolDenied;
 pub use types::HookPayload;
 pub use types::HookResponse;
 pub use types::HookResult;
@@ -1,5 +1,8 @@
 use tokio::process::Command;
 
+use crate::CommandHookConfig;
+use crate::CommandHookEvent;
+use crate::command_hook;
 use crate::types::Hook;
 use crate::types::HookEvent;
 use crate::types::HookPayload;
@@ -8,12 +11,15 @@ use crate::types::HookResponse;
 #[derive(Default, Clone)]
 pub struct HooksConfig {
     pub legacy_notify_argv: Option<Vec<String>>,
+    pub commands: Vec<CommandHookConfig>,
 }
 
 #[derive(Clone)]
 pub struct Hooks {
     after_agent: Vec<Hook>,
     after_tool_use: Vec<Hook>,
+    patch_applied: Vec<Hook>,
+    tool_denied: Vec<Hook>,
 }
 
 impl Default for Hooks {
@@ -26,22 +32,37 @@ impl Default for Hooks {
 // executed after specific events in the Codex lifecycle.
 impl Hooks {
     pub fn new(config: HooksConfig) -> Self {
-        let after_agent = config
-            .legacy_notify_argv
-            .filter(|argv| !argv.is_empty() && !argv[0].is_empty())
-            .map(crate::notify_hook)
-            .into_iter()
-            .collect();
-        Self {
-            after_agent,
+        let mut hooks = Self {
+            after_agent: config
+                .legacy_notify_argv
+                .filter(|argv| !argv.is_empty() && !argv[0].is_empty())
+                .map(crate::notify_hook)
+                .into_iter()
+                .collect(),
             after_tool_use: Vec::new(),
+            patch_applied: Vec::new(),
+            tool_denied: Vec::new(),
+        };
+        for command in config.commands {
+            if command.argv.first().is_none_or(String::is_empty) {
+                continue;
+            }
+            let bucket = match command.event {
+                CommandHookEvent::TurnCompleted => &mut hooks.after_agent,
+                CommandHookEvent::PatchApplied => &mut hooks.patch_applied,
+                CommandHookEvent::ToolDenied => &mut hooks.tool_denied,
+            };
+            bucket.push(command_hook(command));
         }
+        hooks
     }
 
     fn hooks_for_event(&self, hook_event: &HookEvent) -> &[Hook] {
         match hook_event {
             HookEvent::AfterAgent { .. } => &self.after_agent,
             HookEvent::AfterToolUse { .. } => &self.after_tool_use,
+            HookEvent::PatchApplied { .. } => &self.patch_applied,
+            HookEvent::ToolDenied { .. } => &self.tool_denied,
         }
     }
 
@@ -231,6 +252,7 @@ mod tests {
         assert!(
             Hooks::new(HooksConfig {
                 legacy_notify_argv: Some(vec![]),
+                ..HooksConfig::default()
             })
             .after_agent
             .is_empty()
@@ -238,6 +260,7 @@ mod tests {
         assert!(
             Hooks::new(HooksConfig {
                 legacy_notify_argv: Some(vec!["".to_string()]),
+                ..HooksConfig::default()
             })
             .after_agent
             .is_empty()
@@ -245,6 +268,7 @@ mod tests {
         assert_eq!(
             Hooks::new(HooksConfig {
                 legacy_notify_argv: Some(vec!["notify-send".to_string()]),
+                ..HooksConfig::default()
             })
             .after_agent
             .len(),
@@ -252,6 +276,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn hooks_new_registers_commands_by_event() {
+        let command = |event, program: &str| CommandHookConfig {
+            event,
+            argv: vec![program.to_string()],
+            timeout: Duration::from_secs(1),
+        };
+        let hooks = Hooks::new(HooksConfig {
+            legacy_notify_argv: Some(vec!["notify-send".to_string()]),
+            commands: vec![
+                command(CommandHookEvent::TurnCompleted, "fmt"),
+                command(CommandHookEvent::PatchApplied, "ticket"),
+                command(CommandHookEvent::ToolDenied, ""),
+            ],
+        });
+        assert_eq!(
+            hooks
+                .after_agent
+                .iter()
+                .map(|hook| hook.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["legacy_notify", "fmt"]
+        );
+        assert_eq!(hooks.patch_applied.len(), 1);
+        assert!(hooks.tool_denied.is_empty());
+    }
+
     #[tokio::test]
     async fn dispatch_executes_hook() {
         let calls = Arc::new(AtomicUsize::new(0));
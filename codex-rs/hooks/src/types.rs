@@ -137,6 +137,24 @@ pub struct HookEventAfterToolUse {
     pub output_preview: String,
 }
 
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct HookEventPatchApplied {
+    pub turn_id: String,
+    pub call_id: String,
+    /// Files the patch added, updated or deleted.
+    pub paths: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct HookEventToolDenied {
+    pub turn_id: String,
+    pub call_id: String,
+    pub tool_name: String,
+    pub reason: String,
+}
+
 fn serialize_triggered_at<S>(value: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -155,6 +173,14 @@ pub enum HookEvent {
         #[serde(flatten)]
         event: HookEventAfterToolUse,
     },
+    PatchApplied {
+        #[serde(flatten)]
+        event: HookEventPatchApplied,
+    },
+    ToolDenied {
+        #[serde(flatten)]
+        event: HookEventToolDenied,
+    },
 }
 
 #[cfg(test)]
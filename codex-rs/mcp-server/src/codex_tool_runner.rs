@@ -316,7 +316,11 @@ async fn run_codex_tool_session_inner(
                     EventMsg::AgentReasoningDelta(_) => {
                         // TODO: think how we want to support this in the MCP
                     }
-                    EventMsg::McpStartupUpdate(_) | EventMsg::McpStartupComplete(_) => {
+                    EventMsg::McpStartupUpdate(_)
+                    | EventMsg::McpStartupComplete(_)
+                    | EventMsg::McpServerStatus(_)
+                    | EventMsg::ProviderFellBack(_)
+                    | EventMsg::ProviderUnhealthy(_) => {
                         // Ignored in MCP tool runner.
                     }
                     EventMsg::AgentMessage(AgentMessageEvent { .. }) => {
@@ -326,6 +330,7 @@ async fn run_codex_tool_session_inner(
                     | EventMsg::AgentReasoningRawContentDelta(_)
                     | EventMsg::TurnStarted(_)
                     | EventMsg::TokenCount(_)
+                    | EventMsg::CostUpdate(_)
                     | EventMsg::AgentReasoning(_)
                     | EventMsg::AgentReasoningSectionBreak(_)
                     | EventMsg::McpToolCallBegin(_)
@@ -366,8 +371,28 @@ async fn run_codex_tool_session_inner(
                     | EventMsg::UndoCompleted(_)
                     | EventMsg::ExitedReviewMode(_)
                     | EventMsg::RequestUserInput(_)
+                    | EventMsg::PatchReviewRequest(_)
                     | EventMsg::DynamicToolCallRequest(_)
                     | EventMsg::DynamicToolCallResponse(_)
+                    | EventMsg::ToolCallStart(_)
+                    | EventMsg::ToolCallQueued(_)
+                    | EventMsg::TurnCancelled(_)
+                    | EventMsg::TurnTimedOut(_)
+                    | EventMsg::TurnCheckpoint(_)
+                    | EventMsg::ToolCacheHit(_)
+                    | EventMsg::ChaosFaultInjected(_)
+                    | EventMsg::McpToolCallOutputDelta(_)
+                    | EventMsg::ToolOutputRedacted(_)
+                    | EventMsg::SecretsRedacted(_)
+                    | EventMsg::SessionSettingsUpdated(_)
+                    | EventMsg::FeaturesResolved(_)
+                    | EventMsg::SessionShareStarted(_)
+                    | EventMsg::TurnDiffUpdated(_)
+                    | EventMsg::CompactionStarted(_)
+                    | EventMsg::CompactionFinished(_)
+                    | EventMsg::ContextWindowStatus(_)
+                    | EventMsg::CheckpointCreated(_)
+                    | EventMsg::BranchedFromCheckpoint(_)
                     | EventMsg::ContextCompacted(_)
                     | EventMsg::ModelReroute(_)
                     | EventMsg::ThreadRolledBack(_)
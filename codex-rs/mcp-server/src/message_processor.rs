@@ -35,11 +35,20 @@ use crate::codex_tool_config::CodexToolCallReplyParam;
 use crate::codex_tool_config::create_tool_for_codex_tool_call_param;
 use crate::codex_tool_config::create_tool_for_codex_tool_call_reply_param;
 use crate::outgoing_message::OutgoingMessageSender;
+use crate::primitive_tools::APPLY_PATCH_TOOL_NAME;
+use crate::primitive_tools::SEARCH_TOOL_NAME;
+use crate::primitive_tools::SHELL_TOOL_NAME;
+use crate::primitive_tools::call_primitive_tool;
+use crate::primitive_tools::create_tool_for_apply_patch_tool_call_param;
+use crate::primitive_tools::create_tool_for_search_tool_call_param;
+use crate::primitive_tools::create_tool_for_shell_tool_call_param;
 
 pub(crate) struct MessageProcessor {
     outgoing: Arc<OutgoingMessageSender>,
     initialized: bool,
     arg0_paths: Arg0DispatchPaths,
+    /// Config the primitive tools run under.
+    config: Arc<Config>,
     thread_manager: Arc<ThreadManager>,
     running_requests_id_to_codex_uuid: Arc<Mutex<HashMap<RequestId, ThreadId>>>,
 }
@@ -73,6 +82,7 @@ impl MessageProcessor {
             outgoing,
             initialized: false,
             arg0_paths,
+            config,
             thread_manager,
             running_requests_id_to_codex_uuid: Arc::new(Mutex::new(HashMap::new())),
         }
@@ -319,6 +329,9 @@ impl MessageProcessor {
             tools: vec![
                 create_tool_for_codex_tool_call_param(),
                 create_tool_for_codex_tool_call_reply_param(),
+                create_tool_for_apply_patch_tool_call_param(),
+                create_tool_for_shell_tool_call_param(),
+                create_tool_for_search_tool_call_param(),
             ],
             next_cursor: None,
         };
@@ -338,6 +351,9 @@ impl MessageProcessor {
                 self.handle_tool_call_codex_session_reply(id, arguments)
                     .await
             }
+            APPLY_PATCH_TOOL_NAME | SHELL_TOOL_NAME | SEARCH_TOOL_NAME => {
+                self.handle_tool_call_primitive(id, name.to_string(), arguments);
+            }
             _ => {
                 let result = CallToolResult {
                     content: vec![rmcp::model::Content::text(format!("Unknown tool '{name}'"))],
@@ -350,6 +366,25 @@ impl MessageProcessor {
         }
     }
 
+    fn handle_tool_call_primitive(
+        &self,
+        id: RequestId,
+        name: String,
+        arguments: Option<rmcp::model::JsonObject>,
+    ) {
+        let outgoing = self.outgoing.clone();
+        let config = self.config.clone();
+        let arg0_paths = self.arg0_paths.clone();
+
+        // Commands can run for a while, so do not block the message loop.
+        task::spawn(async move {
+            if let Some(result) = call_primitive_tool(&name, arguments, &config, &arg0_paths).await
+            {
+                outgoing.send_response(id, result).await;
+            }
+        });
+    }
+
     async fn handle_tool_call_codex(
         &self,
         id: RequestId,
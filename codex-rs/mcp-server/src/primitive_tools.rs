@@ -0,0 +1,410 @@
+//! Tools that expose individual Codex capabilities, i.e. applying a patch,
+//! running a command and searching files, without starting a Codex session.
+//!
+//! Each call runs once under the sandbox policy of the server's own
+//! [`Config`]. There is no model in the loop, so nothing is ever escalated
+//! for approval: a command the sandbox denies fails.
+
+use std::path::PathBuf;
+
+use codex_apply_patch::CODEX_CORE_APPLY_PATCH_ARG1;
+use codex_arg0::Arg0DispatchPaths;
+use codex_core::config::Config;
+use codex_core::error::CodexErr;
+use codex_core::error::get_error_message_ui;
+use codex_core::exec::ExecParams;
+use codex_core::exec::ExecToolCallOutput;
+use codex_core::exec::process_exec_tool_call;
+use codex_core::exec_env::create_env;
+use codex_core::features::Feature;
+use codex_core::sandboxing::SandboxPermissions;
+use codex_core::windows_sandbox::WindowsSandboxLevelExt;
+use codex_protocol::config_types::WindowsSandboxLevel;
+use rmcp::model::CallToolResult;
+use rmcp::model::JsonObject;
+use rmcp::model::Tool;
+use schemars::JsonSchema;
+use schemars::r#gen::SchemaSettings;
+use serde::Deserialize;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use crate::codex_tool_config::create_tool_input_schema;
+
+pub(crate) const APPLY_PATCH_TOOL_NAME: &str = "apply-patch";
+pub(crate) const SHELL_TOOL_NAME: &str = "shell";
+pub(crate) const SEARCH_TOOL_NAME: &str = "search";
+
+const DEFAULT_SEARCH_LIMIT: u32 = 100;
+const MAX_SEARCH_LIMIT: u32 = 2000;
+
+/// Arguments for the `apply-patch` tool-call.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct ApplyPatchToolCallParam {
+    /// The patch to apply, starting with `*** Begin Patch` and ending with
+    /// `*** End Patch`.
+    pub patch: String,
+
+    /// Directory the patch's paths are relative to. If relative, it is
+    /// resolved against the server's working directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+}
+
+/// Arguments for the `shell` tool-call.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct ShellToolCallParam {
+    /// The command to run, as a program followed by its arguments.
+    pub command: Vec<String>,
+
+    /// Working directory for the command. If relative, it is resolved
+    /// against the server's working directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+
+    /// Time in milliseconds after which the command is killed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Arguments for the `search` tool-call.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct SearchToolCallParam {
+    /// Regular expression to search file contents for.
+    pub pattern: String,
+
+    /// Glob limiting which files are searched (e.g. '*.rs').
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include: Option<String>,
+
+    /// File or directory to search. If relative, it is resolved against the
+    /// server's working directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    /// Maximum number of matching lines to return. Defaults to 100.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+pub(crate) fn create_tool_for_apply_patch_tool_call_param() -> Tool {
+    primitive_tool::<ApplyPatchToolCallParam>(
+        APPLY_PATCH_TOOL_NAME,
+        "Apply Patch",
+        "Apply a patch to files in the workspace, in the format of Codex's apply_patch tool.",
+    )
+}
+
+pub(crate) fn create_tool_for_shell_tool_call_param() -> Tool {
+    primitive_tool::<ShellToolCallParam>(
+        SHELL_TOOL_NAME,
+        "Shell",
+        "Run a command in the Codex sandbox and return its output.",
+    )
+}
+
+pub(crate) fn create_tool_for_search_tool_call_param() -> Tool {
+    primitive_tool::<SearchToolCallParam>(
+        SEARCH_TOOL_NAME,
+        "Search",
+        "Search file contents with ripgrep and return the matching lines.",
+    )
+}
+
+fn primitive_tool<T: JsonSchema>(name: &str, title: &str, description: &str) -> Tool {
+    let schema = SchemaSettings::draft2019_09()
+        .with(|s| {
+            s.inline_subschemas = true;
+            s.option_add_null_type = false;
+        })
+        .into_generator()
+        .into_root_schema_for::<T>();
+
+    let input_schema = create_tool_input_schema(schema, "primitive tool schema should serialize");
+
+    Tool {
+        name: name.to_string().into(),
+        title: Some(title.to_string()),
+        input_schema,
+        output_schema: None,
+        description: Some(description.to_string().into()),
+        annotations: None,
+        execution: None,
+        icons: None,
+        meta: None,
+    }
+}
+
+/// Runs the primitive tool `name`, returning `None` if there is no such tool.
+pub(crate) async fn call_primitive_tool(
+    name: &str,
+    arguments: Option<JsonObject>,
+    config: &Config,
+    arg0_paths: &Arg0DispatchPaths,
+) -> Option<CallToolResult> {
+    let result = match name {
+        APPLY_PATCH_TOOL_NAME => {
+            match parse_arguments::<ApplyPatchToolCallParam>(name, arguments) {
+                Ok(param) => apply_patch(param, config, arg0_paths).await,
+                Err(result) => result,
+            }
+        }
+        SHELL_TOOL_NAME => match parse_arguments::<ShellToolCallParam>(name, arguments) {
+            Ok(param) => shell(param, config, arg0_paths).await,
+            Err(result) => result,
+        },
+        SEARCH_TOOL_NAME => match parse_arguments::<SearchToolCallParam>(name, arguments) {
+            Ok(param) => search(param, config, arg0_paths).await,
+            Err(result) => result,
+        },
+        _ => return None,
+    };
+    Some(result)
+}
+
+fn parse_arguments<T: DeserializeOwned>(
+    name: &str,
+    arguments: Option<JsonObject>,
+) -> Result<T, CallToolResult> {
+    let arguments = serde_json::Value::Object(arguments.unwrap_or_default());
+    serde_json::from_value(arguments).map_err(|e| {
+        error_result(format!(
+            "Failed to parse arguments for {name} tool-call: {e}"
+        ))
+    })
+}
+
+async fn apply_patch(
+    param: ApplyPatchToolCallParam,
+    config: &Config,
+    arg0_paths: &Arg0DispatchPaths,
+) -> CallToolResult {
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => return error_result(format!("failed to determine codex exe: {e}")),
+    };
+    let command = vec![
+        exe.to_string_lossy().to_string(),
+        CODEX_CORE_APPLY_PATCH_ARG1.to_string(),
+        param.patch,
+    ];
+    // Like the agent's apply_patch, run with an empty environment for
+    // determinism and to avoid leaks.
+    let result = run_sandboxed(
+        command,
+        resolve_cwd(config, param.cwd.as_deref()),
+        None,
+        false,
+        config,
+        arg0_paths,
+    )
+    .await;
+    exec_result(result)
+}
+
+async fn shell(
+    param: ShellToolCallParam,
+    config: &Config,
+    arg0_paths: &Arg0DispatchPaths,
+) -> CallToolResult {
+    if param.command.is_empty() {
+        return error_result("command must not be empty".to_string());
+    }
+    let result = run_sandboxed(
+        param.command,
+        resolve_cwd(config, param.cwd.as_deref()),
+        param.timeout_ms,
+        true,
+        config,
+        arg0_paths,
+    )
+    .await;
+    exec_result(result)
+}
+
+async fn search(
+    param: SearchToolCallParam,
+    config: &Config,
+    arg0_paths: &Arg0DispatchPaths,
+) -> CallToolResult {
+    let limit = param
+        .limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+    let mut command = vec![
+        "rg".to_string(),
+        "--line-number".to_string(),
+        "--no-heading".to_string(),
+        "--color".to_string(),
+        "never".to_string(),
+        "--no-messages".to_string(),
+        "--regexp".to_string(),
+        param.pattern,
+    ];
+    if let Some(include) = param.include {
+        command.push("--glob".to_string());
+        command.push(include);
+    }
+    command.push("--".to_string());
+    command.push(param.path.unwrap_or_else(|| ".".to_string()));
+
+    let output =
+        match run_sandboxed(command, config.cwd.clone(), None, true, config, arg0_paths).await {
+            Ok(output) => output,
+            Err(e) => return error_result(get_error_message_ui(&e)),
+        };
+    match output.exit_code {
+        0 => text_result(format_search_matches(&output.stdout.text, limit), false),
+        // rg exits with 1 when nothing matched.
+        1 => text_result("No matches found.".to_string(), false),
+        _ => exec_result(Ok(output)),
+    }
+}
+
+fn format_search_matches(stdout: &str, limit: u32) -> String {
+    let lines: Vec<&str> = stdout.lines().collect();
+    let limit = limit as usize;
+    if lines.len() <= limit {
+        return lines.join("\n");
+    }
+    let omitted = lines.len() - limit;
+    format!(
+        "{}\n... {omitted} more matching lines omitted",
+        lines[..limit].join("\n")
+    )
+}
+
+fn resolve_cwd(config: &Config, cwd: Option<&str>) -> PathBuf {
+    match cwd {
+        Some(cwd) => config.cwd.join(cwd),
+        None => config.cwd.clone(),
+    }
+}
+
+async fn run_sandboxed(
+    command: Vec<String>,
+    cwd: PathBuf,
+    timeout_ms: Option<u64>,
+    inherit_env: bool,
+    config: &Config,
+    arg0_paths: &Arg0DispatchPaths,
+) -> Result<ExecToolCallOutput, CodexErr> {
+    let env = if inherit_env {
+        create_env(&config.permissions.shell_environment_policy, None)
+    } else {
+        Default::default()
+    };
+    let params = ExecParams {
+        command,
+        cwd,
+        expiration: timeout_ms.into(),
+        env,
+        network: None,
+        sandbox_permissions: SandboxPermissions::UseDefault,
+        windows_sandbox_level: WindowsSandboxLevel::from_config(config),
+        justification: None,
+        arg0: None,
+    };
+    process_exec_tool_call(
+        params,
+        config.permissions.sandbox_policy.get(),
+        &config.permissions.file_system_sandbox_policy,
+        config.permissions.network_sandbox_policy,
+        &config.cwd,
+        &arg0_paths.codex_linux_sandbox_exe,
+        config.features.enabled(Feature::UseLinuxSandboxBwrap),
+        None,
+    )
+    .await
+}
+
+fn exec_result(result: Result<ExecToolCallOutput, CodexErr>) -> CallToolResult {
+    let output = match result {
+        Ok(output) => output,
+        Err(e) => return error_result(get_error_message_ui(&e)),
+    };
+    let text = output.aggregated_output.text;
+    CallToolResult {
+        content: vec![rmcp::model::Content::text(text.clone())],
+        structured_content: Some(json!({
+            "exitCode": output.exit_code,
+            "output": text,
+        })),
+        is_error: Some(output.exit_code != 0),
+        meta: None,
+    }
+}
+
+fn text_result(text: String, is_error: bool) -> CallToolResult {
+    CallToolResult {
+        content: vec![rmcp::model::Content::text(text)],
+        structured_content: None,
+        is_error: Some(is_error),
+        meta: None,
+    }
+}
+
+fn error_result(message: String) -> CallToolResult {
+    text_result(message, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn verify_search_tool_json_schema() {
+        let tool = create_tool_for_search_tool_call_param();
+        let tool_json = serde_json::to_value(&tool).expect("tool serializes");
+        let expected_tool_json = serde_json::json!({
+          "description": "Search file contents with ripgrep and return the matching lines.",
+          "inputSchema": {
+            "properties": {
+              "include": {
+                "description": "Glob limiting which files are searched (e.g. '*.rs').",
+                "type": "string"
+              },
+              "limit": {
+                "description": "Maximum number of matching lines to return. Defaults to 100.",
+                "format": "uint32",
+                "minimum": 0.0,
+                "type": "integer"
+              },
+              "path": {
+                "description": "File or directory to search. If relative, it is resolved against the server's working directory.",
+                "type": "string"
+              },
+              "pattern": {
+                "description": "Regular expression to search file contents for.",
+                "type": "string"
+              }
+            },
+            "required": [
+              "pattern"
+            ],
+            "type": "object"
+          },
+          "name": "search",
+          "title": "Search"
+        });
+        assert_eq!(expected_tool_json, tool_json);
+    }
+
+    #[test]
+    fn search_matches_are_truncated_to_limit() {
+        let stdout = "a.rs:1:foo\nb.rs:2:foo\nc.rs:3:foo\n";
+        assert_eq!(
+            format_search_matches(stdout, 3),
+            "a.rs:1:foo\nb.rs:2:foo\nc.rs:3:foo"
+        );
+        assert_eq!(
+            format_search_matches(stdout, 2),
+            "a.rs:1:foo\nb.rs:2:foo\n... 1 more matching lines omitted"
+        );
+    }
+}
@@ -79,6 +79,78 @@ fn ipv4_in_cidr(ip: Ipv4Addr, base: [u8; 4], prefix: u8) -> bool {
     (ip & mask) == (base & mask)
 }
 
+fn ipv6_in_cidr(ip: Ipv6Addr, base: Ipv6Addr, prefix: u8) -> bool {
+    let mask = if prefix == 0 {
+        0
+    } else {
+        u128::MAX << (128 - prefix)
+    };
+    (u128::from(ip) & mask) == (u128::from(base) & mask)
+}
+
+/// An IP range listed in `allowed_domains` or `denied_domains`, such as
+/// `10.0.0.0/8` or `2001:db8::/32`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpCidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl IpCidr {
+    /// Whether a policy entry is written as an IP range rather than a host
+    /// pattern.
+    pub(crate) fn is_cidr_pattern(pattern: &str) -> bool {
+        pattern.contains('/')
+    }
+
+    pub fn parse(pattern: &str) -> Result<Self> {
+        let pattern = pattern.trim();
+        let (address, prefix) = pattern
+            .split_once('/')
+            .with_context(|| format!("invalid CIDR range: {pattern}"))?;
+        let network = address
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .with_context(|| format!("invalid CIDR range: {pattern}"))?;
+        let prefix = prefix
+            .parse::<u8>()
+            .with_context(|| format!("invalid CIDR range: {pattern}"))?;
+        let max_prefix = match network {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        ensure!(
+            prefix <= max_prefix,
+            "invalid CIDR range: {pattern} (prefix is longer than {max_prefix} bits)"
+        );
+        Ok(Self { network, prefix })
+    }
+
+    /// IPv4-mapped IPv6 addresses are matched against IPv4 ranges.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                ipv4_in_cidr(ip, network.octets(), self.prefix)
+            }
+            (IpAddr::V4(network), IpAddr::V6(ip)) => ip
+                .to_ipv4_mapped()
+                .is_some_and(|ip| ipv4_in_cidr(ip, network.octets(), self.prefix)),
+            (IpAddr::V6(network), IpAddr::V6(ip)) => ipv6_in_cidr(ip, network, self.prefix),
+            (IpAddr::V6(_), IpAddr::V4(_)) => false,
+        }
+    }
+}
+
+/// Parses the IP range entries of a domain list; host patterns are skipped.
+pub(crate) fn compile_cidrs(patterns: &[String]) -> Result<Vec<IpCidr>> {
+    patterns
+        .iter()
+        .filter(|pattern| IpCidr::is_cidr_pattern(pattern))
+        .map(|pattern| IpCidr::parse(pattern))
+        .collect()
+}
+
 fn is_non_public_ipv6(ip: Ipv6Addr) -> bool {
     if let Some(v4) = ip.to_ipv4() {
         return is_non_public_ipv4(v4) || ip.is_loopback();
@@ -155,6 +227,10 @@ pub(crate) fn compile_globset(patterns: &[String]) -> Result<GlobSet> {
     let mut builder = GlobSetBuilder::new();
     let mut seen = HashSet::new();
     for pattern in patterns {
+        // IP ranges are matched by `compile_cidrs`.
+        if IpCidr::is_cidr_pattern(pattern) {
+            continue;
+        }
         ensure!(
             !is_global_wildcard_domain_pattern(pattern),
             "unsupported global wildcard domain pattern \"*\"; use exact hosts or scoped wildcards like *.example.com or **.example.com"
@@ -362,6 +438,41 @@ mod tests {
         assert_eq!(true, set.is_match("::1"));
     }
 
+    #[test]
+    fn compile_globset_skips_cidr_ranges() {
+        let set = compile_globset(&["10.0.0.0/8".to_string()]).unwrap();
+
+        assert_eq!(false, set.is_match("10.0.0.0/8"));
+        assert_eq!(false, set.is_match("10.0.0.1"));
+    }
+
+    #[test]
+    fn ip_cidr_matches_addresses_in_range() {
+        let v4 = IpCidr::parse("10.1.0.0/16").unwrap();
+        assert!(v4.contains("10.1.2.3".parse().unwrap()));
+        assert!(v4.contains("::ffff:10.1.2.3".parse().unwrap()));
+        assert!(!v4.contains("10.2.0.1".parse().unwrap()));
+
+        let v6 = IpCidr::parse("[2001:db8::]/32").unwrap();
+        assert!(v6.contains("2001:db8:1::1".parse().unwrap()));
+        assert!(!v6.contains("2001:db9::1".parse().unwrap()));
+        assert!(!v6.contains("10.1.2.3".parse().unwrap()));
+
+        assert!(
+            IpCidr::parse("0.0.0.0/0")
+                .unwrap()
+                .contains("8.8.8.8".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn ip_cidr_rejects_malformed_ranges() {
+        assert!(IpCidr::parse("10.0.0.0/33").is_err());
+        assert!(IpCidr::parse("example.com/8").is_err());
+        assert!(IpCidr::parse("10.0.0.0/").is_err());
+        assert!(compile_cidrs(&["example.com".to_string(), "::/129".to_string()]).is_err());
+    }
+
     #[test]
     fn is_loopback_host_handles_localhost_variants() {
         assert!(is_loopback_host(&Host::parse("localhost").unwrap()));
@@ -3,6 +3,7 @@ use crate::config::NetworkProxyConfig;
 use crate::config::ValidatedUnixSocketPath;
 use crate::mitm::MitmState;
 use crate::policy::Host;
+use crate::policy::IpCidr;
 use crate::policy::is_loopback_host;
 use crate::policy::is_non_public_ip;
 use crate::policy::normalize_host;
@@ -155,6 +156,10 @@ pub struct ConfigState {
     pub config: NetworkProxyConfig,
     pub allow_set: GlobSet,
     pub deny_set: GlobSet,
+    /// IP ranges from `allowed_domains`, which `allow_set` leaves out.
+    pub allow_cidrs: Vec<IpCidr>,
+    /// IP ranges from `denied_domains`, which `deny_set` leaves out.
+    pub deny_cidrs: Vec<IpCidr>,
     pub mitm: Option<Arc<MitmState>>,
     pub constraints: NetworkProxyConstraints,
     pub blocked: VecDeque<BlockedRequest>,
@@ -340,11 +345,21 @@ impl NetworkProxyState {
             Ok(host) => host,
             Err(_) => return Ok(HostBlockDecision::Blocked(HostBlockReason::NotAllowed)),
         };
-        let (deny_set, allow_set, allow_local_binding, allowed_domains_empty, allowed_domains) = {
+        let (
+            deny_set,
+            allow_set,
+            deny_cidrs,
+            allow_cidrs,
+            allow_local_binding,
+            allowed_domains_empty,
+            allowed_domains,
+        ) = {
             let guard = self.state.read().await;
             (
                 guard.deny_set.clone(),
                 guard.allow_set.clone(),
+                guard.deny_cidrs.clone(),
+                guard.allow_cidrs.clone(),
                 guard.config.network.allow_local_binding,
                 guard.config.network.allowed_domains.is_empty(),
                 guard.config.network.allowed_domains.clone(),
@@ -352,16 +367,37 @@ impl NetworkProxyState {
         };
 
         let host_str = host.as_str();
+        let host_no_scope = host_str
+            .split_once('%')
+            .map(|(ip, _)| ip)
+            .unwrap_or(host_str);
+        let ip_literal = host_no_scope.parse::<IpAddr>().ok();
+        let in_any = |cidrs: &[IpCidr], ip: IpAddr| cidrs.iter().any(|cidr| cidr.contains(ip));
 
         // Decision order matters:
         //  1) explicit deny always wins
         //  2) local/private networking is opt-in (defense-in-depth)
         //  3) allowlist is enforced when configured
-        if deny_set.is_match(host_str) {
+        if deny_set.is_match(host_str) || ip_literal.is_some_and(|ip| in_any(&deny_cidrs, ip)) {
             return Ok(HostBlockDecision::Blocked(HostBlockReason::Denied));
         }
 
-        let is_allowlisted = allow_set.is_match(host_str);
+        // Hostnames are checked against denied ranges by the addresses they resolve to, so a
+        // denied range cannot be reached through a name. Allowed ranges only apply to IP
+        // literals; names are allowed by domain patterns.
+        let resolved = if ip_literal.is_none()
+            && (!deny_cidrs.is_empty() || (!allow_local_binding && !is_loopback_host(&host)))
+        {
+            resolve_host(host_str, port).await
+        } else {
+            Vec::new()
+        };
+        if resolved.iter().any(|ip| in_any(&deny_cidrs, *ip)) {
+            return Ok(HostBlockDecision::Blocked(HostBlockReason::Denied));
+        }
+
+        let cidr_allowlisted = ip_literal.is_some_and(|ip| in_any(&allow_cidrs, ip));
+        let is_allowlisted = allow_set.is_match(host_str) || cidr_allowlisted;
         if !allow_local_binding {
             // If the intent is "prevent access to local/internal networks", we must not rely solely
             // on string checks like `localhost` / `127.0.0.1`. Attackers can use DNS rebinding or
@@ -371,25 +407,13 @@ impl NetworkProxyState {
             // request. Explicit local/loopback literals are allowed only when explicitly
             // allowlisted; hostnames that resolve to local/private IPs are blocked even if
             // allowlisted.
-            let local_literal = {
-                let host_no_scope = host_str
-                    .split_once('%')
-                    .map(|(ip, _)| ip)
-                    .unwrap_or(host_str);
-                if is_loopback_host(&host) {
-                    true
-                } else if let Ok(ip) = host_no_scope.parse::<IpAddr>() {
-                    is_non_public_ip(ip)
-                } else {
-                    false
-                }
-            };
+            let local_literal = is_loopback_host(&host) || ip_literal.is_some_and(is_non_public_ip);
 
             if local_literal {
-                if !is_explicit_local_allowlisted(&allowed_domains, &host) {
+                if !cidr_allowlisted && !is_explicit_local_allowlisted(&allowed_domains, &host) {
                     return Ok(HostBlockDecision::Blocked(HostBlockReason::NotAllowedLocal));
                 }
-            } else if host_resolves_to_non_public_ip(host_str, port).await {
+            } else if resolved.iter().any(|ip| is_non_public_ip(*ip)) {
                 return Ok(HostBlockDecision::Blocked(HostBlockReason::NotAllowedLocal));
             }
         }
@@ -693,26 +717,15 @@ pub(crate) fn unix_socket_permissions_supported() -> bool {
     cfg!(target_os = "macos")
 }
 
-async fn host_resolves_to_non_public_ip(host: &str, port: u16) -> bool {
-    if let Ok(ip) = host.parse::<IpAddr>() {
-        return is_non_public_ip(ip);
-    }
-
-    // If DNS lookup fails, default to "not local/private" rather than blocking. In practice, the
-    // subsequent connect attempt will fail anyway, and blocking on transient resolver issues would
-    // make the proxy fragile. The allowlist/denylist remains the primary control plane.
-    let addrs = match timeout(DNS_LOOKUP_TIMEOUT, lookup_host((host, port))).await {
-        Ok(Ok(addrs)) => addrs,
-        Ok(Err(_)) | Err(_) => return false,
-    };
-
-    for addr in addrs {
-        if is_non_public_ip(addr.ip()) {
-            return true;
-        }
+async fn resolve_host(host: &str, port: u16) -> Vec<IpAddr> {
+    // If DNS lookup fails, resolve to nothing, i.e. "not local/private and not in a denied range",
+    // rather than blocking. In practice, the subsequent connect attempt will fail anyway, and
+    // blocking on transient resolver issues would make the proxy fragile. The allowlist/denylist
+    // remains the primary control plane.
+    match timeout(DNS_LOOKUP_TIMEOUT, lookup_host((host, port))).await {
+        Ok(Ok(addrs)) => addrs.map(|addr| addr.ip()).collect(),
+        Ok(Err(_)) | Err(_) => Vec::new(),
     }
-
-    false
 }
 
 fn log_policy_changes(previous: &NetworkProxyConfig, next: &NetworkProxyConfig) {
@@ -858,6 +871,52 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn host_blocked_matches_ip_literals_against_cidr_ranges() {
+        let state = network_proxy_state_for_policy(NetworkProxySettings {
+            allowed_domains: vec!["8.8.0.0/16".to_string(), "10.0.0.0/8".to_string()],
+            denied_domains: vec!["8.8.4.0/24".to_string()],
+            ..NetworkProxySettings::default()
+        });
+
+        assert_eq!(
+            state.host_blocked("8.8.8.8", 443).await.unwrap(),
+            HostBlockDecision::Allowed
+        );
+        assert_eq!(
+            state.host_blocked("8.8.4.4", 443).await.unwrap(),
+            HostBlockDecision::Blocked(HostBlockReason::Denied)
+        );
+        assert_eq!(
+            state.host_blocked("1.1.1.1", 443).await.unwrap(),
+            HostBlockDecision::Blocked(HostBlockReason::NotAllowed)
+        );
+        // An allowed range counts as explicitly allowlisting the local addresses in it.
+        assert_eq!(
+            state.host_blocked("10.1.2.3", 443).await.unwrap(),
+            HostBlockDecision::Allowed
+        );
+        assert_eq!(
+            state.host_blocked("192.168.0.1", 443).await.unwrap(),
+            HostBlockDecision::Blocked(HostBlockReason::NotAllowedLocal)
+        );
+    }
+
+    #[tokio::test]
+    async fn host_blocked_denies_hostnames_resolving_into_denied_ranges() {
+        let state = network_proxy_state_for_policy(NetworkProxySettings {
+            allowed_domains: vec!["localhost".to_string()],
+            denied_domains: vec!["127.0.0.0/8".to_string(), "::1/128".to_string()],
+            allow_local_binding: true,
+            ..NetworkProxySettings::default()
+        });
+
+        assert_eq!(
+            state.host_blocked("localhost", 80).await.unwrap(),
+            HostBlockDecision::Blocked(HostBlockReason::Denied)
+        );
+    }
+
     #[tokio::test]
     async fn add_allowed_domain_removes_matching_deny_entry() {
         let state = network_proxy_state_for_policy(NetworkProxySettings {
@@ -2,6 +2,7 @@ use crate::config::NetworkMode;
 use crate::config::NetworkProxyConfig;
 use crate::mitm::MitmState;
 use crate::policy::DomainPattern;
+use crate::policy::compile_cidrs;
 use crate::policy::compile_globset;
 use crate::policy::is_global_wildcard_domain_pattern;
 use crate::runtime::ConfigState;
@@ -65,6 +66,8 @@ pub fn build_config_state(
         .map_err(NetworkProxyConstraintError::into_anyhow)?;
     let deny_set = compile_globset(&config.network.denied_domains)?;
     let allow_set = compile_globset(&config.network.allowed_domains)?;
+    let deny_cidrs = compile_cidrs(&config.network.denied_domains)?;
+    let allow_cidrs = compile_cidrs(&config.network.allowed_domains)?;
     let mitm = if config.network.mitm {
         Some(Arc::new(MitmState::new(
             config.network.allow_upstream_proxy,
@@ -76,6 +79,8 @@ pub fn build_config_state(
         config,
         allow_set,
         deny_set,
+        allow_cidrs,
+        deny_cidrs,
         mitm,
         constraints,
         blocked: std::collections::VecDeque::new(),
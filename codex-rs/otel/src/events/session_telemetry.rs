@@ -17,6 +17,8 @@ use crate::metrics::names::RESPONSES_API_INFERENCE_TIME_DURATION_METRIC;
 use crate::metrics::names::RESPONSES_API_OVERHEAD_DURATION_METRIC;
 use crate::metrics::names::SSE_EVENT_COUNT_METRIC;
 use crate::metrics::names::SSE_EVENT_DURATION_METRIC;
+use crate::metrics::names::TOOL_CACHE_HIT_METRIC;
+use crate::metrics::names::TOOL_CACHE_MISS_METRIC;
 use crate::metrics::names::TOOL_CALL_COUNT_METRIC;
 use crate::metrics::names::TOOL_CALL_DURATION_METRIC;
 use crate::metrics::names::WEBSOCKET_EVENT_COUNT_METRIC;
@@ -773,6 +775,16 @@ impl SessionTelemetry {
         result
     }
 
+    /// Counts a lookup of `tool_name`'s output in the tool output cache.
+    pub fn tool_cache_lookup(&self, tool_name: &str, hit: bool) {
+        let metric = if hit {
+            TOOL_CACHE_HIT_METRIC
+        } else {
+            TOOL_CACHE_MISS_METRIC
+        };
+        self.counter(metric, 1, &[("tool", tool_name)]);
+    }
+
     pub fn log_tool_failed(&self, tool_name: &str, error: &str) {
         log_event!(
             self,
@@ -926,11 +938,15 @@ impl SessionTelemetry {
             ResponseItem::FunctionCall { .. } => "function_call".into(),
             ResponseItem::FunctionCallOutput { .. } => "function_call_output".into(),
             ResponseItem::CustomToolCall { .. } => "custom_tool_call".into(),
+            ResponseItem::CustomToolCallWithAttachments { .. } => {
+                "custom_tool_call_with_attachments".into()
+            }
             ResponseItem::CustomToolCallOutput { .. } => "custom_tool_call_output".into(),
             ResponseItem::WebSearchCall { .. } => "web_search_call".into(),
             ResponseItem::ImageGenerationCall { .. } => "image_generation_call".into(),
             ResponseItem::GhostSnapshot { .. } => "ghost_snapshot".into(),
             ResponseItem::Compaction { .. } => "compaction".into(),
+            ResponseItem::UnsupportedToolCall { .. } => "unsupported_tool_call".into(),
             ResponseItem::Other => "other".into(),
         }
     }
@@ -1,5 +1,7 @@
 pub(crate) const TOOL_CALL_COUNT_METRIC: &str = "codex.tool.call";
 pub(crate) const TOOL_CALL_DURATION_METRIC: &str = "codex.tool.call.duration_ms";
+pub(crate) const TOOL_CACHE_HIT_METRIC: &str = "codex.tool.cache.hit";
+pub(crate) const TOOL_CACHE_MISS_METRIC: &str = "codex.tool.cache.miss";
 pub(crate) const API_CALL_COUNT_METRIC: &str = "codex.api_request";
 pub(crate) const API_CALL_DURATION_METRIC: &str = "codex.api_request.duration_ms";
 pub(crate) const SSE_EVENT_COUNT_METRIC: &str = "codex.sse_event";
@@ -31,6 +31,22 @@ pub fn current_span_w3c_trace_context() -> Option<W3cTraceContext> {
     })
 }
 
+/// The trace context to propagate on an outbound request made on behalf of
+/// `parent`: the current span's, when that span belongs to `parent`'s trace,
+/// otherwise `parent` itself.
+pub fn current_span_w3c_trace_context_within(parent: &W3cTraceContext) -> W3cTraceContext {
+    let parent_trace_id = context_from_w3c_trace_context(parent)
+        .map(|context| context.span().span_context().trace_id());
+    let current = Span::current().context();
+    let current_span = current.span();
+    let current_span_context = current_span.span_context();
+    if current_span_context.is_valid() && Some(current_span_context.trace_id()) == parent_trace_id {
+        current_span_w3c_trace_context().unwrap_or_else(|| parent.clone())
+    } else {
+        parent.clone()
+    }
+}
+
 pub fn current_span_trace_id() -> Option<String> {
     let context = Span::current().context();
     let span = context.span();
@@ -59,6 +75,16 @@ pub fn set_parent_from_context(span: &Span, context: Context) {
     let _ = span.set_parent(context);
 }
 
+/// Sets each `(key, value)` pair as a string attribute on `span`'s OTel span.
+pub fn set_span_attributes<'a>(
+    span: &Span,
+    attributes: impl IntoIterator<Item = (&'a str, &'a str)>,
+) {
+    for (key, value) in attributes {
+        span.set_attribute(key.to_string(), value.to_string());
+    }
+}
+
 pub fn traceparent_context_from_env() -> Option<Context> {
     TRACEPARENT_CONTEXT
         .get_or_init(load_traceparent_context)
@@ -104,6 +130,8 @@ mod tests {
     use super::context_from_trace_headers;
     use super::context_from_w3c_trace_context;
     use super::current_span_trace_id;
+    use super::current_span_w3c_trace_context_within;
+    use super::set_parent_from_w3c_trace_context;
     use codex_protocol::protocol::W3cTraceContext;
     use opentelemetry::trace::SpanId;
     use opentelemetry::trace::TraceContextExt;
@@ -151,6 +179,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn trace_context_within_parent_prefers_child_span() {
+        let parent = W3cTraceContext {
+            traceparent: Some("00-00000000000000000000000000000011-0000000000000022-01".into()),
+            tracestate: Some("vendor=value".into()),
+        };
+        // Outside of any span, the parent is propagated unchanged.
+        assert_eq!(current_span_w3c_trace_context_within(&parent), parent);
+
+        let provider = SdkTracerProvider::builder().build();
+        let tracer = provider.tracer("codex-otel-tests");
+        let subscriber =
+            tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer));
+        let _guard = subscriber.set_default();
+
+        let span = trace_span!("child");
+        assert!(set_parent_from_w3c_trace_context(&span, &parent));
+        let _entered = span.enter();
+        let child = current_span_w3c_trace_context_within(&parent);
+
+        let traceparent = child.traceparent.expect("traceparent");
+        assert!(
+            traceparent.starts_with("00-00000000000000000000000000000011-"),
+            "unexpected traceparent: {traceparent}"
+        );
+        assert_ne!(
+            traceparent,
+            "00-00000000000000000000000000000011-0000000000000022-01"
+        );
+        assert_eq!(child.tracestate.as_deref(), Some("vendor=value"));
+    }
+
     #[test]
     fn current_span_trace_id_returns_hex_trace_id() {
         let provider = SdkTracerProvider::builder().build();
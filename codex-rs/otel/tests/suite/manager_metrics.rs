@@ -153,3 +153,46 @@ fn manager_attaches_optional_service_name_tag() -> Result<()> {
 
     Ok(())
 }
+
+// Ensures tool cache lookups are counted as hits and misses per tool.
+#[test]
+fn manager_counts_tool_cache_hits_and_misses() -> Result<()> {
+    let (metrics, exporter) = build_metrics_with_defaults(&[])?;
+    let manager = SessionTelemetry::new(
+        ThreadId::new(),
+        "gpt-5.1",
+        "gpt-5.1",
+        None,
+        None,
+        None,
+        "test_originator".to_string(),
+        false,
+        "tty".to_string(),
+        SessionSource::Cli,
+    )
+    .with_metrics_without_metadata_tags(metrics);
+
+    manager.tool_cache_lookup("read_file", false);
+    manager.tool_cache_lookup("read_file", true);
+    manager.tool_cache_lookup("read_file", true);
+    manager.shutdown_metrics()?;
+
+    let resource_metrics = latest_metrics(&exporter);
+    let sum_of = |name: &str| {
+        let metric = find_metric(&resource_metrics, name).expect("counter metric missing");
+        match metric.data() {
+            AggregatedMetrics::U64(MetricData::Sum(sum)) => {
+                let points: Vec<_> = sum.data_points().collect();
+                assert_eq!(points.len(), 1);
+                (points[0].value(), attributes_to_map(points[0].attributes()))
+            }
+            _ => panic!("unexpected counter data"),
+        }
+    };
+
+    let tags = BTreeMap::from([("tool".to_string(), "read_file".to_string())]);
+    assert_eq!(sum_of("codex.tool.cache.hit"), (2, tags.clone()));
+    assert_eq!(sum_of("codex.tool.cache.miss"), (1, tags));
+
+    Ok(())
+}
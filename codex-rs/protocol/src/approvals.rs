@@ -260,3 +260,49 @@ pub struct ApplyPatchApprovalRequestEvent {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub grant_root: Option<PathBuf>,
 }
+
+/// Asks the client to accept or reject each hunk of a patch before it is
+/// applied. Sent instead of [`ApplyPatchApprovalRequestEvent`] when the
+/// `patch_hunk_review` feature is enabled; answered with `Op::PatchReview`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct PatchReviewRequestEvent {
+    /// Responses API call id for the associated patch apply call.
+    pub call_id: String,
+    /// Turn ID that this patch belongs to.
+    #[serde(default)]
+    pub turn_id: String,
+    pub hunks: Vec<PatchReviewHunk>,
+    /// Optional explanatory reason (e.g. request for extra write access).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// One added or deleted file, or one changed region of an updated file.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct PatchReviewHunk {
+    /// Identifies the hunk in the [`PatchReviewResponse`].
+    pub index: u32,
+    pub path: PathBuf,
+    /// Destination of the file when the hunk belongs to a rename.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub move_path: Option<PathBuf>,
+    /// Diff lines prefixed with `-`, `+` or a space for context.
+    pub diff: String,
+}
+
+/// The client's answer to a [`PatchReviewRequestEvent`]. Hunks missing from
+/// `accepted_hunks` are rejected; `rejected_hunks` only adds the reasons,
+/// which are passed back to the model.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct PatchReviewResponse {
+    pub accepted_hunks: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rejected_hunks: Vec<PatchHunkRejection>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct PatchHunkRejection {
+    pub index: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
@@ -315,6 +315,23 @@ pub enum ResponseItem {
         name: String,
         input: String,
     },
+    // A freeform tool call whose text `input` comes with attachments, such as
+    // images or references to resources the tool should read. Answered with a
+    // `custom_tool_call_output`, like `custom_tool_call`.
+    CustomToolCallWithAttachments {
+        #[serde(default, skip_serializing)]
+        #[ts(skip)]
+        id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        status: Option<String>,
+
+        call_id: String,
+        name: String,
+        input: String,
+        #[serde(default)]
+        attachments: Vec<ToolCallAttachment>,
+    },
     // `custom_tool_call_output.output` uses the same wire encoding as
     // `function_call_output.output` so freeform tools can return either plain
     // text or structured content items.
@@ -366,6 +383,17 @@ pub enum ResponseItem {
     Compaction {
         encrypted_content: String,
     },
+    // An output item of a type this client does not know that carries a
+    // `call_id`, i.e. a tool call it cannot run. The stream parser builds it
+    // in place of `Other` so the call fails visibly instead of vanishing.
+    UnsupportedToolCall {
+        // The item's `type` as the provider sent it.
+        item_type: String,
+        call_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        name: Option<String>,
+    },
     #[serde(other)]
     Other,
 }
@@ -857,6 +885,7 @@ impl From<ResponseInputItem> for ResponseItem {
                     Err(tool_call_err) => FunctionCallOutputPayload {
                         body: FunctionCallOutputBody::Text(format!("err: {tool_call_err:?}")),
                         success: Some(false),
+                        error_code: None,
                     },
                 };
                 Self::FunctionCallOutput { call_id, output }
@@ -889,6 +918,25 @@ pub struct LocalShellExecAction {
     pub working_directory: Option<String>,
     pub env: Option<HashMap<String, String>>,
     pub user: Option<String>,
+    /// Why the command needs to run, shown when approval is requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub justification: Option<String>,
+}
+
+/// Content attached to a [`ResponseItem::CustomToolCallWithAttachments`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolCallAttachment {
+    /// An image, as a data URL or a remote URL.
+    InputImage { image_url: String },
+    /// A resource the tool can fetch, such as a file or an MCP resource URI.
+    Resource {
+        uri: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        mime_type: Option<String>,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema, TS)]
@@ -964,6 +1012,7 @@ impl From<Vec<UserInput>> for ResponseInputItem {
                         )
                     }
                     UserInput::Skill { .. } | UserInput::Mention { .. } => Vec::new(), // Tool bodies are injected later in core
+                    UserInput::SystemNote { .. } => Vec::new(), // Sent as a separate system message
                 })
                 .collect::<Vec<ContentItem>>(),
         }
@@ -992,6 +1041,15 @@ pub struct ShellToolCallParams {
     pub additional_permissions: Option<PermissionProfile>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub justification: Option<String>,
+    /// Run the command attached to a pseudo-terminal, for programs that refuse
+    /// to run without one (e.g. `ssh`, `vim`).
+    #[serde(default)]
+    pub use_pty: bool,
+    /// Variables set for this command only, on top of the environment it
+    /// would otherwise inherit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub env: Option<HashMap<String, String>>,
 }
 
 /// If the `name` of a `ResponseItem::FunctionCall` is `shell_command`, the
@@ -1091,11 +1149,12 @@ impl From<crate::dynamic_tools::DynamicToolCallOutputContentItem>
 /// The payload we send back to OpenAI when reporting a tool call result.
 ///
 /// `body` serializes directly as the wire value for `function_call_output.output`.
-/// `success` remains internal metadata for downstream handling.
+/// `success` and `error_code` remain internal metadata for downstream handling.
 #[derive(Debug, Default, Clone, PartialEq, JsonSchema, TS)]
 pub struct FunctionCallOutputPayload {
     pub body: FunctionCallOutputBody,
     pub success: Option<bool>,
+    pub error_code: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema, TS)]
@@ -1103,6 +1162,13 @@ pub struct FunctionCallOutputPayload {
 pub enum FunctionCallOutputBody {
     Text(String),
     ContentItems(Vec<FunctionCallOutputContentItem>),
+    /// URI of a chunked output stream. Core fetches and reassembles the stream
+    /// into [`FunctionCallOutputBody::Text`] before the output is recorded, so
+    /// this variant never appears on the wire; serializing it is an error.
+    #[serde(skip_deserializing)]
+    #[schemars(skip)]
+    #[ts(skip)]
+    Stream(String),
 }
 
 impl FunctionCallOutputBody {
@@ -1116,6 +1182,7 @@ impl FunctionCallOutputBody {
         match self {
             Self::Text(content) => Some(content.clone()),
             Self::ContentItems(items) => function_call_output_content_items_to_text(items),
+            Self::Stream(_) => None,
         }
     }
 }
@@ -1131,6 +1198,7 @@ impl FunctionCallOutputPayload {
         Self {
             body: FunctionCallOutputBody::Text(content),
             success: None,
+            error_code: None,
         }
     }
 
@@ -1138,36 +1206,45 @@ impl FunctionCallOutputPayload {
         Self {
             body: FunctionCallOutputBody::ContentItems(content_items),
             success: None,
+            error_code: None,
         }
     }
 
     pub fn text_content(&self) -> Option<&str> {
         match &self.body {
             FunctionCallOutputBody::Text(content) => Some(content),
-            FunctionCallOutputBody::ContentItems(_) => None,
+            FunctionCallOutputBody::ContentItems(_) | FunctionCallOutputBody::Stream(_) => None,
         }
     }
 
     pub fn text_content_mut(&mut self) -> Option<&mut String> {
         match &mut self.body {
             FunctionCallOutputBody::Text(content) => Some(content),
-            FunctionCallOutputBody::ContentItems(_) => None,
+            FunctionCallOutputBody::ContentItems(_) | FunctionCallOutputBody::Stream(_) => None,
         }
     }
 
     pub fn content_items(&self) -> Option<&[FunctionCallOutputContentItem]> {
         match &self.body {
-            FunctionCallOutputBody::Text(_) => None,
+            FunctionCallOutputBody::Text(_) | FunctionCallOutputBody::Stream(_) => None,
             FunctionCallOutputBody::ContentItems(items) => Some(items),
         }
     }
 
     pub fn content_items_mut(&mut self) -> Option<&mut Vec<FunctionCallOutputContentItem>> {
         match &mut self.body {
-            FunctionCallOutputBody::Text(_) => None,
+            FunctionCallOutputBody::Text(_) | FunctionCallOutputBody::Stream(_) => None,
             FunctionCallOutputBody::ContentItems(items) => Some(items),
         }
     }
+
+    /// Returns the stream URI when the body has not been resolved yet.
+    pub fn stream_uri(&self) -> Option<&str> {
+        match &self.body {
+            FunctionCallOutputBody::Stream(uri) => Some(uri),
+            FunctionCallOutputBody::Text(_) | FunctionCallOutputBody::ContentItems(_) => None,
+        }
+    }
 }
 
 // `function_call_output.output` is encoded as either:
@@ -1181,6 +1258,9 @@ impl Serialize for FunctionCallOutputPayload {
         match &self.body {
             FunctionCallOutputBody::Text(content) => serializer.serialize_str(content),
             FunctionCallOutputBody::ContentItems(items) => items.serialize(serializer),
+            FunctionCallOutputBody::Stream(_) => Err(serde::ser::Error::custom(
+                "tool output stream must be resolved before serialization",
+            )),
         }
     }
 }
@@ -1194,6 +1274,7 @@ impl<'de> Deserialize<'de> for FunctionCallOutputPayload {
         Ok(FunctionCallOutputPayload {
             body,
             success: None,
+            error_code: None,
         })
     }
 }
@@ -1217,12 +1298,14 @@ impl From<&CallToolResult> for FunctionCallOutputPayload {
                     return FunctionCallOutputPayload {
                         body: FunctionCallOutputBody::Text(serialized_structured_content),
                         success: Some(is_success),
+                        error_code: None,
                     };
                 }
                 Err(err) => {
                     return FunctionCallOutputPayload {
                         body: FunctionCallOutputBody::Text(err.to_string()),
                         success: Some(false),
+                        error_code: None,
                     };
                 }
             }
@@ -1234,6 +1317,7 @@ impl From<&CallToolResult> for FunctionCallOutputPayload {
                 return FunctionCallOutputPayload {
                     body: FunctionCallOutputBody::Text(err.to_string()),
                     success: Some(false),
+                    error_code: None,
                 };
             }
         };
@@ -1248,6 +1332,7 @@ impl From<&CallToolResult> for FunctionCallOutputPayload {
         FunctionCallOutputPayload {
             body,
             success: Some(is_success),
+            error_code: None,
         }
     }
 }
@@ -1266,10 +1351,34 @@ fn convert_mcp_content_to_items(
             #[serde(rename = "mimeType", alias = "mime_type")]
             mime_type: Option<String>,
         },
+        /// Embedded resources carry binary files, such as screenshots, as a
+        /// base64 `blob`.
+        #[serde(rename = "resource")]
+        Resource { resource: McpEmbeddedResource },
         #[serde(other)]
         Unknown,
     }
 
+    #[derive(serde::Deserialize)]
+    struct McpEmbeddedResource {
+        blob: Option<String>,
+        #[serde(rename = "mimeType", alias = "mime_type")]
+        mime_type: Option<String>,
+    }
+
+    fn image_item(data: String, mime_type: Option<String>) -> FunctionCallOutputContentItem {
+        let image_url = if data.starts_with("data:") {
+            data
+        } else {
+            let mime_type = mime_type.unwrap_or_else(|| "application/octet-stream".into());
+            format!("data:{mime_type};base64,{data}")
+        };
+        FunctionCallOutputContentItem::InputImage {
+            image_url,
+            detail: None,
+        }
+    }
+
     let mut saw_image = false;
     let mut items = Vec::with_capacity(contents.len());
 
@@ -1278,20 +1387,24 @@ fn convert_mcp_content_to_items(
             Ok(McpContent::Text { text }) => FunctionCallOutputContentItem::InputText { text },
             Ok(McpContent::Image { data, mime_type }) => {
                 saw_image = true;
-                let image_url = if data.starts_with("data:") {
-                    data
-                } else {
-                    let mime_type = mime_type.unwrap_or_else(|| "application/octet-stream".into());
-                    format!("data:{mime_type};base64,{data}")
-                };
-                FunctionCallOutputContentItem::InputImage {
-                    image_url,
-                    detail: None,
+                image_item(data, mime_type)
+            }
+            Ok(McpContent::Resource {
+                resource:
+                    McpEmbeddedResource {
+                        blob: Some(blob),
+                        mime_type: Some(mime_type),
+                    },
+            }) if mime_type.starts_with("image/") => {
+                saw_image = true;
+                image_item(blob, Some(mime_type))
+            }
+            Ok(McpContent::Resource { .. } | McpContent::Unknown) | Err(_) => {
+                FunctionCallOutputContentItem::InputText {
+                    text: serde_json::to_string(content)
+                        .unwrap_or_else(|_| "<content>".to_string()),
                 }
             }
-            Ok(McpContent::Unknown) | Err(_) => FunctionCallOutputContentItem::InputText {
-                text: serde_json::to_string(content).unwrap_or_else(|_| "<content>".to_string()),
-            },
         };
         items.push(item);
     }
@@ -1306,7 +1419,9 @@ fn convert_mcp_content_to_items(
 impl std::fmt::Display for FunctionCallOutputPayload {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self.body {
-            FunctionCallOutputBody::Text(content) => f.write_str(content),
+            FunctionCallOutputBody::Text(content) | FunctionCallOutputBody::Stream(content) => {
+                f.write_str(content)
+            }
             FunctionCallOutputBody::ContentItems(items) => {
                 let content = serde_json::to_string(items).unwrap_or_default();
                 f.write_str(content.as_str())
@@ -1424,6 +1539,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn function_call_output_strings_never_deserialize_as_stream() {
+        let payload =
+            serde_json::from_str::<FunctionCallOutputPayload>("\"https://example.com/s\"")
+                .expect("deserialize function call output");
+        assert_eq!(
+            payload.body,
+            FunctionCallOutputBody::Text("https://example.com/s".to_string())
+        );
+        assert_eq!(payload.stream_uri(), None);
+    }
+
+    #[test]
+    fn unresolved_stream_outputs_do_not_serialize() {
+        let payload = FunctionCallOutputPayload {
+            body: FunctionCallOutputBody::Stream("http://127.0.0.1/s".to_string()),
+            success: Some(true),
+            error_code: None,
+        };
+
+        let err = serde_json::to_string(&payload).expect_err("stream bodies must be resolved");
+        assert!(err.to_string().contains("must be resolved"), "{err}");
+    }
+
     #[test]
     fn permission_profile_is_empty_when_all_fields_are_none() {
         assert_eq!(PermissionProfile::default().is_empty(), true);
@@ -1546,6 +1685,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn convert_mcp_content_to_items_converts_embedded_image_resources() {
+        let contents = vec![
+            serde_json::json!({
+                "type": "resource",
+                "resource": {
+                    "uri": "file:///tmp/screenshot.png",
+                    "mimeType": "image/png",
+                    "blob": "Zm9v",
+                },
+            }),
+            serde_json::json!({
+                "type": "resource",
+                "resource": {
+                    "uri": "file:///tmp/notes.txt",
+                    "mimeType": "text/plain",
+                    "text": "notes",
+                },
+            }),
+        ];
+
+        let items = convert_mcp_content_to_items(&contents).expect("expected image items");
+        assert_eq!(
+            items,
+            vec![
+                FunctionCallOutputContentItem::InputImage {
+                    image_url: "data:image/png;base64,Zm9v".to_string(),
+                    detail: None,
+                },
+                FunctionCallOutputContentItem::InputText {
+                    text: serde_json::to_string(&contents[1]).expect("serialize resource"),
+                },
+            ]
+        );
+    }
+
     #[test]
     fn convert_mcp_content_to_items_returns_none_without_images() {
         let contents = vec![serde_json::json!({
@@ -1816,6 +1991,7 @@ mod tests {
             output: FunctionCallOutputPayload {
                 body: FunctionCallOutputBody::Text("bad".into()),
                 success: Some(false),
+                error_code: None,
             },
         };
 
@@ -2074,6 +2250,8 @@ mod tests {
                 prefix_rule: None,
                 additional_permissions: None,
                 justification: None,
+                use_pty: false,
+                env: None,
             },
             params
         );
@@ -80,6 +80,10 @@ pub fn default_input_modalities() -> Vec<InputModality> {
     vec![InputModality::Text, InputModality::Image]
 }
 
+const fn default_supports_tool_calls() -> bool {
+    true
+}
+
 /// A reasoning effort option that can be surfaced for a model.
 #[derive(Debug, Clone, Deserialize, Serialize, TS, JsonSchema, PartialEq, Eq)]
 pub struct ReasoningEffortPreset {
@@ -179,6 +183,9 @@ pub enum ConfigShellToolType {
 pub enum ApplyPatchToolType {
     Freeform,
     Function,
+    /// A `str_replace_edit` search/replace tool instead of `apply_patch`,
+    /// for models that edit more reliably that way.
+    StrReplace,
 }
 
 #[derive(
@@ -257,6 +264,10 @@ pub struct ModelInfo {
     pub web_search_tool_type: WebSearchToolType,
     pub truncation_policy: TruncationPolicyConfig,
     pub supports_parallel_tool_calls: bool,
+    /// Whether the backend accepts native tool definitions and emits tool calls.
+    /// Legacy payloads predate this field, so it defaults to true.
+    #[serde(default = "default_supports_tool_calls")]
+    pub supports_tool_calls: bool,
     #[serde(default)]
     pub supports_image_detail_original: bool,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -530,6 +541,7 @@ mod tests {
             web_search_tool_type: WebSearchToolType::Text,
             truncation_policy: TruncationPolicyConfig::bytes(10_000),
             supports_parallel_tool_calls: false,
+            supports_tool_calls: true,
             supports_image_detail_original: false,
             context_window: None,
             auto_compact_token_limit: None,
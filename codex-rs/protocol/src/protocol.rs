@@ -61,6 +61,10 @@ pub use crate::approvals::NetworkApprovalContext;
 pub use crate::approvals::NetworkApprovalProtocol;
 pub use crate::approvals::NetworkPolicyAmendment;
 pub use crate::approvals::NetworkPolicyRuleAction;
+pub use crate::approvals::PatchHunkRejection;
+pub use crate::approvals::PatchReviewHunk;
+pub use crate::approvals::PatchReviewRequestEvent;
+pub use crate::approvals::PatchReviewResponse;
 pub use crate::permissions::FileSystemAccessMode;
 pub use crate::permissions::FileSystemPath;
 pub use crate::permissions::FileSystemSandboxEntry;
@@ -179,6 +183,24 @@ pub enum Op {
     /// Terminate all running background terminal processes for this thread.
     CleanBackgroundTerminals,
 
+    /// Write keystrokes, e.g. `"y\n"` or `"\u{3}"` for Ctrl-C, to a running
+    /// `exec_command` process started with `tty: true`. `process_id` is the
+    /// one reported in [`EventMsg::ExecCommandBegin`].
+    TerminalInput { process_id: String, data: String },
+
+    /// Resize the PTY of a running `exec_command` process, in character
+    /// cells.
+    TerminalResize {
+        process_id: String,
+        rows: u16,
+        cols: u16,
+    },
+
+    /// Stop waiting on a running `exec_command` process: the pending
+    /// `exec_command` or `write_stdin` call returns the output so far to the
+    /// model and the process keeps running in the background.
+    TerminalDetach { process_id: String },
+
     /// Start a realtime conversation stream.
     RealtimeConversationStart(ConversationStartParams),
 
@@ -330,6 +352,14 @@ pub enum Op {
         decision: ReviewDecision,
     },
 
+    /// Accept or reject the hunks of a patch, in response to
+    /// [`EventMsg::PatchReviewRequest`].
+    PatchReview {
+        /// The call id of the patch under review.
+        id: String,
+        response: PatchReviewResponse,
+    },
+
     /// Resolve an MCP elicitation request.
     ResolveElicitation {
         /// Name of the MCP server that issued the request.
@@ -419,6 +449,14 @@ pub enum Op {
     /// to generate a summary which will be returned as an AgentMessage event.
     Compact,
 
+    /// Keep the turn started by submission `turn_id` out of every later
+    /// compaction, so its items stay in the history verbatim. Replies with
+    /// [`EventMsg::Error`] if the turn has no items in the current history.
+    PinTurn { turn_id: String },
+
+    /// Let compaction summarize a turn pinned with [`Op::PinTurn`] again.
+    UnpinTurn { turn_id: String },
+
     /// Drop all persisted memory artifacts and memory-tracking DB rows.
     DropMemories,
 
@@ -439,6 +477,53 @@ pub enum Op {
     /// responsible for undoing any edits on disk.
     ThreadRollback { num_turns: u32 },
 
+    /// Save the conversation history and token usage so the thread can later
+    /// return to this point with [`Op::BranchFromCheckpoint`]. Replies with
+    /// [`EventMsg::CheckpointCreated`].
+    Checkpoint,
+
+    /// Replace the conversation history and token usage with those saved by
+    /// [`Op::Checkpoint`], dropping everything since. Branching from a
+    /// checkpoint taken before trying an alternative discards it; the
+    /// alternative can be kept by checkpointing it first.
+    ///
+    /// Like [`Op::ThreadRollback`], this does not revert local filesystem
+    /// changes.
+    BranchFromCheckpoint { checkpoint_id: String },
+
+    /// Request the session's estimated spend per provider and model. Replies
+    /// with [`EventMsg::CostUpdate`].
+    GetCostSummary,
+
+    /// Request the session's effective feature flags and where each value
+    /// came from. Replies with [`EventMsg::FeaturesResolved`].
+    GetFeatures,
+
+    /// Request the running turn's cumulative diff so far. Replies with
+    /// [`EventMsg::TurnDiff`], whose diff is empty when no turn is running or
+    /// the turn has not changed any files.
+    GetTurnDiff,
+
+    /// Start streaming this session's events over WebSocket to read-only
+    /// observers, on `bind_address` (`127.0.0.1` on a free port when
+    /// omitted). Requires the `session_sharing` feature. Replies with
+    /// [`EventMsg::SessionShareStarted`], also when already sharing.
+    StartSessionShare {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bind_address: Option<String>,
+    },
+
+    /// Stop sharing this session and disconnect every observer.
+    StopSessionShare,
+
+    /// Replace the session's token limits, e.g. to let a session whose
+    /// budget is spent keep going. `None` removes a limit. Spend so far is
+    /// kept and counts against the new limits.
+    SetTokenBudget {
+        max_tokens_per_turn: Option<i64>,
+        max_tokens_per_session: Option<i64>,
+    },
+
     /// Request a code review from the agent.
     Review { review_request: ReviewRequest },
 
@@ -1047,9 +1132,24 @@ pub enum EventMsg {
     /// Conversation history was compacted (either automatically or manually).
     ContextCompacted(ContextCompactedEvent),
 
+    /// Compaction of the conversation history began.
+    CompactionStarted(CompactionStartedEvent),
+
+    /// Compaction of the conversation history finished successfully, whether
+    /// or not anything was compacted.
+    CompactionFinished(CompactionFinishedEvent),
+
     /// Conversation history was rolled back by dropping the last N user turns.
     ThreadRolledBack(ThreadRolledBackEvent),
 
+    /// A checkpoint of the conversation was saved in response to
+    /// [`Op::Checkpoint`].
+    CheckpointCreated(CheckpointCreatedEvent),
+
+    /// Conversation history was replaced with a checkpoint's in response to
+    /// [`Op::BranchFromCheckpoint`].
+    BranchedFromCheckpoint(BranchedFromCheckpointEvent),
+
     /// Agent has started a turn.
     /// v1 wire format uses `task_started`; accept `turn_started` for v2 interop.
     #[serde(rename = "task_started", alias = "turn_started")]
@@ -1064,6 +1164,10 @@ pub enum EventMsg {
     /// Optional means unknown — UIs should not display when `None`.
     TokenCount(TokenCountEvent),
 
+    /// Estimated spend of the turn and session, sent after each model
+    /// response and in reply to [`Op::GetCostSummary`].
+    CostUpdate(CostUpdateEvent),
+
     /// Agent text output message
     AgentMessage(AgentMessageEvent),
 
@@ -1093,14 +1197,40 @@ pub enum EventMsg {
     /// Updated session metadata (e.g., thread name changes).
     ThreadNameUpdated(ThreadNameUpdatedEvent),
 
+    /// The settings subsequent turns will use, sent after
+    /// `Op::OverrideTurnContext` changes them.
+    SessionSettingsUpdated(SessionSettingsUpdatedEvent),
+
+    /// The session's effective feature flags, sent after
+    /// `SessionConfigured` and in reply to [`Op::GetFeatures`].
+    FeaturesResolved(FeaturesResolvedEvent),
+
+    /// The session is being shared; see [`Op::StartSessionShare`].
+    SessionShareStarted(SessionShareStartedEvent),
+
     /// Incremental MCP startup progress updates.
     McpStartupUpdate(McpStartupUpdateEvent),
 
     /// Aggregate MCP startup completion summary.
     McpStartupComplete(McpStartupCompleteEvent),
 
+    /// Health of an MCP server that already started, e.g. while it is being
+    /// reconnected after it stopped responding.
+    McpServerStatus(McpServerStatusEvent),
+
+    /// The turn's model provider stayed unavailable after its retries, so the
+    /// request was re-issued to the next provider in `provider_fallbacks`.
+    ProviderFellBack(ProviderFellBackEvent),
+
+    /// The turn's model provider failed too many requests in a row, so its
+    /// circuit breaker opened and requests to it are refused for a while.
+    ProviderUnhealthy(ProviderUnhealthyEvent),
+
     McpToolCallBegin(McpToolCallBeginEvent),
 
+    /// Incremental chunk of output from a running MCP tool call.
+    McpToolCallOutputDelta(McpToolCallOutputDeltaEvent),
+
     McpToolCallEnd(McpToolCallEndEvent),
 
     WebSearchBegin(WebSearchBeginEvent),
@@ -1133,10 +1263,39 @@ pub enum EventMsg {
 
     DynamicToolCallResponse(DynamicToolCallResponseEvent),
 
+    /// Notification that a tool call carrying caller-defined tags is about to
+    /// be dispatched.
+    ToolCallStart(ToolCallStartEvent),
+
+    /// Notification that an MCP tool call is waiting on its server's
+    /// concurrency or rate limit before being dispatched.
+    ToolCallQueued(ToolCallQueuedEvent),
+
+    /// Notification that a tool call was answered from the session's tool
+    /// output cache instead of being dispatched.
+    ToolCacheHit(ToolCacheHitEvent),
+
+    /// Notification that chaos mode injected a fault into a tool call.
+    ChaosFaultInjected(ChaosFaultInjectedEvent),
+
+    /// Notification that redaction removed content from a tool call's output
+    /// before it was recorded.
+    ToolOutputRedacted(ToolOutputRedactedEvent),
+
+    /// Notification that credentials found in a tool call's output were
+    /// replaced with placeholders before it was recorded.
+    SecretsRedacted(SecretsRedactedEvent),
+
+    /// How full the model's context window is, sent at the end of each turn.
+    ContextWindowStatus(ContextWindowStatusEvent),
+
     ElicitationRequest(ElicitationRequestEvent),
 
     ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent),
 
+    /// Ask the client to accept or reject individual hunks of a patch.
+    PatchReviewRequest(PatchReviewRequestEvent),
+
     /// Notification advising the user that something they are using has been
     /// deprecated and should be phased out.
     DeprecationNotice(DeprecationNoticeEvent),
@@ -1160,6 +1319,10 @@ pub enum EventMsg {
 
     TurnDiff(TurnDiffEvent),
 
+    /// Per-file summary of the turn's changes so far, sent after each tool
+    /// call that may change files.
+    TurnDiffUpdated(TurnDiffUpdatedEvent),
+
     /// Response to GetHistoryEntryRequest.
     GetHistoryEntryResponse(GetHistoryEntryResponseEvent),
 
@@ -1185,6 +1348,19 @@ pub enum EventMsg {
 
     TurnAborted(TurnAbortedEvent),
 
+    /// Notification that a running turn was cancelled before it completed.
+    /// Sent just before the corresponding [`EventMsg::TurnAborted`].
+    TurnCancelled(TurnCancelledEvent),
+
+    /// Notification that a turn ran past `turn_timeout_seconds`. Running
+    /// tool calls were cancelled and the model is asked to wrap up; the
+    /// turn still ends with [`EventMsg::TurnComplete`].
+    TurnTimedOut(TurnTimedOutEvent),
+
+    /// Progress of a running turn, written to the rollout so that a turn cut
+    /// short by a crash can be closed out on resume. Not sent to clients.
+    TurnCheckpoint(TurnCheckpointEvent),
+
     /// Notification that the agent is shutting down.
     ShutdownComplete,
 
@@ -1547,6 +1723,19 @@ pub struct ModelRerouteEvent {
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ContextCompactedEvent;
 
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
+pub struct CompactionStartedEvent {
+    pub remote: bool,
+}
+
+/// Token counts are estimates of the history size, and are `None` when the
+/// history could not be estimated.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
+pub struct CompactionFinishedEvent {
+    pub tokens_before: Option<i64>,
+    pub tokens_after: Option<i64>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TurnCompleteEvent {
     pub turn_id: String,
@@ -1647,6 +1836,36 @@ impl TokenUsageInfo {
 pub struct TokenCountEvent {
     pub info: Option<TokenUsageInfo>,
     pub rate_limits: Option<RateLimitSnapshot>,
+    /// Share of the session's input tokens served from the provider's prompt
+    /// cache, from 0 to 1. `None` until some input has been counted.
+    #[serde(default)]
+    pub prompt_cache_hit_rate: Option<f64>,
+}
+
+/// Estimated spend, computed from token usage and per-model prices.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct CostUpdateEvent {
+    /// Estimated cost of the current or most recent turn, in USD.
+    pub turn_cost_usd: f64,
+    /// Estimated cost of the session so far, in USD.
+    pub session_cost_usd: f64,
+    /// Usage per provider and model. Models without a known price count
+    /// toward neither total.
+    pub models: Vec<ModelCost>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct ModelCost {
+    pub provider: String,
+    pub model: String,
+    #[ts(type = "number")]
+    pub input_tokens: i64,
+    #[ts(type = "number")]
+    pub cached_input_tokens: i64,
+    #[ts(type = "number")]
+    pub output_tokens: i64,
+    /// Estimated cost in USD, or `None` when no price is known for the model.
+    pub cost_usd: Option<f64>,
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema, TS)]
@@ -1694,6 +1913,13 @@ impl TokenUsage {
         (self.input_tokens - self.cached_input()).max(0)
     }
 
+    /// Share of input tokens served from the provider's prompt cache, from 0
+    /// to 1, or `None` when no input was counted.
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        (self.input_tokens > 0)
+            .then(|| (self.cached_input() as f64 / self.input_tokens as f64).min(1.0))
+    }
+
     /// Primary count for display as a single absolute value: non-cached input + output.
     pub fn blended_total(&self) -> i64 {
         (self.non_cached_input() + self.output_tokens.max(0)).max(0)
@@ -1853,6 +2079,15 @@ pub struct McpToolCallBeginEvent {
     pub invocation: McpInvocation,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
+pub struct McpToolCallOutputDeltaEvent {
+    /// Identifier for the McpToolCallBegin that produced this chunk.
+    pub call_id: String,
+    /// Partial result reported by the server. The final result's text is the
+    /// concatenation of every chunk.
+    pub delta: String,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
 pub struct McpToolCallEndEvent {
     /// Identifier for the corresponding McpToolCallBegin that finished.
@@ -1864,6 +2099,82 @@ pub struct McpToolCallEndEvent {
     pub result: Result<CallToolResult, String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
+pub struct ToolCallStartEvent {
+    pub call_id: String,
+    pub tool_name: String,
+    /// Key-value tags attached to the call by its caller.
+    pub metadata: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
+pub struct ToolCallQueuedEvent {
+    pub call_id: String,
+    /// MCP server whose limits the call is waiting on.
+    pub server: String,
+    pub tool: String,
+    /// Which of the server's limits the call is waiting on.
+    pub reason: ToolCallQueueReason,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, JsonSchema, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolCallQueueReason {
+    MaxConcurrency,
+    RateLimit,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
+pub struct ToolCacheHitEvent {
+    pub call_id: String,
+    pub tool_name: String,
+    /// Time since the cached output was produced.
+    pub age_ms: u64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
+pub struct ChaosFaultInjectedEvent {
+    pub call_id: String,
+    pub fault_type: ChaosFaultType,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
+pub struct ToolOutputRedactedEvent {
+    pub call_id: String,
+    /// Bytes of matched content that were replaced.
+    pub bytes_redacted: usize,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
+pub struct SecretsRedactedEvent {
+    pub call_id: String,
+    /// Kinds of credentials replaced, e.g. `aws_access_key_id`, each listed
+    /// once.
+    pub kinds: Vec<String>,
+    /// Number of values replaced.
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, JsonSchema, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChaosFaultType {
+    Error,
+    Latency,
+    PayloadCorruption,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
+pub struct ContextWindowStatusEvent {
+    /// Estimated tokens taken up by the conversation history.
+    #[ts(type = "number")]
+    pub used_tokens: i64,
+    /// Size of the model's context window.
+    #[ts(type = "number")]
+    pub max_tokens: i64,
+    /// Percentage (0-100) of the context window in use.
+    pub pct_full: f64,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
 pub struct DynamicToolCallResponseEvent {
     /// Identifier for the corresponding DynamicToolCallRequest.
@@ -2396,6 +2707,7 @@ pub enum ExecCommandSource {
     UserShell,
     UnifiedExecStartup,
     UnifiedExecInteraction,
+    Verify,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
@@ -2544,6 +2856,17 @@ pub struct ThreadRolledBackEvent {
     pub num_turns: u32,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct CheckpointCreatedEvent {
+    /// Id to pass to [`Op::BranchFromCheckpoint`].
+    pub checkpoint_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct BranchedFromCheckpointEvent {
+    pub checkpoint_id: String,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct StreamErrorEvent {
     pub message: String,
@@ -2607,6 +2930,48 @@ pub enum PatchApplyStatus {
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TurnDiffEvent {
     pub unified_diff: String,
+    /// The same changes split by repository, with paths relative to each
+    /// one. Only set when the turn changed files in more than one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub root_diffs: Vec<TurnRootDiff>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct TurnRootDiff {
+    /// Repository root, or the directory of files outside any repository.
+    pub root: PathBuf,
+    pub unified_diff: String,
+}
+
+/// Only sent when the summary differs from the previous one in the turn.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct TurnDiffUpdatedEvent {
+    pub turn_id: String,
+    /// Every file the turn has changed so far, sorted by path.
+    pub files: Vec<TurnDiffFileStat>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct TurnDiffFileStat {
+    pub path: PathBuf,
+    /// Where the file was before the turn, when it was renamed or moved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub old_path: Option<PathBuf>,
+    pub status: TurnDiffFileStatus,
+    pub added_lines: u64,
+    pub removed_lines: u64,
+    /// Either side is not UTF-8 text, so no lines are counted.
+    pub binary: bool,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum TurnDiffFileStatus {
+    Added,
+    Deleted,
+    Modified,
+    Renamed,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
@@ -2655,6 +3020,51 @@ pub struct McpStartupCompleteEvent {
     pub cancelled: Vec<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct McpServerStatusEvent {
+    /// Server name.
+    pub server: String,
+    /// Current health of the server.
+    pub status: McpServerHealth,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ProviderFellBackEvent {
+    /// Id of the provider that became unavailable.
+    pub from_provider: String,
+    /// Id of the provider the turn continues with.
+    pub to_provider: String,
+    /// Model requested from `to_provider`.
+    pub model: String,
+    /// The error that exhausted `from_provider`'s retries.
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ProviderUnhealthyEvent {
+    /// Id of the provider whose circuit breaker opened.
+    pub provider: String,
+    /// Failed requests in a row that opened the circuit breaker.
+    pub consecutive_failures: u32,
+    /// Seconds until a request is sent to the provider again.
+    pub retry_after_seconds: u64,
+    /// The error from the last failed request.
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case", tag = "state")]
+#[ts(rename_all = "snake_case", tag = "state")]
+pub enum McpServerHealth {
+    /// The server answers again after being degraded.
+    Healthy,
+    /// The server stopped responding; calls to its tools fail until it is
+    /// reconnected.
+    Degraded { error: String },
+    /// A reconnect is in progress. `attempt` counts from 1.
+    Reconnecting { attempt: u32 },
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct McpStartupFailure {
     pub server: String,
@@ -2887,6 +3297,62 @@ pub struct ThreadNameUpdatedEvent {
     pub thread_name: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
+pub struct SessionSettingsUpdatedEvent {
+    pub model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<ReasoningEffortConfig>,
+    pub approval_policy: AskForApproval,
+    pub sandbox_policy: SandboxPolicy,
+    pub cwd: PathBuf,
+}
+
+/// Where the effective value of a feature flag came from. Later variants take
+/// precedence over earlier ones.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, JsonSchema, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureSource {
+    /// The built-in default.
+    Default,
+    /// `[feature_defaults]` in requirements, e.g. rolled out through cloud
+    /// requirements.
+    Remote,
+    /// `[features]` or a legacy setting in config.toml.
+    Config,
+    /// `[profiles.<name>.features]` of the active profile.
+    Profile,
+    /// `--enable`, `--disable` or `-c` on the command line.
+    Cli,
+    /// Pinned by requirements.
+    Requirement,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
+pub struct ResolvedFeature {
+    /// The `[features]` key, e.g. `undo`.
+    pub key: String,
+    pub enabled: bool,
+    pub default_enabled: bool,
+    pub source: FeatureSource,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
+pub struct FeaturesResolvedEvent {
+    pub features: Vec<ResolvedFeature>,
+}
+
+/// Where observers can follow the session, sent in reply to
+/// [`Op::StartSessionShare`].
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq, Eq)]
+pub struct SessionShareStartedEvent {
+    /// WebSocket URL, e.g. `ws://127.0.0.1:53121/`. Observers may add an
+    /// `offset` query parameter to replay events from that sequence number.
+    pub url: String,
+    /// Sent by observers as `Authorization: Bearer <token>` or as a `token`
+    /// query parameter.
+    pub token: String,
+}
+
 /// User's decision in response to an ExecApprovalRequest.
 #[derive(Debug, Default, Clone, Deserialize, Serialize, PartialEq, Eq, Display, JsonSchema, TS)]
 #[serde(rename_all = "snake_case")]
@@ -2965,12 +3431,36 @@ pub struct Chunk {
     pub inserted_lines: Vec<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct TurnCheckpointEvent {
+    pub turn_id: String,
+    /// Assistant text streamed since the last completed assistant message.
+    pub partial_agent_message: Option<String>,
+    /// Unified diff of the files changed so far in the turn.
+    pub unified_diff: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TurnAbortedEvent {
     pub turn_id: Option<String>,
     pub reason: TurnAbortReason,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct TurnCancelledEvent {
+    pub turn_id: String,
+    pub reason: TurnAbortReason,
+    /// Assistant text streamed during the turn before it was cancelled.
+    pub partial_output: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct TurnTimedOutEvent {
+    pub turn_id: String,
+    /// The configured limit the turn exceeded.
+    pub timeout_seconds: u64,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
 #[serde(rename_all = "snake_case")]
 pub enum TurnAbortReason {
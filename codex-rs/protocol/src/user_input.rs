@@ -6,6 +6,9 @@ use ts_rs::TS;
 /// Conservative cap so one user message cannot monopolize a large context window.
 pub const MAX_USER_INPUT_TEXT_CHARS: usize = 1 << 20;
 
+/// Prefix of the system message a [`UserInput::SystemNote`] is sent as.
+pub const SYSTEM_NOTE_PREFIX: &str = "[SYSTEM NOTE]: ";
+
 /// User input
 #[non_exhaustive]
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS, JsonSchema)]
@@ -37,6 +40,9 @@ pub enum UserInput {
     /// `path` identifies the exact mention target, for example
     /// `app://<connector-id>` or `plugin://<plugin-name>@<marketplace-name>`.
     Mention { name: String, path: String },
+    /// Ephemeral context from an orchestrator. Sent to the model as a system
+    /// message for the current turn only; never recorded in history.
+    SystemNote { text: String },
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS, JsonSchema)]
@@ -12,6 +12,7 @@ use rmcp::model::ListResourceTemplatesResult;
 use rmcp::model::ListResourcesResult;
 use rmcp::model::ListToolsResult;
 use rmcp::model::PaginatedRequestParams;
+use rmcp::model::ProgressNotificationParam;
 use rmcp::model::RawResource;
 use rmcp::model::RawResourceTemplate;
 use rmcp::model::ReadResourceRequestParams;
@@ -47,6 +48,7 @@ impl TestToolServer {
             Self::echo_tool(),
             Self::image_tool(),
             Self::image_scenario_tool(),
+            Self::stream_chunks_tool(),
         ];
         let resources = vec![Self::memo_resource()];
         let resource_templates = vec![Self::memo_template()];
@@ -77,6 +79,27 @@ impl TestToolServer {
         )
     }
 
+    /// Reports each chunk as the message of a progress notification, when the
+    /// request carries a progress token, then returns all of them as one text.
+    fn stream_chunks_tool() -> Tool {
+        #[expect(clippy::expect_used)]
+        let schema: JsonObject = serde_json::from_value(json!({
+            "type": "object",
+            "properties": {
+                "chunks": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["chunks"],
+            "additionalProperties": false
+        }))
+        .expect("stream_chunks tool schema should deserialize");
+
+        Tool::new(
+            Cow::Borrowed("stream_chunks"),
+            Cow::Borrowed("Stream the provided chunks as progress, then return them joined."),
+            Arc::new(schema),
+        )
+    }
+
     fn image_tool() -> Tool {
         #[expect(clippy::expect_used)]
         let schema: JsonObject = serde_json::from_value(serde_json::json!({
@@ -181,6 +204,11 @@ impl TestToolServer {
     }
 }
 
+#[derive(Deserialize)]
+struct StreamChunksArgs {
+    chunks: Vec<String>,
+}
+
 #[derive(Deserialize)]
 struct EchoArgs {
     message: String,
@@ -293,7 +321,7 @@ impl ServerHandler for TestToolServer {
     async fn call_tool(
         &self,
         request: CallToolRequestParams,
-        _context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
+        context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
     ) -> Result<CallToolResult, McpError> {
         match request.name.as_ref() {
             "echo" => {
@@ -348,6 +376,26 @@ impl ServerHandler for TestToolServer {
                 let args = Self::parse_call_args::<ImageScenarioArgs>(&request, "image_scenario")?;
                 Self::image_scenario_result(args)
             }
+            "stream_chunks" => {
+                let args = Self::parse_call_args::<StreamChunksArgs>(&request, "stream_chunks")?;
+                if let Some(progress_token) = context.meta.get_progress_token() {
+                    for (index, chunk) in args.chunks.iter().enumerate() {
+                        context
+                            .peer
+                            .notify_progress(ProgressNotificationParam {
+                                progress_token: progress_token.clone(),
+                                progress: (index + 1) as _,
+                                total: Some(args.chunks.len() as _),
+                                message: Some(chunk.clone()),
+                            })
+                            .await
+                            .map_err(|err| McpError::internal_error(err.to_string(), None))?;
+                    }
+                }
+                Ok(CallToolResult::success(vec![rmcp::model::Content::text(
+                    args.chunks.concat(),
+                )]))
+            }
             other => Err(McpError::invalid_params(
                 format!("unknown tool: {other}"),
                 None,
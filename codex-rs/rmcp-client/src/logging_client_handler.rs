@@ -1,4 +1,7 @@
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::Mutex as StdMutex;
+use std::sync::PoisonError;
 
 use rmcp::ClientHandler;
 use rmcp::RoleClient;
@@ -12,6 +15,8 @@ use rmcp::model::ProgressNotificationParam;
 use rmcp::model::ResourceUpdatedNotificationParam;
 use rmcp::service::NotificationContext;
 use rmcp::service::RequestContext;
+use serde_json::Value;
+use tokio::sync::mpsc::UnboundedSender;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
@@ -19,17 +24,59 @@ use tracing::warn;
 
 use crate::rmcp_client::SendElicitation;
 
+/// Channels that receive the `message` of each progress notification, keyed
+/// by the progress token of the request it belongs to.
+pub(crate) type ProgressListeners = Arc<StdMutex<HashMap<String, UnboundedSender<String>>>>;
+
+/// Keeps `token` registered in a [`ProgressListeners`] map until dropped.
+pub(crate) struct ProgressRegistration {
+    listeners: ProgressListeners,
+    token: String,
+}
+
+impl ProgressRegistration {
+    pub(crate) fn new(
+        listeners: &ProgressListeners,
+        token: String,
+        listener: UnboundedSender<String>,
+    ) -> Self {
+        listeners
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(token.clone(), listener);
+        Self {
+            listeners: Arc::clone(listeners),
+            token,
+        }
+    }
+}
+
+impl Drop for ProgressRegistration {
+    fn drop(&mut self) {
+        self.listeners
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&self.token);
+    }
+}
+
 #[derive(Clone)]
 pub(crate) struct LoggingClientHandler {
     client_info: ClientInfo,
     send_elicitation: Arc<SendElicitation>,
+    progress_listeners: ProgressListeners,
 }
 
 impl LoggingClientHandler {
-    pub(crate) fn new(client_info: ClientInfo, send_elicitation: SendElicitation) -> Self {
+    pub(crate) fn new(
+        client_info: ClientInfo,
+        send_elicitation: SendElicitation,
+        progress_listeners: ProgressListeners,
+    ) -> Self {
         Self {
             client_info,
             send_elicitation: Arc::new(send_elicitation),
+            progress_listeners,
         }
     }
 }
@@ -66,6 +113,20 @@ impl ClientHandler for LoggingClientHandler {
             "MCP server progress notification (token: {:?}, progress: {}, total: {:?}, message: {:?})",
             params.progress_token, params.progress, params.total, params.message
         );
+        let Some(message) = params.message else {
+            return;
+        };
+        let Ok(Value::String(token)) = serde_json::to_value(&params.progress_token) else {
+            return;
+        };
+        if let Some(listener) = self
+            .progress_listeners
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&token)
+        {
+            let _ = listener.send(message);
+        }
     }
 
     async fn on_resource_updated(
@@ -5,6 +5,8 @@ use std::io;
 use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 
 use anyhow::Result;
@@ -62,12 +64,15 @@ use tokio::io::AsyncBufReadExt;
 use tokio::io::BufReader;
 use tokio::process::Command;
 use tokio::sync::Mutex;
+use tokio::sync::mpsc::UnboundedSender;
 use tokio::time;
 use tracing::info;
 use tracing::warn;
 
 use crate::load_oauth_tokens;
 use crate::logging_client_handler::LoggingClientHandler;
+use crate::logging_client_handler::ProgressListeners;
+use crate::logging_client_handler::ProgressRegistration;
 use crate::oauth::OAuthCredentialsStoreMode;
 use crate::oauth::OAuthPersistor;
 use crate::oauth::StoredOAuthTokens;
@@ -465,6 +470,8 @@ pub struct RmcpClient {
     transport_recipe: TransportRecipe,
     initialize_context: Mutex<Option<InitializeContext>>,
     session_recovery_lock: Mutex<()>,
+    progress_listeners: ProgressListeners,
+    next_progress_token: AtomicU64,
 }
 
 impl RmcpClient {
@@ -493,6 +500,8 @@ impl RmcpClient {
             transport_recipe,
             initialize_context: Mutex::new(None),
             session_recovery_lock: Mutex::new(()),
+            progress_listeners: ProgressListeners::default(),
+            next_progress_token: AtomicU64::new(0),
         })
     }
 
@@ -521,6 +530,8 @@ impl RmcpClient {
             transport_recipe,
             initialize_context: Mutex::new(None),
             session_recovery_lock: Mutex::new(()),
+            progress_listeners: ProgressListeners::default(),
+            next_progress_token: AtomicU64::new(0),
         })
     }
 
@@ -532,7 +543,11 @@ impl RmcpClient {
         timeout: Option<Duration>,
         send_elicitation: SendElicitation,
     ) -> Result<InitializeResult> {
-        let client_handler = LoggingClientHandler::new(params.clone(), send_elicitation);
+        let client_handler = LoggingClientHandler::new(
+            params.clone(),
+            send_elicitation,
+            Arc::clone(&self.progress_listeners),
+        );
         let pending_transport = {
             let mut guard = self.state.lock().await;
             match &mut *guard {
@@ -691,6 +706,42 @@ impl RmcpClient {
         name: String,
         arguments: Option<serde_json::Value>,
         timeout: Option<Duration>,
+    ) -> Result<CallToolResult> {
+        self.call_tool_with_meta(name, arguments, None, timeout)
+            .await
+    }
+
+    /// Like [`RmcpClient::call_tool_with_meta`], but asks the server for
+    /// progress and forwards the `message` of each progress notification for
+    /// this call to `partial_output`, in the order they are received.
+    pub async fn call_tool_with_progress(
+        &self,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        meta: Option<serde_json::Map<String, Value>>,
+        timeout: Option<Duration>,
+        partial_output: UnboundedSender<String>,
+    ) -> Result<CallToolResult> {
+        let token = format!(
+            "codex-progress-{}",
+            self.next_progress_token.fetch_add(1, Ordering::Relaxed)
+        );
+        let mut meta = meta.unwrap_or_default();
+        meta.insert("progressToken".to_string(), Value::String(token.clone()));
+        let _registration =
+            ProgressRegistration::new(&self.progress_listeners, token, partial_output);
+        self.call_tool_with_meta(name, arguments, Some(meta), timeout)
+            .await
+    }
+
+    /// Like [`RmcpClient::call_tool`], but also sends `meta` as the request's
+    /// `_meta`, e.g. to propagate trace context to the server.
+    pub async fn call_tool_with_meta(
+        &self,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        meta: Option<serde_json::Map<String, Value>>,
+        timeout: Option<Duration>,
     ) -> Result<CallToolResult> {
         self.refresh_oauth_if_needed().await;
         let arguments = match arguments {
@@ -703,7 +754,7 @@ impl RmcpClient {
             None => None,
         };
         let rmcp_params = CallToolRequestParams {
-            meta: None,
+            meta: meta.map(rmcp::model::Meta),
             name: name.into(),
             arguments,
             task: None,
@@ -1044,6 +1095,19 @@ impl RmcpClient {
         }
     }
 
+    /// Whether `error`, returned by a request made through an `RmcpClient`,
+    /// means the request or its response could not be carried to or from the
+    /// server, as opposed to the server answering with an error.
+    pub fn is_transport_error(error: &anyhow::Error) -> bool {
+        matches!(
+            error.downcast_ref::<ClientOperationError>(),
+            Some(ClientOperationError::Service(
+                rmcp::service::ServiceError::TransportSend(_)
+                    | rmcp::service::ServiceError::TransportClosed
+            ))
+        )
+    }
+
     fn is_session_expired_404(error: &ClientOperationError) -> bool {
         let ClientOperationError::Service(rmcp::service::ServiceError::TransportSend(error)) =
             error
@@ -0,0 +1,108 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use codex_rmcp_client::ElicitationAction;
+use codex_rmcp_client::ElicitationResponse;
+use codex_rmcp_client::RmcpClient;
+use codex_utils_cargo_bin::CargoBinError;
+use futures::FutureExt as _;
+use pretty_assertions::assert_eq;
+use rmcp::model::ClientCapabilities;
+use rmcp::model::Implementation;
+use rmcp::model::InitializeRequestParams;
+use rmcp::model::ProtocolVersion;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+fn stdio_server_bin() -> Result<PathBuf, CargoBinError> {
+    codex_utils_cargo_bin::cargo_bin("test_stdio_server")
+}
+
+fn init_params() -> InitializeRequestParams {
+    InitializeRequestParams {
+        meta: None,
+        capabilities: ClientCapabilities {
+            experimental: None,
+            extensions: None,
+            roots: None,
+            sampling: None,
+            elicitation: None,
+            tasks: None,
+        },
+        client_info: Implementation {
+            name: "codex-test".into(),
+            version: "0.0.0-test".into(),
+            title: Some("Codex rmcp progress test".into()),
+            description: None,
+            icons: None,
+            website_url: None,
+        },
+        protocol_version: ProtocolVersion::V_2025_06_18,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn progress_messages_are_forwarded_to_the_caller() -> anyhow::Result<()> {
+    let client = RmcpClient::new_stdio_client(
+        stdio_server_bin()?.into(),
+        Vec::<OsString>::new(),
+        None,
+        &[],
+        None,
+    )
+    .await?;
+    client
+        .initialize(
+            init_params(),
+            Some(Duration::from_secs(5)),
+            Box::new(|_, _| {
+                async {
+                    Ok(ElicitationResponse {
+                        action: ElicitationAction::Decline,
+                        content: None,
+                        meta: None,
+                    })
+                }
+                .boxed()
+            }),
+        )
+        .await?;
+
+    let chunks = ["compiling\n", "running 3 tests\n", "ok\n"];
+    let (partial_output_tx, mut partial_output_rx) = mpsc::unbounded_channel();
+    let result = client
+        .call_tool_with_progress(
+            "stream_chunks".to_string(),
+            Some(json!({ "chunks": chunks })),
+            None,
+            Some(Duration::from_secs(5)),
+            partial_output_tx,
+        )
+        .await?;
+
+    let mut streamed = Vec::new();
+    while let Some(chunk) = partial_output_rx.recv().await {
+        streamed.push(chunk);
+    }
+    assert_eq!(streamed, chunks);
+    assert_eq!(
+        result.content,
+        vec![rmcp::model::Content::text(chunks.concat())]
+    );
+
+    // Without a progress listener the same call is buffered as before.
+    let result = client
+        .call_tool(
+            "stream_chunks".to_string(),
+            Some(json!({ "chunks": chunks })),
+            Some(Duration::from_secs(5)),
+        )
+        .await?;
+    assert_eq!(
+        result.content,
+        vec![rmcp::model::Content::text(chunks.concat())]
+    );
+
+    Ok(())
+}
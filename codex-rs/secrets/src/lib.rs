@@ -12,9 +12,13 @@ use serde::Serialize;
 use sha2::Digest;
 use sha2::Sha256;
 
+mod detector;
 mod local;
 mod sanitizer;
 
+pub use detector::SecretKind;
+pub use detector::SecretRedaction;
+pub use detector::redact_detected_secrets;
 pub use local::LocalSecretsBackend;
 pub use sanitizer::redact_secrets;
 
@@ -0,0 +1,20 @@
+CREATE VIRTUAL TABLE thread_search USING fts5(
+    thread_id UNINDEXED,
+    kind UNINDEXED,
+    text,
+    tokenize = 'unicode61'
+);
+
+CREATE TABLE thread_search_models (
+    thread_id TEXT NOT NULL,
+    model TEXT NOT NULL,
+    PRIMARY KEY(thread_id, model)
+);
+
+CREATE INDEX idx_thread_search_models_model ON thread_search_models(model);
+
+-- Re-run the rollout backfill so sessions recorded before the search index
+-- existed are indexed too.
+UPDATE backfill_state
+SET status = 'pending', last_watermark = NULL
+WHERE id = 1;
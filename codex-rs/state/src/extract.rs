@@ -1,4 +1,8 @@
 use crate::model::ThreadMetadata;
+use crate::model::ThreadSearchEntry;
+use crate::model::ThreadSearchEntryKind;
+use crate::model::ThreadSearchIndex;
+use codex_protocol::models::LocalShellAction;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::protocol::EventMsg;
 use codex_protocol::protocol::RolloutItem;
@@ -11,6 +15,25 @@ use serde_json::Value;
 
 const IMAGE_ONLY_USER_MESSAGE_PLACEHOLDER: &str = "[Image]";
 
+/// Function tools whose `command` or `cmd` argument is a shell command.
+const SHELL_TOOL_NAMES: &[&str] = &[
+    "shell",
+    "container.exec",
+    "local_shell",
+    "shell_command",
+    "exec_command",
+];
+
+const APPLY_PATCH_TOOL_NAME: &str = "apply_patch";
+
+/// Patch header prefixes followed by the path of a file the patch touches.
+const PATCH_PATH_PREFIXES: &[&str] = &[
+    "*** Add File: ",
+    "*** Update File: ",
+    "*** Delete File: ",
+    "*** Move to: ",
+];
+
 /// Apply a rollout item to the metadata structure.
 pub fn apply_rollout_item(
     metadata: &mut ThreadMetadata,
@@ -100,6 +123,105 @@ fn apply_response_item(_metadata: &mut ThreadMetadata, _item: &ResponseItem) {
     // Title and first_user_message are derived from EventMsg::UserMessage only.
 }
 
+/// Extract what the full-text search index records for `items`: user and
+/// agent messages, shell commands, the files patches touched, and the models
+/// turns ran with.
+pub fn thread_search_index(items: &[RolloutItem]) -> ThreadSearchIndex {
+    let mut index = ThreadSearchIndex::default();
+    for item in items {
+        match item {
+            RolloutItem::TurnContext(turn_ctx) => {
+                index.models.insert(turn_ctx.model.clone());
+            }
+            RolloutItem::EventMsg(EventMsg::UserMessage(user)) => {
+                push_search_entry(
+                    &mut index,
+                    ThreadSearchEntryKind::UserMessage,
+                    strip_user_message_prefix(user.message.as_str()),
+                );
+            }
+            RolloutItem::EventMsg(EventMsg::AgentMessage(agent)) => {
+                push_search_entry(
+                    &mut index,
+                    ThreadSearchEntryKind::AgentMessage,
+                    agent.message.as_str(),
+                );
+            }
+            RolloutItem::ResponseItem(ResponseItem::LocalShellCall {
+                action: LocalShellAction::Exec(exec),
+                ..
+            }) => {
+                push_search_entry(
+                    &mut index,
+                    ThreadSearchEntryKind::Command,
+                    exec.command.join(" ").as_str(),
+                );
+            }
+            RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+                name, arguments, ..
+            }) => {
+                let Ok(arguments) = serde_json::from_str::<Value>(arguments) else {
+                    continue;
+                };
+                if name == APPLY_PATCH_TOOL_NAME {
+                    if let Some(patch) = arguments.get("input").and_then(Value::as_str) {
+                        push_patch_paths(&mut index, patch);
+                    }
+                } else if SHELL_TOOL_NAMES.contains(&name.as_str())
+                    && let Some(command) = shell_command_text(&arguments)
+                {
+                    push_search_entry(&mut index, ThreadSearchEntryKind::Command, &command);
+                }
+            }
+            RolloutItem::ResponseItem(ResponseItem::CustomToolCall { name, input, .. })
+                if name == APPLY_PATCH_TOOL_NAME =>
+            {
+                push_patch_paths(&mut index, input);
+            }
+            RolloutItem::SessionMeta(_)
+            | RolloutItem::EventMsg(_)
+            | RolloutItem::ResponseItem(_)
+            | RolloutItem::Compacted(_) => {}
+        }
+    }
+    index
+}
+
+fn push_search_entry(index: &mut ThreadSearchIndex, kind: ThreadSearchEntryKind, text: &str) {
+    let text = text.trim();
+    if !text.is_empty() {
+        index.entries.push(ThreadSearchEntry {
+            kind,
+            text: text.to_string(),
+        });
+    }
+}
+
+fn push_patch_paths(index: &mut ThreadSearchIndex, patch: &str) {
+    for line in patch.lines() {
+        if let Some(path) = PATCH_PATH_PREFIXES
+            .iter()
+            .find_map(|prefix| line.strip_prefix(prefix))
+        {
+            push_search_entry(index, ThreadSearchEntryKind::FilePath, path);
+        }
+    }
+}
+
+fn shell_command_text(arguments: &Value) -> Option<String> {
+    match arguments.get("command").or_else(|| arguments.get("cmd"))? {
+        Value::String(command) => Some(command.clone()),
+        Value::Array(parts) => Some(
+            parts
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+        _ => None,
+    }
+}
+
 fn strip_user_message_prefix(text: &str) -> &str {
     match text.find(USER_MESSAGE_BEGIN) {
         Some(idx) => text[idx + USER_MESSAGE_BEGIN.len()..].trim(),
@@ -134,7 +256,10 @@ pub(crate) fn enum_to_string<T: Serialize>(value: &T) -> String {
 #[cfg(test)]
 mod tests {
     use super::apply_rollout_item;
+    use super::thread_search_index;
     use crate::model::ThreadMetadata;
+    use crate::model::ThreadSearchEntry;
+    use crate::model::ThreadSearchEntryKind;
     use chrono::DateTime;
     use chrono::Utc;
     use codex_protocol::ThreadId;
@@ -229,6 +354,59 @@ mod tests {
         assert_eq!(metadata.title, "");
     }
 
+    #[test]
+    fn thread_search_index_collects_messages_commands_and_patched_paths() {
+        let items = vec![
+            RolloutItem::EventMsg(EventMsg::UserMessage(UserMessageEvent {
+                message: format!("{USER_MESSAGE_BEGIN} fix the flaky auth test"),
+                images: None,
+                local_images: vec![],
+                text_elements: vec![],
+            })),
+            RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: r#"{"command":["cargo","test","-p","auth"]}"#.to_string(),
+                call_id: "call-1".to_string(),
+            }),
+            RolloutItem::ResponseItem(ResponseItem::CustomToolCall {
+                id: None,
+                status: None,
+                call_id: "call-2".to_string(),
+                name: "apply_patch".to_string(),
+                input: "*** Begin Patch\n*** Update File: src/auth.rs\n@@\n-a\n+b\n*** End Patch"
+                    .to_string(),
+            }),
+            RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+                id: None,
+                name: "read_file".to_string(),
+                arguments: r#"{"command":"not a shell command"}"#.to_string(),
+                call_id: "call-3".to_string(),
+            }),
+        ];
+
+        let index = thread_search_index(&items);
+
+        assert_eq!(
+            index.entries,
+            vec![
+                ThreadSearchEntry {
+                    kind: ThreadSearchEntryKind::UserMessage,
+                    text: "fix the flaky auth test".to_string(),
+                },
+                ThreadSearchEntry {
+                    kind: ThreadSearchEntryKind::Command,
+                    text: "cargo test -p auth".to_string(),
+                },
+                ThreadSearchEntry {
+                    kind: ThreadSearchEntryKind::FilePath,
+                    text: "src/auth.rs".to_string(),
+                },
+            ]
+        );
+        assert!(index.models.is_empty());
+    }
+
     #[test]
     fn turn_context_does_not_override_session_cwd() {
         let mut metadata = metadata_for_test();
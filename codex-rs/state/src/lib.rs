@@ -24,6 +24,7 @@ pub use runtime::StateRuntime;
 /// Most consumers should prefer [`StateRuntime`].
 pub use extract::apply_rollout_item;
 pub use extract::rollout_item_affects_thread_metadata;
+pub use extract::thread_search_index;
 pub use model::AgentJob;
 pub use model::AgentJobCreateParams;
 pub use model::AgentJobItem;
@@ -44,6 +45,11 @@ pub use model::Stage1OutputRef;
 pub use model::Stage1StartupClaimParams;
 pub use model::ThreadMetadata;
 pub use model::ThreadMetadataBuilder;
+pub use model::ThreadSearchEntry;
+pub use model::ThreadSearchEntryKind;
+pub use model::ThreadSearchHit;
+pub use model::ThreadSearchIndex;
+pub use model::ThreadSearchQuery;
 pub use model::ThreadsPage;
 pub use runtime::logs_db_filename;
 pub use runtime::logs_db_path;
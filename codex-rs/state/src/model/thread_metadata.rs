@@ -49,6 +49,8 @@ pub struct ExtractionOutcome {
     pub memory_mode: Option<String>,
     /// The number of rollout lines that failed to parse.
     pub parse_errors: usize,
+    /// What the full-text search index records for the rollout.
+    pub search_index: super::ThreadSearchIndex,
 }
 
 /// Canonical thread metadata derived from rollout files.
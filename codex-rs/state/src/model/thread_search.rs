@@ -0,0 +1,91 @@
+use anyhow::Result;
+use chrono::DateTime;
+use chrono::Utc;
+use std::collections::BTreeSet;
+
+use super::ThreadMetadata;
+
+/// What a piece of indexed thread text came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ThreadSearchEntryKind {
+    /// A message typed by the user.
+    UserMessage,
+    /// A message from the agent.
+    AgentMessage,
+    /// A shell command the agent ran.
+    Command,
+    /// A file the agent added, edited, moved or deleted with a patch.
+    FilePath,
+}
+
+impl ThreadSearchEntryKind {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            ThreadSearchEntryKind::UserMessage => "user_message",
+            ThreadSearchEntryKind::AgentMessage => "agent_message",
+            ThreadSearchEntryKind::Command => "command",
+            ThreadSearchEntryKind::FilePath => "file_path",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "user_message" => Ok(Self::UserMessage),
+            "agent_message" => Ok(Self::AgentMessage),
+            "command" => Ok(Self::Command),
+            "file_path" => Ok(Self::FilePath),
+            _ => Err(anyhow::anyhow!("invalid thread search entry kind: {value}")),
+        }
+    }
+}
+
+/// A piece of searchable text extracted from a rollout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadSearchEntry {
+    pub kind: ThreadSearchEntryKind,
+    pub text: String,
+}
+
+/// Everything the search index records about a batch of rollout items.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThreadSearchIndex {
+    /// Searchable text, in rollout order.
+    pub entries: Vec<ThreadSearchEntry>,
+    /// Models the thread's turns ran with.
+    pub models: BTreeSet<String>,
+}
+
+impl ThreadSearchIndex {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.models.is_empty()
+    }
+}
+
+/// A full-text query over indexed threads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadSearchQuery {
+    /// Words that must all appear in one indexed entry, matched
+    /// case-insensitively. Words may contain punctuation, e.g. `auth.rs`,
+    /// which then matches the same tokens in that order.
+    pub text: String,
+    /// Only threads updated at or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Only threads created at or before this time.
+    pub until: Option<DateTime<Utc>>,
+    /// Only threads whose working directory or git origin URL contains this.
+    pub repo: Option<String>,
+    /// Only threads with at least one turn run with this model.
+    pub model: Option<String>,
+    /// Maximum number of threads returned.
+    pub limit: usize,
+}
+
+/// A thread matching a [`ThreadSearchQuery`], with its best matching entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadSearchHit {
+    pub thread: ThreadMetadata,
+    pub kind: ThreadSearchEntryKind,
+    /// An excerpt of the matching entry with the matched words wrapped in
+    /// `[` and `]`.
+    pub snippet: String,
+}
@@ -53,6 +53,7 @@ mod agent_jobs;
 mod backfill;
 mod logs;
 mod memories;
+mod search;
 #[cfg(test)]
 mod test_support;
 mod threads;
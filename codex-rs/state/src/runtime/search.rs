@@ -0,0 +1,308 @@
+use super::*;
+use crate::ThreadSearchEntryKind;
+use crate::ThreadSearchHit;
+use crate::ThreadSearchIndex;
+use crate::ThreadSearchQuery;
+
+/// Tokens of context kept on each side of the matched words in a snippet.
+const SNIPPET_TOKENS: i64 = 16;
+
+impl StateRuntime {
+    /// Add rollout text to a thread's full-text search index.
+    pub async fn index_thread_search(
+        &self,
+        thread_id: ThreadId,
+        index: &ThreadSearchIndex,
+    ) -> anyhow::Result<()> {
+        if index.is_empty() {
+            return Ok(());
+        }
+        let mut tx = self.pool.begin().await?;
+        insert_thread_search(&mut tx, thread_id, index).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Replace a thread's search index, e.g. after its whole rollout was
+    /// re-read during backfill.
+    pub async fn replace_thread_search(
+        &self,
+        thread_id: ThreadId,
+        index: &ThreadSearchIndex,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+        delete_thread_search(&mut tx, thread_id).await?;
+        insert_thread_search(&mut tx, thread_id, index).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Find threads with an indexed entry containing every word of the query,
+    /// best match first. Each thread is returned once, with its best entry.
+    pub async fn search_threads(
+        &self,
+        query: &ThreadSearchQuery,
+    ) -> anyhow::Result<Vec<ThreadSearchHit>> {
+        let Some(match_expression) = fts_match_expression(query.text.as_str()) else {
+            return Ok(Vec::new());
+        };
+        let mut builder = QueryBuilder::<Sqlite>::new(
+            r#"
+WITH matches AS (
+    SELECT
+        thread_id,
+        kind,
+        snippet(thread_search, 2, '[', ']', '…', "#,
+        );
+        builder.push(SNIPPET_TOKENS);
+        builder.push(
+            r#") AS snippet,
+        bm25(thread_search) AS score
+    FROM thread_search
+    WHERE thread_search MATCH "#,
+        );
+        builder.push_bind(match_expression);
+        builder.push(
+            r#"
+),
+ranked AS (
+    SELECT
+        *,
+        ROW_NUMBER() OVER (PARTITION BY thread_id ORDER BY score) AS position
+    FROM matches
+)
+SELECT
+    id,
+    rollout_path,
+    created_at,
+    updated_at,
+    source,
+    agent_nickname,
+    agent_role,
+    model_provider,
+    cwd,
+    cli_version,
+    title,
+    sandbox_policy,
+    approval_mode,
+    tokens_used,
+    first_user_message,
+    archived_at,
+    git_sha,
+    git_branch,
+    git_origin_url,
+    ranked.kind AS kind,
+    ranked.snippet AS snippet
+FROM ranked
+JOIN threads ON threads.id = ranked.thread_id
+WHERE ranked.position = 1
+            "#,
+        );
+        if let Some(since) = query.since {
+            builder.push(" AND threads.updated_at >= ");
+            builder.push_bind(datetime_to_epoch_seconds(since));
+        }
+        if let Some(until) = query.until {
+            builder.push(" AND threads.created_at <= ");
+            builder.push_bind(datetime_to_epoch_seconds(until));
+        }
+        if let Some(repo) = query.repo.as_deref() {
+            builder.push(" AND (instr(threads.cwd, ");
+            builder.push_bind(repo);
+            builder.push(") > 0 OR instr(COALESCE(threads.git_origin_url, ''), ");
+            builder.push_bind(repo);
+            builder.push(") > 0)");
+        }
+        if let Some(model) = query.model.as_deref() {
+            builder.push(
+                " AND EXISTS (SELECT 1 FROM thread_search_models WHERE thread_search_models.thread_id = threads.id AND thread_search_models.model = ",
+            );
+            builder.push_bind(model);
+            builder.push(")");
+        }
+        builder.push(" ORDER BY ranked.score, threads.updated_at DESC LIMIT ");
+        builder.push_bind(i64::try_from(query.limit).unwrap_or(i64::MAX));
+
+        let rows = builder.build().fetch_all(self.pool.as_ref()).await?;
+        rows.into_iter()
+            .map(|row| {
+                let kind: String = row.try_get("kind")?;
+                Ok(ThreadSearchHit {
+                    thread: ThreadRow::try_from_row(&row).and_then(ThreadMetadata::try_from)?,
+                    kind: ThreadSearchEntryKind::parse(kind.as_str())?,
+                    snippet: row.try_get("snippet")?,
+                })
+            })
+            .collect()
+    }
+}
+
+pub(super) async fn delete_thread_search(
+    conn: &mut SqliteConnection,
+    thread_id: ThreadId,
+) -> anyhow::Result<()> {
+    let thread_id = thread_id.to_string();
+    sqlx::query("DELETE FROM thread_search WHERE thread_id = ?")
+        .bind(thread_id.as_str())
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("DELETE FROM thread_search_models WHERE thread_id = ?")
+        .bind(thread_id.as_str())
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+async fn insert_thread_search(
+    conn: &mut SqliteConnection,
+    thread_id: ThreadId,
+    index: &ThreadSearchIndex,
+) -> anyhow::Result<()> {
+    let thread_id = thread_id.to_string();
+    for entry in &index.entries {
+        sqlx::query("INSERT INTO thread_search (thread_id, kind, text) VALUES (?, ?, ?)")
+            .bind(thread_id.as_str())
+            .bind(entry.kind.as_str())
+            .bind(entry.text.as_str())
+            .execute(&mut *conn)
+            .await?;
+    }
+    for model in &index.models {
+        sqlx::query(
+            "INSERT INTO thread_search_models (thread_id, model) VALUES (?, ?) ON CONFLICT(thread_id, model) DO NOTHING",
+        )
+        .bind(thread_id.as_str())
+        .bind(model.as_str())
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Turn free text into an FTS5 query requiring every word. Each word is
+/// quoted so characters FTS5 treats as syntax, such as `-`, `:` or `*`, are
+/// searched for literally.
+fn fts_match_expression(text: &str) -> Option<String> {
+    let words = text
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>();
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fts_match_expression;
+    use crate::StateRuntime;
+    use crate::ThreadSearchEntry;
+    use crate::ThreadSearchEntryKind;
+    use crate::ThreadSearchIndex;
+    use crate::ThreadSearchQuery;
+    use crate::runtime::test_support::test_thread_metadata;
+    use crate::runtime::test_support::unique_temp_dir;
+    use codex_protocol::ThreadId;
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeSet;
+
+    fn query(text: &str) -> ThreadSearchQuery {
+        ThreadSearchQuery {
+            text: text.to_string(),
+            since: None,
+            until: None,
+            repo: None,
+            model: None,
+            limit: 10,
+        }
+    }
+
+    #[test]
+    fn match_expression_quotes_each_word() {
+        assert_eq!(
+            fts_match_expression(" auth.rs  say \"hi\" "),
+            Some(r#""auth.rs" "say" """hi""""#.to_string())
+        );
+        assert_eq!(fts_match_expression("   "), None);
+    }
+
+    #[tokio::test]
+    async fn search_threads_returns_best_entry_once_per_thread_and_filters_by_model() {
+        let codex_home = unique_temp_dir();
+        let runtime = StateRuntime::init(codex_home.clone(), "test-provider".to_string())
+            .await
+            .expect("state db should initialize");
+        let first =
+            ThreadId::from_string("00000000-0000-0000-0000-000000000101").expect("valid thread id");
+        let second =
+            ThreadId::from_string("00000000-0000-0000-0000-000000000102").expect("valid thread id");
+        for (thread_id, model, texts) in [
+            (
+                first,
+                "gpt-5",
+                vec![
+                    "please fix the flaky auth test",
+                    "the auth test is flaky because of a race",
+                ],
+            ),
+            (second, "gpt-5-mini", vec!["the auth test passes now"]),
+        ] {
+            runtime
+                .upsert_thread(&test_thread_metadata(
+                    codex_home.as_path(),
+                    thread_id,
+                    codex_home.clone(),
+                ))
+                .await
+                .expect("upsert thread");
+            runtime
+                .index_thread_search(
+                    thread_id,
+                    &ThreadSearchIndex {
+                        entries: texts
+                            .into_iter()
+                            .map(|text| ThreadSearchEntry {
+                                kind: ThreadSearchEntryKind::UserMessage,
+                                text: text.to_string(),
+                            })
+                            .collect(),
+                        models: BTreeSet::from([model.to_string()]),
+                    },
+                )
+                .await
+                .expect("index thread");
+        }
+
+        let hits = runtime
+            .search_threads(&query("flaky auth"))
+            .await
+            .expect("search");
+        assert_eq!(
+            hits.iter().map(|hit| hit.thread.id).collect::<Vec<_>>(),
+            vec![first]
+        );
+        assert!(hits[0].snippet.contains("[flaky]"));
+
+        let hits = runtime
+            .search_threads(&ThreadSearchQuery {
+                model: Some("gpt-5-mini".to_string()),
+                ..query("auth test")
+            })
+            .await
+            .expect("search");
+        assert_eq!(
+            hits.iter().map(|hit| hit.thread.id).collect::<Vec<_>>(),
+            vec![second]
+        );
+
+        runtime
+            .replace_thread_search(second, &ThreadSearchIndex::default())
+            .await
+            .expect("replace index");
+        let hits = runtime
+            .search_threads(&query("passes"))
+            .await
+            .expect("search");
+        assert_eq!(hits, Vec::new());
+
+        let _ = tokio::fs::remove_dir_all(codex_home).await;
+    }
+}
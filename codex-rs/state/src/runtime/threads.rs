@@ -545,12 +545,15 @@ ON CONFLICT(thread_id, position) DO NOTHING
         self.upsert_thread(&metadata).await
     }
 
-    /// Delete a thread metadata row by id.
+    /// Delete a thread metadata row, and its search index, by id.
     pub async fn delete_thread(&self, thread_id: ThreadId) -> anyhow::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        super::search::delete_thread_search(&mut tx, thread_id).await?;
         let result = sqlx::query("DELETE FROM threads WHERE id = ?")
             .bind(thread_id.to_string())
-            .execute(self.pool.as_ref())
+            .execute(&mut *tx)
             .await?;
+        tx.commit().await?;
         Ok(result.rows_affected())
     }
 }
@@ -1027,6 +1030,7 @@ mod tests {
                     model_context_window: None,
                 }),
                 rate_limits: None,
+                prompt_cache_hit_rate: None,
             },
         ))];
         let override_updated_at =
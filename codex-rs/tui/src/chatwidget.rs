@@ -117,6 +117,8 @@ use codex_protocol::protocol::ImageGenerationEndEvent;
 use codex_protocol::protocol::ListCustomPromptsResponseEvent;
 use codex_protocol::protocol::ListSkillsResponseEvent;
 use codex_protocol::protocol::McpListToolsResponseEvent;
+use codex_protocol::protocol::McpServerHealth;
+use codex_protocol::protocol::McpServerStatusEvent;
 use codex_protocol::protocol::McpStartupCompleteEvent;
 use codex_protocol::protocol::McpStartupStatus;
 use codex_protocol::protocol::McpStartupUpdateEvent;
@@ -124,6 +126,8 @@ use codex_protocol::protocol::McpToolCallBeginEvent;
 use codex_protocol::protocol::McpToolCallEndEvent;
 use codex_protocol::protocol::Op;
 use codex_protocol::protocol::PatchApplyBeginEvent;
+use codex_protocol::protocol::ProviderFellBackEvent;
+use codex_protocol::protocol::ProviderUnhealthyEvent;
 use codex_protocol::protocol::RateLimitSnapshot;
 use codex_protocol::protocol::ReviewRequest;
 use codex_protocol::protocol::ReviewTarget;
@@ -1969,6 +1973,33 @@ impl ChatWidget {
         self.request_redraw();
     }
 
+    fn on_mcp_server_status(&mut self, ev: McpServerStatusEvent) {
+        match ev.status {
+            McpServerHealth::Degraded { error } => self.on_warning(format!(
+                "MCP server `{}` stopped responding ({error}); reconnecting.",
+                ev.server
+            )),
+            McpServerHealth::Healthy => {
+                self.add_info_message(format!("MCP server `{}` reconnected.", ev.server), None);
+            }
+            McpServerHealth::Reconnecting { .. } => {}
+        }
+    }
+
+    fn on_provider_fell_back(&mut self, ev: ProviderFellBackEvent) {
+        self.on_warning(format!(
+            "Model provider `{}` is unavailable ({}); continuing with `{}` using {}.",
+            ev.from_provider, ev.reason, ev.to_provider, ev.model
+        ));
+    }
+
+    fn on_provider_unhealthy(&mut self, ev: ProviderUnhealthyEvent) {
+        self.on_warning(format!(
+            "Model provider `{}` failed {} requests in a row ({}); it will not be tried again for {}s.",
+            ev.provider, ev.consecutive_failures, ev.reason, ev.retry_after_seconds
+        ));
+    }
+
     /// Handle a turn aborted due to user interrupt (Esc).
     /// When there are queued user messages, restore them into the composer
     /// separated by newlines rather than auto‑submitting the next one.
@@ -4793,6 +4824,13 @@ impl ChatWidget {
             }
             EventMsg::McpStartupUpdate(ev) => self.on_mcp_startup_update(ev),
             EventMsg::McpStartupComplete(ev) => self.on_mcp_startup_complete(ev),
+            EventMsg::McpServerStatus(ev) => self.on_mcp_server_status(ev),
+            EventMsg::ProviderFellBack(ev) => self.on_provider_fell_back(ev),
+            EventMsg::ProviderUnhealthy(ev) => self.on_provider_unhealthy(ev),
+            EventMsg::TurnTimedOut(ev) => self.on_warning(format!(
+                "Turn exceeded its {}s time limit; running tools were cancelled and the model is wrapping up.",
+                ev.timeout_seconds
+            )),
             EventMsg::TurnAborted(ev) => match ev.reason {
                 TurnAbortReason::Interrupted => {
                     self.on_interrupted_turn(ev.reason);
@@ -4845,7 +4883,9 @@ impl ChatWidget {
                 });
             }
             EventMsg::ShutdownComplete => self.on_shutdown_complete(),
-            EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => self.on_turn_diff(unified_diff),
+            EventMsg::TurnDiff(TurnDiffEvent { unified_diff, .. }) => {
+                self.on_turn_diff(unified_diff)
+            }
             EventMsg::DeprecationNotice(ev) => self.on_deprecation_notice(ev),
             EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
                 self.on_background_event(message)
@@ -4901,8 +4941,28 @@ impl ChatWidget {
             | EventMsg::AgentMessageContentDelta(_)
             | EventMsg::ReasoningContentDelta(_)
             | EventMsg::ReasoningRawContentDelta(_)
+            | EventMsg::PatchReviewRequest(_)
+            | EventMsg::CostUpdate(_)
             | EventMsg::DynamicToolCallRequest(_)
-            | EventMsg::DynamicToolCallResponse(_) => {}
+            | EventMsg::DynamicToolCallResponse(_)
+            | EventMsg::ToolCallStart(_)
+            | EventMsg::ToolCallQueued(_)
+            | EventMsg::TurnCancelled(_)
+            | EventMsg::TurnCheckpoint(_)
+            | EventMsg::ToolCacheHit(_)
+            | EventMsg::ChaosFaultInjected(_)
+            | EventMsg::McpToolCallOutputDelta(_)
+            | EventMsg::ToolOutputRedacted(_)
+            | EventMsg::SecretsRedacted(_)
+            | EventMsg::SessionSettingsUpdated(_)
+            | EventMsg::FeaturesResolved(_)
+            | EventMsg::SessionShareStarted(_)
+            | EventMsg::TurnDiffUpdated(_)
+            | EventMsg::CompactionStarted(_)
+            | EventMsg::CompactionFinished(_)
+            | EventMsg::ContextWindowStatus(_)
+            | EventMsg::CheckpointCreated(_)
+            | EventMsg::BranchedFromCheckpoint(_) => {}
             EventMsg::RealtimeConversationStarted(ev) => {
                 if !from_replay {
                     self.on_realtime_conversation_started(ev);
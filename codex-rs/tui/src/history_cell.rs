@@ -2773,6 +2773,8 @@ mod tests {
             disabled_tools: None,
             scopes: None,
             oauth_resource: None,
+            max_concurrent_calls: None,
+            max_calls_per_minute: None,
         };
         let mut servers = config.mcp_servers.get().clone();
         servers.insert("docs".to_string(), stdio_config);
@@ -2797,6 +2799,8 @@ mod tests {
             disabled_tools: None,
             scopes: None,
             oauth_resource: None,
+            max_concurrent_calls: None,
+            max_calls_per_minute: None,
         };
         servers.insert("http".to_string(), http_config);
         config
@@ -0,0 +1,118 @@
+use std::ffi::OsString;
+use std::path::Path;
+
+use crate::GitToolingError;
+use crate::operations::ensure_git_repository;
+use crate::operations::run_git_for_status;
+use crate::operations::run_git_for_stdout_all;
+
+/// Adds a linked worktree at `worktree_path` with a detached `HEAD` at
+/// `commit`, mirroring `git worktree add --detach <path> <commit>`.
+pub fn add_detached_worktree(
+    repo_path: &Path,
+    worktree_path: &Path,
+    commit: &str,
+) -> Result<(), GitToolingError> {
+    ensure_git_repository(repo_path)?;
+    run_git_for_status(
+        repo_path,
+        vec![
+            OsString::from("worktree"),
+            OsString::from("add"),
+            OsString::from("--detach"),
+            worktree_path.as_os_str().to_os_string(),
+            OsString::from(commit),
+        ],
+        None,
+    )
+}
+
+/// Removes a linked worktree, discarding any changes left in it.
+pub fn remove_worktree(repo_path: &Path, worktree_path: &Path) -> Result<(), GitToolingError> {
+    run_git_for_status(
+        repo_path,
+        vec![
+            OsString::from("worktree"),
+            OsString::from("remove"),
+            OsString::from("--force"),
+            worktree_path.as_os_str().to_os_string(),
+        ],
+        None,
+    )
+}
+
+/// Returns the binary diff between two commits. The diff is empty when both
+/// commits have the same tree.
+pub fn diff_commits(repo_path: &Path, from: &str, to: &str) -> Result<String, GitToolingError> {
+    run_git_for_stdout_all(
+        repo_path,
+        vec![
+            OsString::from("diff"),
+            OsString::from("--binary"),
+            OsString::from(from),
+            OsString::from(to),
+        ],
+        None,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CreateGhostCommitOptions;
+    use crate::create_ghost_commit;
+    use pretty_assertions::assert_eq;
+    use std::process::Command;
+    use tempfile::tempdir;
+
+    fn run_git_in(repo_path: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(repo_path)
+            .args(args)
+            .status()
+            .expect("git command");
+        assert!(status.success(), "git command failed: {args:?}");
+    }
+
+    #[test]
+    fn worktree_changes_diff_against_the_commit_it_started_from() -> Result<(), GitToolingError> {
+        let temp = tempdir()?;
+        let repo = temp.path().join("repo");
+        let worktree = temp.path().join("worktree");
+        std::fs::create_dir(&repo)?;
+        run_git_in(&repo, &["init", "--initial-branch=main"]);
+        std::fs::write(repo.join("tracked.txt"), "before\n")?;
+        run_git_in(&repo, &["add", "tracked.txt"]);
+        run_git_in(
+            &repo,
+            &[
+                "-c",
+                "user.name=Tester",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-m",
+                "init",
+            ],
+        );
+        let base = create_ghost_commit(&CreateGhostCommitOptions::new(&repo))?;
+
+        add_detached_worktree(&repo, &worktree, base.id())?;
+        std::fs::write(worktree.join("tracked.txt"), "after\n")?;
+        std::fs::write(worktree.join("new.txt"), "new\n")?;
+        let changed = create_ghost_commit(&CreateGhostCommitOptions::new(&worktree))?;
+
+        let diff = diff_commits(&repo, base.id(), changed.id())?;
+        assert!(diff.contains("-before\n+after\n"), "{diff}");
+        assert!(diff.contains("+++ b/new.txt"), "{diff}");
+        assert_eq!(
+            std::fs::read_to_string(repo.join("tracked.txt"))?,
+            "before\n"
+        );
+
+        remove_worktree(&repo, &worktree)?;
+        assert!(!worktree.exists());
+
+        Ok(())
+    }
+}
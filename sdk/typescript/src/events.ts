@@ -7,6 +7,8 @@ export type ThreadStartedEvent = {
   type: "thread.started";
   /** The identifier of the new thread. Can be used to resume the thread later. */
   thread_id: string;
+  /** Version of the event schema. Bumped only for breaking changes to events. */
+  schema_version: number;
 };
 
 /**
@@ -31,6 +33,8 @@ export type Usage = {
 export type TurnCompletedEvent = {
   type: "turn.completed";
   usage: Usage;
+  /** The final response parsed as JSON, when the turn ran with an `outputSchema` and the response is valid JSON. */
+  output?: unknown;
 };
 
 /** Indicates that a turn failed with an error. */
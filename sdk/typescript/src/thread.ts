@@ -10,6 +10,8 @@ import { createOutputSchemaFile } from "./outputSchemaFile";
 export type Turn = {
   items: ThreadItem[];
   finalResponse: string;
+  /** The final response parsed as JSON, when the turn ran with an `outputSchema`. */
+  output?: unknown;
   usage: Usage | null;
 };
 
@@ -117,6 +119,7 @@ export class Thread {
     const items: ThreadItem[] = [];
     let finalResponse: string = "";
     let usage: Usage | null = null;
+    let output: unknown;
     let turnFailure: ThreadError | null = null;
     for await (const event of generator) {
       if (event.type === "item.completed") {
@@ -126,6 +129,7 @@ export class Thread {
         items.push(event.item);
       } else if (event.type === "turn.completed") {
         usage = event.usage;
+        output = event.output;
       } else if (event.type === "turn.failed") {
         turnFailure = event.error;
         break;
@@ -134,7 +138,7 @@ export class Thread {
     if (turnFailure) {
       throw new Error(turnFailure.message);
     }
-    return { items, finalResponse, usage };
+    return { items, finalResponse, output, usage };
   }
 }
 
@@ -37,6 +37,7 @@ describe("Codex", () => {
         {
           type: "thread.started",
           thread_id: expect.any(String),
+          schema_version: 1,
         },
         {
           type: "turn.started",